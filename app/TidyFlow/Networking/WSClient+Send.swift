@@ -7,6 +7,8 @@ import TidyFlowShared
     private var protocolExactRules: [(domain: String, action: String)] {
         [
         ("system", "ping"),
+        ("system", "host_power_event"),
+        ("system", "cancel"),
         ("terminal", "spawn_terminal"),
         ("terminal", "kill_terminal"),
         ("terminal", "input"),
@@ -18,6 +20,13 @@ import TidyFlowShared
         ("project", "export_template"),
         ("project", "import_template"),
         ("project", "templates"),
+        ("project", "rename_workspace"),
+        ("project", "apply_workspace_manifest"),
+        ("project", "repair_workspace"),
+        ("project", "set_project_default_branch"),
+        ("project", "link_workspace_issue"),
+        ("health", "restore_state_backup"),
+        ("health", "get_audit_log"),
         ("node", "node_refresh_network"),
         ]
     }
@@ -39,6 +48,7 @@ import TidyFlowShared
         ("project", "run_project_command"),
         ("project", "cancel_project_command"),
         ("project", "template_"),
+        ("project", "proc_"),
         ("node", "node_"),
         ("ai", "ai_"),
         ("evolution", "evo_"),
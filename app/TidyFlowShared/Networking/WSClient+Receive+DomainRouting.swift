@@ -7,6 +7,8 @@ extension WSClient {
     private var receiveProtocolExactRules: [(domain: String, action: String)] {
         [
         ("system", "ping"),
+        ("system", "host_power_event"),
+        ("system", "cancel"),
         ("terminal", "spawn_terminal"),
         ("terminal", "kill_terminal"),
         ("terminal", "input"),
@@ -18,6 +20,13 @@ extension WSClient {
         ("project", "export_template"),
         ("project", "import_template"),
         ("project", "templates"),
+        ("project", "rename_workspace"),
+        ("project", "apply_workspace_manifest"),
+        ("project", "repair_workspace"),
+        ("project", "set_project_default_branch"),
+        ("project", "link_workspace_issue"),
+        ("health", "restore_state_backup"),
+        ("health", "get_audit_log"),
         ("node", "node_refresh_network"),
         ]
     }
@@ -39,6 +48,7 @@ extension WSClient {
         ("project", "run_project_command"),
         ("project", "cancel_project_command"),
         ("project", "template_"),
+        ("project", "proc_"),
         ("node", "node_"),
         ("ai", "ai_"),
         ("evolution", "evo_"),
@@ -70,6 +80,8 @@ extension WSClient {
         "node:node_peer_status",
         "health:health_snapshot",
         "health:health_repair_result",
+        "health:state_backup_restored",
+        "health:audit_log_result",
         // v1.46: Coordinator 域（工作区级 AI 聚合状态增量快照）
         "coordinator:coordinator_snapshot",
     ]
@@ -1,15 +1,26 @@
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, LazyLock, Mutex};
 use std::time::Instant;
 use tracing::debug;
 
+use crate::server::definition;
+use crate::server::editorconfig;
 use crate::server::file_api::{self, FileApiError};
 use crate::server::file_index;
+use crate::server::fuzzy;
+use crate::server::git;
+use crate::server::highlight;
+use crate::server::line_edit;
+use crate::server::line_endings;
 use crate::server::perf as perf_counters;
-use crate::server::protocol::file::FileWorkspacePhase;
+use crate::server::protocol::file::{FileLineEdit, FileQueryMatch, FileWorkspacePhase};
 use crate::server::protocol::{FileEntryInfo, ServerMessage};
+use crate::server::replace;
+use crate::server::text_encoding;
 use crate::workspace::cache_metrics;
+use crate::workspace::config::ProjectConfig;
 
 // ── 文件工作区相位追踪器 ──
 
@@ -244,6 +255,13 @@ impl FileWorkspacePhaseTracker {
 
 const FILE_INDEX_CACHE_TTL_SECS: u64 = 15;
 
+/// 索引快照版本号分配器：全量重建与增量合并都会分配新的版本号（全局单调递增）。
+static FILE_INDEX_VERSION: AtomicU64 = AtomicU64::new(0);
+
+fn next_file_index_version() -> u64 {
+    FILE_INDEX_VERSION.fetch_add(1, Ordering::Relaxed) + 1
+}
+
 /// 文件索引不可变快照——缓存命中只需 Arc 克隆，不再复制整份列表。
 ///
 /// `items` 与 `search_keys` 一一对应，均按 lowercase key 排序。
@@ -254,6 +272,7 @@ struct FileIndexSnapshot {
     search_keys: Vec<String>,
     truncated: bool,
     created_at: Instant,
+    version: u64,
 }
 
 /// 内部缓存条目：持有共享快照，便于读写路径共享同一份数据
@@ -264,123 +283,257 @@ struct FileIndexCacheEntry {
 static FILE_INDEX_CACHE: LazyLock<Mutex<HashMap<String, FileIndexCacheEntry>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
 
+/// 常驻索引根目录（值为 watcher 订阅引用计数）。
+///
+/// 被 watcher 订阅的工作区由 watcher 事件增量维护索引，不受 TTL 约束；
+/// 最后一个订阅退出后恢复 TTL 语义。多个连接可能同时订阅同一工作区，因此按引用计数管理。
+static FILE_INDEX_LIVE_ROOTS: LazyLock<Mutex<HashMap<String, usize>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
 fn file_index_cache_key(root: &Path) -> String {
     root.to_string_lossy().to_string()
 }
 
-pub fn invalidate_file_index_cache(root: &Path) {
+fn is_live_file_index(key: &str) -> bool {
+    FILE_INDEX_LIVE_ROOTS
+        .lock()
+        .map(|roots| roots.contains_key(key))
+        .unwrap_or(false)
+}
+
+/// watcher 订阅工作区时调用：该工作区的索引转为常驻，由增量事件保持新鲜。
+pub fn retain_live_file_index(root: &Path) {
+    if let Ok(mut roots) = FILE_INDEX_LIVE_ROOTS.lock() {
+        *roots.entry(file_index_cache_key(root)).or_insert(0) += 1;
+    }
+}
+
+/// watcher 退订工作区时调用：引用计数归零后恢复 TTL 过期语义。
+pub fn release_live_file_index(root: &Path) {
     let key = file_index_cache_key(root);
-    if let Ok(mut cache) = FILE_INDEX_CACHE.lock() {
-        if cache.remove(&key).is_some() {
-            cache_metrics::record_file_cache_eviction(&key, "invalidated");
+    if let Ok(mut roots) = FILE_INDEX_LIVE_ROOTS.lock() {
+        if let Some(count) = roots.get_mut(&key) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                roots.remove(&key);
+            }
         }
     }
 }
 
-/// 增量更新文件索引缓存：按事件类型精确增删缓存条目，避免全量重扫。
+/// 失效指定工作区的索引缓存，返回被移除快照的版本号（缓存不存在时为 `None`）。
+pub fn invalidate_file_index_cache(root: &Path) -> Option<u64> {
+    let key = file_index_cache_key(root);
+    let mut cache = FILE_INDEX_CACHE.lock().ok()?;
+    let removed = cache.remove(&key)?;
+    cache_metrics::record_file_cache_eviction(&key, "invalidated");
+    Some(removed.snapshot.version)
+}
+
+/// 本地文件变更（写入、重命名、删除等）后的索引维护入口。
+///
+/// 常驻索引交由 watcher 事件增量合并并推送 `file_index_delta`，这里不做处理，
+/// 避免每次保存都触发全量重扫；非常驻索引直接失效，下次请求时全量重建。
+fn on_local_file_mutation(root: &Path) {
+    if !is_live_file_index(&file_index_cache_key(root)) {
+        invalidate_file_index_cache(root);
+    }
+}
+
+/// 一次增量合并的结果，用于构造 `FileIndexDelta` 推送。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileIndexDeltaResult {
+    pub base_version: u64,
+    pub version: u64,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub truncated: bool,
+}
+
+/// 将 watcher 上报的路径规整为相对 root 的路径（兼容绝对路径与相对路径两种输入）。
+fn relative_index_path(root: &Path, raw: &str) -> Option<String> {
+    let path = Path::new(raw);
+    let rel = if path.is_absolute() {
+        path.strip_prefix(root).ok()?.to_string_lossy().to_string()
+    } else {
+        raw.to_string()
+    };
+    let rel = rel.trim_matches('/').to_string();
+    if rel.is_empty() {
+        None
+    } else {
+        Some(rel)
+    }
+}
+
+/// 增量更新文件索引缓存：按文件系统现状对账变更路径，避免全量重扫。
 ///
-/// - `kind="removed"` 或 `kind="deleted"`：从缓存中删除指定路径。
-/// - `kind="created"` 或 `kind="renamed"`：检查文件是否存在，若存在则插入缓存。
-/// - `kind="modified"` 或其他类型：文件名未变，缓存条目无需调整。
+/// - `kind="removed"` 或 `kind="deleted"`：直接移除路径及其子路径（目录被删除）。
+/// - 其他类型：逐条检查文件系统现状——文件存在且未收录则插入；新目录则遍历子树插入；
+///   路径已不存在则移除。watcher 不区分事件类型（统一为 `modified`），因此以对账为准。
 ///
 /// 仅在缓存命中时生效；缓存未命中时退化为下次请求时的全量重建（正常兜底路径）。
-/// TTL 不重置，保持原有 15s 过期逻辑。
-/// 更新时从现有快照克隆数据到可变 Vec，修改后创建新的 Arc 快照写回缓存。
-pub fn update_file_index_incrementally(root: &Path, abs_paths: &[String], kind: &str) {
+/// TTL 不重置；新增后超过 `MAX_FILE_COUNT` 的部分不再收录并标记 `truncated`。
+/// 返回实际生效的增删集合，无变化时返回 `None`。
+pub fn update_file_index_incrementally(
+    root: &Path,
+    paths: &[String],
+    kind: &str,
+) -> Option<FileIndexDeltaResult> {
     let key = file_index_cache_key(root);
     let Ok(mut cache) = FILE_INDEX_CACHE.lock() else {
-        return;
-    };
-    let Some(entry) = cache.get_mut(&key) else {
-        // 缓存不存在，下次请求时全量重建，无需操作
-        return;
+        return None;
     };
+    // 缓存不存在，下次请求时全量重建，无需操作
+    let entry = cache.get_mut(&key)?;
 
-    // 将绝对路径转为相对路径（相对于 root）
-    let rel_paths: Vec<String> = abs_paths
+    let all_rel_paths: Vec<String> = paths
         .iter()
-        .filter_map(|p| {
-            let abs = Path::new(p);
-            abs.strip_prefix(root)
-                .ok()
-                .map(|rel| rel.to_string_lossy().to_string())
-        })
-        .filter(|p| !p.is_empty())
+        .filter_map(|p| relative_index_path(root, p))
         .collect();
+    // 忽略规则变化会影响任意路径，改为按全量遍历结果对账
+    let ignore_rules_changed = all_rel_paths
+        .iter()
+        .any(|p| crate::server::ignore_rules::is_ignore_file(p));
+    let rel_paths: Vec<String> = if ignore_rules_changed {
+        Vec::new()
+    } else {
+        all_rel_paths
+            .into_iter()
+            .filter(|p| file_index::is_indexable_relative(root, p))
+            .collect()
+    };
+    if rel_paths.is_empty() && !ignore_rules_changed {
+        return None;
+    }
 
-    if rel_paths.is_empty() {
-        return;
+    let removal_only = matches!(kind, "removed" | "deleted");
+    let mut to_remove: std::collections::HashSet<String> = std::collections::HashSet::new();
+    let mut removed_dir_prefixes: Vec<String> = Vec::new();
+    let mut candidates: Vec<String> = Vec::new();
+
+    if ignore_rules_changed {
+        let full = file_index::index_files(root).ok()?;
+        let current: std::collections::HashSet<&str> =
+            full.items.iter().map(String::as_str).collect();
+        to_remove.extend(
+            entry
+                .snapshot
+                .items
+                .iter()
+                .filter(|item| !current.contains(item.as_str()))
+                .cloned(),
+        );
+        candidates = full.items;
     }
 
-    // 从现有快照克隆可变工作 Vec（增量更新不频繁，此处 O(n) 拷贝可接受）
-    let snap = &entry.snapshot;
-    let mut items = snap.items.clone();
-    let mut search_keys = snap.search_keys.clone();
-
-    match kind {
-        "removed" | "deleted" => {
-            // 批量删除：构建待删除集合后过滤，O(n) 而非 O(n*m)
-            let to_remove: std::collections::HashSet<&str> =
-                rel_paths.iter().map(|p| p.as_str()).collect();
-            let mut new_items = Vec::with_capacity(items.len());
-            let mut new_keys = Vec::with_capacity(items.len());
-            for (item, key_str) in items.iter().zip(search_keys.iter()) {
-                if !to_remove.contains(item.as_str()) {
-                    new_items.push(item.clone());
-                    new_keys.push(key_str.clone());
+    for rel in &rel_paths {
+        if removal_only {
+            to_remove.insert(rel.clone());
+            removed_dir_prefixes.push(format!("{}/", rel));
+            continue;
+        }
+        match std::fs::metadata(root.join(rel)) {
+            Ok(meta) if meta.is_file() => candidates.push(rel.clone()),
+            Ok(meta) if meta.is_dir() => {
+                // 新目录（如 mv 进来的子树）：复用全量遍历规则收录其下文件
+                if let Ok(sub) = file_index::index_directory(root, rel) {
+                    candidates.extend(sub.items);
                 }
             }
-            items = new_items;
-            search_keys = new_keys;
+            Ok(_) => {}
+            Err(_) => {
+                to_remove.insert(rel.clone());
+                removed_dir_prefixes.push(format!("{}/", rel));
+            }
         }
-        "created" | "renamed" => {
-            // 新增文件：先收集不重复的待插入项，再批量插入
-            let to_insert: Vec<String> = {
-                let existing: std::collections::HashSet<&str> =
-                    items.iter().map(|s| s.as_str()).collect();
-                rel_paths
-                    .iter()
-                    .filter(|rel| {
-                        !existing.contains(rel.as_str()) && root.join(rel.as_str()).is_file()
-                    })
-                    .cloned()
-                    .collect()
-            };
-            for rel in to_insert {
-                let lower = rel.to_lowercase();
-                let pos = search_keys
-                    .binary_search_by(|k| k.as_str().cmp(lower.as_str()))
-                    .unwrap_or_else(|i| i);
-                items.insert(pos, rel);
-                search_keys.insert(pos, lower);
+    }
+
+    let snap = &entry.snapshot;
+    let mut removed = Vec::new();
+    let mut items = Vec::with_capacity(snap.items.len());
+    let mut search_keys = Vec::with_capacity(snap.items.len());
+    for (item, key_str) in snap.items.iter().zip(snap.search_keys.iter()) {
+        let gone = to_remove.contains(item.as_str())
+            || removed_dir_prefixes
+                .iter()
+                .any(|p| item.starts_with(p.as_str()));
+        if gone {
+            removed.push(item.clone());
+        } else {
+            items.push(item.clone());
+            search_keys.push(key_str.clone());
+        }
+    }
+
+    let mut truncated = snap.truncated;
+    let mut added = Vec::new();
+    {
+        let mut existing: std::collections::HashSet<String> = items.iter().cloned().collect();
+        for rel in candidates {
+            if existing.contains(&rel) {
+                continue;
             }
+            if items.len() >= file_index::MAX_FILE_COUNT {
+                truncated = true;
+                break;
+            }
+            let lower = rel.to_lowercase();
+            let pos = search_keys
+                .binary_search_by(|k| k.as_str().cmp(lower.as_str()))
+                .unwrap_or_else(|i| i);
+            items.insert(pos, rel.clone());
+            search_keys.insert(pos, lower);
+            existing.insert(rel.clone());
+            added.push(rel);
         }
-        // "modified" 及其他类型：路径未变，无需调整索引
-        _ => return,
     }
 
+    if added.is_empty() && removed.is_empty() && truncated == snap.truncated {
+        return None;
+    }
+
+    let base_version = snap.version;
+    let version = next_file_index_version();
     let new_count = items.len();
     // 创建新快照，保持原有 created_at（TTL 不重置）
     entry.snapshot = Arc::new(FileIndexSnapshot {
         items,
         search_keys,
-        truncated: snap.truncated,
+        truncated,
         created_at: snap.created_at,
+        version,
     });
 
     debug!(
-        "Incremental file index update: root={:?}, kind={}, paths={:?}, cached_count={}",
-        root, kind, rel_paths, new_count
+        "Incremental file index update: root={:?}, kind={}, added={}, removed={}, cached_count={}, version={}",
+        root,
+        kind,
+        added.len(),
+        removed.len(),
+        new_count,
+        version
     );
-    cache_metrics::record_file_cache_incremental_update(&file_index_cache_key(root), new_count);
+    cache_metrics::record_file_cache_incremental_update(&key, new_count);
+
+    Some(FileIndexDeltaResult {
+        base_version,
+        version,
+        added,
+        removed,
+        truncated,
+    })
 }
 
 /// 读取文件索引缓存。缓存命中时返回 Arc 指针克隆（O(1)，不复制数据）。
+///
+/// 常驻索引（watcher 订阅中）不受 TTL 约束。
 fn read_file_index_cache(root: &Path) -> Option<Arc<FileIndexSnapshot>> {
     let key = file_index_cache_key(root);
+    let live = is_live_file_index(&key);
     let mut cache = FILE_INDEX_CACHE.lock().ok()?;
     let entry = cache.get(&key)?;
-    if entry.snapshot.created_at.elapsed().as_secs() >= FILE_INDEX_CACHE_TTL_SECS {
+    if !live && entry.snapshot.created_at.elapsed().as_secs() >= FILE_INDEX_CACHE_TTL_SECS {
         cache.remove(&key);
         cache_metrics::record_file_cache_eviction(&key, "ttl_expired");
         return None;
@@ -389,19 +542,22 @@ fn read_file_index_cache(root: &Path) -> Option<Arc<FileIndexSnapshot>> {
     Some(Arc::clone(&entry.snapshot))
 }
 
-fn write_file_index_cache(root: &Path, items: &[String], truncated: bool) {
+fn write_file_index_cache(root: &Path, items: &[String], truncated: bool) -> u64 {
     let key = file_index_cache_key(root);
     let item_count = items.len();
+    let version = next_file_index_version();
     let snapshot = Arc::new(FileIndexSnapshot {
         items: items.to_vec(),
         search_keys: items.iter().map(|item| item.to_lowercase()).collect(),
         truncated,
         created_at: Instant::now(),
+        version,
     });
     if let Ok(mut cache) = FILE_INDEX_CACHE.lock() {
         cache.insert(key.clone(), FileIndexCacheEntry { snapshot });
     }
     cache_metrics::record_file_cache_rebuild(&key, item_count);
+    version
 }
 
 /// 将 FileApiError 映射为协议错误码与消息。
@@ -417,6 +573,10 @@ pub fn file_error_to_response(e: &FileApiError) -> (String, String) {
         FileApiError::InvalidName(_) => ("invalid_name".to_string(), e.to_string()),
         FileApiError::TrashError(_) => ("trash_error".to_string(), e.to_string()),
         FileApiError::MoveIntoSelf => ("move_into_self".to_string(), e.to_string()),
+        FileApiError::ProtectedPath(_) => ("protected_path".to_string(), e.to_string()),
+        FileApiError::RangeNotSatisfiable(_) => {
+            ("range_not_satisfiable".to_string(), e.to_string())
+        }
     }
 }
 
@@ -429,6 +589,7 @@ fn file_error_message(e: &FileApiError) -> ServerMessage {
         workspace: None,
         session_id: None,
         cycle_id: None,
+        trace_id: None,
     }
 }
 
@@ -439,7 +600,13 @@ pub fn file_list_message(root: &Path, project: &str, workspace: &str, path: &str
         path.to_string()
     };
 
-    match file_api::list_files(root, &path_str) {
+    let listed = if file_api::provider::is_remote_root(root) {
+        file_api::provider::provider_for_root(root)
+            .and_then(|provider| provider.list_files(&path_str))
+    } else {
+        file_api::list_files(root, &path_str)
+    };
+    match listed {
         Ok(entries) => {
             let items: Vec<FileEntryInfo> = entries
                 .into_iter()
@@ -463,61 +630,743 @@ pub fn file_list_message(root: &Path, project: &str, workspace: &str, path: &str
     }
 }
 
-pub fn file_read_message(root: &Path, project: &str, workspace: &str, path: &str) -> ServerMessage {
-    match file_api::read_file(root, path) {
-        Ok((content, size)) => ServerMessage::FileReadResult {
-            project: project.to_string(),
-            workspace: workspace.to_string(),
-            path: path.to_string(),
-            content: content.into_bytes(),
+/// 文件读写编码：`utf8`（默认）、`binary`（原样传输字节）或磁盘上的字符集
+///
+/// `Charset` 表示磁盘内容为该字符集：读取时转码为 UTF-8 返回，写入时把 UTF-8 转回该字符集。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileContentEncoding {
+    Utf8,
+    Binary,
+    Charset(&'static encoding_rs::Encoding),
+}
+
+impl FileContentEncoding {
+    /// 解析请求中的编码参数，非法取值返回错误描述
+    pub fn parse(raw: Option<&str>) -> Result<Self, String> {
+        match raw.map(str::trim).filter(|s| !s.is_empty()) {
+            None => Ok(Self::Utf8),
+            Some(v) if v.eq_ignore_ascii_case("utf8") || v.eq_ignore_ascii_case("utf-8") => {
+                Ok(Self::Utf8)
+            }
+            Some(v) if v.eq_ignore_ascii_case("binary") => Ok(Self::Binary),
+            Some(v) => match text_encoding::encoding_for_label(v) {
+                Some(encoding) if encoding == encoding_rs::UTF_8 => Ok(Self::Utf8),
+                Some(encoding) => Ok(Self::Charset(encoding)),
+                None => Err(format!(
+                    "Unsupported encoding: {} (expected utf8, binary or a charset such as gbk)",
+                    v
+                )),
+            },
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Utf8 => "utf8",
+            Self::Binary => "binary",
+            Self::Charset(encoding) => encoding.name(),
+        }
+    }
+}
+
+fn invalid_encoding_message(message: String) -> ServerMessage {
+    ServerMessage::Error {
+        code: "invalid_encoding".to_string(),
+        message,
+        project: None,
+        workspace: None,
+        session_id: None,
+        cycle_id: None,
+        trace_id: None,
+    }
+}
+
+/// 项目的文本文件大小上限：`[editor] max_file_size`，未配置时为 `MAX_FILE_SIZE`
+fn max_text_file_size(root: &Path) -> u64 {
+    ProjectConfig::load(root)
+        .ok()
+        .and_then(|config| config.editor.max_file_size)
+        .map(|size| size.clamp(1, file_api::MAX_CONFIGURABLE_FILE_SIZE))
+        .unwrap_or(file_api::MAX_FILE_SIZE)
+}
+
+/// 读取结果中随内容返回的元数据
+struct FileContentMeta {
+    encoding: String,
+    mime_type: String,
+    line_ending: Option<String>,
+    charset: Option<String>,
+}
+
+fn file_content_meta(path: &str, content: &[u8], encoding: FileContentEncoding) -> FileContentMeta {
+    let mime_type = file_api::detect_mime_type(path, content);
+    let line_ending = match encoding {
+        FileContentEncoding::Binary => None,
+        _ => line_endings::detect_line_ending(content).map(str::to_string),
+    };
+    // 转码后的内容已是 UTF-8，原始字符集单独报告
+    let (encoding, charset) = match encoding {
+        FileContentEncoding::Charset(charset) => {
+            (FileContentEncoding::Utf8, Some(charset.name().to_string()))
+        }
+        other => (other, None),
+    };
+    FileContentMeta {
+        encoding: encoding.as_str().to_string(),
+        mime_type,
+        line_ending,
+        charset,
+    }
+}
+
+fn file_read_result(
+    project: &str,
+    workspace: &str,
+    path: &str,
+    content: Vec<u8>,
+    size: u64,
+    encoding: FileContentEncoding,
+    truncated: bool,
+) -> ServerMessage {
+    let meta = file_content_meta(path, &content, encoding);
+    ServerMessage::FileReadResult {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        path: path.to_string(),
+        content,
+        size,
+        encoding: meta.encoding,
+        mime_type: Some(meta.mime_type),
+        line_ending: meta.line_ending,
+        charset: meta.charset,
+        truncated,
+        mtime: None,
+        hash: None,
+    }
+}
+
+/// 以指定字符集读取并转码为 UTF-8（大小上限同 UTF-8 文本）；内容不符合该字符集时返回 None
+fn read_file_as_charset(
+    root: &Path,
+    path: &str,
+    charset: &'static encoding_rs::Encoding,
+    max_size: u64,
+) -> Result<Option<(String, u64)>, FileApiError> {
+    let (bytes, size) = file_api::read_file_binary(root, path)?;
+    if size > max_size {
+        return Err(FileApiError::FileTooLarge);
+    }
+    Ok(text_encoding::decode_with(&bytes, charset).map(|text| (text, size)))
+}
+
+/// 读取文件内容
+///
+/// `preview = true` 时，超过项目大小上限的 UTF-8 文本不再返回 `file_too_large`，
+/// 而是返回上限内的前若干行并标记 `truncated`（只读预览）；二进制文件仍返回 `file_too_large`。
+/// 结果附带文件版本（`mtime` / `hash`），供写回时做冲突校验。
+pub fn file_read_message(
+    root: &Path,
+    project: &str,
+    workspace: &str,
+    path: &str,
+    encoding: Option<&str>,
+    preview: bool,
+) -> ServerMessage {
+    let mut message = file_read_content_message(root, project, workspace, path, encoding, preview);
+    if let ServerMessage::FileReadResult {
+        content,
+        charset,
+        truncated,
+        mtime,
+        hash,
+        ..
+    } = &mut message
+    {
+        let remote = file_api::provider::is_remote_root(root);
+        if !remote {
+            *mtime = file_api::file_mtime(root, path);
+        }
+        // 转码后的内容与磁盘字节不同，需重新读取原始字节计算哈希
+        *hash = match (*truncated, charset.is_some()) {
+            (true, _) => None,
+            (false, false) => Some(file_api::content_hash(content)),
+            (false, true) => read_raw_bytes(root, path)
+                .ok()
+                .map(|bytes| file_api::content_hash(&bytes)),
+        };
+    }
+    message
+}
+
+/// 读取磁盘上的原始字节（本地或远程）
+fn read_raw_bytes(root: &Path, path: &str) -> Result<Vec<u8>, FileApiError> {
+    if file_api::provider::is_remote_root(root) {
+        file_api::provider::provider_for_root(root)
+            .and_then(|provider| provider.read_bytes(path, file_api::MAX_BINARY_FILE_SIZE))
+    } else {
+        file_api::read_file_binary(root, path).map(|(bytes, _)| bytes)
+    }
+}
+
+fn file_read_content_message(
+    root: &Path,
+    project: &str,
+    workspace: &str,
+    path: &str,
+    encoding: Option<&str>,
+    preview: bool,
+) -> ServerMessage {
+    let encoding = match FileContentEncoding::parse(encoding) {
+        Ok(encoding) => encoding,
+        Err(message) => return invalid_encoding_message(message),
+    };
+    let max_size = max_text_file_size(root);
+    if file_api::provider::is_remote_root(root) {
+        return remote_file_read_message(root, project, workspace, path, encoding, max_size);
+    }
+    match encoding {
+        FileContentEncoding::Binary => {
+            return match file_api::read_file_binary(root, path) {
+                Ok((content, size)) => file_read_result(
+                    project,
+                    workspace,
+                    path,
+                    content,
+                    size,
+                    FileContentEncoding::Binary,
+                    false,
+                ),
+                Err(e) => file_error_message(&e),
+            };
+        }
+        FileContentEncoding::Charset(charset) => {
+            return match read_file_as_charset(root, path, charset, max_size) {
+                Ok(Some((text, size))) => file_read_result(
+                    project,
+                    workspace,
+                    path,
+                    text.into_bytes(),
+                    size,
+                    encoding,
+                    false,
+                ),
+                Ok(None) => {
+                    invalid_encoding_message(format!("Content is not valid {}", charset.name()))
+                }
+                Err(e) => file_error_message(&e),
+            };
+        }
+        FileContentEncoding::Utf8 => {}
+    }
+    match file_api::read_file_with_limit(root, path, max_size) {
+        Ok((content, size)) => file_read_result(
+            project,
+            workspace,
+            path,
+            content.into_bytes(),
             size,
-        },
+            FileContentEncoding::Utf8,
+            false,
+        ),
+        Err(FileApiError::FileTooLarge) if preview => {
+            match file_api::read_text_preview(root, path, max_size) {
+                Ok((content, size)) => file_read_result(
+                    project,
+                    workspace,
+                    path,
+                    content.into_bytes(),
+                    size,
+                    FileContentEncoding::Utf8,
+                    true,
+                ),
+                Err(e) => file_error_message(&e),
+            }
+        }
         Err(FileApiError::InvalidUtf8) => {
-            // 非 UTF-8 文件回退为二进制读取。
-            match file_api::read_file_binary(root, path) {
-                Ok((content, size)) => ServerMessage::FileReadResult {
-                    project: project.to_string(),
-                    workspace: workspace.to_string(),
-                    path: path.to_string(),
+            // 非 UTF-8 文件先探测字符集并转码；无法识别为文本时回退为二进制读取，响应中标注实际编码。
+            let (content, size) = match file_api::read_file_binary(root, path) {
+                Ok(read) => read,
+                Err(e) => return file_error_message(&e),
+            };
+            let decoded = (size <= max_size && !file_api::is_binary_content(&content))
+                .then(|| text_encoding::decode_text(&content))
+                .flatten();
+            match decoded {
+                Some((text, charset)) => file_read_result(
+                    project,
+                    workspace,
+                    path,
+                    text.into_bytes(),
+                    size,
+                    FileContentEncoding::Charset(charset),
+                    false,
+                ),
+                None => file_read_result(
+                    project,
+                    workspace,
+                    path,
                     content,
                     size,
-                },
-                Err(e) => file_error_message(&e),
+                    FileContentEncoding::Binary,
+                    false,
+                ),
             }
         }
         Err(e) => file_error_message(&e),
     }
 }
 
+/// 远程项目读取：一次取回原始字节，编码判断规则同本地读取（不支持超限预览）
+fn remote_file_read_message(
+    root: &Path,
+    project: &str,
+    workspace: &str,
+    path: &str,
+    encoding: FileContentEncoding,
+    max_size: u64,
+) -> ServerMessage {
+    let read_limit = match encoding {
+        FileContentEncoding::Binary => file_api::MAX_BINARY_FILE_SIZE,
+        FileContentEncoding::Charset(_) => max_size,
+        FileContentEncoding::Utf8 => max_size.max(file_api::MAX_BINARY_FILE_SIZE),
+    };
+    let content = match file_api::provider::provider_for_root(root)
+        .and_then(|provider| provider.read_bytes(path, read_limit))
+    {
+        Ok(content) => content,
+        Err(e) => return file_error_message(&e),
+    };
+    let size = content.len() as u64;
+    let result = |content: Vec<u8>, encoding: FileContentEncoding| {
+        file_read_result(project, workspace, path, content, size, encoding, false)
+    };
+    match encoding {
+        FileContentEncoding::Binary => result(content, FileContentEncoding::Binary),
+        FileContentEncoding::Charset(charset) => {
+            match text_encoding::decode_with(&content, charset) {
+                Some(text) => result(text.into_bytes(), encoding),
+                None => {
+                    invalid_encoding_message(format!("Content is not valid {}", charset.name()))
+                }
+            }
+        }
+        FileContentEncoding::Utf8 => {
+            if std::str::from_utf8(&content).is_ok() {
+                if size > max_size {
+                    return file_error_message(&FileApiError::FileTooLarge);
+                }
+                return result(content, FileContentEncoding::Utf8);
+            }
+            let decoded = (size <= max_size && !file_api::is_binary_content(&content))
+                .then(|| text_encoding::decode_text(&content))
+                .flatten();
+            match decoded {
+                Some((text, charset)) => {
+                    result(text.into_bytes(), FileContentEncoding::Charset(charset))
+                }
+                None if size > file_api::MAX_BINARY_FILE_SIZE => {
+                    file_error_message(&FileApiError::FileTooLarge)
+                }
+                None => result(content, FileContentEncoding::Binary),
+            }
+        }
+    }
+}
+
+/// 读取文件在指定修订版本中的内容（diff 评审时与工作区版本并排展示）
+///
+/// 编码处理同 `file_read_message` 的默认模式：UTF-8 原样返回，其他可识别字符集转码为 UTF-8，
+/// 二进制内容按 `binary` 返回；文本大小上限同工作区读取。
+pub fn file_read_at_revision_message(
+    root: &Path,
+    project: &str,
+    workspace: &str,
+    path: &str,
+    rev: &str,
+) -> ServerMessage {
+    let max_size = max_text_file_size(root);
+    let read_limit = max_size.max(file_api::MAX_BINARY_FILE_SIZE);
+    let file = match git::git_file_at_revision(root, rev, path, read_limit) {
+        Ok(Some(file)) => file,
+        Ok(None) => {
+            return ServerMessage::Error {
+                code: "file_not_found".to_string(),
+                message: format!("File not found at revision {}: {}", rev, path),
+                project: None,
+                workspace: None,
+                session_id: None,
+                cycle_id: None,
+                trace_id: None,
+            }
+        }
+        Err(git::GitError::PathEscape) => return file_error_message(&FileApiError::PathEscape),
+        Err(e) => {
+            return ServerMessage::Error {
+                code: "git_error".to_string(),
+                message: e.to_string(),
+                project: None,
+                workspace: None,
+                session_id: None,
+                cycle_id: None,
+                trace_id: None,
+            }
+        }
+    };
+    let Some(content) = file.content else {
+        return file_error_message(&FileApiError::FileTooLarge);
+    };
+
+    let (content, encoding) = if file_api::is_binary_content(&content) {
+        (content, FileContentEncoding::Binary)
+    } else if file.size > max_size {
+        return file_error_message(&FileApiError::FileTooLarge);
+    } else if std::str::from_utf8(&content).is_ok() {
+        (content, FileContentEncoding::Utf8)
+    } else {
+        match text_encoding::decode_text(&content) {
+            Some((text, charset)) => (text.into_bytes(), FileContentEncoding::Charset(charset)),
+            None => (content, FileContentEncoding::Binary),
+        }
+    };
+    let meta = file_content_meta(path, &content, encoding);
+    ServerMessage::FileReadAtRevisionResult {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        path: path.to_string(),
+        rev: rev.to_string(),
+        sha: file.sha,
+        content,
+        size: file.size,
+        encoding: meta.encoding,
+        mime_type: Some(meta.mime_type),
+        line_ending: meta.line_ending,
+        charset: meta.charset,
+    }
+}
+
+/// 经 HTTP `/asset` 直出的预览资源
+#[derive(Debug)]
+pub struct FileAsset {
+    pub content: Vec<u8>,
+    /// 文件总大小
+    pub size: u64,
+    /// 实际返回的字节区间（含两端）；整文件响应时为 None
+    pub range: Option<(u64, u64)>,
+    pub mime_type: String,
+}
+
+/// 读取预览资源（图片、字体等），按扩展名与文件头判断 Content-Type
+///
+/// 远程项目一次取回整个文件后再按区间截取。
+pub fn file_asset(
+    root: &Path,
+    path: &str,
+    range: Option<file_api::ByteRange>,
+) -> Result<FileAsset, FileApiError> {
+    let (content, size, range, head) = if file_api::provider::is_remote_root(root) {
+        let bytes = file_api::provider::provider_for_root(root)
+            .and_then(|provider| provider.read_bytes(path, file_api::MAX_ASSET_SIZE))?;
+        let size = bytes.len() as u64;
+        let range = match range {
+            Some(range) => Some(
+                range
+                    .resolve(size)
+                    .ok_or(FileApiError::RangeNotSatisfiable(size))?,
+            ),
+            None => None,
+        };
+        let content = match range {
+            Some((start, end)) => bytes[start as usize..=end as usize].to_vec(),
+            None => bytes.clone(),
+        };
+        (content, size, range, bytes)
+    } else {
+        let (content, size, range) = file_api::read_asset(root, path, range)?;
+        // 区间不从文件头开始时单独读取文件头用于类型嗅探
+        let head = match range {
+            Some((start, _)) if start > 0 => file_api::read_asset(
+                root,
+                path,
+                Some(file_api::ByteRange::From(0, Some(ASSET_SNIFF_LEN - 1))),
+            )
+            .map(|(head, _, _)| head)
+            .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        (content, size, range, head)
+    };
+    let sniff = if head.is_empty() { &content } else { &head };
+    let sniff = &sniff[..sniff.len().min(ASSET_SNIFF_LEN as usize)];
+    Ok(FileAsset {
+        mime_type: file_api::detect_mime_type(path, sniff),
+        content,
+        size,
+        range,
+    })
+}
+
+/// 预览资源类型嗅探读取的头部字节数
+const ASSET_SNIFF_LEN: u64 = 512;
+
+/// 读取 `[offset, offset + length)` 区间并构造 `FileChunk`
+///
+/// `end` 为本次请求的结束偏移（`None` 表示读到文件末尾）；
+/// 达到 `end` 或文件末尾时 `last = true`，客户端据此结束渐进加载。
+pub fn file_read_chunk_message(
+    root: &Path,
+    project: &str,
+    workspace: &str,
+    path: &str,
+    offset: u64,
+    length: u64,
+    end: Option<u64>,
+) -> ServerMessage {
+    match file_api::read_file_range(root, path, offset, length) {
+        Ok((content, total_size)) => {
+            let next = offset + content.len() as u64;
+            let eof = next >= total_size;
+            let last = eof || content.is_empty() || end.is_some_and(|end| next >= end);
+            ServerMessage::FileChunk {
+                project: project.to_string(),
+                workspace: workspace.to_string(),
+                path: path.to_string(),
+                offset,
+                content,
+                total_size,
+                eof,
+                last,
+            }
+        }
+        Err(e) => file_error_message(&e),
+    }
+}
+
+/// 规范化待写入的 UTF-8 文本，并按目标字符集编码为落盘字节
+///
+/// 失败时返回 (错误码, 描述)。
+fn encode_text_for_write(
+    root: &Path,
+    path: &str,
+    content: &[u8],
+    encoding: FileContentEncoding,
+) -> Result<Vec<u8>, (&'static str, String)> {
+    let content_str = std::str::from_utf8(content)
+        .map_err(|_| ("invalid_utf8", "Content is not valid UTF-8".to_string()))?;
+    // 先按换行符策略对齐已有文件，再应用 EditorConfig（显式开启时以其为准）
+    let content_str = line_endings::normalize_for_write(root, path, content_str);
+    let content_str = editorconfig::normalize_for_write(root, path, &content_str);
+    match encoding {
+        FileContentEncoding::Charset(charset) => text_encoding::encode_text(&content_str, charset)
+            .map_err(|message| ("unencodable_content", message)),
+        _ => Ok(content_str.into_owned().into_bytes()),
+    }
+}
+
+/// 写入文件内容
+///
+/// 提供 `expected_hash` / `expected_mtime` 时先做版本校验（见 [`check_write_version`]），
+/// 文件在客户端读取后已被修改则不写入并返回 `file_conflict`。
+#[allow(clippy::too_many_arguments)]
 pub fn file_write_message(
     root: &Path,
     project: &str,
     workspace: &str,
     path: &str,
     content: &[u8],
+    encoding: Option<&str>,
+    expected_mtime: Option<u64>,
+    expected_hash: Option<&str>,
 ) -> ServerMessage {
-    match String::from_utf8(content.to_vec()) {
-        Ok(content_str) => match file_api::write_file(root, path, &content_str) {
-            Ok(size) => {
-                invalidate_file_index_cache(root);
-                ServerMessage::FileWriteResult {
-                    project: project.to_string(),
-                    workspace: workspace.to_string(),
-                    path: path.to_string(),
-                    success: true,
-                    size,
+    let encoding = match FileContentEncoding::parse(encoding) {
+        Ok(encoding) => encoding,
+        Err(message) => return invalid_encoding_message(message),
+    };
+    if let Some(conflict) = check_write_version(
+        root,
+        project,
+        workspace,
+        path,
+        expected_mtime,
+        expected_hash,
+    ) {
+        return conflict;
+    }
+    let remote = file_api::provider::is_remote_root(root);
+    let write_remote = |bytes: &[u8], max_size: u64| {
+        file_api::provider::provider_for_root(root)
+            .and_then(|provider| provider.write_bytes(path, bytes, max_size))
+    };
+    let bytes = match encoding {
+        FileContentEncoding::Binary => std::borrow::Cow::Borrowed(content),
+        encoding => match encode_text_for_write(root, path, content, encoding) {
+            Ok(bytes) => std::borrow::Cow::Owned(bytes),
+            Err((code, message)) => {
+                return ServerMessage::Error {
+                    code: code.to_string(),
+                    message,
+                    project: None,
+                    workspace: None,
+                    session_id: None,
+                    cycle_id: None,
+                    trace_id: None,
                 }
             }
-            Err(e) => file_error_message(&e),
         },
-        Err(_) => ServerMessage::Error {
-            code: "invalid_utf8".to_string(),
-            message: "Content is not valid UTF-8".to_string(),
-            project: None,
-            workspace: None,
-            session_id: None,
-            cycle_id: None,
+    };
+    let write_result = match encoding {
+        FileContentEncoding::Binary if remote => {
+            write_remote(&bytes, file_api::MAX_BINARY_FILE_SIZE)
+        }
+        FileContentEncoding::Binary => file_api::write_file_binary(root, path, &bytes),
+        _ if remote => write_remote(&bytes, max_text_file_size(root)),
+        _ => file_api::write_text_bytes(root, path, &bytes, max_text_file_size(root)),
+    };
+    match write_result {
+        Ok(size) => {
+            on_local_file_mutation(root);
+            ServerMessage::FileWriteResult {
+                project: project.to_string(),
+                workspace: workspace.to_string(),
+                path: path.to_string(),
+                success: true,
+                size,
+                mtime: (!remote)
+                    .then(|| file_api::file_mtime(root, path))
+                    .flatten(),
+                hash: Some(file_api::content_hash(&bytes)),
+            }
+        }
+        Err(e) => file_error_message(&e),
+    }
+}
+
+/// 写入前的版本校验：提供 `expected_hash` 时按内容比较，否则按修改时间比较（远程项目不支持）；
+/// 均未提供时不校验。不一致（含文件已被删除）时返回应答给客户端的 `file_conflict`。
+///
+/// 校验与写入之间不加锁，只防止覆盖客户端读取之后发生的修改。
+fn check_write_version(
+    root: &Path,
+    project: &str,
+    workspace: &str,
+    path: &str,
+    expected_mtime: Option<u64>,
+    expected_hash: Option<&str>,
+) -> Option<ServerMessage> {
+    let remote = file_api::provider::is_remote_root(root);
+    let expected_mtime = expected_mtime.filter(|_| !remote);
+    if expected_hash.is_none() && expected_mtime.is_none() {
+        return None;
+    }
+    let current_hash = match read_raw_bytes(root, path) {
+        Ok(bytes) => Some(file_api::content_hash(&bytes)),
+        Err(FileApiError::FileNotFound) => None,
+        Err(e) => return Some(file_error_message(&e)),
+    };
+    let current_mtime = if remote {
+        None
+    } else {
+        file_api::file_mtime(root, path)
+    };
+    let unchanged = match expected_hash {
+        Some(expected) => current_hash
+            .as_deref()
+            .is_some_and(|hash| hash.eq_ignore_ascii_case(expected.trim())),
+        None => current_hash.is_some() && current_mtime == expected_mtime,
+    };
+    if unchanged {
+        return None;
+    }
+    Some(ServerMessage::FileConflict {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        path: path.to_string(),
+        current_hash,
+        current_mtime,
+    })
+}
+
+/// 按行范围增量编辑文件
+///
+/// 版本校验与 `file_write` 相同；编辑基于磁盘上的当前内容（仅支持 UTF-8 文本）一次性应用，
+/// 结果经与 `file_write` 相同的换行符 / EditorConfig 规范化后写入。
+pub fn file_apply_edit_message(
+    root: &Path,
+    project: &str,
+    workspace: &str,
+    path: &str,
+    edits: &[FileLineEdit],
+    expected_mtime: Option<u64>,
+    expected_hash: Option<&str>,
+) -> ServerMessage {
+    if let Some(conflict) = check_write_version(
+        root,
+        project,
+        workspace,
+        path,
+        expected_mtime,
+        expected_hash,
+    ) {
+        return conflict;
+    }
+    let edit_error = |code: &str, message: String| ServerMessage::Error {
+        code: code.to_string(),
+        message,
+        project: None,
+        workspace: None,
+        session_id: None,
+        cycle_id: None,
+        trace_id: None,
+    };
+    let bytes = match read_raw_bytes(root, path) {
+        Ok(bytes) => bytes,
+        Err(e) => return file_error_message(&e),
+    };
+    let Ok(original) = std::str::from_utf8(&bytes) else {
+        return edit_error(
+            "invalid_utf8",
+            "File is not valid UTF-8, use file_write instead".to_string(),
+        );
+    };
+    let edits: Vec<line_edit::LineEdit> = edits
+        .iter()
+        .map(|edit| line_edit::LineEdit {
+            start_line: edit.start_line,
+            end_line: edit.end_line,
+            new_text: edit.new_text.clone(),
+        })
+        .collect();
+    let updated = match line_edit::apply_line_edits(original, &edits) {
+        Ok(updated) => updated,
+        Err(message) => return edit_error("invalid_edit", message),
+    };
+    match file_write_message(
+        root,
+        project,
+        workspace,
+        path,
+        updated.as_bytes(),
+        None,
+        None,
+        None,
+    ) {
+        ServerMessage::FileWriteResult {
+            project,
+            workspace,
+            path,
+            success,
+            size,
+            mtime,
+            hash,
+        } => ServerMessage::FileApplyEditResult {
+            project,
+            workspace,
+            path,
+            success,
+            size,
+            mtime,
+            hash,
         },
+        other => other,
     }
 }
 
@@ -562,6 +1411,7 @@ pub async fn file_index_message(
             workspace: workspace.to_string(),
             items,
             truncated: snapshot.truncated,
+            version: snapshot.version,
         };
     }
 
@@ -573,7 +1423,7 @@ pub async fn file_index_message(
     cache_metrics::record_file_cache_miss(&root_key);
     let walk_started = Instant::now();
     let result =
-        tokio::task::spawn_blocking(move || file_index::index_files(&root_for_index)).await;
+        crate::util::trace::spawn_blocking(move || file_index::index_files(&root_for_index)).await;
 
     // 索引完成：通知相位追踪器
     FileWorkspacePhaseTracker::on_indexing_completed(project, workspace);
@@ -582,7 +1432,8 @@ pub async fn file_index_message(
         Ok(Ok(mut index_result)) => {
             let walk_ms = walk_started.elapsed().as_millis() as u64;
             perf_counters::record_workspace_file_index_refresh(walk_ms);
-            write_file_index_cache(root.as_path(), &index_result.items, index_result.truncated);
+            let version =
+                write_file_index_cache(root.as_path(), &index_result.items, index_result.truncated);
             let filter_started = Instant::now();
             if let Some(q) = normalized_query.as_ref() {
                 index_result
@@ -602,15 +1453,17 @@ pub async fn file_index_message(
                 workspace: workspace.to_string(),
                 items: index_result.items,
                 truncated: index_result.truncated,
+                version,
             }
         }
         Ok(Err(e)) => ServerMessage::Error {
-            code: "io_error".to_string(),
+            code: io_error_code(&e).to_string(),
             message: format!("Failed to index files: {}", e),
             project: None,
             workspace: None,
             session_id: None,
             cycle_id: None,
+            trace_id: None,
         },
         Err(e) => ServerMessage::Error {
             code: "internal_error".to_string(),
@@ -619,6 +1472,109 @@ pub async fn file_index_message(
             workspace: None,
             session_id: None,
             cycle_id: None,
+            trace_id: None,
+        },
+    }
+}
+
+/// 取消的请求返回 `request_cancelled`，其余 I/O 错误为 `io_error`
+fn io_error_code(err: &std::io::Error) -> &'static str {
+    if crate::util::cancel::is_cancelled_error(err) {
+        crate::util::cancel::CANCELLED_CODE
+    } else {
+        "io_error"
+    }
+}
+
+/// Quick Open：在缓存的文件索引上做模糊匹配，只返回得分最高的 `limit` 条
+pub async fn file_query_message(
+    root: &Path,
+    project: &str,
+    workspace: &str,
+    query: &str,
+    limit: Option<usize>,
+) -> ServerMessage {
+    let snapshot = match read_file_index_cache(root) {
+        Some(snapshot) => snapshot,
+        None => match file_index_message(root, project, workspace, None).await {
+            ServerMessage::FileIndexResult {
+                items,
+                truncated,
+                version,
+                ..
+            } => read_file_index_cache(root).unwrap_or_else(|| {
+                Arc::new(FileIndexSnapshot {
+                    search_keys: items.iter().map(|item| item.to_lowercase()).collect(),
+                    items,
+                    truncated,
+                    created_at: Instant::now(),
+                    version,
+                })
+            }),
+            other => return other,
+        },
+    };
+
+    let limit = limit
+        .unwrap_or(fuzzy::DEFAULT_FUZZY_LIMIT)
+        .min(fuzzy::MAX_FUZZY_LIMIT);
+    let query_owned = query.to_string();
+    let snapshot_for_match = Arc::clone(&snapshot);
+    let started = Instant::now();
+    let result = crate::util::trace::spawn_blocking(move || {
+        fuzzy::top_matches(
+            &snapshot_for_match.items,
+            &snapshot_for_match.search_keys,
+            &query_owned,
+            limit,
+        )
+    })
+    .await;
+    if crate::util::cancel::is_cancelled() {
+        return ServerMessage::Error {
+            code: crate::util::cancel::CANCELLED_CODE.to_string(),
+            message: "Query cancelled".to_string(),
+            project: Some(project.to_string()),
+            workspace: Some(workspace.to_string()),
+            session_id: None,
+            cycle_id: None,
+            trace_id: None,
+        };
+    }
+
+    match result {
+        Ok((matches, total)) => {
+            debug!(
+                "file_query items={} total={} match_ms={}",
+                matches.len(),
+                total,
+                started.elapsed().as_millis()
+            );
+            ServerMessage::FileQueryResult {
+                project: project.to_string(),
+                workspace: workspace.to_string(),
+                query: query.to_string(),
+                items: matches
+                    .into_iter()
+                    .map(|m| FileQueryMatch {
+                        path: snapshot.items[m.index].clone(),
+                        score: m.score,
+                        positions: m.positions,
+                    })
+                    .collect(),
+                total_matches: total as u32,
+                truncated: snapshot.truncated,
+                version: snapshot.version,
+            }
+        }
+        Err(e) => ServerMessage::Error {
+            code: "internal_error".to_string(),
+            message: format!("Query task failed: {}", e),
+            project: None,
+            workspace: None,
+            session_id: None,
+            cycle_id: None,
+            trace_id: None,
         },
     }
 }
@@ -633,7 +1589,7 @@ pub async fn file_content_search_message(
     let root = root.to_path_buf();
     let query_owned = query.to_string();
 
-    let result = tokio::task::spawn_blocking(move || {
+    let result = crate::util::trace::spawn_blocking(move || {
         file_index::search_file_contents(&root, &query_owned, case_sensitive)
     })
     .await;
@@ -676,22 +1632,225 @@ pub async fn file_content_search_message(
                 search_duration_ms: search_result.search_duration_ms,
             }
         }
-        Ok(Err(e)) => ServerMessage::Error {
-            code: "io_error".to_string(),
-            message: format!("文件内容搜索失败: {}", e),
-            project: None,
-            workspace: None,
-            session_id: None,
-            cycle_id: None,
-        },
-        Err(e) => ServerMessage::Error {
-            code: "internal_error".to_string(),
-            message: format!("搜索任务失败: {}", e),
-            project: None,
-            workspace: None,
-            session_id: None,
-            cycle_id: None,
-        },
+        Ok(Err(e)) => ServerMessage::Error {
+            code: io_error_code(&e).to_string(),
+            message: format!("文件内容搜索失败: {}", e),
+            project: None,
+            workspace: None,
+            session_id: None,
+            cycle_id: None,
+            trace_id: None,
+        },
+        Err(e) => ServerMessage::Error {
+            code: "internal_error".to_string(),
+            message: format!("搜索任务失败: {}", e),
+            project: None,
+            workspace: None,
+            session_id: None,
+            cycle_id: None,
+            trace_id: None,
+        },
+    }
+}
+
+/// 跳转定义启发式回退：复用文件索引作为候选文件集，按语言关键字匹配定义行
+pub async fn file_definition_guess_message(
+    root: &Path,
+    project: &str,
+    workspace: &str,
+    symbol: &str,
+    path: Option<&str>,
+) -> ServerMessage {
+    let items = match file_index_message(root, project, workspace, None).await {
+        ServerMessage::FileIndexResult { items, .. } => items,
+        other => return other,
+    };
+
+    let root = root.to_path_buf();
+    let symbol_owned = symbol.to_string();
+    let path_owned = path.map(str::to_string);
+    let started = Instant::now();
+    let result = crate::util::trace::spawn_blocking(move || {
+        definition::guess_definitions(&root, &items, &symbol_owned, path_owned.as_deref())
+    })
+    .await;
+    if crate::util::cancel::is_cancelled() {
+        return ServerMessage::Error {
+            code: crate::util::cancel::CANCELLED_CODE.to_string(),
+            message: "Definition guess cancelled".to_string(),
+            project: Some(project.to_string()),
+            workspace: Some(workspace.to_string()),
+            session_id: None,
+            cycle_id: None,
+            trace_id: None,
+        };
+    }
+
+    match result {
+        Ok(guess) => {
+            debug!(
+                "file_definition_guess symbol={:?} candidates={} truncated={} duration_ms={}",
+                symbol,
+                guess.candidates.len(),
+                guess.truncated,
+                started.elapsed().as_millis()
+            );
+            ServerMessage::FileDefinitionGuessResult {
+                project: project.to_string(),
+                workspace: workspace.to_string(),
+                symbol: symbol.to_string(),
+                path: path.map(str::to_string),
+                candidates: guess
+                    .candidates
+                    .into_iter()
+                    .map(|c| crate::server::protocol::file::FileDefinitionCandidate {
+                        path: c.path,
+                        line: c.line,
+                        column: c.column,
+                        kind: c.kind,
+                        preview: c.preview,
+                        score: c.score,
+                    })
+                    .collect(),
+                truncated: guess.truncated,
+            }
+        }
+        Err(e) => ServerMessage::Error {
+            code: "internal_error".to_string(),
+            message: format!("Definition guess task failed: {}", e),
+            project: None,
+            workspace: None,
+            session_id: None,
+            cycle_id: None,
+            trace_id: None,
+        },
+    }
+}
+
+/// 解析文件生效的 EditorConfig 设置，供客户端编辑器对齐仓库约定
+pub fn file_editorconfig_message(
+    root: &Path,
+    project: &str,
+    workspace: &str,
+    path: &str,
+) -> ServerMessage {
+    if let Err(e) = file_api::resolve_safe_path(root, path) {
+        return file_error_message(&e);
+    }
+    let resolved = editorconfig::resolve_editorconfig(root, path);
+    let settings = resolved.settings;
+    ServerMessage::FileEditorConfigResult {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        path: path.to_string(),
+        settings: crate::server::protocol::file::FileEditorConfigSettings {
+            indent_style: settings.indent_style,
+            indent_size: settings.indent_size,
+            tab_width: settings.tab_width,
+            end_of_line: settings.end_of_line,
+            charset: settings.charset,
+            trim_trailing_whitespace: settings.trim_trailing_whitespace,
+            insert_final_newline: settings.insert_final_newline,
+            max_line_length: settings.max_line_length,
+        },
+        sources: resolved.sources,
+        apply_on_write: editorconfig::apply_on_write_enabled(root),
+    }
+}
+
+/// 语法高亮：读取文件并返回样式区间（或 HTML），结果按内容哈希缓存
+pub async fn file_highlight_message(
+    root: &Path,
+    project: &str,
+    workspace: &str,
+    path: &str,
+    theme: Option<&str>,
+    format: Option<&str>,
+) -> ServerMessage {
+    let format = match highlight::HighlightFormat::parse(format) {
+        Ok(format) => format,
+        Err(message) => return highlight_error_message("invalid_format", message),
+    };
+    let bytes = match read_raw_bytes(root, path) {
+        Ok(bytes) => bytes,
+        Err(e) => return file_error_message(&e),
+    };
+    if bytes.len() as u64 > max_text_file_size(root) {
+        return file_error_message(&FileApiError::FileTooLarge);
+    }
+    let hash = file_api::content_hash(&bytes);
+    let text = match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => {
+            let bytes = e.into_bytes();
+            match (!file_api::is_binary_content(&bytes))
+                .then(|| text_encoding::decode_text(&bytes))
+                .flatten()
+            {
+                Some((text, _)) => text,
+                None => return file_error_message(&FileApiError::InvalidUtf8),
+            }
+        }
+    };
+
+    let path_owned = path.to_string();
+    let hash_owned = hash.clone();
+    let theme_owned = theme.map(str::to_string);
+    let started = Instant::now();
+    let result = crate::util::trace::spawn_blocking(move || {
+        highlight::highlight(
+            &path_owned,
+            &text,
+            &hash_owned,
+            theme_owned.as_deref(),
+            format,
+        )
+    })
+    .await;
+
+    match result {
+        Ok(Ok((output, cached))) => {
+            debug!(
+                "file_highlight path={} syntax={} cached={} highlight_ms={}",
+                path,
+                output.syntax,
+                cached,
+                started.elapsed().as_millis()
+            );
+            ServerMessage::FileHighlightResult {
+                project: project.to_string(),
+                workspace: workspace.to_string(),
+                path: path.to_string(),
+                hash,
+                syntax: output.syntax.clone(),
+                theme: output.theme.clone(),
+                background: output.background.clone(),
+                foreground: output.foreground.clone(),
+                styles: output.styles.clone(),
+                lines: output.lines.clone(),
+                html: output.html.clone(),
+                cached,
+            }
+        }
+        Ok(Err(e @ highlight::HighlightError::UnknownTheme(..))) => {
+            highlight_error_message("invalid_theme", e.to_string())
+        }
+        Ok(Err(e)) => highlight_error_message("highlight_failed", e.to_string()),
+        Err(e) => {
+            highlight_error_message("internal_error", format!("Highlight task failed: {}", e))
+        }
+    }
+}
+
+fn highlight_error_message(code: &str, message: String) -> ServerMessage {
+    ServerMessage::Error {
+        code: code.to_string(),
+        message,
+        project: None,
+        workspace: None,
+        session_id: None,
+        cycle_id: None,
+        trace_id: None,
     }
 }
 
@@ -704,7 +1863,7 @@ pub fn file_rename_message(
 ) -> ServerMessage {
     match file_api::rename_file(root, old_path, new_name) {
         Ok(new_path) => {
-            invalidate_file_index_cache(root);
+            on_local_file_mutation(root);
             ServerMessage::FileRenameResult {
                 project: project.to_string(),
                 workspace: workspace.to_string(),
@@ -736,7 +1895,7 @@ pub fn file_delete_message(
 ) -> ServerMessage {
     match file_api::delete_file(root, path) {
         Ok(()) => {
-            invalidate_file_index_cache(root);
+            on_local_file_mutation(root);
             ServerMessage::FileDeleteResult {
                 project: project.to_string(),
                 workspace: workspace.to_string(),
@@ -758,6 +1917,179 @@ pub fn file_delete_message(
     }
 }
 
+pub fn file_mkdir_message(
+    root: &Path,
+    project: &str,
+    workspace: &str,
+    path: &str,
+) -> ServerMessage {
+    match file_api::create_directory(root, path) {
+        Ok(created) => {
+            on_local_file_mutation(root);
+            ServerMessage::FileMkdirResult {
+                project: project.to_string(),
+                workspace: workspace.to_string(),
+                path: created,
+                success: true,
+                message: None,
+            }
+        }
+        Err(e) => {
+            let (_, message) = file_error_to_response(&e);
+            ServerMessage::FileMkdirResult {
+                project: project.to_string(),
+                workspace: workspace.to_string(),
+                path: path.to_string(),
+                success: false,
+                message: Some(message),
+            }
+        }
+    }
+}
+
+/// 工作区查找替换预览：复用文件索引作为候选文件集，结果暂存为变更集
+pub async fn file_prepare_replace_message(
+    root: &Path,
+    project: &str,
+    workspace: &str,
+    query: replace::ReplaceQuery,
+) -> ServerMessage {
+    let items = match file_index_message(root, project, workspace, None).await {
+        ServerMessage::FileIndexResult { items, .. } => items,
+        other => return other,
+    };
+
+    let root = root.to_path_buf();
+    let started = Instant::now();
+    let result =
+        crate::util::trace::spawn_blocking(move || replace::prepare_replace(&root, &items, &query))
+            .await;
+
+    match result {
+        Ok(Ok(preview)) => {
+            debug!(
+                "file_prepare_replace matches={} files={} truncated={} duration_ms={}",
+                preview.matches.len(),
+                preview.file_count,
+                preview.truncated,
+                started.elapsed().as_millis()
+            );
+            ServerMessage::FilePrepareReplaceResult {
+                project: project.to_string(),
+                workspace: workspace.to_string(),
+                change_set_id: preview.change_set_id,
+                matches: preview
+                    .matches
+                    .into_iter()
+                    .map(|m| crate::server::protocol::file::FileReplaceMatch {
+                        id: m.id,
+                        path: m.path,
+                        line: m.line,
+                        start: m.start,
+                        end: m.end,
+                        preview: m.preview,
+                        matched: m.matched,
+                        replacement: m.replacement,
+                    })
+                    .collect(),
+                file_count: preview.file_count as u32,
+                truncated: preview.truncated,
+            }
+        }
+        Ok(Err(e)) => ServerMessage::Error {
+            code: replace_error_code(&e),
+            message: e.to_string(),
+            project: Some(project.to_string()),
+            workspace: Some(workspace.to_string()),
+            session_id: None,
+            cycle_id: None,
+            trace_id: None,
+        },
+        Err(e) => ServerMessage::Error {
+            code: "internal_error".to_string(),
+            message: format!("Replace preview task failed: {}", e),
+            project: None,
+            workspace: None,
+            session_id: None,
+            cycle_id: None,
+            trace_id: None,
+        },
+    }
+}
+
+fn replace_error_code(e: &replace::ReplaceError) -> String {
+    match e {
+        replace::ReplaceError::File(_, file_err) => file_error_to_response(file_err).0,
+        other => other.code().to_string(),
+    }
+}
+
+/// 应用查找替换变更集（全部文件校验通过后才写入，失败时回滚）
+pub fn file_apply_replace_message(
+    root: &Path,
+    project: &str,
+    workspace: &str,
+    change_set_id: &str,
+    accepted_matches: Option<&[u32]>,
+) -> ServerMessage {
+    match replace::apply_replace(root, change_set_id, accepted_matches) {
+        Ok(result) => {
+            if !result.files_changed.is_empty() {
+                on_local_file_mutation(root);
+            }
+            ServerMessage::FileApplyReplaceResult {
+                project: project.to_string(),
+                workspace: workspace.to_string(),
+                change_set_id: change_set_id.to_string(),
+                success: true,
+                files_changed: result.files_changed,
+                replacements: result.replacements,
+                undo_id: result.undo_id,
+                message: None,
+            }
+        }
+        Err(e) => ServerMessage::FileApplyReplaceResult {
+            project: project.to_string(),
+            workspace: workspace.to_string(),
+            change_set_id: change_set_id.to_string(),
+            success: false,
+            files_changed: Vec::new(),
+            replacements: 0,
+            undo_id: None,
+            message: Some(e.to_string()),
+        },
+    }
+}
+
+pub fn file_undo_replace_message(
+    root: &Path,
+    project: &str,
+    workspace: &str,
+    undo_id: &str,
+) -> ServerMessage {
+    match replace::undo_replace(root, undo_id) {
+        Ok(files_restored) => {
+            on_local_file_mutation(root);
+            ServerMessage::FileUndoReplaceResult {
+                project: project.to_string(),
+                workspace: workspace.to_string(),
+                undo_id: undo_id.to_string(),
+                success: true,
+                files_restored,
+                message: None,
+            }
+        }
+        Err(e) => ServerMessage::FileUndoReplaceResult {
+            project: project.to_string(),
+            workspace: workspace.to_string(),
+            undo_id: undo_id.to_string(),
+            success: false,
+            files_restored: Vec::new(),
+            message: Some(e.to_string()),
+        },
+    }
+}
+
 pub fn file_copy_message(
     root: &Path,
     project: &str,
@@ -767,7 +2099,7 @@ pub fn file_copy_message(
 ) -> ServerMessage {
     match file_api::copy_file_from_absolute(root, source_absolute_path, dest_dir) {
         Ok(dest_path) => {
-            invalidate_file_index_cache(root);
+            on_local_file_mutation(root);
             ServerMessage::FileCopyResult {
                 project: project.to_string(),
                 workspace: workspace.to_string(),
@@ -800,7 +2132,7 @@ pub fn file_move_message(
 ) -> ServerMessage {
     match file_api::move_file(root, old_path, new_dir) {
         Ok(new_path) => {
-            invalidate_file_index_cache(root);
+            on_local_file_mutation(root);
             ServerMessage::FileMoveResult {
                 project: project.to_string(),
                 workspace: workspace.to_string(),
@@ -830,16 +2162,477 @@ mod tests {
     use crate::server::protocol::file::{FileChangeKind, FileWorkspacePhase};
     use tempfile::TempDir;
 
+    #[tokio::test]
+    async fn file_query_ranks_cached_index_matches() {
+        let temp = TempDir::new().expect("create tempdir");
+        std::fs::create_dir_all(temp.path().join("src/server")).unwrap();
+        std::fs::write(temp.path().join("src/server/fuzzy.rs"), "").unwrap();
+        std::fs::write(temp.path().join("src/server/file_api.rs"), "").unwrap();
+        std::fs::write(temp.path().join("README.md"), "").unwrap();
+
+        let ServerMessage::FileQueryResult {
+            items,
+            total_matches,
+            version,
+            ..
+        } = file_query_message(temp.path(), "p", "w", "fuz", Some(1)).await
+        else {
+            panic!("expected file query result");
+        };
+        assert_eq!(total_matches, 1);
+        assert_eq!(items[0].path, "src/server/fuzzy.rs");
+        assert_eq!(items[0].positions, vec![11, 12, 13]);
+        assert_eq!(
+            read_file_index_cache(temp.path()).map(|s| s.version),
+            Some(version)
+        );
+
+        let ServerMessage::FileQueryResult {
+            items,
+            total_matches,
+            ..
+        } = file_query_message(temp.path(), "p", "w", "srv", Some(1)).await
+        else {
+            panic!("expected file query result");
+        };
+        assert_eq!(total_matches, 2);
+        assert_eq!(items.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn file_highlight_caches_by_content_hash() {
+        let temp = TempDir::new().expect("create tempdir");
+        std::fs::write(temp.path().join("lib.rs"), "pub fn highlighted() {}\n").unwrap();
+
+        let ServerMessage::FileHighlightResult {
+            hash,
+            syntax,
+            lines,
+            cached,
+            ..
+        } = file_highlight_message(temp.path(), "p", "w", "lib.rs", None, None).await
+        else {
+            panic!("expected highlight result");
+        };
+        assert_eq!(syntax, "Rust");
+        assert_eq!(lines.len(), 1);
+        assert!(!cached);
+        assert_eq!(hash, file_api::content_hash(b"pub fn highlighted() {}\n"));
+
+        let ServerMessage::FileHighlightResult { cached, .. } =
+            file_highlight_message(temp.path(), "p", "w", "lib.rs", None, None).await
+        else {
+            panic!("expected highlight result");
+        };
+        assert!(cached);
+
+        let ServerMessage::Error { code, .. } =
+            file_highlight_message(temp.path(), "p", "w", "lib.rs", Some("missing"), None).await
+        else {
+            panic!("expected error");
+        };
+        assert_eq!(code, "invalid_theme");
+    }
+
     #[test]
     fn file_write_rejects_invalid_utf8_content() {
         let temp = TempDir::new().expect("create tempdir");
-        let msg = file_write_message(temp.path(), "p", "w", "a.txt", &[0xff, 0xfe]);
+        let msg = file_write_message(
+            temp.path(),
+            "p",
+            "w",
+            "a.txt",
+            &[0xff, 0xfe],
+            None,
+            None,
+            None,
+        );
         let ServerMessage::Error { code, .. } = msg else {
             panic!("expected error message");
         };
         assert_eq!(code, "invalid_utf8");
     }
 
+    #[test]
+    fn file_binary_encoding_round_trips_raw_bytes() {
+        let temp = TempDir::new().expect("create tempdir");
+        let bytes = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0xff, 0x00];
+        let msg = file_write_message(
+            temp.path(),
+            "p",
+            "w",
+            "a.png",
+            &bytes,
+            Some("binary"),
+            None,
+            None,
+        );
+        assert!(matches!(
+            msg,
+            ServerMessage::FileWriteResult { success: true, .. }
+        ));
+
+        let msg = file_read_message(temp.path(), "p", "w", "a.png", None, false);
+        let ServerMessage::FileReadResult {
+            content,
+            encoding,
+            mime_type,
+            ..
+        } = msg
+        else {
+            panic!("expected file read result");
+        };
+        assert_eq!(content, bytes);
+        assert_eq!(encoding, "binary");
+        assert_eq!(mime_type.as_deref(), Some("image/png"));
+    }
+
+    #[test]
+    fn file_write_rejects_stale_version() {
+        let temp = TempDir::new().expect("create tempdir");
+        std::fs::write(temp.path().join("a.txt"), "v1\n").unwrap();
+        let ServerMessage::FileReadResult { mtime, hash, .. } =
+            file_read_message(temp.path(), "p", "w", "a.txt", None, false)
+        else {
+            panic!("expected file read result");
+        };
+        assert_eq!(
+            hash.as_deref(),
+            Some(file_api::content_hash(b"v1\n").as_str())
+        );
+        assert!(mtime.is_some());
+
+        // 外部程序修改后，携带旧哈希的写入被拒绝并返回当前哈希
+        std::fs::write(temp.path().join("a.txt"), "agent\n").unwrap();
+        let msg = file_write_message(
+            temp.path(),
+            "p",
+            "w",
+            "a.txt",
+            b"mine\n",
+            None,
+            None,
+            hash.as_deref(),
+        );
+        let ServerMessage::FileConflict { current_hash, .. } = msg else {
+            panic!("expected file conflict");
+        };
+        assert_eq!(current_hash, Some(file_api::content_hash(b"agent\n")));
+        assert_eq!(
+            std::fs::read(temp.path().join("a.txt")).unwrap(),
+            b"agent\n"
+        );
+
+        // 携带当前哈希即可写入，结果返回新版本
+        let msg = file_write_message(
+            temp.path(),
+            "p",
+            "w",
+            "a.txt",
+            b"mine\n",
+            None,
+            None,
+            current_hash.as_deref(),
+        );
+        let ServerMessage::FileWriteResult { hash, .. } = msg else {
+            panic!("expected file write result");
+        };
+        assert_eq!(hash, Some(file_api::content_hash(b"mine\n")));
+
+        // 文件已被删除时同样视为冲突
+        std::fs::remove_file(temp.path().join("a.txt")).unwrap();
+        let msg = file_write_message(temp.path(), "p", "w", "a.txt", b"x", None, mtime, None);
+        assert!(matches!(
+            msg,
+            ServerMessage::FileConflict {
+                current_hash: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn file_apply_edit_applies_lines_with_version_check() {
+        let temp = TempDir::new().expect("create tempdir");
+        std::fs::write(temp.path().join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        let edits = [
+            FileLineEdit {
+                start_line: 2,
+                end_line: 2,
+                new_text: "TWO\n".to_string(),
+            },
+            FileLineEdit {
+                start_line: 4,
+                end_line: 3,
+                new_text: "four\n".to_string(),
+            },
+        ];
+        let msg = file_apply_edit_message(
+            temp.path(),
+            "p",
+            "w",
+            "a.txt",
+            &edits,
+            None,
+            Some(&file_api::content_hash(b"one\ntwo\nthree\n")),
+        );
+        let ServerMessage::FileApplyEditResult { hash, .. } = msg else {
+            panic!("expected file apply edit result");
+        };
+        let expected = "one\nTWO\nthree\nfour\n";
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join("a.txt")).unwrap(),
+            expected
+        );
+        assert_eq!(hash, Some(file_api::content_hash(expected.as_bytes())));
+
+        // 基于旧版本的编辑被拒绝，越界编辑报错，文件均保持不变
+        let msg = file_apply_edit_message(
+            temp.path(),
+            "p",
+            "w",
+            "a.txt",
+            &edits,
+            None,
+            Some(&file_api::content_hash(b"one\ntwo\nthree\n")),
+        );
+        assert!(matches!(msg, ServerMessage::FileConflict { .. }));
+        let out_of_range = [FileLineEdit {
+            start_line: 9,
+            end_line: 9,
+            new_text: String::new(),
+        }];
+        let msg =
+            file_apply_edit_message(temp.path(), "p", "w", "a.txt", &out_of_range, None, None);
+        assert!(matches!(msg, ServerMessage::Error { ref code, .. } if code == "invalid_edit"));
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join("a.txt")).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn file_write_applies_editorconfig_only_when_enabled() {
+        let temp = TempDir::new().expect("create tempdir");
+        std::fs::write(
+            temp.path().join(".editorconfig"),
+            "root = true\n[*.txt]\nend_of_line = lf\ninsert_final_newline = true\n",
+        )
+        .expect("write editorconfig");
+
+        file_write_message(temp.path(), "p", "w", "a.txt", b"x\r\ny", None, None, None);
+        assert_eq!(std::fs::read(temp.path().join("a.txt")).unwrap(), b"x\r\ny");
+
+        std::fs::write(
+            temp.path().join(".tidyflow.toml"),
+            "[editor]\napply_editorconfig = true\n",
+        )
+        .expect("write project config");
+        let msg = file_write_message(temp.path(), "p", "w", "a.txt", b"x\r\ny", None, None, None);
+        assert!(matches!(
+            msg,
+            ServerMessage::FileWriteResult { size: 4, .. }
+        ));
+        assert_eq!(std::fs::read(temp.path().join("a.txt")).unwrap(), b"x\ny\n");
+
+        let msg = file_editorconfig_message(temp.path(), "p", "w", "a.txt");
+        let ServerMessage::FileEditorConfigResult {
+            settings,
+            sources,
+            apply_on_write,
+            ..
+        } = msg
+        else {
+            panic!("expected editorconfig result");
+        };
+        assert_eq!(settings.end_of_line.as_deref(), Some("lf"));
+        assert_eq!(sources, vec![".editorconfig".to_string()]);
+        assert!(apply_on_write);
+    }
+
+    #[test]
+    fn file_read_transcodes_legacy_charset_and_write_roundtrips() {
+        let temp = TempDir::new().expect("create tempdir");
+        let gbk = text_encoding::encoding_for_label("gbk").unwrap();
+        let text = "// 读取配置文件并初始化数据库连接\nlet x = 1;\n";
+        let original = text_encoding::encode_text(text, gbk).unwrap();
+        std::fs::write(temp.path().join("legacy.rs"), &original).expect("seed file");
+
+        let ServerMessage::FileReadResult {
+            content,
+            encoding,
+            charset,
+            size,
+            ..
+        } = file_read_message(temp.path(), "p", "w", "legacy.rs", None, false)
+        else {
+            panic!("expected read result");
+        };
+        assert_eq!(content, text.as_bytes());
+        assert_eq!(encoding, "utf8");
+        assert_eq!(charset.as_deref(), Some("GBK"));
+        assert_eq!(size, original.len() as u64);
+
+        let edited = text.replace("x = 1", "x = 2");
+        let msg = file_write_message(
+            temp.path(),
+            "p",
+            "w",
+            "legacy.rs",
+            edited.as_bytes(),
+            Some("GBK"),
+            None,
+            None,
+        );
+        assert!(matches!(
+            msg,
+            ServerMessage::FileWriteResult { success: true, .. }
+        ));
+        let written = std::fs::read(temp.path().join("legacy.rs")).unwrap();
+        assert_eq!(written, text_encoding::encode_text(&edited, gbk).unwrap());
+
+        let msg = file_write_message(
+            temp.path(),
+            "p",
+            "w",
+            "legacy.rs",
+            "emoji 😀".as_bytes(),
+            Some("gbk"),
+            None,
+            None,
+        );
+        assert!(
+            matches!(msg, ServerMessage::Error { ref code, .. } if code == "unencodable_content")
+        );
+    }
+
+    #[test]
+    fn file_write_preserves_existing_line_endings() {
+        let temp = TempDir::new().expect("create tempdir");
+        std::fs::write(temp.path().join("win.txt"), "a\r\nb\r\n").expect("seed file");
+
+        let ServerMessage::FileReadResult { line_ending, .. } =
+            file_read_message(temp.path(), "p", "w", "win.txt", None, false)
+        else {
+            panic!("expected read result");
+        };
+        assert_eq!(line_ending.as_deref(), Some("crlf"));
+
+        file_write_message(
+            temp.path(),
+            "p",
+            "w",
+            "win.txt",
+            b"a\nb\nc\n",
+            None,
+            None,
+            None,
+        );
+        assert_eq!(
+            std::fs::read(temp.path().join("win.txt")).unwrap(),
+            b"a\r\nb\r\nc\r\n"
+        );
+    }
+
+    #[test]
+    fn file_read_chunk_marks_last_at_request_end_and_eof() {
+        let temp = TempDir::new().expect("create tempdir");
+        std::fs::write(temp.path().join("big.log"), b"abcdefghij").expect("write file");
+
+        let msg = file_read_chunk_message(temp.path(), "p", "w", "big.log", 0, 4, Some(8));
+        let ServerMessage::FileChunk {
+            content, eof, last, ..
+        } = msg
+        else {
+            panic!("expected file chunk");
+        };
+        assert_eq!(content, b"abcd");
+        assert!(!eof);
+        assert!(!last);
+
+        let msg = file_read_chunk_message(temp.path(), "p", "w", "big.log", 4, 4, Some(8));
+        assert!(matches!(
+            msg,
+            ServerMessage::FileChunk {
+                last: true,
+                eof: false,
+                ..
+            }
+        ));
+
+        let msg = file_read_chunk_message(temp.path(), "p", "w", "big.log", 8, 4, None);
+        let ServerMessage::FileChunk {
+            content,
+            total_size,
+            eof,
+            last,
+            ..
+        } = msg
+        else {
+            panic!("expected file chunk");
+        };
+        assert_eq!(content, b"ij");
+        assert_eq!(total_size, 10);
+        assert!(eof && last);
+    }
+
+    #[test]
+    fn file_read_rejects_unknown_encoding() {
+        let temp = TempDir::new().expect("create tempdir");
+        let msg = file_read_message(temp.path(), "p", "w", "a.txt", Some("klingon"), false);
+        let ServerMessage::Error { code, .. } = msg else {
+            panic!("expected error message");
+        };
+        assert_eq!(code, "invalid_encoding");
+    }
+
+    #[test]
+    fn file_read_respects_project_size_limit_and_preview() {
+        let temp = TempDir::new().expect("create tempdir");
+        std::fs::write(
+            temp.path().join(".tidyflow.toml"),
+            "[editor]\nmax_file_size = 16\n",
+        )
+        .expect("write config");
+        std::fs::write(temp.path().join("big.json"), "{\n\"a\": 1,\n\"b\": 2\n}\n")
+            .expect("seed file");
+
+        let msg = file_read_message(temp.path(), "p", "w", "big.json", None, false);
+        let ServerMessage::Error { code, .. } = msg else {
+            panic!("expected file_too_large");
+        };
+        assert_eq!(code, "file_too_large");
+
+        let ServerMessage::FileReadResult {
+            content,
+            size,
+            truncated,
+            ..
+        } = file_read_message(temp.path(), "p", "w", "big.json", None, true)
+        else {
+            panic!("expected preview result");
+        };
+        assert_eq!(content, b"{\n\"a\": 1,\n");
+        assert_eq!(size, 19);
+        assert!(truncated);
+
+        // 写入同样受项目上限约束
+        let msg = file_write_message(
+            temp.path(),
+            "p",
+            "w",
+            "big.json",
+            &[b'x'; 17],
+            None,
+            None,
+            None,
+        );
+        let ServerMessage::Error { code, .. } = msg else {
+            panic!("expected file_too_large");
+        };
+        assert_eq!(code, "file_too_large");
+    }
+
     // ── FileWorkspacePhase 基础语义 ──
 
     #[test]
@@ -1016,6 +2809,120 @@ mod tests {
         invalidate_file_index_cache(root);
     }
 
+    #[test]
+    fn incremental_update_reconciles_with_filesystem_and_bumps_version() {
+        let temp = TempDir::new().expect("create tempdir");
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "").unwrap();
+        std::fs::write(root.join("src/old.rs"), "").unwrap();
+
+        let base = write_file_index_cache(
+            root,
+            &["src/main.rs".to_string(), "src/old.rs".to_string()],
+            false,
+        );
+
+        // watcher 只上报 modified：新文件按存在性插入，已删除文件按不存在移除
+        std::fs::remove_file(root.join("src/old.rs")).unwrap();
+        std::fs::write(root.join("src/new.rs"), "").unwrap();
+        let delta = update_file_index_incrementally(
+            root,
+            &[
+                "src/old.rs".to_string(),
+                "src/new.rs".to_string(),
+                "src/main.rs".to_string(),
+            ],
+            "modified",
+        )
+        .expect("delta should be produced");
+
+        assert_eq!(delta.base_version, base);
+        assert!(delta.version > base);
+        assert_eq!(delta.added, vec!["src/new.rs".to_string()]);
+        assert_eq!(delta.removed, vec!["src/old.rs".to_string()]);
+
+        let snap = read_file_index_cache(root).unwrap();
+        assert_eq!(snap.version, delta.version);
+        assert_eq!(snap.items, vec!["src/main.rs", "src/new.rs"]);
+
+        // 纯内容修改不产生 delta
+        assert!(
+            update_file_index_incrementally(root, &["src/main.rs".to_string()], "modified")
+                .is_none()
+        );
+
+        invalidate_file_index_cache(root);
+    }
+
+    #[test]
+    fn incremental_update_handles_directory_create_and_remove() {
+        let temp = TempDir::new().expect("create tempdir");
+        let root = temp.path();
+        write_file_index_cache(root, &["a.rs".to_string()], false);
+
+        std::fs::create_dir_all(root.join("pkg/sub")).unwrap();
+        std::fs::write(root.join("pkg/x.rs"), "").unwrap();
+        std::fs::write(root.join("pkg/sub/y.rs"), "").unwrap();
+        let delta = update_file_index_incrementally(root, &["pkg".to_string()], "modified")
+            .expect("new directory should be indexed");
+        assert_eq!(delta.added.len(), 2);
+
+        std::fs::remove_dir_all(root.join("pkg")).unwrap();
+        let delta = update_file_index_incrementally(root, &["pkg".to_string()], "modified")
+            .expect("removed directory should drop children");
+        assert_eq!(delta.removed.len(), 2);
+        assert_eq!(read_file_index_cache(root).unwrap().items, vec!["a.rs"]);
+
+        invalidate_file_index_cache(root);
+    }
+
+    #[test]
+    fn incremental_update_reapplies_changed_ignore_rules() {
+        let temp = TempDir::new().expect("create tempdir");
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("out")).unwrap();
+        std::fs::write(root.join("a.rs"), "").unwrap();
+        std::fs::write(root.join("out/b.js"), "").unwrap();
+        write_file_index_cache(root, &["a.rs".to_string(), "out/b.js".to_string()], false);
+
+        // 被忽略目录中的变更不进入索引
+        std::fs::write(root.join(".gitignore"), "out/\n").unwrap();
+        std::fs::write(root.join("out/c.js"), "").unwrap();
+        let delta = update_file_index_incrementally(root, &[".gitignore".to_string()], "modified")
+            .expect("ignore change should reconcile index");
+        assert_eq!(delta.removed, vec!["out/b.js".to_string()]);
+        assert!(delta.added.is_empty());
+        assert!(
+            update_file_index_incrementally(root, &["out/c.js".to_string()], "modified").is_none()
+        );
+
+        std::fs::remove_file(root.join(".gitignore")).unwrap();
+        let delta = update_file_index_incrementally(root, &[".gitignore".to_string()], "modified")
+            .expect("ignore removal should reconcile index");
+        assert_eq!(
+            delta.added,
+            vec!["out/b.js".to_string(), "out/c.js".to_string()]
+        );
+
+        invalidate_file_index_cache(root);
+    }
+
+    #[test]
+    fn live_file_index_ignores_ttl_until_released() {
+        let temp = TempDir::new().expect("create tempdir");
+        let root = temp.path();
+        let key = file_index_cache_key(root);
+
+        retain_live_file_index(root);
+        retain_live_file_index(root);
+        assert!(is_live_file_index(&key));
+        release_live_file_index(root);
+        assert!(is_live_file_index(&key), "second subscriber still holds");
+        release_live_file_index(root);
+        assert!(!is_live_file_index(&key));
+    }
+
     #[test]
     fn hotspot_perf_multi_workspace_cache_isolation() {
         // 验证不同工作区路径缓存互不污染（多项目同名工作区隔离）
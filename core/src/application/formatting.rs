@@ -3,6 +3,7 @@
 //! 按 (project, workspace) 解析工作区根目录并执行格式化。
 //! 不允许跨工作区复用缓存或路径推断。
 
+use crate::server::editorconfig;
 use crate::server::protocol::formatting::{
     EditorFormattingCapability, EditorFormattingErrorCode, EditorFormatScope,
 };
@@ -77,13 +78,41 @@ pub async fn execute_format(
 ) -> FormatResult {
     let language = detect_language(file_path);
 
-    match language.as_str() {
+    let result = match language.as_str() {
         "swift" => execute_swift_format(workspace_root, scope, text).await,
         "rust" => execute_rust_format(workspace_root, scope, text).await,
         _ => FormatResult::Error {
             error_code: EditorFormattingErrorCode::UnsupportedLanguage,
             message: format!("语言 '{}' 无已注册的格式化器", language),
         },
+    };
+    apply_editorconfig_rules(file_path, workspace_root, text, result)
+}
+
+/// 格式化器输出按 `.editorconfig` 规范化换行符、行尾空白与末尾换行（不改缩进）
+fn apply_editorconfig_rules(
+    file_path: &str,
+    workspace_root: &Path,
+    original: &str,
+    result: FormatResult,
+) -> FormatResult {
+    let FormatResult::Success {
+        formatted_text,
+        formatter_id,
+        scope,
+        ..
+    } = result
+    else {
+        return result;
+    };
+    let settings = editorconfig::resolve_editorconfig(workspace_root, file_path).settings;
+    let formatted_text =
+        editorconfig::apply_to_content(&settings, &formatted_text, false).into_owned();
+    FormatResult::Success {
+        changed: formatted_text != original,
+        formatted_text,
+        formatter_id,
+        scope,
     }
 }
 
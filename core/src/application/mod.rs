@@ -1,10 +1,15 @@
 pub mod file;
 pub mod formatting;
+pub mod proc;
 pub mod project;
 pub mod project_admin;
 pub mod project_command;
+pub mod project_health;
 pub mod project_workspace;
 pub mod settings;
 pub mod sidebar_status;
+pub mod state_backup;
 pub mod task;
 pub mod terminal;
+pub mod warmup;
+pub mod workspace_manifest;
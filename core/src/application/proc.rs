@@ -0,0 +1,170 @@
+use std::path::Path;
+
+use crate::application::project_command::HandlerReply;
+use crate::server::context::{
+    resolve_workspace, send_task_broadcast_event, HandlerContext, SharedAppState,
+    TaskBroadcastEvent, TaskBroadcastTx,
+};
+use crate::server::file_api::provider::is_remote_root;
+use crate::server::proc_supervisor::{self, ProcError, MAX_LOG_LINES};
+use crate::server::protocol::{ProcInfo, ServerMessage};
+
+/// `proc_logs` 未指定行数时返回的行数
+const DEFAULT_PROC_LOG_LINES: usize = 200;
+
+pub(crate) fn proc_error_message(project: &str, workspace: &str, e: &ProcError) -> ServerMessage {
+    ServerMessage::Error {
+        code: e.code().to_string(),
+        message: e.to_string(),
+        project: Some(project.to_string()),
+        workspace: Some(workspace.to_string()),
+        session_id: None,
+        cycle_id: None,
+        trace_id: None,
+    }
+}
+
+/// 进程退出事件不属于任何连接，广播给全部连接
+fn broadcast_proc_exited(task_broadcast_tx: &TaskBroadcastTx, message: ServerMessage) {
+    send_task_broadcast_event(
+        task_broadcast_tx,
+        TaskBroadcastEvent {
+            origin_conn_id: String::new(),
+            message,
+            target_conn_ids: None,
+            skip_when_single_receiver: false,
+        },
+    );
+}
+
+fn reply_only(response: ServerMessage) -> HandlerReply {
+    HandlerReply {
+        response,
+        broadcast: None,
+    }
+}
+
+fn reply_and_broadcast(response: ServerMessage) -> HandlerReply {
+    HandlerReply {
+        broadcast: Some(response.clone()),
+        response,
+    }
+}
+
+/// 启动托管进程，退出时向所有连接广播 `proc_exited`
+pub(crate) fn spawn_supervised(
+    task_broadcast_tx: &TaskBroadcastTx,
+    project: &str,
+    workspace: &str,
+    name: &str,
+    command: &str,
+    cwd: &Path,
+    env: &[(String, String)],
+) -> Result<ProcInfo, ProcError> {
+    let task_broadcast_tx = task_broadcast_tx.clone();
+    let (exit_project, exit_workspace) = (project.to_string(), workspace.to_string());
+    proc_supervisor::start_proc(project, workspace, name, command, cwd, env, move |info| {
+        broadcast_proc_exited(
+            &task_broadcast_tx,
+            ServerMessage::ProcExited {
+                project: exit_project,
+                workspace: exit_workspace,
+                info,
+            },
+        );
+    })
+}
+
+/// 启动托管进程；成功时响应 `proc_started` 并同步给其他连接
+pub async fn start_proc(
+    ctx: &HandlerContext,
+    project: &str,
+    workspace: &str,
+    name: &str,
+    command: &str,
+) -> HandlerReply {
+    let ws_ctx = match resolve_workspace(&ctx.app_state, project, workspace).await {
+        Ok(ws_ctx) => ws_ctx,
+        Err(e) => return reply_only(e.to_server_error()),
+    };
+    if is_remote_root(&ws_ctx.root_path) {
+        return reply_only(ServerMessage::Error {
+            code: "invalid_request".to_string(),
+            message: "Supervised processes are not supported for remote projects".to_string(),
+            project: Some(project.to_string()),
+            workspace: Some(workspace.to_string()),
+            session_id: None,
+            cycle_id: None,
+            trace_id: None,
+        });
+    }
+
+    let result = spawn_supervised(
+        &ctx.task_broadcast_tx,
+        project,
+        workspace,
+        name,
+        command,
+        &ws_ctx.root_path,
+        &[],
+    );
+    match result {
+        Ok(info) => reply_and_broadcast(ServerMessage::ProcStarted {
+            project: project.to_string(),
+            workspace: workspace.to_string(),
+            info,
+        }),
+        Err(e) => reply_only(proc_error_message(project, workspace, &e)),
+    }
+}
+
+/// 停止托管进程；响应 `proc_stopped` 并同步给其他连接
+pub fn stop_proc(project: &str, workspace: &str, name: &str) -> HandlerReply {
+    match proc_supervisor::stop_proc(project, workspace, name) {
+        Ok(info) => reply_and_broadcast(ServerMessage::ProcStopped {
+            project: project.to_string(),
+            workspace: workspace.to_string(),
+            info,
+        }),
+        Err(e) => reply_only(proc_error_message(project, workspace, &e)),
+    }
+}
+
+pub async fn proc_list_message(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+) -> Result<ServerMessage, ServerMessage> {
+    resolve_workspace(app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_server_error())?;
+    Ok(ServerMessage::ProcListResult {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        items: proc_supervisor::list_procs(project, workspace),
+    })
+}
+
+pub async fn proc_logs_message(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+    name: &str,
+    lines: Option<usize>,
+) -> Result<ServerMessage, ServerMessage> {
+    resolve_workspace(app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_server_error())?;
+    let limit = lines
+        .unwrap_or(DEFAULT_PROC_LOG_LINES)
+        .clamp(1, MAX_LOG_LINES);
+    let tail = proc_supervisor::proc_logs(project, workspace, name, limit)
+        .map_err(|e| proc_error_message(project, workspace, &e))?;
+    Ok(ServerMessage::ProcLogsResult {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        name: name.to_string(),
+        lines: tail.lines,
+        dropped_lines: tail.dropped_lines,
+    })
+}
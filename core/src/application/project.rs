@@ -1,6 +1,16 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+
+use crate::application::project_health::{branch_commit_times, project_health};
 use crate::server::context::{HandlerContext, SharedAppState};
-use crate::server::protocol::{ProjectCommandInfo, ProjectInfo, ServerMessage, WorkspaceInfo};
-use crate::workspace::state::{WorkspaceStatus, DEFAULT_WORKSPACE_NAME};
+use crate::server::file_api::provider::is_remote_root;
+use crate::server::protocol::{
+    ListeningPortInfo, ProjectCommandInfo, ProjectInfo, ServerMessage, WorkspaceInfo,
+    WorkspaceTaskInfo,
+};
+use crate::util::exec_env::exec_env_for;
+use crate::workspace::state::{ProjectLoadStatus, WorkspaceStatus, DEFAULT_WORKSPACE_NAME};
 
 pub fn workspace_status_str(status: &WorkspaceStatus) -> String {
     match status {
@@ -13,37 +23,186 @@ pub fn workspace_status_str(status: &WorkspaceStatus) -> String {
 }
 
 pub async fn list_projects_message(app_state: &SharedAppState) -> ServerMessage {
-    let state = app_state.read().await;
-    let mut items: Vec<ProjectInfo> = state
-        .projects
-        .values()
-        .map(|p| ProjectInfo {
-            name: p.name.clone(),
-            root: p.root_path.to_string_lossy().to_string(),
-            workspace_count: p.workspaces.len(),
-            commands: p
-                .commands
-                .iter()
-                .map(|c| ProjectCommandInfo {
-                    id: c.id.clone(),
-                    name: c.name.clone(),
-                    icon: c.icon.clone(),
-                    command: c.command.clone(),
-                    blocking: c.blocking,
-                    interactive: c.interactive,
-                })
-                .collect(),
-        })
-        .collect();
+    // 先在读锁内复制所需字段，git 统计在锁外的阻塞线程中计算
+    let rows = {
+        let state = app_state.read().await;
+        state
+            .projects
+            .values()
+            .map(|p| {
+                let load_status = state.project_load_status(&p.name);
+                let mut worktrees: Vec<PathBuf> = p
+                    .workspaces
+                    .values()
+                    .map(|w| w.worktree_path.clone())
+                    .collect();
+                worktrees.sort();
+                let last_accessed = p.workspaces.values().map(|w| w.last_accessed).max();
+                let info = ProjectInfo {
+                    name: p.name.clone(),
+                    root: p.root_path.to_string_lossy().to_string(),
+                    workspace_count: p.workspaces.len(),
+                    commands: p
+                        .commands
+                        .iter()
+                        .map(|c| ProjectCommandInfo {
+                            id: c.id.clone(),
+                            name: c.name.clone(),
+                            icon: c.icon.clone(),
+                            command: c.command.clone(),
+                            blocking: c.blocking,
+                            interactive: c.interactive,
+                        })
+                        .collect(),
+                    default_branch: p.default_branch.clone(),
+                    ahead_origin: None,
+                    behind_origin: None,
+                    dirty_workspace_count: 0,
+                    last_activity_at: None,
+                    imported_at: p.created_at.to_rfc3339(),
+                    load_status: load_status.as_str().to_string(),
+                    load_error: match load_status {
+                        ProjectLoadStatus::Failed { error, .. } => Some(error),
+                        _ => None,
+                    },
+                    exec_env: String::new(),
+                    wsl_distro: None,
+                };
+                (info, p.root_path.clone(), worktrees, last_accessed)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut items = crate::util::trace::spawn_blocking(move || {
+        rows.into_iter()
+            .map(|(mut info, root, worktrees, last_accessed)| {
+                if is_remote_root(&root) {
+                    // 远程项目的 git 经 ssh 执行，本地健康检查无意义
+                    info.exec_env = "ssh".to_string();
+                    info.last_activity_at = last_accessed.map(|at| at.to_rfc3339());
+                    return info;
+                }
+                let env = exec_env_for(&root);
+                info.exec_env = env.kind().to_string();
+                info.wsl_distro = env.wsl_distro().map(str::to_string);
+                let health = project_health(&root, &info.default_branch, &worktrees);
+                info.ahead_origin = health.ahead_origin;
+                info.behind_origin = health.behind_origin;
+                info.dirty_workspace_count = health.dirty_workspace_count;
+                info.last_activity_at = health
+                    .last_commit_at
+                    .max(last_accessed)
+                    .map(|at| at.to_rfc3339());
+                info
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .unwrap_or_default();
     items.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
     ServerMessage::Projects { items }
 }
 
+/// `list_workspaces` 的服务端过滤与排序条件
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceListFilter {
+    /// 名称子串（大小写不敏感）
+    pub name: Option<String>,
+    pub status: Option<String>,
+    pub branch_prefix: Option<String>,
+    pub sort_by_activity: bool,
+}
+
+const WORKSPACE_STATUSES: &[&str] = &[
+    "ready",
+    "setup_failed",
+    "creating",
+    "initializing",
+    "destroying",
+];
+
+impl WorkspaceListFilter {
+    /// 由请求参数构造；非法的 status / sort 返回错误信息
+    pub fn from_params(
+        name: Option<&str>,
+        status: Option<&str>,
+        branch_prefix: Option<&str>,
+        sort: Option<&str>,
+    ) -> Result<Self, String> {
+        let non_empty = |value: Option<&str>| {
+            value
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        let status = non_empty(status);
+        if let Some(status) = status.as_deref() {
+            if !WORKSPACE_STATUSES.contains(&status) {
+                return Err(format!(
+                    "Invalid workspace status filter: {} (expected {})",
+                    status,
+                    WORKSPACE_STATUSES.join(", ")
+                ));
+            }
+        }
+        let sort_by_activity = match non_empty(sort).as_deref() {
+            None | Some("name") => false,
+            Some("last_activity") => true,
+            Some(other) => {
+                return Err(format!(
+                    "Invalid workspace sort: {} (expected name or last_activity)",
+                    other
+                ))
+            }
+        };
+        Ok(Self {
+            name: non_empty(name).map(|n| n.to_lowercase()),
+            status,
+            branch_prefix: non_empty(branch_prefix),
+            sort_by_activity,
+        })
+    }
+
+    fn is_filtering(&self) -> bool {
+        self.name.is_some() || self.status.is_some() || self.branch_prefix.is_some()
+    }
+
+    fn matches(&self, name: &str, branch: &str, status: &str) -> bool {
+        self.name
+            .as_deref()
+            .is_none_or(|needle| name.to_lowercase().contains(needle))
+            && self.status.as_deref().is_none_or(|s| s == status)
+            && self
+                .branch_prefix
+                .as_deref()
+                .is_none_or(|prefix| branch.starts_with(prefix))
+    }
+}
+
 pub async fn list_workspaces_message(
     ctx: &HandlerContext,
     project: &str,
 ) -> Result<ServerMessage, ServerMessage> {
-    let (default_root, default_branch, mut workspace_rows) = {
+    list_workspaces_filtered_message(ctx, project, &WorkspaceListFilter::default()).await
+}
+
+struct WorkspaceRow {
+    name: String,
+    root: String,
+    branch: String,
+    status: String,
+    last_accessed: Option<DateTime<Utc>>,
+    port: Option<u16>,
+    linked_issue: Option<String>,
+    sparse_paths: Vec<String>,
+}
+
+pub async fn list_workspaces_filtered_message(
+    ctx: &HandlerContext,
+    project: &str,
+    filter: &WorkspaceListFilter,
+) -> Result<ServerMessage, ServerMessage> {
+    let (project_root, mut rows) = {
         let state = ctx.app_state.read().await;
         let Some(p) = state.get_project(project) else {
             return Err(ServerMessage::Error {
@@ -53,63 +212,151 @@ pub async fn list_workspaces_message(
                 workspace: None,
                 session_id: None,
                 cycle_id: None,
+                trace_id: None,
             });
         };
 
-        let rows = p
+        let mut named = p
             .workspaces
             .values()
-            .map(|w| {
-                (
-                    w.name.clone(),
-                    w.worktree_path.to_string_lossy().to_string(),
-                    w.branch.clone(),
-                    workspace_status_str(&w.status),
-                )
+            .map(|w| WorkspaceRow {
+                name: w.name.clone(),
+                root: w.worktree_path.to_string_lossy().to_string(),
+                branch: w.branch.clone(),
+                status: workspace_status_str(&w.status),
+                last_accessed: Some(w.last_accessed),
+                port: w.port,
+                linked_issue: w.linked_issue.clone(),
+                sparse_paths: w.sparse_paths.clone(),
             })
             .collect::<Vec<_>>();
+        named.sort_by(|a, b| a.name.cmp(&b.name));
 
-        (
-            p.root_path.to_string_lossy().to_string(),
-            p.default_branch.clone(),
-            rows,
-        )
+        let mut rows = Vec::with_capacity(named.len() + 1);
+        rows.push(WorkspaceRow {
+            name: DEFAULT_WORKSPACE_NAME.to_string(),
+            root: p.root_path.to_string_lossy().to_string(),
+            branch: p.default_branch.clone(),
+            status: "ready".to_string(),
+            last_accessed: None,
+            port: None,
+            linked_issue: None,
+            sparse_paths: Vec::new(),
+        });
+        rows.extend(named);
+        (p.root_path.clone(), rows)
     };
 
-    workspace_rows.sort_by(|a, b| a.0.cmp(&b.0));
-
-    let mut items: Vec<WorkspaceInfo> = Vec::with_capacity(workspace_rows.len() + 1);
-    items.push(WorkspaceInfo {
-        name: DEFAULT_WORKSPACE_NAME.to_string(),
-        root: default_root,
-        branch: default_branch,
-        status: "ready".to_string(),
-        sidebar_status: crate::application::sidebar_status::workspace_sidebar_status(
-            ctx,
-            project,
-            DEFAULT_WORKSPACE_NAME,
-        )
-        .await,
-    });
+    let total = rows.len();
+    rows.retain(|row| filter.matches(&row.name, &row.branch, &row.status));
 
-    for (name, root, branch, status) in workspace_rows {
+    let commit_times =
+        crate::util::trace::spawn_blocking(move || branch_commit_times(&project_root))
+            .await
+            .unwrap_or_default();
+    let mut rows = rows
+        .into_iter()
+        .map(|row| {
+            let activity = commit_times
+                .get(&row.branch)
+                .copied()
+                .max(row.last_accessed);
+            (row, activity)
+        })
+        .collect::<Vec<_>>();
+    if filter.sort_by_activity {
+        // 稳定排序：活动时间相同（或都缺失）时保持名称顺序
+        rows.sort_by_key(|row| std::cmp::Reverse(row.1));
+    }
+
+    let mut items: Vec<WorkspaceInfo> = Vec::with_capacity(rows.len());
+    for (row, activity) in rows {
         let sidebar_status =
-            crate::application::sidebar_status::workspace_sidebar_status(ctx, project, &name).await;
+            crate::application::sidebar_status::workspace_sidebar_status(ctx, project, &row.name)
+                .await;
+        let suggested_issue = crate::workspace::branch_name::suggested_issue_reference(
+            &row.branch,
+            row.linked_issue.as_deref(),
+        );
         items.push(WorkspaceInfo {
-            name,
-            root,
-            branch,
-            status,
+            name: row.name,
+            root: row.root,
+            branch: row.branch,
+            status: row.status,
             sidebar_status,
+            last_activity_at: activity.map(|at| at.to_rfc3339()),
+            port: row.port,
+            linked_issue: row.linked_issue,
+            suggested_issue,
+            sparse_paths: row.sparse_paths,
         });
     }
 
     Ok(ServerMessage::Workspaces {
         project: project.to_string(),
         items,
+        total: filter.is_filtering().then_some(total),
     })
 }
 
+/// 列出工作区内进程监听的端口；远程项目无法检查进程，返回空列表
+pub async fn list_ports_message(root: PathBuf, project: &str, workspace: &str) -> ServerMessage {
+    let items = if is_remote_root(&root) {
+        Vec::new()
+    } else {
+        crate::util::trace::spawn_blocking(move || {
+            crate::server::ports::list_listening_ports(&root)
+                .into_iter()
+                .map(|p| ListeningPortInfo {
+                    port: p.port,
+                    address: p.address,
+                    pid: p.pid,
+                    command: p.command,
+                })
+                .collect()
+        })
+        .await
+        .unwrap_or_default()
+    };
+    ServerMessage::WorkspacePorts {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        items,
+    }
+}
+
+/// 识别工作区根目录下的任务；远程项目不支持静态解析，返回空列表
+pub async fn list_workspace_tasks_message(
+    root: PathBuf,
+    project: &str,
+    workspace: &str,
+) -> ServerMessage {
+    let items = if is_remote_root(&root) {
+        Vec::new()
+    } else {
+        crate::util::trace::spawn_blocking(move || {
+            crate::server::task_runner::detect_tasks(&root)
+                .into_iter()
+                .map(|task| WorkspaceTaskInfo {
+                    id: task.id(),
+                    runner: task.runner.as_str().to_string(),
+                    name: task.name,
+                    command: task.command,
+                    description: task.description,
+                    file: task.file,
+                })
+                .collect()
+        })
+        .await
+        .unwrap_or_default()
+    };
+    ServerMessage::WorkspaceTasks {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        items,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -146,6 +393,7 @@ mod tests {
         };
         assert_eq!(items.first().map(|i| i.name.as_str()), Some("alpha"));
         assert_eq!(items.last().map(|i| i.name.as_str()), Some("zeta"));
+        assert!(items.iter().all(|i| i.exec_env == "native"));
     }
 
     /// CHK-004: 验证 list_workspaces_message 在不存在的项目上返回 project_not_found 错误
@@ -161,4 +409,28 @@ mod tests {
         // 空状态下项目列表为空
         assert!(state.list_projects().is_empty(), "默认状态下项目列表应为空");
     }
+
+    #[test]
+    fn workspace_list_filter_params() {
+        let filter = WorkspaceListFilter::from_params(
+            Some(" Otter "),
+            Some("ready"),
+            Some("feat/"),
+            Some("last_activity"),
+        )
+        .unwrap();
+        assert!(filter.sort_by_activity);
+        assert!(filter.is_filtering());
+        assert!(filter.matches("brave-otter", "feat/brave-otter", "ready"));
+        assert!(!filter.matches("brave-otter", "tidy/brave-otter", "ready"));
+        assert!(!filter.matches("brave-otter", "feat/brave-otter", "creating"));
+        assert!(!filter.matches("calm-fox", "feat/calm-fox", "ready"));
+
+        let filter = WorkspaceListFilter::from_params(Some(""), None, None, Some("name")).unwrap();
+        assert!(!filter.is_filtering());
+        assert!(!filter.sort_by_activity);
+
+        assert!(WorkspaceListFilter::from_params(None, Some("broken"), None, None).is_err());
+        assert!(WorkspaceListFilter::from_params(None, None, None, Some("recent")).is_err());
+    }
 }
@@ -3,7 +3,8 @@ use std::path::PathBuf;
 use crate::application::project::workspace_status_str;
 use crate::server::context::SharedAppState;
 use crate::server::protocol::{ProjectCommandInfo, ServerMessage, TemplateInfo, WorkspaceInfo};
-use crate::workspace::project::ProjectManager;
+use crate::workspace::project::{CloneProgress, ProjectError, ProjectManager};
+use crate::workspace::state::{Project, Workspace};
 use crate::workspace::workspace::WorkspaceManager;
 
 pub async fn import_project_message(
@@ -15,42 +16,92 @@ pub async fn import_project_message(
     let mut state = app_state.write().await;
 
     match ProjectManager::import_local(&mut state, name, &path_buf) {
-        Ok(project) => ServerMessage::ProjectImported {
-            name: name.to_string(),
-            root: project.root_path.to_string_lossy().to_string(),
-            default_branch: project.default_branch.clone(),
-            workspace: None,
-        },
+        Ok(project) => project_imported_message(name, &project),
+        Err(e) => project_import_error_message(&e),
+    }
+}
+
+/// 克隆 `url` 到托管目录后导入；克隆在阻塞线程中进行，期间不持有状态锁
+pub async fn import_project_from_url_message(
+    app_state: &SharedAppState,
+    name: &str,
+    url: &str,
+    branch: Option<&str>,
+    depth: Option<u32>,
+    on_progress: impl FnMut(CloneProgress) + Send + 'static,
+) -> ServerMessage {
+    if app_state.read().await.get_project(name).is_some() {
+        return project_import_error_message(&ProjectError::AlreadyExists(name.to_string()));
+    }
+
+    let clone_path = ProjectManager::managed_clone_dir(name);
+    let url_owned = url.to_string();
+    let branch_owned = branch.map(str::to_string);
+    let target = clone_path.clone();
+    let cloned = crate::util::trace::spawn_blocking(move || {
+        ProjectManager::clone_repository(
+            &url_owned,
+            branch_owned.as_deref(),
+            depth,
+            &target,
+            on_progress,
+        )
+    })
+    .await
+    .unwrap_or_else(|e| Err(ProjectError::GitError(e.to_string())));
+    if let Err(e) = cloned {
+        return project_import_error_message(&e);
+    }
+
+    let mut state = app_state.write().await;
+    match ProjectManager::import_local(&mut state, name, &clone_path) {
+        Ok(project) => project_imported_message(name, &project),
         Err(e) => {
-            let (code, message) = match &e {
-                crate::workspace::project::ProjectError::AlreadyExists(_) => {
-                    ("project_exists".to_string(), e.to_string())
-                }
-                crate::workspace::project::ProjectError::PathNotFound(_) => {
-                    ("path_not_found".to_string(), e.to_string())
-                }
-                crate::workspace::project::ProjectError::NotGitRepo(_) => {
-                    ("not_git_repo".to_string(), e.to_string())
-                }
-                _ => ("import_error".to_string(), e.to_string()),
-            };
-            ServerMessage::Error {
-                code,
-                message,
-                project: None,
-                workspace: None,
-                session_id: None,
-                cycle_id: None,
-            }
+            // 克隆期间同名项目被导入等情况：不保留无主的克隆目录
+            let _ = std::fs::remove_dir_all(&clone_path);
+            project_import_error_message(&e)
         }
     }
 }
 
+fn project_imported_message(name: &str, project: &Project) -> ServerMessage {
+    ServerMessage::ProjectImported {
+        name: name.to_string(),
+        root: project.root_path.to_string_lossy().to_string(),
+        default_branch: project.default_branch.clone(),
+        workspace: None,
+    }
+}
+
+fn project_import_error_message(e: &ProjectError) -> ServerMessage {
+    let code = match e {
+        ProjectError::AlreadyExists(_) => "project_exists",
+        ProjectError::PathNotFound(_) => "path_not_found",
+        ProjectError::NotGitRepo(_) => "not_git_repo",
+        ProjectError::GitError(_) => "clone_failed",
+        ProjectError::OfflineMode => crate::server::git::OFFLINE_MODE_CODE,
+        ProjectError::Cancelled => crate::util::cancel::CANCELLED_CODE,
+        _ => "import_error",
+    };
+    ServerMessage::Error {
+        code: code.to_string(),
+        message: e.to_string(),
+        project: None,
+        workspace: None,
+        session_id: None,
+        cycle_id: None,
+        trace_id: None,
+    }
+}
+
+/// 创建工作空间；`sparse_paths` 非空时稀疏检出，`on_progress` 以 `(workspace 名, 进度)` 接收 worktree 准备进度
 pub async fn create_workspace_message(
     app_state: &SharedAppState,
     project: &str,
     from_branch: Option<&str>,
     template_id: Option<&str>,
+    sparse_paths: &[String],
+    on_progress: impl FnMut(&str, CloneProgress),
 ) -> ServerMessage {
     let mut state = app_state.write().await;
 
@@ -77,7 +128,15 @@ pub async fn create_workspace_message(
                 })
         });
 
-    match WorkspaceManager::create(&mut state, project, from_branch, false) {
+    match WorkspaceManager::create_with_progress(
+        &mut state,
+        project,
+        from_branch,
+        template_id,
+        false,
+        sparse_paths,
+        on_progress,
+    ) {
         Ok(ws) => {
             // 如果指定了模板，将模板命令应用到项目
             if let Some(cmds) = template_commands {
@@ -89,13 +148,7 @@ pub async fn create_workspace_message(
             }
             ServerMessage::WorkspaceCreated {
                 project: project.to_string(),
-                workspace: WorkspaceInfo {
-                    name: ws.name,
-                    root: ws.worktree_path.to_string_lossy().to_string(),
-                    branch: ws.branch,
-                    status: workspace_status_str(&ws.status),
-                    sidebar_status: Default::default(),
-                },
+                workspace: workspace_info(ws),
             }
         }
         Err(e) => {
@@ -109,6 +162,12 @@ pub async fn create_workspace_message(
                 crate::workspace::workspace::WorkspaceError::NotGitRepo(_) => {
                     ("not_git_repo".to_string(), e.to_string())
                 }
+                crate::workspace::workspace::WorkspaceError::InvalidBranchName(_) => {
+                    ("invalid_branch_name".to_string(), e.to_string())
+                }
+                crate::workspace::workspace::WorkspaceError::InvalidSparsePath(_) => {
+                    ("invalid_sparse_path".to_string(), e.to_string())
+                }
                 _ => ("workspace_error".to_string(), e.to_string()),
             };
             ServerMessage::Error {
@@ -118,6 +177,7 @@ pub async fn create_workspace_message(
                 workspace: None,
                 session_id: None,
                 cycle_id: None,
+                trace_id: None,
             }
         }
     }
@@ -163,6 +223,207 @@ pub async fn remove_workspace_message(
     }
 }
 
+pub async fn rename_workspace_message(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+    new_name: &str,
+    rename_branch: bool,
+    move_worktree: bool,
+) -> ServerMessage {
+    let mut state = app_state.write().await;
+
+    match WorkspaceManager::rename(
+        &mut state,
+        project,
+        workspace,
+        new_name,
+        rename_branch,
+        move_worktree,
+    ) {
+        Ok(ws) => ServerMessage::WorkspaceRenamed {
+            project: project.to_string(),
+            workspace: workspace.to_string(),
+            new_name: ws.name.clone(),
+            ok: true,
+            message: Some("工作空间已重命名".to_string()),
+            info: Some(workspace_info(ws)),
+        },
+        Err(e) => ServerMessage::WorkspaceRenamed {
+            project: project.to_string(),
+            workspace: workspace.to_string(),
+            new_name: new_name.to_string(),
+            ok: false,
+            message: Some(e.to_string()),
+            info: None,
+        },
+    }
+}
+
+pub async fn repair_workspace_message(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+) -> ServerMessage {
+    let mut state = app_state.write().await;
+
+    match WorkspaceManager::repair(&mut state, project, workspace) {
+        Ok(repair) => {
+            let ws = repair.workspace;
+            ServerMessage::WorkspaceRepaired {
+                project: project.to_string(),
+                workspace: workspace.to_string(),
+                ok: true,
+                message: Some(if repair.actions.is_empty() {
+                    "工作空间无需修复".to_string()
+                } else {
+                    "工作空间已修复".to_string()
+                }),
+                actions: repair.actions,
+                info: Some(workspace_info(ws)),
+            }
+        }
+        Err(e) => ServerMessage::WorkspaceRepaired {
+            project: project.to_string(),
+            workspace: workspace.to_string(),
+            ok: false,
+            message: Some(e.to_string()),
+            actions: Vec::new(),
+            info: None,
+        },
+    }
+}
+
+pub async fn set_project_default_branch_message(
+    app_state: &SharedAppState,
+    project: &str,
+    default_branch: &str,
+) -> ServerMessage {
+    let mut state = app_state.write().await;
+    match ProjectManager::set_default_branch(&mut state, project, default_branch) {
+        Ok(branch) => ServerMessage::ProjectDefaultBranchSet {
+            project: project.to_string(),
+            ok: true,
+            default_branch: Some(branch),
+            message: None,
+        },
+        Err(e) => ServerMessage::ProjectDefaultBranchSet {
+            project: project.to_string(),
+            ok: false,
+            default_branch: None,
+            message: Some(e.to_string()),
+        },
+    }
+}
+
+/// 关联或取消关联 workspace 的 issue；`#123` / `123` 形式按项目 `origin` 解析为完整地址
+pub async fn link_workspace_issue_message(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+    url: Option<&str>,
+) -> ServerMessage {
+    let fail = |message: String| ServerMessage::WorkspaceIssueLinked {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        ok: false,
+        message: Some(message),
+        info: None,
+    };
+    let Some(root) = app_state
+        .read()
+        .await
+        .get_project(project)
+        .map(|p| p.root_path.clone())
+    else {
+        return fail(format!("Project not found: {}", project));
+    };
+    let url = match url.map(str::trim).filter(|u| !u.is_empty()) {
+        Some(reference) => match resolve_issue_url(root, reference).await {
+            Ok(url) => Some(url),
+            Err(e) => return fail(e),
+        },
+        None => None,
+    };
+
+    let mut state = app_state.write().await;
+    match WorkspaceManager::link_issue(&mut state, project, workspace, url) {
+        Ok(ws) => ServerMessage::WorkspaceIssueLinked {
+            project: project.to_string(),
+            workspace: workspace.to_string(),
+            ok: true,
+            message: None,
+            info: Some(workspace_info(ws)),
+        },
+        Err(e) => fail(e.to_string()),
+    }
+}
+
+/// 调整 workspace 的稀疏检出目录；`paths` 为空时恢复完整检出
+pub async fn set_workspace_sparse_paths_message(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+    paths: &[String],
+) -> ServerMessage {
+    let mut state = app_state.write().await;
+    match WorkspaceManager::set_sparse_paths(&mut state, project, workspace, paths) {
+        Ok(ws) => ServerMessage::WorkspaceSparsePathsSet {
+            project: project.to_string(),
+            workspace: workspace.to_string(),
+            ok: true,
+            message: None,
+            info: Some(workspace_info(ws)),
+        },
+        Err(e) => ServerMessage::WorkspaceSparsePathsSet {
+            project: project.to_string(),
+            workspace: workspace.to_string(),
+            ok: false,
+            message: Some(e.to_string()),
+            info: None,
+        },
+    }
+}
+
+async fn resolve_issue_url(root: PathBuf, reference: &str) -> Result<String, String> {
+    if reference.starts_with("http://") || reference.starts_with("https://") {
+        return url::Url::parse(reference)
+            .map(|_| reference.to_string())
+            .map_err(|e| format!("Invalid issue URL: {}", e));
+    }
+    let number = reference
+        .strip_prefix('#')
+        .unwrap_or(reference)
+        .parse::<u64>()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| format!("Invalid issue reference: {}", reference))?;
+    crate::util::trace::spawn_blocking(move || crate::server::git::hosted_repo(&root))
+        .await
+        .map_err(|e| format!("Resolve issue task failed: {}", e))?
+        .map(|repo| repo.issue_url(number))
+        .map_err(|e| e.to_string())
+}
+
+fn workspace_info(ws: Workspace) -> WorkspaceInfo {
+    let suggested_issue = crate::workspace::branch_name::suggested_issue_reference(
+        &ws.branch,
+        ws.linked_issue.as_deref(),
+    );
+    WorkspaceInfo {
+        name: ws.name,
+        root: ws.worktree_path.to_string_lossy().to_string(),
+        branch: ws.branch,
+        status: workspace_status_str(&ws.status),
+        sidebar_status: Default::default(),
+        last_activity_at: Some(ws.last_accessed.to_rfc3339()),
+        port: ws.port,
+        linked_issue: ws.linked_issue,
+        suggested_issue,
+        sparse_paths: ws.sparse_paths,
+    }
+}
+
 pub async fn save_project_commands_message(
     app_state: &SharedAppState,
     project: &str,
@@ -457,6 +718,7 @@ pub async fn export_template_message(
             workspace: None,
             session_id: None,
             cycle_id: None,
+            trace_id: None,
         }
     }
 }
@@ -668,4 +930,75 @@ mod tests {
             ServerMessage::TemplateDeleted { ok: false, .. }
         ));
     }
+
+    #[tokio::test]
+    async fn link_workspace_issue_resolves_references_and_clears() {
+        use crate::workspace::state::{AppState, WorkspaceStatus};
+        use std::collections::HashMap;
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+
+        let repo = tempfile::tempdir().unwrap();
+        for args in [
+            vec!["init", "-q"],
+            vec!["remote", "add", "origin", "git@github.com:acme/app.git"],
+        ] {
+            crate::util::exec_env::git_command(repo.path())
+                .args(&args)
+                .output()
+                .unwrap();
+        }
+        let now = chrono::Utc::now();
+        let workspace = Workspace {
+            name: "login".to_string(),
+            worktree_path: repo.path().join("login"),
+            branch: "feat/42-login".to_string(),
+            status: WorkspaceStatus::Ready,
+            created_at: now,
+            last_accessed: now,
+            setup_result: None,
+            recovery_meta: None,
+            port: None,
+            linked_issue: None,
+            sparse_paths: Vec::new(),
+        };
+        let mut state = AppState::default();
+        state.projects.insert(
+            "demo".to_string(),
+            Project {
+                name: "demo".to_string(),
+                root_path: repo.path().to_path_buf(),
+                remote_url: None,
+                default_branch: "main".to_string(),
+                created_at: now,
+                workspaces: HashMap::from([("login".to_string(), workspace)]),
+                commands: vec![],
+            },
+        );
+        let state = Arc::new(RwLock::new(state));
+
+        let linked = |msg: ServerMessage| match msg {
+            ServerMessage::WorkspaceIssueLinked { ok, info, .. } => {
+                assert!(ok);
+                let info = info.unwrap();
+                (info.linked_issue, info.suggested_issue)
+            }
+            other => panic!("unexpected message: {:?}", other),
+        };
+        assert_eq!(
+            linked(link_workspace_issue_message(&state, "demo", "login", Some("#42")).await),
+            (
+                Some("https://github.com/acme/app/issues/42".to_string()),
+                None
+            )
+        );
+        assert_eq!(
+            linked(link_workspace_issue_message(&state, "demo", "login", None).await),
+            (None, Some("#42".to_string()))
+        );
+        assert!(matches!(
+            link_workspace_issue_message(&state, "demo", "login", Some("PROJ-1")).await,
+            ServerMessage::WorkspaceIssueLinked { ok: false, .. }
+        ));
+    }
 }
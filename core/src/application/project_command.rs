@@ -119,6 +119,7 @@ pub async fn run_project_command(
                                 workspace: None,
                                 session_id: None,
                                 cycle_id: None,
+                                trace_id: None,
                             },
                             broadcast: None,
                         };
@@ -134,6 +135,7 @@ pub async fn run_project_command(
                         workspace: None,
                         session_id: None,
                         cycle_id: None,
+                        trace_id: None,
                     },
                     broadcast: None,
                 };
@@ -150,15 +152,16 @@ pub async fn run_project_command(
         task_id: task_id.clone(),
     };
 
-    let mut child = match tokio::process::Command::new(preferred_login_shell())
+    let mut command = std::process::Command::new(preferred_login_shell());
+    command
         .arg("-l")
         .arg("-c")
         .arg(&command_text)
         .current_dir(&cwd)
         .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
-        .spawn()
-    {
+        .stderr(std::process::Stdio::piped());
+    crate::util::trace::apply_to_command(&mut command);
+    let mut child = match tokio::process::Command::from(command).spawn() {
         Ok(child) => child,
         Err(e) => {
             let msg = ServerMessage::ProjectCommandCompleted {
@@ -168,6 +171,7 @@ pub async fn run_project_command(
                 task_id,
                 ok: false,
                 message: Some(format!("执行失败: {}", e)),
+                exit_code: None,
             };
             let _ = ctx.cmd_output_tx.send(msg.clone()).await;
             let _ = crate::server::context::send_task_broadcast_message(
@@ -232,7 +236,7 @@ pub async fn run_project_command(
     let c = command_id.to_string();
     let tid = task_id.clone();
 
-    tokio::spawn(async move {
+    crate::util::trace::spawn(async move {
         let collected = Arc::new(Mutex::new(Vec::<String>::new()));
 
         let (line_tx, mut line_rx) = tokio::sync::mpsc::channel::<String>(512);
@@ -358,6 +362,7 @@ pub async fn run_project_command(
                     task_id: tid,
                     ok: false,
                     message: Some(format!("执行失败: {}", e)),
+                    exit_code: None,
                 };
                 let _ = tx.send(msg.clone()).await;
                 let _ = crate::server::context::send_task_broadcast_message(
@@ -379,10 +384,11 @@ pub async fn run_project_command(
         let all_lines = collected.lock().await;
         let message = summarize_command_output(&all_lines);
         let ok = exit_status.success();
+        let exit_code = exit_status.code();
 
         info!(
-            "ProjectCommand completed: project={}, command_id={}, ok={}",
-            p, c, ok
+            "ProjectCommand completed: project={}, command_id={}, ok={}, exit_code={:?}",
+            p, c, ok, exit_code
         );
 
         let _ = tx
@@ -393,6 +399,7 @@ pub async fn run_project_command(
                 task_id: tid.clone(),
                 ok,
                 message: Some(message.clone()),
+                exit_code,
             })
             .await;
         let _ = crate::server::context::send_task_broadcast_message(
@@ -405,6 +412,7 @@ pub async fn run_project_command(
                 task_id: tid.clone(),
                 ok,
                 message: Some(message.clone()),
+                exit_code,
             },
         );
         let status = if ok { "completed" } else { "failed" };
@@ -469,6 +477,7 @@ pub async fn cancel_project_command(
                 workspace: None,
                 session_id: None,
                 cycle_id: None,
+                trace_id: None,
             },
             broadcast: None,
         };
@@ -492,6 +501,7 @@ pub async fn cancel_project_command(
                 workspace: None,
                 session_id: None,
                 cycle_id: None,
+                trace_id: None,
             },
             broadcast: None,
         };
@@ -566,7 +576,7 @@ async fn broadcast_tasks_snapshot_with(
         crate::server::context::send_task_broadcast_message(broadcast_tx, origin_conn_id, snapshot);
 }
 
-fn preferred_login_shell() -> &'static str {
+pub(crate) fn preferred_login_shell() -> &'static str {
     if Path::new("/bin/zsh").exists() {
         "/bin/zsh"
     } else {
@@ -0,0 +1,204 @@
+//! 项目列表的健康度与活跃度统计
+//!
+//! `list_projects` 需要为每个项目给出默认分支相对 origin 的 ahead/behind、
+//! 有未提交变更的 workspace 数与最近提交时间。这些值需要访问 git，
+//! 按项目缓存 30s；workspace 集合变化时缓存立即失效。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+use crate::server::git;
+use crate::util::exec_env::git_command;
+
+const HEALTH_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// 单个项目的 git 统计
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectHealth {
+    /// 默认分支领先 `origin/<default_branch>` 的提交数；无远程跟踪分支时为 None
+    pub ahead_origin: Option<u32>,
+    pub behind_origin: Option<u32>,
+    /// 有未提交变更的 workspace 数（含默认 workspace）
+    pub dirty_workspace_count: usize,
+    /// 所有本地分支中最近一次提交的时间
+    pub last_commit_at: Option<DateTime<Utc>>,
+}
+
+struct CacheEntry {
+    computed_at: Instant,
+    default_branch: String,
+    worktrees: Vec<PathBuf>,
+    health: ProjectHealth,
+}
+
+static PROJECT_HEALTH_CACHE: LazyLock<Mutex<HashMap<PathBuf, CacheEntry>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 读取项目统计（带缓存）；`worktrees` 为各 workspace 的工作目录
+pub fn project_health(root: &Path, default_branch: &str, worktrees: &[PathBuf]) -> ProjectHealth {
+    if let Ok(cache) = PROJECT_HEALTH_CACHE.lock() {
+        if let Some(entry) = cache.get(root) {
+            if entry.computed_at.elapsed() < HEALTH_CACHE_TTL
+                && entry.default_branch == default_branch
+                && entry.worktrees == worktrees
+            {
+                return entry.health.clone();
+            }
+        }
+    }
+
+    let health = compute_project_health(root, default_branch, worktrees);
+    if let Ok(mut cache) = PROJECT_HEALTH_CACHE.lock() {
+        cache.insert(
+            root.to_path_buf(),
+            CacheEntry {
+                computed_at: Instant::now(),
+                default_branch: default_branch.to_string(),
+                worktrees: worktrees.to_vec(),
+                health: health.clone(),
+            },
+        );
+    }
+    health
+}
+
+fn compute_project_health(
+    root: &Path,
+    default_branch: &str,
+    worktrees: &[PathBuf],
+) -> ProjectHealth {
+    let (ahead_origin, behind_origin) = match origin_divergence(root, default_branch) {
+        Some((ahead, behind)) => (Some(ahead), Some(behind)),
+        None => (None, None),
+    };
+
+    // git_status 自带指纹缓存，这里只关心是否有变更
+    let dirty_workspace_count = std::iter::once(root)
+        .chain(worktrees.iter().map(PathBuf::as_path))
+        .filter(|path| path.exists())
+        .filter(|path| {
+            git::git_status(path, default_branch)
+                .map(|status| !status.items.is_empty())
+                .unwrap_or(false)
+        })
+        .count();
+
+    ProjectHealth {
+        ahead_origin,
+        behind_origin,
+        dirty_workspace_count,
+        last_commit_at: last_commit_at(root),
+    }
+}
+
+fn origin_divergence(root: &Path, default_branch: &str) -> Option<(u32, u32)> {
+    let output = git_command(root)
+        .args(["rev-list", "--left-right", "--count"])
+        .arg(format!(
+            "refs/heads/{0}...refs/remotes/origin/{0}",
+            default_branch
+        ))
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut counts = stdout.split_whitespace().map(|n| n.parse::<u32>().ok());
+    Some((counts.next()??, counts.next()??))
+}
+
+/// 各本地分支最近一次提交的时间（分支短名 → 时间）
+pub fn branch_commit_times(root: &Path) -> HashMap<String, DateTime<Utc>> {
+    let Ok(output) = git_command(root)
+        .args([
+            "for-each-ref",
+            "--format=%(refname:short)%00%(committerdate:unix)",
+            "refs/heads",
+        ])
+        .output()
+    else {
+        return HashMap::new();
+    };
+    if !output.status.success() {
+        return HashMap::new();
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (branch, secs) = line.split_once('\0')?;
+            let at = DateTime::from_timestamp(secs.trim().parse().ok()?, 0)?;
+            Some((branch.to_string(), at))
+        })
+        .collect()
+}
+
+fn last_commit_at(root: &Path) -> Option<DateTime<Utc>> {
+    let output = git_command(root)
+        .args([
+            "for-each-ref",
+            "--sort=-committerdate",
+            "--count=1",
+            "--format=%(committerdate:iso-strict)",
+            "refs/heads",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    DateTime::parse_from_rfc3339(String::from_utf8_lossy(&output.stdout).trim())
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = git_command(dir)
+            .args(args)
+            .output()
+            .expect("run git")
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn computes_origin_divergence_dirty_count_and_last_commit() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        git(root, &["init", "-q", "-b", "main"]);
+        git(root, &["config", "user.name", "Test"]);
+        git(root, &["config", "user.email", "test@example.com"]);
+        git(root, &["config", "commit.gpgsign", "false"]);
+        git(root, &["commit", "-q", "--allow-empty", "-m", "one"]);
+        git(root, &["update-ref", "refs/remotes/origin/main", "HEAD"]);
+        git(root, &["commit", "-q", "--allow-empty", "-m", "two"]);
+
+        let health = compute_project_health(root, "main", &[]);
+        assert_eq!(
+            branch_commit_times(root).get("main").copied(),
+            health.last_commit_at
+        );
+        assert_eq!(health.ahead_origin, Some(1));
+        assert_eq!(health.behind_origin, Some(0));
+        assert_eq!(health.dirty_workspace_count, 0);
+        assert!(health.last_commit_at.is_some());
+
+        std::fs::write(root.join("new.txt"), "x").unwrap();
+        git::invalidate_git_status_cache(root);
+        let missing = root.join("missing-worktree");
+        let health = compute_project_health(root, "main", &[missing]);
+        assert_eq!(health.dirty_workspace_count, 1);
+
+        // 没有远程跟踪分支时不报告 ahead/behind
+        let health = compute_project_health(root, "develop", &[]);
+        assert_eq!((health.ahead_origin, health.behind_origin), (None, None));
+    }
+}
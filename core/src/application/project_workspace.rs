@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use crate::pty::shell::resolve_shell;
 use crate::server::context::{resolve_workspace, HandlerContext};
 use crate::server::protocol::ServerMessage;
 use crate::server::ws::subscribe_terminal;
@@ -25,6 +26,28 @@ pub async fn select_workspace_and_spawn_terminal(
         state.touch_workspace_last_accessed(project, workspace);
     }
 
+    let (default_shell, shell_integration) = {
+        let state = ctx.app_state.read().await;
+        (
+            state.client_settings.default_shell.clone(),
+            state.client_settings.terminal_shell_integration,
+        )
+    };
+    let mut shell = resolve_shell(None, None, default_shell.as_deref()).map_err(|message| {
+        ServerMessage::Error {
+            code: "invalid_shell".to_string(),
+            message,
+            project: None,
+            workspace: None,
+            session_id: None,
+            cycle_id: None,
+            trace_id: None,
+        }
+    })?;
+    if shell_integration {
+        crate::pty::shell_integration::inject(&mut shell);
+    }
+
     let (session_id, shell_name) = {
         let mut reg = ctx.terminal_registry.lock().await;
         reg.spawn(
@@ -36,6 +59,7 @@ pub async fn select_workspace_and_spawn_terminal(
             None,
             None,
             None,
+            shell,
         )
         .map_err(|e| ServerMessage::Error {
             code: "spawn_error".to_string(),
@@ -44,14 +68,23 @@ pub async fn select_workspace_and_spawn_terminal(
             workspace: None,
             session_id: None,
             cycle_id: None,
+            trace_id: None,
         })?
     };
+    crate::application::terminal::apply_terminal_keep_alive(
+        &ctx.app_state,
+        &ctx.terminal_registry,
+        &session_id,
+        None,
+    )
+    .await;
 
     subscribe_terminal(
         &session_id,
         &ctx.terminal_registry,
         &ctx.subscribed_terms,
         &ctx.agg_tx,
+        &ctx.cmd_output_tx,
     )
     .await;
 
@@ -86,6 +119,11 @@ pub async fn cleanup_workspace_before_remove(
         reg.close(tid);
     }
 
+    let procs = crate::server::proc_supervisor::stop_workspace_procs(project, workspace);
+    if !procs.is_empty() {
+        tracing::info!(project, workspace, ?procs, "Stopped supervised processes");
+    }
+
     term_ids
 }
 
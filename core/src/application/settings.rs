@@ -1,7 +1,7 @@
 use crate::server::context::SharedAppState;
 use crate::server::protocol::{
-    ai::ModelSelection, EvolutionStageProfileInfo, KeybindingConfigInfo, ServerMessage,
-    WorkspaceTodoInfo,
+    ai::ModelSelection, DeviceSettingsProfileInfo, EvolutionStageProfileInfo, KeybindingConfigInfo,
+    ServerMessage, WorkspaceTodoInfo,
 };
 use crate::workspace::state::{
     EvolutionStageProfile, KeybindingConfig, WorkspaceTodoItem,
@@ -18,6 +18,22 @@ pub struct SaveClientSettingsParams {
     pub remote_access_enabled: Option<bool>,
     pub node_name: Option<Option<String>>,
     pub node_discovery_enabled: Option<bool>,
+    /// None: 保持现值；Some(None): 清空；Some(Some): 覆盖默认 shell（调用方已校验）。
+    pub default_shell: Option<Option<String>>,
+    /// None: 保持现值；Some(None): 清空；Some(Some): 覆盖 git 全局代理（调用方已校验）。
+    pub git_proxy: Option<Option<String>>,
+    pub git_offline_mode: Option<bool>,
+    /// None: 保持现值；Some: 按主机合并代码托管平台令牌，值为 None 或空串时删除该主机。
+    pub git_hosting_tokens: Option<std::collections::HashMap<String, Option<String>>>,
+    pub terminal_keep_alive: Option<bool>,
+    pub terminal_prevent_sleep: Option<bool>,
+    pub terminal_shell_integration: Option<bool>,
+    /// None: 保持现值；Some(None): 清空；Some(Some): 覆盖推送中继地址（调用方已校验）。
+    pub push_relay_url: Option<Option<String>>,
+    /// None: 保持现值；Some: 按设备合并推送令牌，值为 None 或空串时删除该设备。
+    pub push_tokens: Option<std::collections::HashMap<String, Option<String>>>,
+    /// None: 保持现值；Some: 覆盖后台定时 fetch 间隔（秒，0 为关闭；调用方已校验）。
+    pub git_auto_fetch_interval_secs: Option<u64>,
     /// None: 保持现值；Some: 覆盖全局 Evolution 默认配置。
     pub evolution_default_profiles: Option<Vec<EvolutionStageProfileInfo>>,
     /// None: 保持现值；Some: 覆盖整个 workspace_todos。
@@ -27,11 +43,22 @@ pub struct SaveClientSettingsParams {
     /// None: 保持现值；Some: 覆盖语言级格式化配置。
     pub editor_formatting_configs:
         Option<Vec<crate::server::protocol::formatting::EditorFormattingLanguageConfig>>,
+    /// 设备标识；存在时 `workspace_shortcuts` / `keybindings` 写入该设备的覆盖。
+    pub device_id: Option<String>,
+    pub device_name: Option<String>,
+    /// 删除该设备的覆盖，设备级字段不再写入。
+    pub reset_device_overrides: bool,
 }
 
 /// 读取客户端设置并转换为协议响应消息。
-pub async fn get_client_settings_message(app_state: &SharedAppState) -> ServerMessage {
+///
+/// 携带 `device_id` 时快捷键相关字段为该设备实际生效的值（设备覆盖优先于共享值）。
+pub async fn get_client_settings_message(
+    app_state: &SharedAppState,
+    device_id: Option<&str>,
+) -> ServerMessage {
     let state = app_state.read().await;
+    let device_id = device_id.map(str::trim).filter(|id| !id.is_empty());
     let evolution_agent_profiles = state
         .client_settings
         .evolution_agent_profiles
@@ -46,7 +73,7 @@ pub async fn get_client_settings_message(app_state: &SharedAppState) -> ServerMe
         .collect();
     let keybindings = state
         .client_settings
-        .keybindings
+        .keybindings_for(device_id)
         .iter()
         .map(|kb| KeybindingConfigInfo {
             command_id: kb.command_id.clone(),
@@ -54,14 +81,52 @@ pub async fn get_client_settings_message(app_state: &SharedAppState) -> ServerMe
             context: kb.context.clone(),
         })
         .collect();
+    let device_overrides = device_id
+        .and_then(|id| state.client_settings.device_profiles.get(id))
+        .map(|profile| profile.overridden_fields())
+        .unwrap_or_default();
+    let mut device_profiles: Vec<DeviceSettingsProfileInfo> = state
+        .client_settings
+        .device_profiles
+        .iter()
+        .map(|(id, profile)| DeviceSettingsProfileInfo {
+            device_id: id.clone(),
+            name: profile.name.clone(),
+            overrides: profile.overridden_fields(),
+        })
+        .collect();
+    device_profiles.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+    let mut git_hosting_token_hosts: Vec<String> = state
+        .client_settings
+        .git_hosting_tokens
+        .keys()
+        .cloned()
+        .collect();
+    git_hosting_token_hosts.sort();
+    let mut push_token_devices: Vec<String> =
+        state.client_settings.push_tokens.keys().cloned().collect();
+    push_token_devices.sort();
 
     ServerMessage::ClientSettingsResult {
-        workspace_shortcuts: state.client_settings.workspace_shortcuts.clone(),
+        workspace_shortcuts: state
+            .client_settings
+            .workspace_shortcuts_for(device_id)
+            .clone(),
         merge_ai_agent: state.client_settings.merge_ai_agent.clone(),
         fixed_port: state.client_settings.fixed_port,
         remote_access_enabled: state.client_settings.remote_access_enabled,
         node_name: state.client_settings.node_name.clone(),
         node_discovery_enabled: state.client_settings.node_discovery_enabled,
+        default_shell: state.client_settings.default_shell.clone(),
+        git_proxy: state.client_settings.git_proxy.clone(),
+        git_offline_mode: state.client_settings.git_offline_mode,
+        git_hosting_token_hosts,
+        terminal_keep_alive: state.client_settings.terminal_keep_alive,
+        terminal_prevent_sleep: state.client_settings.terminal_prevent_sleep,
+        terminal_shell_integration: state.client_settings.terminal_shell_integration,
+        push_relay_url: state.client_settings.push_relay_url.clone(),
+        push_token_devices,
+        git_auto_fetch_interval_secs: state.client_settings.git_auto_fetch_interval_secs,
         evolution_default_profiles: to_protocol_profiles(
             &state.client_settings.evolution_default_profiles,
         ),
@@ -71,13 +136,46 @@ pub async fn get_client_settings_message(app_state: &SharedAppState) -> ServerMe
         editor_formatting_configs: to_protocol_formatting_configs(
             &state.client_settings.editor_formatting_configs,
         ),
+        device_id: device_id.map(str::to_string),
+        device_overrides,
+        device_profiles,
     }
 }
 
 /// 写入客户端设置到应用状态（不触发持久化，调用方决定何时保存）。
 pub async fn save_client_settings(app_state: &SharedAppState, params: SaveClientSettingsParams) {
     let mut state = app_state.write().await;
-    state.client_settings.workspace_shortcuts = params.workspace_shortcuts;
+    let device_id = params
+        .device_id
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let keybindings = params.keybindings.map(from_protocol_keybindings);
+    match device_id {
+        Some(device_id) if params.reset_device_overrides => {
+            state.client_settings.device_profiles.remove(&device_id);
+        }
+        Some(device_id) => {
+            let profile = state
+                .client_settings
+                .device_profiles
+                .entry(device_id)
+                .or_default();
+            if let Some(name) = params.device_name {
+                let name = name.trim().to_string();
+                profile.name = (!name.is_empty()).then_some(name);
+            }
+            profile.workspace_shortcuts = Some(params.workspace_shortcuts);
+            if keybindings.is_some() {
+                profile.keybindings = keybindings;
+            }
+        }
+        None => {
+            state.client_settings.workspace_shortcuts = params.workspace_shortcuts;
+            if let Some(keybindings) = keybindings {
+                state.client_settings.keybindings = keybindings;
+            }
+        }
+    }
     state.client_settings.merge_ai_agent = params.merge_ai_agent;
 
     if let Some(port) = params.fixed_port {
@@ -99,6 +197,78 @@ pub async fn save_client_settings(app_state: &SharedAppState, params: SaveClient
         state.client_settings.node_discovery_enabled = enabled;
         state.node_discovery.discovery_enabled = enabled;
     }
+    if let Some(default_shell) = params.default_shell {
+        state.client_settings.default_shell = default_shell
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+    }
+    if let Some(git_proxy) = params.git_proxy {
+        state.client_settings.git_proxy = git_proxy
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+    }
+    if let Some(enabled) = params.git_offline_mode {
+        state.client_settings.git_offline_mode = enabled;
+    }
+    if let Some(tokens) = params.git_hosting_tokens {
+        for (host, token) in tokens {
+            let host = host.trim().to_ascii_lowercase();
+            if host.is_empty() {
+                continue;
+            }
+            match token
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+            {
+                Some(token) => {
+                    state.client_settings.git_hosting_tokens.insert(host, token);
+                }
+                None => {
+                    state.client_settings.git_hosting_tokens.remove(&host);
+                }
+            }
+        }
+    }
+    if let Some(enabled) = params.terminal_keep_alive {
+        state.client_settings.terminal_keep_alive = enabled;
+    }
+    if let Some(enabled) = params.terminal_prevent_sleep {
+        state.client_settings.terminal_prevent_sleep = enabled;
+    }
+    if let Some(enabled) = params.terminal_shell_integration {
+        state.client_settings.terminal_shell_integration = enabled;
+    }
+    if let Some(push_relay_url) = params.push_relay_url {
+        state.client_settings.push_relay_url = push_relay_url
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+    }
+    if let Some(tokens) = params.push_tokens {
+        for (device_id, token) in tokens {
+            let device_id = device_id.trim().to_string();
+            if device_id.is_empty() {
+                continue;
+            }
+            match token
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+            {
+                Some(token) => {
+                    state.client_settings.push_tokens.insert(device_id, token);
+                }
+                None => {
+                    state.client_settings.push_tokens.remove(&device_id);
+                }
+            }
+        }
+    }
+    if let Some(secs) = params.git_auto_fetch_interval_secs {
+        state.client_settings.git_auto_fetch_interval_secs = secs;
+    }
+    crate::server::git::configure_git_network(crate::server::git::GitNetworkSettings {
+        proxy: state.client_settings.git_proxy.clone(),
+        offline_mode: state.client_settings.git_offline_mode,
+    });
     if let Some(profiles) = params.evolution_default_profiles {
         state.client_settings.evolution_default_profiles = from_protocol_profiles(profiles);
     }
@@ -108,16 +278,6 @@ pub async fn save_client_settings(app_state: &SharedAppState, params: SaveClient
             .map(|(workspace_key, items)| (workspace_key, from_protocol_todos(items)))
             .collect();
     }
-    if let Some(kbs) = params.keybindings {
-        state.client_settings.keybindings = kbs
-            .into_iter()
-            .map(|kb| KeybindingConfig {
-                command_id: kb.command_id,
-                key_combination: kb.key_combination,
-                context: kb.context,
-            })
-            .collect();
-    }
     if let Some(configs) = params.editor_formatting_configs {
         state.client_settings.editor_formatting_configs =
             from_protocol_formatting_configs(configs);
@@ -202,6 +362,17 @@ fn to_protocol_profiles(input: &[EvolutionStageProfile]) -> Vec<EvolutionStagePr
         .collect()
 }
 
+fn from_protocol_keybindings(input: Vec<KeybindingConfigInfo>) -> Vec<KeybindingConfig> {
+    input
+        .into_iter()
+        .map(|kb| KeybindingConfig {
+            command_id: kb.command_id,
+            key_combination: kb.key_combination,
+            context: kb.context,
+        })
+        .collect()
+}
+
 fn to_protocol_todos(input: &[WorkspaceTodoItem]) -> Vec<WorkspaceTodoInfo> {
     input
         .iter()
@@ -297,10 +468,23 @@ mod tests {
             remote_access_enabled: None,
             node_name: None,
             node_discovery_enabled: None,
+            default_shell: None,
+            git_proxy: None,
+            git_offline_mode: None,
+            git_hosting_tokens: None,
+            terminal_keep_alive: None,
+            terminal_prevent_sleep: None,
+            terminal_shell_integration: None,
+            push_relay_url: None,
+            push_tokens: None,
+            git_auto_fetch_interval_secs: None,
             evolution_default_profiles: None,
             workspace_todos: None,
             keybindings: None,
             editor_formatting_configs: None,
+            device_id: None,
+            device_name: None,
+            reset_device_overrides: false,
         }
     }
 
@@ -412,4 +596,128 @@ mod tests {
         assert_eq!(todos[0].status, "completed");
         assert_eq!(todos[0].note.as_deref(), Some("新备注"));
     }
+
+    #[tokio::test]
+    async fn save_client_settings_should_keep_device_overrides_separate() {
+        let app_state: SharedAppState = Arc::new(RwLock::new(AppState::default()));
+        let mut params = empty_params();
+        params
+            .workspace_shortcuts
+            .insert("1".to_string(), "demo/shared".to_string());
+        save_client_settings(&app_state, params).await;
+
+        let mut params = empty_params();
+        params.device_id = Some("ipad-1".to_string());
+        params.device_name = Some("iPad".to_string());
+        params.fixed_port = Some(47000);
+        params
+            .workspace_shortcuts
+            .insert("1".to_string(), "demo/ipad".to_string());
+        params.keybindings = Some(vec![KeybindingConfigInfo {
+            command_id: "quickOpen".to_string(),
+            key_combination: "Cmd+P".to_string(),
+            context: "global".to_string(),
+        }]);
+        save_client_settings(&app_state, params).await;
+
+        let shortcut = |msg: &ServerMessage| match msg {
+            ServerMessage::ClientSettingsResult {
+                workspace_shortcuts,
+                fixed_port,
+                ..
+            } => (workspace_shortcuts["1"].clone(), *fixed_port),
+            other => panic!("unexpected message: {:?}", other),
+        };
+        let shared = get_client_settings_message(&app_state, None).await;
+        assert_eq!(shortcut(&shared), ("demo/shared".to_string(), 47000));
+        let phone = get_client_settings_message(&app_state, Some("iphone-1")).await;
+        assert_eq!(shortcut(&phone), ("demo/shared".to_string(), 47000));
+        let ipad = get_client_settings_message(&app_state, Some("ipad-1")).await;
+        assert_eq!(shortcut(&ipad), ("demo/ipad".to_string(), 47000));
+        match ipad {
+            ServerMessage::ClientSettingsResult {
+                keybindings,
+                device_overrides,
+                device_profiles,
+                ..
+            } => {
+                assert_eq!(keybindings.len(), 1);
+                assert_eq!(device_overrides, vec!["workspace_shortcuts", "keybindings"]);
+                assert_eq!(device_profiles[0].name.as_deref(), Some("iPad"));
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        let mut params = empty_params();
+        params.device_id = Some("ipad-1".to_string());
+        params.reset_device_overrides = true;
+        params
+            .workspace_shortcuts
+            .insert("1".to_string(), "demo/ignored".to_string());
+        save_client_settings(&app_state, params).await;
+        let ipad = get_client_settings_message(&app_state, Some("ipad-1")).await;
+        assert_eq!(shortcut(&ipad), ("demo/shared".to_string(), 47000));
+        let state = app_state.read().await;
+        assert!(state.client_settings.device_profiles.is_empty());
+    }
+
+    #[tokio::test]
+    async fn save_client_settings_should_merge_hosting_tokens_by_host() {
+        let app_state: SharedAppState = Arc::new(RwLock::new(AppState::default()));
+        let mut params = empty_params();
+        params.git_hosting_tokens = Some(HashMap::from([
+            ("GitHub.com".to_string(), Some(" ghp_1 ".to_string())),
+            ("gitlab.com".to_string(), Some("glpat_1".to_string())),
+        ]));
+        save_client_settings(&app_state, params).await;
+
+        let mut params = empty_params();
+        params.git_hosting_tokens = Some(HashMap::from([("gitlab.com".to_string(), None)]));
+        save_client_settings(&app_state, params).await;
+
+        {
+            let state = app_state.read().await;
+            let tokens = &state.client_settings.git_hosting_tokens;
+            assert_eq!(tokens.len(), 1);
+            assert_eq!(tokens["github.com"], "ghp_1");
+        }
+        match get_client_settings_message(&app_state, None).await {
+            ServerMessage::ClientSettingsResult {
+                git_hosting_token_hosts,
+                ..
+            } => assert_eq!(git_hosting_token_hosts, vec!["github.com"]),
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn save_client_settings_should_merge_push_tokens_by_device() {
+        let app_state: SharedAppState = Arc::new(RwLock::new(AppState::default()));
+        let mut params = empty_params();
+        params.push_relay_url = Some(Some(" https://push.example.com/notify ".to_string()));
+        params.push_tokens = Some(HashMap::from([
+            ("iphone-1".to_string(), Some("token-1".to_string())),
+            ("ipad-1".to_string(), Some("token-2".to_string())),
+        ]));
+        save_client_settings(&app_state, params).await;
+
+        let mut params = empty_params();
+        params.push_tokens = Some(HashMap::from([("ipad-1".to_string(), None)]));
+        save_client_settings(&app_state, params).await;
+
+        match get_client_settings_message(&app_state, None).await {
+            ServerMessage::ClientSettingsResult {
+                push_relay_url,
+                push_token_devices,
+                ..
+            } => {
+                assert_eq!(
+                    push_relay_url.as_deref(),
+                    Some("https://push.example.com/notify")
+                );
+                assert_eq!(push_token_devices, vec!["iphone-1"]);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
 }
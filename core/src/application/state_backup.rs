@@ -0,0 +1,56 @@
+//! 状态快照恢复
+
+use crate::server::context::SharedAppState;
+use crate::server::protocol::ServerMessage;
+use crate::workspace::state::StateError;
+use crate::workspace::state_backups::{self, STATE_BACKUP_KEEP};
+use crate::workspace::state_store::StateStore;
+
+/// 用快照 `backup_id` 替换当前状态并立即持久化。
+///
+/// 替换前先把当前状态写成一份新快照，恢复错了可以再恢复回去；
+/// 整个过程持有状态写锁，避免 StateSaver 用旧状态覆盖刚恢复的数据。
+pub async fn restore_state_backup_message(
+    app_state: &SharedAppState,
+    state_store: &StateStore,
+    backup_id: &str,
+) -> ServerMessage {
+    let failed = |message: String| ServerMessage::StateBackupRestored {
+        backup_id: backup_id.to_string(),
+        ok: false,
+        message: Some(message),
+        previous_backup_id: None,
+        project_count: 0,
+    };
+
+    let dir = state_backups::default_backups_dir();
+    let mut restored =
+        match state_backups::read_backup(&dir, backup_id, state_store.recovery_dir()).await {
+            Ok(state) => state,
+            Err(e) => return failed(e.to_string()),
+        };
+
+    let mut state = app_state.write().await;
+    // 快照不含令牌，沿用当前配置
+    restored.client_settings.git_hosting_tokens = state.client_settings.git_hosting_tokens.clone();
+    let result: Result<String, StateError> = async {
+        let previous = state_backups::write_backup(&dir, &state, STATE_BACKUP_KEEP + 1).await?;
+        state_store.save(&restored).await?;
+        Ok(previous.id)
+    }
+    .await;
+    match result {
+        Ok(previous_backup_id) => {
+            let project_count = restored.projects.len();
+            *state = restored;
+            ServerMessage::StateBackupRestored {
+                backup_id: backup_id.to_string(),
+                ok: true,
+                message: Some("状态已恢复".to_string()),
+                previous_backup_id: Some(previous_backup_id),
+                project_count,
+            }
+        }
+        Err(e) => failed(e.to_string()),
+    }
+}
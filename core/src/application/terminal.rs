@@ -1,4 +1,4 @@
-use crate::server::context::ConnectionMeta;
+use crate::server::context::{ConnectionMeta, SharedAppState};
 use crate::server::protocol::{RemoteSubscriberDetail, ServerMessage, TerminalInfo};
 use crate::server::remote_sub_registry::SharedRemoteSubRegistry;
 use crate::server::terminal_registry::SharedTerminalRegistry;
@@ -52,10 +52,54 @@ pub async fn term_list_message(
     )
 }
 
-fn terminal_sort_key(item: &TerminalInfo) -> (String, String, String) {
+/// 设置终端保活：`requested` 为空时取客户端设置 `terminal_keep_alive`，
+/// 是否阻止休眠取 `terminal_prevent_sleep`。返回 `(keep_alive, prevent_sleep)`，终端不存在时为 None
+pub async fn apply_terminal_keep_alive(
+    app_state: &SharedAppState,
+    terminal_registry: &SharedTerminalRegistry,
+    term_id: &str,
+    requested: Option<bool>,
+) -> Option<(bool, bool)> {
+    let (default_enabled, prevent_sleep) = {
+        let state = app_state.read().await;
+        (
+            state.client_settings.terminal_keep_alive,
+            state.client_settings.terminal_prevent_sleep,
+        )
+    };
+    let enabled = requested.unwrap_or(default_enabled);
+    terminal_registry
+        .lock()
+        .await
+        .set_keep_alive(term_id, enabled, prevent_sleep)
+}
+
+/// 终端展示名称最大字符数
+pub const MAX_TERMINAL_NAME_CHARS: usize = 64;
+
+/// 规范化终端展示名称：去除首尾空白，空名称表示清除；含控制字符或超长时返回错误信息
+pub fn normalize_terminal_name(name: &str) -> Result<Option<String>, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Ok(None);
+    }
+    if name.chars().any(char::is_control) {
+        return Err("Terminal name must not contain control characters".to_string());
+    }
+    if name.chars().count() > MAX_TERMINAL_NAME_CHARS {
+        return Err(format!(
+            "Terminal name exceeds {} characters",
+            MAX_TERMINAL_NAME_CHARS
+        ));
+    }
+    Ok(Some(name.to_string()))
+}
+
+fn terminal_sort_key(item: &TerminalInfo) -> (String, String, u64, String) {
     (
         item.project.to_lowercase(),
         item.workspace.to_lowercase(),
+        item.order,
         item.term_id.clone(),
     )
 }
@@ -72,13 +116,20 @@ mod tests {
             workspace: "b".to_string(),
             cwd: "/tmp".to_string(),
             status: "running".to_string(),
+            exit_code: None,
             shell: "zsh".to_string(),
             lifecycle_phase: "active".to_string(),
             name: None,
             icon: None,
+            order: 0,
             recovery_phase: None,
             recovery_failed_reason: None,
             remote_subscribers: vec![],
+            keep_alive: false,
+            agent: None,
+            agent_awaiting_input: false,
+            idle_seconds: 0,
+            activity: "idle".to_string(),
         };
         let b = TerminalInfo {
             term_id: "1".to_string(),
@@ -86,15 +137,34 @@ mod tests {
             workspace: "a".to_string(),
             cwd: "/tmp".to_string(),
             status: "running".to_string(),
+            exit_code: None,
             shell: "zsh".to_string(),
             lifecycle_phase: "active".to_string(),
             name: None,
             icon: None,
+            order: 0,
             recovery_phase: None,
             recovery_failed_reason: None,
             remote_subscribers: vec![],
+            keep_alive: false,
+            agent: None,
+            agent_awaiting_input: false,
+            idle_seconds: 0,
+            activity: "idle".to_string(),
         };
 
         assert!(terminal_sort_key(&b) < terminal_sort_key(&a));
     }
+
+    #[test]
+    fn normalize_terminal_name_trims_clears_and_rejects() {
+        assert_eq!(
+            normalize_terminal_name("  build  "),
+            Ok(Some("build".to_string()))
+        );
+        assert_eq!(normalize_terminal_name("   "), Ok(None));
+        assert!(normalize_terminal_name("a\nb").is_err());
+        assert!(normalize_terminal_name(&"终".repeat(MAX_TERMINAL_NAME_CHARS)).is_ok());
+        assert!(normalize_terminal_name(&"x".repeat(MAX_TERMINAL_NAME_CHARS + 1)).is_err());
+    }
 }
@@ -0,0 +1,242 @@
+//! 启动时的工作区预热
+//!
+//! 项目 `.tidyflow.toml` 的 `[warmup]` 段列出需要预热的工作区。Core 启动后在后台逐个项目执行：
+//! 预先创建 integration worktree、构建文件索引与 git 状态（顺带预热磁盘缓存与 git index），
+//! 并按需启动项目命令（如 dev server），客户端连接时环境已就绪。
+//!
+//! 预热是尽力而为：任一步失败只记录日志，不影响其他步骤与服务启动。
+
+use std::path::{Path, PathBuf};
+
+use tracing::{info, warn};
+
+use crate::application::project_command::run_project_command;
+use crate::server::context::HandlerContext;
+use crate::server::file_api::provider::is_remote_root;
+use crate::server::git::IntegrationTarget;
+use crate::server::protocol::ServerMessage;
+use crate::workspace::config::{ProjectConfig, WarmupSection};
+use crate::workspace::state::{ProjectCommand, WorkspaceStatus, DEFAULT_WORKSPACE_NAME};
+use crate::workspace::state_hydrator::ensure_project_hydrated;
+
+/// 单个项目预热所需的状态快照
+struct WarmupTarget {
+    root: PathBuf,
+    default_branch: String,
+    /// 可预热的工作区（default 在前，其余按名称排序）及其根目录
+    workspaces: Vec<(String, PathBuf)>,
+    commands: Vec<ProjectCommand>,
+}
+
+/// 在后台按名称顺序预热所有配置了 `[warmup]` 的项目
+pub fn spawn_startup_warmers(ctx: HandlerContext) {
+    tokio::spawn(async move {
+        let mut projects: Vec<String> = ctx
+            .app_state
+            .read()
+            .await
+            .projects
+            .keys()
+            .cloned()
+            .collect();
+        projects.sort();
+        for project in projects {
+            warm_project(&ctx, &project).await;
+        }
+    });
+}
+
+/// 预热单个项目；未配置 `[warmup]` 或为远程项目时直接返回
+pub async fn warm_project(ctx: &HandlerContext, project: &str) {
+    ensure_project_hydrated(&ctx.app_state, &ctx.state_store, project).await;
+    let Some(target) = warmup_target(ctx, project).await else {
+        return;
+    };
+    if is_remote_root(&target.root) {
+        return;
+    }
+
+    let root = target.root.clone();
+    let config = crate::util::trace::spawn_blocking(move || ProjectConfig::load(&root))
+        .await
+        .ok()
+        .and_then(|result| {
+            result
+                .map_err(|e| {
+                    warn!(project, error = %e, "Failed to load project config for warmup");
+                })
+                .ok()
+        });
+    let Some(warmup) = config.map(|c| c.warmup).filter(WarmupSection::is_enabled) else {
+        return;
+    };
+
+    let available: Vec<String> = target
+        .workspaces
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect();
+    let (selected, missing) = warmup.resolve_workspaces(&available);
+    if !missing.is_empty() {
+        warn!(
+            project,
+            ?missing,
+            "Warmup workspaces not found or not ready"
+        );
+    }
+    info!(project, workspaces = ?selected, "Warming up project");
+
+    if warmup.integration_worktree {
+        warm_integration_worktree(project, &target).await;
+    }
+    for (workspace, root) in target
+        .workspaces
+        .iter()
+        .filter(|(name, _)| selected.contains(name))
+    {
+        if warmup.indexes {
+            warm_indexes(project, workspace, root, &target.default_branch).await;
+        }
+        for task in &warmup.tasks {
+            start_warmup_task(ctx, project, workspace, task, &target.commands).await;
+        }
+    }
+}
+
+async fn warmup_target(ctx: &HandlerContext, project: &str) -> Option<WarmupTarget> {
+    let state = ctx.app_state.read().await;
+    let p = state.get_project(project)?;
+    let mut named: Vec<(String, PathBuf)> = p
+        .workspaces
+        .values()
+        .filter(|w| matches!(w.status, WorkspaceStatus::Ready))
+        .map(|w| (w.name.clone(), w.worktree_path.clone()))
+        .collect();
+    named.sort();
+    let mut workspaces = vec![(DEFAULT_WORKSPACE_NAME.to_string(), p.root_path.clone())];
+    workspaces.extend(named);
+    Some(WarmupTarget {
+        root: p.root_path.clone(),
+        default_branch: p.default_branch.clone(),
+        workspaces,
+        commands: p.commands.clone(),
+    })
+}
+
+async fn warm_integration_worktree(project: &str, target: &WarmupTarget) {
+    let root = target.root.clone();
+    let integration = IntegrationTarget::default_for(project, &target.default_branch);
+    let result = crate::util::trace::spawn_blocking(move || {
+        crate::server::git::ensure_integration_worktree(&root, &integration)
+    })
+    .await;
+    match result {
+        Ok(Ok(path)) => info!(project, path, "Integration worktree warmed up"),
+        Ok(Err(e)) => warn!(project, error = %e, "Failed to warm up integration worktree"),
+        Err(e) => warn!(project, error = %e, "Integration worktree warmup task failed"),
+    }
+}
+
+async fn warm_indexes(project: &str, workspace: &str, root: &Path, default_branch: &str) {
+    let started = std::time::Instant::now();
+    let index = crate::application::file::file_index_message(root, project, workspace, None).await;
+    if let ServerMessage::Error { message, .. } = index {
+        warn!(project, workspace, message, "Failed to warm up file index");
+    }
+
+    let (root, default_branch) = (root.to_path_buf(), default_branch.to_string());
+    let status = crate::util::trace::spawn_blocking(move || {
+        crate::server::git::git_status(&root, &default_branch)
+    })
+    .await;
+    if let Ok(Err(e)) = status {
+        warn!(project, workspace, error = %e, "Failed to warm up git status");
+    }
+    info!(
+        project,
+        workspace,
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "Workspace indexes warmed up"
+    );
+}
+
+/// 按 id 或名称查找可在后台启动的项目命令；交互式命令需要终端，不在此列
+pub(crate) fn find_background_command<'a>(
+    commands: &'a [ProjectCommand],
+    task: &str,
+) -> Option<&'a ProjectCommand> {
+    commands
+        .iter()
+        .find(|c| c.id == task)
+        .or_else(|| commands.iter().find(|c| c.name == task))
+        .filter(|c| !c.interactive)
+}
+
+async fn start_warmup_task(
+    ctx: &HandlerContext,
+    project: &str,
+    workspace: &str,
+    task: &str,
+    commands: &[ProjectCommand],
+) {
+    let Some(command) = find_background_command(commands, task) else {
+        warn!(
+            project,
+            workspace, task, "Warmup task not found or interactive, skipping"
+        );
+        return;
+    };
+    let reply = run_project_command(ctx, project, workspace, &command.id).await;
+    match reply.response {
+        ServerMessage::ProjectCommandStarted { task_id, .. } => {
+            info!(project, workspace, task, task_id, "Warmup task started");
+            // 已连接的客户端（通常没有）同步看到任务启动
+            if let Some(broadcast) = reply.broadcast {
+                let _ = crate::server::context::send_task_broadcast_message(
+                    &ctx.task_broadcast_tx,
+                    &ctx.conn_meta.conn_id,
+                    broadcast,
+                );
+            }
+        }
+        other => warn!(
+            project,
+            workspace,
+            task,
+            response = ?other,
+            "Failed to start warmup task"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(id: &str, name: &str, interactive: bool) -> ProjectCommand {
+        ProjectCommand {
+            id: id.to_string(),
+            name: name.to_string(),
+            icon: String::new(),
+            command: "true".to_string(),
+            blocking: false,
+            interactive,
+        }
+    }
+
+    #[test]
+    fn background_command_matches_id_before_name_and_skips_interactive() {
+        let commands = vec![
+            command("a1", "dev", false),
+            command("dev", "Dev server", false),
+            command("shell", "repl", true),
+        ];
+        assert_eq!(find_background_command(&commands, "dev").unwrap().id, "dev");
+        assert_eq!(
+            find_background_command(&commands, "Dev server").unwrap().id,
+            "dev"
+        );
+        assert!(find_background_command(&commands, "repl").is_none());
+        assert!(find_background_command(&commands, "missing").is_none());
+    }
+}
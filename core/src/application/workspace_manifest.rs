@@ -0,0 +1,303 @@
+//! 声明式工作区清单的应用
+//!
+//! 按清单把项目实际状态向期望状态收敛：缺失的工作区按声明创建，已存在的保持不动，
+//! 清单外的工作区只在结果中标记、从不删除。每个声明工作区的任务以托管进程启动
+//! （已在运行的视为满足），因此重复应用同一清单是幂等的。
+
+use std::path::{Path, PathBuf};
+
+use tracing::{info, warn};
+
+use crate::application::proc::spawn_supervised;
+use crate::application::warmup::find_background_command;
+use crate::server::context::HandlerContext;
+use crate::server::file_api::provider::is_remote_root;
+use crate::server::proc_supervisor::ProcError;
+use crate::server::protocol::{
+    ServerMessage, WorkspaceManifestResult, WorkspaceManifestTaskResult,
+};
+use crate::workspace::manifest::{ManifestAction, ManifestWorkspace, WorkspaceManifest};
+use crate::workspace::state::ProjectCommand;
+use crate::workspace::workspace::WorkspaceManager;
+
+/// 应用清单的结果：响应消息，以及需要同步给其他连接的 `proc_started`
+pub struct ManifestApplyOutcome {
+    pub response: ServerMessage,
+    /// 是否创建了新工作区（需要保存状态并广播工作区列表）
+    pub workspaces_changed: bool,
+    pub proc_broadcasts: Vec<ServerMessage>,
+}
+
+fn manifest_error(project: &str, code: &str, message: String) -> ManifestApplyOutcome {
+    ManifestApplyOutcome {
+        response: ServerMessage::Error {
+            code: code.to_string(),
+            message,
+            project: Some(project.to_string()),
+            workspace: None,
+            session_id: None,
+            cycle_id: None,
+            trace_id: None,
+        },
+        workspaces_changed: false,
+        proc_broadcasts: Vec::new(),
+    }
+}
+
+pub async fn apply_workspace_manifest(
+    ctx: &HandlerContext,
+    project: &str,
+    manifest_text: &str,
+) -> ManifestApplyOutcome {
+    let manifest = match WorkspaceManifest::parse(manifest_text) {
+        Ok(manifest) => manifest,
+        Err(e) => return manifest_error(project, "invalid_manifest", e.to_string()),
+    };
+
+    let mut results = Vec::with_capacity(manifest.workspaces.len());
+    let mut workspaces_changed = false;
+    let extra_workspaces;
+    {
+        // 与 create_workspace 一致：创建 worktree 期间持有写锁
+        let mut state = ctx.app_state.write().await;
+        let Some(p) = state.get_project(project) else {
+            return manifest_error(
+                project,
+                "project_not_found",
+                format!("Project '{}' not found", project),
+            );
+        };
+        let mut existing: Vec<(String, String)> = p
+            .workspaces
+            .values()
+            .map(|w| (w.name.clone(), w.branch.clone()))
+            .collect();
+        existing.sort();
+        extra_workspaces = manifest.extras(&existing);
+
+        for action in manifest.plan(&existing) {
+            let result = match action {
+                ManifestAction::Keep { workspace, branch } => WorkspaceManifestResult {
+                    name: workspace.name.clone(),
+                    status: "existing".to_string(),
+                    declared_branch: workspace.branch_drift(&branch).map(str::to_string),
+                    branch: Some(branch),
+                    message: None,
+                    tasks: Vec::new(),
+                },
+                ManifestAction::Create(workspace) => {
+                    match WorkspaceManager::create_named(
+                        &mut state,
+                        project,
+                        &workspace.name,
+                        workspace.branch.as_deref(),
+                        workspace.base.as_deref(),
+                        false,
+                    ) {
+                        Ok(ws) => {
+                            workspaces_changed = true;
+                            info!(
+                                project,
+                                workspace = ws.name,
+                                branch = ws.branch,
+                                "Manifest workspace created"
+                            );
+                            WorkspaceManifestResult {
+                                name: ws.name,
+                                status: "created".to_string(),
+                                branch: Some(ws.branch),
+                                declared_branch: None,
+                                message: None,
+                                tasks: Vec::new(),
+                            }
+                        }
+                        Err(e) => {
+                            warn!(
+                                project,
+                                workspace = workspace.name,
+                                error = %e,
+                                "Failed to create manifest workspace"
+                            );
+                            WorkspaceManifestResult {
+                                name: workspace.name.clone(),
+                                status: "failed".to_string(),
+                                branch: None,
+                                declared_branch: None,
+                                message: Some(e.to_string()),
+                                tasks: Vec::new(),
+                            }
+                        }
+                    }
+                }
+            };
+            results.push(result);
+        }
+    }
+
+    let (roots, commands) = {
+        let state = ctx.app_state.read().await;
+        match state.get_project(project) {
+            Some(p) => (
+                manifest
+                    .workspaces
+                    .iter()
+                    .map(|ws| p.get_workspace(&ws.name).map(|w| w.worktree_path.clone()))
+                    .collect::<Vec<_>>(),
+                p.commands.clone(),
+            ),
+            None => (vec![None; manifest.workspaces.len()], Vec::new()),
+        }
+    };
+
+    let mut proc_broadcasts = Vec::new();
+    for ((declared, result), root) in manifest.workspaces.iter().zip(&mut results).zip(roots) {
+        let Some(root) = root.filter(|_| result.status != "failed") else {
+            continue;
+        };
+        for task in &declared.tasks {
+            let (task_result, started) =
+                start_manifest_task(ctx, project, declared, task, &root, &commands).await;
+            result.tasks.push(task_result);
+            proc_broadcasts.extend(started);
+        }
+    }
+
+    ManifestApplyOutcome {
+        response: ServerMessage::WorkspaceManifestApplied {
+            project: project.to_string(),
+            results,
+            extra_workspaces,
+        },
+        workspaces_changed,
+        proc_broadcasts,
+    }
+}
+
+/// 任务解析顺序：项目命令（id / 名称，排除交互式）→ 工作区识别出的任务 id
+async fn resolve_task_command(
+    task: &str,
+    root: &Path,
+    commands: &[ProjectCommand],
+) -> Option<(String, String)> {
+    if let Some(command) = find_background_command(commands, task) {
+        return Some((command.id.clone(), command.command.clone()));
+    }
+    let root: PathBuf = root.to_path_buf();
+    let task_id = task.to_string();
+    crate::util::trace::spawn_blocking(move || {
+        crate::server::task_runner::detect_tasks(&root)
+            .into_iter()
+            .find(|t| t.id() == task_id)
+            .map(|t| (t.id(), t.command))
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+/// 任务 id 转为合法的托管进程名（`npm:dev` → `npm-dev`）
+fn proc_name_for_task(task_id: &str) -> String {
+    task_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .take(64)
+        .collect()
+}
+
+async fn start_manifest_task(
+    ctx: &HandlerContext,
+    project: &str,
+    declared: &ManifestWorkspace,
+    task: &str,
+    root: &Path,
+    commands: &[ProjectCommand],
+) -> (WorkspaceManifestTaskResult, Option<ServerMessage>) {
+    let failed = |proc_name: Option<String>, message: String| WorkspaceManifestTaskResult {
+        task: task.to_string(),
+        status: "failed".to_string(),
+        proc_name,
+        message: Some(message),
+    };
+    if is_remote_root(root) {
+        return (
+            failed(
+                None,
+                "Supervised processes are not supported for remote projects".to_string(),
+            ),
+            None,
+        );
+    }
+    let Some((task_id, command)) = resolve_task_command(task, root, commands).await else {
+        return (
+            failed(None, format!("Task '{}' not found or interactive", task)),
+            None,
+        );
+    };
+
+    let proc_name = proc_name_for_task(&task_id);
+    let env: Vec<(String, String)> = declared
+        .env
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    match spawn_supervised(
+        &ctx.task_broadcast_tx,
+        project,
+        &declared.name,
+        &proc_name,
+        &command,
+        root,
+        &env,
+    ) {
+        Ok(info) => (
+            WorkspaceManifestTaskResult {
+                task: task.to_string(),
+                status: "started".to_string(),
+                proc_name: Some(proc_name),
+                message: None,
+            },
+            Some(ServerMessage::ProcStarted {
+                project: project.to_string(),
+                workspace: declared.name.clone(),
+                info,
+            }),
+        ),
+        Err(ProcError::AlreadyRunning(_)) => (
+            WorkspaceManifestTaskResult {
+                task: task.to_string(),
+                status: "running".to_string(),
+                proc_name: Some(proc_name),
+                message: None,
+            },
+            None,
+        ),
+        Err(e) => (failed(Some(proc_name), e.to_string()), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn task_ids_map_to_valid_proc_names() {
+        assert_eq!(proc_name_for_task("npm:dev"), "npm-dev");
+        assert_eq!(
+            proc_name_for_task("cargo:example:demo"),
+            "cargo-example-demo"
+        );
+        assert_eq!(proc_name_for_task("dev-server"), "dev-server");
+        assert!(
+            crate::server::proc_supervisor::validate_proc_name(&proc_name_for_task(
+                &"x".repeat(100)
+            ))
+            .is_ok()
+        );
+    }
+}
@@ -1,7 +1,10 @@
 use chrono::Utc;
 use clap::{Parser, Subcommand};
 use std::env;
+use std::net::SocketAddr;
 use std::path::PathBuf;
+use tidyflow_core::server::TlsConfig;
+use tidyflow_core::workspace::state_backend::{export_state_file, import_state_file};
 use tidyflow_core::workspace::{AppState, ProjectManager, StateStore, WorkspaceManager};
 use tracing::info;
 
@@ -31,6 +34,15 @@ enum Commands {
         /// Bind address for WebSocket/HTTP server (default: 127.0.0.1)
         #[arg(long)]
         bind_addr: Option<String>,
+        /// Listen address as ADDR:PORT (e.g. 0.0.0.0:8439), shorthand for --bind-addr + --port
+        #[arg(long, conflicts_with_all = ["port", "bind_addr"])]
+        listen: Option<SocketAddr>,
+        /// PEM certificate chain; serves HTTPS/WSS when set together with --tls-key
+        #[arg(long, requires = "tls_key")]
+        tls_cert: Option<PathBuf>,
+        /// PEM private key for --tls-cert
+        #[arg(long, requires = "tls_cert")]
+        tls_key: Option<PathBuf>,
     },
     /// Import a project
     Import {
@@ -46,6 +58,9 @@ enum Commands {
         /// Branch to clone (for git import)
         #[arg(long)]
         branch: Option<String>,
+        /// Shallow clone depth (for git import)
+        #[arg(long, requires = "git")]
+        depth: Option<u32>,
     },
     /// Workspace operations
     Ws {
@@ -57,6 +72,11 @@ enum Commands {
         #[command(subcommand)]
         what: ListCommands,
     },
+    /// Import or export the persisted app state
+    State {
+        #[command(subcommand)]
+        action: StateCommands,
+    },
 }
 
 #[derive(Subcommand)]
@@ -91,6 +111,15 @@ enum WsCommands {
         #[arg(long)]
         workspace: String,
     },
+    /// Repair a workspace whose worktree directory or metadata is broken
+    Repair {
+        /// Project name
+        #[arg(long)]
+        project: String,
+        /// Workspace name
+        #[arg(long)]
+        workspace: String,
+    },
     /// Remove a workspace
     Remove {
         /// Project name
@@ -114,6 +143,20 @@ enum ListCommands {
     },
 }
 
+#[derive(Subcommand)]
+enum StateCommands {
+    /// Replace the current state with a JSON state file (state.json, an export, or tidyflow.json)
+    Import {
+        /// JSON file to import
+        file: PathBuf,
+    },
+    /// Write the current state to a JSON file
+    Export {
+        /// Output file
+        file: PathBuf,
+    },
+}
+
 fn parse_env_port() -> Option<u16> {
     env::var("TIDYFLOW_PORT")
         .ok()
@@ -126,12 +169,40 @@ fn sanitize_bind_addr(value: Option<String>) -> Option<String> {
         .filter(|addr| !addr.is_empty())
 }
 
+/// TLS 证书：命令行优先，其次 `TIDYFLOW_TLS_CERT` / `TIDYFLOW_TLS_KEY`；两者须同时提供
+fn resolve_tls_config(
+    cli_cert: Option<PathBuf>,
+    cli_key: Option<PathBuf>,
+) -> Result<Option<TlsConfig>, String> {
+    let env_path = |name: &str| {
+        env::var(name)
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+    let cert = cli_cert.or_else(|| env_path("TIDYFLOW_TLS_CERT"));
+    let key = cli_key.or_else(|| env_path("TIDYFLOW_TLS_KEY"));
+    match (cert, key) {
+        (Some(cert_path), Some(key_path)) => Ok(Some(TlsConfig {
+            cert_path,
+            key_path,
+        })),
+        (None, None) => Ok(None),
+        _ => Err(
+            "TLS requires both a certificate and a private key (--tls-cert / --tls-key)"
+                .to_string(),
+        ),
+    }
+}
+
 async fn resolve_server_port_and_bind(
     cli_port: Option<u16>,
     cli_bind_addr: Option<String>,
     state_store: &StateStore,
 ) -> (u16, String) {
-    let state = state_store.load().await.unwrap_or_default();
+    // 只需客户端设置，跳过 workspace 行
+    let state = state_store.load_project_stubs().await.unwrap_or_default();
     let fixed_port = state.client_settings.fixed_port;
     let remote_access_enabled = state.client_settings.remote_access_enabled;
     let env_bind_addr = sanitize_bind_addr(env::var("TIDYFLOW_BIND_ADDR").ok());
@@ -187,21 +258,44 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     match cli.command {
         None | Some(Commands::Serve { .. }) => {
             let state_store = StateStore::open_default().await?;
-            let (port, bind_addr) = match &cli.command {
-                Some(Commands::Serve { port, bind_addr }) => {
-                    resolve_server_port_and_bind(*port, bind_addr.clone(), &state_store).await
-                }
-                _ => resolve_server_port_and_bind(None, None, &state_store).await,
+            let (cli_port, cli_bind_addr, tls_cert, tls_key) = match cli.command {
+                // clap 保证 --listen 与 --port / --bind-addr 互斥
+                Some(Commands::Serve {
+                    listen: Some(listen),
+                    tls_cert,
+                    tls_key,
+                    ..
+                }) => (
+                    Some(listen.port()),
+                    Some(listen.ip().to_string()),
+                    tls_cert,
+                    tls_key,
+                ),
+                Some(Commands::Serve {
+                    port,
+                    bind_addr,
+                    tls_cert,
+                    tls_key,
+                    ..
+                }) => (port, bind_addr, tls_cert, tls_key),
+                _ => (None, None, None, None),
             };
+            let tls = resolve_tls_config(tls_cert, tls_key)?;
+            let (port, bind_addr) =
+                resolve_server_port_and_bind(cli_port, cli_bind_addr, &state_store).await;
             env::set_var("TIDYFLOW_BIND_ADDR", bind_addr);
-            info!("Starting TidyFlow Core server on port {}", port);
-            tidyflow_core::server::run_server(port).await?;
+            info!(
+                tls = tls.is_some(),
+                "Starting TidyFlow Core server on port {}", port
+            );
+            tidyflow_core::server::run_server_with_tls(port, tls).await?;
         }
         Some(Commands::Import {
             name,
             path,
             git,
             branch,
+            depth,
         }) => {
             let state_store = StateStore::open_default().await?;
             let mut state = state_store.load().await?;
@@ -213,8 +307,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("  Path: {}", project.root_path.display());
                 println!("  Branch: {}", project.default_branch);
             } else if let Some(url) = git {
-                let project =
-                    ProjectManager::import_git(&mut state, &name, &url, branch.as_deref(), None)?;
+                let project = ProjectManager::import_git(
+                    &mut state,
+                    &name,
+                    &url,
+                    branch.as_deref(),
+                    depth,
+                    None,
+                )?;
                 persist_state(&state_store, &mut state).await?;
                 println!("Project cloned and imported: {}", project.name);
                 println!("  Path: {}", project.root_path.display());
@@ -236,6 +336,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     &mut state,
                     &project,
                     from_branch.as_deref(),
+                    None,
                     !no_setup,
                 )?;
                 persist_state(&state_store, &mut state).await?;
@@ -259,6 +360,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 eprintln!("Workspace: {}", workspace);
                 eprintln!("Branch: {}", ws.branch);
                 eprintln!("Status: {:?}", ws.status);
+                if let Some(port) = ws.port {
+                    eprintln!("Port: {}", port);
+                }
                 if let Some(ref result) = ws.setup_result {
                     eprintln!(
                         "Setup: {}/{} steps completed",
@@ -280,6 +384,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                 }
             }
+            WsCommands::Repair { project, workspace } => {
+                let state_store = StateStore::open_default().await?;
+                let mut state = state_store.load().await?;
+                let repair = WorkspaceManager::repair(&mut state, &project, &workspace)?;
+                persist_state(&state_store, &mut state).await?;
+                if repair.actions.is_empty() {
+                    println!("Workspace is healthy: {}", workspace);
+                } else {
+                    println!("Workspace repaired: {}", workspace);
+                    for action in &repair.actions {
+                        println!("  - {}", action);
+                    }
+                }
+            }
             WsCommands::Remove { project, workspace } => {
                 let state_store = StateStore::open_default().await?;
                 let mut state = state_store.load().await?;
@@ -327,6 +445,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
         },
+        Some(Commands::State { action }) => {
+            let state_store = StateStore::open_default().await?;
+            match action {
+                StateCommands::Import { file } => {
+                    let recovery_dir = state_store.recovery_dir().to_path_buf();
+                    let state = import_state_file(&state_store, &file, &recovery_dir).await?;
+                    println!(
+                        "Imported {} project(s) into {} state",
+                        state.projects.len(),
+                        state_store.backend_kind().as_str()
+                    );
+                }
+                StateCommands::Export { file } => {
+                    let state = export_state_file(&state_store, &file).await?;
+                    println!(
+                        "Exported {} project(s) to {}",
+                        state.projects.len(),
+                        file.display()
+                    );
+                }
+            }
+        }
     }
 
     Ok(())
@@ -31,7 +31,6 @@ use sqlx::{
 };
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use std::str::FromStr;
 use std::sync::{
     atomic::{AtomicU64, Ordering},
@@ -40,6 +39,7 @@ use std::sync::{
 use std::time::Instant;
 use std::{fs, io};
 
+use crate::util::exec_env::git_command;
 use crate::workspace::cache_metrics::{self, WorkspaceCacheSnapshot};
 
 // ============================================================================
@@ -207,8 +207,7 @@ impl GitRepoFixture {
 }
 
 fn run_git(dir: &Path, args: &[&str]) {
-    let output = Command::new("git")
-        .current_dir(dir)
+    let output = git_command(dir)
         .args(args)
         .output()
         .unwrap_or_else(|_| panic!("git {:?} failed in {:?}", args, dir));
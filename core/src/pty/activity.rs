@@ -0,0 +1,115 @@
+//! 终端活跃状态判定
+//!
+//! 多个工作区并行时，用户需要快速分辨哪个终端在等待处理。状态由最近输出时间、
+//! 前台是否有命令在运行以及末行是否像输入提示推断：
+//! - `busy`：近期有输出，或前台命令仍在运行；
+//! - `awaiting_input`：前台命令静默且末行像输入提示（`[y/N]`、`Password:` 等），或 Agent 在等待输入；
+//! - `idle`：停在 shell 提示符或进程已退出。
+
+use std::time::Duration;
+
+/// 该时长内有输出视为忙碌
+pub const BUSY_OUTPUT_WINDOW: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalActivity {
+    Busy,
+    Idle,
+    AwaitingInput,
+}
+
+impl TerminalActivity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Busy => "busy",
+            Self::Idle => "idle",
+            Self::AwaitingInput => "awaiting_input",
+        }
+    }
+}
+
+/// 判定活跃状态所需的终端快照
+#[derive(Debug, Clone, Copy)]
+pub struct ActivitySnapshot<'a> {
+    /// 距最近一次输出的时长
+    pub since_output: Duration,
+    /// 前台是否有命令在运行（shell 集成或前台进程组判断）
+    pub command_running: bool,
+    /// Agent 是否在等待输入
+    pub agent_awaiting_input: bool,
+    /// 输出末尾的可见行
+    pub last_line: &'a str,
+}
+
+pub fn classify(snapshot: &ActivitySnapshot<'_>) -> TerminalActivity {
+    if snapshot.since_output < BUSY_OUTPUT_WINDOW {
+        return TerminalActivity::Busy;
+    }
+    if snapshot.agent_awaiting_input {
+        return TerminalActivity::AwaitingInput;
+    }
+    if !snapshot.command_running {
+        return TerminalActivity::Idle;
+    }
+    if looks_like_input_prompt(snapshot.last_line) {
+        TerminalActivity::AwaitingInput
+    } else {
+        TerminalActivity::Busy
+    }
+}
+
+/// 末行是否像交互式输入提示（确认、密码、问题或 REPL 提示符）
+pub fn looks_like_input_prompt(line: &str) -> bool {
+    const MARKERS: &[&str] = &["[y/n]", "(y/n)", "yes/no", "password", "passphrase"];
+    let line = line.trim_end().to_lowercase();
+    if line.is_empty() {
+        return false;
+    }
+    MARKERS.iter().any(|m| line.contains(m)) || line.ends_with(['?', ':', '>'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(
+        since_output_secs: u64,
+        command_running: bool,
+        last_line: &str,
+    ) -> ActivitySnapshot<'_> {
+        ActivitySnapshot {
+            since_output: Duration::from_secs(since_output_secs),
+            command_running,
+            agent_awaiting_input: false,
+            last_line,
+        }
+    }
+
+    #[test]
+    fn classifies_recent_output_prompts_and_silent_commands() {
+        assert_eq!(classify(&snapshot(0, false, "$")), TerminalActivity::Busy);
+        assert_eq!(classify(&snapshot(30, false, "$")), TerminalActivity::Idle);
+        assert_eq!(
+            classify(&snapshot(30, true, "Compiling tidyflow-core")),
+            TerminalActivity::Busy
+        );
+        assert_eq!(
+            classify(&snapshot(30, true, "Overwrite file? [y/N]")),
+            TerminalActivity::AwaitingInput
+        );
+        let agent = ActivitySnapshot {
+            agent_awaiting_input: true,
+            ..snapshot(30, true, "")
+        };
+        assert_eq!(classify(&agent), TerminalActivity::AwaitingInput);
+    }
+
+    #[test]
+    fn detects_input_prompts() {
+        assert!(looks_like_input_prompt("Password: "));
+        assert!(looks_like_input_prompt("Continue (y/n) [n]"));
+        assert!(looks_like_input_prompt(">>>"));
+        assert!(!looks_like_input_prompt("Building [=====>    ] 50%"));
+        assert!(!looks_like_input_prompt(""));
+    }
+}
@@ -0,0 +1,148 @@
+//! 终端内编码 Agent 检测
+//!
+//! 读取终端前台进程组 leader 的命令行，识别已知的编码 Agent CLI（claude、aider 等）。
+//! 通过 npm/pip 安装的 Agent 常以解释器启动（`node .../claude`、`python -m aider`），
+//! 因此遇到解释器或包装命令时继续检查其后的参数。
+
+/// 已知的编码 Agent 可执行文件名
+pub const KNOWN_AGENTS: &[&str] = &[
+    "claude",
+    "aider",
+    "codex",
+    "gemini",
+    "opencode",
+    "cursor-agent",
+];
+
+/// 包目录名到 Agent 名称的映射（入口脚本名不含 Agent 名时使用，如 `claude-code/cli.js`）
+const PACKAGE_ALIASES: &[(&str, &str)] = &[
+    ("claude-code", "claude"),
+    ("aider-chat", "aider"),
+    ("gemini-cli", "gemini"),
+];
+
+/// 启动 Agent 时常见的解释器与包装命令
+const LAUNCHERS: &[&str] = &[
+    "node", "bun", "bunx", "deno", "npx", "pnpx", "uv", "uvx", "pipx",
+];
+
+/// 最多检查的命令行参数个数
+const MAX_INSPECTED_ARGS: usize = 4;
+
+/// 检测进程是否为已知 Agent，返回 Agent 名称
+pub fn detect_agent(pid: i32) -> Option<&'static str> {
+    match_agent(&process_args(pid)?)
+}
+
+/// 根据命令行参数识别 Agent
+pub fn match_agent(args: &[String]) -> Option<&'static str> {
+    for arg in args.iter().take(MAX_INSPECTED_ARGS) {
+        if arg.starts_with('-') {
+            continue;
+        }
+        if let Some(agent) = agent_for_path(arg) {
+            return Some(agent);
+        }
+        if !is_launcher(base_name(arg)) {
+            return None;
+        }
+    }
+    None
+}
+
+fn agent_for_path(path: &str) -> Option<&'static str> {
+    let name = base_name(path);
+    let name = name.strip_suffix(".js").unwrap_or(name);
+    if let Some(agent) = KNOWN_AGENTS.iter().find(|a| **a == name) {
+        return Some(agent);
+    }
+    path.split('/').find_map(|component| {
+        PACKAGE_ALIASES
+            .iter()
+            .find(|(package, _)| *package == component)
+            .map(|(_, agent)| *agent)
+    })
+}
+
+fn is_launcher(name: &str) -> bool {
+    LAUNCHERS.contains(&name) || name.starts_with("python")
+}
+
+fn base_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// 读取进程命令行参数；进程不存在或无权限时返回 None
+#[cfg(target_os = "linux")]
+fn process_args(pid: i32) -> Option<Vec<String>> {
+    let raw = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    let args: Vec<String> = raw
+        .split(|b| *b == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+    (!args.is_empty()).then_some(args)
+}
+
+/// 读取进程命令行参数；进程不存在或无权限时返回 None
+///
+/// `ps` 输出以空格拼接参数，含空格的路径会被拆开，对 Agent 名称匹配无影响
+#[cfg(target_os = "macos")]
+fn process_args(pid: i32) -> Option<Vec<String>> {
+    use std::process::{Command, Stdio};
+
+    let output = Command::new("/bin/ps")
+        .args(["-o", "args=", "-p", &pid.to_string()])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let args: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    (!args.is_empty()).then_some(args)
+}
+
+/// 读取进程命令行参数；当前平台不支持
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn process_args(_pid: i32) -> Option<Vec<String>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(cmdline: &str) -> Vec<String> {
+        cmdline.split(' ').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn matches_direct_and_interpreted_agents() {
+        assert_eq!(match_agent(&args("claude --resume")), Some("claude"));
+        assert_eq!(
+            match_agent(&args("/usr/local/bin/aider --model x")),
+            Some("aider")
+        );
+        assert_eq!(
+            match_agent(&args(
+                "node /opt/node_modules/@anthropic-ai/claude-code/cli.js"
+            )),
+            Some("claude")
+        );
+        assert_eq!(match_agent(&args("python3 -m aider")), Some("aider"));
+        assert_eq!(match_agent(&args("npx codex")), Some("codex"));
+    }
+
+    #[test]
+    fn ignores_other_processes() {
+        assert_eq!(match_agent(&args("vim claude.md")), None);
+        assert_eq!(match_agent(&args("node server.js")), None);
+        assert_eq!(match_agent(&args("-zsh")), None);
+        assert_eq!(match_agent(&[]), None);
+    }
+}
@@ -0,0 +1,124 @@
+//! 终端工作目录跟踪
+//!
+//! 优先使用 shell 主动上报的 OSC 7（`ESC ] 7 ; file://host/path BEL|ST`），无需轮询且对
+//! 远程 shell 同样有效；未上报时回退为读取前台进程的实际 cwd（Linux 读 `/proc/<pid>/cwd`，
+//! macOS 调用 `lsof`）。
+
+use std::path::PathBuf;
+
+/// 从一段 PTY 输出中解析最后一个 OSC 7 上报的目录
+///
+/// 只接受 `file://` URL 的绝对路径并做百分号解码；主机名不校验（ssh 场景下为远端主机）。
+pub fn parse_osc7_cwd(data: &[u8]) -> Option<PathBuf> {
+    const PREFIX: &[u8] = b"\x1b]7;";
+    let mut found = None;
+    let mut rest = data;
+    while let Some(pos) = find(rest, PREFIX) {
+        let body = &rest[pos + PREFIX.len()..];
+        let Some(end) = body
+            .iter()
+            .position(|b| *b == 0x07 || *b == 0x1b || *b == 0x9c)
+        else {
+            break;
+        };
+        if let Some(path) = parse_file_url(&body[..end]) {
+            found = Some(path);
+        }
+        rest = &body[end..];
+    }
+    found
+}
+
+/// 读取进程当前工作目录；进程不存在或无权限时返回 None
+#[cfg(target_os = "linux")]
+pub fn process_cwd(pid: i32) -> Option<PathBuf> {
+    std::fs::read_link(format!("/proc/{}/cwd", pid)).ok()
+}
+
+/// 读取进程当前工作目录；进程不存在或无权限时返回 None
+#[cfg(target_os = "macos")]
+pub fn process_cwd(pid: i32) -> Option<PathBuf> {
+    use std::process::{Command, Stdio};
+
+    let output = Command::new("/usr/sbin/lsof")
+        .args(["-a", "-p", &pid.to_string(), "-d", "cwd", "-Fn"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix('n'))
+        .map(PathBuf::from)
+}
+
+/// 读取进程当前工作目录；当前平台不支持
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn process_cwd(_pid: i32) -> Option<PathBuf> {
+    None
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn parse_file_url(url: &[u8]) -> Option<PathBuf> {
+    let rest = url.strip_prefix(b"file://")?;
+    // 跳过主机名部分，路径从第一个 `/` 开始
+    let path = &rest[rest.iter().position(|b| *b == b'/')?..];
+    let decoded = percent_decode(path)?;
+    Some(PathBuf::from(String::from_utf8(decoded).ok()?))
+}
+
+fn percent_decode(input: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        if input[i] == b'%' {
+            let hex = std::str::from_utf8(input.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(input[i]);
+            i += 1;
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_last_osc7_with_bel_or_st() {
+        assert_eq!(
+            parse_osc7_cwd(b"\x1b]7;file://mac.local/Users/me/src\x07$ "),
+            Some(PathBuf::from("/Users/me/src"))
+        );
+        assert_eq!(
+            parse_osc7_cwd(b"\x1b]7;file:///tmp/a\x1b\\ls\r\n\x1b]7;file://h/tmp/my%20dir\x1b\\"),
+            Some(PathBuf::from("/tmp/my dir"))
+        );
+    }
+
+    #[test]
+    fn ignores_incomplete_or_invalid_sequences() {
+        assert_eq!(parse_osc7_cwd(b"plain output"), None);
+        assert_eq!(parse_osc7_cwd(b"\x1b]7;file:///tmp/a"), None);
+        assert_eq!(parse_osc7_cwd(b"\x1b]7;http://h/tmp\x07"), None);
+        assert_eq!(parse_osc7_cwd(b"\x1b]7;file:///bad%zz\x07"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn reads_own_process_cwd() {
+        assert_eq!(
+            process_cwd(std::process::id() as i32),
+            std::env::current_dir().ok()
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! 终端保活（keep-alive）
+//!
+//! 长时间运行的任务（如通宵构建）可能因终端长时间无活动被回收，或因系统休眠中断。
+//! 开启保活的终端：
+//! - 不参与空闲终端回收；
+//! - 每 [`KEEP_ALIVE_INTERVAL`] 向前台进程组发送一次无副作用的 `SIGCONT`；
+//! - `prevent_sleep` 时在 macOS 上启动 `caffeinate -i -w <pid>`，shell 退出后自动结束。
+
+use std::process::Child;
+use std::time::{Duration, Instant};
+
+use tracing::debug;
+
+/// 保活信号间隔
+pub const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 单个终端的保活状态；drop 时结束 caffeinate
+pub struct KeepAlive {
+    caffeinate: Option<Child>,
+    last_poke_at: Instant,
+}
+
+impl KeepAlive {
+    /// 为 shell 进程 `pid` 开启保活；`prevent_sleep` 仅在 macOS 上生效
+    pub fn start(pid: Option<u32>, prevent_sleep: bool) -> Self {
+        let caffeinate = if prevent_sleep {
+            pid.and_then(spawn_caffeinate)
+        } else {
+            None
+        };
+        Self {
+            caffeinate,
+            last_poke_at: Instant::now(),
+        }
+    }
+
+    /// 是否正在阻止系统休眠
+    pub fn prevents_sleep(&self) -> bool {
+        self.caffeinate.is_some()
+    }
+
+    /// 距上次发送超过间隔时向前台进程组 `pgrp` 发送保活信号；返回是否发送
+    pub fn poke_if_due(&mut self, now: Instant, pgrp: Option<i32>) -> bool {
+        if now.duration_since(self.last_poke_at) < KEEP_ALIVE_INTERVAL {
+            return false;
+        }
+        self.last_poke_at = now;
+        let Some(pgrp) = pgrp.filter(|p| *p > 0) else {
+            return false;
+        };
+        // 前台进程组必然处于运行态，SIGCONT 对其没有影响
+        let result = unsafe { libc::kill(-pgrp, libc::SIGCONT) };
+        if result != 0 {
+            debug!(
+                pgrp,
+                error = %std::io::Error::last_os_error(),
+                "Keep-alive signal failed"
+            );
+            return false;
+        }
+        true
+    }
+}
+
+impl Drop for KeepAlive {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.caffeinate.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn spawn_caffeinate(pid: u32) -> Option<Child> {
+    use std::process::{Command, Stdio};
+
+    Command::new("/usr/bin/caffeinate")
+        .args(["-i", "-w", &pid.to_string()])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| tracing::warn!(error = %e, "Failed to start caffeinate"))
+        .ok()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn spawn_caffeinate(pid: u32) -> Option<Child> {
+    debug!(pid, "Sleep prevention is only supported on macOS");
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poke_waits_for_interval() {
+        let mut keep_alive = KeepAlive::start(None, false);
+        let start = keep_alive.last_poke_at;
+        assert!(!keep_alive.poke_if_due(start + Duration::from_secs(1), None));
+
+        // 向自身进程组发送 SIGCONT 无副作用
+        let own_pgrp = unsafe { libc::getpgrp() };
+        assert!(keep_alive.poke_if_due(start + KEEP_ALIVE_INTERVAL, Some(own_pgrp)));
+        assert!(!keep_alive.poke_if_due(start + KEEP_ALIVE_INTERVAL, Some(own_pgrp)));
+        assert!(!keep_alive.prevents_sleep());
+    }
+}
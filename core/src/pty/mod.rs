@@ -1,5 +1,12 @@
+pub mod activity;
+pub mod agent;
+pub mod cwd;
+pub mod keep_alive;
 pub mod resize;
 pub mod session;
+pub mod shell;
+pub mod shell_integration;
 
 pub use resize::resize_pty;
 pub use session::PtySession;
+pub use shell::ShellSpec;
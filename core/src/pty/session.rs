@@ -1,10 +1,12 @@
-use portable_pty::{Child, CommandBuilder, MasterPty, PtySize};
+use portable_pty::{ChildKiller, CommandBuilder, MasterPty, PtySize};
 use std::io::{self, Read, Write};
 use std::path::PathBuf;
+use std::sync::mpsc;
 use tracing::{debug, error, info, instrument, warn};
 use uuid::Uuid;
 
 use super::resize::resize_pty;
+use super::shell::ShellSpec;
 
 // 终端尺寸来自客户端（macOS/xterm.js、iOS/SwiftTerm、未来可能还有其他设备）。
 // 这里必须做服务端兜底 clamp：异常上报（如 cols/rows 极大）会导致某些 TUI 在 SIGWINCH 后按屏幕大小分配缓冲，
@@ -23,35 +25,30 @@ fn clamp_pty_size(cols: u16, rows: u16) -> (u16, u16) {
 pub struct PtySession {
     session_id: String,
     master: Option<Box<dyn MasterPty + Send>>,
-    child: Box<dyn Child + Send + Sync>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    process_id: Option<u32>,
+    /// 子进程退出码；由等待线程在子进程退出后发送
+    exit_rx: Option<mpsc::Receiver<i32>>,
     reader: Option<Box<dyn Read + Send>>,
     writer: Option<Box<dyn Write + Send>>,
     shell_name: String,
 }
 
 impl PtySession {
+    /// `shell` 由 [`super::shell::resolve_shell`] 解析校验
     #[instrument]
     pub fn new(
         cwd: Option<PathBuf>,
         initial_cols: Option<u16>,
         initial_rows: Option<u16>,
+        shell: ShellSpec,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let session_id = Uuid::new_v4().to_string();
         info!(session_id = %session_id, "Creating new PTY session");
 
-        // Try zsh first, fall back to bash
-        let shell_path = if std::path::Path::new("/bin/zsh").exists() {
-            "/bin/zsh"
-        } else {
-            "/bin/bash"
-        };
-        let shell_name = shell_path
-            .split('/')
-            .next_back()
-            .unwrap_or("shell")
-            .to_string();
+        let shell_name = shell.name();
 
-        debug!(session_id = %session_id, shell = %shell_path, "Selected shell");
+        debug!(session_id = %session_id, shell = ?shell.path, args = ?shell.args, "Selected shell");
 
         // Create PTY system
         let pty_system = portable_pty::native_pty_system();
@@ -88,16 +85,40 @@ impl PtySession {
         debug!(session_id = %session_id, cwd = ?working_dir, "Setting working directory");
 
         // Build command
-        let mut cmd = CommandBuilder::new(shell_path);
+        let mut cmd = CommandBuilder::new(&shell.path);
+        cmd.args(&shell.args);
         cmd.cwd(working_dir);
 
         // Ensure term info is correct for rich terminal features
         cmd.env("TERM", "xterm-256color");
         cmd.env("COLORTERM", "truecolor");
         cmd.env("LANG", "en_US.UTF-8");
+        for (key, value) in &shell.env {
+            cmd.env(key, value);
+        }
 
         // Spawn child process
-        let child = pair.slave.spawn_command(cmd)?;
+        let mut child = pair.slave.spawn_command(cmd)?;
+        let killer = child.clone_killer();
+        let process_id = child.process_id();
+
+        // 等待线程：阻塞等待子进程退出并回收，退出码经 channel 上报（不依赖 PTY EOF）
+        let (exit_tx, exit_rx) = mpsc::channel();
+        let wait_session_id = session_id.clone();
+        std::thread::spawn(move || match child.wait() {
+            Ok(status) => {
+                let exit_code = status.exit_code() as i32;
+                info!(session_id = %wait_session_id, exit_code, "Child process exited");
+                let _ = exit_tx.send(exit_code);
+            }
+            Err(e) => {
+                error!(
+                    session_id = %wait_session_id,
+                    error = %e,
+                    "Error waiting for child process to exit"
+                );
+            }
+        });
 
         // 关闭父进程中的 slave 端 FD，避免 master reader 永远收不到 EOF
         drop(pair.slave);
@@ -115,7 +136,9 @@ impl PtySession {
         Ok(PtySession {
             session_id,
             master: Some(master),
-            child,
+            killer,
+            process_id,
+            exit_rx: Some(exit_rx),
             reader: Some(reader),
             writer: Some(writer),
             shell_name,
@@ -130,6 +153,22 @@ impl PtySession {
         &self.shell_name
     }
 
+    /// shell 进程 PID
+    pub fn process_id(&self) -> Option<u32> {
+        self.process_id
+    }
+
+    /// 终端当前前台进程组
+    pub fn foreground_process_group(&self) -> Option<i32> {
+        self.master.as_ref()?.process_group_leader()
+    }
+
+    /// 取出子进程退出码接收端（仅可取一次）；子进程退出后收到退出码，
+    /// 等待失败时发送端被丢弃
+    pub fn take_exit_receiver(&mut self) -> Option<mpsc::Receiver<i32>> {
+        self.exit_rx.take()
+    }
+
     /// 从 session 中取出 reader，用于独立的读取线程
     pub fn take_reader(
         &mut self,
@@ -215,33 +254,6 @@ impl PtySession {
         Ok(())
     }
 
-    #[instrument(skip(self), fields(session_id = %self.session_id))]
-    pub fn wait(&mut self) -> Option<i32> {
-        match self.child.try_wait() {
-            Ok(Some(status)) => {
-                let exit_code = status.exit_code() as i32;
-                info!(
-                    session_id = %self.session_id,
-                    exit_code,
-                    "Child process exited"
-                );
-                Some(exit_code)
-            }
-            Ok(None) => {
-                debug!(session_id = %self.session_id, "Child process still running");
-                None
-            }
-            Err(e) => {
-                error!(
-                    session_id = %self.session_id,
-                    error = %e,
-                    "Error checking child process status"
-                );
-                None
-            }
-        }
-    }
-
     #[instrument(skip(self), fields(session_id = %self.session_id))]
     pub fn kill(&mut self) {
         info!(session_id = %self.session_id, "Killing PTY session");
@@ -251,32 +263,15 @@ impl PtySession {
         drop(self.writer.take());
 
         // Send SIGHUP to the child process
-        if let Err(e) = self.child.kill() {
-            warn!(
+        // 子进程由等待线程回收，这里只发送信号
+        if let Err(e) = self.killer.kill() {
+            debug!(
                 session_id = %self.session_id,
                 error = %e,
                 "Error sending kill signal to child process"
             );
         }
 
-        // Wait for the child to exit
-        match self.child.wait() {
-            Ok(status) => {
-                info!(
-                    session_id = %self.session_id,
-                    exit_code = status.exit_code(),
-                    "Child process terminated"
-                );
-            }
-            Err(e) => {
-                error!(
-                    session_id = %self.session_id,
-                    error = %e,
-                    "Error waiting for child process to exit"
-                );
-            }
-        }
-
         // 最后释放 master FD
         drop(self.master.take());
     }
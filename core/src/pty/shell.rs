@@ -0,0 +1,224 @@
+//! 终端程序（shell）选择与校验
+//!
+//! 客户端可为单个终端指定 shell 与启动参数，服务端设置可指定默认 shell。
+//! 程序必须在白名单内：按名称指定时取 [`ALLOWED_SHELLS`]，按绝对路径指定时
+//! 路径登记在 `/etc/shells` 中，或文件名在白名单内且位于按名称查找时搜索的目录（PATH 与常见安装目录）。
+
+use std::path::{Path, PathBuf};
+
+use tracing::warn;
+
+/// 允许按名称选择的 shell
+pub const ALLOWED_SHELLS: &[&str] = &[
+    "zsh", "bash", "sh", "dash", "fish", "nu", "ksh", "tcsh", "csh", "elvish", "xonsh", "pwsh",
+];
+
+/// 启动参数个数上限
+const MAX_SHELL_ARGS: usize = 32;
+/// 单个启动参数的最大字节数
+const MAX_SHELL_ARG_LEN: usize = 4096;
+
+/// macOS App 启动的 Core 继承的 PATH 通常不含 Homebrew 等目录，按名称查找时一并搜索
+const EXTRA_SHELL_DIRS: &[&str] = &[
+    "/bin",
+    "/usr/bin",
+    "/usr/local/bin",
+    "/opt/homebrew/bin",
+    "/run/current-system/sw/bin",
+];
+
+/// 解析后的终端程序
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShellSpec {
+    /// 可执行文件绝对路径
+    pub path: PathBuf,
+    pub args: Vec<String>,
+    /// 额外环境变量（如 shell 集成注入的 `ZDOTDIR`）
+    pub env: Vec<(String, String)>,
+}
+
+impl ShellSpec {
+    /// 未指定 shell 时的系统默认：优先 zsh，回退 bash
+    pub fn system_default() -> Self {
+        let path = if Path::new("/bin/zsh").exists() {
+            "/bin/zsh"
+        } else {
+            "/bin/bash"
+        };
+        Self {
+            path: PathBuf::from(path),
+            args: Vec::new(),
+            env: Vec::new(),
+        }
+    }
+
+    /// 展示用名称（可执行文件名）
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "shell".to_string())
+    }
+}
+
+/// 解析终端程序：`shell` 为空时依次取服务端默认 shell（`default_shell`）与系统默认；
+/// `args` 与所选程序组合，校验失败返回可展示给用户的错误信息。
+/// 默认 shell 设置失效（如已卸载）时记录警告并回退系统默认，不阻止终端创建。
+pub fn resolve_shell(
+    shell: Option<&str>,
+    args: Option<&[String]>,
+    default_shell: Option<&str>,
+) -> Result<ShellSpec, String> {
+    let args = args.unwrap_or_default();
+    validate_shell_args(args)?;
+
+    let path = match (non_empty(shell), non_empty(default_shell)) {
+        (Some(requested), _) => resolve_shell_path(requested)?,
+        (None, Some(default)) => resolve_shell_path(default).unwrap_or_else(|e| {
+            warn!(error = %e, "Default shell unavailable, falling back to system shell");
+            ShellSpec::system_default().path
+        }),
+        (None, None) => ShellSpec::system_default().path,
+    };
+    Ok(ShellSpec {
+        path,
+        args: args.to_vec(),
+        env: Vec::new(),
+    })
+}
+
+/// 校验 shell 名称或路径是否允许且存在，返回可执行文件绝对路径
+pub fn resolve_shell_path(shell: &str) -> Result<PathBuf, String> {
+    if shell.contains('/') {
+        let path = Path::new(shell);
+        if !path.is_absolute() {
+            return Err(format!("Shell path must be absolute: {}", shell));
+        }
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        // 只看文件名不够：`/tmp/x/bash` 这类任意位置的同名程序不可信
+        let in_search_dir = path
+            .parent()
+            .is_some_and(|parent| search_dirs().any(|dir| dir == parent));
+        let allowed_by_name = ALLOWED_SHELLS.contains(&name.as_str()) && in_search_dir;
+        if !allowed_by_name && !listed_in_etc_shells(path) {
+            return Err(format!("Shell is not allowed: {}", shell));
+        }
+        if !path.is_file() {
+            return Err(format!("Shell not found: {}", shell));
+        }
+        return Ok(path.to_path_buf());
+    }
+
+    if !ALLOWED_SHELLS.contains(&shell) {
+        return Err(format!(
+            "Shell is not allowed: {} (allowed: {})",
+            shell,
+            ALLOWED_SHELLS.join(", ")
+        ));
+    }
+    find_in_search_dirs(shell).ok_or_else(|| format!("Shell not found: {}", shell))
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn validate_shell_args(args: &[String]) -> Result<(), String> {
+    if args.len() > MAX_SHELL_ARGS {
+        return Err(format!(
+            "Too many shell arguments: {} (max {})",
+            args.len(),
+            MAX_SHELL_ARGS
+        ));
+    }
+    if let Some(arg) = args
+        .iter()
+        .find(|a| a.len() > MAX_SHELL_ARG_LEN || a.contains('\0'))
+    {
+        return Err(format!(
+            "Invalid shell argument: {}",
+            arg.chars().take(64).collect::<String>()
+        ));
+    }
+    Ok(())
+}
+
+fn listed_in_etc_shells(path: &Path) -> bool {
+    std::fs::read_to_string("/etc/shells")
+        .map(|content| {
+            content
+                .lines()
+                .map(str::trim)
+                .any(|line| !line.starts_with('#') && Path::new(line) == path)
+        })
+        .unwrap_or(false)
+}
+
+/// 按名称查找 shell 时搜索的目录：PATH 中的绝对路径与 [`EXTRA_SHELL_DIRS`]
+fn search_dirs() -> impl Iterator<Item = PathBuf> {
+    let path_var = std::env::var_os("PATH").unwrap_or_default();
+    std::env::split_paths(&path_var)
+        .filter(|dir| dir.is_absolute())
+        .collect::<Vec<_>>()
+        .into_iter()
+        .chain(EXTRA_SHELL_DIRS.iter().map(PathBuf::from))
+}
+
+fn find_in_search_dirs(name: &str) -> Option<PathBuf> {
+    search_dirs()
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_shell_falls_back_to_default_setting_then_system() {
+        let system = resolve_shell(None, None, None).unwrap();
+        assert_eq!(system, ShellSpec::system_default());
+
+        let sh = resolve_shell(None, None, Some("sh")).unwrap();
+        assert_eq!(sh.name(), "sh");
+        assert!(sh.path.is_absolute());
+
+        // 失效的默认设置不阻止创建终端
+        let stale = resolve_shell(None, None, Some("/nonexistent/fish")).unwrap();
+        assert_eq!(stale, ShellSpec::system_default());
+
+        let args = vec!["-c".to_string(), "echo hi".to_string()];
+        let explicit = resolve_shell(Some("/bin/sh"), Some(&args), Some("bash")).unwrap();
+        assert_eq!(explicit.path, PathBuf::from("/bin/sh"));
+        assert_eq!(explicit.args, args);
+    }
+
+    #[test]
+    fn resolve_shell_rejects_unlisted_programs_and_bad_args() {
+        assert!(resolve_shell(Some("python3"), None, None).is_err());
+        assert!(resolve_shell(Some("bin/sh"), None, None).is_err());
+        assert!(resolve_shell(Some("/usr/bin/env"), None, None).is_err());
+        assert!(resolve_shell(Some("/nonexistent/zsh"), None, None).is_err());
+
+        // 白名单文件名出现在不受信任的目录中时拒绝
+        let untrusted = tempfile::tempdir().unwrap();
+        let fake_bash = untrusted.path().join("x/bash");
+        std::fs::create_dir_all(fake_bash.parent().unwrap()).unwrap();
+        std::fs::write(&fake_bash, "#!/bin/sh\n").unwrap();
+        let err = resolve_shell_path(fake_bash.to_str().unwrap()).unwrap_err();
+        assert!(err.starts_with("Shell is not allowed"), "{}", err);
+        let trusted = find_in_search_dirs("sh").unwrap();
+        assert_eq!(
+            resolve_shell_path(trusted.to_str().unwrap()).unwrap(),
+            trusted
+        );
+
+        let nul = vec!["a\0b".to_string()];
+        assert!(resolve_shell(Some("sh"), Some(&nul), None).is_err());
+        let many = vec!["-x".to_string(); MAX_SHELL_ARGS + 1];
+        assert!(resolve_shell(Some("sh"), Some(&many), None).is_err());
+    }
+}
@@ -0,0 +1,402 @@
+//! Shell 集成：按命令切分终端输出
+//!
+//! shell 在提示符与命令前后输出 OSC 133 标记（FinalTerm 语义，iTerm2/WezTerm/VS Code 通用）：
+//! - `A` 提示符开始，`B` 输入开始（提示符结束）
+//! - `C` 命令开始执行，`D;<exit>` 命令结束
+//!
+//! 已自带 OSC 133 的提示符（如 starship、fish 4）无需额外配置；开启客户端设置
+//! `terminal_shell_integration` 后，Core 为 zsh（`ZDOTDIR`）与 bash（`--rcfile`）注入 rc 片段，
+//! 先加载用户原有配置再追加标记。命令文本取自 `B` 与 `C` 之间回显的输入。
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use tracing::warn;
+
+use super::shell::ShellSpec;
+use crate::server::protocol::TerminalCommandInfo;
+
+/// 每个终端保留的命令记录数
+pub const MAX_COMMAND_HISTORY: usize = 200;
+/// 命令文本最大字节数（回显捕获上限）
+const MAX_COMMAND_BYTES: usize = 4096;
+
+const ZSHENV: &str = r#"# TidyFlow shell integration：加载用户的 .zshenv
+_tidyflow_zdotdir="$ZDOTDIR"
+ZDOTDIR="${TIDYFLOW_USER_ZDOTDIR:-$HOME}"
+[[ -f "$ZDOTDIR/.zshenv" ]] && source "$ZDOTDIR/.zshenv"
+TIDYFLOW_USER_ZDOTDIR="$ZDOTDIR"
+ZDOTDIR="$_tidyflow_zdotdir"
+unset _tidyflow_zdotdir
+"#;
+
+const ZPROFILE: &str = r#"# TidyFlow shell integration：加载用户的 .zprofile
+_tidyflow_zdotdir="$ZDOTDIR"
+ZDOTDIR="$TIDYFLOW_USER_ZDOTDIR"
+[[ -f "$ZDOTDIR/.zprofile" ]] && source "$ZDOTDIR/.zprofile"
+ZDOTDIR="$_tidyflow_zdotdir"
+unset _tidyflow_zdotdir
+"#;
+
+// .zshrc 之后 ZDOTDIR 恢复为用户目录，用户的 .zlogin 由 zsh 自行加载
+const ZSHRC: &str = r#"# TidyFlow shell integration：加载用户的 .zshrc 后追加 OSC 133 标记
+ZDOTDIR="$TIDYFLOW_USER_ZDOTDIR"
+unset TIDYFLOW_USER_ZDOTDIR
+[[ -f "$ZDOTDIR/.zshrc" ]] && source "$ZDOTDIR/.zshrc"
+
+__tidyflow_precmd() {
+  local ret=$?
+  [[ -n "$__tidyflow_running" ]] && printf '\e]133;D;%s\a' "$ret"
+  __tidyflow_running=
+  printf '\e]133;A\a'
+}
+__tidyflow_preexec() {
+  __tidyflow_running=1
+  printf '\e]133;C\a'
+}
+__tidyflow_prompt_end() {
+  [[ "$PS1" == *$'\e]133;B\a'* ]] || PS1="$PS1%{"$'\e]133;B\a'"%}"
+}
+precmd_functions=(__tidyflow_precmd $precmd_functions __tidyflow_prompt_end)
+preexec_functions+=(__tidyflow_preexec)
+"#;
+
+const BASHRC: &str = r#"# TidyFlow shell integration：加载用户的 .bashrc 后追加 OSC 133 标记
+[[ -f ~/.bashrc ]] && source ~/.bashrc
+
+__tidyflow_save_status() {
+  __tidyflow_status=$?
+  __tidyflow_at_prompt=
+}
+__tidyflow_prompt() {
+  [[ -n "$__tidyflow_running" ]] && printf '\e]133;D;%s\a' "$__tidyflow_status"
+  __tidyflow_running=
+  printf '\e]133;A\a'
+  [[ "$PS1" == *'\e]133;B\a'* ]] || PS1="$PS1"'\[\e]133;B\a\]'
+  __tidyflow_at_prompt=1
+}
+__tidyflow_preexec() {
+  [[ -n "$COMP_LINE" || -z "$__tidyflow_at_prompt" ]] && return
+  [[ "$BASH_COMMAND" == __tidyflow_* ]] && return
+  __tidyflow_at_prompt=
+  __tidyflow_running=1
+  printf '\e]133;C\a'
+}
+PROMPT_COMMAND="__tidyflow_save_status;${PROMPT_COMMAND:+$PROMPT_COMMAND;}__tidyflow_prompt"
+trap '__tidyflow_preexec' DEBUG
+"#;
+
+/// 为支持的 shell 注入集成脚本；脚本写入失败或 shell 不支持时保持原样
+///
+/// bash 仅在未指定启动参数时注入，避免与用户参数（如 `-l`、`--rcfile`）冲突。
+pub fn inject(shell: &mut ShellSpec) {
+    let dir = crate::util::paths::tidyflow_home_dir().join("shell-integration");
+    inject_into(shell, &dir);
+}
+
+/// 把集成脚本写入 `dir` 并调整 shell 的启动参数或环境变量
+fn inject_into(shell: &mut ShellSpec, dir: &Path) {
+    match shell.name().as_str() {
+        "zsh" => {
+            let zsh_dir = dir.join("zsh");
+            let written = [
+                (".zshenv", ZSHENV),
+                (".zprofile", ZPROFILE),
+                (".zshrc", ZSHRC),
+            ]
+            .iter()
+            .all(|(name, content)| write_script(&zsh_dir.join(name), content));
+            if !written {
+                return;
+            }
+            let user_zdotdir = std::env::var("ZDOTDIR")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .or_else(|| dirs::home_dir().map(|h| h.to_string_lossy().to_string()))
+                .unwrap_or_default();
+            shell
+                .env
+                .push(("TIDYFLOW_USER_ZDOTDIR".to_string(), user_zdotdir));
+            shell
+                .env
+                .push(("ZDOTDIR".to_string(), zsh_dir.to_string_lossy().to_string()));
+        }
+        "bash" if shell.args.is_empty() => {
+            let rcfile = dir.join("bash").join("bashrc");
+            if write_script(&rcfile, BASHRC) {
+                shell.args = vec!["--rcfile".to_string(), rcfile.to_string_lossy().to_string()];
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 内容变化时才写入，避免每次建终端都改写文件
+fn write_script(path: &Path, content: &str) -> bool {
+    if std::fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        return true;
+    }
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(path, content));
+    if let Err(e) = result {
+        warn!(path = %path.display(), error = %e, "Failed to write shell integration script");
+        return false;
+    }
+    true
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+enum MarkState {
+    #[default]
+    Idle,
+    /// 处于 `B` 与 `C` 之间，捕获回显的输入
+    Input,
+    Running,
+}
+
+/// 单个终端的 OSC 133 命令跟踪器
+#[derive(Debug, Default)]
+pub struct CommandTracker {
+    state: MarkState,
+    /// 是否收到过 OSC 133 标记（shell 集成生效）
+    integrated: bool,
+    input: Vec<u8>,
+    running: Option<(String, i64, PathBuf)>,
+    history: VecDeque<TerminalCommandInfo>,
+    finished: Vec<TerminalCommandInfo>,
+}
+
+impl CommandTracker {
+    /// 处理一段 PTY 输出；`now_ms` 为 Unix 毫秒，`cwd` 为当前工作目录
+    pub fn feed(&mut self, data: &[u8], now_ms: i64, cwd: &Path) {
+        const PREFIX: &[u8] = b"\x1b]133;";
+        let mut rest = data;
+        loop {
+            let Some(pos) = rest.windows(PREFIX.len()).position(|w| w == PREFIX) else {
+                self.capture(rest);
+                return;
+            };
+            self.capture(&rest[..pos]);
+            let body = &rest[pos + PREFIX.len()..];
+            let Some(end) = body
+                .iter()
+                .position(|b| *b == 0x07 || *b == 0x1b || *b == 0x9c)
+            else {
+                return;
+            };
+            self.mark(&body[..end], now_ms, cwd);
+            // 跳过终止符（`ESC \` 为两字节）
+            let skip = if body[end] == 0x1b && body.get(end + 1) == Some(&b'\\') {
+                2
+            } else {
+                1
+            };
+            rest = &body[end + skip..];
+        }
+    }
+
+    /// 是否停在 shell 提示符；未收到过 OSC 133 标记时无法判断，返回 None
+    pub fn at_prompt(&self) -> Option<bool> {
+        self.integrated.then_some(self.state != MarkState::Running)
+    }
+
+    /// 最近的命令记录（旧在前）
+    pub fn history(&self) -> Vec<TerminalCommandInfo> {
+        self.history.iter().cloned().collect()
+    }
+
+    /// 取出上次调用后新结束的命令
+    pub fn take_finished(&mut self) -> Vec<TerminalCommandInfo> {
+        std::mem::take(&mut self.finished)
+    }
+
+    fn capture(&mut self, text: &[u8]) {
+        if self.state == MarkState::Input {
+            let room = MAX_COMMAND_BYTES.saturating_sub(self.input.len());
+            self.input.extend_from_slice(&text[..text.len().min(room)]);
+        }
+    }
+
+    fn mark(&mut self, params: &[u8], now_ms: i64, cwd: &Path) {
+        self.integrated = true;
+        let params = String::from_utf8_lossy(params);
+        let mut parts = params.split(';');
+        match parts.next() {
+            Some("A") => self.state = MarkState::Idle,
+            Some("B") => {
+                self.state = MarkState::Input;
+                self.input.clear();
+            }
+            Some("C") => {
+                let command = sanitize_command(&std::mem::take(&mut self.input));
+                self.running = Some((command, now_ms, cwd.to_path_buf()));
+                self.state = MarkState::Running;
+            }
+            Some("D") => {
+                self.state = MarkState::Idle;
+                let Some((command, started_at_ms, cwd)) = self.running.take() else {
+                    return;
+                };
+                let info = TerminalCommandInfo {
+                    command,
+                    exit_code: parts.next().and_then(|c| c.trim().parse().ok()),
+                    started_at_ms,
+                    duration_ms: (now_ms - started_at_ms).max(0) as u64,
+                    cwd: cwd.to_string_lossy().to_string(),
+                };
+                if self.history.len() >= MAX_COMMAND_HISTORY {
+                    self.history.pop_front();
+                }
+                self.history.push_back(info.clone());
+                if self.finished.len() < MAX_COMMAND_HISTORY {
+                    self.finished.push(info);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 从回显的输入中还原命令文本：去除转义序列与控制字符，处理退格，取最后一段非空行
+pub(crate) fn sanitize_command(raw: &[u8]) -> String {
+    let text = String::from_utf8_lossy(raw);
+    let mut lines: Vec<String> = vec![String::new()];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI：参数与中间字节后跟一个终止字节
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC：以 BEL 或 ST 结束
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\x08' | '\x7f' => {
+                if let Some(line) = lines.last_mut() {
+                    line.pop();
+                }
+            }
+            '\r' | '\n' => lines.push(String::new()),
+            c if c.is_control() => {}
+            c => {
+                if let Some(line) = lines.last_mut() {
+                    line.push(c);
+                }
+            }
+        }
+    }
+    lines
+        .iter()
+        .rev()
+        .map(|l| l.trim())
+        .find(|l| !l.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_command_text_exit_code_and_duration() {
+        let mut tracker = CommandTracker::default();
+        let cwd = Path::new("/repo");
+        assert_eq!(tracker.at_prompt(), None);
+        tracker.feed(b"\x1b]133;A\x07$ \x1b]133;B\x07", 1_000, cwd);
+        assert_eq!(tracker.at_prompt(), Some(true));
+        tracker.feed(b"cargo tets\x08\x08st\r\n", 1_500, cwd);
+        tracker.feed(b"\x1b]133;C\x07running...\r\n", 2_000, cwd);
+        assert_eq!(tracker.at_prompt(), Some(false));
+        assert!(tracker.take_finished().is_empty());
+        tracker.feed(b"done\r\n\x1b]133;D;1\x1b\\\x1b]133;A\x07$ ", 5_250, cwd);
+
+        let finished = tracker.take_finished();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].command, "cargo test");
+        assert_eq!(finished[0].exit_code, Some(1));
+        assert_eq!(finished[0].started_at_ms, 2_000);
+        assert_eq!(finished[0].duration_ms, 3_250);
+        assert_eq!(finished[0].cwd, "/repo");
+        assert_eq!(tracker.history(), finished);
+        assert!(tracker.take_finished().is_empty());
+    }
+
+    #[test]
+    fn ignores_finish_without_start_and_caps_history() {
+        let mut tracker = CommandTracker::default();
+        let cwd = Path::new("/");
+        // 空回车：只有 D 没有 C
+        tracker.feed(b"\x1b]133;D\x07\x1b]133;A\x07", 0, cwd);
+        assert!(tracker.history().is_empty());
+
+        for i in 0..(MAX_COMMAND_HISTORY + 5) {
+            let chunk = format!("\x1b]133;B\x07cmd{}\r\n\x1b]133;C\x07\x1b]133;D;0\x07", i);
+            tracker.feed(chunk.as_bytes(), i as i64, cwd);
+        }
+        let history = tracker.history();
+        assert_eq!(history.len(), MAX_COMMAND_HISTORY);
+        assert_eq!(history[0].command, "cmd5");
+        assert_eq!(history[0].exit_code, Some(0));
+    }
+
+    #[test]
+    fn sanitizes_escape_sequences_in_echoed_input() {
+        assert_eq!(
+            sanitize_command(b"\x1b[32mgit\x1b[0m status\x1b]0;title\x07\r\n"),
+            "git status"
+        );
+        assert_eq!(sanitize_command(b"ls\rls -la"), "ls -la");
+        assert_eq!(sanitize_command(b""), "");
+    }
+
+    #[test]
+    fn injects_bash_rcfile_only_without_user_args() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut bash = ShellSpec {
+            path: PathBuf::from("/bin/bash"),
+            args: Vec::new(),
+            env: Vec::new(),
+        };
+        inject_into(&mut bash, dir.path());
+        assert_eq!(bash.args[0], "--rcfile");
+        assert!(Path::new(&bash.args[1]).is_file());
+
+        let mut login = ShellSpec {
+            path: PathBuf::from("/bin/bash"),
+            args: vec!["-l".to_string()],
+            env: Vec::new(),
+        };
+        inject_into(&mut login, dir.path());
+        assert_eq!(login.args, vec!["-l"]);
+
+        let mut zsh = ShellSpec {
+            path: PathBuf::from("/bin/zsh"),
+            args: Vec::new(),
+            env: Vec::new(),
+        };
+        inject_into(&mut zsh, dir.path());
+        let zdotdir = zsh
+            .env
+            .iter()
+            .find(|(k, _)| k == "ZDOTDIR")
+            .map(|(_, v)| PathBuf::from(v))
+            .unwrap();
+        assert!(zdotdir.join(".zshrc").is_file());
+    }
+}
@@ -0,0 +1,518 @@
+//! 变更操作审计日志
+//!
+//! 调度层在分发前把会改变状态的请求（提交、合并、文件写入、工作区增删等）追加到
+//! 数据目录下的 `audit-log.jsonl`，每行一条 [`AuditEntry`]，记录时间与发起连接，
+//! 便于事后查看服务端（或驱动它的 Agent）做过什么。客户端经 `get_audit_log` 查询。
+//!
+//! 只记录定位操作对象所需的字段，不记录文件内容、配对密钥等载荷。
+//! 文件超过 [`AUDIT_LOG_MAX_BYTES`] 时轮转为 `audit-log.jsonl.1`，只保留一份旧文件。
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::server::context::ConnectionMeta;
+
+/// 单个日志文件的大小上限，超过后轮转
+pub const AUDIT_LOG_MAX_BYTES: u64 = 8 * 1024 * 1024;
+/// `get_audit_log` 未指定 limit 时返回的条数
+pub const DEFAULT_AUDIT_LOG_LIMIT: usize = 200;
+/// `get_audit_log` 单次最多返回的条数
+pub const MAX_AUDIT_LOG_LIMIT: usize = 1000;
+/// `target` 字段的最大字符数
+const MAX_TARGET_CHARS: usize = 200;
+
+/// 需要审计的 action（会改变仓库、文件系统或 Core 状态）
+const MUTATING_ACTIONS: &[&str] = &[
+    // 终端与进程
+    "spawn_terminal",
+    "kill_terminal",
+    "term_create",
+    "term_close",
+    "term_rename",
+    "proc_start",
+    "proc_stop",
+    "run_project_command",
+    "cancel_project_command",
+    "term_reorder",
+    "term_set_keep_alive",
+    // 文件
+    "file_write",
+    "file_apply_edit",
+    "file_rename",
+    "file_delete",
+    "file_mkdir",
+    "file_copy",
+    "file_move",
+    "file_apply_replace",
+    "file_undo_replace",
+    "file_format_execute",
+    "clipboard_image_upload",
+    // Git
+    "git_stage",
+    "git_unstage",
+    "git_discard",
+    "git_switch_branch",
+    "git_create_branch",
+    "git_commit",
+    "git_fetch",
+    "git_rebase",
+    "git_rebase_continue",
+    "git_rebase_abort",
+    "git_rebase_interactive_execute",
+    "git_ensure_integration_worktree",
+    "git_merge_to_default",
+    "git_merge_continue",
+    "git_merge_abort",
+    "git_rebase_onto_default",
+    "git_rebase_onto_default_continue",
+    "git_rebase_onto_default_abort",
+    "git_reset_integration_worktree",
+    "git_create_pull_request",
+    "git_conflict_accept_ours",
+    "git_conflict_accept_theirs",
+    "git_conflict_accept_both",
+    "git_conflict_mark_resolved",
+    "git_conflict_resolve",
+    "git_stash_save",
+    "git_stash_apply",
+    "git_stash_pop",
+    "git_stash_drop",
+    "git_stash_restore_paths",
+    "git_ai_merge",
+    "git_cherry_pick",
+    "git_cherry_pick_continue",
+    "git_cherry_pick_abort",
+    "git_revert",
+    "git_revert_continue",
+    "git_revert_abort",
+    "git_workspace_op_rollback",
+    "git_generate_change_report",
+    "git_op_cancel",
+    // 项目与工作区
+    "import_project",
+    "import_project_from_url",
+    "remove_project",
+    "create_workspace",
+    "apply_workspace_manifest",
+    "remove_workspace",
+    "rename_workspace",
+    "repair_workspace",
+    "link_workspace_issue",
+    "workspace_set_sparse_paths",
+    "set_project_default_branch",
+    "save_project_commands",
+    // 模板与设置
+    "save_template",
+    "delete_template",
+    "import_template",
+    "save_client_settings",
+    // 节点
+    "node_update_profile",
+    "node_pair_peer",
+    "node_unpair_peer",
+    // AI 会话与 Evolution
+    "ai_session_delete",
+    "ai_session_rename",
+    "evo_start_workspace",
+    "evo_stop_workspace",
+    "evo_stop_all",
+    "evo_resume_workspace",
+    "evo_update_agent_profile",
+    "evo_resolve_blockers",
+    "evo_auto_commit",
+    "evo_adjust_loop_round",
+    // 健康与状态
+    "health_repair",
+    "restore_state_backup",
+];
+
+/// 不需要审计的 action（只读查询、连接级订阅与流控、终端输入与 AI 会话交互）
+///
+/// 与 [`MUTATING_ACTIONS`] 一起覆盖全部 `ClientMessage`，新增消息时须归入其一（由测试检查）。
+#[cfg(test)]
+const READ_ONLY_ACTIONS: &[&str] = &[
+    // 连接与终端交互
+    "input",
+    "resize",
+    "ping",
+    "host_power_event",
+    "cancel",
+    "term_list",
+    "term_focus",
+    "term_attach",
+    "term_detach",
+    "term_output_ack",
+    "term_flow_control",
+    "term_command_history",
+    "proc_list",
+    "proc_logs",
+    // 项目与工作区
+    "list_projects",
+    "list_workspaces",
+    "list_workspace_tasks",
+    "list_ports",
+    "select_workspace",
+    "list_tasks",
+    "watch_subscribe",
+    "watch_unsubscribe",
+    // 文件
+    "file_list",
+    "file_read",
+    "file_read_at_revision",
+    "file_index",
+    "file_query",
+    "file_read_chunked",
+    "file_definition_guess",
+    "file_editorconfig",
+    "file_highlight",
+    "file_watch",
+    "file_unwatch",
+    "file_prepare_replace",
+    "file_format_capabilities_query",
+    // Git
+    "git_status",
+    "git_status_all",
+    "git_status_tree",
+    "git_diff",
+    "git_diff_page",
+    "git_branches",
+    "git_rebase_interactive_plan",
+    "git_op_status",
+    "git_integration_status",
+    "git_repo_stats",
+    "git_detect_large_blobs",
+    "git_check_branch_up_to_date",
+    "git_list_pull_requests",
+    "git_pull_request_status",
+    "git_log",
+    "git_show",
+    "git_suggested_commit_message",
+    "git_generate_commit_message",
+    "git_show_file_diff",
+    "git_blame",
+    "git_diff_range",
+    "git_conflict_detail",
+    "git_stash_list",
+    "git_stash_show",
+    // 模板与设置
+    "list_templates",
+    "export_template",
+    "get_client_settings",
+    // 节点
+    "node_refresh_network",
+    // AI 会话与 Evolution
+    "cancel_ai_task",
+    "ai_chat_start",
+    "ai_chat_send",
+    "ai_chat_command",
+    "ai_chat_abort",
+    "ai_question_reply",
+    "ai_question_reject",
+    "ai_session_list",
+    "ai_session_messages",
+    "ai_session_status",
+    "ai_session_subscribe",
+    "ai_session_unsubscribe",
+    "ai_provider_list",
+    "ai_agent_list",
+    "ai_slash_commands",
+    "ai_session_config_options",
+    "ai_session_set_config_option",
+    "ai_session_search",
+    "ai_code_review",
+    "ai_code_completion",
+    "ai_code_completion_abort",
+    "evo_get_snapshot",
+    "evo_get_agent_profile",
+    "evo_list_cycle_history",
+    // 健康与状态
+    "health_report",
+    "get_audit_log",
+];
+
+/// 依次尝试作为操作对象的载荷字段
+const TARGET_KEYS: &[&str] = &[
+    "path",
+    "old_path",
+    "source_absolute_path",
+    "branch",
+    "new_name",
+    "name",
+    "url",
+    "term_id",
+    "command_id",
+    "template_id",
+    "session_id",
+    "backup_id",
+    "peer_id",
+    "host",
+];
+
+/// 一条审计记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// 收到请求的时间（Unix 毫秒）
+    pub ts_ms: i64,
+    pub conn_id: String,
+    pub remote: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_name: Option<String>,
+    pub domain: String,
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    /// 操作对象（路径、分支、名称等）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+/// action 是否会改变状态、需要审计
+pub fn is_mutating_action(action: &str) -> bool {
+    MUTATING_ACTIONS.contains(&action)
+}
+
+impl AuditEntry {
+    /// 从请求信封构造审计记录；只读请求返回 None
+    pub fn from_request(
+        domain: &str,
+        action: &str,
+        payload: &serde_json::Value,
+        conn_meta: &ConnectionMeta,
+    ) -> Option<Self> {
+        if !is_mutating_action(action) {
+            return None;
+        }
+        let field = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| payload.get(*key).and_then(|v| v.as_str()))
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        Some(Self {
+            ts_ms: chrono::Utc::now().timestamp_millis(),
+            conn_id: conn_meta.conn_id.clone(),
+            remote: conn_meta.is_remote,
+            device_name: conn_meta.device_name.clone(),
+            domain: domain.to_string(),
+            action: action.to_string(),
+            project: field(&["project", "dest_project", "project_name"]),
+            workspace: field(&["workspace", "dest_workspace", "workspace_name"]),
+            target: field(TARGET_KEYS).map(|v| v.chars().take(MAX_TARGET_CHARS).collect()),
+        })
+    }
+}
+
+/// 数据目录下的 `audit-log.jsonl`
+pub fn default_audit_log_path() -> PathBuf {
+    crate::util::paths::tidyflow_home_dir().join("audit-log.jsonl")
+}
+
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".1");
+    PathBuf::from(name)
+}
+
+/// 串行化追加与轮转
+static WRITE_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// 追加一条记录；文件超过上限时先轮转
+pub async fn append_entry(path: &Path, entry: &AuditEntry) -> std::io::Result<()> {
+    let mut line = serde_json::to_vec(entry).map_err(std::io::Error::other)?;
+    line.push(b'\n');
+
+    let _guard = WRITE_LOCK.lock().await;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    if let Ok(meta) = tokio::fs::metadata(path).await {
+        if meta.len() + line.len() as u64 > AUDIT_LOG_MAX_BYTES {
+            tokio::fs::rename(path, rotated_path(path)).await?;
+        }
+    }
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(&line).await?;
+    file.flush().await
+}
+
+/// 若请求会改变状态则写入默认审计日志；写入失败只记录警告，不影响请求处理
+pub async fn record_request(
+    domain: &str,
+    action: &str,
+    payload: &serde_json::Value,
+    conn_meta: &ConnectionMeta,
+) {
+    let Some(entry) = AuditEntry::from_request(domain, action, payload, conn_meta) else {
+        return;
+    };
+    if let Err(e) = append_entry(&default_audit_log_path(), &entry).await {
+        warn!(action = %action, error = %e, "Failed to write audit log entry");
+    }
+}
+
+/// 读取晚于 `since_ms`（不含）的记录，按时间升序返回最近的 `limit` 条；文件不存在时为空
+pub async fn read_entries(
+    path: &Path,
+    since_ms: Option<i64>,
+    limit: Option<usize>,
+) -> std::io::Result<Vec<AuditEntry>> {
+    let limit = limit
+        .unwrap_or(DEFAULT_AUDIT_LOG_LIMIT)
+        .clamp(1, MAX_AUDIT_LOG_LIMIT);
+    let mut entries = Vec::new();
+    for file in [rotated_path(path), path.to_path_buf()] {
+        let content = match tokio::fs::read_to_string(&file).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        // 跳过写入中断留下的残行
+        entries.extend(
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+                .filter(|entry| since_ms.is_none_or(|since| entry.ts_ms > since)),
+        );
+    }
+    let skip = entries.len().saturating_sub(limit);
+    Ok(entries.split_off(skip))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::server::protocol::ClientMessage;
+
+    fn conn_meta() -> ConnectionMeta {
+        ConnectionMeta {
+            conn_id: "conn-1".to_string(),
+            api_key_id: None,
+            client_id: None,
+            subscriber_id: None,
+            is_remote: true,
+            device_name: Some("iPhone".to_string()),
+        }
+    }
+
+    fn entry(ts_ms: i64, action: &str) -> AuditEntry {
+        AuditEntry {
+            ts_ms,
+            conn_id: "conn-1".to_string(),
+            remote: false,
+            device_name: None,
+            domain: "git".to_string(),
+            action: action.to_string(),
+            project: Some("demo".to_string()),
+            workspace: Some("main".to_string()),
+            target: None,
+        }
+    }
+
+    #[test]
+    fn only_mutating_requests_are_recorded_without_payload_contents() {
+        let read = json!({"project": "demo", "workspace": "main", "path": "a.rs"});
+        assert_eq!(
+            AuditEntry::from_request("file", "file_read", &read, &conn_meta()),
+            None
+        );
+
+        let write = json!({
+            "project": "demo",
+            "workspace": "main",
+            "path": "src/lib.rs",
+            "content": [1, 2, 3]
+        });
+        let recorded =
+            AuditEntry::from_request("file", "file_write", &write, &conn_meta()).unwrap();
+        assert_eq!(recorded.project.as_deref(), Some("demo"));
+        assert_eq!(recorded.workspace.as_deref(), Some("main"));
+        assert_eq!(recorded.target.as_deref(), Some("src/lib.rs"));
+        assert!(recorded.remote);
+        assert_eq!(recorded.device_name.as_deref(), Some("iPhone"));
+
+        let pair = json!({"host": "10.0.0.2", "port": 47999, "pair_key": "secret"});
+        let recorded =
+            AuditEntry::from_request("node", "node_pair_peer", &pair, &conn_meta()).unwrap();
+        assert_eq!(recorded.target.as_deref(), Some("10.0.0.2"));
+        assert!(!serde_json::to_string(&recorded).unwrap().contains("secret"));
+    }
+
+    #[test]
+    fn every_client_message_is_classified() {
+        // serde 在未知 tag 的报错里列出全部 variant 名（已应用 rename）
+        let err = serde_json::from_value::<ClientMessage>(json!({"type": "__unknown__"}))
+            .unwrap_err()
+            .to_string();
+        let (_, expected) = err
+            .split_once("expected one of ")
+            .expect("unknown variant error lists variants");
+        let actions: Vec<&str> = expected
+            .split(", ")
+            .map(|name| name.trim_matches('`'))
+            .collect();
+        assert!(actions.len() > 100, "unexpected variant list: {}", err);
+
+        for action in &actions {
+            let mutating = MUTATING_ACTIONS.contains(action);
+            let read_only = READ_ONLY_ACTIONS.contains(action);
+            assert!(
+                mutating != read_only,
+                "{} must be listed in exactly one of MUTATING_ACTIONS / READ_ONLY_ACTIONS",
+                action
+            );
+        }
+        for action in MUTATING_ACTIONS.iter().chain(READ_ONLY_ACTIONS) {
+            assert!(
+                actions.contains(action),
+                "{} is not a ClientMessage",
+                action
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn append_and_read_entries_with_since_and_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit-log.jsonl");
+        assert!(read_entries(&path, None, None).await.unwrap().is_empty());
+
+        for (ts, action) in [
+            (1, "git_commit"),
+            (2, "file_write"),
+            (3, "remove_workspace"),
+        ] {
+            append_entry(&path, &entry(ts, action)).await.unwrap();
+        }
+        let all = read_entries(&path, None, None).await.unwrap();
+        assert_eq!(
+            all.iter().map(|e| e.action.as_str()).collect::<Vec<_>>(),
+            vec!["git_commit", "file_write", "remove_workspace"]
+        );
+
+        let since = read_entries(&path, Some(1), None).await.unwrap();
+        assert_eq!(since.len(), 2);
+        let latest = read_entries(&path, None, Some(1)).await.unwrap();
+        assert_eq!(latest, vec![entry(3, "remove_workspace")]);
+
+        // 轮转后的旧文件仍可读取
+        tokio::fs::rename(&path, rotated_path(&path)).await.unwrap();
+        append_entry(&path, &entry(4, "git_fetch")).await.unwrap();
+        let after_rotation = read_entries(&path, Some(2), None).await.unwrap();
+        assert_eq!(
+            after_rotation.iter().map(|e| e.ts_ms).collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+    }
+}
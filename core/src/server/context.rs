@@ -24,6 +24,27 @@ pub type SharedAppState = Arc<RwLock<AppState>>;
 pub struct FlowControl {
     pub unacked: std::sync::atomic::AtomicU64,
     pub notify: tokio::sync::Notify,
+    /// 客户端通过 `term_flow_control` 暂停了实时输出（PTY 照常运行，输出只进 scrollback）
+    pub client_paused: std::sync::atomic::AtomicBool,
+    /// 自上次上报以来未转发而丢弃的字节数
+    pub dropped: std::sync::atomic::AtomicU64,
+}
+
+impl FlowControl {
+    pub fn new() -> Self {
+        Self {
+            unacked: std::sync::atomic::AtomicU64::new(0),
+            notify: tokio::sync::Notify::new(),
+            client_paused: std::sync::atomic::AtomicBool::new(false),
+            dropped: std::sync::atomic::AtomicU64::new(0),
+        }
+    }
+}
+
+impl Default for FlowControl {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// subscribed_terms 的 value 类型：(转发任务句柄, 流控状态, PTY 背压门控)
@@ -298,6 +319,7 @@ impl AppError {
             workspace: None,
             session_id: None,
             cycle_id: None,
+            trace_id: None,
         }
     }
 
@@ -316,6 +338,7 @@ impl AppError {
             workspace,
             session_id,
             cycle_id,
+            trace_id: None,
         }
     }
 }
@@ -0,0 +1,393 @@
+//! 跳转定义的启发式回退（LSP 接入前的基础导航）
+//!
+//! 在文件索引给出的候选文件中，按语言关键字识别 `<关键字> <符号>` 形式的定义行，
+//! 再结合与当前文件的语言/目录/文件名相关性打分排序。
+
+use std::path::Path;
+
+use super::file_api::MAX_FILE_SIZE;
+
+/// 最多扫描的候选文件数
+pub const MAX_DEFINITION_SCAN_FILES: usize = 5000;
+/// 最多返回的候选定义数
+pub const MAX_DEFINITION_CANDIDATES: usize = 50;
+
+/// 语言族：同一族内的文件视为可以互相引用
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LanguageFamily {
+    Rust,
+    Swift,
+    JavaScript,
+    Python,
+    Go,
+    Jvm,
+    C,
+    Ruby,
+}
+
+impl LanguageFamily {
+    pub fn from_path(path: &str) -> Option<Self> {
+        let ext = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())?
+            .to_ascii_lowercase();
+        match ext.as_str() {
+            "rs" => Some(Self::Rust),
+            "swift" => Some(Self::Swift),
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" | "vue" | "svelte" => {
+                Some(Self::JavaScript)
+            }
+            "py" | "pyi" => Some(Self::Python),
+            "go" => Some(Self::Go),
+            "java" | "kt" | "kts" | "scala" => Some(Self::Jvm),
+            "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" | "m" | "mm" => Some(Self::C),
+            "rb" => Some(Self::Ruby),
+            _ => None,
+        }
+    }
+
+    /// 该语言中紧邻定义名之前的关键字
+    fn keywords(self) -> &'static [&'static str] {
+        match self {
+            Self::Rust => &[
+                "fn",
+                "struct",
+                "enum",
+                "trait",
+                "type",
+                "mod",
+                "union",
+                "const",
+                "static",
+                "macro_rules!",
+            ],
+            Self::Swift => &[
+                "func",
+                "class",
+                "struct",
+                "enum",
+                "protocol",
+                "actor",
+                "typealias",
+                "associatedtype",
+                "let",
+                "var",
+            ],
+            Self::JavaScript => &[
+                "function",
+                "function*",
+                "class",
+                "interface",
+                "type",
+                "enum",
+                "namespace",
+                "const",
+                "let",
+                "var",
+            ],
+            Self::Python => &["def", "class"],
+            Self::Go => &["func", "type", "const", "var"],
+            Self::Jvm => &[
+                "class",
+                "interface",
+                "enum",
+                "record",
+                "object",
+                "trait",
+                "fun",
+                "def",
+                "val",
+                "var",
+            ],
+            Self::C => &[
+                "struct",
+                "class",
+                "enum",
+                "union",
+                "typedef",
+                "namespace",
+                "#define",
+                "@interface",
+                "@protocol",
+            ],
+            Self::Ruby => &["def", "class", "module"],
+        }
+    }
+}
+
+/// 定义种类，同时决定基础得分
+fn keyword_kind(keyword: &str) -> (&'static str, i32) {
+    match keyword {
+        "fn" | "func" | "function" | "function*" | "def" | "fun" | "macro_rules!" | "#define" => {
+            ("function", 90)
+        }
+        "const" | "static" | "let" | "var" | "val" => ("variable", 60),
+        _ => ("type", 100),
+    }
+}
+
+/// 单条候选定义
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefinitionCandidate {
+    pub path: String,
+    /// 行号（1-based）
+    pub line: u32,
+    /// 列号（0-based，符号在行内的字节偏移）
+    pub column: u32,
+    /// "type" | "function" | "variable"
+    pub kind: String,
+    pub preview: String,
+    pub score: i32,
+}
+
+/// 启发式定义查找结果
+#[derive(Debug, Clone, Default)]
+pub struct DefinitionGuessResult {
+    pub candidates: Vec<DefinitionCandidate>,
+    pub truncated: bool,
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// 判断单行是否为 `symbol` 的定义，返回 (种类, 基础得分, 列号)
+pub fn match_definition_line(
+    line: &str,
+    symbol: &str,
+    family: LanguageFamily,
+) -> Option<(&'static str, i32, u32)> {
+    if symbol.is_empty() {
+        return None;
+    }
+    let mut search_from = 0;
+    while let Some(offset) = line[search_from..].find(symbol) {
+        let pos = search_from + offset;
+        let end = pos + symbol.len();
+        search_from = end;
+
+        let before_ok = line[..pos]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !is_ident_char(c));
+        let after_ok = line[end..].chars().next().is_none_or(|c| !is_ident_char(c));
+        if !before_ok || !after_ok {
+            continue;
+        }
+
+        let prefix = line[..pos].trim_end();
+        // Go 方法：func (r *Recv) Name(
+        if family == LanguageFamily::Go {
+            let trimmed = prefix.trim_start();
+            if trimmed.starts_with("func (") && trimmed.ends_with(')') {
+                return Some(("function", 90, pos as u32));
+            }
+        }
+        let Some(last_token) = prefix.split_whitespace().next_back() else {
+            continue;
+        };
+        if family.keywords().contains(&last_token) {
+            let (kind, score) = keyword_kind(last_token);
+            return Some((kind, score, pos as u32));
+        }
+    }
+    None
+}
+
+fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+fn file_stem(path: &str) -> &str {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.split('.').next().unwrap_or(name)
+}
+
+/// 在给定候选文件（相对路径，通常来自文件索引）中查找 `symbol` 的定义
+///
+/// `from_path` 为发起跳转的文件：同语言族的文件优先，未知语言时扫描所有可识别源文件。
+/// 相关性加分：同文件 +30，同目录 +15，文件名与符号同名 +25，测试目录 -10。
+pub fn guess_definitions(
+    workspace_root: &Path,
+    files: &[String],
+    symbol: &str,
+    from_path: Option<&str>,
+) -> DefinitionGuessResult {
+    let symbol = symbol.trim();
+    if symbol.is_empty() || !symbol.chars().all(is_ident_char) {
+        return DefinitionGuessResult::default();
+    }
+    let from_family = from_path.and_then(LanguageFamily::from_path);
+    let from_dir = from_path.map(parent_dir);
+    let symbol_lower = symbol.to_lowercase();
+
+    let mut candidates = Vec::new();
+    let mut truncated = false;
+    let mut scanned = 0usize;
+
+    for rel_path in files {
+        // 请求被取消时停止扫描，调用方丢弃部分结果
+        if crate::util::cancel::is_cancelled() {
+            truncated = true;
+            break;
+        }
+        let Some(family) = LanguageFamily::from_path(rel_path) else {
+            continue;
+        };
+        if from_family.is_some_and(|f| f != family) {
+            continue;
+        }
+        if scanned >= MAX_DEFINITION_SCAN_FILES {
+            truncated = true;
+            break;
+        }
+        scanned += 1;
+
+        let abs_path = workspace_root.join(rel_path);
+        match std::fs::metadata(&abs_path) {
+            Ok(meta) if meta.is_file() && meta.len() <= MAX_FILE_SIZE => {}
+            _ => continue,
+        }
+        let Ok(text) = std::fs::read_to_string(&abs_path) else {
+            continue;
+        };
+        // 快速预筛：整文件不含符号直接跳过
+        if !text.contains(symbol) {
+            continue;
+        }
+
+        let mut bonus = 0;
+        if from_path == Some(rel_path.as_str()) {
+            bonus += 30;
+        } else if from_dir == Some(parent_dir(rel_path)) {
+            bonus += 15;
+        }
+        if file_stem(rel_path).to_lowercase() == symbol_lower {
+            bonus += 25;
+        }
+        if rel_path
+            .split('/')
+            .any(|seg| matches!(seg, "test" | "tests" | "__tests__" | "spec"))
+        {
+            bonus -= 10;
+        }
+
+        for (idx, line) in text.lines().enumerate() {
+            if let Some((kind, base, column)) = match_definition_line(line, symbol, family) {
+                candidates.push(DefinitionCandidate {
+                    path: rel_path.clone(),
+                    line: (idx + 1) as u32,
+                    column,
+                    kind: kind.to_string(),
+                    preview: line.trim().to_string(),
+                    score: base + bonus,
+                });
+            }
+        }
+    }
+
+    candidates.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.path.cmp(&b.path))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+    if candidates.len() > MAX_DEFINITION_CANDIDATES {
+        candidates.truncate(MAX_DEFINITION_CANDIDATES);
+        truncated = true;
+    }
+
+    DefinitionGuessResult {
+        candidates,
+        truncated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn match_definition_line_respects_language_keywords() {
+        let rust = LanguageFamily::Rust;
+        assert_eq!(
+            match_definition_line("pub(crate) fn load_config() {", "load_config", rust),
+            Some(("function", 90, 14))
+        );
+        assert_eq!(
+            match_definition_line("pub struct Config {", "Config", rust).map(|m| m.0),
+            Some("type")
+        );
+        // 调用点与子串不算定义
+        assert!(match_definition_line("let c = load_config();", "load_config", rust).is_none());
+        assert!(match_definition_line("fn load_config_v2() {}", "load_config", rust).is_none());
+
+        let go = LanguageFamily::Go;
+        assert_eq!(
+            match_definition_line("func (s *Server) Start() error {", "Start", go).map(|m| m.0),
+            Some("function")
+        );
+        let ts = LanguageFamily::JavaScript;
+        assert_eq!(
+            match_definition_line("export default async function render(x) {", "render", ts)
+                .map(|m| m.0),
+            Some("function")
+        );
+        assert_eq!(
+            match_definition_line("#define MAX_LEN 16", "MAX_LEN", LanguageFamily::C).map(|m| m.0),
+            Some("function")
+        );
+    }
+
+    #[test]
+    fn guess_definitions_ranks_by_relevance() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("src/config")).unwrap();
+        std::fs::create_dir_all(root.join("tests")).unwrap();
+        std::fs::write(root.join("src/config/loader.rs"), "pub struct Loader;\n").unwrap();
+        std::fs::write(
+            root.join("src/main.rs"),
+            "fn main() {\n    Loader::new();\n}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("tests/fixtures.rs"), "struct Loader;\n").unwrap();
+
+        let files = vec![
+            "src/config/loader.rs".to_string(),
+            "src/main.rs".to_string(),
+            "tests/fixtures.rs".to_string(),
+        ];
+        let result = guess_definitions(root, &files, "Loader", Some("src/main.rs"));
+        assert!(!result.truncated);
+        let paths: Vec<&str> = result.candidates.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/config/loader.rs", "tests/fixtures.rs"]);
+        assert_eq!(result.candidates[0].line, 1);
+        assert_eq!(result.candidates[0].kind, "type");
+        assert!(result.candidates[0].score > result.candidates[1].score);
+
+        // 非标识符符号直接返回空
+        assert!(guess_definitions(root, &files, "a b", None)
+            .candidates
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn cancelled_request_stops_scanning() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("lib.rs"), "pub fn load() {}\n").unwrap();
+        let root = temp.path().to_path_buf();
+        let guess = crate::util::cancel::scope("definition-cancel".to_string(), async move {
+            assert!(crate::util::cancel::cancel("definition-cancel"));
+            crate::util::trace::spawn_blocking(move || {
+                guess_definitions(&root, &["lib.rs".to_string()], "load", None)
+            })
+            .await
+            .unwrap()
+        })
+        .await;
+        assert!(guess.candidates.is_empty());
+        assert!(guess.truncated);
+    }
+}
@@ -0,0 +1,495 @@
+//! EditorConfig 解析与应用
+//!
+//! 从文件所在目录向上逐级查找 `.editorconfig`（遇到 `root = true` 或到达工作区根为止），
+//! 按"越靠近文件优先、同文件内越靠后优先"合并匹配段的属性。
+//! 解析结果供客户端编辑器对齐仓库约定；项目在 `.tidyflow.toml` 中开启
+//! `[editor] apply_editorconfig = true` 后，`file_write` 写入文本时会按规则规范化内容。
+
+use std::borrow::Cow;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::workspace::config::ProjectConfig;
+
+pub const EDITORCONFIG_FILE: &str = ".editorconfig";
+
+/// 单个文件解析后的 EditorConfig 设置（未声明的属性为 None）
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EditorConfigSettings {
+    /// "space" | "tab"
+    pub indent_style: Option<String>,
+    pub indent_size: Option<u32>,
+    pub tab_width: Option<u32>,
+    /// "lf" | "crlf" | "cr"
+    pub end_of_line: Option<String>,
+    /// "utf-8" | "utf-8-bom" | "latin1" | "utf-16be" | "utf-16le"
+    pub charset: Option<String>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub insert_final_newline: Option<bool>,
+    pub max_line_length: Option<u32>,
+    /// `indent_size = tab`：缩进宽度取 tab_width
+    indent_size_is_tab: bool,
+}
+
+/// 解析结果：合并后的设置与参与合并的配置文件（相对工作区根，由远及近）
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedEditorConfig {
+    pub settings: EditorConfigSettings,
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct EditorConfigFile {
+    root: bool,
+    sections: Vec<(String, Vec<(String, String)>)>,
+}
+
+fn parse_editorconfig(content: &str) -> EditorConfigFile {
+    let mut file = EditorConfigFile::default();
+    for raw in content.lines() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            file.sections
+                .push((line[1..line.len() - 1].to_string(), Vec::new()));
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().to_ascii_lowercase();
+        let value = value.trim().to_string();
+        match file.sections.last_mut() {
+            Some((_, props)) => props.push((key, value)),
+            None if key == "root" => file.root = value.eq_ignore_ascii_case("true"),
+            None => {}
+        }
+    }
+    file
+}
+
+/// 展开 `{n1..n2}` 数字区间为候选列表（区间过大时退化为任意整数）
+fn numeric_range_regex(inner: &str) -> Option<String> {
+    let (start, end) = inner.split_once("..")?;
+    let start: i64 = start.parse().ok()?;
+    let end: i64 = end.parse().ok()?;
+    let (lo, hi) = if start <= end {
+        (start, end)
+    } else {
+        (end, start)
+    };
+    if hi - lo > 1000 {
+        return Some(r"[+-]?\d+".to_string());
+    }
+    let alts: Vec<String> = (lo..=hi).map(|n| n.to_string()).collect();
+    Some(format!("(?:{})", alts.join("|")))
+}
+
+/// 将 EditorConfig glob 转换为正则
+///
+/// `*` 不跨目录、`**` 跨目录、`?` 单字符、`[...]`/`[!...]` 字符集、
+/// `{a,b}` 候选、`{1..3}` 数字区间；不含 `/` 的模式可匹配任意层级的文件名。
+pub fn glob_to_regex(glob: &str) -> Option<Regex> {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    let mut brace_depth = 0usize;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if i + 1 < chars.len() => {
+                out.push_str(&regex::escape(&chars[i + 1].to_string()));
+                i += 1;
+            }
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                out.push_str(".*");
+                i += 1;
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            '[' => match chars[i + 1..].iter().position(|&ch| ch == ']') {
+                Some(len) => {
+                    let class: String = chars[i + 1..i + 1 + len].iter().collect();
+                    let class = match class.strip_prefix('!') {
+                        Some(rest) => format!("^{}", rest),
+                        None => class,
+                    };
+                    out.push('[');
+                    out.push_str(&class.replace('\\', "\\\\"));
+                    out.push(']');
+                    i += len + 1;
+                }
+                None => out.push_str(r"\["),
+            },
+            '{' => {
+                let close = chars[i + 1..].iter().position(|&ch| ch == '}');
+                let inner: Option<String> =
+                    close.map(|len| chars[i + 1..i + 1 + len].iter().collect());
+                if let Some(range) = inner.as_deref().and_then(numeric_range_regex) {
+                    out.push_str(&range);
+                    i += close.unwrap_or(0) + 1;
+                } else if inner.as_deref().is_some_and(|s| s.contains(',')) {
+                    out.push_str("(?:");
+                    brace_depth += 1;
+                } else {
+                    out.push_str(r"\{");
+                }
+            }
+            ',' if brace_depth > 0 => out.push('|'),
+            '}' if brace_depth > 0 => {
+                out.push(')');
+                brace_depth -= 1;
+            }
+            _ => out.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    for _ in 0..brace_depth {
+        out.push(')');
+    }
+
+    let anchored = if glob.contains('/') {
+        format!("^{}$", out.trim_start_matches('/'))
+    } else {
+        format!("^(?:.*/)?{}$", out)
+    };
+    Regex::new(&anchored).ok()
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+impl EditorConfigSettings {
+    fn set(&mut self, key: &str, value: &str) {
+        // 取值非法或为 `unset` 时统一清除该属性
+        let lower = value.to_ascii_lowercase();
+        match key {
+            "indent_style" => {
+                self.indent_style = matches!(lower.as_str(), "space" | "tab").then_some(lower)
+            }
+            "indent_size" => {
+                self.indent_size_is_tab = lower == "tab";
+                self.indent_size = lower.parse().ok();
+            }
+            "tab_width" => self.tab_width = lower.parse().ok(),
+            "end_of_line" => {
+                self.end_of_line = matches!(lower.as_str(), "lf" | "crlf" | "cr").then_some(lower)
+            }
+            "charset" => {
+                self.charset = matches!(
+                    lower.as_str(),
+                    "utf-8" | "utf-8-bom" | "latin1" | "utf-16be" | "utf-16le"
+                )
+                .then_some(lower)
+            }
+            "trim_trailing_whitespace" => self.trim_trailing_whitespace = parse_bool(&lower),
+            "insert_final_newline" => self.insert_final_newline = parse_bool(&lower),
+            "max_line_length" => self.max_line_length = lower.parse().ok(),
+            _ => {}
+        }
+    }
+
+    /// 按规范补全派生值：tab_width 默认等于 indent_size，反之亦然
+    fn finalize(&mut self) {
+        if self.tab_width.is_none() {
+            self.tab_width = self.indent_size;
+        }
+        if self.indent_size.is_none()
+            && (self.indent_size_is_tab || self.indent_style.as_deref() == Some("tab"))
+        {
+            self.indent_size = self.tab_width;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// 解析工作区内某个文件（相对路径）的 EditorConfig 设置
+pub fn resolve_editorconfig(workspace_root: &Path, relative_path: &str) -> ResolvedEditorConfig {
+    let relative_path = relative_path
+        .trim_start_matches("./")
+        .trim_start_matches('/');
+
+    // 收集从文件所在目录到工作区根的各级目录（由近及远）
+    let mut dirs: Vec<String> = Vec::new();
+    let mut current = relative_path;
+    while let Some((parent, _)) = current.rsplit_once('/') {
+        dirs.push(parent.to_string());
+        current = parent;
+    }
+    dirs.push(String::new());
+
+    let mut chain = Vec::new();
+    for dir in dirs {
+        let config_path = workspace_root.join(&dir).join(EDITORCONFIG_FILE);
+        let Ok(content) = std::fs::read_to_string(&config_path) else {
+            continue;
+        };
+        let parsed = parse_editorconfig(&content);
+        let is_root = parsed.root;
+        chain.push((dir, parsed));
+        if is_root {
+            break;
+        }
+    }
+
+    let mut resolved = ResolvedEditorConfig::default();
+    for (dir, file) in chain.into_iter().rev() {
+        let path_in_dir = if dir.is_empty() {
+            relative_path
+        } else {
+            relative_path
+                .strip_prefix(dir.as_str())
+                .map(|rest| rest.trim_start_matches('/'))
+                .unwrap_or(relative_path)
+        };
+        let mut matched = false;
+        for (glob, props) in &file.sections {
+            if glob_to_regex(glob).is_some_and(|re| re.is_match(path_in_dir)) {
+                matched = true;
+                for (key, value) in props {
+                    resolved.settings.set(key, value);
+                }
+            }
+        }
+        if matched {
+            resolved.sources.push(if dir.is_empty() {
+                EDITORCONFIG_FILE.to_string()
+            } else {
+                format!("{}/{}", dir, EDITORCONFIG_FILE)
+            });
+        }
+    }
+    resolved.settings.finalize();
+    resolved
+}
+
+fn reindent<'a>(line: &'a str, style: &str, tab_width: usize) -> Cow<'a, str> {
+    let indent_len = line.len() - line.trim_start_matches([' ', '\t']).len();
+    let indent = &line[..indent_len];
+    let needs_change = match style {
+        "space" => indent.contains('\t'),
+        _ => indent.contains(' ') && indent.chars().filter(|&c| c == ' ').count() >= tab_width,
+    };
+    if !needs_change {
+        return Cow::Borrowed(line);
+    }
+    let width = indent.chars().fold(0usize, |col, c| match c {
+        '\t' => (col / tab_width + 1) * tab_width,
+        _ => col + 1,
+    });
+    let new_indent = if style == "space" {
+        " ".repeat(width)
+    } else {
+        format!(
+            "{}{}",
+            "\t".repeat(width / tab_width),
+            " ".repeat(width % tab_width)
+        )
+    };
+    Cow::Owned(format!("{}{}", new_indent, &line[indent_len..]))
+}
+
+/// 按 EditorConfig 规则规范化文本
+///
+/// `reindent` 为 false 时只处理换行符、行尾空白、文件末尾换行与 BOM，
+/// 用于格式化器输出的后处理（缩进交由格式化器决定）。
+pub fn apply_to_content<'a>(
+    settings: &EditorConfigSettings,
+    content: &'a str,
+    reindent_lines: bool,
+) -> Cow<'a, str> {
+    if settings.is_empty() {
+        return Cow::Borrowed(content);
+    }
+    let (had_bom, body) = match content.strip_prefix('\u{feff}') {
+        Some(rest) => (true, rest),
+        None => (false, content),
+    };
+    let eol = match settings.end_of_line.as_deref() {
+        Some("crlf") => Some("\r\n"),
+        Some("cr") => Some("\r"),
+        Some("lf") => Some("\n"),
+        _ => None,
+    };
+    let indent_style = settings.indent_style.as_deref().filter(|_| reindent_lines);
+    let tab_width = settings
+        .tab_width
+        .or(settings.indent_size)
+        .unwrap_or(4)
+        .max(1) as usize;
+    let trim = settings.trim_trailing_whitespace == Some(true);
+
+    let mut out = String::with_capacity(body.len() + 16);
+    let mut rest = body;
+    while !rest.is_empty() {
+        let (line, ending, next) = match rest.find(['\r', '\n']) {
+            Some(pos) if rest[pos..].starts_with("\r\n") => {
+                (&rest[..pos], "\r\n", &rest[pos + 2..])
+            }
+            Some(pos) => (&rest[..pos], &rest[pos..pos + 1], &rest[pos + 1..]),
+            None => (rest, "", ""),
+        };
+        let mut line = Cow::Borrowed(line);
+        if let Some(style) = indent_style {
+            if let Cow::Owned(changed) = reindent(&line, style, tab_width) {
+                line = Cow::Owned(changed);
+            }
+        }
+        if trim {
+            let trimmed = line.trim_end_matches([' ', '\t']);
+            if trimmed.len() != line.len() {
+                line = Cow::Owned(trimmed.to_string());
+            }
+        }
+        out.push_str(&line);
+        if !ending.is_empty() {
+            out.push_str(eol.unwrap_or(ending));
+        }
+        rest = next;
+    }
+
+    if settings.insert_final_newline == Some(true)
+        && !out.is_empty()
+        && !out.ends_with(['\n', '\r'])
+    {
+        let fallback = if body.contains("\r\n") { "\r\n" } else { "\n" };
+        out.push_str(eol.unwrap_or(fallback));
+    }
+
+    let with_bom = match settings.charset.as_deref() {
+        Some("utf-8-bom") => true,
+        Some("utf-8") => false,
+        _ => had_bom,
+    };
+    if with_bom {
+        out.insert(0, '\u{feff}');
+    }
+
+    if out == content {
+        Cow::Borrowed(content)
+    } else {
+        Cow::Owned(out)
+    }
+}
+
+/// 项目是否开启了写入时应用 EditorConfig
+pub fn apply_on_write_enabled(workspace_root: &Path) -> bool {
+    ProjectConfig::load(workspace_root)
+        .map(|config| config.editor.apply_editorconfig)
+        .unwrap_or(false)
+}
+
+/// 写入前规范化文本：项目未开启或无匹配规则时原样返回
+pub fn normalize_for_write<'a>(
+    workspace_root: &Path,
+    relative_path: &str,
+    content: &'a str,
+) -> Cow<'a, str> {
+    if !apply_on_write_enabled(workspace_root) {
+        return Cow::Borrowed(content);
+    }
+    let resolved = resolve_editorconfig(workspace_root, relative_path);
+    apply_to_content(&resolved.settings, content, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn glob_to_regex_follows_editorconfig_rules() {
+        let matches = |glob: &str, path: &str| glob_to_regex(glob).unwrap().is_match(path);
+        assert!(matches("*", "src/main.rs"));
+        assert!(matches("*.{js,ts}", "web/app.ts"));
+        assert!(!matches("*.{js,ts}", "web/app.rs"));
+        assert!(matches("lib/**.py", "lib/a/b/c.py"));
+        assert!(!matches("lib/*.py", "lib/a/c.py"));
+        assert!(matches("Makefile", "tools/Makefile"));
+        assert!(matches("file[0-9].txt", "file3.txt"));
+        assert!(matches("v{1..3}.md", "docs/v2.md"));
+        assert!(!matches("v{1..3}.md", "docs/v4.md"));
+    }
+
+    #[test]
+    fn resolve_merges_nested_files_and_stops_at_root() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("web/src")).unwrap();
+        std::fs::write(
+            root.join(EDITORCONFIG_FILE),
+            "root = true\n\n[*]\nindent_style = space\nindent_size = 4\nend_of_line = lf\n\n[Makefile]\nindent_style = tab\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("web/.editorconfig"),
+            "[*.ts]\nindent_size = 2\ninsert_final_newline = true\n",
+        )
+        .unwrap();
+
+        let resolved = resolve_editorconfig(root, "web/src/app.ts");
+        assert_eq!(resolved.settings.indent_style.as_deref(), Some("space"));
+        assert_eq!(resolved.settings.indent_size, Some(2));
+        assert_eq!(resolved.settings.tab_width, Some(2));
+        assert_eq!(resolved.settings.insert_final_newline, Some(true));
+        assert_eq!(
+            resolved.sources,
+            vec![".editorconfig".to_string(), "web/.editorconfig".to_string()]
+        );
+
+        let make = resolve_editorconfig(root, "Makefile");
+        assert_eq!(make.settings.indent_style.as_deref(), Some("tab"));
+        assert!(resolve_editorconfig(TempDir::new().unwrap().path(), "a.rs")
+            .settings
+            .is_empty());
+    }
+
+    #[test]
+    fn apply_to_content_normalizes_whitespace() {
+        let settings = EditorConfigSettings {
+            indent_style: Some("space".to_string()),
+            indent_size: Some(2),
+            tab_width: Some(2),
+            end_of_line: Some("lf".to_string()),
+            charset: Some("utf-8".to_string()),
+            trim_trailing_whitespace: Some(true),
+            insert_final_newline: Some(true),
+            ..Default::default()
+        };
+        let input = "\u{feff}fn a() {  \r\n\tbody();\t\r\n}";
+        assert_eq!(
+            apply_to_content(&settings, input, true),
+            "fn a() {\n  body();\n}\n"
+        );
+        // 格式化后处理不改缩进
+        assert_eq!(
+            apply_to_content(&settings, input, false),
+            "fn a() {\n\tbody();\n}\n"
+        );
+
+        let tabs = EditorConfigSettings {
+            indent_style: Some("tab".to_string()),
+            tab_width: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(
+            apply_to_content(&tabs, "        x\n  y\n", true),
+            "\t\tx\n  y\n"
+        );
+        assert!(matches!(
+            apply_to_content(&tabs, "\tok\n", true),
+            Cow::Borrowed(_)
+        ));
+    }
+}
@@ -1,17 +1,38 @@
 //! File API for workspace file operations
 //!
 //! Provides secure file list/read/write within workspace boundaries.
+//! 远程（SSH/SFTP）项目经 [`provider::FileProvider`] 访问，见 `provider` / `sftp` 子模块。
 
-use std::collections::HashSet;
+pub mod provider;
+mod sftp;
+
+use crate::server::ignore_rules::{self, IgnoreRules};
 use std::fs;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use tracing::{debug, warn};
 
 /// Maximum file size: 1MB
 pub const MAX_FILE_SIZE: u64 = 1_048_576;
 
+/// 二进制读写（`encoding = "binary"`）的大小上限：8MB
+pub const MAX_BINARY_FILE_SIZE: u64 = 8 * 1_048_576;
+
+/// 分块读取的默认块大小：256KB
+pub const DEFAULT_CHUNK_SIZE: u64 = 256 * 1024;
+
+/// 分块读取的单块上限（与整文件读取上限一致）
+pub const MAX_CHUNK_SIZE: u64 = MAX_FILE_SIZE;
+
+/// 项目可配置的文本文件大小上限（`[editor] max_file_size`）的最大值：16MB
+pub const MAX_CONFIGURABLE_FILE_SIZE: u64 = 16 * 1_048_576;
+
+/// 预览资源（图片、字体等）经 HTTP `/asset` 直出的文件大小上限：32MB
+pub const MAX_ASSET_SIZE: u64 = 32 * 1_048_576;
+
+/// 二进制嗅探检查的头部字节数（与 git 判断二进制的范围一致）
+const BINARY_SNIFF_LEN: usize = 8000;
+
 /// Maximum path length
 pub const MAX_PATH_LENGTH: usize = 4096;
 
@@ -21,7 +42,7 @@ pub struct FileEntry {
     pub name: String,
     pub is_dir: bool,
     pub size: u64,
-    /// 是否被 .gitignore 忽略
+    /// 是否被 .gitignore / .ignore 忽略
     pub is_ignored: bool,
     /// 是否为符号链接
     pub is_symlink: bool,
@@ -44,6 +65,10 @@ pub enum FileApiError {
     TrashError(String),
     /// v1.25: 不能将目录移入自身
     MoveIntoSelf,
+    /// 受保护路径（工作区根目录、`.git` 元数据）禁止删除
+    ProtectedPath(String),
+    /// 请求的字节区间超出文件范围（携带文件总大小）
+    RangeNotSatisfiable(u64),
 }
 
 impl std::fmt::Display for FileApiError {
@@ -52,13 +77,17 @@ impl std::fmt::Display for FileApiError {
             FileApiError::PathEscape => write!(f, "Path escapes workspace root"),
             FileApiError::PathTooLong => write!(f, "Path exceeds maximum length"),
             FileApiError::FileNotFound => write!(f, "File not found"),
-            FileApiError::FileTooLarge => write!(f, "File exceeds 1MB limit"),
+            FileApiError::FileTooLarge => write!(f, "File exceeds size limit"),
             FileApiError::InvalidUtf8 => write!(f, "File is not valid UTF-8"),
             FileApiError::IoError(e) => write!(f, "I/O error: {}", e),
             FileApiError::TargetExists => write!(f, "Target file already exists"),
             FileApiError::InvalidName(reason) => write!(f, "Invalid file name: {}", reason),
             FileApiError::TrashError(msg) => write!(f, "Trash error: {}", msg),
             FileApiError::MoveIntoSelf => write!(f, "Cannot move directory into itself"),
+            FileApiError::ProtectedPath(reason) => write!(f, "Protected path: {}", reason),
+            FileApiError::RangeNotSatisfiable(size) => {
+                write!(f, "Requested range not satisfiable (file size {})", size)
+            }
         }
     }
 }
@@ -84,6 +113,9 @@ pub fn resolve_safe_path(
         return Err(FileApiError::PathTooLong);
     }
 
+    // 开发模式下的故障注入（见 util::chaos）
+    crate::util::chaos::fs_fault()?;
+
     // Normalize the relative path (handle . and ..)
     let mut components = Vec::new();
     for component in relative_path.split(['/', '\\']) {
@@ -134,45 +166,6 @@ pub fn resolve_safe_path(
     }
 }
 
-/// 批量检查文件是否被 git 忽略
-/// 返回被忽略的文件名集合
-fn get_ignored_files(dir_path: &Path, file_names: &[String]) -> HashSet<String> {
-    if file_names.is_empty() {
-        return HashSet::new();
-    }
-
-    // 构建相对路径列表（相对于 dir_path）
-    let input = file_names.join("\n");
-
-    let output = match Command::new("git")
-        .args(["check-ignore", "--stdin"])
-        .current_dir(dir_path)
-        .stdin(std::process::Stdio::piped())
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::null())
-        .spawn()
-    {
-        Ok(mut child) => {
-            if let Some(stdin) = child.stdin.as_mut() {
-                let _ = stdin.write_all(input.as_bytes());
-            }
-            match child.wait_with_output() {
-                Ok(o) => o,
-                Err(_) => return HashSet::new(),
-            }
-        }
-        Err(_) => return HashSet::new(),
-    };
-
-    // git check-ignore 返回被忽略的文件，每行一个
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    stdout
-        .lines()
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .collect()
-}
-
 /// List files in a directory within workspace
 pub fn list_files(
     workspace_root: &Path,
@@ -204,15 +197,23 @@ pub fn list_files(
         raw_entries.push((name, metadata.is_dir(), metadata.len(), is_symlink));
     }
 
-    // 批量检查 git 忽略状态
-    let file_names: Vec<String> = raw_entries.iter().map(|(n, _, _, _)| n.clone()).collect();
-    let ignored_set = get_ignored_files(&dir_path, &file_names);
+    // 忽略状态与文件索引共用同一套 .gitignore / .ignore 规则；位于被忽略目录中的条目一律视为忽略
+    let rel_dir = relative_path.trim_matches('/');
+    let rel_dir = if rel_dir == "." { "" } else { rel_dir };
+    let dir_ignored =
+        !rel_dir.is_empty() && ignore_rules::is_path_ignored(workspace_root, rel_dir, true);
+    let rules = IgnoreRules::for_dir(workspace_root, rel_dir);
 
     // 构建最终结果
     let mut entries: Vec<FileEntry> = raw_entries
         .into_iter()
         .map(|(name, is_dir, len, is_symlink)| {
-            let is_ignored = ignored_set.contains(&name);
+            let rel = if rel_dir.is_empty() {
+                name.clone()
+            } else {
+                format!("{}/{}", rel_dir, name)
+            };
+            let is_ignored = dir_ignored || rules.is_ignored(&rel, is_dir);
             FileEntry {
                 name,
                 is_dir,
@@ -223,20 +224,33 @@ pub fn list_files(
         })
         .collect();
 
-    // Sort: directories first, then alphabetically
+    sort_file_entries(&mut entries);
+
+    Ok(entries)
+}
+
+/// Sort: directories first, then alphabetically
+fn sort_file_entries(entries: &mut [FileEntry]) {
     entries.sort_by(|a, b| match (a.is_dir, b.is_dir) {
         (true, false) => std::cmp::Ordering::Less,
         (false, true) => std::cmp::Ordering::Greater,
         _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
     });
-
-    Ok(entries)
 }
 
 /// Read file content as UTF-8 string
 pub fn read_file(
     workspace_root: &Path,
     relative_path: &str,
+) -> Result<(String, u64), FileApiError> {
+    read_file_with_limit(workspace_root, relative_path, MAX_FILE_SIZE)
+}
+
+/// 以指定大小上限读取 UTF-8 文本（项目配置了 `max_file_size` 时使用）
+pub fn read_file_with_limit(
+    workspace_root: &Path,
+    relative_path: &str,
+    max_size: u64,
 ) -> Result<(String, u64), FileApiError> {
     let file_path = resolve_safe_path(workspace_root, relative_path)?;
 
@@ -244,7 +258,7 @@ pub fn read_file(
 
     // Check file size
     let metadata = fs::metadata(&file_path)?;
-    if metadata.len() > MAX_FILE_SIZE {
+    if metadata.len() > max_size {
         return Err(FileApiError::FileTooLarge);
     }
 
@@ -261,7 +275,7 @@ pub fn read_file(
 }
 
 /// 读取二进制文件（不做 UTF-8 校验）
-/// 用于图片等非文本文件
+/// 用于图片等非文本文件，上限为 `MAX_BINARY_FILE_SIZE`
 pub fn read_file_binary(
     workspace_root: &Path,
     relative_path: &str,
@@ -271,7 +285,7 @@ pub fn read_file_binary(
     debug!("Reading binary file: {:?}", file_path);
 
     let metadata = fs::metadata(&file_path)?;
-    if metadata.len() > MAX_FILE_SIZE {
+    if metadata.len() > MAX_BINARY_FILE_SIZE {
         return Err(FileApiError::FileTooLarge);
     }
 
@@ -283,11 +297,217 @@ pub fn read_file_binary(
     Ok((content, size))
 }
 
+/// 只读预览超出大小上限的文本文件：返回前 `max_size` 字节内的完整行
+///
+/// 头部按魔数 / NUL 字节嗅探为二进制，或不是合法 UTF-8 时返回 `FileTooLarge`（无法预览）。
+/// 返回 (预览内容, 文件总大小)。
+pub fn read_text_preview(
+    workspace_root: &Path,
+    relative_path: &str,
+    max_size: u64,
+) -> Result<(String, u64), FileApiError> {
+    let file_path = resolve_safe_path(workspace_root, relative_path)?;
+    let metadata = fs::metadata(&file_path)?;
+    if !metadata.is_file() {
+        return Err(FileApiError::FileNotFound);
+    }
+
+    let mut head = Vec::with_capacity(max_size.min(metadata.len()) as usize);
+    fs::File::open(&file_path)?
+        .take(max_size)
+        .read_to_end(&mut head)?;
+    if is_binary_content(&head) {
+        return Err(FileApiError::FileTooLarge);
+    }
+
+    // 截断处可能落在多字节字符中间，只容忍末尾不完整的序列
+    let valid_len = match std::str::from_utf8(&head) {
+        Ok(_) => head.len(),
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => return Err(FileApiError::FileTooLarge),
+    };
+    head.truncate(valid_len);
+    // 尽量在行尾截断；单行文件（如 .min.js）保留截断处之前的全部内容
+    if (head.len() as u64) < metadata.len() {
+        if let Some(pos) = head.iter().rposition(|&b| b == b'\n') {
+            head.truncate(pos + 1);
+        }
+    }
+    let text = String::from_utf8(head).map_err(|_| FileApiError::InvalidUtf8)?;
+    Ok((text, metadata.len()))
+}
+
+/// 按字节区间读取文件（分块读取，不受整文件大小上限约束）
+///
+/// `length` 会被截断到 `MAX_CHUNK_SIZE`；`offset` 超过文件末尾时返回空内容。
+/// 返回 (内容, 文件总大小)。
+pub fn read_file_range(
+    workspace_root: &Path,
+    relative_path: &str,
+    offset: u64,
+    length: u64,
+) -> Result<(Vec<u8>, u64), FileApiError> {
+    use std::io::{Seek, SeekFrom};
+
+    let file_path = resolve_safe_path(workspace_root, relative_path)?;
+    let metadata = fs::metadata(&file_path)?;
+    if !metadata.is_file() {
+        return Err(FileApiError::FileNotFound);
+    }
+    let total_size = metadata.len();
+    if offset >= total_size {
+        return Ok((Vec::new(), total_size));
+    }
+
+    let length = length.min(MAX_CHUNK_SIZE).min(total_size - offset);
+    let mut file = fs::File::open(&file_path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut content = Vec::with_capacity(length as usize);
+    file.take(length).read_to_end(&mut content)?;
+
+    Ok((content, total_size))
+}
+
+/// HTTP `Range` 请求的单个字节区间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+    /// `bytes=start-` 或 `bytes=start-end`（含两端）
+    From(u64, Option<u64>),
+    /// `bytes=-n`：最后 n 个字节
+    Suffix(u64),
+}
+
+impl ByteRange {
+    /// 解析 `Range` 请求头；多区间与非 bytes 单位不支持，返回 None（按整文件响应）
+    pub fn parse(header: &str) -> Option<Self> {
+        let spec = header.trim().strip_prefix("bytes=")?.trim();
+        if spec.contains(',') {
+            return None;
+        }
+        let (start, end) = spec.split_once('-')?;
+        let (start, end) = (start.trim(), end.trim());
+        if start.is_empty() {
+            return end.parse().ok().map(Self::Suffix);
+        }
+        let start = start.parse().ok()?;
+        let end = if end.is_empty() {
+            None
+        } else {
+            Some(end.parse().ok()?)
+        };
+        Some(Self::From(start, end))
+    }
+
+    /// 按文件大小解析为含两端的 `(start, end)`；无法满足时返回 None
+    pub fn resolve(self, size: u64) -> Option<(u64, u64)> {
+        if size == 0 {
+            return None;
+        }
+        match self {
+            Self::From(start, end) => {
+                let end = end.unwrap_or(u64::MAX).min(size - 1);
+                (start <= end).then_some((start, end))
+            }
+            Self::Suffix(0) => None,
+            Self::Suffix(len) => Some((size.saturating_sub(len), size - 1)),
+        }
+    }
+}
+
+/// 读取预览资源，返回 (内容, 文件总大小, 实际返回的区间)
+///
+/// 文件不得超过 `MAX_ASSET_SIZE`；`range` 为 None 时返回整个文件。
+pub fn read_asset(
+    workspace_root: &Path,
+    relative_path: &str,
+    range: Option<ByteRange>,
+) -> Result<(Vec<u8>, u64, Option<(u64, u64)>), FileApiError> {
+    use std::io::{Seek, SeekFrom};
+
+    let file_path = resolve_safe_path(workspace_root, relative_path)?;
+    let metadata = fs::metadata(&file_path)?;
+    if !metadata.is_file() {
+        return Err(FileApiError::FileNotFound);
+    }
+    let size = metadata.len();
+    if size > MAX_ASSET_SIZE {
+        return Err(FileApiError::FileTooLarge);
+    }
+    let range = match range {
+        Some(range) => Some(
+            range
+                .resolve(size)
+                .ok_or(FileApiError::RangeNotSatisfiable(size))?,
+        ),
+        None => None,
+    };
+    let (start, end) = range.unwrap_or((0, size.saturating_sub(1)));
+    let length = if size == 0 { 0 } else { end - start + 1 };
+
+    let mut file = fs::File::open(&file_path)?;
+    file.seek(SeekFrom::Start(start))?;
+    let mut content = Vec::with_capacity(length as usize);
+    file.take(length).read_to_end(&mut content)?;
+    Ok((content, size, range))
+}
+
+/// 内容哈希：git blob SHA-1（与 `git hash-object` 一致），用于写入前的版本校验
+pub fn content_hash(content: &[u8]) -> String {
+    gix::objs::compute_hash(gix::hash::Kind::Sha1, gix::objs::Kind::Blob, content)
+        .map(|id| id.to_string())
+        .unwrap_or_default()
+}
+
+/// 文件修改时间（Unix 毫秒）；文件不存在或无法获取时为 None
+pub fn file_mtime(workspace_root: &Path, relative_path: &str) -> Option<u64> {
+    let file_path = resolve_safe_path(workspace_root, relative_path).ok()?;
+    fs::metadata(file_path)
+        .ok()?
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_millis() as u64)
+}
+
 /// Write file content atomically
 pub fn write_file(
     workspace_root: &Path,
     relative_path: &str,
     content: &str,
+) -> Result<u64, FileApiError> {
+    write_bytes_atomically(
+        workspace_root,
+        relative_path,
+        content.as_bytes(),
+        MAX_FILE_SIZE,
+    )
+}
+
+/// 写入已编码的文本字节（如转回 GBK 的内容），上限由调用方按项目配置给出
+pub fn write_text_bytes(
+    workspace_root: &Path,
+    relative_path: &str,
+    content: &[u8],
+    max_size: u64,
+) -> Result<u64, FileApiError> {
+    write_bytes_atomically(workspace_root, relative_path, content, max_size)
+}
+
+/// 原样写入二进制内容（不做 UTF-8 校验），上限为 `MAX_BINARY_FILE_SIZE`
+pub fn write_file_binary(
+    workspace_root: &Path,
+    relative_path: &str,
+    content: &[u8],
+) -> Result<u64, FileApiError> {
+    write_bytes_atomically(workspace_root, relative_path, content, MAX_BINARY_FILE_SIZE)
+}
+
+fn write_bytes_atomically(
+    workspace_root: &Path,
+    relative_path: &str,
+    content: &[u8],
+    max_size: u64,
 ) -> Result<u64, FileApiError> {
     let file_path = resolve_safe_path(workspace_root, relative_path)?;
 
@@ -295,7 +515,7 @@ pub fn write_file(
 
     // Check content size
     let size = content.len() as u64;
-    if size > MAX_FILE_SIZE {
+    if size > max_size {
         return Err(FileApiError::FileTooLarge);
     }
 
@@ -308,7 +528,7 @@ pub fn write_file(
     let temp_path = file_path.with_extension("tmp");
     {
         let mut temp_file = fs::File::create(&temp_path)?;
-        temp_file.write_all(content.as_bytes())?;
+        temp_file.write_all(content)?;
         temp_file.sync_all()?;
     }
 
@@ -318,6 +538,79 @@ pub fn write_file(
     Ok(size)
 }
 
+/// 内容魔数与对应的 MIME 类型
+const MIME_SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"\x00asm", "application/wasm"),
+];
+
+/// 只用于二进制嗅探的魔数（可执行文件、数据库、压缩包等）
+const BINARY_SIGNATURES: &[&[u8]] = &[
+    b"\x7fELF",
+    b"\xcf\xfa\xed\xfe",
+    b"\xca\xfe\xba\xbe",
+    b"SQLite format 3\x00",
+    b"7z\xbc\xaf\x27\x1c",
+    b"Rar!\x1a\x07",
+    b"\x28\xb5\x2f\xfd",
+];
+
+fn is_webp(content: &[u8]) -> bool {
+    content.len() >= 12 && &content[0..4] == b"RIFF" && &content[8..12] == b"WEBP"
+}
+
+/// 按魔数与头部 NUL 字节嗅探二进制内容
+///
+/// 带 UTF-16 BOM 的内容虽含 NUL 字节，仍按文本处理。
+pub fn is_binary_content(content: &[u8]) -> bool {
+    if is_webp(content)
+        || MIME_SIGNATURES
+            .iter()
+            .any(|(magic, _)| content.starts_with(magic))
+        || BINARY_SIGNATURES
+            .iter()
+            .any(|magic| content.starts_with(magic))
+    {
+        return true;
+    }
+    if content.starts_with(b"\xff\xfe") || content.starts_with(b"\xfe\xff") {
+        return false;
+    }
+    content[..content.len().min(BINARY_SNIFF_LEN)].contains(&0)
+}
+
+/// 检测文件 MIME 类型：优先按内容魔数嗅探，其次按扩展名推断
+///
+/// 扩展名无法识别时，合法 UTF-8 内容视为 `text/plain`，否则为 `application/octet-stream`。
+pub fn detect_mime_type(relative_path: &str, content: &[u8]) -> String {
+    if is_webp(content) {
+        return "image/webp".to_string();
+    }
+    if let Some((_, mime)) = MIME_SIGNATURES
+        .iter()
+        .find(|(magic, _)| content.starts_with(magic))
+    {
+        // zip 容器（docx/xlsx/jar 等）以扩展名为准
+        if *mime != "application/zip" {
+            return mime.to_string();
+        }
+    }
+    if let Some(guess) = mime_guess::from_path(relative_path).first() {
+        return guess.essence_str().to_string();
+    }
+    if std::str::from_utf8(content).is_ok() {
+        "text/plain".to_string()
+    } else {
+        "application/octet-stream".to_string()
+    }
+}
+
 /// v1.23: 验证文件名是否有效
 fn validate_filename(name: &str) -> Result<(), FileApiError> {
     // 不能为空
@@ -402,15 +695,111 @@ pub fn delete_file(workspace_root: &Path, path: &str) -> Result<(), FileApiError
     if !full_path.exists() {
         return Err(FileApiError::FileNotFound);
     }
+    // resolve_safe_path 返回跟随符号链接后的路径；删除链接时只应移除链接本身
+    let lexical_path = workspace_root.join(path.trim_start_matches(['/', '\\']));
+    let is_symlink = !path.split(['/', '\\']).any(|seg| seg == "..")
+        && fs::symlink_metadata(&lexical_path)
+            .map(|m| m.file_type().is_symlink())
+            .unwrap_or(false);
+    let target = if is_symlink { lexical_path } else { full_path };
+    ensure_deletable(workspace_root, &target, is_symlink)?;
 
-    debug!("Moving to trash: {:?}", full_path);
+    debug!("Moving to trash: {:?}", target);
 
     // 使用 trash crate 移到回收站
-    trash::delete(&full_path).map_err(|e| FileApiError::TrashError(e.to_string()))?;
+    trash::delete(&target).map_err(|e| FileApiError::TrashError(e.to_string()))?;
 
     Ok(())
 }
 
+/// 递归删除前的安全检查
+///
+/// - 不允许删除工作区根目录（空路径、`.` 或 `a/..` 都会解析到根）
+/// - 不允许删除 `.git` 及其内部内容（worktree 中 `.git` 为文件，同样保护）
+/// - 符号链接按链接本身处理，不会跟随到目标目录
+fn ensure_deletable(
+    workspace_root: &Path,
+    target: &Path,
+    is_symlink: bool,
+) -> Result<(), FileApiError> {
+    let root_canonical = workspace_root.canonicalize()?;
+    let relative = if is_symlink {
+        target
+            .strip_prefix(workspace_root)
+            .map_err(|_| FileApiError::PathEscape)?
+            .to_path_buf()
+    } else {
+        target
+            .canonicalize()?
+            .strip_prefix(&root_canonical)
+            .map_err(|_| FileApiError::PathEscape)?
+            .to_path_buf()
+    };
+    if relative.as_os_str().is_empty() {
+        return Err(FileApiError::ProtectedPath(
+            "不能删除工作区根目录".to_string(),
+        ));
+    }
+    if relative
+        .components()
+        .any(|c| c.as_os_str() == std::ffi::OsStr::new(".git"))
+    {
+        return Err(FileApiError::ProtectedPath(
+            "不能删除 .git 元数据".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// 创建目录（含缺失的父目录）
+///
+/// 目标已存在时返回 `TargetExists`；每个路径段都需通过文件名校验。
+/// 返回规范化后的相对路径。
+pub fn create_directory(workspace_root: &Path, path: &str) -> Result<String, FileApiError> {
+    let segments: Vec<&str> = path
+        .split(['/', '\\'])
+        .filter(|s| !s.is_empty() && *s != ".")
+        .collect();
+    if segments.is_empty() {
+        return Err(FileApiError::InvalidName("目录路径不能为空".to_string()));
+    }
+    for segment in &segments {
+        validate_filename(segment)?;
+    }
+
+    let relative = segments.join("/");
+    let full_path = resolve_safe_path(workspace_root, &relative)?;
+    if full_path.exists() {
+        return Err(FileApiError::TargetExists);
+    }
+
+    // 创建前先复核最近的已存在祖先：防止经由符号链接父目录在工作区之外创建任何目录
+    let mut existing = 0;
+    while existing < segments.len()
+        && workspace_root
+            .join(segments[..=existing].join("/"))
+            .exists()
+    {
+        existing += 1;
+    }
+    let root_canonical = workspace_root.canonicalize()?;
+    let mut current = workspace_root
+        .join(segments[..existing].join("/"))
+        .canonicalize()?;
+    if !current.starts_with(&root_canonical) {
+        return Err(FileApiError::PathEscape);
+    }
+
+    debug!("Creating directory: {:?}", full_path);
+    // 只逐级创建缺失部分，且基于已规范化的祖先路径
+    for segment in &segments[existing..] {
+        current.push(segment);
+        fs::create_dir(&current)?;
+    }
+
+    Ok(relative)
+}
+
 /// v1.24: 生成不冲突的文件名
 /// 如果目标路径已存在，添加 (1), (2) 等后缀
 fn generate_unique_path(dest_dir: &Path, name: &str) -> PathBuf {
@@ -639,4 +1028,239 @@ mod tests {
         assert_eq!(entries[0].name, "test.txt");
         assert!(!entries[0].is_dir);
     }
+
+    #[test]
+    fn test_list_files_marks_ignored_entries() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("venv/lib")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join(".gitignore"), "venv/\n*.pyc\n").unwrap();
+        fs::write(root.join("src/app.py"), "").unwrap();
+        fs::write(root.join("src/app.pyc"), "").unwrap();
+        fs::write(root.join("venv/lib/site.py"), "").unwrap();
+
+        let ignored = |dir: &str| -> Vec<(String, bool)> {
+            list_files(root, dir)
+                .unwrap()
+                .into_iter()
+                .map(|e| (e.name, e.is_ignored))
+                .collect()
+        };
+        assert_eq!(
+            ignored(""),
+            vec![
+                ("src".to_string(), false),
+                ("venv".to_string(), true),
+                (".gitignore".to_string(), false),
+            ]
+        );
+        assert_eq!(
+            ignored("src"),
+            vec![("app.py".to_string(), false), ("app.pyc".to_string(), true)]
+        );
+        assert_eq!(ignored("venv/lib"), vec![("site.py".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_delete_rejects_protected_paths() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join(".git/refs")).unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+
+        for path in ["", ".", "src/..", ".git", ".git/refs"] {
+            assert!(
+                matches!(delete_file(root, path), Err(FileApiError::ProtectedPath(_))),
+                "path {:?} should be protected",
+                path
+            );
+        }
+        assert!(root.join(".git/refs").is_dir());
+    }
+
+    #[test]
+    fn test_create_directory() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+
+        assert_eq!(create_directory(root, "a/b/./c/").unwrap(), "a/b/c");
+        assert!(root.join("a/b/c").is_dir());
+        assert!(matches!(
+            create_directory(root, "a/b"),
+            Err(FileApiError::TargetExists)
+        ));
+        assert!(matches!(
+            create_directory(root, "../outside"),
+            Err(FileApiError::InvalidName(_))
+        ));
+        assert!(matches!(
+            create_directory(root, "/./"),
+            Err(FileApiError::InvalidName(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_directory_rejects_symlinked_parent_outside_workspace() {
+        let temp = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let root = temp.path();
+        std::os::unix::fs::symlink(outside.path(), root.join("link")).unwrap();
+
+        assert!(matches!(
+            create_directory(root, "link/a/b"),
+            Err(FileApiError::PathEscape)
+        ));
+        assert_eq!(std::fs::read_dir(outside.path()).unwrap().count(), 0);
+
+        // 指向工作区内部的符号链接仍可使用
+        std::fs::create_dir(root.join("inner")).unwrap();
+        std::os::unix::fs::symlink(root.join("inner"), root.join("inner_link")).unwrap();
+        assert_eq!(
+            create_directory(root, "inner_link/x/y").unwrap(),
+            "inner_link/x/y"
+        );
+        assert!(root.join("inner/x/y").is_dir());
+    }
+
+    #[test]
+    fn test_read_file_range() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::write(root.join("log.txt"), b"0123456789").unwrap();
+
+        let (chunk, total) = read_file_range(root, "log.txt", 2, 4).unwrap();
+        assert_eq!(chunk, b"2345");
+        assert_eq!(total, 10);
+
+        let (tail, _) = read_file_range(root, "log.txt", 8, 100).unwrap();
+        assert_eq!(tail, b"89");
+
+        let (empty, _) = read_file_range(root, "log.txt", 10, 4).unwrap();
+        assert!(empty.is_empty());
+
+        std::fs::create_dir(root.join("dir")).unwrap();
+        assert!(matches!(
+            read_file_range(root, "dir", 0, 4),
+            Err(FileApiError::FileNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_read_asset_ranges() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::write(root.join("font.woff2"), b"0123456789").unwrap();
+
+        assert_eq!(
+            ByteRange::parse("bytes=2-5"),
+            Some(ByteRange::From(2, Some(5)))
+        );
+        assert_eq!(ByteRange::parse("bytes=7-"), Some(ByteRange::From(7, None)));
+        assert_eq!(ByteRange::parse("bytes=-3"), Some(ByteRange::Suffix(3)));
+        assert_eq!(ByteRange::parse("bytes=0-1,4-5"), None);
+        assert_eq!(ByteRange::parse("items=0-1"), None);
+
+        let (all, size, range) = read_asset(root, "font.woff2", None).unwrap();
+        assert_eq!(
+            (all.as_slice(), size, range),
+            (&b"0123456789"[..], 10, None)
+        );
+
+        let (part, _, range) =
+            read_asset(root, "font.woff2", ByteRange::parse("bytes=2-5")).unwrap();
+        assert_eq!((part.as_slice(), range), (&b"2345"[..], Some((2, 5))));
+
+        let (tail, _, range) =
+            read_asset(root, "font.woff2", ByteRange::parse("bytes=-3")).unwrap();
+        assert_eq!((tail.as_slice(), range), (&b"789"[..], Some((7, 9))));
+
+        let (clamped, _, _) =
+            read_asset(root, "font.woff2", ByteRange::parse("bytes=8-99")).unwrap();
+        assert_eq!(clamped, b"89");
+
+        assert!(matches!(
+            read_asset(root, "font.woff2", ByteRange::parse("bytes=10-")),
+            Err(FileApiError::RangeNotSatisfiable(10))
+        ));
+        assert!(matches!(
+            read_asset(root, "../escape.png", None),
+            Err(FileApiError::PathEscape)
+        ));
+    }
+
+    #[test]
+    fn test_read_text_preview() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::write(root.join("data.json"), "[1,\n2,\n3]\n").unwrap();
+        std::fs::write(root.join("app.min.js"), "var 变量=1;".repeat(4)).unwrap();
+        std::fs::write(root.join("lib.so"), b"\x7fELF\x02\x01\x01rest").unwrap();
+
+        assert!(matches!(
+            read_file_with_limit(root, "data.json", 8),
+            Err(FileApiError::FileTooLarge)
+        ));
+        // 多行文本在最后一个完整行处截断
+        assert_eq!(
+            read_text_preview(root, "data.json", 8).unwrap(),
+            ("[1,\n2,\n".to_string(), 10)
+        );
+        // 单行文本不会截断在多字节字符中间
+        let (preview, total) = read_text_preview(root, "app.min.js", 18).unwrap();
+        assert_eq!(preview, "var 变量=1;var ");
+        assert_eq!(total, 52);
+        assert!(matches!(
+            read_text_preview(root, "lib.so", 4),
+            Err(FileApiError::FileTooLarge)
+        ));
+
+        assert!(is_binary_content(b"\x89PNG\r\n\x1a\n"));
+        assert!(is_binary_content(b"text\x00more"));
+        assert!(!is_binary_content(b"\xff\xfea\x00"));
+        assert!(!is_binary_content("普通文本".as_bytes()));
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+
+        let content: Vec<u8> = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0xff];
+        let size = write_file_binary(root, "img/logo.png", &content).unwrap();
+        assert_eq!(size, content.len() as u64);
+
+        assert!(matches!(
+            read_file(root, "img/logo.png"),
+            Err(FileApiError::InvalidUtf8)
+        ));
+        let (read_back, read_size) = read_file_binary(root, "img/logo.png").unwrap();
+        assert_eq!(read_back, content);
+        assert_eq!(read_size, size);
+
+        let oversized = vec![0u8; (MAX_BINARY_FILE_SIZE + 1) as usize];
+        assert!(matches!(
+            write_file_binary(root, "big.bin", &oversized),
+            Err(FileApiError::FileTooLarge)
+        ));
+    }
+
+    #[test]
+    fn test_detect_mime_type() {
+        assert_eq!(
+            detect_mime_type("a.bin", b"\x89PNG\r\n\x1a\nrest"),
+            "image/png"
+        );
+        assert_eq!(detect_mime_type("doc.pdf", b"%PDF-1.7"), "application/pdf");
+        assert_eq!(
+            detect_mime_type("index.html", b"<html></html>"),
+            "text/html"
+        );
+        assert_eq!(detect_mime_type("LICENSE", b"MIT"), "text/plain");
+        assert_eq!(
+            detect_mime_type("blob", &[0xff, 0xfe, 0x00]),
+            "application/octet-stream"
+        );
+    }
 }
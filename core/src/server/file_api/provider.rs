@@ -0,0 +1,286 @@
+//! 文件提供者：本地与远程（SSH/SFTP）项目共用的文件 / git 访问抽象
+//!
+//! 项目根目录写作 `ssh://[user@]host[:port]/abs/path`（或 `sftp://...`）时视为远程项目：
+//! 列目录、读写文件经 SFTP 子系统完成，git 命令经 `ssh` 在远端执行。
+//! 认证、跳板机（ProxyJump）等均沿用本机 OpenSSH 配置，Core 不保存任何凭据。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::sync::Arc;
+
+use super::{FileApiError, FileEntry, MAX_BINARY_FILE_SIZE, MAX_PATH_LENGTH};
+use crate::util::exec_env::git_command;
+
+/// 项目文件与 git 的访问入口
+pub trait FileProvider: Send + Sync {
+    /// `local` | `sftp`
+    fn kind(&self) -> &'static str;
+
+    /// 列出目录（相对根目录的路径，`""` / `"."` 为根目录）
+    fn list_files(&self, relative_path: &str) -> Result<Vec<FileEntry>, FileApiError>;
+
+    /// 读取文件原始字节；文件大于 `max_size` 时返回 `FileTooLarge`
+    fn read_bytes(&self, relative_path: &str, max_size: u64) -> Result<Vec<u8>, FileApiError>;
+
+    /// 原子写入（先写临时文件再重命名），返回写入的字节数
+    fn write_bytes(
+        &self,
+        relative_path: &str,
+        content: &[u8],
+        max_size: u64,
+    ) -> Result<u64, FileApiError>;
+
+    /// 在项目根目录执行 git 并收集输出
+    fn git_output(&self, args: &[&str]) -> Result<Output, FileApiError>;
+
+    /// 读取 UTF-8 文本，返回 (内容, 字节数)
+    fn read_file_with_limit(
+        &self,
+        relative_path: &str,
+        max_size: u64,
+    ) -> Result<(String, u64), FileApiError> {
+        let bytes = self.read_bytes(relative_path, max_size)?;
+        let text = String::from_utf8(bytes).map_err(|_| FileApiError::InvalidUtf8)?;
+        let size = text.len() as u64;
+        Ok((text, size))
+    }
+
+    /// 读取二进制内容，上限为 `MAX_BINARY_FILE_SIZE`
+    fn read_file_binary(&self, relative_path: &str) -> Result<(Vec<u8>, u64), FileApiError> {
+        let bytes = self.read_bytes(relative_path, MAX_BINARY_FILE_SIZE)?;
+        let size = bytes.len() as u64;
+        Ok((bytes, size))
+    }
+}
+
+/// 本地文件系统（委托给 `file_api` 的既有实现）
+pub struct LocalFileProvider {
+    root: PathBuf,
+}
+
+impl LocalFileProvider {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl FileProvider for LocalFileProvider {
+    fn kind(&self) -> &'static str {
+        "local"
+    }
+
+    fn list_files(&self, relative_path: &str) -> Result<Vec<FileEntry>, FileApiError> {
+        super::list_files(&self.root, relative_path)
+    }
+
+    fn read_bytes(&self, relative_path: &str, max_size: u64) -> Result<Vec<u8>, FileApiError> {
+        let file_path = super::resolve_safe_path(&self.root, relative_path)?;
+        let metadata = fs::metadata(&file_path)?;
+        if !metadata.is_file() {
+            return Err(FileApiError::FileNotFound);
+        }
+        if metadata.len() > max_size {
+            return Err(FileApiError::FileTooLarge);
+        }
+        Ok(fs::read(&file_path)?)
+    }
+
+    fn write_bytes(
+        &self,
+        relative_path: &str,
+        content: &[u8],
+        max_size: u64,
+    ) -> Result<u64, FileApiError> {
+        super::write_bytes_atomically(&self.root, relative_path, content, max_size)
+    }
+
+    fn git_output(&self, args: &[&str]) -> Result<Output, FileApiError> {
+        Ok(git_command(&self.root).args(args).output()?)
+    }
+}
+
+/// 远程项目根目录：`ssh://[user@]host[:port]/abs/path`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RemoteRoot {
+    /// 传给 ssh 的目标（`user@host` 或 `~/.ssh/config` 中的 Host 别名）
+    pub destination: String,
+    pub port: Option<u16>,
+    /// 远端路径（不含末尾 `/`）；URL 中以 `/~/` 开头时为相对登录用户主目录的路径
+    pub path: String,
+}
+
+impl RemoteRoot {
+    /// 解析远程根目录；不是 `ssh://` / `sftp://` URL 时返回 None
+    pub fn parse(root: &str) -> Option<Self> {
+        let scheme_end = root.find("://")?;
+        let scheme = &root[..scheme_end];
+        if !scheme.eq_ignore_ascii_case("ssh") && !scheme.eq_ignore_ascii_case("sftp") {
+            return None;
+        }
+        let url = url::Url::parse(root).ok()?;
+        let host = url.host_str().filter(|host| !host.is_empty())?;
+        // 以 `-` 开头的用户名或主机会被 ssh 当作选项（如 `-oProxyCommand=...`）
+        if host.starts_with('-') || url.username().starts_with('-') {
+            return None;
+        }
+        let destination = if url.username().is_empty() {
+            host.to_string()
+        } else {
+            format!("{}@{}", url.username(), host)
+        };
+        let decoded = percent_decode(url.path());
+        // SFTP 与 ssh 执行的相对路径都以登录用户主目录为基准
+        let path = match decoded.trim_end_matches('/') {
+            "" => "/".to_string(),
+            "/~" => ".".to_string(),
+            trimmed => trimmed.strip_prefix("/~/").unwrap_or(trimmed).to_string(),
+        };
+        Some(Self {
+            destination,
+            port: url.port(),
+            path,
+        })
+    }
+
+    pub fn from_root(root: &Path) -> Option<Self> {
+        Self::parse(&root.to_string_lossy())
+    }
+
+    /// 将相对路径解析为远端绝对路径，拒绝越过根目录
+    ///
+    /// 远端不做 canonicalize：ssh 用户本就可访问整个远端文件系统，这里只防止请求路径越界。
+    pub fn resolve(&self, relative_path: &str) -> Result<String, FileApiError> {
+        if relative_path.len() > MAX_PATH_LENGTH {
+            return Err(FileApiError::PathTooLong);
+        }
+        let mut components = Vec::new();
+        for component in relative_path.split(['/', '\\']) {
+            match component {
+                "" | "." => continue,
+                ".." => {
+                    if components.pop().is_none() {
+                        return Err(FileApiError::PathEscape);
+                    }
+                }
+                c => components.push(c),
+            }
+        }
+        if components.is_empty() {
+            return Ok(self.path.clone());
+        }
+        let base = self.path.trim_end_matches('/');
+        Ok(format!("{}/{}", base, components.join("/")))
+    }
+}
+
+fn percent_decode(raw: &str) -> String {
+    let bytes = raw.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(value) = raw
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                out.push(value);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// 根目录是否指向远程项目
+pub fn is_remote_root(root: &Path) -> bool {
+    RemoteRoot::from_root(root).is_some()
+}
+
+/// 按项目根目录选择文件提供者；远程项目会复用（必要时建立）到该主机的 SFTP 会话
+pub fn provider_for_root(root: &Path) -> Result<Arc<dyn FileProvider>, FileApiError> {
+    match RemoteRoot::from_root(root) {
+        Some(remote) => Ok(Arc::new(super::sftp::SftpFileProvider::connect(remote)?)),
+        None => Ok(Arc::new(LocalFileProvider::new(root))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_remote_roots() {
+        let remote = RemoteRoot::parse("ssh://deploy@jump.example.com:2222/srv/app/").unwrap();
+        assert_eq!(remote.destination, "deploy@jump.example.com");
+        assert_eq!(remote.port, Some(2222));
+        assert_eq!(remote.path, "/srv/app");
+
+        let remote = RemoteRoot::parse("sftp://devbox/~/code/my%20app").unwrap();
+        assert_eq!(remote.destination, "devbox");
+        assert_eq!(remote.port, None);
+        assert_eq!(remote.path, "code/my app");
+        assert_eq!(RemoteRoot::parse("ssh://devbox").unwrap().path, "/");
+
+        for root in [
+            "/home/me/proj",
+            r"C:\proj",
+            "https://example.com/x",
+            "ssh:///x",
+            "ssh://-oProxyCommand=touch%20pwned@devbox/srv",
+            "sftp://-oProxyCommand=touch/srv",
+            "ssh://-p2222/srv",
+        ] {
+            assert!(RemoteRoot::parse(root).is_none(), "{}", root);
+        }
+        assert!(!is_remote_root(Path::new("/tmp")));
+    }
+
+    #[test]
+    fn resolve_rejects_escape() {
+        let remote = RemoteRoot::parse("ssh://devbox/srv/app").unwrap();
+        assert_eq!(remote.resolve("").unwrap(), "/srv/app");
+        assert_eq!(
+            remote.resolve("./src/../lib/a.rs").unwrap(),
+            "/srv/app/lib/a.rs"
+        );
+        assert!(matches!(
+            remote.resolve("src/../../etc/passwd"),
+            Err(FileApiError::PathEscape)
+        ));
+
+        let home = RemoteRoot::parse("ssh://devbox/~").unwrap();
+        assert_eq!(home.resolve("a.txt").unwrap(), "./a.txt");
+    }
+
+    #[test]
+    fn local_provider_reads_and_writes_within_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = provider_for_root(dir.path()).unwrap();
+        assert_eq!(provider.kind(), "local");
+
+        assert_eq!(provider.write_bytes("a.txt", b"hello", 16).unwrap(), 5);
+        assert_eq!(
+            provider.read_file_with_limit("a.txt", 16).unwrap().0,
+            "hello"
+        );
+        assert!(matches!(
+            provider.read_bytes("a.txt", 4),
+            Err(FileApiError::FileTooLarge)
+        ));
+        assert!(matches!(
+            provider.read_bytes("../a.txt", 16),
+            Err(FileApiError::PathEscape)
+        ));
+        let names: Vec<_> = provider
+            .list_files("")
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        assert_eq!(names, ["a.txt"]);
+    }
+}
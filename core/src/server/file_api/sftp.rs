@@ -0,0 +1,889 @@
+//! SFTP 文件提供者
+//!
+//! 经本机 `ssh -s -- <destination> sftp` 打开 SFTP 子系统，实现一个最小的 SFTP v3 客户端：
+//! 只覆盖文件提供者需要的目录列举、stat、整文件读写、重命名与删除。
+//! 请求串行发送、逐个等待响应；同一主机复用一条会话，连接断开后下次访问自动重连。
+//! git 命令经 `ssh -- <destination> git -C <root> ...` 在远端执行。
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Read, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Output, Stdio};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use tracing::{debug, warn};
+
+use super::provider::{FileProvider, RemoteRoot};
+use super::{FileApiError, FileEntry};
+
+const SSH_FXP_INIT: u8 = 1;
+const SSH_FXP_VERSION: u8 = 2;
+const SSH_FXP_OPEN: u8 = 3;
+const SSH_FXP_CLOSE: u8 = 4;
+const SSH_FXP_READ: u8 = 5;
+const SSH_FXP_WRITE: u8 = 6;
+const SSH_FXP_OPENDIR: u8 = 11;
+const SSH_FXP_READDIR: u8 = 12;
+const SSH_FXP_REMOVE: u8 = 13;
+const SSH_FXP_STAT: u8 = 17;
+const SSH_FXP_RENAME: u8 = 18;
+const SSH_FXP_STATUS: u8 = 101;
+const SSH_FXP_HANDLE: u8 = 102;
+const SSH_FXP_DATA: u8 = 103;
+const SSH_FXP_NAME: u8 = 104;
+const SSH_FXP_ATTRS: u8 = 105;
+const SSH_FXP_EXTENDED: u8 = 200;
+
+const SSH_FXF_READ: u32 = 0x01;
+const SSH_FXF_WRITE: u32 = 0x02;
+const SSH_FXF_CREAT: u32 = 0x08;
+const SSH_FXF_TRUNC: u32 = 0x10;
+
+const SSH_FX_OK: u32 = 0;
+const SSH_FX_EOF: u32 = 1;
+const SSH_FX_NO_SUCH_FILE: u32 = 2;
+const SSH_FX_PERMISSION_DENIED: u32 = 3;
+
+const SSH_FILEXFER_ATTR_SIZE: u32 = 0x01;
+const SSH_FILEXFER_ATTR_UIDGID: u32 = 0x02;
+const SSH_FILEXFER_ATTR_PERMISSIONS: u32 = 0x04;
+const SSH_FILEXFER_ATTR_ACMODTIME: u32 = 0x08;
+const SSH_FILEXFER_ATTR_EXTENDED: u32 = 0x8000_0000;
+
+const S_IFMT: u32 = 0o170000;
+const S_IFDIR: u32 = 0o040000;
+const S_IFLNK: u32 = 0o120000;
+
+/// 单次 READ / WRITE 的数据块大小（OpenSSH 服务端上限为 256KB，取常用的 32KB）
+const IO_CHUNK_SIZE: u32 = 32 * 1024;
+
+/// 响应包长度上限，防止异常数据导致超大分配
+const MAX_PACKET_LEN: usize = 4 * 1024 * 1024;
+
+/// 文件属性（只解析用到的字段）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct FileAttrs {
+    pub(super) size: Option<u64>,
+    pub(super) permissions: Option<u32>,
+}
+
+impl FileAttrs {
+    fn is_dir(&self) -> bool {
+        self.permissions
+            .is_some_and(|mode| mode & S_IFMT == S_IFDIR)
+    }
+
+    fn is_symlink(&self) -> bool {
+        self.permissions
+            .is_some_and(|mode| mode & S_IFMT == S_IFLNK)
+    }
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_u64(buf: &mut Vec<u8>, value: u64) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_bytes(buf: &mut Vec<u8>, value: &[u8]) {
+    put_u32(buf, value.len() as u32);
+    buf.extend_from_slice(value);
+}
+
+fn protocol_error(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// 响应体游标
+struct Cursor<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.buf.len())
+            .ok_or_else(|| protocol_error("truncated SFTP packet"))?;
+        let slice = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(((self.u32()? as u64) << 32) | self.u32()? as u64)
+    }
+
+    fn bytes(&mut self) -> io::Result<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    fn attrs(&mut self) -> io::Result<FileAttrs> {
+        let flags = self.u32()?;
+        let mut attrs = FileAttrs::default();
+        if flags & SSH_FILEXFER_ATTR_SIZE != 0 {
+            attrs.size = Some(self.u64()?);
+        }
+        if flags & SSH_FILEXFER_ATTR_UIDGID != 0 {
+            self.take(8)?;
+        }
+        if flags & SSH_FILEXFER_ATTR_PERMISSIONS != 0 {
+            attrs.permissions = Some(self.u32()?);
+        }
+        if flags & SSH_FILEXFER_ATTR_ACMODTIME != 0 {
+            self.take(8)?;
+        }
+        if flags & SSH_FILEXFER_ATTR_EXTENDED != 0 {
+            for _ in 0..self.u32()? {
+                self.bytes()?;
+                self.bytes()?;
+            }
+        }
+        Ok(attrs)
+    }
+}
+
+/// 将 SSH_FXP_STATUS 响应转换为结果：`SSH_FX_OK` 为 Ok，其余映射为 io 错误
+fn status_result(body: &[u8]) -> io::Result<u32> {
+    let mut cursor = Cursor::new(body);
+    let code = cursor.u32()?;
+    if code == SSH_FX_OK || code == SSH_FX_EOF {
+        return Ok(code);
+    }
+    let message = cursor
+        .bytes()
+        .map(|raw| String::from_utf8_lossy(raw).into_owned())
+        .unwrap_or_default();
+    let kind = match code {
+        SSH_FX_NO_SUCH_FILE => io::ErrorKind::NotFound,
+        SSH_FX_PERMISSION_DENIED => io::ErrorKind::PermissionDenied,
+        _ => io::ErrorKind::Other,
+    };
+    Err(io::Error::new(
+        kind,
+        format!("SFTP status {}: {}", code, message),
+    ))
+}
+
+/// SFTP v3 通道（与传输无关，便于用内存管道测试）
+pub(super) struct SftpChannel<R, W> {
+    reader: R,
+    writer: W,
+    next_id: u32,
+}
+
+impl<R: Read, W: Write> SftpChannel<R, W> {
+    /// 完成 INIT / VERSION 握手
+    pub(super) fn open(reader: R, writer: W) -> io::Result<Self> {
+        let mut channel = Self {
+            reader,
+            writer,
+            next_id: 1,
+        };
+        let mut payload = Vec::new();
+        put_u32(&mut payload, 3);
+        channel.write_packet(SSH_FXP_INIT, &payload)?;
+        let (kind, _) = channel.read_packet()?;
+        if kind != SSH_FXP_VERSION {
+            return Err(protocol_error(format!(
+                "expected SSH_FXP_VERSION, got {}",
+                kind
+            )));
+        }
+        Ok(channel)
+    }
+
+    fn write_packet(&mut self, kind: u8, payload: &[u8]) -> io::Result<()> {
+        let mut packet = Vec::with_capacity(payload.len() + 5);
+        put_u32(&mut packet, payload.len() as u32 + 1);
+        packet.push(kind);
+        packet.extend_from_slice(payload);
+        self.writer.write_all(&packet)?;
+        self.writer.flush()
+    }
+
+    fn read_packet(&mut self) -> io::Result<(u8, Vec<u8>)> {
+        let mut header = [0u8; 4];
+        self.reader.read_exact(&mut header)?;
+        let len = u32::from_be_bytes(header) as usize;
+        if len == 0 || len > MAX_PACKET_LEN {
+            return Err(protocol_error(format!(
+                "invalid SFTP packet length {}",
+                len
+            )));
+        }
+        let mut packet = vec![0u8; len];
+        self.reader.read_exact(&mut packet)?;
+        let kind = packet[0];
+        packet.remove(0);
+        Ok((kind, packet))
+    }
+
+    /// 发送带请求 id 的请求并等待对应响应，返回 (响应类型, 去掉 id 的响应体)
+    fn request(&mut self, kind: u8, payload: &[u8]) -> io::Result<(u8, Vec<u8>)> {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        let mut body = Vec::with_capacity(payload.len() + 4);
+        put_u32(&mut body, id);
+        body.extend_from_slice(payload);
+        self.write_packet(kind, &body)?;
+
+        let (reply_kind, reply) = self.read_packet()?;
+        let reply_id = Cursor::new(&reply).u32()?;
+        if reply_id != id {
+            return Err(protocol_error(format!(
+                "SFTP response id mismatch: expected {}, got {}",
+                id, reply_id
+            )));
+        }
+        Ok((reply_kind, reply[4..].to_vec()))
+    }
+
+    fn expect_status(&mut self, kind: u8, payload: &[u8]) -> io::Result<()> {
+        match self.request(kind, payload)? {
+            (SSH_FXP_STATUS, body) => status_result(&body).map(|_| ()),
+            (other, _) => Err(protocol_error(format!("unexpected SFTP reply {}", other))),
+        }
+    }
+
+    fn expect_handle(&mut self, kind: u8, payload: &[u8]) -> io::Result<Vec<u8>> {
+        match self.request(kind, payload)? {
+            (SSH_FXP_HANDLE, body) => Ok(Cursor::new(&body).bytes()?.to_vec()),
+            (SSH_FXP_STATUS, body) => {
+                status_result(&body)?;
+                Err(protocol_error("SFTP open returned no handle"))
+            }
+            (other, _) => Err(protocol_error(format!("unexpected SFTP reply {}", other))),
+        }
+    }
+
+    fn close(&mut self, handle: &[u8]) -> io::Result<()> {
+        let mut payload = Vec::new();
+        put_bytes(&mut payload, handle);
+        self.expect_status(SSH_FXP_CLOSE, &payload)
+    }
+
+    /// 跟随符号链接的 stat
+    pub(super) fn stat(&mut self, path: &str) -> io::Result<FileAttrs> {
+        let mut payload = Vec::new();
+        put_bytes(&mut payload, path.as_bytes());
+        match self.request(SSH_FXP_STAT, &payload)? {
+            (SSH_FXP_ATTRS, body) => Cursor::new(&body).attrs(),
+            (SSH_FXP_STATUS, body) => {
+                status_result(&body)?;
+                Err(io::Error::from(io::ErrorKind::NotFound))
+            }
+            (other, _) => Err(protocol_error(format!("unexpected SFTP reply {}", other))),
+        }
+    }
+
+    /// 列出目录项（不含 `.` / `..`），属性不跟随符号链接
+    pub(super) fn read_dir(&mut self, path: &str) -> io::Result<Vec<(String, FileAttrs)>> {
+        let mut payload = Vec::new();
+        put_bytes(&mut payload, path.as_bytes());
+        let handle = self.expect_handle(SSH_FXP_OPENDIR, &payload)?;
+
+        let mut read_payload = Vec::new();
+        put_bytes(&mut read_payload, &handle);
+        let mut entries = Vec::new();
+        let result = loop {
+            match self.request(SSH_FXP_READDIR, &read_payload) {
+                Ok((SSH_FXP_NAME, body)) => {
+                    let mut cursor = Cursor::new(&body);
+                    let parsed = (|| -> io::Result<()> {
+                        for _ in 0..cursor.u32()? {
+                            let name = String::from_utf8_lossy(cursor.bytes()?).into_owned();
+                            let _long_name = cursor.bytes()?;
+                            let attrs = cursor.attrs()?;
+                            if name != "." && name != ".." {
+                                entries.push((name, attrs));
+                            }
+                        }
+                        Ok(())
+                    })();
+                    if let Err(e) = parsed {
+                        break Err(e);
+                    }
+                }
+                Ok((SSH_FXP_STATUS, body)) => match status_result(&body) {
+                    Ok(SSH_FX_EOF) => break Ok(()),
+                    Ok(_) => break Err(protocol_error("SFTP readdir returned OK without data")),
+                    Err(e) => break Err(e),
+                },
+                Ok((other, _)) => {
+                    break Err(protocol_error(format!("unexpected SFTP reply {}", other)))
+                }
+                Err(e) => break Err(e),
+            }
+        };
+        let closed = self.close(&handle);
+        result?;
+        closed?;
+        Ok(entries)
+    }
+
+    /// 读取整个文件，最多读取 `limit + 1` 字节（调用方据此判断超限）
+    pub(super) fn read_file(&mut self, path: &str, limit: u64) -> io::Result<Vec<u8>> {
+        let mut payload = Vec::new();
+        put_bytes(&mut payload, path.as_bytes());
+        put_u32(&mut payload, SSH_FXF_READ);
+        put_u32(&mut payload, 0);
+        let handle = self.expect_handle(SSH_FXP_OPEN, &payload)?;
+
+        let mut content = Vec::new();
+        let result = loop {
+            if content.len() as u64 > limit {
+                break Ok(());
+            }
+            let mut read_payload = Vec::new();
+            put_bytes(&mut read_payload, &handle);
+            put_u64(&mut read_payload, content.len() as u64);
+            put_u32(&mut read_payload, IO_CHUNK_SIZE);
+            match self.request(SSH_FXP_READ, &read_payload) {
+                Ok((SSH_FXP_DATA, body)) => match Cursor::new(&body).bytes() {
+                    Ok(data) => content.extend_from_slice(data),
+                    Err(e) => break Err(e),
+                },
+                Ok((SSH_FXP_STATUS, body)) => match status_result(&body) {
+                    Ok(_) => break Ok(()),
+                    Err(e) => break Err(e),
+                },
+                Ok((other, _)) => {
+                    break Err(protocol_error(format!("unexpected SFTP reply {}", other)))
+                }
+                Err(e) => break Err(e),
+            }
+        };
+        let closed = self.close(&handle);
+        result?;
+        closed?;
+        Ok(content)
+    }
+
+    /// 创建或截断文件并写入全部内容
+    pub(super) fn write_file(&mut self, path: &str, content: &[u8]) -> io::Result<()> {
+        let mut payload = Vec::new();
+        put_bytes(&mut payload, path.as_bytes());
+        put_u32(&mut payload, SSH_FXF_WRITE | SSH_FXF_CREAT | SSH_FXF_TRUNC);
+        put_u32(&mut payload, 0);
+        let handle = self.expect_handle(SSH_FXP_OPEN, &payload)?;
+
+        let mut result = Ok(());
+        for (index, chunk) in content.chunks(IO_CHUNK_SIZE as usize).enumerate() {
+            let mut write_payload = Vec::with_capacity(chunk.len() + handle.len() + 16);
+            put_bytes(&mut write_payload, &handle);
+            put_u64(&mut write_payload, index as u64 * IO_CHUNK_SIZE as u64);
+            put_bytes(&mut write_payload, chunk);
+            result = self.expect_status(SSH_FXP_WRITE, &write_payload);
+            if result.is_err() {
+                break;
+            }
+        }
+        let closed = self.close(&handle);
+        result?;
+        closed
+    }
+
+    /// 覆盖式重命名：优先 `posix-rename@openssh.com`，不支持时退回 REMOVE + RENAME
+    pub(super) fn rename_overwrite(&mut self, from: &str, to: &str) -> io::Result<()> {
+        let mut payload = Vec::new();
+        put_bytes(&mut payload, b"posix-rename@openssh.com");
+        put_bytes(&mut payload, from.as_bytes());
+        put_bytes(&mut payload, to.as_bytes());
+        if self.expect_status(SSH_FXP_EXTENDED, &payload).is_ok() {
+            return Ok(());
+        }
+
+        match self.remove(to) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        let mut payload = Vec::new();
+        put_bytes(&mut payload, from.as_bytes());
+        put_bytes(&mut payload, to.as_bytes());
+        self.expect_status(SSH_FXP_RENAME, &payload)
+    }
+
+    pub(super) fn remove(&mut self, path: &str) -> io::Result<()> {
+        let mut payload = Vec::new();
+        put_bytes(&mut payload, path.as_bytes());
+        self.expect_status(SSH_FXP_REMOVE, &payload)
+    }
+}
+
+/// POSIX shell 单引号转义（ssh 会把远端命令参数拼接后交给登录 shell 解析）
+pub(super) fn shell_quote(arg: &str) -> String {
+    let is_safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./=:,@%+".contains(c));
+    if is_safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// 连接该远端的 ssh 基础命令（非交互；Unix 下复用 ControlMaster 连接）
+fn ssh_command(remote: &RemoteRoot) -> Command {
+    let mut cmd = Command::new("ssh");
+    cmd.args(["-o", "BatchMode=yes"]);
+    #[cfg(unix)]
+    {
+        let control_dir = crate::util::paths::tidyflow_home_dir().join("ssh");
+        if std::fs::create_dir_all(&control_dir).is_ok() {
+            cmd.args(["-o", "ControlMaster=auto", "-o", "ControlPersist=60"])
+                .arg("-o")
+                .arg(format!("ControlPath={}/%C", control_dir.display()));
+        }
+    }
+    if let Some(port) = remote.port {
+        cmd.arg("-p").arg(port.to_string());
+    }
+    cmd
+}
+
+type ProcessChannel = SftpChannel<ChildStdout, ChildStdin>;
+
+/// 一条 SFTP 会话（持有 ssh 子进程）
+struct SftpSession {
+    child: Mutex<Child>,
+    channel: Mutex<ProcessChannel>,
+}
+
+impl SftpSession {
+    fn spawn(remote: &RemoteRoot) -> io::Result<Self> {
+        let mut child = ssh_command(remote)
+            .arg("-s")
+            .arg("--")
+            .arg(&remote.destination)
+            .arg("sftp")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            let _ = child.kill();
+            return Err(io::Error::other("ssh stdio unavailable"));
+        };
+        match SftpChannel::open(stdout, stdin) {
+            Ok(channel) => Ok(Self {
+                child: Mutex::new(child),
+                channel: Mutex::new(channel),
+            }),
+            Err(e) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Drop for SftpSession {
+    fn drop(&mut self) {
+        if let Ok(child) = self.child.get_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// (destination, port) -> 会话
+static SESSIONS: LazyLock<Mutex<HashMap<(String, Option<u16>), Arc<SftpSession>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn session_key(remote: &RemoteRoot) -> (String, Option<u16>) {
+    (remote.destination.clone(), remote.port)
+}
+
+/// SFTP 远程项目的文件提供者
+pub struct SftpFileProvider {
+    remote: RemoteRoot,
+    session: Arc<SftpSession>,
+}
+
+impl SftpFileProvider {
+    /// 复用到该主机的会话，不存在时建立新会话
+    pub fn connect(remote: RemoteRoot) -> Result<Self, FileApiError> {
+        let key = session_key(&remote);
+        let mut sessions = SESSIONS
+            .lock()
+            .map_err(|_| FileApiError::IoError(io::Error::other("SFTP session lock poisoned")))?;
+        let session = match sessions.get(&key) {
+            Some(session) => session.clone(),
+            None => {
+                debug!(destination = %remote.destination, "Opening SFTP session");
+                let session = Arc::new(SftpSession::spawn(&remote)?);
+                sessions.insert(key, session.clone());
+                session
+            }
+        };
+        Ok(Self { remote, session })
+    }
+
+    /// 在会话上执行操作；传输层错误（连接断开等）时丢弃缓存的会话，下次访问重连
+    fn with_channel<T>(
+        &self,
+        op: impl FnOnce(&mut ProcessChannel) -> io::Result<T>,
+    ) -> Result<T, FileApiError> {
+        let mut channel =
+            self.session.channel.lock().map_err(|_| {
+                FileApiError::IoError(io::Error::other("SFTP channel lock poisoned"))
+            })?;
+        op(&mut channel).map_err(|e| {
+            if matches!(
+                e.kind(),
+                io::ErrorKind::BrokenPipe
+                    | io::ErrorKind::UnexpectedEof
+                    | io::ErrorKind::InvalidData
+            ) {
+                warn!(destination = %self.remote.destination, error = %e, "SFTP session lost");
+                if let Ok(mut sessions) = SESSIONS.lock() {
+                    let key = session_key(&self.remote);
+                    if sessions
+                        .get(&key)
+                        .is_some_and(|cached| Arc::ptr_eq(cached, &self.session))
+                    {
+                        sessions.remove(&key);
+                    }
+                }
+            }
+            FileApiError::from(e)
+        })
+    }
+
+    /// 在远端 `cwd` 目录执行 git
+    fn git_output_in(&self, cwd: &str, args: &[&str]) -> Result<Output, FileApiError> {
+        let mut remote_command = format!("git -C {}", shell_quote(cwd));
+        for arg in args {
+            remote_command.push(' ');
+            remote_command.push_str(&shell_quote(arg));
+        }
+        Ok(ssh_command(&self.remote)
+            .arg("--")
+            .arg(&self.remote.destination)
+            .arg(remote_command)
+            .stdin(Stdio::null())
+            .output()?)
+    }
+
+    /// 批量检查目录中的文件是否被 git 忽略（失败时视为都未忽略）
+    fn ignored_names(&self, dir: &str, names: &[String]) -> HashSet<String> {
+        if names.is_empty() {
+            return HashSet::new();
+        }
+        let mut args = vec!["check-ignore", "--"];
+        args.extend(names.iter().map(String::as_str));
+        match self.git_output_in(dir, &args) {
+            Ok(output) => String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty())
+                .collect(),
+            Err(_) => HashSet::new(),
+        }
+    }
+}
+
+impl FileProvider for SftpFileProvider {
+    fn kind(&self) -> &'static str {
+        "sftp"
+    }
+
+    fn list_files(&self, relative_path: &str) -> Result<Vec<FileEntry>, FileApiError> {
+        let dir = self.remote.resolve(relative_path)?;
+        let raw_entries = self.with_channel(|channel| {
+            let mut entries = Vec::new();
+            for (name, attrs) in channel.read_dir(&dir)? {
+                if name == ".git" || name == ".DS_Store" {
+                    continue;
+                }
+                let is_symlink = attrs.is_symlink();
+                // 与本地一致：is_dir / size 反映符号链接目标；悬空链接按普通文件处理
+                let target = if is_symlink {
+                    channel
+                        .stat(&format!("{}/{}", dir.trim_end_matches('/'), name))
+                        .unwrap_or_default()
+                } else {
+                    attrs
+                };
+                entries.push((name, target.is_dir(), target.size.unwrap_or(0), is_symlink));
+            }
+            Ok(entries)
+        })?;
+
+        let names: Vec<String> = raw_entries.iter().map(|(name, ..)| name.clone()).collect();
+        let ignored = self.ignored_names(&dir, &names);
+        let mut entries: Vec<FileEntry> = raw_entries
+            .into_iter()
+            .map(|(name, is_dir, size, is_symlink)| FileEntry {
+                is_ignored: ignored.contains(&name),
+                name,
+                is_dir,
+                size: if is_dir { 0 } else { size },
+                is_symlink,
+            })
+            .collect();
+        super::sort_file_entries(&mut entries);
+        Ok(entries)
+    }
+
+    fn read_bytes(&self, relative_path: &str, max_size: u64) -> Result<Vec<u8>, FileApiError> {
+        let path = self.remote.resolve(relative_path)?;
+        let content = self.with_channel(|channel| {
+            let attrs = channel.stat(&path)?;
+            if attrs.is_dir() {
+                return Err(io::Error::from(io::ErrorKind::NotFound));
+            }
+            if attrs.size.is_some_and(|size| size > max_size) {
+                return Ok(None);
+            }
+            channel.read_file(&path, max_size).map(Some)
+        })?;
+        match content {
+            Some(content) if content.len() as u64 <= max_size => Ok(content),
+            _ => Err(FileApiError::FileTooLarge),
+        }
+    }
+
+    fn write_bytes(
+        &self,
+        relative_path: &str,
+        content: &[u8],
+        max_size: u64,
+    ) -> Result<u64, FileApiError> {
+        if content.len() as u64 > max_size {
+            return Err(FileApiError::FileTooLarge);
+        }
+        let path = self.remote.resolve(relative_path)?;
+        let Some((dir, name)) = path.rsplit_once('/').filter(|(_, name)| !name.is_empty()) else {
+            return Err(FileApiError::InvalidName("empty file name".to_string()));
+        };
+        if path == self.remote.path {
+            return Err(FileApiError::InvalidName("empty file name".to_string()));
+        }
+        let temp_path = format!(
+            "{}/.{}.tidyflow-{}.tmp",
+            dir,
+            name,
+            uuid::Uuid::new_v4().simple()
+        );
+        self.with_channel(|channel| {
+            let written = channel
+                .write_file(&temp_path, content)
+                .and_then(|_| channel.rename_overwrite(&temp_path, &path));
+            if written.is_err() {
+                let _ = channel.remove(&temp_path);
+            }
+            written
+        })?;
+        Ok(content.len() as u64)
+    }
+
+    fn git_output(&self, args: &[&str]) -> Result<Output, FileApiError> {
+        self.git_output_in(&self.remote.path, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::io::Cursor as IoCursor;
+
+    /// 按请求顺序预先录制的服务端响应（不含长度前缀和请求 id 之外的部分）
+    fn server_stream(replies: &[(u8, Vec<u8>)]) -> Vec<u8> {
+        let mut stream = Vec::new();
+        // VERSION 3
+        let mut version = Vec::new();
+        put_u32(&mut version, 3);
+        push_packet(&mut stream, SSH_FXP_VERSION, &version);
+        for (index, (kind, body)) in replies.iter().enumerate() {
+            let mut payload = Vec::new();
+            put_u32(&mut payload, index as u32 + 1);
+            payload.extend_from_slice(body);
+            push_packet(&mut stream, *kind, &payload);
+        }
+        stream
+    }
+
+    fn push_packet(stream: &mut Vec<u8>, kind: u8, payload: &[u8]) {
+        put_u32(stream, payload.len() as u32 + 1);
+        stream.push(kind);
+        stream.extend_from_slice(payload);
+    }
+
+    fn status(code: u32) -> (u8, Vec<u8>) {
+        let mut body = Vec::new();
+        put_u32(&mut body, code);
+        put_bytes(&mut body, b"msg");
+        put_bytes(&mut body, b"");
+        (SSH_FXP_STATUS, body)
+    }
+
+    fn handle(value: &[u8]) -> (u8, Vec<u8>) {
+        let mut body = Vec::new();
+        put_bytes(&mut body, value);
+        (SSH_FXP_HANDLE, body)
+    }
+
+    fn attrs_body(size: u64, mode: u32) -> Vec<u8> {
+        let mut body = Vec::new();
+        put_u32(
+            &mut body,
+            SSH_FILEXFER_ATTR_SIZE | SSH_FILEXFER_ATTR_PERMISSIONS | SSH_FILEXFER_ATTR_ACMODTIME,
+        );
+        put_u64(&mut body, size);
+        put_u32(&mut body, mode);
+        put_u32(&mut body, 0);
+        put_u32(&mut body, 0);
+        body
+    }
+
+    /// 解析客户端发出的请求：(类型, 请求体中的第一个字符串)
+    fn sent_requests(written: &[u8]) -> Vec<(u8, String)> {
+        let mut cursor = Cursor::new(written);
+        let mut requests = Vec::new();
+        while cursor.pos < written.len() {
+            let len = cursor.u32().unwrap() as usize;
+            let packet = cursor.take(len).unwrap();
+            let kind = packet[0];
+            if kind == SSH_FXP_INIT {
+                continue;
+            }
+            let mut body = Cursor::new(&packet[1..]);
+            let _id = body.u32().unwrap();
+            let first = body
+                .bytes()
+                .map(|raw| String::from_utf8_lossy(raw).into_owned())
+                .unwrap_or_default();
+            requests.push((kind, first));
+        }
+        requests
+    }
+
+    #[test]
+    fn read_dir_skips_dot_entries_and_parses_attrs() {
+        let mut names = Vec::new();
+        put_u32(&mut names, 3);
+        for (name, size, mode) in [
+            (".", 0, S_IFDIR | 0o755),
+            ("src", 4096, S_IFDIR | 0o755),
+            ("link", 7, S_IFLNK | 0o777),
+        ] {
+            put_bytes(&mut names, name.as_bytes());
+            put_bytes(&mut names, b"long name");
+            names.extend_from_slice(&attrs_body(size, mode));
+        }
+        let stream = server_stream(&[
+            handle(b"h1"),
+            (SSH_FXP_NAME, names),
+            status(SSH_FX_EOF),
+            status(SSH_FX_OK),
+        ]);
+        let mut written = Vec::new();
+        let mut channel = SftpChannel::open(IoCursor::new(stream), &mut written).unwrap();
+        let entries = channel.read_dir("/srv/app").unwrap();
+        drop(channel);
+
+        let summary: BTreeMap<_, _> = entries
+            .iter()
+            .map(|(name, attrs)| (name.as_str(), (attrs.is_dir(), attrs.is_symlink())))
+            .collect();
+        assert_eq!(
+            summary,
+            BTreeMap::from([("link", (false, true)), ("src", (true, false))])
+        );
+        assert_eq!(
+            sent_requests(&written)
+                .into_iter()
+                .map(|(kind, _)| kind)
+                .collect::<Vec<_>>(),
+            [
+                SSH_FXP_OPENDIR,
+                SSH_FXP_READDIR,
+                SSH_FXP_READDIR,
+                SSH_FXP_CLOSE
+            ]
+        );
+    }
+
+    #[test]
+    fn read_file_stops_at_eof_and_reports_missing_files() {
+        let mut data = Vec::new();
+        put_bytes(&mut data, "hello 世界".as_bytes());
+        let stream = server_stream(&[
+            handle(b"h"),
+            (SSH_FXP_DATA, data),
+            status(SSH_FX_EOF),
+            status(SSH_FX_OK),
+            status(SSH_FX_NO_SUCH_FILE),
+        ]);
+        let mut written = Vec::new();
+        let mut channel = SftpChannel::open(IoCursor::new(stream), &mut written).unwrap();
+        assert_eq!(
+            channel.read_file("/srv/a.txt", 1024).unwrap(),
+            "hello 世界".as_bytes()
+        );
+        let missing = channel.stat("/srv/missing").unwrap_err();
+        assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+        assert!(matches!(
+            FileApiError::from(missing),
+            FileApiError::FileNotFound
+        ));
+    }
+
+    #[test]
+    fn rename_falls_back_when_posix_rename_is_unsupported() {
+        let stream = server_stream(&[status(8), status(SSH_FX_NO_SUCH_FILE), status(SSH_FX_OK)]);
+        let mut written = Vec::new();
+        let mut channel = SftpChannel::open(IoCursor::new(stream), &mut written).unwrap();
+        channel.rename_overwrite("/srv/.a.tmp", "/srv/a").unwrap();
+        drop(channel);
+        assert_eq!(
+            sent_requests(&written),
+            [
+                (SSH_FXP_EXTENDED, "posix-rename@openssh.com".to_string()),
+                (SSH_FXP_REMOVE, "/srv/a".to_string()),
+                (SSH_FXP_RENAME, "/srv/.a.tmp".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn mismatched_response_id_is_a_protocol_error() {
+        let mut stream = server_stream(&[]);
+        let mut payload = Vec::new();
+        put_u32(&mut payload, 42);
+        put_u32(&mut payload, SSH_FX_OK);
+        push_packet(&mut stream, SSH_FXP_STATUS, &payload);
+        let mut channel = SftpChannel::open(IoCursor::new(stream), Vec::new()).unwrap();
+        let err = channel.remove("/srv/a").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn shell_quote_escapes_single_quotes() {
+        assert_eq!(shell_quote("status"), "status");
+        assert_eq!(shell_quote("--format=%H"), "--format=%H");
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("$(rm -rf /)"), "'$(rm -rf /)'");
+    }
+}
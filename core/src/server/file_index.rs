@@ -1,15 +1,20 @@
 //! File Index API for workspace file indexing
 //!
 //! Provides recursive file listing for Quick Open (Cmd+P) functionality.
-//! Filters out common build artifacts and version control directories.
+//! Filters out hidden entries and anything excluded by `.gitignore` / `.ignore`
+//! (see [`super::ignore_rules`]), plus common build artifact directories.
 
 use std::path::{Path, PathBuf};
 use tracing::{debug, warn};
 
+use super::ignore_rules::{self, IgnoreRules};
+
 /// Maximum number of files to return (prevents memory issues on large repos)
 pub const MAX_FILE_COUNT: usize = 50000;
 
 /// Default directories to ignore during indexing
+///
+/// Applied with the lowest precedence, so a repository can re-include one with `!name/`.
 pub const DEFAULT_IGNORE_DIRS: &[&str] = &[
     ".git",
     ".build",
@@ -40,19 +45,41 @@ pub struct FileIndexResult {
 
 /// Index all files in a workspace directory recursively
 ///
-/// Returns relative paths from workspace_root, filtering out ignored directories.
+/// Returns relative paths from workspace_root, filtering out ignored files and directories.
 /// Stops at MAX_FILE_COUNT to prevent memory issues.
+/// Returns an `Interrupted` error once the current request is cancelled.
 pub fn index_files(workspace_root: &Path) -> Result<FileIndexResult, std::io::Error> {
+    index_directory(workspace_root, "")
+}
+
+/// Index the files under `rel_dir` only, applying the ignore rules inherited from
+/// `workspace_root`. Returned paths are relative to `workspace_root`.
+pub fn index_directory(
+    workspace_root: &Path,
+    rel_dir: &str,
+) -> Result<FileIndexResult, std::io::Error> {
     let mut items = Vec::new();
     let mut truncated = false;
 
     // Canonicalize root for safety checks
     let root_canonical = workspace_root.canonicalize()?;
+    let rel_dir = rel_dir.trim_matches('/');
+    let start_dir = root_canonical.join(rel_dir).canonicalize()?;
+    if !start_dir.starts_with(&root_canonical) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "directory escapes workspace root",
+        ));
+    }
 
     // Stack-based traversal to avoid deep recursion
-    let mut stack: Vec<PathBuf> = vec![root_canonical.clone()];
+    let mut stack: Vec<(PathBuf, IgnoreRules)> =
+        vec![(start_dir, IgnoreRules::for_dir(&root_canonical, rel_dir))];
 
-    while let Some(current_dir) = stack.pop() {
+    while let Some((current_dir, rules)) = stack.pop() {
+        if crate::util::cancel::is_cancelled() {
+            return Err(crate::util::cancel::cancelled_error());
+        }
         if items.len() >= MAX_FILE_COUNT {
             truncated = true;
             break;
@@ -93,6 +120,9 @@ pub fn index_files(workspace_root: &Path) -> Result<FileIndexResult, std::io::Er
                 Ok(ft) => ft,
                 Err(_) => continue,
             };
+            let Some(rel) = relative_to(&path, &root_canonical) else {
+                continue;
+            };
 
             if file_type.is_symlink() {
                 // 解析符号链接目标，检查是否越界，然后按目标类型分流
@@ -101,33 +131,28 @@ pub fn index_files(workspace_root: &Path) -> Result<FileIndexResult, std::io::Er
                         warn!("Skipping symlink escaping root: {:?}", path);
                         continue;
                     }
-                    // 读取链接目标的 metadata（一次 stat）
+                    let Some(target_rel) = relative_to(&canonical, &root_canonical) else {
+                        continue;
+                    };
+                    // 读取链接目标的 metadata（一次 stat）；忽略规则按链接自身路径判定
                     match std::fs::metadata(&canonical) {
+                        Ok(m) if rules.is_ignored(&rel, m.is_dir()) => {}
                         Ok(m) if m.is_dir() => {
-                            let target_name =
-                                canonical.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                            if !should_ignore_dir(target_name) {
-                                stack.push(canonical);
-                            }
-                        }
-                        Ok(m) if m.is_file() => {
-                            if let Ok(relative) = canonical.strip_prefix(&root_canonical) {
-                                items.push(relative.to_string_lossy().to_string());
-                            }
+                            let target_rules = IgnoreRules::for_dir(&root_canonical, &target_rel);
+                            stack.push((canonical, target_rules));
                         }
+                        Ok(m) if m.is_file() => items.push(target_rel),
                         _ => {}
                     }
                 }
             } else if file_type.is_dir() {
-                if should_ignore_dir(&file_name) {
+                if rules.is_ignored(&rel, true) {
                     continue;
                 }
-                stack.push(path);
-            } else if file_type.is_file() {
-                // Get relative path from root
-                if let Ok(relative) = path.strip_prefix(&root_canonical) {
-                    items.push(relative.to_string_lossy().to_string());
-                }
+                let child_rules = rules.descend(&path, &rel);
+                stack.push((path, child_rules));
+            } else if file_type.is_file() && !rules.is_ignored(&rel, false) {
+                items.push(rel);
             }
         }
     }
@@ -186,6 +211,7 @@ pub struct FileContentSearchItemInternal {
 ///
 /// 复用 `index_files` 相同的目录遍历和忽略逻辑，额外跳过二进制文件。
 /// 返回匹配项列表，按 path ASC → line ASC → column ASC 排序。
+/// 当前请求被取消（见 `util::cancel`）时返回 `Interrupted` 错误。
 pub fn search_file_contents(
     workspace_root: &Path,
     query: &str,
@@ -216,9 +242,15 @@ pub fn search_file_contents(
 
     // 收集所有需要搜索的文件路径（复用 index_files 的遍历逻辑）
     let mut file_paths: Vec<(PathBuf, String)> = Vec::new();
-    let mut stack: Vec<PathBuf> = vec![root_canonical.clone()];
-
-    while let Some(current_dir) = stack.pop() {
+    let mut stack: Vec<(PathBuf, IgnoreRules)> = vec![(
+        root_canonical.clone(),
+        IgnoreRules::for_root(&root_canonical),
+    )];
+
+    while let Some((current_dir, rules)) = stack.pop() {
+        if crate::util::cancel::is_cancelled() {
+            return Err(crate::util::cancel::cancelled_error());
+        }
         if files_scanned + file_paths.len() >= MAX_SEARCH_FILES {
             truncated = true;
             break;
@@ -253,39 +285,36 @@ pub fn search_file_contents(
                 Ok(ft) => ft,
                 Err(_) => continue,
             };
+            let Some(rel) = relative_to(&path, &root_canonical) else {
+                continue;
+            };
 
             if file_type.is_symlink() {
                 if let Ok(canonical) = path.canonicalize() {
                     if !canonical.starts_with(&root_canonical) {
                         continue;
                     }
+                    let Some(target_rel) = relative_to(&canonical, &root_canonical) else {
+                        continue;
+                    };
                     match std::fs::metadata(&canonical) {
+                        Ok(m) if rules.is_ignored(&rel, m.is_dir()) => {}
                         Ok(m) if m.is_dir() => {
-                            let target_name =
-                                canonical.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                            if !should_ignore_dir(target_name) {
-                                stack.push(canonical);
-                            }
-                        }
-                        Ok(m) if m.is_file() => {
-                            if let Ok(relative) = canonical.strip_prefix(&root_canonical) {
-                                let rel_str = relative.to_string_lossy().to_string();
-                                file_paths.push((canonical, rel_str));
-                            }
+                            let target_rules = IgnoreRules::for_dir(&root_canonical, &target_rel);
+                            stack.push((canonical, target_rules));
                         }
+                        Ok(m) if m.is_file() => file_paths.push((canonical, target_rel)),
                         _ => {}
                     }
                 }
             } else if file_type.is_dir() {
-                if should_ignore_dir(&file_name) {
+                if rules.is_ignored(&rel, true) {
                     continue;
                 }
-                stack.push(path);
-            } else if file_type.is_file() {
-                if let Ok(relative) = path.strip_prefix(&root_canonical) {
-                    let rel_str = relative.to_string_lossy().to_string();
-                    file_paths.push((path, rel_str));
-                }
+                let child_rules = rules.descend(&path, &rel);
+                stack.push((path, child_rules));
+            } else if file_type.is_file() && !rules.is_ignored(&rel, false) {
+                file_paths.push((path, rel));
             }
         }
     }
@@ -295,6 +324,9 @@ pub fn search_file_contents(
 
     // 逐文件搜索
     'outer: for (abs_path, rel_path) in &file_paths {
+        if crate::util::cancel::is_cancelled() {
+            return Err(crate::util::cancel::cancelled_error());
+        }
         if files_scanned >= MAX_SEARCH_FILES {
             truncated = true;
             break;
@@ -405,9 +437,25 @@ pub fn search_file_contents(
     })
 }
 
-/// Check if a directory name should be ignored
-fn should_ignore_dir(name: &str) -> bool {
-    DEFAULT_IGNORE_DIRS.contains(&name)
+/// 相对 root 的路径（`/` 分隔）
+fn relative_to(path: &Path, root: &Path) -> Option<String> {
+    path.strip_prefix(root)
+        .ok()
+        .map(|relative| relative.to_string_lossy().to_string())
+}
+
+/// 判断相对路径是否会被 `index_files` 收录。
+///
+/// 与全量遍历保持同一套过滤规则：任一路径段以 `.` 开头，或路径及其上级目录命中忽略规则即排除，
+/// 供增量索引在合并 watcher 事件时复用，避免增量与全量结果漂移。
+/// 路径已不存在时按文件判定（只对目录生效的规则不再命中）。
+pub fn is_indexable_relative(workspace_root: &Path, rel_path: &str) -> bool {
+    let rel_path = rel_path.trim_matches('/');
+    if rel_path.is_empty() || rel_path.split('/').any(|segment| segment.starts_with('.')) {
+        return false;
+    }
+    let is_dir = workspace_root.join(rel_path).is_dir();
+    !ignore_rules::is_path_ignored(workspace_root, rel_path, is_dir)
 }
 
 #[cfg(test)]
@@ -453,6 +501,49 @@ mod tests {
         assert!(!result.items.iter().any(|p| p.contains("node_modules")));
     }
 
+    #[test]
+    fn test_is_indexable_relative_matches_walk_rules() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+
+        assert!(is_indexable_relative(root, "src/main.rs"));
+        assert!(is_indexable_relative(root, "README.md"));
+        assert!(!is_indexable_relative(root, ".hidden"));
+        assert!(!is_indexable_relative(root, "src/.cache/a.rs"));
+        assert!(!is_indexable_relative(root, "node_modules/pkg/index.js"));
+        assert!(!is_indexable_relative(root, "target/debug/app"));
+        assert!(!is_indexable_relative(root, "logs/app.log"));
+        assert!(!is_indexable_relative(root, ""));
+    }
+
+    #[test]
+    fn test_index_files_respects_ignore_files() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+
+        fs::create_dir_all(root.join("src/generated")).unwrap();
+        fs::create_dir_all(root.join("env/lib")).unwrap();
+        fs::create_dir_all(root.join("build")).unwrap();
+        fs::write(root.join(".gitignore"), "env/\n*.log\n!build/\n").unwrap();
+        fs::write(root.join("src/.ignore"), "generated/\n").unwrap();
+        fs::write(root.join("src/lib.rs"), "needle").unwrap();
+        fs::write(root.join("src/generated/api.rs"), "needle").unwrap();
+        fs::write(root.join("env/lib/site.py"), "needle").unwrap();
+        fs::write(root.join("build/notes.md"), "").unwrap();
+        fs::write(root.join("debug.log"), "").unwrap();
+
+        let result = index_files(root).unwrap();
+        assert_eq!(result.items, vec!["build/notes.md", "src/lib.rs"]);
+
+        let sub = index_directory(root, "src").unwrap();
+        assert_eq!(sub.items, vec!["src/lib.rs"]);
+
+        let search = search_file_contents(root, "needle", false).unwrap();
+        let paths: Vec<&str> = search.items.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/lib.rs"]);
+    }
+
     #[test]
     fn test_index_files_ignores_hidden() {
         let temp = TempDir::new().unwrap();
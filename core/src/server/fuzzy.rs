@@ -0,0 +1,255 @@
+//! Quick Open 模糊匹配
+//!
+//! fzf 风格的子序列匹配：查询的每个字符按顺序出现在路径中即命中，
+//! 再按命中位置打分——词首（路径分隔符、`_`/`-`/`.` 之后、驼峰边界）、连续命中与文件名内的命中加分，
+//! 间隔扣分。对每个候选用动态规划取最高分的命中方案，并返回命中的字符位置供客户端高亮。
+
+use crate::util::cancel;
+
+/// 默认返回条数
+pub const DEFAULT_FUZZY_LIMIT: usize = 50;
+/// 返回条数上限
+pub const MAX_FUZZY_LIMIT: usize = 500;
+
+const SCORE_MATCH: i64 = 16;
+const SCORE_GAP_START: i64 = -3;
+const SCORE_GAP_EXTENSION: i64 = -1;
+const BONUS_PATH_BOUNDARY: i64 = 10;
+const BONUS_WORD_BOUNDARY: i64 = 8;
+const BONUS_CAMEL_CASE: i64 = 7;
+const BONUS_CONSECUTIVE: i64 = 5;
+const BONUS_FILE_NAME: i64 = 4;
+/// 扫描候选时每隔多少条检查一次取消
+const CANCEL_CHECK_INTERVAL: usize = 1024;
+
+/// 单条命中结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// 候选在输入列表中的下标
+    pub index: usize,
+    pub score: i64,
+    /// 命中字符在路径中的位置（按字符计数，0-based）
+    pub positions: Vec<u32>,
+}
+
+/// 对候选路径做模糊匹配，返回得分最高的 `limit` 条和命中总数
+///
+/// `keys` 为与 `items` 一一对应的小写路径；查询大小写不敏感。
+/// 同分时文件名较短的优先，其次是较短的路径，最后按路径字典序。
+pub fn top_matches(
+    items: &[String],
+    keys: &[String],
+    query: &str,
+    limit: usize,
+) -> (Vec<FuzzyMatch>, usize) {
+    let needle: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let mut matches: Vec<FuzzyMatch> = items
+        .iter()
+        .zip(keys.iter())
+        .enumerate()
+        // 请求被取消时提前结束扫描，调用方丢弃部分结果
+        .take_while(|(index, _)| index % CANCEL_CHECK_INTERVAL != 0 || !cancel::is_cancelled())
+        .filter(|(_, (_, key))| is_subsequence(&needle, key))
+        .filter_map(|(index, (item, _))| {
+            score_candidate(&needle, item).map(|(score, positions)| FuzzyMatch {
+                index,
+                score,
+                positions,
+            })
+        })
+        .collect();
+    let total = matches.len();
+
+    let file_name_len = |path: &str| path.rsplit('/').next().map_or(0, str::len);
+    let rank = |a: &FuzzyMatch, b: &FuzzyMatch| {
+        let (path_a, path_b) = (&items[a.index], &items[b.index]);
+        b.score
+            .cmp(&a.score)
+            .then_with(|| file_name_len(path_a).cmp(&file_name_len(path_b)))
+            .then_with(|| path_a.len().cmp(&path_b.len()))
+            .then_with(|| path_a.cmp(path_b))
+    };
+    if limit < matches.len() {
+        if limit > 0 {
+            matches.select_nth_unstable_by(limit - 1, rank);
+        }
+        matches.truncate(limit);
+    }
+    matches.sort_unstable_by(rank);
+    (matches, total)
+}
+
+fn is_subsequence(needle: &[char], key: &str) -> bool {
+    let mut rest = needle.iter().peekable();
+    for c in key.chars() {
+        if rest.peek() == Some(&&c) {
+            rest.next();
+        }
+    }
+    rest.peek().is_none()
+}
+
+/// 计算单个候选的最高分与命中位置；不构成子序列时返回 None
+pub fn score_candidate(needle: &[char], candidate: &str) -> Option<(i64, Vec<u32>)> {
+    let chars: Vec<char> = candidate.chars().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let (m, n) = (needle.len(), chars.len());
+    if m == 0 {
+        return Some((0, Vec::new()));
+    }
+    if m > n {
+        return None;
+    }
+
+    let file_name_start = chars
+        .iter()
+        .rposition(|&c| c == '/')
+        .map(|idx| idx + 1)
+        .unwrap_or(0);
+    let bonus: Vec<i64> = (0..n)
+        .map(|j| {
+            let position_bonus = char_bonus(j.checked_sub(1).map(|p| chars[p]), chars[j]);
+            let file_name_bonus = if j >= file_name_start {
+                BONUS_FILE_NAME
+            } else {
+                0
+            };
+            position_bonus + file_name_bonus
+        })
+        .collect();
+
+    // score[i][j]: needle[..=i] 命中且 needle[i] 落在 j 时的最高分；from[i][j] 记录 needle[i-1] 的位置
+    const NONE: i64 = i64::MIN / 2;
+    let mut score = vec![vec![NONE; n]; m];
+    let mut from = vec![vec![0usize; n]; m];
+    for j in 0..n {
+        if lower[j] == needle[0] {
+            score[0][j] = SCORE_MATCH + bonus[j];
+        }
+    }
+    for i in 1..m {
+        // 跨过间隔接续的最优前驱：分数与位置
+        let mut gap_best = NONE;
+        let mut gap_from = 0usize;
+        for j in i..n {
+            if j >= 2 && score[i - 1][j - 2] > NONE {
+                let candidate = score[i - 1][j - 2] + SCORE_GAP_START;
+                if candidate >= gap_best + SCORE_GAP_EXTENSION {
+                    gap_best = candidate;
+                    gap_from = j - 2;
+                } else {
+                    gap_best += SCORE_GAP_EXTENSION;
+                }
+            } else if gap_best > NONE {
+                gap_best += SCORE_GAP_EXTENSION;
+            }
+            if lower[j] != needle[i] {
+                continue;
+            }
+            let consecutive = if score[i - 1][j - 1] > NONE {
+                score[i - 1][j - 1] + BONUS_CONSECUTIVE
+            } else {
+                NONE
+            };
+            let (best, prev) = if consecutive >= gap_best {
+                (consecutive, j - 1)
+            } else {
+                (gap_best, gap_from)
+            };
+            if best > NONE {
+                score[i][j] = best + SCORE_MATCH + bonus[j];
+                from[i][j] = prev;
+            }
+        }
+    }
+
+    let (mut j, best) = score[m - 1]
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| **s > NONE)
+        .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(&a.0)))
+        .map(|(j, s)| (j, *s))?;
+    let mut positions = vec![0u32; m];
+    for i in (0..m).rev() {
+        positions[i] = j as u32;
+        if i > 0 {
+            j = from[i][j];
+        }
+    }
+    Some((best, positions))
+}
+
+fn char_bonus(prev: Option<char>, current: char) -> i64 {
+    match prev {
+        None | Some('/') | Some('\\') => BONUS_PATH_BOUNDARY,
+        Some(p) if !p.is_alphanumeric() => BONUS_WORD_BOUNDARY,
+        Some(p) if p.is_lowercase() && current.is_uppercase() => BONUS_CAMEL_CASE,
+        Some(p) if !p.is_ascii_digit() && current.is_ascii_digit() => BONUS_CAMEL_CASE,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranked(items: &[&str], query: &str) -> Vec<String> {
+        let items: Vec<String> = items.iter().map(|s| s.to_string()).collect();
+        let keys: Vec<String> = items.iter().map(|s| s.to_lowercase()).collect();
+        let (matches, _) = top_matches(&items, &keys, query, 10);
+        matches
+            .into_iter()
+            .map(|m| items[m.index].clone())
+            .collect()
+    }
+
+    #[test]
+    fn ranks_boundary_and_file_name_matches_first() {
+        let items = [
+            "docs/assets/fancy-module-notes.md",
+            "src/fuzzy_matcher.rs",
+            "src/server/file_manager.rs",
+            "core/src/server/fuzzy.rs",
+        ];
+        assert_eq!(
+            ranked(&items, "fuzzy"),
+            vec!["core/src/server/fuzzy.rs", "src/fuzzy_matcher.rs"]
+        );
+        assert_eq!(ranked(&items, "fm")[0], "src/server/file_manager.rs");
+        assert!(ranked(&items, "zzz").is_empty());
+    }
+
+    #[test]
+    fn reports_positions_and_limits_results() {
+        let needle: Vec<char> = "wsc".chars().collect();
+        let (_, positions) = score_candidate(&needle, "app/WSClient.swift").unwrap();
+        assert_eq!(positions, vec![4, 5, 6]);
+
+        let items: Vec<String> = (0..20).map(|i| format!("src/mod{}.rs", i)).collect();
+        let keys = items.clone();
+        let (matches, total) = top_matches(&items, &keys, "mod", 5);
+        assert_eq!(total, 20);
+        assert_eq!(matches.len(), 5);
+        assert!(matches.windows(2).all(|w| w[0].score >= w[1].score));
+    }
+
+    #[tokio::test]
+    async fn cancelled_request_stops_scanning() {
+        let items: Vec<String> = (0..5000).map(|i| format!("src/mod{}.rs", i)).collect();
+        let (_, total) = cancel::scope("fuzzy-cancel".to_string(), async move {
+            assert!(cancel::cancel("fuzzy-cancel"));
+            crate::util::trace::spawn_blocking(move || top_matches(&items, &items, "mod", 5))
+                .await
+                .unwrap()
+        })
+        .await;
+        assert_eq!(total, 0);
+    }
+}
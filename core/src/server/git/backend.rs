@@ -0,0 +1,539 @@
+//! Git 后端选择
+//!
+//! 热路径（status / log / 单文件 diff / 分支列表）经 [`GitBackend`] 分派：
+//! 默认使用 gix 进程内实现，遇到 gix 不支持或无法与 `git` 输出保持一致的情形
+//! （如 patience 算法、属性文件过滤、带过滤条件的 log、gix 读取失败）逐次回退到 git 命令行实现。
+//! 项目 `.tidyflow.toml` 中 `[git] backend = "cli"` 可整体切换为命令行实现。
+
+use std::path::Path;
+
+use tracing::warn;
+
+use super::branches::{git_branches_cli, git_branches_gix};
+use super::operations::{get_base_diff, get_tracked_diff, get_untracked_diff};
+use super::status::{git_log_cli, git_log_gix, git_status_cli, git_status_gix};
+use super::unified_diff::gix_file_diff;
+use super::utils::*;
+use crate::workspace::config::ProjectConfig;
+
+/// 可选的后端实现
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GitBackendKind {
+    /// gix 进程内实现，不支持的情形回退到命令行
+    #[default]
+    Gix,
+    /// 全部通过 `git` 子进程
+    Cli,
+}
+
+impl GitBackendKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Gix => "gix",
+            Self::Cli => "cli",
+        }
+    }
+
+    /// 解析配置值（大小写不敏感，`gitoxide` / `git` 为别名）
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "gix" | "gitoxide" => Some(Self::Gix),
+            "cli" | "git" => Some(Self::Cli),
+            _ => None,
+        }
+    }
+}
+
+/// 单文件 diff 的对比对象
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffTarget<'a> {
+    /// 工作区 vs 暂存区（`git diff`）
+    Working,
+    /// 暂存区 vs HEAD（`git diff --cached`）
+    Staged,
+    /// 工作区 vs 指定提交（`git diff <base>`）
+    Base(&'a str),
+    /// 未跟踪文件 vs 空文件（`git diff --no-index /dev/null`）
+    Untracked,
+}
+
+/// 热路径 Git 查询的实现
+pub trait GitBackend: Send + Sync {
+    fn kind(&self) -> GitBackendKind;
+
+    /// 工作区状态（不含缓存，缓存由 [`super::status::git_status`] 负责）
+    fn status(
+        &self,
+        workspace_root: &Path,
+        default_branch: &str,
+    ) -> Result<GitStatusResult, GitError>;
+
+    /// 提交历史，多取一条用于判断 `has_more`
+    fn log(
+        &self,
+        workspace_root: &Path,
+        limit: usize,
+        filter: &GitLogFilter,
+    ) -> Result<GitLogResult, GitError>;
+
+    /// 单文件 unified diff 文本（超出 `MAX_DIFF_SIZE` 时截断），无变化时为空串
+    fn file_diff(
+        &self,
+        workspace_root: &Path,
+        path: &str,
+        target: DiffTarget<'_>,
+        algorithm: Option<&str>,
+    ) -> Result<(String, bool), GitError>;
+
+    /// 本地分支列表与当前分支
+    fn branches(&self, workspace_root: &Path) -> Result<GitBranchesResult, GitError>;
+}
+
+/// git 命令行实现
+pub struct CliBackend;
+
+impl GitBackend for CliBackend {
+    fn kind(&self) -> GitBackendKind {
+        GitBackendKind::Cli
+    }
+
+    fn status(
+        &self,
+        workspace_root: &Path,
+        default_branch: &str,
+    ) -> Result<GitStatusResult, GitError> {
+        git_status_cli(workspace_root, default_branch)
+    }
+
+    fn log(
+        &self,
+        workspace_root: &Path,
+        limit: usize,
+        filter: &GitLogFilter,
+    ) -> Result<GitLogResult, GitError> {
+        git_log_cli(workspace_root, limit, filter)
+    }
+
+    fn file_diff(
+        &self,
+        workspace_root: &Path,
+        path: &str,
+        target: DiffTarget<'_>,
+        algorithm: Option<&str>,
+    ) -> Result<(String, bool), GitError> {
+        match target {
+            DiffTarget::Working => get_tracked_diff(workspace_root, path, "working", algorithm),
+            DiffTarget::Staged => get_tracked_diff(workspace_root, path, "staged", algorithm),
+            DiffTarget::Base(base) => get_base_diff(workspace_root, path, base, algorithm),
+            DiffTarget::Untracked => get_untracked_diff(workspace_root, path, algorithm),
+        }
+    }
+
+    fn branches(&self, workspace_root: &Path) -> Result<GitBranchesResult, GitError> {
+        git_branches_cli(workspace_root)
+    }
+}
+
+/// gix 实现；不支持或失败时回退到 [`CliBackend`]（非 Git 目录的错误原样返回）
+pub struct GixBackend;
+
+impl GixBackend {
+    fn fallback<T>(
+        op: &str,
+        workspace_root: &Path,
+        result: Result<T, GitError>,
+        cli: impl FnOnce() -> Result<T, GitError>,
+    ) -> Result<T, GitError> {
+        match result {
+            Err(GitError::NotAGitRepo) => Err(GitError::NotAGitRepo),
+            Err(e) => {
+                warn!(
+                    "gix {} failed, falling back to git cli: root={} err={}",
+                    op,
+                    workspace_root.display(),
+                    e
+                );
+                cli()
+            }
+            ok => ok,
+        }
+    }
+}
+
+impl GitBackend for GixBackend {
+    fn kind(&self) -> GitBackendKind {
+        GitBackendKind::Gix
+    }
+
+    fn status(
+        &self,
+        workspace_root: &Path,
+        default_branch: &str,
+    ) -> Result<GitStatusResult, GitError> {
+        Self::fallback(
+            "status",
+            workspace_root,
+            git_status_gix(workspace_root, default_branch),
+            || CliBackend.status(workspace_root, default_branch),
+        )
+    }
+
+    fn log(
+        &self,
+        workspace_root: &Path,
+        limit: usize,
+        filter: &GitLogFilter,
+    ) -> Result<GitLogResult, GitError> {
+        // 路径 / 作者 / 消息 / 日期过滤与分页游标交给 `git log`
+        if !filter.is_empty() {
+            return CliBackend.log(workspace_root, limit, filter);
+        }
+        Self::fallback(
+            "log",
+            workspace_root,
+            git_log_gix(workspace_root, limit),
+            || CliBackend.log(workspace_root, limit, filter),
+        )
+    }
+
+    fn file_diff(
+        &self,
+        workspace_root: &Path,
+        path: &str,
+        target: DiffTarget<'_>,
+        algorithm: Option<&str>,
+    ) -> Result<(String, bool), GitError> {
+        let result = gix_file_diff(workspace_root, path, target, algorithm);
+        match result {
+            Ok(Some(text)) => Ok(truncate_if_needed(&text)),
+            Ok(None) => CliBackend.file_diff(workspace_root, path, target, algorithm),
+            Err(e) => Self::fallback("diff", workspace_root, Err(e), || {
+                CliBackend.file_diff(workspace_root, path, target, algorithm)
+            }),
+        }
+    }
+
+    fn branches(&self, workspace_root: &Path) -> Result<GitBranchesResult, GitError> {
+        Self::fallback(
+            "branches",
+            workspace_root,
+            git_branches_gix(workspace_root),
+            || CliBackend.branches(workspace_root),
+        )
+    }
+}
+
+/// 读取项目配置的后端；未配置或取值无效时为 gix
+pub fn configured_backend(workspace_root: &Path) -> GitBackendKind {
+    let Some(value) = ProjectConfig::load(workspace_root)
+        .ok()
+        .and_then(|config| config.git.backend)
+    else {
+        return GitBackendKind::default();
+    };
+    GitBackendKind::parse(&value).unwrap_or_else(|| {
+        warn!(
+            "Unknown [git] backend '{}' in {}, using gix",
+            value,
+            workspace_root.display()
+        );
+        GitBackendKind::default()
+    })
+}
+
+/// 按项目配置选择后端
+pub fn backend_for(workspace_root: &Path) -> &'static dyn GitBackend {
+    match configured_backend(workspace_root) {
+        GitBackendKind::Gix => &GixBackend,
+        GitBackendKind::Cli => &CliBackend,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::exec_env::git_command;
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let output = git_command(dir).args(args).output().expect("run git");
+        assert!(output.status.success(), "git {:?} failed", args);
+    }
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let full = root.join(path);
+        std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+        std::fs::write(full, content).unwrap();
+    }
+
+    fn init_repo() -> tempfile::TempDir {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        run_git(root, &["init", "-q", "-b", "main"]);
+        run_git(root, &["config", "user.name", "Alice"]);
+        run_git(root, &["config", "user.email", "alice@example.com"]);
+        run_git(root, &["config", "commit.gpgsign", "false"]);
+        let body: String = (1..=30)
+            .map(|i| format!("    let v{} = {};\n", i, i))
+            .collect();
+        write(root, "src/lib.rs", &format!("fn main() {{\n{}}}\n", body));
+        write(root, "notes.txt", "alpha\nbeta\ngamma");
+        write(root, "sp ace.txt", "x y\n");
+        write(root, "gone.txt", "bye\n");
+        write(root, "staged_gone.txt", "bye\n");
+        run_git(root, &["add", "-A"]);
+        run_git(root, &["commit", "-q", "-m", "init"]);
+        tmp
+    }
+
+    #[test]
+    fn backend_kind_parses_config_values() {
+        assert_eq!(GitBackendKind::parse(" GIX "), Some(GitBackendKind::Gix));
+        assert_eq!(GitBackendKind::parse("gitoxide"), Some(GitBackendKind::Gix));
+        assert_eq!(GitBackendKind::parse("git"), Some(GitBackendKind::Cli));
+        assert_eq!(GitBackendKind::parse("libgit2"), None);
+
+        let tmp = tempfile::tempdir().unwrap();
+        assert_eq!(configured_backend(tmp.path()), GitBackendKind::Gix);
+        std::fs::write(
+            tmp.path().join(".tidyflow.toml"),
+            "[git]\nbackend = \"cli\"\n",
+        )
+        .unwrap();
+        assert_eq!(backend_for(tmp.path()).kind(), GitBackendKind::Cli);
+    }
+
+    #[test]
+    fn gix_file_diff_matches_git_cli_output() {
+        let tmp = init_repo();
+        let root = tmp.path();
+        // 两处相距较远的修改（两个 hunk，带函数名上下文）与末尾缺换行的修改
+        let body: String = (1..=30)
+            .map(|i| match i {
+                3 => "    let v3 = 33;\n".to_string(),
+                25 => "    let v25 = 2525;\n    let extra = 0;\n".to_string(),
+                _ => format!("    let v{} = {};\n", i, i),
+            })
+            .collect();
+        write(root, "src/lib.rs", &format!("fn main() {{\n{}}}\n", body));
+        write(root, "notes.txt", "alpha\nbeta\ndelta");
+        write(root, "sp ace.txt", "x y\nz\n");
+        std::fs::remove_file(root.join("gone.txt")).unwrap();
+        write(root, "added.txt", "one\ntwo\n");
+        write(root, "empty.txt", "");
+        run_git(root, &["add", "added.txt", "empty.txt"]);
+        run_git(root, &["rm", "-q", "staged_gone.txt"]);
+        write(root, "untracked.txt", "new\nno newline");
+
+        let cases = [
+            ("src/lib.rs", DiffTarget::Working),
+            ("notes.txt", DiffTarget::Working),
+            ("sp ace.txt", DiffTarget::Working),
+            ("gone.txt", DiffTarget::Working),
+            ("added.txt", DiffTarget::Staged),
+            ("empty.txt", DiffTarget::Staged),
+            ("staged_gone.txt", DiffTarget::Staged),
+            ("src/lib.rs", DiffTarget::Staged),
+            ("untracked.txt", DiffTarget::Untracked),
+        ];
+        for (path, target) in cases {
+            for algorithm in [None, Some("histogram"), Some("minimal")] {
+                let gix = gix_file_diff(root, path, target, algorithm)
+                    .unwrap()
+                    .unwrap_or_else(|| panic!("gix fell back for {} {:?}", path, target));
+                let (cli, _) = CliBackend.file_diff(root, path, target, algorithm).unwrap();
+                assert_eq!(gix, cli, "{} {:?} {:?}", path, target, algorithm);
+            }
+        }
+
+        // patience 与指定 base 交给命令行
+        assert!(
+            gix_file_diff(root, "notes.txt", DiffTarget::Working, Some("patience"))
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            gix_file_diff(root, "notes.txt", DiffTarget::Base("HEAD"), None)
+                .unwrap()
+                .is_none()
+        );
+        let (via_backend, truncated) = GixBackend
+            .file_diff(root, "notes.txt", DiffTarget::Base("HEAD"), None)
+            .unwrap();
+        assert!(via_backend.contains("+delta") && !truncated);
+    }
+
+    #[test]
+    fn gix_and_cli_status_log_and_branches_agree() {
+        let tmp = init_repo();
+        let root = tmp.path();
+        run_git(root, &["checkout", "-q", "-b", "feature"]);
+        write(root, "feature.txt", "f\n");
+        run_git(root, &["add", "feature.txt"]);
+        run_git(root, &["commit", "-q", "-m", "feature work"]);
+        run_git(root, &["tag", "-a", "v1", "-m", "release"]);
+        write(root, "notes.txt", "changed\n");
+        run_git(root, &["add", "notes.txt"]);
+        write(root, "notes.txt", "changed again\n");
+        write(root, "src/lib.rs", "fn main() {}\n");
+        write(root, "new.txt", "n\n");
+        // 未跟踪目录（含嵌套子目录）在两个后端都折叠为 `dir/`
+        write(root, "scratch/deep/a.txt", "a\n");
+        write(root, "src/gen/b.rs", "b\n");
+
+        let gix = GixBackend.status(root, "main").unwrap();
+        let cli = CliBackend.status(root, "main").unwrap();
+        let summary = |status: &GitStatusResult| {
+            status
+                .items
+                .iter()
+                .map(|item| format!("{}:{}:{}", item.path, item.code, item.staged))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(summary(&gix), summary(&cli));
+        assert_eq!(
+            summary(&cli),
+            vec![
+                "new.txt:??:false",
+                "notes.txt:M:false",
+                "notes.txt:M:true",
+                "scratch/:??:false",
+                "src/gen/:??:false",
+                "src/lib.rs:M:false",
+            ]
+        );
+        assert_eq!(gix.staged_count, cli.staged_count);
+        assert_eq!(gix.current_branch.as_deref(), Some("feature"));
+        assert_eq!(cli.current_branch, gix.current_branch);
+        assert_eq!((cli.ahead_by, cli.behind_by), (Some(1), Some(0)));
+        assert_eq!((gix.ahead_by, gix.behind_by), (cli.ahead_by, cli.behind_by));
+
+        let filter = GitLogFilter::default();
+        let gix_log = GixBackend.log(root, 10, &filter).unwrap();
+        let cli_log = CliBackend.log(root, 10, &filter).unwrap();
+        let entries = |log: &GitLogResult| {
+            log.entries
+                .iter()
+                .map(|e| {
+                    let mut refs = e.refs.clone();
+                    refs.sort();
+                    (e.sha.clone(), e.message.clone(), e.author.clone(), refs)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(entries(&gix_log), entries(&cli_log));
+        assert_eq!(cli_log.entries[0].refs.len(), 2);
+
+        let gix_branches = GixBackend.branches(root).unwrap();
+        let cli_branches = CliBackend.branches(root).unwrap();
+        assert_eq!(gix_branches.current, cli_branches.current);
+        let names = |result: &GitBranchesResult| {
+            result
+                .branches
+                .iter()
+                .map(|b| b.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&cli_branches), vec!["feature", "main"]);
+        assert_eq!(names(&gix_branches), names(&cli_branches));
+
+        let not_repo = tempfile::tempdir().unwrap();
+        assert!(CliBackend
+            .status(not_repo.path(), "main")
+            .unwrap()
+            .items
+            .is_empty());
+        assert!(matches!(
+            CliBackend.log(not_repo.path(), 10, &filter),
+            Err(GitError::NotAGitRepo)
+        ));
+    }
+
+    #[test]
+    fn branch_listing_reports_upstream_tip_and_worktrees() {
+        let tmp = init_repo();
+        let root = tmp.path();
+        run_git(
+            root,
+            &[
+                "remote",
+                "add",
+                "origin",
+                "https://example.invalid/repo.git",
+            ],
+        );
+        run_git(root, &["update-ref", "refs/remotes/origin/main", "HEAD"]);
+        run_git(root, &["config", "branch.main.remote", "origin"]);
+        run_git(root, &["config", "branch.main.merge", "refs/heads/main"]);
+        write(root, "next.txt", "n\n");
+        run_git(root, &["add", "next.txt"]);
+        run_git(root, &["commit", "-q", "-m", "next"]);
+        run_git(root, &["branch", "feature", "HEAD~1"]);
+        run_git(root, &["branch", "-q", "--set-upstream-to=main", "feature"]);
+        run_git(root, &["branch", "stale"]);
+        run_git(root, &["config", "branch.stale.remote", "origin"]);
+        run_git(root, &["config", "branch.stale.merge", "refs/heads/stale"]);
+        let other = tempfile::tempdir().unwrap();
+        let wt = other.path().join("wt");
+        run_git(
+            root,
+            &["worktree", "add", "-q", wt.to_str().unwrap(), "feature"],
+        );
+
+        let canonical =
+            |path: &Option<String>| path.as_ref().map(|p| Path::new(p).canonicalize().unwrap());
+        let summary = |result: &GitBranchesResult| {
+            result
+                .branches
+                .iter()
+                .map(|b| {
+                    (
+                        b.name.clone(),
+                        b.upstream.clone(),
+                        b.ahead_by,
+                        b.behind_by,
+                        b.last_commit_sha.clone(),
+                        b.last_commit_date.clone(),
+                        canonical(&b.worktree_path),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let cli = CliBackend.branches(root).unwrap();
+        let by_name = |name: &str| cli.branches.iter().find(|b| b.name == name).unwrap();
+        let main = by_name("main");
+        assert_eq!(main.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((main.ahead_by, main.behind_by), (Some(1), Some(0)));
+        assert_eq!(main.last_commit_sha.as_ref().map(String::len), Some(40));
+        assert!(main.last_commit_date.is_some());
+        assert!(main.worktree_path.is_none());
+        let feature = by_name("feature");
+        assert_eq!(feature.upstream.as_deref(), Some("main"));
+        assert_eq!((feature.ahead_by, feature.behind_by), (Some(0), Some(1)));
+        assert_eq!(
+            canonical(&feature.worktree_path),
+            Some(wt.canonicalize().unwrap())
+        );
+        let stale = by_name("stale");
+        assert_eq!(stale.upstream.as_deref(), Some("origin/stale"));
+        assert_eq!((stale.ahead_by, stale.behind_by), (None, None));
+        assert_eq!(summary(&GixBackend.branches(root).unwrap()), summary(&cli));
+
+        // 在链接 worktree 中查看：主 worktree 的分支被标记，当前分支不标记
+        let from_wt = CliBackend.branches(&wt).unwrap();
+        assert_eq!(from_wt.current, "feature");
+        let main = from_wt.branches.iter().find(|b| b.name == "main").unwrap();
+        assert_eq!(
+            canonical(&main.worktree_path),
+            Some(root.canonicalize().unwrap())
+        );
+        assert!(from_wt
+            .branches
+            .iter()
+            .all(|b| b.name == "main" || b.worktree_path.is_none()));
+        assert_eq!(
+            summary(&GixBackend.branches(&wt).unwrap()),
+            summary(&from_wt)
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! 二进制文件（尤其是图片）的前后版本信息
+//!
+//! `git diff` 对二进制文件只输出 "Binary files ... differ"。为了让客户端渲染前后对比，
+//! 这里读取变更两侧的 blob，返回大小、MIME 类型与图片尺寸（只解析文件头，不解码像素），
+//! 并可按需附带不超过 [`MAX_DIFF_SIZE`] 的原始内容。
+
+use std::path::Path;
+
+use super::diff_range::parse_revision_range;
+use super::utils::*;
+use crate::server::file_api::detect_mime_type;
+use crate::util::cancel;
+use crate::util::exec_env::git_command;
+
+/// 超过该大小的 blob 不读取内容，仅按扩展名推断 MIME 类型
+pub const MAX_BLOB_SCAN_SIZE: u64 = 8 * 1024 * 1024;
+
+/// 二进制文件某一侧的版本信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryBlobInfo {
+    pub size: u64,
+    pub mime_type: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// 请求附带内容且大小不超过 [`MAX_DIFF_SIZE`] 时的原始字节
+    pub content: Option<Vec<u8>>,
+}
+
+/// 二进制 diff 两侧的版本；新增文件无 `old`，删除文件无 `new`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BinaryDiffBlobs {
+    pub old: Option<BinaryBlobInfo>,
+    pub new: Option<BinaryBlobInfo>,
+}
+
+/// diff 一侧的内容来源
+enum BlobSource {
+    /// `git cat-file` 可解析的对象名，如 `HEAD:./a.png`、`:./a.png`（暂存区）
+    Object(String),
+    WorkTree,
+}
+
+/// `git diff` 输出是否为二进制文件的占位行
+pub fn is_binary_diff_output(text: &str) -> bool {
+    text.lines()
+        .any(|line| line.starts_with("Binary files ") && line.ends_with(" differ"))
+}
+
+/// 读取二进制 diff 两侧的版本信息，对比基准与 `git_diff` 的 `base` / `mode` / `range` 一致
+pub fn git_binary_diff_blobs(
+    workspace_root: &Path,
+    path: &str,
+    base: Option<&str>,
+    mode: &str,
+    range: Option<&str>,
+    include_content: bool,
+) -> Result<BinaryDiffBlobs, GitError> {
+    validate_path(workspace_root, path)?;
+    if get_git_repo_root(workspace_root).is_none() {
+        return Err(GitError::NotAGitRepo);
+    }
+    let object = |rev: &str| BlobSource::Object(format!("{}:./{}", rev, path));
+    let (old, new) = if let Some(range) = range {
+        let range = parse_revision_range(range)?;
+        let old_rev = if range.symmetric {
+            merge_base(workspace_root, &range.base, &range.head)?
+        } else {
+            range.base.clone()
+        };
+        (object(&old_rev), object(&range.head))
+    } else if let Some(base) = base {
+        (object(base), BlobSource::WorkTree)
+    } else if mode == "staged" {
+        (object("HEAD"), object(""))
+    } else {
+        (object(""), BlobSource::WorkTree)
+    };
+
+    Ok(BinaryDiffBlobs {
+        old: read_blob(workspace_root, path, &old, include_content)?,
+        new: read_blob(workspace_root, path, &new, include_content)?,
+    })
+}
+
+fn merge_base(workspace_root: &Path, base: &str, head: &str) -> Result<String, GitError> {
+    let output = cancel::output(git_command(workspace_root).args(["merge-base", base, head]))
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        return Err(GitError::CommandFailed(format!(
+            "No merge base between {} and {}",
+            base, head
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// 读取一侧的版本信息；该侧不存在该文件时返回 `None`
+fn read_blob(
+    workspace_root: &Path,
+    path: &str,
+    source: &BlobSource,
+    include_content: bool,
+) -> Result<Option<BinaryBlobInfo>, GitError> {
+    let (size, bytes) = match source {
+        BlobSource::WorkTree => {
+            let full_path = workspace_root.join(path);
+            let Ok(meta) = std::fs::metadata(&full_path) else {
+                return Ok(None);
+            };
+            if !meta.is_file() {
+                return Ok(None);
+            }
+            let bytes = if meta.len() <= MAX_BLOB_SCAN_SIZE {
+                Some(std::fs::read(&full_path).map_err(GitError::IoError)?)
+            } else {
+                None
+            };
+            (meta.len(), bytes)
+        }
+        BlobSource::Object(spec) => {
+            let output = cancel::output(git_command(workspace_root).args(["cat-file", "-s", spec]))
+                .map_err(GitError::IoError)?;
+            if !output.status.success() {
+                return Ok(None);
+            }
+            let Ok(size) = String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .parse::<u64>()
+            else {
+                return Ok(None);
+            };
+            let bytes = if size <= MAX_BLOB_SCAN_SIZE {
+                let output =
+                    cancel::output(git_command(workspace_root).args(["cat-file", "blob", spec]))
+                        .map_err(GitError::IoError)?;
+                if !output.status.success() {
+                    return Ok(None);
+                }
+                Some(output.stdout)
+            } else {
+                None
+            };
+            (size, bytes)
+        }
+    };
+
+    let (width, height) = bytes
+        .as_deref()
+        .and_then(image_dimensions)
+        .map_or((None, None), |(w, h)| (Some(w), Some(h)));
+    let mime_type = match bytes.as_deref() {
+        Some(bytes) => detect_mime_type(path, bytes),
+        // 内容未读取时只能按扩展名推断
+        None => mime_guess::from_path(path).first().map_or_else(
+            || "application/octet-stream".to_string(),
+            |mime| mime.essence_str().to_string(),
+        ),
+    };
+    let content = bytes.filter(|bytes| include_content && bytes.len() <= MAX_DIFF_SIZE);
+    Ok(Some(BinaryBlobInfo {
+        size,
+        mime_type,
+        width,
+        height,
+        content,
+    }))
+}
+
+fn u16_be(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 2)?;
+    Some(u32::from(u16::from_be_bytes([b[0], b[1]])))
+}
+
+fn u16_le(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 2)?;
+    Some(u32::from(u16::from_le_bytes([b[0], b[1]])))
+}
+
+fn u24_le(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 3)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], 0]))
+}
+
+fn u32_le(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 4)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// 从文件头解析图片宽高（PNG / GIF / JPEG / WebP / BMP），无法识别时返回 `None`
+pub fn image_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") && bytes.get(12..16) == Some(b"IHDR") {
+        let b = bytes.get(16..24)?;
+        return Some((
+            u32::from_be_bytes([b[0], b[1], b[2], b[3]]),
+            u32::from_be_bytes([b[4], b[5], b[6], b[7]]),
+        ));
+    }
+    if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        return Some((u16_le(bytes, 6)?, u16_le(bytes, 8)?));
+    }
+    if bytes.starts_with(b"BM") {
+        let width = i32::from_le_bytes(bytes.get(18..22)?.try_into().ok()?);
+        let height = i32::from_le_bytes(bytes.get(22..26)?.try_into().ok()?);
+        // 高度为负表示自上而下存储
+        return Some((width.unsigned_abs(), height.unsigned_abs()));
+    }
+    if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        return match bytes.get(12..16)? {
+            b"VP8 " => Some((u16_le(bytes, 26)? & 0x3fff, u16_le(bytes, 28)? & 0x3fff)),
+            b"VP8L" => {
+                let bits = u32_le(bytes, 21)?;
+                Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+            }
+            b"VP8X" => Some((u24_le(bytes, 24)? + 1, u24_le(bytes, 27)? + 1)),
+            _ => None,
+        };
+    }
+    if bytes.starts_with(b"\xff\xd8") {
+        return jpeg_dimensions(bytes);
+    }
+    None
+}
+
+/// 逐段扫描 JPEG，读取首个 SOF 段中的尺寸
+fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let mut i = 2;
+    loop {
+        while *bytes.get(i)? != 0xff {
+            i += 1;
+        }
+        while *bytes.get(i)? == 0xff {
+            i += 1;
+        }
+        let marker = *bytes.get(i)?;
+        i += 1;
+        match marker {
+            // 无长度字段的标记
+            0x01 | 0xd0..=0xd7 => continue,
+            // 到达 SOI / EOI / SOS 仍未遇到 SOF
+            0xd8..=0xda => return None,
+            // SOF0..SOF15，排除 DHT / JPG / DAC
+            0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                return Some((u16_be(bytes, i + 5)?, u16_be(bytes, i + 3)?));
+            }
+            _ => i += usize::try_from(u16_be(bytes, i)?).ok()?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let output = git_command(dir).args(args).output().expect("run git");
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        bytes.extend(width.to_be_bytes());
+        bytes.extend(height.to_be_bytes());
+        bytes.extend([8, 6, 0, 0, 0, 0, 0, 0, 0]);
+        bytes
+    }
+
+    #[test]
+    fn image_dimensions_parses_common_headers() {
+        assert_eq!(image_dimensions(&png(640, 480)), Some((640, 480)));
+
+        let gif = b"GIF89a\x20\x03\x58\x02\0\0".to_vec();
+        assert_eq!(image_dimensions(&gif), Some((800, 600)));
+
+        let mut bmp = b"BM".to_vec();
+        bmp.resize(18, 0);
+        bmp.extend(32i32.to_le_bytes());
+        bmp.extend((-16i32).to_le_bytes());
+        assert_eq!(image_dimensions(&bmp), Some((32, 16)));
+
+        let mut webp = b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0\0\0\0\0".to_vec();
+        webp.extend([0x3f, 0x01, 0x00, 0xef, 0x00, 0x00]);
+        assert_eq!(image_dimensions(&webp), Some((320, 240)));
+
+        // SOI，APP0（长度 16），SOF0：精度 8，高 200，宽 300
+        let mut jpeg = vec![0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10];
+        jpeg.extend([0u8; 14]);
+        jpeg.extend([0xff, 0xc0, 0x00, 0x11, 0x08, 0x00, 0xc8, 0x01, 0x2c, 0x03]);
+        assert_eq!(image_dimensions(&jpeg), Some((300, 200)));
+
+        assert_eq!(image_dimensions(b"plain text"), None);
+        assert_eq!(image_dimensions(b"\x89PNG\r\n\x1a\n"), None);
+    }
+
+    #[test]
+    fn binary_diff_blobs_reads_both_sides() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        run_git(root, &["init", "-q"]);
+        std::fs::write(root.join("logo.png"), png(16, 16)).unwrap();
+        run_git(root, &["add", "."]);
+        run_git(
+            root,
+            &[
+                "-c",
+                "user.name=t",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "-q",
+                "-m",
+                "init",
+            ],
+        );
+        std::fs::write(root.join("logo.png"), png(32, 24)).unwrap();
+
+        let blobs = git_binary_diff_blobs(root, "logo.png", None, "working", None, true).unwrap();
+        let old = blobs.old.unwrap();
+        let new = blobs.new.unwrap();
+        assert_eq!((old.width, old.height), (Some(16), Some(16)));
+        assert_eq!((new.width, new.height), (Some(32), Some(24)));
+        assert_eq!(old.mime_type, "image/png");
+        assert_eq!(new.content.as_deref(), Some(png(32, 24).as_slice()));
+
+        // 暂存区与 HEAD 相同时两侧一致；不附带内容
+        let staged = git_binary_diff_blobs(root, "logo.png", None, "staged", None, false).unwrap();
+        assert_eq!(staged.old, staged.new);
+        assert!(staged.new.unwrap().content.is_none());
+
+        // 新增的未跟踪文件没有旧版本
+        std::fs::write(root.join("new.png"), png(1, 1)).unwrap();
+        let added = git_binary_diff_blobs(root, "new.png", None, "working", None, false).unwrap();
+        assert!(added.old.is_none());
+        assert_eq!(added.new.unwrap().size, png(1, 1).len() as u64);
+    }
+
+    #[test]
+    fn is_binary_diff_output_matches_git_placeholder() {
+        assert!(is_binary_diff_output(
+            "diff --git a/x.png b/x.png\nBinary files a/x.png and /dev/null differ\n"
+        ));
+        assert!(!is_binary_diff_output("@@ -1 +1 @@\n-a\n+b\n"));
+    }
+}
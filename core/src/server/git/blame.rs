@@ -0,0 +1,265 @@
+//! Git blame 查询
+//!
+//! 基于 `git blame --incremental` 输出按提交聚合的行区间，供编辑器 gutter 展示。
+//! 支持 `.git-blame-ignore-revs` 与项目级 `[git] ignore_revs_files` 配置，
+//! 避免大规模格式化提交污染行归属。
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::utils::*;
+use crate::util::exec_env::git_command;
+use crate::workspace::config::ProjectConfig;
+
+/// 未配置 `ignore_revs_files` 时默认探测的文件
+pub const DEFAULT_IGNORE_REVS_FILE: &str = ".git-blame-ignore-revs";
+
+// ── 领域类型 ──
+
+/// 一段连续行归属于同一提交的 blame 区间
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlameHunk {
+    /// 完整提交 SHA（未提交的行为全 0）
+    pub sha: String,
+    /// 当前文件中的起始行（1-based）
+    pub start_line: u32,
+    /// 提交中对应的原始起始行（1-based）
+    pub orig_start_line: u32,
+    pub line_count: u32,
+    pub author: String,
+    pub author_email: String,
+    /// 作者时间（Unix 秒）
+    pub author_time: i64,
+    pub summary: String,
+    /// 该提交在原始提交中的路径（跨重命名时与当前路径不同）
+    pub orig_path: String,
+    /// 是否为边界提交（blame 范围的起点）
+    pub boundary: bool,
+}
+
+/// Blame 查询结果
+#[derive(Debug)]
+pub struct BlameResult {
+    pub path: String,
+    pub hunks: Vec<BlameHunk>,
+    /// 实际生效的 ignore-revs 文件（相对工作区根）
+    pub ignore_revs_files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct CommitMeta {
+    author: String,
+    author_email: String,
+    author_time: i64,
+    summary: String,
+    boundary: bool,
+}
+
+/// 解析本次 blame 生效的 ignore-revs 文件列表
+///
+/// 项目配置了 `[git] ignore_revs_files` 时按配置取值（不存在的文件跳过），
+/// 否则仅在 `.git-blame-ignore-revs` 存在时使用它。
+pub fn resolve_ignore_revs_files(workspace_root: &Path) -> Vec<String> {
+    let configured = ProjectConfig::load(workspace_root)
+        .ok()
+        .and_then(|config| config.git.ignore_revs_files);
+    let candidates = configured.unwrap_or_else(|| vec![DEFAULT_IGNORE_REVS_FILE.to_string()]);
+    candidates
+        .into_iter()
+        .filter(|file| {
+            validate_path(workspace_root, file)
+                .map(|full| full.is_file())
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// 获取单个文件的 blame 区间
+///
+/// `rev` 为空时对工作区当前内容 blame（未提交的行归属于全 0 SHA）。
+pub fn git_blame(
+    workspace_root: &Path,
+    path: &str,
+    rev: Option<&str>,
+) -> Result<BlameResult, GitError> {
+    validate_path(workspace_root, path)?;
+
+    if get_git_repo_root(workspace_root).is_none() {
+        return Err(GitError::NotAGitRepo);
+    }
+
+    let ignore_revs_files = resolve_ignore_revs_files(workspace_root);
+
+    let mut args: Vec<String> = vec!["blame".to_string(), "--incremental".to_string()];
+    for file in &ignore_revs_files {
+        args.push("--ignore-revs-file".to_string());
+        args.push(file.clone());
+    }
+    if let Some(rev) = rev.map(str::trim).filter(|r| !r.is_empty()) {
+        if rev.starts_with('-') {
+            return Err(GitError::CommandFailed(format!(
+                "Invalid revision: {}",
+                rev
+            )));
+        }
+        args.push(rev.to_string());
+    }
+    args.push("--".to_string());
+    args.push(path.to_string());
+
+    let output = git_command(workspace_root)
+        .args(&args)
+        .output()
+        .map_err(GitError::IoError)?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(GitError::CommandFailed(stderr));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(BlameResult {
+        path: path.to_string(),
+        hunks: parse_blame_incremental(&stdout),
+        ignore_revs_files,
+    })
+}
+
+/// 解析 `git blame --incremental` 输出
+///
+/// 每个区间以 `<sha> <orig_line> <final_line> <count>` 开头、以 `filename <path>` 结束；
+/// 提交元数据只在该提交首次出现时输出，后续区间复用。
+pub fn parse_blame_incremental(output: &str) -> Vec<BlameHunk> {
+    let mut commits: HashMap<String, CommitMeta> = HashMap::new();
+    let mut hunks = Vec::new();
+    let mut current: Option<(String, u32, u32, u32)> = None;
+
+    for line in output.lines() {
+        let Some((sha, orig_start, start, count)) = current.clone() else {
+            let mut parts = line.split_whitespace();
+            let header = (
+                parts.next(),
+                parts.next().and_then(|v| v.parse::<u32>().ok()),
+                parts.next().and_then(|v| v.parse::<u32>().ok()),
+                parts.next().and_then(|v| v.parse::<u32>().ok()),
+            );
+            if let (Some(sha), Some(orig), Some(start), Some(count)) = header {
+                if sha.len() >= 40 && sha.chars().all(|c| c.is_ascii_hexdigit()) {
+                    commits.entry(sha.to_string()).or_default();
+                    current = Some((sha.to_string(), orig, start, count));
+                }
+            }
+            continue;
+        };
+
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        let meta = commits.entry(sha.clone()).or_default();
+        match key {
+            "author" => meta.author = value.to_string(),
+            "author-mail" => {
+                meta.author_email = value
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string()
+            }
+            "author-time" => meta.author_time = value.parse().unwrap_or(0),
+            "summary" => meta.summary = value.to_string(),
+            "boundary" => meta.boundary = true,
+            "filename" => {
+                let meta = meta.clone();
+                hunks.push(BlameHunk {
+                    sha,
+                    start_line: start,
+                    orig_start_line: orig_start,
+                    line_count: count,
+                    author: meta.author,
+                    author_email: meta.author_email,
+                    author_time: meta.author_time,
+                    summary: meta.summary,
+                    orig_path: value.to_string(),
+                    boundary: meta.boundary,
+                });
+                current = None;
+            }
+            _ => {}
+        }
+    }
+
+    hunks.sort_by_key(|h| h.start_line);
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHA_A: &str = "1111111111111111111111111111111111111111";
+    const SHA_B: &str = "2222222222222222222222222222222222222222";
+
+    #[test]
+    fn parse_incremental_reuses_commit_metadata_and_sorts() {
+        let output = format!(
+            "{SHA_B} 3 3 2\n\
+             author Bob\n\
+             author-mail <bob@example.com>\n\
+             author-time 1700000100\n\
+             author-tz +0000\n\
+             summary Second\n\
+             previous {SHA_A} src/lib.rs\n\
+             filename src/lib.rs\n\
+             {SHA_A} 1 1 2\n\
+             author Alice\n\
+             author-mail <alice@example.com>\n\
+             author-time 1700000000\n\
+             author-tz +0000\n\
+             summary Initial\n\
+             boundary\n\
+             filename src/old.rs\n\
+             {SHA_B} 5 5 1\n\
+             filename src/lib.rs\n"
+        );
+
+        let hunks = parse_blame_incremental(&output);
+        assert_eq!(hunks.len(), 3);
+
+        assert_eq!(hunks[0].sha, SHA_A);
+        assert_eq!(hunks[0].start_line, 1);
+        assert_eq!(hunks[0].line_count, 2);
+        assert_eq!(hunks[0].author_email, "alice@example.com");
+        assert_eq!(hunks[0].orig_path, "src/old.rs");
+        assert!(hunks[0].boundary);
+
+        assert_eq!(hunks[1].sha, SHA_B);
+        assert_eq!(hunks[1].summary, "Second");
+        assert!(!hunks[1].boundary);
+
+        // 第二次出现的提交没有元数据行，应复用首次解析的结果
+        assert_eq!(hunks[2].start_line, 5);
+        assert_eq!(hunks[2].author, "Bob");
+        assert_eq!(hunks[2].author_time, 1_700_000_100);
+    }
+
+    #[test]
+    fn resolve_ignore_revs_files_prefers_config_and_skips_missing() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        assert!(resolve_ignore_revs_files(root).is_empty());
+
+        std::fs::write(root.join(DEFAULT_IGNORE_REVS_FILE), format!("{SHA_A}\n")).unwrap();
+        assert_eq!(
+            resolve_ignore_revs_files(root),
+            vec![DEFAULT_IGNORE_REVS_FILE.to_string()]
+        );
+
+        std::fs::write(root.join("fmt-revs"), format!("{SHA_B}\n")).unwrap();
+        std::fs::write(
+            root.join(".tidyflow.toml"),
+            "[git]\nignore_revs_files = [\"fmt-revs\", \"missing-revs\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            resolve_ignore_revs_files(root),
+            vec!["fmt-revs".to_string()]
+        );
+    }
+}
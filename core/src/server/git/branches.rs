@@ -2,23 +2,31 @@
 //!
 //! Provides functions for listing, switching, and creating branches.
 
-use std::path::Path;
-use std::process::Command;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
+use super::backend::backend_for;
+use super::status::{count_ahead_behind, git_time_to_iso};
 use super::utils::*;
+use crate::util::exec_env::git_command;
 
 /// List local branches and get current branch
 ///
-/// Uses:
-/// - `git rev-parse --abbrev-ref HEAD` for current branch
-/// - `git for-each-ref refs/heads --format="%(refname:short)"` for branch list
+/// 每个分支附带上游、领先/落后提交数、末端提交与所在的其他 worktree，
+/// 经 [`GitBackend`](super::backend::GitBackend) 分派到 gix 或 git 命令行实现。
 pub fn git_branches(workspace_root: &Path) -> Result<GitBranchesResult, GitError> {
+    backend_for(workspace_root).branches(workspace_root)
+}
+
+/// gix 后端：读取本地分支引用与 HEAD
+pub(super) fn git_branches_gix(workspace_root: &Path) -> Result<GitBranchesResult, GitError> {
     let repo = gix::discover(workspace_root).map_err(|_| GitError::NotAGitRepo)?;
     let current = match repo.head_name() {
         Ok(Some(name)) => name.shorten().to_string(),
         Ok(None) => "HEAD".to_string(),
         Err(_) => "HEAD".to_string(),
     };
+    let mut checked_out = checked_out_branches_gix(&repo);
 
     let refs = repo
         .references()
@@ -28,32 +36,281 @@ pub fn git_branches(workspace_root: &Path) -> Result<GitBranchesResult, GitError
         .local_branches()
         .map_err(|e| GitError::CommandFailed(format!("Failed to list local branches: {}", e)))?;
     for item in iter {
-        let reference = item.map_err(|e| {
+        let mut reference = item.map_err(|e| {
             GitError::CommandFailed(format!("Failed to iterate branch refs: {}", e))
         })?;
         let name = reference.name().shorten().to_string();
-        if !name.is_empty() {
-            branches.push(GitBranchInfo { name });
+        if name.is_empty() {
+            continue;
         }
+        let mut info = GitBranchInfo {
+            worktree_path: checked_out.remove(&name),
+            name,
+            ..Default::default()
+        };
+        let Ok(id) = reference.peel_to_id() else {
+            branches.push(info);
+            continue;
+        };
+        let id = id.detach();
+        info.last_commit_sha = Some(id.to_string());
+        info.last_commit_date = repo
+            .find_commit(id)
+            .ok()
+            .and_then(|commit| commit.time().ok())
+            .map(git_time_to_iso);
+
+        if let Some(upstream) = upstream_ref_gix(&repo, reference.name()) {
+            info.upstream = Some(upstream.shorten().to_string());
+            // 上游引用不存在（已删除或尚未 fetch）时不给出计数，与 `git branch -vv` 的 gone 一致
+            let upstream_id = repo
+                .try_find_reference(upstream.as_ref())
+                .ok()
+                .flatten()
+                .and_then(|mut r| r.peel_to_id().ok().map(|id| id.detach()));
+            if let Some(upstream_id) = upstream_id {
+                info.ahead_by = count_ahead_behind(&repo, id, upstream_id).ok();
+                info.behind_by = count_ahead_behind(&repo, upstream_id, id).ok();
+            }
+        }
+        branches.push(info);
     }
     branches.sort_by(|a, b| a.name.cmp(&b.name));
 
     Ok(GitBranchesResult { current, branches })
 }
 
+/// 分支的上游引用；`branch.<name>.remote = .` 时上游就是本地分支本身
+fn upstream_ref_gix(
+    repo: &gix::Repository,
+    name: &gix::refs::FullNameRef,
+) -> Option<gix::refs::FullName> {
+    let direction = gix::remote::Direction::Fetch;
+    let key = format!("branch.{}.remote", name.shorten());
+    if repo.config_snapshot().string(key.as_str()).as_deref() == Some(".".into()) {
+        return repo
+            .branch_remote_ref_name(name, direction)?
+            .ok()
+            .map(|r| r.into_owned());
+    }
+    repo.branch_remote_tracking_ref_name(name, direction)?
+        .ok()
+        .map(|r| r.into_owned())
+}
+
+/// 读取各 worktree 的 HEAD，返回「分支名 → worktree 路径」（跳过当前 worktree）
+fn checked_out_branches_gix(repo: &gix::Repository) -> HashMap<String, String> {
+    let mut heads: Vec<(PathBuf, PathBuf)> = Vec::new();
+    // 链接 worktree 中 common_dir 形如 `.git/worktrees/<id>/../..`，先规范化
+    let common_dir = repo
+        .common_dir()
+        .canonicalize()
+        .unwrap_or_else(|_| repo.common_dir().to_path_buf());
+    // 主 worktree：非 bare 仓库的 `.git` 目录所在目录
+    if common_dir.file_name().is_some_and(|name| name == ".git") {
+        if let Some(parent) = common_dir.parent() {
+            heads.push((common_dir.join("HEAD"), parent.to_path_buf()));
+        }
+    }
+    for proxy in repo.worktrees().unwrap_or_default() {
+        if let Ok(base) = proxy.base() {
+            heads.push((proxy.git_dir().join("HEAD"), base));
+        }
+    }
+
+    let current = repo.workdir().and_then(|dir| dir.canonicalize().ok());
+    let mut result = HashMap::new();
+    for (head_file, worktree) in heads {
+        if current.is_some() && worktree.canonicalize().ok() == current {
+            continue;
+        }
+        let Ok(head) = std::fs::read_to_string(&head_file) else {
+            continue;
+        };
+        if let Some(branch) = head.trim().strip_prefix("ref: refs/heads/") {
+            result.insert(branch.to_string(), worktree.to_string_lossy().to_string());
+        }
+    }
+    result
+}
+
+/// cli 后端
+///
+/// Uses:
+/// - `git symbolic-ref --short -q HEAD` for current branch (detached → "HEAD")
+/// - `git for-each-ref refs/heads` for branch list, upstream tracking, tip commit and worktree
+pub(super) fn git_branches_cli(workspace_root: &Path) -> Result<GitBranchesResult, GitError> {
+    let Some(repo_root) = get_git_repo_root(workspace_root) else {
+        return Err(GitError::NotAGitRepo);
+    };
+    let current = git_command(workspace_root)
+        .args(["symbolic-ref", "--short", "-q", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "HEAD".to_string());
+
+    let output = git_command(workspace_root)
+        .args([
+            "for-each-ref",
+            "--format=%(refname:short)%00%(upstream:short)%00%(upstream:track,nobracket)%00%(objectname)%00%(committerdate:iso-strict)%00%(worktreepath)",
+            "refs/heads",
+        ])
+        .output()
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        return Err(GitError::CommandFailed(format!(
+            "Failed to list local branches: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let current_root = Path::new(&repo_root).canonicalize().ok();
+    let non_empty = |value: &str| (!value.is_empty()).then(|| value.to_string());
+    let mut branches: Vec<GitBranchInfo> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\0');
+            let name = fields.next()?.trim();
+            if name.is_empty() {
+                return None;
+            }
+            let upstream = non_empty(fields.next().unwrap_or_default());
+            let (ahead_by, behind_by) = match upstream {
+                Some(_) => parse_upstream_track(fields.next().unwrap_or_default()),
+                None => (None, None),
+            };
+            let last_commit_sha = non_empty(fields.next().unwrap_or_default());
+            let last_commit_date = non_empty(fields.next().unwrap_or_default());
+            let worktree_path = non_empty(fields.next().unwrap_or_default()).filter(|path| {
+                current_root.is_none() || Path::new(path).canonicalize().ok() != current_root
+            });
+            Some(GitBranchInfo {
+                name: name.to_string(),
+                upstream,
+                ahead_by,
+                behind_by,
+                last_commit_sha,
+                last_commit_date,
+                worktree_path,
+            })
+        })
+        .collect();
+    branches.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(GitBranchesResult { current, branches })
+}
+
+/// 解析 `%(upstream:track,nobracket)`：空串表示与上游一致，`gone` 表示上游引用已不存在
+fn parse_upstream_track(track: &str) -> (Option<i32>, Option<i32>) {
+    let track = track.trim();
+    if track == "gone" {
+        return (None, None);
+    }
+    let (mut ahead, mut behind) = (0, 0);
+    for part in track.split(',').map(str::trim) {
+        if let Some(n) = part.strip_prefix("ahead ") {
+            ahead = n.parse().unwrap_or(0);
+        } else if let Some(n) = part.strip_prefix("behind ") {
+            behind = n.parse().unwrap_or(0);
+        }
+    }
+    (Some(ahead), Some(behind))
+}
+
+/// 分支被其他 worktree 占用时的错误码
+pub const BRANCH_IN_USE_CODE: &str = "branch_in_use";
+
+/// 查找检出了 `branch` 的其他 worktree（`git worktree list --porcelain`），不含当前 worktree
+pub fn branch_worktree_owner(
+    workspace_root: &Path,
+    branch: &str,
+) -> Result<Option<String>, GitError> {
+    let Some(repo_root) = get_git_repo_root(workspace_root) else {
+        return Err(GitError::NotAGitRepo);
+    };
+    let output = git_command(workspace_root)
+        .args(["worktree", "list", "--porcelain"])
+        .output()
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        return Err(GitError::CommandFailed(format!(
+            "Failed to list worktrees: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let current = Path::new(&repo_root).canonicalize().ok();
+    let target = format!("refs/heads/{}", branch);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for block in stdout.split("\n\n") {
+        let mut path = None;
+        let mut head_branch = None;
+        for line in block.lines() {
+            if let Some(value) = line.strip_prefix("worktree ") {
+                path = Some(value);
+            } else if let Some(value) = line.strip_prefix("branch ") {
+                head_branch = Some(value);
+            }
+        }
+        let (Some(path), Some(head_branch)) = (path, head_branch) else {
+            continue;
+        };
+        if head_branch == target && Path::new(path).canonicalize().ok() != current {
+            return Ok(Some(path.to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// 切换前检查分支占用；`force_detach` 时先把占用方 worktree 的 HEAD 分离到同一提交
+fn ensure_branch_available(
+    workspace_root: &Path,
+    branch: &str,
+    force_detach: bool,
+) -> Result<(), GitError> {
+    let Some(worktree_path) = branch_worktree_owner(workspace_root, branch)? else {
+        return Ok(());
+    };
+    if !force_detach {
+        return Err(GitError::BranchInUse {
+            branch: branch.to_string(),
+            worktree_path,
+        });
+    }
+    let output = git_command(&worktree_path)
+        .args(["switch", "--detach"])
+        .output()
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        return Err(GitError::CommandFailed(format!(
+            "Failed to detach HEAD in '{}': {}",
+            worktree_path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
 /// Switch to a different branch
 ///
-/// Uses `git switch <branch>` (Git 2.23+), falls back to `git checkout <branch>`
-pub fn git_switch_branch(workspace_root: &Path, branch: &str) -> Result<GitOpResult, GitError> {
+/// Uses `git switch <branch>` (Git 2.23+), falls back to `git checkout <branch>`.
+/// 目标分支已在其他 worktree 检出时返回 [`GitError::BranchInUse`]；
+/// `force_detach` 为 true 时先分离对方的 HEAD 再切换。
+pub fn git_switch_branch(
+    workspace_root: &Path,
+    branch: &str,
+    force_detach: bool,
+) -> Result<GitOpResult, GitError> {
     // Check if it's a git repo
     if get_git_repo_root(workspace_root).is_none() {
         return Err(GitError::NotAGitRepo);
     }
+    ensure_branch_available(workspace_root, branch, force_detach)?;
 
     // Try git switch first (Git 2.23+)
-    let switch_output = Command::new("git")
+    let switch_output = git_command(workspace_root)
         .args(["switch", branch])
-        .current_dir(workspace_root)
         .output();
 
     match switch_output {
@@ -87,9 +344,8 @@ pub fn git_switch_branch(workspace_root: &Path, branch: &str) -> Result<GitOpRes
     }
 
     // Fallback to git checkout
-    let checkout_output = Command::new("git")
+    let checkout_output = git_command(workspace_root)
         .args(["checkout", branch])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -121,17 +377,18 @@ pub fn git_switch_branch(workspace_root: &Path, branch: &str) -> Result<GitOpRes
 
 /// Create and switch to a new branch
 ///
-/// Uses `git switch -c <branch>` (Git 2.23+), falls back to `git checkout -b <branch>`
+/// Uses `git switch -c <branch>` (Git 2.23+), falls back to `git checkout -b <branch>`.
+/// 同名分支已在其他 worktree 检出时返回 [`GitError::BranchInUse`]。
 pub fn git_create_branch(workspace_root: &Path, branch: &str) -> Result<GitOpResult, GitError> {
     // Check if it's a git repo
     if get_git_repo_root(workspace_root).is_none() {
         return Err(GitError::NotAGitRepo);
     }
+    ensure_branch_available(workspace_root, branch, false)?;
 
     // Try git switch -c first (Git 2.23+)
-    let switch_output = Command::new("git")
+    let switch_output = git_command(workspace_root)
         .args(["switch", "-c", branch])
-        .current_dir(workspace_root)
         .output();
 
     match switch_output {
@@ -165,9 +422,8 @@ pub fn git_create_branch(workspace_root: &Path, branch: &str) -> Result<GitOpRes
     }
 
     // Fallback to git checkout -b
-    let checkout_output = Command::new("git")
+    let checkout_output = git_command(workspace_root)
         .args(["checkout", "-b", branch])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -196,3 +452,71 @@ pub fn git_create_branch(workspace_root: &Path, branch: &str) -> Result<GitOpRes
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_git(dir: &Path, args: &[&str]) -> String {
+        let output = git_command(dir).args(args).output().expect("run git");
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[test]
+    fn switch_to_branch_held_by_other_worktree_requires_force_detach() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("repo");
+        std::fs::create_dir_all(&root).unwrap();
+        run_git(&root, &["init", "-q", "-b", "main"]);
+        run_git(&root, &["config", "user.name", "Alice"]);
+        run_git(&root, &["config", "user.email", "alice@example.com"]);
+        run_git(&root, &["config", "commit.gpgsign", "false"]);
+        std::fs::write(root.join("a.txt"), "a\n").unwrap();
+        run_git(&root, &["add", "a.txt"]);
+        run_git(&root, &["commit", "-q", "-m", "init"]);
+        let wt = tmp.path().join("wt");
+        run_git(
+            &root,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "feature",
+                wt.to_str().unwrap(),
+            ],
+        );
+
+        let owner = branch_worktree_owner(&root, "feature").unwrap().unwrap();
+        assert_eq!(
+            Path::new(&owner).canonicalize().unwrap(),
+            wt.canonicalize().unwrap()
+        );
+        // 当前 worktree 自己检出的分支不算占用
+        assert!(branch_worktree_owner(&wt, "feature").unwrap().is_none());
+        assert!(branch_worktree_owner(&root, "missing").unwrap().is_none());
+
+        assert!(matches!(
+            git_switch_branch(&root, "feature", false),
+            Err(GitError::BranchInUse { ref branch, .. }) if branch == "feature"
+        ));
+        assert!(matches!(
+            git_create_branch(&root, "feature"),
+            Err(GitError::BranchInUse { .. })
+        ));
+        assert_eq!(run_git(&root, &["symbolic-ref", "--short", "HEAD"]), "main");
+
+        let result = git_switch_branch(&root, "feature", true).unwrap();
+        assert!(result.ok, "{:?}", result.message);
+        assert_eq!(
+            run_git(&root, &["symbolic-ref", "--short", "HEAD"]),
+            "feature"
+        );
+        let detached = git_command(&wt)
+            .args(["symbolic-ref", "-q", "HEAD"])
+            .output()
+            .unwrap();
+        assert!(!detached.status.success());
+    }
+}
@@ -0,0 +1,326 @@
+//! Git status 缓存
+//!
+//! 按工作区（+ default_branch）缓存 `git_status` 结果，避免 UI 频繁查询时反复扫描工作树。
+//!
+//! 命中判定：
+//! - 指纹：index / HEAD / 当前分支 ref 的 mtime+len 均未变化；worktree 的 `.git` 为文件，
+//!   经其中的 `gitdir` 与 `commondir` 定位实际的 index 与 ref。
+//! - TTL：作为 watcher 丢事件时的保底淘汰，默认 30 秒，
+//!   可由 `.tidyflow.toml` 的 `[git] status_cache_ttl_secs` 调整（0 关闭缓存）。
+//!
+//! 失效来源：watcher 上报的文件 / Git 变化、经服务端执行的 Git 操作、主机唤醒。
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use super::utils::{resolve_git_dirs, GitStatusResult};
+use crate::workspace::cache_metrics;
+use crate::workspace::config::ProjectConfig;
+
+/// 默认 TTL（秒）
+pub const DEFAULT_STATUS_CACHE_TTL_SECS: u64 = 30;
+
+/// `status_cache_ttl_secs` 允许的上限（秒）
+pub const MAX_STATUS_CACHE_TTL_SECS: u64 = 600;
+
+// ── 指纹类型 ──
+
+/// 单个文件的 mtime + len 指纹（不感知内容，仅感知是否被修改过）
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileFingerprint {
+    mtime_ns: u64,
+    len: u64,
+}
+
+impl FileFingerprint {
+    fn from_path(path: &Path) -> Option<Self> {
+        let meta = std::fs::metadata(path).ok()?;
+        let mtime_ns = meta
+            .modified()
+            .ok()?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .ok()?
+            .as_nanos() as u64;
+        Some(FileFingerprint {
+            mtime_ns,
+            len: meta.len(),
+        })
+    }
+}
+
+/// Git 状态缓存命中判定指纹。
+///
+/// 持有 index、HEAD 和当前分支 ref 文件的 mtime+len 指纹。
+/// 任一文件变化时指纹不匹配，触发重建。
+/// detached HEAD 或无法定位分支 ref 文件时，`branch_ref_fp` 为 None，
+/// 此时仍可依赖 `index_fp` 和 `head_fp` 进行命中判定。
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct GitStatusFingerprint {
+    index_fp: Option<FileFingerprint>,
+    head_fp: Option<FileFingerprint>,
+    branch_ref_fp: Option<FileFingerprint>,
+}
+
+impl GitStatusFingerprint {
+    /// 计算当前工作区的 Git 文件指纹。无需打开仓库，仅 stat 3 个文件。
+    pub(super) fn compute(workspace_root: &Path) -> Self {
+        let (git_dir, common_dir) = resolve_git_dirs(workspace_root).unwrap_or_else(|| {
+            let dot_git = workspace_root.join(".git");
+            (dot_git.clone(), dot_git)
+        });
+        let index_fp = FileFingerprint::from_path(&git_dir.join("index"));
+        let head_fp = FileFingerprint::from_path(&git_dir.join("HEAD"));
+
+        // 从 HEAD 内容解析当前分支 ref，找到对应 ref 文件（worktree 的分支 ref 在主仓库中）
+        let branch_ref_fp = std::fs::read_to_string(git_dir.join("HEAD"))
+            .ok()
+            .and_then(|content| content.strip_prefix("ref: ").map(|s| s.trim().to_string()))
+            .and_then(|ref_name| FileFingerprint::from_path(&common_dir.join(&ref_name)));
+
+        GitStatusFingerprint {
+            index_fp,
+            head_fp,
+            branch_ref_fp,
+        }
+    }
+
+    /// 如果至少有一个文件指纹可采样，则可用于命中判定
+    pub(super) fn is_sampable(&self) -> bool {
+        self.index_fp.is_some() || self.head_fp.is_some()
+    }
+}
+
+// ── 缓存结构 ──
+
+/// 缓存条目（含指纹，用于精确命中判定）
+struct CacheEntry {
+    result: GitStatusResult,
+    fingerprint: GitStatusFingerprint,
+    created_at: Instant,
+    /// 写入时按项目配置确定，命中判定无需再读配置
+    ttl: Duration,
+}
+
+/// 全局 git status 缓存（key 包含 default_branch 以隔离不同分支配置）
+static GIT_STATUS_CACHE: LazyLock<Mutex<HashMap<String, CacheEntry>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 缓存查询结果：未命中时带回已计算的指纹，重建后写回
+pub(super) enum CacheLookup {
+    Hit(GitStatusResult),
+    Miss(GitStatusFingerprint),
+}
+
+pub(super) fn cache_key(workspace_root: &Path, default_branch: &str) -> String {
+    format!("{}#{}", workspace_root.to_string_lossy(), default_branch)
+}
+
+/// 项目配置的 TTL（`[git] status_cache_ttl_secs`），未配置时为默认值
+pub fn status_cache_ttl(workspace_root: &Path) -> Duration {
+    let secs = ProjectConfig::load(workspace_root)
+        .ok()
+        .and_then(|config| config.git.status_cache_ttl_secs)
+        .map(|secs| secs.min(MAX_STATUS_CACHE_TTL_SECS))
+        .unwrap_or(DEFAULT_STATUS_CACHE_TTL_SECS);
+    Duration::from_secs(secs)
+}
+
+/// 查询缓存（先计算指纹，不持锁 stat 3 个文件）
+pub(super) fn lookup(workspace_root: &Path, key: &str) -> CacheLookup {
+    let current_fp = GitStatusFingerprint::compute(workspace_root);
+
+    if let Ok(mut cache) = GIT_STATUS_CACHE.lock() {
+        if let Some(entry) = cache.get(key) {
+            if entry.created_at.elapsed() < entry.ttl {
+                let hit = if current_fp.is_sampable() {
+                    current_fp == entry.fingerprint
+                } else {
+                    // 无法采样指纹（非 git 目录或 .git 不可读），退化为 TTL
+                    true
+                };
+                if hit {
+                    cache_metrics::record_git_cache_hit(key);
+                    return CacheLookup::Hit(entry.result.clone());
+                }
+                cache_metrics::record_git_cache_eviction(key, "fingerprint_changed");
+            } else {
+                cache_metrics::record_git_cache_eviction(key, "ttl_expired");
+            }
+            cache.remove(key);
+        }
+    }
+    cache_metrics::record_git_cache_miss(key);
+    CacheLookup::Miss(current_fp)
+}
+
+/// 写入重建结果；TTL 为 0 时不缓存
+pub(super) fn store(
+    workspace_root: &Path,
+    key: &str,
+    result: &GitStatusResult,
+    fingerprint: GitStatusFingerprint,
+) {
+    let ttl = status_cache_ttl(workspace_root);
+    if ttl.is_zero() {
+        return;
+    }
+    if let Ok(mut cache) = GIT_STATUS_CACHE.lock() {
+        cache.insert(
+            key.to_string(),
+            CacheEntry {
+                result: result.clone(),
+                fingerprint,
+                created_at: Instant::now(),
+                ttl,
+            },
+        );
+    }
+    cache_metrics::record_git_cache_rebuild(key, result.items.len());
+}
+
+/// 使指定工作区的 git status 缓存失效（清除所有 default_branch 变体）
+pub fn invalidate_git_status_cache(workspace_root: &Path) {
+    let root_prefix = format!("{}#", workspace_root.to_string_lossy());
+    if let Ok(mut cache) = GIT_STATUS_CACHE.lock() {
+        let keys_to_remove: Vec<String> = cache
+            .keys()
+            .filter(|k| k.starts_with(&root_prefix))
+            .cloned()
+            .collect();
+        for key in keys_to_remove {
+            cache.remove(&key);
+            cache_metrics::record_git_cache_eviction(&key, "invalidated");
+        }
+    }
+}
+
+/// 使全部工作区的 git status 缓存失效（主机唤醒后仓库可能已被外部修改）
+pub fn invalidate_all_git_status_cache() {
+    if let Ok(mut cache) = GIT_STATUS_CACHE.lock() {
+        for key in cache.keys() {
+            cache_metrics::record_git_cache_eviction(key, "invalidated");
+        }
+        cache.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_result(root: &Path) -> GitStatusResult {
+        GitStatusResult {
+            repo_root: root.to_string_lossy().to_string(),
+            items: vec![],
+            has_staged_changes: false,
+            staged_count: 0,
+            current_branch: None,
+            default_branch: None,
+            ahead_by: None,
+            behind_by: None,
+            compared_branch: None,
+        }
+    }
+
+    #[test]
+    fn hotspot_perf_git_fingerprint_is_sampable_on_nonexistent_path() {
+        // 非 Git 仓库或路径不存在时，指纹应标记为 not sampable，不 panic
+        let root = Path::new("/nonexistent/no_such_repo");
+        let fp = GitStatusFingerprint::compute(root);
+        assert!(!fp.is_sampable(), "non-existent path must not be sampable");
+    }
+
+    #[test]
+    fn hotspot_perf_invalidate_clears_all_branch_variants() {
+        // 验证 invalidate 按前缀匹配清除所有关联 key（多分支场景）
+        let root = Path::new("/tmp/hotspot_invalidate_test");
+        let key_main = cache_key(root, "main");
+        let key_feat = cache_key(root, "feature/foo");
+
+        for key in [&key_main, &key_feat] {
+            store(
+                root,
+                key,
+                &empty_result(root),
+                GitStatusFingerprint::compute(root),
+            );
+        }
+        assert!(matches!(lookup(root, &key_main), CacheLookup::Hit(_)));
+
+        invalidate_git_status_cache(root);
+
+        let cache = GIT_STATUS_CACHE.lock().unwrap();
+        assert!(
+            !cache.contains_key(&key_main),
+            "main variant should be cleared"
+        );
+        assert!(
+            !cache.contains_key(&key_feat),
+            "feature variant should be cleared"
+        );
+    }
+
+    #[test]
+    fn ttl_is_configurable_and_zero_disables_cache() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        assert_eq!(
+            status_cache_ttl(root),
+            Duration::from_secs(DEFAULT_STATUS_CACHE_TTL_SECS)
+        );
+
+        std::fs::write(
+            root.join(".tidyflow.toml"),
+            "[git]\nstatus_cache_ttl_secs = 5000\n",
+        )
+        .unwrap();
+        assert_eq!(
+            status_cache_ttl(root),
+            Duration::from_secs(MAX_STATUS_CACHE_TTL_SECS)
+        );
+
+        std::fs::write(
+            root.join(".tidyflow.toml"),
+            "[git]\nstatus_cache_ttl_secs = 0\n",
+        )
+        .unwrap();
+        let key = cache_key(root, "main");
+        store(
+            root,
+            &key,
+            &empty_result(root),
+            GitStatusFingerprint::compute(root),
+        );
+        assert!(matches!(lookup(root, &key), CacheLookup::Miss(_)));
+    }
+
+    #[test]
+    fn worktree_fingerprint_follows_gitdir_and_commondir() {
+        let temp = tempfile::tempdir().unwrap();
+        let main_git = temp.path().join("repo/.git");
+        let wt_git = main_git.join("worktrees/feat");
+        std::fs::create_dir_all(wt_git.clone()).unwrap();
+        std::fs::create_dir_all(main_git.join("refs/heads")).unwrap();
+        std::fs::write(wt_git.join("commondir"), "../..\n").unwrap();
+        std::fs::write(wt_git.join("HEAD"), "ref: refs/heads/feat\n").unwrap();
+        std::fs::write(wt_git.join("index"), "v1").unwrap();
+        std::fs::write(main_git.join("refs/heads/feat"), "abc\n").unwrap();
+
+        let worktree = temp.path().join("feat");
+        std::fs::create_dir_all(&worktree).unwrap();
+        std::fs::write(
+            worktree.join(".git"),
+            format!("gitdir: {}\n", wt_git.display()),
+        )
+        .unwrap();
+
+        let before = GitStatusFingerprint::compute(&worktree);
+        assert!(before.is_sampable());
+        assert!(before.branch_ref_fp.is_some());
+
+        std::fs::write(wt_git.join("index"), "v2-staged").unwrap();
+        assert_ne!(GitStatusFingerprint::compute(&worktree), before);
+    }
+}
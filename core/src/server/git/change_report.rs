@@ -0,0 +1,431 @@
+//! 分支变更报告导出
+//!
+//! 把工作区分支相对默认分支的变更（文件统计、逐文件 diff、提交列表）渲染为
+//! 单个自包含 HTML 文件，保存在数据目录的 `reports/` 下，可经 HTTP 下载后
+//! 分享给不使用 tidyflow 的评审者。报告不引用任何外部资源，离线可读。
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use super::diff_range::{git_diff_range, RangeDiffFile};
+use super::utils::*;
+use crate::util::exec_env::git_command;
+
+/// 报告内嵌补丁的上限（字节），超出部分截断
+pub const MAX_REPORT_DIFF_SIZE: usize = 4 * 1024 * 1024;
+/// 报告最多列出的提交数
+pub const MAX_REPORT_COMMITS: usize = 500;
+/// 数据目录中最多保留的报告数，超出时删除最旧的
+const MAX_STORED_REPORTS: usize = 50;
+
+/// 报告中的单个提交
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeReportCommit {
+    pub sha: String,
+    pub author: String,
+    /// ISO 8601 作者时间
+    pub date: String,
+    pub subject: String,
+}
+
+/// 生成报告所需的全部数据
+#[derive(Debug, Clone)]
+pub struct ChangeReport {
+    /// 对比基准（默认分支）
+    pub base: String,
+    /// 当前分支名（分离 HEAD 时为短 SHA）
+    pub head: String,
+    pub merge_base: Option<String>,
+    pub files: Vec<RangeDiffFile>,
+    pub total_additions: i32,
+    pub total_deletions: i32,
+    pub files_truncated: bool,
+    pub commits: Vec<ChangeReportCommit>,
+    pub commits_truncated: bool,
+    /// `base...HEAD` 的 unified diff
+    pub diff: String,
+    pub diff_truncated: bool,
+}
+
+/// 收集当前分支相对 `default_branch` 的变更（以合并基为起点，只看本分支一侧）
+pub fn collect_change_report(
+    workspace_root: &Path,
+    default_branch: &str,
+) -> Result<ChangeReport, GitError> {
+    let summary = git_diff_range(workspace_root, &format!("{}...HEAD", default_branch), None)?;
+    let head = match run_git(workspace_root, &["rev-parse", "--abbrev-ref", "HEAD"])?.trim() {
+        "HEAD" => get_short_head_sha(workspace_root).unwrap_or_else(|| "HEAD".to_string()),
+        branch => branch.to_string(),
+    };
+
+    let max_count = format!("--max-count={}", MAX_REPORT_COMMITS + 1);
+    let log_range = format!("{}..HEAD", summary.range.base);
+    let log = run_git(
+        workspace_root,
+        &[
+            "log",
+            &max_count,
+            "--format=%H%x1f%an%x1f%aI%x1f%s%x1e",
+            &log_range,
+            "--",
+        ],
+    )?;
+    let mut commits = parse_report_log(&log);
+    let commits_truncated = commits.len() > MAX_REPORT_COMMITS;
+    commits.truncate(MAX_REPORT_COMMITS);
+
+    let diff = run_git(
+        workspace_root,
+        &["diff", "-M", "--no-color", &summary.range.spec(), "--"],
+    )?;
+    let (diff, diff_truncated) = truncate_at_line(diff, MAX_REPORT_DIFF_SIZE);
+
+    Ok(ChangeReport {
+        base: summary.range.base,
+        head,
+        merge_base: summary.merge_base,
+        files: summary.files,
+        total_additions: summary.total_additions,
+        total_deletions: summary.total_deletions,
+        files_truncated: summary.truncated,
+        commits,
+        commits_truncated,
+        diff,
+        diff_truncated,
+    })
+}
+
+fn run_git(workspace_root: &Path, args: &[&str]) -> Result<String, GitError> {
+    let output = crate::util::cancel::output(git_command(workspace_root).args(args))
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(GitError::CommandFailed(stderr));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// 解析 `%H%x1f%an%x1f%aI%x1f%s%x1e` 格式的日志
+fn parse_report_log(stdout: &str) -> Vec<ChangeReportCommit> {
+    stdout
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').split('\x1f');
+            let sha = fields.next()?;
+            if !is_full_sha(sha) {
+                return None;
+            }
+            Some(ChangeReportCommit {
+                sha: sha.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                subject: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// 在不超过 `max` 字节的最后一个完整行处截断
+fn truncate_at_line(mut text: String, max: usize) -> (String, bool) {
+    if text.len() <= max {
+        return (text, false);
+    }
+    let mut cut = max;
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    let cut = text[..cut].rfind('\n').map_or(cut, |pos| pos + 1);
+    text.truncate(cut);
+    (text, true)
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            ch => out.push(ch),
+        }
+    }
+    out
+}
+
+const REPORT_STYLE: &str = "\
+body{font:14px/1.5 -apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;margin:0 auto;max-width:1100px;padding:24px;color:#1f2328}\
+h1{font-size:22px;margin:0 0 4px}h2{font-size:17px;margin:32px 0 8px;border-bottom:1px solid #d0d7de;padding-bottom:4px}\
+.meta{color:#59636e}.stats span{margin-right:16px}.add{color:#1a7f37}.del{color:#cf222e}.note{color:#9a6700}\
+table{border-collapse:collapse;width:100%}td,th{text-align:left;padding:4px 8px;border-bottom:1px solid #eaeef2;vertical-align:top}\
+td.num{text-align:right;white-space:nowrap;font-variant-numeric:tabular-nums}code,pre{font:12px/1.45 ui-monospace,SFMono-Regular,Menlo,monospace}\
+.file{border:1px solid #d0d7de;border-radius:6px;margin:16px 0;overflow:hidden}.file h3{margin:0;padding:8px 12px;font-size:13px;background:#f6f8fa;border-bottom:1px solid #d0d7de}\
+.file pre{margin:0;padding:8px 0;overflow-x:auto}.file pre div{padding:0 12px;white-space:pre}\
+.l-add{background:#dafbe1}.l-del{background:#ffebe9}.l-hunk{background:#ddf4ff;color:#59636e}.l-meta{color:#59636e}";
+
+/// 渲染为自包含 HTML
+pub fn render_change_report_html(
+    report: &ChangeReport,
+    project: &str,
+    workspace: &str,
+    generated_at: &str,
+) -> String {
+    let mut html = String::with_capacity(report.diff.len() * 2 + 16 * 1024);
+    let title = format!(
+        "{} / {}: {} → {}",
+        project, workspace, report.head, report.base
+    );
+    let _ = writeln!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\"><head><meta charset=\"utf-8\">\
+         <meta name=\"viewport\" content=\"width=device-width,initial-scale=1\">\
+         <title>{}</title><style>{}</style></head><body>",
+        escape_html(&title),
+        REPORT_STYLE
+    );
+    let _ = writeln!(
+        html,
+        "<h1>{}</h1><div class=\"meta\">Generated {}{}</div>",
+        escape_html(&title),
+        escape_html(generated_at),
+        report
+            .merge_base
+            .as_deref()
+            .map(|sha| format!(" · merge base <code>{}</code>", escape_html(short_sha(sha))))
+            .unwrap_or_default()
+    );
+    let _ = writeln!(
+        html,
+        "<p class=\"stats\"><span>{} files changed</span><span class=\"add\">+{}</span>\
+         <span class=\"del\">−{}</span><span>{} commits</span></p>",
+        report.files.len(),
+        report.total_additions,
+        report.total_deletions,
+        report.commits.len()
+    );
+
+    html.push_str("<h2>Commits</h2>\n");
+    if report.commits.is_empty() {
+        html.push_str("<p class=\"meta\">No commits ahead of the base branch.</p>\n");
+    } else {
+        html.push_str(
+            "<table><tr><th>Commit</th><th>Subject</th><th>Author</th><th>Date</th></tr>\n",
+        );
+        for commit in &report.commits {
+            let _ = writeln!(
+                html,
+                "<tr><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(short_sha(&commit.sha)),
+                escape_html(&commit.subject),
+                escape_html(&commit.author),
+                escape_html(&commit.date)
+            );
+        }
+        html.push_str("</table>\n");
+        if report.commits_truncated {
+            let _ = writeln!(
+                html,
+                "<p class=\"note\">Only the latest {} commits are listed.</p>",
+                MAX_REPORT_COMMITS
+            );
+        }
+    }
+
+    html.push_str("<h2>Files</h2>\n");
+    if report.files.is_empty() {
+        html.push_str("<p class=\"meta\">No changes.</p>\n");
+    } else {
+        html.push_str("<table><tr><th></th><th>Path</th><th>Added</th><th>Removed</th></tr>\n");
+        for file in &report.files {
+            let path = match &file.orig_path {
+                Some(orig) => format!("{} → {}", orig, file.path),
+                None => file.path.clone(),
+            };
+            let count =
+                |value: Option<i32>| value.map_or_else(|| "binary".to_string(), |v| v.to_string());
+            let _ = writeln!(
+                html,
+                "<tr><td><code>{}</code></td><td>{}</td><td class=\"num add\">{}</td>\
+                 <td class=\"num del\">{}</td></tr>",
+                escape_html(&file.code),
+                escape_html(&path),
+                count(file.additions),
+                count(file.deletions)
+            );
+        }
+        html.push_str("</table>\n");
+        if report.files_truncated {
+            html.push_str("<p class=\"note\">The file list was truncated.</p>\n");
+        }
+    }
+
+    html.push_str("<h2>Diff</h2>\n");
+    render_diff(&mut html, &report.diff);
+    if report.diff_truncated {
+        let _ = writeln!(
+            html,
+            "<p class=\"note\">The diff exceeds {} MB and was truncated.</p>",
+            MAX_REPORT_DIFF_SIZE / (1024 * 1024)
+        );
+    }
+    html.push_str("</body></html>\n");
+    html
+}
+
+/// 按 `diff --git` 拆成逐文件的区块并按行着色
+fn render_diff(html: &mut String, diff: &str) {
+    let mut in_file = false;
+    for line in diff.lines() {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            if in_file {
+                html.push_str("</pre></div>\n");
+            }
+            in_file = true;
+            let name = header.split_once(" b/").map_or(header, |(_, path)| path);
+            let _ = write!(
+                html,
+                "<div class=\"file\"><h3>{}</h3><pre>",
+                escape_html(name)
+            );
+            continue;
+        }
+        if !in_file {
+            continue;
+        }
+        let class = if line.starts_with("@@") {
+            "l-hunk"
+        } else if line.starts_with("+++") || line.starts_with("---") {
+            "l-meta"
+        } else if line.starts_with('+') {
+            "l-add"
+        } else if line.starts_with('-') {
+            "l-del"
+        } else if line.starts_with(' ') || line.is_empty() {
+            ""
+        } else {
+            "l-meta"
+        };
+        let _ = writeln!(html, "<div class=\"{}\">{}</div>", class, escape_html(line));
+    }
+    if in_file {
+        html.push_str("</pre></div>\n");
+    }
+}
+
+fn short_sha(sha: &str) -> &str {
+    sha.get(..10).unwrap_or(sha)
+}
+
+/// 报告保存目录
+pub fn change_reports_dir() -> PathBuf {
+    crate::util::paths::tidyflow_home_dir().join("reports")
+}
+
+/// 保存报告并返回报告 ID；同时清理超出保留数量的旧报告
+pub fn save_change_report(dir: &Path, html: &str) -> std::io::Result<String> {
+    std::fs::create_dir_all(dir)?;
+    // v7 UUID 按时间排序，文件名顺序即生成顺序
+    let report_id = uuid::Uuid::now_v7().to_string();
+    std::fs::write(dir.join(format!("{}.html", report_id)), html)?;
+    prune_change_reports(dir, MAX_STORED_REPORTS);
+    Ok(report_id)
+}
+
+/// 收集、渲染并保存报告，返回报告数据、报告 ID 与文件大小
+pub fn generate_change_report(
+    workspace_root: &Path,
+    project: &str,
+    workspace: &str,
+    default_branch: &str,
+) -> Result<(ChangeReport, String, u64), GitError> {
+    let report = collect_change_report(workspace_root, default_branch)?;
+    let generated_at = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let html = render_change_report_html(&report, project, workspace, &generated_at);
+    let report_id = save_change_report(&change_reports_dir(), &html).map_err(GitError::IoError)?;
+    Ok((report, report_id, html.len() as u64))
+}
+
+fn prune_change_reports(dir: &Path, keep: usize) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut reports: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "html"))
+        .collect();
+    if reports.len() <= keep {
+        return;
+    }
+    reports.sort();
+    for path in &reports[..reports.len() - keep] {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// 按报告 ID 定位已保存的报告；ID 非法或报告不存在时返回 None
+pub fn change_report_path(dir: &Path, report_id: &str) -> Option<PathBuf> {
+    let id = uuid::Uuid::parse_str(report_id).ok()?;
+    let path = dir.join(format!("{}.html", id.hyphenated()));
+    path.is_file().then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_AUTHOR_NAME", "Tester")
+            .env("GIT_AUTHOR_EMAIL", "t@example.com")
+            .env("GIT_COMMITTER_NAME", "Tester")
+            .env("GIT_COMMITTER_EMAIL", "t@example.com")
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn renders_branch_changes_as_escaped_html() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        git(root, &["init", "-q", "-b", "main"]);
+        std::fs::write(root.join("a.txt"), "one\n").unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-q", "-m", "init"]);
+        git(root, &["checkout", "-q", "-b", "feature"]);
+        std::fs::write(root.join("a.txt"), "one\n<script>two</script>\n").unwrap();
+        git(root, &["commit", "-q", "-am", "Add <b>two</b>"]);
+
+        let report = collect_change_report(root, "main").unwrap();
+        assert_eq!(report.head, "feature");
+        assert_eq!(report.files.len(), 1);
+        assert_eq!(report.total_additions, 1);
+        assert_eq!(report.commits.len(), 1);
+        assert_eq!(report.commits[0].subject, "Add <b>two</b>");
+
+        let html = render_change_report_html(&report, "p", "w", "2026-01-01T00:00:00Z");
+        assert!(html.contains("<div class=\"l-add\">+&lt;script&gt;two&lt;/script&gt;</div>"));
+        assert!(html.contains("Add &lt;b&gt;two&lt;/b&gt;"));
+        assert!(!html.contains("<script>"));
+
+        let dir = root.join("reports");
+        let id = save_change_report(&dir, &html).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(change_report_path(&dir, &id).unwrap()).unwrap(),
+            html
+        );
+        assert!(change_report_path(&dir, "../a").is_none());
+    }
+
+    #[test]
+    fn truncates_diff_on_line_boundary() {
+        let (text, truncated) = truncate_at_line("aaa\nbbb\nccc\n".to_string(), 9);
+        assert_eq!((text.as_str(), truncated), ("aaa\nbbb\n", true));
+        let (text, truncated) = truncate_at_line("aaa\n".to_string(), 9);
+        assert_eq!((text.as_str(), truncated), ("aaa\n", false));
+    }
+}
@@ -3,46 +3,144 @@
 //! Provides functions for committing, fetching, and rebasing.
 
 use std::path::Path;
-use std::process::Command;
 
+use super::rebase_interactive::clear_interactive_rebase_files;
 use super::sequencer::{is_cherry_picking, is_reverting, read_current_sequencer_commit, read_sequencer_pending_commits};
-use super::status::invalidate_git_status_cache;
+use super::fetch_coordinator::{coalesce_fetch, fetch_coordination_key};
+use super::network::{apply_git_proxy, ensure_online};
+use super::progress::{run_git_streaming, strip_progress_lines};
+use super::cache::invalidate_git_status_cache;
 use super::utils::*;
+use crate::util::exec_env::git_command;
+use crate::workspace::project::CloneProgress;
+
+/// 提交信息为空
+pub const COMMIT_ERROR_EMPTY_MESSAGE: &str = "empty_message";
+/// 暂存区为空
+pub const COMMIT_ERROR_NOTHING_STAGED: &str = "nothing_staged";
+/// 未配置 user.name / user.email
+pub const COMMIT_ERROR_IDENTITY: &str = "identity_missing";
+/// pre-commit / commit-msg 等钩子失败
+pub const COMMIT_ERROR_HOOK: &str = "hook_failed";
+/// GPG / SSH / X.509 签名失败（多为密钥需要 pinentry 输入口令而 Core 无法弹出）
+pub const COMMIT_ERROR_SIGNING: &str = "signing_failed";
+/// 其他失败
+pub const COMMIT_ERROR_OTHER: &str = "commit_failed";
+
+/// stderr 中表示签名失败的片段
+const SIGNING_FAILURE_MARKERS: &[&str] = &[
+    "gpg failed to sign",
+    "failed to sign the data",
+    "signing failed",
+    "no secret key",
+    "no default secret key",
+    "inappropriate ioctl for device",
+    "pinentry",
+    "bad passphrase",
+    "incorrect passphrase",
+    "ssh-keygen",
+    "couldn't sign",
+    "load key",
+    "gpgsm",
+];
+
+/// 仓库的提交签名配置（`commit.gpgsign` / `gpg.format` / `user.signingkey`）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitSigningConfig {
+    /// `commit.gpgsign`：默认是否签名
+    pub enabled: bool,
+    /// `gpg.format`：`openpgp`（默认）/ `ssh` / `x509`
+    pub format: String,
+    pub signing_key: Option<String>,
+    /// `gpg.<format>.program`，openpgp 兼容 `gpg.program`
+    pub program: Option<String>,
+}
+
+/// 读取工作区生效的签名配置（含全局与仓库级）
+pub fn commit_signing_config(workspace_root: &Path) -> CommitSigningConfig {
+    let get = |key: &str| {
+        git_command(workspace_root)
+            .args(["config", "--get", key])
+            .output()
+            .ok()
+            .filter(|out| out.status.success())
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let enabled = git_command(workspace_root)
+        .args(["config", "--type=bool", "--get", "commit.gpgsign"])
+        .output()
+        .ok()
+        .is_some_and(|out| out.status.success() && out.stdout.trim_ascii() == b"true");
+    let format = get("gpg.format").unwrap_or_else(|| "openpgp".to_string());
+    let program = get(&format!("gpg.{}.program", format))
+        .or_else(|| (format == "openpgp").then(|| get("gpg.program")).flatten());
+    CommitSigningConfig {
+        enabled,
+        format,
+        signing_key: get("user.signingkey"),
+        program,
+    }
+}
 
 /// Commit staged changes
 ///
 /// Uses `git commit -m <message>` to create a commit.
 /// Returns the short SHA of the new commit on success.
-pub fn git_commit(workspace_root: &Path, message: &str) -> Result<GitCommitResult, GitError> {
+/// `sign` 覆盖仓库的 `commit.gpgsign`：`Some(true)` 加 `-S`，`Some(false)` 加 `--no-gpg-sign`，
+/// `None` 沿用配置。
+pub fn git_commit(
+    workspace_root: &Path,
+    message: &str,
+    sign: Option<bool>,
+) -> Result<GitCommitResult, GitError> {
     // Check if it's a git repo
     if get_git_repo_root(workspace_root).is_none() {
         return Err(GitError::NotAGitRepo);
     }
+    let failure = |code: &str, message: String| GitCommitResult {
+        ok: false,
+        message: Some(message),
+        sha: None,
+        signed: false,
+        error_code: Some(code.to_string()),
+    };
 
     // Validate message is not empty
     let trimmed_message = message.trim();
     if trimmed_message.is_empty() {
-        return Ok(GitCommitResult {
-            ok: false,
-            message: Some("Commit message cannot be empty".to_string()),
-            sha: None,
-        });
+        return Ok(failure(
+            COMMIT_ERROR_EMPTY_MESSAGE,
+            "Commit message cannot be empty".to_string(),
+        ));
     }
 
     // Check if there are staged changes
     let (has_staged, _) = check_staged_changes(workspace_root);
     if !has_staged {
-        return Ok(GitCommitResult {
-            ok: false,
-            message: Some("No staged changes to commit".to_string()),
-            sha: None,
-        });
+        return Ok(failure(
+            COMMIT_ERROR_NOTHING_STAGED,
+            "No staged changes to commit".to_string(),
+        ));
     }
 
+    let signing = commit_signing_config(workspace_root);
+    let wants_signature = sign.unwrap_or(signing.enabled);
+
     // Run git commit
-    let output = Command::new("git")
-        .args(["commit", "-m", trimmed_message])
-        .current_dir(workspace_root)
+    let mut cmd = git_command(workspace_root);
+    cmd.arg("commit");
+    match sign {
+        Some(true) => {
+            cmd.arg("-S");
+        }
+        Some(false) => {
+            cmd.arg("--no-gpg-sign");
+        }
+        None => {}
+    }
+    let output = cmd
+        .args(["-m", trimmed_message])
         .output()
         .map_err(GitError::IoError)?;
 
@@ -57,35 +155,78 @@ pub fn git_commit(workspace_root: &Path, message: &str) -> Result<GitCommitResul
                 sha.as_deref().unwrap_or("unknown")
             )),
             sha,
+            signed: wants_signature && head_is_signed(workspace_root),
+            error_code: None,
         })
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-        // Check for common errors and provide helpful messages
-        let error_msg = if stderr.contains("user.name") || stderr.contains("user.email") {
-            "Git identity not configured. Run: git config user.name \"Your Name\" && git config user.email \"you@example.com\"".to_string()
-        } else if stderr.contains("pre-commit") || stderr.contains("hook") {
-            format!("Pre-commit hook failed: {}", stderr)
-        } else if stderr.is_empty() {
-            "Commit failed".to_string()
+        let (code, error_msg) = classify_commit_failure(&stderr, wants_signature, &signing);
+        Ok(failure(code, error_msg))
+    }
+}
+
+/// 按 stderr 归类提交失败，返回错误码与提示信息
+fn classify_commit_failure(
+    stderr: &str,
+    wants_signature: bool,
+    signing: &CommitSigningConfig,
+) -> (&'static str, String) {
+    let lower = stderr.to_ascii_lowercase();
+    let signing_failed = wants_signature
+        && (SIGNING_FAILURE_MARKERS.iter().any(|m| lower.contains(m))
+            || lower.contains("failed to write commit object"));
+    // Check for common errors and provide helpful messages
+    if signing_failed {
+        let agent = if signing.format == "ssh" {
+            "ssh-agent"
         } else {
-            stderr
+            "gpg-agent"
         };
+        (
+            COMMIT_ERROR_SIGNING,
+            format!(
+                "Commit signing failed ({}): {}. If the key needs a passphrase, unlock it in a terminal or cache it in {} first, or commit with sign = false",
+                signing.format, stderr, agent
+            ),
+        )
+    } else if stderr.contains("user.name") || stderr.contains("user.email") {
+        (
+            COMMIT_ERROR_IDENTITY,
+            "Git identity not configured. Run: git config user.name \"Your Name\" && git config user.email \"you@example.com\"".to_string(),
+        )
+    } else if stderr.contains("pre-commit") || stderr.contains("hook") {
+        (
+            COMMIT_ERROR_HOOK,
+            format!("Pre-commit hook failed: {}", stderr),
+        )
+    } else if stderr.is_empty() {
+        (COMMIT_ERROR_OTHER, "Commit failed".to_string())
+    } else {
+        (COMMIT_ERROR_OTHER, stderr.to_string())
+    }
+}
 
-        Ok(GitCommitResult {
-            ok: false,
-            message: Some(error_msg),
-            sha: None,
+/// HEAD 提交对象是否带 `gpgsig` 头
+fn head_is_signed(workspace_root: &Path) -> bool {
+    git_command(workspace_root)
+        .args(["cat-file", "commit", "HEAD"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .is_some_and(|out| {
+            String::from_utf8_lossy(&out.stdout)
+                .lines()
+                .take_while(|line| !line.is_empty())
+                .any(|line| line.starts_with("gpgsig"))
         })
-    }
 }
 
 /// Check if there are staged changes
 ///
 /// Uses `git diff --cached --name-only` to list staged files.
 fn check_staged_changes(workspace_root: &Path) -> (bool, usize) {
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["diff", "--cached", "--name-only"])
-        .current_dir(workspace_root)
         .output();
 
     match output {
@@ -101,18 +242,38 @@ fn check_staged_changes(workspace_root: &Path) -> (bool, usize) {
 /// Fetch from remote
 ///
 /// Uses `git fetch` to update remote tracking branches.
+/// 同一仓库（含其各 worktree）的并发调用合并为一次实际 fetch，见 [`coalesce_fetch`]。
 pub fn git_fetch(workspace_root: &Path) -> Result<GitOpResult, GitError> {
+    git_fetch_with_progress(workspace_root, &mut |_| {})
+}
+
+/// 同 [`git_fetch`]，并逐行回调 `git fetch --progress` 的进度；所在请求被取消时终止 fetch
+///
+/// 合并到他人进行中的 fetch 时不回调进度。
+pub fn git_fetch_with_progress(
+    workspace_root: &Path,
+    on_progress: &mut dyn FnMut(CloneProgress),
+) -> Result<GitOpResult, GitError> {
     if get_git_repo_root(workspace_root).is_none() {
         return Err(GitError::NotAGitRepo);
     }
+    ensure_online("fetch")?;
 
-    let output = Command::new("git")
-        .args(["fetch"])
-        .current_dir(workspace_root)
-        .output()
-        .map_err(GitError::IoError)?;
+    coalesce_fetch(fetch_coordination_key(workspace_root), || {
+        run_git_fetch(workspace_root, on_progress)
+    })
+}
 
-    if output.status.success() {
+fn run_git_fetch(
+    workspace_root: &Path,
+    on_progress: &mut dyn FnMut(CloneProgress),
+) -> Result<GitOpResult, GitError> {
+    let mut cmd = git_command(workspace_root);
+    apply_git_proxy(&mut cmd, Some(workspace_root));
+    cmd.args(["fetch", "--progress"]);
+    let (status, stderr) = run_git_streaming(&mut cmd, on_progress)?;
+
+    if status.success() {
         Ok(GitOpResult {
             op: "fetch".to_string(),
             ok: true,
@@ -121,7 +282,7 @@ pub fn git_fetch(workspace_root: &Path) -> Result<GitOpResult, GitError> {
             scope: "all".to_string(),
         })
     } else {
-        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let stderr = strip_progress_lines(&stderr);
         Ok(GitOpResult {
             op: "fetch".to_string(),
             ok: false,
@@ -171,9 +332,8 @@ pub fn git_op_status(workspace_root: &Path) -> Result<GitOpStatusResult, GitErro
     // Get onto branch for rebase
     let onto = if state == GitOpState::Rebasing {
         // Try to read the onto ref
-        let output = Command::new("git")
+        let output = git_command(workspace_root)
             .args(["rev-parse", "--git-path", "rebase-merge/onto"])
-            .current_dir(workspace_root)
             .output();
 
         if let Ok(out) = output {
@@ -228,6 +388,15 @@ pub fn git_op_status(workspace_root: &Path) -> Result<GitOpStatusResult, GitErro
 ///
 /// Uses `git rebase <onto_branch>`. Returns conflict info if rebase pauses.
 pub fn git_rebase(workspace_root: &Path, onto_branch: &str) -> Result<GitRebaseResult, GitError> {
+    git_rebase_with_progress(workspace_root, onto_branch, &mut |_| {})
+}
+
+/// 同 [`git_rebase`]，并回调 `Rebasing (n/m)` 进度；所在请求被取消时终止 rebase 并回滚到开始前的状态
+pub fn git_rebase_with_progress(
+    workspace_root: &Path,
+    onto_branch: &str,
+    on_progress: &mut dyn FnMut(CloneProgress),
+) -> Result<GitRebaseResult, GitError> {
     if get_git_repo_root(workspace_root).is_none() {
         return Err(GitError::NotAGitRepo);
     }
@@ -245,13 +414,23 @@ pub fn git_rebase(workspace_root: &Path, onto_branch: &str) -> Result<GitRebaseR
         });
     }
 
-    let output = Command::new("git")
-        .args(["rebase", onto_branch])
-        .current_dir(workspace_root)
-        .output()
-        .map_err(GitError::IoError)?;
+    let mut cmd = git_command(workspace_root);
+    cmd.args(["rebase", onto_branch]);
+    let (status, stderr) = match run_git_streaming(&mut cmd, on_progress) {
+        Err(GitError::IoError(e)) if crate::util::cancel::is_cancelled_error(&e) => {
+            // 取消后回到 rebase 开始前的状态
+            if is_rebasing(workspace_root) {
+                let _ = git_command(workspace_root)
+                    .args(["rebase", "--abort"])
+                    .output();
+            }
+            invalidate_git_status_cache(workspace_root);
+            return Err(GitError::IoError(e));
+        }
+        result => result?,
+    };
 
-    if output.status.success() {
+    if status.success() {
         Ok(GitRebaseResult {
             ok: true,
             state: "completed".to_string(),
@@ -272,7 +451,7 @@ pub fn git_rebase(workspace_root: &Path, onto_branch: &str) -> Result<GitRebaseR
                 conflict_files,
             })
         } else {
-            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            let stderr = strip_progress_lines(&stderr);
             Ok(GitRebaseResult {
                 ok: false,
                 state: "error".to_string(),
@@ -304,12 +483,17 @@ pub fn git_rebase_continue(workspace_root: &Path) -> Result<GitRebaseResult, Git
         });
     }
 
-    let output = Command::new("git")
+    // 非交互环境下不能等待编辑器；squash/reword 的消息由交互式 rebase 的 exec 步骤负责
+    let output = git_command(workspace_root)
         .args(["rebase", "--continue"])
-        .current_dir(workspace_root)
+        .env("GIT_EDITOR", "true")
         .output()
         .map_err(GitError::IoError)?;
 
+    if !is_rebasing(workspace_root) {
+        clear_interactive_rebase_files(workspace_root);
+    }
+
     if output.status.success() {
         // Check if rebase is complete
         if is_rebasing(workspace_root) {
@@ -389,13 +573,13 @@ pub fn git_rebase_abort(workspace_root: &Path) -> Result<GitRebaseResult, GitErr
         });
     }
 
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["rebase", "--abort"])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
     if output.status.success() {
+        clear_interactive_rebase_files(workspace_root);
         Ok(GitRebaseResult {
             ok: true,
             state: "aborted".to_string(),
@@ -460,6 +644,11 @@ pub fn git_conflict_detail(
         base_content,
         ours_content,
         theirs_content,
+        regions: if is_binary {
+            Vec::new()
+        } else {
+            parse_conflict_regions(&current_content)
+        },
         current_content,
         conflict_markers_count,
         is_binary,
@@ -469,9 +658,8 @@ pub fn git_conflict_detail(
 /// 读取 git 暂存区指定 stage 的 blob 内容
 fn read_git_blob_stage(workspace_root: &Path, path: &str, stage: u8) -> Option<String> {
     let blob_ref = format!(":{stage}:{path}");
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["show", &blob_ref])
-        .current_dir(workspace_root)
         .output()
         .ok()?;
     if output.status.success() {
@@ -489,9 +677,8 @@ pub fn git_conflict_accept_ours(
 ) -> Result<ConflictActionResult, GitError> {
     let _full_path = validate_path(workspace_root, path)?;
 
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["checkout", "--ours", "--", path])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -529,9 +716,8 @@ pub fn git_conflict_accept_theirs(
 ) -> Result<ConflictActionResult, GitError> {
     let _full_path = validate_path(workspace_root, path)?;
 
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["checkout", "--theirs", "--", path])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -609,11 +795,43 @@ pub fn git_conflict_mark_resolved(
     })
 }
 
+/// 以自定义内容解决冲突：写入文件后 git add
+///
+/// 内容中仍残留 `<<<<<<<` / `>>>>>>>` 冲突标记时拒绝写入，返回 ok = false。
+pub fn git_conflict_resolve_custom(
+    workspace_root: &Path,
+    path: &str,
+    context: &str,
+    content: &str,
+) -> Result<ConflictActionResult, GitError> {
+    let full_path = validate_path(workspace_root, path)?;
+
+    if has_conflict_markers(content) {
+        return Ok(ConflictActionResult {
+            ok: false,
+            action: "resolve_custom".to_string(),
+            message: Some("Content still contains conflict markers".to_string()),
+            snapshot: build_conflict_snapshot(workspace_root, context),
+        });
+    }
+
+    std::fs::write(&full_path, content.as_bytes()).map_err(GitError::IoError)?;
+
+    git_stage_file(workspace_root, path)?;
+    invalidate_git_status_cache(workspace_root);
+
+    Ok(ConflictActionResult {
+        ok: true,
+        action: "resolve_custom".to_string(),
+        message: None,
+        snapshot: build_conflict_snapshot(workspace_root, context),
+    })
+}
+
 /// 内部辅助：暂存单个文件
 fn git_stage_file(workspace_root: &Path, path: &str) -> Result<(), GitError> {
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["add", "--", path])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -0,0 +1,633 @@
+//! 提交信息建议：按项目模板组合工作区上下文与暂存区摘要
+//!
+//! `.tidyflow.toml` 的 `[git] commit_template` 决定建议的提交信息格式，
+//! 默认为 `{issue} {type}: {summary}`。支持的占位符：
+//! - `{issue}`：关联链接中的 issue 编号（`#123`、`ABC-123`），缺省时从分支名提取
+//! - `{type}`：分支前缀（`feat/`、`fix/` 等）对应的类型，缺省时按暂存文件推断
+//! - `{scope}`：暂存文件最深公共目录名，全部位于根目录时为空
+//! - `{summary}`：暂存区变更摘要（如 `add login.rs`、`update 3 files in src/auth`）
+//! - `{branch}` / `{workspace}`：当前分支名与工作区名
+//!
+//! 取值为空的占位符会连同留下的空括号与多余空白一起去掉。
+
+use std::path::{Path, PathBuf};
+
+use super::commit_message_command::{
+    staged_diff_for_command, CommitMessageCommandContext, CommitMessageGenerator,
+    MAX_COMMAND_INPUT_BYTES,
+};
+use super::diff_range::parse_name_status_z;
+use super::utils::GitError;
+use crate::util::exec_env::git_command;
+use crate::util::template::{self, Token};
+use crate::workspace::config::ProjectConfig;
+
+/// `message` 由模板渲染
+pub const SUGGESTION_SOURCE_TEMPLATE: &str = "template";
+/// `message` 由外部命令生成
+pub const SUGGESTION_SOURCE_COMMAND: &str = "command";
+/// `message` 由 HTTP 端点生成
+pub const SUGGESTION_SOURCE_ENDPOINT: &str = "endpoint";
+
+/// 未配置模板时的默认值
+pub const DEFAULT_COMMIT_TEMPLATE: &str = "{issue} {type}: {summary}";
+
+const PLACEHOLDERS: &[&str] = &["issue", "type", "scope", "summary", "branch", "workspace"];
+
+/// 分支前缀 -> 提交类型
+const BRANCH_TYPE_PREFIXES: &[(&str, &str)] = &[
+    ("feat", "feat"),
+    ("feature", "feat"),
+    ("fix", "fix"),
+    ("bugfix", "fix"),
+    ("hotfix", "fix"),
+    ("docs", "docs"),
+    ("doc", "docs"),
+    ("refactor", "refactor"),
+    ("perf", "perf"),
+    ("test", "test"),
+    ("tests", "test"),
+    ("chore", "chore"),
+    ("build", "build"),
+    ("ci", "ci"),
+    ("style", "style"),
+];
+
+/// 渲染模板所需的取值
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CommitMessageContext<'a> {
+    pub issue: Option<&'a str>,
+    pub commit_type: &'a str,
+    pub scope: Option<&'a str>,
+    pub summary: &'a str,
+    pub branch: &'a str,
+    pub workspace: &'a str,
+}
+
+/// 暂存区中的一项变更
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StagedChange {
+    /// 状态码首字母：A / M / D / R / C / T
+    pub code: char,
+    pub path: String,
+    /// 重命名 / 复制前的路径
+    pub old_path: Option<String>,
+}
+
+/// 建议结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuggestedCommitMessage {
+    pub message: String,
+    /// `template` | `command` | `endpoint`
+    pub source: String,
+    /// 是否配置了 `[git] commit_message_command` 或 `commit_message_endpoint`
+    pub generator_available: bool,
+    pub template: String,
+    pub issue_id: Option<String>,
+    pub commit_type: String,
+    pub scope: Option<String>,
+    pub summary: String,
+    pub branch: String,
+    pub staged_files: usize,
+}
+
+fn tokenize(template: &str) -> Result<Vec<Token<'_>>, String> {
+    template::tokenize(template, "commit", PLACEHOLDERS)
+}
+
+/// 校验模板语法（占位符合法、括号成对）
+pub fn validate_commit_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("Commit template is empty".to_string());
+    }
+    tokenize(template).map(|_| ())
+}
+
+/// 按模板渲染提交信息（仅首行），并清理空占位符留下的痕迹
+pub fn render_commit_template(
+    template: &str,
+    ctx: &CommitMessageContext<'_>,
+) -> Result<String, String> {
+    let mut rendered = String::new();
+    for token in tokenize(template)? {
+        match token {
+            Token::Literal(text) => rendered.push_str(text),
+            Token::Placeholder(name) => rendered.push_str(match name {
+                "issue" => ctx.issue.unwrap_or_default(),
+                "type" => ctx.commit_type,
+                "scope" => ctx.scope.unwrap_or_default(),
+                "summary" => ctx.summary,
+                "branch" => ctx.branch,
+                _ => ctx.workspace,
+            }),
+        }
+    }
+
+    let mut message = rendered.replace("()", "").replace("[]", "");
+    message = message.split_whitespace().collect::<Vec<_>>().join(" ");
+    // 摘要为空时不留下 `feat:` 之类的悬空分隔符
+    let trimmed = message.trim_matches(|c: char| c == ':' || c == '-' || c.is_whitespace());
+    Ok(trimmed.to_string())
+}
+
+fn is_issue_key(token: &str) -> bool {
+    let Some((key, number)) = token.split_once('-') else {
+        return false;
+    };
+    key.len() >= 2
+        && key.starts_with(|c: char| c.is_ascii_alphabetic())
+        && key.chars().all(|c| c.is_ascii_alphanumeric())
+        && is_issue_number(number)
+}
+
+fn is_issue_number(token: &str) -> bool {
+    (1..=7).contains(&token.len()) && token.chars().all(|c| c.is_ascii_digit())
+}
+
+/// 从关联链接提取 issue 编号
+///
+/// - GitHub / GitLab / Gitea：`.../issues/123`、`.../pull/123`、`.../merge_requests/123` → `#123`
+/// - Jira / Linear 等：路径中形如 `ABC-123` 的段 → `ABC-123`
+pub fn issue_id_from_url(url: &str) -> Option<String> {
+    let without_query = url.split(['?', '#']).next().unwrap_or_default();
+    let path = without_query
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(without_query);
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    for pair in segments.windows(2) {
+        if matches!(
+            pair[0],
+            "issues" | "issue" | "pull" | "pulls" | "merge_requests"
+        ) && is_issue_number(pair[1])
+        {
+            return Some(format!("#{}", pair[1]));
+        }
+    }
+    segments
+        .iter()
+        .skip(1)
+        .find(|segment| is_issue_key(segment))
+        .map(|segment| segment.to_ascii_uppercase())
+}
+
+/// 从分支名提取 issue 编号：优先 `ABC-123`，其次独立的数字段（`fix/123-crash` → `#123`）
+pub fn issue_id_from_branch(branch: &str) -> Option<String> {
+    let segments: Vec<Vec<&str>> = branch
+        .split('/')
+        .map(|segment| segment.split(['-', '_']).collect())
+        .collect();
+    for parts in &segments {
+        for pair in parts.windows(2) {
+            let key = format!("{}-{}", pair[0], pair[1]);
+            let is_type_prefix = BRANCH_TYPE_PREFIXES
+                .iter()
+                .any(|(name, _)| pair[0].eq_ignore_ascii_case(name));
+            if !is_type_prefix && is_issue_key(&key) {
+                return Some(key.to_ascii_uppercase());
+            }
+        }
+    }
+    // 首段通常是 `feat` / `tidy` 之类的前缀
+    segments
+        .iter()
+        .flatten()
+        .skip(1)
+        .find(|part| is_issue_number(part))
+        .map(|number| format!("#{}", number))
+}
+
+/// 分支前缀对应的提交类型（`feature/x` → `feat`）
+pub fn commit_type_from_branch(branch: &str) -> Option<&'static str> {
+    let (prefix, rest) = branch.split_once('/')?;
+    if rest.is_empty() {
+        return None;
+    }
+    let prefix = prefix.to_ascii_lowercase();
+    BRANCH_TYPE_PREFIXES
+        .iter()
+        .find(|(name, _)| *name == prefix)
+        .map(|(_, commit_type)| *commit_type)
+}
+
+fn is_doc_path(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    lower.starts_with("docs/")
+        || lower.contains("/docs/")
+        || [".md", ".mdx", ".rst", ".adoc", ".txt"]
+            .iter()
+            .any(|ext| lower.ends_with(ext))
+}
+
+fn is_test_path(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    let file_name = lower.rsplit('/').next().unwrap_or_default();
+    lower.starts_with("tests/")
+        || lower.contains("/tests/")
+        || lower.contains("/__tests__/")
+        || file_name.contains("_test.")
+        || file_name.contains(".test.")
+        || file_name.contains(".spec.")
+        || file_name.starts_with("test_")
+}
+
+/// 按暂存文件推断提交类型
+pub fn infer_commit_type(changes: &[StagedChange]) -> &'static str {
+    if changes.is_empty() {
+        return "chore";
+    }
+    if changes.iter().all(|change| is_doc_path(&change.path)) {
+        "docs"
+    } else if changes.iter().all(|change| is_test_path(&change.path)) {
+        "test"
+    } else if changes.iter().all(|change| change.code == 'A') {
+        "feat"
+    } else {
+        "chore"
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// 暂存文件的最深公共目录（相对仓库根），全部位于根目录时为 None
+fn common_dir(changes: &[StagedChange]) -> Option<String> {
+    let mut dirs = changes.iter().map(|change| {
+        change
+            .path
+            .rsplit_once('/')
+            .map(|(dir, _)| dir)
+            .unwrap_or_default()
+    });
+    let first: Vec<&str> = dirs.next()?.split('/').filter(|s| !s.is_empty()).collect();
+    let mut common_len = first.len();
+    for dir in dirs {
+        let parts: Vec<&str> = dir.split('/').filter(|s| !s.is_empty()).collect();
+        common_len = first
+            .iter()
+            .zip(parts.iter())
+            .take(common_len)
+            .take_while(|(a, b)| a == b)
+            .count();
+    }
+    (common_len > 0).then(|| first[..common_len].join("/"))
+}
+
+fn verb_for(code: char) -> &'static str {
+    match code {
+        'A' | 'C' => "add",
+        'D' => "remove",
+        'R' => "rename",
+        _ => "update",
+    }
+}
+
+/// 暂存区变更摘要
+pub fn summarize_staged(changes: &[StagedChange]) -> String {
+    let verb = match changes.first() {
+        Some(first) if changes.iter().all(|change| change.code == first.code) => {
+            verb_for(first.code)
+        }
+        _ => "update",
+    };
+    match changes {
+        [] => String::new(),
+        [change] => match (&change.old_path, change.code) {
+            (Some(old_path), 'R') => format!(
+                "rename {} to {}",
+                file_name(old_path),
+                file_name(&change.path)
+            ),
+            _ => format!("{} {}", verb, file_name(&change.path)),
+        },
+        [a, b] if verb != "rename" => {
+            format!("{} {} and {}", verb, file_name(&a.path), file_name(&b.path))
+        }
+        _ => match common_dir(changes) {
+            Some(dir) => format!("{} {} files in {}", verb, changes.len(), dir),
+            None => format!("{} {} files", verb, changes.len()),
+        },
+    }
+}
+
+fn git_stdout(workspace_root: &Path, args: &[&str]) -> Result<String, GitError> {
+    let output = git_command(workspace_root)
+        .args(args)
+        .output()
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("not a git repository") {
+            return Err(GitError::NotAGitRepo);
+        }
+        return Err(GitError::CommandFailed(stderr.trim().to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// 为工作区生成提交信息建议
+///
+/// `link_url` 为客户端关联到该工作区的 issue / PR 链接，可省略。
+/// `generate` 为 true 时改由 `[git] commit_message_command` 生成 `message`（未配置时报错），
+/// 其余字段仍按模板规则计算。同步版本不调用 HTTP 端点，端点生成见 [`git_generate_commit_message`]。
+pub fn git_suggested_commit_message(
+    workspace_root: &Path,
+    workspace: &str,
+    link_url: Option<&str>,
+    generate: bool,
+) -> Result<SuggestedCommitMessage, GitError> {
+    let (mut suggestion, generator) = prepare_suggestion(workspace_root, workspace, link_url)?;
+    if !generate {
+        return Ok(suggestion);
+    }
+    let command = match generator {
+        Some(CommitMessageGenerator::Command(command)) => command,
+        Some(CommitMessageGenerator::Endpoint(_)) => {
+            return Err(GitError::CommandFailed(
+                "Commit message endpoint requires asynchronous generation".to_string(),
+            ))
+        }
+        None => return Err(no_generator_error()),
+    };
+    // 暂存区为空时不调用外部命令
+    if suggestion.staged_files == 0 {
+        return Ok(suggestion);
+    }
+    let diff = staged_diff_for_command(workspace_root, MAX_COMMAND_INPUT_BYTES)?;
+    suggestion.message = command.run(&diff, &command_context(&suggestion, workspace))?;
+    suggestion.source = SUGGESTION_SOURCE_COMMAND.to_string();
+    Ok(suggestion)
+}
+
+/// 由 `[git] commit_message_command` 或 `commit_message_endpoint` 生成提交信息
+///
+/// 未配置任何生成方式时报错；暂存区为空时不调用外部生成，返回模板结果。
+pub async fn git_generate_commit_message(
+    workspace_root: PathBuf,
+    workspace: String,
+    link_url: Option<String>,
+) -> Result<SuggestedCommitMessage, GitError> {
+    let prepared = crate::util::trace::spawn_blocking(move || {
+        let (suggestion, generator) =
+            prepare_suggestion(&workspace_root, &workspace, link_url.as_deref())?;
+        let generator = generator.ok_or_else(no_generator_error)?;
+        let diff = if suggestion.staged_files == 0 {
+            None
+        } else {
+            Some(staged_diff_for_command(
+                &workspace_root,
+                MAX_COMMAND_INPUT_BYTES,
+            )?)
+        };
+        Ok::<_, GitError>((suggestion, generator, diff, workspace))
+    })
+    .await
+    .map_err(|e| GitError::CommandFailed(format!("Commit message task failed: {}", e)))?;
+    let (mut suggestion, generator, diff, workspace) = prepared?;
+    let Some(diff) = diff else {
+        return Ok(suggestion);
+    };
+
+    let message = match &generator {
+        CommitMessageGenerator::Command(command) => {
+            let command = command.clone();
+            let owned = suggestion.clone();
+            crate::util::trace::spawn_blocking(move || {
+                command.run(&diff, &command_context(&owned, &workspace))
+            })
+            .await
+            .map_err(|e| GitError::CommandFailed(format!("Commit message task failed: {}", e)))??
+        }
+        CommitMessageGenerator::Endpoint(endpoint) => {
+            endpoint
+                .run(&diff, &command_context(&suggestion, &workspace))
+                .await?
+        }
+    };
+    suggestion.message = message;
+    suggestion.source = generator.source().to_string();
+    Ok(suggestion)
+}
+
+fn no_generator_error() -> GitError {
+    GitError::CommandFailed(
+        "No commit message generator configured ([git] commit_message_command or commit_message_endpoint)"
+            .to_string(),
+    )
+}
+
+fn command_context<'a>(
+    suggestion: &'a SuggestedCommitMessage,
+    workspace: &'a str,
+) -> CommitMessageCommandContext<'a> {
+    CommitMessageCommandContext {
+        branch: &suggestion.branch,
+        workspace,
+        issue: suggestion.issue_id.as_deref(),
+        suggested_message: &suggestion.message,
+    }
+}
+
+/// 按模板计算建议，并解析外部生成配置
+fn prepare_suggestion(
+    workspace_root: &Path,
+    workspace: &str,
+    link_url: Option<&str>,
+) -> Result<(SuggestedCommitMessage, Option<CommitMessageGenerator>), GitError> {
+    let git_config = ProjectConfig::load(workspace_root)
+        .map(|config| config.git)
+        .unwrap_or_default();
+    let generator = CommitMessageGenerator::from_config(&git_config)?;
+    let template = git_config
+        .commit_template
+        .filter(|template| !template.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_COMMIT_TEMPLATE.to_string());
+    validate_commit_template(&template).map_err(GitError::CommandFailed)?;
+
+    let branch = git_stdout(workspace_root, &["branch", "--show-current"])?
+        .trim()
+        .to_string();
+    let name_status = git_stdout(
+        workspace_root,
+        &["diff", "--cached", "--name-status", "-z", "-M"],
+    )?;
+    let changes: Vec<StagedChange> = parse_name_status_z(&name_status)
+        .into_iter()
+        .map(|(code, path, old_path)| StagedChange {
+            code: code.chars().next().unwrap_or('M'),
+            path,
+            old_path,
+        })
+        .collect();
+
+    let issue_id = link_url
+        .and_then(issue_id_from_url)
+        .or_else(|| issue_id_from_branch(&branch));
+    let commit_type = commit_type_from_branch(&branch)
+        .unwrap_or_else(|| infer_commit_type(&changes))
+        .to_string();
+    let scope = common_dir(&changes).map(|dir| file_name(&dir).to_string());
+    let summary = summarize_staged(&changes);
+
+    let message = render_commit_template(
+        &template,
+        &CommitMessageContext {
+            issue: issue_id.as_deref(),
+            commit_type: &commit_type,
+            scope: scope.as_deref(),
+            summary: &summary,
+            branch: &branch,
+            workspace,
+        },
+    )
+    .map_err(GitError::CommandFailed)?;
+
+    let suggestion = SuggestedCommitMessage {
+        message,
+        source: SUGGESTION_SOURCE_TEMPLATE.to_string(),
+        generator_available: generator.is_some(),
+        template,
+        issue_id,
+        commit_type,
+        scope,
+        summary,
+        branch,
+        staged_files: changes.len(),
+    };
+    Ok((suggestion, generator))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(code: char, path: &str) -> StagedChange {
+        StagedChange {
+            code,
+            path: path.to_string(),
+            old_path: None,
+        }
+    }
+
+    #[test]
+    fn issue_id_from_links() {
+        for (url, expected) in [
+            ("https://github.com/acme/app/issues/42", Some("#42")),
+            ("https://github.com/acme/app/pull/7/files", Some("#7")),
+            (
+                "https://gitlab.com/acme/app/-/merge_requests/15#note_1",
+                Some("#15"),
+            ),
+            (
+                "https://acme.atlassian.net/browse/PROJ-123?focus=1",
+                Some("PROJ-123"),
+            ),
+            (
+                "https://linear.app/acme/issue/eng-42/fix-login",
+                Some("ENG-42"),
+            ),
+            ("https://example.com/docs/intro", None),
+        ] {
+            assert_eq!(issue_id_from_url(url).as_deref(), expected, "{}", url);
+        }
+    }
+
+    #[test]
+    fn issue_id_and_type_from_branch() {
+        assert_eq!(
+            issue_id_from_branch("feat/proj-12-login").as_deref(),
+            Some("PROJ-12")
+        );
+        assert_eq!(
+            issue_id_from_branch("fix/123-crash").as_deref(),
+            Some("#123")
+        );
+        assert_eq!(issue_id_from_branch("tidy/brave-otter"), None);
+        assert_eq!(issue_id_from_branch("release-20240101"), None);
+        assert_eq!(issue_id_from_branch("fix-42-crash").as_deref(), Some("#42"));
+
+        assert_eq!(commit_type_from_branch("feature/login"), Some("feat"));
+        assert_eq!(commit_type_from_branch("hotfix/x"), Some("fix"));
+        assert_eq!(commit_type_from_branch("tidy/brave-otter"), None);
+        assert_eq!(commit_type_from_branch("main"), None);
+    }
+
+    #[test]
+    fn summarizes_staged_changes() {
+        assert_eq!(summarize_staged(&[]), "");
+        assert_eq!(
+            summarize_staged(&[change('A', "src/auth/login.rs")]),
+            "add login.rs"
+        );
+        assert_eq!(
+            summarize_staged(&[StagedChange {
+                code: 'R',
+                path: "src/new.rs".to_string(),
+                old_path: Some("src/old.rs".to_string()),
+            }]),
+            "rename old.rs to new.rs"
+        );
+        assert_eq!(
+            summarize_staged(&[change('M', "a.rs"), change('D', "b.rs")]),
+            "update a.rs and b.rs"
+        );
+        assert_eq!(
+            summarize_staged(&[
+                change('M', "src/auth/a.rs"),
+                change('M', "src/auth/b.rs"),
+                change('M', "src/auth/oauth/c.rs"),
+            ]),
+            "update 3 files in src/auth"
+        );
+
+        assert_eq!(infer_commit_type(&[change('M', "docs/guide.md")]), "docs");
+        assert_eq!(
+            infer_commit_type(&[change('M', "core/tests/flow.rs")]),
+            "test"
+        );
+        assert_eq!(infer_commit_type(&[change('A', "src/new.rs")]), "feat");
+        assert_eq!(
+            infer_commit_type(&[change('M', "src/a.rs"), change('A', "b.rs")]),
+            "chore"
+        );
+    }
+
+    #[test]
+    fn renders_templates_and_drops_empty_placeholders() {
+        let ctx = CommitMessageContext {
+            issue: Some("#42"),
+            commit_type: "feat",
+            scope: Some("auth"),
+            summary: "add login.rs",
+            branch: "feat/42-login",
+            workspace: "login",
+        };
+        assert_eq!(
+            render_commit_template(DEFAULT_COMMIT_TEMPLATE, &ctx).unwrap(),
+            "#42 feat: add login.rs"
+        );
+        assert_eq!(
+            render_commit_template("{type}({scope}): {summary} [{issue}]", &ctx).unwrap(),
+            "feat(auth): add login.rs [#42]"
+        );
+
+        let empty = CommitMessageContext {
+            commit_type: "chore",
+            ..CommitMessageContext::default()
+        };
+        assert_eq!(
+            render_commit_template("{type}({scope}): {summary} [{issue}]", &empty).unwrap(),
+            "chore"
+        );
+        assert_eq!(
+            render_commit_template(DEFAULT_COMMIT_TEMPLATE, &empty).unwrap(),
+            "chore"
+        );
+
+        assert!(validate_commit_template("{ticket}: x").is_err());
+        assert!(validate_commit_template("{type: x").is_err());
+        assert!(validate_commit_template("  ").is_err());
+    }
+}
@@ -0,0 +1,594 @@
+//! 外部命令生成提交信息（如调用 LLM 的脚本）
+//!
+//! `.tidyflow.toml` 的 `[git] commit_message_command` 配置 argv 数组（不经 shell 解析）。
+//! 命令从 stdin 读取暂存区 diff，从 stdout 输出提交信息，运行受以下限制：
+//! - 工作目录为临时空目录，不直接暴露工作区
+//! - 环境变量清空，仅保留 PATH / HOME / LANG 等基础变量与 `commit_message_env` 显式放行的变量，
+//!   另注入 `TIDYFLOW_BRANCH` / `TIDYFLOW_WORKSPACE` / `TIDYFLOW_ISSUE` / `TIDYFLOW_SUGGESTED_MESSAGE`
+//!   与当前请求的 `TIDYFLOW_TRACE_ID`
+//! - stdin 最多 [`MAX_COMMAND_INPUT_BYTES`]，stdout 最多读取 [`MAX_COMMAND_OUTPUT_BYTES`]
+//! - 超过 `commit_message_timeout`（默认 30 秒，上限 120 秒）即终止
+//!
+//! 也可改为配置 `[git] commit_message_endpoint`：Core 以 JSON POST diff、上述上下文与 trace id，
+//! 响应体为 `{ "message": "..." }` 或纯文本，超时规则相同。
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use super::utils::GitError;
+use crate::util::exec_env::git_command;
+use crate::workspace::config::GitSection;
+
+/// 传给命令的 diff 上限（128KB），超出部分截断并追加说明行
+pub const MAX_COMMAND_INPUT_BYTES: usize = 128 * 1024;
+/// 读取命令输出的上限（8KB）
+pub const MAX_COMMAND_OUTPUT_BYTES: usize = 8 * 1024;
+/// 默认超时（秒）
+pub const DEFAULT_COMMAND_TIMEOUT_SECS: u64 = 30;
+/// 超时上限（秒）
+pub const MAX_COMMAND_TIMEOUT_SECS: u64 = 120;
+
+/// 读取端点响应体的上限（64KB），JSON 包装字段不计入提交信息长度
+pub const MAX_ENDPOINT_RESPONSE_BYTES: usize = 64 * 1024;
+
+/// 始终透传给命令的基础环境变量
+const BASE_ENV_VARS: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "LANG",
+    "LC_ALL",
+    "LC_CTYPE",
+    "TMPDIR",
+    "SYSTEMROOT",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+];
+
+const TRUNCATED_NOTE: &str = "\n[diff truncated by TidyFlow]\n";
+
+/// 命令运行时可读取的上下文
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CommitMessageCommandContext<'a> {
+    pub branch: &'a str,
+    pub workspace: &'a str,
+    pub issue: Option<&'a str>,
+    /// 模板渲染出的建议，可作为命令的提示
+    pub suggested_message: &'a str,
+}
+
+/// 已解析的命令配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitMessageCommand {
+    pub argv: Vec<String>,
+    pub env_allowlist: Vec<String>,
+    pub timeout: Duration,
+}
+
+impl CommitMessageCommand {
+    /// 从 `[git]` 段读取；未配置或 argv 为空时返回 None
+    pub fn from_config(git: &GitSection) -> Option<Self> {
+        let argv: Vec<String> = git
+            .commit_message_command
+            .as_ref()?
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        if argv.first().is_none_or(|program| program.trim().is_empty()) {
+            return None;
+        }
+        Some(Self {
+            argv,
+            env_allowlist: git.commit_message_env.clone().unwrap_or_default(),
+            timeout: command_timeout(git),
+        })
+    }
+
+    /// 以 `input` 为 stdin 运行命令，返回清理后的提交信息
+    pub fn run(
+        &self,
+        input: &[u8],
+        ctx: &CommitMessageCommandContext<'_>,
+    ) -> Result<String, GitError> {
+        let sandbox = SandboxDir::create()?;
+        let mut cmd = Command::new(&self.argv[0]);
+        cmd.args(&self.argv[1..])
+            .current_dir(&sandbox.0)
+            .env_clear()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        for name in BASE_ENV_VARS
+            .iter()
+            .copied()
+            .chain(self.env_allowlist.iter().map(String::as_str))
+        {
+            if let Some(value) = std::env::var_os(name) {
+                cmd.env(name, value);
+            }
+        }
+        cmd.env("TIDYFLOW_BRANCH", ctx.branch)
+            .env("TIDYFLOW_WORKSPACE", ctx.workspace)
+            .env("TIDYFLOW_ISSUE", ctx.issue.unwrap_or_default())
+            .env("TIDYFLOW_SUGGESTED_MESSAGE", ctx.suggested_message);
+        crate::util::trace::apply_to_command(&mut cmd);
+
+        let mut child = cmd.spawn().map_err(|e| {
+            GitError::CommandFailed(format!(
+                "Failed to start commit message command '{}': {}",
+                self.argv[0], e
+            ))
+        })?;
+
+        // stdin / stdout / stderr 各用独立线程，避免管道写满互相阻塞
+        let mut stdin = child.stdin.take();
+        let input = input.to_vec();
+        let writer = std::thread::spawn(move || {
+            if let Some(stdin) = stdin.as_mut() {
+                // 命令不读 stdin 就退出时会 broken pipe，忽略即可
+                let _ = stdin.write_all(&input);
+            }
+        });
+        let stdout_reader = spawn_capped_reader(child.stdout.take(), MAX_COMMAND_OUTPUT_BYTES);
+        let stderr_reader = spawn_capped_reader(child.stderr.take(), MAX_COMMAND_OUTPUT_BYTES);
+
+        let started = Instant::now();
+        let status = loop {
+            match child.try_wait().map_err(GitError::IoError)? {
+                Some(status) => break status,
+                None if started.elapsed() >= self.timeout => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(GitError::CommandFailed(format!(
+                        "Commit message command timed out after {}s",
+                        self.timeout.as_secs()
+                    )));
+                }
+                None => std::thread::sleep(Duration::from_millis(20)),
+            }
+        };
+        let _ = writer.join();
+        let stdout = stdout_reader.join().unwrap_or_default();
+        let stderr = stderr_reader.join().unwrap_or_default();
+
+        if !status.success() {
+            let stderr = String::from_utf8_lossy(&stderr);
+            return Err(GitError::CommandFailed(format!(
+                "Commit message command exited with {}: {}",
+                status,
+                stderr.trim()
+            )));
+        }
+        let message = clean_command_output(&String::from_utf8_lossy(&stdout));
+        if message.is_empty() {
+            return Err(GitError::CommandFailed(
+                "Commit message command produced no output".to_string(),
+            ));
+        }
+        Ok(message)
+    }
+}
+
+/// 已解析的 HTTP 端点配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitMessageEndpoint {
+    pub url: String,
+    /// 鉴权令牌所在的环境变量名
+    pub token_env: Option<String>,
+    pub timeout: Duration,
+}
+
+impl CommitMessageEndpoint {
+    /// 从 `[git]` 段读取；未配置时返回 None，地址不是 http(s) 时报错
+    pub fn from_config(git: &GitSection) -> Result<Option<Self>, GitError> {
+        let Some(url) = git
+            .commit_message_endpoint
+            .as_deref()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+        else {
+            return Ok(None);
+        };
+        let parsed = url::Url::parse(url).map_err(|e| {
+            GitError::CommandFailed(format!("Invalid commit message endpoint '{}': {}", url, e))
+        })?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(GitError::CommandFailed(format!(
+                "Commit message endpoint must be an http(s) URL: {}",
+                url
+            )));
+        }
+        Ok(Some(Self {
+            url: url.to_string(),
+            token_env: git
+                .commit_message_endpoint_token_env
+                .as_deref()
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string),
+            timeout: command_timeout(git),
+        }))
+    }
+
+    /// POST diff 与上下文，返回清理后的提交信息
+    pub async fn run(
+        &self,
+        input: &[u8],
+        ctx: &CommitMessageCommandContext<'_>,
+    ) -> Result<String, GitError> {
+        let failed = |e: String| GitError::CommandFailed(e);
+        let client = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .build()
+            .map_err(|e| failed(e.to_string()))?;
+        let mut request = client
+            .post(&self.url)
+            .header("User-Agent", "tidyflow")
+            .json(&json!({
+                "diff": String::from_utf8_lossy(input),
+                "branch": ctx.branch,
+                "workspace": ctx.workspace,
+                "issue": ctx.issue,
+                "suggested_message": ctx.suggested_message,
+                "trace_id": crate::util::trace::current_trace_id(),
+            }));
+        if let Some(token) = self
+            .token_env
+            .as_deref()
+            .and_then(|name| std::env::var(name).ok())
+        {
+            request = request.bearer_auth(token);
+        }
+
+        let mut response = request.send().await.map_err(|e| {
+            if e.is_timeout() {
+                failed(format!(
+                    "Commit message endpoint timed out after {}s",
+                    self.timeout.as_secs()
+                ))
+            } else {
+                failed(format!("Commit message endpoint request failed: {}", e))
+            }
+        })?;
+        let status = response.status();
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| failed(format!("Commit message endpoint request failed: {}", e)))?
+        {
+            let room = MAX_ENDPOINT_RESPONSE_BYTES - body.len();
+            body.extend_from_slice(&chunk[..chunk.len().min(room)]);
+            if body.len() == MAX_ENDPOINT_RESPONSE_BYTES {
+                break;
+            }
+        }
+        if !status.is_success() {
+            let text = String::from_utf8_lossy(&body);
+            let detail: String = text.trim().chars().take(500).collect();
+            return Err(failed(format!(
+                "Commit message endpoint returned {}: {}",
+                status, detail
+            )));
+        }
+        let message = endpoint_message(&body);
+        if message.is_empty() {
+            return Err(failed(
+                "Commit message endpoint produced no output".to_string(),
+            ));
+        }
+        Ok(message)
+    }
+}
+
+/// 外部生成方式：命令优先，其次 HTTP 端点
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommitMessageGenerator {
+    Command(CommitMessageCommand),
+    Endpoint(CommitMessageEndpoint),
+}
+
+impl CommitMessageGenerator {
+    /// 从 `[git]` 段读取；均未配置时返回 None
+    pub fn from_config(git: &GitSection) -> Result<Option<Self>, GitError> {
+        if let Some(command) = CommitMessageCommand::from_config(git) {
+            return Ok(Some(Self::Command(command)));
+        }
+        Ok(CommitMessageEndpoint::from_config(git)?.map(Self::Endpoint))
+    }
+
+    /// 写入 `source` 字段的生成方式
+    pub fn source(&self) -> &'static str {
+        match self {
+            Self::Command(_) => super::commit_message::SUGGESTION_SOURCE_COMMAND,
+            Self::Endpoint(_) => super::commit_message::SUGGESTION_SOURCE_ENDPOINT,
+        }
+    }
+}
+
+fn command_timeout(git: &GitSection) -> Duration {
+    let timeout_secs = git
+        .commit_message_timeout
+        .unwrap_or(DEFAULT_COMMAND_TIMEOUT_SECS)
+        .clamp(1, MAX_COMMAND_TIMEOUT_SECS);
+    Duration::from_secs(timeout_secs)
+}
+
+/// 解析端点响应：JSON 取 `message` 字段（或整体为字符串），否则按纯文本处理
+fn endpoint_message(body: &[u8]) -> String {
+    let text = String::from_utf8_lossy(body);
+    let raw = match serde_json::from_str::<Value>(&text) {
+        Ok(Value::String(message)) => message,
+        Ok(Value::Object(map)) => map
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        _ => text.into_owned(),
+    };
+    let mut message = clean_command_output(&raw);
+    if message.len() > MAX_COMMAND_OUTPUT_BYTES {
+        let mut end = MAX_COMMAND_OUTPUT_BYTES;
+        while !message.is_char_boundary(end) {
+            end -= 1;
+        }
+        message.truncate(end);
+    }
+    message
+}
+
+/// 命令的临时工作目录，结束后删除
+struct SandboxDir(PathBuf);
+
+impl SandboxDir {
+    fn create() -> Result<Self, GitError> {
+        let path = std::env::temp_dir().join(format!(
+            "tidyflow-commit-message-{}",
+            uuid::Uuid::new_v4().simple()
+        ));
+        std::fs::create_dir_all(&path).map_err(GitError::IoError)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for SandboxDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn spawn_capped_reader<R: Read + Send + 'static>(
+    source: Option<R>,
+    cap: usize,
+) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut source) = source {
+            let _ = source.by_ref().take(cap as u64).read_to_end(&mut buf);
+            // 继续排空，避免命令因管道写满而卡住直到超时
+            let _ = std::io::copy(&mut source, &mut std::io::sink());
+        }
+        buf
+    })
+}
+
+/// 去掉首尾空白与整体包裹的 ``` 代码块
+fn clean_command_output(raw: &str) -> String {
+    let trimmed = raw.trim();
+    let unfenced = trimmed
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(|inner| inner.split_once('\n').map(|(_, body)| body).unwrap_or(""))
+        .unwrap_or(trimmed);
+    unfenced
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// 读取暂存区 diff，超过 `max_bytes` 时截断并追加说明行
+pub fn staged_diff_for_command(
+    workspace_root: &Path,
+    max_bytes: usize,
+) -> Result<Vec<u8>, GitError> {
+    let mut child = git_command(workspace_root)
+        .args(["diff", "--cached", "--no-color", "--no-ext-diff", "-M"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(GitError::IoError)?;
+    let reader = spawn_capped_reader(child.stdout.take(), max_bytes + 1);
+    let stderr_reader = spawn_capped_reader(child.stderr.take(), MAX_COMMAND_OUTPUT_BYTES);
+    let status = child.wait().map_err(GitError::IoError)?;
+    let mut diff = reader.join().unwrap_or_default();
+    let stderr = stderr_reader.join().unwrap_or_default();
+    if !status.success() {
+        let stderr = String::from_utf8_lossy(&stderr);
+        if stderr.contains("not a git repository") {
+            return Err(GitError::NotAGitRepo);
+        }
+        return Err(GitError::CommandFailed(stderr.trim().to_string()));
+    }
+    if diff.len() > max_bytes {
+        diff.truncate(max_bytes);
+        diff.extend_from_slice(TRUNCATED_NOTE.as_bytes());
+    }
+    Ok(diff)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn command(argv: &[&str], timeout_secs: u64) -> CommitMessageCommand {
+        CommitMessageCommand {
+            argv: argv.iter().map(|s| s.to_string()).collect(),
+            env_allowlist: Vec::new(),
+            timeout: Duration::from_secs(timeout_secs),
+        }
+    }
+
+    #[test]
+    fn from_config_requires_program_and_clamps_timeout() {
+        let mut git = GitSection::default();
+        assert!(CommitMessageCommand::from_config(&git).is_none());
+        git.commit_message_command = Some(vec![" ".to_string()]);
+        assert!(CommitMessageCommand::from_config(&git).is_none());
+
+        git.commit_message_command = Some(vec!["llm".to_string(), "-s".to_string()]);
+        git.commit_message_timeout = Some(3600);
+        let cmd = CommitMessageCommand::from_config(&git).unwrap();
+        assert_eq!(cmd.argv, ["llm", "-s"]);
+        assert_eq!(cmd.timeout, Duration::from_secs(MAX_COMMAND_TIMEOUT_SECS));
+    }
+
+    #[test]
+    fn runs_in_sandbox_with_stdin_and_context_env() {
+        let ctx = CommitMessageCommandContext {
+            branch: "feat/x",
+            workspace: "ws",
+            issue: Some("#7"),
+            suggested_message: "#7 feat: add x",
+        };
+        let script = r#"read -r first; printf '```\n%s %s %s\n```\n' "$TIDYFLOW_ISSUE" "$first" "${SECRET_TOKEN:-none}""#;
+        std::env::set_var("SECRET_TOKEN", "leak");
+        let message = command(&["sh", "-c", script], 5)
+            .run(b"diff --git a/x b/x\n", &ctx)
+            .unwrap();
+        assert_eq!(message, "#7 diff --git a/x b/x none");
+    }
+
+    #[test]
+    fn reports_failures_timeouts_and_caps_output() {
+        let ctx = CommitMessageCommandContext::default();
+        let err = command(&["sh", "-c", "echo boom >&2; exit 3"], 5)
+            .run(b"", &ctx)
+            .unwrap_err();
+        assert!(err.to_string().contains("boom"), "{}", err);
+
+        let err = command(&["sh", "-c", "true"], 5)
+            .run(b"", &ctx)
+            .unwrap_err();
+        assert!(err.to_string().contains("no output"), "{}", err);
+
+        let started = Instant::now();
+        let err = command(&["sleep", "10"], 1).run(b"", &ctx).unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let big = command(&["sh", "-c", "head -c 100000 /dev/zero | tr '\\0' a"], 5)
+            .run(b"", &ctx)
+            .unwrap();
+        assert_eq!(big.len(), MAX_COMMAND_OUTPUT_BYTES);
+
+        assert!(command(&["/nonexistent/llm"], 5).run(b"", &ctx).is_err());
+    }
+
+    /// 在本地端口启动测试端点，返回地址
+    async fn serve(router: axum::Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, router).await;
+        });
+        format!("http://{}", addr)
+    }
+
+    fn endpoint(url: String) -> CommitMessageEndpoint {
+        CommitMessageEndpoint {
+            url,
+            token_env: None,
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    #[test]
+    fn generator_prefers_command_and_validates_endpoint() {
+        let mut git = GitSection::default();
+        assert!(CommitMessageGenerator::from_config(&git).unwrap().is_none());
+
+        git.commit_message_endpoint = Some("ftp://example.com/x".to_string());
+        assert!(CommitMessageGenerator::from_config(&git).is_err());
+
+        git.commit_message_endpoint = Some("http://127.0.0.1:9/commit".to_string());
+        git.commit_message_endpoint_token_env = Some("LLM_TOKEN".to_string());
+        let generator = CommitMessageGenerator::from_config(&git).unwrap().unwrap();
+        assert_eq!(generator.source(), "endpoint");
+        assert!(matches!(
+            generator,
+            CommitMessageGenerator::Endpoint(ref e) if e.token_env.as_deref() == Some("LLM_TOKEN")
+        ));
+
+        git.commit_message_command = Some(vec!["llm".to_string()]);
+        let generator = CommitMessageGenerator::from_config(&git).unwrap().unwrap();
+        assert_eq!(generator.source(), "command");
+    }
+
+    #[tokio::test]
+    async fn endpoint_posts_diff_and_reads_json_or_text() {
+        use axum::routing::post;
+        use axum::Json;
+
+        let base = serve(
+            axum::Router::new()
+                .route(
+                    "/json",
+                    post(|Json(body): Json<Value>| async move {
+                        Json(json!({
+                            "message": format!(
+                                "{} {}",
+                                body["issue"].as_str().unwrap_or_default(),
+                                body["diff"].as_str().unwrap_or_default().trim()
+                            )
+                        }))
+                    }),
+                )
+                .route("/text", post(|| async { "```\nfix: plain text\n```\n" }))
+                .route(
+                    "/fail",
+                    post(|| async { (axum::http::StatusCode::BAD_GATEWAY, "model offline") }),
+                )
+                .route("/empty", post(|| async { "  " })),
+        )
+        .await;
+        let ctx = CommitMessageCommandContext {
+            branch: "feat/x",
+            workspace: "ws",
+            issue: Some("#7"),
+            suggested_message: "#7 feat: add x",
+        };
+
+        let message = endpoint(format!("{}/json", base))
+            .run(b"diff --git a/x b/x\n", &ctx)
+            .await
+            .unwrap();
+        assert_eq!(message, "#7 diff --git a/x b/x");
+
+        let message = endpoint(format!("{}/text", base))
+            .run(b"", &ctx)
+            .await
+            .unwrap();
+        assert_eq!(message, "fix: plain text");
+
+        let err = endpoint(format!("{}/fail", base))
+            .run(b"", &ctx)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("model offline"), "{}", err);
+
+        let err = endpoint(format!("{}/empty", base))
+            .run(b"", &ctx)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no output"), "{}", err);
+    }
+}
@@ -0,0 +1,347 @@
+//! unified diff 文本解析为结构化 hunk
+//!
+//! `git_diff(format = "structured")` 使用：客户端拿到按行分类、带行号的 hunk，
+//! 可直接渲染并排 diff，无需各自实现 unified diff 解析。
+
+use super::utils::GitError;
+
+pub const DIFF_FORMAT_UNIFIED: &str = "unified";
+pub const DIFF_FORMAT_STRUCTURED: &str = "structured";
+
+/// hunk 内的一行
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffLine {
+    /// "context" | "add" | "delete"
+    pub kind: &'static str,
+    /// 去掉前缀（' ' / '+' / '-'）后的内容
+    pub text: String,
+    /// 旧文件中的行号（1-based），新增行为 None
+    pub old_line: Option<u32>,
+    /// 新文件中的行号（1-based），删除行为 None
+    pub new_line: Option<u32>,
+    /// 该行之后紧跟 `\ No newline at end of file`
+    pub no_newline: bool,
+    /// 行内变更区间（字符偏移，左闭右开），仅 `intraline` 时对成对的删除/新增行计算
+    pub highlights: Vec<(u32, u32)>,
+}
+
+/// 一个 `@@ -a,b +c,d @@` 区块
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffHunk {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    /// `@@ ... @@` 之后的函数上下文（可能为空）
+    pub header: String,
+    pub lines: Vec<DiffLine>,
+}
+
+/// 校验 diff 输出格式，返回是否需要结构化 hunk
+pub fn is_structured_format(format: Option<&str>) -> Result<bool, GitError> {
+    match format {
+        None | Some(DIFF_FORMAT_UNIFIED) => Ok(false),
+        Some(DIFF_FORMAT_STRUCTURED) => Ok(true),
+        Some(other) => Err(GitError::CommandFailed(format!(
+            "Invalid diff format: {} (expected unified or structured)",
+            other
+        ))),
+    }
+}
+
+/// 解析 `-a,b` / `+c` 形式的范围，省略行数时为 1
+fn parse_range(spec: &str) -> Option<(u32, u32)> {
+    match spec.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((spec.parse().ok()?, 1)),
+    }
+}
+
+fn parse_hunk_header(line: &str) -> Option<DiffHunk> {
+    let rest = line.strip_prefix("@@ ")?;
+    let (ranges, header) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(' ')?;
+    let (old_start, old_lines) = parse_range(old.strip_prefix('-')?)?;
+    let (new_start, new_lines) = parse_range(new.strip_prefix('+')?)?;
+    Some(DiffHunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        header: header.trim_start().to_string(),
+        lines: Vec::new(),
+    })
+}
+
+/// 解析 unified diff 文本；文件头（`diff --git` / `---` / `+++` 等）被跳过
+pub fn parse_unified_hunks(text: &str) -> Vec<DiffHunk> {
+    let mut hunks: Vec<DiffHunk> = Vec::new();
+    let mut old_line = 0;
+    let mut new_line = 0;
+
+    for line in text.lines() {
+        if line.starts_with("@@ ") {
+            if let Some(hunk) = parse_hunk_header(line) {
+                old_line = hunk.old_start;
+                new_line = hunk.new_start;
+                hunks.push(hunk);
+            }
+            continue;
+        }
+        // 第一个 hunk 之前、以及 hunk 行数耗尽之后都是文件头
+        let Some(hunk) = hunks.last_mut() else {
+            continue;
+        };
+        let consumed = old_line >= hunk.old_start + hunk.old_lines
+            && new_line >= hunk.new_start + hunk.new_lines;
+        if consumed && !line.starts_with('\\') {
+            continue;
+        }
+        let (kind, body) = match line.as_bytes().first() {
+            Some(b' ') => ("context", &line[1..]),
+            Some(b'+') => ("add", &line[1..]),
+            Some(b'-') => ("delete", &line[1..]),
+            Some(b'\\') => {
+                if let Some(last) = hunk.lines.last_mut() {
+                    last.no_newline = true;
+                }
+                continue;
+            }
+            // 空上下文行可能被去掉了前导空格
+            None => ("context", ""),
+            Some(_) => continue,
+        };
+        let (old, new) = match kind {
+            "add" => (None, Some(new_line)),
+            "delete" => (Some(old_line), None),
+            _ => (Some(old_line), Some(new_line)),
+        };
+        if old.is_some() {
+            old_line += 1;
+        }
+        if new.is_some() {
+            new_line += 1;
+        }
+        hunk.lines.push(DiffLine {
+            kind,
+            text: body.to_string(),
+            old_line: old,
+            new_line: new,
+            no_newline: false,
+            highlights: Vec::new(),
+        });
+    }
+    hunks
+}
+
+/// 单行参与行内 diff 的最大 token 数，超过时不计算高亮
+const MAX_INTRALINE_TOKENS: usize = 400;
+
+/// 行内 diff 的 token：连续的字母数字/下划线、连续空白，或单个其他字符
+/// 返回 (起始字符偏移, 文本)
+fn tokenize(text: &str) -> Vec<(u32, &str)> {
+    #[derive(PartialEq)]
+    enum Class {
+        Word,
+        Space,
+        Other,
+    }
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            Class::Word
+        } else if c.is_whitespace() {
+            Class::Space
+        } else {
+            Class::Other
+        }
+    };
+    let mut tokens = Vec::new();
+    let mut start: Option<(usize, u32, Class)> = None;
+    for (char_idx, (byte_idx, c)) in text.char_indices().enumerate() {
+        let cls = class(c);
+        if let Some((s_byte, s_char, s_cls)) = start.take() {
+            if s_cls == cls && cls != Class::Other {
+                start = Some((s_byte, s_char, s_cls));
+                continue;
+            }
+            tokens.push((s_char, &text[s_byte..byte_idx]));
+        }
+        start = Some((byte_idx, char_idx as u32, cls));
+    }
+    if let Some((s_byte, s_char, _)) = start {
+        tokens.push((s_char, &text[s_byte..]));
+    }
+    tokens
+}
+
+/// 基于 token LCS 计算两行各自未匹配的字符区间；两行差异过大时返回 None（整行高亮即可）
+fn intraline_ranges(old: &str, new: &str) -> Option<(Vec<(u32, u32)>, Vec<(u32, u32)>)> {
+    let a = tokenize(old);
+    let b = tokenize(new);
+    if a.len() > MAX_INTRALINE_TOKENS || b.len() > MAX_INTRALINE_TOKENS {
+        return None;
+    }
+    // lcs[i][j]：a[i..] 与 b[j..] 的最长公共子序列长度
+    let mut lcs = vec![vec![0u16; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i].1 == b[j].1 {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut keep_a = vec![false; a.len()];
+    let mut keep_b = vec![false; b.len()];
+    let (mut i, mut j) = (0, 0);
+    let mut common_chars = 0;
+    while i < a.len() && j < b.len() {
+        if a[i].1 == b[j].1 {
+            keep_a[i] = true;
+            keep_b[j] = true;
+            common_chars += a[i].1.chars().count();
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    // 公共部分不足较长一行的一半时，行内高亮反而干扰阅读
+    let longest = old.chars().count().max(new.chars().count());
+    if common_chars * 2 < longest {
+        return None;
+    }
+
+    let ranges = |tokens: &[(u32, &str)], keep: &[bool], total: u32| {
+        let mut out: Vec<(u32, u32)> = Vec::new();
+        for (idx, (start, _)) in tokens.iter().enumerate() {
+            if keep[idx] {
+                continue;
+            }
+            let end = tokens.get(idx + 1).map(|(s, _)| *s).unwrap_or(total);
+            match out.last_mut() {
+                Some(last) if last.1 == *start => last.1 = end,
+                _ => out.push((*start, end)),
+            }
+        }
+        out
+    };
+    Some((
+        ranges(&a, &keep_a, old.chars().count() as u32),
+        ranges(&b, &keep_b, new.chars().count() as u32),
+    ))
+}
+
+/// 为 hunk 中成对的删除/新增行计算行内高亮
+///
+/// 连续的删除行与紧随其后的新增行按顺序一一配对，多出的行不计算。
+pub fn compute_intraline_highlights(hunks: &mut [DiffHunk]) {
+    for hunk in hunks {
+        let lines = &mut hunk.lines;
+        let mut idx = 0;
+        while idx < lines.len() {
+            if lines[idx].kind != "delete" {
+                idx += 1;
+                continue;
+            }
+            let del_start = idx;
+            while idx < lines.len() && lines[idx].kind == "delete" {
+                idx += 1;
+            }
+            let add_start = idx;
+            while idx < lines.len() && lines[idx].kind == "add" {
+                idx += 1;
+            }
+            let pairs = (add_start - del_start).min(idx - add_start);
+            for k in 0..pairs {
+                let (old_idx, new_idx) = (del_start + k, add_start + k);
+                if let Some((old_ranges, new_ranges)) =
+                    intraline_ranges(&lines[old_idx].text, &lines[new_idx].text)
+                {
+                    lines[old_idx].highlights = old_ranges;
+                    lines[new_idx].highlights = new_ranges;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_unified_hunks_tracks_line_numbers() {
+        let text = "diff --git a/a.txt b/a.txt\n\
+index 1..2 100644\n\
+--- a/a.txt\n\
++++ b/a.txt\n\
+@@ -1,3 +1,3 @@ fn main()\n \
+keep\n\
+-old\n\
++new\n \
+tail\n\
+@@ -10 +10,2 @@\n\
+-last\n\
+\\ No newline at end of file\n\
++last\n\
++added\n";
+        let hunks = parse_unified_hunks(text);
+        assert_eq!(hunks.len(), 2);
+
+        let first = &hunks[0];
+        assert_eq!(
+            (
+                first.old_start,
+                first.old_lines,
+                first.new_start,
+                first.new_lines
+            ),
+            (1, 3, 1, 3)
+        );
+        assert_eq!(first.header, "fn main()");
+        let kinds: Vec<_> = first.lines.iter().map(|l| l.kind).collect();
+        assert_eq!(kinds, vec!["context", "delete", "add", "context"]);
+        assert_eq!(first.lines[1].old_line, Some(2));
+        assert_eq!(first.lines[2].new_line, Some(2));
+        assert_eq!(
+            (first.lines[3].old_line, first.lines[3].new_line),
+            (Some(3), Some(3))
+        );
+
+        let second = &hunks[1];
+        assert_eq!((second.old_lines, second.new_lines), (1, 2));
+        assert!(second.lines[0].no_newline);
+        assert_eq!(second.lines[2].new_line, Some(11));
+    }
+
+    #[test]
+    fn intraline_highlights_changed_words() {
+        let text = "@@ -1,3 +1,3 @@\n-let count = items.len();\n-unrelated line\n+let total = items.len();\n+something completely different here\n";
+        let mut hunks = parse_unified_hunks(text);
+        compute_intraline_highlights(&mut hunks);
+        let lines = &hunks[0].lines;
+        // "count" -> "total"
+        assert_eq!(lines[0].highlights, vec![(4, 9)]);
+        assert_eq!(lines[2].highlights, vec![(4, 9)]);
+        // 差异过大的配对不高亮
+        assert!(lines[1].highlights.is_empty());
+        assert!(lines[3].highlights.is_empty());
+
+        assert_eq!(
+            intraline_ranges("名字 = 旧值", "名字 = 新值").unwrap(),
+            (vec![(5, 7)], vec![(5, 7)])
+        );
+    }
+
+    #[test]
+    fn diff_format_validation() {
+        assert!(!is_structured_format(None).unwrap());
+        assert!(!is_structured_format(Some("unified")).unwrap());
+        assert!(is_structured_format(Some("structured")).unwrap());
+        assert!(is_structured_format(Some("json")).is_err());
+    }
+}
@@ -0,0 +1,337 @@
+//! 任意提交区间的 diff（如 `main...HEAD`）
+//!
+//! 区间摘要一次返回所有变更文件的状态码与增删行数，单文件的 unified diff
+//! 由客户端展开时再通过 `git_diff(range=...)` 按需获取，避免一次传输整个区间的补丁。
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::binary_diff::is_binary_diff_output;
+use super::operations::resolve_diff_algorithm;
+use super::utils::*;
+use crate::util::exec_env::git_command;
+
+/// 区间摘要最多返回的文件数
+pub const MAX_RANGE_DIFF_FILES: usize = 2000;
+
+/// 解析后的修订区间
+#[derive(Debug, Clone, PartialEq)]
+pub struct RevisionRange {
+    pub base: String,
+    pub head: String,
+    /// `base...head`：以两者的合并基为起点，只看 head 一侧的变更
+    pub symmetric: bool,
+}
+
+impl RevisionRange {
+    /// 传给 git 的区间参数
+    pub fn spec(&self) -> String {
+        let sep = if self.symmetric { "..." } else { ".." };
+        format!("{}{}{}", self.base, sep, self.head)
+    }
+}
+
+/// 区间内单个文件的变更摘要
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeDiffFile {
+    pub path: String,
+    /// 重命名/复制前的路径
+    pub orig_path: Option<String>,
+    /// 状态码：A/M/D/R/C/T
+    pub code: String,
+    pub additions: Option<i32>,
+    pub deletions: Option<i32>,
+    pub is_binary: bool,
+}
+
+/// 区间 diff 摘要
+#[derive(Debug)]
+pub struct RangeDiffSummary {
+    pub range: RevisionRange,
+    /// 对称区间实际使用的合并基 SHA
+    pub merge_base: Option<String>,
+    pub files: Vec<RangeDiffFile>,
+    pub total_additions: i32,
+    pub total_deletions: i32,
+    pub truncated: bool,
+}
+
+/// 解析 `base..head` / `base...head` / `base`（等价于 `base..HEAD`）
+pub fn parse_revision_range(range: &str) -> Result<RevisionRange, GitError> {
+    let range = range.trim();
+    let (base, head, symmetric) = if let Some((base, head)) = range.split_once("...") {
+        (base, head, true)
+    } else if let Some((base, head)) = range.split_once("..") {
+        (base, head, false)
+    } else {
+        (range, "", false)
+    };
+    let base = if base.is_empty() { "HEAD" } else { base };
+    let head = if head.is_empty() { "HEAD" } else { head };
+
+    for rev in [base, head] {
+        if rev.starts_with('-') || rev.contains("..") || rev.chars().any(char::is_whitespace) {
+            return Err(GitError::CommandFailed(format!(
+                "Invalid revision range: {}",
+                range
+            )));
+        }
+    }
+    Ok(RevisionRange {
+        base: base.to_string(),
+        head: head.to_string(),
+        symmetric,
+    })
+}
+
+fn run_git(workspace_root: &Path, args: &[String]) -> Result<String, GitError> {
+    let output = crate::util::cancel::output(git_command(workspace_root).args(args))
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(GitError::CommandFailed(stderr));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn pathspec_args(path: Option<&str>) -> Vec<String> {
+    let mut args = vec!["--".to_string()];
+    if let Some(path) = path.filter(|p| !p.is_empty() && *p != ".") {
+        args.push(path.to_string());
+    }
+    args
+}
+
+/// 解析 `git diff --name-status -z` 输出：`<code>\0<path>\0` 或 `R100\0<old>\0<new>\0`
+pub fn parse_name_status_z(output: &str) -> Vec<(String, String, Option<String>)> {
+    let mut entries = Vec::new();
+    let mut fields = output.split('\0').filter(|f| !f.is_empty());
+    while let Some(status) = fields.next() {
+        let code = status.chars().next().unwrap_or('M').to_string();
+        if code == "R" || code == "C" {
+            let (Some(old), Some(new)) = (fields.next(), fields.next()) else {
+                break;
+            };
+            entries.push((code, new.to_string(), Some(old.to_string())));
+        } else {
+            let Some(path) = fields.next() else { break };
+            entries.push((code, path.to_string(), None));
+        }
+    }
+    entries
+}
+
+/// 解析 `git diff --numstat -z` 输出，返回 新路径 -> (增, 删)，二进制文件为 None
+///
+/// 重命名条目形如 `<add>\t<del>\t\0<old>\0<new>\0`。
+pub fn parse_numstat_z(output: &str) -> HashMap<String, Option<(i32, i32)>> {
+    let mut stats = HashMap::new();
+    let mut fields = output.split('\0');
+    while let Some(field) = fields.next() {
+        if field.is_empty() {
+            continue;
+        }
+        let mut parts = field.splitn(3, '\t');
+        let (Some(add), Some(del), Some(path)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        let path = if path.is_empty() {
+            let _old = fields.next();
+            match fields.next() {
+                Some(new) => new.to_string(),
+                None => break,
+            }
+        } else {
+            path.to_string()
+        };
+        let counts = match (add.parse::<i32>(), del.parse::<i32>()) {
+            (Ok(a), Ok(d)) => Some((a, d)),
+            _ => None,
+        };
+        stats.insert(path, counts);
+    }
+    stats
+}
+
+/// 获取区间内（可限定目录）所有变更文件的摘要
+pub fn git_diff_range(
+    workspace_root: &Path,
+    range: &str,
+    path: Option<&str>,
+) -> Result<RangeDiffSummary, GitError> {
+    if let Some(path) = path {
+        validate_path(workspace_root, path)?;
+    }
+    if get_git_repo_root(workspace_root).is_none() {
+        return Err(GitError::NotAGitRepo);
+    }
+    let range = parse_revision_range(range)?;
+    let spec = range.spec();
+
+    let merge_base = if range.symmetric {
+        let args = vec![
+            "merge-base".to_string(),
+            range.base.clone(),
+            range.head.clone(),
+        ];
+        Some(run_git(workspace_root, &args)?.trim().to_string())
+    } else {
+        None
+    };
+
+    let mut name_status_args = vec![
+        "diff".to_string(),
+        "--name-status".to_string(),
+        "-z".to_string(),
+        "-M".to_string(),
+        spec.clone(),
+    ];
+    name_status_args.extend(pathspec_args(path));
+    let name_status = run_git(workspace_root, &name_status_args)?;
+
+    let mut numstat_args = vec![
+        "diff".to_string(),
+        "--numstat".to_string(),
+        "-z".to_string(),
+        "-M".to_string(),
+        spec,
+    ];
+    numstat_args.extend(pathspec_args(path));
+    let numstat = parse_numstat_z(&run_git(workspace_root, &numstat_args)?);
+
+    let mut entries = parse_name_status_z(&name_status);
+    let truncated = entries.len() > MAX_RANGE_DIFF_FILES;
+    entries.truncate(MAX_RANGE_DIFF_FILES);
+
+    let mut total_additions = 0;
+    let mut total_deletions = 0;
+    let files = entries
+        .into_iter()
+        .map(|(code, path, orig_path)| {
+            let counts = numstat.get(&path).copied().flatten();
+            if let Some((a, d)) = counts {
+                total_additions += a;
+                total_deletions += d;
+            }
+            RangeDiffFile {
+                is_binary: matches!(numstat.get(&path), Some(None)),
+                additions: counts.map(|(a, _)| a),
+                deletions: counts.map(|(_, d)| d),
+                path,
+                orig_path,
+                code,
+            }
+        })
+        .collect();
+
+    Ok(RangeDiffSummary {
+        range,
+        merge_base,
+        files,
+        total_additions,
+        total_deletions,
+        truncated,
+    })
+}
+
+/// 获取区间内单个文件的 unified diff（区间摘要展开时按需调用）
+pub fn git_diff_file_in_range(
+    workspace_root: &Path,
+    range: &str,
+    path: &str,
+    algorithm: Option<&str>,
+) -> Result<GitDiffResult, GitError> {
+    validate_path(workspace_root, path)?;
+    let algorithm = resolve_diff_algorithm(workspace_root, algorithm)?;
+    if get_git_repo_root(workspace_root).is_none() {
+        return Err(GitError::NotAGitRepo);
+    }
+    let range = parse_revision_range(range)?;
+
+    let mut status_args = vec![
+        "diff".to_string(),
+        "--name-status".to_string(),
+        "-z".to_string(),
+        range.spec(),
+    ];
+    status_args.extend(pathspec_args(Some(path)));
+    let code = parse_name_status_z(&run_git(workspace_root, &status_args)?)
+        .into_iter()
+        .next()
+        .map(|(code, _, _)| code)
+        .unwrap_or_else(|| "M".to_string());
+
+    let mut args = vec!["diff".to_string()];
+    if let Some(name) = algorithm {
+        args.push(format!("--diff-algorithm={}", name));
+    }
+    args.push(range.spec());
+    args.extend(pathspec_args(Some(path)));
+    let raw = run_git(workspace_root, &args)?;
+
+    let is_binary = is_binary_diff_output(&raw);
+    let (text, truncated) = if is_binary {
+        (String::new(), false)
+    } else {
+        truncate_if_needed(&raw)
+    };
+
+    Ok(GitDiffResult {
+        path: path.to_string(),
+        code,
+        format: "unified".to_string(),
+        text,
+        is_binary,
+        truncated,
+        mode: "range".to_string(),
+        smart_diff: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_revision_range_variants() {
+        let r = parse_revision_range("main...feature/x").unwrap();
+        assert_eq!(
+            (r.base.as_str(), r.head.as_str(), r.symmetric),
+            ("main", "feature/x", true)
+        );
+        assert_eq!(r.spec(), "main...feature/x");
+
+        let r = parse_revision_range("v1.0..").unwrap();
+        assert_eq!(r.spec(), "v1.0..HEAD");
+
+        let r = parse_revision_range("origin/main").unwrap();
+        assert_eq!(r.spec(), "origin/main..HEAD");
+
+        assert!(parse_revision_range("--output=x..HEAD").is_err());
+        assert!(parse_revision_range("a..b..c").is_err());
+    }
+
+    #[test]
+    fn parse_z_outputs_handle_renames_and_binary() {
+        let name_status = "M\0src/lib.rs\0R087\0old.rs\0src/new.rs\0A\0logo.png\0";
+        let entries = parse_name_status_z(name_status);
+        assert_eq!(
+            entries,
+            vec![
+                ("M".to_string(), "src/lib.rs".to_string(), None),
+                (
+                    "R".to_string(),
+                    "src/new.rs".to_string(),
+                    Some("old.rs".to_string())
+                ),
+                ("A".to_string(), "logo.png".to_string(), None),
+            ]
+        );
+
+        let numstat = "3\t1\tsrc/lib.rs\x002\t2\t\0old.rs\0src/new.rs\0-\t-\tlogo.png\0";
+        let stats = parse_numstat_z(numstat);
+        assert_eq!(stats.get("src/lib.rs"), Some(&Some((3, 1))));
+        assert_eq!(stats.get("src/new.rs"), Some(&Some((2, 2))));
+        assert_eq!(stats.get("logo.png"), Some(&None));
+    }
+}
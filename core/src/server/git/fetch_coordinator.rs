@@ -0,0 +1,141 @@
+//! 同仓库并发 fetch 合并
+//!
+//! 同一项目的多个 worktree 共享对象库与远程配置，同时触发 fetch 既重复下载，
+//! 也会争用对象库锁。这里按仓库公共目录（`git rev-parse --git-common-dir`）登记
+//! 进行中的 fetch：首个请求执行实际 fetch，期间到达的同仓库请求等待并复用其结果。
+//! fetch 结束后登记即移除，之后的请求会重新执行。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, LazyLock, Mutex};
+
+use tracing::debug;
+
+use super::utils::*;
+
+/// 进行中的 fetch；错误以文本共享（`GitError` 不可克隆）
+#[derive(Default)]
+struct FetchFlight {
+    result: Mutex<Option<Result<GitOpResult, String>>>,
+    done: Condvar,
+}
+
+/// 仓库公共目录 → 进行中的 fetch
+static FETCH_FLIGHTS: LazyLock<Mutex<HashMap<PathBuf, Arc<FetchFlight>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 仓库级去重键：所有 worktree 共享的 git 公共目录；无法解析时退回到 worktree 路径
+pub fn fetch_coordination_key(workspace_root: &Path) -> PathBuf {
+    let common_dir = gix::discover(workspace_root)
+        .map(|repo| repo.common_dir().to_path_buf())
+        .unwrap_or_else(|_| workspace_root.to_path_buf());
+    common_dir.canonicalize().unwrap_or(common_dir)
+}
+
+/// 以 `key` 合并并发 fetch：已有同键 fetch 在执行时等待其结果，否则由当前调用执行 `run`
+pub fn coalesce_fetch<F>(key: PathBuf, run: F) -> Result<GitOpResult, GitError>
+where
+    F: FnOnce() -> Result<GitOpResult, GitError>,
+{
+    let (flight, leader) = {
+        let mut flights = FETCH_FLIGHTS.lock().unwrap_or_else(|e| e.into_inner());
+        match flights.get(&key) {
+            Some(flight) => (flight.clone(), false),
+            None => {
+                let flight = Arc::new(FetchFlight::default());
+                flights.insert(key.clone(), flight.clone());
+                (flight, true)
+            }
+        }
+    };
+
+    if !leader {
+        debug!(key = %key.display(), "Joining in-flight git fetch");
+        let mut result = flight.result.lock().unwrap_or_else(|e| e.into_inner());
+        while result.is_none() {
+            result = flight.done.wait(result).unwrap_or_else(|e| e.into_inner());
+        }
+        return result
+            .clone()
+            .unwrap_or_else(|| Err("fetch aborted".to_string()))
+            .map_err(GitError::CommandFailed);
+    }
+
+    // `run` panic 时也要释放登记并唤醒等待方
+    let mut finish = FinishFlight {
+        key,
+        flight,
+        result: Some(Err("fetch aborted".to_string())),
+    };
+    let result = run();
+    finish.result = Some(match &result {
+        Ok(op) => Ok(op.clone()),
+        Err(e) => Err(e.to_string()),
+    });
+    drop(finish);
+    result
+}
+
+struct FinishFlight {
+    key: PathBuf,
+    flight: Arc<FetchFlight>,
+    result: Option<Result<GitOpResult, String>>,
+}
+
+impl Drop for FinishFlight {
+    fn drop(&mut self) {
+        FETCH_FLIGHTS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.key);
+        *self.flight.result.lock().unwrap_or_else(|e| e.into_inner()) = self.result.take();
+        self.flight.done.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Barrier;
+    use std::time::Duration;
+
+    fn fetched() -> GitOpResult {
+        GitOpResult {
+            op: "fetch".to_string(),
+            ok: true,
+            message: Some("Fetched from remote".to_string()),
+            path: None,
+            scope: "all".to_string(),
+        }
+    }
+
+    #[test]
+    fn concurrent_fetches_on_same_key_run_once() {
+        let key = PathBuf::from("/tmp/tidyflow-fetch-coalesce-test");
+        let runs = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(4));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let (key, runs, barrier) = (key.clone(), runs.clone(), barrier.clone());
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    coalesce_fetch(key, || {
+                        runs.fetch_add(1, Ordering::SeqCst);
+                        std::thread::sleep(Duration::from_millis(200));
+                        Ok(fetched())
+                    })
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.join().unwrap().unwrap().ok);
+        }
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        // 前一次结束后不再复用结果
+        let err = coalesce_fetch(key, || Err(GitError::CommandFailed("offline".to_string())));
+        assert!(err.is_err());
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+}
@@ -4,33 +4,83 @@
 //! without affecting the user's working directory.
 
 use std::path::{Path, PathBuf};
-use std::process::Command;
 
+use super::network::{apply_git_proxy, ensure_online, git_network_settings};
 use super::utils::*;
+use crate::util::exec_env::git_command;
 
-/// Get the integration worktree path for a project
-fn get_integration_worktree_path(project_name: &str) -> PathBuf {
-    // Sanitize project name (alphanumeric + hyphen only)
-    let sanitized: String = project_name
-        .chars()
+/// 集成目标：合并 / rebase 的目标分支及其专属集成 worktree
+///
+/// 项目默认分支沿用 `__integration` 目录；其他目标分支（如 `release/1.x`）各自使用
+/// `__integration-<分支>`，多个目标的合并、冲突与重置互不干扰。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrationTarget {
+    pub project_name: String,
+    /// 目标分支
+    pub branch: String,
+    /// 是否为项目默认分支
+    pub is_default: bool,
+}
+
+impl IntegrationTarget {
+    /// 项目默认分支对应的集成目标
+    pub fn default_for(project_name: &str, default_branch: &str) -> Self {
+        Self {
+            project_name: project_name.to_string(),
+            branch: default_branch.to_string(),
+            is_default: true,
+        }
+    }
+
+    /// 按请求的目标分支选择集成目标；省略、为空或等于默认分支时为默认目标
+    pub fn resolve(project_name: &str, default_branch: &str, branch: Option<&str>) -> Self {
+        match branch.map(str::trim).filter(|b| !b.is_empty()) {
+            Some(branch) if branch != default_branch => Self {
+                project_name: project_name.to_string(),
+                branch: branch.to_string(),
+                is_default: false,
+            },
+            _ => Self::default_for(project_name, default_branch),
+        }
+    }
+
+    /// 集成 worktree 路径
+    pub fn worktree_path(&self) -> PathBuf {
+        let dir = if self.is_default {
+            "__integration".to_string()
+        } else {
+            format!(
+                "__integration-{}",
+                sanitize_path_segment(&self.branch, &['.', '_'])
+            )
+        };
+        crate::util::paths::tidyflow_home_dir()
+            .join("worktrees")
+            .join(sanitize_path_segment(&self.project_name, &[]))
+            .join(dir)
+    }
+}
+
+/// 解析冲突向导的 `context`：`integration` 指默认集成目标（`Some(None)`），
+/// `integration:<分支>` 指该分支的集成目标；其他取值（`workspace`）返回 None
+pub fn integration_context_branch(context: &str) -> Option<Option<&str>> {
+    match context.strip_prefix("integration")? {
+        "" => Some(None),
+        rest => rest.strip_prefix(':').map(Some),
+    }
+}
+
+/// 目录名只保留字母数字、`-` 与 `extra` 中的字符，其余替换为 `-`
+fn sanitize_path_segment(name: &str, extra: &[char]) -> String {
+    name.chars()
         .map(|c| {
-            if c.is_alphanumeric() || c == '-' {
+            if c.is_alphanumeric() || c == '-' || extra.contains(&c) {
                 c
             } else {
                 '-'
             }
         })
-        .collect();
-
-    crate::util::paths::tidyflow_home_dir()
-        .join("worktrees")
-        .join(sanitized)
-        .join("__integration")
-}
-
-/// 公开访问集成工作树路径（用于冲突向导等需要直接操作工作树的场景）
-pub fn get_integration_worktree_root(project_name: &str) -> std::path::PathBuf {
-    get_integration_worktree_path(project_name)
+        .collect()
 }
 
 /// Check if integration worktree exists
@@ -41,10 +91,7 @@ fn integration_worktree_exists(path: &Path) -> bool {
 /// Check if integration worktree is clean (no uncommitted changes, no merge/rebase in progress)
 fn is_integration_clean(path: &Path) -> bool {
     // Check for uncommitted changes
-    let status_output = Command::new("git")
-        .args(["status", "--porcelain"])
-        .current_dir(path)
-        .output();
+    let status_output = git_command(path).args(["status", "--porcelain"]).output();
 
     let has_changes = match status_output {
         Ok(out) => !String::from_utf8_lossy(&out.stdout).trim().is_empty(),
@@ -71,9 +118,8 @@ fn is_integration_clean(path: &Path) -> bool {
 /// UX-4: Check if currently in a rebase state (for integration worktree)
 fn is_rebasing_in_worktree(worktree_path: &Path) -> bool {
     // Check via git command for worktrees (handles both .git file and .git dir)
-    let output = Command::new("git")
+    let output = git_command(worktree_path)
         .args(["rev-parse", "--git-path", "rebase-merge"])
-        .current_dir(worktree_path)
         .output();
 
     if let Ok(out) = output {
@@ -84,9 +130,8 @@ fn is_rebasing_in_worktree(worktree_path: &Path) -> bool {
     }
 
     // Also check rebase-apply (for git am / git rebase --apply)
-    let output = Command::new("git")
+    let output = git_command(worktree_path)
         .args(["rev-parse", "--git-path", "rebase-apply"])
-        .current_dir(worktree_path)
         .output();
 
     if let Ok(out) = output {
@@ -104,10 +149,10 @@ fn is_rebasing_in_worktree(worktree_path: &Path) -> bool {
 /// Creates the worktree if it doesn't exist, or validates it's clean if it does.
 pub fn ensure_integration_worktree(
     repo_root: &Path,
-    project_name: &str,
-    default_branch: &str,
+    target: &IntegrationTarget,
 ) -> Result<String, GitError> {
-    let integration_path = get_integration_worktree_path(project_name);
+    let integration_path = target.worktree_path();
+    let default_branch = target.branch.as_str();
 
     if integration_worktree_exists(&integration_path) {
         // Worktree exists, check if clean
@@ -118,9 +163,8 @@ pub fn ensure_integration_worktree(
         }
 
         // Ensure we're on the default branch
-        let checkout_output = Command::new("git")
+        let checkout_output = git_command(&integration_path)
             .args(["checkout", default_branch])
-            .current_dir(&integration_path)
             .output()
             .map_err(GitError::IoError)?;
 
@@ -141,14 +185,13 @@ pub fn ensure_integration_worktree(
     }
 
     // Create the worktree
-    let output = Command::new("git")
+    let output = git_command(repo_root)
         .args([
             "worktree",
             "add",
             integration_path.to_string_lossy().as_ref(),
             default_branch,
         ])
-        .current_dir(repo_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -168,11 +211,9 @@ pub fn ensure_integration_worktree(
 }
 
 /// Get integration worktree status
-pub fn integration_status(
-    project_name: &str,
-    default_branch: &str,
-) -> Result<IntegrationStatusResult, GitError> {
-    let integration_path = get_integration_worktree_path(project_name);
+pub fn integration_status(target: &IntegrationTarget) -> Result<IntegrationStatusResult, GitError> {
+    let integration_path = target.worktree_path();
+    let default_branch = target.branch.as_str();
 
     if !integration_worktree_exists(&integration_path) {
         return Ok(IntegrationStatusResult {
@@ -242,23 +283,21 @@ pub fn integration_status(
 /// This performs the merge in the integration worktree, not the user's workspace.
 pub fn merge_to_default(
     repo_root: &Path,
-    project_name: &str,
+    target: &IntegrationTarget,
     source_branch: &str,
-    default_branch: &str,
 ) -> Result<MergeToDefaultResult, GitError> {
+    let default_branch = target.branch.as_str();
     // Ensure integration worktree exists and is clean
-    let integration_path_str =
-        ensure_integration_worktree(repo_root, project_name, default_branch)?;
+    let integration_path_str = ensure_integration_worktree(repo_root, target)?;
     let integration_path = PathBuf::from(&integration_path_str);
 
     // Verify source branch exists
-    let show_ref_output = Command::new("git")
+    let show_ref_output = git_command(repo_root)
         .args([
             "show-ref",
             "--verify",
             &format!("refs/heads/{}", source_branch),
         ])
-        .current_dir(repo_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -275,9 +314,8 @@ pub fn merge_to_default(
     }
 
     // Perform the merge
-    let merge_output = Command::new("git")
+    let merge_output = git_command(&integration_path)
         .args(["merge", source_branch, "--no-edit"])
-        .current_dir(&integration_path)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -329,8 +367,8 @@ pub fn merge_to_default(
 }
 
 /// Continue a merge after conflict resolution
-pub fn merge_continue(project_name: &str) -> Result<MergeToDefaultResult, GitError> {
-    let integration_path = get_integration_worktree_path(project_name);
+pub fn merge_continue(target: &IntegrationTarget) -> Result<MergeToDefaultResult, GitError> {
+    let integration_path = target.worktree_path();
 
     if !integration_worktree_exists(&integration_path) {
         return Ok(MergeToDefaultResult {
@@ -374,9 +412,8 @@ pub fn merge_continue(project_name: &str) -> Result<MergeToDefaultResult, GitErr
     }
 
     // Stage all changes
-    let add_output = Command::new("git")
+    let add_output = git_command(&integration_path)
         .args(["add", "-A"])
-        .current_dir(&integration_path)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -396,9 +433,8 @@ pub fn merge_continue(project_name: &str) -> Result<MergeToDefaultResult, GitErr
     }
 
     // Complete the merge with commit
-    let commit_output = Command::new("git")
+    let commit_output = git_command(&integration_path)
         .args(["commit", "--no-edit"])
-        .current_dir(&integration_path)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -453,8 +489,8 @@ pub fn merge_continue(project_name: &str) -> Result<MergeToDefaultResult, GitErr
 }
 
 /// Abort a merge in progress
-pub fn merge_abort(project_name: &str) -> Result<MergeToDefaultResult, GitError> {
-    let integration_path = get_integration_worktree_path(project_name);
+pub fn merge_abort(target: &IntegrationTarget) -> Result<MergeToDefaultResult, GitError> {
+    let integration_path = target.worktree_path();
 
     if !integration_worktree_exists(&integration_path) {
         return Ok(MergeToDefaultResult {
@@ -480,9 +516,8 @@ pub fn merge_abort(project_name: &str) -> Result<MergeToDefaultResult, GitError>
         });
     }
 
-    let output = Command::new("git")
+    let output = git_command(&integration_path)
         .args(["merge", "--abort"])
-        .current_dir(&integration_path)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -528,23 +563,24 @@ pub fn merge_abort(project_name: &str) -> Result<MergeToDefaultResult, GitError>
 /// 4. Rebase onto default branch
 pub fn rebase_onto_default(
     repo_root: &Path,
-    project_name: &str,
+    target: &IntegrationTarget,
     source_branch: &str,
-    default_branch: &str,
 ) -> Result<RebaseOntoDefaultResult, GitError> {
+    let default_branch = target.branch.as_str();
+    // Rebase 需要先 fetch 远程默认分支，离线时直接拒绝
+    ensure_online("fetch")?;
+
     // Ensure integration worktree exists and is clean
-    let integration_path_str =
-        ensure_integration_worktree(repo_root, project_name, default_branch)?;
+    let integration_path_str = ensure_integration_worktree(repo_root, target)?;
     let integration_path = PathBuf::from(&integration_path_str);
 
     // Verify source branch exists
-    let show_ref_output = Command::new("git")
+    let show_ref_output = git_command(repo_root)
         .args([
             "show-ref",
             "--verify",
             &format!("refs/heads/{}", source_branch),
         ])
-        .current_dir(repo_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -561,9 +597,8 @@ pub fn rebase_onto_default(
     }
 
     // Checkout the source branch in integration worktree
-    let checkout_output = Command::new("git")
+    let checkout_output = git_command(&integration_path)
         .args(["checkout", source_branch])
-        .current_dir(&integration_path)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -583,9 +618,10 @@ pub fn rebase_onto_default(
     }
 
     // Fetch latest from remote
-    let fetch_output = Command::new("git")
+    let mut fetch_cmd = git_command(&integration_path);
+    apply_git_proxy(&mut fetch_cmd, Some(repo_root));
+    let fetch_output = fetch_cmd
         .args(["fetch", "origin"])
-        .current_dir(&integration_path)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -606,9 +642,8 @@ pub fn rebase_onto_default(
 
     // Perform the rebase onto default branch (use origin/<default_branch> for remote tracking)
     let rebase_target = format!("origin/{}", default_branch);
-    let rebase_output = Command::new("git")
+    let rebase_output = git_command(&integration_path)
         .args(["rebase", &rebase_target])
-        .current_dir(&integration_path)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -661,9 +696,9 @@ pub fn rebase_onto_default(
 
 /// UX-4: Continue a rebase after conflict resolution
 pub fn rebase_onto_default_continue(
-    project_name: &str,
+    target: &IntegrationTarget,
 ) -> Result<RebaseOntoDefaultResult, GitError> {
-    let integration_path = get_integration_worktree_path(project_name);
+    let integration_path = target.worktree_path();
 
     if !integration_worktree_exists(&integration_path) {
         return Ok(RebaseOntoDefaultResult {
@@ -707,9 +742,8 @@ pub fn rebase_onto_default_continue(
     }
 
     // Stage all changes (required before rebase --continue)
-    let add_output = Command::new("git")
+    let add_output = git_command(&integration_path)
         .args(["add", "-A"])
-        .current_dir(&integration_path)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -729,9 +763,8 @@ pub fn rebase_onto_default_continue(
     }
 
     // Continue the rebase
-    let continue_output = Command::new("git")
+    let continue_output = git_command(&integration_path)
         .args(["rebase", "--continue"])
-        .current_dir(&integration_path)
         .env("GIT_EDITOR", "true") // Skip editor for commit message
         .output()
         .map_err(GitError::IoError)?;
@@ -815,8 +848,10 @@ pub fn rebase_onto_default_continue(
 }
 
 /// UX-4: Abort a rebase in progress
-pub fn rebase_onto_default_abort(project_name: &str) -> Result<RebaseOntoDefaultResult, GitError> {
-    let integration_path = get_integration_worktree_path(project_name);
+pub fn rebase_onto_default_abort(
+    target: &IntegrationTarget,
+) -> Result<RebaseOntoDefaultResult, GitError> {
+    let integration_path = target.worktree_path();
 
     if !integration_worktree_exists(&integration_path) {
         return Ok(RebaseOntoDefaultResult {
@@ -842,9 +877,8 @@ pub fn rebase_onto_default_abort(project_name: &str) -> Result<RebaseOntoDefault
         });
     }
 
-    let output = Command::new("git")
+    let output = git_command(&integration_path)
         .args(["rebase", "--abort"])
-        .current_dir(&integration_path)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -890,10 +924,10 @@ pub fn rebase_onto_default_abort(project_name: &str) -> Result<RebaseOntoDefault
 /// SAFETY: This only affects the integration worktree, not user's workspace
 pub fn reset_integration_worktree(
     repo_root: &Path,
-    project_name: &str,
-    default_branch: &str,
+    target: &IntegrationTarget,
 ) -> Result<ResetIntegrationWorktreeResult, GitError> {
-    let integration_path = get_integration_worktree_path(project_name);
+    let integration_path = target.worktree_path();
+    let default_branch = target.branch.as_str();
     let integration_path_str = integration_path.to_string_lossy().to_string();
 
     // Safety check: Validate path is under ~/.tidyflow/worktrees/
@@ -910,24 +944,21 @@ pub fn reset_integration_worktree(
     if integration_worktree_exists(&integration_path) {
         // Abort any in-progress rebase
         if is_rebasing_in_worktree(&integration_path) {
-            let _ = Command::new("git")
+            let _ = git_command(&integration_path)
                 .args(["rebase", "--abort"])
-                .current_dir(&integration_path)
                 .output();
         }
 
         // Abort any in-progress merge
         if is_merging(&integration_path) {
-            let _ = Command::new("git")
+            let _ = git_command(&integration_path)
                 .args(["merge", "--abort"])
-                .current_dir(&integration_path)
                 .output();
         }
 
         // Remove the worktree forcefully
-        let remove_output = Command::new("git")
+        let remove_output = git_command(repo_root)
             .args(["worktree", "remove", &integration_path_str, "--force"])
-            .current_dir(repo_root)
             .output()
             .map_err(GitError::IoError)?;
 
@@ -939,10 +970,7 @@ pub fn reset_integration_worktree(
             }
 
             // Also prune stale worktree entries
-            let _ = Command::new("git")
-                .args(["worktree", "prune"])
-                .current_dir(repo_root)
-                .output();
+            let _ = git_command(repo_root).args(["worktree", "prune"]).output();
         }
     }
 
@@ -952,9 +980,8 @@ pub fn reset_integration_worktree(
     }
 
     // Recreate the worktree
-    let create_output = Command::new("git")
+    let create_output = git_command(repo_root)
         .args(["worktree", "add", &integration_path_str, default_branch])
-        .current_dir(repo_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -1005,17 +1032,21 @@ pub fn check_branch_divergence(
         return Err(GitError::NotAGitRepo);
     }
 
-    // Fetch from origin (safe, read-only operation)
+    // Fetch from origin (safe, read-only operation); offline or sleeping hosts use local refs only
     // Use a timeout to avoid blocking indefinitely on network issues
-    let fetch_output = Command::new("git")
-        .args(["fetch", "origin", "--no-tags"])
-        .current_dir(workspace_root)
-        .output();
+    let fetch_output =
+        if git_network_settings().offline_mode || crate::server::power::is_host_sleeping() {
+            None
+        } else {
+            let mut fetch_cmd = git_command(workspace_root);
+            apply_git_proxy(&mut fetch_cmd, Some(workspace_root));
+            Some(fetch_cmd.args(["fetch", "origin", "--no-tags"]).output())
+        };
 
     // Log fetch result but don't fail if fetch fails (network might be unavailable)
-    if let Err(e) = &fetch_output {
+    if let Some(Err(e)) = &fetch_output {
         tracing::warn!("Git fetch failed (continuing with local data): {}", e);
-    } else if let Ok(output) = &fetch_output {
+    } else if let Some(Ok(output)) = &fetch_output {
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
             tracing::warn!(
@@ -1025,13 +1056,21 @@ pub fn check_branch_divergence(
         }
     }
 
+    compare_with_remote_default(workspace_root, current_branch, default_branch)
+}
+
+/// 用本地已有的远程跟踪引用计算当前分支相对 `origin/<default_branch>` 的领先/落后数（不 fetch）
+pub fn compare_with_remote_default(
+    workspace_root: &Path,
+    current_branch: &str,
+    default_branch: &str,
+) -> Result<BranchDivergenceResult, GitError> {
     // Build the comparison ref
     let remote_ref = format!("origin/{}", default_branch);
 
     // Check if the remote ref exists
-    let check_ref_output = Command::new("git")
+    let check_ref_output = git_command(workspace_root)
         .args(["rev-parse", "--verify", &remote_ref])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -1044,14 +1083,13 @@ pub fn check_branch_divergence(
 
     // Run git rev-list to get ahead/behind counts
     // Format: <ahead>\t<behind>
-    let rev_list_output = Command::new("git")
+    let rev_list_output = git_command(workspace_root)
         .args([
             "rev-list",
             "--left-right",
             "--count",
             &format!("{}...{}", current_branch, remote_ref),
         ])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
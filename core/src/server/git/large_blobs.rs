@@ -0,0 +1,261 @@
+//! 历史大文件检测与重写指引
+//!
+//! 扫描所有引用可达历史中超过阈值的 blob，给出路径、大小与引入它们的提交，
+//! 帮助定位克隆 / 创建 worktree 变慢的原因。可选附带 `git filter-repo` 的重写计划：
+//! 只生成命令文本供用户在新克隆中执行，Core 从不改写历史。
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::Stdio;
+
+use super::repo_stats::{parse_blob_check_line, scan_history_objects};
+use super::utils::*;
+use crate::util::exec_env::git_command;
+
+/// 未指定阈值时的默认值（1 MiB）
+pub const DEFAULT_LARGE_BLOB_THRESHOLD: u64 = 1024 * 1024;
+/// 最多返回的 blob 个数（按大小降序截断）
+pub const MAX_LARGE_BLOBS: usize = 200;
+/// 每个 blob 最多列出的提交数
+pub const MAX_LARGE_BLOB_COMMITS: usize = 20;
+
+/// 引入某个大 blob 的提交
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LargeBlobCommit {
+    pub sha: String,
+    pub subject: String,
+    /// 提交时间（Unix 秒）
+    pub committed_at: i64,
+}
+
+/// 超过阈值的 blob
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LargeBlob {
+    pub sha: String,
+    /// 该 blob 在历史中出现过的路径（去重、排序）
+    pub paths: Vec<String>,
+    /// 未压缩大小（字节）
+    pub size: u64,
+    /// 对象库中的实际占用（字节）
+    pub disk_size: u64,
+    /// 写入该 blob 的提交（新到旧，最多 [`MAX_LARGE_BLOB_COMMITS`] 个）
+    pub commits: Vec<LargeBlobCommit>,
+}
+
+/// `git filter-repo` 重写计划（仅供参考执行，Core 不运行）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterRepoPlan {
+    /// 本机是否可用 `git filter-repo`
+    pub available: bool,
+    /// 按大小剥离：`git filter-repo --strip-blobs-bigger-than <threshold>`
+    pub strip_by_size_command: String,
+    /// 按路径移除：`git filter-repo --invert-paths --path ...`
+    pub remove_paths_command: String,
+    /// 重写后预计从对象库移除的字节数（各 blob `disk_size` 之和）
+    pub estimated_savings_bytes: u64,
+}
+
+/// 大文件检测结果
+#[derive(Debug, Clone)]
+pub struct LargeBlobReport {
+    pub threshold: u64,
+    pub blobs: Vec<LargeBlob>,
+    /// 超过阈值的 blob 总数（可能大于返回的个数）
+    pub total_count: usize,
+    pub total_size: u64,
+    pub truncated: bool,
+    pub filter_repo: Option<FilterRepoPlan>,
+}
+
+/// 检测历史中大于 `threshold` 字节的 blob；`with_filter_repo_plan` 时附带重写计划
+pub fn git_detect_large_blobs(
+    repo_root: &Path,
+    threshold: Option<u64>,
+    with_filter_repo_plan: bool,
+) -> Result<LargeBlobReport, GitError> {
+    let threshold = threshold
+        .filter(|t| *t > 0)
+        .unwrap_or(DEFAULT_LARGE_BLOB_THRESHOLD);
+
+    let mut by_sha: HashMap<String, LargeBlob> = scan_history_objects(repo_root, |lines| {
+        let mut by_sha: HashMap<String, LargeBlob> = HashMap::new();
+        for line in lines {
+            let Some(blob) = parse_blob_check_line(&line) else {
+                continue;
+            };
+            if blob.size <= threshold {
+                continue;
+            }
+            let entry = by_sha.entry(blob.sha.clone()).or_insert_with(|| LargeBlob {
+                sha: blob.sha,
+                paths: Vec::new(),
+                size: blob.size,
+                disk_size: blob.disk_size,
+                commits: Vec::new(),
+            });
+            if !blob.path.is_empty() && !entry.paths.contains(&blob.path) {
+                entry.paths.push(blob.path);
+            }
+        }
+        by_sha
+    })?;
+
+    let total_count = by_sha.len();
+    let total_size = by_sha.values().map(|b| b.size).sum();
+    if !by_sha.is_empty() {
+        attach_introducing_commits(repo_root, &mut by_sha)?;
+    }
+
+    let mut blobs: Vec<LargeBlob> = by_sha.into_values().collect();
+    for blob in &mut blobs {
+        blob.paths.sort();
+    }
+    blobs.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.sha.cmp(&b.sha)));
+    let truncated = blobs.len() > MAX_LARGE_BLOBS;
+    blobs.truncate(MAX_LARGE_BLOBS);
+
+    let filter_repo = with_filter_repo_plan.then(|| FilterRepoPlan {
+        available: filter_repo_available(repo_root),
+        strip_by_size_command: format!("git filter-repo --strip-blobs-bigger-than {}", threshold),
+        remove_paths_command: remove_paths_command(&blobs),
+        estimated_savings_bytes: blobs.iter().map(|b| b.disk_size).sum(),
+    });
+
+    Ok(LargeBlobReport {
+        threshold,
+        blobs,
+        total_count,
+        total_size,
+        truncated,
+        filter_repo,
+    })
+}
+
+/// 流式扫描 `git log --all --raw`，记录写入各大 blob 的提交（新到旧）与全部路径
+fn attach_introducing_commits(
+    repo_root: &Path,
+    by_sha: &mut HashMap<String, LargeBlob>,
+) -> Result<(), GitError> {
+    let mut child = git_command(repo_root)
+        .args([
+            "-c",
+            "core.quotePath=false",
+            "log",
+            "--all",
+            "--raw",
+            "--no-abbrev",
+            "--no-renames",
+            "--format=%x00%H%x1f%ct%x1f%s",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(GitError::IoError)?;
+    let Some(stdout) = child.stdout.take() else {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(GitError::CommandFailed(
+            "log stdout unavailable".to_string(),
+        ));
+    };
+
+    let mut current: Option<LargeBlobCommit> = None;
+    for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+        if let Some(header) = line.strip_prefix('\0') {
+            let mut fields = header.splitn(3, '\u{1f}');
+            current = match (fields.next(), fields.next(), fields.next()) {
+                (Some(sha), Some(ts), subject) => Some(LargeBlobCommit {
+                    sha: sha.to_string(),
+                    subject: subject.unwrap_or_default().to_string(),
+                    committed_at: ts.parse().unwrap_or(0),
+                }),
+                _ => None,
+            };
+            continue;
+        }
+        // :<旧模式> <新模式> <旧 sha> <新 sha> <状态>\t<路径>
+        let (Some(commit), Some(meta)) = (current.as_ref(), line.strip_prefix(':')) else {
+            continue;
+        };
+        let (meta, path) = meta.split_once('\t').unwrap_or((meta, ""));
+        let Some(blob) = meta.split(' ').nth(3).and_then(|sha| by_sha.get_mut(sha)) else {
+            continue;
+        };
+        // rev-list --objects 每个对象只给出首个路径，其余路径从这里补齐
+        if !path.is_empty() && !blob.paths.iter().any(|p| p == path) {
+            blob.paths.push(path.to_string());
+        }
+        if blob.commits.len() < MAX_LARGE_BLOB_COMMITS
+            && !blob.commits.iter().any(|c| c.sha == commit.sha)
+        {
+            blob.commits.push(commit.clone());
+        }
+    }
+
+    let status = child.wait().map_err(GitError::IoError)?;
+    if !status.success() {
+        return Err(GitError::CommandFailed(
+            "failed to scan commit history".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn filter_repo_available(repo_root: &Path) -> bool {
+    git_command(repo_root)
+        .args(["filter-repo", "--version"])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+fn remove_paths_command(blobs: &[LargeBlob]) -> String {
+    let mut paths: Vec<&str> = blobs
+        .iter()
+        .flat_map(|b| b.paths.iter().map(String::as_str))
+        .collect();
+    paths.sort_unstable();
+    paths.dedup();
+    let mut command = "git filter-repo --invert-paths".to_string();
+    for path in paths {
+        command.push_str(" --path ");
+        command.push_str(&shell_quote(path));
+    }
+    command
+}
+
+fn shell_quote(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./".contains(c))
+    {
+        return value.to_string();
+    }
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_paths_command_quotes_and_dedups_paths() {
+        let blob = |paths: &[&str]| LargeBlob {
+            sha: "a".to_string(),
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+            size: 10,
+            disk_size: 10,
+            commits: Vec::new(),
+        };
+        let command = remove_paths_command(&[
+            blob(&["assets/video.mp4", "it's big.bin"]),
+            blob(&["assets/video.mp4"]),
+        ]);
+        assert_eq!(
+            command,
+            "git filter-repo --invert-paths --path assets/video.mp4 --path 'it'\\''s big.bin'"
+        );
+    }
+}
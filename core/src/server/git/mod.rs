@@ -3,26 +3,82 @@
 // This module is split into logical submodules:
 // - utils: Common types, constants, error handling, and helper functions
 // - status: Status queries (git_status, git_log, git_show)
+// - backend: GitBackend trait selecting gix or git CLI for status / log / diff / branch listing
+// - cache: Per-workspace git status cache (fingerprint hit check, configurable TTL, invalidation)
 // - operations: File operations (diff, stage, unstage, discard)
 // - branches: Branch management (list, switch, create)
+// - change_report: Self-contained HTML report of a branch's changes versus the default branch
 // - commit: Commit and rebase operations
+// - commit_message: Suggested commit messages from workspace context and templates
+// - commit_message_command: Sandboxed external command that generates commit messages
+// - fetch_coordinator: Coalesces concurrent fetches of the same repository across worktrees
 // - integration: Integration worktree management
+// - network: Proxy and offline mode for network operations (fetch / clone)
+// - progress: Progress streaming and cancellation (op_id) for long-running fetch / rebase / clone
+// - pull_request: Push a branch and open a GitHub pull request / GitLab merge request
+// - large_blobs: Oversized blobs in history and `git filter-repo` rewrite plans (dry-run only)
+// - blame: Line attribution (blame) with ignore-revs support
+// - binary_diff: Before/after blob metadata (size, MIME, image dimensions) for binary diffs
+// - diff_range: Commit range diffs (per-file summary + on-demand patches)
+// - diff_hunks: Unified diff text parsed into structured hunks
+// - unified_diff: git-compatible single-file unified diff rendered with gix (GitBackend gix path)
+// - rebase_interactive: Interactive rebase planning and GIT_SEQUENCE_EDITOR-driven execution
+// - repo_stats: Repository statistics (objects, packs, largest files) and maintenance suggestions
+// - smart_diff: Noise-reducing diffs for lockfiles / notebooks, .gitattributes drivers
 
+pub mod backend;
+pub mod binary_diff;
+pub mod blame;
 pub mod branches;
+pub mod cache;
+pub mod change_report;
 pub mod commit;
+pub mod commit_message;
+pub mod commit_message_command;
+pub mod diff_hunks;
+pub mod diff_range;
+pub mod fetch_coordinator;
 pub mod integration;
+pub mod large_blobs;
+pub mod network;
 pub mod operations;
+pub mod progress;
+pub mod pull_request;
+pub mod rebase_interactive;
+pub mod repo_stats;
 pub mod sequencer;
+pub mod smart_diff;
 pub mod stash;
 pub mod status;
+mod unified_diff;
 pub mod utils;
 
+#[cfg(test)]
+mod parser_props_test;
+
 // Re-export all public items for backward compatibility
+pub use backend::{backend_for, configured_backend, GitBackend, GitBackendKind};
+pub use binary_diff::*;
+pub use blame::*;
 pub use branches::*;
+pub use cache::{invalidate_all_git_status_cache, invalidate_git_status_cache};
+pub use change_report::*;
 pub use commit::*;
+pub use commit_message::*;
+pub use commit_message_command::*;
+pub use diff_hunks::*;
+pub use diff_range::*;
+pub use fetch_coordinator::*;
 pub use integration::*;
+pub use large_blobs::*;
+pub use network::*;
 pub use operations::*;
+pub use progress::*;
+pub use pull_request::*;
+pub use rebase_interactive::*;
+pub use repo_stats::*;
 pub use sequencer::*;
+pub use smart_diff::*;
 pub use stash::*;
 pub use status::*;
 pub use utils::*;
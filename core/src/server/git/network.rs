@@ -0,0 +1,175 @@
+//! git 网络操作的代理与离线模式
+//!
+//! 全局设置来自客户端设置（`git_proxy` / `git_offline_mode`），启动与保存设置时经
+//! [`configure_git_network`] 写入；项目可在 `.tidyflow.toml` 的 `[git] proxy` 覆盖代理，
+//! `proxy = "direct"` 表示该项目不走代理。
+//!
+//! 只作用于 fetch / clone 等网络操作：离线模式下这些操作直接返回 `offline_mode` 错误，
+//! 不再启动注定挂起或超时的子进程。
+
+use std::path::Path;
+use std::process::Command;
+use std::sync::{LazyLock, RwLock};
+
+use super::utils::GitError;
+use crate::workspace::config::ProjectConfig;
+
+/// 离线模式错误码
+pub const OFFLINE_MODE_CODE: &str = "offline_mode";
+
+/// 项目级代理取该值时表示直连（忽略全局代理与继承的代理环境变量）
+pub const DIRECT_PROXY: &str = "direct";
+
+/// 传给 git / libcurl 的代理环境变量（curl 出于安全考虑忽略大写 `HTTP_PROXY`）
+const PROXY_ENV_VARS: &[&str] = &[
+    "http_proxy",
+    "https_proxy",
+    "HTTPS_PROXY",
+    "all_proxy",
+    "ALL_PROXY",
+];
+
+/// 全局 git 网络设置
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitNetworkSettings {
+    /// 代理地址（如 `http://127.0.0.1:7890`、`socks5h://127.0.0.1:1080`）
+    pub proxy: Option<String>,
+    pub offline_mode: bool,
+}
+
+impl GitNetworkSettings {
+    /// 离线模式下拒绝网络操作 `op`
+    pub fn ensure_online(&self, op: &str) -> Result<(), GitError> {
+        if self.offline_mode {
+            return Err(GitError::OfflineMode(op.to_string()));
+        }
+        Ok(())
+    }
+}
+
+static GIT_NETWORK_SETTINGS: LazyLock<RwLock<GitNetworkSettings>> =
+    LazyLock::new(|| RwLock::new(GitNetworkSettings::default()));
+
+/// 更新全局 git 网络设置
+pub fn configure_git_network(settings: GitNetworkSettings) {
+    *GIT_NETWORK_SETTINGS
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = settings;
+}
+
+/// 当前全局 git 网络设置
+pub fn git_network_settings() -> GitNetworkSettings {
+    GIT_NETWORK_SETTINGS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// 按全局设置检查网络操作 `op` 是否允许
+pub fn ensure_online(op: &str) -> Result<(), GitError> {
+    git_network_settings().ensure_online(op)
+}
+
+/// 校验代理地址：需带协议前缀，`direct` 仅在项目级配置中有意义
+pub fn validate_proxy_url(proxy: &str) -> Result<(), String> {
+    let proxy = proxy.trim();
+    let valid_scheme = [
+        "http://",
+        "https://",
+        "socks4://",
+        "socks4a://",
+        "socks5://",
+        "socks5h://",
+    ]
+    .iter()
+    .any(|scheme| proxy.starts_with(scheme) && proxy.len() > scheme.len());
+    if !valid_scheme || proxy.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(format!(
+            "Invalid proxy url: {} (must start with http://, https://, socks4:// or socks5://)",
+            proxy
+        ));
+    }
+    Ok(())
+}
+
+/// 为网络类 git 子进程设置代理：项目 `[git] proxy` 优先，其次全局设置；均未设置时不改动环境
+pub fn apply_git_proxy(cmd: &mut Command, project_root: Option<&Path>) {
+    let project_proxy = project_root
+        .and_then(|root| ProjectConfig::load(root).ok())
+        .and_then(|config| config.git.proxy)
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty());
+    let proxy = project_proxy.or_else(|| git_network_settings().proxy);
+    match proxy.as_deref() {
+        None => {}
+        Some(DIRECT_PROXY) => {
+            for var in PROXY_ENV_VARS {
+                cmd.env_remove(var);
+            }
+            cmd.env("no_proxy", "*").env("NO_PROXY", "*");
+        }
+        Some(proxy) => {
+            for var in PROXY_ENV_VARS {
+                cmd.env(var, proxy);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env_of(cmd: &Command, key: &str) -> Option<Option<String>> {
+        cmd.get_envs()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.map(|v| v.to_string_lossy().to_string()))
+    }
+
+    #[test]
+    fn offline_mode_rejects_network_ops() {
+        let offline = GitNetworkSettings {
+            proxy: None,
+            offline_mode: true,
+        };
+        let err = offline.ensure_online("fetch").unwrap_err();
+        assert!(matches!(err, GitError::OfflineMode(ref op) if op == "fetch"));
+        assert!(GitNetworkSettings::default().ensure_online("fetch").is_ok());
+    }
+
+    #[test]
+    fn validate_proxy_url_requires_scheme() {
+        assert!(validate_proxy_url("http://127.0.0.1:7890").is_ok());
+        assert!(validate_proxy_url("socks5h://proxy:1080").is_ok());
+        assert!(validate_proxy_url("127.0.0.1:7890").is_err());
+        assert!(validate_proxy_url("http://").is_err());
+        assert!(validate_proxy_url("http://a b").is_err());
+    }
+
+    #[test]
+    fn project_proxy_overrides_global_and_direct_clears_env() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".tidyflow.toml"),
+            "[git]\nproxy = \"direct\"\n",
+        )
+        .unwrap();
+
+        let mut cmd = Command::new("git");
+        apply_git_proxy(&mut cmd, Some(dir.path()));
+        assert_eq!(env_of(&cmd, "https_proxy"), Some(None));
+        assert_eq!(env_of(&cmd, "no_proxy"), Some(Some("*".to_string())));
+
+        std::fs::write(
+            dir.path().join(".tidyflow.toml"),
+            "[git]\nproxy = \"http://proxy.local:3128\"\n",
+        )
+        .unwrap();
+        let mut cmd = Command::new("git");
+        apply_git_proxy(&mut cmd, Some(dir.path()));
+        assert_eq!(
+            env_of(&cmd, "https_proxy"),
+            Some(Some("http://proxy.local:3128".to_string()))
+        );
+    }
+}
@@ -3,23 +3,35 @@
 //! Provides functions for staging, unstaging, and discarding file changes.
 
 use std::path::Path;
-use std::process::Command;
 
-use super::status::{git_file_status, invalidate_git_status_cache};
+use super::backend::{backend_for, CliBackend, DiffTarget, GitBackend};
+use super::cache::invalidate_git_status_cache;
+use super::smart_diff::{builtin_smart_diff, gitattributes_diff_driver, SMART_DIFF_GITATTRIBUTES};
+use super::status::git_file_status;
 use super::utils::*;
+use crate::util::cancel;
+use crate::util::exec_env::git_command;
+use crate::workspace::config::ProjectConfig;
 
 /// Get git diff for a specific file
 ///
 /// For tracked files: `git diff -- <path>` (working) or `git diff --cached -- <path>` (staged)
 /// For untracked files: `git diff --no-index /dev/null -- <path>`
+///
+/// `algorithm` 为空时回退到 `.tidyflow.toml` 中的 `[git] diff_algorithm`，
+/// 两者都未设置则沿用 git 自身的 `diff.algorithm` 配置。
 pub fn git_diff(
     workspace_root: &Path,
     path: &str,
     base: Option<&str>,
     mode: &str, // "working" or "staged"
+    algorithm: Option<&str>,
+    smart_diff: bool,
 ) -> Result<GitDiffResult, GitError> {
     // Validate path
     let _full_path = validate_path(workspace_root, path)?;
+    let algorithm = resolve_diff_algorithm(workspace_root, algorithm)?;
+    let algorithm = algorithm.as_deref();
 
     // Check if it's a git repo
     if get_git_repo_root(workspace_root).is_none() {
@@ -42,27 +54,57 @@ pub fn git_diff(
             is_binary: true,
             truncated: false,
             mode: mode.to_string(),
+            smart_diff: None,
         });
     }
 
+    // 智能 diff：内置规则命中时直接返回；.gitattributes 声明驱动时由 git 按驱动生成
+    let mut smart_label = None;
+    if smart_diff {
+        if let Some((text, truncated, label)) =
+            builtin_smart_diff(workspace_root, path, base, mode, algorithm)?
+        {
+            return Ok(GitDiffResult {
+                path: path.to_string(),
+                code,
+                format: "unified".to_string(),
+                text,
+                is_binary: false,
+                truncated,
+                mode: mode.to_string(),
+                smart_diff: Some(label.to_string()),
+            });
+        }
+        if gitattributes_diff_driver(workspace_root, path).is_some() {
+            smart_label = Some(SMART_DIFF_GITATTRIBUTES.to_string());
+        }
+    }
+
     // Get diff based on status
-    let (text, truncated) = if let Some(b) = base {
+    let target = if let Some(b) = base {
         // 指定 base（如 "HEAD"）：对比指定提交与工作区
         if code == "??" {
-            get_untracked_diff(workspace_root, path)?
+            Some(DiffTarget::Untracked)
         } else {
-            get_base_diff(workspace_root, path, b)?
+            Some(DiffTarget::Base(b))
         }
     } else if code == "??" {
         // Untracked file - diff against /dev/null (no staged changes for untracked)
-        if mode == "staged" {
-            (String::new(), false)
-        } else {
-            get_untracked_diff(workspace_root, path)?
-        }
+        (mode != "staged").then_some(DiffTarget::Untracked)
+    } else if mode == "staged" {
+        Some(DiffTarget::Staged)
     } else {
-        // Tracked file - normal diff
-        get_tracked_diff(workspace_root, path, mode)?
+        Some(DiffTarget::Working)
+    };
+    let (text, truncated) = match target {
+        // .gitattributes 声明的 diff 驱动只有 git 自身能执行
+        Some(target) if smart_label.is_some() => {
+            CliBackend.file_diff(workspace_root, path, target, algorithm)?
+        }
+        Some(target) => {
+            backend_for(workspace_root).file_diff(workspace_root, path, target, algorithm)?
+        }
+        None => (String::new(), false),
     };
 
     Ok(GitDiffResult {
@@ -73,26 +115,48 @@ pub fn git_diff(
         is_binary: false,
         truncated,
         mode: mode.to_string(),
+        smart_diff: smart_label,
     })
 }
 
+/// 解析本次 diff 实际使用的算法：请求参数优先，其次项目配置
+pub fn resolve_diff_algorithm(
+    workspace_root: &Path,
+    requested: Option<&str>,
+) -> Result<Option<String>, GitError> {
+    if let Some(algorithm) = normalize_diff_algorithm(requested)? {
+        return Ok(Some(algorithm));
+    }
+    let project_default = ProjectConfig::load(workspace_root)
+        .ok()
+        .and_then(|config| config.git.diff_algorithm);
+    normalize_diff_algorithm(project_default.as_deref())
+}
+
+/// 构造 `--diff-algorithm=<name>` 参数（未指定算法时为空）
+fn diff_algorithm_args(algorithm: Option<&str>) -> Vec<String> {
+    algorithm
+        .map(|name| vec![format!("--diff-algorithm={}", name)])
+        .unwrap_or_default()
+}
+
 /// Get diff for tracked file
-fn get_tracked_diff(
+pub(super) fn get_tracked_diff(
     workspace_root: &Path,
     path: &str,
     mode: &str,
+    algorithm: Option<&str>,
 ) -> Result<(String, bool), GitError> {
-    let args = if mode == "staged" {
-        vec!["diff", "--cached", "--", path]
-    } else {
-        vec!["diff", "--", path]
-    };
+    let mut args = vec!["diff".to_string()];
+    if mode == "staged" {
+        args.push("--cached".to_string());
+    }
+    args.extend(diff_algorithm_args(algorithm));
+    args.push("--".to_string());
+    args.push(path.to_string());
 
-    let output = Command::new("git")
-        .args(&args)
-        .current_dir(workspace_root)
-        .output()
-        .map_err(GitError::IoError)?;
+    let output =
+        cancel::output(git_command(workspace_root).args(&args)).map_err(GitError::IoError)?;
 
     let text = String::from_utf8_lossy(&output.stdout);
     let (text, truncated) = truncate_if_needed(&text);
@@ -101,16 +165,19 @@ fn get_tracked_diff(
 }
 
 /// Get diff between a base commit and working directory for a tracked file
-fn get_base_diff(
+pub(super) fn get_base_diff(
     workspace_root: &Path,
     path: &str,
     base: &str,
+    algorithm: Option<&str>,
 ) -> Result<(String, bool), GitError> {
-    let output = Command::new("git")
-        .args(["diff", base, "--", path])
-        .current_dir(workspace_root)
-        .output()
-        .map_err(GitError::IoError)?;
+    let output = cancel::output(
+        git_command(workspace_root)
+            .arg("diff")
+            .args(diff_algorithm_args(algorithm))
+            .args([base, "--", path]),
+    )
+    .map_err(GitError::IoError)?;
 
     let text = String::from_utf8_lossy(&output.stdout);
     let (text, truncated) = truncate_if_needed(&text);
@@ -119,12 +186,18 @@ fn get_base_diff(
 }
 
 /// Get diff for untracked file (diff against /dev/null)
-fn get_untracked_diff(workspace_root: &Path, path: &str) -> Result<(String, bool), GitError> {
-    let output = Command::new("git")
-        .args(["diff", "--no-index", "/dev/null", path])
-        .current_dir(workspace_root)
-        .output()
-        .map_err(GitError::IoError)?;
+pub(super) fn get_untracked_diff(
+    workspace_root: &Path,
+    path: &str,
+    algorithm: Option<&str>,
+) -> Result<(String, bool), GitError> {
+    let output = cancel::output(
+        git_command(workspace_root)
+            .args(["diff", "--no-index"])
+            .args(diff_algorithm_args(algorithm))
+            .args(["/dev/null", path]),
+    )
+    .map_err(GitError::IoError)?;
 
     // Note: --no-index returns exit code 1 when files differ, which is expected
     let text = String::from_utf8_lossy(&output.stdout);
@@ -157,9 +230,8 @@ pub fn git_stage(
         (vec!["add", "--", p], Some(p.to_string()))
     };
 
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(&args)
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -214,14 +286,12 @@ pub fn git_unstage(
 
     // Try git restore --staged first (Git 2.23+)
     let restore_result = if scope == "all" {
-        Command::new("git")
+        git_command(workspace_root)
             .args(["restore", "--staged", "."])
-            .current_dir(workspace_root)
             .output()
     } else {
-        Command::new("git")
+        git_command(workspace_root)
             .args(["restore", "--staged", "--", path.unwrap()])
-            .current_dir(workspace_root)
             .output()
     };
 
@@ -239,14 +309,10 @@ pub fn git_unstage(
         _ => {
             // Fallback to git reset
             let reset_output = if scope == "all" {
-                Command::new("git")
-                    .args(["reset"])
-                    .current_dir(workspace_root)
-                    .output()
+                git_command(workspace_root).args(["reset"]).output()
             } else {
-                Command::new("git")
+                git_command(workspace_root)
                     .args(["reset", "--", path.unwrap()])
-                    .current_dir(workspace_root)
                     .output()
             };
 
@@ -303,9 +369,8 @@ pub fn git_discard(
 
     if scope == "all" {
         // Discard all tracked changes
-        let output = Command::new("git")
+        let output = git_command(workspace_root)
             .args(["restore", "."])
-            .current_dir(workspace_root)
             .output()
             .map_err(GitError::IoError)?;
 
@@ -326,9 +391,8 @@ pub fn git_discard(
 
         // 如果需要同时清理未跟踪文件
         if include_untracked {
-            let clean_output = Command::new("git")
+            let clean_output = git_command(workspace_root)
                 .args(["clean", "-fd"])
-                .current_dir(workspace_root)
                 .output()
                 .map_err(GitError::IoError)?;
 
@@ -371,9 +435,8 @@ pub fn git_discard(
 
         if is_untracked {
             // Untracked file: use git clean -f to delete
-            let output = Command::new("git")
+            let output = git_command(workspace_root)
                 .args(["clean", "-f", "--", p])
-                .current_dir(workspace_root)
                 .output()
                 .map_err(GitError::IoError)?;
 
@@ -402,9 +465,8 @@ pub fn git_discard(
             }
         } else {
             // Tracked file: use git restore
-            let output = Command::new("git")
+            let output = git_command(workspace_root)
                 .args(["restore", "--", p])
-                .current_dir(workspace_root)
                 .output()
                 .map_err(GitError::IoError)?;
 
@@ -0,0 +1,236 @@
+//! porcelain / log 输出解析器的属性测试
+//!
+//! 用固定种子的 `StdRng` 生成随机输入（重命名、组合 XY 码、含空格 / 引号 / 多字节
+//! 字符 / ` -> ` 的路径、含 0x1e 的提交消息），断言信息带上种子以便复现。
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::status::parse_porcelain_status_line;
+use super::utils::{parse_conflict_entries_z, split_log_records};
+
+const CASES: u64 = 512;
+
+/// 容易让解析器误判的路径片段
+const PATH_SEGMENTS: &[&str] = &[
+    "a",
+    "src",
+    "main.rs",
+    "中文",
+    "é",
+    "😀",
+    "with space",
+    "x -> y",
+    "UU x",
+    "R  y",
+    "tab\tname",
+    "\"quoted\"",
+    "-dash",
+    ".hidden",
+    "back\\slash",
+    "rs\u{1e}sep",
+];
+
+/// porcelain v1 中出现的 X / Y 状态字符
+const STATUS_CODES: &[char] = &[' ', 'M', 'T', 'A', 'D', 'R', 'C', 'U', '?', '!'];
+
+fn pick<T: Copy>(rng: &mut StdRng, items: &[T]) -> T {
+    items[rng.gen_range(0..items.len())]
+}
+
+fn random_path(rng: &mut StdRng) -> String {
+    (0..rng.gen_range(1..=3))
+        .map(|_| pick(rng, PATH_SEGMENTS))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn random_xy(rng: &mut StdRng) -> String {
+    match rng.gen_range(0..10) {
+        0 => "??".to_string(),
+        1 => "!!".to_string(),
+        2 => pick(rng, &["UU", "AA", "DD", "AU", "UA", "DU", "UD"]).to_string(),
+        _ => format!("{}{}", pick(rng, STATUS_CODES), pick(rng, STATUS_CODES)),
+    }
+}
+
+fn is_rename_or_copy(xy: &str) -> bool {
+    xy.contains(['R', 'C'])
+}
+
+fn expected_conflict_type(xy: &str) -> Option<&'static str> {
+    match xy {
+        "UU" => Some("content"),
+        "AA" => Some("add_add"),
+        "DD" => Some("delete_delete"),
+        "AU" | "UA" => Some("add_modify"),
+        "DU" | "UD" => Some("delete_modify"),
+        _ => None,
+    }
+}
+
+fn random_bytes_lossy(rng: &mut StdRng, max_len: usize) -> String {
+    let bytes: Vec<u8> = (0..rng.gen_range(0..max_len))
+        .map(|_| {
+            if rng.gen_bool(0.5) {
+                pick(rng, b"UAD?!R \0\n>-\xe4\xb8\xad")
+            } else {
+                rng.gen()
+            }
+        })
+        .collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[test]
+fn prop_porcelain_status_line_ignores_path_content() {
+    for seed in 0..CASES {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let xy = random_xy(&mut rng);
+        let path = random_path(&mut rng);
+        let line = if is_rename_or_copy(&xy) {
+            format!("{} {} -> {}", xy, random_path(&mut rng), path)
+        } else {
+            format!("{} {}", xy, path)
+        };
+        // 状态只由 XY 决定，与路径里的空格、箭头、多字节字符无关
+        assert_eq!(
+            parse_porcelain_status_line(&line),
+            parse_porcelain_status_line(&format!("{} a", xy)),
+            "seed {}: {:?}",
+            seed,
+            line
+        );
+    }
+}
+
+#[test]
+fn porcelain_status_line_combined_codes() {
+    let cases = [
+        ("MM", Some(("M", true))),
+        ("AM", Some(("A", true))),
+        ("RM", Some(("R", true))),
+        (" R", Some(("R", false))),
+        (" T", Some(("T", false))),
+        ("UU", Some(("U", true))),
+        ("??", Some(("??", false))),
+        ("!!", None),
+        ("  ", None),
+    ];
+    for (xy, expected) in cases {
+        assert_eq!(
+            parse_porcelain_status_line(&format!("{} file.txt", xy)),
+            expected.map(|(code, staged)| (code.to_string(), staged)),
+            "{:?}",
+            xy
+        );
+    }
+}
+
+#[test]
+fn prop_porcelain_status_line_never_panics() {
+    for seed in 0..CASES {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let line = random_bytes_lossy(&mut rng, 12);
+        let _ = parse_porcelain_status_line(&line);
+    }
+    // 多字节字符落在 XY 位置时跳过，而不是在字符中间切片
+    assert_eq!(parse_porcelain_status_line("中 file"), None);
+    assert_eq!(parse_porcelain_status_line("M中file"), None);
+}
+
+#[test]
+fn prop_conflict_entries_z_stay_aligned_after_renames() {
+    for seed in 0..CASES {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut stdout = String::new();
+        let mut expected = Vec::new();
+        for _ in 0..rng.gen_range(0..8) {
+            let xy = random_xy(&mut rng);
+            let path = random_path(&mut rng);
+            stdout.push_str(&format!("{} {}\0", xy, path));
+            if is_rename_or_copy(&xy) {
+                // 原路径字段可能恰好形如 `UU x`，不能被当作一条冲突记录
+                stdout.push_str(&random_path(&mut rng));
+                stdout.push('\0');
+            }
+            if let Some(kind) = expected_conflict_type(&xy) {
+                expected.push((path, kind.to_string()));
+            }
+        }
+
+        let actual: Vec<_> = parse_conflict_entries_z(&stdout)
+            .into_iter()
+            .map(|entry| (entry.path, entry.conflict_type))
+            .collect();
+        assert_eq!(actual, expected, "seed {}: {:?}", seed, stdout);
+    }
+}
+
+#[test]
+fn prop_conflict_entries_z_never_panics() {
+    for seed in 0..CASES {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let _ = parse_conflict_entries_z(&random_bytes_lossy(&mut rng, 64));
+    }
+}
+
+/// 按 `git log --format=%H%x00%an%x00%cI%x00%B%x1e` 拼出输出（tformat 在每条记录后加换行）
+fn render_log(commits: &[(String, String, String)]) -> String {
+    commits
+        .iter()
+        .map(|(sha, author, message)| {
+            format!(
+                "{}\0{}\0{}\0{}\u{1e}\n",
+                sha, author, "2024-01-01T00:00:00Z", message
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn prop_log_records_survive_separator_in_message() {
+    for seed in 0..CASES {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let commits: Vec<(String, String, String)> = (0..rng.gen_range(0..6))
+            .map(|_| {
+                let sha: String = (0..40)
+                    .map(|_| pick(&mut rng, b"0123456789abcdef") as char)
+                    .collect();
+                let author = pick(&mut rng, &["Ann", "李雷", "a b", "x\u{1e}y"]).to_string();
+                let message: String = (0..rng.gen_range(0..5))
+                    .map(|_| {
+                        pick(
+                            &mut rng,
+                            &["fix", "中文", "\u{1e}", "\n", "\n\n", "a\u{1e}b", " ", "😀"],
+                        )
+                    })
+                    .collect();
+                (sha, author, message)
+            })
+            .collect();
+
+        let records = split_log_records(&render_log(&commits), '\0');
+        let parsed: Vec<(String, String, String)> = records
+            .iter()
+            .map(|record| {
+                let mut fields = record.splitn(4, '\0');
+                let sha = fields.next().unwrap_or_default().to_string();
+                let author = fields.next().unwrap_or_default().to_string();
+                let _date = fields.next();
+                let message = fields.next().unwrap_or_default().to_string();
+                (sha, author, message)
+            })
+            .collect();
+        assert_eq!(parsed, commits, "seed {}", seed);
+    }
+}
+
+#[test]
+fn log_records_drop_leading_garbage() {
+    let sha = "0123456789abcdef0123456789abcdef01234567";
+    let stdout = format!("stray\u{1e}\n{}\0Ann\0date\0msg\n\u{1e}\n", sha);
+    let records = split_log_records(&stdout, '\0');
+    assert_eq!(records, [format!("{}\0Ann\0date\0msg\n", sha)]);
+    assert!(split_log_records("", '\0').is_empty());
+}
@@ -0,0 +1,237 @@
+//! 长耗时 git 操作的进度与取消
+//!
+//! fetch、rebase、克隆等操作开始时以 [`begin_operation`] 登记 `op_id`，并在以该 id 为键的
+//! 取消作用域（[`crate::util::cancel::scope`]）中执行。子进程经 [`run_git_streaming`] 启动：
+//! stderr 进度行逐行解析后回调；客户端发送 `git_op_cancel { op_id }` 时先向子进程所在进程组
+//! 发送 SIGTERM（git 借此删除锁文件），宽限期后仍未退出则 SIGKILL，随后由各操作完成清理。
+
+use std::collections::HashMap;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::{debug, warn};
+
+use super::utils::GitError;
+use crate::util::cancel::{self, CancelToken};
+use crate::workspace::project::{stream_git_progress, CloneProgress};
+
+/// 轮询取消标记的间隔
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// SIGTERM 后等待进程组退出的时间
+const TERMINATE_GRACE: Duration = Duration::from_secs(3);
+
+/// 进行中的 git 操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitOperationInfo {
+    /// 操作名：`fetch` / `rebase` / `clone`
+    pub op: String,
+    pub project: String,
+    pub workspace: String,
+}
+
+static ACTIVE_OPERATIONS: LazyLock<Mutex<HashMap<String, GitOperationInfo>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 操作结束（或其 future 被丢弃）时注销登记
+pub struct GitOperationGuard {
+    op_id: String,
+}
+
+impl GitOperationGuard {
+    pub fn op_id(&self) -> &str {
+        &self.op_id
+    }
+}
+
+impl Drop for GitOperationGuard {
+    fn drop(&mut self) {
+        ACTIVE_OPERATIONS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.op_id);
+    }
+}
+
+/// 登记一个 git 操作并分配 `op_id`
+pub fn begin_operation(op: &str, project: &str, workspace: &str) -> GitOperationGuard {
+    let op_id = uuid::Uuid::new_v4().to_string();
+    ACTIVE_OPERATIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(
+            op_id.clone(),
+            GitOperationInfo {
+                op: op.to_string(),
+                project: project.to_string(),
+                workspace: workspace.to_string(),
+            },
+        );
+    GitOperationGuard { op_id }
+}
+
+/// 取消进行中的 git 操作，返回是否找到该操作
+pub fn cancel_operation(op_id: &str) -> bool {
+    let info = ACTIVE_OPERATIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(op_id)
+        .cloned();
+    match info {
+        Some(info) => {
+            debug!(op_id, op = %info.op, project = %info.project, "Cancelling git operation");
+            cancel::cancel(op_id)
+        }
+        None => false,
+    }
+}
+
+/// 启动 git 子进程并流式解析 stderr 进度，返回退出状态与 stderr 中的完整行
+///
+/// 当前请求（或 `op_id` 作用域）被取消时终止子进程所在进程组，返回
+/// [`cancel::cancelled_error`]；调用方据此清理残留状态（如未完成的 rebase、克隆目录）。
+pub fn run_git_streaming(
+    cmd: &mut Command,
+    on_progress: &mut dyn FnMut(CloneProgress),
+) -> Result<(ExitStatus, String), GitError> {
+    let token = cancel::current();
+    if token.as_ref().is_some_and(CancelToken::is_cancelled) {
+        return Err(GitError::IoError(cancel::cancelled_error()));
+    }
+
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    // 独立进程组：取消时连同 git 派生的 remote helper / index-pack 一起结束
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(cmd, 0);
+    let mut child = cmd.spawn().map_err(GitError::IoError)?;
+
+    let done = Arc::new(AtomicBool::new(false));
+    let watcher = token.clone().map(|token| {
+        let done = done.clone();
+        let pid = child.id();
+        std::thread::spawn(move || watch_cancel(&token, pid, &done))
+    });
+
+    let stderr_text = child
+        .stderr
+        .take()
+        .map(|stderr| stream_git_progress(stderr, &mut |progress| on_progress(progress)))
+        .unwrap_or_default();
+    let status = child.wait().map_err(GitError::IoError);
+    done.store(true, Ordering::SeqCst);
+    if let Some(watcher) = watcher {
+        let _ = watcher.join();
+    }
+    if token.as_ref().is_some_and(CancelToken::is_cancelled) {
+        return Err(GitError::IoError(cancel::cancelled_error()));
+    }
+    Ok((status?, stderr_text))
+}
+
+/// 去掉 stderr 中的进度行与清行序列，只保留可作为错误信息的内容
+pub fn strip_progress_lines(stderr: &str) -> String {
+    stderr
+        .lines()
+        .map(|line| line.trim_start_matches("\u{1b}[K").trim())
+        .filter(|line| {
+            !line.is_empty()
+                && !line.starts_with("remote:")
+                && !line.contains('%')
+                && !line.ends_with(", done.")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 子进程运行期间轮询取消标记，取消时终止其进程组
+fn watch_cancel(token: &CancelToken, pid: u32, done: &AtomicBool) {
+    while !done.load(Ordering::SeqCst) {
+        if token.is_cancelled() {
+            terminate_group(pid, done);
+            return;
+        }
+        std::thread::sleep(CANCEL_POLL_INTERVAL);
+    }
+}
+
+fn terminate_group(pid: u32, done: &AtomicBool) {
+    let Ok(pid) = i32::try_from(pid) else {
+        return;
+    };
+    // 进程以自身 pid 为进程组 id 启动
+    if unsafe { libc::kill(-pid, libc::SIGTERM) } != 0 {
+        warn!(
+            pid,
+            error = %std::io::Error::last_os_error(),
+            "Failed to terminate git process group"
+        );
+        return;
+    }
+    let deadline = Instant::now() + TERMINATE_GRACE;
+    while Instant::now() < deadline {
+        if done.load(Ordering::SeqCst) {
+            return;
+        }
+        std::thread::sleep(CANCEL_POLL_INTERVAL);
+    }
+    unsafe {
+        libc::kill(-pid, libc::SIGKILL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operations_are_unregistered_when_guard_drops() {
+        let guard = begin_operation("fetch", "demo", "default");
+        let op_id = guard.op_id().to_string();
+        assert!(ACTIVE_OPERATIONS.lock().unwrap().contains_key(&op_id));
+        drop(guard);
+        assert!(!cancel_operation(&op_id));
+    }
+
+    #[test]
+    fn strip_progress_lines_keeps_errors_only() {
+        let stderr = "remote: Enumerating objects: 5, done.\nReceiving objects: 100% (5/5), done.\n\u{1b}[Kfatal: couldn't find remote ref nope\n";
+        assert_eq!(
+            strip_progress_lines(stderr),
+            "fatal: couldn't find remote ref nope"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn cancel_operation_kills_streaming_process() {
+        let guard = begin_operation("fetch", "demo", "default");
+        let op_id = guard.op_id().to_string();
+        let started = Instant::now();
+        let result = cancel::scope(op_id.clone(), async {
+            let work = crate::util::trace::spawn_blocking(|| {
+                let mut lines = Vec::new();
+                let result = run_git_streaming(
+                    Command::new("sh").args([
+                        "-c",
+                        "printf 'Receiving objects:  10%% (1/10)\\r' >&2; sleep 5",
+                    ]),
+                    &mut |progress| lines.push(progress),
+                );
+                (result, lines)
+            });
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            assert!(cancel_operation(&op_id));
+            work.await.unwrap()
+        })
+        .await;
+
+        let (result, lines) = result;
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(matches!(result, Err(GitError::IoError(ref e)) if cancel::is_cancelled_error(e)));
+        assert_eq!(lines[0].phase, "receiving_objects");
+        assert_eq!(lines[0].percent, Some(10));
+    }
+}
@@ -0,0 +1,849 @@
+//! Pull Request / Merge Request：创建与状态查询
+//!
+//! 从 `origin` 的远端地址识别托管平台（GitHub / GitLab；自建实例的主机名无法识别时用
+//! `.tidyflow.toml` 的 `[git] hosting` 指定），推送当前分支后调用平台 REST API 创建 PR，
+//! 并查询 PR 的可合并性与 CI 检查结果。
+//! 访问令牌来自客户端设置 `git_hosting_tokens`（按主机名），不会回传给客户端。
+
+use std::path::Path;
+use std::time::Duration;
+
+use futures::StreamExt;
+use serde_json::{json, Value};
+
+use super::network::{apply_git_proxy, ensure_online};
+use super::utils::{get_git_repo_root, GitError};
+use crate::util::exec_env::git_command;
+use crate::workspace::config::ProjectConfig;
+
+/// 未配置访问令牌的错误码
+pub const HOSTING_TOKEN_MISSING_CODE: &str = "hosting_token_missing";
+
+/// REST 请求超时
+const API_TIMEOUT: Duration = Duration::from_secs(30);
+/// 列表中并发查询 PR 详情的上限
+const PULL_REQUEST_STATUS_CONCURRENCY: usize = 4;
+
+/// 代码托管平台
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostingProvider {
+    GitHub,
+    GitLab,
+}
+
+impl HostingProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HostingProvider::GitHub => "github",
+            HostingProvider::GitLab => "gitlab",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "github" => Some(Self::GitHub),
+            "gitlab" => Some(Self::GitLab),
+            _ => None,
+        }
+    }
+
+    /// 按主机名推断平台
+    fn detect(host: &str) -> Option<Self> {
+        let host = host.to_ascii_lowercase();
+        if host.contains("github") {
+            Some(Self::GitHub)
+        } else if host.contains("gitlab") {
+            Some(Self::GitLab)
+        } else {
+            None
+        }
+    }
+}
+
+/// 远端仓库在托管平台上的位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostedRepo {
+    pub provider: HostingProvider,
+    /// 主机名（https 地址带非默认端口时含端口），同时作为令牌的键
+    pub host: String,
+    /// 仓库路径，如 `owner/repo`、`group/subgroup/repo`
+    pub path: String,
+}
+
+impl HostedRepo {
+    /// 解析 `https://host/owner/repo.git`、`ssh://git@host:22/owner/repo.git`、
+    /// `git@host:owner/repo.git`；`provider` 为 None 时按主机名推断
+    pub fn from_remote_url(url: &str, provider: Option<HostingProvider>) -> Option<Self> {
+        let url = url.trim();
+        let (host, path) = if url.contains("://") {
+            let parsed = url::Url::parse(url).ok()?;
+            let host = parsed.host_str()?.to_string();
+            let host = match (parsed.scheme(), parsed.port()) {
+                ("http" | "https", Some(port)) => format!("{}:{}", host, port),
+                _ => host,
+            };
+            (host, parsed.path().to_string())
+        } else {
+            // scp 风格：[user@]host:path
+            let (authority, path) = url.split_once(':')?;
+            let host = authority.rsplit('@').next()?.to_string();
+            (host, path.to_string())
+        };
+        let path = path.trim_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path).trim_matches('/');
+        if host.is_empty() || !path.contains('/') {
+            return None;
+        }
+        let provider = provider.or_else(|| HostingProvider::detect(&host))?;
+        Some(Self {
+            provider,
+            host,
+            path: path.to_string(),
+        })
+    }
+
+    /// issue 网页地址
+    pub fn issue_url(&self, number: u64) -> String {
+        match self.provider {
+            HostingProvider::GitHub => {
+                format!("https://{}/{}/issues/{}", self.host, self.path, number)
+            }
+            HostingProvider::GitLab => {
+                format!("https://{}/{}/-/issues/{}", self.host, self.path, number)
+            }
+        }
+    }
+
+    /// REST API 根地址
+    pub fn api_base(&self) -> String {
+        match self.provider {
+            HostingProvider::GitHub if self.host == "github.com" => {
+                "https://api.github.com".to_string()
+            }
+            HostingProvider::GitHub => format!("https://{}/api/v3", self.host),
+            HostingProvider::GitLab => format!("https://{}/api/v4", self.host),
+        }
+    }
+}
+
+/// 创建 PR 的参数
+#[derive(Debug, Clone)]
+pub struct PullRequestParams<'a> {
+    pub title: &'a str,
+    pub body: &'a str,
+    /// 源分支
+    pub head: &'a str,
+    /// 目标分支
+    pub base: &'a str,
+    pub draft: bool,
+}
+
+/// 创建成功的 PR
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreatedPullRequest {
+    pub number: u64,
+    pub url: String,
+}
+
+/// 单项 CI 检查的结论
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckState {
+    Pending,
+    Success,
+    Failure,
+    /// 跳过、取消或允许失败，不影响整体结论
+    Neutral,
+}
+
+impl CheckState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CheckState::Pending => "pending",
+            CheckState::Success => "success",
+            CheckState::Failure => "failure",
+            CheckState::Neutral => "neutral",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PullRequestCheck {
+    pub name: String,
+    pub state: CheckState,
+    pub url: Option<String>,
+}
+
+/// PR 的状态、可合并性与 CI 检查
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PullRequestStatus {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    pub branch: String,
+    pub target_branch: String,
+    pub head_sha: Option<String>,
+    pub draft: bool,
+    /// `open` / `closed` / `merged`
+    pub state: String,
+    /// None 表示平台尚未计算完成
+    pub mergeable: Option<bool>,
+    /// 平台原始的合并状态（GitHub `mergeable_state`，GitLab `detailed_merge_status`）
+    pub merge_state: Option<String>,
+    pub checks: Vec<PullRequestCheck>,
+}
+
+impl PullRequestStatus {
+    /// 汇总结论：无检查为 `none`，任一失败为 `failure`，仍有进行中为 `pending`，否则 `success`
+    pub fn checks_state(&self) -> &'static str {
+        if self.checks.is_empty() {
+            "none"
+        } else if self.checks.iter().any(|c| c.state == CheckState::Failure) {
+            "failure"
+        } else if self.checks.iter().any(|c| c.state == CheckState::Pending) {
+            "pending"
+        } else {
+            "success"
+        }
+    }
+}
+
+/// 按主机名查找访问令牌
+pub fn hosting_token(
+    tokens: &std::collections::HashMap<String, String>,
+    host: &str,
+) -> Option<String> {
+    tokens.get(&host.to_ascii_lowercase()).cloned()
+}
+
+/// 读取 `origin` 地址并识别托管平台
+pub fn hosted_repo(workspace_root: &Path) -> Result<HostedRepo, GitError> {
+    if get_git_repo_root(workspace_root).is_none() {
+        return Err(GitError::NotAGitRepo);
+    }
+    let output = git_command(workspace_root)
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        return Err(GitError::CommandFailed(
+            "Remote 'origin' is not configured".to_string(),
+        ));
+    }
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let configured = ProjectConfig::load(workspace_root)
+        .ok()
+        .and_then(|config| config.git.hosting);
+    let provider = match configured
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+    {
+        Some(value) => Some(HostingProvider::parse(value).ok_or_else(|| {
+            GitError::CommandFailed(format!(
+                "Unsupported [git] hosting: {} (expected github or gitlab)",
+                value
+            ))
+        })?),
+        None => None,
+    };
+    HostedRepo::from_remote_url(&url, provider).ok_or_else(|| {
+        GitError::CommandFailed(format!(
+            "Cannot determine GitHub/GitLab repository from remote '{}' (set [git] hosting)",
+            url
+        ))
+    })
+}
+
+/// 推送分支到 `origin` 并设置 upstream
+pub fn push_branch(workspace_root: &Path, branch: &str) -> Result<(), GitError> {
+    ensure_online("push")?;
+    let mut cmd = git_command(workspace_root);
+    apply_git_proxy(&mut cmd, Some(workspace_root));
+    let output = cmd
+        .args(["push", "-u", "origin", branch])
+        .output()
+        .map_err(GitError::IoError)?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        Err(GitError::CommandFailed(if stderr.is_empty() {
+            format!("Failed to push {}", branch)
+        } else {
+            stderr
+        }))
+    }
+}
+
+/// 调用平台 API 创建 PR；失败时返回平台给出的错误信息
+pub async fn create_pull_request(
+    repo: &HostedRepo,
+    token: &str,
+    params: &PullRequestParams<'_>,
+) -> Result<CreatedPullRequest, String> {
+    let (url, body) = request_spec(repo, params);
+    let client = api_client()?;
+    let payload = send_api(repo, token, client.post(&url).json(&body)).await?;
+    parse_created(repo.provider, &payload)
+        .ok_or_else(|| "Unexpected response from hosting API".to_string())
+}
+
+/// 列出源分支在 `branches` 中的打开状态 PR（不含 fork 发起的 PR），附带可合并性与 CI 检查
+pub async fn list_pull_requests(
+    repo: &HostedRepo,
+    token: &str,
+    branches: &[String],
+) -> Result<Vec<PullRequestStatus>, String> {
+    let client = api_client()?;
+    let url = match repo.provider {
+        HostingProvider::GitHub => format!(
+            "{}/repos/{}/pulls?state=open&per_page=100",
+            repo.api_base(),
+            repo.path
+        ),
+        HostingProvider::GitLab => format!(
+            "{}/projects/{}/merge_requests?state=opened&per_page=100",
+            repo.api_base(),
+            encoded_project(repo)
+        ),
+    };
+    let payload = send_api(repo, token, client.get(&url)).await?;
+    let numbers: Vec<u64> = payload
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter(|item| is_same_repo_pull(repo, item))
+                .filter_map(|item| {
+                    let (branch, number) = match repo.provider {
+                        HostingProvider::GitHub => (
+                            item.pointer("/head/ref")?.as_str()?,
+                            item.get("number")?.as_u64()?,
+                        ),
+                        HostingProvider::GitLab => (
+                            item.get("source_branch")?.as_str()?,
+                            item.get("iid")?.as_u64()?,
+                        ),
+                    };
+                    branches.iter().any(|b| b == branch).then_some(number)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    futures::stream::iter(numbers)
+        .map(|number| pull_request_status_with(&client, repo, token, number))
+        .buffered(PULL_REQUEST_STATUS_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect()
+}
+
+/// 查询单个 PR 的状态、可合并性与 CI 检查
+pub async fn pull_request_status(
+    repo: &HostedRepo,
+    token: &str,
+    number: u64,
+) -> Result<PullRequestStatus, String> {
+    pull_request_status_with(&api_client()?, repo, token, number).await
+}
+
+async fn pull_request_status_with(
+    client: &reqwest::Client,
+    repo: &HostedRepo,
+    token: &str,
+    number: u64,
+) -> Result<PullRequestStatus, String> {
+    let base = repo.api_base();
+    match repo.provider {
+        HostingProvider::GitHub => {
+            let url = format!("{}/repos/{}/pulls/{}", base, repo.path, number);
+            let payload = send_api(repo, token, client.get(&url)).await?;
+            let mut status = parse_github_pull(&payload)
+                .ok_or_else(|| "Unexpected response from hosting API".to_string())?;
+            if let Some(sha) = status.head_sha.clone() {
+                let commit = format!("{}/repos/{}/commits/{}", base, repo.path, sha);
+                let runs_url = format!("{}/check-runs?per_page=100", commit);
+                let statuses_url = format!("{}/status", commit);
+                let (runs, statuses) = futures::join!(
+                    send_api(repo, token, client.get(&runs_url)),
+                    send_api(repo, token, client.get(&statuses_url)),
+                );
+                status.checks = github_checks(&runs?, &statuses?);
+            }
+            Ok(status)
+        }
+        HostingProvider::GitLab => {
+            let project = encoded_project(repo);
+            let url = format!("{}/projects/{}/merge_requests/{}", base, project, number);
+            let payload = send_api(repo, token, client.get(&url)).await?;
+            let mut status = parse_gitlab_merge_request(&payload)
+                .ok_or_else(|| "Unexpected response from hosting API".to_string())?;
+            if let Some(pipeline) = payload.get("head_pipeline").filter(|p| p.is_object()) {
+                let jobs = match pipeline.get("id").and_then(Value::as_u64) {
+                    Some(id) => {
+                        let url = format!(
+                            "{}/projects/{}/pipelines/{}/jobs?per_page=100",
+                            base, project, id
+                        );
+                        send_api(repo, token, client.get(&url)).await?
+                    }
+                    None => Value::Null,
+                };
+                status.checks = gitlab_checks(pipeline, &jobs);
+            }
+            Ok(status)
+        }
+    }
+}
+
+fn api_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(API_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// 附加平台鉴权头并发送请求；非 2xx 时返回平台错误信息
+async fn send_api(
+    repo: &HostedRepo,
+    token: &str,
+    request: reqwest::RequestBuilder,
+) -> Result<Value, String> {
+    let request = request.header("User-Agent", "tidyflow");
+    let request = match repo.provider {
+        HostingProvider::GitHub => request
+            .bearer_auth(token)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28"),
+        HostingProvider::GitLab => request.header("PRIVATE-TOKEN", token),
+    };
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", repo.host, e))?;
+    let status = response.status();
+    let payload: Value = response.json().await.unwrap_or(Value::Null);
+    if !status.is_success() {
+        return Err(format!(
+            "{} ({})",
+            api_error_message(&payload),
+            status.as_u16()
+        ));
+    }
+    Ok(payload)
+}
+
+fn encoded_project(repo: &HostedRepo) -> String {
+    url::form_urlencoded::byte_serialize(repo.path.as_bytes()).collect()
+}
+
+/// 排除 fork 仓库发起的 PR：其源分支名与本地工作空间无关
+fn is_same_repo_pull(repo: &HostedRepo, item: &Value) -> bool {
+    match repo.provider {
+        HostingProvider::GitHub => item
+            .pointer("/head/repo/full_name")
+            .and_then(Value::as_str)
+            .map_or(true, |name| name.eq_ignore_ascii_case(&repo.path)),
+        HostingProvider::GitLab => item.get("source_project_id") == item.get("target_project_id"),
+    }
+}
+
+fn parse_github_pull(payload: &Value) -> Option<PullRequestStatus> {
+    let merged = payload.get("merged").and_then(Value::as_bool) == Some(true)
+        || payload.get("merged_at").is_some_and(|v| !v.is_null());
+    let state = if merged {
+        "merged".to_string()
+    } else {
+        payload.get("state")?.as_str()?.to_string()
+    };
+    Some(PullRequestStatus {
+        number: payload.get("number")?.as_u64()?,
+        title: payload.get("title")?.as_str()?.to_string(),
+        url: payload.get("html_url")?.as_str()?.to_string(),
+        branch: payload.pointer("/head/ref")?.as_str()?.to_string(),
+        target_branch: payload.pointer("/base/ref")?.as_str()?.to_string(),
+        head_sha: payload
+            .pointer("/head/sha")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        draft: payload
+            .get("draft")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        state,
+        mergeable: payload.get("mergeable").and_then(Value::as_bool),
+        merge_state: payload
+            .get("mergeable_state")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        checks: Vec::new(),
+    })
+}
+
+fn parse_gitlab_merge_request(payload: &Value) -> Option<PullRequestStatus> {
+    let merge_state = payload
+        .get("detailed_merge_status")
+        .or_else(|| payload.get("merge_status"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let mergeable = if payload.get("has_conflicts").and_then(Value::as_bool) == Some(true) {
+        Some(false)
+    } else {
+        match merge_state.as_deref() {
+            Some("mergeable" | "can_be_merged") => Some(true),
+            Some("checking" | "unchecked" | "preparing" | "approvals_syncing") | None => None,
+            Some(_) => Some(false),
+        }
+    };
+    let state = match payload.get("state")?.as_str()? {
+        "opened" => "open",
+        "merged" => "merged",
+        _ => "closed",
+    };
+    Some(PullRequestStatus {
+        number: payload.get("iid")?.as_u64()?,
+        title: payload.get("title")?.as_str()?.to_string(),
+        url: payload.get("web_url")?.as_str()?.to_string(),
+        branch: payload.get("source_branch")?.as_str()?.to_string(),
+        target_branch: payload.get("target_branch")?.as_str()?.to_string(),
+        head_sha: payload
+            .get("sha")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        draft: payload
+            .get("draft")
+            .or_else(|| payload.get("work_in_progress"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        state: state.to_string(),
+        mergeable,
+        merge_state,
+        checks: Vec::new(),
+    })
+}
+
+/// GitHub：check runs 与旧式 commit status 合并为一个检查列表
+fn github_checks(runs: &Value, statuses: &Value) -> Vec<PullRequestCheck> {
+    let mut checks: Vec<PullRequestCheck> = runs
+        .get("check_runs")
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|run| {
+                    let state = if run.get("status").and_then(Value::as_str) != Some("completed") {
+                        CheckState::Pending
+                    } else {
+                        match run.get("conclusion").and_then(Value::as_str) {
+                            Some("success") => CheckState::Success,
+                            Some("neutral" | "skipped" | "stale") => CheckState::Neutral,
+                            _ => CheckState::Failure,
+                        }
+                    };
+                    Some(PullRequestCheck {
+                        name: run.get("name")?.as_str()?.to_string(),
+                        state,
+                        url: run
+                            .get("html_url")
+                            .and_then(Value::as_str)
+                            .map(str::to_string),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some(items) = statuses.get("statuses").and_then(Value::as_array) {
+        checks.extend(items.iter().filter_map(|status| {
+            let state = match status.get("state")?.as_str()? {
+                "success" => CheckState::Success,
+                "pending" => CheckState::Pending,
+                _ => CheckState::Failure,
+            };
+            Some(PullRequestCheck {
+                name: status.get("context")?.as_str()?.to_string(),
+                state,
+                url: status
+                    .get("target_url")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+            })
+        }));
+    }
+    checks
+}
+
+/// GitLab：流水线各作业为检查项；无法读取作业时以流水线整体作为单个检查
+fn gitlab_checks(pipeline: &Value, jobs: &Value) -> Vec<PullRequestCheck> {
+    let state_of = |item: &Value| match item.get("status").and_then(Value::as_str) {
+        Some("success") => CheckState::Success,
+        Some("failed") if item.get("allow_failure").and_then(Value::as_bool) == Some(true) => {
+            CheckState::Neutral
+        }
+        Some("failed") => CheckState::Failure,
+        Some("canceled" | "skipped" | "manual") => CheckState::Neutral,
+        _ => CheckState::Pending,
+    };
+    let checks: Vec<PullRequestCheck> = jobs
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|job| {
+                    Some(PullRequestCheck {
+                        name: job.get("name")?.as_str()?.to_string(),
+                        state: state_of(job),
+                        url: job
+                            .get("web_url")
+                            .and_then(Value::as_str)
+                            .map(str::to_string),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if !checks.is_empty() {
+        return checks;
+    }
+    vec![PullRequestCheck {
+        name: "pipeline".to_string(),
+        state: state_of(pipeline),
+        url: pipeline
+            .get("web_url")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    }]
+}
+
+/// 请求地址与 JSON 请求体
+fn request_spec(repo: &HostedRepo, params: &PullRequestParams<'_>) -> (String, Value) {
+    match repo.provider {
+        HostingProvider::GitHub => (
+            format!("{}/repos/{}/pulls", repo.api_base(), repo.path),
+            json!({
+                "title": params.title,
+                "body": params.body,
+                "head": params.head,
+                "base": params.base,
+                "draft": params.draft,
+            }),
+        ),
+        HostingProvider::GitLab => {
+            let project = encoded_project(repo);
+            // GitLab 以标题前缀标记草稿，兼容不支持 `draft` 参数的旧版本
+            let title = if params.draft {
+                format!("Draft: {}", params.title)
+            } else {
+                params.title.to_string()
+            };
+            (
+                format!("{}/projects/{}/merge_requests", repo.api_base(), project),
+                json!({
+                    "title": title,
+                    "description": params.body,
+                    "source_branch": params.head,
+                    "target_branch": params.base,
+                }),
+            )
+        }
+    }
+}
+
+fn parse_created(provider: HostingProvider, payload: &Value) -> Option<CreatedPullRequest> {
+    let (number, url) = match provider {
+        HostingProvider::GitHub => ("number", "html_url"),
+        HostingProvider::GitLab => ("iid", "web_url"),
+    };
+    Some(CreatedPullRequest {
+        number: payload.get(number)?.as_u64()?,
+        url: payload.get(url)?.as_str()?.to_string(),
+    })
+}
+
+/// 提取错误信息：GitHub 为 `message` + `errors[].message`，GitLab 的 `message` 可能是数组
+fn api_error_message(payload: &Value) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    match payload.get("message") {
+        Some(Value::String(message)) => parts.push(message.clone()),
+        Some(Value::Array(items)) => parts.extend(
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(str::to_string)),
+        ),
+        _ => {}
+    }
+    if let Some(errors) = payload.get("errors").and_then(Value::as_array) {
+        parts.extend(
+            errors
+                .iter()
+                .filter_map(|e| e.get("message").and_then(Value::as_str).map(str::to_string)),
+        );
+    }
+    if parts.is_empty() {
+        "Hosting API request failed".to_string()
+    } else {
+        parts.join(": ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_remote_urls_and_detects_provider() {
+        let expected = HostedRepo {
+            provider: HostingProvider::GitHub,
+            host: "github.com".to_string(),
+            path: "owner/repo".to_string(),
+        };
+        for url in [
+            "https://github.com/owner/repo.git",
+            "https://user@github.com/owner/repo/",
+            "git@github.com:owner/repo.git",
+            "ssh://git@github.com:22/owner/repo.git",
+        ] {
+            assert_eq!(
+                HostedRepo::from_remote_url(url, None),
+                Some(expected.clone()),
+                "{url}"
+            );
+        }
+
+        let gitlab =
+            HostedRepo::from_remote_url("https://gitlab.example.com:8443/group/sub/app.git", None)
+                .unwrap();
+        assert_eq!(gitlab.provider, HostingProvider::GitLab);
+        assert_eq!(gitlab.host, "gitlab.example.com:8443");
+        assert_eq!(gitlab.api_base(), "https://gitlab.example.com:8443/api/v4");
+
+        // 无法识别的主机需显式指定平台
+        assert_eq!(
+            HostedRepo::from_remote_url("git@git.corp:team/app.git", None),
+            None
+        );
+        let ghe =
+            HostedRepo::from_remote_url("git@git.corp:team/app.git", Some(HostingProvider::GitHub))
+                .unwrap();
+        assert_eq!(ghe.api_base(), "https://git.corp/api/v3");
+        assert_eq!(HostedRepo::from_remote_url("/tmp/origin.git", None), None);
+    }
+
+    #[test]
+    fn builds_provider_specific_requests() {
+        let params = PullRequestParams {
+            title: "Add feature",
+            body: "details",
+            head: "feat/x",
+            base: "main",
+            draft: true,
+        };
+        let github = HostedRepo::from_remote_url("git@github.com:o/r.git", None).unwrap();
+        let (url, body) = request_spec(&github, &params);
+        assert_eq!(url, "https://api.github.com/repos/o/r/pulls");
+        assert_eq!(body["head"], "feat/x");
+        assert_eq!(body["draft"], true);
+
+        let gitlab = HostedRepo::from_remote_url("git@gitlab.com:g/s/r.git", None).unwrap();
+        let (url, body) = request_spec(&gitlab, &params);
+        assert_eq!(
+            url,
+            "https://gitlab.com/api/v4/projects/g%2Fs%2Fr/merge_requests"
+        );
+        assert_eq!(body["title"], "Draft: Add feature");
+        assert_eq!(body["source_branch"], "feat/x");
+
+        assert_eq!(
+            parse_created(
+                HostingProvider::GitLab,
+                &json!({"iid": 7, "web_url": "https://gitlab.com/g/s/r/-/merge_requests/7"})
+            ),
+            Some(CreatedPullRequest {
+                number: 7,
+                url: "https://gitlab.com/g/s/r/-/merge_requests/7".to_string(),
+            })
+        );
+        assert_eq!(
+            api_error_message(&json!({
+                "message": "Validation Failed",
+                "errors": [{"message": "A pull request already exists for o:feat/x."}]
+            })),
+            "Validation Failed: A pull request already exists for o:feat/x."
+        );
+        assert_eq!(
+            api_error_message(&json!({"message": ["Another open merge request already exists"]})),
+            "Another open merge request already exists"
+        );
+    }
+
+    #[test]
+    fn parses_pull_request_status_and_checks() {
+        let pull = json!({
+            "number": 12,
+            "title": "Add feature",
+            "html_url": "https://github.com/o/r/pull/12",
+            "state": "open",
+            "draft": false,
+            "merged": false,
+            "mergeable": null,
+            "mergeable_state": "unknown",
+            "head": {"ref": "feat/x", "sha": "abc"},
+            "base": {"ref": "main"}
+        });
+        let mut status = parse_github_pull(&pull).unwrap();
+        assert_eq!(status.branch, "feat/x");
+        assert_eq!(status.head_sha.as_deref(), Some("abc"));
+        assert_eq!(status.mergeable, None);
+        assert_eq!(status.checks_state(), "none");
+
+        status.checks = github_checks(
+            &json!({"check_runs": [
+                {"name": "build", "status": "completed", "conclusion": "success"},
+                {"name": "lint", "status": "completed", "conclusion": "skipped"},
+                {"name": "test", "status": "in_progress", "conclusion": null}
+            ]}),
+            &json!({"statuses": [{"context": "ci/legacy", "state": "success"}]}),
+        );
+        assert_eq!(status.checks.len(), 4);
+        assert_eq!(status.checks[1].state, CheckState::Neutral);
+        assert_eq!(status.checks_state(), "pending");
+        status.checks[2].state = CheckState::Failure;
+        assert_eq!(status.checks_state(), "failure");
+        status.checks.remove(2);
+        assert_eq!(status.checks_state(), "success");
+
+        let mr = json!({
+            "iid": 7,
+            "title": "Draft: Add feature",
+            "web_url": "https://gitlab.com/g/r/-/merge_requests/7",
+            "state": "opened",
+            "draft": true,
+            "source_branch": "feat/x",
+            "target_branch": "main",
+            "sha": "def",
+            "has_conflicts": false,
+            "detailed_merge_status": "mergeable"
+        });
+        let status = parse_gitlab_merge_request(&mr).unwrap();
+        assert_eq!(status.state, "open");
+        assert_eq!(status.mergeable, Some(true));
+        assert!(status.draft);
+
+        let pipeline = json!({"id": 3, "status": "running", "web_url": "https://gitlab.com/p/3"});
+        let checks = gitlab_checks(
+            &pipeline,
+            &json!([
+                {"name": "build", "status": "success"},
+                {"name": "flaky", "status": "failed", "allow_failure": true}
+            ]),
+        );
+        assert_eq!(
+            checks.iter().map(|c| c.state).collect::<Vec<_>>(),
+            vec![CheckState::Success, CheckState::Neutral]
+        );
+        let checks = gitlab_checks(&pipeline, &Value::Null);
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].state, CheckState::Pending);
+    }
+}
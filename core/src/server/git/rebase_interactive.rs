@@ -0,0 +1,510 @@
+//! 交互式 rebase：规划与执行
+//!
+//! `git_rebase_interactive_plan` 列出 `base..HEAD` 上待整理的提交；
+//! `git_rebase_interactive_execute` 按客户端给出的动作列表生成 todo，
+//! 通过 `GIT_SEQUENCE_EDITOR` 替换 git 自动生成的 todo 后执行 `git rebase -i`。
+//!
+//! 新提交信息不走编辑器：reword / 带消息的 squash 在 todo 中追加
+//! `exec git commit --amend -F <file>`，消息文件存放在 git 目录下，
+//! 因此冲突暂停后通过 `git_rebase_continue` 继续时同样生效。
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use super::cache::invalidate_git_status_cache;
+use super::utils::*;
+use crate::util::exec_env::git_command;
+
+/// 消息文件与 todo 所在目录（位于 git 目录下）
+const REBASE_FILES_DIR: &str = "tidyflow-rebase";
+
+/// 待整理的单个提交
+#[derive(Debug, Clone)]
+pub struct RebasePlanCommit {
+    pub sha: String,
+    pub short_sha: String,
+    /// 提交信息首行
+    pub subject: String,
+    /// 完整提交信息
+    pub message: String,
+    pub author: String,
+    /// ISO 日期
+    pub date: String,
+}
+
+/// 交互式 rebase 规划结果
+#[derive(Debug)]
+pub struct RebasePlanResult {
+    pub base: String,
+    pub base_sha: String,
+    /// 按应用顺序（旧 → 新）排列，不含 merge 提交
+    pub commits: Vec<RebasePlanCommit>,
+}
+
+/// todo 动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RebasePlanAction {
+    Pick,
+    Reword,
+    Squash,
+    Fixup,
+    Drop,
+}
+
+impl RebasePlanAction {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pick" => Some(Self::Pick),
+            "reword" => Some(Self::Reword),
+            "squash" => Some(Self::Squash),
+            "fixup" => Some(Self::Fixup),
+            "drop" => Some(Self::Drop),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pick => "pick",
+            Self::Reword => "reword",
+            Self::Squash => "squash",
+            Self::Fixup => "fixup",
+            Self::Drop => "drop",
+        }
+    }
+}
+
+/// 客户端提交的单步动作
+#[derive(Debug, Clone)]
+pub struct RebasePlanStep {
+    pub sha: String,
+    pub action: RebasePlanAction,
+    /// reword 必填；squash 可选（省略时合并各提交信息）；其余动作忽略
+    pub message: Option<String>,
+}
+
+fn rebase_error(message: impl Into<String>) -> GitRebaseResult {
+    GitRebaseResult {
+        ok: false,
+        state: "error".to_string(),
+        message: Some(message.into()),
+        conflicts: vec![],
+        conflict_files: vec![],
+    }
+}
+
+/// 列出 `base..HEAD` 上将被 rebase 的提交（旧 → 新）
+pub fn git_rebase_interactive_plan(
+    workspace_root: &Path,
+    base: &str,
+) -> Result<RebasePlanResult, GitError> {
+    if get_git_repo_root(workspace_root).is_none() {
+        return Err(GitError::NotAGitRepo);
+    }
+
+    let output = git_command(workspace_root)
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("{}^{{commit}}", base))
+        .output()
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        return Err(GitError::CommandFailed(format!(
+            "Unknown rebase base: {}",
+            base
+        )));
+    }
+    let base_sha = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    // 与 rebase 的默认行为一致：merge 提交不进入 todo
+    let output = git_command(workspace_root)
+        .args([
+            "log",
+            "--no-merges",
+            "--reverse",
+            "--topo-order",
+            "--format=%H%x1f%h%x1f%an%x1f%aI%x1f%B%x1e",
+        ])
+        .arg(format!("{}..HEAD", base_sha))
+        .output()
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        return Err(GitError::CommandFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let commits = split_log_records(&stdout, '\x1f')
+        .into_iter()
+        .filter_map(|record| {
+            let mut fields = record.splitn(5, '\x1f');
+            let sha = fields.next()?.to_string();
+            if sha.is_empty() {
+                return None;
+            }
+            let short_sha = fields.next()?.to_string();
+            let author = fields.next()?.to_string();
+            let date = fields.next()?.to_string();
+            let message = fields.next()?.trim_end().to_string();
+            let subject = message.lines().next().unwrap_or_default().to_string();
+            Some(RebasePlanCommit {
+                sha,
+                short_sha,
+                subject,
+                message,
+                author,
+                date,
+            })
+        })
+        .collect();
+
+    Ok(RebasePlanResult {
+        base: base.to_string(),
+        base_sha,
+        commits,
+    })
+}
+
+/// 按动作列表执行交互式 rebase
+///
+/// 动作列表必须恰好覆盖 `base..HEAD` 上的每个提交一次（不需要的提交显式 `drop`），
+/// 防止规划之后分支又有新提交时被静默丢弃。冲突时与 `git_rebase` 一样返回
+/// `conflict` 状态，后续使用 `git_rebase_continue` / `git_rebase_abort`。
+pub fn git_rebase_interactive_execute(
+    workspace_root: &Path,
+    base: &str,
+    steps: &[RebasePlanStep],
+) -> Result<GitRebaseResult, GitError> {
+    if get_git_repo_root(workspace_root).is_none() {
+        return Err(GitError::NotAGitRepo);
+    }
+
+    if is_rebasing(workspace_root) {
+        let mut result = rebase_error("Already in a rebase. Use continue or abort.");
+        result.conflicts = get_conflict_files(workspace_root);
+        result.conflict_files = get_conflict_file_entries(workspace_root);
+        return Ok(result);
+    }
+
+    let plan = git_rebase_interactive_plan(workspace_root, base)?;
+    if let Err(message) = validate_steps(&plan.commits, steps) {
+        return Ok(rebase_error(message));
+    }
+
+    let files_dir = rebase_files_dir(workspace_root)?;
+    let _ = std::fs::remove_dir_all(&files_dir);
+    std::fs::create_dir_all(&files_dir).map_err(GitError::IoError)?;
+
+    let todo = build_todo(&plan.commits, steps, &files_dir).map_err(GitError::IoError)?;
+    let todo_path = files_dir.join("git-rebase-todo");
+    std::fs::write(&todo_path, todo).map_err(GitError::IoError)?;
+
+    let output = git_command(workspace_root)
+        .args(["rebase", "-i", "--no-autosquash", &plan.base_sha])
+        .env(
+            "GIT_SEQUENCE_EDITOR",
+            format!("cp {}", shell_quote(&todo_path.to_string_lossy())),
+        )
+        // 新提交信息已通过 exec 写入，任何情况下都不应阻塞在编辑器上
+        .env("GIT_EDITOR", "true")
+        .output()
+        .map_err(GitError::IoError)?;
+
+    invalidate_git_status_cache(workspace_root);
+
+    if output.status.success() && !is_rebasing(workspace_root) {
+        let _ = std::fs::remove_dir_all(&files_dir);
+        return Ok(GitRebaseResult {
+            ok: true,
+            state: "completed".to_string(),
+            message: Some(format!("Rebased {} commits onto {}", steps.len(), base)),
+            conflicts: vec![],
+            conflict_files: vec![],
+        });
+    }
+
+    if is_rebasing(workspace_root) {
+        let conflicts = get_conflict_files(workspace_root);
+        let conflict_files = get_conflict_file_entries(workspace_root);
+        let message = if conflicts.is_empty() {
+            // exec 失败（如 commit hook 拒绝）同样会暂停 rebase
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            if stderr.is_empty() {
+                "Rebase paused".to_string()
+            } else {
+                stderr
+            }
+        } else {
+            "Rebase paused due to conflicts".to_string()
+        };
+        return Ok(GitRebaseResult {
+            ok: false,
+            state: "conflict".to_string(),
+            message: Some(message),
+            conflicts,
+            conflict_files,
+        });
+    }
+
+    let _ = std::fs::remove_dir_all(&files_dir);
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    Ok(rebase_error(if stderr.is_empty() {
+        "Rebase failed".to_string()
+    } else {
+        stderr
+    }))
+}
+
+/// 清理交互式 rebase 生成的 todo 与消息文件（rebase 结束后调用）
+pub fn clear_interactive_rebase_files(workspace_root: &Path) {
+    if let Ok(dir) = rebase_files_dir(workspace_root) {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
+
+fn rebase_files_dir(workspace_root: &Path) -> Result<PathBuf, GitError> {
+    let output = git_command(workspace_root)
+        .args(["rev-parse", "--absolute-git-dir"])
+        .output()
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        return Err(GitError::NotAGitRepo);
+    }
+    let git_dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(PathBuf::from(git_dir).join(REBASE_FILES_DIR))
+}
+
+fn validate_steps(commits: &[RebasePlanCommit], steps: &[RebasePlanStep]) -> Result<(), String> {
+    if commits.is_empty() {
+        return Err("No commits to rebase".to_string());
+    }
+
+    let mut seen = HashSet::new();
+    for step in steps {
+        let Some(commit) = resolve_commit(commits, &step.sha) else {
+            return Err(format!(
+                "Commit {} is not part of the rebase range",
+                step.sha
+            ));
+        };
+        if !seen.insert(commit.sha.as_str()) {
+            return Err(format!("Commit {} appears more than once", step.sha));
+        }
+        if step.action == RebasePlanAction::Reword
+            && step
+                .message
+                .as_deref()
+                .map_or(true, |m| m.trim().is_empty())
+        {
+            return Err(format!("Reword of {} requires a message", step.sha));
+        }
+    }
+    if seen.len() != commits.len() {
+        return Err(
+            "Plan must list every commit in the range (use drop to remove commits)".to_string(),
+        );
+    }
+
+    match steps
+        .iter()
+        .find(|step| step.action != RebasePlanAction::Drop)
+        .map(|step| step.action)
+    {
+        None => Err("Plan drops every commit".to_string()),
+        Some(RebasePlanAction::Squash | RebasePlanAction::Fixup) => {
+            Err("The first kept commit cannot be squash or fixup".to_string())
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+/// 支持完整 SHA 或唯一前缀
+fn resolve_commit<'a>(commits: &'a [RebasePlanCommit], sha: &str) -> Option<&'a RebasePlanCommit> {
+    if sha.len() < 4 {
+        return None;
+    }
+    let mut matches = commits.iter().filter(|c| c.sha.starts_with(sha));
+    let first = matches.next()?;
+    matches.next().is_none().then_some(first)
+}
+
+/// 生成 todo 文本
+///
+/// squash 统一转成 fixup + 链尾 exec 改写消息：未指定消息时按 git 默认行为把被合并提交的信息依次拼接。
+fn build_todo(
+    commits: &[RebasePlanCommit],
+    steps: &[RebasePlanStep],
+    files_dir: &Path,
+) -> std::io::Result<String> {
+    let mut todo = String::new();
+    // 当前 pick 及其后 squash 链的提交信息；rewritten 表示需要在链尾改写消息
+    let mut chain: Vec<&str> = Vec::new();
+    let mut rewritten = false;
+    let mut message_index = 0;
+
+    let mut flush = |todo: &mut String, chain: &mut Vec<&str>, rewritten: &mut bool| {
+        if std::mem::take(rewritten) {
+            message_index += 1;
+            let path = files_dir.join(format!("message-{}", message_index));
+            std::fs::write(&path, chain.join("\n\n"))?;
+            todo.push_str(&format!(
+                "exec git commit --amend --allow-empty -F {}\n",
+                shell_quote(&path.to_string_lossy())
+            ));
+        }
+        chain.clear();
+        std::io::Result::Ok(())
+    };
+
+    for step in steps {
+        let commit = resolve_commit(commits, &step.sha).expect("validated step");
+        let message = step.message.as_deref().filter(|m| !m.trim().is_empty());
+        match step.action {
+            RebasePlanAction::Drop => {
+                todo.push_str(&format!("drop {}\n", commit.sha));
+            }
+            RebasePlanAction::Pick | RebasePlanAction::Reword => {
+                flush(&mut todo, &mut chain, &mut rewritten)?;
+                todo.push_str(&format!("pick {}\n", commit.sha));
+                if step.action == RebasePlanAction::Reword {
+                    chain.push(message.expect("validated reword message"));
+                    rewritten = true;
+                } else {
+                    chain.push(&commit.message);
+                }
+            }
+            RebasePlanAction::Squash => {
+                todo.push_str(&format!("fixup {}\n", commit.sha));
+                // 指定消息时作为整条链合并后的最终消息
+                if let Some(message) = message {
+                    chain.clear();
+                    chain.push(message);
+                } else {
+                    chain.push(&commit.message);
+                }
+                rewritten = true;
+            }
+            RebasePlanAction::Fixup => {
+                todo.push_str(&format!("fixup {}\n", commit.sha));
+            }
+        }
+    }
+    flush(&mut todo, &mut chain, &mut rewritten)?;
+    Ok(todo)
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = git_command(dir).args(args).output().expect("run git");
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn commit_file(dir: &Path, name: &str, content: &str, message: &str) {
+        fs::write(dir.join(name), content).unwrap();
+        git(dir, &["add", name]);
+        git(dir, &["commit", "-q", "-m", message]);
+    }
+
+    fn init_repo() -> tempfile::TempDir {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        git(dir, &["init", "-q", "-b", "main"]);
+        git(dir, &["config", "user.name", "Test"]);
+        git(dir, &["config", "user.email", "test@example.com"]);
+        git(dir, &["config", "commit.gpgsign", "false"]);
+        commit_file(dir, "base.txt", "base\n", "base");
+        tmp
+    }
+
+    fn step(sha: &str, action: RebasePlanAction, message: Option<&str>) -> RebasePlanStep {
+        RebasePlanStep {
+            sha: sha.to_string(),
+            action,
+            message: message.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn plan_and_execute_cleanup() {
+        let tmp = init_repo();
+        let dir = tmp.path();
+        commit_file(dir, "a.txt", "a\n", "add a");
+        commit_file(dir, "a.txt", "a fixed\n", "fix typo in a");
+        commit_file(dir, "b.txt", "b\n", "add b");
+        commit_file(dir, "c.txt", "c\n", "wip c");
+        commit_file(dir, "d.txt", "d\n", "add d\n\nbody of d");
+
+        let plan = git_rebase_interactive_plan(dir, "HEAD~5").unwrap();
+        let subjects: Vec<_> = plan.commits.iter().map(|c| c.subject.as_str()).collect();
+        assert_eq!(
+            subjects,
+            vec!["add a", "fix typo in a", "add b", "wip c", "add d"]
+        );
+        assert_eq!(plan.commits[4].message, "add d\n\nbody of d");
+        let sha = |i: usize| plan.commits[i].sha.clone();
+
+        // 省略提交会被拒绝
+        let partial = [step(&sha(0), RebasePlanAction::Pick, None)];
+        let result = git_rebase_interactive_execute(dir, "HEAD~5", &partial).unwrap();
+        assert_eq!(result.state, "error");
+
+        let steps = [
+            step(&sha(3), RebasePlanAction::Reword, Some("add c")),
+            step(&plan.commits[0].short_sha, RebasePlanAction::Pick, None),
+            step(&sha(1), RebasePlanAction::Fixup, None),
+            step(&sha(2), RebasePlanAction::Pick, None),
+            step(&sha(4), RebasePlanAction::Squash, Some("add b and d")),
+        ];
+        let result = git_rebase_interactive_execute(dir, "HEAD~5", &steps).unwrap();
+        assert!(result.ok, "{:?}", result.message);
+
+        let log = git(dir, &["log", "--format=%s", "main"]);
+        assert_eq!(log, "add b and d\nadd a\nadd c\nbase");
+        assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "a fixed\n");
+        assert!(dir.join("d.txt").exists());
+        assert!(!rebase_files_dir(dir).unwrap().exists());
+    }
+
+    #[test]
+    fn reword_applies_after_conflict_continue() {
+        let tmp = init_repo();
+        let dir = tmp.path();
+        commit_file(dir, "f.txt", "one\n", "first");
+        commit_file(dir, "f.txt", "two\n", "second");
+        commit_file(dir, "g.txt", "g\n", "third");
+
+        let plan = git_rebase_interactive_plan(dir, "main~3").unwrap();
+        let sha = |i: usize| plan.commits[i].sha.clone();
+        // 先应用 second 会与不存在的 f.txt 冲突
+        let steps = [
+            step(&sha(1), RebasePlanAction::Reword, Some("second, reworded")),
+            step(&sha(0), RebasePlanAction::Drop, None),
+            step(&sha(2), RebasePlanAction::Squash, None),
+        ];
+        let result = git_rebase_interactive_execute(dir, "main~3", &steps).unwrap();
+        assert_eq!(result.state, "conflict", "{:?}", result.message);
+
+        fs::write(dir.join("f.txt"), "two\n").unwrap();
+        git(dir, &["add", "f.txt"]);
+        let result = super::super::git_rebase_continue(dir).unwrap();
+        assert!(result.ok, "{:?}", result.message);
+
+        let message = git(dir, &["log", "-1", "--format=%B"]);
+        assert_eq!(message, "second, reworded\n\nthird");
+        assert_eq!(git(dir, &["rev-list", "--count", "HEAD"]), "2");
+    }
+}
@@ -0,0 +1,400 @@
+//! 仓库统计与维护状态
+//!
+//! 汇总 `git count-objects -v`、`git rev-list` 与对象库文件信息，供“仓库健康度”页面展示，
+//! 并据此给出 gc / 维护建议。对象库在各 worktree 间共享，按项目根目录统计即可。
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use super::utils::*;
+use crate::util::exec_env::git_command;
+
+/// 返回的历史最大文件个数
+pub const REPO_STATS_LARGEST_FILES: usize = 10;
+
+/// 未配置 `gc.auto` 时 git 的默认松散对象阈值
+const DEFAULT_GC_AUTO: u64 = 6700;
+/// 未配置 `gc.autoPackLimit` 时 git 的默认 pack 个数阈值
+const DEFAULT_GC_AUTO_PACK_LIMIT: u64 = 50;
+/// 建议注册 `git maintenance` 的 pack 总大小下限（100 MiB）
+const MAINTENANCE_SUGGEST_PACK_BYTES: u64 = 100 * 1024 * 1024;
+/// `gc.log` 内容最多返回的字节数
+const MAX_GC_LOG_BYTES: usize = 4096;
+/// 历史对象扫描时 `git cat-file --batch-check` 的输出格式
+pub const HISTORY_BLOB_CHECK_FORMAT: &str =
+    "%(objecttype) %(objectname) %(objectsize) %(objectsize:disk) %(rest)";
+
+/// 历史中的大文件（按 blob 去重，路径取首次出现的路径）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoLargeFile {
+    pub path: String,
+    pub sha: String,
+    /// 未压缩大小（字节）
+    pub size: u64,
+    /// 在对象库中的实际占用（字节，压缩/增量后）
+    pub disk_size: u64,
+}
+
+/// 仓库统计
+#[derive(Debug, Clone, Default)]
+pub struct RepoStats {
+    /// 所有引用可达的提交数
+    pub commit_count: u64,
+    pub loose_objects: u64,
+    pub loose_size_bytes: u64,
+    pub packed_objects: u64,
+    pub pack_count: u64,
+    pub pack_size_bytes: u64,
+    /// 已打包但仍以松散形式存在、可被 prune 的对象数
+    pub prune_packable: u64,
+    pub garbage_count: u64,
+    pub garbage_size_bytes: u64,
+    pub largest_files: Vec<RepoLargeFile>,
+    /// 最近一次 gc 的估计时间（Unix 秒）：最大 pack 文件的修改时间
+    pub last_gc_at: Option<i64>,
+    /// 上次自动 gc 失败时留下的 `gc.log` 内容
+    pub gc_log: Option<String>,
+    /// 是否已通过 `git maintenance register` 注册后台维护
+    pub maintenance_registered: bool,
+    /// 维护建议：`gc` | `prune_garbage` | `gc_failed` | `register_maintenance`
+    pub suggestions: Vec<String>,
+}
+
+/// `git count-objects -v` 的字段
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CountObjects {
+    pub count: u64,
+    pub size_kib: u64,
+    pub in_pack: u64,
+    pub packs: u64,
+    pub size_pack_kib: u64,
+    pub prune_packable: u64,
+    pub garbage: u64,
+    pub size_garbage_kib: u64,
+}
+
+/// 解析 `git count-objects -v` 输出（未知字段忽略）
+pub fn parse_count_objects(stdout: &str) -> CountObjects {
+    let mut stats = CountObjects::default();
+    for line in stdout.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let Ok(value) = value.trim().parse::<u64>() else {
+            continue;
+        };
+        match key.trim() {
+            "count" => stats.count = value,
+            "size" => stats.size_kib = value,
+            "in-pack" => stats.in_pack = value,
+            "packs" => stats.packs = value,
+            "size-pack" => stats.size_pack_kib = value,
+            "prune-packable" => stats.prune_packable = value,
+            "garbage" => stats.garbage = value,
+            "size-garbage" => stats.size_garbage_kib = value,
+            _ => {}
+        }
+    }
+    stats
+}
+
+/// 解析 [`HISTORY_BLOB_CHECK_FORMAT`] 格式的一行，非 blob 对象返回 `None`
+pub fn parse_blob_check_line(line: &str) -> Option<RepoLargeFile> {
+    let mut parts = line.splitn(5, ' ');
+    let (Some("blob"), Some(sha), Some(size), Some(disk)) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    Some(RepoLargeFile {
+        path: parts.next().unwrap_or_default().to_string(),
+        sha: sha.to_string(),
+        size: size.parse().ok()?,
+        disk_size: disk.parse().ok()?,
+    })
+}
+
+/// 从 [`HISTORY_BLOB_CHECK_FORMAT`] 格式的输出中选出最大的 `limit` 个 blob（按未压缩大小降序）
+pub fn largest_blobs<I>(lines: I, limit: usize) -> Vec<RepoLargeFile>
+where
+    I: IntoIterator<Item = String>,
+{
+    if limit == 0 {
+        return Vec::new();
+    }
+    let mut seen = HashSet::new();
+    // 小顶堆：(大小, sha, 路径, 磁盘大小)，保留最大的 limit 个
+    let mut heap: BinaryHeap<Reverse<(u64, String, String, u64)>> = BinaryHeap::new();
+    for line in lines {
+        let Some(blob) = parse_blob_check_line(&line) else {
+            continue;
+        };
+        if !seen.insert(blob.sha.clone()) {
+            continue;
+        }
+        heap.push(Reverse((blob.size, blob.sha, blob.path, blob.disk_size)));
+        if heap.len() > limit {
+            heap.pop();
+        }
+    }
+    let mut files: Vec<RepoLargeFile> = heap
+        .into_iter()
+        .map(|Reverse((size, sha, path, disk_size))| RepoLargeFile {
+            path,
+            sha,
+            size,
+            disk_size,
+        })
+        .collect();
+    files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    files
+}
+
+/// 根据统计结果与 gc 阈值给出维护建议
+pub fn maintenance_suggestions(
+    stats: &RepoStats,
+    gc_auto: u64,
+    gc_auto_pack_limit: u64,
+) -> Vec<String> {
+    let mut suggestions = Vec::new();
+    if stats.gc_log.is_some() {
+        suggestions.push("gc_failed".to_string());
+    }
+    let too_many_loose = gc_auto > 0 && stats.loose_objects > gc_auto;
+    let too_many_packs = gc_auto_pack_limit > 0 && stats.pack_count > gc_auto_pack_limit;
+    if too_many_loose || too_many_packs || stats.prune_packable > 0 {
+        suggestions.push("gc".to_string());
+    }
+    if stats.garbage_count > 0 {
+        suggestions.push("prune_garbage".to_string());
+    }
+    if !stats.maintenance_registered && stats.pack_size_bytes >= MAINTENANCE_SUGGEST_PACK_BYTES {
+        suggestions.push("register_maintenance".to_string());
+    }
+    suggestions
+}
+
+/// 统计仓库对象、提交与大文件，并给出维护建议
+pub fn git_repo_stats(repo_root: &Path) -> Result<RepoStats, GitError> {
+    let count_output = run_git(repo_root, &["count-objects", "-v"])?;
+    let counts = parse_count_objects(&count_output);
+
+    let commit_count = run_git(repo_root, &["rev-list", "--count", "--all"])
+        .ok()
+        .and_then(|out| out.trim().parse::<u64>().ok())
+        .unwrap_or(0);
+
+    let common_dir = git_common_dir(repo_root)?;
+    let gc_log = std::fs::read(common_dir.join("gc.log"))
+        .ok()
+        .map(|bytes| {
+            let text = String::from_utf8_lossy(&bytes[..bytes.len().min(MAX_GC_LOG_BYTES)]);
+            text.trim().to_string()
+        })
+        .filter(|text| !text.is_empty());
+
+    let mut stats = RepoStats {
+        commit_count,
+        loose_objects: counts.count,
+        loose_size_bytes: counts.size_kib * 1024,
+        packed_objects: counts.in_pack,
+        pack_count: counts.packs,
+        pack_size_bytes: counts.size_pack_kib * 1024,
+        prune_packable: counts.prune_packable,
+        garbage_count: counts.garbage,
+        garbage_size_bytes: counts.size_garbage_kib * 1024,
+        largest_files: largest_files_in_history(repo_root, REPO_STATS_LARGEST_FILES)?,
+        last_gc_at: largest_pack_mtime(&common_dir.join("objects").join("pack")),
+        gc_log,
+        maintenance_registered: maintenance_registered(repo_root),
+        suggestions: Vec::new(),
+    };
+    let gc_auto = config_u64(repo_root, "gc.auto").unwrap_or(DEFAULT_GC_AUTO);
+    let gc_auto_pack_limit =
+        config_u64(repo_root, "gc.autoPackLimit").unwrap_or(DEFAULT_GC_AUTO_PACK_LIMIT);
+    stats.suggestions = maintenance_suggestions(&stats, gc_auto, gc_auto_pack_limit);
+    Ok(stats)
+}
+
+fn run_git(repo_root: &Path, args: &[&str]) -> Result<String, GitError> {
+    let output = git_command(repo_root)
+        .args(args)
+        .output()
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("not a git repository") {
+            return Err(GitError::NotAGitRepo);
+        }
+        return Err(GitError::CommandFailed(stderr.trim().to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn git_common_dir(repo_root: &Path) -> Result<PathBuf, GitError> {
+    let out = run_git(repo_root, &["rev-parse", "--git-common-dir"])?;
+    let dir = PathBuf::from(out.trim());
+    Ok(if dir.is_absolute() {
+        dir
+    } else {
+        repo_root.join(dir)
+    })
+}
+
+fn config_u64(repo_root: &Path, key: &str) -> Option<u64> {
+    run_git(repo_root, &["config", "--get", key])
+        .ok()
+        .and_then(|out| out.trim().parse::<u64>().ok())
+}
+
+/// `maintenance.repo`（通常位于全局配置）中是否包含本仓库
+fn maintenance_registered(repo_root: &Path) -> bool {
+    let Ok(toplevel) = run_git(repo_root, &["rev-parse", "--show-toplevel"]) else {
+        return false;
+    };
+    let toplevel = toplevel.trim();
+    run_git(repo_root, &["config", "--get-all", "maintenance.repo"])
+        .map(|out| out.lines().any(|line| line.trim() == toplevel))
+        .unwrap_or(false)
+}
+
+/// gc 会把对象重新打进一个大 pack，最大 pack 的修改时间即最近一次 gc 的近似时间
+fn largest_pack_mtime(pack_dir: &Path) -> Option<i64> {
+    std::fs::read_dir(pack_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "pack"))
+        .filter_map(|entry| entry.metadata().ok())
+        .max_by_key(|meta| meta.len())
+        .and_then(|meta| meta.modified().ok())
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+}
+
+fn largest_files_in_history(
+    repo_root: &Path,
+    limit: usize,
+) -> Result<Vec<RepoLargeFile>, GitError> {
+    scan_history_objects(repo_root, |lines| largest_blobs(lines, limit))
+}
+
+/// `git rev-list --objects --all` 直接接到 `git cat-file --batch-check`，
+/// 以 [`HISTORY_BLOB_CHECK_FORMAT`] 格式逐行流式交给 `consume`
+pub fn scan_history_objects<T>(
+    repo_root: &Path,
+    consume: impl FnOnce(&mut dyn Iterator<Item = String>) -> T,
+) -> Result<T, GitError> {
+    let mut rev_list = git_command(repo_root)
+        .args(["rev-list", "--objects", "--all"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(GitError::IoError)?;
+    let Some(rev_list_stdout) = rev_list.stdout.take() else {
+        let _ = rev_list.kill();
+        let _ = rev_list.wait();
+        return Err(GitError::CommandFailed(
+            "rev-list stdout unavailable".to_string(),
+        ));
+    };
+    let mut cat_file = match git_command(repo_root)
+        .arg("cat-file")
+        .arg(format!("--batch-check={}", HISTORY_BLOB_CHECK_FORMAT))
+        .stdin(Stdio::from(rev_list_stdout))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            let _ = rev_list.kill();
+            let _ = rev_list.wait();
+            return Err(GitError::IoError(e));
+        }
+    };
+
+    let Some(stdout) = cat_file.stdout.take() else {
+        let _ = cat_file.kill();
+        let _ = cat_file.wait();
+        let _ = rev_list.wait();
+        return Err(GitError::CommandFailed(
+            "cat-file stdout unavailable".to_string(),
+        ));
+    };
+    let mut lines = BufReader::new(stdout).lines().map_while(Result::ok);
+    let result = consume(&mut lines);
+    // consume 可能提前返回：读完剩余输出，避免子进程因管道写满而阻塞
+    lines.for_each(drop);
+    let cat_status = cat_file.wait().map_err(GitError::IoError)?;
+    let rev_status = rev_list.wait().map_err(GitError::IoError)?;
+    if !rev_status.success() || !cat_status.success() {
+        return Err(GitError::CommandFailed(
+            "failed to enumerate history objects".to_string(),
+        ));
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_count_objects_verbose_output() {
+        let out = "count: 12\nsize: 48\nin-pack: 3400\npacks: 2\nsize-pack: 1024\nprune-packable: 1\ngarbage: 0\nsize-garbage: 0\n";
+        let stats = parse_count_objects(out);
+        assert_eq!(stats.count, 12);
+        assert_eq!(stats.size_kib, 48);
+        assert_eq!(stats.in_pack, 3400);
+        assert_eq!(stats.packs, 2);
+        assert_eq!(stats.size_pack_kib, 1024);
+        assert_eq!(stats.prune_packable, 1);
+    }
+
+    #[test]
+    fn largest_blobs_keeps_top_n_unique_blobs() {
+        let lines = [
+            "commit aaaa 200 150 ",
+            "tree bbbb 90 80 ",
+            "blob c1 10 8 small.txt",
+            "blob c2 5000 900 assets/big file.bin",
+            "blob c3 300 200 mid.txt",
+            "blob c2 5000 900 renamed.bin",
+            "blob c4 7000 7000 huge.iso",
+        ]
+        .into_iter()
+        .map(str::to_string);
+        let top = largest_blobs(lines, 2);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].path, "huge.iso");
+        assert_eq!(top[1].path, "assets/big file.bin");
+        assert_eq!(top[1].disk_size, 900);
+    }
+
+    #[test]
+    fn suggests_gc_and_maintenance_from_thresholds() {
+        let mut stats = RepoStats {
+            loose_objects: 7000,
+            pack_size_bytes: 200 * 1024 * 1024,
+            ..Default::default()
+        };
+        assert_eq!(
+            maintenance_suggestions(&stats, DEFAULT_GC_AUTO, DEFAULT_GC_AUTO_PACK_LIMIT),
+            vec!["gc", "register_maintenance"]
+        );
+        stats.loose_objects = 10;
+        stats.maintenance_registered = true;
+        stats.garbage_count = 1;
+        assert_eq!(
+            maintenance_suggestions(&stats, DEFAULT_GC_AUTO, DEFAULT_GC_AUTO_PACK_LIMIT),
+            vec!["prune_garbage"]
+        );
+        // gc.auto = 0 表示禁用自动 gc，不再按松散对象数建议
+        stats.loose_objects = 100_000;
+        stats.garbage_count = 0;
+        assert!(maintenance_suggestions(&stats, 0, DEFAULT_GC_AUTO_PACK_LIMIT).is_empty());
+    }
+}
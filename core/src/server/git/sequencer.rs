@@ -4,11 +4,11 @@
 
 use std::collections::HashMap;
 use std::path::Path;
-use std::process::Command;
 use std::sync::Mutex;
 
-use super::status::invalidate_git_status_cache;
+use super::cache::invalidate_git_status_cache;
 use super::utils::*;
+use crate::util::exec_env::git_command;
 
 // ============================================================================
 // 类型定义
@@ -150,9 +150,8 @@ pub fn git_cherry_pick(
     let mut args = vec!["cherry-pick".to_string()];
     args.extend(commit_shas.iter().cloned());
 
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(&args)
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -226,9 +225,8 @@ pub fn git_cherry_pick_continue(workspace_root: &Path) -> Result<SequencerResult
         });
     }
 
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["cherry-pick", "--continue"])
-        .current_dir(workspace_root)
         .env("GIT_EDITOR", "true")
         .output()
         .map_err(GitError::IoError)?;
@@ -336,9 +334,8 @@ pub fn git_cherry_pick_abort(workspace_root: &Path) -> Result<SequencerResult, G
         });
     }
 
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["cherry-pick", "--abort"])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -426,9 +423,8 @@ pub fn git_revert(
     let mut args = vec!["revert".to_string(), "--no-edit".to_string()];
     args.extend(commit_shas.iter().cloned());
 
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(&args)
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -502,9 +498,8 @@ pub fn git_revert_continue(workspace_root: &Path) -> Result<SequencerResult, Git
         });
     }
 
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["revert", "--continue"])
-        .current_dir(workspace_root)
         .env("GIT_EDITOR", "true")
         .output()
         .map_err(GitError::IoError)?;
@@ -611,9 +606,8 @@ pub fn git_revert_abort(workspace_root: &Path) -> Result<SequencerResult, GitErr
         });
     }
 
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["revert", "--abort"])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -693,9 +687,8 @@ pub fn git_workspace_op_rollback(
     }
 
     // 执行回滚：git reset --hard <original_head>
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["reset", "--hard", &receipt.original_head])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -753,9 +746,8 @@ pub fn get_full_head_sha(workspace_root: &Path) -> Option<String> {
 
 /// 检测工作区是否 clean（没有未提交变更）
 fn is_workspace_clean(workspace_root: &Path) -> bool {
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["status", "--porcelain"])
-        .current_dir(workspace_root)
         .output();
     match output {
         Ok(out) => out.status.success() && out.stdout.is_empty(),
@@ -827,3 +819,86 @@ pub fn read_current_sequencer_commit(workspace_root: &Path) -> Option<String> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn git(root: &Path, args: &[&str]) -> String {
+        let output = git_command(root)
+            .args(args)
+            .env("GIT_EDITOR", "true")
+            .output()
+            .expect("run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    /// main 上修改 a.txt，feature 分支对同一行做不同修改，返回 feature 提交 SHA
+    fn init_diverged_repo(root: &Path) -> String {
+        git(root, &["init", "-q", "-b", "main"]);
+        git(root, &["config", "user.name", "tidyflow"]);
+        git(root, &["config", "user.email", "tidyflow@example.com"]);
+        git(root, &["config", "commit.gpgsign", "false"]);
+        std::fs::write(root.join("a.txt"), "base\n").unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-qm", "base"]);
+        git(root, &["checkout", "-qb", "feature"]);
+        std::fs::write(root.join("a.txt"), "feature\n").unwrap();
+        git(root, &["commit", "-qam", "feature"]);
+        let feature_sha = git(root, &["rev-parse", "HEAD"]);
+        git(root, &["checkout", "-q", "main"]);
+        std::fs::write(root.join("a.txt"), "main\n").unwrap();
+        git(root, &["commit", "-qam", "main"]);
+        feature_sha
+    }
+
+    #[test]
+    fn cherry_pick_conflict_then_continue() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let sha = init_diverged_repo(root);
+
+        let result = git_cherry_pick(root, std::slice::from_ref(&sha)).unwrap();
+        assert_eq!(result.state, "conflict");
+        assert_eq!(result.conflicts, vec!["a.txt".to_string()]);
+        assert!(is_cherry_picking(root));
+
+        // 未解决冲突时 continue 仍报告冲突
+        let result = git_cherry_pick_continue(root).unwrap();
+        assert_eq!(result.state, "conflict");
+
+        std::fs::write(root.join("a.txt"), "resolved\n").unwrap();
+        git(root, &["add", "a.txt"]);
+        let result = git_cherry_pick_continue(root).unwrap();
+        assert!(result.ok, "{:?}", result.message);
+        assert_eq!(result.state, "completed");
+        assert!(!is_cherry_picking(root));
+        assert_eq!(git(root, &["log", "-1", "--format=%s"]), "feature");
+    }
+
+    #[test]
+    fn revert_conflict_then_abort_restores_head() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        init_diverged_repo(root);
+        std::fs::write(root.join("a.txt"), "main again\n").unwrap();
+        git(root, &["commit", "-qam", "main again"]);
+        let head = git(root, &["rev-parse", "HEAD"]);
+        let target = git(root, &["rev-parse", "HEAD~1"]);
+
+        let result = git_revert(root, &[target]).unwrap();
+        assert_eq!(result.operation_kind, WorkspaceOperationKind::Revert);
+        assert_eq!(result.state, "conflict");
+        assert!(is_reverting(root));
+
+        // 已有 sequencer 操作时拒绝新的 cherry-pick
+        let busy = git_cherry_pick(root, std::slice::from_ref(&head)).unwrap();
+        assert_eq!(busy.state, "error");
+
+        let result = git_revert_abort(root).unwrap();
+        assert!(result.ok);
+        assert!(!is_reverting(root));
+        assert_eq!(git(root, &["rev-parse", "HEAD"]), head);
+    }
+}
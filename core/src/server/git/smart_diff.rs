@@ -0,0 +1,366 @@
+//! 常见噪音文件的智能 diff（`git_diff.smart_diff`）
+//!
+//! - `.gitattributes` 为路径声明了 diff 驱动（`diff=<driver>`）时尊重用户配置，
+//!   交给 git 按驱动（textconv）生成 diff，不再套用内置规则；
+//! - `package-lock.json` / `Cargo.lock` 归纳为「包名 版本」列表后再 diff，
+//!   只显示新增、删除与版本变化的依赖；
+//! - `.ipynb` 去掉单元格输出与执行计数后再 diff，只保留源码与元数据变化。
+//!
+//! 归纳后的两侧文本仍以 `git diff --no-index` 生成标准 unified diff，
+//! 因而结构化 hunk、行内高亮等后续处理无需区分。
+
+use std::path::{Path, PathBuf};
+
+use super::utils::*;
+use crate::util::exec_env::git_command;
+
+/// 应用了 `.gitattributes` 中声明的 diff 驱动
+pub const SMART_DIFF_GITATTRIBUTES: &str = "gitattributes";
+/// 依赖锁文件归纳为包版本列表
+pub const SMART_DIFF_LOCKFILE: &str = "lockfile";
+/// Jupyter notebook 去除输出
+pub const SMART_DIFF_NOTEBOOK: &str = "notebook";
+
+/// 内置的智能 diff 规则
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SmartDiffKind {
+    NpmLock,
+    CargoLock,
+    Notebook,
+}
+
+impl SmartDiffKind {
+    fn for_path(path: &str) -> Option<Self> {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        match name {
+            "package-lock.json" | "npm-shrinkwrap.json" => Some(Self::NpmLock),
+            "Cargo.lock" => Some(Self::CargoLock),
+            _ if name.ends_with(".ipynb") => Some(Self::Notebook),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::NpmLock | Self::CargoLock => SMART_DIFF_LOCKFILE,
+            Self::Notebook => SMART_DIFF_NOTEBOOK,
+        }
+    }
+
+    /// 归纳文件内容；空内容（新增/删除的一侧）保持为空，无法解析时返回 None
+    fn normalize(self, content: &str) -> Option<String> {
+        if content.trim().is_empty() {
+            return Some(String::new());
+        }
+        match self {
+            Self::NpmLock => summarize_npm_lock(content),
+            Self::CargoLock => summarize_cargo_lock(content),
+            Self::Notebook => strip_notebook_outputs(content),
+        }
+    }
+}
+
+/// 读取 `.gitattributes` 中对该路径生效的 diff 驱动名
+///
+/// `diff` / `-diff` / 未声明都不算驱动，返回 None。
+pub fn gitattributes_diff_driver(workspace_root: &Path, path: &str) -> Option<String> {
+    let output = git_command(workspace_root)
+        .args(["check-attr", "diff", "--", path])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // 输出形如 `<path>: diff: <value>`
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let value = stdout.lines().next()?.rsplit(": ").next()?.trim();
+    match value {
+        "" | "unspecified" | "set" | "unset" => None,
+        driver => Some(driver.to_string()),
+    }
+}
+
+/// `npm ls` 风格的依赖名：`node_modules/a/node_modules/b` -> `a > b`
+fn npm_package_name(key: &str) -> String {
+    key.trim_start_matches("node_modules/")
+        .split("/node_modules/")
+        .collect::<Vec<_>>()
+        .join(" > ")
+}
+
+/// package-lock.json（lockfileVersion 1/2/3）归纳为排序后的「包名 版本」行
+fn summarize_npm_lock(content: &str) -> Option<String> {
+    fn collect_v1(
+        prefix: &str,
+        deps: &serde_json::Map<String, serde_json::Value>,
+        out: &mut Vec<String>,
+    ) {
+        for (name, dep) in deps {
+            let full_name = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{} > {}", prefix, name)
+            };
+            let version = dep.get("version").and_then(|v| v.as_str()).unwrap_or("?");
+            out.push(format!("{} {}", full_name, version));
+            if let Some(nested) = dep.get("dependencies").and_then(|v| v.as_object()) {
+                collect_v1(&full_name, nested, out);
+            }
+        }
+    }
+
+    let lock: serde_json::Value = serde_json::from_str(content).ok()?;
+    let mut lines = Vec::new();
+    if let Some(packages) = lock.get("packages").and_then(|v| v.as_object()) {
+        for (key, package) in packages {
+            // 空 key 是项目自身
+            if key.is_empty() {
+                continue;
+            }
+            let version = package
+                .get("version")
+                .and_then(|v| v.as_str())
+                .unwrap_or("?");
+            lines.push(format!("{} {}", npm_package_name(key), version));
+        }
+    } else if let Some(deps) = lock.get("dependencies").and_then(|v| v.as_object()) {
+        collect_v1("", deps, &mut lines);
+    } else {
+        return None;
+    }
+    lines.sort();
+    Some(lines.into_iter().map(|line| line + "\n").collect())
+}
+
+/// Cargo.lock 归纳为排序后的「包名 版本」行
+fn summarize_cargo_lock(content: &str) -> Option<String> {
+    let lock: toml::Value = toml::from_str(content).ok()?;
+    let packages = lock.get("package")?.as_array()?;
+    let mut lines: Vec<String> = packages
+        .iter()
+        .filter_map(|package| {
+            let name = package.get("name")?.as_str()?;
+            let version = package.get("version")?.as_str()?;
+            Some(format!("{} {}\n", name, version))
+        })
+        .collect();
+    lines.sort();
+    Some(lines.concat())
+}
+
+/// 去掉 notebook 单元格的输出与执行计数，按 nbformat 习惯的单空格缩进重新输出
+fn strip_notebook_outputs(content: &str) -> Option<String> {
+    use serde::Serialize;
+
+    let mut notebook: serde_json::Value = serde_json::from_str(content).ok()?;
+    let cells = notebook.get_mut("cells")?.as_array_mut()?;
+    for cell in cells {
+        let Some(cell) = cell.as_object_mut() else {
+            continue;
+        };
+        if cell.contains_key("outputs") {
+            cell.insert("outputs".to_string(), serde_json::Value::Array(Vec::new()));
+        }
+        if cell.contains_key("execution_count") {
+            cell.insert("execution_count".to_string(), serde_json::Value::Null);
+        }
+    }
+    let mut buf = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut buf, formatter);
+    notebook.serialize(&mut serializer).ok()?;
+    buf.push(b'\n');
+    String::from_utf8(buf).ok()
+}
+
+/// 读取 git 对象（如 `HEAD:path`、`:path`）；不存在时视为空文件
+fn read_git_object(workspace_root: &Path, spec: &str) -> String {
+    git_command(workspace_root)
+        .args(["show", spec])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        .unwrap_or_default()
+}
+
+/// 在临时目录中对两段文本执行 `git diff --no-index`，文件头与仓库内路径一致
+fn diff_texts(
+    path: &str,
+    old: &str,
+    new: &str,
+    algorithm: Option<&str>,
+) -> Result<String, GitError> {
+    let temp_root: PathBuf =
+        std::env::temp_dir().join(format!("tidyflow-smart-diff-{}", uuid::Uuid::new_v4()));
+    let write_side = |side: &str, content: &str| -> Result<(), GitError> {
+        let file = temp_root.join(side).join(path);
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent).map_err(GitError::IoError)?;
+        }
+        std::fs::write(&file, content).map_err(GitError::IoError)
+    };
+    let result = write_side("a", old)
+        .and_then(|_| write_side("b", new))
+        .and_then(|_| {
+            let mut args = vec![
+                "diff".to_string(),
+                "--no-index".to_string(),
+                "--no-color".to_string(),
+                "--no-prefix".to_string(),
+            ];
+            if let Some(algorithm) = algorithm {
+                args.push(format!("--diff-algorithm={}", algorithm));
+            }
+            args.extend([
+                "--".to_string(),
+                format!("a/{}", path),
+                format!("b/{}", path),
+            ]);
+            // --no-index 在有差异时退出码为 1，属正常情况
+            git_command(&temp_root)
+                .args(&args)
+                .output()
+                .map_err(GitError::IoError)
+                .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        });
+    let _ = std::fs::remove_dir_all(&temp_root);
+    result
+}
+
+/// 按内置规则生成智能 diff
+///
+/// 路径不适用内置规则、`.gitattributes` 已声明驱动，或任一侧内容无法解析时返回 None，
+/// 调用方回退到普通 diff。返回 (diff 文本, 是否截断, 标签)。
+pub fn builtin_smart_diff(
+    workspace_root: &Path,
+    path: &str,
+    base: Option<&str>,
+    mode: &str,
+    algorithm: Option<&str>,
+) -> Result<Option<(String, bool, &'static str)>, GitError> {
+    let Some(kind) = SmartDiffKind::for_path(path) else {
+        return Ok(None);
+    };
+    if gitattributes_diff_driver(workspace_root, path).is_some() {
+        return Ok(None);
+    }
+    let read_worktree = || std::fs::read_to_string(workspace_root.join(path)).unwrap_or_default();
+    let (old, new) = match base {
+        Some(base) => (
+            read_git_object(workspace_root, &format!("{}:{}", base, path)),
+            read_worktree(),
+        ),
+        None if mode == "staged" => (
+            read_git_object(workspace_root, &format!("HEAD:{}", path)),
+            read_git_object(workspace_root, &format!(":{}", path)),
+        ),
+        None => (
+            read_git_object(workspace_root, &format!(":{}", path)),
+            read_worktree(),
+        ),
+    };
+    let (Some(old), Some(new)) = (kind.normalize(&old), kind.normalize(&new)) else {
+        return Ok(None);
+    };
+    let text = diff_texts(path, &old, &new, algorithm)?;
+    let (text, truncated) = truncate_if_needed(&text);
+    Ok(Some((text, truncated, kind.label())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarize_npm_lock_versions() {
+        let v3 = r#"{
+  "lockfileVersion": 3,
+  "packages": {
+    "": { "name": "app" },
+    "node_modules/react": { "version": "18.3.1", "resolved": "https://x", "integrity": "sha512-a" },
+    "node_modules/a/node_modules/b": { "version": "1.0.0" }
+  }
+}"#;
+        assert_eq!(
+            summarize_npm_lock(v3).unwrap(),
+            "a > b 1.0.0\nreact 18.3.1\n"
+        );
+
+        let v1 = r#"{"lockfileVersion": 1, "dependencies": {"lodash": {"version": "4.17.21", "dependencies": {"x": {"version": "2.0.0"}}}}}"#;
+        assert_eq!(
+            summarize_npm_lock(v1).unwrap(),
+            "lodash 4.17.21\nlodash > x 2.0.0\n"
+        );
+        assert!(summarize_npm_lock("{\"name\": \"app\"}").is_none());
+    }
+
+    #[test]
+    fn summarize_cargo_lock_versions() {
+        let lock = "version = 3\n\n[[package]]\nname = \"serde\"\nversion = \"1.0.200\"\nchecksum = \"abc\"\n\n[[package]]\nname = \"anyhow\"\nversion = \"1.0.86\"\n";
+        assert_eq!(
+            summarize_cargo_lock(lock).unwrap(),
+            "anyhow 1.0.86\nserde 1.0.200\n"
+        );
+    }
+
+    #[test]
+    fn notebook_outputs_are_stripped() {
+        let nb = r#"{"cells": [{"cell_type": "code", "execution_count": 7, "metadata": {}, "outputs": [{"output_type": "stream", "text": ["hello\n"]}], "source": ["print('hello')"]}], "metadata": {}, "nbformat": 4, "nbformat_minor": 5}"#;
+        let stripped = strip_notebook_outputs(nb).unwrap();
+        assert!(stripped.contains("\"execution_count\": null"));
+        assert!(stripped.contains("\"outputs\": []"));
+        assert!(stripped.contains("print('hello')"));
+        assert!(!stripped.contains("hello\\n"));
+    }
+
+    #[test]
+    fn smart_diff_for_lockfile_in_repo() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        let git = |args: &[&str]| {
+            git_command(root)
+                .args(args)
+                .output()
+                .map(|o| o.status.success())
+                .unwrap_or(false)
+        };
+        if !git(&["init", "-q"]) {
+            return;
+        }
+        git(&["config", "user.name", "tidyflow"]);
+        git(&["config", "user.email", "tidyflow@example.com"]);
+        let lock = |version: &str| {
+            format!(
+                "{{\"lockfileVersion\": 3, \"packages\": {{\"\": {{}}, \"node_modules/left-pad\": {{\"version\": \"1.0.0\"}}, \"node_modules/react\": {{\"version\": \"{}\", \"integrity\": \"sha512-{}\"}}}}}}",
+                version, version
+            )
+        };
+        std::fs::write(root.join("package-lock.json"), lock("18.2.0")).unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "init"]);
+        std::fs::write(root.join("package-lock.json"), lock("18.3.1")).unwrap();
+
+        let (text, truncated, label) =
+            builtin_smart_diff(root, "package-lock.json", None, "working", None)
+                .unwrap()
+                .unwrap();
+        assert_eq!(label, SMART_DIFF_LOCKFILE);
+        assert!(!truncated);
+        assert!(text.contains("--- a/package-lock.json"));
+        assert!(text.contains("-react 18.2.0\n+react 18.3.1\n"));
+        assert!(!text.contains("integrity"));
+
+        // .gitattributes 声明了驱动时让位给 git
+        std::fs::write(root.join(".gitattributes"), "*.json diff=custom\n").unwrap();
+        assert_eq!(
+            gitattributes_diff_driver(root, "package-lock.json").as_deref(),
+            Some("custom")
+        );
+        assert!(
+            builtin_smart_diff(root, "package-lock.json", None, "working", None)
+                .unwrap()
+                .is_none()
+        );
+    }
+}
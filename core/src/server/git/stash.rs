@@ -3,10 +3,10 @@
 //! 提供 stash 的列表查询、详情查看、创建、应用、弹出、删除和文件恢复功能。
 
 use std::path::Path;
-use std::process::Command;
 
-use super::status::invalidate_git_status_cache;
+use super::cache::invalidate_git_status_cache;
 use super::utils::*;
+use crate::util::exec_env::git_command;
 
 // ── 领域类型 ──
 
@@ -124,9 +124,8 @@ fn normalize_stash_id(stash_id: &str) -> String {
 
 /// 检查 stash 是否包含未跟踪文件（第三个 parent）
 fn has_untracked_parent(workspace_root: &Path, stash_ref: &str) -> bool {
-    Command::new("git")
+    git_command(workspace_root)
         .args(["rev-parse", "--verify", &format!("{}^3", stash_ref)])
-        .current_dir(workspace_root)
         .output()
         .map(|o| o.status.success())
         .unwrap_or(false)
@@ -134,9 +133,8 @@ fn has_untracked_parent(workspace_root: &Path, stash_ref: &str) -> bool {
 
 /// 从 stash show 获取文件数
 fn get_stash_file_count(workspace_root: &Path, stash_ref: &str) -> usize {
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["stash", "show", "--name-only", stash_ref])
-        .current_dir(workspace_root)
         .output();
 
     let base_count = match &output {
@@ -151,7 +149,7 @@ fn get_stash_file_count(workspace_root: &Path, stash_ref: &str) -> usize {
 
     // 加上未跟踪文件数
     let untracked_count = if has_untracked_parent(workspace_root, stash_ref) {
-        let ut_output = Command::new("git")
+        let ut_output = git_command(workspace_root)
             .args([
                 "diff-tree",
                 "--no-commit-id",
@@ -159,7 +157,6 @@ fn get_stash_file_count(workspace_root: &Path, stash_ref: &str) -> usize {
                 "-r",
                 &format!("{}^3", stash_ref),
             ])
-            .current_dir(workspace_root)
             .output();
         match ut_output {
             Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout)
@@ -211,7 +208,7 @@ fn get_untracked_files_in_stash(workspace_root: &Path, stash_ref: &str) -> Vec<S
     if !has_untracked_parent(workspace_root, stash_ref) {
         return vec![];
     }
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args([
             "diff-tree",
             "--no-commit-id",
@@ -219,7 +216,6 @@ fn get_untracked_files_in_stash(workspace_root: &Path, stash_ref: &str) -> Vec<S
             "-r",
             &format!("{}^3", stash_ref),
         ])
-        .current_dir(workspace_root)
         .output();
     match output {
         Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout)
@@ -271,9 +267,8 @@ pub fn git_stash_list(workspace_root: &Path) -> Result<StashListResult, GitError
     }
 
     // 使用可靠的 format 字符串：stash_id | ISO日期 | 消息
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["stash", "list", "--format=%gd|%ai|%gs"])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -339,9 +334,8 @@ pub fn git_stash_show(
     let stash_ref = normalize_stash_id(stash_id);
 
     // 获取 stash 条目信息
-    let list_output = Command::new("git")
+    let list_output = git_command(workspace_root)
         .args(["stash", "list", "--format=%gd|%ai|%gs"])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -388,9 +382,8 @@ pub fn git_stash_show(
         });
 
     // 获取 numstat 信息（tracked 文件）
-    let numstat_output = Command::new("git")
+    let numstat_output = git_command(workspace_root)
         .args(["stash", "show", "--numstat", &stash_ref])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -443,9 +436,8 @@ pub fn git_stash_show(
     let file_count = files.len();
 
     // 获取 diff 文本
-    let diff_output = Command::new("git")
+    let diff_output = git_command(workspace_root)
         .args(["stash", "show", "-p", &stash_ref])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -507,9 +499,8 @@ pub fn git_stash_save(
     let mut full_args = args;
     full_args.extend(path_strs.iter());
 
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(&full_args)
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -582,9 +573,8 @@ pub fn git_stash_apply(
     validate_stash_id(stash_id)?;
     let stash_ref = normalize_stash_id(stash_id);
 
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["stash", "apply", &stash_ref])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -652,9 +642,8 @@ pub fn git_stash_pop(
 
     if apply_result.state == StashOpState::Completed {
         // apply 成功后再 drop
-        let drop_output = Command::new("git")
+        let drop_output = git_command(workspace_root)
             .args(["stash", "drop", &stash_ref])
-            .current_dir(workspace_root)
             .output()
             .map_err(GitError::IoError)?;
 
@@ -706,9 +695,8 @@ pub fn git_stash_drop(
     validate_stash_id(stash_id)?;
     let stash_ref = normalize_stash_id(stash_id);
 
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["stash", "drop", &stash_ref])
-        .current_dir(workspace_root)
         .output()
         .map_err(GitError::IoError)?;
 
@@ -776,9 +764,8 @@ pub fn git_stash_restore_paths(
 
         if untracked_files.contains(path) {
             // 未跟踪文件：用 git show 从第三个 parent 提取
-            let show_output = Command::new("git")
+            let show_output = git_command(workspace_root)
                 .args(["show", &format!("{}^3:{}", stash_ref, path)])
-                .current_dir(workspace_root)
                 .output()
                 .map_err(GitError::IoError)?;
 
@@ -801,9 +788,8 @@ pub fn git_stash_restore_paths(
             }
         } else {
             // tracked 文件：使用 git checkout
-            let checkout_output = Command::new("git")
+            let checkout_output = git_command(workspace_root)
                 .args(["checkout", &stash_ref, "--", path])
-                .current_dir(workspace_root)
                 .output()
                 .map_err(GitError::IoError)?;
 
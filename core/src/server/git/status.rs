@@ -7,102 +7,21 @@ use gix::bstr::ByteSlice;
 use gix::status::index_worktree::iter::Summary;
 use std::collections::HashMap;
 use std::path::Path;
-use std::process::Command;
-use std::sync::{LazyLock, Mutex};
-use std::time::{Instant, SystemTime};
+use std::time::Instant;
 use tracing::debug;
 use tracing::warn;
 
+use super::backend::backend_for;
+use super::cache::{self, CacheLookup};
 use super::utils::*;
 use crate::server::perf as perf_counters;
-use crate::workspace::cache_metrics;
-
-// ── 指纹类型 ──
-
-/// 单个文件的 mtime + len 指纹（不感知内容，仅感知是否被修改过）
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct FileFingerprint {
-    mtime_ns: u64,
-    len: u64,
-}
-
-impl FileFingerprint {
-    fn from_path(path: &Path) -> Option<Self> {
-        let meta = std::fs::metadata(path).ok()?;
-        let mtime_ns = meta
-            .modified()
-            .ok()?
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .ok()?
-            .as_nanos() as u64;
-        Some(FileFingerprint {
-            mtime_ns,
-            len: meta.len(),
-        })
-    }
-}
-
-/// Git 状态缓存命中判定指纹。
-///
-/// 持有 `.git/index`、`.git/HEAD` 和当前分支 ref 文件的 mtime+len 指纹。
-/// 任一文件变化时指纹不匹配，触发重建。
-/// detached HEAD 或无法定位分支 ref 文件时，`branch_ref_fp` 为 None，
-/// 此时仍可依赖 `index_fp` 和 `head_fp` 进行命中判定。
-#[derive(Debug, Clone, PartialEq)]
-struct GitStatusFingerprint {
-    index_fp: Option<FileFingerprint>,
-    head_fp: Option<FileFingerprint>,
-    branch_ref_fp: Option<FileFingerprint>,
-}
-
-impl GitStatusFingerprint {
-    /// 计算当前工作区的 Git 文件指纹。无需打开仓库，仅 stat 3 个文件。
-    fn compute(workspace_root: &Path) -> Self {
-        let git_dir = workspace_root.join(".git");
-        let index_fp = FileFingerprint::from_path(&git_dir.join("index"));
-        let head_fp = FileFingerprint::from_path(&git_dir.join("HEAD"));
-
-        // 从 HEAD 内容解析当前分支 ref，找到对应 ref 文件
-        let branch_ref_fp = std::fs::read_to_string(git_dir.join("HEAD"))
-            .ok()
-            .and_then(|content| content.strip_prefix("ref: ").map(|s| s.trim().to_string()))
-            .and_then(|ref_name| FileFingerprint::from_path(&git_dir.join(&ref_name)));
-
-        GitStatusFingerprint {
-            index_fp,
-            head_fp,
-            branch_ref_fp,
-        }
-    }
-
-    /// 如果至少有一个文件指纹可采样，则可用于命中判定
-    fn is_sampable(&self) -> bool {
-        self.index_fp.is_some() || self.head_fp.is_some()
-    }
-}
-
-// ── 缓存结构 ──
-
-/// 缓存条目（含指纹，用于精确命中判定）
-struct CacheEntry {
-    result: GitStatusResult,
-    fingerprint: GitStatusFingerprint,
-    created_at: Instant,
-}
-
-/// 缓存 TTL 从 5s 升级到 30s，仅作为 watcher 丢事件时的保底淘汰。
-/// 主命中判定依赖指纹（index / HEAD / 分支 ref mtime+len），TTL 不再是主策略。
-const CACHE_TTL_SECS: u64 = 30;
-
-/// 全局 git status 缓存（key 包含 default_branch 以隔离不同分支配置）
-static GIT_STATUS_CACHE: LazyLock<Mutex<HashMap<String, CacheEntry>>> =
-    LazyLock::new(|| Mutex::new(HashMap::new()));
+use crate::util::exec_env::git_command;
 
 fn bstr_to_string(input: &gix::bstr::BStr) -> String {
     String::from_utf8_lossy(input.as_ref()).to_string()
 }
 
-fn git_time_to_iso(time: gix::date::Time) -> String {
+pub(super) fn git_time_to_iso(time: gix::date::Time) -> String {
     if let Some(offset) = chrono::FixedOffset::east_opt(time.offset) {
         if let chrono::LocalResult::Single(dt) = offset.timestamp_opt(time.seconds, 0) {
             return dt.to_rfc3339();
@@ -158,10 +77,22 @@ fn tree_index_change_to_entry(change: gix::diff::index::Change) -> GitStatusEntr
 }
 
 fn index_worktree_item_to_entry(item: gix::status::index_worktree::Item) -> Option<GitStatusEntry> {
-    let path = bstr_to_string(item.rela_path());
+    let mut path = bstr_to_string(item.rela_path());
     let mut orig_path = None;
-    if let gix::status::index_worktree::Item::Rewrite { source, .. } = &item {
-        orig_path = Some(bstr_to_string(source.rela_path()));
+    match &item {
+        gix::status::index_worktree::Item::Rewrite { source, .. } => {
+            orig_path = Some(bstr_to_string(source.rela_path()));
+        }
+        // 折叠的未跟踪目录（含嵌套仓库）与 cli 后端一致，以 `dir/` 表示
+        gix::status::index_worktree::Item::DirectoryContents { entry, .. }
+            if matches!(
+                entry.disk_kind,
+                Some(gix::dir::entry::Kind::Directory | gix::dir::entry::Kind::Repository)
+            ) && !path.ends_with('/') =>
+        {
+            path.push('/');
+        }
+        _ => {}
     }
 
     let summary = item.summary()?;
@@ -198,9 +129,9 @@ fn sort_status_items(items: &mut [GitStatusEntry]) {
 
 /// Get git status for a workspace.
 ///
-/// 缓存命中策略（优先级从高到低）：
-/// 1. 指纹命中（.git/index + HEAD + 分支 ref 均未变化）→ 直接返回
-/// 2. TTL 兜底（30s，用于 watcher 丢事件时的保底淘汰）→ 重建
+/// 缓存命中策略（优先级从高到低，见 [`super::cache`]）：
+/// 1. 指纹命中（index + HEAD + 分支 ref 均未变化）→ 直接返回
+/// 2. TTL 兜底（默认 30s，`[git] status_cache_ttl_secs` 可调）→ 重建
 /// 3. 首次调用 → 全量重建（冷路径）
 ///
 /// 冷路径一次性产出 status items、current_branch 和 divergence，
@@ -209,59 +140,130 @@ pub fn git_status(
     workspace_root: &Path,
     default_branch: &str,
 ) -> Result<GitStatusResult, GitError> {
-    let key = format!("{}#{}", workspace_root.to_string_lossy(), default_branch);
+    let key = cache::cache_key(workspace_root, default_branch);
     let refresh_started = Instant::now();
 
-    // 先计算指纹（不持锁，stat 3 个文件）
-    let current_fp = GitStatusFingerprint::compute(workspace_root);
-
-    if let Ok(cache) = GIT_STATUS_CACHE.lock() {
-        if let Some(entry) = cache.get(&key) {
-            let elapsed = entry.created_at.elapsed().as_secs();
-            if elapsed < CACHE_TTL_SECS {
-                let hit = if current_fp.is_sampable() {
-                    current_fp == entry.fingerprint
-                } else {
-                    // 无法采样指纹（非 git 目录或 .git 不可读），退化为 TTL
-                    true
-                };
-                if hit {
-                    perf_counters::record_workspace_git_status_refresh(
-                        refresh_started.elapsed().as_millis() as u64,
-                    );
-                    cache_metrics::record_git_cache_hit(&key);
-                    return Ok(entry.result.clone());
-                } else {
-                    cache_metrics::record_git_cache_eviction(&key, "fingerprint_changed");
-                }
-            } else {
-                cache_metrics::record_git_cache_eviction(&key, "ttl_expired");
-            }
+    let fingerprint = match cache::lookup(workspace_root, &key) {
+        CacheLookup::Hit(result) => {
+            perf_counters::record_workspace_git_status_refresh(
+                refresh_started.elapsed().as_millis() as u64,
+            );
+            return Ok(result);
         }
-    }
+        CacheLookup::Miss(fingerprint) => fingerprint,
+    };
 
     // 缓存未命中：冷路径全量重建
-    cache_metrics::record_git_cache_miss(&key);
-    let result = git_status_uncached(workspace_root, default_branch)?;
-    let item_count = result.items.len();
-    let refresh_ms = refresh_started.elapsed().as_millis() as u64;
-    perf_counters::record_workspace_git_status_refresh(refresh_ms);
-
-    if let Ok(mut cache) = GIT_STATUS_CACHE.lock() {
-        cache.insert(
-            key.clone(),
-            CacheEntry {
-                result: result.clone(),
-                fingerprint: current_fp,
-                created_at: Instant::now(),
-            },
-        );
-    }
-    cache_metrics::record_git_cache_rebuild(&key, item_count);
+    let result = backend_for(workspace_root).status(workspace_root, default_branch)?;
+    perf_counters::record_workspace_git_status_refresh(refresh_started.elapsed().as_millis() as u64);
+    cache::store(workspace_root, &key, &result, fingerprint);
 
     Ok(result)
 }
 
+/// 展开未跟踪目录时最多返回的文件数（所有目录合计）
+pub const MAX_UNTRACKED_EXPANSION: usize = 5000;
+
+/// 解析 `untracked_files` 参数：`normal`（默认，未跟踪目录折叠为一条）或 `all`（展开全部）
+pub fn parse_untracked_files_mode(mode: Option<&str>) -> Result<bool, GitError> {
+    match mode.map(str::trim).filter(|m| !m.is_empty()) {
+        None | Some("normal") => Ok(false),
+        Some("all") => Ok(true),
+        Some(other) => Err(GitError::CommandFailed(format!(
+            "Invalid untracked_files mode: {} (expected normal or all)",
+            other
+        ))),
+    }
+}
+
+/// 将 status 中折叠的未跟踪目录展开为其中的文件（遵循 `.gitignore`）
+///
+/// `all` 等价于 `git status -uall`；否则只展开 `dirs` 中列出的目录。展开按目录路径顺序进行，
+/// 展开后总数会超过 [`MAX_UNTRACKED_EXPANSION`] 的目录保持折叠，此时返回 `true`。
+/// 只修改传入的结果，不影响 status 缓存。
+pub fn expand_untracked_dirs(
+    workspace_root: &Path,
+    items: &mut Vec<GitStatusEntry>,
+    all: bool,
+    dirs: &[String],
+) -> Result<bool, GitError> {
+    expand_untracked_dirs_with_limit(workspace_root, items, all, dirs, MAX_UNTRACKED_EXPANSION)
+}
+
+fn expand_untracked_dirs_with_limit(
+    workspace_root: &Path,
+    items: &mut Vec<GitStatusEntry>,
+    all: bool,
+    dirs: &[String],
+    limit: usize,
+) -> Result<bool, GitError> {
+    let requested = |path: &str| {
+        all || dirs
+            .iter()
+            .any(|dir| dir.trim_end_matches('/') == path.trim_end_matches('/'))
+    };
+    let mut targets: Vec<String> = items
+        .iter()
+        // 两个后端都以结尾 `/` 表示折叠的未跟踪目录
+        .filter(|item| item.code == "??" && item.path.ends_with('/') && requested(&item.path))
+        .map(|item| item.path.clone())
+        .collect();
+    if targets.is_empty() {
+        return Ok(false);
+    }
+    targets.sort();
+
+    let output = crate::util::cancel::output(
+        git_command(workspace_root)
+            .args(["ls-files", "--others", "--exclude-standard", "-z", "--"])
+            .args(
+                targets
+                    .iter()
+                    .map(|dir| format!(":(literal){}", dir.trim_end_matches('/'))),
+            ),
+    )
+    .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        return Err(GitError::CommandFailed(format!(
+            "git ls-files failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let files: Vec<&str> = stdout.split('\0').filter(|f| !f.is_empty()).collect();
+
+    let mut expanded = 0;
+    let mut truncated = false;
+    for dir in targets {
+        let prefix = format!("{}/", dir.trim_end_matches('/'));
+        let inside: Vec<&str> = files
+            .iter()
+            .copied()
+            .filter(|file| file.starts_with(&prefix))
+            .collect();
+        // 嵌套仓库等无法展开的目录保持原样
+        if inside.is_empty() {
+            continue;
+        }
+        if expanded + inside.len() > limit {
+            truncated = true;
+            continue;
+        }
+        expanded += inside.len();
+        items.retain(|item| !(item.code == "??" && item.path == dir));
+        items.extend(inside.into_iter().map(|file| GitStatusEntry {
+            path: file.to_string(),
+            code: "??".to_string(),
+            orig_path: None,
+            staged: false,
+            additions: None,
+            deletions: None,
+        }));
+    }
+    sort_status_items(items);
+    Ok(truncated)
+}
+
 /// 获取当前分支名。
 pub fn git_current_branch(workspace_root: &Path) -> Result<Option<String>, GitError> {
     let repo = match gix::discover(workspace_root) {
@@ -276,7 +278,7 @@ pub fn git_current_branch(workspace_root: &Path) -> Result<Option<String>, GitEr
     Ok(head_name.map(|name| name.shorten().to_string()))
 }
 
-fn count_ahead_behind(
+pub(super) fn count_ahead_behind(
     repo: &gix::Repository,
     tip: gix::hash::ObjectId,
     hidden: gix::hash::ObjectId,
@@ -342,22 +344,6 @@ pub fn check_branch_divergence_local(
     })
 }
 
-/// 使指定工作区的 git status 缓存失效（清除所有 default_branch 变体）
-pub fn invalidate_git_status_cache(workspace_root: &Path) {
-    let root_prefix = format!("{}#", workspace_root.to_string_lossy());
-    if let Ok(mut cache) = GIT_STATUS_CACHE.lock() {
-        let keys_to_remove: Vec<String> = cache
-            .keys()
-            .filter(|k| k.starts_with(&root_prefix))
-            .cloned()
-            .collect();
-        for key in keys_to_remove {
-            cache.remove(&key);
-            cache_metrics::record_git_cache_eviction(&key, "invalidated");
-        }
-    }
-}
-
 /// 复用已打开仓库计算分支分歧（避免重复打开仓库）
 fn compute_divergence_from_repo(
     repo: &gix::Repository,
@@ -406,33 +392,36 @@ fn compute_divergence_from_repo(
     })
 }
 
-/// 实际执行 git status 查询（无缓存）。
+/// 非 Git 目录的空状态
+fn empty_status(default_branch: &str) -> GitStatusResult {
+    GitStatusResult {
+        repo_root: String::new(),
+        items: vec![],
+        has_staged_changes: false,
+        staged_count: 0,
+        current_branch: None,
+        default_branch: if default_branch.is_empty() {
+            None
+        } else {
+            Some(default_branch.to_string())
+        },
+        ahead_by: None,
+        behind_by: None,
+        compared_branch: None,
+    }
+}
+
+/// gix 后端：执行 git status 查询（无缓存）。
 ///
 /// 冷路径一次性产出 items、current_branch 和 divergence，
 /// 复用同一个已打开的仓库，避免重复 `gix::discover`。
-fn git_status_uncached(
+pub(super) fn git_status_gix(
     workspace_root: &Path,
     default_branch: &str,
 ) -> Result<GitStatusResult, GitError> {
     let repo = match gix::discover(workspace_root) {
         Ok(repo) => repo,
-        Err(_) => {
-            return Ok(GitStatusResult {
-                repo_root: String::new(),
-                items: vec![],
-                has_staged_changes: false,
-                staged_count: 0,
-                current_branch: None,
-                default_branch: if default_branch.is_empty() {
-                    None
-                } else {
-                    Some(default_branch.to_string())
-                },
-                ahead_by: None,
-                behind_by: None,
-                compared_branch: None,
-            });
-        }
+        Err(_) => return Ok(empty_status(default_branch)),
     };
 
     let repo_root = repo
@@ -509,10 +498,119 @@ fn git_status_uncached(
     })
 }
 
+/// cli 后端：`git status --porcelain=v1 -z` 解析为与 gix 后端一致的条目
+///
+/// 同时有暂存与未暂存变更的文件拆成两条（X 与 Y 各一条），冲突统一为 `U`。
+pub(super) fn git_status_cli(
+    workspace_root: &Path,
+    default_branch: &str,
+) -> Result<GitStatusResult, GitError> {
+    let Some(repo_root) = git_stdout(workspace_root, &["rev-parse", "--show-toplevel"]) else {
+        return Ok(empty_status(default_branch));
+    };
+
+    let output = git_command(workspace_root)
+        .args(["status", "--porcelain=v1", "-z"])
+        .output()
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        return Err(GitError::CommandFailed(format!(
+            "git status failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let mut items = parse_porcelain_status_z(&String::from_utf8_lossy(&output.stdout));
+    sort_status_items(&mut items);
+    let staged_count = items.iter().filter(|item| item.staged).count();
+
+    let current_branch = git_stdout(workspace_root, &["symbolic-ref", "--short", "-q", "HEAD"]);
+    let divergence = current_branch
+        .as_deref()
+        .filter(|branch| *branch != default_branch && !default_branch.is_empty())
+        .and_then(|branch| {
+            let range = format!("refs/heads/{}...refs/heads/{}", branch, default_branch);
+            git_stdout(
+                workspace_root,
+                &["rev-list", "--left-right", "--count", &range, "--"],
+            )
+        })
+        .and_then(|counts| {
+            let (ahead, behind) = counts.split_once(char::is_whitespace)?;
+            Some((ahead.trim().parse().ok()?, behind.trim().parse().ok()?))
+        });
+
+    let mut result = empty_status(default_branch);
+    result.repo_root = repo_root;
+    result.items = items;
+    result.has_staged_changes = staged_count > 0;
+    result.staged_count = staged_count;
+    result.current_branch = current_branch;
+    if let Some((ahead_by, behind_by)) = divergence {
+        result.ahead_by = Some(ahead_by);
+        result.behind_by = Some(behind_by);
+        result.compared_branch = Some(default_branch.to_string());
+    }
+    Ok(result)
+}
+
+/// 解析 `git status --porcelain=v1 -z` 输出；重命名 / 复制记录后紧跟原路径
+pub(super) fn parse_porcelain_status_z(stdout: &str) -> Vec<GitStatusEntry> {
+    let entry = |path: &str, code: &str, orig_path: Option<String>, staged: bool| GitStatusEntry {
+        path: path.to_string(),
+        code: code.to_string(),
+        orig_path,
+        staged,
+        additions: None,
+        deletions: None,
+    };
+    let mut items = Vec::new();
+    let mut records = stdout.split('\0').filter(|r| !r.is_empty());
+    while let Some(record) = records.next() {
+        let Some((xy, path)) = split_porcelain_record(record) else {
+            continue;
+        };
+        let mut codes = xy.chars();
+        let (Some(x), Some(y)) = (codes.next(), codes.next()) else {
+            continue;
+        };
+        let orig_path = if matches!(x, 'R' | 'C') {
+            records.next().map(str::to_string)
+        } else {
+            None
+        };
+        match (x, y) {
+            ('?', '?') => items.push(entry(path, "??", None, false)),
+            ('!', _) | (_, '!') => {}
+            ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D') => {
+                items.push(entry(path, "U", None, false))
+            }
+            _ => {
+                if x != ' ' {
+                    items.push(entry(path, &x.to_string(), orig_path, true));
+                }
+                if y != ' ' {
+                    items.push(entry(path, &y.to_string(), None, false));
+                }
+            }
+        }
+    }
+    items
+}
+
+/// 执行 git 子命令并返回去尾空白的 stdout；失败或输出为空时为 None
+fn git_stdout(workspace_root: &Path, args: &[&str]) -> Option<String> {
+    let output = git_command(workspace_root).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!stdout.is_empty()).then_some(stdout)
+}
+
 /// 获取单个文件的 git 状态码（仅 1 个查询）
 pub fn git_file_status(workspace_root: &Path, path: &str) -> Option<(String, bool)> {
     let started = Instant::now();
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args([
             "status",
             "--porcelain=v1",
@@ -520,7 +618,6 @@ pub fn git_file_status(workspace_root: &Path, path: &str) -> Option<(String, boo
             "--",
             path,
         ])
-        .current_dir(workspace_root)
         .output()
         .ok()?;
     if !output.status.success() {
@@ -539,17 +636,19 @@ pub fn git_file_status(workspace_root: &Path, path: &str) -> Option<(String, boo
     parsed
 }
 
-fn parse_porcelain_status_line(line: &str) -> Option<(String, bool)> {
-    let bytes = line.as_bytes();
-    if bytes.len() < 3 {
-        return None;
-    }
-
-    let x = bytes[0] as char;
-    let y = bytes[1] as char;
+/// 解析单行 porcelain v1 状态，返回 (状态码, 是否已暂存)
+///
+/// 优先报告暂存区（X）状态；忽略文件（`!!`）与格式不符的行返回 None。
+pub(super) fn parse_porcelain_status_line(line: &str) -> Option<(String, bool)> {
+    let (xy, _) = split_porcelain_record(line)?;
+    let mut codes = xy.chars();
+    let (x, y) = (codes.next()?, codes.next()?);
     if x == '?' && y == '?' {
         return Some(("??".to_string(), false));
     }
+    if x == '!' || y == '!' {
+        return None;
+    }
 
     if x != ' ' && x != '?' {
         return Some((x.to_string(), true));
@@ -561,31 +660,33 @@ fn parse_porcelain_status_line(line: &str) -> Option<(String, bool)> {
 }
 
 /// Get git log (commit history) for a workspace
-pub fn git_log(workspace_root: &Path, limit: usize) -> Result<GitLogResult, GitError> {
+///
+/// 经 [`GitBackend`](super::backend::GitBackend) 分派：gix 后端无过滤条件时直接遍历，
+/// 带分页游标或过滤条件时交给 `git log` 处理（路径、作者、消息与日期过滤）。
+/// 多取一条用于判断 `has_more`。
+pub fn git_log(
+    workspace_root: &Path,
+    limit: usize,
+    filter: &GitLogFilter,
+) -> Result<GitLogResult, GitError> {
+    backend_for(workspace_root).log(workspace_root, limit, filter)
+}
+
+/// gix 后端：无过滤条件时遍历提交历史（调用方保证 `filter` 为空）
+pub(super) fn git_log_gix(workspace_root: &Path, limit: usize) -> Result<GitLogResult, GitError> {
     let repo = gix::discover(workspace_root).map_err(|_| GitError::NotAGitRepo)?;
 
     let head_id = match repo.head_id() {
         Ok(id) => id.detach(),
-        Err(_) => return Ok(GitLogResult { entries: vec![] }),
+        Err(_) => {
+            return Ok(GitLogResult {
+                entries: vec![],
+                has_more: false,
+            })
+        }
     };
 
-    let mut refs_by_commit: HashMap<String, Vec<String>> = HashMap::new();
-    if let Ok(refs) = repo.references() {
-        if let Ok(iter) = refs.all() {
-            for item in iter {
-                let Ok(mut reference) = item else {
-                    continue;
-                };
-                let ref_name = reference.name().shorten().to_string();
-                if let Ok(id) = reference.peel_to_id() {
-                    refs_by_commit
-                        .entry(id.to_string())
-                        .or_default()
-                        .push(ref_name);
-                }
-            }
-        }
-    }
+    let mut refs_by_commit = refs_by_commit(&repo);
 
     let walk = repo
         .rev_walk([head_id])
@@ -596,7 +697,12 @@ pub fn git_log(workspace_root: &Path, limit: usize) -> Result<GitLogResult, GitE
         .map_err(|e| GitError::CommandFailed(format!("Failed to walk git history: {}", e)))?;
 
     let mut entries = Vec::new();
-    for info in walk.take(limit) {
+    let mut has_more = false;
+    for info in walk.take(limit.saturating_add(1)) {
+        if entries.len() == limit {
+            has_more = true;
+            break;
+        }
         let info = info
             .map_err(|e| GitError::CommandFailed(format!("Failed to read commit info: {}", e)))?;
         let commit = info
@@ -624,7 +730,188 @@ pub fn git_log(workspace_root: &Path, limit: usize) -> Result<GitLogResult, GitE
         });
     }
 
-    Ok(GitLogResult { entries })
+    Ok(GitLogResult { entries, has_more })
+}
+
+/// 提交 SHA → 指向它的引用短名
+fn refs_by_commit(repo: &gix::Repository) -> HashMap<String, Vec<String>> {
+    let mut refs_by_commit: HashMap<String, Vec<String>> = HashMap::new();
+    if let Ok(refs) = repo.references() {
+        if let Ok(iter) = refs.all() {
+            for item in iter {
+                let Ok(mut reference) = item else {
+                    continue;
+                };
+                let ref_name = reference.name().shorten().to_string();
+                if let Ok(id) = reference.peel_to_id() {
+                    refs_by_commit
+                        .entry(id.to_string())
+                        .or_default()
+                        .push(ref_name);
+                }
+            }
+        }
+    }
+    refs_by_commit
+}
+
+/// 提交 SHA → 指向它的引用短名（`git for-each-ref`，附注标签按其指向的提交归类）
+fn refs_by_commit_cli(workspace_root: &Path) -> HashMap<String, Vec<String>> {
+    let mut refs_by_commit: HashMap<String, Vec<String>> = HashMap::new();
+    let Some(stdout) = git_stdout(
+        workspace_root,
+        &[
+            "for-each-ref",
+            "--format=%(objectname) %(*objectname) %(refname:short)",
+        ],
+    ) else {
+        return refs_by_commit;
+    };
+    for line in stdout.lines() {
+        let mut fields = line.splitn(3, ' ');
+        let (Some(object), Some(peeled), Some(name)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let target = if peeled.is_empty() { object } else { peeled };
+        refs_by_commit
+            .entry(target.to_string())
+            .or_default()
+            .push(name.to_string());
+    }
+    refs_by_commit
+}
+
+/// 拒绝会被 git 当作选项解析的参数
+fn reject_option_like(name: &str, value: &str) -> Result<(), GitError> {
+    if value.starts_with('-') {
+        return Err(GitError::CommandFailed(format!(
+            "Invalid {} filter: {}",
+            name, value
+        )));
+    }
+    Ok(())
+}
+
+/// cli 后端：`git log` 查询提交历史，支持分页游标与全部过滤条件
+pub(super) fn git_log_cli(
+    workspace_root: &Path,
+    limit: usize,
+    filter: &GitLogFilter,
+) -> Result<GitLogResult, GitError> {
+    if git_stdout(workspace_root, &["rev-parse", "--git-dir"]).is_none() {
+        return Err(GitError::NotAGitRepo);
+    }
+    let empty = GitLogResult {
+        entries: vec![],
+        has_more: false,
+    };
+
+    let start = match filter.before_sha.as_deref() {
+        Some(sha) => {
+            if sha.is_empty() || sha.len() > 40 || !sha.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(GitError::CommandFailed("Invalid SHA format".to_string()));
+            }
+            let revision = format!("{}^{{commit}}", sha);
+            let parents = git_stdout(
+                workspace_root,
+                &["rev-list", "--parents", "-n", "1", &revision, "--"],
+            )
+            .ok_or_else(|| GitError::CommandFailed(format!("Invalid revision '{}'", sha)))?;
+            let mut ids = parents.split_whitespace();
+            let commit_id = ids.next().unwrap_or_default().to_string();
+            // 根提交之前没有历史
+            if ids.next().is_none() {
+                return Ok(empty);
+            }
+            format!("{}^@", commit_id)
+        }
+        None => match filter.branch.as_deref() {
+            Some(branch) => {
+                reject_option_like("branch", branch)?;
+                branch.to_string()
+            }
+            None => {
+                if git_stdout(workspace_root, &["rev-parse", "--verify", "-q", "HEAD"]).is_none() {
+                    return Ok(empty);
+                }
+                "HEAD".to_string()
+            }
+        },
+    };
+
+    let mut args = vec![
+        "log".to_string(),
+        "--no-color".to_string(),
+        "--date-order".to_string(),
+        "--format=%H%x00%an%x00%cI%x00%B%x1e".to_string(),
+        format!("--max-count={}", limit.saturating_add(1)),
+    ];
+    if filter.skip > 0 {
+        args.push(format!("--skip={}", filter.skip));
+    }
+    if filter.author.is_some() || filter.grep.is_some() {
+        args.push("--fixed-strings".to_string());
+        args.push("--regexp-ignore-case".to_string());
+    }
+    if let Some(author) = filter.author.as_deref() {
+        args.push(format!("--author={}", author));
+    }
+    if let Some(grep) = filter.grep.as_deref() {
+        args.push(format!("--grep={}", grep));
+    }
+    if let Some(since) = filter.since.as_deref() {
+        reject_option_like("since", since)?;
+        args.push(format!("--since={}", since));
+    }
+    if let Some(until) = filter.until.as_deref() {
+        reject_option_like("until", until)?;
+        args.push(format!("--until={}", until));
+    }
+    args.push(start);
+    args.push("--".to_string());
+    if let Some(path) = filter.path.as_deref() {
+        validate_path(workspace_root, path)?;
+        args.push(path.to_string());
+    }
+
+    let output = git_command(workspace_root)
+        .args(&args)
+        .output()
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(GitError::CommandFailed(format!(
+            "git log failed: {}",
+            stderr
+        )));
+    }
+
+    let mut refs_by_commit = refs_by_commit_cli(workspace_root);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut entries = Vec::new();
+    let mut has_more = false;
+    for record in split_log_records(&stdout, '\0') {
+        if entries.len() == limit {
+            has_more = true;
+            break;
+        }
+        let mut fields = record.splitn(4, '\0');
+        let full_sha = fields.next().unwrap_or_default().to_string();
+        let author = fields.next().unwrap_or("Unknown").to_string();
+        let date = fields.next().unwrap_or_default().to_string();
+        let message = fields.next().unwrap_or_default().trim().to_string();
+        entries.push(GitLogEntry {
+            sha: full_sha.chars().take(7).collect(),
+            message,
+            author,
+            date,
+            refs: refs_by_commit.remove(&full_sha).unwrap_or_default(),
+        });
+    }
+
+    Ok(GitLogResult { entries, has_more })
 }
 
 /// Get details for a single commit
@@ -731,6 +1018,176 @@ pub fn git_show(workspace_root: &Path, sha: &str) -> Result<GitShowResult, GitEr
     })
 }
 
+/// 获取某个提交中单个文件的 unified diff（相对第一父提交；根提交相对空树）
+///
+/// `path` 必须是该提交变更列表（`git_show`）中的路径；重命名/复制时
+/// 同时传入原路径，使 diff 以重命名形式呈现而不是一删一增。
+pub fn git_show_file_diff(
+    workspace_root: &Path,
+    sha: &str,
+    path: &str,
+    algorithm: Option<&str>,
+) -> Result<GitShowFileDiffResult, GitError> {
+    let algorithm = super::operations::resolve_diff_algorithm(workspace_root, algorithm)?;
+    let show = git_show(workspace_root, sha)?;
+    let Some(entry) = show.files.into_iter().find(|f| f.path == path) else {
+        return Err(GitError::CommandFailed(format!(
+            "File '{}' is not changed in commit {}",
+            path, show.sha
+        )));
+    };
+
+    let mut args = vec![
+        "--literal-pathspecs".to_string(),
+        "show".to_string(),
+        "--format=".to_string(),
+        "--no-color".to_string(),
+        "--no-ext-diff".to_string(),
+        "--diff-merges=first-parent".to_string(),
+        "-M".to_string(),
+    ];
+    if let Some(name) = algorithm {
+        args.push(format!("--diff-algorithm={}", name));
+    }
+    args.push(show.full_sha.clone());
+    args.push("--".to_string());
+    if let Some(old_path) = &entry.old_path {
+        args.push(old_path.clone());
+    }
+    args.push(entry.path.clone());
+
+    let output = git_command(workspace_root)
+        .args(&args)
+        .output()
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        return Err(GitError::CommandFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    let raw = String::from_utf8_lossy(&output.stdout);
+    let is_binary = raw
+        .lines()
+        .any(|line| line.starts_with("Binary files ") && line.ends_with(" differ"));
+    let (text, truncated) = if is_binary {
+        (String::new(), false)
+    } else {
+        truncate_if_needed(&raw)
+    };
+
+    Ok(GitShowFileDiffResult {
+        full_sha: show.full_sha,
+        old_path: entry.old_path,
+        diff: GitDiffResult {
+            path: entry.path,
+            code: entry.status,
+            format: "unified".to_string(),
+            text,
+            is_binary,
+            truncated,
+            mode: "commit".to_string(),
+            smart_diff: None,
+        },
+    })
+}
+
+/// 读取文件在指定修订版本（分支、标签、SHA、`HEAD~1` 等）中的内容（`git show <rev>:<path>`）
+///
+/// 路径相对工作区根；该修订中不存在此文件时返回 `Ok(None)`。
+/// 大小超过 `max_size` 时只返回大小，不读取内容。
+pub fn git_file_at_revision(
+    workspace_root: &Path,
+    rev: &str,
+    path: &str,
+    max_size: u64,
+) -> Result<Option<GitFileAtRevision>, GitError> {
+    let rev = rev.trim();
+    if rev.is_empty()
+        || rev.starts_with('-')
+        || rev.contains(':')
+        || rev.chars().any(char::is_whitespace)
+    {
+        return Err(GitError::CommandFailed(format!(
+            "Invalid revision: {}",
+            rev
+        )));
+    }
+    let path = path.trim_start_matches("./");
+    if path.is_empty() || path.starts_with('/') {
+        return Err(GitError::CommandFailed(format!("Invalid path: {}", path)));
+    }
+    validate_path(workspace_root, path)?;
+    if get_git_repo_root(workspace_root).is_none() {
+        return Err(GitError::NotAGitRepo);
+    }
+
+    let output = git_command(workspace_root)
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("{}^{{commit}}", rev))
+        .output()
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        return Err(GitError::CommandFailed(format!(
+            "Revision not found: {}",
+            rev
+        )));
+    }
+    let sha = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    // `<sha>:./<path>` 相对当前目录解析，工作区位于仓库子目录时同样适用
+    let mut child = git_command(workspace_root)
+        .args(["cat-file", "--batch-check"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .map_err(GitError::IoError)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        use std::io::Write;
+        writeln!(stdin, "{}:./{}", sha, path).map_err(GitError::IoError)?;
+    }
+    let output = child.wait_with_output().map_err(GitError::IoError)?;
+    let check = String::from_utf8_lossy(&output.stdout);
+    // 缺失时输出 `<对象名> missing`，对象名中可能含空格
+    if check.trim_end().ends_with(" missing") {
+        return Ok(None);
+    }
+    let mut fields = check.split_whitespace();
+    let (Some(oid), Some(kind), Some(size)) = (fields.next(), fields.next(), fields.next()) else {
+        return Ok(None);
+    };
+    if kind != "blob" {
+        return Err(GitError::CommandFailed(format!(
+            "Not a file at revision {}: {}",
+            rev, path
+        )));
+    }
+    let size: u64 = size
+        .parse()
+        .map_err(|_| GitError::CommandFailed(format!("Unexpected cat-file output: {}", check)))?;
+    if size > max_size {
+        return Ok(Some(GitFileAtRevision {
+            sha,
+            size,
+            content: None,
+        }));
+    }
+
+    let output = git_command(workspace_root)
+        .args(["cat-file", "blob", oid])
+        .output()
+        .map_err(GitError::IoError)?;
+    if !output.status.success() {
+        return Err(GitError::CommandFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(Some(GitFileAtRevision {
+        sha,
+        size,
+        content: Some(output.stdout),
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -934,14 +1391,6 @@ mod tests {
 
     // ── 热点路径定向测试（WI-005 / CHK-002）──
 
-    #[test]
-    fn hotspot_perf_git_fingerprint_is_sampable_on_nonexistent_path() {
-        // 非 Git 仓库或路径不存在时，指纹应标记为 not sampable，不 panic
-        let root = std::path::Path::new("/nonexistent/no_such_repo");
-        let fp = GitStatusFingerprint::compute(root);
-        assert!(!fp.is_sampable(), "non-existent path must not be sampable");
-    }
-
     #[test]
     fn hotspot_perf_git_status_non_git_dir_returns_empty() {
         // 对非 Git 目录调用 git_status 应返回空结果，不 panic 或返回错误串
@@ -961,48 +1410,142 @@ mod tests {
         let _ = std::fs::remove_dir_all(&tmp);
     }
 
+    fn run_git_cmd(dir: &Path, args: &[&str]) {
+        let status = git_command(dir)
+            .args(args)
+            .output()
+            .expect("run git")
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
     #[test]
-    fn hotspot_perf_invalidate_clears_all_branch_variants() {
-        // 验证 invalidate 按前缀匹配清除所有关联 key（多分支场景）
-        use std::path::Path;
-        let root = Path::new("/tmp/hotspot_invalidate_test");
-        let key_main = format!("{}#main", root.to_string_lossy());
-        let key_feat = format!("{}#feature/foo", root.to_string_lossy());
-
-        // 直接向缓存插入两个变体
-        {
-            let mut cache = GIT_STATUS_CACHE.lock().unwrap();
-            let make_entry = || CacheEntry {
-                result: GitStatusResult {
-                    repo_root: root.to_string_lossy().to_string(),
-                    items: vec![],
-                    has_staged_changes: false,
-                    staged_count: 0,
-                    current_branch: None,
-                    default_branch: None,
-                    ahead_by: None,
-                    behind_by: None,
-                    compared_branch: None,
-                },
-                created_at: std::time::Instant::now(),
-                fingerprint: GitStatusFingerprint::compute(root),
-            };
-            cache.insert(key_main.clone(), make_entry());
-            cache.insert(key_feat.clone(), make_entry());
+    fn test_git_log_pagination_and_filters() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        run_git_cmd(root, &["init", "-q", "-b", "main"]);
+        run_git_cmd(root, &["config", "user.name", "Alice"]);
+        run_git_cmd(root, &["config", "user.email", "alice@example.com"]);
+        run_git_cmd(root, &["config", "commit.gpgsign", "false"]);
+        for idx in 1..=4 {
+            std::fs::write(root.join(format!("f{}.txt", idx % 2)), idx.to_string()).unwrap();
+            run_git_cmd(root, &["add", "-A"]);
+            // 固定提交时间，保证按时间排序稳定
+            let date = format!("2024-01-0{}T00:00:00Z", idx);
+            let status = git_command(root)
+                .args(["commit", "-q", "-m", &format!("change {}", idx)])
+                .env("GIT_AUTHOR_DATE", &date)
+                .env("GIT_COMMITTER_DATE", &date)
+                .status()
+                .expect("run git commit");
+            assert!(status.success());
         }
 
-        // 调用 invalidate 清除
-        invalidate_git_status_cache(root);
+        let page = git_log(root, 2, &GitLogFilter::default()).unwrap();
+        assert_eq!(page.entries.len(), 2);
+        assert!(page.has_more);
+        assert_eq!(page.entries[0].message, "change 4");
 
-        // 两个 key 都应被清除
-        let cache = GIT_STATUS_CACHE.lock().unwrap();
-        assert!(
-            !cache.contains_key(&key_main),
-            "main variant should be cleared"
-        );
-        assert!(
-            !cache.contains_key(&key_feat),
-            "feature variant should be cleared"
-        );
+        let cursor = GitLogFilter {
+            before_sha: Some(page.entries[1].sha.clone()),
+            ..Default::default()
+        };
+        let next = git_log(root, 10, &cursor).unwrap();
+        let messages: Vec<_> = next.entries.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["change 2", "change 1"]);
+        assert!(!next.has_more);
+
+        let skipped = GitLogFilter {
+            skip: 3,
+            ..Default::default()
+        };
+        let last = git_log(root, 10, &skipped).unwrap();
+        assert_eq!(last.entries.len(), 1);
+        // 根提交之前没有历史
+        let before_root = GitLogFilter {
+            before_sha: Some(last.entries[0].sha.clone()),
+            ..Default::default()
+        };
+        assert!(git_log(root, 10, &before_root).unwrap().entries.is_empty());
+
+        let by_path = GitLogFilter {
+            path: Some("f1.txt".to_string()),
+            grep: Some("CHANGE".to_string()),
+            author: Some("alice".to_string()),
+            ..Default::default()
+        };
+        let messages: Vec<_> = git_log(root, 10, &by_path)
+            .unwrap()
+            .entries
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(messages, vec!["change 3", "change 1"]);
+
+        let by_author = GitLogFilter {
+            author: Some("bob".to_string()),
+            ..Default::default()
+        };
+        assert!(git_log(root, 10, &by_author).unwrap().entries.is_empty());
+
+        let option_like = GitLogFilter {
+            branch: Some("--all".to_string()),
+            ..Default::default()
+        };
+        assert!(git_log(root, 10, &option_like).is_err());
+    }
+
+    #[test]
+    fn expand_untracked_dirs_lists_files_respecting_gitignore() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        run_git_cmd(root, &["init", "-q"]);
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        std::fs::create_dir_all(root.join("new/deep")).unwrap();
+        std::fs::write(root.join("new/a.txt"), "a").unwrap();
+        std::fs::write(root.join("new/deep/b.txt"), "b").unwrap();
+        std::fs::write(root.join("new/skip.log"), "x").unwrap();
+        std::fs::create_dir_all(root.join("other")).unwrap();
+        std::fs::write(root.join("other/c.txt"), "c").unwrap();
+
+        let paths = |items: &[GitStatusEntry]| -> Vec<String> {
+            items.iter().map(|item| item.path.clone()).collect()
+        };
+        let cli = git_status_cli(root, "main").unwrap().items;
+        let gix = git_status_gix(root, "main").unwrap().items;
+        assert_eq!(paths(&cli), vec![".gitignore", "new/", "other/"]);
+        assert_eq!(paths(&gix), paths(&cli));
+
+        for status in [&cli, &gix] {
+            for dir in ["new", "new/"] {
+                let mut items = status.clone();
+                let truncated =
+                    expand_untracked_dirs(root, &mut items, false, &[dir.to_string()]).unwrap();
+                assert!(!truncated);
+                assert_eq!(
+                    paths(&items),
+                    vec![".gitignore", "new/a.txt", "new/deep/b.txt", "other/"]
+                );
+                assert!(items.iter().all(|item| item.code == "??" && !item.staged));
+            }
+
+            let mut items = status.clone();
+            assert!(!expand_untracked_dirs(root, &mut items, true, &[]).unwrap());
+            assert_eq!(
+                paths(&items),
+                vec![".gitignore", "new/a.txt", "new/deep/b.txt", "other/c.txt"]
+            );
+
+            // 超出上限的目录保持折叠，其余目录照常展开
+            let mut items = status.clone();
+            assert!(expand_untracked_dirs_with_limit(root, &mut items, true, &[], 2).unwrap());
+            assert_eq!(
+                paths(&items),
+                vec![".gitignore", "new/a.txt", "new/deep/b.txt", "other/"]
+            );
+            let mut items = status.clone();
+            assert!(expand_untracked_dirs_with_limit(root, &mut items, true, &[], 1).unwrap());
+            assert_eq!(paths(&items), vec![".gitignore", "new/", "other/c.txt"]);
+        }
     }
 }
@@ -0,0 +1,454 @@
+//! gix 生成单文件 unified diff
+//!
+//! 输出与 `git diff` 逐字节一致：`diff --git` 头、新增 / 删除模式行、按 git 规则缩写的 `index` 行、
+//! `---` / `+++`、hunk 头（长度为 1 时省略 `,1`）、默认规则的函数名上下文与
+//! `\ No newline at end of file` 标记；hunk 经缩进启发式后处理，与 git 默认的
+//! `diff.indentHeuristic` 落点一致。
+//!
+//! 无法保证一致的情形返回 `Ok(None)`，由调用方回退到命令行：指定 base、patience 算法、
+//! 存在属性文件（diff 驱动 / textconv / eol 过滤）、影响输出格式的 diff 配置、文件模式变化、
+//! 冲突或 intent-to-add 条目、符号链接 / 子模块、需要转义的路径与二进制内容。
+
+use std::borrow::Cow;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use gix::bstr::ByteSlice;
+use imara_diff::{Algorithm, Diff, Hunk, InternedInput};
+
+use super::backend::DiffTarget;
+use super::utils::*;
+
+/// hunk 前后的上下文行数（git 默认值）
+const CONTEXT_LINES: u32 = 3;
+
+/// 函数名上下文的最大字节数（与 git 一致）
+const FUNC_CONTEXT_MAX_BYTES: usize = 80;
+
+/// git 判断二进制时检查的前缀长度
+const BINARY_PROBE_BYTES: usize = 8000;
+
+const MODE_FILE: u32 = 0o100644;
+const MODE_EXECUTABLE: u32 = 0o100755;
+
+/// 影响 `git diff` 输出而此实现未模拟的配置项，任一已设置即回退
+const UNSUPPORTED_CONFIG_KEYS: &[&str] = &[
+    "core.abbrev",
+    "core.attributesFile",
+    "core.quotePath",
+    "diff.context",
+    "diff.interHunkContext",
+    "diff.noprefix",
+    "diff.mnemonicPrefix",
+    "diff.srcPrefix",
+    "diff.dstPrefix",
+    "diff.external",
+    "diff.suppressBlankEmpty",
+    "diff.indentHeuristic",
+];
+
+/// diff 一侧的文件版本
+struct Side {
+    id: gix::ObjectId,
+    mode: u32,
+    data: Vec<u8>,
+}
+
+/// 用 gix 生成 `path` 的 unified diff；无变化时为空串，需回退命令行时为 `None`
+pub(super) fn gix_file_diff(
+    workspace_root: &Path,
+    path: &str,
+    target: DiffTarget<'_>,
+    algorithm: Option<&str>,
+) -> Result<Option<String>, GitError> {
+    if matches!(target, DiffTarget::Base(_)) || path_needs_quoting(path) {
+        return Ok(None);
+    }
+    let repo = gix::discover(workspace_root).map_err(|_| GitError::NotAGitRepo)?;
+    let Some(workdir) = repo.workdir().map(Path::to_path_buf) else {
+        return Ok(None);
+    };
+    // 路径相对 workspace 根，与仓库根不一致时 git 输出的路径会带前缀
+    if !same_dir(&workdir, workspace_root) {
+        return Ok(None);
+    }
+
+    let config = repo.config_snapshot();
+    if UNSUPPORTED_CONFIG_KEYS
+        .iter()
+        .any(|key| config.string(*key).is_some())
+        || config.boolean("core.autocrlf").unwrap_or(false)
+        || config
+            .string("core.autocrlf")
+            .is_some_and(|v| v.eq_ignore_ascii_case(b"input"))
+    {
+        return Ok(None);
+    }
+    let configured_algorithm = config.string("diff.algorithm").map(|v| v.to_string());
+    let Some(algorithm) = imara_algorithm(algorithm.or(configured_algorithm.as_deref())) else {
+        return Ok(None);
+    };
+    if has_attributes_file(&repo, &workdir, path) {
+        return Ok(None);
+    }
+    let file_mode = config.boolean("core.fileMode").unwrap_or(true);
+
+    let (old, new) = match target {
+        DiffTarget::Working => {
+            // 未跟踪文件由调用方按 Untracked 处理
+            let Some(Some(index_side)) = index_side(&repo, path)? else {
+                return Ok(None);
+            };
+            let worktree_mode = (!file_mode).then_some(index_side.mode);
+            let Some(worktree_side) = worktree_side(&repo, &workdir, path, worktree_mode)? else {
+                return Ok(None);
+            };
+            (Some(index_side), worktree_side)
+        }
+        DiffTarget::Staged => {
+            let (Some(head_side), Some(index_side)) =
+                (head_side(&repo, path)?, index_side(&repo, path)?)
+            else {
+                return Ok(None);
+            };
+            (head_side, index_side)
+        }
+        DiffTarget::Untracked => {
+            let Some(worktree_side) = worktree_side(&repo, &workdir, path, None)? else {
+                return Ok(None);
+            };
+            (None, worktree_side)
+        }
+        DiffTarget::Base(_) => return Ok(None),
+    };
+
+    let text = match (old, new) {
+        (None, None) => String::new(),
+        (Some(old), Some(new)) if old.mode != new.mode => return Ok(None),
+        (Some(old), Some(new)) if old.id == new.id => String::new(),
+        (old, new) => {
+            if [&old, &new]
+                .into_iter()
+                .flatten()
+                .any(|side| is_binary(&side.data))
+            {
+                return Ok(None);
+            }
+            let hex_len = abbrev_len(&repo);
+            render_file_diff(path, old.as_ref(), new.as_ref(), algorithm, hex_len)
+        }
+    };
+    Ok(Some(text))
+}
+
+/// 与 git 的 `--diff-algorithm` 对应；patience 与无法识别的名称返回 None
+fn imara_algorithm(name: Option<&str>) -> Option<Algorithm> {
+    match normalize_diff_algorithm(name).ok()?.as_deref() {
+        None | Some("myers") => Some(Algorithm::Myers),
+        Some("minimal") => Some(Algorithm::MyersMinimal),
+        Some("histogram") => Some(Algorithm::Histogram),
+        _ => None,
+    }
+}
+
+/// git 默认（`core.quotePath=true`）会给控制字符、引号、反斜杠与非 ASCII 路径加引号转义
+fn path_needs_quoting(path: &str) -> bool {
+    path.bytes()
+        .any(|b| b < 0x20 || b == 0x7f || b == b'"' || b == b'\\' || b >= 0x80)
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// 文件所在各级目录的 `.gitattributes`、`info/attributes` 或全局属性文件存在时，
+/// git 可能对内容做过滤或换用 diff 驱动
+fn has_attributes_file(repo: &gix::Repository, workdir: &Path, path: &str) -> bool {
+    let mut candidates: Vec<PathBuf> = vec![repo.common_dir().join("info").join("attributes")];
+    let mut dir = workdir.to_path_buf();
+    candidates.push(dir.join(".gitattributes"));
+    if let Some((parents, _)) = path.rsplit_once('/') {
+        for component in parents.split('/') {
+            dir.push(component);
+            candidates.push(dir.join(".gitattributes"));
+        }
+    }
+    let xdg_config = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")));
+    if let Some(xdg_config) = xdg_config {
+        candidates.push(xdg_config.join("git").join("attributes"));
+    }
+    candidates.iter().any(|candidate| candidate.exists())
+}
+
+/// 暂存区中的版本：外层 None 表示需回退，内层 None 表示暂存区中不存在
+fn index_side(repo: &gix::Repository, path: &str) -> Result<Option<Option<Side>>, GitError> {
+    let index = repo
+        .index_or_empty()
+        .map_err(|e| GitError::CommandFailed(format!("Failed to read index: {}", e)))?;
+    let Some(range) = index.entry_range(path.as_bytes().as_bstr()) else {
+        return Ok(Some(None));
+    };
+    let entry = &index.entries()[range.start];
+    let skip = gix::index::entry::Flags::INTENT_TO_ADD
+        | gix::index::entry::Flags::SKIP_WORKTREE
+        | gix::index::entry::Flags::ASSUME_VALID;
+    if range.len() != 1 || entry.stage_raw() != 0 || entry.flags.intersects(skip) {
+        return Ok(None);
+    }
+    let mode = match entry.mode {
+        gix::index::entry::Mode::FILE => MODE_FILE,
+        gix::index::entry::Mode::FILE_EXECUTABLE => MODE_EXECUTABLE,
+        _ => return Ok(None),
+    };
+    Ok(Some(Some(Side {
+        id: entry.id,
+        mode,
+        data: read_blob(repo, entry.id)?,
+    })))
+}
+
+/// HEAD 中的版本：外层 None 表示需回退，内层 None 表示 HEAD 中不存在
+fn head_side(repo: &gix::Repository, path: &str) -> Result<Option<Option<Side>>, GitError> {
+    let Ok(commit) = repo.head_commit() else {
+        return Ok(None);
+    };
+    let tree = commit
+        .tree()
+        .map_err(|e| GitError::CommandFailed(format!("Failed to read HEAD tree: {}", e)))?;
+    let entry = tree
+        .lookup_entry_by_path(path)
+        .map_err(|e| GitError::CommandFailed(format!("Failed to look up '{}': {}", path, e)))?;
+    let Some(entry) = entry else {
+        return Ok(Some(None));
+    };
+    let mode = match entry.mode().kind() {
+        gix::object::tree::EntryKind::Blob => MODE_FILE,
+        gix::object::tree::EntryKind::BlobExecutable => MODE_EXECUTABLE,
+        _ => return Ok(None),
+    };
+    let id = entry.object_id();
+    Ok(Some(Some(Side {
+        id,
+        mode,
+        data: read_blob(repo, id)?,
+    })))
+}
+
+/// 工作区中的版本：外层 None 表示需回退，内层 None 表示文件不存在
+///
+/// `mode` 为 `Some` 时沿用给定模式（`core.fileMode=false`）。
+fn worktree_side(
+    repo: &gix::Repository,
+    workdir: &Path,
+    path: &str,
+    mode: Option<u32>,
+) -> Result<Option<Option<Side>>, GitError> {
+    let full_path = workdir.join(path);
+    let metadata = match std::fs::symlink_metadata(&full_path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Some(None)),
+        Err(e) => return Err(GitError::IoError(e)),
+    };
+    if !metadata.is_file() {
+        return Ok(None);
+    }
+    let data = std::fs::read(&full_path).map_err(GitError::IoError)?;
+    let id = gix::objs::compute_hash(repo.object_hash(), gix::objs::Kind::Blob, &data)
+        .map_err(|e| GitError::CommandFailed(format!("Failed to hash '{}': {}", path, e)))?;
+    Ok(Some(Some(Side {
+        id,
+        mode: mode.unwrap_or_else(|| worktree_mode(&metadata)),
+        data,
+    })))
+}
+
+#[cfg(unix)]
+fn worktree_mode(metadata: &std::fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    if metadata.permissions().mode() & 0o111 != 0 {
+        MODE_EXECUTABLE
+    } else {
+        MODE_FILE
+    }
+}
+
+#[cfg(not(unix))]
+fn worktree_mode(_metadata: &std::fs::Metadata) -> u32 {
+    MODE_FILE
+}
+
+fn read_blob(repo: &gix::Repository, id: gix::ObjectId) -> Result<Vec<u8>, GitError> {
+    repo.find_blob(id)
+        .map(|blob| blob.data.clone())
+        .map_err(|e| GitError::CommandFailed(format!("Failed to read blob {}: {}", id, e)))
+}
+
+fn is_binary(data: &[u8]) -> bool {
+    data[..data.len().min(BINARY_PROBE_BYTES)].contains(&0)
+}
+
+/// git 的自动缩写长度：按打包对象数估算，最少 7 位
+fn abbrev_len(repo: &gix::Repository) -> usize {
+    let count = repo.objects.packed_object_count().unwrap_or(0);
+    let bits = 64 - count.leading_zeros();
+    (bits.div_ceil(2) as usize).max(7)
+}
+
+fn render_file_diff(
+    path: &str,
+    old: Option<&Side>,
+    new: Option<&Side>,
+    algorithm: Algorithm,
+    hex_len: usize,
+) -> String {
+    let null_id = gix::ObjectId::null(
+        old.or(new)
+            .map(|side| side.id.kind())
+            .unwrap_or(gix::hash::Kind::Sha1),
+    );
+    let old_id = old.map_or(null_id, |side| side.id);
+    let new_id = new.map_or(null_id, |side| side.id);
+    let name_tab = if path.contains(' ') { "\t" } else { "" };
+
+    let mut out: Vec<u8> = Vec::new();
+    out.extend_from_slice(format!("diff --git a/{0} b/{0}\n", path).as_bytes());
+    match (old, new) {
+        (None, Some(new)) => {
+            out.extend_from_slice(format!("new file mode {:o}\n", new.mode).as_bytes())
+        }
+        (Some(old), None) => {
+            out.extend_from_slice(format!("deleted file mode {:o}\n", old.mode).as_bytes())
+        }
+        _ => {}
+    }
+    let index_line = format!(
+        "index {}..{}",
+        old_id.to_hex_with_len(hex_len),
+        new_id.to_hex_with_len(hex_len)
+    );
+    match (old, new) {
+        (Some(old), Some(_)) => {
+            out.extend_from_slice(format!("{} {:o}\n", index_line, old.mode).as_bytes())
+        }
+        _ => out.extend_from_slice(format!("{}\n", index_line).as_bytes()),
+    }
+
+    let before: &[u8] = old.map_or(&[], |side| side.data.as_slice());
+    let after: &[u8] = new.map_or(&[], |side| side.data.as_slice());
+    let hunks = render_hunks(before, after, algorithm);
+    if !hunks.is_empty() {
+        let old_name: Cow<'_, str> = match old {
+            Some(_) => format!("a/{}{}", path, name_tab).into(),
+            None => "/dev/null".into(),
+        };
+        let new_name: Cow<'_, str> = match new {
+            Some(_) => format!("b/{}{}", path, name_tab).into(),
+            None => "/dev/null".into(),
+        };
+        out.extend_from_slice(format!("--- {}\n+++ {}\n", old_name, new_name).as_bytes());
+        out.extend_from_slice(&hunks);
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// 按 git 规则把变更分组为 hunk 并输出（相邻变更间隔不超过两倍上下文时合并）
+fn render_hunks(before: &[u8], after: &[u8], algorithm: Algorithm) -> Vec<u8> {
+    let input = InternedInput::new(before, after);
+    let mut diff = Diff::compute(algorithm, &input);
+    diff.postprocess_lines(&input);
+    let line = |token: imara_diff::Token| -> &[u8] { input.interner[token] };
+    let old_lines: Vec<&[u8]> = input.before.iter().map(|&t| line(t)).collect();
+    let new_lines: Vec<&[u8]> = input.after.iter().map(|&t| line(t)).collect();
+
+    let mut groups: Vec<Vec<Hunk>> = Vec::new();
+    for hunk in diff.hunks() {
+        match groups.last_mut() {
+            Some(group)
+                if hunk.before.start - group[group.len() - 1].before.end <= 2 * CONTEXT_LINES =>
+            {
+                group.push(hunk)
+            }
+            _ => groups.push(vec![hunk]),
+        }
+    }
+
+    let mut out = Vec::new();
+    for group in groups {
+        let (first, last) = (&group[0], &group[group.len() - 1]);
+        let leading = first.before.start.min(CONTEXT_LINES);
+        let trailing = (old_lines.len() as u32 - last.before.end).min(CONTEXT_LINES);
+        let old_start = first.before.start - leading;
+        let new_start = first.after.start - leading;
+        let old_len = last.before.end + trailing - old_start;
+        let new_len = last.after.end + trailing - new_start;
+
+        out.extend_from_slice(
+            format!(
+                "@@ -{} +{} @@",
+                hunk_range(old_start, old_len),
+                hunk_range(new_start, new_len)
+            )
+            .as_bytes(),
+        );
+        if let Some(func) = func_context(&old_lines[..old_start as usize]) {
+            out.push(b' ');
+            out.extend_from_slice(func);
+        }
+        out.push(b'\n');
+
+        let mut pos = old_start;
+        for hunk in &group {
+            for text in &old_lines[pos as usize..hunk.before.start as usize] {
+                push_line(&mut out, b' ', text);
+            }
+            for text in &old_lines[hunk.before.start as usize..hunk.before.end as usize] {
+                push_line(&mut out, b'-', text);
+            }
+            for text in &new_lines[hunk.after.start as usize..hunk.after.end as usize] {
+                push_line(&mut out, b'+', text);
+            }
+            pos = hunk.before.end;
+        }
+        for text in &old_lines[pos as usize..(last.before.end + trailing) as usize] {
+            push_line(&mut out, b' ', text);
+        }
+    }
+    out
+}
+
+/// hunk 头中的区间：长度为 0 时起点取前一行，长度为 1 时省略长度
+fn hunk_range(start: u32, len: u32) -> String {
+    match len {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, len),
+    }
+}
+
+/// git 默认的函数名规则：hunk 之前最近一行以字母、`_` 或 `$` 开头的行
+fn func_context<'a>(preceding: &[&'a [u8]]) -> Option<&'a [u8]> {
+    let text = preceding.iter().rev().find(|text| {
+        text.first()
+            .is_some_and(|&b| b.is_ascii_alphabetic() || b == b'_' || b == b'$')
+    })?;
+    let text = &text[..text.len().min(FUNC_CONTEXT_MAX_BYTES)];
+    let end = text
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(0, |i| i + 1);
+    Some(&text[..end])
+}
+
+fn push_line(out: &mut Vec<u8>, prefix: u8, text: &[u8]) {
+    out.push(prefix);
+    out.extend_from_slice(text);
+    if !text.ends_with(b"\n") {
+        out.extend_from_slice(b"\n\\ No newline at end of file\n");
+    }
+}
@@ -2,11 +2,19 @@
 //!
 //! Provides common types, constants, and utility functions used across git operations.
 
+use std::cell::Cell;
 use std::path::{Path, PathBuf};
 
+use crate::util::exec_env::git_command;
+
 /// Maximum diff size in bytes (1MB)
 pub const MAX_DIFF_SIZE: usize = 1_048_576;
 
+thread_local! {
+    /// 分页读取大 diff 时的起始字节偏移，见 [`with_diff_page_offset`]
+    static DIFF_PAGE_OFFSET: Cell<usize> = const { Cell::new(0) };
+}
+
 /// Git status entry (porcelain v1: X=index/staged, Y=worktree/unstaged)
 #[derive(Debug, Clone)]
 pub struct GitStatusEntry {
@@ -45,10 +53,12 @@ pub struct GitDiffResult {
     pub is_binary: bool,
     pub truncated: bool,
     pub mode: String,
+    /// 实际应用的智能 diff：gitattributes | lockfile | notebook
+    pub smart_diff: Option<String>,
 }
 
 /// Git operation result (stage/unstage)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GitOpResult {
     pub op: String,
     pub ok: bool,
@@ -58,9 +68,19 @@ pub struct GitOpResult {
 }
 
 /// Git branch info
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct GitBranchInfo {
     pub name: String,
+    /// 上游分支短名（如 `origin/main`），未配置时为 None
+    pub upstream: Option<String>,
+    /// 相对上游的领先/落后提交数；未配置上游或上游引用已不存在时为 None
+    pub ahead_by: Option<i32>,
+    pub behind_by: Option<i32>,
+    /// 分支末端提交（完整 SHA）与提交时间（ISO 8601）
+    pub last_commit_sha: Option<String>,
+    pub last_commit_date: Option<String>,
+    /// 该分支已在其他 worktree 中检出时的 worktree 路径（不含当前 worktree）
+    pub worktree_path: Option<String>,
 }
 
 /// Git branches result
@@ -76,6 +96,10 @@ pub struct GitCommitResult {
     pub ok: bool,
     pub message: Option<String>,
     pub sha: Option<String>,
+    /// 新提交是否带签名
+    pub signed: bool,
+    /// 失败分类，见 `COMMIT_ERROR_*`
+    pub error_code: Option<String>,
 }
 
 /// Git operation state (for rebase/merge/cherry-pick/revert)
@@ -138,6 +162,60 @@ pub struct GitLogEntry {
 #[derive(Debug)]
 pub struct GitLogResult {
     pub entries: Vec<GitLogEntry>,
+    /// 是否还有更早的提交（用于无限滚动）
+    pub has_more: bool,
+}
+
+/// Git log 分页与过滤条件
+#[derive(Debug, Clone, Default)]
+pub struct GitLogFilter {
+    /// 跳过前 N 条匹配的提交
+    pub skip: usize,
+    /// 游标分页：只返回该提交之前（不含）的历史
+    pub before_sha: Option<String>,
+    /// 作者名或邮箱子串（大小写不敏感）
+    pub author: Option<String>,
+    /// 只返回修改过该路径的提交
+    pub path: Option<String>,
+    /// 提交消息子串（大小写不敏感）
+    pub grep: Option<String>,
+    /// 起止时间，接受 git 的日期格式（如 `2024-01-01`、`2 weeks ago`）
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// 起始分支或引用，缺省为 HEAD
+    pub branch: Option<String>,
+}
+
+impl GitLogFilter {
+    /// 去掉首尾空白，空字符串视为未设置
+    pub fn normalized(self) -> Self {
+        let clean = |value: Option<String>| {
+            value
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        Self {
+            skip: self.skip,
+            before_sha: clean(self.before_sha),
+            author: clean(self.author),
+            path: clean(self.path),
+            grep: clean(self.grep),
+            since: clean(self.since),
+            until: clean(self.until),
+            branch: clean(self.branch),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.skip == 0
+            && self.before_sha.is_none()
+            && self.author.is_none()
+            && self.path.is_none()
+            && self.grep.is_none()
+            && self.since.is_none()
+            && self.until.is_none()
+            && self.branch.is_none()
+    }
 }
 
 /// Git show 文件变更条目
@@ -160,6 +238,25 @@ pub struct GitShowResult {
     pub files: Vec<GitShowFileEntry>,
 }
 
+/// 提交内单个文件的 diff
+#[derive(Debug)]
+pub struct GitShowFileDiffResult {
+    pub full_sha: String,
+    /// 重命名/复制前的路径
+    pub old_path: Option<String>,
+    pub diff: GitDiffResult,
+}
+
+/// 指定修订版本中的文件内容
+#[derive(Debug)]
+pub struct GitFileAtRevision {
+    /// 修订解析出的提交 SHA（完整）
+    pub sha: String,
+    pub size: u64,
+    /// 超过读取上限时为 None
+    pub content: Option<Vec<u8>>,
+}
+
 /// Integration worktree state
 #[derive(Debug, Clone, PartialEq)]
 pub enum IntegrationState {
@@ -245,6 +342,13 @@ pub enum GitError {
     PathEscape,
     IoError(std::io::Error),
     CommandFailed(String),
+    /// 离线模式下拒绝的网络操作（参数为操作名，如 `fetch`）
+    OfflineMode(String),
+    /// 目标分支已在其他 worktree 中检出
+    BranchInUse {
+        branch: String,
+        worktree_path: String,
+    },
 }
 
 impl std::fmt::Display for GitError {
@@ -254,6 +358,17 @@ impl std::fmt::Display for GitError {
             GitError::PathEscape => write!(f, "Path escapes workspace root"),
             GitError::IoError(e) => write!(f, "IO error: {}", e),
             GitError::CommandFailed(msg) => write!(f, "Git command failed: {}", msg),
+            GitError::OfflineMode(op) => {
+                write!(f, "Offline mode is enabled, git {} skipped", op)
+            }
+            GitError::BranchInUse {
+                branch,
+                worktree_path,
+            } => write!(
+                f,
+                "Branch '{}' is already checked out at '{}'",
+                branch, worktree_path
+            ),
         }
     }
 }
@@ -285,6 +400,23 @@ pub fn get_git_repo_root(workspace_root: &Path) -> Option<String> {
     Some(workdir.to_string_lossy().to_string())
 }
 
+/// 工作区的 (git_dir, common_dir)，无需打开仓库
+///
+/// 普通仓库两者均为 `.git`；worktree 的 `.git` 为 `gitdir: <path>` 文件，common_dir 由其中的
+/// `commondir` 指向主仓库。`.git` 不存在或无法解析时返回 None。
+pub fn resolve_git_dirs(workspace_root: &Path) -> Option<(PathBuf, PathBuf)> {
+    let dot_git = workspace_root.join(".git");
+    if dot_git.is_dir() {
+        return Some((dot_git.clone(), dot_git));
+    }
+    let git_dir = gix::discover::path::from_gitdir_file(&dot_git).ok()?;
+    let common_dir = match gix::discover::path::from_plain_file(&git_dir.join("commondir")) {
+        Some(Ok(common)) => git_dir.join(common),
+        _ => git_dir.clone(),
+    };
+    Some((git_dir, common_dir))
+}
+
 /// Check if a file is binary
 pub fn check_binary(workspace_root: &Path, path: &str) -> bool {
     let full_path = workspace_root.join(path);
@@ -295,17 +427,67 @@ pub fn check_binary(workspace_root: &Path, path: &str) -> bool {
 }
 
 /// Truncate text if it exceeds MAX_DIFF_SIZE
+///
+/// 在 [`with_diff_page_offset`] 内调用时，返回从该偏移开始的一页。
 pub fn truncate_if_needed(text: &str) -> (String, bool) {
-    if text.len() > MAX_DIFF_SIZE {
-        // Find a good break point (end of line)
-        let truncated_text = &text[..MAX_DIFF_SIZE];
-        if let Some(last_newline) = truncated_text.rfind('\n') {
-            (truncated_text[..=last_newline].to_string(), true)
-        } else {
-            (truncated_text.to_string(), true)
-        }
+    diff_page(text, DIFF_PAGE_OFFSET.get())
+}
+
+/// 截取 `text` 从 `offset` 字节开始、不超过 [`MAX_DIFF_SIZE`] 的一页，尽量在行尾断开
+///
+/// 返回的布尔值表示该页之后是否还有剩余内容；下一页的偏移为 `offset + 页长度`。
+pub fn diff_page(text: &str, offset: usize) -> (String, bool) {
+    let mut start = offset.min(text.len());
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    let rest = &text[start..];
+    if rest.len() <= MAX_DIFF_SIZE {
+        return (rest.to_string(), false);
+    }
+    let mut end = MAX_DIFF_SIZE;
+    while !rest.is_char_boundary(end) {
+        end -= 1;
+    }
+    let page = &rest[..end];
+    match page.rfind('\n') {
+        Some(last_newline) => (page[..=last_newline].to_string(), true),
+        None => (page.to_string(), true),
+    }
+}
+
+/// 在当前线程上以 `offset` 为起点生成 diff：期间 [`truncate_if_needed`] 跳过前 `offset` 字节
+pub fn with_diff_page_offset<R>(offset: usize, f: impl FnOnce() -> R) -> R {
+    let previous = DIFF_PAGE_OFFSET.replace(offset);
+    let result = f();
+    DIFF_PAGE_OFFSET.set(previous);
+    result
+}
+
+/// 支持的 diff 算法（透传给 `git diff --diff-algorithm`）
+pub const DIFF_ALGORITHMS: &[&str] = &["myers", "minimal", "patience", "histogram"];
+
+/// 校验并规范化 diff 算法名称（大小写不敏感，`default` 视为 `myers`）
+///
+/// `None` 或空字符串表示沿用 git 自身配置（不追加 `--diff-algorithm`）。
+pub fn normalize_diff_algorithm(algorithm: Option<&str>) -> Result<Option<String>, GitError> {
+    let Some(raw) = algorithm.map(str::trim).filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+    let lowered = raw.to_ascii_lowercase();
+    let name = if lowered == "default" {
+        "myers"
     } else {
-        (text.to_string(), false)
+        lowered.as_str()
+    };
+    if DIFF_ALGORITHMS.contains(&name) {
+        Ok(Some(name.to_string()))
+    } else {
+        Err(GitError::CommandFailed(format!(
+            "Unsupported diff algorithm: {} (expected one of: {})",
+            raw,
+            DIFF_ALGORITHMS.join(", ")
+        )))
     }
 }
 
@@ -389,6 +571,102 @@ pub struct ConflictFileDetail {
     pub conflict_markers_count: usize,
     /// 是否为二进制文件
     pub is_binary: bool,
+    /// 按冲突标记解析出的冲突区块（二进制文件为空）
+    pub regions: Vec<ConflictRegion>,
+}
+
+/// 工作区文件中一组 `<<<<<<< / ======= / >>>>>>>` 冲突区块
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConflictRegion {
+    /// 区块序号（0-based）
+    pub index: usize,
+    /// `<<<<<<<` 所在行（1-based）
+    pub start_line: usize,
+    /// `>>>>>>>` 所在行（1-based，含）
+    pub end_line: usize,
+    /// `<<<<<<<` 之后的标签（如 HEAD）
+    pub ours_label: String,
+    /// `>>>>>>>` 之后的标签（如分支名或提交）
+    pub theirs_label: String,
+    /// 我方内容（保留原换行符）
+    pub ours: String,
+    /// diff3 / zdiff3 风格下 `|||||||` 与 `=======` 之间的公共祖先内容
+    pub base: Option<String>,
+    /// 对方内容（保留原换行符）
+    pub theirs: String,
+}
+
+/// 冲突标记行：`marker` 重复 7 次，其后为行尾或空格 + 标签
+fn conflict_marker_label<'a>(line: &'a str, marker: char) -> Option<&'a str> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let rest = line.strip_prefix(&marker.to_string().repeat(7))?;
+    if rest.is_empty() {
+        Some("")
+    } else {
+        rest.strip_prefix(' ')
+    }
+}
+
+/// 解析文件内容中的冲突区块；未闭合的区块被忽略
+pub fn parse_conflict_regions(content: &str) -> Vec<ConflictRegion> {
+    enum Section {
+        Ours,
+        Base,
+        Theirs,
+    }
+    let mut regions = Vec::new();
+    let mut current: Option<(ConflictRegion, Section)> = None;
+
+    for (idx, line) in content.split_inclusive('\n').enumerate() {
+        let line_no = idx + 1;
+        let Some((region, section)) = current.as_mut() else {
+            if let Some(label) = conflict_marker_label(line, '<') {
+                current = Some((
+                    ConflictRegion {
+                        index: regions.len(),
+                        start_line: line_no,
+                        end_line: line_no,
+                        ours_label: label.to_string(),
+                        theirs_label: String::new(),
+                        ours: String::new(),
+                        base: None,
+                        theirs: String::new(),
+                    },
+                    Section::Ours,
+                ));
+            }
+            continue;
+        };
+        match section {
+            Section::Ours if conflict_marker_label(line, '|').is_some() => {
+                region.base = Some(String::new());
+                *section = Section::Base;
+            }
+            Section::Ours | Section::Base if conflict_marker_label(line, '=') == Some("") => {
+                *section = Section::Theirs;
+            }
+            Section::Theirs if conflict_marker_label(line, '>').is_some() => {
+                if let Some((mut region, _)) = current.take() {
+                    region.end_line = line_no;
+                    region.theirs_label = conflict_marker_label(line, '>')
+                        .unwrap_or_default()
+                        .to_string();
+                    regions.push(region);
+                }
+            }
+            Section::Ours => region.ours.push_str(line),
+            Section::Base => region.base.get_or_insert_with(String::new).push_str(line),
+            Section::Theirs => region.theirs.push_str(line),
+        }
+    }
+    regions
+}
+
+/// 内容中是否仍残留冲突标记
+pub fn has_conflict_markers(content: &str) -> bool {
+    content.split_inclusive('\n').any(|line| {
+        conflict_marker_label(line, '<').is_some() || conflict_marker_label(line, '>').is_some()
+    })
 }
 
 /// 冲突快照（整个上下文的冲突状态）
@@ -406,19 +684,16 @@ pub struct ConflictSnapshot {
 #[derive(Debug)]
 pub struct ConflictActionResult {
     pub ok: bool,
-    pub action: String, // "accept_ours" | "accept_theirs" | "accept_both" | "mark_resolved"
+    pub action: String, // "accept_ours" | "accept_theirs" | "accept_both" | "mark_resolved" | "resolve_custom"
     pub message: Option<String>,
     pub snapshot: ConflictSnapshot,
 }
 
 /// 根据 git status 获取语义化冲突文件条目列表
 pub fn get_conflict_file_entries(workspace_root: &Path) -> Vec<ConflictFileEntry> {
-    use std::process::Command;
-
     // 使用 git status --porcelain 获取冲突信息（XY 编码判断冲突类型）
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["status", "--porcelain", "-z"])
-        .current_dir(workspace_root)
         .output();
 
     let Ok(out) = output else {
@@ -428,18 +703,36 @@ pub fn get_conflict_file_entries(workspace_root: &Path) -> Vec<ConflictFileEntry
         return vec![];
     }
 
-    let stdout = String::from_utf8_lossy(&out.stdout);
-    let mut entries = Vec::new();
+    parse_conflict_entries_z(&String::from_utf8_lossy(&out.stdout))
+}
 
-    for record in stdout.split('\0') {
-        let record = record.trim_end_matches('\0');
-        if record.len() < 3 {
-            continue;
-        }
-        let xy = &record[..2];
-        let path = record[3..].to_string();
-        if path.is_empty() {
+/// 拆分 porcelain v1 记录 `XY PATH`；格式不符（过短、第三个字符不是空格、
+/// XY 非 ASCII）时返回 None，调用方跳过该条而不是 panic
+pub fn split_porcelain_record(record: &str) -> Option<(&str, &str)> {
+    if record.as_bytes().get(2) != Some(&b' ') {
+        return None;
+    }
+    let xy = record.get(..2)?;
+    let path = record.get(3..)?;
+    if path.is_empty() {
+        return None;
+    }
+    Some((xy, path))
+}
+
+/// 解析 `git status --porcelain -z` 输出中的冲突条目
+///
+/// `-z` 模式下重命名 / 复制条目（X 或 Y 为 R/C）后紧跟一个原路径字段，
+/// 必须一并消费，否则原路径会被当作新记录解析，错位影响后续所有条目。
+pub fn parse_conflict_entries_z(stdout: &str) -> Vec<ConflictFileEntry> {
+    let mut entries = Vec::new();
+    let mut records = stdout.split('\0');
+    while let Some(record) = records.next() {
+        let Some((xy, path)) = split_porcelain_record(record) else {
             continue;
+        };
+        if xy.contains(['R', 'C']) {
+            records.next();
         }
 
         // XY 为 UU/AA/DD/AU/UA/DU/UD 时为冲突
@@ -453,7 +746,7 @@ pub fn get_conflict_file_entries(workspace_root: &Path) -> Vec<ConflictFileEntry
         };
 
         entries.push(ConflictFileEntry {
-            path,
+            path: path.to_string(),
             conflict_type: conflict_type.to_string(),
             staged: false,
         });
@@ -461,6 +754,35 @@ pub fn get_conflict_file_entries(workspace_root: &Path) -> Vec<ConflictFileEntry
     entries
 }
 
+/// 是否为完整的对象 SHA（SHA-1 40 位或 SHA-256 64 位十六进制）
+pub fn is_full_sha(s: &str) -> bool {
+    (s.len() == 40 || s.len() == 64) && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// 按 0x1e 切分 `git log --format=%H<sep>...%B%x1e` 的输出，返回每个提交的记录文本
+///
+/// `%B` 必须是格式中的最后一个字段。提交消息本身可能含 0x1e：
+/// 不以 `<完整 SHA><sep>` 开头的片段并回上一条记录的消息，而不是被当成一个新提交。
+pub fn split_log_records(stdout: &str, field_sep: char) -> Vec<String> {
+    // 最后一条记录以 `0x1e\n` 结尾，去掉后剩下的 0x1e 都是分隔符或消息内容
+    let stdout = stdout.trim_end_matches('\n');
+    let stdout = stdout.strip_suffix('\x1e').unwrap_or(stdout);
+    let mut records: Vec<String> = Vec::new();
+    for fragment in stdout.split('\x1e') {
+        let trimmed = fragment.trim_start_matches('\n');
+        let starts_record = trimmed
+            .split_once(field_sep)
+            .is_some_and(|(sha, _)| is_full_sha(sha));
+        if starts_record {
+            records.push(trimmed.to_string());
+        } else if let Some(last) = records.last_mut() {
+            last.push('\x1e');
+            last.push_str(fragment);
+        }
+    }
+    records
+}
+
 /// 获取冲突快照（填充 all_resolved 字段）
 pub fn build_conflict_snapshot(workspace_root: &Path, context: &str) -> ConflictSnapshot {
     let files = get_conflict_file_entries(workspace_root);
@@ -476,6 +798,21 @@ pub fn build_conflict_snapshot(workspace_root: &Path, context: &str) -> Conflict
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_normalize_diff_algorithm() {
+        assert_eq!(normalize_diff_algorithm(None).unwrap(), None);
+        assert_eq!(normalize_diff_algorithm(Some("  ")).unwrap(), None);
+        assert_eq!(
+            normalize_diff_algorithm(Some("Histogram")).unwrap(),
+            Some("histogram".to_string())
+        );
+        assert_eq!(
+            normalize_diff_algorithm(Some("default")).unwrap(),
+            Some("myers".to_string())
+        );
+        assert!(normalize_diff_algorithm(Some("bogus")).is_err());
+    }
+
     #[test]
     fn test_truncate_if_needed() {
         let short_text = "short text";
@@ -508,6 +845,36 @@ mod tests {
         assert!(result.ends_with('\n'));
     }
 
+    #[test]
+    fn test_diff_page_continues_from_offset() {
+        let line = "b".repeat(99);
+        let mut long_text = String::new();
+        while long_text.len() < MAX_DIFF_SIZE + 500 {
+            long_text.push_str(&line);
+            long_text.push('\n');
+        }
+        let (first, more) = truncate_if_needed(&long_text);
+        assert!(more);
+        let (second, more) = with_diff_page_offset(first.len(), || truncate_if_needed(&long_text));
+        assert!(!more);
+        assert_eq!(format!("{}{}", first, second), long_text);
+        // 页偏移只在作用域内生效
+        assert_eq!(truncate_if_needed(&long_text).0, first);
+    }
+
+    #[test]
+    fn test_diff_page_respects_char_boundaries() {
+        let long_text = "中".repeat(MAX_DIFF_SIZE / 3 + 10);
+        let (page, more) = diff_page(&long_text, 0);
+        assert!(more);
+        assert!(page.len() <= MAX_DIFF_SIZE);
+        // 偏移落在多字节字符中间时从下一个字符开始
+        let (rest, _) = diff_page(&long_text, 1);
+        assert!(rest.len() <= MAX_DIFF_SIZE);
+        assert!(rest.starts_with('中'));
+        assert_eq!(diff_page(&long_text, usize::MAX), (String::new(), false));
+    }
+
     #[test]
     fn test_git_op_state_as_str() {
         assert_eq!(GitOpState::Normal.as_str(), "normal");
@@ -583,6 +950,7 @@ mod tests {
     fn test_git_branch_info() {
         let info = GitBranchInfo {
             name: "feature/test".to_string(),
+            ..Default::default()
         };
         assert_eq!(info.name, "feature/test");
     }
@@ -594,9 +962,11 @@ mod tests {
             branches: vec![
                 GitBranchInfo {
                     name: "develop".to_string(),
+                    ..Default::default()
                 },
                 GitBranchInfo {
                     name: "main".to_string(),
+                    ..Default::default()
                 },
             ],
         };
@@ -610,6 +980,8 @@ mod tests {
             ok: true,
             message: Some("Committed: abc1234".to_string()),
             sha: Some("abc1234".to_string()),
+            signed: false,
+            error_code: None,
         };
         assert!(success.ok);
         assert!(success.sha.is_some());
@@ -618,6 +990,8 @@ mod tests {
             ok: false,
             message: Some("No staged changes".to_string()),
             sha: None,
+            signed: false,
+            error_code: Some("nothing_staged".to_string()),
         };
         assert!(!failure.ok);
         assert!(failure.sha.is_none());
@@ -875,6 +1249,7 @@ mod tests {
             current_content: String::new(),
             conflict_markers_count: 0,
             is_binary: true,
+            regions: Vec::new(),
         };
         assert!(detail.is_binary);
         assert_eq!(detail.conflict_markers_count, 0);
@@ -892,11 +1267,55 @@ mod tests {
             current_content: "<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\n".to_string(),
             conflict_markers_count: 1,
             is_binary: false,
+            regions: parse_conflict_regions(
+                "<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\n",
+            ),
         };
         assert!(!detail.is_binary);
         assert_eq!(detail.conflict_markers_count, 1);
         assert!(detail.base_content.is_some());
         assert!(detail.ours_content.is_some());
         assert!(detail.theirs_content.is_some());
+        assert_eq!(detail.regions.len(), detail.conflict_markers_count);
+    }
+
+    #[test]
+    fn test_parse_conflict_regions_merge_and_diff3() {
+        let content = "head\n\
+<<<<<<< HEAD\n\
+ours line\r\n\
+=======\n\
+theirs line\n\
+>>>>>>> feature\n\
+middle\n\
+<<<<<<< HEAD\n\
+a\n\
+||||||| merged common ancestors\n\
+base\n\
+=======\n\
+>>>>>>> 1a2b3c4 (commit message)\n\
+<<<<<<< HEAD\n\
+unterminated\n";
+        let regions = parse_conflict_regions(content);
+        assert_eq!(regions.len(), 2);
+
+        let first = &regions[0];
+        assert_eq!((first.start_line, first.end_line), (2, 6));
+        assert_eq!(first.ours_label, "HEAD");
+        assert_eq!(first.theirs_label, "feature");
+        assert_eq!(first.ours, "ours line\r\n");
+        assert_eq!(first.theirs, "theirs line\n");
+        assert_eq!(first.base, None);
+
+        let second = &regions[1];
+        assert_eq!(second.index, 1);
+        assert_eq!((second.start_line, second.end_line), (8, 13));
+        assert_eq!(second.ours, "a\n");
+        assert_eq!(second.base.as_deref(), Some("base\n"));
+        assert_eq!(second.theirs, "");
+        assert_eq!(second.theirs_label, "1a2b3c4 (commit message)");
+
+        assert!(has_conflict_markers(content));
+        assert!(!has_conflict_markers("a\n======= not a marker\n"));
     }
 }
@@ -0,0 +1,274 @@
+//! 多项目后台定时 fetch
+//!
+//! 配置了 `git_auto_fetch_interval_secs`（默认 0，关闭）后，后台任务按间隔依次对全部项目执行
+//! `git fetch`，随后计算各工作区当前分支相对 `origin/<默认分支>` 的领先/落后数，
+//! 使 git status 缓存失效并推送 `git_remote_updated`，客户端无需手动 fetch 即可看到最新分歧。
+//!
+//! 离线模式或主机休眠期间跳过；同一仓库与手动 fetch 的并发调用由 [`git::git_fetch`] 合并。
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use tracing::{debug, info, warn};
+
+use crate::server::context::{
+    send_task_broadcast_event, SharedAppState, TaskBroadcastEvent, TaskBroadcastTx,
+};
+use crate::server::git;
+use crate::server::protocol::ServerMessage;
+use crate::workspace::state::DEFAULT_WORKSPACE_NAME;
+
+/// 非 0 间隔的下限（秒），避免频繁访问远程
+pub const MIN_FETCH_INTERVAL_SECS: u64 = 60;
+/// 调度检查间隔：设置变更在该时间内生效
+const SCHEDULER_TICK: Duration = Duration::from_secs(30);
+
+/// 校验定时 fetch 间隔：0（关闭）或不小于 [`MIN_FETCH_INTERVAL_SECS`]
+pub fn validate_fetch_interval(secs: u64) -> Result<(), String> {
+    if secs != 0 && secs < MIN_FETCH_INTERVAL_SECS {
+        return Err(format!(
+            "Invalid git auto fetch interval: {}s (must be 0 to disable or at least {}s)",
+            secs, MIN_FETCH_INTERVAL_SECS
+        ));
+    }
+    Ok(())
+}
+
+/// 单个项目的 fetch 目标
+#[derive(Debug, Clone)]
+struct FetchTarget {
+    project: String,
+    root_path: PathBuf,
+    default_branch: String,
+    /// (工作区名, 路径)，default 在前
+    workspaces: Vec<(String, PathBuf)>,
+}
+
+async fn collect_targets(app_state: &SharedAppState) -> Vec<FetchTarget> {
+    let state = app_state.read().await;
+    let mut targets: Vec<FetchTarget> = state
+        .projects
+        .values()
+        .map(|proj| {
+            let mut named: Vec<(String, PathBuf)> = proj
+                .workspaces
+                .values()
+                .map(|w| (w.name.clone(), w.worktree_path.clone()))
+                .collect();
+            named.sort_by(|a, b| a.0.cmp(&b.0));
+            let mut workspaces = vec![(DEFAULT_WORKSPACE_NAME.to_string(), proj.root_path.clone())];
+            workspaces.extend(named);
+            FetchTarget {
+                project: proj.name.clone(),
+                root_path: proj.root_path.clone(),
+                default_branch: proj.default_branch.clone(),
+                workspaces,
+            }
+        })
+        .collect();
+    targets.sort_by(|a, b| a.project.cmp(&b.project));
+    targets
+}
+
+/// fetch 单个项目并返回各工作区的 `git_remote_updated`；比较失败的工作区（如无远程默认分支）被跳过
+async fn fetch_project(target: FetchTarget) -> Result<Vec<ServerMessage>, String> {
+    crate::util::trace::spawn_blocking(move || {
+        let result = git::git_fetch(&target.root_path).map_err(|e| e.to_string())?;
+        if !result.ok {
+            return Err(result.message.unwrap_or_else(|| "fetch failed".to_string()));
+        }
+        let mut messages = Vec::new();
+        for (workspace, root) in &target.workspaces {
+            git::invalidate_git_status_cache(root);
+            let current_branch = git::git_current_branch(root).ok().flatten();
+            let branch = current_branch.as_deref().unwrap_or("HEAD");
+            match git::compare_with_remote_default(root, branch, &target.default_branch) {
+                Ok(divergence) => messages.push(ServerMessage::GitRemoteUpdated {
+                    project: target.project.clone(),
+                    workspace: workspace.clone(),
+                    current_branch,
+                    ahead_by: divergence.ahead_by,
+                    behind_by: divergence.behind_by,
+                    compared_branch: divergence.compared_branch,
+                }),
+                Err(e) => debug!(
+                    project = %target.project,
+                    workspace = %workspace,
+                    error = %e,
+                    "Skipped remote divergence after auto fetch"
+                ),
+            }
+        }
+        Ok(messages)
+    })
+    .await
+    .map_err(|e| format!("Auto fetch task failed: {}", e))?
+}
+
+/// 依次 fetch 全部项目并推送结果
+async fn fetch_all_projects(app_state: &SharedAppState, task_broadcast_tx: &TaskBroadcastTx) {
+    let targets = collect_targets(app_state).await;
+    let started = Instant::now();
+    for target in targets {
+        if git::git_network_settings().offline_mode || crate::server::power::is_host_sleeping() {
+            return;
+        }
+        if !target.root_path.is_dir() {
+            continue;
+        }
+        let project = target.project.clone();
+        match fetch_project(target).await {
+            Ok(messages) => {
+                for message in messages {
+                    send_task_broadcast_event(
+                        task_broadcast_tx,
+                        TaskBroadcastEvent {
+                            origin_conn_id: String::new(),
+                            message,
+                            target_conn_ids: None,
+                            skip_when_single_receiver: false,
+                        },
+                    );
+                }
+            }
+            Err(e) => warn!(project = %project, error = %e, "Auto fetch failed"),
+        }
+    }
+    info!(
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "Auto fetch finished"
+    );
+}
+
+/// 启动后台定时 fetch 任务；间隔取自设置，为 0 时不执行
+pub fn spawn_git_fetch_scheduler(app_state: SharedAppState, task_broadcast_tx: TaskBroadcastTx) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SCHEDULER_TICK);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut last_run: Option<Instant> = None;
+        loop {
+            interval.tick().await;
+            let secs = app_state
+                .read()
+                .await
+                .client_settings
+                .git_auto_fetch_interval_secs;
+            if secs == 0 {
+                continue;
+            }
+            if git::git_network_settings().offline_mode || crate::server::power::is_host_sleeping()
+            {
+                continue;
+            }
+            if last_run.is_some_and(|at| at.elapsed() < Duration::from_secs(secs)) {
+                continue;
+            }
+            fetch_all_projects(&app_state, &task_broadcast_tx).await;
+            last_run = Some(Instant::now());
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::exec_env::git_command;
+    use std::path::Path;
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let output = git_command(dir).args(args).output().expect("run git");
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    fn commit(dir: &Path, name: &str) {
+        std::fs::write(dir.join(name), name).unwrap();
+        run_git(dir, &["add", "."]);
+        run_git(
+            dir,
+            &[
+                "-c",
+                "user.name=t",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "-q",
+                "-m",
+                name,
+            ],
+        );
+    }
+
+    #[test]
+    fn validate_fetch_interval_allows_zero_or_minimum() {
+        assert!(validate_fetch_interval(0).is_ok());
+        assert!(validate_fetch_interval(MIN_FETCH_INTERVAL_SECS).is_ok());
+        assert!(validate_fetch_interval(3600).is_ok());
+        assert!(validate_fetch_interval(10).is_err());
+    }
+
+    #[tokio::test]
+    async fn fetch_project_reports_remote_divergence_per_workspace() {
+        let temp = tempfile::tempdir().unwrap();
+        let origin = temp.path().join("origin.git");
+        let upstream = temp.path().join("upstream");
+        let local = temp.path().join("local");
+        std::fs::create_dir_all(&upstream).unwrap();
+        run_git(
+            temp.path(),
+            &["init", "-q", "--bare", "-b", "main", "origin.git"],
+        );
+        run_git(&upstream, &["init", "-q", "-b", "main"]);
+        commit(&upstream, "a.txt");
+        run_git(
+            &upstream,
+            &["remote", "add", "origin", origin.to_str().unwrap()],
+        );
+        run_git(&upstream, &["push", "-q", "origin", "main"]);
+        run_git(
+            temp.path(),
+            &["clone", "-q", origin.to_str().unwrap(), "local"],
+        );
+        commit(&local, "local.txt");
+
+        // 远程新增两个提交，本地尚未 fetch
+        commit(&upstream, "b.txt");
+        commit(&upstream, "c.txt");
+        run_git(&upstream, &["push", "-q", "origin", "main"]);
+
+        let messages = fetch_project(FetchTarget {
+            project: "demo".to_string(),
+            root_path: local.clone(),
+            default_branch: "main".to_string(),
+            workspaces: vec![
+                (DEFAULT_WORKSPACE_NAME.to_string(), local.clone()),
+                ("gone".to_string(), temp.path().join("missing")),
+            ],
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(messages.len(), 1);
+        match &messages[0] {
+            ServerMessage::GitRemoteUpdated {
+                project,
+                workspace,
+                current_branch,
+                ahead_by,
+                behind_by,
+                compared_branch,
+            } => {
+                assert_eq!(project, "demo");
+                assert_eq!(workspace, DEFAULT_WORKSPACE_NAME);
+                assert_eq!(current_branch.as_deref(), Some("main"));
+                assert_eq!((*ahead_by, *behind_by), (1, 2));
+                assert_eq!(compared_branch, "origin/main");
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+}
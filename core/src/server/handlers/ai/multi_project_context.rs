@@ -5,9 +5,9 @@
 
 use std::collections::HashMap;
 use std::path::Path;
-use std::process::Command;
 
 use crate::server::protocol::ai::ProjectContextSummary;
+use crate::util::exec_env::git_command;
 
 // ============================================================================
 // 提取项目提及
@@ -180,9 +180,8 @@ fn collect_git_context(dir: &str) -> String {
 
 /// 在指定目录执行 git 命令，返回标准输出字符串；失败时返回空字符串。
 fn run_git_command(dir: &str, args: &[&str]) -> String {
-    Command::new("git")
+    git_command(dir)
         .args(args)
-        .current_dir(dir)
         .output()
         .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
         .unwrap_or_default()
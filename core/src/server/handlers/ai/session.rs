@@ -303,6 +303,7 @@ pub(super) async fn handle_ai_read_via_http_required(
             workspace,
             session_id: None,
             cycle_id: None,
+            trace_id: None,
         },
     )
     .await?;
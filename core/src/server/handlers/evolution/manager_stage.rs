@@ -3,7 +3,6 @@ use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use std::sync::Arc;
 use tokio::time::{sleep, timeout, Duration};
 use tracing::{error, info, warn};
@@ -33,6 +32,7 @@ use super::utils::{
     sanitize_validation_attempt, sanitize_validation_attempts, write_json, write_jsonc_text,
 };
 use super::{EvolutionManager, MAX_STAGE_RUNTIME_SECS};
+use crate::util::exec_env::git_command;
 
 const VALIDATION_REMINDER_MAX_RETRIES: u32 = 3;
 const IMPLEMENT_CONFIG_RESERVED_PREFIX: &str = "__evo_internal_";
@@ -1130,9 +1130,8 @@ fn ensure_stage_field_matches(
 }
 
 fn git_repo_has_changes(workspace_root: &Path) -> Result<bool, String> {
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["status", "--porcelain"])
-        .current_dir(workspace_root)
         .output()
         .map_err(|e| format!("执行 git status 失败: {}", e))?;
     if !output.status.success() {
@@ -1143,9 +1142,8 @@ fn git_repo_has_changes(workspace_root: &Path) -> Result<bool, String> {
 }
 
 fn git_head_sha(workspace_root: &Path) -> Result<Option<String>, String> {
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["rev-parse", "--verify", "HEAD"])
-        .current_dir(workspace_root)
         .output()
         .map_err(|e| format!("执行 git rev-parse 失败: {}", e))?;
     if !output.status.success() {
@@ -1160,9 +1158,8 @@ fn git_head_sha(workspace_root: &Path) -> Result<Option<String>, String> {
 }
 
 fn git_dir_path(workspace_root: &Path) -> Result<PathBuf, String> {
-    let output = Command::new("git")
+    let output = git_command(workspace_root)
         .args(["rev-parse", "--git-dir"])
-        .current_dir(workspace_root)
         .output()
         .map_err(|e| format!("执行 git rev-parse --git-dir 失败: {}", e))?;
     if !output.status.success() {
@@ -1209,19 +1206,17 @@ fn collect_commits_between(
     }
 
     let rev_list_output = if let Some(before_sha) = before {
-        Command::new("git")
+        git_command(workspace_root)
             .args([
                 "rev-list",
                 "--reverse",
                 &format!("{}..{}", before_sha, after_sha),
             ])
-            .current_dir(workspace_root)
             .output()
             .map_err(|e| format!("执行 git rev-list 失败: {}", e))?
     } else {
-        Command::new("git")
+        git_command(workspace_root)
             .args(["rev-list", "--reverse", after_sha])
-            .current_dir(workspace_root)
             .output()
             .map_err(|e| format!("执行 git rev-list 失败: {}", e))?
     };
@@ -1243,7 +1238,7 @@ fn collect_commits_between(
 
     let mut commits: Vec<AIGitCommit> = Vec::with_capacity(shas.len());
     for sha in shas {
-        let output = Command::new("git")
+        let output = git_command(workspace_root)
             .args([
                 "show",
                 "--name-only",
@@ -1252,7 +1247,6 @@ fn collect_commits_between(
                 "1",
                 &sha,
             ])
-            .current_dir(workspace_root)
             .output()
             .map_err(|e| format!("执行 git show 失败: {}", e))?;
         if !output.status.success() {
@@ -5975,9 +5969,9 @@ mod tests {
         ArtifactValidationError, EvolutionManager, ImplementLane, StageValidationContext,
         PLAN_MARKDOWN_FILE,
     };
+    use crate::util::exec_env::git_command;
     use chrono::Utc;
     use std::path::Path;
-    use std::process::Command;
     use std::sync::atomic::{AtomicU32, Ordering};
     use std::sync::Arc;
     use tempfile::tempdir;
@@ -7053,9 +7047,8 @@ mod tests {
     #[test]
     fn validate_stage_artifacts_with_context_should_reject_dirty_auto_commit_without_reason() {
         let dir = tempdir().expect("tempdir should succeed");
-        let init_status = Command::new("git")
+        let init_status = git_command(dir.path())
             .arg("init")
-            .current_dir(dir.path())
             .status()
             .expect("git init should run");
         assert!(init_status.success(), "git init should succeed");
@@ -9370,6 +9363,9 @@ mod tests {
                     last_accessed: Utc::now(),
                     setup_result: None,
                     recovery_meta: None,
+                    port: None,
+                    linked_issue: None,
+                    sparse_paths: Vec::new(),
                 },
             )]),
             commands: Vec::new(),
@@ -9452,6 +9448,9 @@ mod tests {
                             last_accessed: Utc::now(),
                             setup_result: None,
                             recovery_meta: None,
+                            port: None,
+                            linked_issue: None,
+                            sparse_paths: Vec::new(),
                         },
                     )]),
                     commands: Vec::new(),
@@ -9507,6 +9506,9 @@ mod tests {
                             last_accessed: Utc::now(),
                             setup_result: None,
                             recovery_meta: None,
+                            port: None,
+                            linked_issue: None,
+                            sparse_paths: Vec::new(),
                         },
                     )]),
                     commands: Vec::new(),
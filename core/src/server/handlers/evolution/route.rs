@@ -28,6 +28,7 @@ async fn send_read_via_http_required(
             workspace,
             session_id: None,
             cycle_id: None,
+            trace_id: None,
         },
     )
     .await
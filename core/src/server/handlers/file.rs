@@ -42,6 +42,19 @@ pub async fn handle_file_message(
             .await?;
             return Ok(true);
         }
+        ClientMessage::FileReadAtRevision {
+            project, workspace, ..
+        } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "file_read_at_revision",
+                "/api/v1/projects/:project/workspaces/:workspace/files/revision",
+                Some(project.clone()),
+                Some(workspace.clone()),
+            )
+            .await?;
+            return Ok(true);
+        }
         ClientMessage::FileIndex {
             project, workspace, ..
         } => {
@@ -55,6 +68,58 @@ pub async fn handle_file_message(
             .await?;
             return Ok(true);
         }
+        ClientMessage::FileQuery {
+            project, workspace, ..
+        } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "file_query",
+                "/api/v1/projects/:project/workspaces/:workspace/files/query",
+                Some(project.clone()),
+                Some(workspace.clone()),
+            )
+            .await?;
+            return Ok(true);
+        }
+        ClientMessage::FileDefinitionGuess {
+            project, workspace, ..
+        } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "file_definition_guess",
+                "/api/v1/projects/:project/workspaces/:workspace/files/definition",
+                Some(project.clone()),
+                Some(workspace.clone()),
+            )
+            .await?;
+            return Ok(true);
+        }
+        ClientMessage::FileHighlight {
+            project, workspace, ..
+        } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "file_highlight",
+                "/api/v1/projects/:project/workspaces/:workspace/files/highlight",
+                Some(project.clone()),
+                Some(workspace.clone()),
+            )
+            .await?;
+            return Ok(true);
+        }
+        ClientMessage::FileEditorConfig {
+            project, workspace, ..
+        } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "file_editorconfig",
+                "/api/v1/projects/:project/workspaces/:workspace/files/editorconfig",
+                Some(project.clone()),
+                Some(workspace.clone()),
+            )
+            .await?;
+            return Ok(true);
+        }
         _ => {}
     }
 
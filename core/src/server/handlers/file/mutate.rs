@@ -3,6 +3,7 @@ use crate::server::ws::OutboundTx as WebSocket;
 use crate::application::file as file_app;
 use crate::server::context::{resolve_workspace, SharedAppState};
 use crate::server::protocol::{ClientMessage, ServerMessage};
+use crate::server::replace::ReplaceQuery;
 use crate::server::ws::send_message;
 
 pub async fn handle_mutate_message(
@@ -73,6 +74,132 @@ pub async fn handle_mutate_message(
             send_message(socket, &msg).await?;
             Ok(true)
         }
+        ClientMessage::FileMkdir {
+            project,
+            workspace,
+            path,
+        } => {
+            let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
+                Ok(ctx) => ctx,
+                Err(e) => {
+                    send_message(
+                        socket,
+                        &ServerMessage::FileMkdirResult {
+                            project: project.clone(),
+                            workspace: workspace.clone(),
+                            path: path.clone(),
+                            success: false,
+                            message: Some(e.to_string()),
+                        },
+                    )
+                    .await?;
+                    return Ok(true);
+                }
+            };
+
+            let msg = file_app::file_mkdir_message(&ws_ctx.root_path, project, workspace, path);
+            send_message(socket, &msg).await?;
+            Ok(true)
+        }
+        ClientMessage::FilePrepareReplace {
+            project,
+            workspace,
+            query,
+            replacement,
+            regex,
+            case_sensitive,
+            include_globs,
+        } => {
+            let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
+                Ok(ctx) => ctx,
+                Err(e) => {
+                    send_message(socket, &e.to_server_error()).await?;
+                    return Ok(true);
+                }
+            };
+
+            let msg = file_app::file_prepare_replace_message(
+                &ws_ctx.root_path,
+                project,
+                workspace,
+                ReplaceQuery {
+                    query: query.clone(),
+                    replacement: replacement.clone(),
+                    regex: *regex,
+                    case_sensitive: *case_sensitive,
+                    include_globs: include_globs.clone(),
+                },
+            )
+            .await;
+            send_message(socket, &msg).await?;
+            Ok(true)
+        }
+        ClientMessage::FileApplyReplace {
+            project,
+            workspace,
+            change_set_id,
+            accepted_matches,
+        } => {
+            let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
+                Ok(ctx) => ctx,
+                Err(e) => {
+                    send_message(
+                        socket,
+                        &ServerMessage::FileApplyReplaceResult {
+                            project: project.clone(),
+                            workspace: workspace.clone(),
+                            change_set_id: change_set_id.clone(),
+                            success: false,
+                            files_changed: Vec::new(),
+                            replacements: 0,
+                            undo_id: None,
+                            message: Some(e.to_string()),
+                        },
+                    )
+                    .await?;
+                    return Ok(true);
+                }
+            };
+
+            let msg = file_app::file_apply_replace_message(
+                &ws_ctx.root_path,
+                project,
+                workspace,
+                change_set_id,
+                accepted_matches.as_deref(),
+            );
+            send_message(socket, &msg).await?;
+            Ok(true)
+        }
+        ClientMessage::FileUndoReplace {
+            project,
+            workspace,
+            undo_id,
+        } => {
+            let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
+                Ok(ctx) => ctx,
+                Err(e) => {
+                    send_message(
+                        socket,
+                        &ServerMessage::FileUndoReplaceResult {
+                            project: project.clone(),
+                            workspace: workspace.clone(),
+                            undo_id: undo_id.clone(),
+                            success: false,
+                            files_restored: Vec::new(),
+                            message: Some(e.to_string()),
+                        },
+                    )
+                    .await?;
+                    return Ok(true);
+                }
+            };
+
+            let msg =
+                file_app::file_undo_replace_message(&ws_ctx.root_path, project, workspace, undo_id);
+            send_message(socket, &msg).await?;
+            Ok(true)
+        }
         ClientMessage::FileCopy {
             dest_project,
             dest_workspace,
@@ -34,6 +34,19 @@ pub(crate) async fn query_file_index(
     Ok(file_app::file_index_message(&ws_ctx.root_path, project, workspace, query).await)
 }
 
+pub(crate) async fn query_file_query(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+    query: &str,
+    limit: Option<usize>,
+) -> Result<crate::server::protocol::ServerMessage, crate::server::protocol::ServerMessage> {
+    let ws_ctx = resolve_workspace(app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_server_error())?;
+    Ok(file_app::file_query_message(&ws_ctx.root_path, project, workspace, query, limit).await)
+}
+
 pub(crate) async fn query_file_content_search(
     app_state: &SharedAppState,
     project: &str,
@@ -56,6 +69,69 @@ pub(crate) async fn query_file_content_search(
     )
 }
 
+pub(crate) async fn query_file_definition_guess(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+    symbol: &str,
+    path: Option<&str>,
+) -> Result<crate::server::protocol::ServerMessage, crate::server::protocol::ServerMessage> {
+    let ws_ctx = resolve_workspace(app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_server_error())?;
+    Ok(
+        file_app::file_definition_guess_message(
+            &ws_ctx.root_path,
+            project,
+            workspace,
+            symbol,
+            path,
+        )
+        .await,
+    )
+}
+
+pub(crate) async fn query_file_editorconfig(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+    path: &str,
+) -> Result<crate::server::protocol::ServerMessage, crate::server::protocol::ServerMessage> {
+    let ws_ctx = resolve_workspace(app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_server_error())?;
+    Ok(file_app::file_editorconfig_message(
+        &ws_ctx.root_path,
+        project,
+        workspace,
+        path,
+    ))
+}
+
+pub(crate) async fn query_file_highlight(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+    path: &str,
+    theme: Option<&str>,
+    format: Option<&str>,
+) -> Result<crate::server::protocol::ServerMessage, crate::server::protocol::ServerMessage> {
+    let ws_ctx = resolve_workspace(app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_server_error())?;
+    Ok(
+        file_app::file_highlight_message(
+            &ws_ctx.root_path,
+            project,
+            workspace,
+            path,
+            theme,
+            format,
+        )
+        .await,
+    )
+}
+
 pub async fn handle_query_message(
     client_msg: &ClientMessage,
     socket: &WebSocket,
@@ -90,6 +166,21 @@ pub async fn handle_query_message(
                 Ok(true)
             }
         },
+        ClientMessage::FileQuery {
+            project,
+            workspace,
+            query,
+            limit,
+        } => match query_file_query(app_state, project, workspace, query, *limit).await {
+            Ok(msg) => {
+                send_message(socket, &msg).await?;
+                Ok(true)
+            }
+            Err(err) => {
+                send_message(socket, &err).await?;
+                Ok(true)
+            }
+        },
         _ => Ok(false),
     }
 }
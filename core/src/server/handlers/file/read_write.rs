@@ -2,7 +2,8 @@ use crate::server::ws::OutboundTx as WebSocket;
 
 use crate::application::file as file_app;
 use crate::server::context::{resolve_workspace, SharedAppState};
-use crate::server::protocol::ClientMessage;
+use crate::server::file_api::{DEFAULT_CHUNK_SIZE, MAX_CHUNK_SIZE};
+use crate::server::protocol::{ClientMessage, ServerMessage};
 use crate::server::ws::send_message;
 
 pub(crate) async fn query_file_read(
@@ -10,6 +11,8 @@ pub(crate) async fn query_file_read(
     project: &str,
     workspace: &str,
     path: &str,
+    encoding: Option<&str>,
+    preview: bool,
 ) -> Result<crate::server::protocol::ServerMessage, crate::server::protocol::ServerMessage> {
     let ws_ctx = resolve_workspace(app_state, project, workspace)
         .await
@@ -19,9 +22,43 @@ pub(crate) async fn query_file_read(
         project,
         workspace,
         path,
+        encoding,
+        preview,
     ))
 }
 
+pub(crate) async fn query_file_read_at_revision(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+    path: &str,
+    rev: &str,
+) -> Result<ServerMessage, ServerMessage> {
+    let ws_ctx = resolve_workspace(app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_server_error())?;
+    let root = ws_ctx.root_path;
+    let (project, workspace, path, rev) = (
+        project.to_string(),
+        workspace.to_string(),
+        path.to_string(),
+        rev.to_string(),
+    );
+    crate::util::trace::spawn_blocking(move || {
+        file_app::file_read_at_revision_message(&root, &project, &workspace, &path, &rev)
+    })
+    .await
+    .map_err(|e| ServerMessage::Error {
+        code: "internal_error".to_string(),
+        message: format!("File read at revision task failed: {}", e),
+        project: None,
+        workspace: None,
+        session_id: None,
+        cycle_id: None,
+        trace_id: None,
+    })
+}
+
 pub async fn handle_read_write_message(
     client_msg: &ClientMessage,
     socket: &WebSocket,
@@ -32,21 +69,66 @@ pub async fn handle_read_write_message(
             project,
             workspace,
             path,
-        } => match query_file_read(app_state, project, workspace, path).await {
-            Ok(msg) => {
-                send_message(socket, &msg).await?;
-                Ok(true)
-            }
-            Err(err) => {
-                send_message(socket, &err).await?;
-                Ok(true)
+            encoding,
+            preview,
+        } => {
+            match query_file_read(
+                app_state,
+                project,
+                workspace,
+                path,
+                encoding.as_deref(),
+                *preview,
+            )
+            .await
+            {
+                Ok(msg) => {
+                    send_message(socket, &msg).await?;
+                    Ok(true)
+                }
+                Err(err) => {
+                    send_message(socket, &err).await?;
+                    Ok(true)
+                }
             }
-        },
+        }
         ClientMessage::FileWrite {
             project,
             workspace,
             path,
             content,
+            encoding,
+            expected_mtime,
+            expected_hash,
+        } => {
+            let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
+                Ok(ctx) => ctx,
+                Err(e) => {
+                    send_message(socket, &e.to_server_error()).await?;
+                    return Ok(true);
+                }
+            };
+
+            let msg = file_app::file_write_message(
+                &ws_ctx.root_path,
+                project,
+                workspace,
+                path,
+                content,
+                encoding.as_deref(),
+                *expected_mtime,
+                expected_hash.as_deref(),
+            );
+            send_message(socket, &msg).await?;
+            Ok(true)
+        }
+        ClientMessage::FileApplyEdit {
+            project,
+            workspace,
+            path,
+            edits,
+            expected_mtime,
+            expected_hash,
         } => {
             let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
                 Ok(ctx) => ctx,
@@ -56,11 +138,98 @@ pub async fn handle_read_write_message(
                 }
             };
 
-            let msg =
-                file_app::file_write_message(&ws_ctx.root_path, project, workspace, path, content);
+            let msg = file_app::file_apply_edit_message(
+                &ws_ctx.root_path,
+                project,
+                workspace,
+                path,
+                edits,
+                *expected_mtime,
+                expected_hash.as_deref(),
+            );
             send_message(socket, &msg).await?;
             Ok(true)
         }
+        ClientMessage::FileReadChunked {
+            project,
+            workspace,
+            path,
+            offset,
+            length,
+            stream,
+            chunk_size,
+        } => {
+            let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
+                Ok(ctx) => ctx,
+                Err(e) => {
+                    send_message(socket, &e.to_server_error()).await?;
+                    return Ok(true);
+                }
+            };
+
+            // 非流式：单块，长度默认一个块；流式：按块推送直到 length 耗尽或文件末尾
+            let chunk_size = if *stream {
+                chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE)
+            } else {
+                length.unwrap_or(DEFAULT_CHUNK_SIZE)
+            }
+            .clamp(1, MAX_CHUNK_SIZE);
+            let end = if *stream {
+                length.map(|len| offset.saturating_add(len))
+            } else {
+                Some(offset.saturating_add(chunk_size))
+            };
+
+            let mut next_offset = *offset;
+            loop {
+                // 客户端取消后不再读取后续分块
+                if crate::util::cancel::is_cancelled() {
+                    let cancelled = ServerMessage::Error {
+                        code: crate::util::cancel::CANCELLED_CODE.to_string(),
+                        message: "File read cancelled".to_string(),
+                        project: Some(project.clone()),
+                        workspace: Some(workspace.clone()),
+                        session_id: None,
+                        cycle_id: None,
+                        trace_id: None,
+                    };
+                    send_message(socket, &cancelled).await?;
+                    break;
+                }
+                let want = end
+                    .map(|end| end.saturating_sub(next_offset).min(chunk_size))
+                    .unwrap_or(chunk_size);
+                let root = ws_ctx.root_path.clone();
+                let (project_c, workspace_c, path_c) =
+                    (project.clone(), workspace.clone(), path.clone());
+                let msg = crate::util::trace::spawn_blocking(move || {
+                    file_app::file_read_chunk_message(
+                        &root,
+                        &project_c,
+                        &workspace_c,
+                        &path_c,
+                        next_offset,
+                        want,
+                        end,
+                    )
+                })
+                .await
+                .map_err(|e| format!("File chunk task failed: {}", e))?;
+
+                let advance = match &msg {
+                    ServerMessage::FileChunk { content, last, .. } if !*last => {
+                        Some(content.len() as u64)
+                    }
+                    _ => None,
+                };
+                send_message(socket, &msg).await?;
+                match advance {
+                    Some(n) => next_offset += n,
+                    None => break,
+                }
+            }
+            Ok(true)
+        }
         _ => Ok(false),
     }
 }
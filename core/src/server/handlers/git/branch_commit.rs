@@ -11,6 +11,7 @@ use crate::server::git;
 use crate::server::protocol::{ClientMessage, GitBranchInfo, ServerMessage};
 use crate::server::ws::send_message;
 use crate::util::shell_launch::{wrap_command_for_login_zsh, LOGIN_ZSH_PATH};
+use crate::workspace::state::DEFAULT_WORKSPACE_NAME;
 
 pub async fn handle_message(
     client_msg: &ClientMessage,
@@ -30,14 +31,14 @@ pub async fn handle_message(
             };
 
             let root = ws_ctx.root_path;
-            let result = tokio::task::spawn_blocking(move || git::git_branches(&root)).await;
+            let result = crate::util::trace::spawn_blocking(move || git::git_branches(&root)).await;
 
             match result {
                 Ok(Ok(branches_result)) => {
                     let branches: Vec<GitBranchInfo> = branches_result
                         .branches
                         .into_iter()
-                        .map(|b| GitBranchInfo { name: b.name })
+                        .map(GitBranchInfo::from)
                         .collect();
 
                     send_message(
@@ -61,6 +62,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -75,6 +77,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -88,6 +91,7 @@ pub async fn handle_message(
             project,
             workspace,
             branch,
+            force_detach,
         } => {
             let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
                 Ok(ctx) => ctx,
@@ -99,9 +103,11 @@ pub async fn handle_message(
 
             let root = ws_ctx.root_path;
             let branch_clone = branch.clone();
-            let result =
-                tokio::task::spawn_blocking(move || git::git_switch_branch(&root, &branch_clone))
-                    .await;
+            let force_detach = *force_detach;
+            let result = crate::util::trace::spawn_blocking(move || {
+                git::git_switch_branch(&root, &branch_clone, force_detach)
+            })
+            .await;
 
             match result {
                 Ok(Ok(op_result)) => {
@@ -119,6 +125,10 @@ pub async fn handle_message(
                     )
                     .await?;
                 }
+                Ok(Err(e @ git::GitError::BranchInUse { .. })) => {
+                    send_message(socket, &branch_in_use_error(app_state, project, &e).await)
+                        .await?;
+                }
                 Ok(Err(e)) => {
                     send_message(
                         socket,
@@ -144,6 +154,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -168,9 +179,10 @@ pub async fn handle_message(
 
             let root = ws_ctx.root_path;
             let branch_clone = branch.clone();
-            let result =
-                tokio::task::spawn_blocking(move || git::git_create_branch(&root, &branch_clone))
-                    .await;
+            let result = crate::util::trace::spawn_blocking(move || {
+                git::git_create_branch(&root, &branch_clone)
+            })
+            .await;
 
             match result {
                 Ok(Ok(op_result)) => {
@@ -188,6 +200,10 @@ pub async fn handle_message(
                     )
                     .await?;
                 }
+                Ok(Err(e @ git::GitError::BranchInUse { .. })) => {
+                    send_message(socket, &branch_in_use_error(app_state, project, &e).await)
+                        .await?;
+                }
                 Ok(Err(e)) => {
                     send_message(
                         socket,
@@ -213,6 +229,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -226,6 +243,7 @@ pub async fn handle_message(
             project,
             workspace,
             message,
+            sign,
         } => {
             let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
                 Ok(ctx) => ctx,
@@ -237,8 +255,11 @@ pub async fn handle_message(
 
             let root = ws_ctx.root_path;
             let message_clone = message.clone();
-            let result =
-                tokio::task::spawn_blocking(move || git::git_commit(&root, &message_clone)).await;
+            let sign = *sign;
+            let result = crate::util::trace::spawn_blocking(move || {
+                git::git_commit(&root, &message_clone, sign)
+            })
+            .await;
 
             match result {
                 Ok(Ok(commit_result)) => {
@@ -250,6 +271,8 @@ pub async fn handle_message(
                             ok: commit_result.ok,
                             message: commit_result.message,
                             sha: commit_result.sha,
+                            signed: commit_result.signed,
+                            error_code: commit_result.error_code,
                         },
                     )
                     .await?;
@@ -263,6 +286,8 @@ pub async fn handle_message(
                             ok: false,
                             message: Some(format!("{}", e)),
                             sha: None,
+                            signed: false,
+                            error_code: Some(git::COMMIT_ERROR_OTHER.to_string()),
                         },
                     )
                     .await?;
@@ -277,6 +302,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -641,3 +667,45 @@ mod tests {
         assert!(err.contains("empty"));
     }
 }
+
+/// 分支被其他 worktree 占用：`workspace` 为占用方的工作区名（无法对应到工作区时省略）
+async fn branch_in_use_error(
+    app_state: &SharedAppState,
+    project: &str,
+    error: &git::GitError,
+) -> ServerMessage {
+    let owner = match error {
+        git::GitError::BranchInUse { worktree_path, .. } => {
+            workspace_for_path(app_state, project, Path::new(worktree_path)).await
+        }
+        _ => None,
+    };
+    ServerMessage::Error {
+        code: git::BRANCH_IN_USE_CODE.to_string(),
+        message: error.to_string(),
+        project: Some(project.to_string()),
+        workspace: owner,
+        session_id: None,
+        cycle_id: None,
+        trace_id: None,
+    }
+}
+
+/// 按 worktree 路径反查工作区名（`default` 对应项目根目录）
+async fn workspace_for_path(
+    app_state: &SharedAppState,
+    project: &str,
+    path: &Path,
+) -> Option<String> {
+    let target = path.canonicalize().ok()?;
+    let state = app_state.read().await;
+    let proj = state.get_project(project)?;
+    std::iter::once((DEFAULT_WORKSPACE_NAME, &proj.root_path))
+        .chain(
+            proj.workspaces
+                .iter()
+                .map(|(name, ws)| (name.as_str(), &ws.worktree_path)),
+        )
+        .find(|(_, root)| root.canonicalize().ok().as_ref() == Some(&target))
+        .map(|(name, _)| name.to_string())
+}
@@ -0,0 +1,69 @@
+//! 生成提交信息 WS 处理器：暂存区 diff 交给项目配置的外部命令或 HTTP 端点
+
+use crate::server::context::{resolve_workspace, SharedAppState};
+use crate::server::git;
+use crate::server::protocol::{ClientMessage, ServerMessage};
+use crate::server::ws::send_message;
+use crate::server::ws::OutboundTx as WebSocket;
+
+pub async fn handle_message(
+    client_msg: &ClientMessage,
+    socket: &WebSocket,
+    app_state: &SharedAppState,
+) -> Result<bool, String> {
+    let ClientMessage::GitGenerateCommitMessage {
+        project,
+        workspace,
+        link_url,
+    } = client_msg
+    else {
+        return Ok(false);
+    };
+
+    let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
+        Ok(ctx) => ctx,
+        Err(e) => {
+            send_message(socket, &e.to_server_error()).await?;
+            return Ok(true);
+        }
+    };
+    let link_url = match link_url.as_deref().map(str::trim) {
+        Some(url) if !url.is_empty() => Some(url.to_string()),
+        _ => app_state
+            .read()
+            .await
+            .get_project(project)
+            .and_then(|p| p.get_workspace(workspace))
+            .and_then(|w| w.linked_issue.clone()),
+    };
+
+    let result =
+        git::git_generate_commit_message(ws_ctx.root_path, workspace.clone(), link_url).await;
+    let msg = match result {
+        Ok(suggestion) => ServerMessage::GitSuggestedCommitMessageResult {
+            project: project.clone(),
+            workspace: workspace.clone(),
+            message: suggestion.message,
+            source: suggestion.source,
+            generator_available: suggestion.generator_available,
+            template: suggestion.template,
+            issue_id: suggestion.issue_id,
+            commit_type: suggestion.commit_type,
+            scope: suggestion.scope,
+            summary: suggestion.summary,
+            branch: suggestion.branch,
+            staged_files: suggestion.staged_files,
+        },
+        Err(e) => ServerMessage::Error {
+            code: "git_error".to_string(),
+            message: format!("Git generate commit message failed: {}", e),
+            project: Some(project.clone()),
+            workspace: Some(workspace.clone()),
+            session_id: None,
+            cycle_id: None,
+            trace_id: None,
+        },
+    };
+    send_message(socket, &msg).await?;
+    Ok(true)
+}
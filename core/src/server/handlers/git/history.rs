@@ -16,6 +16,14 @@ pub async fn handle_message(
             project,
             workspace,
             limit,
+            skip,
+            before_sha,
+            author,
+            path,
+            grep,
+            since,
+            until,
+            branch,
         } => {
             let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
                 Ok(ctx) => ctx,
@@ -27,7 +35,21 @@ pub async fn handle_message(
 
             let root = ws_ctx.root_path;
             let limit_copy = *limit;
-            let result = tokio::task::spawn_blocking(move || git::git_log(&root, limit_copy)).await;
+            let filter = git::GitLogFilter {
+                skip: *skip,
+                before_sha: before_sha.clone(),
+                author: author.clone(),
+                path: path.clone(),
+                grep: grep.clone(),
+                since: since.clone(),
+                until: until.clone(),
+                branch: branch.clone(),
+            }
+            .normalized();
+            let result = crate::util::trace::spawn_blocking(move || {
+                git::git_log(&root, limit_copy, &filter)
+            })
+            .await;
 
             match result {
                 Ok(Ok(log_result)) => {
@@ -49,6 +71,7 @@ pub async fn handle_message(
                             project: project.clone(),
                             workspace: workspace.clone(),
                             entries,
+                            has_more: log_result.has_more,
                         },
                     )
                     .await?;
@@ -63,6 +86,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -77,6 +101,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -85,6 +110,63 @@ pub async fn handle_message(
             Ok(true)
         }
 
+        ClientMessage::GitGenerateChangeReport { project, workspace } => {
+            let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
+                Ok(ctx) => ctx,
+                Err(e) => {
+                    send_message(socket, &e.to_server_error()).await?;
+                    return Ok(true);
+                }
+            };
+
+            let root = ws_ctx.root_path;
+            let default_branch = ws_ctx.default_branch;
+            let (project_name, workspace_name) = (project.clone(), workspace.clone());
+            let result = crate::util::trace::spawn_blocking(move || {
+                git::generate_change_report(&root, &project_name, &workspace_name, &default_branch)
+            })
+            .await;
+
+            let msg = match result {
+                Ok(Ok((report, report_id, size))) => ServerMessage::GitChangeReportResult {
+                    project: project.clone(),
+                    workspace: workspace.clone(),
+                    download_path: format!("/api/v1/change-reports/{}", report_id),
+                    report_id,
+                    file_count: report.files.len(),
+                    total_additions: report.total_additions,
+                    total_deletions: report.total_deletions,
+                    commit_count: report.commits.len(),
+                    truncated: report.files_truncated
+                        || report.commits_truncated
+                        || report.diff_truncated,
+                    base: report.base,
+                    head: report.head,
+                    size,
+                },
+                Ok(Err(e)) => ServerMessage::Error {
+                    code: "git_error".to_string(),
+                    message: format!("Change report failed: {}", e),
+                    project: None,
+                    workspace: None,
+                    session_id: None,
+                    cycle_id: None,
+                    trace_id: None,
+                },
+                Err(e) => ServerMessage::Error {
+                    code: "internal_error".to_string(),
+                    message: format!("Change report task failed: {}", e),
+                    project: None,
+                    workspace: None,
+                    session_id: None,
+                    cycle_id: None,
+                    trace_id: None,
+                },
+            };
+            send_message(socket, &msg).await?;
+            Ok(true)
+        }
+
         // v1.20: Git show (single commit details)
         ClientMessage::GitShow {
             project,
@@ -102,7 +184,7 @@ pub async fn handle_message(
             let root = ws_ctx.root_path;
             let sha_clone = sha.clone();
             let result =
-                tokio::task::spawn_blocking(move || git::git_show(&root, &sha_clone)).await;
+                crate::util::trace::spawn_blocking(move || git::git_show(&root, &sha_clone)).await;
 
             match result {
                 Ok(Ok(show_result)) => {
@@ -142,6 +224,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -156,6 +239,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
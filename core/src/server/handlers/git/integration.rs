@@ -23,6 +23,18 @@ pub async fn handle_message(
             onto_branch,
         } => handlers::handle_git_rebase(project, workspace, onto_branch, socket, app_state).await,
 
+        ClientMessage::GitRebaseInteractiveExecute {
+            project,
+            workspace,
+            base,
+            plan,
+        } => {
+            handlers::handle_git_rebase_interactive_execute(
+                project, workspace, base, plan, socket, app_state,
+            )
+            .await
+        }
+
         ClientMessage::GitRebaseContinue { project, workspace } => {
             handlers::handle_git_rebase_continue(project, workspace, socket, app_state).await
         }
@@ -35,35 +47,46 @@ pub async fn handle_message(
             handlers::handle_git_op_status(project, workspace, socket, app_state).await
         }
 
-        ClientMessage::GitEnsureIntegrationWorktree { project } => {
-            handlers::handle_git_ensure_integration_worktree(project, socket, app_state).await
+        ClientMessage::GitOpCancel { op_id } => handlers::handle_git_op_cancel(op_id, socket).await,
+
+        ClientMessage::GitEnsureIntegrationWorktree { project, branch } => {
+            handlers::handle_git_ensure_integration_worktree(
+                project,
+                branch.as_deref(),
+                socket,
+                app_state,
+            )
+            .await
         }
 
         ClientMessage::GitMergeToDefault {
             project,
             workspace,
             default_branch,
+            verify,
         } => {
             handlers::handle_git_merge_to_default(
                 project,
                 workspace,
-                default_branch,
+                default_branch.as_deref(),
+                *verify,
                 socket,
                 app_state,
             )
             .await
         }
 
-        ClientMessage::GitMergeContinue { project } => {
-            handlers::handle_git_merge_continue(project, socket, app_state).await
+        ClientMessage::GitMergeContinue { project, branch } => {
+            handlers::handle_git_merge_continue(project, branch.as_deref(), socket, app_state).await
         }
 
-        ClientMessage::GitMergeAbort { project } => {
-            handlers::handle_git_merge_abort(project, socket, app_state).await
+        ClientMessage::GitMergeAbort { project, branch } => {
+            handlers::handle_git_merge_abort(project, branch.as_deref(), socket, app_state).await
         }
 
-        ClientMessage::GitIntegrationStatus { project } => {
-            handlers::handle_git_integration_status(project, socket, app_state).await
+        ClientMessage::GitIntegrationStatus { project, branch } => {
+            handlers::handle_git_integration_status(project, branch.as_deref(), socket, app_state)
+                .await
         }
 
         ClientMessage::GitRebaseOntoDefault {
@@ -74,23 +97,41 @@ pub async fn handle_message(
             handlers::handle_git_rebase_onto_default(
                 project,
                 workspace,
-                default_branch,
+                default_branch.as_deref(),
                 socket,
                 app_state,
             )
             .await
         }
 
-        ClientMessage::GitRebaseOntoDefaultContinue { project } => {
-            handlers::handle_git_rebase_onto_default_continue(project, socket, app_state).await
+        ClientMessage::GitRebaseOntoDefaultContinue { project, branch } => {
+            handlers::handle_git_rebase_onto_default_continue(
+                project,
+                branch.as_deref(),
+                socket,
+                app_state,
+            )
+            .await
         }
 
-        ClientMessage::GitRebaseOntoDefaultAbort { project } => {
-            handlers::handle_git_rebase_onto_default_abort(project, socket, app_state).await
+        ClientMessage::GitRebaseOntoDefaultAbort { project, branch } => {
+            handlers::handle_git_rebase_onto_default_abort(
+                project,
+                branch.as_deref(),
+                socket,
+                app_state,
+            )
+            .await
         }
 
-        ClientMessage::GitResetIntegrationWorktree { project } => {
-            handlers::handle_git_reset_integration_worktree(project, socket, app_state).await
+        ClientMessage::GitResetIntegrationWorktree { project, branch } => {
+            handlers::handle_git_reset_integration_worktree(
+                project,
+                branch.as_deref(),
+                socket,
+                app_state,
+            )
+            .await
         }
 
         ClientMessage::GitCheckBranchUpToDate { project, workspace } => {
@@ -123,6 +164,7 @@ pub async fn handle_message(
                 path,
                 context,
                 "accept_ours",
+                None,
                 socket,
                 app_state,
             )
@@ -141,6 +183,7 @@ pub async fn handle_message(
                 path,
                 context,
                 "accept_theirs",
+                None,
                 socket,
                 app_state,
             )
@@ -159,6 +202,7 @@ pub async fn handle_message(
                 path,
                 context,
                 "accept_both",
+                None,
                 socket,
                 app_state,
             )
@@ -177,6 +221,53 @@ pub async fn handle_message(
                 path,
                 context,
                 "mark_resolved",
+                None,
+                socket,
+                app_state,
+            )
+            .await
+        }
+
+        ClientMessage::GitConflictResolve {
+            project,
+            workspace,
+            path,
+            context,
+            resolution,
+            content,
+        } => {
+            let action = match resolution.as_str() {
+                "ours" => "accept_ours",
+                "theirs" => "accept_theirs",
+                "both" => "accept_both",
+                "custom" => "resolve_custom",
+                other => {
+                    crate::server::ws::send_message(
+                        socket,
+                        &crate::server::protocol::ServerMessage::Error {
+                            code: "invalid_request".to_string(),
+                            message: format!(
+                                "Invalid conflict resolution: {} (expected ours, theirs, both or custom)",
+                                other
+                            ),
+                            project: Some(project.clone()),
+                            workspace: Some(workspace.clone()),
+                            session_id: None,
+                            cycle_id: None,
+                            trace_id: None,
+                        },
+                    )
+                    .await?;
+                    return Ok(true);
+                }
+            };
+            handlers::handle_git_conflict_action(
+                project,
+                workspace,
+                path,
+                context,
+                action,
+                content.clone(),
                 socket,
                 app_state,
             )
@@ -1,10 +1,14 @@
 mod fetch;
 mod merge;
+mod progress;
 mod rebase;
 mod status;
+mod verify;
 
 pub(crate) use fetch::handle_git_fetch;
 
+pub(crate) use progress::handle_git_op_cancel;
+
 pub(crate) use merge::{
     handle_git_conflict_action, handle_git_conflict_detail, handle_git_ensure_integration_worktree,
     handle_git_merge_abort, handle_git_merge_continue, handle_git_merge_to_default,
@@ -13,8 +17,8 @@ pub(crate) use merge::{
 
 pub(crate) use rebase::{
     handle_git_rebase, handle_git_rebase_abort, handle_git_rebase_continue,
-    handle_git_rebase_onto_default, handle_git_rebase_onto_default_abort,
-    handle_git_rebase_onto_default_continue,
+    handle_git_rebase_interactive_execute, handle_git_rebase_onto_default,
+    handle_git_rebase_onto_default_abort, handle_git_rebase_onto_default_continue,
 };
 
 pub(crate) use status::{
@@ -19,7 +19,14 @@ pub(crate) async fn handle_git_fetch(
         }
     };
     let root = ws_ctx.root_path;
-    let result = tokio::task::spawn_blocking(move || git::git_fetch(&root)).await;
+    let result = super::progress::run_git_operation(
+        socket,
+        "fetch",
+        project,
+        workspace,
+        move |on_progress| git::git_fetch_with_progress(&root, on_progress),
+    )
+    .await;
     match result {
         Ok(Ok(op_result)) => {
             send_message(
@@ -36,6 +43,36 @@ pub(crate) async fn handle_git_fetch(
             )
             .await?;
         }
+        Ok(Err(git::GitError::OfflineMode(op))) => {
+            send_message(
+                socket,
+                &ServerMessage::Error {
+                    code: git::OFFLINE_MODE_CODE.to_string(),
+                    message: format!("Offline mode is enabled, git {} skipped", op),
+                    project: Some(project.to_string()),
+                    workspace: Some(workspace.to_string()),
+                    session_id: None,
+                    cycle_id: None,
+                    trace_id: None,
+                },
+            )
+            .await?;
+        }
+        Ok(Err(git::GitError::IoError(e))) if crate::util::cancel::is_cancelled_error(&e) => {
+            send_message(
+                socket,
+                &ServerMessage::Error {
+                    code: crate::util::cancel::CANCELLED_CODE.to_string(),
+                    message: "Git fetch cancelled".to_string(),
+                    project: Some(project.to_string()),
+                    workspace: Some(workspace.to_string()),
+                    session_id: None,
+                    cycle_id: None,
+                    trace_id: None,
+                },
+            )
+            .await?;
+        }
         Ok(Err(e)) => {
             send_message(
                 socket,
@@ -61,6 +98,7 @@ pub(crate) async fn handle_git_fetch(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -8,6 +8,7 @@ use crate::server::ws::send_message;
 
 pub(crate) async fn handle_git_ensure_integration_worktree(
     project: &str,
+    branch: Option<&str>,
     socket: &WebSocket,
     app_state: &SharedAppState,
 ) -> Result<bool, String> {
@@ -19,10 +20,10 @@ pub(crate) async fn handle_git_ensure_integration_worktree(
         }
     };
     let root = proj_ctx.root_path;
-    let project_name = proj_ctx.project_name;
-    let default_branch = proj_ctx.default_branch;
-    let result = tokio::task::spawn_blocking(move || {
-        git::ensure_integration_worktree(&root, &project_name, &default_branch)
+    let target =
+        git::IntegrationTarget::resolve(&proj_ctx.project_name, &proj_ctx.default_branch, branch);
+    let result = crate::util::trace::spawn_blocking(move || {
+        git::ensure_integration_worktree(&root, &target)
     })
     .await;
     match result {
@@ -68,6 +69,7 @@ pub(crate) async fn handle_git_ensure_integration_worktree(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -79,7 +81,8 @@ pub(crate) async fn handle_git_ensure_integration_worktree(
 pub(crate) async fn handle_git_merge_to_default(
     project: &str,
     workspace: &str,
-    default_branch: &str,
+    default_branch: Option<&str>,
+    verify: bool,
     socket: &WebSocket,
     app_state: &SharedAppState,
 ) -> Result<bool, String> {
@@ -116,35 +119,39 @@ pub(crate) async fn handle_git_merge_to_default(
         return Ok(true);
     }
 
-    let default_branch_clone = default_branch.to_string();
-    let result = tokio::task::spawn_blocking(move || {
-        git::merge_to_default(&root, &project_name, &source_branch, &default_branch_clone)
+    let target =
+        git::IntegrationTarget::resolve(&project_name, &proj_ctx.default_branch, default_branch);
+    let merge_root = root.clone();
+    let result = crate::util::trace::spawn_blocking(move || {
+        git::merge_to_default(&merge_root, &target, &source_branch)
     })
     .await;
     match result {
         Ok(Ok(r)) => {
-            send_message(
-                socket,
-                &ServerMessage::GitMergeToDefaultResult {
-                    project: project.to_string(),
-                    ok: r.ok,
-                    state: r.state,
-                    message: r.message,
-                    conflicts: r.conflicts,
-                    conflict_files: r
-                        .conflict_files
-                        .iter()
-                        .map(|f| crate::server::protocol::ConflictFileEntryInfo {
-                            path: f.path.clone(),
-                            conflict_type: f.conflict_type.clone(),
-                            staged: f.staged,
-                        })
-                        .collect(),
-                    head_sha: r.head_sha,
-                    integration_path: r.integration_path,
-                },
-            )
-            .await?;
+            // 合并成功后按需在集成 worktree 中运行校验命令
+            let verify_path = r.integration_path.clone().filter(|_| verify && r.ok);
+            let msg = ServerMessage::GitMergeToDefaultResult {
+                project: project.to_string(),
+                ok: r.ok,
+                state: r.state,
+                message: r.message,
+                conflicts: r.conflicts,
+                conflict_files: r
+                    .conflict_files
+                    .iter()
+                    .map(|f| crate::server::protocol::ConflictFileEntryInfo {
+                        path: f.path.clone(),
+                        conflict_type: f.conflict_type.clone(),
+                        staged: f.staged,
+                    })
+                    .collect(),
+                head_sha: r.head_sha,
+                integration_path: r.integration_path,
+            };
+            send_message(socket, &msg).await?;
+            if let Some(path) = verify_path {
+                super::verify::spawn_merge_verify(project.to_string(), root, path, socket.clone());
+            }
         }
         Ok(Err(e)) => {
             send_message(
@@ -172,6 +179,7 @@ pub(crate) async fn handle_git_merge_to_default(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -182,6 +190,7 @@ pub(crate) async fn handle_git_merge_to_default(
 
 pub(crate) async fn handle_git_merge_continue(
     project: &str,
+    branch: Option<&str>,
     socket: &WebSocket,
     app_state: &SharedAppState,
 ) -> Result<bool, String> {
@@ -192,32 +201,30 @@ pub(crate) async fn handle_git_merge_continue(
             return Ok(true);
         }
     };
-    let project_name = proj_ctx.project_name;
-    let result = tokio::task::spawn_blocking(move || git::merge_continue(&project_name)).await;
+    let target =
+        git::IntegrationTarget::resolve(&proj_ctx.project_name, &proj_ctx.default_branch, branch);
+    let result = crate::util::trace::spawn_blocking(move || git::merge_continue(&target)).await;
     match result {
         Ok(Ok(r)) => {
-            send_message(
-                socket,
-                &ServerMessage::GitMergeToDefaultResult {
-                    project: project.to_string(),
-                    ok: r.ok,
-                    state: r.state,
-                    message: r.message,
-                    conflicts: r.conflicts,
-                    conflict_files: r
-                        .conflict_files
-                        .iter()
-                        .map(|f| crate::server::protocol::ConflictFileEntryInfo {
-                            path: f.path.clone(),
-                            conflict_type: f.conflict_type.clone(),
-                            staged: f.staged,
-                        })
-                        .collect(),
-                    head_sha: r.head_sha,
-                    integration_path: r.integration_path,
-                },
-            )
-            .await?;
+            let msg = ServerMessage::GitMergeToDefaultResult {
+                project: project.to_string(),
+                ok: r.ok,
+                state: r.state,
+                message: r.message,
+                conflicts: r.conflicts,
+                conflict_files: r
+                    .conflict_files
+                    .iter()
+                    .map(|f| crate::server::protocol::ConflictFileEntryInfo {
+                        path: f.path.clone(),
+                        conflict_type: f.conflict_type.clone(),
+                        staged: f.staged,
+                    })
+                    .collect(),
+                head_sha: r.head_sha,
+                integration_path: r.integration_path,
+            };
+            send_message(socket, &msg).await?;
         }
         Ok(Err(e)) => {
             send_message(
@@ -245,6 +252,7 @@ pub(crate) async fn handle_git_merge_continue(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -255,6 +263,7 @@ pub(crate) async fn handle_git_merge_continue(
 
 pub(crate) async fn handle_git_merge_abort(
     project: &str,
+    branch: Option<&str>,
     socket: &WebSocket,
     app_state: &SharedAppState,
 ) -> Result<bool, String> {
@@ -265,32 +274,30 @@ pub(crate) async fn handle_git_merge_abort(
             return Ok(true);
         }
     };
-    let project_name = proj_ctx.project_name;
-    let result = tokio::task::spawn_blocking(move || git::merge_abort(&project_name)).await;
+    let target =
+        git::IntegrationTarget::resolve(&proj_ctx.project_name, &proj_ctx.default_branch, branch);
+    let result = crate::util::trace::spawn_blocking(move || git::merge_abort(&target)).await;
     match result {
         Ok(Ok(r)) => {
-            send_message(
-                socket,
-                &ServerMessage::GitMergeToDefaultResult {
-                    project: project.to_string(),
-                    ok: r.ok,
-                    state: r.state,
-                    message: r.message,
-                    conflicts: r.conflicts,
-                    conflict_files: r
-                        .conflict_files
-                        .iter()
-                        .map(|f| crate::server::protocol::ConflictFileEntryInfo {
-                            path: f.path.clone(),
-                            conflict_type: f.conflict_type.clone(),
-                            staged: f.staged,
-                        })
-                        .collect(),
-                    head_sha: r.head_sha,
-                    integration_path: r.integration_path,
-                },
-            )
-            .await?;
+            let msg = ServerMessage::GitMergeToDefaultResult {
+                project: project.to_string(),
+                ok: r.ok,
+                state: r.state,
+                message: r.message,
+                conflicts: r.conflicts,
+                conflict_files: r
+                    .conflict_files
+                    .iter()
+                    .map(|f| crate::server::protocol::ConflictFileEntryInfo {
+                        path: f.path.clone(),
+                        conflict_type: f.conflict_type.clone(),
+                        staged: f.staged,
+                    })
+                    .collect(),
+                head_sha: r.head_sha,
+                integration_path: r.integration_path,
+            };
+            send_message(socket, &msg).await?;
         }
         Ok(Err(e)) => {
             send_message(
@@ -318,6 +325,7 @@ pub(crate) async fn handle_git_merge_abort(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -328,6 +336,7 @@ pub(crate) async fn handle_git_merge_abort(
 
 pub(crate) async fn handle_git_reset_integration_worktree(
     project: &str,
+    branch: Option<&str>,
     socket: &WebSocket,
     app_state: &SharedAppState,
 ) -> Result<bool, String> {
@@ -338,11 +347,11 @@ pub(crate) async fn handle_git_reset_integration_worktree(
             return Ok(true);
         }
     };
-    let project_name = proj_ctx.project_name;
     let repo_root = proj_ctx.root_path;
-    let default_branch = proj_ctx.default_branch;
-    let result = tokio::task::spawn_blocking(move || {
-        git::reset_integration_worktree(&PathBuf::from(&repo_root), &project_name, &default_branch)
+    let target =
+        git::IntegrationTarget::resolve(&proj_ctx.project_name, &proj_ctx.default_branch, branch);
+    let result = crate::util::trace::spawn_blocking(move || {
+        git::reset_integration_worktree(&PathBuf::from(&repo_root), &target)
     })
     .await;
     match result {
@@ -380,6 +389,7 @@ pub(crate) async fn handle_git_reset_integration_worktree(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -405,7 +415,7 @@ pub(crate) async fn handle_git_conflict_detail(
     use crate::server::git;
 
     // 根据 context 选择工作目录
-    let root = if context == "integration" {
+    let root = if let Some(branch) = git::integration_context_branch(context) {
         // 集成工作树路径
         let proj_ctx = match crate::server::context::resolve_project(app_state, project).await {
             Ok(ctx) => ctx,
@@ -414,8 +424,8 @@ pub(crate) async fn handle_git_conflict_detail(
                 return Ok(true);
             }
         };
-        let project_name = proj_ctx.project_name.clone();
-        git::get_integration_worktree_root(&project_name)
+        git::IntegrationTarget::resolve(&proj_ctx.project_name, &proj_ctx.default_branch, branch)
+            .worktree_path()
     } else {
         let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
             Ok(ctx) => ctx,
@@ -429,7 +439,7 @@ pub(crate) async fn handle_git_conflict_detail(
 
     let path_owned = path.to_string();
     let context_owned = context.to_string();
-    let result = tokio::task::spawn_blocking(move || {
+    let result = crate::util::trace::spawn_blocking(move || {
         git::git_conflict_detail(&root, &path_owned, &context_owned)
     })
     .await;
@@ -449,6 +459,9 @@ pub(crate) async fn handle_git_conflict_detail(
                     current_content: detail.current_content,
                     conflict_markers_count: detail.conflict_markers_count,
                     is_binary: detail.is_binary,
+                    regions: crate::server::handlers::git::query::conflict_region_infos(
+                        detail.regions,
+                    ),
                 },
             )
             .await?;
@@ -463,6 +476,7 @@ pub(crate) async fn handle_git_conflict_detail(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -477,6 +491,7 @@ pub(crate) async fn handle_git_conflict_detail(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -485,20 +500,23 @@ pub(crate) async fn handle_git_conflict_detail(
     Ok(true)
 }
 
-/// 执行冲突解决动作（accept_ours/accept_theirs/accept_both/mark_resolved）
+/// 执行冲突解决动作（accept_ours/accept_theirs/accept_both/mark_resolved/resolve_custom）
+///
+/// `content` 仅用于 resolve_custom，作为解决后的完整文件内容。
 pub(crate) async fn handle_git_conflict_action(
     project: &str,
     workspace: &str,
     path: &str,
     context: &str,
     action: &str,
+    content: Option<String>,
     socket: &crate::server::ws::OutboundTx,
     app_state: &crate::server::context::SharedAppState,
 ) -> Result<bool, String> {
     use crate::server::context::resolve_workspace;
     use crate::server::git;
 
-    let root = if context == "integration" {
+    let root = if let Some(branch) = git::integration_context_branch(context) {
         let proj_ctx = match crate::server::context::resolve_project(app_state, project).await {
             Ok(ctx) => ctx,
             Err(e) => {
@@ -506,8 +524,8 @@ pub(crate) async fn handle_git_conflict_action(
                 return Ok(true);
             }
         };
-        let project_name = proj_ctx.project_name.clone();
-        git::get_integration_worktree_root(&project_name)
+        git::IntegrationTarget::resolve(&proj_ctx.project_name, &proj_ctx.default_branch, branch)
+            .worktree_path()
     } else {
         let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
             Ok(ctx) => ctx,
@@ -522,11 +540,19 @@ pub(crate) async fn handle_git_conflict_action(
     let path_owned = path.to_string();
     let context_owned = context.to_string();
     let action_owned = action.to_string();
-    let result = tokio::task::spawn_blocking(move || match action_owned.as_str() {
+    let result = crate::util::trace::spawn_blocking(move || match action_owned.as_str() {
         "accept_ours" => git::git_conflict_accept_ours(&root, &path_owned, &context_owned),
         "accept_theirs" => git::git_conflict_accept_theirs(&root, &path_owned, &context_owned),
         "accept_both" => git::git_conflict_accept_both(&root, &path_owned, &context_owned),
         "mark_resolved" => git::git_conflict_mark_resolved(&root, &path_owned, &context_owned),
+        "resolve_custom" => match content {
+            Some(content) => {
+                git::git_conflict_resolve_custom(&root, &path_owned, &context_owned, &content)
+            }
+            None => Err(crate::server::git::GitError::CommandFailed(
+                "Custom resolution requires content".to_string(),
+            )),
+        },
         other => Err(crate::server::git::GitError::CommandFailed(format!(
             "Unknown conflict action: {}",
             other
@@ -576,6 +602,7 @@ pub(crate) async fn handle_git_conflict_action(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -590,6 +617,7 @@ pub(crate) async fn handle_git_conflict_action(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -0,0 +1,74 @@
+use crate::server::ws::OutboundTx as WebSocket;
+
+use crate::server::git;
+use crate::server::protocol::ServerMessage;
+use crate::server::ws::send_message;
+use crate::workspace::project::CloneProgress;
+
+/// 以新登记的 `op_id` 执行长耗时 git 操作：推送 `git_op_progress`，并可经 `git_op_cancel` 取消
+///
+/// `work` 在阻塞线程上执行，进度回调中同一阶段的百分比不变时不重复推送。
+pub(super) async fn run_git_operation<R, F>(
+    socket: &WebSocket,
+    op: &str,
+    project: &str,
+    workspace: &str,
+    work: F,
+) -> Result<R, tokio::task::JoinError>
+where
+    R: Send + 'static,
+    F: FnOnce(&mut dyn FnMut(CloneProgress)) -> R + Send + 'static,
+{
+    let operation = git::begin_operation(op, project, workspace);
+    let op_id = operation.op_id().to_string();
+    let progress_message = {
+        let (op_id, op) = (op_id.clone(), op.to_string());
+        let (project, workspace) = (project.to_string(), workspace.to_string());
+        move |progress: CloneProgress| ServerMessage::GitOpProgress {
+            op_id: op_id.clone(),
+            op: op.clone(),
+            project: project.clone(),
+            workspace: workspace.clone(),
+            phase: progress.phase,
+            percent: progress.percent,
+            message: progress.message,
+        }
+    };
+    let started = progress_message(CloneProgress {
+        phase: "started".to_string(),
+        percent: None,
+        message: format!("git {} started", op),
+    });
+    let _ = send_message(socket, &started).await;
+
+    let progress_socket = socket.clone();
+    let mut last: Option<(String, Option<u8>)> = None;
+    let mut on_progress = move |progress: CloneProgress| {
+        let key = (progress.phase.clone(), progress.percent);
+        if last.as_ref() == Some(&key) {
+            return;
+        }
+        last = Some(key);
+        // 在阻塞线程上调用，队列满时丢弃进度而不是等待
+        let _ = progress_socket.try_send(progress_message(progress));
+    };
+    let result = crate::util::cancel::scope(op_id, async move {
+        crate::util::trace::spawn_blocking(move || work(&mut on_progress)).await
+    })
+    .await;
+    drop(operation);
+    result
+}
+
+pub(crate) async fn handle_git_op_cancel(op_id: &str, socket: &WebSocket) -> Result<bool, String> {
+    let cancelled = git::cancel_operation(op_id);
+    send_message(
+        socket,
+        &ServerMessage::GitOpCancelResult {
+            op_id: op_id.to_string(),
+            cancelled,
+        },
+    )
+    .await?;
+    Ok(true)
+}
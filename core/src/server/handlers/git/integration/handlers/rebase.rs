@@ -4,7 +4,7 @@ use crate::server::context::{
     resolve_project, resolve_workspace, resolve_workspace_branch, SharedAppState,
 };
 use crate::server::git;
-use crate::server::protocol::ServerMessage;
+use crate::server::protocol::{GitRebasePlanStepInfo, ServerMessage};
 use crate::server::ws::send_message;
 
 pub(crate) async fn handle_git_rebase(
@@ -23,27 +23,46 @@ pub(crate) async fn handle_git_rebase(
     };
     let root = ws_ctx.root_path;
     let onto_clone = onto_branch.to_string();
-    let result = tokio::task::spawn_blocking(move || git::git_rebase(&root, &onto_clone)).await;
+    let result = super::progress::run_git_operation(
+        socket,
+        "rebase",
+        project,
+        workspace,
+        move |on_progress| git::git_rebase_with_progress(&root, &onto_clone, on_progress),
+    )
+    .await;
     match result {
         Ok(Ok(r)) => {
+            let msg = ServerMessage::GitRebaseResult {
+                project: project.to_string(),
+                workspace: workspace.to_string(),
+                ok: r.ok,
+                state: r.state,
+                message: r.message,
+                conflicts: r.conflicts,
+                conflict_files: r
+                    .conflict_files
+                    .iter()
+                    .map(|f| crate::server::protocol::ConflictFileEntryInfo {
+                        path: f.path.clone(),
+                        conflict_type: f.conflict_type.clone(),
+                        staged: f.staged,
+                    })
+                    .collect(),
+            };
+            send_message(socket, &msg).await?;
+        }
+        Ok(Err(git::GitError::IoError(e))) if crate::util::cancel::is_cancelled_error(&e) => {
             send_message(
                 socket,
-                &ServerMessage::GitRebaseResult {
-                    project: project.to_string(),
-                    workspace: workspace.to_string(),
-                    ok: r.ok,
-                    state: r.state,
-                    message: r.message,
-                    conflicts: r.conflicts,
-                    conflict_files: r
-                        .conflict_files
-                        .iter()
-                        .map(|f| crate::server::protocol::ConflictFileEntryInfo {
-                            path: f.path.clone(),
-                            conflict_type: f.conflict_type.clone(),
-                            staged: f.staged,
-                        })
-                        .collect(),
+                &ServerMessage::Error {
+                    code: crate::util::cancel::CANCELLED_CODE.to_string(),
+                    message: "Git rebase cancelled".to_string(),
+                    project: Some(project.to_string()),
+                    workspace: Some(workspace.to_string()),
+                    session_id: None,
+                    cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -73,6 +92,7 @@ pub(crate) async fn handle_git_rebase(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -81,12 +101,42 @@ pub(crate) async fn handle_git_rebase(
     Ok(true)
 }
 
-pub(crate) async fn handle_git_rebase_continue(
+pub(crate) async fn handle_git_rebase_interactive_execute(
     project: &str,
     workspace: &str,
+    base: &str,
+    plan: &[GitRebasePlanStepInfo],
     socket: &WebSocket,
     app_state: &SharedAppState,
 ) -> Result<bool, String> {
+    let mut steps = Vec::with_capacity(plan.len());
+    for step in plan {
+        let Some(action) = git::RebasePlanAction::parse(&step.action) else {
+            send_message(
+                socket,
+                &ServerMessage::Error {
+                    code: "invalid_request".to_string(),
+                    message: format!(
+                        "Invalid rebase action: {} (expected pick, reword, squash, fixup or drop)",
+                        step.action
+                    ),
+                    project: Some(project.to_string()),
+                    workspace: Some(workspace.to_string()),
+                    session_id: None,
+                    cycle_id: None,
+                    trace_id: None,
+                },
+            )
+            .await?;
+            return Ok(true);
+        };
+        steps.push(git::RebasePlanStep {
+            sha: step.sha.clone(),
+            action,
+            message: step.message.clone(),
+        });
+    }
+
     let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
         Ok(ctx) => ctx,
         Err(e) => {
@@ -95,31 +145,102 @@ pub(crate) async fn handle_git_rebase_continue(
         }
     };
     let root = ws_ctx.root_path;
-    let result = tokio::task::spawn_blocking(move || git::git_rebase_continue(&root)).await;
+    let base_clone = base.to_string();
+    let result = crate::util::trace::spawn_blocking(move || {
+        git::git_rebase_interactive_execute(&root, &base_clone, &steps)
+    })
+    .await;
     match result {
         Ok(Ok(r)) => {
+            let msg = ServerMessage::GitRebaseResult {
+                project: project.to_string(),
+                workspace: workspace.to_string(),
+                ok: r.ok,
+                state: r.state,
+                message: r.message,
+                conflicts: r.conflicts,
+                conflict_files: r
+                    .conflict_files
+                    .iter()
+                    .map(|f| crate::server::protocol::ConflictFileEntryInfo {
+                        path: f.path.clone(),
+                        conflict_type: f.conflict_type.clone(),
+                        staged: f.staged,
+                    })
+                    .collect(),
+            };
+            send_message(socket, &msg).await?;
+        }
+        Ok(Err(e)) => {
             send_message(
                 socket,
                 &ServerMessage::GitRebaseResult {
                     project: project.to_string(),
                     workspace: workspace.to_string(),
-                    ok: r.ok,
-                    state: r.state,
-                    message: r.message,
-                    conflicts: r.conflicts,
-                    conflict_files: r
-                        .conflict_files
-                        .iter()
-                        .map(|f| crate::server::protocol::ConflictFileEntryInfo {
-                            path: f.path.clone(),
-                            conflict_type: f.conflict_type.clone(),
-                            staged: f.staged,
-                        })
-                        .collect(),
+                    ok: false,
+                    state: "error".to_string(),
+                    message: Some(format!("{}", e)),
+                    conflicts: vec![],
+                    conflict_files: vec![],
                 },
             )
             .await?;
         }
+        Err(e) => {
+            send_message(
+                socket,
+                &ServerMessage::Error {
+                    code: "internal_error".to_string(),
+                    message: format!("Git interactive rebase task failed: {}", e),
+                    project: None,
+                    workspace: None,
+                    session_id: None,
+                    cycle_id: None,
+                    trace_id: None,
+                },
+            )
+            .await?;
+        }
+    }
+    Ok(true)
+}
+
+pub(crate) async fn handle_git_rebase_continue(
+    project: &str,
+    workspace: &str,
+    socket: &WebSocket,
+    app_state: &SharedAppState,
+) -> Result<bool, String> {
+    let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
+        Ok(ctx) => ctx,
+        Err(e) => {
+            send_message(socket, &e.to_server_error()).await?;
+            return Ok(true);
+        }
+    };
+    let root = ws_ctx.root_path;
+    let result = crate::util::trace::spawn_blocking(move || git::git_rebase_continue(&root)).await;
+    match result {
+        Ok(Ok(r)) => {
+            let msg = ServerMessage::GitRebaseResult {
+                project: project.to_string(),
+                workspace: workspace.to_string(),
+                ok: r.ok,
+                state: r.state,
+                message: r.message,
+                conflicts: r.conflicts,
+                conflict_files: r
+                    .conflict_files
+                    .iter()
+                    .map(|f| crate::server::protocol::ConflictFileEntryInfo {
+                        path: f.path.clone(),
+                        conflict_type: f.conflict_type.clone(),
+                        staged: f.staged,
+                    })
+                    .collect(),
+            };
+            send_message(socket, &msg).await?;
+        }
         Ok(Err(e)) => {
             send_message(
                 socket,
@@ -145,6 +266,7 @@ pub(crate) async fn handle_git_rebase_continue(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -167,30 +289,27 @@ pub(crate) async fn handle_git_rebase_abort(
         }
     };
     let root = ws_ctx.root_path;
-    let result = tokio::task::spawn_blocking(move || git::git_rebase_abort(&root)).await;
+    let result = crate::util::trace::spawn_blocking(move || git::git_rebase_abort(&root)).await;
     match result {
         Ok(Ok(r)) => {
-            send_message(
-                socket,
-                &ServerMessage::GitRebaseResult {
-                    project: project.to_string(),
-                    workspace: workspace.to_string(),
-                    ok: r.ok,
-                    state: r.state,
-                    message: r.message,
-                    conflicts: r.conflicts,
-                    conflict_files: r
-                        .conflict_files
-                        .iter()
-                        .map(|f| crate::server::protocol::ConflictFileEntryInfo {
-                            path: f.path.clone(),
-                            conflict_type: f.conflict_type.clone(),
-                            staged: f.staged,
-                        })
-                        .collect(),
-                },
-            )
-            .await?;
+            let msg = ServerMessage::GitRebaseResult {
+                project: project.to_string(),
+                workspace: workspace.to_string(),
+                ok: r.ok,
+                state: r.state,
+                message: r.message,
+                conflicts: r.conflicts,
+                conflict_files: r
+                    .conflict_files
+                    .iter()
+                    .map(|f| crate::server::protocol::ConflictFileEntryInfo {
+                        path: f.path.clone(),
+                        conflict_type: f.conflict_type.clone(),
+                        staged: f.staged,
+                    })
+                    .collect(),
+            };
+            send_message(socket, &msg).await?;
         }
         Ok(Err(e)) => {
             send_message(
@@ -217,6 +336,7 @@ pub(crate) async fn handle_git_rebase_abort(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -228,7 +348,7 @@ pub(crate) async fn handle_git_rebase_abort(
 pub(crate) async fn handle_git_rebase_onto_default(
     project: &str,
     workspace: &str,
-    default_branch: &str,
+    default_branch: Option<&str>,
     socket: &WebSocket,
     app_state: &SharedAppState,
 ) -> Result<bool, String> {
@@ -264,35 +384,33 @@ pub(crate) async fn handle_git_rebase_onto_default(
         return Ok(true);
     }
 
-    let default_branch_clone = default_branch.to_string();
-    let result = tokio::task::spawn_blocking(move || {
-        git::rebase_onto_default(&root, &project_name, &source_branch, &default_branch_clone)
+    let target =
+        git::IntegrationTarget::resolve(&project_name, &proj_ctx.default_branch, default_branch);
+    let result = crate::util::trace::spawn_blocking(move || {
+        git::rebase_onto_default(&root, &target, &source_branch)
     })
     .await;
     match result {
         Ok(Ok(r)) => {
-            send_message(
-                socket,
-                &ServerMessage::GitRebaseOntoDefaultResult {
-                    project: project.to_string(),
-                    ok: r.ok,
-                    state: r.state,
-                    message: r.message,
-                    conflicts: r.conflicts,
-                    conflict_files: r
-                        .conflict_files
-                        .iter()
-                        .map(|f| crate::server::protocol::ConflictFileEntryInfo {
-                            path: f.path.clone(),
-                            conflict_type: f.conflict_type.clone(),
-                            staged: f.staged,
-                        })
-                        .collect(),
-                    head_sha: r.head_sha,
-                    integration_path: r.integration_path,
-                },
-            )
-            .await?;
+            let msg = ServerMessage::GitRebaseOntoDefaultResult {
+                project: project.to_string(),
+                ok: r.ok,
+                state: r.state,
+                message: r.message,
+                conflicts: r.conflicts,
+                conflict_files: r
+                    .conflict_files
+                    .iter()
+                    .map(|f| crate::server::protocol::ConflictFileEntryInfo {
+                        path: f.path.clone(),
+                        conflict_type: f.conflict_type.clone(),
+                        staged: f.staged,
+                    })
+                    .collect(),
+                head_sha: r.head_sha,
+                integration_path: r.integration_path,
+            };
+            send_message(socket, &msg).await?;
         }
         Ok(Err(e)) => {
             send_message(
@@ -320,6 +438,7 @@ pub(crate) async fn handle_git_rebase_onto_default(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -330,6 +449,7 @@ pub(crate) async fn handle_git_rebase_onto_default(
 
 pub(crate) async fn handle_git_rebase_onto_default_continue(
     project: &str,
+    branch: Option<&str>,
     socket: &WebSocket,
     app_state: &SharedAppState,
 ) -> Result<bool, String> {
@@ -340,33 +460,32 @@ pub(crate) async fn handle_git_rebase_onto_default_continue(
             return Ok(true);
         }
     };
-    let project_name = proj_ctx.project_name;
+    let target =
+        git::IntegrationTarget::resolve(&proj_ctx.project_name, &proj_ctx.default_branch, branch);
     let result =
-        tokio::task::spawn_blocking(move || git::rebase_onto_default_continue(&project_name)).await;
+        crate::util::trace::spawn_blocking(move || git::rebase_onto_default_continue(&target))
+            .await;
     match result {
         Ok(Ok(r)) => {
-            send_message(
-                socket,
-                &ServerMessage::GitRebaseOntoDefaultResult {
-                    project: project.to_string(),
-                    ok: r.ok,
-                    state: r.state,
-                    message: r.message,
-                    conflicts: r.conflicts,
-                    conflict_files: r
-                        .conflict_files
-                        .iter()
-                        .map(|f| crate::server::protocol::ConflictFileEntryInfo {
-                            path: f.path.clone(),
-                            conflict_type: f.conflict_type.clone(),
-                            staged: f.staged,
-                        })
-                        .collect(),
-                    head_sha: r.head_sha,
-                    integration_path: r.integration_path,
-                },
-            )
-            .await?;
+            let msg = ServerMessage::GitRebaseOntoDefaultResult {
+                project: project.to_string(),
+                ok: r.ok,
+                state: r.state,
+                message: r.message,
+                conflicts: r.conflicts,
+                conflict_files: r
+                    .conflict_files
+                    .iter()
+                    .map(|f| crate::server::protocol::ConflictFileEntryInfo {
+                        path: f.path.clone(),
+                        conflict_type: f.conflict_type.clone(),
+                        staged: f.staged,
+                    })
+                    .collect(),
+                head_sha: r.head_sha,
+                integration_path: r.integration_path,
+            };
+            send_message(socket, &msg).await?;
         }
         Ok(Err(e)) => {
             send_message(
@@ -394,6 +513,7 @@ pub(crate) async fn handle_git_rebase_onto_default_continue(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -404,6 +524,7 @@ pub(crate) async fn handle_git_rebase_onto_default_continue(
 
 pub(crate) async fn handle_git_rebase_onto_default_abort(
     project: &str,
+    branch: Option<&str>,
     socket: &WebSocket,
     app_state: &SharedAppState,
 ) -> Result<bool, String> {
@@ -414,33 +535,31 @@ pub(crate) async fn handle_git_rebase_onto_default_abort(
             return Ok(true);
         }
     };
-    let project_name = proj_ctx.project_name;
+    let target =
+        git::IntegrationTarget::resolve(&proj_ctx.project_name, &proj_ctx.default_branch, branch);
     let result =
-        tokio::task::spawn_blocking(move || git::rebase_onto_default_abort(&project_name)).await;
+        crate::util::trace::spawn_blocking(move || git::rebase_onto_default_abort(&target)).await;
     match result {
         Ok(Ok(r)) => {
-            send_message(
-                socket,
-                &ServerMessage::GitRebaseOntoDefaultResult {
-                    project: project.to_string(),
-                    ok: r.ok,
-                    state: r.state,
-                    message: r.message,
-                    conflicts: r.conflicts,
-                    conflict_files: r
-                        .conflict_files
-                        .iter()
-                        .map(|f| crate::server::protocol::ConflictFileEntryInfo {
-                            path: f.path.clone(),
-                            conflict_type: f.conflict_type.clone(),
-                            staged: f.staged,
-                        })
-                        .collect(),
-                    head_sha: r.head_sha,
-                    integration_path: r.integration_path,
-                },
-            )
-            .await?;
+            let msg = ServerMessage::GitRebaseOntoDefaultResult {
+                project: project.to_string(),
+                ok: r.ok,
+                state: r.state,
+                message: r.message,
+                conflicts: r.conflicts,
+                conflict_files: r
+                    .conflict_files
+                    .iter()
+                    .map(|f| crate::server::protocol::ConflictFileEntryInfo {
+                        path: f.path.clone(),
+                        conflict_type: f.conflict_type.clone(),
+                        staged: f.staged,
+                    })
+                    .collect(),
+                head_sha: r.head_sha,
+                integration_path: r.integration_path,
+            };
+            send_message(socket, &msg).await?;
         }
         Ok(Err(e)) => {
             send_message(
@@ -468,6 +587,7 @@ pub(crate) async fn handle_git_rebase_onto_default_abort(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -21,7 +21,7 @@ pub(crate) async fn handle_git_op_status(
         }
     };
     let root = ws_ctx.root_path;
-    let result = tokio::task::spawn_blocking(move || git::git_op_status(&root)).await;
+    let result = crate::util::trace::spawn_blocking(move || git::git_op_status(&root)).await;
     match result {
         Ok(Ok(r)) => {
             send_message(
@@ -60,6 +60,7 @@ pub(crate) async fn handle_git_op_status(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -74,6 +75,7 @@ pub(crate) async fn handle_git_op_status(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -84,6 +86,7 @@ pub(crate) async fn handle_git_op_status(
 
 pub(crate) async fn handle_git_integration_status(
     project: &str,
+    branch: Option<&str>,
     socket: &WebSocket,
     app_state: &SharedAppState,
 ) -> Result<bool, String> {
@@ -94,12 +97,9 @@ pub(crate) async fn handle_git_integration_status(
             return Ok(true);
         }
     };
-    let project_name = proj_ctx.project_name;
-    let default_branch = proj_ctx.default_branch;
-    let result = tokio::task::spawn_blocking(move || {
-        git::integration_status(&project_name, &default_branch)
-    })
-    .await;
+    let target =
+        git::IntegrationTarget::resolve(&proj_ctx.project_name, &proj_ctx.default_branch, branch);
+    let result = crate::util::trace::spawn_blocking(move || git::integration_status(&target)).await;
     match result {
         Ok(Ok(r)) => {
             send_message(
@@ -138,6 +138,7 @@ pub(crate) async fn handle_git_integration_status(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -152,6 +153,7 @@ pub(crate) async fn handle_git_integration_status(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -196,7 +198,7 @@ pub(crate) async fn handle_git_check_branch_up_to_date(
                 conflicts: vec![],
                 conflict_files: vec![],
                 head: None,
-                default_branch: "main".to_string(),
+                default_branch: proj_ctx.default_branch.clone(),
                 path: root.to_string_lossy().to_string(),
                 is_clean: true,
                 branch_ahead_by: None,
@@ -212,19 +214,16 @@ pub(crate) async fn handle_git_check_branch_up_to_date(
     let default_branch_clone = default_branch.clone();
     let current_branch_clone = current_branch.clone();
 
-    let result = tokio::task::spawn_blocking(move || {
+    let result = crate::util::trace::spawn_blocking(move || {
         git::check_branch_divergence(&root, &current_branch_clone, &default_branch_clone)
     })
     .await;
 
     match result {
         Ok(Ok(divergence_result)) => {
-            let integration_result = tokio::task::spawn_blocking({
-                let project_name = project_name.clone();
-                let default_branch = default_branch.clone();
-                move || git::integration_status(&project_name, &default_branch)
-            })
-            .await;
+            let target = git::IntegrationTarget::default_for(&project_name, &default_branch);
+            let integration_result =
+                crate::util::trace::spawn_blocking(move || git::integration_status(&target)).await;
 
             match integration_result {
                 Ok(Ok(r)) => {
@@ -264,6 +263,7 @@ pub(crate) async fn handle_git_check_branch_up_to_date(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -278,6 +278,7 @@ pub(crate) async fn handle_git_check_branch_up_to_date(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -294,6 +295,7 @@ pub(crate) async fn handle_git_check_branch_up_to_date(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -308,6 +310,7 @@ pub(crate) async fn handle_git_check_branch_up_to_date(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -0,0 +1,258 @@
+//! 合并后校验：在集成 worktree 中运行 `[git] merge_verify_command`
+//!
+//! 输出逐行推送 `git_merge_verify_output`，结束后推送 `git_merge_verify_result`，
+//! 用户可在推送前确认合并结果能否通过测试 / lint。
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use tokio::io::AsyncBufReadExt;
+use tracing::info;
+
+use crate::server::protocol::ServerMessage;
+use crate::server::ws::OutboundTx as WebSocket;
+use crate::workspace::config::ProjectConfig;
+
+/// 默认超时（秒）
+const DEFAULT_VERIFY_TIMEOUT_SECS: u64 = 1800;
+/// 超时上限（秒）
+const MAX_VERIFY_TIMEOUT_SECS: u64 = 7200;
+/// 结论中保留的输出末尾行数
+const SUMMARY_TAIL_LINES: usize = 20;
+
+/// 项目配置中的校验命令与超时
+pub(super) fn verify_settings(project_root: &Path) -> (Option<String>, Duration) {
+    let git = ProjectConfig::load(project_root)
+        .map(|config| config.git)
+        .unwrap_or_default();
+    let command = git
+        .merge_verify_command
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty());
+    let secs = git
+        .merge_verify_timeout
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_VERIFY_TIMEOUT_SECS)
+        .min(MAX_VERIFY_TIMEOUT_SECS);
+    (command, Duration::from_secs(secs))
+}
+
+/// 后台执行校验，不阻塞当前连接的消息处理
+pub(super) fn spawn_merge_verify(
+    project: String,
+    project_root: PathBuf,
+    integration_path: String,
+    socket: WebSocket,
+) {
+    crate::util::trace::spawn(async move {
+        let root = project_root.clone();
+        let (command, timeout) = crate::util::trace::spawn_blocking(move || verify_settings(&root))
+            .await
+            .unwrap_or((None, Duration::from_secs(DEFAULT_VERIFY_TIMEOUT_SECS)));
+        let task_id = uuid::Uuid::new_v4().to_string();
+        let result = run_merge_verify(
+            &project,
+            &task_id,
+            command.as_deref(),
+            timeout,
+            Path::new(&integration_path),
+            &socket,
+        )
+        .await;
+        let _ = socket.send(result).await;
+    });
+}
+
+/// 运行校验命令，逐行推送输出并返回 `GitMergeVerifyResult`
+pub(super) async fn run_merge_verify(
+    project: &str,
+    task_id: &str,
+    command: Option<&str>,
+    timeout: Duration,
+    integration_path: &Path,
+    socket: &WebSocket,
+) -> ServerMessage {
+    let started = Instant::now();
+    let verdict =
+        |ok: bool, exit_code: Option<i32>, message: String| ServerMessage::GitMergeVerifyResult {
+            project: project.to_string(),
+            task_id: task_id.to_string(),
+            ok,
+            command: command.unwrap_or_default().to_string(),
+            exit_code,
+            message: Some(message).filter(|m| !m.is_empty()),
+            integration_path: integration_path.to_string_lossy().to_string(),
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+    let Some(command) = command else {
+        return verdict(
+            false,
+            None,
+            "No merge verify command configured ([git] merge_verify_command)".to_string(),
+        );
+    };
+
+    let mut cmd =
+        std::process::Command::new(crate::application::project_command::preferred_login_shell());
+    cmd.arg("-l")
+        .arg("-c")
+        .arg(command)
+        .current_dir(integration_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    crate::util::trace::apply_to_command(&mut cmd);
+    let mut cmd = tokio::process::Command::from(cmd);
+    cmd.kill_on_drop(true);
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            return verdict(
+                false,
+                None,
+                format!("Failed to start verify command: {}", e),
+            )
+        }
+    };
+
+    let (line_tx, mut line_rx) = tokio::sync::mpsc::channel::<String>(512);
+    if let Some(pipe) = child.stdout.take() {
+        let tx = line_tx.clone();
+        tokio::spawn(async move {
+            let mut lines = tokio::io::BufReader::new(pipe).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if tx.send(line).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    if let Some(pipe) = child.stderr.take() {
+        let tx = line_tx.clone();
+        tokio::spawn(async move {
+            let mut lines = tokio::io::BufReader::new(pipe).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if tx.send(line).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    drop(line_tx);
+
+    let mut tail: Vec<String> = Vec::new();
+    let run = async {
+        while let Some(line) = line_rx.recv().await {
+            let _ = socket
+                .send(ServerMessage::GitMergeVerifyOutput {
+                    project: project.to_string(),
+                    task_id: task_id.to_string(),
+                    line: line.clone(),
+                })
+                .await;
+            if tail.len() == SUMMARY_TAIL_LINES {
+                tail.remove(0);
+            }
+            tail.push(line);
+        }
+        child.wait().await
+    };
+    let status = tokio::time::timeout(timeout, run).await;
+
+    match status {
+        Ok(Ok(status)) => {
+            info!(
+                "Merge verify finished: project={}, ok={}, exit_code={:?}",
+                project,
+                status.success(),
+                status.code()
+            );
+            verdict(status.success(), status.code(), tail.join("\n"))
+        }
+        Ok(Err(e)) => verdict(false, None, format!("Verify command failed: {}", e)),
+        Err(_) => {
+            let _ = child.kill().await;
+            verdict(
+                false,
+                None,
+                format!("Verify command timed out after {}s", timeout.as_secs()),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn run(command: Option<&str>, timeout: Duration) -> (ServerMessage, Vec<String>) {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, mut rx) = tokio::sync::mpsc::channel(64);
+        let result = run_merge_verify("demo", "t1", command, timeout, dir.path(), &tx).await;
+        drop(tx);
+        let mut lines = Vec::new();
+        while let Some(msg) = rx.recv().await {
+            if let ServerMessage::GitMergeVerifyOutput { line, .. } = msg {
+                lines.push(line);
+            }
+        }
+        (result, lines)
+    }
+
+    #[tokio::test]
+    async fn streams_output_and_reports_exit_status() {
+        let (result, lines) = run(
+            Some("echo one; echo two >&2; exit 3"),
+            Duration::from_secs(30),
+        )
+        .await;
+        // 登录 shell 可能额外输出 profile 信息，只检查命令自身的输出
+        assert!(lines.contains(&"one".to_string()) && lines.contains(&"two".to_string()));
+        match result {
+            ServerMessage::GitMergeVerifyResult {
+                ok,
+                exit_code,
+                message,
+                ..
+            } => {
+                assert!(!ok);
+                assert_eq!(exit_code, Some(3));
+                assert!(message.unwrap().contains("one"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let (result, _) = run(Some("true"), Duration::from_secs(30)).await;
+        assert!(matches!(
+            result,
+            ServerMessage::GitMergeVerifyResult {
+                ok: true,
+                exit_code: Some(0),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn reports_missing_command_and_timeout() {
+        let (result, _) = run(None, Duration::from_secs(30)).await;
+        assert!(matches!(
+            result,
+            ServerMessage::GitMergeVerifyResult {
+                ok: false,
+                exit_code: None,
+                ..
+            }
+        ));
+
+        let (result, _) = run(Some("sleep 5"), Duration::from_millis(200)).await;
+        match result {
+            ServerMessage::GitMergeVerifyResult { ok, message, .. } => {
+                assert!(!ok);
+                assert!(message.unwrap().contains("timed out"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
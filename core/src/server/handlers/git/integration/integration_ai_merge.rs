@@ -23,7 +23,7 @@ pub async fn handle_git_ai_merge(
     project: String,
     workspace: String,
     ai_agent: Option<String>,
-    default_branch: String,
+    default_branch: Option<String>,
     socket: &WebSocket,
     app_state: &SharedAppState,
     ctx: &HandlerContext,
@@ -55,8 +55,13 @@ pub async fn handle_git_ai_merge(
         return Ok(true);
     }
 
+    let target = git::IntegrationTarget::resolve(
+        &proj_ctx.project_name,
+        &proj_ctx.default_branch,
+        default_branch.as_deref(),
+    );
+    let default_branch = target.branch.clone();
     let root = proj_ctx.root_path;
-    let project_name = proj_ctx.project_name;
     let ai_agent_type = ai_agent.unwrap_or_else(|| "cursor".to_string());
     let project_for_task = project.clone();
     let workspace_for_task = workspace.clone();
@@ -83,16 +88,15 @@ pub async fn handle_git_ai_merge(
     let task_history = ctx.task_history.clone();
     let task_id_for_history = task_id.clone();
 
-    let join_handle = tokio::spawn(async move {
+    let join_handle = crate::util::trace::spawn(async move {
         let pid_for_blocking = child_pid_clone.clone();
         let result = tokio::time::timeout(
             AI_AGENT_TIMEOUT,
-            tokio::task::spawn_blocking(move || {
+            crate::util::trace::spawn_blocking(move || {
                 handle_ai_merge_internal(
                     &root,
-                    &project_name,
+                    &target,
                     &source_branch,
-                    &default_branch,
                     &ai_agent_type,
                     Some(&pid_for_blocking),
                 )
@@ -246,20 +250,18 @@ struct AIMergeOutput {
 /// 内部函数：执行 AI 智能合并逻辑
 fn handle_ai_merge_internal(
     repo_root: &std::path::Path,
-    project_name: &str,
+    target: &git::IntegrationTarget,
     source_branch: &str,
-    default_branch: &str,
     ai_agent: &str,
     pid_holder: Option<&Arc<StdMutex<Option<u32>>>>,
 ) -> Result<AIMergeOutput, String> {
     // 确保 integration worktree 存在
-    let integration_path =
-        git::ensure_integration_worktree(repo_root, project_name, default_branch)
-            .map_err(|e| format!("Failed to ensure integration worktree: {}", e))?;
+    let integration_path = git::ensure_integration_worktree(repo_root, target)
+        .map_err(|e| format!("Failed to ensure integration worktree: {}", e))?;
     let integration_root = std::path::PathBuf::from(&integration_path);
 
     // 构建合并 prompt
-    let prompt = build_ai_merge_prompt(source_branch, default_branch);
+    let prompt = build_ai_merge_prompt(source_branch, &target.branch);
 
     // 调用 AI agent
     let agent_args = branch_commit::build_ai_agent_command(ai_agent, &prompt)?;
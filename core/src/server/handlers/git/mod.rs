@@ -4,8 +4,10 @@ use crate::server::context::{HandlerContext, SharedAppState};
 use crate::server::protocol::ClientMessage;
 
 pub(crate) mod branch_commit;
+mod commit_message;
 mod history;
 mod integration;
+mod pull_request;
 pub(crate) mod query;
 mod route;
 mod sequencer;
@@ -0,0 +1,163 @@
+//! 创建 Pull Request WS 处理器：推送当前分支后调用托管平台 API
+
+use crate::server::context::{resolve_workspace, SharedAppState};
+use crate::server::git;
+use crate::server::protocol::{ClientMessage, ServerMessage};
+use crate::server::ws::send_message;
+use crate::server::ws::OutboundTx as WebSocket;
+
+pub async fn handle_message(
+    client_msg: &ClientMessage,
+    socket: &WebSocket,
+    app_state: &SharedAppState,
+) -> Result<bool, String> {
+    let ClientMessage::GitCreatePullRequest {
+        project,
+        workspace,
+        title,
+        body,
+        target_branch,
+        draft,
+    } = client_msg
+    else {
+        return Ok(false);
+    };
+
+    let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
+        Ok(ctx) => ctx,
+        Err(e) => {
+            send_message(socket, &e.to_server_error()).await?;
+            return Ok(true);
+        }
+    };
+    let target = target_branch
+        .as_deref()
+        .map(str::trim)
+        .filter(|b| !b.is_empty())
+        .unwrap_or(&ws_ctx.default_branch)
+        .to_string();
+    let result = |ok: bool,
+                  provider: Option<git::HostingProvider>,
+                  branch: &str,
+                  created: Option<git::CreatedPullRequest>,
+                  message: Option<String>| {
+        ServerMessage::GitCreatePullRequestResult {
+            project: project.clone(),
+            workspace: workspace.clone(),
+            ok,
+            provider: provider.map(|p| p.as_str().to_string()),
+            branch: branch.to_string(),
+            target_branch: target.clone(),
+            url: created.as_ref().map(|c| c.url.clone()),
+            number: created.map(|c| c.number),
+            message,
+        }
+    };
+
+    let title = title.trim();
+    if title.is_empty() {
+        let msg = result(false, None, "", None, Some("Title is required".to_string()));
+        send_message(socket, &msg).await?;
+        return Ok(true);
+    }
+
+    let root = ws_ctx.root_path.clone();
+    let resolved = crate::util::trace::spawn_blocking(move || {
+        let repo = git::hosted_repo(&root)?;
+        let branch = git::git_current_branch(&root)?.ok_or_else(|| {
+            git::GitError::CommandFailed("Cannot create a pull request from a detached HEAD".into())
+        })?;
+        Ok::<_, git::GitError>((repo, branch))
+    })
+    .await
+    .map_err(|e| format!("Git create pull request task failed: {}", e))?;
+    let (repo, branch) = match resolved {
+        Ok(v) => v,
+        Err(e) => {
+            send_message(socket, &result(false, None, "", None, Some(e.to_string()))).await?;
+            return Ok(true);
+        }
+    };
+    if branch == target {
+        let msg = result(
+            false,
+            Some(repo.provider),
+            &branch,
+            None,
+            Some(format!("Source and target branch are both '{}'", branch)),
+        );
+        send_message(socket, &msg).await?;
+        return Ok(true);
+    }
+
+    let token = git::hosting_token(
+        &app_state.read().await.client_settings.git_hosting_tokens,
+        &repo.host,
+    );
+    let Some(token) = token else {
+        send_message(
+            socket,
+            &ServerMessage::Error {
+                code: git::HOSTING_TOKEN_MISSING_CODE.to_string(),
+                message: format!("No access token configured for {}", repo.host),
+                project: Some(project.clone()),
+                workspace: Some(workspace.clone()),
+                session_id: None,
+                cycle_id: None,
+                trace_id: None,
+            },
+        )
+        .await?;
+        return Ok(true);
+    };
+
+    let root = ws_ctx.root_path.clone();
+    let push_branch = branch.clone();
+    let pushed = crate::util::trace::spawn_blocking(move || git::push_branch(&root, &push_branch))
+        .await
+        .map_err(|e| format!("Git push task failed: {}", e))?;
+    match pushed {
+        Ok(()) => {}
+        Err(git::GitError::OfflineMode(op)) => {
+            send_message(
+                socket,
+                &ServerMessage::Error {
+                    code: git::OFFLINE_MODE_CODE.to_string(),
+                    message: format!("Offline mode is enabled, git {} skipped", op),
+                    project: Some(project.clone()),
+                    workspace: Some(workspace.clone()),
+                    session_id: None,
+                    cycle_id: None,
+                    trace_id: None,
+                },
+            )
+            .await?;
+            return Ok(true);
+        }
+        Err(e) => {
+            let msg = result(
+                false,
+                Some(repo.provider),
+                &branch,
+                None,
+                Some(format!("Push failed: {}", e)),
+            );
+            send_message(socket, &msg).await?;
+            return Ok(true);
+        }
+    }
+
+    let params = git::PullRequestParams {
+        title,
+        body: body.as_deref().unwrap_or_default(),
+        head: &branch,
+        base: &target,
+        draft: *draft,
+    };
+    let msg = match git::create_pull_request(&repo, &token, &params).await {
+        Ok(created) => result(true, Some(repo.provider), &branch, Some(created), None),
+        Err(e) => result(false, Some(repo.provider), &branch, None, Some(e)),
+    };
+    send_message(socket, &msg).await?;
+    Ok(true)
+}
@@ -1,17 +1,33 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use futures::StreamExt;
+
 use crate::server::context::{
-    resolve_project, resolve_workspace, resolve_workspace_branch, SharedAppState,
+    resolve_project, resolve_workspace, resolve_workspace_branch, AppError, SharedAppState,
 };
 use crate::server::git;
 use crate::server::protocol::{
-    ConflictFileEntryInfo, GitBranchInfo, GitLogEntryInfo, GitShowFileInfo, GitStashEntryInfo,
-    GitStashFileInfo, GitStatusEntry, ServerMessage,
+    ConflictFileEntryInfo, ConflictRegionInfo, GitBinaryBlobInfo, GitBlameHunkInfo, GitBranchInfo,
+    GitDiffHighlightRange, GitDiffHunkInfo, GitDiffLineInfo, GitFilterRepoPlanInfo,
+    GitLargeBlobCommitInfo, GitLargeBlobInfo, GitLogEntryInfo, GitPullRequestCheckInfo,
+    GitPullRequestInfo, GitRangeDiffFileInfo, GitRebasePlanCommitInfo, GitRepoLargeFileInfo,
+    GitShowFileInfo, GitStashEntryInfo, GitStashFileInfo, GitStatusDirectoryInfo, GitStatusEntry,
+    GitWorkspaceStatusSummary, ServerMessage,
 };
+use crate::workspace::state::DEFAULT_WORKSPACE_NAME;
 
 pub(crate) async fn query_git_status(
     app_state: &SharedAppState,
     project: &str,
     workspace: &str,
+    untracked_files: Option<&str>,
+    expand_untracked: Vec<String>,
 ) -> Result<ServerMessage, String> {
+    let expand_all = git::parse_untracked_files_mode(untracked_files)
+        .map_err(|e| format!("Git status failed: {}", e))?;
     let ws_ctx = resolve_workspace(app_state, project, workspace)
         .await
         .map_err(|e| e.to_string())?;
@@ -19,11 +35,15 @@ pub(crate) async fn query_git_status(
     let default_branch = ws_ctx.default_branch;
 
     // git_status 现在一次性产出 status items、current_branch 和 divergence（复用同一 repo 对象）
-    let status_result =
-        tokio::task::spawn_blocking(move || git::git_status(&root, &default_branch))
-            .await
-            .map_err(|e| format!("Git status task failed: {}", e))?
-            .map_err(|e| format!("Git status failed: {}", e))?;
+    let (status_result, untracked_truncated) = crate::util::trace::spawn_blocking(move || {
+        let mut status = git::git_status(&root, &default_branch)?;
+        let truncated =
+            git::expand_untracked_dirs(&root, &mut status.items, expand_all, &expand_untracked)?;
+        Ok::<_, git::GitError>((status, truncated))
+    })
+    .await
+    .map_err(|e| format!("Git status task failed: {}", e))?
+    .map_err(|e| format!("Git status failed: {}", e))?;
 
     let items: Vec<GitStatusEntry> = status_result
         .items
@@ -50,9 +70,161 @@ pub(crate) async fn query_git_status(
         ahead_by: status_result.ahead_by,
         behind_by: status_result.behind_by,
         compared_branch: status_result.compared_branch,
+        untracked_truncated,
     })
 }
 
+pub(crate) async fn query_git_status_tree(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+) -> Result<ServerMessage, String> {
+    let ws_ctx = resolve_workspace(app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_string())?;
+    let root = ws_ctx.root_path;
+    let default_branch = ws_ctx.default_branch;
+    let status_result =
+        crate::util::trace::spawn_blocking(move || git::git_status(&root, &default_branch))
+            .await
+            .map_err(|e| format!("Git status task failed: {}", e))?
+            .map_err(|e| format!("Git status failed: {}", e))?;
+
+    Ok(ServerMessage::GitStatusTreeResult {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        directories: aggregate_status_tree(&status_result.items),
+    })
+}
+
+/// 将 status 条目按目录聚合：每个条目计入其所有上级目录（含根目录），未跟踪目录（`dir/`）也计入自身
+///
+/// 两个 status 后端都以结尾 `/` 表示折叠的未跟踪目录，据此区分目录与文件。
+fn aggregate_status_tree(items: &[git::GitStatusEntry]) -> Vec<GitStatusDirectoryInfo> {
+    let mut directories: BTreeMap<String, GitStatusDirectoryInfo> = BTreeMap::new();
+    for item in items {
+        let is_dir = item.path.ends_with('/');
+        let path = item.path.trim_end_matches('/');
+        let mut dirs = vec![String::new()];
+        let mut segments: Vec<&str> = path.split('/').collect();
+        if !is_dir {
+            segments.pop();
+        }
+        for index in 1..=segments.len() {
+            dirs.push(segments[..index].join("/"));
+        }
+        for dir in dirs {
+            let entry = directories
+                .entry(dir.clone())
+                .or_insert_with(|| GitStatusDirectoryInfo {
+                    path: dir,
+                    dirty_count: 0,
+                    staged_count: 0,
+                    unstaged_count: 0,
+                    untracked_count: 0,
+                    conflict_count: 0,
+                });
+            entry.dirty_count += 1;
+            match (item.code.as_str(), item.staged) {
+                ("??", _) => entry.untracked_count += 1,
+                ("U", _) => entry.conflict_count += 1,
+                (_, true) => entry.staged_count += 1,
+                (_, false) => entry.unstaged_count += 1,
+            }
+        }
+    }
+    directories.into_values().collect()
+}
+
+/// `git_status_all` 同时执行的工作区 status 数量上限
+const GIT_STATUS_ALL_CONCURRENCY: usize = 4;
+
+pub(crate) async fn query_git_status_all(
+    app_state: &SharedAppState,
+    project: &str,
+) -> Result<ServerMessage, String> {
+    let (default_branch, workspaces) = {
+        let state = app_state.read().await;
+        let proj = state
+            .get_project(project)
+            .ok_or_else(|| AppError::ProjectNotFound(project.to_string()).to_string())?;
+        let mut named: Vec<(String, PathBuf)> = proj
+            .workspaces
+            .values()
+            .map(|w| (w.name.clone(), w.worktree_path.clone()))
+            .collect();
+        named.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut workspaces = vec![(DEFAULT_WORKSPACE_NAME.to_string(), proj.root_path.clone())];
+        workspaces.extend(named);
+        (proj.default_branch.clone(), workspaces)
+    };
+
+    // 各工作区 status 并发执行（有上限），结果保持列表顺序；单个失败只记录在该工作区的摘要中
+    let summaries = futures::stream::iter(workspaces)
+        .map(|(workspace, root)| {
+            let default_branch = default_branch.clone();
+            async move {
+                let result = if root.is_dir() {
+                    crate::util::trace::spawn_blocking(move || {
+                        git::git_status(&root, &default_branch)
+                    })
+                    .await
+                    .map_err(|e| format!("Git status task failed: {}", e))
+                    .and_then(|r| r.map_err(|e| format!("Git status failed: {}", e)))
+                } else {
+                    Err(format!("Workspace path not found: {}", root.display()))
+                };
+                summarize_workspace_status(workspace, result)
+            }
+        })
+        .buffered(GIT_STATUS_ALL_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+    Ok(ServerMessage::GitStatusAllResult {
+        project: project.to_string(),
+        workspaces: summaries,
+    })
+}
+
+fn summarize_workspace_status(
+    workspace: String,
+    result: Result<git::GitStatusResult, String>,
+) -> GitWorkspaceStatusSummary {
+    let mut summary = GitWorkspaceStatusSummary {
+        workspace,
+        current_branch: None,
+        staged_count: 0,
+        unstaged_count: 0,
+        untracked_count: 0,
+        conflict_count: 0,
+        ahead_by: None,
+        behind_by: None,
+        compared_branch: None,
+        error: None,
+    };
+    let status = match result {
+        Ok(status) => status,
+        Err(e) => {
+            summary.error = Some(e);
+            return summary;
+        }
+    };
+    for item in &status.items {
+        match (item.code.as_str(), item.staged) {
+            ("??", _) => summary.untracked_count += 1,
+            ("U", _) => summary.conflict_count += 1,
+            (_, true) => summary.staged_count += 1,
+            (_, false) => summary.unstaged_count += 1,
+        }
+    }
+    summary.current_branch = status.current_branch;
+    summary.ahead_by = status.ahead_by;
+    summary.behind_by = status.behind_by;
+    summary.compared_branch = status.compared_branch;
+    summary
+}
+
 pub(crate) async fn query_git_diff(
     app_state: &SharedAppState,
     project: &str,
@@ -60,7 +232,16 @@ pub(crate) async fn query_git_diff(
     path: &str,
     base: Option<String>,
     mode: &str,
+    algorithm: Option<String>,
+    range: Option<String>,
+    format: Option<String>,
+    intraline: bool,
+    smart_diff: bool,
+    include_blobs: bool,
 ) -> Result<ServerMessage, String> {
+    let structured = git::is_structured_format(format.as_deref())
+        .map_err(|e| format!("Git diff failed: {}", e))?
+        || intraline;
     let ws_ctx = resolve_workspace(app_state, project, workspace)
         .await
         .map_err(|e| e.to_string())?;
@@ -68,24 +249,271 @@ pub(crate) async fn query_git_diff(
     let path_clone = path.to_string();
     let base_clone = base.clone();
     let mode_clone = mode.to_string();
-    let diff_result = tokio::task::spawn_blocking(move || {
-        git::git_diff(&root, &path_clone, base_clone.as_deref(), &mode_clone)
+    let range_clone = range.clone();
+    let (diff_result, (old_blob, new_blob)) = crate::util::trace::spawn_blocking(move || {
+        let diff_result = match range_clone.as_deref() {
+            Some(range) => {
+                git::git_diff_file_in_range(&root, range, &path_clone, algorithm.as_deref())
+            }
+            None => git::git_diff(
+                &root,
+                &path_clone,
+                base_clone.as_deref(),
+                &mode_clone,
+                algorithm.as_deref(),
+                smart_diff,
+            ),
+        }?;
+        let blobs = binary_blob_infos(
+            &root,
+            &diff_result,
+            base_clone.as_deref(),
+            &mode_clone,
+            range_clone.as_deref(),
+            include_blobs,
+        );
+        Ok::<_, git::GitError>((diff_result, blobs))
     })
     .await
     .map_err(|e| format!("Git diff task failed: {}", e))?
     .map_err(|e| format!("Git diff failed: {}", e))?;
 
+    let hunks = structured.then(|| structured_diff_hunks(&diff_result, intraline));
+    let next_offset = diff_result
+        .truncated
+        .then_some(diff_result.text.len() as u64);
     Ok(ServerMessage::GitDiffResult {
         project: project.to_string(),
         workspace: workspace.to_string(),
         path: path.to_string(),
         code: diff_result.code,
-        format: diff_result.format,
+        format: if structured {
+            git::DIFF_FORMAT_STRUCTURED.to_string()
+        } else {
+            diff_result.format
+        },
         text: diff_result.text,
         is_binary: diff_result.is_binary,
         truncated: diff_result.truncated,
         mode: diff_result.mode,
         base,
+        range,
+        hunks,
+        smart_diff: diff_result.smart_diff,
+        next_offset,
+        old_blob,
+        new_blob,
+    })
+}
+
+/// 二进制文件 diff 两侧的版本信息（大小、MIME 类型、图片尺寸）；非二进制或读取失败时为空
+pub(crate) fn binary_blob_infos(
+    root: &std::path::Path,
+    diff: &git::GitDiffResult,
+    base: Option<&str>,
+    mode: &str,
+    range: Option<&str>,
+    include_blobs: bool,
+) -> (Option<GitBinaryBlobInfo>, Option<GitBinaryBlobInfo>) {
+    if !diff.is_binary && !git::is_binary_diff_output(&diff.text) {
+        return (None, None);
+    }
+    let blobs = match git::git_binary_diff_blobs(root, &diff.path, base, mode, range, include_blobs)
+    {
+        Ok(blobs) => blobs,
+        Err(e) => {
+            tracing::debug!(path = %diff.path, error = %e, "Failed to read binary diff blobs");
+            return (None, None);
+        }
+    };
+    let convert = |blob: git::BinaryBlobInfo| GitBinaryBlobInfo {
+        size: blob.size,
+        mime_type: blob.mime_type,
+        width: blob.width,
+        height: blob.height,
+        content_base64: blob.content.map(|bytes| BASE64_STANDARD.encode(bytes)),
+    };
+    (blobs.old.map(convert), blobs.new.map(convert))
+}
+
+/// 大 diff 的分页读取：重新生成 diff 并返回从 `offset` 开始的一页
+pub(crate) async fn query_git_diff_page(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+    path: &str,
+    base: Option<String>,
+    mode: &str,
+    algorithm: Option<String>,
+    range: Option<String>,
+    smart_diff: bool,
+    offset: u64,
+) -> Result<ServerMessage, String> {
+    let ws_ctx = resolve_workspace(app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_string())?;
+    let root = ws_ctx.root_path;
+    let path_clone = path.to_string();
+    let base_clone = base.clone();
+    let mode_clone = mode.to_string();
+    let range_clone = range.clone();
+    let page_offset = usize::try_from(offset).unwrap_or(usize::MAX);
+    let diff_result = crate::util::trace::spawn_blocking(move || {
+        git::with_diff_page_offset(page_offset, || match range_clone {
+            Some(range) => {
+                git::git_diff_file_in_range(&root, &range, &path_clone, algorithm.as_deref())
+            }
+            None => git::git_diff(
+                &root,
+                &path_clone,
+                base_clone.as_deref(),
+                &mode_clone,
+                algorithm.as_deref(),
+                smart_diff,
+            ),
+        })
+    })
+    .await
+    .map_err(|e| format!("Git diff task failed: {}", e))?
+    .map_err(|e| format!("Git diff failed: {}", e))?;
+
+    Ok(ServerMessage::GitDiffPageResult {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        path: path.to_string(),
+        mode: diff_result.mode,
+        base,
+        range,
+        offset,
+        next_offset: diff_result
+            .truncated
+            .then_some(offset + diff_result.text.len() as u64),
+        text: diff_result.text,
+        truncated: diff_result.truncated,
+    })
+}
+
+/// 将 unified diff 文本解析为协议 hunk（二进制文件为空）
+pub(crate) fn structured_diff_hunks(
+    diff: &git::GitDiffResult,
+    intraline: bool,
+) -> Vec<GitDiffHunkInfo> {
+    if diff.is_binary {
+        return Vec::new();
+    }
+    let mut hunks = git::parse_unified_hunks(&diff.text);
+    if intraline {
+        git::compute_intraline_highlights(&mut hunks);
+    }
+    hunks
+        .into_iter()
+        .map(|hunk| GitDiffHunkInfo {
+            old_start: hunk.old_start,
+            old_lines: hunk.old_lines,
+            new_start: hunk.new_start,
+            new_lines: hunk.new_lines,
+            header: hunk.header,
+            lines: hunk
+                .lines
+                .into_iter()
+                .map(|line| GitDiffLineInfo {
+                    kind: line.kind.to_string(),
+                    text: line.text,
+                    old_line: line.old_line,
+                    new_line: line.new_line,
+                    no_newline: line.no_newline,
+                    highlights: line
+                        .highlights
+                        .into_iter()
+                        .map(|(start, end)| GitDiffHighlightRange { start, end })
+                        .collect(),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+pub(crate) async fn query_git_rebase_plan(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+    base: &str,
+) -> Result<ServerMessage, String> {
+    let ws_ctx = resolve_workspace(app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_string())?;
+    let root = ws_ctx.root_path;
+    let base_clone = base.to_string();
+    let plan = crate::util::trace::spawn_blocking(move || {
+        git::git_rebase_interactive_plan(&root, &base_clone)
+    })
+    .await
+    .map_err(|e| format!("Git rebase plan task failed: {}", e))?
+    .map_err(|e| format!("Git rebase plan failed: {}", e))?;
+
+    Ok(ServerMessage::GitRebaseInteractivePlanResult {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        base: plan.base,
+        base_sha: plan.base_sha,
+        commits: plan
+            .commits
+            .into_iter()
+            .map(|c| GitRebasePlanCommitInfo {
+                sha: c.sha,
+                short_sha: c.short_sha,
+                subject: c.subject,
+                message: c.message,
+                author: c.author,
+                date: c.date,
+            })
+            .collect(),
+    })
+}
+
+pub(crate) async fn query_git_diff_range(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+    range: &str,
+    path: Option<String>,
+) -> Result<ServerMessage, String> {
+    let ws_ctx = resolve_workspace(app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_string())?;
+    let root = ws_ctx.root_path;
+    let range_clone = range.to_string();
+    let path_clone = path.clone();
+    let summary = crate::util::trace::spawn_blocking(move || {
+        git::git_diff_range(&root, &range_clone, path_clone.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Git diff range task failed: {}", e))?
+    .map_err(|e| format!("Git diff range failed: {}", e))?;
+
+    Ok(ServerMessage::GitDiffRangeResult {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        range: summary.range.spec(),
+        base: summary.range.base,
+        head: summary.range.head,
+        merge_base: summary.merge_base,
+        path,
+        files: summary
+            .files
+            .into_iter()
+            .map(|f| GitRangeDiffFileInfo {
+                path: f.path,
+                orig_path: f.orig_path,
+                code: f.code,
+                additions: f.additions,
+                deletions: f.deletions,
+                is_binary: f.is_binary,
+            })
+            .collect(),
+        total_additions: summary.total_additions,
+        total_deletions: summary.total_deletions,
+        truncated: summary.truncated,
     })
 }
 
@@ -98,7 +526,7 @@ pub(crate) async fn query_git_branches(
         .await
         .map_err(|e| e.to_string())?;
     let root = ws_ctx.root_path;
-    let branches_result = tokio::task::spawn_blocking(move || git::git_branches(&root))
+    let branches_result = crate::util::trace::spawn_blocking(move || git::git_branches(&root))
         .await
         .map_err(|e| format!("Git branches task failed: {}", e))?
         .map_err(|e| format!("Git branches failed: {}", e))?;
@@ -110,7 +538,7 @@ pub(crate) async fn query_git_branches(
         branches: branches_result
             .branches
             .into_iter()
-            .map(|b| GitBranchInfo { name: b.name })
+            .map(GitBranchInfo::from)
             .collect(),
     })
 }
@@ -120,15 +548,18 @@ pub(crate) async fn query_git_log(
     project: &str,
     workspace: &str,
     limit: usize,
+    filter: git::GitLogFilter,
 ) -> Result<ServerMessage, String> {
     let ws_ctx = resolve_workspace(app_state, project, workspace)
         .await
         .map_err(|e| e.to_string())?;
     let root = ws_ctx.root_path;
-    let log_result = tokio::task::spawn_blocking(move || git::git_log(&root, limit))
-        .await
-        .map_err(|e| format!("Git log task failed: {}", e))?
-        .map_err(|e| format!("Git log failed: {}", e))?;
+    let filter = filter.normalized();
+    let log_result =
+        crate::util::trace::spawn_blocking(move || git::git_log(&root, limit, &filter))
+            .await
+            .map_err(|e| format!("Git log task failed: {}", e))?
+            .map_err(|e| format!("Git log failed: {}", e))?;
 
     Ok(ServerMessage::GitLogResult {
         project: project.to_string(),
@@ -144,6 +575,7 @@ pub(crate) async fn query_git_log(
                 refs: e.refs,
             })
             .collect(),
+        has_more: log_result.has_more,
     })
 }
 
@@ -158,7 +590,7 @@ pub(crate) async fn query_git_show(
         .map_err(|e| e.to_string())?;
     let root = ws_ctx.root_path;
     let sha_clone = sha.to_string();
-    let show_result = tokio::task::spawn_blocking(move || git::git_show(&root, &sha_clone))
+    let show_result = crate::util::trace::spawn_blocking(move || git::git_show(&root, &sha_clone))
         .await
         .map_err(|e| format!("Git show task failed: {}", e))?
         .map_err(|e| format!("Git show failed: {}", e))?;
@@ -184,6 +616,136 @@ pub(crate) async fn query_git_show(
     })
 }
 
+pub(crate) async fn query_git_show_file_diff(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+    sha: &str,
+    path: &str,
+    algorithm: Option<String>,
+    format: Option<String>,
+    intraline: bool,
+) -> Result<ServerMessage, String> {
+    let structured = git::is_structured_format(format.as_deref())
+        .map_err(|e| format!("Git show file diff failed: {}", e))?
+        || intraline;
+    let ws_ctx = resolve_workspace(app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_string())?;
+    let root = ws_ctx.root_path;
+    let sha_clone = sha.to_string();
+    let path_clone = path.to_string();
+    let result = crate::util::trace::spawn_blocking(move || {
+        git::git_show_file_diff(&root, &sha_clone, &path_clone, algorithm.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Git show file diff task failed: {}", e))?
+    .map_err(|e| format!("Git show file diff failed: {}", e))?;
+
+    let hunks = structured.then(|| structured_diff_hunks(&result.diff, intraline));
+    Ok(ServerMessage::GitShowFileDiffResult {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        sha: result.full_sha,
+        path: result.diff.path,
+        old_path: result.old_path,
+        code: result.diff.code,
+        format: if structured {
+            git::DIFF_FORMAT_STRUCTURED.to_string()
+        } else {
+            result.diff.format
+        },
+        text: result.diff.text,
+        is_binary: result.diff.is_binary,
+        truncated: result.diff.truncated,
+        hunks,
+    })
+}
+
+pub(crate) async fn query_git_suggested_commit_message(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+    link_url: Option<String>,
+    generate: bool,
+) -> Result<ServerMessage, String> {
+    let ws_ctx = resolve_workspace(app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_string())?;
+    let root = ws_ctx.root_path;
+    let workspace_clone = workspace.to_string();
+    let result = if generate {
+        git::git_generate_commit_message(root, workspace_clone, link_url).await
+    } else {
+        crate::util::trace::spawn_blocking(move || {
+            git::git_suggested_commit_message(&root, &workspace_clone, link_url.as_deref(), false)
+        })
+        .await
+        .map_err(|e| format!("Git suggested commit message task failed: {}", e))?
+    }
+    .map_err(|e| format!("Git suggested commit message failed: {}", e))?;
+
+    Ok(ServerMessage::GitSuggestedCommitMessageResult {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        message: result.message,
+        source: result.source,
+        generator_available: result.generator_available,
+        template: result.template,
+        issue_id: result.issue_id,
+        commit_type: result.commit_type,
+        scope: result.scope,
+        summary: result.summary,
+        branch: result.branch,
+        staged_files: result.staged_files,
+    })
+}
+
+pub(crate) async fn query_git_blame(
+    app_state: &SharedAppState,
+    project: &str,
+    workspace: &str,
+    path: &str,
+    rev: Option<String>,
+) -> Result<ServerMessage, String> {
+    let ws_ctx = resolve_workspace(app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_string())?;
+    let root = ws_ctx.root_path;
+    let path_clone = path.to_string();
+    let rev_clone = rev.clone();
+    let blame_result = crate::util::trace::spawn_blocking(move || {
+        git::git_blame(&root, &path_clone, rev_clone.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Git blame task failed: {}", e))?
+    .map_err(|e| format!("Git blame failed: {}", e))?;
+
+    Ok(ServerMessage::GitBlameResult {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        path: blame_result.path,
+        rev,
+        hunks: blame_result
+            .hunks
+            .into_iter()
+            .map(|h| GitBlameHunkInfo {
+                sha: h.sha,
+                start_line: h.start_line,
+                orig_start_line: h.orig_start_line,
+                line_count: h.line_count,
+                author: h.author,
+                author_email: h.author_email,
+                author_time: h.author_time,
+                summary: h.summary,
+                orig_path: h.orig_path,
+                boundary: h.boundary,
+            })
+            .collect(),
+        ignore_revs_files: blame_result.ignore_revs_files,
+    })
+}
+
 pub(crate) async fn query_git_op_status(
     app_state: &SharedAppState,
     project: &str,
@@ -195,7 +757,7 @@ pub(crate) async fn query_git_op_status(
     let root = ws_ctx.root_path;
     let project_c = project.to_string();
     let workspace_c = workspace.to_string();
-    let result = tokio::task::spawn_blocking(move || {
+    let result = crate::util::trace::spawn_blocking(move || {
         let status = git::git_op_status(&root)?;
         let receipt = git::sequencer::get_rollback_receipt(&project_c, &workspace_c);
         Ok::<_, git::GitError>((status, receipt))
@@ -237,21 +799,112 @@ pub(crate) async fn query_git_op_status(
     })
 }
 
-pub(crate) async fn query_git_integration_status(
+pub(crate) async fn query_git_repo_stats(
     app_state: &SharedAppState,
     project: &str,
 ) -> Result<ServerMessage, String> {
     let proj_ctx = resolve_project(app_state, project)
         .await
         .map_err(|e| e.to_string())?;
-    let project_name = proj_ctx.project_name;
-    let default_branch = proj_ctx.default_branch;
-    let result = tokio::task::spawn_blocking(move || {
-        git::integration_status(&project_name, &default_branch)
+    let root = proj_ctx.root_path;
+    let stats = crate::util::trace::spawn_blocking(move || git::git_repo_stats(&root))
+        .await
+        .map_err(|e| format!("Git repo stats task failed: {}", e))?
+        .map_err(|e| format!("Git repo stats failed: {}", e))?;
+
+    Ok(ServerMessage::GitRepoStatsResult {
+        project: project.to_string(),
+        commit_count: stats.commit_count,
+        loose_objects: stats.loose_objects,
+        loose_size_bytes: stats.loose_size_bytes,
+        packed_objects: stats.packed_objects,
+        pack_count: stats.pack_count,
+        pack_size_bytes: stats.pack_size_bytes,
+        prune_packable: stats.prune_packable,
+        garbage_count: stats.garbage_count,
+        garbage_size_bytes: stats.garbage_size_bytes,
+        largest_files: stats
+            .largest_files
+            .into_iter()
+            .map(|f| GitRepoLargeFileInfo {
+                path: f.path,
+                sha: f.sha,
+                size: f.size,
+                disk_size: f.disk_size,
+            })
+            .collect(),
+        last_gc_at: stats.last_gc_at,
+        gc_log: stats.gc_log,
+        maintenance_registered: stats.maintenance_registered,
+        suggestions: stats.suggestions,
+    })
+}
+
+pub(crate) async fn query_git_detect_large_blobs(
+    app_state: &SharedAppState,
+    project: &str,
+    threshold: Option<u64>,
+    filter_repo_plan: bool,
+) -> Result<ServerMessage, String> {
+    let proj_ctx = resolve_project(app_state, project)
+        .await
+        .map_err(|e| e.to_string())?;
+    let root = proj_ctx.root_path;
+    let report = crate::util::trace::spawn_blocking(move || {
+        git::git_detect_large_blobs(&root, threshold, filter_repo_plan)
     })
     .await
-    .map_err(|e| format!("Integration status task failed: {}", e))?
-    .map_err(|e| format!("Integration status failed: {}", e))?;
+    .map_err(|e| format!("Git large blob scan task failed: {}", e))?
+    .map_err(|e| format!("Git large blob scan failed: {}", e))?;
+
+    Ok(ServerMessage::GitDetectLargeBlobsResult {
+        project: project.to_string(),
+        threshold: report.threshold,
+        blobs: report
+            .blobs
+            .into_iter()
+            .map(|b| GitLargeBlobInfo {
+                sha: b.sha,
+                paths: b.paths,
+                size: b.size,
+                disk_size: b.disk_size,
+                commits: b
+                    .commits
+                    .into_iter()
+                    .map(|c| GitLargeBlobCommitInfo {
+                        sha: c.sha,
+                        subject: c.subject,
+                        committed_at: c.committed_at,
+                    })
+                    .collect(),
+            })
+            .collect(),
+        total_count: report.total_count,
+        total_size: report.total_size,
+        truncated: report.truncated,
+        filter_repo: report.filter_repo.map(|plan| GitFilterRepoPlanInfo {
+            available: plan.available,
+            strip_by_size_command: plan.strip_by_size_command,
+            remove_paths_command: plan.remove_paths_command,
+            estimated_savings_bytes: plan.estimated_savings_bytes,
+        }),
+    })
+}
+
+pub(crate) async fn query_git_integration_status(
+    app_state: &SharedAppState,
+    project: &str,
+    branch: Option<&str>,
+) -> Result<ServerMessage, String> {
+    let proj_ctx = resolve_project(app_state, project)
+        .await
+        .map_err(|e| e.to_string())?;
+    let target =
+        git::IntegrationTarget::resolve(&proj_ctx.project_name, &proj_ctx.default_branch, branch);
+    let result = crate::util::trace::spawn_blocking(move || git::integration_status(&target))
+        .await
+        .map_err(|e| format!("Integration status task failed: {}", e))?
+        .map_err(|e| format!("Integration status failed: {}", e))?;
 
     Ok(ServerMessage::GitIntegrationStatusResult {
         project: project.to_string(),
@@ -276,6 +929,132 @@ pub(crate) async fn query_git_integration_status(
     })
 }
 
+pub(crate) async fn query_git_pull_requests(
+    app_state: &SharedAppState,
+    project: &str,
+) -> Result<ServerMessage, String> {
+    let (root, workspaces) = {
+        let state = app_state.read().await;
+        let proj = state
+            .get_project(project)
+            .ok_or_else(|| AppError::ProjectNotFound(project.to_string()).to_string())?;
+        let workspaces: Vec<(String, String)> = proj
+            .workspaces
+            .values()
+            .map(|w| (w.branch.clone(), w.name.clone()))
+            .collect();
+        (proj.root_path.clone(), workspaces)
+    };
+    let (repo, token, default_branch) = resolve_pull_request_repo(app_state, root).await?;
+    let mut branches: Vec<(String, String)> = Vec::new();
+    if let Some(branch) = default_branch {
+        branches.push((branch, DEFAULT_WORKSPACE_NAME.to_string()));
+    }
+    branches.extend(workspaces);
+    let names: Vec<String> = branches.iter().map(|(b, _)| b.clone()).collect();
+
+    let statuses = git::list_pull_requests(&repo, &token, &names)
+        .await
+        .map_err(|e| format!("Pull request query failed: {}", e))?;
+    let items = statuses
+        .into_iter()
+        .map(|status| {
+            let workspace = branches
+                .iter()
+                .find(|(b, _)| *b == status.branch)
+                .map(|(_, w)| w.clone());
+            to_pull_request_info(status, workspace)
+        })
+        .collect();
+    Ok(ServerMessage::GitPullRequestsResult {
+        project: project.to_string(),
+        provider: repo.provider.as_str().to_string(),
+        items,
+    })
+}
+
+pub(crate) async fn query_git_pull_request_status(
+    app_state: &SharedAppState,
+    project: &str,
+    number: u64,
+) -> Result<ServerMessage, String> {
+    let proj_ctx = resolve_project(app_state, project)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (repo, token, default_branch) =
+        resolve_pull_request_repo(app_state, proj_ctx.root_path).await?;
+    let status = git::pull_request_status(&repo, &token, number)
+        .await
+        .map_err(|e| format!("Pull request query failed: {}", e))?;
+    let workspace = if default_branch.as_deref() == Some(status.branch.as_str()) {
+        Some(DEFAULT_WORKSPACE_NAME.to_string())
+    } else {
+        app_state
+            .read()
+            .await
+            .get_project(project)
+            .and_then(|p| p.workspaces.values().find(|w| w.branch == status.branch))
+            .map(|w| w.name.clone())
+    };
+    Ok(ServerMessage::GitPullRequestStatusResult {
+        project: project.to_string(),
+        provider: repo.provider.as_str().to_string(),
+        pull_request: to_pull_request_info(status, workspace),
+    })
+}
+
+/// 识别托管仓库并取得访问令牌；同时返回项目根目录当前分支（default 工作空间）
+async fn resolve_pull_request_repo(
+    app_state: &SharedAppState,
+    root: PathBuf,
+) -> Result<(git::HostedRepo, String, Option<String>), String> {
+    let (repo, current_branch) = crate::util::trace::spawn_blocking(move || {
+        git::ensure_online("pull request query")?;
+        let repo = git::hosted_repo(&root)?;
+        Ok::<_, git::GitError>((repo, git::git_current_branch(&root)?))
+    })
+    .await
+    .map_err(|e| format!("Pull request query task failed: {}", e))?
+    .map_err(|e| match e {
+        git::GitError::OfflineMode(op) => format!("Offline mode is enabled, git {} skipped", op),
+        other => other.to_string(),
+    })?;
+    let token = git::hosting_token(
+        &app_state.read().await.client_settings.git_hosting_tokens,
+        &repo.host,
+    )
+    .ok_or_else(|| format!("Missing access token for {}", repo.host))?;
+    Ok((repo, token, current_branch))
+}
+
+fn to_pull_request_info(
+    status: git::PullRequestStatus,
+    workspace: Option<String>,
+) -> GitPullRequestInfo {
+    GitPullRequestInfo {
+        checks_state: status.checks_state().to_string(),
+        number: status.number,
+        title: status.title,
+        url: status.url,
+        branch: status.branch,
+        target_branch: status.target_branch,
+        workspace,
+        draft: status.draft,
+        state: status.state,
+        mergeable: status.mergeable,
+        merge_state: status.merge_state,
+        checks: status
+            .checks
+            .into_iter()
+            .map(|c| GitPullRequestCheckInfo {
+                name: c.name,
+                state: c.state.as_str().to_string(),
+                url: c.url,
+            })
+            .collect(),
+    }
+}
+
 pub(crate) async fn query_git_check_branch_up_to_date(
     app_state: &SharedAppState,
     project: &str,
@@ -304,7 +1083,7 @@ pub(crate) async fn query_git_check_branch_up_to_date(
             conflicts: vec![],
             conflict_files: vec![],
             head: None,
-            default_branch: "main".to_string(),
+            default_branch: proj_ctx.default_branch.clone(),
             path: root.to_string_lossy().to_string(),
             is_clean: true,
             branch_ahead_by: None,
@@ -317,21 +1096,19 @@ pub(crate) async fn query_git_check_branch_up_to_date(
     let default_branch_clone = default_branch.clone();
     let current_branch_clone = current_branch.clone();
 
-    let divergence_result = tokio::task::spawn_blocking(move || {
+    let divergence_result = crate::util::trace::spawn_blocking(move || {
         git::check_branch_divergence(&root, &current_branch_clone, &default_branch_clone)
     })
     .await
     .map_err(|e| format!("Branch divergence task failed: {}", e))?
     .map_err(|e| format!("Branch divergence failed: {}", e))?;
 
-    let integration_result = tokio::task::spawn_blocking({
-        let project_name = project_name.clone();
-        let default_branch = default_branch.clone();
-        move || git::integration_status(&project_name, &default_branch)
-    })
-    .await
-    .map_err(|e| format!("Integration status task failed: {}", e))?
-    .map_err(|e| format!("Integration status failed: {}", e))?;
+    let target = git::IntegrationTarget::default_for(&project_name, &default_branch);
+    let integration_result =
+        crate::util::trace::spawn_blocking(move || git::integration_status(&target))
+            .await
+            .map_err(|e| format!("Integration status task failed: {}", e))?
+            .map_err(|e| format!("Integration status failed: {}", e))?;
 
     Ok(ServerMessage::GitIntegrationStatusResult {
         project: project.to_string(),
@@ -363,11 +1140,12 @@ pub(crate) async fn query_git_conflict_detail(
     path: &str,
     context: &str,
 ) -> Result<ServerMessage, String> {
-    let root = if context == "integration" {
+    let root = if let Some(branch) = git::integration_context_branch(context) {
         let proj_ctx = resolve_project(app_state, project)
             .await
             .map_err(|e| e.to_string())?;
-        git::get_integration_worktree_root(&proj_ctx.project_name)
+        git::IntegrationTarget::resolve(&proj_ctx.project_name, &proj_ctx.default_branch, branch)
+            .worktree_path()
     } else {
         resolve_workspace(app_state, project, workspace)
             .await
@@ -377,7 +1155,7 @@ pub(crate) async fn query_git_conflict_detail(
 
     let path_owned = path.to_string();
     let context_owned = context.to_string();
-    let detail = tokio::task::spawn_blocking(move || {
+    let detail = crate::util::trace::spawn_blocking(move || {
         git::git_conflict_detail(&root, &path_owned, &context_owned)
     })
     .await
@@ -395,9 +1173,27 @@ pub(crate) async fn query_git_conflict_detail(
         current_content: detail.current_content,
         conflict_markers_count: detail.conflict_markers_count,
         is_binary: detail.is_binary,
+        regions: conflict_region_infos(detail.regions),
     })
 }
 
+/// 冲突区块转协议 DTO（HTTP 与 WS 冲突详情共用）
+pub(crate) fn conflict_region_infos(regions: Vec<git::ConflictRegion>) -> Vec<ConflictRegionInfo> {
+    regions
+        .into_iter()
+        .map(|r| ConflictRegionInfo {
+            index: r.index,
+            start_line: r.start_line,
+            end_line: r.end_line,
+            ours_label: r.ours_label,
+            theirs_label: r.theirs_label,
+            ours: r.ours,
+            base: r.base,
+            theirs: r.theirs,
+        })
+        .collect()
+}
+
 pub(crate) async fn query_git_stash_list(
     app_state: &SharedAppState,
     project: &str,
@@ -408,7 +1204,7 @@ pub(crate) async fn query_git_stash_list(
         .map_err(|e| e.to_string())?;
     let root = ws_ctx.root_path;
 
-    let result = tokio::task::spawn_blocking(move || git::git_stash_list(&root))
+    let result = crate::util::trace::spawn_blocking(move || git::git_stash_list(&root))
         .await
         .map_err(|e| format!("Git stash list task failed: {}", e))?
         .map_err(|e| format!("Git stash list failed: {}", e))?;
@@ -446,7 +1242,7 @@ pub(crate) async fn query_git_stash_show(
     let stash_id_clone = stash_id.to_string();
 
     let result =
-        tokio::task::spawn_blocking(move || git::git_stash_show(&root, &stash_id_clone))
+        crate::util::trace::spawn_blocking(move || git::git_stash_show(&root, &stash_id_clone))
             .await
             .map_err(|e| format!("Git stash show task failed: {}", e))?
             .map_err(|e| format!("Git stash show failed: {}", e))?;
@@ -480,3 +1276,211 @@ pub(crate) async fn query_git_stash_show(
         is_binary_summary_truncated: result.is_binary_summary_truncated,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::exec_env::git_command;
+    use crate::workspace::state::{AppState, Project, Workspace, WorkspaceStatus};
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let output = git_command(dir).args(args).output().expect("run git");
+        assert!(output.status.success(), "git {:?} failed", args);
+    }
+
+    fn workspace(name: &str, path: PathBuf) -> (String, Workspace) {
+        let now = chrono::Utc::now();
+        let workspace = Workspace {
+            name: name.to_string(),
+            worktree_path: path,
+            branch: name.to_string(),
+            status: WorkspaceStatus::Ready,
+            created_at: now,
+            last_accessed: now,
+            setup_result: None,
+            recovery_meta: None,
+            port: None,
+            linked_issue: None,
+            sparse_paths: Vec::new(),
+        };
+        (name.to_string(), workspace)
+    }
+
+    #[tokio::test]
+    async fn git_status_all_summarizes_every_workspace_in_order() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("repo");
+        std::fs::create_dir_all(&root).unwrap();
+        run_git(&root, &["init", "-q", "-b", "main"]);
+        run_git(&root, &["config", "user.name", "Alice"]);
+        run_git(&root, &["config", "user.email", "alice@example.com"]);
+        run_git(&root, &["config", "commit.gpgsign", "false"]);
+        std::fs::write(root.join("a.txt"), "a\n").unwrap();
+        run_git(&root, &["add", "a.txt"]);
+        run_git(&root, &["commit", "-q", "-m", "init"]);
+
+        let feature = tmp.path().join("feature");
+        run_git(
+            &root,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "feature",
+                feature.to_str().unwrap(),
+            ],
+        );
+        std::fs::write(feature.join("b.txt"), "b\n").unwrap();
+        run_git(&feature, &["add", "b.txt"]);
+        run_git(&feature, &["commit", "-q", "-m", "feature"]);
+        std::fs::write(feature.join("a.txt"), "changed\n").unwrap();
+        std::fs::write(feature.join("c.txt"), "c\n").unwrap();
+        std::fs::write(root.join("new.txt"), "n\n").unwrap();
+
+        let mut state = AppState::default();
+        state.add_project(Project {
+            name: "demo".to_string(),
+            root_path: root.clone(),
+            remote_url: None,
+            default_branch: "main".to_string(),
+            created_at: chrono::Utc::now(),
+            workspaces: HashMap::from([
+                workspace("feature", feature),
+                workspace("archived", tmp.path().join("missing")),
+            ]),
+            commands: vec![],
+        });
+        let app_state: SharedAppState = Arc::new(RwLock::new(state));
+
+        let ServerMessage::GitStatusAllResult {
+            project,
+            workspaces,
+        } = query_git_status_all(&app_state, "demo").await.unwrap()
+        else {
+            panic!("unexpected response");
+        };
+        assert_eq!(project, "demo");
+        let names: Vec<&str> = workspaces.iter().map(|w| w.workspace.as_str()).collect();
+        assert_eq!(names, vec!["default", "archived", "feature"]);
+
+        let default = &workspaces[0];
+        assert_eq!(default.current_branch.as_deref(), Some("main"));
+        assert_eq!((default.untracked_count, default.unstaged_count), (1, 0));
+        assert!(default.error.is_none());
+
+        assert!(workspaces[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("not found"));
+
+        let feature = &workspaces[2];
+        assert_eq!(feature.current_branch.as_deref(), Some("feature"));
+        assert_eq!(
+            (
+                feature.staged_count,
+                feature.unstaged_count,
+                feature.untracked_count
+            ),
+            (0, 1, 1)
+        );
+        assert_eq!((feature.ahead_by, feature.behind_by), (Some(1), Some(0)));
+
+        assert!(query_git_status_all(&app_state, "nope")
+            .await
+            .unwrap_err()
+            .contains("not found"));
+    }
+
+    #[test]
+    fn aggregate_status_tree_counts_every_ancestor_directory() {
+        let entry = |path: &str, code: &str, staged: bool| git::GitStatusEntry {
+            path: path.to_string(),
+            code: code.to_string(),
+            orig_path: None,
+            staged,
+            additions: None,
+            deletions: None,
+        };
+        let directories = aggregate_status_tree(&[
+            entry("README.md", "M", false),
+            entry("src/lib.rs", "M", true),
+            entry("src/git/status.rs", "U", false),
+            entry("assets/", "??", false),
+        ]);
+        let counts: Vec<(&str, usize, usize, usize, usize, usize)> = directories
+            .iter()
+            .map(|d| {
+                (
+                    d.path.as_str(),
+                    d.dirty_count,
+                    d.staged_count,
+                    d.unstaged_count,
+                    d.untracked_count,
+                    d.conflict_count,
+                )
+            })
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("", 4, 1, 1, 1, 1),
+                ("assets", 1, 0, 0, 1, 0),
+                ("src", 2, 1, 0, 0, 1),
+                ("src/git", 1, 0, 0, 0, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn aggregate_status_tree_matches_across_backends() {
+        use git::backend::{CliBackend, GitBackend, GixBackend};
+
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        run_git(root, &["init", "-q", "-b", "main"]);
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "a\n").unwrap();
+        run_git(root, &["add", "."]);
+        run_git(
+            root,
+            &[
+                "-c",
+                "user.name=t",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "-q",
+                "-m",
+                "init",
+            ],
+        );
+        std::fs::write(root.join("src/lib.rs"), "b\n").unwrap();
+        std::fs::create_dir_all(root.join("src/gen/deep")).unwrap();
+        std::fs::write(root.join("src/gen/deep/out.rs"), "x\n").unwrap();
+        std::fs::write(root.join("notes.txt"), "n\n").unwrap();
+
+        let gix = aggregate_status_tree(&GixBackend.status(root, "main").unwrap().items);
+        let cli = aggregate_status_tree(&CliBackend.status(root, "main").unwrap().items);
+        let summary = |directories: &[GitStatusDirectoryInfo]| {
+            directories
+                .iter()
+                .map(|d| (d.path.clone(), d.dirty_count, d.untracked_count))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(summary(&gix), summary(&cli));
+        assert_eq!(
+            summary(&cli),
+            vec![
+                (String::new(), 3, 2),
+                ("src".to_string(), 2, 1),
+                ("src/gen".to_string(), 1, 1),
+            ]
+        );
+    }
+}
@@ -4,7 +4,10 @@ use crate::server::context::{HandlerContext, SharedAppState};
 use crate::server::handlers::dispatch_handlers;
 use crate::server::protocol::ClientMessage;
 
-use super::{branch_commit, history, integration, sequencer, stage_ops, stash, status_diff};
+use super::{
+    branch_commit, commit_message, history, integration, pull_request, sequencer, stage_ops, stash,
+    status_diff,
+};
 
 /// 标准 Git 消息路由（按既有顺序短路匹配）。
 pub async fn handle_standard_git_routes(
@@ -14,7 +17,9 @@ pub async fn handle_standard_git_routes(
     ctx: &HandlerContext,
 ) -> Result<bool, String> {
     match client_msg {
-        ClientMessage::GitStatus { project, workspace } => {
+        ClientMessage::GitStatus {
+            project, workspace, ..
+        } => {
             crate::server::handlers::send_read_via_http_required(
                 socket,
                 "git_status",
@@ -25,6 +30,28 @@ pub async fn handle_standard_git_routes(
             .await?;
             return Ok(true);
         }
+        ClientMessage::GitStatusAll { project } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "git_status_all",
+                "/api/v1/projects/:project/git/status-all",
+                Some(project.clone()),
+                None,
+            )
+            .await?;
+            return Ok(true);
+        }
+        ClientMessage::GitStatusTree { project, workspace } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "git_status_tree",
+                "/api/v1/projects/:project/workspaces/:workspace/git/status-tree",
+                Some(project.clone()),
+                Some(workspace.clone()),
+            )
+            .await?;
+            return Ok(true);
+        }
         ClientMessage::GitDiff {
             project, workspace, ..
         } => {
@@ -38,6 +65,19 @@ pub async fn handle_standard_git_routes(
             .await?;
             return Ok(true);
         }
+        ClientMessage::GitDiffPage {
+            project, workspace, ..
+        } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "git_diff_page",
+                "/api/v1/projects/:project/workspaces/:workspace/git/diff-page",
+                Some(project.clone()),
+                Some(workspace.clone()),
+            )
+            .await?;
+            return Ok(true);
+        }
         ClientMessage::GitBranches { project, workspace } => {
             crate::server::handlers::send_read_via_http_required(
                 socket,
@@ -75,6 +115,71 @@ pub async fn handle_standard_git_routes(
             .await?;
             return Ok(true);
         }
+        ClientMessage::GitShowFileDiff {
+            project, workspace, ..
+        } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "git_show_file_diff",
+                "/api/v1/projects/:project/workspaces/:workspace/git/commits/:sha/diff",
+                Some(project.clone()),
+                Some(workspace.clone()),
+            )
+            .await?;
+            return Ok(true);
+        }
+        ClientMessage::GitSuggestedCommitMessage {
+            project, workspace, ..
+        } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "git_suggested_commit_message",
+                "/api/v1/projects/:project/workspaces/:workspace/git/suggested-commit-message",
+                Some(project.clone()),
+                Some(workspace.clone()),
+            )
+            .await?;
+            return Ok(true);
+        }
+        ClientMessage::GitBlame {
+            project, workspace, ..
+        } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "git_blame",
+                "/api/v1/projects/:project/workspaces/:workspace/git/blame",
+                Some(project.clone()),
+                Some(workspace.clone()),
+            )
+            .await?;
+            return Ok(true);
+        }
+        ClientMessage::GitDiffRange {
+            project, workspace, ..
+        } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "git_diff_range",
+                "/api/v1/projects/:project/workspaces/:workspace/git/diff-range",
+                Some(project.clone()),
+                Some(workspace.clone()),
+            )
+            .await?;
+            return Ok(true);
+        }
+        ClientMessage::GitRebaseInteractivePlan {
+            project, workspace, ..
+        } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "git_rebase_interactive_plan",
+                "/api/v1/projects/:project/workspaces/:workspace/git/rebase-plan",
+                Some(project.clone()),
+                Some(workspace.clone()),
+            )
+            .await?;
+            return Ok(true);
+        }
         ClientMessage::GitOpStatus { project, workspace } => {
             crate::server::handlers::send_read_via_http_required(
                 socket,
@@ -86,7 +191,7 @@ pub async fn handle_standard_git_routes(
             .await?;
             return Ok(true);
         }
-        ClientMessage::GitIntegrationStatus { project } => {
+        ClientMessage::GitIntegrationStatus { project, .. } => {
             crate::server::handlers::send_read_via_http_required(
                 socket,
                 "git_integration_status",
@@ -97,6 +202,28 @@ pub async fn handle_standard_git_routes(
             .await?;
             return Ok(true);
         }
+        ClientMessage::GitRepoStats { project } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "git_repo_stats",
+                "/api/v1/projects/:project/git/repo-stats",
+                Some(project.clone()),
+                None,
+            )
+            .await?;
+            return Ok(true);
+        }
+        ClientMessage::GitDetectLargeBlobs { project, .. } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "git_detect_large_blobs",
+                "/api/v1/projects/:project/git/large-blobs",
+                Some(project.clone()),
+                None,
+            )
+            .await?;
+            return Ok(true);
+        }
         ClientMessage::GitCheckBranchUpToDate { project, workspace } => {
             crate::server::handlers::send_read_via_http_required(
                 socket,
@@ -145,6 +272,28 @@ pub async fn handle_standard_git_routes(
             .await?;
             return Ok(true);
         }
+        ClientMessage::GitListPullRequests { project } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "git_list_pull_requests",
+                "/api/v1/projects/:project/git/pull-requests",
+                Some(project.clone()),
+                None,
+            )
+            .await?;
+            return Ok(true);
+        }
+        ClientMessage::GitPullRequestStatus { project, .. } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "git_pull_request_status",
+                "/api/v1/projects/:project/git/pull-requests/:number",
+                Some(project.clone()),
+                None,
+            )
+            .await?;
+            return Ok(true);
+        }
         _ => {}
     }
 
@@ -156,6 +305,8 @@ pub async fn handle_standard_git_routes(
         branch_commit::handle_message(client_msg, socket, app_state, ctx),
         integration::handle_message(client_msg, socket, app_state, ctx),
         history::handle_message(client_msg, socket, app_state),
+        pull_request::handle_message(client_msg, socket, app_state),
+        commit_message::handle_message(client_msg, socket, app_state),
     );
 
     Ok(false)
@@ -30,7 +30,7 @@ pub async fn handle_message(
             let shas = commit_shas.clone();
             let project_c = project.clone();
             let workspace_c = workspace.clone();
-            let result = tokio::task::spawn_blocking(move || {
+            let result = crate::util::trace::spawn_blocking(move || {
                 let original_head =
                     git::sequencer::get_full_head_sha(&root).unwrap_or_default();
                 let res = git::git_cherry_pick(&root, &shas);
@@ -67,6 +67,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -81,6 +82,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -128,7 +130,7 @@ pub async fn handle_message(
             let shas = commit_shas.clone();
             let project_c = project.clone();
             let workspace_c = workspace.clone();
-            let result = tokio::task::spawn_blocking(move || {
+            let result = crate::util::trace::spawn_blocking(move || {
                 let original_head =
                     git::sequencer::get_full_head_sha(&root).unwrap_or_default();
                 let res = git::git_revert(&root, &shas);
@@ -165,6 +167,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -179,6 +182,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -221,7 +225,7 @@ pub async fn handle_message(
             let root = ws_ctx.root_path;
             let project_c = project.clone();
             let workspace_c = workspace.clone();
-            let result = tokio::task::spawn_blocking(move || {
+            let result = crate::util::trace::spawn_blocking(move || {
                 git::git_workspace_op_rollback(&root, &project_c, &workspace_c)
             })
             .await;
@@ -250,6 +254,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -264,6 +269,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -296,7 +302,7 @@ async fn handle_sequencer_continue_abort(
     let project_c = project.to_string();
     let workspace_c = workspace.to_string();
 
-    let result = tokio::task::spawn_blocking(move || {
+    let result = crate::util::trace::spawn_blocking(move || {
         match op_str.as_str() {
             "cherry_pick_continue" => git::git_cherry_pick_continue(&root),
             "cherry_pick_abort" => {
@@ -337,6 +343,7 @@ async fn handle_sequencer_continue_abort(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -351,6 +358,7 @@ async fn handle_sequencer_continue_abort(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await?;
@@ -365,29 +373,26 @@ async fn send_sequencer_result(
     workspace: &str,
     result: &git::SequencerResult,
 ) -> Result<(), String> {
-    send_message(
-        socket,
-        &ServerMessage::GitSequencerResult {
-            project: project.to_string(),
-            workspace: workspace.to_string(),
-            operation_kind: result.operation_kind.as_str().to_string(),
-            ok: result.ok,
-            state: result.state.clone(),
-            message: result.message.clone(),
-            conflicts: result.conflicts.clone(),
-            conflict_files: result
-                .conflict_files
-                .iter()
-                .map(|f| ConflictFileEntryInfo {
-                    path: f.path.clone(),
-                    conflict_type: f.conflict_type.clone(),
-                    staged: f.staged,
-                })
-                .collect(),
-            completed_count: result.completed_count,
-            pending_count: result.pending_count,
-            current_commit: result.current_commit.clone(),
-        },
-    )
-    .await
+    let msg = ServerMessage::GitSequencerResult {
+        project: project.to_string(),
+        workspace: workspace.to_string(),
+        operation_kind: result.operation_kind.as_str().to_string(),
+        ok: result.ok,
+        state: result.state.clone(),
+        message: result.message.clone(),
+        conflicts: result.conflicts.clone(),
+        conflict_files: result
+            .conflict_files
+            .iter()
+            .map(|f| ConflictFileEntryInfo {
+                path: f.path.clone(),
+                conflict_type: f.conflict_type.clone(),
+                staged: f.staged,
+            })
+            .collect(),
+        completed_count: result.completed_count,
+        pending_count: result.pending_count,
+        current_commit: result.current_commit.clone(),
+    };
+    send_message(socket, &msg).await
 }
@@ -29,7 +29,7 @@ pub async fn handle_message(
             let root = ws_ctx.root_path;
             let path_clone = path.clone();
             let scope_clone = scope.clone();
-            let result = tokio::task::spawn_blocking(move || {
+            let result = crate::util::trace::spawn_blocking(move || {
                 git::git_stage(&root, path_clone.as_deref(), &scope_clone)
             })
             .await;
@@ -75,6 +75,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -101,7 +102,7 @@ pub async fn handle_message(
             let root = ws_ctx.root_path;
             let path_clone = path.clone();
             let scope_clone = scope.clone();
-            let result = tokio::task::spawn_blocking(move || {
+            let result = crate::util::trace::spawn_blocking(move || {
                 git::git_unstage(&root, path_clone.as_deref(), &scope_clone)
             })
             .await;
@@ -147,6 +148,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -175,7 +177,7 @@ pub async fn handle_message(
             let path_clone = path.clone();
             let scope_clone = scope.clone();
             let include_untracked_clone = *include_untracked;
-            let result = tokio::task::spawn_blocking(move || {
+            let result = crate::util::trace::spawn_blocking(move || {
                 git::git_discard(
                     &root,
                     path_clone.as_deref(),
@@ -226,6 +228,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
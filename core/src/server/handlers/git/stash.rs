@@ -76,7 +76,7 @@ pub async fn handle_message(
             let keep_index = *keep_index;
             let paths_clone = paths.clone();
 
-            let result = tokio::task::spawn_blocking(move || {
+            let result = crate::util::trace::spawn_blocking(move || {
                 git::git_stash_save(
                     &root,
                     msg_clone.as_deref(),
@@ -112,6 +112,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -136,7 +137,7 @@ pub async fn handle_message(
             let root = ws_ctx.root_path;
             let stash_id_clone = stash_id.clone();
 
-            let result = tokio::task::spawn_blocking(move || {
+            let result = crate::util::trace::spawn_blocking(move || {
                 git::git_stash_apply(&root, &stash_id_clone)
             })
             .await;
@@ -166,6 +167,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -190,7 +192,7 @@ pub async fn handle_message(
             let root = ws_ctx.root_path;
             let stash_id_clone = stash_id.clone();
 
-            let result = tokio::task::spawn_blocking(move || {
+            let result = crate::util::trace::spawn_blocking(move || {
                 git::git_stash_pop(&root, &stash_id_clone)
             })
             .await;
@@ -220,6 +222,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -244,7 +247,7 @@ pub async fn handle_message(
             let root = ws_ctx.root_path;
             let stash_id_clone = stash_id.clone();
 
-            let result = tokio::task::spawn_blocking(move || {
+            let result = crate::util::trace::spawn_blocking(move || {
                 git::git_stash_drop(&root, &stash_id_clone)
             })
             .await;
@@ -274,6 +277,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -300,7 +304,7 @@ pub async fn handle_message(
             let stash_id_clone = stash_id.clone();
             let paths_clone = paths.clone();
 
-            let result = tokio::task::spawn_blocking(move || {
+            let result = crate::util::trace::spawn_blocking(move || {
                 git::git_stash_restore_paths(&root, &stash_id_clone, &paths_clone)
             })
             .await;
@@ -335,6 +339,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
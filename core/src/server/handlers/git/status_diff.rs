@@ -12,7 +12,31 @@ pub async fn handle_message(
 ) -> Result<bool, String> {
     match client_msg {
         // v1.5: Git status
-        ClientMessage::GitStatus { project, workspace } => {
+        ClientMessage::GitStatus {
+            project,
+            workspace,
+            untracked_files,
+            expand_untracked,
+        } => {
+            let expand_all = match git::parse_untracked_files_mode(untracked_files.as_deref()) {
+                Ok(expand_all) => expand_all,
+                Err(e) => {
+                    send_message(
+                        socket,
+                        &ServerMessage::Error {
+                            code: "git_error".to_string(),
+                            message: format!("Git status failed: {}", e),
+                            project: None,
+                            workspace: None,
+                            session_id: None,
+                            cycle_id: None,
+                            trace_id: None,
+                        },
+                    )
+                    .await?;
+                    return Ok(true);
+                }
+            };
             let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
                 Ok(ctx) => ctx,
                 Err(e) => {
@@ -25,11 +49,21 @@ pub async fn handle_message(
             let default_branch = ws_ctx.default_branch;
 
             // git_status 现在一次性产出 status items、current_branch 和 divergence
-            let result =
-                tokio::task::spawn_blocking(move || git::git_status(&root, &default_branch)).await;
+            let expand_untracked = expand_untracked.clone();
+            let result = crate::util::trace::spawn_blocking(move || {
+                let mut status = git::git_status(&root, &default_branch)?;
+                let truncated = git::expand_untracked_dirs(
+                    &root,
+                    &mut status.items,
+                    expand_all,
+                    &expand_untracked,
+                )?;
+                Ok::<_, git::GitError>((status, truncated))
+            })
+            .await;
 
             match result {
-                Ok(Ok(status_result)) => {
+                Ok(Ok((status_result, untracked_truncated))) => {
                     let items: Vec<GitStatusEntry> = status_result
                         .items
                         .into_iter()
@@ -57,6 +91,7 @@ pub async fn handle_message(
                             ahead_by: status_result.ahead_by,
                             behind_by: status_result.behind_by,
                             compared_branch: status_result.compared_branch,
+                            untracked_truncated,
                         },
                     )
                     .await?;
@@ -71,6 +106,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -85,6 +121,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -100,7 +137,32 @@ pub async fn handle_message(
             path,
             base,
             mode,
+            algorithm,
+            range,
+            format,
+            intraline,
+            smart_diff,
+            include_blobs,
         } => {
+            let structured = match git::is_structured_format(format.as_deref()) {
+                Ok(structured) => structured || *intraline,
+                Err(e) => {
+                    send_message(
+                        socket,
+                        &ServerMessage::Error {
+                            code: "git_error".to_string(),
+                            message: format!("Git diff failed: {}", e),
+                            project: None,
+                            workspace: None,
+                            session_id: None,
+                            cycle_id: None,
+                            trace_id: None,
+                        },
+                    )
+                    .await?;
+                    return Ok(true);
+                }
+            };
             let ws_ctx = match resolve_workspace(app_state, project, workspace).await {
                 Ok(ctx) => ctx,
                 Err(e) => {
@@ -113,13 +175,46 @@ pub async fn handle_message(
             let path_clone = path.clone();
             let base_clone = base.clone();
             let mode_clone = mode.clone();
-            let result = tokio::task::spawn_blocking(move || {
-                git::git_diff(&root, &path_clone, base_clone.as_deref(), &mode_clone)
+            let algorithm_clone = algorithm.clone();
+            let range_clone = range.clone();
+            let smart_diff = *smart_diff;
+            let include_blobs = *include_blobs;
+            let result = crate::util::trace::spawn_blocking(move || {
+                let diff_result = match range_clone.as_deref() {
+                    Some(range) => git::git_diff_file_in_range(
+                        &root,
+                        range,
+                        &path_clone,
+                        algorithm_clone.as_deref(),
+                    ),
+                    None => git::git_diff(
+                        &root,
+                        &path_clone,
+                        base_clone.as_deref(),
+                        &mode_clone,
+                        algorithm_clone.as_deref(),
+                        smart_diff,
+                    ),
+                }?;
+                let blobs = super::query::binary_blob_infos(
+                    &root,
+                    &diff_result,
+                    base_clone.as_deref(),
+                    &mode_clone,
+                    range_clone.as_deref(),
+                    include_blobs,
+                );
+                Ok::<_, git::GitError>((diff_result, blobs))
             })
             .await;
 
             match result {
-                Ok(Ok(diff_result)) => {
+                Ok(Ok((diff_result, (old_blob, new_blob)))) => {
+                    let hunks = structured
+                        .then(|| super::query::structured_diff_hunks(&diff_result, *intraline));
+                    let next_offset = diff_result
+                        .truncated
+                        .then_some(diff_result.text.len() as u64);
                     send_message(
                         socket,
                         &ServerMessage::GitDiffResult {
@@ -127,12 +222,22 @@ pub async fn handle_message(
                             workspace: workspace.clone(),
                             path: path.clone(),
                             code: diff_result.code,
-                            format: diff_result.format,
+                            format: if structured {
+                                git::DIFF_FORMAT_STRUCTURED.to_string()
+                            } else {
+                                diff_result.format
+                            },
                             text: diff_result.text,
                             is_binary: diff_result.is_binary,
                             truncated: diff_result.truncated,
                             mode: diff_result.mode,
                             base: base.clone(),
+                            range: range.clone(),
+                            hunks,
+                            smart_diff: diff_result.smart_diff,
+                            next_offset,
+                            old_blob,
+                            new_blob,
                         },
                     )
                     .await?;
@@ -147,6 +252,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -161,6 +267,7 @@ pub async fn handle_message(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
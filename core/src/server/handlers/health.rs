@@ -1,6 +1,6 @@
 //! 健康域消息处理器（WI-002 / WI-003）
 //!
-//! 处理客户端健康上报（`health_report`）和修复动作请求（`health_repair`）。
+//! 处理客户端健康上报（`health_report`）、修复动作请求（`health_repair`）、状态快照恢复（`restore_state_backup`）与审计日志查询（`get_audit_log`）。
 //! WI-002: 新增门禁裁决查询支持。
 
 use crate::server::ws::OutboundTx as WebSocket;
@@ -44,12 +44,71 @@ pub async fn handle_health_message(
                 request.clone(),
                 "client_request",
                 ctx.app_state.clone(),
+                &ctx.state_store,
+                &ctx.save_tx,
             )
             .await;
             send_message(socket, &ServerMessage::HealthRepairResult { audit }).await?;
             Ok(true)
         }
 
+        ClientMessage::RestoreStateBackup { backup_id } => {
+            let msg = crate::application::state_backup::restore_state_backup_message(
+                &ctx.app_state,
+                &ctx.state_store,
+                backup_id,
+            )
+            .await;
+            let success = matches!(msg, ServerMessage::StateBackupRestored { ok: true, .. });
+            if let ServerMessage::StateBackupRestored {
+                ok: false, message, ..
+            } = &msg
+            {
+                tracing::warn!(
+                    backup_id = %backup_id,
+                    error = message.as_deref().unwrap_or("unknown"),
+                    "Failed to restore state backup"
+                );
+            }
+            send_message(socket, &msg).await?;
+            if success {
+                // 项目列表整体变化，其他连接经快照广播刷新
+                let snapshot =
+                    crate::application::project::list_projects_message(&ctx.app_state).await;
+                let _ = crate::server::context::send_task_broadcast_message(
+                    &ctx.task_broadcast_tx,
+                    &ctx.conn_meta.conn_id,
+                    snapshot,
+                );
+            }
+            Ok(true)
+        }
+
+        ClientMessage::GetAuditLog { since, limit } => {
+            let path = crate::server::audit_log::default_audit_log_path();
+            match crate::server::audit_log::read_entries(&path, *since, *limit).await {
+                Ok(entries) => {
+                    send_message(socket, &ServerMessage::AuditLogResult { entries }).await?;
+                }
+                Err(e) => {
+                    send_message(
+                        socket,
+                        &ServerMessage::Error {
+                            code: "audit_log_read_failed".to_string(),
+                            message: format!("Failed to read audit log: {}", e),
+                            project: None,
+                            workspace: None,
+                            session_id: None,
+                            cycle_id: None,
+                            trace_id: None,
+                        },
+                    )
+                    .await?;
+                }
+            }
+            Ok(true)
+        }
+
         _ => Ok(false),
     }
 }
@@ -32,10 +32,23 @@ pub async fn handle_node_message(
                     remote_access_enabled: None,
                     node_name: Some(node_name.clone()),
                     node_discovery_enabled: Some(*discovery_enabled),
+                    default_shell: None,
+                    git_proxy: None,
+                    git_offline_mode: None,
+                    git_hosting_tokens: None,
+                    terminal_keep_alive: None,
+                    terminal_prevent_sleep: None,
+                    terminal_shell_integration: None,
+                    push_relay_url: None,
+                    push_tokens: None,
+                    git_auto_fetch_interval_secs: None,
                     evolution_default_profiles: None,
                     workspace_todos: None,
                     keybindings: None,
                     editor_formatting_configs: None,
+                    device_id: None,
+                    device_name: None,
+                    reset_device_overrides: false,
                 },
             )
             .await;
@@ -28,7 +28,7 @@ pub async fn handle_project_message(
             .await?;
             return Ok(true);
         }
-        ClientMessage::ListWorkspaces { project } => {
+        ClientMessage::ListWorkspaces { project, .. } => {
             crate::server::handlers::send_read_via_http_required(
                 socket,
                 "list_workspaces",
@@ -50,6 +50,52 @@ pub async fn handle_project_message(
             .await?;
             return Ok(true);
         }
+        ClientMessage::ListWorkspaceTasks { project, workspace } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "list_workspace_tasks",
+                "/api/v1/projects/:project/workspaces/:workspace/tasks",
+                Some(project.clone()),
+                Some(workspace.clone()),
+            )
+            .await?;
+            return Ok(true);
+        }
+        ClientMessage::ListPorts { project, workspace } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "list_ports",
+                "/api/v1/projects/:project/workspaces/:workspace/ports",
+                Some(project.clone()),
+                Some(workspace.clone()),
+            )
+            .await?;
+            return Ok(true);
+        }
+        ClientMessage::ProcList { project, workspace } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "proc_list",
+                "/api/v1/projects/:project/workspaces/:workspace/procs",
+                Some(project.clone()),
+                Some(workspace.clone()),
+            )
+            .await?;
+            return Ok(true);
+        }
+        ClientMessage::ProcLogs {
+            project, workspace, ..
+        } => {
+            crate::server::handlers::send_read_via_http_required(
+                socket,
+                "proc_logs",
+                "/api/v1/projects/:project/workspaces/:workspace/procs/:name/logs",
+                Some(project.clone()),
+                Some(workspace.clone()),
+            )
+            .await?;
+            return Ok(true);
+        }
         ClientMessage::ListTemplates => {
             crate::server::handlers::send_read_via_http_required(
                 socket,
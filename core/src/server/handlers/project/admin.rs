@@ -4,14 +4,18 @@ use tracing::{info, warn};
 use crate::application::project::{list_projects_message, list_workspaces_message};
 use crate::application::project_admin::{
     create_workspace_message, delete_template_message, export_template_message,
-    import_project_message, import_template_message, list_templates_message,
-    project_commands_saved_ok, remove_project_message, remove_workspace_message,
-    save_project_commands_message, save_template_message,
+    import_project_from_url_message, import_project_message, import_template_message,
+    link_workspace_issue_message, list_templates_message, project_commands_saved_ok,
+    remove_project_message, remove_workspace_message, rename_workspace_message,
+    repair_workspace_message, save_project_commands_message, save_template_message,
+    set_project_default_branch_message, set_workspace_sparse_paths_message,
 };
 use crate::application::project_workspace::cleanup_workspace_before_remove;
+use crate::application::workspace_manifest::apply_workspace_manifest;
 use crate::server::context::HandlerContext;
 use crate::server::protocol::{ClientMessage, ServerMessage};
 use crate::server::ws::send_message;
+use crate::workspace::project::CloneProgress;
 
 pub async fn handle_admin_message(
     client_msg: &ClientMessage,
@@ -34,16 +38,104 @@ pub async fn handle_admin_message(
             }
             Ok(true)
         }
+        ClientMessage::ImportProjectFromUrl {
+            name,
+            url,
+            branch,
+            depth,
+        } => {
+            info!("ImportProjectFromUrl request: name={}, url={}", name, url);
+            // 克隆可能持续数分钟，放到后台任务，避免阻塞该连接的其他请求
+            let ctx = ctx.clone();
+            let socket = socket.clone();
+            let (name, url, branch, depth) = (name.clone(), url.clone(), branch.clone(), *depth);
+            crate::util::trace::spawn(async move {
+                // 克隆登记为可取消的 git 操作，op_id 随进度推送给客户端
+                let operation = crate::server::git::begin_operation(
+                    "clone",
+                    &name,
+                    crate::workspace::state::DEFAULT_WORKSPACE_NAME,
+                );
+                let op_id = operation.op_id().to_string();
+                let progress_op_id = op_id.clone();
+                let progress_socket = socket.clone();
+                let progress_name = name.clone();
+                let mut last: Option<(String, Option<u8>)> = None;
+                let on_progress = move |progress: CloneProgress| {
+                    // 同一阶段的百分比不变时不重复推送
+                    let key = (progress.phase.clone(), progress.percent);
+                    if last.as_ref() == Some(&key) {
+                        return;
+                    }
+                    last = Some(key);
+                    let _ = progress_socket.blocking_send(ServerMessage::ProjectCloneProgress {
+                        name: progress_name.clone(),
+                        op_id: progress_op_id.clone(),
+                        phase: progress.phase,
+                        percent: progress.percent,
+                        message: progress.message,
+                    });
+                };
+                let msg = crate::util::cancel::scope(
+                    op_id,
+                    import_project_from_url_message(
+                        &ctx.app_state,
+                        &name,
+                        &url,
+                        branch.as_deref(),
+                        depth,
+                        on_progress,
+                    ),
+                )
+                .await;
+                drop(operation);
+                let success = matches!(msg, ServerMessage::ProjectImported { .. });
+                if success {
+                    info!("Project cloned and imported: {}", name);
+                } else {
+                    warn!("Failed to import project from url: {}", name);
+                }
+                if send_message(&socket, &msg).await.is_err() {
+                    warn!("Connection closed before clone finished: {}", name);
+                }
+                if success {
+                    let _ = ctx.save_tx.send(()).await;
+                    broadcast_projects_snapshot(&ctx).await;
+                    broadcast_workspaces_snapshot(&ctx, &name).await;
+                }
+            });
+            Ok(true)
+        }
         ClientMessage::CreateWorkspace {
             project,
             from_branch,
             template_id,
+            sparse_paths,
         } => {
+            let mut last: Option<(String, Option<u8>)> = None;
+            let on_progress = |workspace: &str, progress: CloneProgress| {
+                // 同一阶段的百分比不变时不重复推送
+                let key = (progress.phase.clone(), progress.percent);
+                if last.as_ref() == Some(&key) {
+                    return;
+                }
+                last = Some(key);
+                // 在异步上下文中同步执行，不能阻塞等待发送队列
+                let _ = socket.try_send(ServerMessage::WorkspaceSetupProgress {
+                    project: project.clone(),
+                    workspace: workspace.to_string(),
+                    phase: progress.phase,
+                    percent: progress.percent,
+                    message: progress.message,
+                });
+            };
             let msg = create_workspace_message(
                 &ctx.app_state,
                 project,
                 from_branch.as_deref(),
                 template_id.as_deref(),
+                sparse_paths,
+                on_progress,
             )
             .await;
             let success = matches!(msg, ServerMessage::WorkspaceCreated { .. });
@@ -55,6 +147,24 @@ pub async fn handle_admin_message(
             }
             Ok(true)
         }
+        ClientMessage::ApplyWorkspaceManifest { project, manifest } => {
+            info!("ApplyWorkspaceManifest request: project={}", project);
+            let outcome = apply_workspace_manifest(ctx, project, manifest).await;
+            send_message(socket, &outcome.response).await?;
+            if outcome.workspaces_changed {
+                let _ = ctx.save_tx.send(()).await;
+                broadcast_projects_snapshot(ctx).await;
+                broadcast_workspaces_snapshot(ctx, project).await;
+            }
+            for message in outcome.proc_broadcasts {
+                let _ = crate::server::context::send_task_broadcast_message(
+                    &ctx.task_broadcast_tx,
+                    &ctx.conn_meta.conn_id,
+                    message,
+                );
+            }
+            Ok(true)
+        }
         ClientMessage::RemoveProject { name } => {
             info!("RemoveProject request: name={}", name);
             let msg = remove_project_message(&ctx.app_state, name).await;
@@ -72,6 +182,7 @@ pub async fn handle_admin_message(
             }
             send_message(socket, &msg).await?;
             if matches!(msg, ServerMessage::ProjectRemoved { ok: true, .. }) {
+                crate::server::proc_supervisor::stop_project_procs(name);
                 let _ = ctx.save_tx.send(()).await;
                 broadcast_projects_snapshot(ctx).await;
             }
@@ -116,6 +227,147 @@ pub async fn handle_admin_message(
             }
             Ok(true)
         }
+        ClientMessage::RenameWorkspace {
+            project,
+            workspace,
+            new_name,
+            rename_branch,
+            move_worktree,
+        } => {
+            info!(
+                "RenameWorkspace request: project={}, workspace={}, new_name={}",
+                project, workspace, new_name
+            );
+
+            // 终端按 workspace 名登记，重命名后旧终端无法再路由，统一先关闭
+            let closed_terminals = cleanup_workspace_before_remove(ctx, project, workspace).await;
+            for tid in &closed_terminals {
+                info!(
+                    "Closed terminal {} for workspace {}/{}",
+                    tid, project, workspace
+                );
+            }
+
+            let msg = rename_workspace_message(
+                &ctx.app_state,
+                project,
+                workspace,
+                new_name,
+                *rename_branch,
+                *move_worktree,
+            )
+            .await;
+            if let ServerMessage::WorkspaceRenamed {
+                ok: false, message, ..
+            } = &msg
+            {
+                warn!(
+                    "Failed to rename workspace: {} / {}, error: {}",
+                    project,
+                    workspace,
+                    message.as_deref().unwrap_or("unknown")
+                );
+            }
+            let success = matches!(msg, ServerMessage::WorkspaceRenamed { ok: true, .. });
+            send_message(socket, &msg).await?;
+            if success {
+                let _ = ctx.save_tx.send(()).await;
+                // 其他客户端通过广播得知名称变化
+                let _ = crate::server::context::send_task_broadcast_message(
+                    &ctx.task_broadcast_tx,
+                    &ctx.conn_meta.conn_id,
+                    msg,
+                );
+                broadcast_projects_snapshot(ctx).await;
+                broadcast_workspaces_snapshot(ctx, project).await;
+            }
+            Ok(true)
+        }
+        ClientMessage::RepairWorkspace { project, workspace } => {
+            info!(
+                "RepairWorkspace request: project={}, workspace={}",
+                project, workspace
+            );
+            let msg = repair_workspace_message(&ctx.app_state, project, workspace).await;
+            if let ServerMessage::WorkspaceRepaired {
+                ok: false, message, ..
+            } = &msg
+            {
+                warn!(
+                    "Failed to repair workspace: {} / {}, error: {}",
+                    project,
+                    workspace,
+                    message.as_deref().unwrap_or("unknown")
+                );
+            }
+            let success = matches!(msg, ServerMessage::WorkspaceRepaired { ok: true, .. });
+            send_message(socket, &msg).await?;
+            if success {
+                let _ = ctx.save_tx.send(()).await;
+                broadcast_projects_snapshot(ctx).await;
+                broadcast_workspaces_snapshot(ctx, project).await;
+            }
+            Ok(true)
+        }
+        ClientMessage::LinkWorkspaceIssue {
+            project,
+            workspace,
+            url,
+        } => {
+            info!(
+                "LinkWorkspaceIssue request: project={}, workspace={}",
+                project, workspace
+            );
+            let msg =
+                link_workspace_issue_message(&ctx.app_state, project, workspace, url.as_deref())
+                    .await;
+            let success = matches!(msg, ServerMessage::WorkspaceIssueLinked { ok: true, .. });
+            send_message(socket, &msg).await?;
+            if success {
+                let _ = ctx.save_tx.send(()).await;
+                broadcast_workspaces_snapshot(ctx, project).await;
+            }
+            Ok(true)
+        }
+        ClientMessage::WorkspaceSetSparsePaths {
+            project,
+            workspace,
+            paths,
+        } => {
+            info!(
+                "WorkspaceSetSparsePaths request: project={}, workspace={}, paths={}",
+                project,
+                workspace,
+                paths.len()
+            );
+            let msg =
+                set_workspace_sparse_paths_message(&ctx.app_state, project, workspace, paths).await;
+            let success = matches!(msg, ServerMessage::WorkspaceSparsePathsSet { ok: true, .. });
+            send_message(socket, &msg).await?;
+            if success {
+                let _ = ctx.save_tx.send(()).await;
+                broadcast_workspaces_snapshot(ctx, project).await;
+            }
+            Ok(true)
+        }
+        ClientMessage::SetProjectDefaultBranch {
+            project,
+            default_branch,
+        } => {
+            info!(
+                "SetProjectDefaultBranch request: project={}, branch={}",
+                project, default_branch
+            );
+            let msg =
+                set_project_default_branch_message(&ctx.app_state, project, default_branch).await;
+            let success = matches!(msg, ServerMessage::ProjectDefaultBranchSet { ok: true, .. });
+            send_message(socket, &msg).await?;
+            if success {
+                let _ = ctx.save_tx.send(()).await;
+                broadcast_projects_snapshot(ctx).await;
+            }
+            Ok(true)
+        }
         ClientMessage::SaveProjectCommands { project, commands } => {
             info!("SaveProjectCommands request: project={}", project);
             let msg = save_project_commands_message(&ctx.app_state, project, commands).await;
@@ -1,6 +1,9 @@
 use crate::server::ws::OutboundTx as WebSocket;
 
-use crate::application::project::{list_projects_message, list_workspaces_message};
+use crate::application::project::{
+    list_ports_message, list_projects_message, list_workspace_tasks_message,
+    list_workspaces_filtered_message, WorkspaceListFilter,
+};
 use crate::application::task::list_tasks_snapshot_message;
 use crate::server::context::HandlerContext;
 use crate::server::protocol::ClientMessage;
@@ -15,8 +18,9 @@ pub(crate) async fn query_list_projects(
 pub(crate) async fn query_list_workspaces(
     ctx: &HandlerContext,
     project: &str,
+    filter: &WorkspaceListFilter,
 ) -> Result<crate::server::protocol::ServerMessage, crate::server::protocol::ServerMessage> {
-    list_workspaces_message(ctx, project).await
+    list_workspaces_filtered_message(ctx, project, filter).await
 }
 
 pub(crate) async fn query_list_tasks(
@@ -25,6 +29,47 @@ pub(crate) async fn query_list_tasks(
     list_tasks_snapshot_message(&ctx.task_history).await
 }
 
+pub(crate) async fn query_list_workspace_tasks(
+    ctx: &HandlerContext,
+    project: &str,
+    workspace: &str,
+) -> Result<crate::server::protocol::ServerMessage, crate::server::protocol::ServerMessage> {
+    let ws_ctx = crate::server::context::resolve_workspace(&ctx.app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_server_error())?;
+    Ok(list_workspace_tasks_message(ws_ctx.root_path, project, workspace).await)
+}
+
+pub(crate) async fn query_list_ports(
+    ctx: &HandlerContext,
+    project: &str,
+    workspace: &str,
+) -> Result<crate::server::protocol::ServerMessage, crate::server::protocol::ServerMessage> {
+    let ws_ctx = crate::server::context::resolve_workspace(&ctx.app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_server_error())?;
+    Ok(list_ports_message(ws_ctx.root_path, project, workspace).await)
+}
+
+pub(crate) async fn query_proc_list(
+    ctx: &HandlerContext,
+    project: &str,
+    workspace: &str,
+) -> Result<crate::server::protocol::ServerMessage, crate::server::protocol::ServerMessage> {
+    crate::application::proc::proc_list_message(&ctx.app_state, project, workspace).await
+}
+
+pub(crate) async fn query_proc_logs(
+    ctx: &HandlerContext,
+    project: &str,
+    workspace: &str,
+    name: &str,
+    lines: Option<usize>,
+) -> Result<crate::server::protocol::ServerMessage, crate::server::protocol::ServerMessage> {
+    crate::application::proc::proc_logs_message(&ctx.app_state, project, workspace, name, lines)
+        .await
+}
+
 pub async fn handle_query_message(
     client_msg: &ClientMessage,
     socket: &WebSocket,
@@ -36,8 +81,38 @@ pub async fn handle_query_message(
             send_message(socket, &msg).await?;
             Ok(true)
         }
-        ClientMessage::ListWorkspaces { project } => {
-            match query_list_workspaces(ctx, project).await {
+        ClientMessage::ListWorkspaces {
+            project,
+            name,
+            status,
+            branch_prefix,
+            sort,
+        } => {
+            let filter = match WorkspaceListFilter::from_params(
+                name.as_deref(),
+                status.as_deref(),
+                branch_prefix.as_deref(),
+                sort.as_deref(),
+            ) {
+                Ok(filter) => filter,
+                Err(message) => {
+                    send_message(
+                        socket,
+                        &crate::server::protocol::ServerMessage::Error {
+                            code: "invalid_request".to_string(),
+                            message,
+                            project: Some(project.clone()),
+                            workspace: None,
+                            session_id: None,
+                            cycle_id: None,
+                            trace_id: None,
+                        },
+                    )
+                    .await?;
+                    return Ok(true);
+                }
+            };
+            match query_list_workspaces(ctx, project, &filter).await {
                 Ok(msg) => send_message(socket, &msg).await?,
                 Err(err_msg) => send_message(socket, &err_msg).await?,
             }
@@ -48,6 +123,18 @@ pub async fn handle_query_message(
             send_message(socket, &msg).await?;
             Ok(true)
         }
+        ClientMessage::ListWorkspaceTasks { project, workspace } => {
+            match query_list_workspace_tasks(ctx, project, workspace).await {
+                Ok(msg) | Err(msg) => send_message(socket, &msg).await?,
+            }
+            Ok(true)
+        }
+        ClientMessage::ListPorts { project, workspace } => {
+            match query_list_ports(ctx, project, workspace).await {
+                Ok(msg) | Err(msg) => send_message(socket, &msg).await?,
+            }
+            Ok(true)
+        }
         _ => Ok(false),
     }
 }
@@ -1,6 +1,7 @@
 use crate::server::ws::OutboundTx as WebSocket;
 use tracing::info;
 
+use crate::application::proc::{start_proc, stop_proc};
 use crate::application::project_command::{cancel_project_command, run_project_command};
 use crate::application::project_workspace::select_workspace_and_spawn_terminal;
 use crate::server::context::HandlerContext;
@@ -75,6 +76,47 @@ pub async fn handle_runtime_message(
             }
             Ok(true)
         }
+        ClientMessage::ProcStart {
+            project,
+            workspace,
+            name,
+            command,
+        } => {
+            info!(
+                "ProcStart request: project={}, workspace={}, name={}",
+                project, workspace, name
+            );
+            let reply = start_proc(ctx, project, workspace, name, command).await;
+            send_message(socket, &reply.response).await?;
+            if let Some(message) = reply.broadcast {
+                let _ = crate::server::context::send_task_broadcast_message(
+                    &ctx.task_broadcast_tx,
+                    &ctx.conn_meta.conn_id,
+                    message,
+                );
+            }
+            Ok(true)
+        }
+        ClientMessage::ProcStop {
+            project,
+            workspace,
+            name,
+        } => {
+            info!(
+                "ProcStop request: project={}, workspace={}, name={}",
+                project, workspace, name
+            );
+            let reply = stop_proc(project, workspace, name);
+            send_message(socket, &reply.response).await?;
+            if let Some(message) = reply.broadcast {
+                let _ = crate::server::context::send_task_broadcast_message(
+                    &ctx.task_broadcast_tx,
+                    &ctx.conn_meta.conn_id,
+                    message,
+                );
+            }
+            Ok(true)
+        }
         _ => Ok(false),
     }
 }
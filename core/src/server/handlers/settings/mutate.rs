@@ -22,12 +22,86 @@ pub async fn handle_mutate_message(
             remote_access_enabled,
             node_name,
             node_discovery_enabled,
+            default_shell,
+            git_proxy,
+            git_offline_mode,
+            git_hosting_tokens,
+            terminal_keep_alive,
+            terminal_prevent_sleep,
+            terminal_shell_integration,
+            push_relay_url,
+            push_tokens,
+            git_auto_fetch_interval_secs,
             evolution_default_profiles,
             workspace_todos,
             keybindings,
             editor_formatting_configs,
+            device_id,
+            device_name,
+            reset_device_overrides,
         } => {
             info!("SaveClientSettings request");
+            if let Some(Some(shell)) = default_shell {
+                if !shell.trim().is_empty() {
+                    if let Err(message) = crate::pty::shell::resolve_shell_path(shell.trim()) {
+                        send_message(
+                            socket,
+                            &ServerMessage::ClientSettingsSaved {
+                                ok: false,
+                                message: Some(message),
+                            },
+                        )
+                        .await?;
+                        return Ok(true);
+                    }
+                }
+            }
+            if let Some(Some(proxy)) = git_proxy {
+                if !proxy.trim().is_empty() {
+                    if let Err(message) = crate::server::git::validate_proxy_url(proxy) {
+                        send_message(
+                            socket,
+                            &ServerMessage::ClientSettingsSaved {
+                                ok: false,
+                                message: Some(message),
+                            },
+                        )
+                        .await?;
+                        return Ok(true);
+                    }
+                }
+            }
+            if let Some(Some(url)) = push_relay_url {
+                if !url.trim().is_empty() {
+                    if let Err(message) = crate::server::push_relay::validate_relay_url(url.trim())
+                    {
+                        send_message(
+                            socket,
+                            &ServerMessage::ClientSettingsSaved {
+                                ok: false,
+                                message: Some(message),
+                            },
+                        )
+                        .await?;
+                        return Ok(true);
+                    }
+                }
+            }
+            if let Some(secs) = git_auto_fetch_interval_secs {
+                if let Err(message) =
+                    crate::server::git_fetch_scheduler::validate_fetch_interval(*secs)
+                {
+                    send_message(
+                        socket,
+                        &ServerMessage::ClientSettingsSaved {
+                            ok: false,
+                            message: Some(message),
+                        },
+                    )
+                    .await?;
+                    return Ok(true);
+                }
+            }
             save_client_settings(
                 &ctx.app_state,
                 SaveClientSettingsParams {
@@ -37,10 +111,23 @@ pub async fn handle_mutate_message(
                     remote_access_enabled: *remote_access_enabled,
                     node_name: node_name.clone(),
                     node_discovery_enabled: *node_discovery_enabled,
+                    default_shell: default_shell.clone(),
+                    git_proxy: git_proxy.clone(),
+                    git_offline_mode: *git_offline_mode,
+                    git_hosting_tokens: git_hosting_tokens.clone(),
+                    terminal_keep_alive: *terminal_keep_alive,
+                    terminal_prevent_sleep: *terminal_prevent_sleep,
+                    terminal_shell_integration: *terminal_shell_integration,
+                    push_relay_url: push_relay_url.clone(),
+                    push_tokens: push_tokens.clone(),
+                    git_auto_fetch_interval_secs: *git_auto_fetch_interval_secs,
                     evolution_default_profiles: evolution_default_profiles.clone(),
                     workspace_todos: workspace_todos.clone(),
                     keybindings: keybindings.clone(),
                     editor_formatting_configs: editor_formatting_configs.clone(),
+                    device_id: device_id.clone(),
+                    device_name: device_name.clone(),
+                    reset_device_overrides: *reset_device_overrides,
                 },
             )
             .await;
@@ -57,7 +144,8 @@ pub async fn handle_mutate_message(
             )
             .await?;
 
-            let snapshot = get_client_settings_message(&ctx.app_state).await;
+            // 广播共享视图；其他设备按 `device_profiles` 判断是否保留本机覆盖
+            let snapshot = get_client_settings_message(&ctx.app_state, None).await;
             let _ = crate::server::context::send_task_broadcast_message(
                 &ctx.task_broadcast_tx,
                 &ctx.conn_meta.conn_id,
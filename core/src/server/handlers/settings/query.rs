@@ -7,8 +7,9 @@ use crate::server::ws::send_message;
 
 pub(crate) async fn query_client_settings(
     ctx: &HandlerContext,
+    device_id: Option<&str>,
 ) -> crate::server::protocol::ServerMessage {
-    get_client_settings_message(&ctx.app_state).await
+    get_client_settings_message(&ctx.app_state, device_id).await
 }
 
 pub async fn handle_query_message(
@@ -18,7 +19,7 @@ pub async fn handle_query_message(
 ) -> Result<bool, String> {
     match client_msg {
         ClientMessage::GetClientSettings => {
-            let msg = query_client_settings(ctx).await;
+            let msg = query_client_settings(ctx, None).await;
             send_message(socket, &msg).await?;
             Ok(true)
         }
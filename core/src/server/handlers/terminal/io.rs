@@ -3,7 +3,7 @@ use tracing::debug;
 
 use crate::server::context::HandlerContext;
 use crate::server::protocol::{ClientMessage, ServerMessage};
-use crate::server::ws::{ack_terminal_output, send_message};
+use crate::server::ws::{ack_terminal_output, send_message, set_terminal_output_paused};
 
 pub async fn handle_io_message(
     client_msg: &ClientMessage,
@@ -41,6 +41,7 @@ pub async fn handle_io_message(
                         workspace: None,
                         session_id: None,
                         cycle_id: None,
+                        trace_id: None,
                     },
                 )
                 .await?;
@@ -67,6 +68,29 @@ pub async fn handle_io_message(
             ack_terminal_output(term_id, *bytes, &ctx.subscribed_terms).await;
             Ok(true)
         }
+        ClientMessage::TermFlowControl { term_id, paused } => {
+            debug!("TermFlowControl: term_id={}, paused={}", term_id, paused);
+            let msg = match set_terminal_output_paused(term_id, *paused, &ctx.subscribed_terms)
+                .await
+            {
+                Some(dropped_bytes) => ServerMessage::TermFlowState {
+                    term_id: term_id.clone(),
+                    paused: *paused,
+                    dropped_bytes,
+                },
+                None => ServerMessage::Error {
+                    code: "term_not_found".to_string(),
+                    message: format!("Terminal '{}' is not attached on this connection", term_id),
+                    project: None,
+                    workspace: None,
+                    session_id: None,
+                    cycle_id: None,
+                    trace_id: None,
+                },
+            };
+            send_message(socket, &msg).await?;
+            Ok(true)
+        }
         _ => Ok(false),
     }
 }
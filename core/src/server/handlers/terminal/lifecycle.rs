@@ -2,6 +2,8 @@ use crate::server::ws::OutboundTx as WebSocket;
 use std::path::PathBuf;
 use tracing::{debug, info};
 
+use crate::application::terminal::{apply_terminal_keep_alive, normalize_terminal_name};
+use crate::pty::shell::{resolve_shell, ShellSpec};
 use crate::server::context::HandlerContext;
 use crate::server::protocol::{ClientMessage, ServerMessage};
 use crate::server::terminal_registry::ATTACH_REPLAY_LIMIT_BYTES;
@@ -13,7 +15,7 @@ pub async fn handle_lifecycle_message(
     ctx: &HandlerContext,
 ) -> Result<bool, String> {
     match client_msg {
-        ClientMessage::SpawnTerminal { cwd } => {
+        ClientMessage::SpawnTerminal { cwd, shell, args } => {
             let cwd_path = PathBuf::from(&cwd);
             if !cwd_path.exists() {
                 send_message(
@@ -25,11 +27,19 @@ pub async fn handle_lifecycle_message(
                         workspace: None,
                         session_id: None,
                         cycle_id: None,
+                        trace_id: None,
                     },
                 )
                 .await?;
                 return Ok(true);
             }
+            let shell_spec = match resolve_terminal_shell(ctx, shell, args).await {
+                Ok(spec) => spec,
+                Err(e) => {
+                    send_message(socket, &e).await?;
+                    return Ok(true);
+                }
+            };
 
             let (session_id, shell_name) = {
                 let mut reg = ctx.terminal_registry.lock().await;
@@ -42,15 +52,19 @@ pub async fn handle_lifecycle_message(
                     None,
                     None,
                     None,
+                    shell_spec,
                 )
                 .map_err(|e| format!("Spawn error: {}", e))?
             };
+            apply_terminal_keep_alive(&ctx.app_state, &ctx.terminal_registry, &session_id, None)
+                .await;
 
             subscribe_terminal(
                 &session_id,
                 &ctx.terminal_registry,
                 &ctx.subscribed_terms,
                 &ctx.agg_tx,
+                &ctx.cmd_output_tx,
             )
             .await;
 
@@ -114,12 +128,23 @@ pub async fn handle_lifecycle_message(
             rows,
             name,
             icon,
+            shell,
+            args,
+            keep_alive,
         } => {
             info!(
                 project = %project,
                 workspace = %workspace,
+                shell = ?shell,
                 "TermCreate request received"
             );
+            let shell_spec = match resolve_terminal_shell(ctx, shell, args).await {
+                Ok(spec) => spec,
+                Err(e) => {
+                    send_message(socket, &e).await?;
+                    return Ok(true);
+                }
+            };
 
             match crate::server::context::resolve_workspace(&ctx.app_state, project, workspace)
                 .await
@@ -136,15 +161,24 @@ pub async fn handle_lifecycle_message(
                             *rows,
                             name.clone(),
                             icon.clone(),
+                            shell_spec,
                         )
                         .map_err(|e| format!("Spawn error: {}", e))?
                     };
+                    apply_terminal_keep_alive(
+                        &ctx.app_state,
+                        &ctx.terminal_registry,
+                        &term_id,
+                        *keep_alive,
+                    )
+                    .await;
 
                     subscribe_terminal(
                         &term_id,
                         &ctx.terminal_registry,
                         &ctx.subscribed_terms,
                         &ctx.agg_tx,
+                        &ctx.cmd_output_tx,
                     )
                     .await;
 
@@ -241,12 +275,128 @@ pub async fn handle_lifecycle_message(
                         workspace: None,
                         session_id: None,
                         cycle_id: None,
+                        trace_id: None,
                     },
                 )
                 .await?;
             }
             Ok(true)
         }
+        ClientMessage::TermSetKeepAlive {
+            term_id,
+            keep_alive,
+        } => {
+            let updated = apply_terminal_keep_alive(
+                &ctx.app_state,
+                &ctx.terminal_registry,
+                term_id,
+                Some(*keep_alive),
+            )
+            .await;
+            match updated {
+                Some((keep_alive, prevent_sleep)) => {
+                    info!(
+                        term_id = %term_id,
+                        keep_alive,
+                        prevent_sleep,
+                        "Terminal keep-alive updated"
+                    );
+                    let msg = ServerMessage::TermKeepAliveUpdated {
+                        term_id: term_id.clone(),
+                        keep_alive,
+                        prevent_sleep,
+                    };
+                    send_message(socket, &msg).await?;
+                    let _ = crate::server::context::send_task_broadcast_message(
+                        &ctx.task_broadcast_tx,
+                        &ctx.conn_meta.conn_id,
+                        msg,
+                    );
+                }
+                None => {
+                    send_message(
+                        socket,
+                        &ServerMessage::Error {
+                            code: "term_not_found".to_string(),
+                            message: format!("Terminal '{}' not found", term_id),
+                            project: None,
+                            workspace: None,
+                            session_id: None,
+                            cycle_id: None,
+                            trace_id: None,
+                        },
+                    )
+                    .await?;
+                }
+            }
+            Ok(true)
+        }
+        ClientMessage::TermRename { term_id, name } => {
+            let name = match normalize_terminal_name(name) {
+                Ok(name) => name,
+                Err(message) => {
+                    send_message(
+                        socket,
+                        &ServerMessage::Error {
+                            code: "invalid_term_name".to_string(),
+                            message,
+                            project: None,
+                            workspace: None,
+                            session_id: None,
+                            cycle_id: None,
+                            trace_id: None,
+                        },
+                    )
+                    .await?;
+                    return Ok(true);
+                }
+            };
+            let renamed = ctx
+                .terminal_registry
+                .lock()
+                .await
+                .rename(term_id, name.clone());
+            if renamed {
+                info!(term_id = %term_id, name = ?name, "Terminal renamed");
+                let msg = ServerMessage::TermRenamed {
+                    term_id: term_id.clone(),
+                    name,
+                };
+                send_message(socket, &msg).await?;
+                let _ = crate::server::context::send_task_broadcast_message(
+                    &ctx.task_broadcast_tx,
+                    &ctx.conn_meta.conn_id,
+                    msg,
+                );
+            } else {
+                send_message(
+                    socket,
+                    &ServerMessage::Error {
+                        code: "term_not_found".to_string(),
+                        message: format!("Terminal '{}' not found", term_id),
+                        project: None,
+                        workspace: None,
+                        session_id: None,
+                        cycle_id: None,
+                        trace_id: None,
+                    },
+                )
+                .await?;
+            }
+            Ok(true)
+        }
+        ClientMessage::TermReorder { term_ids } => {
+            let term_ids = ctx.terminal_registry.lock().await.reorder(term_ids);
+            debug!(count = term_ids.len(), "Terminal order updated");
+            let msg = ServerMessage::TermReordered { term_ids };
+            send_message(socket, &msg).await?;
+            let _ = crate::server::context::send_task_broadcast_message(
+                &ctx.task_broadcast_tx,
+                &ctx.conn_meta.conn_id,
+                msg,
+            );
+            Ok(true)
+        }
         ClientMessage::TermFocus { term_id } => {
             tracing::debug!(
                 term_id = %term_id,
@@ -276,6 +426,7 @@ pub async fn handle_lifecycle_message(
                     &ctx.terminal_registry,
                     &ctx.subscribed_terms,
                     &ctx.agg_tx,
+                    &ctx.cmd_output_tx,
                 )
                 .await;
 
@@ -319,6 +470,7 @@ pub async fn handle_lifecycle_message(
                         workspace: None,
                         session_id: None,
                         cycle_id: None,
+                        trace_id: None,
                     },
                 )
                 .await?;
@@ -341,3 +493,33 @@ pub async fn handle_lifecycle_message(
         _ => Ok(false),
     }
 }
+
+/// 按请求与服务端默认 shell 设置解析终端程序；不在白名单内时返回 `invalid_shell` 错误。
+/// 开启 `terminal_shell_integration` 时注入 shell 集成脚本
+async fn resolve_terminal_shell(
+    ctx: &HandlerContext,
+    shell: &Option<String>,
+    args: &Option<Vec<String>>,
+) -> Result<ShellSpec, ServerMessage> {
+    let (default_shell, shell_integration) = {
+        let state = ctx.app_state.read().await;
+        (
+            state.client_settings.default_shell.clone(),
+            state.client_settings.terminal_shell_integration,
+        )
+    };
+    let mut spec = resolve_shell(shell.as_deref(), args.as_deref(), default_shell.as_deref())
+        .map_err(|message| ServerMessage::Error {
+            code: "invalid_shell".to_string(),
+            message,
+            project: None,
+            workspace: None,
+            session_id: None,
+            cycle_id: None,
+            trace_id: None,
+        })?;
+    if shell_integration {
+        crate::pty::shell_integration::inject(&mut spec);
+    }
+    Ok(spec)
+}
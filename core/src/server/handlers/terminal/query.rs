@@ -3,7 +3,7 @@ use tracing::info;
 
 use crate::application::terminal as terminal_app;
 use crate::server::context::{ConnectionMeta, HandlerContext};
-use crate::server::protocol::ClientMessage;
+use crate::server::protocol::{ClientMessage, ServerMessage};
 use crate::server::ws::send_message;
 
 pub(crate) async fn query_term_list(
@@ -35,6 +35,26 @@ pub async fn handle_query_message(
             send_message(socket, &msg).await?;
             Ok(true)
         }
+        ClientMessage::TermCommandHistory { term_id } => {
+            let history = ctx.terminal_registry.lock().await.command_history(term_id);
+            let msg = match history {
+                Some(commands) => ServerMessage::TermCommandHistoryResult {
+                    term_id: term_id.clone(),
+                    commands,
+                },
+                None => ServerMessage::Error {
+                    code: "term_not_found".to_string(),
+                    message: format!("Terminal '{}' not found", term_id),
+                    project: None,
+                    workspace: None,
+                    session_id: None,
+                    cycle_id: None,
+                    trace_id: None,
+                },
+            };
+            send_message(socket, &msg).await?;
+            Ok(true)
+        }
         _ => Ok(false),
     }
 }
@@ -13,7 +13,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, RwLock};
 use tracing::warn;
 
 use crate::server::context::SharedAppState;
@@ -23,6 +23,8 @@ use crate::server::protocol::health::{
     SystemHealthStatus,
 };
 use crate::server::terminal_registry::SharedTerminalRegistry;
+use crate::workspace::state::ProjectLoadStatus;
+use crate::workspace::state_store::StateStore;
 
 // ============================================================================
 // 时间工具
@@ -374,6 +376,13 @@ pub fn register_builtin_probes(
         Box::new(move || probe_workspace_recovery(&state_clone2)),
     );
 
+    // 项目加载失败探针（损坏数据已隔离，可通过 repair_project 修复）
+    let state_clone3 = app_state.clone();
+    reg.register_probe(
+        "core.project_load",
+        Box::new(move || probe_project_load(&state_clone3)),
+    );
+
     // 终端注册表资源压力探针
     reg.register_probe("core.terminal_budget", Box::new(probe_terminal_budget));
 
@@ -497,6 +506,52 @@ fn probe_workspace_recovery(app_state: &SharedAppState) -> Vec<HealthIncident> {
     incidents
 }
 
+fn project_load_incident_id(project: &str) -> String {
+    format!("project_load_failed:{}", project)
+}
+
+/// 项目加载失败探针：每个 `Failed` 项目对应一条 incident，摘要包含恢复文件位置
+fn probe_project_load(app_state: &SharedAppState) -> Vec<HealthIncident> {
+    let state = match app_state.try_read() {
+        Ok(s) => s,
+        Err(_) => return Vec::new(),
+    };
+    let now = unix_ms();
+    let mut incidents = Vec::new();
+
+    for (project, status) in &state.project_load_status {
+        let ProjectLoadStatus::Failed {
+            error,
+            recovery_file,
+        } = status
+        else {
+            continue;
+        };
+        if !state.projects.contains_key(project) {
+            continue;
+        }
+        let recovery_hint = recovery_file
+            .as_deref()
+            .map(|path| format!("，原始数据已隔离到 {path}"))
+            .unwrap_or_default();
+        incidents.push(HealthIncident {
+            incident_id: project_load_incident_id(project),
+            severity: IncidentSeverity::Critical,
+            recoverability: IncidentRecoverability::Recoverable,
+            source: IncidentSource::CoreProcess,
+            root_cause: "project_load_failed".to_string(),
+            summary: Some(format!(
+                "项目 {} 加载失败：{}{}",
+                project, error, recovery_hint
+            )),
+            first_seen_at: now,
+            last_seen_at: now,
+            context: HealthContext::for_project(project),
+        });
+    }
+    incidents
+}
+
 /// 终端恢复状态探针：检查注册表中持有 Recovering 或 RecoveryFailed 状态的终端
 ///
 /// 每个 incident 携带对应 `(project, workspace)` 上下文，按工作区边界隔离。
@@ -562,9 +617,12 @@ pub async fn execute_repair(
     request: RepairActionRequest,
     trigger: &str,
     app_state: SharedAppState,
+    state_store: &StateStore,
+    save_tx: &mpsc::Sender<()>,
 ) -> RepairAuditEntry {
     let started_at = unix_ms();
-    let (outcome, result_summary, incident_resolved) = do_repair(&request, app_state.clone()).await;
+    let (outcome, result_summary, incident_resolved) =
+        do_repair(&request, app_state.clone(), state_store, save_tx).await;
     let duration_ms = unix_ms().saturating_sub(started_at);
 
     let audit = RepairAuditEntry {
@@ -601,6 +659,11 @@ pub async fn execute_repair(
                         reg.resolve_workspace_incidents(project, workspace);
                     }
                 }
+                if request.action == RepairActionKind::RepairProject {
+                    if let Some(project) = &request.context.project {
+                        reg.resolve_incident(&project_load_incident_id(project));
+                    }
+                }
             }
             reg.append_audit(audit.clone());
         };
@@ -612,6 +675,8 @@ pub async fn execute_repair(
 async fn do_repair(
     request: &RepairActionRequest,
     app_state: SharedAppState,
+    state_store: &StateStore,
+    save_tx: &mpsc::Sender<()>,
 ) -> (RepairOutcome, Option<String>, bool) {
     match &request.action {
         RepairActionKind::RefreshHealthSnapshot => {
@@ -664,9 +729,97 @@ async fn do_repair(
             // 订阅恢复：清理 remote_sub_registry 中的残留订阅
             restore_subscriptions(request, &app_state).await
         }
+
+        RepairActionKind::RepairProject => {
+            let Some(project) = &request.context.project else {
+                return (
+                    RepairOutcome::Failed,
+                    Some("缺少 project 上下文".to_string()),
+                    false,
+                );
+            };
+            repair_project(project, &app_state, state_store, save_tx).await
+        }
     }
 }
 
+async fn repair_project(
+    project: &str,
+    app_state: &SharedAppState,
+    state_store: &StateStore,
+    save_tx: &mpsc::Sender<()>,
+) -> (RepairOutcome, Option<String>, bool) {
+    {
+        let state = app_state.read().await;
+        let Some(entry) = state.get_project(project) else {
+            return (
+                RepairOutcome::Failed,
+                Some(format!("项目 {} 不存在", project)),
+                false,
+            );
+        };
+        if !matches!(
+            state.project_load_status(project),
+            ProjectLoadStatus::Failed { .. }
+        ) {
+            return (
+                RepairOutcome::AlreadyHealthy,
+                Some(format!("项目 {} 已正常加载", project)),
+                true,
+            );
+        }
+        if entry.root_path.as_os_str().is_empty() {
+            return (
+                RepairOutcome::Failed,
+                Some(format!("项目 {} 缺少根路径，请移除后重新导入", project)),
+                false,
+            );
+        }
+    }
+
+    let (workspaces, dropped) = match state_store.salvage_project_workspaces(project).await {
+        Ok(result) => result,
+        Err(e) => {
+            return (
+                RepairOutcome::Failed,
+                Some(format!("读取项目 {} 失败：{}", project, e)),
+                false,
+            )
+        }
+    };
+    let kept = workspaces.len();
+    {
+        let mut state = app_state.write().await;
+        let Some(entry) = state.get_project_mut(project) else {
+            return (
+                RepairOutcome::Failed,
+                Some(format!("项目 {} 不存在", project)),
+                false,
+            );
+        };
+        for (name, workspace) in workspaces {
+            entry.workspaces.entry(name).or_insert(workspace);
+        }
+        // 标记为已加载后，下一次保存会用内存数据覆盖该项目的 workspace 行
+        state.project_load_status.remove(project);
+    }
+    let _ = save_tx.send(()).await;
+    tracing::info!(
+        project,
+        kept,
+        dropped,
+        "health repair: project restored from quarantine"
+    );
+    (
+        RepairOutcome::Success,
+        Some(format!(
+            "项目 {} 已恢复：保留 {} 个工作区，丢弃 {} 条损坏记录",
+            project, kept, dropped
+        )),
+        true,
+    )
+}
+
 async fn invalidate_workspace_cache(
     project: &str,
     workspace: &str,
@@ -1289,4 +1442,59 @@ mod tests {
         let mut reg = registry.blocking_write();
         reg.active_incidents.remove(incident_id);
     }
+
+    #[tokio::test]
+    async fn repair_project_clears_failed_load_status() {
+        use crate::workspace::state::{AppState, Project};
+
+        let store = StateStore::open_in_memory_for_test()
+            .await
+            .expect("in-memory store should initialize");
+        let mut state = AppState::default();
+        state.add_project(Project {
+            name: "broken".to_string(),
+            root_path: "/tmp/broken".into(),
+            remote_url: None,
+            default_branch: "main".to_string(),
+            created_at: chrono::Utc::now(),
+            workspaces: HashMap::new(),
+            commands: vec![],
+        });
+        state.project_load_status.insert(
+            "broken".to_string(),
+            ProjectLoadStatus::Failed {
+                error: "bad workspace row".to_string(),
+                recovery_file: Some("/tmp/recovery/broken.json".to_string()),
+            },
+        );
+        let shared: SharedAppState = Arc::new(RwLock::new(state));
+
+        let incidents = probe_project_load(&shared);
+        assert_eq!(incidents.len(), 1);
+        assert_eq!(incidents[0].incident_id, "project_load_failed:broken");
+        assert!(incidents[0]
+            .summary
+            .as_deref()
+            .is_some_and(|s| s.contains("/tmp/recovery/broken.json")));
+
+        let (save_tx, mut save_rx) = mpsc::channel(1);
+        let request = RepairActionRequest {
+            request_id: "repair-broken".to_string(),
+            action: RepairActionKind::RepairProject,
+            context: HealthContext::for_project("broken"),
+            incident_id: None,
+        };
+        let (outcome, _, resolved) = do_repair(&request, shared.clone(), &store, &save_tx).await;
+        assert_eq!(outcome, RepairOutcome::Success);
+        assert!(resolved);
+        assert!(save_rx.try_recv().is_ok(), "修复后应触发保存");
+        assert_eq!(
+            shared.read().await.project_load_status("broken"),
+            ProjectLoadStatus::Loaded
+        );
+        assert!(probe_project_load(&shared).is_empty());
+
+        let (outcome, _, _) = do_repair(&request, shared.clone(), &store, &save_tx).await;
+        assert_eq!(outcome, RepairOutcome::AlreadyHealthy);
+    }
 }
@@ -0,0 +1,319 @@
+//! 语法高亮服务
+//!
+//! 基于 syntect 内置语法与主题，把文件内容转换为按行的样式区间（或 HTML 片段），
+//! 让客户端无需打包语法定义即可渲染高亮代码。
+//! 结果按（内容哈希、语法、主题、格式）缓存，同一内容重复打开时不再重新解析。
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::sync::{Arc, LazyLock, Mutex};
+
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Color, FontStyle, Style, Theme, ThemeSet};
+use syntect::html::{append_highlighted_html_for_styled_line, IncludeBackground};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+use crate::server::protocol::file::{FileHighlightSpan, FileHighlightStyle};
+
+/// 未指定主题时使用的默认主题
+pub const DEFAULT_HIGHLIGHT_THEME: &str = "base16-ocean.dark";
+
+/// 高亮结果缓存条数上限（超出后淘汰最早写入的条目）
+pub const HIGHLIGHT_CACHE_CAPACITY: usize = 64;
+
+/// 超过该字符数的行不做语法解析，整行按默认样式输出（避免压缩文件拖慢解析）
+pub const MAX_HIGHLIGHT_LINE_CHARS: usize = 10_000;
+
+static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+static HIGHLIGHT_CACHE: LazyLock<Mutex<HighlightCache>> =
+    LazyLock::new(|| Mutex::new(HighlightCache::default()));
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HighlightFormat {
+    /// 样式表 + 按行样式区间
+    Spans,
+    /// 带内联样式的 `<pre>` HTML 片段
+    Html,
+}
+
+impl HighlightFormat {
+    pub fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value.map(str::trim).filter(|v| !v.is_empty()) {
+            None | Some("spans") => Ok(Self::Spans),
+            Some("html") => Ok(Self::Html),
+            Some(other) => Err(format!(
+                "Unsupported highlight format: {} (expected spans or html)",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum HighlightError {
+    #[error("Unknown theme: {0} (available: {1})")]
+    UnknownTheme(String, String),
+    #[error("Highlight failed: {0}")]
+    Failed(String),
+}
+
+/// 单个文件的高亮结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighlightOutput {
+    /// 识别出的语法名称（无法识别时为 "Plain Text"）
+    pub syntax: String,
+    pub theme: String,
+    /// 主题背景色 / 默认前景色（`#rrggbb` 或 `#rrggbbaa`）
+    pub background: Option<String>,
+    pub foreground: Option<String>,
+    /// 去重后的样式表，`lines` 中的区间按下标引用
+    pub styles: Vec<FileHighlightStyle>,
+    pub lines: Vec<Vec<FileHighlightSpan>>,
+    pub html: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct HighlightCacheKey {
+    hash: String,
+    syntax: String,
+    theme: String,
+    format: HighlightFormat,
+}
+
+#[derive(Default)]
+struct HighlightCache {
+    entries: HashMap<HighlightCacheKey, Arc<HighlightOutput>>,
+    order: VecDeque<HighlightCacheKey>,
+}
+
+impl HighlightCache {
+    fn get(&self, key: &HighlightCacheKey) -> Option<Arc<HighlightOutput>> {
+        self.entries.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: HighlightCacheKey, output: Arc<HighlightOutput>) {
+        if self.entries.insert(key.clone(), output).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > HIGHLIGHT_CACHE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// 内置主题名称（按字典序）
+pub fn available_themes() -> Vec<String> {
+    THEME_SET.themes.keys().cloned().collect()
+}
+
+/// 高亮文件内容；返回结果与是否命中缓存
+///
+/// `hash` 为内容哈希（`file_api::content_hash`），与语法、主题、格式共同作为缓存键。
+pub fn highlight(
+    path: &str,
+    text: &str,
+    hash: &str,
+    theme: Option<&str>,
+    format: HighlightFormat,
+) -> Result<(Arc<HighlightOutput>, bool), HighlightError> {
+    let theme_name = theme
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .unwrap_or(DEFAULT_HIGHLIGHT_THEME);
+    let theme = THEME_SET.themes.get(theme_name).ok_or_else(|| {
+        HighlightError::UnknownTheme(theme_name.to_string(), available_themes().join(", "))
+    })?;
+    let syntax = syntax_for(path, text);
+
+    let key = HighlightCacheKey {
+        hash: hash.to_string(),
+        syntax: syntax.name.clone(),
+        theme: theme_name.to_string(),
+        format,
+    };
+    if let Some(output) = HIGHLIGHT_CACHE.lock().ok().and_then(|c| c.get(&key)) {
+        return Ok((output, true));
+    }
+
+    let output = Arc::new(render(text, syntax, theme, theme_name, format)?);
+    if let Ok(mut cache) = HIGHLIGHT_CACHE.lock() {
+        cache.insert(key, Arc::clone(&output));
+    }
+    Ok((output, false))
+}
+
+/// 按扩展名 / 完整文件名识别语法，失败时再看首行（shebang、modeline），最后回退为纯文本
+fn syntax_for(path: &str, text: &str) -> &'static SyntaxReference {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let extension = file_name.rsplit_once('.').map(|(_, ext)| ext);
+    extension
+        .and_then(|ext| SYNTAX_SET.find_syntax_by_extension(ext))
+        .or_else(|| SYNTAX_SET.find_syntax_by_extension(file_name))
+        .or_else(|| {
+            text.lines()
+                .next()
+                .and_then(|line| SYNTAX_SET.find_syntax_by_first_line(line))
+        })
+        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text())
+}
+
+fn render(
+    text: &str,
+    syntax: &SyntaxReference,
+    theme: &Theme,
+    theme_name: &str,
+    format: HighlightFormat,
+) -> Result<HighlightOutput, HighlightError> {
+    let default_style = Style {
+        foreground: theme.settings.foreground.unwrap_or(Color::BLACK),
+        background: theme.settings.background.unwrap_or(Color::WHITE),
+        font_style: FontStyle::empty(),
+    };
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    let mut styles: Vec<FileHighlightStyle> = Vec::new();
+    let mut style_index: HashMap<Style, u32> = HashMap::new();
+    let mut lines = Vec::new();
+    let mut html = String::new();
+    if format == HighlightFormat::Html {
+        let background = theme.settings.background.unwrap_or(Color::WHITE);
+        let _ = writeln!(
+            html,
+            "<pre style=\"background-color:{};\">",
+            hex(background)
+        );
+    }
+
+    for line in LinesWithEndings::from(text) {
+        let ranges: Vec<(Style, &str)> = if line.chars().count() > MAX_HIGHLIGHT_LINE_CHARS {
+            vec![(default_style, line)]
+        } else {
+            highlighter
+                .highlight_line(line, &SYNTAX_SET)
+                .map_err(|e| HighlightError::Failed(e.to_string()))?
+        };
+        match format {
+            HighlightFormat::Spans => {
+                let mut spans: Vec<FileHighlightSpan> = Vec::with_capacity(ranges.len());
+                let mut start = 0u32;
+                for (style, piece) in ranges {
+                    let piece = piece.trim_end_matches(['\n', '\r']);
+                    let length = piece.chars().count() as u32;
+                    if length == 0 {
+                        continue;
+                    }
+                    let index = *style_index.entry(style).or_insert_with(|| {
+                        styles.push(to_protocol_style(style));
+                        styles.len() as u32 - 1
+                    });
+                    // 相邻同样式区间合并，减小传输体积
+                    match spans.last_mut() {
+                        Some(last) if last.style == index => last.length += length,
+                        _ => spans.push(FileHighlightSpan {
+                            start,
+                            length,
+                            style: index,
+                        }),
+                    }
+                    start += length;
+                }
+                lines.push(spans);
+            }
+            HighlightFormat::Html => {
+                append_highlighted_html_for_styled_line(&ranges, IncludeBackground::No, &mut html)
+                    .map_err(|e| HighlightError::Failed(e.to_string()))?;
+            }
+        }
+    }
+    if format == HighlightFormat::Html {
+        html.push_str("</pre>\n");
+    }
+
+    Ok(HighlightOutput {
+        syntax: syntax.name.clone(),
+        theme: theme_name.to_string(),
+        background: theme.settings.background.map(hex),
+        foreground: theme.settings.foreground.map(hex),
+        styles,
+        lines,
+        html: (format == HighlightFormat::Html).then_some(html),
+    })
+}
+
+fn to_protocol_style(style: Style) -> FileHighlightStyle {
+    FileHighlightStyle {
+        foreground: hex(style.foreground),
+        bold: style.font_style.contains(FontStyle::BOLD),
+        italic: style.font_style.contains(FontStyle::ITALIC),
+        underline: style.font_style.contains(FontStyle::UNDERLINE),
+    }
+}
+
+fn hex(color: Color) -> String {
+    if color.a == 0xff {
+        format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+    } else {
+        format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            color.r, color.g, color.b, color.a
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlights_rust_into_merged_spans_and_caches_by_hash() {
+        let text = "fn main() {\n    let x = 1;\n}\n";
+        let (output, cached) =
+            highlight("src/main.rs", text, "hash-a", None, HighlightFormat::Spans).unwrap();
+        assert!(!cached);
+        assert_eq!(output.syntax, "Rust");
+        assert_eq!(output.theme, DEFAULT_HIGHLIGHT_THEME);
+        assert_eq!(output.lines.len(), 3);
+        // 每行区间首尾相接并覆盖整行（不含换行符）
+        for (spans, line) in output.lines.iter().zip(text.lines()) {
+            let covered: u32 = spans.iter().map(|s| s.length).sum();
+            assert_eq!(covered as usize, line.chars().count());
+            assert!(spans.windows(2).all(|w| w[0].style != w[1].style));
+        }
+        // `fn` 关键字与标识符 `main` 样式不同
+        let first = &output.lines[0];
+        assert_eq!((first[0].start, first[0].length), (0, 2));
+        assert!(output.styles.len() > 2);
+
+        let (again, cached) =
+            highlight("src/main.rs", text, "hash-a", None, HighlightFormat::Spans).unwrap();
+        assert!(cached);
+        assert!(Arc::ptr_eq(&output, &again));
+    }
+
+    #[test]
+    fn renders_html_and_rejects_unknown_theme() {
+        let (output, _) = highlight(
+            "script",
+            "#!/bin/bash\necho <hi>\n",
+            "hash-b",
+            Some("InspiredGitHub"),
+            HighlightFormat::Html,
+        )
+        .unwrap();
+        assert!(output.syntax.contains("bash"));
+        let html = output.html.as_deref().unwrap();
+        assert!(html.starts_with("<pre style=\"background-color:#ffffff;\">"));
+        assert!(html.contains("&lt;") && !html.contains("<hi>"));
+        assert!(output.lines.is_empty());
+
+        assert!(matches!(
+            highlight("a.rs", "", "hash-c", Some("nope"), HighlightFormat::Spans),
+            Err(HighlightError::UnknownTheme(..))
+        ));
+        assert!(HighlightFormat::parse(Some("svg")).is_err());
+    }
+}
@@ -0,0 +1,324 @@
+//! `.gitignore` / `.ignore` 规则
+//!
+//! 文件索引、内容搜索与目录列表共用同一套忽略判定，尽量与 git 保持一致：
+//! - 规则来自 `.git/info/exclude` 以及各级目录下的 `.gitignore`、`.ignore`；
+//! - 越深的忽略文件优先，同一文件内越靠后的规则优先，`!` 开头取反；
+//! - 以 `/` 结尾的模式只匹配目录；含 `/` 的模式相对所在目录锚定，否则匹配任意层级的名称；
+//! - [`DEFAULT_IGNORE_DIRS`] 作为优先级最低的内置规则，仓库可用 `!build/` 之类的规则重新收录。
+//!
+//! 目录被忽略后其下的文件无法再被取反规则收录（git 同样如此），遍历时直接跳过整棵子树。
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use regex::Regex;
+
+use super::file_index::DEFAULT_IGNORE_DIRS;
+
+/// 各级目录中读取的忽略文件名
+pub const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore"];
+
+#[derive(Debug)]
+struct IgnorePattern {
+    regex: Regex,
+    negated: bool,
+    dir_only: bool,
+}
+
+#[derive(Debug)]
+struct IgnoreLayer {
+    /// 规则所在目录（相对工作区根，根目录为空串）
+    base: String,
+    patterns: Vec<IgnorePattern>,
+}
+
+/// 从工作区根到某个目录累积的忽略规则，子目录共享上级的规则层
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    layers: Vec<Arc<IgnoreLayer>>,
+}
+
+impl IgnoreRules {
+    /// 工作区根目录的规则：内置目录、`.git/info/exclude` 与根目录下的忽略文件
+    pub fn for_root(root: &Path) -> Self {
+        let mut rules = Self::default();
+        let defaults = DEFAULT_IGNORE_DIRS
+            .iter()
+            .filter_map(|name| parse_line(&format!("{}/", name)))
+            .collect();
+        rules.push_layer(String::new(), defaults);
+        if let Some(content) =
+            info_exclude_path(root).and_then(|path| std::fs::read_to_string(path).ok())
+        {
+            rules.push_layer(String::new(), parse_ignore_file(&content));
+        }
+        rules.descend(root, "")
+    }
+
+    /// 从根逐级加载到 `rel_dir`（含）为止的规则，不检查途经目录本身是否被忽略
+    pub fn for_dir(root: &Path, rel_dir: &str) -> Self {
+        let mut rules = Self::for_root(root);
+        let mut current = String::new();
+        for segment in rel_dir.split('/').filter(|s| !s.is_empty()) {
+            if !current.is_empty() {
+                current.push('/');
+            }
+            current.push_str(segment);
+            rules = rules.descend(&root.join(&current), &current);
+        }
+        rules
+    }
+
+    /// 进入子目录 `dir`（`rel_dir` 为其相对工作区根的路径）时追加该目录下忽略文件的规则
+    pub fn descend(&self, dir: &Path, rel_dir: &str) -> Self {
+        let patterns: Vec<IgnorePattern> = IGNORE_FILES
+            .iter()
+            .filter_map(|name| std::fs::read_to_string(dir.join(name)).ok())
+            .flat_map(|content| parse_ignore_file(&content))
+            .collect();
+        let mut rules = self.clone();
+        rules.push_layer(rel_dir.to_string(), patterns);
+        rules
+    }
+
+    fn push_layer(&mut self, base: String, patterns: Vec<IgnorePattern>) {
+        if !patterns.is_empty() {
+            self.layers.push(Arc::new(IgnoreLayer { base, patterns }));
+        }
+    }
+
+    /// 判断相对工作区根的路径本身是否命中忽略规则（不检查上级目录）
+    pub fn is_ignored(&self, rel_path: &str, is_dir: bool) -> bool {
+        for layer in self.layers.iter().rev() {
+            let local = if layer.base.is_empty() {
+                rel_path
+            } else {
+                match rel_path
+                    .strip_prefix(layer.base.as_str())
+                    .and_then(|rest| rest.strip_prefix('/'))
+                {
+                    Some(rest) => rest,
+                    None => continue,
+                }
+            };
+            for pattern in layer.patterns.iter().rev() {
+                if pattern.dir_only && !is_dir {
+                    continue;
+                }
+                if pattern.regex.is_match(local) {
+                    return !pattern.negated;
+                }
+            }
+        }
+        false
+    }
+}
+
+/// 判断路径或其任一上级目录是否被忽略，供不经遍历的单路径判定（如 watcher 事件）使用
+pub fn is_path_ignored(root: &Path, rel_path: &str, is_dir: bool) -> bool {
+    let segments: Vec<&str> = rel_path.split('/').filter(|s| !s.is_empty()).collect();
+    let Some((last, parents)) = segments.split_last() else {
+        return false;
+    };
+    let mut rules = IgnoreRules::for_root(root);
+    let mut current = String::new();
+    for segment in parents {
+        if !current.is_empty() {
+            current.push('/');
+        }
+        current.push_str(segment);
+        if rules.is_ignored(&current, true) {
+            return true;
+        }
+        rules = rules.descend(&root.join(&current), &current);
+    }
+    let rel = if current.is_empty() {
+        last.to_string()
+    } else {
+        format!("{}/{}", current, last)
+    };
+    rules.is_ignored(&rel, is_dir)
+}
+
+/// 路径是否为忽略文件本身（其变化会影响其他路径的判定）
+pub fn is_ignore_file(rel_path: &str) -> bool {
+    let name = rel_path.rsplit('/').next().unwrap_or(rel_path);
+    IGNORE_FILES.contains(&name) || rel_path.trim_start_matches('/') == ".git/info/exclude"
+}
+
+/// `.git` 为目录时取其下的 `info/exclude`；worktree 的 `.git` 为文件，需经 `gitdir`/`commondir` 找到主仓库
+fn info_exclude_path(root: &Path) -> Option<PathBuf> {
+    let (_, common_dir) = crate::server::git::resolve_git_dirs(root)?;
+    Some(common_dir.join("info/exclude"))
+}
+
+fn parse_ignore_file(content: &str) -> Vec<IgnorePattern> {
+    content.lines().filter_map(parse_line).collect()
+}
+
+fn parse_line(line: &str) -> Option<IgnorePattern> {
+    let mut line = line.trim_end_matches('\r');
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    // 行尾空格忽略，除非以反斜杠转义
+    while line.ends_with(' ') && !line.ends_with("\\ ") {
+        line = &line[..line.len() - 1];
+    }
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let (dir_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let anchored = line.contains('/');
+    let glob = line.strip_prefix('/').unwrap_or(line);
+    if glob.is_empty() {
+        return None;
+    }
+    Some(IgnorePattern {
+        regex: glob_to_regex(glob, anchored)?,
+        negated,
+        dir_only,
+    })
+}
+
+/// gitignore glob 转正则：`*`/`?` 不跨目录，`**/` 匹配零或多级目录，结尾的 `/**` 匹配目录下全部内容
+fn glob_to_regex(glob: &str, anchored: bool) -> Option<Regex> {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if i + 1 < chars.len() => {
+                out.push_str(&regex::escape(&chars[i + 1].to_string()));
+                i += 1;
+            }
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                let segment_start = i == 0 || chars[i - 1] == '/';
+                if segment_start && chars.get(i + 2) == Some(&'/') {
+                    out.push_str("(?:.*/)?");
+                    i += 2;
+                } else if segment_start && i + 2 == chars.len() {
+                    out.push_str(".*");
+                    i += 1;
+                } else {
+                    out.push_str("[^/]*");
+                    i += 1;
+                }
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            '[' => match chars[i + 1..].iter().position(|&ch| ch == ']') {
+                Some(len) if len > 0 => {
+                    let class: String = chars[i + 1..i + 1 + len].iter().collect();
+                    let class = match class.strip_prefix('!') {
+                        Some(rest) => format!("^{}", rest),
+                        None => class,
+                    };
+                    out.push('[');
+                    out.push_str(&class.replace('\\', "\\\\").replace('[', "\\["));
+                    out.push(']');
+                    i += len + 1;
+                }
+                _ => out.push_str(r"\["),
+            },
+            _ => out.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    let pattern = if anchored {
+        format!("^{}$", out)
+    } else {
+        format!("^(?:.*/)?{}$", out)
+    };
+    Regex::new(&pattern).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn rules_from(content: &str) -> IgnoreRules {
+        let mut rules = IgnoreRules::default();
+        rules.push_layer(String::new(), parse_ignore_file(content));
+        rules
+    }
+
+    #[test]
+    fn patterns_follow_gitignore_semantics() {
+        let rules = rules_from(
+            "# comment\n*.log\n!keep.log\nout/\n/root-only.txt\ndocs/*.md\nvendor/**\n**/gen/*.rs\nfoo\\ \n",
+        );
+        assert!(rules.is_ignored("a.log", false));
+        assert!(rules.is_ignored("deep/b.log", false));
+        assert!(!rules.is_ignored("keep.log", false));
+        assert!(rules.is_ignored("out", true));
+        assert!(rules.is_ignored("src/out", true));
+        assert!(!rules.is_ignored("out", false));
+        assert!(rules.is_ignored("root-only.txt", false));
+        assert!(!rules.is_ignored("src/root-only.txt", false));
+        assert!(rules.is_ignored("docs/a.md", false));
+        assert!(!rules.is_ignored("docs/sub/a.md", false));
+        assert!(rules.is_ignored("vendor/pkg/lib.rs", false));
+        assert!(!rules.is_ignored("vendor", true));
+        assert!(rules.is_ignored("gen/a.rs", false));
+        assert!(rules.is_ignored("x/y/gen/a.rs", false));
+        assert!(rules.is_ignored("foo ", false));
+        assert!(!rules.is_ignored("src/main.rs", false));
+    }
+
+    #[test]
+    fn nested_ignore_files_take_precedence() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join(".git/info")).unwrap();
+        fs::create_dir_all(root.join("app/build")).unwrap();
+        fs::write(root.join(".git/info/exclude"), "local.txt\n").unwrap();
+        fs::write(root.join(".gitignore"), "*.tmp\n").unwrap();
+        fs::write(root.join("app/.ignore"), "!keep.tmp\n!build/\n").unwrap();
+
+        let rules = IgnoreRules::for_root(root);
+        assert!(rules.is_ignored("local.txt", false));
+        assert!(rules.is_ignored("a.tmp", false));
+        assert!(rules.is_ignored("build", true));
+        assert!(!rules.is_ignored("build", false));
+
+        let app = IgnoreRules::for_dir(root, "app");
+        assert!(!app.is_ignored("app/keep.tmp", false));
+        assert!(app.is_ignored("app/other.tmp", false));
+        assert!(!app.is_ignored("app/build", true));
+
+        assert!(is_path_ignored(root, "build/out.o", false));
+        assert!(!is_path_ignored(root, "app/build/out.o", false));
+        assert!(is_path_ignored(root, "app/x.tmp", false));
+        assert!(is_ignore_file("app/.gitignore"));
+        assert!(!is_ignore_file("app/main.rs"));
+    }
+
+    #[test]
+    fn worktree_uses_main_repository_exclude() {
+        let temp = TempDir::new().unwrap();
+        let main_git = temp.path().join("repo/.git");
+        let wt_git = main_git.join("worktrees/feat");
+        fs::create_dir_all(&wt_git).unwrap();
+        fs::create_dir_all(main_git.join("info")).unwrap();
+        fs::write(wt_git.join("commondir"), "../..\n").unwrap();
+        fs::write(main_git.join("info/exclude"), "local.txt\n").unwrap();
+
+        let worktree = temp.path().join("feat");
+        fs::create_dir_all(&worktree).unwrap();
+        fs::write(
+            worktree.join(".git"),
+            format!("gitdir: {}\n", wt_git.display()),
+        )
+        .unwrap();
+
+        assert!(IgnoreRules::for_root(&worktree).is_ignored("local.txt", false));
+    }
+}
@@ -0,0 +1,100 @@
+//! 按行范围的增量编辑
+//!
+//! `file_apply_edit` 只提交改动的行而不是整个文件，减小蜂窝网络下的负载。
+//! 所有编辑的行号都基于同一份原始内容，互不重叠，一次性应用。
+
+/// 单条行编辑：用 `new_text` 替换 `[start_line, end_line]`（1-based，含两端）
+///
+/// - `new_text` 替换整行内容，包括行尾换行符：把第 3 行改成 `foo` 应提交 `"foo\n"`；
+/// - `end_line = start_line - 1` 表示在 `start_line` 之前插入（`start_line = 行数 + 1` 即追加到末尾）；
+/// - `new_text` 为空表示删除这些行。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineEdit {
+    pub start_line: u32,
+    pub end_line: u32,
+    pub new_text: String,
+}
+
+/// 把编辑应用到 `content`，行号越界或编辑互相重叠时返回错误说明
+pub fn apply_line_edits(content: &str, edits: &[LineEdit]) -> Result<String, String> {
+    if edits.is_empty() {
+        return Err("No edits to apply".to_string());
+    }
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let line_count = lines.len() as u32;
+
+    let mut sorted: Vec<&LineEdit> = edits.iter().collect();
+    // 同一位置的插入排在替换之前
+    sorted.sort_by_key(|edit| (edit.start_line, edit.end_line));
+
+    let mut next_line = 1u32;
+    for edit in &sorted {
+        if edit.start_line == 0 || edit.end_line < edit.start_line - 1 || edit.end_line > line_count
+        {
+            return Err(format!(
+                "Invalid line range {}-{} (file has {} lines)",
+                edit.start_line, edit.end_line, line_count
+            ));
+        }
+        if edit.start_line < next_line {
+            return Err(format!("Overlapping edits at line {}", edit.start_line));
+        }
+        next_line = edit.end_line + 1;
+    }
+
+    let mut output = String::with_capacity(content.len());
+    let mut cursor = 0usize;
+    for edit in sorted {
+        let start = edit.start_line as usize - 1;
+        output.extend(lines[cursor..start].iter().copied());
+        output.push_str(&edit.new_text);
+        cursor = (edit.end_line as usize).max(start);
+    }
+    output.extend(lines[cursor..].iter().copied());
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(start_line: u32, end_line: u32, new_text: &str) -> LineEdit {
+        LineEdit {
+            start_line,
+            end_line,
+            new_text: new_text.to_string(),
+        }
+    }
+
+    #[test]
+    fn applies_replacements_insertions_and_deletions() {
+        let content = "a\nb\nc\nd";
+        let edits = [
+            edit(4, 4, "D\n"),
+            edit(2, 2, "B1\nB2\n"),
+            edit(1, 0, "top\n"),
+            edit(3, 3, ""),
+            edit(5, 4, "\ntail"),
+        ];
+        assert_eq!(
+            apply_line_edits(content, &edits).unwrap(),
+            "top\na\nB1\nB2\nD\n\ntail"
+        );
+        assert_eq!(apply_line_edits("", &[edit(1, 0, "x\n")]).unwrap(), "x\n");
+    }
+
+    #[test]
+    fn rejects_invalid_or_overlapping_edits() {
+        let content = "a\nb\nc\n";
+        assert!(apply_line_edits(content, &[]).is_err());
+        assert!(apply_line_edits(content, &[edit(0, 1, "x")]).is_err());
+        assert!(apply_line_edits(content, &[edit(3, 4, "x")]).is_err());
+        assert!(apply_line_edits(content, &[edit(3, 1, "x")]).is_err());
+        assert!(apply_line_edits(content, &[edit(1, 2, "x"), edit(2, 3, "y")]).is_err());
+        // 紧邻的编辑互不影响
+        assert_eq!(
+            apply_line_edits(content, &[edit(1, 1, "A\n"), edit(2, 2, "B\n")]).unwrap(),
+            "A\nB\nc\n"
+        );
+    }
+}
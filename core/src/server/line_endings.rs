@@ -0,0 +1,216 @@
+//! 换行符检测与写入策略
+//!
+//! `file_read` 报告文件当前使用的换行符；`file_write` 写入文本前按项目
+//! `.tidyflow.toml` 的 `[editor] line_endings` 策略统一换行符，避免移动端编辑器
+//! 把 CRLF 文件整体改写成 LF（或反之）造成整文件 diff。
+
+use std::borrow::Cow;
+use std::path::Path;
+
+use crate::util::exec_env::git_command;
+use crate::workspace::config::{LineEndingPolicy, ProjectConfig};
+
+/// 文本中各类换行符的数量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineEndingStats {
+    pub lf: usize,
+    pub crlf: usize,
+    pub cr: usize,
+}
+
+impl LineEndingStats {
+    pub fn detect(content: &[u8]) -> Self {
+        let mut stats = Self::default();
+        let mut i = 0;
+        while i < content.len() {
+            match content[i] {
+                b'\r' if content.get(i + 1) == Some(&b'\n') => {
+                    stats.crlf += 1;
+                    i += 1;
+                }
+                b'\r' => stats.cr += 1,
+                b'\n' => stats.lf += 1,
+                _ => {}
+            }
+            i += 1;
+        }
+        stats
+    }
+
+    /// 统一使用的换行符："lf" | "crlf" | "cr" | "mixed"；没有换行时为 None
+    pub fn style(&self) -> Option<&'static str> {
+        match (self.lf > 0, self.crlf > 0, self.cr > 0) {
+            (false, false, false) => None,
+            (true, false, false) => Some("lf"),
+            (false, true, false) => Some("crlf"),
+            (false, false, true) => Some("cr"),
+            _ => Some("mixed"),
+        }
+    }
+}
+
+/// 检测内容的换行符风格，见 [`LineEndingStats::style`]
+pub fn detect_line_ending(content: &[u8]) -> Option<&'static str> {
+    LineEndingStats::detect(content).style()
+}
+
+/// 将所有换行符（LF / CRLF / CR）统一为 `eol`
+pub fn convert_line_endings<'a>(content: &'a str, eol: &str) -> Cow<'a, str> {
+    let stats = LineEndingStats::detect(content.as_bytes());
+    let already = match eol {
+        "\n" => stats.crlf == 0 && stats.cr == 0,
+        "\r\n" => stats.lf == 0 && stats.cr == 0,
+        _ => stats.lf == 0 && stats.crlf == 0,
+    };
+    if already {
+        return Cow::Borrowed(content);
+    }
+    let mut out = String::with_capacity(content.len() + stats.lf);
+    let mut rest = content;
+    while let Some(pos) = rest.find(['\r', '\n']) {
+        out.push_str(&rest[..pos]);
+        out.push_str(eol);
+        rest = if rest[pos..].starts_with("\r\n") {
+            &rest[pos + 2..]
+        } else {
+            &rest[pos + 1..]
+        };
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+fn eol_str(style: &str) -> Option<&'static str> {
+    match style {
+        "lf" => Some("\n"),
+        "crlf" => Some("\r\n"),
+        "cr" => Some("\r"),
+        _ => None,
+    }
+}
+
+/// 读取 `.gitattributes` 中对该路径生效的 `eol`（仅 `lf` / `crlf`）
+///
+/// 声明了 `-text`（二进制）时返回 None，由调用方保持原样。
+pub fn gitattributes_eol(workspace_root: &Path, relative_path: &str) -> Option<&'static str> {
+    let output = git_command(workspace_root)
+        .args(["check-attr", "eol", "--", relative_path])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // 输出形如 `<path>: eol: crlf`
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let value = stdout.lines().next()?.rsplit(": ").next()?.trim();
+    match value {
+        "lf" => Some("lf"),
+        "crlf" => Some("crlf"),
+        _ => None,
+    }
+}
+
+/// 按策略确定写入时的目标换行符；None 表示不改动
+fn target_eol(
+    policy: LineEndingPolicy,
+    workspace_root: &Path,
+    relative_path: &str,
+) -> Option<&'static str> {
+    let preserve = || {
+        let existing = std::fs::read(workspace_root.join(relative_path)).ok()?;
+        detect_line_ending(&existing).and_then(eol_str)
+    };
+    match policy {
+        LineEndingPolicy::Lf => Some("\n"),
+        LineEndingPolicy::Crlf => Some("\r\n"),
+        LineEndingPolicy::Preserve => preserve(),
+        LineEndingPolicy::Auto => gitattributes_eol(workspace_root, relative_path)
+            .and_then(eol_str)
+            .or_else(preserve),
+    }
+}
+
+/// 写入前按项目策略统一换行符
+///
+/// `preserve` 下沿用磁盘上已有文件的换行符：已有文件混合换行、没有换行或文件不存在时原样写入。
+pub fn normalize_for_write<'a>(
+    workspace_root: &Path,
+    relative_path: &str,
+    content: &'a str,
+) -> Cow<'a, str> {
+    let policy = ProjectConfig::load(workspace_root)
+        .map(|config| config.editor.line_endings)
+        .unwrap_or_default();
+    match target_eol(policy, workspace_root, relative_path) {
+        Some(eol) => convert_line_endings(content, eol),
+        None => Cow::Borrowed(content),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn detect_and_convert_line_endings() {
+        assert_eq!(detect_line_ending(b"a\nb\n"), Some("lf"));
+        assert_eq!(detect_line_ending(b"a\r\nb\r\n"), Some("crlf"));
+        assert_eq!(detect_line_ending(b"a\r\nb\n"), Some("mixed"));
+        assert_eq!(detect_line_ending(b"single line"), None);
+
+        assert_eq!(
+            convert_line_endings("a\nb\r\nc\rd", "\r\n"),
+            "a\r\nb\r\nc\r\nd"
+        );
+        assert_eq!(convert_line_endings("a\r\nb\r\n", "\n"), "a\nb\n");
+        assert!(matches!(
+            convert_line_endings("a\nb\n", "\n"),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn preserve_policy_follows_existing_file() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::write(root.join("win.txt"), "one\r\ntwo\r\n").unwrap();
+        std::fs::write(root.join("mixed.txt"), "one\r\ntwo\n").unwrap();
+
+        // 移动端以 LF 提交整份内容，写回时仍保持 CRLF
+        assert_eq!(
+            normalize_for_write(root, "win.txt", "one\ntwo\nthree\n"),
+            "one\r\ntwo\r\nthree\r\n"
+        );
+        assert_eq!(normalize_for_write(root, "mixed.txt", "a\nb\n"), "a\nb\n");
+        assert_eq!(normalize_for_write(root, "new.txt", "a\r\nb\n"), "a\r\nb\n");
+    }
+
+    #[test]
+    fn explicit_and_gitattributes_policies() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::write(
+            root.join(".tidyflow.toml"),
+            "[editor]\nline_endings = \"lf\"\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("win.txt"), "one\r\n").unwrap();
+        assert_eq!(normalize_for_write(root, "win.txt", "a\r\nb\r\n"), "a\nb\n");
+
+        let status = git_command(root).args(["init", "-q"]).status();
+        if !matches!(status, Ok(s) if s.success()) {
+            return;
+        }
+        std::fs::write(
+            root.join(".tidyflow.toml"),
+            "[editor]\nline_endings = \"auto\"\n",
+        )
+        .unwrap();
+        std::fs::write(root.join(".gitattributes"), "*.bat text eol=crlf\n").unwrap();
+        assert_eq!(gitattributes_eol(root, "run.bat"), Some("crlf"));
+        assert_eq!(normalize_for_write(root, "run.bat", "a\nb\n"), "a\r\nb\r\n");
+        // 未声明 eol 时退回 preserve
+        assert_eq!(normalize_for_write(root, "win.txt", "a\nb\n"), "a\r\nb\r\n");
+    }
+}
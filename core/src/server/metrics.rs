@@ -0,0 +1,317 @@
+//! Prometheus 指标导出
+//!
+//! `GET /metrics` 以 Prometheus 文本格式输出连接数、终端数、按类型的 git 操作耗时、
+//! 消息吞吐与文件监听事件数，供在共享开发机上运行 Core 的用户接入监控。
+//! 本模块维护导出专用的计数器与直方图，其余数值取自 [`crate::server::perf`] 快照。
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::server::terminal_registry::TerminalResourceInfo;
+
+/// 耗时直方图的桶上界（毫秒）
+const LATENCY_BUCKETS_MS: [u64; 13] = [
+    5, 10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 30_000, 60_000,
+];
+
+/// 当前 WebSocket 连接数
+static CONNECTIONS_ACTIVE: AtomicU64 = AtomicU64::new(0);
+/// 累计 WebSocket 连接数
+static CONNECTIONS_TOTAL: AtomicU64 = AtomicU64::new(0);
+/// 按 domain 统计的已分发请求数
+static REQUESTS_BY_DOMAIN: Mutex<BTreeMap<&'static str, u64>> = Mutex::new(BTreeMap::new());
+/// 按 action 统计的 git 操作耗时
+static GIT_OPERATION_LATENCY: Mutex<BTreeMap<String, LatencyHistogram>> =
+    Mutex::new(BTreeMap::new());
+/// 按类型统计的文件监听事件数
+static WATCHER_EVENTS: Mutex<BTreeMap<&'static str, u64>> = Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Clone, Default)]
+struct LatencyHistogram {
+    /// 各桶的非累计计数，末位为 +Inf
+    buckets: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    sum_micros: u64,
+    count: u64,
+}
+
+impl LatencyHistogram {
+    fn observe(&mut self, elapsed: Duration) {
+        let ms = elapsed.as_millis() as u64;
+        let index = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.buckets[index] += 1;
+        self.sum_micros += elapsed.as_micros() as u64;
+        self.count += 1;
+    }
+}
+
+/// 连接存活期间持有；drop 时活跃连接数减一
+pub struct ConnectionGuard(());
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        CONNECTIONS_ACTIVE.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// 记录新建连接
+pub fn track_connection() -> ConnectionGuard {
+    CONNECTIONS_ACTIVE.fetch_add(1, Ordering::Relaxed);
+    CONNECTIONS_TOTAL.fetch_add(1, Ordering::Relaxed);
+    ConnectionGuard(())
+}
+
+/// 记录一次已分发的请求；git 域额外按 action 记录耗时
+pub fn record_request(domain: &'static str, action: &str, elapsed: Duration) {
+    {
+        let mut requests = REQUESTS_BY_DOMAIN.lock().unwrap_or_else(|e| e.into_inner());
+        *requests.entry(domain).or_default() += 1;
+    }
+    if domain == "git" {
+        let mut latency = GIT_OPERATION_LATENCY
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        latency
+            .entry(action.to_string())
+            .or_default()
+            .observe(elapsed);
+    }
+}
+
+/// 记录一次文件监听事件（`file`、`git_status`、`file_watch`）
+pub fn record_watcher_event(kind: &'static str) {
+    let mut events = WATCHER_EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+    *events.entry(kind).or_default() += 1;
+}
+
+fn write_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn write_single(out: &mut String, name: &str, kind: &str, help: &str, value: u64) {
+    write_header(out, name, kind, help);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+fn write_labeled<'a>(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    label: &str,
+    values: impl IntoIterator<Item = (&'a str, u64)>,
+) {
+    write_header(out, name, "counter", help);
+    for (key, value) in values {
+        let _ = writeln!(
+            out,
+            "{}{{{}=\"{}\"}} {}",
+            name,
+            label,
+            escape_label(key),
+            value
+        );
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// 以 Prometheus 文本格式（0.0.4）输出全部指标
+pub fn render(terminals: &TerminalResourceInfo) -> String {
+    let perf = crate::server::perf::snapshot_perf_metrics();
+    let mut out = String::new();
+
+    write_header(
+        &mut out,
+        "tidyflow_build_info",
+        "gauge",
+        "Core build information.",
+    );
+    let _ = writeln!(
+        out,
+        "tidyflow_build_info{{version=\"{}\"}} 1",
+        env!("CARGO_PKG_VERSION")
+    );
+    write_single(
+        &mut out,
+        "tidyflow_connections_active",
+        "gauge",
+        "Open WebSocket connections.",
+        CONNECTIONS_ACTIVE.load(Ordering::Relaxed),
+    );
+    write_single(
+        &mut out,
+        "tidyflow_connections_total",
+        "counter",
+        "WebSocket connections accepted since start.",
+        CONNECTIONS_TOTAL.load(Ordering::Relaxed),
+    );
+    write_single(
+        &mut out,
+        "tidyflow_terminals",
+        "gauge",
+        "Live terminals.",
+        terminals.total_terminal_count as u64,
+    );
+    write_single(
+        &mut out,
+        "tidyflow_terminal_scrollback_bytes",
+        "gauge",
+        "Scrollback bytes held by all terminals.",
+        terminals.total_scrollback_bytes as u64,
+    );
+    write_single(
+        &mut out,
+        "tidyflow_terminal_output_dropped_bytes_total",
+        "counter",
+        "Terminal output bytes dropped under backpressure.",
+        perf.terminal_output_dropped_bytes_total,
+    );
+    write_single(
+        &mut out,
+        "tidyflow_ws_messages_received_total",
+        "counter",
+        "WebSocket messages decoded from clients.",
+        perf.ws_decode.count,
+    );
+    write_single(
+        &mut out,
+        "tidyflow_ws_messages_sent_total",
+        "counter",
+        "WebSocket messages encoded for clients.",
+        perf.ws_encode.count,
+    );
+    write_single(
+        &mut out,
+        "tidyflow_ws_task_broadcast_lag_total",
+        "counter",
+        "Broadcast messages skipped by lagging receivers.",
+        perf.ws_task_broadcast_lag_total,
+    );
+    {
+        let requests = REQUESTS_BY_DOMAIN.lock().unwrap_or_else(|e| e.into_inner());
+        write_labeled(
+            &mut out,
+            "tidyflow_ws_requests_total",
+            "Client requests dispatched, by protocol domain.",
+            "domain",
+            requests.iter().map(|(domain, count)| (*domain, *count)),
+        );
+    }
+    {
+        let events = WATCHER_EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+        write_labeled(
+            &mut out,
+            "tidyflow_watcher_events_total",
+            "File watcher events emitted, by kind.",
+            "kind",
+            events.iter().map(|(kind, count)| (*kind, *count)),
+        );
+    }
+
+    let name = "tidyflow_git_operation_duration_seconds";
+    write_header(
+        &mut out,
+        name,
+        "histogram",
+        "Git operation latency over WebSocket, by action.",
+    );
+    let latency = GIT_OPERATION_LATENCY
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    for (op, histogram) in latency.iter() {
+        let op = escape_label(op);
+        let mut cumulative = 0;
+        for (bound, count) in LATENCY_BUCKETS_MS.iter().zip(histogram.buckets.iter()) {
+            cumulative += count;
+            let _ = writeln!(
+                out,
+                "{}_bucket{{op=\"{}\",le=\"{}\"}} {}",
+                name,
+                op,
+                *bound as f64 / 1000.0,
+                cumulative
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{{op=\"{}\",le=\"+Inf\"}} {}",
+            name, op, histogram.count
+        );
+        let _ = writeln!(
+            out,
+            "{}_sum{{op=\"{}\"}} {}",
+            name,
+            op,
+            histogram.sum_micros as f64 / 1_000_000.0
+        );
+        let _ = writeln!(out, "{}_count{{op=\"{}\"}} {}", name, op, histogram.count);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terminals() -> TerminalResourceInfo {
+        TerminalResourceInfo {
+            total_terminal_count: 3,
+            total_scrollback_bytes: 1024,
+            global_budget_bytes: 0,
+            budget_used_percent: 0,
+            per_workspace: vec![],
+        }
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative_in_output() {
+        record_request("git", "git_metrics_test", Duration::from_millis(3));
+        record_request("git", "git_metrics_test", Duration::from_millis(700));
+        record_request("file", "file_read", Duration::from_millis(1));
+
+        let text = render(&terminals());
+        assert!(text.contains("tidyflow_terminals 3\n"));
+        assert!(text.contains("# TYPE tidyflow_git_operation_duration_seconds histogram\n"));
+        assert!(text.contains(
+            "tidyflow_git_operation_duration_seconds_bucket{op=\"git_metrics_test\",le=\"0.005\"} 1\n"
+        ));
+        assert!(text.contains(
+            "tidyflow_git_operation_duration_seconds_bucket{op=\"git_metrics_test\",le=\"0.5\"} 1\n"
+        ));
+        assert!(text.contains(
+            "tidyflow_git_operation_duration_seconds_bucket{op=\"git_metrics_test\",le=\"1\"} 2\n"
+        ));
+        assert!(text.contains(
+            "tidyflow_git_operation_duration_seconds_count{op=\"git_metrics_test\"} 2\n"
+        ));
+        // 非 git 域只计数，不记录耗时
+        assert!(!text.contains("op=\"file_read\""));
+        assert!(text.contains("tidyflow_ws_requests_total{domain=\"file\"}"));
+    }
+
+    #[test]
+    fn connection_guard_tracks_active_connections() {
+        let guard = track_connection();
+        assert!(CONNECTIONS_ACTIVE.load(Ordering::Relaxed) >= 1);
+        assert!(render(&terminals()).contains("# TYPE tidyflow_connections_active gauge\n"));
+        drop(guard);
+        record_watcher_event("git_status");
+        assert!(render(&terminals()).contains("tidyflow_watcher_events_total{kind=\"git_status\"}"));
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
@@ -1,15 +1,32 @@
+pub mod audit_log;
 pub mod context;
+pub mod definition;
+pub mod editorconfig;
 pub mod file_api;
 pub mod file_index;
+pub mod fuzzy;
 pub mod git;
+pub mod git_fetch_scheduler;
 pub mod handlers;
 pub mod health;
+pub mod highlight;
+pub mod ignore_rules;
+pub mod line_edit;
+pub mod line_endings;
+pub mod metrics;
 pub mod node;
 pub mod perf;
+pub mod power;
+pub mod ports;
+pub mod proc_supervisor;
 pub mod protocol;
+pub mod push_relay;
 pub mod remote_connection_registry;
 pub mod remote_sub_registry;
+pub mod replace;
+pub mod task_runner;
 pub mod terminal_registry;
+pub mod text_encoding;
 pub mod watcher;
 pub mod ws;
 
@@ -27,4 +44,4 @@ pub use protocol::{
     ClientMessage, GitStatusEntry, ProjectInfo, ServerMessage, WorkspaceInfo, PROTOCOL_VERSION,
 };
 pub use watcher::{WatchEvent, WorkspaceWatcher};
-pub use ws::run_server;
+pub use ws::{run_server, run_server_with_tls, TlsConfig};
@@ -18,6 +18,8 @@ static WS_TASK_BROADCAST_SKIPPED_EMPTY_TARGET_TOTAL: AtomicU64 = AtomicU64::new(
 static WS_TASK_BROADCAST_FILTERED_TARGET_TOTAL: AtomicU64 = AtomicU64::new(0);
 /// `terminal_unacked_timeout_total`
 static TERMINAL_UNACKED_TIMEOUT_TOTAL: AtomicU64 = AtomicU64::new(0);
+/// `terminal_output_dropped_bytes_total`
+static TERMINAL_OUTPUT_DROPPED_BYTES_TOTAL: AtomicU64 = AtomicU64::new(0);
 /// `project_command_output_throttled_total`
 static PROJECT_COMMAND_OUTPUT_THROTTLED_TOTAL: AtomicU64 = AtomicU64::new(0);
 /// `project_command_output_emitted_total`
@@ -164,6 +166,20 @@ pub fn record_terminal_unacked_timeout(term_id: &str, unacked_before_decay: u64)
     }
 }
 
+/// 记录终端实时输出因限速积压或客户端暂停而丢弃的字节数（数据仍保留在 scrollback）
+pub fn record_terminal_output_dropped(bytes: u64) {
+    if bytes == 0 {
+        return;
+    }
+    let total = TERMINAL_OUTPUT_DROPPED_BYTES_TOTAL.fetch_add(bytes, Ordering::Relaxed) + bytes;
+    if perf_logging_enabled() {
+        info!(
+            "perf terminal_output_dropped_bytes_total={} this_batch={}",
+            total, bytes
+        );
+    }
+}
+
 pub fn record_project_command_output_throttled(dropped: u64) {
     if dropped == 0 {
         return;
@@ -436,6 +452,7 @@ pub struct PerfMetricsSnapshot {
     pub terminal_unacked_timeout_total: u64,
     pub terminal_reclaimed_total: u64,
     pub terminal_scrollback_trim_total: u64,
+    pub terminal_output_dropped_bytes_total: u64,
     // -- 项目命令输出 --
     pub project_command_output_throttled_total: u64,
     pub project_command_output_emitted_total: u64,
@@ -480,6 +497,8 @@ pub fn snapshot_perf_metrics() -> PerfMetricsSnapshot {
         terminal_unacked_timeout_total: TERMINAL_UNACKED_TIMEOUT_TOTAL.load(Ordering::Relaxed),
         terminal_reclaimed_total: TERMINAL_RECLAIMED_TOTAL.load(Ordering::Relaxed),
         terminal_scrollback_trim_total: TERMINAL_SCROLLBACK_TRIM_TOTAL.load(Ordering::Relaxed),
+        terminal_output_dropped_bytes_total: TERMINAL_OUTPUT_DROPPED_BYTES_TOTAL
+            .load(Ordering::Relaxed),
         project_command_output_throttled_total: PROJECT_COMMAND_OUTPUT_THROTTLED_TOTAL
             .load(Ordering::Relaxed),
         project_command_output_emitted_total: PROJECT_COMMAND_OUTPUT_EMITTED_TOTAL
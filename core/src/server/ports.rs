@@ -0,0 +1,345 @@
+//! 工作区端口占用探测
+//!
+//! 并行 worktree 中的 dev server 经常抢同一个端口。这里找出工作目录位于工作区根目录下的
+//! 进程所监听的 TCP 端口，供客户端展示“running on :3000”。
+//! Linux 直接解析 `/proc`（`/proc/net/tcp{,6}` + 进程 fd / cwd），其他平台调用 `lsof`。
+//! 只能看到当前用户有权限检查的进程。
+
+use std::collections::HashSet;
+use std::path::Path;
+
+/// 进程命令行展示长度上限
+const MAX_COMMAND_LEN: usize = 200;
+
+/// 一个监听中的端口
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListeningPort {
+    pub port: u16,
+    /// 监听地址，如 `127.0.0.1`、`::`、`*`
+    pub address: String,
+    pub pid: u32,
+    pub command: String,
+}
+
+/// 列出工作目录位于 `root` 下的进程监听的 TCP 端口，按端口排序；同一进程同一端口只报告一次
+pub fn list_listening_ports(root: &Path) -> Vec<ListeningPort> {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let mut ports = collect_listening_ports(&root);
+    let mut seen = HashSet::new();
+    ports.retain(|p| seen.insert((p.pid, p.port)));
+    ports.sort_by_key(|p| (p.port, p.pid));
+    ports
+}
+
+fn truncate_command(command: String) -> String {
+    if command.chars().count() <= MAX_COMMAND_LEN {
+        return command;
+    }
+    let mut truncated: String = command.chars().take(MAX_COMMAND_LEN).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(target_os = "linux")]
+fn collect_listening_ports(root: &Path) -> Vec<ListeningPort> {
+    use std::collections::HashMap;
+
+    let mut listeners: HashMap<u64, (String, u16)> = HashMap::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        if let Ok(content) = std::fs::read_to_string(table) {
+            for (inode, address, port) in parse_proc_net_tcp(&content) {
+                listeners.insert(inode, (address, port));
+            }
+        }
+    }
+    if listeners.is_empty() {
+        return Vec::new();
+    }
+
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    let mut ports = Vec::new();
+    for entry in entries.flatten() {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        let proc_dir = entry.path();
+        let rooted =
+            std::fs::read_link(proc_dir.join("cwd")).is_ok_and(|cwd| cwd.starts_with(root));
+        if !rooted {
+            continue;
+        }
+        let Ok(fds) = std::fs::read_dir(proc_dir.join("fd")) else {
+            continue;
+        };
+        let mut command = None;
+        for fd in fds.flatten() {
+            let Some(inode) = std::fs::read_link(fd.path())
+                .ok()
+                .and_then(|target| parse_socket_inode(&target.to_string_lossy()))
+            else {
+                continue;
+            };
+            let Some((address, port)) = listeners.get(&inode) else {
+                continue;
+            };
+            let command = command.get_or_insert_with(|| proc_command(&proc_dir));
+            ports.push(ListeningPort {
+                port: *port,
+                address: address.clone(),
+                pid,
+                command: command.clone(),
+            });
+        }
+    }
+    ports
+}
+
+#[cfg(target_os = "linux")]
+fn proc_command(proc_dir: &Path) -> String {
+    let cmdline = std::fs::read(proc_dir.join("cmdline"))
+        .map(|raw| {
+            raw.split(|b| *b == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default();
+    if !cmdline.is_empty() {
+        return truncate_command(cmdline);
+    }
+    std::fs::read_to_string(proc_dir.join("comm"))
+        .map(|comm| comm.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// `socket:[12345]` → 12345
+#[cfg(any(target_os = "linux", test))]
+fn parse_socket_inode(link: &str) -> Option<u64> {
+    link.strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// 解析 `/proc/net/tcp` 或 `/proc/net/tcp6`，返回处于 LISTEN 状态的 `(inode, 地址, 端口)`
+#[cfg(any(target_os = "linux", test))]
+fn parse_proc_net_tcp(content: &str) -> Vec<(u64, String, u16)> {
+    const TCP_LISTEN: &str = "0A";
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[3] != TCP_LISTEN {
+                return None;
+            }
+            let (addr_hex, port_hex) = fields[1].split_once(':')?;
+            let port = u16::from_str_radix(port_hex, 16).ok()?;
+            let inode = fields[9].parse::<u64>().ok().filter(|inode| *inode != 0)?;
+            Some((inode, parse_proc_net_addr(addr_hex)?, port))
+        })
+        .collect()
+}
+
+/// 内核按 32 位字的主机字节序（小端）输出地址
+#[cfg(any(target_os = "linux", test))]
+fn parse_proc_net_addr(hex: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(16);
+    for word in hex.as_bytes().chunks(8) {
+        let word = u32::from_str_radix(std::str::from_utf8(word).ok()?, 16).ok()?;
+        bytes.extend_from_slice(&word.to_le_bytes());
+    }
+    match bytes.len() {
+        4 => Some(std::net::Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]).to_string()),
+        16 => {
+            let octets: [u8; 16] = bytes.try_into().ok()?;
+            Some(std::net::Ipv6Addr::from(octets).to_string())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn collect_listening_ports(root: &Path) -> Vec<ListeningPort> {
+    use std::collections::HashMap;
+
+    let listen = std::process::Command::new("lsof")
+        .args(["-nP", "-iTCP", "-sTCP:LISTEN", "-Fpcn"])
+        .output();
+    let Ok(listen) = listen else {
+        return Vec::new();
+    };
+    let listeners = parse_lsof_listen(&String::from_utf8_lossy(&listen.stdout));
+    if listeners.is_empty() {
+        return Vec::new();
+    }
+
+    let pids: Vec<String> = listeners
+        .iter()
+        .map(|p| p.pid.to_string())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let cwd = std::process::Command::new("lsof")
+        .args(["-a", "-d", "cwd", "-Fn", "-p", &pids.join(",")])
+        .output();
+    let cwds: HashMap<u32, String> = cwd
+        .map(|out| parse_lsof_cwd(&String::from_utf8_lossy(&out.stdout)))
+        .unwrap_or_default();
+
+    listeners
+        .into_iter()
+        .filter(|p| {
+            cwds.get(&p.pid)
+                .is_some_and(|cwd| Path::new(cwd).starts_with(root))
+        })
+        .map(|p| ListeningPort {
+            command: truncate_command(p.command),
+            ..p
+        })
+        .collect()
+}
+
+/// 解析 `lsof -nP -iTCP -sTCP:LISTEN -Fpcn` 的字段输出
+#[cfg(any(not(target_os = "linux"), test))]
+fn parse_lsof_listen(output: &str) -> Vec<ListeningPort> {
+    let mut ports = Vec::new();
+    let (mut pid, mut command) = (None, String::new());
+    for line in output.lines() {
+        let Some(tag) = line.chars().next() else {
+            continue;
+        };
+        let value = &line[tag.len_utf8()..];
+        match tag {
+            'p' => {
+                pid = value.parse::<u32>().ok();
+                command.clear();
+            }
+            'c' => command = value.to_string(),
+            'n' => {
+                let (Some(pid), Some((address, port))) = (pid, value.rsplit_once(':')) else {
+                    continue;
+                };
+                let Ok(port) = port.parse::<u16>() else {
+                    continue;
+                };
+                ports.push(ListeningPort {
+                    port,
+                    address: address
+                        .trim_start_matches('[')
+                        .trim_end_matches(']')
+                        .to_string(),
+                    pid,
+                    command: command.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+    ports
+}
+
+/// 解析 `lsof -a -d cwd -Fn -p ...` 的输出，返回 pid → 工作目录
+#[cfg(any(not(target_os = "linux"), test))]
+fn parse_lsof_cwd(output: &str) -> std::collections::HashMap<u32, String> {
+    let mut cwds = std::collections::HashMap::new();
+    let mut pid = None;
+    for line in output.lines() {
+        if let Some(value) = line.strip_prefix('p') {
+            pid = value.parse::<u32>().ok();
+        } else if let (Some(value), Some(pid)) = (line.strip_prefix('n'), pid) {
+            cwds.insert(pid, value.to_string());
+        }
+    }
+    cwds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_proc_net_tcp_listeners() {
+        let tcp = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n\
+   0: 0100007F:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 4242 1 0000000000000000 100 0 0 10 0\n\
+   1: 0100007F:0BB9 0100007F:D431 01 00000000:00000000 00:00000000 00000000  1000        0 4243 1 0000000000000000 20 4 30 10 -1\n";
+        assert_eq!(
+            parse_proc_net_tcp(tcp),
+            vec![(4242, "127.0.0.1".to_string(), 3000)]
+        );
+
+        let tcp6 = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n\
+   0: 00000000000000000000000000000000:1F90 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 77 1 0000000000000000 100 0 0 10 0\n\
+   1: 00000000000000000000000001000000:1F91 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 78 1 0000000000000000 100 0 0 10 0\n";
+        assert_eq!(
+            parse_proc_net_tcp(tcp6),
+            vec![(77, "::".to_string(), 8080), (78, "::1".to_string(), 8081)]
+        );
+        assert_eq!(parse_socket_inode("socket:[4242]"), Some(4242));
+        assert_eq!(parse_socket_inode("pipe:[4242]"), None);
+    }
+
+    #[test]
+    fn parses_lsof_field_output() {
+        let listen =
+            "p501\ncnode\nf23\nn*:3000\nf24\nn[::1]:3001\np777\ncpython3\nf3\nn127.0.0.1:8000\n";
+        let ports = parse_lsof_listen(listen);
+        assert_eq!(ports.len(), 3);
+        assert_eq!(
+            (ports[0].pid, ports[0].port, ports[0].address.as_str()),
+            (501, 3000, "*")
+        );
+        assert_eq!(ports[1].address, "::1");
+        assert_eq!(
+            (ports[2].command.as_str(), ports[2].port),
+            ("python3", 8000)
+        );
+
+        let cwds = parse_lsof_cwd("p501\nfcwd\nn/work/api\np777\nfcwd\nn/work/web\n");
+        assert_eq!(cwds.get(&501).map(String::as_str), Some("/work/api"));
+        assert_eq!(cwds.get(&777).map(String::as_str), Some("/work/web"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn finds_listener_rooted_in_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut child = std::process::Command::new("python3")
+            .args([
+                "-c",
+                "import socket,time\ns=socket.socket()\ns.bind(('127.0.0.1',0))\ns.listen()\nprint(s.getsockname()[1],flush=True)\ntime.sleep(30)",
+            ])
+            .current_dir(dir.path())
+            .stdout(std::process::Stdio::piped())
+            .spawn();
+        let Ok(ref mut child) = child else {
+            // 环境没有 python3 时跳过
+            return;
+        };
+        let mut line = String::new();
+        std::io::BufRead::read_line(
+            &mut std::io::BufReader::new(child.stdout.take().unwrap()),
+            &mut line,
+        )
+        .unwrap();
+        let port: u16 = line.trim().parse().unwrap();
+
+        let ports = list_listening_ports(dir.path());
+        let other = tempfile::tempdir().unwrap();
+        let other_ports = list_listening_ports(other.path());
+        let _ = child.kill();
+        let _ = child.wait();
+
+        let found = ports.iter().find(|p| p.port == port).expect("port found");
+        assert_eq!(found.pid, child.id());
+        assert!(found.command.contains("python3"));
+        assert!(other_ports.iter().all(|p| p.port != port));
+    }
+}
@@ -0,0 +1,181 @@
+//! 主机休眠/唤醒感知
+//!
+//! 事件来源有两个：
+//! - 客户端转发的系统通知（`host_power_event`，macOS App 监听 `NSWorkspace` 休眠/唤醒）；
+//! - Core 自身的兜底检测：墙钟前进明显快于单调时钟（休眠期间单调时钟停止）即视为刚唤醒。
+//!
+//! 两个来源可能对同一次唤醒各报一次，多个客户端也会同时转发，
+//! 这里在 [`WAKE_COALESCE_WINDOW`] 内合并为一次 [`PowerEvent::DidWake`]，
+//! 避免唤醒后每个连接重复全量重扫。
+
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use tokio::sync::broadcast;
+use tracing::{debug, info};
+
+/// 同一次唤醒的合并窗口
+pub const WAKE_COALESCE_WINDOW: Duration = Duration::from_secs(30);
+/// 兜底检测的采样间隔
+const SLEEP_DETECT_INTERVAL: Duration = Duration::from_secs(5);
+/// 墙钟比单调时钟多走超过该值时判定发生过休眠
+const SLEEP_GAP_THRESHOLD: Duration = Duration::from_secs(20);
+
+/// 主机电源事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerEvent {
+    /// 即将休眠：暂停 watcher 与后台网络操作
+    WillSleep,
+    /// 已唤醒：`slept_ms` 为估算的休眠时长（未知时为 0）
+    DidWake { slept_ms: u64 },
+}
+
+#[derive(Debug, Default)]
+struct PowerState {
+    /// 收到休眠通知的墙钟时间
+    sleeping_since: Option<SystemTime>,
+    /// 最近一次发出唤醒事件的时间
+    last_wake_at: Option<Instant>,
+}
+
+impl PowerState {
+    fn on_will_sleep(&mut self, now: SystemTime) -> Option<PowerEvent> {
+        if self.sleeping_since.is_some() {
+            return None;
+        }
+        self.sleeping_since = Some(now);
+        Some(PowerEvent::WillSleep)
+    }
+
+    /// `detected_gap` 为兜底检测测得的休眠时长
+    fn on_did_wake(
+        &mut self,
+        now: SystemTime,
+        now_mono: Instant,
+        detected_gap: Option<Duration>,
+    ) -> Option<PowerEvent> {
+        let slept = match self.sleeping_since.take() {
+            Some(since) => now.duration_since(since).unwrap_or_default(),
+            None => {
+                let recently_woke = self
+                    .last_wake_at
+                    .is_some_and(|at| now_mono.duration_since(at) < WAKE_COALESCE_WINDOW);
+                if recently_woke {
+                    return None;
+                }
+                detected_gap.unwrap_or_default()
+            }
+        };
+        self.last_wake_at = Some(now_mono);
+        Some(PowerEvent::DidWake {
+            slept_ms: slept.as_millis() as u64,
+        })
+    }
+}
+
+static POWER_STATE: LazyLock<Mutex<PowerState>> =
+    LazyLock::new(|| Mutex::new(PowerState::default()));
+
+static POWER_EVENTS: LazyLock<broadcast::Sender<PowerEvent>> =
+    LazyLock::new(|| broadcast::channel(16).0);
+
+/// 订阅电源事件（每个 WS 连接一个接收端）
+pub fn subscribe_power_events() -> broadcast::Receiver<PowerEvent> {
+    POWER_EVENTS.subscribe()
+}
+
+/// 主机是否处于休眠流程中（已收到休眠通知、尚未唤醒）
+pub fn is_host_sleeping() -> bool {
+    POWER_STATE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .sleeping_since
+        .is_some()
+}
+
+/// 主机即将休眠；重复通知只生效一次
+pub fn notify_will_sleep() {
+    let event = POWER_STATE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .on_will_sleep(SystemTime::now());
+    if let Some(event) = event {
+        info!("Host will sleep, pausing watchers");
+        let _ = POWER_EVENTS.send(event);
+    }
+}
+
+/// 主机已唤醒；合并窗口内的重复通知被忽略
+pub fn notify_did_wake(detected_gap: Option<Duration>) {
+    let event = POWER_STATE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .on_did_wake(SystemTime::now(), Instant::now(), detected_gap);
+    match event {
+        Some(event) => {
+            info!(?event, "Host did wake, revalidating state");
+            crate::server::git::cache::invalidate_all_git_status_cache();
+            let _ = POWER_EVENTS.send(event);
+        }
+        None => debug!("Duplicate wake notification coalesced"),
+    }
+}
+
+/// 启动休眠兜底检测：客户端未转发系统通知（如 Linux、iOS 远程连接）时仍能感知唤醒
+pub fn spawn_sleep_detector() {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SLEEP_DETECT_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut last_mono = Instant::now();
+        let mut last_wall = SystemTime::now();
+        loop {
+            interval.tick().await;
+            let (now_mono, now_wall) = (Instant::now(), SystemTime::now());
+            let mono_elapsed = now_mono.duration_since(last_mono);
+            let wall_elapsed = now_wall.duration_since(last_wall).unwrap_or_default();
+            (last_mono, last_wall) = (now_mono, now_wall);
+
+            let gap = wall_elapsed.saturating_sub(mono_elapsed);
+            if gap >= SLEEP_GAP_THRESHOLD {
+                debug!(gap_ms = gap.as_millis() as u64, "Clock gap detected");
+                notify_did_wake(Some(gap));
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wake_notifications_are_coalesced() {
+        let mut state = PowerState::default();
+        let wall = SystemTime::now();
+        let mono = Instant::now();
+
+        assert_eq!(state.on_will_sleep(wall), Some(PowerEvent::WillSleep));
+        assert_eq!(state.on_will_sleep(wall), None);
+
+        let woke = wall + Duration::from_secs(3600);
+        assert_eq!(
+            state.on_did_wake(woke, mono, None),
+            Some(PowerEvent::DidWake {
+                slept_ms: 3_600_000
+            })
+        );
+        // 兜底检测与其他客户端随后报告的同一次唤醒被合并
+        let gap = Some(Duration::from_secs(3600));
+        assert_eq!(
+            state.on_did_wake(woke, mono + Duration::from_secs(5), gap),
+            None
+        );
+        // 合并窗口之后未收到休眠通知的唤醒按检测到的间隔上报
+        assert_eq!(
+            state.on_did_wake(woke, mono + WAKE_COALESCE_WINDOW, gap),
+            Some(PowerEvent::DidWake {
+                slept_ms: 3_600_000
+            })
+        );
+    }
+}
@@ -0,0 +1,585 @@
+//! 工作区常驻进程托管（process supervisor）
+//!
+//! dev server 等长时间运行的进程按 `(project, workspace, name)` 登记，由 Core 持有而非连接持有：
+//! - 连接断开不影响进程，重连后可通过 `proc_list` / `proc_logs` 找回状态与最近输出；
+//! - 进程在独立进程组中启动，停止时向整个进程组发送 `SIGTERM`，超时后 `SIGKILL`；
+//! - 工作区删除 / 重命名、项目移除与 Core 退出时自动停止对应进程。
+//!
+//! 每个进程保留最近 [`MAX_LOG_LINES`] 行 stdout/stderr 输出；已退出的进程保留记录供查看日志，
+//! 同名进程再次启动时替换旧记录。
+
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tracing::{info, warn};
+
+use crate::server::protocol::ProcInfo;
+
+/// 每个进程保留的日志行数
+pub const MAX_LOG_LINES: usize = 5000;
+/// 单行日志的最大字节数，超出部分截断
+const MAX_LOG_LINE_BYTES: usize = 4096;
+/// 每个工作区登记的进程数上限（含已退出的记录）
+pub const MAX_PROCS_PER_WORKSPACE: usize = 16;
+/// 进程名最大长度
+const MAX_PROC_NAME_LEN: usize = 64;
+/// `SIGTERM` 后等待退出的时间，超时发送 `SIGKILL`
+const STOP_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+#[derive(Error, Debug)]
+pub enum ProcError {
+    #[error("Invalid process name: {0} (letters, digits, '.', '_' and '-' only)")]
+    InvalidName(String),
+    #[error("Command must not be empty")]
+    EmptyCommand,
+    #[error("Process '{0}' is already running")]
+    AlreadyRunning(String),
+    #[error("Process '{0}' not found")]
+    NotFound(String),
+    #[error("Too many processes in workspace (max {MAX_PROCS_PER_WORKSPACE})")]
+    LimitExceeded,
+    #[error("Failed to start process: {0}")]
+    SpawnFailed(#[from] std::io::Error),
+}
+
+impl ProcError {
+    /// 协议错误码
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidName(_) | Self::EmptyCommand => "invalid_request",
+            Self::AlreadyRunning(_) => "proc_already_running",
+            Self::NotFound(_) => "proc_not_found",
+            Self::LimitExceeded => "proc_limit_exceeded",
+            Self::SpawnFailed(_) => "proc_spawn_failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProcState {
+    Running,
+    /// 已发送 `SIGTERM`，等待退出
+    Stopping,
+    /// 被 `proc_stop` 或清理流程停止
+    Stopped,
+    /// 自行退出
+    Exited,
+}
+
+impl ProcState {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Stopping => "stopping",
+            Self::Stopped => "stopped",
+            Self::Exited => "exited",
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        matches!(self, Self::Running | Self::Stopping)
+    }
+}
+
+type ProcKey = (String, String, String);
+
+struct ManagedProc {
+    /// 启动序号：同名进程重启后，旧进程的读取 / 等待任务不再写入新记录
+    generation: u64,
+    command: String,
+    pid: Option<u32>,
+    state: ProcState,
+    exit_code: Option<i32>,
+    started_at_ms: i64,
+    exited_at_ms: Option<i64>,
+    logs: VecDeque<String>,
+    /// 因超出 [`MAX_LOG_LINES`] 被丢弃的行数
+    dropped_lines: u64,
+}
+
+impl ManagedProc {
+    fn info(&self, name: &str) -> ProcInfo {
+        ProcInfo {
+            name: name.to_string(),
+            command: self.command.clone(),
+            status: self.state.as_str().to_string(),
+            pid: self.pid,
+            exit_code: self.exit_code,
+            started_at_ms: self.started_at_ms,
+            exited_at_ms: self.exited_at_ms,
+        }
+    }
+
+    fn push_log(&mut self, line: String) {
+        if self.logs.len() >= MAX_LOG_LINES {
+            self.logs.pop_front();
+            self.dropped_lines += 1;
+        }
+        self.logs.push_back(line);
+    }
+}
+
+#[derive(Default)]
+struct Supervisor {
+    procs: HashMap<ProcKey, ManagedProc>,
+    next_generation: u64,
+}
+
+static SUPERVISOR: LazyLock<Mutex<Supervisor>> =
+    LazyLock::new(|| Mutex::new(Supervisor::default()));
+
+fn supervisor() -> std::sync::MutexGuard<'static, Supervisor> {
+    SUPERVISOR.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn key(project: &str, workspace: &str, name: &str) -> ProcKey {
+    (project.to_string(), workspace.to_string(), name.to_string())
+}
+
+/// 日志查询结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcLogTail {
+    pub lines: Vec<String>,
+    /// 缓冲区已丢弃的更早输出行数
+    pub dropped_lines: u64,
+}
+
+/// 校验进程名：非空、不超过 64 字符，仅含字母数字与 `.` `_` `-`
+pub fn validate_proc_name(name: &str) -> Result<(), ProcError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_PROC_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if valid {
+        Ok(())
+    } else {
+        Err(ProcError::InvalidName(name.to_string()))
+    }
+}
+
+/// 在 `cwd` 中以登录 shell 启动进程，`env` 为追加的环境变量；`on_exit` 在进程退出后以最终状态调用
+pub fn start_proc<F>(
+    project: &str,
+    workspace: &str,
+    name: &str,
+    command: &str,
+    cwd: &Path,
+    env: &[(String, String)],
+    on_exit: F,
+) -> Result<ProcInfo, ProcError>
+where
+    F: FnOnce(ProcInfo) + Send + 'static,
+{
+    validate_proc_name(name)?;
+    if command.trim().is_empty() {
+        return Err(ProcError::EmptyCommand);
+    }
+    let proc_key = key(project, workspace, name);
+
+    let mut sup = supervisor();
+    if sup.procs.get(&proc_key).is_some_and(|p| p.state.is_alive()) {
+        return Err(ProcError::AlreadyRunning(name.to_string()));
+    }
+    make_room(&mut sup, project, workspace, &proc_key)?;
+
+    let shell = crate::pty::ShellSpec::system_default();
+    let mut cmd = std::process::Command::new(&shell.path);
+    cmd.arg("-l")
+        .arg("-c")
+        .arg(command)
+        .current_dir(cwd)
+        .envs(env.iter().map(|(k, v)| (k, v)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // 独立进程组：停止时连同子进程（如 npm 拉起的 node）一起结束
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    crate::util::trace::apply_to_command(&mut cmd);
+    let mut child = tokio::process::Command::from(cmd).spawn()?;
+
+    sup.next_generation += 1;
+    let generation = sup.next_generation;
+    let entry = ManagedProc {
+        generation,
+        command: command.to_string(),
+        pid: child.id(),
+        state: ProcState::Running,
+        exit_code: None,
+        started_at_ms: chrono::Utc::now().timestamp_millis(),
+        exited_at_ms: None,
+        logs: VecDeque::new(),
+        dropped_lines: 0,
+    };
+    let info = entry.info(name);
+    sup.procs.insert(proc_key.clone(), entry);
+    drop(sup);
+    info!(project, workspace, name, pid = ?info.pid, "Supervised process started");
+
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    tokio::spawn(async move {
+        let readers = async {
+            tokio::join!(
+                pipe_logs(stdout, proc_key.clone(), generation),
+                pipe_logs(stderr, proc_key.clone(), generation),
+            )
+        };
+        let (status, _) = tokio::join!(child.wait(), readers);
+        let exit_code = status.ok().and_then(|s| s.code());
+
+        let final_info = {
+            let mut sup = supervisor();
+            let Some(entry) = sup
+                .procs
+                .get_mut(&proc_key)
+                .filter(|p| p.generation == generation)
+            else {
+                return;
+            };
+            entry.state = match entry.state {
+                ProcState::Stopping => ProcState::Stopped,
+                _ => ProcState::Exited,
+            };
+            entry.exit_code = exit_code;
+            entry.exited_at_ms = Some(chrono::Utc::now().timestamp_millis());
+            entry.info(&proc_key.2)
+        };
+        info!(
+            project = %proc_key.0,
+            workspace = %proc_key.1,
+            name = %proc_key.2,
+            status = %final_info.status,
+            exit_code = ?exit_code,
+            "Supervised process exited"
+        );
+        on_exit(final_info);
+    });
+
+    Ok(info)
+}
+
+/// 工作区达到上限时淘汰最早退出的记录；全部仍在运行时报错
+fn make_room(
+    sup: &mut Supervisor,
+    project: &str,
+    workspace: &str,
+    proc_key: &ProcKey,
+) -> Result<(), ProcError> {
+    if sup.procs.contains_key(proc_key) {
+        // 同名旧记录会被替换，不占用新名额
+        return Ok(());
+    }
+    let in_workspace: Vec<(&ProcKey, &ManagedProc)> = sup
+        .procs
+        .iter()
+        .filter(|(k, _)| k.0 == project && k.1 == workspace)
+        .collect();
+    if in_workspace.len() < MAX_PROCS_PER_WORKSPACE {
+        return Ok(());
+    }
+    let oldest_exited = in_workspace
+        .iter()
+        .filter(|(_, p)| !p.state.is_alive())
+        .min_by_key(|(_, p)| p.exited_at_ms)
+        .map(|(k, _)| (*k).clone());
+    match oldest_exited {
+        Some(evict) => {
+            sup.procs.remove(&evict);
+            Ok(())
+        }
+        None => Err(ProcError::LimitExceeded),
+    }
+}
+
+async fn pipe_logs<R>(pipe: Option<R>, proc_key: ProcKey, generation: u64)
+where
+    R: AsyncRead + Unpin,
+{
+    let Some(pipe) = pipe else {
+        return;
+    };
+    let mut reader = BufReader::new(pipe);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let raw = buf.strip_suffix(b"\n").unwrap_or(&buf);
+        let raw = raw.strip_suffix(b"\r").unwrap_or(raw);
+        let mut line = String::from_utf8_lossy(raw).into_owned();
+        if line.len() > MAX_LOG_LINE_BYTES {
+            let mut end = MAX_LOG_LINE_BYTES;
+            while !line.is_char_boundary(end) {
+                end -= 1;
+            }
+            line.truncate(end);
+        }
+        let mut sup = supervisor();
+        match sup
+            .procs
+            .get_mut(&proc_key)
+            .filter(|p| p.generation == generation)
+        {
+            Some(entry) => entry.push_log(line),
+            // 记录已被替换或清理，继续读取以免子进程因管道写满而阻塞
+            None => continue,
+        }
+    }
+}
+
+fn signal_group(pid: Option<u32>, signal: i32) {
+    let Some(pid) = pid.and_then(|p| i32::try_from(p).ok()).filter(|p| *p > 0) else {
+        return;
+    };
+    // 进程以自身 pid 为进程组 id 启动
+    if unsafe { libc::kill(-pid, signal) } != 0 {
+        warn!(
+            pid,
+            signal,
+            error = %std::io::Error::last_os_error(),
+            "Failed to signal supervised process group"
+        );
+    }
+}
+
+/// 停止进程：发送 `SIGTERM`，[`STOP_GRACE_PERIOD`] 后仍未退出则 `SIGKILL`。
+/// 已退出的进程直接返回其记录。
+pub fn stop_proc(project: &str, workspace: &str, name: &str) -> Result<ProcInfo, ProcError> {
+    let proc_key = key(project, workspace, name);
+    let (info, pid, generation) = {
+        let mut sup = supervisor();
+        let entry = sup
+            .procs
+            .get_mut(&proc_key)
+            .ok_or_else(|| ProcError::NotFound(name.to_string()))?;
+        if entry.state != ProcState::Running {
+            return Ok(entry.info(name));
+        }
+        entry.state = ProcState::Stopping;
+        (entry.info(name), entry.pid, entry.generation)
+    };
+    signal_group(pid, libc::SIGTERM);
+    tokio::spawn(async move {
+        tokio::time::sleep(STOP_GRACE_PERIOD).await;
+        let still_running = supervisor()
+            .procs
+            .get(&proc_key)
+            .is_some_and(|p| p.generation == generation && p.state == ProcState::Stopping);
+        if still_running {
+            warn!(name = %proc_key.2, "Supervised process ignored SIGTERM, killing");
+            signal_group(pid, libc::SIGKILL);
+        }
+    });
+    Ok(info)
+}
+
+/// 停止并移除匹配的进程记录，返回被停止的进程名
+fn stop_matching(filter: impl Fn(&ProcKey) -> bool) -> Vec<String> {
+    let removed: Vec<(ProcKey, ManagedProc)> = {
+        let mut sup = supervisor();
+        let keys: Vec<ProcKey> = sup.procs.keys().filter(|k| filter(k)).cloned().collect();
+        keys.into_iter()
+            .filter_map(|k| sup.procs.remove(&k).map(|p| (k, p)))
+            .collect()
+    };
+    let mut stopped = Vec::new();
+    for ((_, _, name), entry) in removed {
+        if entry.state.is_alive() {
+            signal_group(entry.pid, libc::SIGTERM);
+            let pid = entry.pid;
+            // 记录已移除，无法再判断是否退出；宽限期后对仍存在的进程组补发 SIGKILL
+            if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                handle.spawn(async move {
+                    tokio::time::sleep(STOP_GRACE_PERIOD).await;
+                    if process_group_alive(pid) {
+                        signal_group(pid, libc::SIGKILL);
+                    }
+                });
+            }
+            stopped.push(name);
+        }
+    }
+    stopped
+}
+
+fn process_group_alive(pid: Option<u32>) -> bool {
+    pid.and_then(|p| i32::try_from(p).ok())
+        .filter(|p| *p > 0)
+        .is_some_and(|p| unsafe { libc::kill(-p, 0) } == 0)
+}
+
+/// 停止并清理工作区的所有进程（工作区删除 / 重命名时调用）
+pub fn stop_workspace_procs(project: &str, workspace: &str) -> Vec<String> {
+    stop_matching(|k| k.0 == project && k.1 == workspace)
+}
+
+/// 停止并清理项目的所有进程（项目移除时调用）
+pub fn stop_project_procs(project: &str) -> Vec<String> {
+    stop_matching(|k| k.0 == project)
+}
+
+/// Core 退出前停止全部进程：`SIGTERM` 后最多等待宽限期，仍存活的进程组 `SIGKILL`
+pub async fn shutdown_all_procs() {
+    let pids: Vec<Option<u32>> = {
+        let mut sup = supervisor();
+        sup.procs
+            .drain()
+            .filter(|(_, p)| p.state.is_alive())
+            .map(|(_, p)| p.pid)
+            .collect()
+    };
+    if pids.is_empty() {
+        return;
+    }
+    info!(count = pids.len(), "Stopping supervised processes");
+    for pid in &pids {
+        signal_group(*pid, libc::SIGTERM);
+    }
+    let deadline = tokio::time::Instant::now() + STOP_GRACE_PERIOD;
+    while tokio::time::Instant::now() < deadline && pids.iter().any(|p| process_group_alive(*p)) {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    for pid in pids.into_iter().filter(|p| process_group_alive(*p)) {
+        signal_group(pid, libc::SIGKILL);
+    }
+}
+
+/// 工作区登记的进程，按名称排序
+pub fn list_procs(project: &str, workspace: &str) -> Vec<ProcInfo> {
+    let sup = supervisor();
+    let mut items: Vec<ProcInfo> = sup
+        .procs
+        .iter()
+        .filter(|(k, _)| k.0 == project && k.1 == workspace)
+        .map(|(k, p)| p.info(&k.2))
+        .collect();
+    items.sort_by(|a, b| a.name.cmp(&b.name));
+    items
+}
+
+/// 最近 `limit` 行日志（不超过 [`MAX_LOG_LINES`]）
+pub fn proc_logs(
+    project: &str,
+    workspace: &str,
+    name: &str,
+    limit: usize,
+) -> Result<ProcLogTail, ProcError> {
+    let sup = supervisor();
+    let entry = sup
+        .procs
+        .get(&key(project, workspace, name))
+        .ok_or_else(|| ProcError::NotFound(name.to_string()))?;
+    let skip = entry.logs.len().saturating_sub(limit);
+    Ok(ProcLogTail {
+        lines: entry.logs.iter().skip(skip).cloned().collect(),
+        dropped_lines: entry.dropped_lines + skip as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::oneshot;
+
+    #[test]
+    fn proc_names_are_validated() {
+        assert!(validate_proc_name("dev-server.1").is_ok());
+        assert!(validate_proc_name("").is_err());
+        assert!(validate_proc_name("a b").is_err());
+        assert!(validate_proc_name("../x").is_err());
+        assert!(validate_proc_name(&"x".repeat(65)).is_err());
+    }
+
+    #[tokio::test]
+    async fn proc_runs_captures_logs_and_reports_exit() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, rx) = oneshot::channel();
+        let info = start_proc(
+            "proc-test",
+            "default",
+            "echo",
+            "echo hello; echo oops >&2; exit 3",
+            dir.path(),
+            &[],
+            move |info| {
+                let _ = tx.send(info);
+            },
+        )
+        .unwrap();
+        assert_eq!(info.status, "running");
+
+        let exited = tokio::time::timeout(Duration::from_secs(10), rx)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(exited.status, "exited");
+        assert_eq!(exited.exit_code, Some(3));
+
+        // 登录 shell 的 profile 可能额外输出内容，只检查命令自身的输出
+        let logs = proc_logs("proc-test", "default", "echo", 100)
+            .unwrap()
+            .lines;
+        assert!(logs.contains(&"hello".to_string()));
+        assert!(logs.contains(&"oops".to_string()));
+        let tail = proc_logs("proc-test", "default", "echo", 1).unwrap();
+        assert_eq!(tail.lines.len(), 1);
+        assert_eq!(tail.dropped_lines, logs.len() as u64 - 1);
+
+        assert_eq!(list_procs("proc-test", "default").len(), 1);
+        assert_eq!(
+            stop_workspace_procs("proc-test", "default"),
+            Vec::<String>::new()
+        );
+        assert!(list_procs("proc-test", "default").is_empty());
+    }
+
+    #[tokio::test]
+    async fn stop_terminates_process_group() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, rx) = oneshot::channel();
+        start_proc(
+            "proc-test-stop",
+            "default",
+            "sleeper",
+            "sleep 30",
+            dir.path(),
+            &[],
+            move |info| {
+                let _ = tx.send(info);
+            },
+        )
+        .unwrap();
+        let again = start_proc(
+            "proc-test-stop",
+            "default",
+            "sleeper",
+            "sleep 30",
+            dir.path(),
+            &[],
+            |_| {},
+        );
+        assert!(matches!(again, Err(ProcError::AlreadyRunning(_))));
+
+        let stopping = stop_proc("proc-test-stop", "default", "sleeper").unwrap();
+        assert_eq!(stopping.status, "stopping");
+        let stopped = tokio::time::timeout(Duration::from_secs(10), rx)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stopped.status, "stopped");
+        assert!(matches!(
+            stop_proc("proc-test-stop", "default", "missing"),
+            Err(ProcError::NotFound(_))
+        ));
+        stop_project_procs("proc-test-stop");
+    }
+}
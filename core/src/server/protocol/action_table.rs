@@ -5,6 +5,8 @@
 
 pub const EXACT_RULES: &[(&str, &str)] = &[
     ("system", "ping"),
+    ("system", "host_power_event"),
+    ("system", "cancel"),
     ("terminal", "spawn_terminal"),
     ("terminal", "kill_terminal"),
     ("terminal", "input"),
@@ -16,7 +18,14 @@ pub const EXACT_RULES: &[(&str, &str)] = &[
     ("project", "export_template"),
     ("project", "import_template"),
     ("project", "templates"),
+    ("project", "rename_workspace"),
+    ("project", "apply_workspace_manifest"),
+    ("project", "repair_workspace"),
+    ("project", "set_project_default_branch"),
+    ("project", "link_workspace_issue"),
     ("node", "node_refresh_network"),
+    ("health", "restore_state_backup"),
+    ("health", "get_audit_log"),
 ];
 
 pub const PREFIX_RULES: &[(&str, &str)] = &[
@@ -35,6 +44,7 @@ pub const PREFIX_RULES: &[(&str, &str)] = &[
     ("project", "run_project_command"),
     ("project", "cancel_project_command"),
     ("project", "template_"),
+    ("project", "proc_"),
     ("node", "node_"),
     ("ai", "ai_"),
     ("evolution", "evo_"),
@@ -30,6 +30,10 @@
 
 use serde::{Deserialize, Serialize};
 
+fn default_file_encoding() -> String {
+    "utf8".to_string()
+}
+
 /// 文件工作区相位：描述某个 `(project, workspace)` 的文件子系统聚合就绪状态。
 ///
 /// Core 运行时按 `(project, workspace)` 键维护实例，
@@ -150,6 +154,17 @@ pub struct FileContentSearchMatchRange {
     pub end: u32,
 }
 
+/// Quick Open 模糊匹配：单条结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileQueryMatch {
+    /// 文件相对路径（相对于工作区根目录）
+    pub path: String,
+    /// 匹配得分，越高越相关
+    pub score: i64,
+    /// 命中字符在 `path` 中的位置（按字符计数，0-based），用于高亮
+    pub positions: Vec<u32>,
+}
+
 /// 文件内容搜索：单条匹配结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileContentSearchItem {
@@ -171,6 +186,99 @@ pub struct FileContentSearchItem {
     pub after_context: Vec<String>,
 }
 
+/// 语法高亮样式
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileHighlightStyle {
+    /// 前景色（`#rrggbb` 或 `#rrggbbaa`）
+    pub foreground: String,
+    #[serde(default)]
+    pub bold: bool,
+    #[serde(default)]
+    pub italic: bool,
+    #[serde(default)]
+    pub underline: bool,
+}
+
+/// 语法高亮区间：行内起始位置与长度（按 Unicode 字符计数，不含换行符）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileHighlightSpan {
+    pub start: u32,
+    pub length: u32,
+    /// `styles` 中的下标
+    pub style: u32,
+}
+
+/// 单个文件生效的 EditorConfig 设置（未声明的属性省略）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileEditorConfigSettings {
+    /// "space" | "tab"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indent_style: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indent_size: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_width: Option<u32>,
+    /// "lf" | "crlf" | "cr"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_of_line: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub charset: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trim_trailing_whitespace: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub insert_final_newline: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_line_length: Option<u32>,
+}
+
+/// 查找替换预览：单条匹配
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileReplaceMatch {
+    /// 变更集内唯一编号，`file_apply_replace` 按此勾选
+    pub id: u32,
+    /// 文件相对路径（相对于工作区根目录）
+    pub path: String,
+    /// 匹配所在行号（1-based）
+    pub line: u32,
+    /// 行内起始字节偏移（0-based）
+    pub start: u32,
+    /// 行内结束字节偏移（不含）
+    pub end: u32,
+    /// 匹配行预览文本
+    pub preview: String,
+    /// 命中的原文
+    pub matched: String,
+    /// 替换后的文本（正则模式已展开捕获组）
+    pub replacement: String,
+}
+
+/// 增量编辑：用 `new_text` 替换 `[start_line, end_line]` 行（1-based，含两端）
+///
+/// `new_text` 包含行尾换行符；`end_line = start_line - 1` 表示在 `start_line` 之前插入。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileLineEdit {
+    pub start_line: u32,
+    pub end_line: u32,
+    pub new_text: String,
+}
+
+/// 跳转定义启发式回退：单条候选定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDefinitionCandidate {
+    /// 文件相对路径（相对于工作区根目录）
+    pub path: String,
+    /// 定义所在行号（1-based）
+    pub line: u32,
+    /// 符号在该行的列号（0-based）
+    pub column: u32,
+    /// 定义种类："type" | "function" | "variable"
+    pub kind: String,
+    /// 定义行预览文本
+    pub preview: String,
+    /// 可能性得分，越高越靠前
+    pub score: i32,
+}
+
 /// 文件内容搜索：完整搜索结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileContentSearchResult {
@@ -202,6 +310,19 @@ pub enum FileRequest {
         project: String,
         workspace: String,
         path: String,
+        /// 读取编码："utf8"（默认，非 UTF-8 自动回退为二进制）或 "binary"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        encoding: Option<String>,
+        /// 超出大小上限的文本文件以只读预览方式返回前若干行
+        #[serde(default)]
+        preview: bool,
+    },
+    /// 读取文件在指定修订版本（分支、标签、SHA、`HEAD~1` 等）中的内容
+    FileReadAtRevision {
+        project: String,
+        workspace: String,
+        path: String,
+        rev: String,
     },
     FileWrite {
         project: String,
@@ -209,6 +330,28 @@ pub enum FileRequest {
         path: String,
         #[serde(with = "serde_bytes")]
         content: Vec<u8>,
+        /// 写入编码："utf8"（默认，校验 UTF-8）或 "binary"（原样写入字节）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        encoding: Option<String>,
+        /// 读取时的修改时间（Unix 毫秒）；磁盘上的文件已变化时拒绝写入
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expected_mtime: Option<u64>,
+        /// 读取时的内容哈希；提供时优先于 `expected_mtime`，按内容判断是否变化
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expected_hash: Option<String>,
+    },
+    /// 按行范围增量编辑文件，只提交改动的行；编辑均基于读取时的内容，互不重叠
+    FileApplyEdit {
+        project: String,
+        workspace: String,
+        path: String,
+        edits: Vec<FileLineEdit>,
+        /// 读取时的修改时间（Unix 毫秒）；磁盘上的文件已变化时拒绝写入
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expected_mtime: Option<u64>,
+        /// 读取时的内容哈希；提供时优先于 `expected_mtime`，按内容判断是否变化
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expected_hash: Option<String>,
     },
     FileIndex {
         project: String,
@@ -216,6 +359,57 @@ pub enum FileRequest {
         #[serde(skip_serializing_if = "Option::is_none")]
         query: Option<String>,
     },
+    FileQuery {
+        project: String,
+        workspace: String,
+        query: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<usize>,
+    },
+    /// 按字节区间读取文件；`stream = true` 时连续推送多个 `file_chunk` 直到区间结束
+    FileReadChunked {
+        project: String,
+        workspace: String,
+        path: String,
+        #[serde(default)]
+        offset: u64,
+        /// 读取长度；省略时单次读取为一个默认块，流式读取到文件末尾
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        length: Option<u64>,
+        #[serde(default)]
+        stream: bool,
+        /// 流式读取的块大小（默认 256KB，上限 1MB）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        chunk_size: Option<u64>,
+    },
+    /// 跳转定义启发式回退（LSP 接入前的基础导航）
+    FileDefinitionGuess {
+        project: String,
+        workspace: String,
+        symbol: String,
+        /// 发起跳转的文件（用于语言识别与相关性排序）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+    },
+    /// 解析文件生效的 EditorConfig 设置
+    #[serde(rename = "file_editorconfig")]
+    FileEditorConfig {
+        project: String,
+        workspace: String,
+        path: String,
+    },
+    /// 语法高亮：返回样式区间（或 HTML），客户端无需内置语法定义
+    FileHighlight {
+        project: String,
+        workspace: String,
+        path: String,
+        /// 主题名称（默认 `base16-ocean.dark`）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        theme: Option<String>,
+        /// "spans"（默认）| "html"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+    },
     FileRename {
         project: String,
         workspace: String,
@@ -227,6 +421,39 @@ pub enum FileRequest {
         workspace: String,
         path: String,
     },
+    /// 创建目录（含缺失的父目录）
+    FileMkdir {
+        project: String,
+        workspace: String,
+        path: String,
+    },
+    /// 工作区查找替换：计算全部匹配并返回预览
+    FilePrepareReplace {
+        project: String,
+        workspace: String,
+        query: String,
+        replacement: String,
+        #[serde(default)]
+        regex: bool,
+        #[serde(default)]
+        case_sensitive: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        include_globs: Vec<String>,
+    },
+    /// 应用预览中被勾选的匹配；省略 accepted_matches 表示全部应用
+    FileApplyReplace {
+        project: String,
+        workspace: String,
+        change_set_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        accepted_matches: Option<Vec<u32>>,
+    },
+    /// 撤销一次已应用的查找替换
+    FileUndoReplace {
+        project: String,
+        workspace: String,
+        undo_id: String,
+    },
     FileCopy {
         dest_project: String,
         dest_workspace: String,
@@ -244,6 +471,17 @@ pub enum FileRequest {
         workspace: String,
     },
     WatchUnsubscribe,
+    /// 监控单个文件的磁盘变化（外部编辑检测），变化时推送 `file_watch_changed`
+    FileWatch {
+        project: String,
+        workspace: String,
+        path: String,
+    },
+    FileUnwatch {
+        project: String,
+        workspace: String,
+        path: String,
+    },
 }
 
 /// 文件相关的服务端消息
@@ -263,6 +501,48 @@ pub enum FileResponse {
         #[serde(with = "serde_bytes")]
         content: Vec<u8>,
         size: u64,
+        /// 实际返回的内容编码："utf8" 或 "binary"
+        #[serde(default = "default_file_encoding")]
+        encoding: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+        /// 文本文件当前的换行符："lf" | "crlf" | "cr" | "mixed"（二进制或无换行时省略）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        line_ending: Option<String>,
+        /// 非 UTF-8 文本转码前的原始字符集（如 "GBK"）；写回时作为 `encoding` 传入
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        charset: Option<String>,
+        /// 预览模式下内容被截断（只读，`size` 仍为完整文件大小）
+        #[serde(default)]
+        truncated: bool,
+        /// 修改时间（Unix 毫秒）；远程项目省略。写回时作为 `expected_mtime` 传入
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mtime: Option<u64>,
+        /// 磁盘内容哈希（git blob SHA-1）；预览截断时省略。写回时作为 `expected_hash` 传入
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hash: Option<String>,
+    },
+    FileReadAtRevisionResult {
+        project: String,
+        workspace: String,
+        path: String,
+        /// 回显请求的修订
+        rev: String,
+        /// 修订解析出的完整提交 SHA
+        sha: String,
+        #[serde(with = "serde_bytes")]
+        content: Vec<u8>,
+        size: u64,
+        /// 实际返回的内容编码："utf8" 或 "binary"
+        #[serde(default = "default_file_encoding")]
+        encoding: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        line_ending: Option<String>,
+        /// 非 UTF-8 文本转码前的原始字符集（如 "GBK"）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        charset: Option<String>,
     },
     FileWriteResult {
         project: String,
@@ -270,12 +550,128 @@ pub enum FileResponse {
         path: String,
         success: bool,
         size: u64,
+        /// 写入后的修改时间（Unix 毫秒），供下一次写入的 `expected_mtime` 使用
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mtime: Option<u64>,
+        /// 写入后的内容哈希，供下一次写入的 `expected_hash` 使用
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hash: Option<String>,
+    },
+    FileApplyEditResult {
+        project: String,
+        workspace: String,
+        path: String,
+        success: bool,
+        size: u64,
+        /// 写入后的修改时间（Unix 毫秒），供下一次编辑的 `expected_mtime` 使用
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mtime: Option<u64>,
+        /// 写入后的内容哈希，供下一次编辑的 `expected_hash` 使用
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hash: Option<String>,
+    },
+    /// `file_write` / `file_apply_edit` 的版本校验失败：文件在客户端读取后已被修改，未写入
+    FileConflict {
+        project: String,
+        workspace: String,
+        path: String,
+        /// 当前磁盘内容哈希；文件已被删除时省略
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        current_hash: Option<String>,
+        /// 当前修改时间（Unix 毫秒）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        current_mtime: Option<u64>,
     },
     FileIndexResult {
         project: String,
         workspace: String,
         items: Vec<String>,
         truncated: bool,
+        #[serde(default)]
+        version: u64,
+    },
+    FileQueryResult {
+        project: String,
+        workspace: String,
+        query: String,
+        items: Vec<FileQueryMatch>,
+        total_matches: u32,
+        truncated: bool,
+        #[serde(default)]
+        version: u64,
+    },
+    FileChunk {
+        project: String,
+        workspace: String,
+        path: String,
+        offset: u64,
+        #[serde(with = "serde_bytes")]
+        content: Vec<u8>,
+        total_size: u64,
+        /// 本块是否已读到文件末尾
+        eof: bool,
+        /// 是否为本次请求的最后一块
+        last: bool,
+    },
+    FileDefinitionGuessResult {
+        project: String,
+        workspace: String,
+        symbol: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+        candidates: Vec<FileDefinitionCandidate>,
+        truncated: bool,
+    },
+    #[serde(rename = "file_editorconfig_result")]
+    FileEditorConfigResult {
+        project: String,
+        workspace: String,
+        path: String,
+        settings: FileEditorConfigSettings,
+        /// 参与合并的 `.editorconfig`（相对工作区根，由远及近）
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        sources: Vec<String>,
+        /// 项目是否开启写入时应用（`.tidyflow.toml` `[editor] apply_editorconfig`）
+        apply_on_write: bool,
+    },
+    FileHighlightResult {
+        project: String,
+        workspace: String,
+        path: String,
+        /// 内容哈希（与 `file_read_result.hash` 一致），同一哈希的结果可复用
+        hash: String,
+        /// 识别出的语法名称
+        syntax: String,
+        theme: String,
+        /// 主题背景色 / 默认前景色
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        background: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        foreground: Option<String>,
+        /// 样式表（`spans` 格式），`lines` 中的区间按下标引用
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        styles: Vec<FileHighlightStyle>,
+        /// 按行的样式区间（`spans` 格式）
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        lines: Vec<Vec<FileHighlightSpan>>,
+        /// `html` 格式的 `<pre>` 片段
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        html: Option<String>,
+        /// 是否命中服务端高亮缓存
+        cached: bool,
+    },
+    FileIndexDelta {
+        project: String,
+        workspace: String,
+        base_version: u64,
+        version: u64,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        added: Vec<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        removed: Vec<String>,
+        truncated: bool,
+        #[serde(default)]
+        reset: bool,
     },
     FileRenameResult {
         project: String,
@@ -294,6 +690,48 @@ pub enum FileResponse {
         #[serde(skip_serializing_if = "Option::is_none")]
         message: Option<String>,
     },
+    FileMkdirResult {
+        project: String,
+        workspace: String,
+        path: String,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    FilePrepareReplaceResult {
+        project: String,
+        workspace: String,
+        change_set_id: String,
+        matches: Vec<FileReplaceMatch>,
+        /// 涉及的文件数
+        file_count: u32,
+        /// 匹配数超过上限时为 true（仅预览中的匹配可被应用）
+        truncated: bool,
+    },
+    FileApplyReplaceResult {
+        project: String,
+        workspace: String,
+        change_set_id: String,
+        success: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        files_changed: Vec<String>,
+        replacements: u32,
+        /// 撤销凭据，传给 `file_undo_replace`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        undo_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    FileUndoReplaceResult {
+        project: String,
+        workspace: String,
+        undo_id: String,
+        success: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        files_restored: Vec<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
     FileCopyResult {
         project: String,
         workspace: String,
@@ -323,4 +761,27 @@ pub enum FileResponse {
         paths: Vec<String>,
         kind: String,
     },
+    FileWatched {
+        project: String,
+        workspace: String,
+        path: String,
+    },
+    FileUnwatched {
+        project: String,
+        workspace: String,
+        path: String,
+    },
+    /// 被 `file_watch` 监控的文件在磁盘上发生变化
+    FileWatchChanged {
+        project: String,
+        workspace: String,
+        path: String,
+        /// 修改时间（Unix 毫秒）；文件被删除时为空
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mtime: Option<u64>,
+        /// 文件大小（字节）；文件被删除时为空
+        #[serde(skip_serializing_if = "Option::is_none")]
+        size: Option<u64>,
+        deleted: bool,
+    },
 }
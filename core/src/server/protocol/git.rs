@@ -19,6 +19,21 @@ pub enum GitRequest {
     GitStatus {
         project: String,
         workspace: String,
+        /// 未跟踪文件的展示方式：normal（默认，目录折叠为一条）| all（等价于 `-uall`）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        untracked_files: Option<String>,
+        /// 需要按需展开的未跟踪目录
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        expand_untracked: Vec<String>,
+    },
+    /// 批量查询项目下所有工作区（含 default）的 Git 状态摘要，供工作区列表使用
+    GitStatusAll {
+        project: String,
+    },
+    /// 按目录聚合的变更计数，供文件浏览器显示目录角标
+    GitStatusTree {
+        project: String,
+        workspace: String,
     },
     GitDiff {
         project: String,
@@ -28,6 +43,42 @@ pub enum GitRequest {
         base: Option<String>,
         #[serde(default = "default_diff_mode")]
         mode: String,
+        /// diff 算法：myers / minimal / patience / histogram；省略时使用项目默认
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        algorithm: Option<String>,
+        /// 提交区间（`base..head` / `base...head`）；设置后忽略 base/mode，对比区间内该文件
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        range: Option<String>,
+        /// 输出格式："unified"（默认，仅 text）| "structured"（额外返回 hunks）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+        /// 为成对的删除/新增行计算行内高亮（隐含 format = "structured"）
+        #[serde(default)]
+        intraline: bool,
+        /// 对锁文件 / notebook 使用降噪 diff，并尊重 `.gitattributes` 中的 diff 驱动
+        #[serde(default)]
+        smart_diff: bool,
+        /// 二进制文件在 `old_blob` / `new_blob` 中附带不超过 1MB 的原始内容
+        #[serde(default)]
+        include_blobs: bool,
+    },
+    /// 读取大 diff 的后续页：参数同 `git_diff`，从 `offset` 字节处继续
+    GitDiffPage {
+        project: String,
+        workspace: String,
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        base: Option<String>,
+        #[serde(default = "default_diff_mode")]
+        mode: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        algorithm: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        range: Option<String>,
+        #[serde(default)]
+        smart_diff: bool,
+        /// 上一页返回的 `next_offset`
+        offset: u64,
     },
     GitStage {
         project: String,
@@ -63,6 +114,9 @@ pub enum GitRequest {
         project: String,
         workspace: String,
         branch: String,
+        /// 分支已被其他 worktree 检出时，先分离对方的 HEAD 再切换
+        #[serde(default)]
+        force_detach: bool,
     },
     GitCreateBranch {
         project: String,
@@ -73,6 +127,9 @@ pub enum GitRequest {
         project: String,
         workspace: String,
         message: String,
+        /// 覆盖仓库的 `commit.gpgsign`：true 强制签名，false 不签名，省略沿用配置
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sign: Option<bool>,
     },
     GitFetch {
         project: String,
@@ -91,56 +148,208 @@ pub enum GitRequest {
         project: String,
         workspace: String,
     },
+    /// 列出 `base..HEAD` 上可供交互式整理的提交
+    GitRebaseInteractivePlan {
+        project: String,
+        workspace: String,
+        base: String,
+    },
+    /// 按有序动作列表执行交互式 rebase，结果通过 `git_rebase_result` 返回
+    GitRebaseInteractiveExecute {
+        project: String,
+        workspace: String,
+        base: String,
+        plan: Vec<super::GitRebasePlanStepInfo>,
+    },
     GitOpStatus {
         project: String,
         workspace: String,
     },
+    /// 取消进行中的长耗时 git 操作（fetch / rebase / 克隆），`op_id` 取自进度消息
+    GitOpCancel {
+        op_id: String,
+    },
     GitEnsureIntegrationWorktree {
         project: String,
+        /// 集成目标分支；省略时为项目默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
     },
     GitMergeToDefault {
         project: String,
         workspace: String,
-        default_branch: String,
+        /// 省略时使用项目配置的默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default_branch: Option<String>,
+        /// 合并成功后在集成 worktree 中运行 `[git] merge_verify_command`
+        #[serde(default)]
+        verify: bool,
     },
     GitMergeContinue {
         project: String,
+        /// 集成目标分支；省略时为项目默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
     },
     GitMergeAbort {
         project: String,
+        /// 集成目标分支；省略时为项目默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
     },
     GitIntegrationStatus {
         project: String,
+        /// 集成目标分支；省略时为项目默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
+    },
+    /// 仓库统计与维护状态（对象数、pack 大小、历史大文件、提交数、最近 gc 时间）
+    GitRepoStats {
+        project: String,
+    },
+    /// 检测历史中超过阈值的 blob，可附带 `git filter-repo` 重写计划（仅生成命令，不执行）
+    GitDetectLargeBlobs {
+        project: String,
+        /// 字节阈值，省略时为 1 MiB
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        threshold: Option<u64>,
+        #[serde(default)]
+        filter_repo_plan: bool,
     },
     GitRebaseOntoDefault {
         project: String,
         workspace: String,
-        default_branch: String,
+        /// 省略时使用项目配置的默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default_branch: Option<String>,
     },
     GitRebaseOntoDefaultContinue {
         project: String,
+        /// 集成目标分支；省略时为项目默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
     },
     GitRebaseOntoDefaultAbort {
         project: String,
+        /// 集成目标分支；省略时为项目默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
     },
     GitResetIntegrationWorktree {
         project: String,
+        /// 集成目标分支；省略时为项目默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
     },
     GitCheckBranchUpToDate {
         project: String,
         workspace: String,
     },
+    /// 推送当前分支并在 GitHub / GitLab 上创建 PR（MR），结果为 `git_create_pull_request_result`
+    GitCreatePullRequest {
+        project: String,
+        workspace: String,
+        title: String,
+        #[serde(default)]
+        body: Option<String>,
+        /// 目标分支；省略时为项目默认分支
+        #[serde(default)]
+        target_branch: Option<String>,
+        #[serde(default)]
+        draft: bool,
+    },
+    /// 项目各工作空间分支上打开的 PR（含可合并性与 CI 检查）
+    GitListPullRequests {
+        project: String,
+    },
+    /// 单个 PR 的状态
+    GitPullRequestStatus {
+        project: String,
+        number: u64,
+    },
     GitLog {
         project: String,
         workspace: String,
         #[serde(default = "default_git_log_limit")]
         limit: usize,
+        /// 跳过前 N 条匹配的提交
+        #[serde(default)]
+        skip: usize,
+        /// 游标分页：返回该提交之前（不含）的历史
+        #[serde(default)]
+        before_sha: Option<String>,
+        #[serde(default)]
+        author: Option<String>,
+        #[serde(default)]
+        path: Option<String>,
+        #[serde(default)]
+        grep: Option<String>,
+        #[serde(default)]
+        since: Option<String>,
+        #[serde(default)]
+        until: Option<String>,
+        #[serde(default)]
+        branch: Option<String>,
     },
     GitShow {
         project: String,
         workspace: String,
         sha: String,
     },
+    /// 基于工作区上下文（关联链接、分支名）、暂存区摘要与 `[git] commit_template` 生成提交信息建议
+    GitSuggestedCommitMessage {
+        project: String,
+        workspace: String,
+        /// 工作区关联的 issue / PR 链接，用于提取 issue 编号
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        link_url: Option<String>,
+        /// 为 true 时由 `[git] commit_message_command` 生成 message
+        #[serde(default)]
+        generate: bool,
+    },
+    /// 暂存区 diff 交给 `[git] commit_message_command` / `commit_message_endpoint` 生成提交信息，
+    /// 响应 `git_suggested_commit_message_result`
+    GitGenerateCommitMessage {
+        project: String,
+        workspace: String,
+        /// 省略时使用工作区关联的 issue
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        link_url: Option<String>,
+    },
+    /// 提交内单个文件的 diff（相对第一父提交），供提交详情展开
+    GitShowFileDiff {
+        project: String,
+        workspace: String,
+        sha: String,
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        algorithm: Option<String>,
+        /// 输出格式："unified"（默认）| "structured"（额外返回 hunks）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+        #[serde(default)]
+        intraline: bool,
+    },
+    GitBlame {
+        project: String,
+        workspace: String,
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rev: Option<String>,
+    },
+    /// 提交区间 diff 摘要（可限定目录），单文件补丁通过 `git_diff.range` 按需获取
+    GitDiffRange {
+        project: String,
+        workspace: String,
+        range: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+    },
+    /// 生成分支相对默认分支的变更报告（自包含 HTML，保存在数据目录供下载）
+    GitGenerateChangeReport {
+        project: String,
+        workspace: String,
+    },
     // v1.40: 冲突向导
     /// 读取单个冲突文件的四路对比内容
     GitConflictDetail {
@@ -178,6 +387,18 @@ pub enum GitRequest {
         path: String,
         context: String,
     },
+    /// 按指定方式解决冲突并暂存
+    GitConflictResolve {
+        project: String,
+        workspace: String,
+        path: String,
+        context: String,
+        /// ours | theirs | both | custom
+        resolution: String,
+        /// resolution = custom 时写入的完整文件内容
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content: Option<String>,
+    },
 
     // v1.50: Git stash 操作
     GitStashList {
@@ -280,6 +501,20 @@ pub enum GitResponse {
         behind_by: Option<i32>,
         #[serde(skip_serializing_if = "Option::is_none")]
         compared_branch: Option<String>,
+        /// 展开未跟踪目录时触达文件数上限，部分目录仍保持折叠
+        #[serde(default)]
+        untracked_truncated: bool,
+    },
+    /// 项目下各工作区的 Git 状态摘要（default 在前，其余按名称排序）
+    GitStatusAllResult {
+        project: String,
+        workspaces: Vec<super::GitWorkspaceStatusSummary>,
+    },
+    /// 含变更的目录及其（含子目录的）变更计数，按路径排序；根目录的 `path` 为空串
+    GitStatusTreeResult {
+        project: String,
+        workspace: String,
+        directories: Vec<super::GitStatusDirectoryInfo>,
     },
     GitDiffResult {
         project: String,
@@ -293,6 +528,39 @@ pub enum GitResponse {
         mode: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         base: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        range: Option<String>,
+        /// `format = "structured"` 时返回解析后的 hunk
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hunks: Option<Vec<super::GitDiffHunkInfo>>,
+        /// 实际应用的智能 diff：gitattributes | lockfile | notebook
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        smart_diff: Option<String>,
+        /// `truncated` 时下一页的起始字节偏移，用于 `git_diff_page`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        next_offset: Option<u64>,
+        /// 二进制文件的旧版本信息；新增文件省略
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        old_blob: Option<super::GitBinaryBlobInfo>,
+        /// 二进制文件的新版本信息；删除文件省略
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        new_blob: Option<super::GitBinaryBlobInfo>,
+    },
+    /// `git_diff_page` 的结果：`text` 为从 `offset` 开始的一页 unified diff
+    GitDiffPageResult {
+        project: String,
+        workspace: String,
+        path: String,
+        mode: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        base: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        range: Option<String>,
+        offset: u64,
+        text: String,
+        truncated: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        next_offset: Option<u64>,
     },
     GitOpResult {
         project: String,
@@ -305,6 +573,24 @@ pub enum GitResponse {
         path: Option<String>,
         scope: String,
     },
+    /// 长耗时 git 操作的进度；开始时先推送一条 `phase = "started"`
+    GitOpProgress {
+        op_id: String,
+        /// `fetch` / `rebase`
+        op: String,
+        project: String,
+        workspace: String,
+        /// 阶段：`started` / `receiving_objects` / `resolving_deltas` / `rebasing` 等
+        phase: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        percent: Option<u8>,
+        message: String,
+    },
+    GitOpCancelResult {
+        op_id: String,
+        /// 是否找到进行中的操作
+        cancelled: bool,
+    },
     GitBranchesResult {
         project: String,
         workspace: String,
@@ -319,6 +605,13 @@ pub enum GitResponse {
         message: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         sha: Option<String>,
+        /// 新提交是否带签名
+        #[serde(default)]
+        signed: bool,
+        /// 失败分类：`empty_message` / `nothing_staged` / `identity_missing` / `hook_failed` /
+        /// `signing_failed` / `commit_failed`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error_code: Option<String>,
     },
     GitRebaseResult {
         project: String,
@@ -376,6 +669,56 @@ pub enum GitResponse {
         #[serde(skip_serializing_if = "Option::is_none")]
         integration_path: Option<String>,
     },
+    /// 合并后校验命令的输出（逐行）
+    GitMergeVerifyOutput {
+        project: String,
+        task_id: String,
+        line: String,
+    },
+    /// 合并后校验结论
+    GitMergeVerifyResult {
+        project: String,
+        task_id: String,
+        ok: bool,
+        /// 执行的校验命令；未配置时为空
+        command: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        exit_code: Option<i32>,
+        /// 失败原因或输出末尾摘要
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        integration_path: String,
+        duration_ms: u64,
+    },
+    GitCreatePullRequestResult {
+        project: String,
+        workspace: String,
+        ok: bool,
+        /// 托管平台：`github` / `gitlab`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        provider: Option<String>,
+        /// 源分支；识别失败时为空
+        branch: String,
+        target_branch: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        url: Option<String>,
+        /// PR 编号（GitLab 为项目内 `iid`）
+        #[serde(skip_serializing_if = "Option::is_none")]
+        number: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    GitPullRequestsResult {
+        project: String,
+        /// 托管平台：`github` / `gitlab`
+        provider: String,
+        items: Vec<super::GitPullRequestInfo>,
+    },
+    GitPullRequestStatusResult {
+        project: String,
+        provider: String,
+        pull_request: super::GitPullRequestInfo,
+    },
     GitIntegrationStatusResult {
         project: String,
         state: String,
@@ -396,6 +739,44 @@ pub enum GitResponse {
         #[serde(skip_serializing_if = "Option::is_none")]
         compared_branch: Option<String>,
     },
+    /// 仓库统计与维护状态
+    GitRepoStatsResult {
+        project: String,
+        /// 所有引用可达的提交数
+        commit_count: u64,
+        loose_objects: u64,
+        loose_size_bytes: u64,
+        packed_objects: u64,
+        pack_count: u64,
+        pack_size_bytes: u64,
+        prune_packable: u64,
+        garbage_count: u64,
+        garbage_size_bytes: u64,
+        /// 历史中最大的文件（按未压缩大小降序）
+        largest_files: Vec<super::GitRepoLargeFileInfo>,
+        /// 最近一次 gc 的估计时间（Unix 秒）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        last_gc_at: Option<i64>,
+        /// 上次自动 gc 失败时的 `gc.log` 内容
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        gc_log: Option<String>,
+        maintenance_registered: bool,
+        /// 维护建议：`gc` | `prune_garbage` | `gc_failed` | `register_maintenance`
+        #[serde(default)]
+        suggestions: Vec<String>,
+    },
+    /// 历史大文件检测结果
+    GitDetectLargeBlobsResult {
+        project: String,
+        threshold: u64,
+        /// 超过阈值的 blob（按大小降序，最多 200 个）
+        blobs: Vec<super::GitLargeBlobInfo>,
+        total_count: usize,
+        total_size: u64,
+        truncated: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        filter_repo: Option<super::GitFilterRepoPlanInfo>,
+    },
     GitRebaseOntoDefaultResult {
         project: String,
         ok: bool,
@@ -424,6 +805,9 @@ pub enum GitResponse {
         project: String,
         workspace: String,
         entries: Vec<super::GitLogEntryInfo>,
+        /// 是否还有更早的提交
+        #[serde(default)]
+        has_more: bool,
     },
     GitShowResult {
         project: String,
@@ -436,10 +820,113 @@ pub enum GitResponse {
         date: String,
         files: Vec<super::GitShowFileInfo>,
     },
+    /// 提交信息建议
+    GitSuggestedCommitMessageResult {
+        project: String,
+        workspace: String,
+        /// 建议的提交信息：模板渲染（仅首行）或外部命令 / 端点生成
+        message: String,
+        /// `template` | `command` | `endpoint`
+        source: String,
+        /// 是否配置了外部生成命令（客户端据此显示“生成提交信息”）
+        generator_available: bool,
+        /// 实际使用的模板
+        template: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        issue_id: Option<String>,
+        commit_type: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        scope: Option<String>,
+        summary: String,
+        branch: String,
+        staged_files: usize,
+    },
+    /// 提交内单个文件的 diff
+    GitShowFileDiffResult {
+        project: String,
+        workspace: String,
+        /// 完整 SHA
+        sha: String,
+        path: String,
+        /// 重命名/复制前的路径
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        old_path: Option<String>,
+        /// 该文件在提交中的状态码：A/M/D/R/C
+        code: String,
+        format: String,
+        text: String,
+        is_binary: bool,
+        truncated: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hunks: Option<Vec<super::GitDiffHunkInfo>>,
+    },
+    GitRebaseInteractivePlanResult {
+        project: String,
+        workspace: String,
+        base: String,
+        base_sha: String,
+        /// 按应用顺序（旧 → 新）排列
+        commits: Vec<super::GitRebasePlanCommitInfo>,
+    },
+    GitBlameResult {
+        project: String,
+        workspace: String,
+        path: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        rev: Option<String>,
+        hunks: Vec<super::GitBlameHunkInfo>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        ignore_revs_files: Vec<String>,
+    },
+    GitDiffRangeResult {
+        project: String,
+        workspace: String,
+        /// 规范化后的区间（如 `main...HEAD`）
+        range: String,
+        base: String,
+        head: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        merge_base: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+        files: Vec<super::GitRangeDiffFileInfo>,
+        total_additions: i32,
+        total_deletions: i32,
+        truncated: bool,
+    },
+    /// 变更报告已生成，通过 `download_path` 下载
+    GitChangeReportResult {
+        project: String,
+        workspace: String,
+        report_id: String,
+        base: String,
+        head: String,
+        file_count: usize,
+        total_additions: i32,
+        total_deletions: i32,
+        commit_count: usize,
+        /// 文件列表、提交列表或补丁超出上限被截断
+        truncated: bool,
+        /// 报告大小（字节）
+        size: u64,
+        /// HTTP 下载路径（`GET`，需鉴权）
+        download_path: String,
+    },
     GitStatusChanged {
         project: String,
         workspace: String,
     },
+    /// 后台定时 fetch 后的远程分歧（相对 `origin/<默认分支>`）
+    GitRemoteUpdated {
+        project: String,
+        workspace: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        current_branch: Option<String>,
+        ahead_by: i32,
+        behind_by: i32,
+        /// 比较的远程引用（如 `origin/main`）
+        compared_branch: String,
+    },
     // v1.40: 冲突向导响应
     /// 单文件冲突详情（四路对比内容）
     GitConflictDetailResult {
@@ -457,6 +944,9 @@ pub enum GitResponse {
         current_content: String,
         conflict_markers_count: usize,
         is_binary: bool,
+        /// 按冲突标记解析出的冲突区块
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        regions: Vec<super::ConflictRegionInfo>,
     },
     /// 冲突解决动作结果（含最新快照）
     GitConflictActionResult {
@@ -464,7 +954,7 @@ pub enum GitResponse {
         workspace: String,
         context: String,
         path: String,
-        /// 已执行的动作：accept_ours | accept_theirs | accept_both | mark_resolved
+        /// 已执行的动作：accept_ours | accept_theirs | accept_both | mark_resolved | resolve_custom
         action: String,
         ok: bool,
         #[serde(skip_serializing_if = "Option::is_none")]
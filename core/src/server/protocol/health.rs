@@ -37,6 +37,13 @@ impl HealthContext {
         Self::default()
     }
 
+    pub fn for_project(project: impl Into<String>) -> Self {
+        Self {
+            project: Some(project.into()),
+            ..Self::default()
+        }
+    }
+
     pub fn for_workspace(project: impl Into<String>, workspace: impl Into<String>) -> Self {
         Self {
             project: Some(project.into()),
@@ -167,6 +174,8 @@ pub enum RepairActionKind {
     RebuildWorkspaceCache,
     /// 恢复运行时订阅（remote_sub_registry 丢失连接后）
     RestoreSubscriptions,
+    /// 修复加载失败的项目：丢弃已隔离的损坏记录，保留其余数据
+    RepairProject,
 }
 
 /// 修复动作请求（由客户端或 Core 内部触发）
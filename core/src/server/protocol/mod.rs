@@ -94,11 +94,41 @@ pub enum ClientMessage {
         term_id: Option<String>,
     },
     Ping,
+    /// 客户端转发的主机休眠/唤醒通知
+    HostPowerEvent {
+        event: HostPowerEventKind,
+    },
+    /// 放弃进行中的请求（WS 包络 request_id 或 HTTP `x-tidyflow-request-id`）
+    Cancel {
+        request_id: String,
+    },
 
     // v1: Control plane - Workspace management
     ListProjects,
     ListWorkspaces {
         project: String,
+        /// 名称子串过滤（大小写不敏感）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        /// 状态过滤：ready | setup_failed | creating | initializing | destroying
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        status: Option<String>,
+        /// 分支名前缀过滤
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch_prefix: Option<String>,
+        /// 排序：name（默认，default 工作区置顶）| last_activity（最近活动在前）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sort: Option<String>,
+    },
+    /// 识别工作区根目录下的任务（npm scripts、justfile、Makefile、Cargo bin / example）
+    ListWorkspaceTasks {
+        project: String,
+        workspace: String,
+    },
+    /// 列出工作目录位于工作区内的进程所监听的 TCP 端口
+    ListPorts {
+        project: String,
+        workspace: String,
     },
     SelectWorkspace {
         project: String,
@@ -106,6 +136,12 @@ pub enum ClientMessage {
     },
     SpawnTerminal {
         cwd: String,
+        /// 终端程序（名称或绝对路径，需在白名单内）；为空时使用服务端默认 shell
+        #[serde(default)]
+        shell: Option<String>,
+        /// 终端程序启动参数
+        #[serde(default)]
+        args: Option<Vec<String>>,
     },
 
     // v1: Session management
@@ -126,11 +162,34 @@ pub enum ClientMessage {
         /// 客户端自定义图标标识，用于重连恢复
         #[serde(default)]
         icon: Option<String>,
+        /// 终端程序（名称或绝对路径，需在白名单内）；为空时使用服务端默认 shell
+        #[serde(default)]
+        shell: Option<String>,
+        /// 终端程序启动参数
+        #[serde(default)]
+        args: Option<Vec<String>>,
+        /// 是否开启保活；为空时使用客户端设置 `terminal_keep_alive`
+        #[serde(default)]
+        keep_alive: Option<bool>,
     },
     TermList,
     TermClose {
         term_id: String,
     },
+    /// 开启或关闭终端保活
+    TermSetKeepAlive {
+        term_id: String,
+        keep_alive: bool,
+    },
+    /// 重命名终端；空字符串表示清除自定义名称
+    TermRename {
+        term_id: String,
+        name: String,
+    },
+    /// 调整终端展示顺序；未列出的终端保持原有相对顺序排在其后
+    TermReorder {
+        term_ids: Vec<String>,
+    },
     TermFocus {
         term_id: String,
     },
@@ -146,6 +205,19 @@ pub enum ClientMessage {
         project: String,
         workspace: String,
         path: String,
+        /// 读取编码："utf8"（默认，非 UTF-8 自动回退为二进制）或 "binary"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        encoding: Option<String>,
+        /// 超出大小上限的文本文件以只读预览方式返回前若干行
+        #[serde(default)]
+        preview: bool,
+    },
+    /// 读取文件在指定修订版本（分支、标签、SHA、`HEAD~1` 等）中的内容
+    FileReadAtRevision {
+        project: String,
+        workspace: String,
+        path: String,
+        rev: String,
     },
     FileWrite {
         project: String,
@@ -153,6 +225,28 @@ pub enum ClientMessage {
         path: String,
         #[serde(with = "serde_bytes")]
         content: Vec<u8>,
+        /// 写入编码："utf8"（默认，校验 UTF-8）或 "binary"（原样写入字节）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        encoding: Option<String>,
+        /// 读取时的修改时间（Unix 毫秒）；磁盘上的文件已变化时拒绝写入
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expected_mtime: Option<u64>,
+        /// 读取时的内容哈希；提供时优先于 `expected_mtime`，按内容判断是否变化
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expected_hash: Option<String>,
+    },
+    /// 按行范围增量编辑文件，只提交改动的行；编辑均基于读取时的内容，互不重叠
+    FileApplyEdit {
+        project: String,
+        workspace: String,
+        path: String,
+        edits: Vec<file::FileLineEdit>,
+        /// 读取时的修改时间（Unix 毫秒）；磁盘上的文件已变化时拒绝写入
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expected_mtime: Option<u64>,
+        /// 读取时的内容哈希；提供时优先于 `expected_mtime`，按内容判断是否变化
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expected_hash: Option<String>,
     },
 
     // v1.4: File index for Quick Open
@@ -162,11 +256,79 @@ pub enum ClientMessage {
         #[serde(skip_serializing_if = "Option::is_none")]
         query: Option<String>,
     },
+    /// Quick Open 服务端模糊匹配：只返回得分最高的若干条，不下发完整索引
+    FileQuery {
+        project: String,
+        workspace: String,
+        query: String,
+        /// 返回条数（默认 50，上限 500）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<usize>,
+    },
+    /// 按字节区间读取文件；`stream = true` 时连续推送多个 `file_chunk` 直到区间结束
+    FileReadChunked {
+        project: String,
+        workspace: String,
+        path: String,
+        #[serde(default)]
+        offset: u64,
+        /// 读取长度；省略时单次读取为一个默认块，流式读取到文件末尾
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        length: Option<u64>,
+        #[serde(default)]
+        stream: bool,
+        /// 流式读取的块大小（默认 256KB，上限 1MB）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        chunk_size: Option<u64>,
+    },
+    /// 跳转定义启发式回退（LSP 接入前的基础导航）
+    FileDefinitionGuess {
+        project: String,
+        workspace: String,
+        symbol: String,
+        /// 发起跳转的文件（用于语言识别与相关性排序）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+    },
+    /// 解析文件生效的 EditorConfig 设置
+    #[serde(rename = "file_editorconfig")]
+    FileEditorConfig {
+        project: String,
+        workspace: String,
+        path: String,
+    },
+    /// 语法高亮：返回样式区间（或 HTML），客户端无需内置语法定义
+    FileHighlight {
+        project: String,
+        workspace: String,
+        path: String,
+        /// 主题名称（默认 `base16-ocean.dark`）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        theme: Option<String>,
+        /// "spans"（默认）| "html"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+    },
 
     // v1.5: Git tools
     GitStatus {
         project: String,
         workspace: String,
+        /// 未跟踪文件的展示方式：normal（默认，目录折叠为一条）| all（等价于 `-uall`）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        untracked_files: Option<String>,
+        /// 需要按需展开的未跟踪目录
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        expand_untracked: Vec<String>,
+    },
+    /// 批量查询项目下所有工作区（含 default）的 Git 状态摘要，供工作区列表使用
+    GitStatusAll {
+        project: String,
+    },
+    /// 按目录聚合的变更计数，供文件浏览器显示目录角标
+    GitStatusTree {
+        project: String,
+        workspace: String,
     },
     GitDiff {
         project: String,
@@ -176,6 +338,42 @@ pub enum ClientMessage {
         base: Option<String>,
         #[serde(default = "default_diff_mode")]
         mode: String, // "working" or "staged"
+        /// diff 算法：myers / minimal / patience / histogram；省略时使用项目默认
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        algorithm: Option<String>,
+        /// 提交区间（`base..head` / `base...head`）；设置后忽略 base/mode，对比区间内该文件
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        range: Option<String>,
+        /// 输出格式："unified"（默认，仅 text）| "structured"（额外返回 hunks）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+        /// 为成对的删除/新增行计算行内高亮（隐含 format = "structured"）
+        #[serde(default)]
+        intraline: bool,
+        /// 对锁文件 / notebook 使用降噪 diff，并尊重 `.gitattributes` 中的 diff 驱动
+        #[serde(default)]
+        smart_diff: bool,
+        /// 二进制文件在 `old_blob` / `new_blob` 中附带不超过 1MB 的原始内容
+        #[serde(default)]
+        include_blobs: bool,
+    },
+    /// 读取大 diff 的后续页：参数同 `git_diff`，从 `offset` 字节处继续
+    GitDiffPage {
+        project: String,
+        workspace: String,
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        base: Option<String>,
+        #[serde(default = "default_diff_mode")]
+        mode: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        algorithm: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        range: Option<String>,
+        #[serde(default)]
+        smart_diff: bool,
+        /// 上一页返回的 `next_offset`
+        offset: u64,
     },
 
     // v1.6: Git stage/unstage operations
@@ -217,6 +415,9 @@ pub enum ClientMessage {
         project: String,
         workspace: String,
         branch: String,
+        /// 分支已被其他 worktree 检出时，先分离对方的 HEAD 再切换
+        #[serde(default)]
+        force_detach: bool,
     },
     // v1.9: Git create branch
     GitCreateBranch {
@@ -229,6 +430,9 @@ pub enum ClientMessage {
         project: String,
         workspace: String,
         message: String,
+        /// 覆盖仓库的 `commit.gpgsign`：true 强制签名，false 不签名，省略沿用配置
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sign: Option<bool>,
     },
 
     // v1.11: Git rebase/fetch operations (UX-3a)
@@ -249,46 +453,104 @@ pub enum ClientMessage {
         project: String,
         workspace: String,
     },
+    /// 列出 `base..HEAD` 上可供交互式整理的提交
+    GitRebaseInteractivePlan {
+        project: String,
+        workspace: String,
+        base: String,
+    },
+    /// 按有序动作列表执行交互式 rebase，结果通过 `git_rebase_result` 返回
+    GitRebaseInteractiveExecute {
+        project: String,
+        workspace: String,
+        base: String,
+        plan: Vec<GitRebasePlanStepInfo>,
+    },
     GitOpStatus {
         project: String,
         workspace: String,
     },
+    /// 取消进行中的长耗时 git 操作（fetch / rebase / 克隆），`op_id` 取自进度消息
+    GitOpCancel {
+        op_id: String,
+    },
 
     // v1.12: Git merge to default via integration worktree (UX-3b)
     GitEnsureIntegrationWorktree {
         project: String,
+        /// 集成目标分支；省略时为项目默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
     },
     GitMergeToDefault {
         project: String,
         workspace: String,
-        default_branch: String,
+        /// 省略时使用项目配置的默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default_branch: Option<String>,
+        /// 合并成功后在集成 worktree 中运行 `[git] merge_verify_command`
+        #[serde(default)]
+        verify: bool,
     },
     GitMergeContinue {
         project: String,
+        /// 集成目标分支；省略时为项目默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
     },
     GitMergeAbort {
         project: String,
+        /// 集成目标分支；省略时为项目默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
     },
     GitIntegrationStatus {
         project: String,
+        /// 集成目标分支；省略时为项目默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
+    },
+    /// 仓库统计与维护状态（对象数、pack 大小、历史大文件、提交数、最近 gc 时间）
+    GitRepoStats {
+        project: String,
+    },
+    /// 检测历史中超过阈值的 blob，可附带 `git filter-repo` 重写计划（仅生成命令，不执行）
+    GitDetectLargeBlobs {
+        project: String,
+        /// 字节阈值，省略时为 1 MiB
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        threshold: Option<u64>,
+        #[serde(default)]
+        filter_repo_plan: bool,
     },
 
     // v1.13: Git rebase onto default via integration worktree (UX-4)
     GitRebaseOntoDefault {
         project: String,
         workspace: String,
-        default_branch: String,
+        /// 省略时使用项目配置的默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default_branch: Option<String>,
     },
     GitRebaseOntoDefaultContinue {
         project: String,
+        /// 集成目标分支；省略时为项目默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
     },
     GitRebaseOntoDefaultAbort {
         project: String,
+        /// 集成目标分支；省略时为项目默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
     },
 
     // v1.14: Git reset integration worktree (UX-5)
     GitResetIntegrationWorktree {
         project: String,
+        /// 集成目标分支；省略时为项目默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
     },
 
     // v1.15: Git check branch up to date (UX-6)
@@ -296,18 +558,58 @@ pub enum ClientMessage {
         project: String,
         workspace: String,
     },
+    /// 推送当前分支并在 GitHub / GitLab 上创建 PR（MR），结果为 `git_create_pull_request_result`
+    GitCreatePullRequest {
+        project: String,
+        workspace: String,
+        title: String,
+        #[serde(default)]
+        body: Option<String>,
+        /// 目标分支；省略时为项目默认分支
+        #[serde(default)]
+        target_branch: Option<String>,
+        #[serde(default)]
+        draft: bool,
+    },
+    /// 项目各工作空间分支上打开的 PR（含可合并性与 CI 检查）；读取动作，经 HTTP 提供
+    GitListPullRequests {
+        project: String,
+    },
+    /// 单个 PR 的状态；读取动作，经 HTTP 提供
+    GitPullRequestStatus {
+        project: String,
+        number: u64,
+    },
 
     // v1.16: Project/Workspace import
     ImportProject {
         name: String,
         path: String,
     },
+    /// 克隆远程仓库到托管目录并导入为项目；克隆期间推送 `project_clone_progress`
+    ImportProjectFromUrl {
+        name: String,
+        url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
+        /// 浅克隆深度
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        depth: Option<u32>,
+    },
     CreateWorkspace {
         project: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         from_branch: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         template_id: Option<String>,
+        /// 稀疏检出（cone 模式）的目录列表；为空时完整检出
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        sparse_paths: Vec<String>,
+    },
+    /// 按声明式清单（TOML / JSON）创建缺失的工作区并启动其任务，标记清单外的工作区
+    ApplyWorkspaceManifest {
+        project: String,
+        manifest: String,
     },
 
     // v1.17: Remove project
@@ -320,6 +622,36 @@ pub enum ClientMessage {
         project: String,
         workspace: String,
     },
+    /// 重命名工作空间；可选同步重命名分支与 worktree 目录
+    RenameWorkspace {
+        project: String,
+        workspace: String,
+        new_name: String,
+        #[serde(default)]
+        rename_branch: bool,
+        #[serde(default)]
+        move_worktree: bool,
+    },
+    /// 修复 worktree 目录缺失或元数据过期的工作空间
+    RepairWorkspace {
+        project: String,
+        workspace: String,
+    },
+    /// 关联 issue：`url` 为完整地址，或 `#123` / `123`（按 `origin` 解析为 GitHub / GitLab issue 地址）；
+    /// 省略或为空时取消关联
+    LinkWorkspaceIssue {
+        project: String,
+        workspace: String,
+        #[serde(default)]
+        url: Option<String>,
+    },
+    /// 调整工作空间的稀疏检出目录（cone 模式）；`paths` 为空时恢复完整检出
+    WorkspaceSetSparsePaths {
+        project: String,
+        workspace: String,
+        #[serde(default)]
+        paths: Vec<String>,
+    },
 
     // v1.19: Git log (commit history)
     GitLog {
@@ -327,6 +659,24 @@ pub enum ClientMessage {
         workspace: String,
         #[serde(default = "default_git_log_limit")]
         limit: usize,
+        /// 跳过前 N 条匹配的提交
+        #[serde(default)]
+        skip: usize,
+        /// 游标分页：返回该提交之前（不含）的历史
+        #[serde(default)]
+        before_sha: Option<String>,
+        #[serde(default)]
+        author: Option<String>,
+        #[serde(default)]
+        path: Option<String>,
+        #[serde(default)]
+        grep: Option<String>,
+        #[serde(default)]
+        since: Option<String>,
+        #[serde(default)]
+        until: Option<String>,
+        #[serde(default)]
+        branch: Option<String>,
     },
 
     // v1.20: Git show (single commit details)
@@ -335,6 +685,61 @@ pub enum ClientMessage {
         workspace: String,
         sha: String,
     },
+    /// 基于工作区上下文（关联链接、分支名）、暂存区摘要与 `[git] commit_template` 生成提交信息建议
+    GitSuggestedCommitMessage {
+        project: String,
+        workspace: String,
+        /// 工作区关联的 issue / PR 链接，用于提取 issue 编号
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        link_url: Option<String>,
+        /// 为 true 时由 `[git] commit_message_command` 生成 message
+        #[serde(default)]
+        generate: bool,
+    },
+    /// 暂存区 diff 交给 `[git] commit_message_command` / `commit_message_endpoint` 生成提交信息，
+    /// 响应 `git_suggested_commit_message_result`
+    GitGenerateCommitMessage {
+        project: String,
+        workspace: String,
+        /// 省略时使用工作区关联的 issue
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        link_url: Option<String>,
+    },
+    /// 提交内单个文件的 diff（相对第一父提交），供提交详情展开
+    GitShowFileDiff {
+        project: String,
+        workspace: String,
+        sha: String,
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        algorithm: Option<String>,
+        /// 输出格式："unified"（默认）| "structured"（额外返回 hunks）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+        #[serde(default)]
+        intraline: bool,
+    },
+    /// 文件逐行归属（遵循 ignore-revs 配置）
+    GitBlame {
+        project: String,
+        workspace: String,
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rev: Option<String>,
+    },
+    /// 提交区间 diff 摘要（可限定目录），单文件补丁通过 `git_diff.range` 按需获取
+    GitDiffRange {
+        project: String,
+        workspace: String,
+        range: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+    },
+    /// 生成分支相对默认分支的变更报告（自包含 HTML，保存在数据目录供下载）
+    GitGenerateChangeReport {
+        project: String,
+        workspace: String,
+    },
 
     // v1.40: 冲突向导动作
     /// 读取单个冲突文件的四路对比内容
@@ -373,6 +778,18 @@ pub enum ClientMessage {
         path: String,
         context: String,
     },
+    /// 按指定方式解决冲突并暂存
+    GitConflictResolve {
+        project: String,
+        workspace: String,
+        path: String,
+        context: String,
+        /// ours | theirs | both | custom
+        resolution: String,
+        /// resolution = custom 时写入的完整文件内容
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content: Option<String>,
+    },
 
     // v1.50: Git stash 操作
     GitStashList {
@@ -438,6 +855,36 @@ pub enum ClientMessage {
         /// 是否开启节点发现广播
         #[serde(default)]
         node_discovery_enabled: Option<bool>,
+        /// 新建终端的默认 shell；Some(None) 表示显式清空（回退系统默认）
+        #[serde(default)]
+        default_shell: Option<Option<String>>,
+        /// git 网络操作的全局代理；Some(None) 表示显式清空
+        #[serde(default)]
+        git_proxy: Option<Option<String>>,
+        /// 离线模式开关
+        #[serde(default)]
+        git_offline_mode: Option<bool>,
+        /// 代码托管平台访问令牌（key: 主机名）；按主机合并，值为 null 表示删除该主机
+        #[serde(default)]
+        git_hosting_tokens: Option<std::collections::HashMap<String, Option<String>>>,
+        /// 新建终端默认开启保活
+        #[serde(default)]
+        terminal_keep_alive: Option<bool>,
+        /// 保活终端运行期间阻止系统休眠（macOS）
+        #[serde(default)]
+        terminal_prevent_sleep: Option<bool>,
+        /// 新建 zsh / bash 终端时注入 shell 集成脚本
+        #[serde(default)]
+        terminal_shell_integration: Option<bool>,
+        /// 推送通知中继地址；Some(None) 表示显式清空（停止推送）
+        #[serde(default)]
+        push_relay_url: Option<Option<String>>,
+        /// 设备 APNs 推送令牌（key: device_id）；按设备合并，值为 null 表示删除该设备
+        #[serde(default)]
+        push_tokens: Option<std::collections::HashMap<String, Option<String>>>,
+        /// 后台定时 fetch 全部项目的间隔（秒）；0 表示关闭，非 0 时不得小于 60
+        #[serde(default)]
+        git_auto_fetch_interval_secs: Option<u64>,
         /// Evolution 全局默认配置；为 None 时保持服务端现值不变。
         #[serde(default)]
         evolution_default_profiles: Option<Vec<EvolutionStageProfileInfo>>,
@@ -450,6 +897,16 @@ pub enum ClientMessage {
         /// 语言级格式化配置；为 None 时保持服务端现值不变。
         #[serde(default)]
         editor_formatting_configs: Option<Vec<formatting::EditorFormattingLanguageConfig>>,
+        /// 客户端设备标识；携带时 `workspace_shortcuts` / `keybindings` 写入该设备的覆盖，
+        /// 其余字段仍写入共享部分
+        #[serde(default)]
+        device_id: Option<String>,
+        /// 设备显示名，随 `device_id` 一起保存
+        #[serde(default)]
+        device_name: Option<String>,
+        /// 删除该设备的覆盖（回退到共享值），本次请求中的设备级字段不再写入
+        #[serde(default)]
+        reset_device_overrides: bool,
     },
 
     NodeUpdateProfile {
@@ -473,6 +930,17 @@ pub enum ClientMessage {
         workspace: String,
     },
     WatchUnsubscribe,
+    /// 监控单个文件的磁盘变化（外部编辑检测），变化时推送 `file_watch_changed`
+    FileWatch {
+        project: String,
+        workspace: String,
+        path: String,
+    },
+    FileUnwatch {
+        project: String,
+        workspace: String,
+        path: String,
+    },
 
     // v1.23: File rename/delete
     FileRename {
@@ -486,6 +954,39 @@ pub enum ClientMessage {
         workspace: String,
         path: String,
     },
+    /// 创建目录（含缺失的父目录）
+    FileMkdir {
+        project: String,
+        workspace: String,
+        path: String,
+    },
+    /// 工作区查找替换：计算全部匹配并返回预览
+    FilePrepareReplace {
+        project: String,
+        workspace: String,
+        query: String,
+        replacement: String,
+        #[serde(default)]
+        regex: bool,
+        #[serde(default)]
+        case_sensitive: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        include_globs: Vec<String>,
+    },
+    /// 应用预览中被勾选的匹配；省略 accepted_matches 表示全部应用
+    FileApplyReplace {
+        project: String,
+        workspace: String,
+        change_set_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        accepted_matches: Option<Vec<u32>>,
+    },
+    /// 撤销一次已应用的查找替换
+    FileUndoReplace {
+        project: String,
+        workspace: String,
+        undo_id: String,
+    },
 
     // v1.24: File copy (使用绝对路径支持跨项目/外部文件复制)
     FileCopy {
@@ -510,7 +1011,9 @@ pub enum ClientMessage {
         workspace: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         ai_agent: Option<String>,
-        default_branch: String,
+        /// 省略时使用项目配置的默认分支
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default_branch: Option<String>,
     },
 
     // v1.27: Terminal persistence — 重连附着
@@ -528,6 +1031,21 @@ pub enum ClientMessage {
         term_id: String,
         bytes: u64,
     },
+    /// 暂停 / 恢复当前连接上某终端的实时输出；暂停期间 PTY 照常运行，输出只写入 scrollback
+    TermFlowControl {
+        term_id: String,
+        paused: bool,
+    },
+    /// 查询终端最近执行的命令（需 shell 集成输出 OSC 133 标记）
+    TermCommandHistory {
+        term_id: String,
+    },
+
+    /// 修改项目默认分支（集成、rebase、合并与分歧检查均以此为准）
+    SetProjectDefaultBranch {
+        project: String,
+        default_branch: String,
+    },
 
     // v1.29: 项目命令管理
     SaveProjectCommands {
@@ -546,6 +1064,31 @@ pub enum ClientMessage {
         #[serde(skip_serializing_if = "Option::is_none")]
         task_id: Option<String>,
     },
+    /// 在工作区中启动托管进程（如 dev server），连接断开后继续运行
+    ProcStart {
+        project: String,
+        workspace: String,
+        name: String,
+        command: String,
+    },
+    ProcStop {
+        project: String,
+        workspace: String,
+        name: String,
+    },
+    ProcList {
+        project: String,
+        workspace: String,
+    },
+    /// 托管进程最近的输出
+    ProcLogs {
+        project: String,
+        workspace: String,
+        name: String,
+        /// 返回的行数，默认 200
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        lines: Option<usize>,
+    },
 
     // v1.40: 工作流模板管理
     ListTemplates,
@@ -906,6 +1449,17 @@ pub enum ClientMessage {
     HealthRepair {
         request: health::RepairActionRequest,
     },
+    /// 用轮转快照（`GET /api/v1/system/state-backups`）整体替换当前状态
+    RestoreStateBackup {
+        backup_id: String,
+    },
+    /// 查询变更操作审计日志；`since` 为 Unix 毫秒（不含），返回最近 `limit` 条（默认 200，最多 1000）
+    GetAuditLog {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        since: Option<i64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<usize>,
+    },
 
     // v1.60: Workspace sequencer 操作（cherry-pick / revert / rollback）
     GitCherryPick {
@@ -944,6 +1498,10 @@ fn default_diff_mode() -> String {
     "working".to_string()
 }
 
+fn default_file_encoding() -> String {
+    "utf8".to_string()
+}
+
 fn default_git_scope() -> String {
     "file".to_string()
 }
@@ -973,6 +1531,21 @@ pub enum ServerMessage {
         term_id: Option<String>,
     },
     Pong,
+    /// `cancel` 的回执；`cancelled = false` 表示请求已结束或未知
+    CancelResult {
+        request_id: String,
+        cancelled: bool,
+    },
+    /// 主机唤醒后推送：客户端应重新拉取 git 状态与文件列表
+    ServerResumed {
+        /// 估算的休眠时长（毫秒），未知时为 0
+        slept_ms: u64,
+    },
+    /// Core 即将退出（SIGTERM / SIGINT 或父进程退出），客户端应稍后重连
+    ServerShutdown {
+        /// `sigterm` | `sigint` | `parent_exited`
+        reason: String,
+    },
 
     // v1: Control plane responses
     Projects {
@@ -981,6 +1554,19 @@ pub enum ServerMessage {
     Workspaces {
         project: String,
         items: Vec<WorkspaceInfo>,
+        /// 过滤前的 workspace 总数（含 default），仅在指定过滤条件时返回
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        total: Option<usize>,
+    },
+    WorkspaceTasks {
+        project: String,
+        workspace: String,
+        items: Vec<WorkspaceTaskInfo>,
+    },
+    WorkspacePorts {
+        project: String,
+        workspace: String,
+        items: Vec<ListeningPortInfo>,
     },
     SelectedWorkspace {
         project: String,
@@ -1016,6 +1602,50 @@ pub enum ServerMessage {
     TermClosed {
         term_id: String,
     },
+    TermKeepAliveUpdated {
+        term_id: String,
+        keep_alive: bool,
+        /// 是否正在阻止系统休眠（仅 macOS）
+        prevent_sleep: bool,
+    },
+    /// 终端名称已变更，广播给全部连接
+    TermRenamed {
+        term_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+    },
+    /// 终端展示顺序已变更，广播给全部连接；`term_ids` 为调整后的完整顺序
+    TermReordered {
+        term_ids: Vec<String>,
+    },
+    /// 终端工作目录变化（OSC 7 上报或前台进程 cwd 变化），广播给全部连接
+    TermCwdChanged {
+        term_id: String,
+        project: String,
+        workspace: String,
+        cwd: String,
+    },
+    /// `term_command_history` 的结果，命令按执行顺序排列（旧在前）
+    TermCommandHistoryResult {
+        term_id: String,
+        commands: Vec<TerminalCommandInfo>,
+    },
+    /// 终端中一条命令执行结束（shell 集成），广播给全部连接
+    TermCommandFinished {
+        term_id: String,
+        project: String,
+        workspace: String,
+        command: TerminalCommandInfo,
+    },
+    /// 终端中编码 Agent 出现、退出或开始/结束等待输入，广播给全部连接
+    TermAgentStateChanged {
+        term_id: String,
+        project: String,
+        workspace: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        agent: Option<String>,
+        awaiting_input: bool,
+    },
 
     // v1.3: File operation responses
     FileListResult {
@@ -1031,6 +1661,48 @@ pub enum ServerMessage {
         #[serde(with = "serde_bytes")]
         content: Vec<u8>,
         size: u64,
+        /// 实际返回的内容编码："utf8" 或 "binary"
+        #[serde(default = "default_file_encoding")]
+        encoding: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+        /// 文本文件当前的换行符："lf" | "crlf" | "cr" | "mixed"（二进制或无换行时省略）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        line_ending: Option<String>,
+        /// 非 UTF-8 文本转码前的原始字符集（如 "GBK"）；写回时作为 `encoding` 传入
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        charset: Option<String>,
+        /// 预览模式下内容被截断（只读，`size` 仍为完整文件大小）
+        #[serde(default)]
+        truncated: bool,
+        /// 修改时间（Unix 毫秒）；远程项目省略。写回时作为 `expected_mtime` 传入
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mtime: Option<u64>,
+        /// 磁盘内容哈希（git blob SHA-1）；预览截断时省略。写回时作为 `expected_hash` 传入
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hash: Option<String>,
+    },
+    FileReadAtRevisionResult {
+        project: String,
+        workspace: String,
+        path: String,
+        /// 回显请求的修订
+        rev: String,
+        /// 修订解析出的完整提交 SHA
+        sha: String,
+        #[serde(with = "serde_bytes")]
+        content: Vec<u8>,
+        size: u64,
+        /// 实际返回的内容编码："utf8" 或 "binary"
+        #[serde(default = "default_file_encoding")]
+        encoding: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        line_ending: Option<String>,
+        /// 非 UTF-8 文本转码前的原始字符集（如 "GBK"）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        charset: Option<String>,
     },
     FileWriteResult {
         project: String,
@@ -1038,6 +1710,37 @@ pub enum ServerMessage {
         path: String,
         success: bool,
         size: u64,
+        /// 写入后的修改时间（Unix 毫秒），供下一次写入的 `expected_mtime` 使用
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mtime: Option<u64>,
+        /// 写入后的内容哈希，供下一次写入的 `expected_hash` 使用
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hash: Option<String>,
+    },
+    FileApplyEditResult {
+        project: String,
+        workspace: String,
+        path: String,
+        success: bool,
+        size: u64,
+        /// 写入后的修改时间（Unix 毫秒），供下一次编辑的 `expected_mtime` 使用
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mtime: Option<u64>,
+        /// 写入后的内容哈希，供下一次编辑的 `expected_hash` 使用
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hash: Option<String>,
+    },
+    /// `file_write` / `file_apply_edit` 的版本校验失败：文件在客户端读取后已被修改，未写入
+    FileConflict {
+        project: String,
+        workspace: String,
+        path: String,
+        /// 当前磁盘内容哈希；文件已被删除时省略
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        current_hash: Option<String>,
+        /// 当前修改时间（Unix 毫秒）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        current_mtime: Option<u64>,
     },
 
     // v1.4: File index result for Quick Open
@@ -1046,6 +1749,40 @@ pub enum ServerMessage {
         workspace: String,
         items: Vec<String>,
         truncated: bool,
+        /// 索引快照版本号，用于与后续 `file_index_delta` 对齐
+        #[serde(default)]
+        version: u64,
+    },
+    /// Quick Open 模糊匹配结果（按得分降序）
+    FileQueryResult {
+        project: String,
+        workspace: String,
+        query: String,
+        items: Vec<file::FileQueryMatch>,
+        /// 命中总数（可能多于 `items`）
+        total_matches: u32,
+        /// 底层索引是否因文件数上限被截断
+        truncated: bool,
+        /// 匹配所用的索引快照版本
+        #[serde(default)]
+        version: u64,
+    },
+    /// 文件索引增量（由 watcher 事件驱动，客户端按 base_version 校验后合并）
+    FileIndexDelta {
+        project: String,
+        workspace: String,
+        /// 增量基于的快照版本
+        base_version: u64,
+        /// 应用增量后的快照版本
+        version: u64,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        added: Vec<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        removed: Vec<String>,
+        truncated: bool,
+        /// 为 true 时索引已失效，客户端应丢弃本地索引并重新拉取
+        #[serde(default)]
+        reset: bool,
     },
 
     // v1.42: 文件内容搜索结果
@@ -1059,6 +1796,67 @@ pub enum ServerMessage {
         truncated: bool,
         search_duration_ms: u64,
     },
+    FileChunk {
+        project: String,
+        workspace: String,
+        path: String,
+        offset: u64,
+        #[serde(with = "serde_bytes")]
+        content: Vec<u8>,
+        total_size: u64,
+        /// 本块是否已读到文件末尾
+        eof: bool,
+        /// 是否为本次请求的最后一块
+        last: bool,
+    },
+    FileDefinitionGuessResult {
+        project: String,
+        workspace: String,
+        symbol: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+        /// 按可能性降序排列的候选定义
+        candidates: Vec<file::FileDefinitionCandidate>,
+        truncated: bool,
+    },
+    #[serde(rename = "file_editorconfig_result")]
+    FileEditorConfigResult {
+        project: String,
+        workspace: String,
+        path: String,
+        settings: file::FileEditorConfigSettings,
+        /// 参与合并的 `.editorconfig`（相对工作区根，由远及近）
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        sources: Vec<String>,
+        /// 项目是否开启写入时应用（`.tidyflow.toml` `[editor] apply_editorconfig`）
+        apply_on_write: bool,
+    },
+    FileHighlightResult {
+        project: String,
+        workspace: String,
+        path: String,
+        /// 内容哈希（与 `file_read_result.hash` 一致），同一哈希的结果可复用
+        hash: String,
+        /// 识别出的语法名称
+        syntax: String,
+        theme: String,
+        /// 主题背景色 / 默认前景色
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        background: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        foreground: Option<String>,
+        /// 样式表（`spans` 格式），`lines` 中的区间按下标引用
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        styles: Vec<file::FileHighlightStyle>,
+        /// 按行的样式区间（`spans` 格式）
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        lines: Vec<Vec<file::FileHighlightSpan>>,
+        /// `html` 格式的 `<pre>` 片段
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        html: Option<String>,
+        /// 是否命中服务端高亮缓存
+        cached: bool,
+    },
 
     // v1.10: File external change conflict detection
     /// 文件外部变更冲突检测通知（由 watcher 触发）
@@ -1117,6 +1915,20 @@ pub enum ServerMessage {
         behind_by: Option<i32>,
         #[serde(skip_serializing_if = "Option::is_none")]
         compared_branch: Option<String>,
+        /// 展开未跟踪目录时触达文件数上限，部分目录仍保持折叠
+        #[serde(default)]
+        untracked_truncated: bool,
+    },
+    /// 项目下各工作区的 Git 状态摘要（default 在前，其余按名称排序）
+    GitStatusAllResult {
+        project: String,
+        workspaces: Vec<GitWorkspaceStatusSummary>,
+    },
+    /// 含变更的目录及其（含子目录的）变更计数，按路径排序；根目录的 `path` 为空串
+    GitStatusTreeResult {
+        project: String,
+        workspace: String,
+        directories: Vec<GitStatusDirectoryInfo>,
     },
     GitDiffResult {
         project: String,
@@ -1130,6 +1942,39 @@ pub enum ServerMessage {
         mode: String, // Echo back the mode
         #[serde(skip_serializing_if = "Option::is_none")]
         base: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        range: Option<String>,
+        /// `format = "structured"` 时返回解析后的 hunk
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hunks: Option<Vec<GitDiffHunkInfo>>,
+        /// 实际应用的智能 diff：gitattributes | lockfile | notebook
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        smart_diff: Option<String>,
+        /// `truncated` 时下一页的起始字节偏移，用于 `git_diff_page`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        next_offset: Option<u64>,
+        /// 二进制文件的旧版本信息；新增文件省略
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        old_blob: Option<GitBinaryBlobInfo>,
+        /// 二进制文件的新版本信息；删除文件省略
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        new_blob: Option<GitBinaryBlobInfo>,
+    },
+    /// `git_diff_page` 的结果：`text` 为从 `offset` 开始的一页 unified diff
+    GitDiffPageResult {
+        project: String,
+        workspace: String,
+        path: String,
+        mode: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        base: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        range: Option<String>,
+        offset: u64,
+        text: String,
+        truncated: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        next_offset: Option<u64>,
     },
 
     // v1.6: Git operation result
@@ -1144,6 +1989,24 @@ pub enum ServerMessage {
         path: Option<String>,
         scope: String, // "file" or "all"
     },
+    /// 长耗时 git 操作的进度；开始时先推送一条 `phase = "started"`
+    GitOpProgress {
+        op_id: String,
+        /// `fetch` / `rebase`
+        op: String,
+        project: String,
+        workspace: String,
+        /// 阶段：`started` / `receiving_objects` / `resolving_deltas` / `rebasing` 等
+        phase: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        percent: Option<u8>,
+        message: String,
+    },
+    GitOpCancelResult {
+        op_id: String,
+        /// 是否找到进行中的操作
+        cancelled: bool,
+    },
 
     // v1.8: Git branches result
     GitBranchesResult {
@@ -1162,6 +2025,13 @@ pub enum ServerMessage {
         message: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         sha: Option<String>,
+        /// 新提交是否带签名
+        #[serde(default)]
+        signed: bool,
+        /// 失败分类：`empty_message` / `nothing_staged` / `identity_missing` / `hook_failed` /
+        /// `signing_failed` / `commit_failed`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error_code: Option<String>,
     },
 
     // v1.11: Git rebase result (UX-3a)
@@ -1221,6 +2091,56 @@ pub enum ServerMessage {
         #[serde(skip_serializing_if = "Option::is_none")]
         integration_path: Option<String>,
     },
+    /// 合并后校验命令的输出（逐行）
+    GitMergeVerifyOutput {
+        project: String,
+        task_id: String,
+        line: String,
+    },
+    /// 合并后校验结论
+    GitMergeVerifyResult {
+        project: String,
+        task_id: String,
+        ok: bool,
+        /// 执行的校验命令；未配置时为空
+        command: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        exit_code: Option<i32>,
+        /// 失败原因或输出末尾摘要
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        integration_path: String,
+        duration_ms: u64,
+    },
+    GitCreatePullRequestResult {
+        project: String,
+        workspace: String,
+        ok: bool,
+        /// 托管平台：`github` / `gitlab`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        provider: Option<String>,
+        /// 源分支；识别失败时为空
+        branch: String,
+        target_branch: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        url: Option<String>,
+        /// PR 编号（GitLab 为项目内 `iid`）
+        #[serde(skip_serializing_if = "Option::is_none")]
+        number: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    GitPullRequestsResult {
+        project: String,
+        /// 托管平台：`github` / `gitlab`
+        provider: String,
+        items: Vec<GitPullRequestInfo>,
+    },
+    GitPullRequestStatusResult {
+        project: String,
+        provider: String,
+        pull_request: GitPullRequestInfo,
+    },
 
     // v1.12: Git integration worktree status result (UX-3b)
     GitIntegrationStatusResult {
@@ -1244,6 +2164,44 @@ pub enum ServerMessage {
         #[serde(skip_serializing_if = "Option::is_none")]
         compared_branch: Option<String>,
     },
+    /// 仓库统计与维护状态
+    GitRepoStatsResult {
+        project: String,
+        /// 所有引用可达的提交数
+        commit_count: u64,
+        loose_objects: u64,
+        loose_size_bytes: u64,
+        packed_objects: u64,
+        pack_count: u64,
+        pack_size_bytes: u64,
+        prune_packable: u64,
+        garbage_count: u64,
+        garbage_size_bytes: u64,
+        /// 历史中最大的文件（按未压缩大小降序）
+        largest_files: Vec<GitRepoLargeFileInfo>,
+        /// 最近一次 gc 的估计时间（Unix 秒）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        last_gc_at: Option<i64>,
+        /// 上次自动 gc 失败时的 `gc.log` 内容
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        gc_log: Option<String>,
+        maintenance_registered: bool,
+        /// 维护建议：`gc` | `prune_garbage` | `gc_failed` | `register_maintenance`
+        #[serde(default)]
+        suggestions: Vec<String>,
+    },
+    /// 历史大文件检测结果
+    GitDetectLargeBlobsResult {
+        project: String,
+        threshold: u64,
+        /// 超过阈值的 blob（按大小降序，最多 200 个）
+        blobs: Vec<GitLargeBlobInfo>,
+        total_count: usize,
+        total_size: u64,
+        truncated: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        filter_repo: Option<GitFilterRepoPlanInfo>,
+    },
 
     // v1.13: Git rebase onto default result (UX-4)
     GitRebaseOntoDefaultResult {
@@ -1286,9 +2244,24 @@ pub enum ServerMessage {
         session_id: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         cycle_id: Option<String>,
+        /// 请求追踪 ID，对应服务端日志中的 `trace_id` 与子进程的 `TIDYFLOW_TRACE_ID`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trace_id: Option<String>,
     },
 
     // v1.16: Project/Workspace import results
+    /// 项目克隆进度（`import_project_from_url`）
+    ProjectCloneProgress {
+        name: String,
+        /// 阶段：`receiving_objects` / `resolving_deltas` / `updating_files` 等
+        phase: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        percent: Option<u8>,
+        message: String,
+        /// 克隆操作 id，可用于 `git_op_cancel`
+        #[serde(default)]
+        op_id: String,
+    },
     ProjectImported {
         name: String,
         root: String,
@@ -1296,10 +2269,26 @@ pub enum ServerMessage {
         #[serde(skip_serializing_if = "Option::is_none")]
         workspace: Option<WorkspaceInfo>,
     },
+    /// 工作空间创建过程中的准备进度（`create_workspace`，目前为 Git LFS 对象下载）
+    WorkspaceSetupProgress {
+        project: String,
+        workspace: String,
+        /// 阶段：`lfs_pull` / `downloading_lfs_objects` / `lfs_done` / `lfs_failed` / `lfs_unavailable`
+        phase: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        percent: Option<u8>,
+        message: String,
+    },
     WorkspaceCreated {
         project: String,
         workspace: WorkspaceInfo,
     },
+    WorkspaceManifestApplied {
+        project: String,
+        results: Vec<WorkspaceManifestResult>,
+        /// 现有但未在清单中声明的工作区（不会被删除）
+        extra_workspaces: Vec<String>,
+    },
 
     // v1.17: Remove project result
     ProjectRemoved {
@@ -1317,12 +2306,58 @@ pub enum ServerMessage {
         #[serde(skip_serializing_if = "Option::is_none")]
         message: Option<String>,
     },
+    /// 重命名结果；`workspace` 为原名称，成功时 `info` 为重命名后的工作空间
+    WorkspaceRenamed {
+        project: String,
+        workspace: String,
+        new_name: String,
+        ok: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        info: Option<WorkspaceInfo>,
+    },
+    /// 修复结果；`actions` 为实际执行的修复动作，成功时 `info` 为修复后的工作空间
+    WorkspaceRepaired {
+        project: String,
+        workspace: String,
+        ok: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        actions: Vec<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        info: Option<WorkspaceInfo>,
+    },
+    /// issue 关联结果；`info.linked_issue` 为生效后的地址
+    WorkspaceIssueLinked {
+        project: String,
+        workspace: String,
+        ok: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        info: Option<WorkspaceInfo>,
+    },
+    /// 稀疏检出目录调整结果；`info.sparse_paths` 为生效后的目录
+    WorkspaceSparsePathsSet {
+        project: String,
+        workspace: String,
+        ok: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        info: Option<WorkspaceInfo>,
+    },
 
     // v1.19: Git log result
     GitLogResult {
         project: String,
         workspace: String,
         entries: Vec<GitLogEntryInfo>,
+        /// 是否还有更早的提交
+        #[serde(default)]
+        has_more: bool,
     },
 
     // v1.20: Git show result (single commit details)
@@ -1337,6 +2372,99 @@ pub enum ServerMessage {
         date: String,
         files: Vec<GitShowFileInfo>,
     },
+    /// 提交信息建议
+    GitSuggestedCommitMessageResult {
+        project: String,
+        workspace: String,
+        /// 建议的提交信息：模板渲染（仅首行）或外部命令 / 端点生成
+        message: String,
+        /// `template` | `command` | `endpoint`
+        source: String,
+        /// 是否配置了外部生成命令（客户端据此显示“生成提交信息”）
+        generator_available: bool,
+        /// 实际使用的模板
+        template: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        issue_id: Option<String>,
+        commit_type: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        scope: Option<String>,
+        summary: String,
+        branch: String,
+        staged_files: usize,
+    },
+    /// 提交内单个文件的 diff
+    GitShowFileDiffResult {
+        project: String,
+        workspace: String,
+        /// 完整 SHA
+        sha: String,
+        path: String,
+        /// 重命名/复制前的路径
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        old_path: Option<String>,
+        /// 该文件在提交中的状态码：A/M/D/R/C
+        code: String,
+        format: String,
+        text: String,
+        is_binary: bool,
+        truncated: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hunks: Option<Vec<GitDiffHunkInfo>>,
+    },
+    GitRebaseInteractivePlanResult {
+        project: String,
+        workspace: String,
+        base: String,
+        base_sha: String,
+        /// 按应用顺序（旧 → 新）排列
+        commits: Vec<GitRebasePlanCommitInfo>,
+    },
+    GitBlameResult {
+        project: String,
+        workspace: String,
+        path: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        rev: Option<String>,
+        hunks: Vec<GitBlameHunkInfo>,
+        /// 实际生效的 ignore-revs 文件
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        ignore_revs_files: Vec<String>,
+    },
+    GitDiffRangeResult {
+        project: String,
+        workspace: String,
+        /// 规范化后的区间（如 `main...HEAD`）
+        range: String,
+        base: String,
+        head: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        merge_base: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+        files: Vec<GitRangeDiffFileInfo>,
+        total_additions: i32,
+        total_deletions: i32,
+        truncated: bool,
+    },
+    /// 变更报告已生成，通过 `download_path` 下载
+    GitChangeReportResult {
+        project: String,
+        workspace: String,
+        report_id: String,
+        base: String,
+        head: String,
+        file_count: usize,
+        total_additions: i32,
+        total_deletions: i32,
+        commit_count: usize,
+        /// 文件列表、提交列表或补丁超出上限被截断
+        truncated: bool,
+        /// 报告大小（字节）
+        size: u64,
+        /// HTTP 下载路径（`GET`，需鉴权）
+        download_path: String,
+    },
 
     // v1.21: Client settings result
     ClientSettingsResult {
@@ -1349,6 +2477,28 @@ pub enum ServerMessage {
         node_name: Option<String>,
         #[serde(default)]
         node_discovery_enabled: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default_shell: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        git_proxy: Option<String>,
+        #[serde(default)]
+        git_offline_mode: bool,
+        /// 已配置访问令牌的主机（不回传令牌本身）
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        git_hosting_token_hosts: Vec<String>,
+        #[serde(default)]
+        terminal_keep_alive: bool,
+        #[serde(default)]
+        terminal_prevent_sleep: bool,
+        #[serde(default)]
+        terminal_shell_integration: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        push_relay_url: Option<String>,
+        /// 已登记推送令牌的设备（不回传令牌本身）
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        push_token_devices: Vec<String>,
+        #[serde(default)]
+        git_auto_fetch_interval_secs: u64,
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         evolution_default_profiles: Vec<EvolutionStageProfileInfo>,
         #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
@@ -1359,6 +2509,15 @@ pub enum ServerMessage {
         keybindings: Vec<KeybindingConfigInfo>,
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         editor_formatting_configs: Vec<formatting::EditorFormattingLanguageConfig>,
+        /// 请求的设备标识；存在时 `workspace_shortcuts` / `keybindings` 为该设备实际生效的值
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        device_id: Option<String>,
+        /// 该设备覆盖了共享值的字段
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        device_overrides: Vec<String>,
+        /// 所有带覆盖的设备
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        device_profiles: Vec<DeviceSettingsProfileInfo>,
     },
     ClientSettingsSaved {
         ok: bool,
@@ -1408,10 +2567,44 @@ pub enum ServerMessage {
         paths: Vec<String>,
         kind: String,
     },
+    FileWatched {
+        project: String,
+        workspace: String,
+        path: String,
+    },
+    FileUnwatched {
+        project: String,
+        workspace: String,
+        path: String,
+    },
+    /// 被 `file_watch` 监控的文件在磁盘上发生变化
+    FileWatchChanged {
+        project: String,
+        workspace: String,
+        path: String,
+        /// 修改时间（Unix 毫秒）；文件被删除时为空
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mtime: Option<u64>,
+        /// 文件大小（字节）；文件被删除时为空
+        #[serde(skip_serializing_if = "Option::is_none")]
+        size: Option<u64>,
+        deleted: bool,
+    },
     GitStatusChanged {
         project: String,
         workspace: String,
     },
+    /// 后台定时 fetch 后的远程分歧（相对 `origin/<默认分支>`）
+    GitRemoteUpdated {
+        project: String,
+        workspace: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        current_branch: Option<String>,
+        ahead_by: i32,
+        behind_by: i32,
+        /// 比较的远程引用（如 `origin/main`）
+        compared_branch: String,
+    },
 
     // v1.40: 冲突向导响应
     /// 单文件冲突详情（四路对比内容）
@@ -1430,6 +2623,9 @@ pub enum ServerMessage {
         current_content: String,
         conflict_markers_count: usize,
         is_binary: bool,
+        /// 按冲突标记解析出的冲突区块
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        regions: Vec<ConflictRegionInfo>,
     },
     /// 冲突解决动作结果（含最新冲突快照）
     GitConflictActionResult {
@@ -1437,7 +2633,7 @@ pub enum ServerMessage {
         workspace: String,
         context: String,
         path: String,
-        /// 已执行的动作：accept_ours | accept_theirs | accept_both | mark_resolved
+        /// 已执行的动作：accept_ours | accept_theirs | accept_both | mark_resolved | resolve_custom
         action: String,
         ok: bool,
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -1494,6 +2690,48 @@ pub enum ServerMessage {
         #[serde(skip_serializing_if = "Option::is_none")]
         message: Option<String>,
     },
+    FileMkdirResult {
+        project: String,
+        workspace: String,
+        path: String,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    FilePrepareReplaceResult {
+        project: String,
+        workspace: String,
+        change_set_id: String,
+        matches: Vec<file::FileReplaceMatch>,
+        /// 涉及的文件数
+        file_count: u32,
+        /// 匹配数超过上限时为 true（仅预览中的匹配可被应用）
+        truncated: bool,
+    },
+    FileApplyReplaceResult {
+        project: String,
+        workspace: String,
+        change_set_id: String,
+        success: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        files_changed: Vec<String>,
+        replacements: u32,
+        /// 撤销凭据，传给 `file_undo_replace`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        undo_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    FileUndoReplaceResult {
+        project: String,
+        workspace: String,
+        undo_id: String,
+        success: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        files_restored: Vec<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
 
     // v1.24: File copy result
     FileCopyResult {
@@ -1543,9 +2781,27 @@ pub enum ServerMessage {
         icon: Option<String>,
     },
 
+    /// `term_flow_control` 结果；`dropped_bytes` 为自上次上报以来因限速积压或暂停而未转发的字节数，
+    /// 非 0 时客户端可 `term_attach` 按 scrollback 重新同步
+    TermFlowState {
+        term_id: String,
+        paused: bool,
+        dropped_bytes: u64,
+    },
+
     // v1.32: 远程终端订阅变更通知（推送给本地连接）
     RemoteTermChanged,
 
+    /// 默认分支修改结果；成功时 `default_branch` 为生效后的分支
+    ProjectDefaultBranchSet {
+        project: String,
+        ok: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        default_branch: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+
     // v1.29: 项目命令结果
     ProjectCommandsSaved {
         project: String,
@@ -1567,6 +2823,9 @@ pub enum ServerMessage {
         ok: bool,
         #[serde(skip_serializing_if = "Option::is_none")]
         message: Option<String>,
+        /// 进程退出码；启动失败或被信号终止时为空
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        exit_code: Option<i32>,
     },
     ProjectCommandCancelled {
         project: String,
@@ -1579,6 +2838,36 @@ pub enum ServerMessage {
         task_id: String,
         line: String,
     },
+    ProcStarted {
+        project: String,
+        workspace: String,
+        info: ProcInfo,
+    },
+    /// `proc_stop` 的响应；进程退出后另行推送 `proc_exited`
+    ProcStopped {
+        project: String,
+        workspace: String,
+        info: ProcInfo,
+    },
+    /// 托管进程退出（广播给所有连接）
+    ProcExited {
+        project: String,
+        workspace: String,
+        info: ProcInfo,
+    },
+    ProcListResult {
+        project: String,
+        workspace: String,
+        items: Vec<ProcInfo>,
+    },
+    ProcLogsResult {
+        project: String,
+        workspace: String,
+        name: String,
+        lines: Vec<String>,
+        /// 未返回的更早输出行数（含缓冲区已丢弃的部分）
+        dropped_lines: u64,
+    },
 
     // v1.40: 工作流模板管理
     Templates {
@@ -2080,6 +3369,20 @@ pub enum ServerMessage {
     HealthRepairResult {
         audit: health::RepairAuditEntry,
     },
+    /// 恢复结果；`previous_backup_id` 为恢复前自动保存的当前状态，可用于撤销
+    StateBackupRestored {
+        backup_id: String,
+        ok: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        previous_backup_id: Option<String>,
+        project_count: usize,
+    },
+    /// 审计日志查询结果，按时间升序
+    AuditLogResult {
+        entries: Vec<crate::server::audit_log::AuditEntry>,
+    },
 
     // v1.46: Core 推送工作区 Coordinator 聚合状态快照（增量更新，每条消息对应一个工作区）
     #[serde(rename = "coordinator_snapshot")]
@@ -2262,6 +3565,34 @@ pub struct ProjectInfo {
     pub workspace_count: usize,
     #[serde(default)]
     pub commands: Vec<ProjectCommandInfo>,
+    #[serde(default)]
+    pub default_branch: String,
+    /// 默认分支领先 `origin/<default_branch>` 的提交数；无远程跟踪分支时省略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ahead_origin: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub behind_origin: Option<u32>,
+    /// 有未提交变更的 workspace 数（含默认 workspace）
+    #[serde(default)]
+    pub dirty_workspace_count: usize,
+    /// 最近活动时间（RFC3339）：本地分支最近提交与 workspace 最近访问中的较晚者
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_activity_at: Option<String>,
+    /// 导入时间（RFC3339）
+    #[serde(default)]
+    pub imported_at: String,
+    /// 加载状态：`loaded` | `pending`（workspace 仍在后台加载）| `failed`
+    #[serde(default)]
+    pub load_status: String,
+    /// 加载失败原因（仅 `load_status=failed` 时有值）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub load_error: Option<String>,
+    /// git 等外部命令的执行环境：`native` | `wsl` | `network`
+    #[serde(default)]
+    pub exec_env: String,
+    /// WSL 发行版名（仅 `exec_env=wsl` 时有值）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wsl_distro: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -2282,6 +3613,21 @@ pub struct WorkspaceInfo {
     pub status: String,
     #[serde(default)]
     pub sidebar_status: WorkspaceSidebarStatusInfo,
+    /// 最近活动时间（RFC3339）：分支最近提交与 workspace 最近访问中的较晚者
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_activity_at: Option<String>,
+    /// setup 中 `{{port}}` 使用的预留端口
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// 关联的 issue 地址
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linked_issue: Option<String>,
+    /// 未关联时从分支名推断的 issue 引用（如 `#123`），可直接作为 `link_workspace_issue.url`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggested_issue: Option<String>,
+    /// 稀疏检出（cone 模式）的目录列表；为空表示完整检出
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sparse_paths: Vec<String>,
 }
 
 // ============================================================================
@@ -2344,6 +3690,9 @@ pub struct TerminalInfo {
     pub workspace: String,
     pub cwd: String,
     pub status: String, // "running" or "exited"
+    /// 进程退出码（仅 status 为 "exited" 时有值）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
     /// 客户端连接层生命周期相位："entering"/"active"/"resuming"/"idle"/"recovering"/"recovery_failed"
     #[serde(default = "default_lifecycle_phase")]
     pub lifecycle_phase: String,
@@ -2353,6 +3702,9 @@ pub struct TerminalInfo {
     pub name: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub icon: Option<String>,
+    /// 展示顺序（同一工作区内升序），由 `term_reorder` 调整
+    #[serde(default)]
+    pub order: u64,
     /// Core 重启恢复相位（仅当 lifecycle_phase 为 recovering/recovery_failed 时非 None）
     /// 客户端应以此字段作为恢复状态权威来源，不得自行推导
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -2362,12 +3714,54 @@ pub struct TerminalInfo {
     pub recovery_failed_reason: Option<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub remote_subscribers: Vec<RemoteSubscriberDetail>,
+    /// 是否开启保活（不参与空闲回收）
+    #[serde(default)]
+    pub keep_alive: bool,
+    /// 前台运行的编码 Agent（如 "claude"）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// Agent 是否在等待用户输入
+    #[serde(default)]
+    pub agent_awaiting_input: bool,
+    /// 距最近一次输入或输出的秒数
+    #[serde(default)]
+    pub idle_seconds: u64,
+    /// 活跃状态："busy"/"idle"/"awaiting_input"
+    #[serde(default = "default_terminal_activity")]
+    pub activity: String,
+}
+
+/// shell 集成记录的一条终端命令
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TerminalCommandInfo {
+    /// 命令文本（取自回显的输入，可能为空）
+    pub command: String,
+    /// 退出码；shell 未上报时为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// 开始执行时间（Unix 毫秒）
+    pub started_at_ms: i64,
+    pub duration_ms: u64,
+    /// 开始执行时的工作目录
+    pub cwd: String,
+}
+
+/// 主机电源事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HostPowerEventKind {
+    WillSleep,
+    DidWake,
 }
 
 fn default_lifecycle_phase() -> String {
     "active".to_string()
 }
 
+fn default_terminal_activity() -> String {
+    "idle".to_string()
+}
+
 /// 远程订阅者详情（用于协议传输）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RemoteSubscriberDetail {
@@ -2407,9 +3801,74 @@ pub struct GitStatusEntry {
     pub deletions: Option<i32>,
 }
 
+/// 单个工作区的 Git 状态摘要（`git_status_all`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitWorkspaceStatusSummary {
+    pub workspace: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_branch: Option<String>,
+    /// 有暂存变更的文件数
+    pub staged_count: usize,
+    /// 有未暂存变更的已跟踪文件数
+    pub unstaged_count: usize,
+    pub untracked_count: usize,
+    pub conflict_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ahead_by: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub behind_by: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compared_branch: Option<String>,
+    /// 查询失败原因（此时计数均为 0）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 单个目录的变更计数（`git_status_tree`），包含全部子目录中的条目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitStatusDirectoryInfo {
+    /// 相对仓库根目录的路径，不带结尾 `/`
+    pub path: String,
+    /// 目录下的变更文件总数
+    pub dirty_count: usize,
+    pub staged_count: usize,
+    pub unstaged_count: usize,
+    pub untracked_count: usize,
+    pub conflict_count: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GitBranchInfo {
     pub name: String,
+    /// 上游分支短名（如 `origin/main`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<String>,
+    /// 相对上游的领先/落后提交数；无上游或上游已不存在时省略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ahead_by: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub behind_by: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_commit_sha: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_commit_date: Option<String>,
+    /// 已在其他 worktree 中检出时的路径，此时不能切换到该分支
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree_path: Option<String>,
+}
+
+impl From<crate::server::git::GitBranchInfo> for GitBranchInfo {
+    fn from(b: crate::server::git::GitBranchInfo) -> Self {
+        Self {
+            name: b.name,
+            upstream: b.upstream,
+            ahead_by: b.ahead_by,
+            behind_by: b.behind_by,
+            last_commit_sha: b.last_commit_sha,
+            last_commit_date: b.last_commit_date,
+            worktree_path: b.worktree_path,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -2430,6 +3889,195 @@ pub struct GitShowFileInfo {
     pub old_path: Option<String>,
 }
 
+/// 托管平台上的 PR（GitLab 为 MR）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitPullRequestInfo {
+    /// PR 编号（GitLab 为项目内 `iid`）
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    pub branch: String,
+    pub target_branch: String,
+    /// 源分支对应的工作空间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    pub draft: bool,
+    /// `open` / `closed` / `merged`
+    pub state: String,
+    /// 是否可合并；平台尚未计算完成时省略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mergeable: Option<bool>,
+    /// 平台原始合并状态（GitHub `mergeable_state`，GitLab `detailed_merge_status`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_state: Option<String>,
+    /// CI 汇总：`none` / `pending` / `success` / `failure`
+    pub checks_state: String,
+    #[serde(default)]
+    pub checks: Vec<GitPullRequestCheckInfo>,
+}
+
+/// PR 的单项 CI 检查
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitPullRequestCheckInfo {
+    pub name: String,
+    /// `pending` / `success` / `failure` / `neutral`
+    pub state: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// 仓库历史中的大文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitRepoLargeFileInfo {
+    pub path: String,
+    pub sha: String,
+    /// 未压缩大小（字节）
+    pub size: u64,
+    /// 对象库中的实际占用（字节）
+    pub disk_size: u64,
+}
+
+/// 写入大 blob 的提交
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitLargeBlobCommitInfo {
+    pub sha: String,
+    pub subject: String,
+    /// 提交时间（Unix 秒）
+    pub committed_at: i64,
+}
+
+/// 历史中超过阈值的 blob
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitLargeBlobInfo {
+    pub sha: String,
+    /// 该 blob 在历史中出现过的路径
+    pub paths: Vec<String>,
+    pub size: u64,
+    pub disk_size: u64,
+    /// 写入该 blob 的提交（新到旧，最多 20 个）
+    pub commits: Vec<GitLargeBlobCommitInfo>,
+}
+
+/// `git filter-repo` 重写计划（仅命令文本）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitFilterRepoPlanInfo {
+    /// 本机是否安装了 `git filter-repo`
+    pub available: bool,
+    pub strip_by_size_command: String,
+    pub remove_paths_command: String,
+    pub estimated_savings_bytes: u64,
+}
+
+/// 交互式 rebase 规划中的提交
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitRebasePlanCommitInfo {
+    pub sha: String,
+    pub short_sha: String,
+    pub subject: String,
+    /// 完整提交信息，供 reword / squash 编辑时预填
+    pub message: String,
+    pub author: String,
+    pub date: String,
+}
+
+/// 交互式 rebase 的单步动作
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitRebasePlanStepInfo {
+    pub sha: String,
+    /// pick | reword | squash | fixup | drop
+    pub action: String,
+    /// reword 必填；squash 可选，作为合并后的提交信息
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Blame 区间：连续若干行归属于同一提交
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitBlameHunkInfo {
+    pub sha: String,
+    /// 当前文件中的起始行（1-based）
+    pub start_line: u32,
+    pub orig_start_line: u32,
+    pub line_count: u32,
+    pub author: String,
+    pub author_email: String,
+    /// 作者时间（Unix 秒）
+    pub author_time: i64,
+    pub summary: String,
+    pub orig_path: String,
+    #[serde(default)]
+    pub boundary: bool,
+}
+
+/// 提交区间 diff 中单个文件的变更摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitRangeDiffFileInfo {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orig_path: Option<String>,
+    /// 状态码：A/M/D/R/C/T
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additions: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deletions: Option<i32>,
+    #[serde(default)]
+    pub is_binary: bool,
+}
+
+/// 二进制文件 diff 一侧的版本信息（`git_diff_result.old_blob` / `new_blob`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitBinaryBlobInfo {
+    pub size: u64,
+    pub mime_type: String,
+    /// 图片宽度（像素），非图片或无法解析时省略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// `include_blobs` 时附带的 base64 内容；超过 1MB 时省略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_base64: Option<String>,
+}
+
+/// 结构化 diff 的单个 hunk（`git_diff` 的 `format = "structured"`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitDiffHunkInfo {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    /// `@@ ... @@` 之后的函数上下文
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub header: String,
+    pub lines: Vec<GitDiffLineInfo>,
+}
+
+/// 结构化 diff 的单行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitDiffLineInfo {
+    /// context | add | delete
+    pub kind: String,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_line: Option<u32>,
+    /// 该行之后没有换行（`\ No newline at end of file`）
+    #[serde(default)]
+    pub no_newline: bool,
+    /// 行内变更区间（`intraline = true` 时）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub highlights: Vec<GitDiffHighlightRange>,
+}
+
+/// 行内高亮区间：按 Unicode 字符计的偏移，左闭右开
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct GitDiffHighlightRange {
+    pub start: u32,
+    pub end: u32,
+}
+
 /// 冲突文件条目信息（v1.40: 冲突向导协议 DTO）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConflictFileEntryInfo {
@@ -2441,6 +4089,21 @@ pub struct ConflictFileEntryInfo {
     pub staged: bool,
 }
 
+/// 冲突区块信息（`<<<<<<<` 到 `>>>>>>>`，行号 1-based 且含标记行）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictRegionInfo {
+    pub index: usize,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub ours_label: String,
+    pub theirs_label: String,
+    pub ours: String,
+    /// diff3 风格冲突中的公共祖先内容
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    pub theirs: String,
+}
+
 /// 冲突快照信息（v1.40: 冲突向导协议 DTO）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConflictSnapshotInfo {
@@ -2501,6 +4164,16 @@ pub struct KeybindingConfigInfo {
     pub context: String,
 }
 
+/// 设备设置覆盖摘要（用于协议传输）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DeviceSettingsProfileInfo {
+    pub device_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// 覆盖了共享值的字段
+    pub overrides: Vec<String>,
+}
+
 /// 工作空间待办项（用于协议传输）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceTodoInfo {
@@ -2528,6 +4201,79 @@ pub struct ProjectCommandInfo {
     pub interactive: bool,
 }
 
+/// 工作区中识别出的任务（用于协议传输）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceTaskInfo {
+    /// 工作区内唯一标识：`<runner>:<name>`
+    pub id: String,
+    /// 来源：npm | just | make | cargo
+    pub runner: String,
+    pub name: String,
+    /// 在工作区根目录执行的完整命令
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// 定义所在文件（相对工作区根）
+    pub file: String,
+}
+
+/// 工作区内进程监听的端口（用于协议传输）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListeningPortInfo {
+    pub port: u16,
+    /// 监听地址，如 `127.0.0.1`、`::`、`*`
+    pub address: String,
+    pub pid: u32,
+    /// 进程命令行（过长时截断）
+    pub command: String,
+}
+
+/// 清单中单个工作区的应用结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceManifestResult {
+    pub name: String,
+    /// created | existing | failed
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// 已存在的工作区分支与清单声明不一致时为声明的分支
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub declared_branch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub tasks: Vec<WorkspaceManifestTaskResult>,
+}
+
+/// 清单任务的启动结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceManifestTaskResult {
+    pub task: String,
+    /// started | running | failed
+    pub status: String,
+    /// 托管进程名，可用于 `proc_logs` / `proc_stop`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proc_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// 托管进程信息（用于协议传输）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProcInfo {
+    pub name: String,
+    pub command: String,
+    /// running | stopping | stopped | exited
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    /// 退出码；运行中或被信号终止时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub started_at_ms: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exited_at_ms: Option<i64>,
+}
+
 /// 工作流模板命令（协议传输用）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TemplateCommandInfo {
@@ -2891,7 +4637,11 @@ pub fn v1_capabilities() -> Vec<String> {
         "cwd_spawn".to_string(),
         "file_operations".to_string(),
         "file_index".to_string(),
+        "file_query".to_string(),
+        "file_highlight".to_string(),
         "git_tools".to_string(),
+        "git_status_all".to_string(),
+        "git_status_tree".to_string(),
         "git_stage_unstage".to_string(),
         "git_discard".to_string(),
         "git_branches".to_string(),
@@ -2929,6 +4679,16 @@ impl ServerMessage {
             workspace: None,
             session_id: None,
             cycle_id: None,
+            trace_id: None,
+        }
+    }
+
+    /// 为错误消息补上当前请求的 trace id（已带或不在请求作用域内时不变）
+    pub fn attach_trace_id(&mut self) {
+        if let ServerMessage::Error { trace_id, .. } = self {
+            if trace_id.is_none() {
+                *trace_id = crate::util::trace::current_trace_id();
+            }
         }
     }
 
@@ -2948,6 +4708,7 @@ impl ServerMessage {
             workspace,
             session_id,
             cycle_id,
+            trace_id: None,
         }
     }
 }
@@ -2973,6 +4734,72 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_import_project_from_url() {
+        let json = r#"{"type":"import_project_from_url","name":"demo","url":"https://example.com/demo.git","depth":1}"#;
+        match serde_json::from_str::<ClientMessage>(json) {
+            Ok(ClientMessage::ImportProjectFromUrl {
+                name,
+                url,
+                branch,
+                depth,
+            }) => {
+                assert_eq!(name, "demo");
+                assert_eq!(url, "https://example.com/demo.git");
+                assert_eq!(branch, None);
+                assert_eq!(depth, Some(1));
+            }
+            Ok(other) => panic!("Unexpected message type: {:?}", other),
+            Err(e) => panic!("Parse error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_parse_rename_workspace_defaults_to_name_only() {
+        let json = r#"{"type":"rename_workspace","project":"demo","workspace":"brave-otter","new_name":"login"}"#;
+        match serde_json::from_str::<ClientMessage>(json) {
+            Ok(ClientMessage::RenameWorkspace {
+                project,
+                workspace,
+                new_name,
+                rename_branch,
+                move_worktree,
+            }) => {
+                assert_eq!(project, "demo");
+                assert_eq!(workspace, "brave-otter");
+                assert_eq!(new_name, "login");
+                assert!(!rename_branch);
+                assert!(!move_worktree);
+            }
+            Ok(other) => panic!("Unexpected message type: {:?}", other),
+            Err(e) => panic!("Parse error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_parse_term_create_with_shell_and_args() {
+        let json = r#"{"type":"term_create","project":"demo","workspace":"default","shell":"fish","args":["-l"]}"#;
+        match serde_json::from_str::<ClientMessage>(json) {
+            Ok(ClientMessage::TermCreate { shell, args, .. }) => {
+                assert_eq!(shell.as_deref(), Some("fish"));
+                assert_eq!(args, Some(vec!["-l".to_string()]));
+            }
+            Ok(other) => panic!("Unexpected message type: {:?}", other),
+            Err(e) => panic!("Parse error: {}", e),
+        }
+
+        let json = r#"{"type":"spawn_terminal","cwd":"/tmp"}"#;
+        match serde_json::from_str::<ClientMessage>(json) {
+            Ok(ClientMessage::SpawnTerminal { cwd, shell, args }) => {
+                assert_eq!(cwd, "/tmp");
+                assert_eq!(shell, None);
+                assert_eq!(args, None);
+            }
+            Ok(other) => panic!("Unexpected message type: {:?}", other),
+            Err(e) => panic!("Parse error: {}", e),
+        }
+    }
+
     #[test]
     fn test_parse_cancel_project_command_with_task_id() {
         let json = r#"{"type":"cancel_project_command","project":"demo","workspace":"default","command_id":"build","task_id":"task-1"}"#;
@@ -3295,6 +5122,7 @@ mod tests {
             workspace: Some("missing".to_string()),
             session_id: None,
             cycle_id: None,
+            trace_id: None,
         };
         let contextual_json = serde_json::to_string(&contextual).unwrap();
         let parsed: ServerMessage = serde_json::from_str(&contextual_json).unwrap();
@@ -3314,6 +5142,27 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn error_attaches_trace_id_inside_request_scope() {
+        let mut outside = ServerMessage::make_error("git_error", "merge failed");
+        outside.attach_trace_id();
+        let json = serde_json::to_string(&outside).unwrap();
+        assert!(!json.contains("\"trace_id\""));
+
+        let traced = crate::util::trace::scope("0123abcd".to_string(), "git_merge", async {
+            let mut msg = ServerMessage::make_error("git_error", "merge failed");
+            msg.attach_trace_id();
+            msg
+        })
+        .await;
+        match traced {
+            ServerMessage::Error { trace_id, .. } => {
+                assert_eq!(trace_id.as_deref(), Some("0123abcd"));
+            }
+            _ => panic!("Expected ServerMessage::Error"),
+        }
+    }
+
     #[test]
     fn log_entry_should_be_rejected() {
         let payload = serde_json::json!({
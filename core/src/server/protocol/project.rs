@@ -9,6 +9,28 @@ pub enum ProjectRequest {
     ListProjects,
     ListWorkspaces {
         project: String,
+        /// 名称子串过滤（大小写不敏感）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        /// 状态过滤：ready | setup_failed | creating | initializing | destroying
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        status: Option<String>,
+        /// 分支名前缀过滤
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch_prefix: Option<String>,
+        /// 排序：name（默认，default 工作区置顶）| last_activity（最近活动在前）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sort: Option<String>,
+    },
+    /// 识别工作区根目录下的任务（npm scripts、justfile、Makefile、Cargo bin / example）
+    ListWorkspaceTasks {
+        project: String,
+        workspace: String,
+    },
+    /// 列出工作目录位于工作区内的进程所监听的 TCP 端口
+    ListPorts {
+        project: String,
+        workspace: String,
     },
     SelectWorkspace {
         project: String,
@@ -18,12 +40,30 @@ pub enum ProjectRequest {
         name: String,
         path: String,
     },
+    /// 克隆远程仓库到托管目录并导入为项目；克隆期间推送 `project_clone_progress`
+    ImportProjectFromUrl {
+        name: String,
+        url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
+        /// 浅克隆深度
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        depth: Option<u32>,
+    },
     CreateWorkspace {
         project: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         from_branch: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         template_id: Option<String>,
+        /// 稀疏检出（cone 模式）的目录列表；为空时完整检出
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        sparse_paths: Vec<String>,
+    },
+    /// 按声明式清单（TOML / JSON）创建缺失的工作区并启动其任务，标记清单外的工作区
+    ApplyWorkspaceManifest {
+        project: String,
+        manifest: String,
     },
     RemoveProject {
         name: String,
@@ -32,6 +72,41 @@ pub enum ProjectRequest {
         project: String,
         workspace: String,
     },
+    /// 重命名工作空间；可选同步重命名分支与 worktree 目录
+    RenameWorkspace {
+        project: String,
+        workspace: String,
+        new_name: String,
+        #[serde(default)]
+        rename_branch: bool,
+        #[serde(default)]
+        move_worktree: bool,
+    },
+    /// 修复 worktree 目录缺失或元数据过期的工作空间
+    RepairWorkspace {
+        project: String,
+        workspace: String,
+    },
+    /// 关联 issue：`url` 为完整地址，或 `#123` / `123`（按 `origin` 解析为 GitHub / GitLab issue 地址）；
+    /// 省略或为空时取消关联
+    LinkWorkspaceIssue {
+        project: String,
+        workspace: String,
+        #[serde(default)]
+        url: Option<String>,
+    },
+    /// 调整工作空间的稀疏检出目录（cone 模式）；`paths` 为空时恢复完整检出
+    WorkspaceSetSparsePaths {
+        project: String,
+        workspace: String,
+        #[serde(default)]
+        paths: Vec<String>,
+    },
+    /// 修改项目默认分支（集成、rebase、合并与分歧检查均以此为准）
+    SetProjectDefaultBranch {
+        project: String,
+        default_branch: String,
+    },
     SaveProjectCommands {
         project: String,
         commands: Vec<super::ProjectCommandInfo>,
@@ -48,6 +123,31 @@ pub enum ProjectRequest {
         #[serde(skip_serializing_if = "Option::is_none")]
         task_id: Option<String>,
     },
+    /// 在工作区中启动托管进程（如 dev server），连接断开后继续运行
+    ProcStart {
+        project: String,
+        workspace: String,
+        name: String,
+        command: String,
+    },
+    ProcStop {
+        project: String,
+        workspace: String,
+        name: String,
+    },
+    ProcList {
+        project: String,
+        workspace: String,
+    },
+    /// 托管进程最近的输出
+    ProcLogs {
+        project: String,
+        workspace: String,
+        name: String,
+        /// 返回的行数，默认 200
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        lines: Option<usize>,
+    },
     // v1.40: 工作流模板管理
     ListTemplates,
     SaveTemplate {
@@ -74,6 +174,19 @@ pub enum ProjectResponse {
     Workspaces {
         project: String,
         items: Vec<super::WorkspaceInfo>,
+        /// 过滤前的 workspace 总数（含 default），仅在指定过滤条件时返回
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        total: Option<usize>,
+    },
+    WorkspaceTasks {
+        project: String,
+        workspace: String,
+        items: Vec<super::WorkspaceTaskInfo>,
+    },
+    WorkspacePorts {
+        project: String,
+        workspace: String,
+        items: Vec<super::ListeningPortInfo>,
     },
     SelectedWorkspace {
         project: String,
@@ -82,6 +195,18 @@ pub enum ProjectResponse {
         session_id: String,
         shell: String,
     },
+    /// 项目克隆进度（`import_project_from_url`）
+    ProjectCloneProgress {
+        name: String,
+        /// 阶段：`receiving_objects` / `resolving_deltas` / `updating_files` 等
+        phase: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        percent: Option<u8>,
+        message: String,
+        /// 克隆操作 id，可用于 `git_op_cancel`
+        #[serde(default)]
+        op_id: String,
+    },
     ProjectImported {
         name: String,
         root: String,
@@ -89,10 +214,25 @@ pub enum ProjectResponse {
         #[serde(skip_serializing_if = "Option::is_none")]
         workspace: Option<super::WorkspaceInfo>,
     },
+    /// 工作空间创建过程中的准备进度（`create_workspace`，目前为 Git LFS 对象下载）
+    WorkspaceSetupProgress {
+        project: String,
+        workspace: String,
+        /// 阶段：`lfs_pull` / `downloading_lfs_objects` / `lfs_done` / `lfs_failed` / `lfs_unavailable`
+        phase: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        percent: Option<u8>,
+        message: String,
+    },
     WorkspaceCreated {
         project: String,
         workspace: super::WorkspaceInfo,
     },
+    WorkspaceManifestApplied {
+        project: String,
+        results: Vec<super::WorkspaceManifestResult>,
+        extra_workspaces: Vec<String>,
+    },
     ProjectRemoved {
         name: String,
         ok: bool,
@@ -106,6 +246,53 @@ pub enum ProjectResponse {
         #[serde(skip_serializing_if = "Option::is_none")]
         message: Option<String>,
     },
+    WorkspaceRenamed {
+        project: String,
+        workspace: String,
+        new_name: String,
+        ok: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        info: Option<super::WorkspaceInfo>,
+    },
+    WorkspaceRepaired {
+        project: String,
+        workspace: String,
+        ok: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        actions: Vec<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        info: Option<super::WorkspaceInfo>,
+    },
+    WorkspaceIssueLinked {
+        project: String,
+        workspace: String,
+        ok: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        info: Option<super::WorkspaceInfo>,
+    },
+    WorkspaceSparsePathsSet {
+        project: String,
+        workspace: String,
+        ok: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        info: Option<super::WorkspaceInfo>,
+    },
+    ProjectDefaultBranchSet {
+        project: String,
+        ok: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        default_branch: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
     ProjectCommandsSaved {
         project: String,
         ok: bool,
@@ -126,6 +313,9 @@ pub enum ProjectResponse {
         ok: bool,
         #[serde(skip_serializing_if = "Option::is_none")]
         message: Option<String>,
+        /// 进程退出码；启动失败或被信号终止时为空
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        exit_code: Option<i32>,
     },
     ProjectCommandCancelled {
         project: String,
@@ -137,6 +327,36 @@ pub enum ProjectResponse {
         task_id: String,
         line: String,
     },
+    ProcStarted {
+        project: String,
+        workspace: String,
+        info: super::ProcInfo,
+    },
+    /// `proc_stop` 的响应；进程退出后另行推送 `proc_exited`
+    ProcStopped {
+        project: String,
+        workspace: String,
+        info: super::ProcInfo,
+    },
+    /// 托管进程退出（广播给所有连接）
+    ProcExited {
+        project: String,
+        workspace: String,
+        info: super::ProcInfo,
+    },
+    ProcListResult {
+        project: String,
+        workspace: String,
+        items: Vec<super::ProcInfo>,
+    },
+    ProcLogsResult {
+        project: String,
+        workspace: String,
+        name: String,
+        lines: Vec<String>,
+        /// 未返回的更早输出行数（含缓冲区已丢弃的部分）
+        dropped_lines: u64,
+    },
     // v1.40: 工作流模板管理
     Templates {
         items: Vec<super::TemplateInfo>,
@@ -21,6 +21,26 @@ pub enum SettingsRequest {
         #[serde(default)]
         node_discovery_enabled: Option<bool>,
         #[serde(default)]
+        default_shell: Option<Option<String>>,
+        #[serde(default)]
+        git_proxy: Option<Option<String>>,
+        #[serde(default)]
+        git_offline_mode: Option<bool>,
+        #[serde(default)]
+        git_hosting_tokens: Option<std::collections::HashMap<String, Option<String>>>,
+        #[serde(default)]
+        terminal_keep_alive: Option<bool>,
+        #[serde(default)]
+        terminal_prevent_sleep: Option<bool>,
+        #[serde(default)]
+        terminal_shell_integration: Option<bool>,
+        #[serde(default)]
+        push_relay_url: Option<Option<String>>,
+        #[serde(default)]
+        push_tokens: Option<std::collections::HashMap<String, Option<String>>>,
+        #[serde(default)]
+        git_auto_fetch_interval_secs: Option<u64>,
+        #[serde(default)]
         evolution_default_profiles: Option<Vec<super::EvolutionStageProfileInfo>>,
         #[serde(default)]
         workspace_todos: Option<std::collections::HashMap<String, Vec<super::WorkspaceTodoInfo>>>,
@@ -28,6 +48,12 @@ pub enum SettingsRequest {
         keybindings: Option<Vec<super::KeybindingConfigInfo>>,
         #[serde(default)]
         editor_formatting_configs: Option<Vec<super::formatting::EditorFormattingLanguageConfig>>,
+        #[serde(default)]
+        device_id: Option<String>,
+        #[serde(default)]
+        device_name: Option<String>,
+        #[serde(default)]
+        reset_device_overrides: bool,
     },
 }
 
@@ -45,6 +71,26 @@ pub enum SettingsResponse {
         node_name: Option<String>,
         #[serde(default)]
         node_discovery_enabled: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default_shell: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        git_proxy: Option<String>,
+        #[serde(default)]
+        git_offline_mode: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        git_hosting_token_hosts: Vec<String>,
+        #[serde(default)]
+        terminal_keep_alive: bool,
+        #[serde(default)]
+        terminal_prevent_sleep: bool,
+        #[serde(default)]
+        terminal_shell_integration: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        push_relay_url: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        push_token_devices: Vec<String>,
+        #[serde(default)]
+        git_auto_fetch_interval_secs: u64,
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         evolution_default_profiles: Vec<super::EvolutionStageProfileInfo>,
         #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
@@ -56,6 +102,12 @@ pub enum SettingsResponse {
         keybindings: Vec<super::KeybindingConfigInfo>,
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         editor_formatting_configs: Vec<super::formatting::EditorFormattingLanguageConfig>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        device_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        device_overrides: Vec<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        device_profiles: Vec<super::DeviceSettingsProfileInfo>,
     },
     ClientSettingsSaved {
         ok: bool,
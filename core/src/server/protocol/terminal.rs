@@ -27,6 +27,10 @@ pub enum TerminalRequest {
     },
     SpawnTerminal {
         cwd: String,
+        #[serde(default)]
+        shell: Option<String>,
+        #[serde(default)]
+        args: Option<Vec<String>>,
     },
     KillTerminal,
     TermCreate {
@@ -36,11 +40,28 @@ pub enum TerminalRequest {
         name: Option<String>,
         #[serde(default)]
         icon: Option<String>,
+        #[serde(default)]
+        shell: Option<String>,
+        #[serde(default)]
+        args: Option<Vec<String>>,
+        #[serde(default)]
+        keep_alive: Option<bool>,
     },
     TermList,
     TermClose {
         term_id: String,
     },
+    TermSetKeepAlive {
+        term_id: String,
+        keep_alive: bool,
+    },
+    TermRename {
+        term_id: String,
+        name: String,
+    },
+    TermReorder {
+        term_ids: Vec<String>,
+    },
     TermFocus {
         term_id: String,
     },
@@ -55,6 +76,14 @@ pub enum TerminalRequest {
         term_id: String,
         bytes: u64,
     },
+    /// 暂停 / 恢复当前连接上某终端的实时输出；暂停期间 PTY 照常运行，输出只写入 scrollback
+    TermFlowControl {
+        term_id: String,
+        paused: bool,
+    },
+    TermCommandHistory {
+        term_id: String,
+    },
 }
 
 /// 终端相关的服务端消息
@@ -86,6 +115,43 @@ pub enum TerminalResponse {
     TermClosed {
         term_id: String,
     },
+    TermKeepAliveUpdated {
+        term_id: String,
+        keep_alive: bool,
+        prevent_sleep: bool,
+    },
+    TermRenamed {
+        term_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+    },
+    TermReordered {
+        term_ids: Vec<String>,
+    },
+    TermCwdChanged {
+        term_id: String,
+        project: String,
+        workspace: String,
+        cwd: String,
+    },
+    TermCommandHistoryResult {
+        term_id: String,
+        commands: Vec<super::TerminalCommandInfo>,
+    },
+    TermCommandFinished {
+        term_id: String,
+        project: String,
+        workspace: String,
+        command: super::TerminalCommandInfo,
+    },
+    TermAgentStateChanged {
+        term_id: String,
+        project: String,
+        workspace: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        agent: Option<String>,
+        awaiting_input: bool,
+    },
     TermAttached {
         term_id: String,
         project: String,
@@ -99,6 +165,11 @@ pub enum TerminalResponse {
         #[serde(default, skip_serializing_if = "Option::is_none")]
         icon: Option<String>,
     },
+    TermFlowState {
+        term_id: String,
+        paused: bool,
+        dropped_bytes: u64,
+    },
     #[serde(rename = "output_batch")]
     OutputBatch {
         items: Vec<TerminalOutputBatchItem>,
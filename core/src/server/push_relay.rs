@@ -0,0 +1,312 @@
+//! 推送通知中继
+//!
+//! iOS App 进入后台后 WebSocket 会断开，收不到任何事件。配置了推送中继地址
+//! （用户自建、持有 APNs 证书的 HTTP 服务）且设备登记了 APNs 令牌时，Core 把重要事件
+//! 逐设备 POST 到中继，由中继转发为 APNs 推送：
+//! - 耗时较长的终端命令结束（shell 集成）；
+//! - git 操作产生冲突；
+//! - 终端中的编码 Agent 等待输入。
+//!
+//! 事件经有界队列交给后台任务发送，队列满时丢弃，不阻塞调用方。
+//! 终端事件由终端状态跟踪任务经 [`notify_message`] 上报；git 冲突结果是请求响应，
+//! 在统一出口 [`crate::server::ws::send_message`] 经 [`notify_response`] 识别，handler 无需各自上报。
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use crate::server::context::SharedAppState;
+use crate::server::protocol::ServerMessage;
+
+/// 终端命令耗时超过该值才推送结束通知
+pub const LONG_COMMAND_THRESHOLD_MS: u64 = 30_000;
+/// 待发送事件队列容量
+const PUSH_QUEUE_CAPACITY: usize = 64;
+/// 单次 POST 超时
+const PUSH_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushEventKind {
+    CommandFinished,
+    ConflictDetected,
+    AgentWaiting,
+}
+
+/// 推送给中继的事件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PushEvent {
+    pub kind: PushEventKind,
+    pub title: String,
+    pub body: String,
+    pub project: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub term_id: Option<String>,
+}
+
+/// POST 到中继的请求体
+#[derive(Debug, Serialize)]
+struct RelayRequest<'a> {
+    device_id: &'a str,
+    device_token: &'a str,
+    #[serde(flatten)]
+    event: &'a PushEvent,
+}
+
+impl PushEvent {
+    /// 从服务端消息中提取需要推送的事件；不重要的消息返回 None
+    pub fn from_message(msg: &ServerMessage) -> Option<Self> {
+        Self::from_terminal_event(msg).or_else(|| Self::from_git_result(msg))
+    }
+
+    fn from_terminal_event(msg: &ServerMessage) -> Option<Self> {
+        match msg {
+            ServerMessage::TermCommandFinished {
+                term_id,
+                project,
+                workspace,
+                command,
+            } if command.duration_ms >= LONG_COMMAND_THRESHOLD_MS => {
+                let status = match command.exit_code {
+                    Some(0) => "succeeded".to_string(),
+                    Some(code) => format!("failed with exit code {}", code),
+                    None => "finished".to_string(),
+                };
+                Some(Self {
+                    kind: PushEventKind::CommandFinished,
+                    title: format!("Command {}", status),
+                    body: format!("{} ({}s)", command.command, command.duration_ms / 1000),
+                    project: project.clone(),
+                    workspace: Some(workspace.clone()),
+                    term_id: Some(term_id.clone()),
+                })
+            }
+            ServerMessage::TermAgentStateChanged {
+                term_id,
+                project,
+                workspace,
+                agent: Some(agent),
+                awaiting_input: true,
+            } => Some(Self {
+                kind: PushEventKind::AgentWaiting,
+                title: format!("{} is waiting for input", agent),
+                body: format!("{}/{}", project, workspace),
+                project: project.clone(),
+                workspace: Some(workspace.clone()),
+                term_id: Some(term_id.clone()),
+            }),
+            _ => None,
+        }
+    }
+
+    /// git 操作结果中的冲突
+    fn from_git_result(msg: &ServerMessage) -> Option<Self> {
+        match msg {
+            ServerMessage::GitRebaseResult {
+                project,
+                workspace,
+                state,
+                conflicts,
+                ..
+            }
+            | ServerMessage::GitSequencerResult {
+                project,
+                workspace,
+                state,
+                conflicts,
+                ..
+            } if state.contains("conflict") => {
+                Some(Self::conflict(project, Some(workspace), conflicts))
+            }
+            ServerMessage::GitMergeToDefaultResult {
+                project,
+                state,
+                conflicts,
+                ..
+            }
+            | ServerMessage::GitRebaseOntoDefaultResult {
+                project,
+                state,
+                conflicts,
+                ..
+            } if state.contains("conflict") => Some(Self::conflict(project, None, conflicts)),
+            _ => None,
+        }
+    }
+
+    fn conflict(project: &str, workspace: Option<&String>, conflicts: &[String]) -> Self {
+        let location = match workspace {
+            Some(workspace) => format!("{}/{}", project, workspace),
+            None => project.to_string(),
+        };
+        Self {
+            kind: PushEventKind::ConflictDetected,
+            title: "Conflict detected".to_string(),
+            body: format!("{}: {} conflicted file(s)", location, conflicts.len()),
+            project: project.to_string(),
+            workspace: workspace.cloned(),
+            term_id: None,
+        }
+    }
+}
+
+static PUSH_TX: OnceLock<mpsc::Sender<PushEvent>> = OnceLock::new();
+
+/// 若消息需要推送则入队；推送中继未启动或队列已满时忽略
+pub fn notify_message(msg: &ServerMessage) {
+    enqueue(PushEvent::from_message(msg));
+}
+
+/// 发往客户端的请求响应：只识别 git 冲突结果。
+///
+/// 终端事件经广播逐连接下发也会经过同一出口，已由跟踪任务上报，这里不重复入队。
+pub fn notify_response(msg: &ServerMessage) {
+    enqueue(PushEvent::from_git_result(msg));
+}
+
+fn enqueue(event: Option<PushEvent>) {
+    let (Some(tx), Some(event)) = (PUSH_TX.get(), event) else {
+        return;
+    };
+    if tx.try_send(event).is_err() {
+        debug!("Push relay queue full, event dropped");
+    }
+}
+
+/// 校验推送中继地址：必须为 http(s) URL
+pub fn validate_relay_url(url: &str) -> Result<(), String> {
+    match reqwest::Url::parse(url.trim()) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.has_host() => Ok(()),
+        _ => Err(format!(
+            "Invalid push relay url: {} (must start with http:// or https://)",
+            url
+        )),
+    }
+}
+
+/// 启动推送中继后台任务（进程内只启动一次）
+pub fn spawn_push_relay(app_state: SharedAppState) {
+    let (tx, mut rx) = mpsc::channel::<PushEvent>(PUSH_QUEUE_CAPACITY);
+    if PUSH_TX.set(tx).is_err() {
+        return;
+    }
+    tokio::spawn(async move {
+        let client = match reqwest::Client::builder()
+            .timeout(PUSH_REQUEST_TIMEOUT)
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => {
+                warn!(error = %e, "Failed to create push relay client");
+                return;
+            }
+        };
+        while let Some(event) = rx.recv().await {
+            let (url, tokens) = {
+                let state = app_state.read().await;
+                (
+                    state.client_settings.push_relay_url.clone(),
+                    state.client_settings.push_tokens.clone(),
+                )
+            };
+            let Some(url) = url else {
+                continue;
+            };
+            for (device_id, device_token) in &tokens {
+                let request = RelayRequest {
+                    device_id,
+                    device_token,
+                    event: &event,
+                };
+                match client.post(&url).json(&request).send().await {
+                    Ok(resp) if resp.status().is_success() => {
+                        debug!(device_id = %device_id, kind = ?event.kind, "Push relayed");
+                    }
+                    Ok(resp) => {
+                        warn!(
+                            device_id = %device_id,
+                            status = %resp.status(),
+                            "Push relay rejected event"
+                        );
+                    }
+                    Err(e) => {
+                        warn!(device_id = %device_id, error = %e, "Push relay request failed");
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::protocol::TerminalCommandInfo;
+
+    fn command_finished(duration_ms: u64) -> ServerMessage {
+        ServerMessage::TermCommandFinished {
+            term_id: "t1".to_string(),
+            project: "demo".to_string(),
+            workspace: "main".to_string(),
+            command: TerminalCommandInfo {
+                command: "cargo build".to_string(),
+                exit_code: Some(1),
+                started_at_ms: 0,
+                duration_ms,
+                cwd: "/repo".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn only_long_commands_agent_waits_and_conflicts_are_pushed() {
+        assert_eq!(PushEvent::from_message(&command_finished(1_000)), None);
+        let event = PushEvent::from_message(&command_finished(45_000)).unwrap();
+        assert_eq!(event.kind, PushEventKind::CommandFinished);
+        assert_eq!(event.title, "Command failed with exit code 1");
+        assert_eq!(event.body, "cargo build (45s)");
+
+        let waiting = ServerMessage::TermAgentStateChanged {
+            term_id: "t1".to_string(),
+            project: "demo".to_string(),
+            workspace: "main".to_string(),
+            agent: Some("claude".to_string()),
+            awaiting_input: true,
+        };
+        assert_eq!(
+            PushEvent::from_message(&waiting).map(|e| e.kind),
+            Some(PushEventKind::AgentWaiting)
+        );
+
+        let conflict = ServerMessage::GitRebaseOntoDefaultResult {
+            project: "demo".to_string(),
+            ok: false,
+            state: "rebase_conflict".to_string(),
+            message: None,
+            conflicts: vec!["src/lib.rs".to_string()],
+            conflict_files: vec![],
+            head_sha: None,
+            integration_path: None,
+        };
+        let event = PushEvent::from_message(&conflict).unwrap();
+        assert_eq!(event.kind, PushEventKind::ConflictDetected);
+        assert_eq!(event.body, "demo: 1 conflicted file(s)");
+
+        // 响应出口只识别 git 结果，终端事件经广播下发时不重复推送
+        assert_eq!(PushEvent::from_git_result(&conflict), Some(event));
+        assert_eq!(PushEvent::from_git_result(&waiting), None);
+    }
+
+    #[test]
+    fn validate_relay_url_requires_http_scheme() {
+        assert!(validate_relay_url("https://push.example.com/notify").is_ok());
+        assert!(validate_relay_url("http://192.168.1.2:8080").is_ok());
+        assert!(validate_relay_url("push.example.com").is_err());
+        assert!(validate_relay_url("ftp://push.example.com").is_err());
+    }
+}
@@ -0,0 +1,581 @@
+//! 工作区批量查找替换（预览 → 确认应用 → 撤销）
+//!
+//! `prepare_replace` 扫描候选文件并生成带编号的匹配预览，结果以变更集形式暂存；
+//! `apply_replace` 只应用客户端勾选的匹配，写入前校验文件自预览后未被修改，
+//! 任一文件写入失败时回滚已写入的文件；成功后生成撤销记录供 `undo_replace` 还原。
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use regex::{Regex, RegexBuilder};
+
+use super::file_api::{self, FileApiError, MAX_FILE_SIZE};
+
+/// 单次预览最多记录的匹配数
+pub const MAX_REPLACE_MATCHES: usize = 10_000;
+/// 变更集 / 撤销记录的保留时长
+const REPLACE_STATE_TTL: Duration = Duration::from_secs(30 * 60);
+/// 预览行文本的最大长度（字节）
+const MAX_PREVIEW_LEN: usize = 512;
+
+/// 查找替换错误
+#[derive(Debug)]
+pub enum ReplaceError {
+    InvalidQuery(String),
+    ChangeSetNotFound,
+    /// 文件自预览后已被修改
+    Stale(String),
+    File(String, FileApiError),
+    /// 请求被客户端取消
+    Cancelled,
+}
+
+impl std::fmt::Display for ReplaceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplaceError::InvalidQuery(msg) => write!(f, "Invalid query: {}", msg),
+            ReplaceError::ChangeSetNotFound => write!(f, "Change set not found or expired"),
+            ReplaceError::Stale(path) => {
+                write!(f, "File changed since preview: {}", path)
+            }
+            ReplaceError::File(path, e) => write!(f, "{}: {}", path, e),
+            ReplaceError::Cancelled => write!(f, "Replace preview cancelled"),
+        }
+    }
+}
+
+impl ReplaceError {
+    /// 协议错误码
+    pub fn code(&self) -> &'static str {
+        match self {
+            ReplaceError::InvalidQuery(_) => "invalid_query",
+            ReplaceError::ChangeSetNotFound => "change_set_not_found",
+            ReplaceError::Stale(_) => "stale_change_set",
+            ReplaceError::File(..) => "io_error",
+            ReplaceError::Cancelled => crate::util::cancel::CANCELLED_CODE,
+        }
+    }
+}
+
+/// 查找替换参数
+#[derive(Debug, Clone)]
+pub struct ReplaceQuery {
+    pub query: String,
+    pub replacement: String,
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub include_globs: Vec<String>,
+}
+
+/// 单条匹配（行内字节区间）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplaceMatch {
+    /// 变更集内唯一编号，`apply_replace` 按此勾选
+    pub id: u32,
+    pub path: String,
+    /// 行号（1-based）
+    pub line: u32,
+    /// 行内起始字节偏移（0-based）
+    pub start: u32,
+    /// 行内结束字节偏移（不含）
+    pub end: u32,
+    pub preview: String,
+    pub matched: String,
+    pub replacement: String,
+}
+
+/// 预览结果
+#[derive(Debug, Clone)]
+pub struct ReplacePreview {
+    pub change_set_id: String,
+    pub matches: Vec<ReplaceMatch>,
+    pub file_count: usize,
+    pub truncated: bool,
+}
+
+/// 应用结果
+#[derive(Debug, Clone)]
+pub struct ReplaceApplyResult {
+    pub files_changed: Vec<String>,
+    pub replacements: u32,
+    pub undo_id: Option<String>,
+}
+
+struct ChangeSet {
+    root: String,
+    matches: Vec<ReplaceMatch>,
+    file_hashes: HashMap<String, u64>,
+    created_at: Instant,
+}
+
+struct UndoRecord {
+    root: String,
+    /// path -> (原始内容, 应用后内容哈希)
+    files: Vec<(String, String, u64)>,
+    created_at: Instant,
+}
+
+static CHANGE_SETS: LazyLock<Mutex<HashMap<String, ChangeSet>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static UNDO_RECORDS: LazyLock<Mutex<HashMap<String, UndoRecord>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn root_key(root: &Path) -> String {
+    root.to_string_lossy().to_string()
+}
+
+fn purge_expired() {
+    if let Ok(mut sets) = CHANGE_SETS.lock() {
+        sets.retain(|_, set| set.created_at.elapsed() < REPLACE_STATE_TTL);
+    }
+    if let Ok(mut records) = UNDO_RECORDS.lock() {
+        records.retain(|_, record| record.created_at.elapsed() < REPLACE_STATE_TTL);
+    }
+}
+
+/// 简单 glob 匹配：`*` 不跨目录、`**` 可跨目录、`?` 匹配单个字符
+///
+/// 不含 `/` 的模式只与文件名比较（`*.rs` 匹配任意深度的 Rust 文件）。
+pub fn glob_matches(pattern: &str, path: &str) -> bool {
+    /// 带记忆的回溯：每个 (模式位置, 路径位置) 只展开一次，多个 `*` / `**` 时不会指数级回溯
+    struct Matcher<'a> {
+        p: &'a [u8],
+        s: &'a [u8],
+        failed: Vec<bool>,
+    }
+
+    impl Matcher<'_> {
+        fn matches(&mut self, pi: usize, si: usize) -> bool {
+            let state = pi * (self.s.len() + 1) + si;
+            if self.failed[state] {
+                return false;
+            }
+            let (p, s) = (&self.p[pi..], &self.s[si..]);
+            let matched = match p.first() {
+                None => s.is_empty(),
+                Some(b'*') if p.get(1) == Some(&b'*') => {
+                    // `**/` 也可匹配零层目录
+                    let next = pi + 2 + usize::from(p.get(2) == Some(&b'/'));
+                    (si..=self.s.len()).any(|i| self.matches(next, i))
+                }
+                Some(b'*') => (si..=self.s.len())
+                    .take_while(|&i| i == si || self.s[i - 1] != b'/')
+                    .any(|i| self.matches(pi + 1, i)),
+                Some(b'?') => !s.is_empty() && s[0] != b'/' && self.matches(pi + 1, si + 1),
+                Some(c) => s.first() == Some(c) && self.matches(pi + 1, si + 1),
+            };
+            if !matched {
+                self.failed[state] = true;
+            }
+            matched
+        }
+    }
+
+    fn matches(p: &[u8], s: &[u8]) -> bool {
+        let mut matcher = Matcher {
+            p,
+            s,
+            failed: vec![false; (p.len() + 1) * (s.len() + 1)],
+        };
+        matcher.matches(0, 0)
+    }
+    let pattern = pattern.trim().trim_start_matches("./");
+    if pattern.contains('/') {
+        matches(pattern.as_bytes(), path.as_bytes())
+    } else {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        matches(pattern.as_bytes(), name.as_bytes())
+    }
+}
+
+fn build_regex(query: &ReplaceQuery) -> Result<Regex, ReplaceError> {
+    if query.query.is_empty() {
+        return Err(ReplaceError::InvalidQuery("query is empty".to_string()));
+    }
+    let pattern = if query.regex {
+        query.query.clone()
+    } else {
+        regex::escape(&query.query)
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!query.case_sensitive)
+        .build()
+        .map_err(|e| ReplaceError::InvalidQuery(e.to_string()))
+}
+
+fn truncate_preview(line: &str) -> String {
+    if line.len() <= MAX_PREVIEW_LEN {
+        return line.to_string();
+    }
+    let mut cut = MAX_PREVIEW_LEN;
+    while !line.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    line[..cut].to_string()
+}
+
+/// 扫描候选文件（相对路径，通常来自文件索引）生成替换预览并暂存变更集
+pub fn prepare_replace(
+    workspace_root: &Path,
+    files: &[String],
+    query: &ReplaceQuery,
+) -> Result<ReplacePreview, ReplaceError> {
+    purge_expired();
+    let re = build_regex(query)?;
+
+    let mut matches = Vec::new();
+    let mut file_hashes = HashMap::new();
+    let mut truncated = false;
+
+    'files: for rel_path in files {
+        if crate::util::cancel::is_cancelled() {
+            return Err(ReplaceError::Cancelled);
+        }
+        if !query.include_globs.is_empty()
+            && !query
+                .include_globs
+                .iter()
+                .any(|glob| glob_matches(glob, rel_path))
+        {
+            continue;
+        }
+        let abs_path = workspace_root.join(rel_path);
+        match std::fs::metadata(&abs_path) {
+            Ok(meta) if meta.is_file() && meta.len() <= MAX_FILE_SIZE => {}
+            _ => continue,
+        }
+        let Ok(content) = std::fs::read_to_string(&abs_path) else {
+            continue;
+        };
+        if content.contains('\0') || !re.is_match(&content) {
+            continue;
+        }
+
+        let mut file_matched = false;
+        for (idx, line) in content.lines().enumerate() {
+            for caps in re.captures_iter(line) {
+                let Some(m) = caps.get(0) else { continue };
+                if m.is_empty() {
+                    continue;
+                }
+                if matches.len() >= MAX_REPLACE_MATCHES {
+                    truncated = true;
+                    break 'files;
+                }
+                let mut replacement = String::new();
+                if query.regex {
+                    caps.expand(&query.replacement, &mut replacement);
+                } else {
+                    replacement.push_str(&query.replacement);
+                }
+                matches.push(ReplaceMatch {
+                    id: matches.len() as u32,
+                    path: rel_path.clone(),
+                    line: (idx + 1) as u32,
+                    start: m.start() as u32,
+                    end: m.end() as u32,
+                    preview: truncate_preview(line),
+                    matched: m.as_str().to_string(),
+                    replacement,
+                });
+                file_matched = true;
+            }
+        }
+        if file_matched {
+            file_hashes.insert(rel_path.clone(), content_hash(&content));
+        }
+    }
+
+    let change_set_id = uuid::Uuid::new_v4().to_string();
+    let file_count = file_hashes.len();
+    if let Ok(mut sets) = CHANGE_SETS.lock() {
+        sets.insert(
+            change_set_id.clone(),
+            ChangeSet {
+                root: root_key(workspace_root),
+                matches: matches.clone(),
+                file_hashes,
+                created_at: Instant::now(),
+            },
+        );
+    }
+
+    Ok(ReplacePreview {
+        change_set_id,
+        matches,
+        file_count,
+        truncated,
+    })
+}
+
+/// 对单个文件内容应用一组匹配（同一行内按起始偏移倒序替换，保留原有换行符）
+fn apply_matches_to_content(content: &str, matches: &[&ReplaceMatch]) -> String {
+    let mut by_line: BTreeMap<u32, Vec<&ReplaceMatch>> = BTreeMap::new();
+    for m in matches {
+        by_line.entry(m.line).or_default().push(m);
+    }
+
+    let mut output = String::with_capacity(content.len());
+    for (idx, raw_line) in content.split_inclusive('\n').enumerate() {
+        let line_no = (idx + 1) as u32;
+        let Some(line_matches) = by_line.get_mut(&line_no) else {
+            output.push_str(raw_line);
+            continue;
+        };
+        let mut line = raw_line.to_string();
+        line_matches.sort_by_key(|m| std::cmp::Reverse(m.start));
+        for m in line_matches.iter() {
+            let (start, end) = (m.start as usize, m.end as usize);
+            if end <= line.len() && line.is_char_boundary(start) && line.is_char_boundary(end) {
+                line.replace_range(start..end, &m.replacement);
+            }
+        }
+        output.push_str(&line);
+    }
+    output
+}
+
+/// 应用变更集中被勾选的匹配（`accepted = None` 表示全部应用）
+///
+/// 先校验全部涉及文件自预览后未变化，再逐个原子写入；中途失败会回滚已写入的文件。
+pub fn apply_replace(
+    workspace_root: &Path,
+    change_set_id: &str,
+    accepted: Option<&[u32]>,
+) -> Result<ReplaceApplyResult, ReplaceError> {
+    purge_expired();
+    let change_set = CHANGE_SETS
+        .lock()
+        .ok()
+        .and_then(|mut sets| sets.remove(change_set_id))
+        .filter(|set| set.root == root_key(workspace_root))
+        .ok_or(ReplaceError::ChangeSetNotFound)?;
+
+    let mut by_file: BTreeMap<&str, Vec<&ReplaceMatch>> = BTreeMap::new();
+    for m in &change_set.matches {
+        if accepted.is_none_or(|ids| ids.contains(&m.id)) {
+            by_file.entry(m.path.as_str()).or_default().push(m);
+        }
+    }
+
+    // 阶段一：读取并校验所有文件，计算新内容
+    let mut planned: Vec<(String, String, String)> = Vec::new();
+    let mut replacements = 0u32;
+    for (path, file_matches) in &by_file {
+        let original = file_api::read_file(workspace_root, path)
+            .map(|(content, _)| content)
+            .map_err(|e| ReplaceError::File(path.to_string(), e))?;
+        if change_set.file_hashes.get(*path) != Some(&content_hash(&original)) {
+            return Err(ReplaceError::Stale(path.to_string()));
+        }
+        let updated = apply_matches_to_content(&original, file_matches);
+        replacements += file_matches.len() as u32;
+        planned.push((path.to_string(), original, updated));
+    }
+
+    // 阶段二：逐个写入，失败时回滚
+    let mut written: Vec<&(String, String, String)> = Vec::new();
+    for entry in &planned {
+        if let Err(e) = file_api::write_file(workspace_root, &entry.0, &entry.2) {
+            for (path, original, _) in written {
+                let _ = file_api::write_file(workspace_root, path, original);
+            }
+            return Err(ReplaceError::File(entry.0.clone(), e));
+        }
+        written.push(entry);
+    }
+
+    let undo_id = if planned.is_empty() {
+        None
+    } else {
+        let undo_id = uuid::Uuid::new_v4().to_string();
+        if let Ok(mut records) = UNDO_RECORDS.lock() {
+            records.insert(
+                undo_id.clone(),
+                UndoRecord {
+                    root: change_set.root.clone(),
+                    files: planned
+                        .iter()
+                        .map(|(path, original, updated)| {
+                            (path.clone(), original.clone(), content_hash(updated))
+                        })
+                        .collect(),
+                    created_at: Instant::now(),
+                },
+            );
+        }
+        Some(undo_id)
+    };
+
+    Ok(ReplaceApplyResult {
+        files_changed: planned.into_iter().map(|(path, _, _)| path).collect(),
+        replacements,
+        undo_id,
+    })
+}
+
+/// 撤销一次 `apply_replace`：仅当文件仍保持替换后的内容时才还原
+pub fn undo_replace(workspace_root: &Path, undo_id: &str) -> Result<Vec<String>, ReplaceError> {
+    let record = UNDO_RECORDS
+        .lock()
+        .ok()
+        .and_then(|mut records| records.remove(undo_id))
+        .filter(|record| record.root == root_key(workspace_root))
+        .ok_or(ReplaceError::ChangeSetNotFound)?;
+
+    for (path, _, updated_hash) in &record.files {
+        let current = file_api::read_file(workspace_root, path)
+            .map(|(content, _)| content)
+            .map_err(|e| ReplaceError::File(path.clone(), e))?;
+        if content_hash(&current) != *updated_hash {
+            return Err(ReplaceError::Stale(path.clone()));
+        }
+    }
+    let mut restored = Vec::new();
+    for (path, original, _) in &record.files {
+        file_api::write_file(workspace_root, path, original)
+            .map_err(|e| ReplaceError::File(path.clone(), e))?;
+        restored.push(path.clone());
+    }
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn query(q: &str, r: &str, regex: bool) -> ReplaceQuery {
+        ReplaceQuery {
+            query: q.to_string(),
+            replacement: r.to_string(),
+            regex,
+            case_sensitive: true,
+            include_globs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn glob_matches_basename_and_paths() {
+        assert!(glob_matches("*.rs", "src/server/mod.rs"));
+        assert!(!glob_matches("*.rs", "src/main.ts"));
+        assert!(glob_matches("src/**/*.rs", "src/server/git/mod.rs"));
+        assert!(glob_matches("src/**/*.rs", "src/lib.rs"));
+        assert!(!glob_matches("src/*.rs", "src/server/mod.rs"));
+        assert!(glob_matches("docs/?.md", "docs/a.md"));
+    }
+
+    #[test]
+    fn glob_matches_many_stars_without_backtracking_blowup() {
+        let path = format!("{}/b.txt", "a/".repeat(40).trim_end_matches('/'));
+        let pattern = "**/a*a*a*a*a*a*a*a*a*a*a*a*/**/c";
+        let started = std::time::Instant::now();
+        assert!(!glob_matches(pattern, &path));
+        let stars = "*a".repeat(30);
+        assert!(glob_matches(&stars, &"a".repeat(60)));
+        assert!(!glob_matches(&stars, &format!("{}b", "a".repeat(60))));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+        assert!(glob_matches("**/**/**/b.txt", &path));
+    }
+
+    #[tokio::test]
+    async fn prepare_replace_stops_when_cancelled() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("a.txt"), "foo\n").unwrap();
+        let root = temp.path().to_path_buf();
+        let query = ReplaceQuery {
+            query: "foo".to_string(),
+            replacement: "bar".to_string(),
+            regex: false,
+            case_sensitive: true,
+            include_globs: Vec::new(),
+        };
+        let result = crate::util::cancel::scope("replace-cancel".to_string(), async move {
+            assert!(crate::util::cancel::cancel("replace-cancel"));
+            crate::util::trace::spawn_blocking(move || {
+                prepare_replace(&root, &["a.txt".to_string()], &query)
+            })
+            .await
+            .unwrap()
+        })
+        .await;
+        assert!(matches!(result, Err(ReplaceError::Cancelled)));
+    }
+
+    #[test]
+    fn prepare_apply_and_undo_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::write(
+            root.join("a.rs"),
+            "let old_name = 1;\nold_name += old_name;\r\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("b.ts"), "old_name\n").unwrap();
+        let files = vec!["a.rs".to_string(), "b.ts".to_string()];
+
+        let mut q = query("old_name", "new_name", false);
+        q.include_globs = vec!["*.rs".to_string()];
+        let preview = prepare_replace(root, &files, &q).unwrap();
+        assert_eq!(preview.matches.len(), 3);
+        assert_eq!(preview.file_count, 1);
+
+        // 只接受第 0、2 号匹配
+        let result = apply_replace(root, &preview.change_set_id, Some(&[0, 2])).unwrap();
+        assert_eq!(result.replacements, 2);
+        assert_eq!(result.files_changed, vec!["a.rs".to_string()]);
+        assert_eq!(
+            std::fs::read_to_string(root.join("a.rs")).unwrap(),
+            "let new_name = 1;\nold_name += new_name;\r\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("b.ts")).unwrap(),
+            "old_name\n"
+        );
+
+        // 变更集只能应用一次
+        assert!(matches!(
+            apply_replace(root, &preview.change_set_id, None),
+            Err(ReplaceError::ChangeSetNotFound)
+        ));
+
+        let restored = undo_replace(root, result.undo_id.as_deref().unwrap()).unwrap();
+        assert_eq!(restored, vec!["a.rs".to_string()]);
+        assert_eq!(
+            std::fs::read_to_string(root.join("a.rs")).unwrap(),
+            "let old_name = 1;\nold_name += old_name;\r\n"
+        );
+    }
+
+    #[test]
+    fn apply_rejects_stale_files_and_supports_regex_captures() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::write(root.join("c.py"), "get_user(1)\nget_item(2)\n").unwrap();
+        let files = vec!["c.py".to_string()];
+
+        let preview =
+            prepare_replace(root, &files, &query(r"get_(\w+)\(", "fetch_${1}(", true)).unwrap();
+        assert_eq!(preview.matches[0].replacement, "fetch_user(");
+        assert_eq!(preview.matches[1].replacement, "fetch_item(");
+
+        std::fs::write(root.join("c.py"), "get_user(1)\n").unwrap();
+        assert!(matches!(
+            apply_replace(root, &preview.change_set_id, None),
+            Err(ReplaceError::Stale(_))
+        ));
+        assert!(matches!(
+            prepare_replace(root, &files, &query("(", "", true)),
+            Err(ReplaceError::InvalidQuery(_))
+        ));
+    }
+}
@@ -0,0 +1,508 @@
+//! 工作区任务识别
+//!
+//! 解析工作区根目录下常见任务运行器的定义，归一化为可一键运行的任务列表：
+//! - `package.json` 的 `scripts`（按锁文件选择 npm / pnpm / yarn / bun）；
+//! - `justfile` 的公开 recipe；
+//! - `Makefile` 的显式目标；
+//! - `Cargo.toml` 的 bin 与 example（含 `src/bin/`、`examples/` 自动发现）。
+//!
+//! 只做静态解析，不执行任何任务运行器；单个文件解析失败时跳过该来源。
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use tracing::debug;
+
+/// 参与解析的文件大小上限，超出视为非手写配置
+const MAX_TASK_FILE_SIZE: u64 = 1024 * 1024;
+/// 返回的任务数上限
+pub const MAX_DETECTED_TASKS: usize = 500;
+
+const MAKEFILE_NAMES: &[&str] = &["GNUmakefile", "makefile", "Makefile"];
+const JUSTFILE_NAMES: &[&str] = &["justfile", "Justfile", ".justfile"];
+
+/// 任务来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskRunnerKind {
+    Npm,
+    Just,
+    Make,
+    Cargo,
+}
+
+impl TaskRunnerKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Npm => "npm",
+            Self::Just => "just",
+            Self::Make => "make",
+            Self::Cargo => "cargo",
+        }
+    }
+}
+
+/// 识别出的任务
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedTask {
+    pub runner: TaskRunnerKind,
+    /// 来源内的任务名（npm script、recipe、make 目标、bin / example 名）
+    pub name: String,
+    /// 在工作区根目录执行的完整命令
+    pub command: String,
+    /// 说明：npm script 原文，或 justfile / Makefile 中紧邻的注释
+    pub description: Option<String>,
+    /// 定义所在文件（相对工作区根）
+    pub file: String,
+}
+
+impl DetectedTask {
+    /// 工作区内唯一标识：`<runner>:<name>`
+    pub fn id(&self) -> String {
+        format!("{}:{}", self.runner.as_str(), self.name)
+    }
+}
+
+/// 识别工作区根目录下的任务；结果按来源分组，来源内保持文件中的定义顺序
+pub fn detect_tasks(root: &Path) -> Vec<DetectedTask> {
+    let mut tasks = Vec::new();
+    tasks.extend(detect_npm_scripts(root));
+    tasks.extend(detect_just_recipes(root));
+    tasks.extend(detect_make_targets(root));
+    tasks.extend(detect_cargo_targets(root));
+    tasks.truncate(MAX_DETECTED_TASKS);
+    tasks
+}
+
+fn read_task_file(path: &Path) -> Option<String> {
+    let meta = std::fs::metadata(path).ok()?;
+    if !meta.is_file() || meta.len() > MAX_TASK_FILE_SIZE {
+        return None;
+    }
+    std::fs::read_to_string(path)
+        .map_err(|e| debug!(path = %path.display(), error = %e, "Failed to read task file"))
+        .ok()
+}
+
+fn first_existing<'a>(root: &Path, names: &[&'a str]) -> Option<(&'a str, String)> {
+    names
+        .iter()
+        .find_map(|name| read_task_file(&root.join(name)).map(|content| (*name, content)))
+}
+
+/// 命令行参数需要引用时加单引号
+fn shell_quote(arg: &str) -> String {
+    let safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.:/@+=,".contains(c));
+    if safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// package.json
+// ---------------------------------------------------------------------------
+
+/// 按锁文件推断包管理器
+fn npm_client(root: &Path) -> &'static str {
+    if root.join("pnpm-lock.yaml").exists() {
+        "pnpm"
+    } else if root.join("yarn.lock").exists() {
+        "yarn"
+    } else if root.join("bun.lockb").exists() || root.join("bun.lock").exists() {
+        "bun"
+    } else {
+        "npm"
+    }
+}
+
+fn detect_npm_scripts(root: &Path) -> Vec<DetectedTask> {
+    let Some(content) = read_task_file(&root.join("package.json")) else {
+        return Vec::new();
+    };
+    let manifest: serde_json::Value = match serde_json::from_str(&content) {
+        Ok(value) => value,
+        Err(e) => {
+            debug!(error = %e, "Invalid package.json, skipping npm scripts");
+            return Vec::new();
+        }
+    };
+    let Some(scripts) = manifest.get("scripts").and_then(|s| s.as_object()) else {
+        return Vec::new();
+    };
+    let client = npm_client(root);
+    scripts
+        .iter()
+        .filter_map(|(name, script)| {
+            let script = script.as_str()?;
+            Some(DetectedTask {
+                runner: TaskRunnerKind::Npm,
+                name: name.clone(),
+                command: format!("{} run {}", client, shell_quote(name)),
+                description: Some(script.to_string()).filter(|s| !s.trim().is_empty()),
+                file: "package.json".to_string(),
+            })
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// justfile
+// ---------------------------------------------------------------------------
+
+fn detect_just_recipes(root: &Path) -> Vec<DetectedTask> {
+    let Some((file, content)) = first_existing(root, JUSTFILE_NAMES) else {
+        return Vec::new();
+    };
+    parse_justfile(&content)
+        .into_iter()
+        .map(|(name, description)| DetectedTask {
+            runner: TaskRunnerKind::Just,
+            command: format!("just {}", shell_quote(&name)),
+            name,
+            description,
+            file: file.to_string(),
+        })
+        .collect()
+}
+
+/// 解析 justfile 顶层 recipe，返回 `(名称, 注释)`；`_` 开头与 `[private]` 的 recipe 不返回
+fn parse_justfile(content: &str) -> Vec<(String, Option<String>)> {
+    let mut recipes = Vec::new();
+    let mut comment: Option<String> = None;
+    let mut private = false;
+    for line in content.lines() {
+        if line.starts_with([' ', '\t']) || line.trim().is_empty() {
+            comment = None;
+            private = false;
+            continue;
+        }
+        if let Some(text) = line.strip_prefix('#') {
+            comment =
+                Some(text.trim().to_string()).filter(|s| !s.is_empty() && !s.starts_with('!'));
+            continue;
+        }
+        if line.starts_with('[') {
+            private |= line.contains("private");
+            continue;
+        }
+        if let Some(name) = parse_just_recipe_header(line) {
+            if !private && !name.starts_with('_') {
+                recipes.push((name, comment.take()));
+            }
+        }
+        comment = None;
+        private = false;
+    }
+    recipes
+}
+
+fn parse_just_recipe_header(line: &str) -> Option<String> {
+    const KEYWORDS: &[&str] = &["set", "alias", "export", "import", "mod"];
+    let line = line.strip_prefix('@').unwrap_or(line);
+    let name_len = line
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(line.len());
+    let (name, rest) = line.split_at(name_len);
+    if name.is_empty() || KEYWORDS.contains(&name) || !rest.starts_with([':', ' ', '\t']) {
+        return None;
+    }
+    // 首个 `:` 为 `:=` 时是变量赋值；参数默认值中的 `:` 不影响判断
+    let colon = rest.find(':')?;
+    (!rest[colon..].starts_with(":=")).then(|| name.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Makefile
+// ---------------------------------------------------------------------------
+
+fn detect_make_targets(root: &Path) -> Vec<DetectedTask> {
+    let Some((file, content)) = first_existing(root, MAKEFILE_NAMES) else {
+        return Vec::new();
+    };
+    parse_makefile(&content)
+        .into_iter()
+        .map(|(name, description)| DetectedTask {
+            runner: TaskRunnerKind::Make,
+            command: format!("make {}", shell_quote(&name)),
+            name,
+            description,
+            file: file.to_string(),
+        })
+        .collect()
+}
+
+/// 解析显式目标，返回 `(目标, 说明)`；说明取 `target: ## 说明` 或上一行 `#` 注释。
+/// 跳过特殊目标（`.PHONY` 等）、模式规则与含变量的目标。
+fn parse_makefile(content: &str) -> Vec<(String, Option<String>)> {
+    let mut targets: Vec<(String, Option<String>)> = Vec::new();
+    let mut seen = BTreeSet::new();
+    let mut comment: Option<String> = None;
+    for line in content.lines() {
+        if line.starts_with(['\t', ' ']) || line.trim().is_empty() {
+            comment = None;
+            continue;
+        }
+        if let Some(text) = line.strip_prefix('#') {
+            comment =
+                Some(text.trim_start_matches('#').trim().to_string()).filter(|s| !s.is_empty());
+            continue;
+        }
+        let Some(colon) = line.find(':') else {
+            comment = None;
+            continue;
+        };
+        let (head, rest) = line.split_at(colon);
+        // `:=` / `::=` 为变量赋值；`=` 出现在冒号前也是赋值（如 `A = b:c`）
+        if rest.starts_with(":=") || rest.starts_with("::=") || head.contains('=') {
+            comment = None;
+            continue;
+        }
+        let inline = rest
+            .split_once("##")
+            .map(|(_, text)| text.trim().to_string())
+            .filter(|s| !s.is_empty());
+        let description = inline.or_else(|| comment.take());
+        for target in head.split_whitespace() {
+            let valid = !target.starts_with('.')
+                && !target.contains(['%', '$', '(', ')'])
+                && seen.insert(target.to_string());
+            if valid {
+                targets.push((target.to_string(), description.clone()));
+            }
+        }
+        comment = None;
+    }
+    targets
+}
+
+// ---------------------------------------------------------------------------
+// Cargo.toml
+// ---------------------------------------------------------------------------
+
+fn detect_cargo_targets(root: &Path) -> Vec<DetectedTask> {
+    let Some(content) = read_task_file(&root.join("Cargo.toml")) else {
+        return Vec::new();
+    };
+    let manifest: toml::Value = match toml::from_str(&content) {
+        Ok(value) => value,
+        Err(e) => {
+            debug!(error = %e, "Invalid Cargo.toml, skipping cargo targets");
+            return Vec::new();
+        }
+    };
+    // 纯 workspace 清单没有自己的 target
+    let Some(package) = manifest.get("package") else {
+        return Vec::new();
+    };
+
+    let explicit_names = |key: &str| -> Vec<String> {
+        manifest
+            .get(key)
+            .and_then(|v| v.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| item.get("name").and_then(|n| n.as_str()))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let autodiscover =
+        |key: &str| -> bool { package.get(key).and_then(|v| v.as_bool()).unwrap_or(true) };
+
+    let mut bins = explicit_names("bin");
+    if autodiscover("autobins") {
+        if root.join("src/main.rs").is_file() {
+            if let Some(name) = package.get("name").and_then(|n| n.as_str()) {
+                bins.push(name.to_string());
+            }
+        }
+        bins.extend(discover_rust_targets(&root.join("src/bin")));
+    }
+    let mut examples = explicit_names("example");
+    if autodiscover("autoexamples") {
+        examples.extend(discover_rust_targets(&root.join("examples")));
+    }
+
+    let mut seen = BTreeSet::new();
+    let mut tasks = Vec::new();
+    for (kind, name) in bins
+        .into_iter()
+        .map(|name| ("bin", name))
+        .chain(examples.into_iter().map(|name| ("example", name)))
+    {
+        if !seen.insert((kind, name.clone())) {
+            continue;
+        }
+        tasks.push(DetectedTask {
+            runner: TaskRunnerKind::Cargo,
+            command: format!("cargo run --{} {}", kind, shell_quote(&name)),
+            // example 与同名 bin 区分
+            name: if kind == "bin" {
+                name
+            } else {
+                format!("example:{}", name)
+            },
+            description: None,
+            file: "Cargo.toml".to_string(),
+        });
+    }
+    tasks
+}
+
+/// 按 Cargo 约定发现目录下的 target：`<name>.rs` 或 `<name>/main.rs`
+fn discover_rust_targets(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                return path
+                    .join("main.rs")
+                    .is_file()
+                    .then(|| entry.file_name().to_string_lossy().to_string());
+            }
+            (path.extension().is_some_and(|ext| ext == "rs"))
+                .then(|| path.file_stem())
+                .flatten()
+                .map(|stem| stem.to_string_lossy().to_string())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn detects_npm_scripts_with_lockfile_client() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "package.json",
+            r#"{"scripts": {"dev": "vite", "test:unit": "vitest run"}}"#,
+        );
+        write(dir.path(), "pnpm-lock.yaml", "");
+
+        let tasks = detect_tasks(dir.path());
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].id(), "npm:dev");
+        assert_eq!(tasks[0].command, "pnpm run dev");
+        assert_eq!(tasks[0].description.as_deref(), Some("vite"));
+        assert_eq!(tasks[1].command, "pnpm run test:unit");
+    }
+
+    #[test]
+    fn parses_make_targets_and_descriptions() {
+        let makefile = "\
+CC := gcc
+VERSION = 1.0
+.PHONY: build test
+# Build everything
+build: deps
+\t$(CC) main.c
+test: build ## Run tests
+\t./run-tests
+%.o: %.c
+\t$(CC) -c $<
+install uninstall:
+\techo
+build:
+";
+        let targets = parse_makefile(makefile);
+        assert_eq!(
+            targets,
+            vec![
+                ("build".to_string(), Some("Build everything".to_string())),
+                ("test".to_string(), Some("Run tests".to_string())),
+                ("install".to_string(), None),
+                ("uninstall".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_public_just_recipes() {
+        let justfile = "\
+set shell := [\"bash\", \"-c\"]
+version := \"1.0\"
+alias b := build
+
+# Compile the project
+build target=\"debug\":
+    cargo build
+
+@fmt:
+    cargo fmt
+
+_helper:
+    echo hidden
+
+[private]
+secret:
+    echo hidden
+
+deploy env: build
+    ./deploy {{env}}
+";
+        assert_eq!(
+            parse_justfile(justfile),
+            vec![
+                ("build".to_string(), Some("Compile the project".to_string())),
+                ("fmt".to_string(), None),
+                ("deploy".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_cargo_bins_and_examples() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "Cargo.toml",
+            "[package]\nname = \"app\"\n\n[[bin]]\nname = \"tool\"\npath = \"tools/tool.rs\"\n",
+        );
+        write(dir.path(), "src/main.rs", "fn main() {}");
+        write(dir.path(), "src/bin/worker.rs", "fn main() {}");
+        write(dir.path(), "examples/demo/main.rs", "fn main() {}");
+
+        let commands: Vec<String> = detect_tasks(dir.path())
+            .into_iter()
+            .map(|t| t.command)
+            .collect();
+        assert_eq!(
+            commands,
+            vec![
+                "cargo run --bin tool",
+                "cargo run --bin app",
+                "cargo run --bin worker",
+                "cargo run --example demo",
+            ]
+        );
+
+        // 纯 workspace 清单不产生任务
+        write(
+            dir.path(),
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"core\"]\n",
+        );
+        assert!(detect_tasks(dir.path()).is_empty());
+    }
+}
@@ -3,12 +3,18 @@ use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio::sync::{broadcast, mpsc, watch, Mutex};
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
-use crate::pty::PtySession;
-use crate::server::protocol::TerminalInfo;
+use crate::pty::activity::{classify, ActivitySnapshot, TerminalActivity};
+use crate::pty::agent::detect_agent;
+use crate::pty::cwd::{parse_osc7_cwd, process_cwd};
+use crate::pty::keep_alive::KeepAlive;
+use crate::pty::shell_integration::{sanitize_command, CommandTracker};
+use crate::pty::{PtySession, ShellSpec};
+use crate::server::context::{send_task_broadcast_event, TaskBroadcastEvent, TaskBroadcastTx};
+use crate::server::protocol::{ServerMessage, TerminalCommandInfo, TerminalInfo};
 
 // chrono は chrono::Utc 経由で使用
 use chrono;
@@ -31,6 +37,21 @@ const IDLE_REAP_TIMEOUT_SECS: u64 = 3600;
 /// 后台空闲检测间隔：30 秒
 const REAPER_INTERVAL_SECS: u64 = 30;
 
+/// 已退出终端在最后一次输出后的保留时长：60 秒，供客户端展示退出码、回看输出
+const EXITED_RETENTION_SECS: u64 = 60;
+
+/// 工作目录检测间隔：1 秒（仅检查期间有新输出的终端）
+const CWD_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 子进程退出后等待读取线程读完剩余输出的最长时间；后台进程占用 PTY 时不再等待 EOF
+const EXIT_OUTPUT_GRACE: Duration = Duration::from_secs(1);
+
+/// 判定活跃状态时读取的输出尾部字节数
+const ACTIVITY_TAIL_BYTES: usize = 512;
+
+/// Agent 输出静默超过该时长视为等待用户输入
+const AGENT_IDLE_THRESHOLD: Duration = Duration::from_secs(3);
+
 // ============================================================================
 // 终端资源可观测性类型
 // ============================================================================
@@ -290,15 +311,34 @@ pub struct TerminalEntry {
     pub term_id: String,
     pub project: String,
     pub workspace: String,
+    /// 当前工作目录：初始为启动目录，随 OSC 7 上报或前台进程 cwd 更新
     pub cwd: PathBuf,
+    /// shell 是否上报过 OSC 7；上报过则不再读取进程 cwd
+    pub cwd_reported: bool,
+    /// 上次检测后是否有新输出（`cd` 后总会输出提示符）
+    pub cwd_dirty: bool,
+    /// 最近一次 OSC 7 上报、尚未应用的目录
+    pub pending_osc7_cwd: Option<PathBuf>,
+    /// shell 集成（OSC 133）命令记录
+    pub commands: CommandTracker,
+    /// 上次检测 Agent 时的前台进程组；变化后重新检测
+    pub agent_pgid: Option<i32>,
+    /// 前台进程中检测到的编码 Agent（如 "claude"）
+    pub detected_agent: Option<&'static str>,
+    /// 已推送给客户端的 Agent 状态
+    pub agent: Option<&'static str>,
+    pub agent_awaiting_input: bool,
     pub shell: String,
-    pub status: TerminalStatus,
+    /// 子进程退出码；子进程退出后由退出上报线程写入（尽量在 PTY 输出读完之后）
+    pub exit_rx: watch::Receiver<Option<i32>>,
     /// 客户端连接层生命周期相位
     pub lifecycle_phase: TerminalLifecyclePhase,
     /// 客户端自定义展示名称（如命令名）
     pub name: Option<String>,
     /// 客户端自定义图标标识
     pub icon: Option<String>,
+    /// 展示顺序（同一工作区内升序排列），跨设备一致
+    pub order: u64,
     /// 终端恢复元数据（Core 重启后持久化恢复所需最小字段）
     pub recovery_meta: Option<TerminalRecoveryMeta>,
     /// 多订阅者广播通道（term_id, data）
@@ -308,16 +348,71 @@ pub struct TerminalEntry {
     pub flow_gate: Arc<PtyFlowGate>,
     /// 最近活跃时间（写入 input 或收到 PTY 输出时更新）
     pub last_active_at: Instant,
+    /// 最近一次 PTY 输出时间
+    pub last_output_at: Instant,
+    /// 最近一次客户端输入时间
+    pub last_input_at: Instant,
+    /// 保活状态；开启时不参与空闲回收
+    pub keep_alive: Option<KeepAlive>,
+}
+
+impl TerminalEntry {
+    /// 当前 PTY 进程状态
+    pub fn status(&self) -> TerminalStatus {
+        match *self.exit_rx.borrow() {
+            Some(code) => TerminalStatus::Exited(code),
+            None => TerminalStatus::Running,
+        }
+    }
+
+    /// 距最近一次输入或输出的时长
+    pub fn idle_duration(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_output_at.max(self.last_input_at))
+    }
+
+    /// 当前活跃状态；已退出的终端视为空闲
+    pub fn activity(&self, now: Instant) -> TerminalActivity {
+        if !matches!(self.status(), TerminalStatus::Running) {
+            return TerminalActivity::Idle;
+        }
+        // 优先使用 shell 集成标记，否则以前台进程组是否为 shell 判断
+        let command_running = match self.commands.at_prompt() {
+            Some(at_prompt) => !at_prompt,
+            None => match (
+                self.session.foreground_process_group(),
+                self.session.process_id(),
+            ) {
+                (Some(pgid), Some(pid)) => pgid > 0 && pgid != pid as i32,
+                _ => false,
+            },
+        };
+        let last_line = sanitize_command(&self.scrollback.snapshot_limited(ACTIVITY_TAIL_BYTES));
+        classify(&ActivitySnapshot {
+            since_output: now.saturating_duration_since(self.last_output_at),
+            command_running,
+            agent_awaiting_input: self.agent_awaiting_input,
+            last_line: &last_line,
+        })
+    }
 }
 
 /// 全局终端注册表，生命周期 = Core 进程生命周期
 pub struct TerminalRegistry {
     terminals: HashMap<String, TerminalEntry>,
     default_term_id: Option<String>,
+    /// 下一个新建终端的展示顺序
+    next_order: u64,
 }
 
 pub type SharedTerminalRegistry = Arc<Mutex<TerminalRegistry>>;
 
+/// 订阅终端所需的句柄：输出广播、背压门控、退出码
+pub type TerminalSubscribeHandles = (
+    broadcast::Receiver<(String, Vec<u8>)>,
+    Arc<PtyFlowGate>,
+    watch::Receiver<Option<i32>>,
+);
+
 impl Default for TerminalRegistry {
     fn default() -> Self {
         Self::new()
@@ -329,6 +424,7 @@ impl TerminalRegistry {
         Self {
             terminals: HashMap::new(),
             default_term_id: None,
+            next_order: 0,
         }
     }
 
@@ -343,14 +439,16 @@ impl TerminalRegistry {
         initial_rows: Option<u16>,
         name: Option<String>,
         icon: Option<String>,
+        shell: ShellSpec,
     ) -> Result<(String, String), String> {
         let term_id = Uuid::new_v4().to_string();
         let cwd_path = cwd.unwrap_or_else(|| {
             PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| "/".to_string()))
         });
 
-        let mut session = PtySession::new(Some(cwd_path.clone()), initial_cols, initial_rows)
-            .map_err(|e| format!("Failed to create PTY: {}", e))?;
+        let mut session =
+            PtySession::new(Some(cwd_path.clone()), initial_cols, initial_rows, shell)
+                .map_err(|e| format!("Failed to create PTY: {}", e))?;
 
         let shell_name = session.shell_name().to_string();
 
@@ -370,6 +468,21 @@ impl TerminalRegistry {
         let reader = session
             .take_reader()
             .map_err(|e| format!("Failed to take reader: {}", e))?;
+        let child_exit_rx = session.take_exit_receiver();
+        let (exit_tx, exit_rx) = watch::channel::<Option<i32>>(None);
+        // 读取线程结束时丢弃发送端，通知退出上报线程输出已读完
+        let (drained_tx, drained_rx) = std::sync::mpsc::channel::<()>();
+
+        // 退出上报线程：独立于 PTY EOF，避免后台子进程持有 slave 端时退出码永远不上报
+        if let Some(child_exit_rx) = child_exit_rx {
+            std::thread::spawn(move || {
+                if let Ok(code) = child_exit_rx.recv() {
+                    // 先让读取线程转发完最后的输出，超时则直接上报
+                    let _ = drained_rx.recv_timeout(EXIT_OUTPUT_GRACE);
+                    let _ = exit_tx.send(Some(code));
+                }
+            });
+        }
 
         // PTY 读取线程：复用原有 ANSI 序列完整性检查逻辑
         std::thread::spawn(move || {
@@ -424,6 +537,7 @@ impl TerminalRegistry {
                     }
                 }
             }
+            drop(drained_tx);
         });
 
         let entry = TerminalEntry {
@@ -432,35 +546,49 @@ impl TerminalRegistry {
             project: project.unwrap_or_default(),
             workspace: workspace.unwrap_or_default(),
             cwd: cwd_path,
+            cwd_reported: false,
+            cwd_dirty: false,
+            pending_osc7_cwd: None,
+            commands: CommandTracker::default(),
+            agent_pgid: None,
+            detected_agent: None,
+            agent: None,
+            agent_awaiting_input: false,
             shell: shell_name.clone(),
-            status: TerminalStatus::Running,
+            exit_rx,
             lifecycle_phase: TerminalLifecyclePhase::Entering,
             name,
             icon,
+            order: self.next_order,
             recovery_meta: None,
             output_tx,
             scrollback: ScrollbackBuffer::new(DEFAULT_SCROLLBACK_CAPACITY),
             flow_gate,
             last_active_at: Instant::now(),
+            last_output_at: Instant::now(),
+            last_input_at: Instant::now(),
+            keep_alive: None,
         };
 
         if self.default_term_id.is_none() {
             self.default_term_id = Some(term_id.clone());
         }
 
+        self.next_order += 1;
         self.terminals.insert(term_id.clone(), entry);
 
         Ok((term_id, shell_name))
     }
 
-    /// 订阅终端输出，返回 (broadcast::Receiver, Arc<PtyFlowGate>)
-    pub fn subscribe(
-        &self,
-        term_id: &str,
-    ) -> Option<(broadcast::Receiver<(String, Vec<u8>)>, Arc<PtyFlowGate>)> {
-        self.terminals
-            .get(term_id)
-            .map(|e| (e.output_tx.subscribe(), e.flow_gate.clone()))
+    /// 订阅终端输出，返回 (broadcast::Receiver, Arc<PtyFlowGate>, 退出码 watch)
+    pub fn subscribe(&self, term_id: &str) -> Option<TerminalSubscribeHandles> {
+        self.terminals.get(term_id).map(|e| {
+            (
+                e.output_tx.subscribe(),
+                e.flow_gate.clone(),
+                e.exit_rx.clone(),
+            )
+        })
     }
 
     /// 获取终端的 scrollback 快照（全量）
@@ -539,7 +667,7 @@ impl TerminalRegistry {
     }
 
     /// 回收空闲/退出终端：
-    /// - 已退出（Exited）且订阅者为 0 的终端立即回收
+    /// - 已退出（Exited）且订阅者为 0 的终端在最后输出后保留 EXITED_RETENTION_SECS 再回收
     /// - 运行中但订阅者为 0 且空闲超时的终端回收
     ///
     /// 返回被回收的 term_id 列表。
@@ -560,9 +688,16 @@ impl TerminalRegistry {
             .iter()
             .filter_map(|(id, entry)| {
                 let subs = entry.flow_gate.subscriber_count();
-                match &entry.status {
-                    TerminalStatus::Exited(_) if subs == 0 => Some(id.clone()),
-                    TerminalStatus::Running if subs == 0 => {
+                match entry.status() {
+                    TerminalStatus::Exited(_) if subs == 0 => {
+                        let retention = Duration::from_secs(EXITED_RETENTION_SECS);
+                        if now.duration_since(entry.last_active_at) >= retention {
+                            Some(id.clone())
+                        } else {
+                            None
+                        }
+                    }
+                    TerminalStatus::Running if subs == 0 && entry.keep_alive.is_none() => {
                         if now.duration_since(entry.last_active_at) >= idle_timeout {
                             Some(id.clone())
                         } else {
@@ -591,6 +726,81 @@ impl TerminalRegistry {
         to_reclaim
     }
 
+    /// 开启或关闭终端保活，返回 `(keep_alive, prevent_sleep)`；终端不存在时返回 None
+    pub fn set_keep_alive(
+        &mut self,
+        term_id: &str,
+        enabled: bool,
+        prevent_sleep: bool,
+    ) -> Option<(bool, bool)> {
+        let entry = self.terminals.get_mut(term_id)?;
+        entry.keep_alive = enabled.then(|| {
+            // 已开启时保留原状态，仅在阻止休眠选项变化时重建
+            match entry.keep_alive.take() {
+                Some(current) if current.prevents_sleep() == prevent_sleep => current,
+                _ => KeepAlive::start(entry.session.process_id(), prevent_sleep),
+            }
+        });
+        Some(keep_alive_flags(entry))
+    }
+
+    /// 设置终端展示名称（None 表示清除），同步到恢复元数据；终端不存在时返回 false
+    pub fn rename(&mut self, term_id: &str, name: Option<String>) -> bool {
+        let Some(entry) = self.terminals.get_mut(term_id) else {
+            return false;
+        };
+        if let Some(meta) = entry.recovery_meta.as_mut() {
+            meta.name = name.clone();
+        }
+        entry.name = name;
+        true
+    }
+
+    /// 调整终端展示顺序：`term_ids` 中存在的终端按给定顺序排在最前，
+    /// 其余终端保持原有相对顺序排在其后。返回调整后的完整顺序
+    pub fn reorder(&mut self, term_ids: &[String]) -> Vec<String> {
+        let mut ordered: Vec<String> = Vec::with_capacity(self.terminals.len());
+        for term_id in term_ids {
+            if self.terminals.contains_key(term_id) && !ordered.contains(term_id) {
+                ordered.push(term_id.clone());
+            }
+        }
+        let mut rest: Vec<(u64, String)> = self
+            .terminals
+            .values()
+            .filter(|e| !ordered.contains(&e.term_id))
+            .map(|e| (e.order, e.term_id.clone()))
+            .collect();
+        rest.sort();
+        ordered.extend(rest.into_iter().map(|(_, term_id)| term_id));
+
+        for (order, term_id) in ordered.iter().enumerate() {
+            if let Some(entry) = self.terminals.get_mut(term_id) {
+                entry.order = order as u64;
+            }
+        }
+        self.next_order = ordered.len() as u64;
+        ordered
+    }
+
+    /// 向到期的保活终端发送保活信号，返回发送个数
+    pub fn keep_alive_tick(&mut self) -> usize {
+        let now = Instant::now();
+        let mut poked = 0;
+        for entry in self.terminals.values_mut() {
+            if !matches!(entry.status(), TerminalStatus::Running) {
+                continue;
+            }
+            let pgrp = entry.session.foreground_process_group();
+            if let Some(keep_alive) = entry.keep_alive.as_mut() {
+                if keep_alive.poke_if_due(now, pgrp) {
+                    poked += 1;
+                }
+            }
+        }
+        poked
+    }
+
     /// 更新指定终端的最近活跃时间（由外部异步路径调用）
     pub fn update_last_active(&mut self, term_id: &str) {
         if let Some(entry) = self.terminals.get_mut(term_id) {
@@ -645,6 +855,7 @@ impl TerminalRegistry {
     pub fn write_input(&mut self, term_id: &str, data: &[u8]) -> Result<(), String> {
         if let Some(entry) = self.terminals.get_mut(term_id) {
             entry.last_active_at = Instant::now();
+            entry.last_input_at = entry.last_active_at;
             entry
                 .session
                 .write_input(data)
@@ -737,6 +948,7 @@ impl TerminalRegistry {
 
     /// 列出所有终端信息
     pub fn list(&self) -> Vec<TerminalInfo> {
+        let now = Instant::now();
         self.terminals
             .values()
             .map(|e| TerminalInfo {
@@ -744,16 +956,19 @@ impl TerminalRegistry {
                 project: e.project.clone(),
                 workspace: e.workspace.clone(),
                 cwd: e.cwd.to_string_lossy().to_string(),
-                status: match &e.status {
+                status: match e.status() {
                     TerminalStatus::Running => "running".to_string(),
-                    TerminalStatus::Exited(code) => {
-                        format!("exited({})", code)
-                    }
+                    TerminalStatus::Exited(_) => "exited".to_string(),
+                },
+                exit_code: match e.status() {
+                    TerminalStatus::Running => None,
+                    TerminalStatus::Exited(code) => Some(code),
                 },
                 lifecycle_phase: e.lifecycle_phase.as_str().to_string(),
                 shell: e.shell.clone(),
                 name: e.name.clone(),
                 icon: e.icon.clone(),
+                order: e.order,
                 recovery_phase: if matches!(
                     e.lifecycle_phase,
                     TerminalLifecyclePhase::Recovering | TerminalLifecyclePhase::RecoveryFailed
@@ -767,6 +982,11 @@ impl TerminalRegistry {
                     .as_ref()
                     .and_then(|m| m.failed_reason.clone()),
                 remote_subscribers: Vec::new(),
+                keep_alive: e.keep_alive.is_some(),
+                agent: e.agent.map(str::to_string),
+                agent_awaiting_input: e.agent_awaiting_input,
+                idle_seconds: e.idle_duration(now).as_secs(),
+                activity: e.activity(now).as_str().to_string(),
             })
             .collect()
     }
@@ -775,7 +995,7 @@ impl TerminalRegistry {
     pub fn collect_recovery_metas(&self) -> Vec<TerminalRecoveryMeta> {
         self.terminals
             .values()
-            .filter(|e| matches!(e.status, TerminalStatus::Running))
+            .filter(|e| matches!(e.status(), TerminalStatus::Running))
             .map(|e| TerminalRecoveryMeta {
                 term_id: e.term_id.clone(),
                 project: e.project.clone(),
@@ -868,6 +1088,136 @@ impl TerminalRegistry {
         })
     }
 
+    /// 应用 OSC 7 上报的目录，并取出有新输出但未上报 OSC 7 的运行中终端；
+    /// 返回 (目录变化推送, (term_id, 待读取 cwd 的进程 PID))。
+    /// 进程优先取前台进程组 leader，取不到时退回 shell PID
+    pub fn collect_cwd_updates(&mut self) -> (Vec<ServerMessage>, Vec<(String, i32)>) {
+        let mut reported = Vec::new();
+        let mut candidates = Vec::new();
+        for entry in self.terminals.values_mut() {
+            if let Some(cwd) = entry.pending_osc7_cwd.take() {
+                reported.push((entry.term_id.clone(), cwd));
+            }
+            if !std::mem::take(&mut entry.cwd_dirty)
+                || entry.cwd_reported
+                || !matches!(entry.status(), TerminalStatus::Running)
+            {
+                continue;
+            }
+            let pid = entry
+                .session
+                .foreground_process_group()
+                .filter(|p| *p > 0)
+                .or_else(|| entry.session.process_id().map(|p| p as i32));
+            if let Some(pid) = pid {
+                candidates.push((entry.term_id.clone(), pid));
+            }
+        }
+        let changes = reported
+            .into_iter()
+            .filter_map(|(term_id, cwd)| self.update_cwd(&term_id, cwd))
+            .collect();
+        (changes, candidates)
+    }
+
+    /// 取出前台进程组变化、需要重新检测 Agent 的运行中终端，返回 (term_id, 前台进程组)；
+    /// 已退出或前台进程组不可读的终端直接清除 Agent
+    pub fn collect_agent_candidates(&mut self) -> Vec<(String, i32)> {
+        let mut candidates = Vec::new();
+        for entry in self.terminals.values_mut() {
+            let pgid = match entry.status() {
+                TerminalStatus::Running => {
+                    entry.session.foreground_process_group().filter(|p| *p > 0)
+                }
+                TerminalStatus::Exited(_) => None,
+            };
+            if pgid == entry.agent_pgid {
+                continue;
+            }
+            entry.agent_pgid = pgid;
+            entry.detected_agent = None;
+            if let Some(pgid) = pgid {
+                candidates.push((entry.term_id.clone(), pgid));
+            }
+        }
+        candidates
+    }
+
+    /// 应用 Agent 检测结果并按输出静默时长判定是否等待输入；
+    /// 状态变化时返回 `TermAgentStateChanged` 推送
+    pub fn apply_agent_updates(
+        &mut self,
+        detected: Vec<(String, i32, Option<&'static str>)>,
+    ) -> Vec<ServerMessage> {
+        for (term_id, pgid, agent) in detected {
+            if let Some(entry) = self.terminals.get_mut(&term_id) {
+                // 检测期间前台进程组已变化时结果作废，等待下一轮
+                if entry.agent_pgid == Some(pgid) {
+                    entry.detected_agent = agent;
+                }
+            }
+        }
+        let now = Instant::now();
+        let mut changes = Vec::new();
+        for entry in self.terminals.values_mut() {
+            let agent = entry.detected_agent;
+            let awaiting_input =
+                agent.is_some() && now.duration_since(entry.last_output_at) >= AGENT_IDLE_THRESHOLD;
+            if agent == entry.agent && awaiting_input == entry.agent_awaiting_input {
+                continue;
+            }
+            entry.agent = agent;
+            entry.agent_awaiting_input = awaiting_input;
+            changes.push(ServerMessage::TermAgentStateChanged {
+                term_id: entry.term_id.clone(),
+                project: entry.project.clone(),
+                workspace: entry.workspace.clone(),
+                agent: agent.map(str::to_string),
+                awaiting_input,
+            });
+        }
+        changes
+    }
+
+    /// 终端最近执行的命令；终端不存在时返回 None
+    pub fn command_history(&self, term_id: &str) -> Option<Vec<TerminalCommandInfo>> {
+        self.terminals.get(term_id).map(|e| e.commands.history())
+    }
+
+    /// 取出各终端新结束的命令，转为 `TermCommandFinished` 推送
+    pub fn take_finished_commands(&mut self) -> Vec<ServerMessage> {
+        self.terminals
+            .values_mut()
+            .flat_map(|e| {
+                let (term_id, project, workspace) =
+                    (e.term_id.clone(), e.project.clone(), e.workspace.clone());
+                e.commands.take_finished().into_iter().map(move |command| {
+                    ServerMessage::TermCommandFinished {
+                        term_id: term_id.clone(),
+                        project: project.clone(),
+                        workspace: workspace.clone(),
+                        command,
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// 更新终端工作目录；目录有变化时返回 `TermCwdChanged` 推送
+    pub fn update_cwd(&mut self, term_id: &str, cwd: PathBuf) -> Option<ServerMessage> {
+        let entry = self.terminals.get_mut(term_id)?;
+        if entry.cwd == cwd {
+            return None;
+        }
+        entry.cwd = cwd;
+        Some(ServerMessage::TermCwdChanged {
+            term_id: entry.term_id.clone(),
+            project: entry.project.clone(),
+            workspace: entry.workspace.clone(),
+            cwd: entry.cwd.to_string_lossy().to_string(),
+        })
+    }
+
     pub fn contains(&self, term_id: &str) -> bool {
         self.terminals.contains_key(term_id)
     }
@@ -885,6 +1235,13 @@ impl TerminalRegistry {
     }
 }
 
+fn keep_alive_flags(entry: &TerminalEntry) -> (bool, bool) {
+    match &entry.keep_alive {
+        Some(keep_alive) => (true, keep_alive.prevents_sleep()),
+        None => (false, false),
+    }
+}
+
 /// 启动 scrollback 写入 task（异步处理，避免 std::thread 中 await Mutex）
 pub fn spawn_scrollback_writer(
     registry: SharedTerminalRegistry,
@@ -895,9 +1252,19 @@ pub fn spawn_scrollback_writer(
         while let Some((term_id, data)) = rx.recv().await {
             let mut reg = registry.lock().await;
             if let Some(entry) = reg.terminals.get_mut(&term_id) {
+                // 读取线程保证转义序列不被截断，可直接在单块中解析 OSC 7
+                if let Some(cwd) = parse_osc7_cwd(&data) {
+                    entry.cwd_reported = true;
+                    entry.pending_osc7_cwd = Some(cwd);
+                }
+                entry.cwd_dirty = true;
+                entry
+                    .commands
+                    .feed(&data, chrono::Utc::now().timestamp_millis(), &entry.cwd);
                 entry.scrollback.push(data);
                 // PTY 输出到达时更新活跃时间（后台进程也算活跃）
                 entry.last_active_at = Instant::now();
+                entry.last_output_at = entry.last_active_at;
             }
         }
         info!("Scrollback writer task exited");
@@ -906,10 +1273,82 @@ pub fn spawn_scrollback_writer(
     tx
 }
 
+/// 启动终端状态跟踪后台任务
+///
+/// 每 CWD_POLL_INTERVAL 应用 OSC 7 上报的目录；未上报 OSC 7 的终端在有新输出后读取前台进程 cwd。
+/// 目录变化时向全部连接广播 `TermCwdChanged`，shell 集成记录的命令结束时广播 `TermCommandFinished`。
+/// 前台进程组变化时检测编码 Agent，Agent 出现、退出或开始/结束等待输入时广播 `TermAgentStateChanged`。
+pub fn spawn_terminal_state_tracker(
+    registry: SharedTerminalRegistry,
+    task_broadcast_tx: TaskBroadcastTx,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CWD_POLL_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+
+            let (mut changes, candidates) = {
+                let mut reg = registry.lock().await;
+                let (mut changes, candidates) = reg.collect_cwd_updates();
+                changes.extend(reg.take_finished_commands());
+                (changes, candidates)
+            };
+            if !candidates.is_empty() {
+                // macOS 上需调用 lsof，不能持锁执行
+                let resolved = crate::util::trace::spawn_blocking(move || {
+                    candidates
+                        .into_iter()
+                        .filter_map(|(term_id, pid)| process_cwd(pid).map(|cwd| (term_id, cwd)))
+                        .collect::<Vec<_>>()
+                })
+                .await
+                .unwrap_or_default();
+                let mut reg = registry.lock().await;
+                changes.extend(
+                    resolved
+                        .into_iter()
+                        .filter_map(|(term_id, cwd)| reg.update_cwd(&term_id, cwd)),
+                );
+            }
+
+            let agent_candidates = registry.lock().await.collect_agent_candidates();
+            let detected = if agent_candidates.is_empty() {
+                Vec::new()
+            } else {
+                // macOS 上需调用 ps，不能持锁执行
+                crate::util::trace::spawn_blocking(move || {
+                    agent_candidates
+                        .into_iter()
+                        .map(|(term_id, pgid)| (term_id, pgid, detect_agent(pgid)))
+                        .collect::<Vec<_>>()
+                })
+                .await
+                .unwrap_or_default()
+            };
+            changes.extend(registry.lock().await.apply_agent_updates(detected));
+
+            for message in changes {
+                crate::server::push_relay::notify_message(&message);
+                send_task_broadcast_event(
+                    &task_broadcast_tx,
+                    TaskBroadcastEvent {
+                        origin_conn_id: String::new(),
+                        message,
+                        target_conn_ids: None,
+                        skip_when_single_receiver: false,
+                    },
+                );
+            }
+        }
+    });
+}
+
 /// 启动空闲终端回收后台任务
 ///
 /// 每 REAPER_INTERVAL_SECS 秒运行一次，回收无订阅者的空闲/退出终端，
-/// 同时触发全局 scrollback 预算裁剪。
+/// 同时触发全局 scrollback 预算裁剪与保活终端的保活信号。
 pub fn spawn_idle_reaper(registry: SharedTerminalRegistry) {
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(Duration::from_secs(REAPER_INTERVAL_SECS));
@@ -919,16 +1358,20 @@ pub fn spawn_idle_reaper(registry: SharedTerminalRegistry) {
         loop {
             interval.tick().await;
 
-            let (reclaimed, trimmed) = {
+            let (reclaimed, trimmed, poked) = {
                 let mut reg = registry.lock().await;
                 let reclaimed = reg.reclaim_idle(idle_timeout);
                 let trimmed = reg.trim_scrollback_to_budget();
-                (reclaimed.len(), trimmed)
+                let poked = reg.keep_alive_tick();
+                (reclaimed.len(), trimmed, poked)
             };
 
             if reclaimed > 0 {
                 crate::server::perf::record_terminal_reclaimed(reclaimed as u64);
             }
+            if poked > 0 {
+                debug!(count = poked, "Keep-alive signals sent");
+            }
             if trimmed > 0 {
                 crate::server::perf::record_terminal_scrollback_trim(trimmed as u64);
                 warn!(
@@ -964,32 +1407,33 @@ fn align_to_utf8_start(mut data: Vec<u8>) -> Vec<u8> {
 /// - OSC (Operating System Command): ESC ] ... BEL 或 ESC \\
 /// - DCS (Device Control String): ESC P ... ESC \\
 /// - 简单序列: ESC 后跟单个字符
-fn find_incomplete_escape_sequence(data: &[u8]) -> Option<usize> {
+pub(crate) fn find_incomplete_escape_sequence(data: &[u8]) -> Option<usize> {
     if data.is_empty() {
         return None;
     }
 
     let search_start = data.len().saturating_sub(256);
+    // 从后往前扫描，保留最靠前的未完成序列：OSC/DCS 的终止符 `ESC \\` 被切断时，
+    // 末尾孤立的 ESC 与它所属的 OSC/DCS 都算未完成，应从 OSC/DCS 起整段保留
+    let mut incomplete = None;
 
     for i in (search_start..data.len()).rev() {
         if data[i] == 0x1b {
             let remaining = &data[i..];
 
             if remaining.len() < 2 {
-                return Some(i);
+                incomplete = Some(i);
+                continue;
             }
 
             match remaining[1] {
                 b'[' => {
-                    if remaining.len() == 2 {
-                        return Some(i);
-                    }
                     let found_terminator = remaining
                         .iter()
                         .skip(2)
                         .any(|&c| (0x40..=0x7E).contains(&c));
                     if !found_terminator {
-                        return Some(i);
+                        incomplete = Some(i);
                     }
                 }
                 b']' => {
@@ -1008,7 +1452,7 @@ fn find_incomplete_escape_sequence(data: &[u8]) -> Option<usize> {
                         }
                     }
                     if !found_terminator {
-                        return Some(i);
+                        incomplete = Some(i);
                     }
                 }
                 b'P' => {
@@ -1023,13 +1467,16 @@ fn find_incomplete_escape_sequence(data: &[u8]) -> Option<usize> {
                         }
                     }
                     if !found_terminator {
-                        return Some(i);
+                        incomplete = Some(i);
                     }
                 }
                 _ => {}
             }
         }
     }
+    if incomplete.is_some() {
+        return incomplete;
+    }
 
     // 检查 UTF-8 多字节字符是否被截断
     if !data.is_empty() {
@@ -1057,6 +1504,8 @@ fn find_incomplete_escape_sequence(data: &[u8]) -> Option<usize> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
 
     #[test]
     fn test_scrollback_buffer() {
@@ -1150,6 +1599,292 @@ mod tests {
         assert!(reclaimed.is_empty());
     }
 
+    #[test]
+    fn test_reclaim_idle_skips_keep_alive_terminals() {
+        let mut reg = TerminalRegistry::new();
+        let (scrollback_tx, _scrollback_rx) = mpsc::channel(16);
+        let shell = ShellSpec {
+            path: PathBuf::from("/bin/sh"),
+            args: Vec::new(),
+            env: Vec::new(),
+        };
+        let (term_id, _) = reg
+            .spawn(
+                None,
+                None,
+                None,
+                scrollback_tx,
+                None,
+                None,
+                None,
+                None,
+                shell,
+            )
+            .expect("spawn sh");
+
+        assert_eq!(
+            reg.set_keep_alive(&term_id, true, false),
+            Some((true, false))
+        );
+        assert!(reg.list()[0].keep_alive);
+        assert!(reg.reclaim_idle(Duration::from_secs(0)).is_empty());
+
+        assert_eq!(
+            reg.set_keep_alive(&term_id, false, false),
+            Some((false, false))
+        );
+        assert_eq!(reg.reclaim_idle(Duration::from_secs(0)), vec![term_id]);
+        assert_eq!(reg.set_keep_alive("missing", true, false), None);
+    }
+
+    #[test]
+    fn test_exited_terminal_reports_exit_code_and_is_retained() {
+        let mut reg = TerminalRegistry::new();
+        let (scrollback_tx, _scrollback_rx) = mpsc::channel(16);
+        let shell = ShellSpec {
+            path: PathBuf::from("/bin/sh"),
+            args: vec!["-c".to_string(), "exit 3".to_string()],
+            env: Vec::new(),
+        };
+        let (term_id, _) = reg
+            .spawn(
+                None,
+                None,
+                None,
+                scrollback_tx,
+                None,
+                None,
+                None,
+                None,
+                shell,
+            )
+            .expect("spawn sh");
+        let (_rx, _gate, exit_rx) = reg.subscribe(&term_id).expect("subscribe");
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while exit_rx.borrow().is_none() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(*exit_rx.borrow(), Some(3));
+
+        let info = &reg.list()[0];
+        assert_eq!(info.status, "exited");
+        assert_eq!(info.exit_code, Some(3));
+        // 刚退出的终端保留一段时间，供客户端展示退出码
+        assert!(reg.reclaim_idle(Duration::from_secs(0)).is_empty());
+    }
+
+    #[test]
+    fn test_exit_code_reported_while_background_child_holds_pty() {
+        let mut reg = TerminalRegistry::new();
+        let (scrollback_tx, mut scrollback_rx) = mpsc::channel(16);
+        // 后台进程继承 PTY slave 端，shell 退出后读取线程收不到 EOF
+        let shell = ShellSpec {
+            path: PathBuf::from("/bin/sh"),
+            args: vec!["-c".to_string(), "sleep 30 & exit 3".to_string()],
+            env: Vec::new(),
+        };
+        let (term_id, _) = reg
+            .spawn(
+                None,
+                None,
+                None,
+                scrollback_tx,
+                None,
+                None,
+                None,
+                None,
+                shell,
+            )
+            .expect("spawn sh");
+        let (_rx, _gate, exit_rx) = reg.subscribe(&term_id).expect("subscribe");
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while exit_rx.borrow().is_none() && Instant::now() < deadline {
+            while scrollback_rx.try_recv().is_ok() {}
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(*exit_rx.borrow(), Some(3));
+        assert_eq!(reg.list()[0].exit_code, Some(3));
+        reg.close_all();
+    }
+
+    #[test]
+    fn test_rename_and_reorder_terminals() {
+        let mut reg = TerminalRegistry::new();
+        let (scrollback_tx, _scrollback_rx) = mpsc::channel(16);
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let shell = ShellSpec {
+                path: PathBuf::from("/bin/sh"),
+                args: vec!["-c".to_string(), "sleep 5".to_string()],
+                env: Vec::new(),
+            };
+            let (term_id, _) = reg
+                .spawn(
+                    None,
+                    None,
+                    None,
+                    scrollback_tx.clone(),
+                    None,
+                    None,
+                    None,
+                    None,
+                    shell,
+                )
+                .expect("spawn sh");
+            ids.push(term_id);
+        }
+        let order_of = |reg: &TerminalRegistry, term_id: &str| {
+            reg.list()
+                .into_iter()
+                .find(|i| i.term_id == term_id)
+                .map(|i| i.order)
+                .unwrap()
+        };
+        assert!(order_of(&reg, &ids[0]) < order_of(&reg, &ids[1]));
+        assert!(order_of(&reg, &ids[1]) < order_of(&reg, &ids[2]));
+
+        assert!(reg.rename(&ids[1], Some("server".to_string())));
+        assert!(!reg.rename("missing", Some("x".to_string())));
+        let info = reg
+            .list()
+            .into_iter()
+            .find(|i| i.term_id == ids[1])
+            .unwrap();
+        assert_eq!(info.name.as_deref(), Some("server"));
+
+        // 未列出的终端保持原有相对顺序排在其后，未知 ID 被忽略
+        let ordered = reg.reorder(&[ids[2].clone(), "missing".to_string()]);
+        assert_eq!(
+            ordered,
+            vec![ids[2].clone(), ids[0].clone(), ids[1].clone()]
+        );
+        assert_eq!(order_of(&reg, &ids[2]), 0);
+        assert_eq!(order_of(&reg, &ids[1]), 2);
+        reg.close_all();
+    }
+
+    #[test]
+    fn test_agent_state_changes_with_detection_and_output_silence() {
+        let mut reg = TerminalRegistry::new();
+        let (scrollback_tx, _scrollback_rx) = mpsc::channel(16);
+        let shell = ShellSpec {
+            path: PathBuf::from("/bin/sh"),
+            args: vec!["-c".to_string(), "sleep 5".to_string()],
+            env: Vec::new(),
+        };
+        let (term_id, _) = reg
+            .spawn(
+                None,
+                None,
+                None,
+                scrollback_tx,
+                None,
+                None,
+                None,
+                None,
+                shell,
+            )
+            .expect("spawn sh");
+        let pgid = 4242;
+        reg.terminals.get_mut(&term_id).unwrap().agent_pgid = Some(pgid);
+
+        // 刚检测到 Agent 且仍在输出：处于工作中
+        let changes = reg.apply_agent_updates(vec![(term_id.clone(), pgid, Some("claude"))]);
+        assert!(matches!(
+            changes.as_slice(),
+            [ServerMessage::TermAgentStateChanged { agent: Some(agent), awaiting_input: false, .. }]
+                if agent == "claude"
+        ));
+        assert!(reg.apply_agent_updates(Vec::new()).is_empty());
+
+        // 输出静默超过阈值：等待输入
+        reg.terminals.get_mut(&term_id).unwrap().last_output_at =
+            Instant::now() - AGENT_IDLE_THRESHOLD;
+        let changes = reg.apply_agent_updates(Vec::new());
+        assert!(matches!(
+            changes.as_slice(),
+            [ServerMessage::TermAgentStateChanged {
+                awaiting_input: true,
+                ..
+            }]
+        ));
+        let info = &reg.list()[0];
+        assert_eq!(info.agent.as_deref(), Some("claude"));
+        assert!(info.agent_awaiting_input);
+        assert_eq!(info.activity, "awaiting_input");
+
+        // 前台进程组已变化的过期检测结果被忽略
+        reg.terminals.get_mut(&term_id).unwrap().detected_agent = None;
+        let changes = reg.apply_agent_updates(vec![(term_id.clone(), pgid + 1, Some("aider"))]);
+        assert!(matches!(
+            changes.as_slice(),
+            [ServerMessage::TermAgentStateChanged {
+                agent: None,
+                awaiting_input: false,
+                ..
+            }]
+        ));
+        reg.close_all();
+    }
+
+    #[test]
+    fn test_collect_cwd_updates_applies_osc7_and_polls_dirty_terminals() {
+        let mut reg = TerminalRegistry::new();
+        let (scrollback_tx, _scrollback_rx) = mpsc::channel(16);
+        let shell = ShellSpec {
+            path: PathBuf::from("/bin/sh"),
+            args: Vec::new(),
+            env: Vec::new(),
+        };
+        let (term_id, _) = reg
+            .spawn(
+                Some(PathBuf::from("/tmp")),
+                Some("p".to_string()),
+                Some("w".to_string()),
+                scrollback_tx,
+                None,
+                None,
+                None,
+                None,
+                shell,
+            )
+            .expect("spawn sh");
+
+        // 有新输出但未上报 OSC 7：需要读取进程 cwd
+        reg.terminals.get_mut(&term_id).unwrap().cwd_dirty = true;
+        let (changes, candidates) = reg.collect_cwd_updates();
+        assert!(changes.is_empty());
+        assert_eq!(candidates.len(), 1);
+        assert!(reg.collect_cwd_updates().1.is_empty());
+
+        // OSC 7 上报后直接应用，不再轮询
+        {
+            let entry = reg.terminals.get_mut(&term_id).unwrap();
+            entry.cwd_reported = true;
+            entry.cwd_dirty = true;
+            entry.pending_osc7_cwd = Some(PathBuf::from("/var"));
+        }
+        let (changes, candidates) = reg.collect_cwd_updates();
+        assert!(candidates.is_empty());
+        match changes.as_slice() {
+            [ServerMessage::TermCwdChanged {
+                term_id: id,
+                project,
+                workspace,
+                cwd,
+            }] => {
+                assert_eq!(id, &term_id);
+                assert_eq!((project.as_str(), workspace.as_str()), ("p", "w"));
+                assert_eq!(cwd, "/var");
+            }
+            other => panic!("unexpected changes: {:?}", other.len()),
+        }
+        assert_eq!(reg.list()[0].cwd, "/var");
+        assert!(reg.update_cwd(&term_id, PathBuf::from("/var")).is_none());
+    }
+
     #[test]
     fn test_scrollback_limited_single_chunk_exact_boundary() {
         let mut buf = ScrollbackBuffer::new(1024);
@@ -1229,4 +1964,115 @@ mod tests {
         let data = b"Hello\xe4\xbd";
         assert_eq!(find_incomplete_escape_sequence(data), Some(5));
     }
+
+    #[test]
+    fn test_find_incomplete_escape_sequence_split_string_terminator() {
+        // OSC 8 超链接的 ST（ESC \\）被切断：整段 OSC 都要保留，而不只是末尾的 ESC
+        let data = b"Hi\x1b]8;;https://example.com\x1b";
+        assert_eq!(find_incomplete_escape_sequence(data), Some(2));
+
+        let data = b"Hi\x1bPq#0\x1b";
+        assert_eq!(find_incomplete_escape_sequence(data), Some(2));
+    }
+
+    /// 随机生成由文本、多字节字符与完整转义序列组成的终端输出，
+    /// 同时返回每个偏移是否可以安全切分（不在转义序列或多字节字符内部）
+    fn random_terminal_stream(rng: &mut StdRng) -> (Vec<u8>, Vec<bool>) {
+        fn pick<'a>(rng: &mut StdRng, items: &[&'a str]) -> &'a str {
+            items[rng.gen_range(0..items.len())]
+        }
+        fn text(rng: &mut StdRng) -> String {
+            (0..rng.gen_range(0..6))
+                .map(|_| pick(rng, &["a", "Z", " ", ";", "[", "]", "\\", "你", "é", "😀"]))
+                .collect()
+        }
+
+        let mut stream = Vec::new();
+        let mut safe = vec![true];
+        while stream.len() < 160 {
+            let token = match rng.gen_range(0..7) {
+                0 => text(rng),
+                1 => format!(
+                    "\x1b[{}{}",
+                    (0..rng.gen_range(0..6))
+                        .map(|_| pick(rng, &["0", "3", "8", ";"]))
+                        .collect::<String>(),
+                    pick(rng, &["m", "H", "J", "K", "A"])
+                ),
+                2 => format!("\x1b]0;{}\x07", text(rng)),
+                3 => format!("\x1b]8;;https://x/{}\x1b\\", text(rng)),
+                4 => format!("\x1bPq{}\x1b\\", text(rng)),
+                5 => pick(rng, &["\x1b7", "\x1b8", "\x1b="]).to_string(),
+                _ => pick(rng, &["plain ascii", "é", "中文", "😀"]).to_string(),
+            };
+            // 普通文本可以在任意字符边界切分，转义序列内部不行
+            let is_text = !token.contains('\x1b');
+            stream.extend_from_slice(token.as_bytes());
+            safe.extend((1..token.len()).map(|k| is_text && token.is_char_boundary(k)));
+            if !token.is_empty() {
+                safe.push(true);
+            }
+        }
+        (stream, safe)
+    }
+
+    #[test]
+    fn prop_find_incomplete_escape_sequence_never_splits_sequences() {
+        for seed in 0..512 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let (stream, safe) = random_terminal_stream(&mut rng);
+
+            // 模拟 PTY 读循环：任意长度的读取块 + pending 拼接
+            let mut pending: Vec<u8> = Vec::new();
+            let mut emitted = Vec::new();
+            let mut offset = 0;
+            while offset < stream.len() {
+                let n = rng.gen_range(1..=48).min(stream.len() - offset);
+                let mut data = std::mem::take(&mut pending);
+                data.extend_from_slice(&stream[offset..offset + n]);
+                offset += n;
+                if let Some(start) = find_incomplete_escape_sequence(&data) {
+                    assert!(start <= data.len(), "seed {seed}");
+                    pending = data.split_off(start);
+                }
+                emitted.extend_from_slice(&data);
+                assert!(
+                    safe[emitted.len()],
+                    "seed {seed}: split inside a sequence at {} of {:?}",
+                    emitted.len(),
+                    String::from_utf8_lossy(&stream)
+                );
+                assert!(std::str::from_utf8(&data).is_ok(), "seed {seed}");
+            }
+            assert!(
+                pending.is_empty(),
+                "seed {seed}: complete stream left pending"
+            );
+            assert_eq!(emitted, stream, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn prop_find_incomplete_escape_sequence_arbitrary_bytes() {
+        let mut rng = StdRng::seed_from_u64(0x7e5c);
+        for _ in 0..2048 {
+            let len = rng.gen_range(0..400);
+            let data: Vec<u8> = (0..len)
+                .map(|_| {
+                    // 偏向转义序列与 UTF-8 相关的字节，其余为任意字节
+                    const INTERESTING: [u8; 11] = [
+                        0x1b, b'[', b']', b'P', b'\\', 0x07, b'm', 0xe4, 0xbd, 0xf0, 0x80,
+                    ];
+                    if rng.gen_bool(0.7) {
+                        INTERESTING[rng.gen_range(0..INTERESTING.len())]
+                    } else {
+                        rng.gen()
+                    }
+                })
+                .collect();
+            if let Some(start) = find_incomplete_escape_sequence(&data) {
+                assert!(start < data.len());
+            }
+        }
+    }
 }
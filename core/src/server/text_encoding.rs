@@ -0,0 +1,95 @@
+//! 非 UTF-8 文本文件的编码探测与转码
+//!
+//! `file_read` 遇到非法 UTF-8 时先探测字符集（GBK / Shift_JIS / Big5 / windows-125x 等），
+//! 能无损解码的转成 UTF-8 返回并报告原始字符集，否则仍按二进制返回；
+//! `file_write` 指定字符集时把客户端提交的 UTF-8 文本转回原编码再落盘。
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
+
+/// 按 WHATWG 标签解析字符集（如 `gbk`、`shift_jis`、`big5`、`latin1`），大小写不敏感
+pub fn encoding_for_label(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
+}
+
+/// 探测非 UTF-8 字节的字符集
+///
+/// 含 NUL 字节（且没有 UTF-16 BOM）视为二进制，返回 None。
+pub fn detect_charset(bytes: &[u8]) -> Option<&'static Encoding> {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return Some(encoding);
+    }
+    if bytes.contains(&0) {
+        return None;
+    }
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    let guess = detector.guess(None, false);
+    (guess != UTF_8).then_some(guess)
+}
+
+/// 以指定字符集无损解码（去掉 BOM）；存在非法序列时返回 None
+pub fn decode_with(bytes: &[u8], encoding: &'static Encoding) -> Option<String> {
+    let bytes = match Encoding::for_bom(bytes) {
+        Some((bom_encoding, bom_len)) if bom_encoding == encoding => &bytes[bom_len..],
+        _ => bytes,
+    };
+    encoding
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .map(|text| text.into_owned())
+}
+
+/// 探测并转码为 UTF-8；无法确定为文本时返回 None（调用方按二进制处理）
+pub fn decode_text(bytes: &[u8]) -> Option<(String, &'static Encoding)> {
+    let encoding = detect_charset(bytes)?;
+    decode_with(bytes, encoding).map(|text| (text, encoding))
+}
+
+/// 将 UTF-8 文本转回指定字符集
+///
+/// 目标字符集无法表示的字符会返回错误而不是写入 `&#NNNN;` 替代；
+/// UTF-16 等只能解码不能编码的字符集同样返回错误。
+pub fn encode_text(text: &str, encoding: &'static Encoding) -> Result<Vec<u8>, String> {
+    if encoding.output_encoding() != encoding {
+        return Err(format!("Writing {} is not supported", encoding.name()));
+    }
+    let (bytes, _, had_errors) = encoding.encode(text);
+    if had_errors {
+        return Err(format!(
+            "Content contains characters not representable in {}",
+            encoding.name()
+        ));
+    }
+    Ok(bytes.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_and_roundtrips_legacy_charsets() {
+        let gbk = encoding_for_label("gbk").unwrap();
+        let source = "// 中文注释：读取配置文件并初始化数据库连接\nfn main() {}\n";
+        let bytes = encode_text(source, gbk).unwrap();
+        assert!(std::str::from_utf8(&bytes).is_err());
+
+        let (decoded, detected) = decode_text(&bytes).unwrap();
+        assert_eq!(decoded, source);
+        assert_eq!(detected, gbk);
+
+        let sjis = encoding_for_label("shift_jis").unwrap();
+        let source = "設定ファイルを読み込んでデータベースに接続します。\n";
+        let bytes = encode_text(source, sjis).unwrap();
+        assert_eq!(decode_text(&bytes).unwrap(), (source.to_string(), sjis));
+    }
+
+    #[test]
+    fn binary_and_unencodable_content() {
+        assert!(decode_text(&[0x89, b'P', b'N', b'G', 0x00, 0xff]).is_none());
+
+        let latin1 = encoding_for_label("latin1").unwrap();
+        assert!(encode_text("中文", latin1).is_err());
+        assert!(encode_text("x", encoding_for_label("utf-16le").unwrap()).is_err());
+    }
+}
@@ -12,16 +12,28 @@
 //! - `subscribe` 失败 → 保持 `Idle`（由调用方决定是否重试）
 //! - `unsubscribe` → `Idle`
 //! - watcher 运行时错误 → `Degraded`（通过 event loop 上报）
+//!
+//! 订阅期间该工作区的文件索引为常驻索引（见 `retain_live_file_index`），
+//! 由 `FileChanged` 事件增量合并，不再按 TTL 过期重扫。
+//!
+//! ## 单文件监控
+//!
+//! 编辑器打开的文件可经 `file_watch` 单独监控（与工作区订阅相互独立，可跨工作区），
+//! 文件在磁盘上的修改时间或大小变化、被删除时推送 `FileWatchChanged`，
+//! 供客户端提示重新加载。监控的是文件所在目录，原子替换（写临时文件再重命名）同样能被捕获。
 
 use notify::{RecommendedWatcher, RecursiveMode};
 use notify_debouncer_mini::{new_debouncer, DebouncedEvent, Debouncer};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, UNIX_EPOCH};
 use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 
-use crate::application::file::FileWorkspacePhaseTracker;
+use crate::application::file::{
+    release_live_file_index, retain_live_file_index, FileWorkspacePhaseTracker,
+};
 use crate::server::protocol::file::FileChangeKind;
 
 /// 文件变化事件类型
@@ -36,6 +48,35 @@ pub enum WatchEvent {
     },
     /// Git 状态变化（.git/index 或 .git/HEAD 变化）
     GitStatusChanged { project: String, workspace: String },
+    /// 单文件监控的文件发生变化；`mtime` / `size` 为空表示文件已不存在
+    FileWatchChanged {
+        project: String,
+        workspace: String,
+        path: String,
+        mtime: Option<u64>,
+        size: Option<u64>,
+    },
+}
+
+/// 每个连接可同时监控的文件数上限
+pub const MAX_FILE_WATCHES: usize = 64;
+
+/// 单文件监控的防抖间隔
+const FILE_WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// 文件状态：(修改时间 Unix 毫秒, 大小)；文件不存在时为 `None`
+type FileStat = Option<(u64, u64)>;
+
+/// 单文件监控
+struct FileWatch {
+    project: String,
+    workspace: String,
+    /// 客户端传入的相对路径，原样回传
+    rel_path: String,
+    /// 最近一次上报（或开始监控时）的文件状态，与事件线程共享
+    last_stat: Arc<Mutex<FileStat>>,
+    /// 暂停期间为空
+    debouncer: Option<Debouncer<RecommendedWatcher>>,
 }
 
 /// 需要忽略的目录列表
@@ -69,6 +110,10 @@ pub struct WorkspaceWatcher {
     debouncer: Option<Debouncer<RecommendedWatcher>>,
     /// 事件发送通道
     event_tx: mpsc::Sender<WatchEvent>,
+    /// 主机休眠期间暂停：保留订阅信息，唤醒后重建监控
+    paused: bool,
+    /// 单文件监控，键为文件绝对路径
+    file_watches: HashMap<PathBuf, FileWatch>,
 }
 
 impl WorkspaceWatcher {
@@ -80,6 +125,8 @@ impl WorkspaceWatcher {
             watch_path: None,
             debouncer: None,
             event_tx,
+            paused: false,
+            file_watches: HashMap::new(),
         }
     }
 
@@ -131,6 +178,8 @@ impl WorkspaceWatcher {
 
         // 通知相位追踪器：watcher 就绪
         FileWorkspacePhaseTracker::on_watch_subscribed(&project, &workspace);
+        // 订阅期间文件索引转为常驻，由 watcher 事件增量维护
+        retain_live_file_index(&path);
 
         self.project = Some(project);
         self.workspace = Some(workspace);
@@ -150,12 +199,49 @@ impl WorkspaceWatcher {
             // 通知相位追踪器：watcher 退订
             FileWorkspacePhaseTracker::on_watch_unsubscribed(project, workspace);
         }
+        if let Some(ref path) = self.watch_path {
+            release_live_file_index(path);
+        }
 
         // 丢弃 debouncer 会自动停止监控
         self.debouncer = None;
         self.project = None;
         self.workspace = None;
         self.watch_path = None;
+        self.paused = false;
+    }
+
+    /// 暂停监控（主机即将休眠）：停止 notify 监控，保留订阅以便唤醒后恢复
+    pub fn pause(&mut self) {
+        for watch in self.file_watches.values_mut() {
+            watch.debouncer = None;
+        }
+        if self.debouncer.take().is_some() {
+            debug!("Watcher paused");
+            self.paused = true;
+        }
+    }
+
+    /// 恢复暂停的监控，返回恢复的 `(project, workspace, path)`。
+    /// 休眠期间的变化不会补发事件，调用方需按全量重扫处理。
+    pub fn resume(&mut self) -> Option<(String, String, PathBuf)> {
+        self.resume_file_watches();
+        if !self.paused {
+            return None;
+        }
+        self.paused = false;
+        let (project, workspace, path) = (
+            self.project.clone()?,
+            self.workspace.clone()?,
+            self.watch_path.clone()?,
+        );
+        match self.subscribe(project.clone(), workspace.clone(), path.clone()) {
+            Ok(()) => Some((project, workspace, path)),
+            Err(e) => {
+                warn!("Failed to resume watcher: {}", e);
+                None
+            }
+        }
     }
 
     /// 检查是否已订阅
@@ -171,6 +257,129 @@ impl WorkspaceWatcher {
         }
     }
 
+    /// 开始监控单个文件（`file_path` 为已校验的绝对路径）；已在监控时直接返回成功
+    pub fn watch_file(
+        &mut self,
+        project: String,
+        workspace: String,
+        rel_path: String,
+        file_path: PathBuf,
+    ) -> Result<(), String> {
+        if self.file_watches.contains_key(&file_path) {
+            return Ok(());
+        }
+        if self.file_watches.len() >= MAX_FILE_WATCHES {
+            return Err(format!(
+                "Too many watched files (limit {})",
+                MAX_FILE_WATCHES
+            ));
+        }
+        let last_stat = Arc::new(Mutex::new(file_stat(&file_path)));
+        let mut watch = FileWatch {
+            project,
+            workspace,
+            rel_path,
+            last_stat,
+            debouncer: None,
+        };
+        watch.debouncer = Some(self.start_file_watch(&watch, &file_path)?);
+        debug!("Watching file: {}", file_path.display());
+        self.file_watches.insert(file_path, watch);
+        Ok(())
+    }
+
+    /// 停止监控单个文件，返回此前是否在监控
+    pub fn unwatch_file(&mut self, file_path: &Path) -> bool {
+        self.file_watches.remove(file_path).is_some()
+    }
+
+    /// 当前监控的文件数
+    pub fn watched_file_count(&self) -> usize {
+        self.file_watches.len()
+    }
+
+    /// 唤醒后重建单文件监控；休眠期间发生的变化在重建时立即补发
+    fn resume_file_watches(&mut self) {
+        let paused = self
+            .file_watches
+            .iter()
+            .filter(|(_, watch)| watch.debouncer.is_none())
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        for path in paused {
+            let Some(watch) = self.file_watches.get(&path) else {
+                continue;
+            };
+            match self.start_file_watch(watch, &path) {
+                Ok(debouncer) => {
+                    if let Some(watch) = self.file_watches.get_mut(&path) {
+                        watch.debouncer = Some(debouncer);
+                    }
+                }
+                Err(e) => warn!("Failed to resume file watch {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    /// 监控文件所在目录并启动事件线程；线程启动时先与 `last_stat` 对账一次
+    fn start_file_watch(
+        &self,
+        watch: &FileWatch,
+        file_path: &Path,
+    ) -> Result<Debouncer<RecommendedWatcher>, String> {
+        let parent = file_path
+            .parent()
+            .ok_or_else(|| format!("Invalid file path: {}", file_path.display()))?;
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut debouncer = new_debouncer(FILE_WATCH_DEBOUNCE, tx)
+            .map_err(|e| format!("Failed to create debouncer: {}", e))?;
+        debouncer
+            .watcher()
+            .watch(parent, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch path: {}", e))?;
+
+        let event_tx = self.event_tx.clone();
+        let project = watch.project.clone();
+        let workspace = watch.workspace.clone();
+        let rel_path = watch.rel_path.clone();
+        let last_stat = watch.last_stat.clone();
+        let file_path = file_path.to_path_buf();
+        std::thread::spawn(move || {
+            let report = || {
+                let current = file_stat(&file_path);
+                let mut last = last_stat.lock().unwrap_or_else(|e| e.into_inner());
+                if *last == current {
+                    return true;
+                }
+                *last = current;
+                let event = WatchEvent::FileWatchChanged {
+                    project: project.clone(),
+                    workspace: workspace.clone(),
+                    path: rel_path.clone(),
+                    mtime: current.map(|(mtime, _)| mtime),
+                    size: current.map(|(_, size)| size),
+                };
+                crate::server::metrics::record_watcher_event("file_watch");
+                event_tx.blocking_send(event).is_ok()
+            };
+            if !report() {
+                return;
+            }
+            while let Ok(result) = rx.recv() {
+                match result {
+                    Ok(events) if events.iter().any(|e| e.path == file_path) => {
+                        if !report() {
+                            break;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("File watch error: {}", e),
+                }
+            }
+        });
+        Ok(debouncer)
+    }
+
     /// 事件处理循环
     fn event_loop(
         rx: std::sync::mpsc::Receiver<Result<Vec<DebouncedEvent>, notify::Error>>,
@@ -235,6 +444,7 @@ impl WorkspaceWatcher {
                 project: project.to_string(),
                 workspace: workspace.to_string(),
             };
+            crate::server::metrics::record_watcher_event("git_status");
             if let Err(e) = event_tx.blocking_send(event) {
                 warn!("Failed to send git status changed event: {}", e);
             }
@@ -251,6 +461,7 @@ impl WorkspaceWatcher {
                 paths,
                 kind: kind.as_str().to_string(),
             };
+            crate::server::metrics::record_watcher_event("file");
             if let Err(e) = event_tx.blocking_send(event) {
                 warn!("Failed to send file changed event: {}", e);
             }
@@ -321,6 +532,17 @@ impl WorkspaceWatcher {
     }
 }
 
+fn file_stat(path: &Path) -> FileStat {
+    let metadata = std::fs::metadata(path).ok().filter(|m| m.is_file())?;
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    Some((mtime, metadata.len()))
+}
+
 impl Drop for WorkspaceWatcher {
     fn drop(&mut self) {
         self.unsubscribe();
@@ -389,4 +611,90 @@ mod tests {
             &root
         ));
     }
+
+    #[test]
+    fn test_pause_and_resume_keep_subscription() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, _rx) = mpsc::channel(8);
+        let mut watcher = WorkspaceWatcher::new(tx);
+        assert!(watcher.resume().is_none());
+
+        watcher
+            .subscribe("p".to_string(), "w".to_string(), dir.path().to_path_buf())
+            .unwrap();
+        watcher.pause();
+        assert!(!watcher.is_subscribed());
+        assert_eq!(watcher.current_subscription(), Some(("p", "w")));
+
+        let resumed = watcher.resume().expect("paused watcher should resume");
+        assert_eq!(resumed.2, dir.path().to_path_buf());
+        assert!(watcher.is_subscribed());
+        assert!(watcher.resume().is_none());
+        watcher.unsubscribe();
+    }
+
+    #[tokio::test]
+    async fn test_file_watch_reports_external_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let file = root.join("main.rs");
+        std::fs::write(&file, "fn main() {}").unwrap();
+        std::fs::write(root.join("other.rs"), "").unwrap();
+
+        let (tx, mut rx) = mpsc::channel(8);
+        let mut watcher = WorkspaceWatcher::new(tx);
+        watcher
+            .watch_file(
+                "p".to_string(),
+                "w".to_string(),
+                "main.rs".to_string(),
+                file.clone(),
+            )
+            .unwrap();
+        watcher
+            .watch_file(
+                "p".to_string(),
+                "w".to_string(),
+                "main.rs".to_string(),
+                file.clone(),
+            )
+            .unwrap();
+        assert_eq!(watcher.watched_file_count(), 1);
+
+        // 其他文件的变化不上报
+        std::fs::write(root.join("other.rs"), "changed").unwrap();
+        // 原子替换：写临时文件再重命名
+        let tmp = root.join(".main.rs.tmp");
+        std::fs::write(&tmp, "fn main() { println!(); }").unwrap();
+        std::fs::rename(&tmp, &file).unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("file change should be reported")
+            .unwrap();
+        match event {
+            WatchEvent::FileWatchChanged { path, size, .. } => {
+                assert_eq!(path, "main.rs");
+                assert_eq!(size, Some(25));
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        std::fs::remove_file(&file).unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("deletion should be reported")
+            .unwrap();
+        assert!(matches!(
+            event,
+            WatchEvent::FileWatchChanged {
+                mtime: None,
+                size: None,
+                ..
+            }
+        ));
+
+        assert!(watcher.unwatch_file(&file));
+        assert!(!watcher.unwatch_file(&file));
+    }
 }
@@ -27,10 +27,22 @@ pub(super) type OutboundRx = tokio::sync::mpsc::Receiver<ServerMessage>;
 
 /// Run the WebSocket server on the specified port
 pub async fn run_server(port: u16) -> Result<(), Box<dyn std::error::Error>> {
-    server_runtime::run_server(port).await
+    server_runtime::run_server(port, None).await
 }
 
-pub use terminal::{ack_terminal_output, subscribe_terminal, unsubscribe_terminal};
+/// Run the server with optional TLS (HTTPS / WSS) for remote access
+pub async fn run_server_with_tls(
+    port: u16,
+    tls: Option<TlsConfig>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    server_runtime::run_server(port, tls).await
+}
+
+pub use transport::tls::TlsConfig;
+
+pub use terminal::{
+    ack_terminal_output, set_terminal_output_paused, subscribe_terminal, unsubscribe_terminal,
+};
 
 pub(super) async fn with_request_id<F, T>(request_id: Option<String>, fut: F) -> T
 where
@@ -57,11 +69,14 @@ fn record_outbound_queue_depth(outbound_tx: &OutboundTx) {
     crate::server::perf::record_ws_outbound_queue_depth(depth as u64);
 }
 
-/// 将服务端消息入队到每连接统一 outbound queue。
+/// 将服务端消息入队到每连接统一 outbound queue；git 冲突结果同时转发到推送中继。
 pub async fn send_message(outbound_tx: &OutboundTx, msg: &ServerMessage) -> Result<(), String> {
     record_outbound_queue_depth(outbound_tx);
+    crate::server::push_relay::notify_response(msg);
+    let mut msg = msg.clone();
+    msg.attach_trace_id();
     outbound_tx
-        .send(msg.clone())
+        .send(msg)
         .await
         .map_err(|_| "outbound queue closed".to_string())
 }
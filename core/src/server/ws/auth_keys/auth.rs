@@ -5,8 +5,18 @@ use tracing::trace;
 use super::model::{AuthorizedAPIKeyInfo, SharedRemoteAPIKeyRegistry, WsAuthQuery};
 use super::store::lookup_api_key_info;
 
+/// 以固定时间比较启动 token，避免按首个不同字节提前返回泄露 token 前缀
+pub(in crate::server::ws) fn token_matches(provided: &str, expected: &str) -> bool {
+    let (provided, expected) = (provided.as_bytes(), expected.as_bytes());
+    let mut diff = provided.len() ^ expected.len();
+    for (i, byte) in expected.iter().enumerate() {
+        diff |= usize::from(provided.get(i).copied().unwrap_or(0) ^ byte);
+    }
+    std::hint::black_box(diff) == 0
+}
+
 pub(in crate::server::ws) async fn authorize_token(
-    expected: Option<&str>,
+    expected: &str,
     provided: Option<&str>,
     query: &WsAuthQuery,
     api_key_registry: &SharedRemoteAPIKeyRegistry,
@@ -15,7 +25,7 @@ pub(in crate::server::ws) async fn authorize_token(
     if token.is_empty() {
         return None;
     }
-    if expected.is_some_and(|expected_token| expected_token == token) {
+    if token_matches(token, expected) {
         return Some(AuthorizedAPIKeyInfo {
             key_id: String::new(),
         });
@@ -37,18 +47,29 @@ pub(in crate::server::ws) async fn authorize_token(
 }
 
 pub(in crate::server::ws) async fn is_ws_token_authorized(
-    expected: Option<&str>,
+    expected: &str,
     query: &WsAuthQuery,
     api_key_registry: &SharedRemoteAPIKeyRegistry,
 ) -> bool {
-    match expected {
-        None => true,
-        Some(_) => authorize_token(expected, query.token.as_deref(), query, api_key_registry)
-            .await
-            .is_some(),
-    }
+    authorize_token(expected, query.token.as_deref(), query, api_key_registry)
+        .await
+        .is_some()
 }
 
 pub(in crate::server::ws) fn is_request_from_loopback(addr: SocketAddr) -> bool {
     addr.ip().is_loopback()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::token_matches;
+
+    #[test]
+    fn token_matches_requires_identical_tokens() {
+        assert!(token_matches("secret-token", "secret-token"));
+        assert!(!token_matches("secret-tokeN", "secret-token"));
+        assert!(!token_matches("secret", "secret-token"));
+        assert!(!token_matches("secret-token-longer", "secret-token"));
+        assert!(!token_matches("", "secret-token"));
+    }
+}
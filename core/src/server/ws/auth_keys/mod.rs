@@ -3,7 +3,7 @@ mod handlers;
 mod model;
 mod store;
 
-pub(in crate::server::ws) use auth::{authorize_token, is_ws_token_authorized};
+pub(in crate::server::ws) use auth::{authorize_token, is_ws_token_authorized, token_matches};
 pub(in crate::server::ws) use handlers::{
     create_api_key_handler, delete_api_key_handler, list_api_keys_handler,
 };
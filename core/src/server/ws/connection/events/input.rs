@@ -31,6 +31,7 @@ pub(in crate::server::ws) async fn handle_binary_client_message(
                 workspace: None,
                 session_id: None,
                 cycle_id: None,
+                trace_id: None,
             },
             &format!(
                 "Failed to send error message: conn_id={}, message_type={}",
@@ -1,8 +1,10 @@
 mod broadcast;
 mod common;
 mod input;
+mod power;
 mod watch;
 
 pub(in crate::server::ws) use broadcast::{handle_remote_term_event, handle_task_broadcast_event};
 pub(in crate::server::ws) use input::handle_binary_client_message;
+pub(in crate::server::ws) use power::handle_power_event;
 pub(in crate::server::ws) use watch::{forward_command_output, handle_watch_event};
@@ -0,0 +1,56 @@
+use crate::server::ws::OutboundTx as WebSocket;
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::application::file::invalidate_file_index_cache;
+use crate::server::git::cache::invalidate_git_status_cache;
+use crate::server::power::PowerEvent;
+use crate::server::protocol::ServerMessage;
+use crate::server::watcher::WorkspaceWatcher;
+
+use super::common::emit_message;
+
+/// 休眠时暂停本连接的 watcher；唤醒后恢复监控，按全量重扫失效已订阅工作区的
+/// 文件索引与 git 状态，并推送 `server_resumed` 让客户端刷新
+pub(in crate::server::ws) async fn handle_power_event(
+    event: PowerEvent,
+    socket: &WebSocket,
+    watcher: &Arc<tokio::sync::Mutex<WorkspaceWatcher>>,
+) {
+    match event {
+        PowerEvent::WillSleep => {
+            watcher.lock().await.pause();
+        }
+        PowerEvent::DidWake { slept_ms } => {
+            let resumed = watcher.lock().await.resume();
+            if let Some((project, workspace, root)) = resumed {
+                debug!(
+                    "Watcher resumed after wake: project={}, workspace={}",
+                    project, workspace
+                );
+                invalidate_git_status_cache(&root);
+                if let Some(base_version) = invalidate_file_index_cache(&root) {
+                    let delta = ServerMessage::FileIndexDelta {
+                        project: project.clone(),
+                        workspace: workspace.clone(),
+                        base_version,
+                        version: 0,
+                        added: Vec::new(),
+                        removed: Vec::new(),
+                        truncated: false,
+                        reset: true,
+                    };
+                    emit_message(socket, &delta, "Failed to send file index reset").await;
+                }
+                let git_changed = ServerMessage::GitStatusChanged { project, workspace };
+                emit_message(socket, &git_changed, "Failed to send git status changed").await;
+            }
+            emit_message(
+                socket,
+                &ServerMessage::ServerResumed { slept_ms },
+                "Failed to send server resumed message",
+            )
+            .await;
+        }
+    }
+}
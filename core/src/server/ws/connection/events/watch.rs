@@ -3,15 +3,16 @@ use tracing::debug;
 
 use crate::application::file::{invalidate_file_index_cache, update_file_index_incrementally};
 use crate::server::context::{HandlerContext, SharedAppState};
-use crate::server::git::status::invalidate_git_status_cache;
+use crate::server::git::cache::invalidate_git_status_cache;
 use crate::server::protocol::ServerMessage;
 use crate::server::watcher::WatchEvent;
 
 use super::common::emit_message;
 
 /// 单次 FileChanged 事件中路径数量超过此阈值时，放弃增量更新，直接全量失效。
-/// 避免大批量文件操作（如 npm install、git checkout）时做无效的逐条插入。
-const INCREMENTAL_UPDATE_PATH_THRESHOLD: usize = 32;
+/// 避免大批量文件操作（如 npm install、git checkout）时做无效的逐条对账。
+/// 增量合并按路径逐条 stat 对账，单次开销很小，阈值可以放宽到数百条。
+const INCREMENTAL_UPDATE_PATH_THRESHOLD: usize = 512;
 
 pub(in crate::server::ws) async fn handle_watch_event(
     watch_event: WatchEvent,
@@ -33,17 +34,40 @@ pub(in crate::server::ws) async fn handle_watch_event(
 
             let ws_ctx =
                 crate::server::context::resolve_workspace(app_state, &project, &workspace).await;
+            let mut index_delta = None;
             if let Ok(ctx) = ws_ctx {
                 invalidate_git_status_cache(&ctx.root_path);
 
                 // 增量更新策略：
-                // - 路径数量较少且 kind 明确时，尝试增量更新索引（避免全量重扫）
+                // - 路径数量未超过阈值时，按文件系统现状增量合并索引（避免全量重扫）
                 // - 路径数量超过阈值时（如 npm install），直接失效让下次全量重建
-                if paths.len() <= INCREMENTAL_UPDATE_PATH_THRESHOLD {
-                    update_file_index_incrementally(&ctx.root_path, &paths, &kind);
+                index_delta = if paths.len() <= INCREMENTAL_UPDATE_PATH_THRESHOLD {
+                    update_file_index_incrementally(&ctx.root_path, &paths, &kind).map(|delta| {
+                        ServerMessage::FileIndexDelta {
+                            project: project.clone(),
+                            workspace: workspace.clone(),
+                            base_version: delta.base_version,
+                            version: delta.version,
+                            added: delta.added,
+                            removed: delta.removed,
+                            truncated: delta.truncated,
+                            reset: false,
+                        }
+                    })
                 } else {
-                    invalidate_file_index_cache(&ctx.root_path);
-                }
+                    invalidate_file_index_cache(&ctx.root_path).map(|base_version| {
+                        ServerMessage::FileIndexDelta {
+                            project: project.clone(),
+                            workspace: workspace.clone(),
+                            base_version,
+                            version: 0,
+                            added: Vec::new(),
+                            removed: Vec::new(),
+                            truncated: false,
+                            reset: true,
+                        }
+                    })
+                };
             }
 
             let msg = ServerMessage::FileChanged {
@@ -53,6 +77,14 @@ pub(in crate::server::ws) async fn handle_watch_event(
                 kind,
             };
             emit_message(socket, &msg, "Failed to send file changed message").await;
+            if let Some(delta_msg) = index_delta {
+                emit_message(
+                    socket,
+                    &delta_msg,
+                    "Failed to send file index delta message",
+                )
+                .await;
+            }
         }
         WatchEvent::GitStatusChanged { project, workspace } => {
             debug!(
@@ -70,6 +102,27 @@ pub(in crate::server::ws) async fn handle_watch_event(
             let msg = ServerMessage::GitStatusChanged { project, workspace };
             emit_message(socket, &msg, "Failed to send git status changed message").await;
         }
+        WatchEvent::FileWatchChanged {
+            project,
+            workspace,
+            path,
+            mtime,
+            size,
+        } => {
+            debug!(
+                "Watched file changed: project={}, workspace={}, path={}",
+                project, workspace, path
+            );
+            let msg = ServerMessage::FileWatchChanged {
+                project,
+                workspace,
+                path,
+                deleted: mtime.is_none(),
+                mtime,
+                size,
+            };
+            emit_message(socket, &msg, "Failed to send file watch changed message").await;
+        }
     }
 }
 
@@ -1,10 +1,13 @@
 use crate::server::context::{ConnectionMeta, HandlerContext, SharedAppState};
+use crate::server::power::PowerEvent;
 use crate::server::protocol::ServerMessage;
-use crate::server::watcher::WatchEvent;
+use crate::server::watcher::{WatchEvent, WorkspaceWatcher};
 use crate::server::ws::connection::shared_types::{
     RemoteTermRecvResult, RemoteTermRx, TaskBroadcastRecvResult,
 };
 use crate::server::ws::OutboundTx as WebSocket;
+use std::sync::Arc;
+use tokio::sync::broadcast;
 
 pub(super) async fn handle_watch_channel_event(
     watch_event: WatchEvent,
@@ -15,6 +18,17 @@ pub(super) async fn handle_watch_channel_event(
     super::super::events::handle_watch_event(watch_event, socket, app_state, handler_ctx).await;
 }
 
+pub(super) async fn handle_power_channel_event(
+    result: Result<PowerEvent, broadcast::error::RecvError>,
+    socket: &WebSocket,
+    watcher: &Arc<tokio::sync::Mutex<WorkspaceWatcher>>,
+) {
+    // 发送端为全局静态，不会关闭；积压时丢弃的事件由之后的事件覆盖
+    if let Ok(event) = result {
+        super::super::events::handle_power_event(event, socket, watcher).await;
+    }
+}
+
 pub(super) async fn handle_cmd_output_event(msg: ServerMessage, socket: &WebSocket) {
     super::super::events::forward_command_output(msg, socket).await;
 }
@@ -1,8 +1,9 @@
 use tracing::{debug, info, trace};
 
 use crate::server::context::{ConnectionMeta, HandlerContext, SharedAppState};
+use crate::server::power::PowerEvent;
 use crate::server::protocol::ServerMessage;
-use crate::server::watcher::WatchEvent;
+use crate::server::watcher::{WatchEvent, WorkspaceWatcher};
 use crate::server::ws::connection::shared_types::{RemoteTermRx, TaskBroadcastRx};
 use crate::server::ws::OutboundTx;
 
@@ -17,6 +18,8 @@ pub(in crate::server::ws) struct EventLoopDeps {
     pub outbound_tx: OutboundTx,
     pub agg_rx: tokio::sync::mpsc::Receiver<(String, Vec<u8>)>,
     pub rx_watch: tokio::sync::mpsc::Receiver<WatchEvent>,
+    pub watcher: std::sync::Arc<tokio::sync::Mutex<WorkspaceWatcher>>,
+    pub power_rx: tokio::sync::broadcast::Receiver<PowerEvent>,
     pub cmd_output_rx: tokio::sync::mpsc::Receiver<ServerMessage>,
     pub task_broadcast_rx: TaskBroadcastRx,
     pub remote_term_rx: Option<RemoteTermRx>,
@@ -76,6 +79,14 @@ pub(in crate::server::ws) async fn run_outbound_event_loop(mut deps: EventLoopDe
                 (select_wait_ms, handle_ms, false)
             }
 
+            result = deps.power_rx.recv() => {
+                let select_wait_ms = select_started.elapsed().as_millis() as u64;
+                let handle_started = std::time::Instant::now();
+                channels::handle_power_channel_event(result, &deps.outbound_tx, &deps.watcher).await;
+                let handle_ms = handle_started.elapsed().as_millis() as u64;
+                (select_wait_ms, handle_ms, false)
+            }
+
             Some(msg) = deps.cmd_output_rx.recv() => {
                 let select_wait_ms = select_started.elapsed().as_millis() as u64;
                 let handle_started = std::time::Instant::now();
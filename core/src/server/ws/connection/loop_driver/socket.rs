@@ -73,6 +73,8 @@ async fn handle_socket_recv_result(
     }
 }
 
+/// 读取循环：消息按序逐条处理；处理期间继续读取下一条，`cancel` 立即执行
+/// （以便放弃当前长耗时请求），其余消息暂存至当前消息处理完毕，保持原有顺序
 pub(in crate::server::ws) async fn run_reader_loop(
     mut socket_rx: futures::stream::SplitStream<WebSocket>,
     outbound_tx: OutboundTx,
@@ -80,12 +82,37 @@ pub(in crate::server::ws) async fn run_reader_loop(
     watcher: std::sync::Arc<tokio::sync::Mutex<WorkspaceWatcher>>,
     conn_meta: ConnectionMeta,
 ) -> bool {
+    let mut pending = None;
     loop {
-        let msg_result = socket_rx.next().await;
-        if let LoopControl::Break =
-            handle_socket_recv_result(msg_result, &outbound_tx, &handler_ctx, &watcher, &conn_meta)
-                .await
-        {
+        let msg_result = match pending.take() {
+            Some(msg_result) => msg_result,
+            None => socket_rx.next().await,
+        };
+        let handling =
+            handle_socket_recv_result(msg_result, &outbound_tx, &handler_ctx, &watcher, &conn_meta);
+        tokio::pin!(handling);
+        let control = loop {
+            tokio::select! {
+                control = &mut handling => break control,
+                next = socket_rx.next(), if pending.is_none() => {
+                    if let Some(Ok(Message::Binary(data))) = &next {
+                        if crate::server::ws::dispatch::probe_client_message_type(data) == "cancel" {
+                            super::super::events::handle_binary_client_message(
+                                data,
+                                &outbound_tx,
+                                &handler_ctx,
+                                &watcher,
+                                &conn_meta,
+                            )
+                            .await;
+                            continue;
+                        }
+                    }
+                    pending = Some(next);
+                }
+            }
+        };
+        if let LoopControl::Break = control {
             return false;
         }
     }
@@ -95,6 +95,8 @@ async fn run_connection_loop(
             outbound_tx: outbound_tx.clone(),
             agg_rx,
             rx_watch,
+            watcher: watcher.clone(),
+            power_rx: crate::server::power::subscribe_power_events(),
             cmd_output_rx,
             task_broadcast_rx,
             remote_term_rx,
@@ -140,6 +142,7 @@ async fn run_connection_loop(
                     workspace: None,
                     session_id: None,
                     cycle_id: None,
+                    trace_id: None,
                 },
             )
             .await;
@@ -173,6 +176,7 @@ pub(super) async fn handle_socket(
         "New WebSocket connection established (conn_id={}, remote={})",
         conn_meta.conn_id, conn_meta.is_remote
     );
+    let _connection_guard = crate::server::metrics::track_connection();
     let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<String>();
     if let Some(key_id) = conn_meta.api_key_id.as_deref() {
         let mut registry = remote_connection_registry.lock().await;
@@ -43,6 +43,7 @@ fn build_dispatch_input(data: &[u8]) -> Result<DispatchInput, String> {
 
 async fn dispatch_parsed_message(
     route: crate::server::protocol::domain_table::DomainRoute,
+    action: &str,
     client_msg: &ClientMessage,
     socket: &WebSocket,
     ctx: &HandlerContext,
@@ -50,7 +51,13 @@ async fn dispatch_parsed_message(
 ) -> Result<bool, String> {
     let dispatch_started = std::time::Instant::now();
     let result = router::dispatch_domain_handler(route, client_msg, socket, ctx, watcher).await;
-    crate::server::perf::record_ws_dispatch_ms(dispatch_started.elapsed().as_millis() as u64);
+    let elapsed = dispatch_started.elapsed();
+    crate::server::perf::record_ws_dispatch_ms(elapsed.as_millis() as u64);
+    crate::server::metrics::record_request(
+        crate::server::protocol::domain_table::domain_route_id(route),
+        action,
+        elapsed,
+    );
     result
 }
 
@@ -64,6 +71,7 @@ async fn send_unhandled_message(socket: &WebSocket) -> Result<(), String> {
             workspace: None,
             session_id: None,
             cycle_id: None,
+            trace_id: None,
         },
     )
     .await
@@ -85,8 +93,11 @@ pub(super) async fn handle_client_message(
 
     let input = build_dispatch_input(data)?;
     let request_id = input.envelope.request_id.clone();
+    let trace_id = crate::util::trace::new_trace_id();
+    let action = input.envelope.action.clone();
 
-    crate::server::ws::with_request_id(Some(request_id), async {
+    let cancel_id = request_id.clone();
+    let handled = crate::server::ws::with_request_id(Some(request_id), async {
         trace!(
             "Parsed client message: domain={}, action={}, discriminant={:?}",
             input.envelope.domain,
@@ -95,8 +106,35 @@ pub(super) async fn handle_client_message(
         );
 
         audit::log_ai_control_message(&input.client_msg, ctx);
+        crate::server::audit_log::record_request(
+            &input.envelope.domain,
+            &input.envelope.action,
+            &input.envelope.payload,
+            &ctx.conn_meta,
+        )
+        .await;
+
+        // 项目级请求先确保目标项目已完成懒加载水合
+        if let Some(project) = input
+            .envelope
+            .payload
+            .get("project")
+            .and_then(|value| value.as_str())
+        {
+            crate::workspace::ensure_project_hydrated(&ctx.app_state, &ctx.state_store, project)
+                .await;
+        }
 
-        if !dispatch_parsed_message(input.route, &input.client_msg, socket, ctx, watcher).await? {
+        if !dispatch_parsed_message(
+            input.route,
+            &input.envelope.action,
+            &input.client_msg,
+            socket,
+            ctx,
+            watcher,
+        )
+        .await?
+        {
             warn!(
                 "Unhandled message type: domain={}, action={}, discriminant={:?}",
                 input.envelope.domain,
@@ -107,6 +145,8 @@ pub(super) async fn handle_client_message(
         }
 
         Ok(())
-    })
-    .await
+    });
+    // 分发 future 体积很大，装箱后再逐层包裹，避免 debug 构建下按值搬移撑爆 worker 栈
+    let cancellable = crate::util::cancel::scope(cancel_id, Box::pin(handled));
+    crate::util::trace::scope(trace_id, &action, cancellable).await
 }
@@ -1,7 +1,7 @@
 use crate::server::ws::OutboundTx as WebSocket;
 
 use crate::server::context::HandlerContext;
-use crate::server::protocol::{ClientMessage, ServerMessage};
+use crate::server::protocol::{ClientMessage, HostPowerEventKind, ServerMessage};
 use crate::server::ws::send_message;
 
 pub(super) async fn handle_system_domain(
@@ -13,6 +13,26 @@ pub(super) async fn handle_system_domain(
             send_message(socket, &ServerMessage::Pong).await?;
             Ok(true)
         }
+        ClientMessage::HostPowerEvent { event } => {
+            match event {
+                HostPowerEventKind::WillSleep => crate::server::power::notify_will_sleep(),
+                HostPowerEventKind::DidWake => crate::server::power::notify_did_wake(None),
+            }
+            Ok(true)
+        }
+        ClientMessage::Cancel { request_id } => {
+            let cancelled = crate::util::cancel::cancel(request_id);
+            tracing::debug!(request_id = %request_id, cancelled, "Cancel requested");
+            send_message(
+                socket,
+                &ServerMessage::CancelResult {
+                    request_id: request_id.clone(),
+                    cancelled,
+                },
+            )
+            .await?;
+            Ok(true)
+        }
         _ => Ok(false),
     }
 }
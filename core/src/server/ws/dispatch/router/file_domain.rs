@@ -1,7 +1,10 @@
 use crate::server::ws::OutboundTx as WebSocket;
+use std::path::PathBuf;
 use tracing::{info, trace};
 
 use crate::server::context::HandlerContext;
+use crate::server::file_api;
+use crate::server::file_api::provider::is_remote_root;
 use crate::server::protocol::{ClientMessage, ServerMessage};
 use crate::server::ws::dispatch::shared_types::DispatchWatcher;
 use crate::server::ws::send_message;
@@ -21,6 +24,22 @@ pub(super) async fn handle_file_domain(
             handle_watch_unsubscribe(socket, watcher).await?;
             Ok(true)
         }
+        ClientMessage::FileWatch {
+            project,
+            workspace,
+            path,
+        } => {
+            handle_file_watch(project, workspace, path, socket, ctx, watcher).await?;
+            Ok(true)
+        }
+        ClientMessage::FileUnwatch {
+            project,
+            workspace,
+            path,
+        } => {
+            handle_file_unwatch(project, workspace, path, socket, ctx, watcher).await?;
+            Ok(true)
+        }
         _ => handle_regular_file_message(client_msg, socket, ctx).await,
     }
 }
@@ -61,6 +80,7 @@ async fn handle_watch_subscribe(
                             workspace: None,
                             session_id: None,
                             cycle_id: None,
+                            trace_id: None,
                         },
                     )
                     .await?;
@@ -84,6 +104,115 @@ async fn handle_watch_unsubscribe(
     send_message(socket, &ServerMessage::WatchUnsubscribed).await
 }
 
+async fn handle_file_watch(
+    project: &str,
+    workspace: &str,
+    path: &str,
+    socket: &WebSocket,
+    ctx: &HandlerContext,
+    watcher: &DispatchWatcher,
+) -> Result<(), String> {
+    trace!(
+        "FileWatch: project={}, workspace={}, path={}",
+        project,
+        workspace,
+        path
+    );
+    let file_path = match resolve_watch_path(project, workspace, path, ctx).await {
+        Ok(file_path) => file_path,
+        Err(msg) => return send_message(socket, &msg).await,
+    };
+    let result = watcher.lock().await.watch_file(
+        project.to_string(),
+        workspace.to_string(),
+        path.to_string(),
+        file_path,
+    );
+    let msg = match result {
+        Ok(()) => ServerMessage::FileWatched {
+            project: project.to_string(),
+            workspace: workspace.to_string(),
+            path: path.to_string(),
+        },
+        Err(e) => ServerMessage::Error {
+            code: "file_watch_failed".to_string(),
+            message: e,
+            project: Some(project.to_string()),
+            workspace: Some(workspace.to_string()),
+            session_id: None,
+            cycle_id: None,
+            trace_id: None,
+        },
+    };
+    send_message(socket, &msg).await
+}
+
+async fn handle_file_unwatch(
+    project: &str,
+    workspace: &str,
+    path: &str,
+    socket: &WebSocket,
+    ctx: &HandlerContext,
+    watcher: &DispatchWatcher,
+) -> Result<(), String> {
+    trace!(
+        "FileUnwatch: project={}, workspace={}, path={}",
+        project,
+        workspace,
+        path
+    );
+    match resolve_watch_path(project, workspace, path, ctx).await {
+        Ok(file_path) => {
+            watcher.lock().await.unwatch_file(&file_path);
+        }
+        Err(msg) => return send_message(socket, &msg).await,
+    }
+    send_message(
+        socket,
+        &ServerMessage::FileUnwatched {
+            project: project.to_string(),
+            workspace: workspace.to_string(),
+            path: path.to_string(),
+        },
+    )
+    .await
+}
+
+/// 解析被监控文件的绝对路径；远程项目不支持单文件监控
+async fn resolve_watch_path(
+    project: &str,
+    workspace: &str,
+    path: &str,
+    ctx: &HandlerContext,
+) -> Result<PathBuf, ServerMessage> {
+    let ws_ctx = crate::server::context::resolve_workspace(&ctx.app_state, project, workspace)
+        .await
+        .map_err(|e| e.to_server_error())?;
+    let error = |code: &str, message: String| ServerMessage::Error {
+        code: code.to_string(),
+        message,
+        project: Some(project.to_string()),
+        workspace: Some(workspace.to_string()),
+        session_id: None,
+        cycle_id: None,
+        trace_id: None,
+    };
+    if is_remote_root(&ws_ctx.root_path) {
+        return Err(error(
+            "invalid_request",
+            "File watch is not supported for remote projects".to_string(),
+        ));
+    }
+    let file_path = file_api::resolve_safe_path(&ws_ctx.root_path, path)
+        .map_err(|e| error("file_watch_failed", e.to_string()))?;
+    // 按所在目录规范化（不解析文件本身），文件删除后 file_unwatch 仍能命中同一监控
+    let parent = file_path.parent().and_then(|p| p.canonicalize().ok());
+    Ok(match (parent, file_path.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => file_path,
+    })
+}
+
 async fn handle_regular_file_message(
     client_msg: &ClientMessage,
     socket: &WebSocket,
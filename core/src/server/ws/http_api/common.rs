@@ -18,8 +18,16 @@ pub(in crate::server::ws) enum ApiError {
     BadRequest(String),
     NotFound(String),
     Internal(String),
+    /// 客户端经 `cancel` 放弃了该请求
+    Cancelled,
 }
 
+/// 非标准状态码 499（Client Closed Request），用于已取消的请求
+const CLIENT_CLOSED_REQUEST: StatusCode = match StatusCode::from_u16(499) {
+    Ok(status) => status,
+    Err(_) => StatusCode::BAD_REQUEST,
+};
+
 #[derive(Debug, Serialize)]
 struct ApiErrorBody {
     code: String,
@@ -28,6 +36,8 @@ struct ApiErrorBody {
     project: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     workspace: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace_id: Option<String>,
 }
 
 impl IntoResponse for ApiError {
@@ -49,6 +59,11 @@ impl IntoResponse for ApiError {
                 "internal_error".to_string(),
                 message,
             ),
+            ApiError::Cancelled => (
+                CLIENT_CLOSED_REQUEST,
+                crate::util::cancel::CANCELLED_CODE.to_string(),
+                "request cancelled".to_string(),
+            ),
         };
 
         (
@@ -58,6 +73,7 @@ impl IntoResponse for ApiError {
                 message,
                 project: None,
                 workspace: None,
+                trace_id: crate::util::trace::current_trace_id(),
             }),
         )
             .into_response()
@@ -65,8 +81,9 @@ impl IntoResponse for ApiError {
 }
 
 pub(in crate::server::ws) fn json_from_server_message(
-    message: ServerMessage,
+    mut message: ServerMessage,
 ) -> Result<Json<serde_json::Value>, ApiError> {
+    message.attach_trace_id();
     serde_json::to_value(message)
         .map(Json)
         .map_err(|e| ApiError::Internal(format!("serialize response failed: {}", e)))
@@ -198,3 +215,68 @@ pub(in crate::server::ws) fn build_http_handler_context(
         state_store: ctx.state_store.clone(),
     }
 }
+
+/// 路由中间件：路径含 `:project` 时，先确保该项目已完成懒加载水合
+pub(in crate::server::ws) async fn hydrate_project_middleware(
+    axum::extract::State(ctx): axum::extract::State<
+        crate::server::ws::transport::bootstrap::AppContext,
+    >,
+    params: Option<axum::extract::RawPathParams>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> Response {
+    let project = params.as_ref().and_then(|params| {
+        params
+            .iter()
+            .find(|(key, _)| *key == "project")
+            .map(|(_, value)| value.to_string())
+    });
+    if let Some(project) = project {
+        crate::workspace::ensure_project_hydrated(&ctx.app_state, &ctx.state_store, &project).await;
+    }
+    next.run(request).await
+}
+
+/// 为每个 HTTP 请求分配 trace id：处理期间的日志与子进程携带该 ID，响应头回写 `x-tidyflow-trace-id`
+pub(in crate::server::ws) async fn trace_request_middleware(
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> Response {
+    let trace_id = crate::util::trace::new_trace_id();
+    let action = format!("{} {}", request.method(), request.uri().path());
+    let mut response =
+        crate::util::trace::scope(trace_id.clone(), &action, next.run(request)).await;
+    if let Ok(value) = axum::http::HeaderValue::from_str(&trace_id) {
+        response.headers_mut().insert(TRACE_ID_HEADER, value);
+    }
+    response
+}
+
+/// 请求携带 `x-tidyflow-request-id` 时登记取消令牌，使其可被 WS `cancel` 放弃；
+/// 处理期间被取消的请求统一以 499 `request_cancelled` 响应
+pub(in crate::server::ws) async fn cancel_request_middleware(
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> Response {
+    let request_id = request
+        .headers()
+        .get(crate::util::cancel::REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string);
+    let Some(request_id) = request_id else {
+        return next.run(request).await;
+    };
+    crate::util::cancel::scope(request_id, async {
+        let response = next.run(request).await;
+        if crate::util::cancel::is_cancelled() {
+            return ApiError::Cancelled.into_response();
+        }
+        response
+    })
+    .await
+}
+
+/// 回写 trace id 的 HTTP 响应头
+const TRACE_ID_HEADER: &str = "x-tidyflow-trace-id";
@@ -1,6 +1,7 @@
 use axum::{
     extract::{Path, Query, State},
-    http::HeaderMap,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
@@ -9,6 +10,7 @@ use serde::{Deserialize, Serialize};
 
 use super::auth::ensure_http_authorized;
 use super::common::{json_from_server_message, ApiError, WorkspaceQueryContext};
+use crate::server::file_api::{ByteRange, FileApiError};
 
 #[derive(Debug, Deserialize)]
 pub(in crate::server::ws) struct WorkspacePath {
@@ -32,8 +34,54 @@ pub(in crate::server::ws) struct FileIndexQuery {
     token: Option<String>,
 }
 
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct FileQueryQuery {
+    #[serde(default)]
+    query: Option<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct FileHighlightQuery {
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    theme: Option<String>,
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    token: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Default)]
 pub(in crate::server::ws) struct FileContentQuery {
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    encoding: Option<String>,
+    #[serde(default)]
+    preview: bool,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct FileRevisionQuery {
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    rev: Option<String>,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct FileDefinitionQuery {
+    #[serde(default)]
+    symbol: Option<String>,
     #[serde(default)]
     path: Option<String>,
     #[serde(default)]
@@ -49,6 +97,39 @@ pub(in crate::server::ws) struct FileReadHTTPResponse {
     path: String,
     size: u64,
     content_base64: String,
+    encoding: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line_ending: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    charset: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mtime: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(in crate::server::ws) struct FileReadAtRevisionHTTPResponse {
+    #[serde(rename = "type")]
+    msg_type: &'static str,
+    project: String,
+    workspace: String,
+    path: String,
+    rev: String,
+    sha: String,
+    size: u64,
+    content_base64: String,
+    encoding: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line_ending: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    charset: Option<String>,
 }
 
 pub(in crate::server::ws) async fn file_list_handler(
@@ -99,6 +180,31 @@ pub(in crate::server::ws) async fn file_index_handler(
     json_from_server_message(response)
 }
 
+pub(in crate::server::ws) async fn file_query_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<WorkspacePath>,
+    Query(query): Query<FileQueryQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    let response = crate::server::handlers::file::query::query_file_query(
+        &ctx.app_state,
+        &path.project,
+        &path.workspace,
+        query.query.as_deref().unwrap_or(""),
+        query.limit,
+    )
+    .await
+    .map_err(|e| {
+        qctx.map_query_error(match e {
+            crate::server::protocol::ServerMessage::Error { message, .. } => message,
+            _ => "file query failed".to_string(),
+        })
+    })?;
+    json_from_server_message(response)
+}
+
 #[derive(Debug, Deserialize, Default)]
 pub(in crate::server::ws) struct FileSearchQuery {
     #[serde(default)]
@@ -136,6 +242,100 @@ pub(in crate::server::ws) async fn file_search_handler(
     json_from_server_message(response)
 }
 
+pub(in crate::server::ws) async fn file_definition_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<WorkspacePath>,
+    Query(query): Query<FileDefinitionQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let symbol = query
+        .symbol
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| ApiError::BadRequest("missing symbol".to_string()))?;
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    let response = crate::server::handlers::file::query::query_file_definition_guess(
+        &ctx.app_state,
+        &path.project,
+        &path.workspace,
+        symbol,
+        query
+            .path
+            .as_deref()
+            .filter(|value| !value.trim().is_empty()),
+    )
+    .await
+    .map_err(|e| {
+        qctx.map_query_error(match e {
+            crate::server::protocol::ServerMessage::Error { message, .. } => message,
+            _ => "file definition guess failed".to_string(),
+        })
+    })?;
+    json_from_server_message(response)
+}
+
+pub(in crate::server::ws) async fn file_editorconfig_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<WorkspacePath>,
+    Query(query): Query<FileContentQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let file_path = query
+        .path
+        .as_deref()
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| ApiError::BadRequest("missing path".to_string()))?;
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    let response = crate::server::handlers::file::query::query_file_editorconfig(
+        &ctx.app_state,
+        &path.project,
+        &path.workspace,
+        file_path,
+    )
+    .await
+    .map_err(|e| {
+        qctx.map_query_error(match e {
+            crate::server::protocol::ServerMessage::Error { message, .. } => message,
+            _ => "file editorconfig failed".to_string(),
+        })
+    })?;
+    json_from_server_message(response)
+}
+
+pub(in crate::server::ws) async fn file_highlight_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<WorkspacePath>,
+    Query(query): Query<FileHighlightQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let file_path = query
+        .path
+        .as_deref()
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| ApiError::BadRequest("missing path".to_string()))?;
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    let response = crate::server::handlers::file::query::query_file_highlight(
+        &ctx.app_state,
+        &path.project,
+        &path.workspace,
+        file_path,
+        query.theme.as_deref(),
+        query.format.as_deref(),
+    )
+    .await
+    .map_err(|e| {
+        qctx.map_query_error(match e {
+            crate::server::protocol::ServerMessage::Error { message, .. } => message,
+            _ => "file highlight failed".to_string(),
+        })
+    })?;
+    json_from_server_message(response)
+}
+
 pub(in crate::server::ws) async fn file_content_handler(
     State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
     headers: HeaderMap,
@@ -154,6 +354,8 @@ pub(in crate::server::ws) async fn file_content_handler(
         &path.project,
         &path.workspace,
         read_path,
+        query.encoding.as_deref(),
+        query.preview,
     )
     .await
     .map_err(|e| {
@@ -170,6 +372,13 @@ pub(in crate::server::ws) async fn file_content_handler(
             path,
             content,
             size,
+            encoding,
+            mime_type,
+            line_ending,
+            charset,
+            truncated,
+            mtime,
+            hash,
         } => Ok(Json(FileReadHTTPResponse {
             msg_type: "file_read_result",
             project,
@@ -177,9 +386,177 @@ pub(in crate::server::ws) async fn file_content_handler(
             path,
             size,
             content_base64: BASE64_STANDARD.encode(content),
+            encoding,
+            mime_type,
+            line_ending,
+            charset,
+            truncated,
+            mtime,
+            hash,
         })),
         _ => Err(ApiError::Internal(
             "unexpected file read response type".to_string(),
         )),
     }
 }
+
+pub(in crate::server::ws) async fn file_revision_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<WorkspacePath>,
+    Query(query): Query<FileRevisionQuery>,
+) -> Result<Json<FileReadAtRevisionHTTPResponse>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let read_path = query
+        .path
+        .as_deref()
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| ApiError::BadRequest("missing path".to_string()))?;
+    let rev = query
+        .rev
+        .as_deref()
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| ApiError::BadRequest("missing rev".to_string()))?;
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    let response = crate::server::handlers::file::read_write::query_file_read_at_revision(
+        &ctx.app_state,
+        &path.project,
+        &path.workspace,
+        read_path,
+        rev,
+    )
+    .await
+    .map_err(|e| {
+        qctx.map_query_error(match e {
+            crate::server::protocol::ServerMessage::Error { message, .. } => message,
+            _ => "file read at revision failed".to_string(),
+        })
+    })?;
+
+    match response {
+        crate::server::protocol::ServerMessage::FileReadAtRevisionResult {
+            project,
+            workspace,
+            path,
+            rev,
+            sha,
+            content,
+            size,
+            encoding,
+            mime_type,
+            line_ending,
+            charset,
+        } => Ok(Json(FileReadAtRevisionHTTPResponse {
+            msg_type: "file_read_at_revision_result",
+            project,
+            workspace,
+            path,
+            rev,
+            sha,
+            size,
+            content_base64: BASE64_STANDARD.encode(content),
+            encoding,
+            mime_type,
+            line_ending,
+            charset,
+        })),
+        crate::server::protocol::ServerMessage::Error { message, .. } => {
+            Err(qctx.map_query_error(message))
+        }
+        _ => Err(ApiError::Internal(
+            "unexpected file read at revision response type".to_string(),
+        )),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(in crate::server::ws) struct AssetPath {
+    project: String,
+    workspace: String,
+    path: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct AssetQuery {
+    #[serde(default)]
+    token: Option<String>,
+}
+
+/// 预览资源直出：原样返回文件字节，支持单区间 `Range` 请求
+///
+/// 供 Markdown 预览与文件浏览器中的 `<img>` / `@font-face` 直接引用（可经 `?token=` 鉴权），
+/// 无需经 WS 以 base64 传输。响应禁止内容嗅探并以沙箱 CSP 隔离（SVG 等不执行脚本）。
+pub(in crate::server::ws) async fn file_asset_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<AssetPath>,
+    Query(query): Query<AssetQuery>,
+) -> Result<Response, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    let ws_ctx =
+        crate::server::context::resolve_workspace(&ctx.app_state, &path.project, &path.workspace)
+            .await
+            .map_err(|e| qctx.map_query_error(e.to_string()))?;
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(ByteRange::parse);
+
+    // 本地读取与 SFTP 读取均为阻塞 IO，放到阻塞线程避免大文件占住 tokio worker
+    let root = ws_ctx.root_path;
+    let rel_path = path.path.clone();
+    let result = crate::util::trace::spawn_blocking(move || {
+        crate::application::file::file_asset(&root, &rel_path, range)
+    })
+    .await
+    .map_err(|e| ApiError::Internal(format!("File asset task failed: {}", e)))?;
+    let asset = match result {
+        Ok(asset) => asset,
+        Err(FileApiError::RangeNotSatisfiable(size)) => {
+            return Ok((
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [(header::CONTENT_RANGE, format!("bytes */{}", size))],
+            )
+                .into_response());
+        }
+        Err(e) => {
+            let (_, message) = crate::application::file::file_error_to_response(&e);
+            return Err(match e {
+                FileApiError::FileNotFound => qctx.not_found_error(message),
+                FileApiError::PathEscape
+                | FileApiError::PathTooLong
+                | FileApiError::FileTooLarge => qctx.bad_request_error(message),
+                _ => ApiError::Internal(message),
+            });
+        }
+    };
+
+    let status = if asset.range.is_some() {
+        StatusCode::PARTIAL_CONTENT
+    } else {
+        StatusCode::OK
+    };
+    let mut response = (status, asset.content).into_response();
+    let response_headers = response.headers_mut();
+    let mut insert = |name: header::HeaderName, value: String| {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            response_headers.insert(name, value);
+        }
+    };
+    insert(header::CONTENT_TYPE, asset.mime_type);
+    insert(header::ACCEPT_RANGES, "bytes".to_string());
+    insert(header::CACHE_CONTROL, "no-cache".to_string());
+    insert(header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string());
+    insert(
+        header::CONTENT_SECURITY_POLICY,
+        "default-src 'none'; img-src 'self' data:; style-src 'unsafe-inline'; sandbox".to_string(),
+    );
+    if let Some((start, end)) = asset.range {
+        insert(
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", start, end, asset.size),
+        );
+    }
+    Ok(response)
+}
@@ -1,12 +1,13 @@
 use axum::{
     extract::{Path, Query, State},
-    http::HeaderMap,
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::Deserialize;
 
 use super::auth::ensure_http_authorized;
-use super::common::{json_from_server_message, ApiError, WorkspaceQueryContext};
+use super::common::{json_from_server_message, map_query_error, ApiError, WorkspaceQueryContext};
 
 #[derive(Debug, Deserialize)]
 pub(in crate::server::ws) struct WorkspacePath {
@@ -26,12 +27,34 @@ pub(in crate::server::ws) struct CommitPath {
     sha: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub(in crate::server::ws) struct PullRequestPath {
+    project: String,
+    number: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub(in crate::server::ws) struct ChangeReportPath {
+    report_id: String,
+}
+
 #[derive(Debug, Deserialize, Default)]
 pub(in crate::server::ws) struct TokenQuery {
     #[serde(default)]
     token: Option<String>,
 }
 
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct GitStatusQuery {
+    #[serde(default)]
+    untracked_files: Option<String>,
+    /// 逗号分隔的未跟踪目录列表
+    #[serde(default)]
+    expand_untracked: Option<String>,
+    #[serde(default)]
+    token: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Default)]
 pub(in crate::server::ws) struct GitDiffQuery {
     path: String,
@@ -40,6 +63,102 @@ pub(in crate::server::ws) struct GitDiffQuery {
     #[serde(default)]
     base: Option<String>,
     #[serde(default)]
+    algorithm: Option<String>,
+    #[serde(default)]
+    range: Option<String>,
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    intraline: bool,
+    #[serde(default)]
+    smart_diff: bool,
+    #[serde(default)]
+    include_blobs: bool,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct GitDiffPageQuery {
+    path: String,
+    offset: u64,
+    #[serde(default)]
+    mode: Option<String>,
+    #[serde(default)]
+    base: Option<String>,
+    #[serde(default)]
+    algorithm: Option<String>,
+    #[serde(default)]
+    range: Option<String>,
+    #[serde(default)]
+    smart_diff: bool,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct GitShowFileDiffQuery {
+    path: String,
+    #[serde(default)]
+    algorithm: Option<String>,
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    intraline: bool,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct GitSuggestedCommitMessageQuery {
+    #[serde(default)]
+    link_url: Option<String>,
+    #[serde(default)]
+    generate: bool,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct GitLargeBlobsQuery {
+    #[serde(default)]
+    threshold: Option<u64>,
+    #[serde(default)]
+    filter_repo_plan: bool,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct GitIntegrationStatusQuery {
+    #[serde(default)]
+    branch: Option<String>,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct GitDiffRangeQuery {
+    range: String,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct GitRebasePlanQuery {
+    base: String,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct GitBlameQuery {
+    path: String,
+    #[serde(default)]
+    rev: Option<String>,
+    #[serde(default)]
     token: Option<String>,
 }
 
@@ -48,6 +167,22 @@ pub(in crate::server::ws) struct GitLogQuery {
     #[serde(default)]
     limit: Option<usize>,
     #[serde(default)]
+    skip: Option<usize>,
+    #[serde(default)]
+    before_sha: Option<String>,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    grep: Option<String>,
+    #[serde(default)]
+    since: Option<String>,
+    #[serde(default)]
+    until: Option<String>,
+    #[serde(default)]
+    branch: Option<String>,
+    #[serde(default)]
     token: Option<String>,
 }
 
@@ -80,14 +215,45 @@ pub(in crate::server::ws) async fn git_status_handler(
     State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
     headers: HeaderMap,
     Path(path): Path<WorkspacePath>,
-    Query(query): Query<TokenQuery>,
+    Query(query): Query<GitStatusQuery>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
     let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    let expand_untracked = query
+        .expand_untracked
+        .as_deref()
+        .map(|dirs| {
+            dirs.split(',')
+                .map(str::trim)
+                .filter(|dir| !dir.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
     let response = crate::server::handlers::git::query::query_git_status(
         &ctx.app_state,
         &path.project,
         &path.workspace,
+        query.untracked_files.as_deref(),
+        expand_untracked,
+    )
+    .await
+    .map_err(|e| map_git_error(&qctx, e))?;
+    json_from_server_message(response)
+}
+
+pub(in crate::server::ws) async fn git_status_tree_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<WorkspacePath>,
+    Query(query): Query<TokenQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    let response = crate::server::handlers::git::query::query_git_status_tree(
+        &ctx.app_state,
+        &path.project,
+        &path.workspace,
     )
     .await
     .map_err(|e| map_git_error(&qctx, e))?;
@@ -109,6 +275,82 @@ pub(in crate::server::ws) async fn git_diff_handler(
         &query.path,
         query.base,
         query.mode.as_deref().unwrap_or("working"),
+        query.algorithm,
+        query.range,
+        query.format,
+        query.intraline,
+        query.smart_diff,
+        query.include_blobs,
+    )
+    .await
+    .map_err(|e| map_git_error(&qctx, e))?;
+    json_from_server_message(response)
+}
+
+pub(in crate::server::ws) async fn git_diff_page_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<WorkspacePath>,
+    Query(query): Query<GitDiffPageQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    let response = crate::server::handlers::git::query::query_git_diff_page(
+        &ctx.app_state,
+        &path.project,
+        &path.workspace,
+        &query.path,
+        query.base,
+        query.mode.as_deref().unwrap_or("working"),
+        query.algorithm,
+        query.range,
+        query.smart_diff,
+        query.offset,
+    )
+    .await
+    .map_err(|e| map_git_error(&qctx, e))?;
+    json_from_server_message(response)
+}
+
+pub(in crate::server::ws) async fn git_diff_range_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<WorkspacePath>,
+    Query(query): Query<GitDiffRangeQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    if query.range.trim().is_empty() {
+        return Err(ApiError::BadRequest("range is required".to_string()));
+    }
+    let response = crate::server::handlers::git::query::query_git_diff_range(
+        &ctx.app_state,
+        &path.project,
+        &path.workspace,
+        &query.range,
+        query.path,
+    )
+    .await
+    .map_err(|e| map_git_error(&qctx, e))?;
+    json_from_server_message(response)
+}
+
+pub(in crate::server::ws) async fn git_rebase_plan_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<WorkspacePath>,
+    Query(query): Query<GitRebasePlanQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    if query.base.trim().is_empty() {
+        return Err(ApiError::BadRequest("base is required".to_string()));
+    }
+    let response = crate::server::handlers::git::query::query_git_rebase_plan(
+        &ctx.app_state,
+        &path.project,
+        &path.workspace,
+        &query.base,
     )
     .await
     .map_err(|e| map_git_error(&qctx, e))?;
@@ -146,6 +388,16 @@ pub(in crate::server::ws) async fn git_log_handler(
         &path.project,
         &path.workspace,
         query.limit.unwrap_or(50),
+        crate::server::git::GitLogFilter {
+            skip: query.skip.unwrap_or(0),
+            before_sha: query.before_sha,
+            author: query.author,
+            path: query.path,
+            grep: query.grep,
+            since: query.since,
+            until: query.until,
+            branch: query.branch,
+        },
     )
     .await
     .map_err(|e| map_git_error(&qctx, e))?;
@@ -171,6 +423,73 @@ pub(in crate::server::ws) async fn git_commit_show_handler(
     json_from_server_message(response)
 }
 
+pub(in crate::server::ws) async fn git_commit_file_diff_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<CommitPath>,
+    Query(query): Query<GitShowFileDiffQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    if query.path.trim().is_empty() {
+        return Err(ApiError::BadRequest("path is required".to_string()));
+    }
+    let response = crate::server::handlers::git::query::query_git_show_file_diff(
+        &ctx.app_state,
+        &path.project,
+        &path.workspace,
+        &path.sha,
+        &query.path,
+        query.algorithm,
+        query.format,
+        query.intraline,
+    )
+    .await
+    .map_err(|e| map_git_error(&qctx, e))?;
+    json_from_server_message(response)
+}
+
+pub(in crate::server::ws) async fn git_suggested_commit_message_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<WorkspacePath>,
+    Query(query): Query<GitSuggestedCommitMessageQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    let link_url = query.link_url.filter(|url| !url.trim().is_empty());
+    let response = crate::server::handlers::git::query::query_git_suggested_commit_message(
+        &ctx.app_state,
+        &path.project,
+        &path.workspace,
+        link_url,
+        query.generate,
+    )
+    .await
+    .map_err(|e| map_git_error(&qctx, e))?;
+    json_from_server_message(response)
+}
+
+pub(in crate::server::ws) async fn git_blame_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<WorkspacePath>,
+    Query(query): Query<GitBlameQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    let response = crate::server::handlers::git::query::query_git_blame(
+        &ctx.app_state,
+        &path.project,
+        &path.workspace,
+        &query.path,
+        query.rev,
+    )
+    .await
+    .map_err(|e| map_git_error(&qctx, e))?;
+    json_from_server_message(response)
+}
+
 pub(in crate::server::ws) async fn git_op_status_handler(
     State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
     headers: HeaderMap,
@@ -193,18 +512,96 @@ pub(in crate::server::ws) async fn git_integration_status_handler(
     State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
     headers: HeaderMap,
     Path(path): Path<ProjectPath>,
-    Query(query): Query<TokenQuery>,
+    Query(query): Query<GitIntegrationStatusQuery>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
     let response = crate::server::handlers::git::query::query_git_integration_status(
         &ctx.app_state,
         &path.project,
+        query.branch.as_deref(),
     )
     .await
     .map_err(ApiError::BadRequest)?;
     json_from_server_message(response)
 }
 
+pub(in crate::server::ws) async fn git_status_all_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<ProjectPath>,
+    Query(query): Query<TokenQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let response =
+        crate::server::handlers::git::query::query_git_status_all(&ctx.app_state, &path.project)
+            .await
+            .map_err(map_query_error)?;
+    json_from_server_message(response)
+}
+
+pub(in crate::server::ws) async fn git_repo_stats_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<ProjectPath>,
+    Query(query): Query<TokenQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let response =
+        crate::server::handlers::git::query::query_git_repo_stats(&ctx.app_state, &path.project)
+            .await
+            .map_err(map_query_error)?;
+    json_from_server_message(response)
+}
+
+pub(in crate::server::ws) async fn git_pull_requests_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<ProjectPath>,
+    Query(query): Query<TokenQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let response =
+        crate::server::handlers::git::query::query_git_pull_requests(&ctx.app_state, &path.project)
+            .await
+            .map_err(map_query_error)?;
+    json_from_server_message(response)
+}
+
+pub(in crate::server::ws) async fn git_pull_request_status_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<PullRequestPath>,
+    Query(query): Query<TokenQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let response = crate::server::handlers::git::query::query_git_pull_request_status(
+        &ctx.app_state,
+        &path.project,
+        path.number,
+    )
+    .await
+    .map_err(map_query_error)?;
+    json_from_server_message(response)
+}
+
+pub(in crate::server::ws) async fn git_large_blobs_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<ProjectPath>,
+    Query(query): Query<GitLargeBlobsQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let response = crate::server::handlers::git::query::query_git_detect_large_blobs(
+        &ctx.app_state,
+        &path.project,
+        query.threshold,
+        query.filter_repo_plan,
+    )
+    .await
+    .map_err(map_query_error)?;
+    json_from_server_message(response)
+}
+
 pub(in crate::server::ws) async fn git_check_branch_up_to_date_handler(
     State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
     headers: HeaderMap,
@@ -279,3 +676,42 @@ pub(in crate::server::ws) async fn git_stash_show_handler(
     .map_err(|e| map_git_error(&qctx, e))?;
     json_from_server_message(response)
 }
+
+/// 下载 `git_generate_change_report` 生成的报告（可经 `?token=` 鉴权，便于直接在浏览器打开）
+pub(in crate::server::ws) async fn git_change_report_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<ChangeReportPath>,
+    Query(query): Query<TokenQuery>,
+) -> Result<Response, ApiError> {
+    let _identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let dir = crate::server::git::change_reports_dir();
+    let file = crate::server::git::change_report_path(&dir, &path.report_id).ok_or_else(|| {
+        ApiError::NotFound(format!("Change report not found: {}", path.report_id))
+    })?;
+    let content = tokio::fs::read(&file)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    let mut response = content.into_response();
+    let response_headers = response.headers_mut();
+    let mut insert = |name: header::HeaderName, value: String| {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            response_headers.insert(name, value);
+        }
+    };
+    insert(header::CONTENT_TYPE, "text/html; charset=utf-8".to_string());
+    insert(
+        header::CONTENT_DISPOSITION,
+        format!(
+            "attachment; filename=\"tidyflow-change-report-{}.html\"",
+            path.report_id
+        ),
+    );
+    insert(header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string());
+    insert(
+        header::CONTENT_SECURITY_POLICY,
+        "default-src 'none'; style-src 'unsafe-inline'".to_string(),
+    );
+    Ok(response)
+}
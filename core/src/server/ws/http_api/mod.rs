@@ -15,26 +15,40 @@ pub(in crate::server::ws) use ai::{
     ai_session_messages_handler, ai_session_slash_commands_handler, ai_session_status_handler,
     ai_sessions_handler,
 };
+pub(in crate::server::ws) use auth::{parse_bearer_token, parse_optional_header};
+pub(in crate::server::ws) use common::{
+    build_http_handler_context, cancel_request_middleware, hydrate_project_middleware,
+    trace_request_middleware,
+};
 pub(in crate::server::ws) use evolution::{
     evolution_agent_profile_handler, evolution_cycle_history_handler, evolution_snapshot_handler,
 };
 pub(in crate::server::ws) use file::{
-    file_content_handler, file_index_handler, file_list_handler, file_search_handler,
+    file_asset_handler, file_content_handler, file_definition_handler, file_editorconfig_handler,
+    file_highlight_handler, file_index_handler, file_list_handler, file_query_handler,
+    file_revision_handler, file_search_handler,
 };
 pub(in crate::server::ws) use git::{
-    git_branches_handler, git_check_branch_up_to_date_handler, git_commit_show_handler,
-    git_conflict_detail_handler, git_diff_handler, git_integration_status_handler, git_log_handler,
-    git_op_status_handler, git_stash_list_handler, git_stash_show_handler, git_status_handler,
+    git_blame_handler, git_branches_handler, git_change_report_handler,
+    git_check_branch_up_to_date_handler, git_commit_file_diff_handler, git_commit_show_handler,
+    git_conflict_detail_handler, git_diff_handler, git_diff_page_handler, git_diff_range_handler,
+    git_integration_status_handler, git_large_blobs_handler, git_log_handler,
+    git_op_status_handler, git_pull_request_status_handler, git_pull_requests_handler,
+    git_rebase_plan_handler, git_repo_stats_handler, git_stash_list_handler,
+    git_stash_show_handler, git_status_all_handler, git_status_handler, git_status_tree_handler,
+    git_suggested_commit_message_handler,
 };
 pub(in crate::server::ws) use node::{
     node_discovery_handler, node_network_handler, node_pair_register_handler,
     node_pair_unregister_handler, node_self_handler,
 };
 pub(in crate::server::ws) use project::{
-    client_settings_handler, projects_handler, tasks_handler, template_export_handler,
-    templates_handler, workspaces_handler,
+    client_settings_handler, ports_handler, proc_logs_handler, procs_handler, projects_handler,
+    tasks_handler, template_export_handler, templates_handler, workspace_tasks_handler,
+    workspaces_handler,
 };
 pub(in crate::server::ws) use system::{
-    system_health_snapshot_handler, system_repair_handler, system_snapshot_handler,
+    healthz_handler, metrics_handler, readyz_handler, system_health_snapshot_handler,
+    system_repair_handler, system_snapshot_handler, system_state_backups_handler,
 };
 pub(in crate::server::ws) use terminal::terminals_handler;
@@ -227,7 +227,7 @@ mod tests {
             save_tx,
             terminal_registry: Arc::new(tokio::sync::Mutex::new(TerminalRegistry::new())),
             scrollback_tx,
-            expected_ws_token: Some("required-token".to_string()),
+            expected_ws_token: "required-token".to_string(),
             api_key_registry: Arc::new(tokio::sync::Mutex::new(
                 crate::server::ws::auth_keys::new_api_key_registry(&[]),
             )),
@@ -6,7 +6,9 @@ use axum::{
 use serde::Deserialize;
 
 use super::auth::ensure_http_authorized;
-use super::common::{build_http_handler_context, json_from_server_message, ApiError};
+use super::common::{
+    build_http_handler_context, json_from_server_message, ApiError, WorkspaceQueryContext,
+};
 
 #[derive(Debug, Deserialize, Default)]
 pub(in crate::server::ws) struct TokenQuery {
@@ -14,11 +16,54 @@ pub(in crate::server::ws) struct TokenQuery {
     token: Option<String>,
 }
 
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct ClientSettingsQuery {
+    #[serde(default)]
+    device_id: Option<String>,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct WorkspacesQuery {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    branch_prefix: Option<String>,
+    #[serde(default)]
+    sort: Option<String>,
+    #[serde(default)]
+    token: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub(in crate::server::ws) struct ProjectPath {
     project: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub(in crate::server::ws) struct ProjectWorkspacePath {
+    project: String,
+    workspace: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub(in crate::server::ws) struct ProcLogsQuery {
+    #[serde(default)]
+    lines: Option<usize>,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(in crate::server::ws) struct ProcPath {
+    project: String,
+    workspace: String,
+    name: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub(in crate::server::ws) struct TemplatePath {
     template_id: String,
@@ -39,13 +84,21 @@ pub(in crate::server::ws) async fn workspaces_handler(
     State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
     headers: HeaderMap,
     Path(path): Path<ProjectPath>,
-    Query(query): Query<TokenQuery>,
+    Query(query): Query<WorkspacesQuery>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     let identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let filter = crate::application::project::WorkspaceListFilter::from_params(
+        query.name.as_deref(),
+        query.status.as_deref(),
+        query.branch_prefix.as_deref(),
+        query.sort.as_deref(),
+    )
+    .map_err(ApiError::BadRequest)?;
     let handler_ctx = build_http_handler_context(&ctx, Some(&identity));
     match crate::server::handlers::project::query::query_list_workspaces(
         &handler_ctx,
         &path.project,
+        &filter,
     )
     .await
     {
@@ -64,15 +117,116 @@ pub(in crate::server::ws) async fn tasks_handler(
     json_from_server_message(response)
 }
 
-pub(in crate::server::ws) async fn client_settings_handler(
+pub(in crate::server::ws) async fn workspace_tasks_handler(
     State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
     headers: HeaderMap,
+    Path(path): Path<ProjectWorkspacePath>,
     Query(query): Query<TokenQuery>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     let identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
     let handler_ctx = build_http_handler_context(&ctx, Some(&identity));
-    let response =
-        crate::server::handlers::settings::query::query_client_settings(&handler_ctx).await;
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    let response = crate::server::handlers::project::query::query_list_workspace_tasks(
+        &handler_ctx,
+        &path.project,
+        &path.workspace,
+    )
+    .await
+    .map_err(|e| {
+        qctx.map_query_error(match e {
+            crate::server::protocol::ServerMessage::Error { message, .. } => message,
+            _ => "list workspace tasks failed".to_string(),
+        })
+    })?;
+    json_from_server_message(response)
+}
+
+pub(in crate::server::ws) async fn ports_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<ProjectWorkspacePath>,
+    Query(query): Query<TokenQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let handler_ctx = build_http_handler_context(&ctx, Some(&identity));
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    let response = crate::server::handlers::project::query::query_list_ports(
+        &handler_ctx,
+        &path.project,
+        &path.workspace,
+    )
+    .await
+    .map_err(|e| {
+        qctx.map_query_error(match e {
+            crate::server::protocol::ServerMessage::Error { message, .. } => message,
+            _ => "list ports failed".to_string(),
+        })
+    })?;
+    json_from_server_message(response)
+}
+
+pub(in crate::server::ws) async fn procs_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<ProjectWorkspacePath>,
+    Query(query): Query<TokenQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let handler_ctx = build_http_handler_context(&ctx, Some(&identity));
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    let response = crate::server::handlers::project::query::query_proc_list(
+        &handler_ctx,
+        &path.project,
+        &path.workspace,
+    )
+    .await
+    .map_err(|e| {
+        qctx.map_query_error(match e {
+            crate::server::protocol::ServerMessage::Error { message, .. } => message,
+            _ => "list procs failed".to_string(),
+        })
+    })?;
+    json_from_server_message(response)
+}
+
+pub(in crate::server::ws) async fn proc_logs_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Path(path): Path<ProcPath>,
+    Query(query): Query<ProcLogsQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let handler_ctx = build_http_handler_context(&ctx, Some(&identity));
+    let qctx = WorkspaceQueryContext::new(&path.project, &path.workspace);
+    let response = crate::server::handlers::project::query::query_proc_logs(
+        &handler_ctx,
+        &path.project,
+        &path.workspace,
+        &path.name,
+        query.lines,
+    )
+    .await
+    .map_err(|e| {
+        qctx.map_query_error(match e {
+            crate::server::protocol::ServerMessage::Error { message, .. } => message,
+            _ => "read proc logs failed".to_string(),
+        })
+    })?;
+    json_from_server_message(response)
+}
+
+pub(in crate::server::ws) async fn client_settings_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Query(query): Query<ClientSettingsQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let identity = ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let handler_ctx = build_http_handler_context(&ctx, Some(&identity));
+    let response = crate::server::handlers::settings::query::query_client_settings(
+        &handler_ctx,
+        query.device_id.as_deref(),
+    )
+    .await;
     json_from_server_message(response)
 }
 
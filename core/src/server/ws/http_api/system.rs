@@ -1,8 +1,14 @@
 use std::collections::HashMap;
 
-use axum::{extract::State, Json};
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap},
+    response::IntoResponse,
+    Json,
+};
 use serde::{Deserialize, Serialize};
 
+use super::auth::ensure_http_authorized;
 use super::common::{build_http_handler_context, map_query_error, ApiError};
 use crate::server::context::SharedAppState;
 use crate::server::perf::{PerfMetricsSnapshot, TerminalPerfSnapshot};
@@ -252,11 +258,84 @@ pub(in crate::server::ws) async fn system_repair_handler(
         body.request,
         "client_request",
         ctx.app_state.clone(),
+        &ctx.state_store,
+        &ctx.save_tx,
     )
     .await;
     Ok(Json(RepairResponseBody { audit }))
 }
 
+#[derive(Debug, Deserialize)]
+pub(in crate::server::ws) struct SystemTokenQuery {
+    token: Option<String>,
+}
+
+/// 状态快照列表响应体
+#[derive(Debug, Serialize)]
+pub(in crate::server::ws) struct StateBackupsResponse {
+    pub backups: Vec<crate::workspace::state_backups::StateBackupInfo>,
+}
+
+/// 列出可用于 `restore_state_backup` 的状态快照，最新的在前
+pub(in crate::server::ws) async fn system_state_backups_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Query(query): Query<SystemTokenQuery>,
+) -> Result<Json<StateBackupsResponse>, ApiError> {
+    ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let dir = crate::workspace::state_backups::default_backups_dir();
+    let backups = crate::workspace::state_backups::list_backups(&dir)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Json(StateBackupsResponse { backups }))
+}
+
+/// 存活与就绪探针响应体
+#[derive(Debug, Serialize)]
+pub(in crate::server::ws) struct ProbeResponse {
+    status: &'static str,
+}
+
+/// 存活探针：进程能处理 HTTP 请求即返回 200（免鉴权，供 launchd / systemd 等使用）
+pub(in crate::server::ws) async fn healthz_handler() -> Json<ProbeResponse> {
+    Json(ProbeResponse { status: "ok" })
+}
+
+/// 就绪探针：进入关闭流程后返回 503，负载均衡与监督进程据此停止转发（免鉴权）
+pub(in crate::server::ws) async fn readyz_handler() -> (axum::http::StatusCode, Json<ProbeResponse>)
+{
+    if crate::server::ws::transport::lifecycle::is_shutting_down() {
+        (
+            axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            Json(ProbeResponse {
+                status: "shutting_down",
+            }),
+        )
+    } else {
+        (
+            axum::http::StatusCode::OK,
+            Json(ProbeResponse { status: "ready" }),
+        )
+    }
+}
+
+/// Prometheus 文本格式指标；token 可经 `Authorization: Bearer` 或 `?token=` 提供
+pub(in crate::server::ws) async fn metrics_handler(
+    State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
+    headers: HeaderMap,
+    Query(query): Query<SystemTokenQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    ensure_http_authorized(&ctx, &headers, query.token.as_deref()).await?;
+    let terminals = ctx.terminal_registry.lock().await.resource_info();
+    Ok((
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        crate::server::metrics::render(&terminals),
+    ))
+}
+
 /// 从 Evolution 快照消息提取调度器信息和工作区索引（避免重复查询）
 fn evolution_index_and_scheduler_from_message(
     msg: ServerMessage,
@@ -346,6 +425,11 @@ async fn build_workspace_items_and_metrics(
                 branch: project.default_branch.clone(),
                 status: "ready".to_string(),
                 sidebar_status: Default::default(),
+                last_activity_at: None,
+                port: None,
+                linked_issue: None,
+                suggested_issue: None,
+                sparse_paths: Vec::new(),
             };
             let coordinator_ai_default =
                 build_coordinator_ai_dto(&session_statuses, &project_name, DEFAULT_WORKSPACE_NAME);
@@ -383,6 +467,14 @@ async fn build_workspace_items_and_metrics(
                     branch: ws.branch.clone(),
                     status: crate::application::project::workspace_status_str(&ws.status),
                     sidebar_status: Default::default(),
+                    last_activity_at: None,
+                    port: ws.port,
+                    linked_issue: ws.linked_issue.clone(),
+                    suggested_issue: crate::workspace::branch_name::suggested_issue_reference(
+                        &ws.branch,
+                        ws.linked_issue.as_deref(),
+                    ),
+                    sparse_paths: ws.sparse_paths.clone(),
                 };
                 let recovery_state = ws.recovery_meta.as_ref().and_then(|m| {
                     if m.needs_attention() {
@@ -683,6 +775,9 @@ mod tests {
                     last_accessed: Utc::now(),
                     setup_result: None,
                     recovery_meta: None,
+                    port: None,
+                    linked_issue: None,
+                    sparse_paths: Vec::new(),
                 },
             )]),
             commands: Vec::new(),
@@ -703,7 +798,7 @@ mod tests {
             save_tx,
             terminal_registry: Arc::new(tokio::sync::Mutex::new(TerminalRegistry::new())),
             scrollback_tx,
-            expected_ws_token: Some("required-token".to_string()),
+            expected_ws_token: "required-token".to_string(),
             api_key_registry: Arc::new(tokio::sync::Mutex::new(
                 crate::server::ws::auth_keys::new_api_key_registry(&[]),
             )),
@@ -943,6 +1038,9 @@ mod tests {
                 failed_context: None,
                 interrupted_at: Some(now),
             }),
+            port: None,
+            linked_issue: None,
+            sparse_paths: Vec::new(),
         };
         state.add_project(Project {
             name: "project-a".to_string(),
@@ -964,6 +1062,9 @@ mod tests {
             last_accessed: now,
             setup_result: None,
             recovery_meta: None,
+            port: None,
+            linked_issue: None,
+            sparse_paths: Vec::new(),
         };
         state.add_project(Project {
             name: "project-b".to_string(),
@@ -1175,4 +1276,37 @@ mod tests {
             "空 diagnoses 应被 skip_serializing_if 省略"
         );
     }
+
+    #[tokio::test]
+    async fn metrics_handler_should_require_token_and_render_prometheus_text() {
+        let ctx = make_test_context(make_test_state()).await;
+        let unauthorized = metrics_handler(
+            State(ctx.clone()),
+            HeaderMap::new(),
+            Query(SystemTokenQuery { token: None }),
+        )
+        .await;
+        assert!(matches!(unauthorized, Err(ApiError::Unauthorized)));
+
+        let response = metrics_handler(
+            State(ctx),
+            HeaderMap::new(),
+            Query(SystemTokenQuery {
+                token: Some("required-token".to_string()),
+            }),
+        )
+        .await
+        .unwrap_or_else(|_| panic!("authorized metrics request should succeed"))
+        .into_response();
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/plain; version=0.0.4; charset=utf-8"
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("read body");
+        let text = String::from_utf8(body.to_vec()).expect("utf8 body");
+        assert!(text.contains("tidyflow_terminals 0\n"));
+        assert!(text.contains("# TYPE tidyflow_git_operation_duration_seconds histogram"));
+    }
 }
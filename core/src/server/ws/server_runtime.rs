@@ -1,13 +1,42 @@
 use std::io::Write;
 use std::net::SocketAddr;
 
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::server::protocol::PROTOCOL_VERSION;
+use crate::server::ws::transport::tls::{load_rustls_config, TlsConfig};
 
-pub(in crate::server::ws) async fn run_server(port: u16) -> Result<(), Box<dyn std::error::Error>> {
+/// 优雅关闭时等待存量连接的上限（TLS 模式）
+const TLS_GRACEFUL_SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// `host:port`，IPv6 地址加方括号
+fn listen_addr(bind_addr: &str, port: u16) -> String {
+    if bind_addr.contains(':') && !bind_addr.starts_with('[') {
+        format!("[{}]:{}", bind_addr, port)
+    } else {
+        format!("{}:{}", bind_addr, port)
+    }
+}
+
+fn is_loopback_bind_addr(bind_addr: &str) -> bool {
+    matches!(
+        bind_addr.trim(),
+        "" | "127.0.0.1" | "::1" | "[::1]" | "localhost"
+    )
+}
+
+pub(in crate::server::ws) async fn run_server(
+    port: u16,
+    tls: Option<TlsConfig>,
+) -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting WebSocket server on port {}", port);
 
+    // 证书问题在绑定端口、输出 bootstrap 之前暴露
+    let rustls_config = match &tls {
+        Some(tls) => Some(load_rustls_config(tls)?),
+        None => None,
+    };
+
     let shutdown_tx = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
     crate::server::ws::transport::lifecycle::spawn_parent_monitor(shutdown_tx.clone());
 
@@ -21,7 +50,7 @@ pub(in crate::server::ws) async fn run_server(port: u16) -> Result<(), Box<dyn s
     };
     let ai_state = ctx.ai_state.clone();
 
-    let addr = format!("{}:{}", bind_addr, port);
+    let addr = listen_addr(&bind_addr, port);
     let listener = match tokio::net::TcpListener::bind(&addr).await {
         Ok(listener) => listener,
         Err(e) => {
@@ -41,6 +70,7 @@ pub(in crate::server::ws) async fn run_server(port: u16) -> Result<(), Box<dyn s
         "bind_addr": bind_addr,
         "fixed_port": fixed_port,
         "remote_access_enabled": remote_access_enabled,
+        "tls": rustls_config.is_some(),
         "protocol_version": PROTOCOL_VERSION,
         "core_version": env!("CARGO_PKG_VERSION"),
     });
@@ -49,29 +79,89 @@ pub(in crate::server::ws) async fn run_server(port: u16) -> Result<(), Box<dyn s
         let _ = std::io::stdout().flush();
     }
 
+    // 监听就绪后再预热，避免拖慢启动
+    let mut warmup_ctx = crate::server::ws::http_api::build_http_handler_context(&ctx, None);
+    warmup_ctx.conn_meta.conn_id = "startup-warmup".to_string();
+    crate::application::warmup::spawn_startup_warmers(warmup_ctx);
+
+    let shutdown_broadcast_tx = ctx.task_broadcast_tx.clone();
+    let app_state = ctx.app_state.clone();
+    let state_store = ctx.state_store.clone();
+    let terminal_registry = ctx.terminal_registry.clone();
     let app = crate::server::ws::transport::bootstrap::build_router(ctx);
 
     info!(
-        "Listening on ws://{}/ws (protocol v{})",
-        local_addr, PROTOCOL_VERSION
+        "Listening on {}://{}/ws (protocol v{})",
+        if rustls_config.is_some() { "wss" } else { "ws" },
+        local_addr,
+        PROTOCOL_VERSION
     );
+    if rustls_config.is_none() && !is_loopback_bind_addr(&bind_addr) {
+        warn!(
+            bind_addr = %bind_addr,
+            "Listening on a non-loopback address without TLS; the auth token is sent in cleartext (use --tls-cert/--tls-key)"
+        );
+    }
 
     crate::server::ws::transport::lifecycle::spawn_shutdown_signal_listener(shutdown_tx.clone());
 
-    let serve_result = axum::serve(
-        listener,
-        app.into_make_service_with_connect_info::<SocketAddr>(),
-    )
-    .with_graceful_shutdown(async move {
+    let wait_for_shutdown = async move {
         while !shutdown_tx.load(std::sync::atomic::Ordering::SeqCst) {
             tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         }
         info!("Graceful shutdown initiated");
-    })
-    .await;
+        crate::server::ws::transport::lifecycle::begin_shutdown(&shutdown_broadcast_tx).await;
+    };
+    let make_service = app.into_make_service_with_connect_info::<SocketAddr>();
+
+    let serve_result = match rustls_config {
+        None => {
+            axum::serve(listener, make_service)
+                .with_graceful_shutdown(wait_for_shutdown)
+                .await
+        }
+        Some(rustls_config) => {
+            let handle = axum_server::Handle::new();
+            let shutdown_handle = handle.clone();
+            tokio::spawn(async move {
+                wait_for_shutdown.await;
+                shutdown_handle.graceful_shutdown(Some(TLS_GRACEFUL_SHUTDOWN_TIMEOUT));
+            });
+            match listener.into_std() {
+                Ok(std_listener) => {
+                    axum_server::from_tcp_rustls(std_listener, rustls_config)
+                        .handle(handle)
+                        .serve(make_service)
+                        .await
+                }
+                Err(e) => Err(e),
+            }
+        }
+    };
 
+    crate::server::ws::transport::lifecycle::release_resources(
+        &app_state,
+        &state_store,
+        &terminal_registry,
+    )
+    .await;
     crate::server::handlers::ai::shutdown_agents(&ai_state).await;
+    crate::server::proc_supervisor::shutdown_all_procs().await;
     serve_result?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listen_addr_brackets_ipv6() {
+        assert_eq!(listen_addr("0.0.0.0", 8439), "0.0.0.0:8439");
+        assert_eq!(listen_addr("::", 8439), "[::]:8439");
+        assert_eq!(listen_addr("[::1]", 8439), "[::1]:8439");
+        assert!(is_loopback_bind_addr("::1"));
+        assert!(!is_loopback_bind_addr("0.0.0.0"));
+    }
+}
@@ -24,3 +24,19 @@ pub async fn ack_terminal_output(
         }
     }
 }
+
+/// 客户端暂停 / 恢复终端实时输出（`term_flow_control`）
+///
+/// 返回自上次上报以来丢弃的字节数；当前连接未订阅该终端时返回 `None`。
+pub async fn set_terminal_output_paused(
+    term_id: &str,
+    paused: bool,
+    subscribed_terms: &Arc<tokio::sync::Mutex<HashMap<String, TermSubscription>>>,
+) -> Option<u64> {
+    let subs = subscribed_terms.lock().await;
+    let (_handle, fc, _flow_gate) = subs.get(term_id)?;
+    fc.client_paused.store(paused, Ordering::Relaxed);
+    // 唤醒可能正在等待 ACK 的转发任务
+    fc.notify.notify_one();
+    Some(fc.dropped.swap(0, Ordering::Relaxed))
+}
@@ -1,5 +1,6 @@
 mod ack;
+mod pacing;
 mod subscription;
 
-pub use ack::ack_terminal_output;
+pub use ack::{ack_terminal_output, set_terminal_output_paused};
 pub use subscription::{subscribe_terminal, unsubscribe_terminal};
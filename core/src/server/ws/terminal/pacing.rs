@@ -0,0 +1,156 @@
+//! 终端输出节流：按终端限速并合并输出块
+//!
+//! 高吞吐命令（如 `yarn build`）每秒产生数 MB 输出，逐块转发会占满共享的聚合通道与
+//! WebSocket，拖慢其他终端。转发任务先把输出合并进有上限的待发队列，按令牌桶限速成块发送；
+//! 队列超限时丢弃最旧的数据——这部分输出已写入 scrollback，客户端可用 `term_attach` 重新同步。
+
+use std::time::{Duration, Instant};
+
+use crate::server::terminal_registry::find_incomplete_escape_sequence;
+
+/// 单终端持续转发速率（字节/秒）
+pub const OUTPUT_RATE_BYTES_PER_SEC: f64 = 1024.0 * 1024.0;
+/// 令牌桶容量：空闲后允许的突发字节数
+pub const OUTPUT_BURST_BYTES: usize = 256 * 1024;
+/// 待发队列上限，超出部分从最旧处丢弃
+pub const MAX_PENDING_BYTES: usize = 512 * 1024;
+/// 合并发送间隔
+pub const FLUSH_INTERVAL: Duration = Duration::from_millis(16);
+
+/// 单个订阅的输出合并队列与令牌桶
+pub struct OutputPacer {
+    pending: Vec<u8>,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl OutputPacer {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            pending: Vec::new(),
+            tokens: OUTPUT_BURST_BYTES as f64,
+            last_refill: now,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// 追加输出；超出队列上限时丢弃最旧的数据，返回本次丢弃的字节数
+    pub fn push(&mut self, data: &[u8]) -> u64 {
+        self.pending.extend_from_slice(data);
+        if self.pending.len() <= MAX_PENDING_BYTES {
+            return 0;
+        }
+        let mut cut = self.pending.len() - MAX_PENDING_BYTES;
+        // 尽量从换行处截断，避免从转义序列或多字节字符中间开始显示
+        if let Some(pos) = self.pending[cut..].iter().position(|b| *b == b'\n') {
+            cut += pos + 1;
+        }
+        self.pending.drain(..cut);
+        cut as u64
+    }
+
+    /// 丢弃全部待发数据，返回丢弃的字节数
+    pub fn clear(&mut self) -> u64 {
+        let len = self.pending.len() as u64;
+        self.pending.clear();
+        len
+    }
+
+    /// 取出令牌允许发送的数据；切分点避开未完成的转义序列与 UTF-8 字符
+    pub fn take(&mut self, now: Instant) -> Option<Vec<u8>> {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * OUTPUT_RATE_BYTES_PER_SEC).min(OUTPUT_BURST_BYTES as f64);
+        self.last_refill = now;
+
+        let budget = self.tokens as usize;
+        if self.pending.is_empty() || budget == 0 {
+            return None;
+        }
+        let mut n = budget.min(self.pending.len());
+        if n < self.pending.len() {
+            if let Some(start) = find_incomplete_escape_sequence(&self.pending[..n]) {
+                if start > 0 {
+                    n = start;
+                }
+            }
+            while n > 0 && (self.pending[n] & 0xC0) == 0x80 {
+                n -= 1;
+            }
+            if n == 0 {
+                return None;
+            }
+        }
+        self.tokens -= n as f64;
+        Some(self.pending.drain(..n).collect())
+    }
+
+    /// 不受限速约束地取出全部待发数据（终端退出时收尾）
+    pub fn take_all(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coalesces_chunks_and_limits_rate() {
+        let start = Instant::now();
+        let mut pacer = OutputPacer::new(start);
+        pacer.push(b"hello ");
+        pacer.push(b"world");
+        assert_eq!(pacer.take(start).unwrap(), b"hello world");
+        assert!(pacer.take(start).is_none());
+
+        // 突发额度用尽后按速率放行
+        pacer.push(&vec![b'x'; OUTPUT_BURST_BYTES * 2]);
+        assert_eq!(pacer.take(start).unwrap().len(), OUTPUT_BURST_BYTES - 11);
+        assert!(pacer.take(start).is_none());
+        let later = start + Duration::from_millis(10);
+        let sent = pacer.take(later).unwrap().len();
+        let expected = (OUTPUT_RATE_BYTES_PER_SEC * 0.01) as usize;
+        assert!(sent.abs_diff(expected) <= 1, "sent {}", sent);
+    }
+
+    #[test]
+    fn drops_oldest_output_at_line_boundary() {
+        let start = Instant::now();
+        let mut pacer = OutputPacer::new(start);
+        let line = format!("{}\n", "a".repeat(1023));
+        for _ in 0..(MAX_PENDING_BYTES / line.len()) {
+            assert_eq!(pacer.push(line.as_bytes()), 0);
+        }
+        let dropped = pacer.push(b"tail\n");
+        assert_eq!(dropped, line.len() as u64);
+        assert_eq!(pacer.clear(), (MAX_PENDING_BYTES - line.len() + 5) as u64);
+        assert!(pacer.is_empty());
+    }
+
+    #[test]
+    fn does_not_split_escape_sequences_or_utf8() {
+        let start = Instant::now();
+        let mut pacer = OutputPacer::new(start);
+        pacer.tokens = 0.0;
+        let mut data = vec![b'a'; 8];
+        data.extend_from_slice(b"\x1b[31m");
+        data.extend_from_slice("中".as_bytes());
+        pacer.push(&data);
+
+        pacer.tokens = 10.0;
+        assert_eq!(pacer.take(start).unwrap(), b"aaaaaaaa");
+        pacer.tokens = 6.0;
+        assert_eq!(pacer.take(start).unwrap(), b"\x1b[31m");
+        pacer.tokens = 2.0;
+        assert!(pacer.take(start).is_none());
+        pacer.tokens = 3.0;
+        assert_eq!(pacer.take(start).unwrap(), "中".as_bytes());
+        assert!(pacer.take_all().is_empty());
+    }
+}
@@ -2,10 +2,11 @@ use std::collections::HashMap;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
-use tokio::sync::Notify;
 use tracing::warn;
 
+use super::pacing::{OutputPacer, FLUSH_INTERVAL};
 use crate::server::context::{FlowControl, TermSubscription};
+use crate::server::protocol::ServerMessage;
 use crate::server::terminal_registry::{PtyFlowGate, SharedTerminalRegistry};
 
 async fn wait_flow_control_window(
@@ -14,7 +15,10 @@ async fn wait_flow_control_window(
     flow_gate: &PtyFlowGate,
     is_paused: &mut bool,
 ) {
-    while flow_control.unacked.load(Ordering::Relaxed) > super::super::FLOW_CONTROL_HIGH_WATER {
+    // 客户端主动暂停时不再等待 ACK，由调用方丢弃待发输出
+    while flow_control.unacked.load(Ordering::Relaxed) > super::super::FLOW_CONTROL_HIGH_WATER
+        && !flow_control.client_paused.load(Ordering::Relaxed)
+    {
         if !*is_paused {
             *is_paused = true;
             flow_gate.mark_paused();
@@ -44,28 +48,47 @@ async fn wait_flow_control_window(
     }
 }
 
-/// 订阅终端输出：从 registry 的 broadcast 接收数据，转发到聚合通道
-/// 带流控：当 unacked 超过高水位时暂停转发，等待前端 ACK
+/// 记录未转发而丢弃的输出字节数
+fn record_dropped(flow_control: &FlowControl, bytes: u64) {
+    if bytes > 0 {
+        flow_control.dropped.fetch_add(bytes, Ordering::Relaxed);
+        crate::server::perf::record_terminal_output_dropped(bytes);
+    }
+}
+
+/// 等待聚合通道中的输出被连接循环取走（最多约 500ms），使 Exit 排在最后一段输出之后
+async fn wait_agg_drained(agg_tx: &tokio::sync::mpsc::Sender<(String, Vec<u8>)>) {
+    for _ in 0..50 {
+        if agg_tx.capacity() == agg_tx.max_capacity() {
+            return;
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+    }
+}
+
+/// 订阅终端输出：从 registry 的 broadcast 接收数据，合并、限速后转发到聚合通道
+/// 带流控：当 unacked 超过高水位时暂停转发，等待前端 ACK；
+/// 客户端暂停（`term_flow_control`）期间输出只进 scrollback，不占用连接带宽；
+/// 进程退出后转发剩余输出，再经 `cmd_output_tx` 发送 `Exit` 并结束订阅
 pub async fn subscribe_terminal(
     term_id: &str,
     registry: &SharedTerminalRegistry,
     subscribed_terms: &Arc<tokio::sync::Mutex<HashMap<String, TermSubscription>>>,
     agg_tx: &tokio::sync::mpsc::Sender<(String, Vec<u8>)>,
+    cmd_output_tx: &tokio::sync::mpsc::Sender<ServerMessage>,
 ) -> bool {
     let reg = registry.lock().await;
-    let (rx, flow_gate) = match reg.subscribe(term_id) {
-        Some(pair) => pair,
+    let (rx, flow_gate, exit_rx) = match reg.subscribe(term_id) {
+        Some(handles) => handles,
         None => return false,
     };
     drop(reg);
 
     let agg_tx = agg_tx.clone();
+    let cmd_output_tx = cmd_output_tx.clone();
     let tid = term_id.to_string();
 
-    let fc = Arc::new(FlowControl {
-        unacked: std::sync::atomic::AtomicU64::new(0),
-        notify: Notify::new(),
-    });
+    let fc = Arc::new(FlowControl::new());
     let fc_clone = fc.clone();
     let fg_clone = flow_gate.clone();
 
@@ -74,27 +97,86 @@ pub async fn subscribe_terminal(
 
     let handle = tokio::spawn(async move {
         let mut rx = rx;
+        let mut exit_rx = exit_rx;
+        // 未发布退出码（等待子进程失败）时 watch 关闭，之后不再监听
+        let mut exit_open = true;
         let mut is_paused = false;
+        let mut pacer = OutputPacer::new(std::time::Instant::now());
+        let mut flush = tokio::time::interval(FLUSH_INTERVAL);
+        flush.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
         loop {
-            wait_flow_control_window(&tid, &fc_clone, &fg_clone, &mut is_paused).await;
-
-            match rx.recv().await {
-                Ok((id, data)) => {
-                    if id == tid {
-                        let data_len = data.len() as u64;
-                        if agg_tx.send((id, data)).await.is_err() {
+            tokio::select! {
+                received = rx.recv() => match received {
+                    Ok((id, data)) => {
+                        if id != tid {
+                            continue;
+                        }
+                        if fc_clone.client_paused.load(Ordering::Relaxed) {
+                            record_dropped(&fc_clone, data.len() as u64);
+                        } else {
+                            let dropped = pacer.push(&data);
+                            record_dropped(&fc_clone, dropped);
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("Terminal {} output lagged by {} messages", tid, n);
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                        // 终端已退出：不受限速约束地发出剩余输出
+                        let rest = pacer.take_all();
+                        if !rest.is_empty() && !fc_clone.client_paused.load(Ordering::Relaxed) {
+                            let _ = agg_tx.send((tid.clone(), rest)).await;
+                        }
+                        break;
+                    }
+                },
+                exit = async { exit_rx.wait_for(Option::is_some).await.ok().and_then(|c| *c) }, if exit_open => {
+                    let Some(code) = exit else {
+                        exit_open = false;
+                        continue;
+                    };
+                    // 退出码在读取线程读完输出（或等待超时）后发布，这里收齐广播中剩余的输出；
+                    // 之后仍占用 PTY 的后台进程输出只进入 scrollback
+                    while let Ok((id, data)) = rx.try_recv() {
+                        if id == tid {
+                            let dropped = pacer.push(&data);
+                            record_dropped(&fc_clone, dropped);
+                        }
+                    }
+                    let rest = pacer.take_all();
+                    if fc_clone.client_paused.load(Ordering::Relaxed) {
+                        record_dropped(&fc_clone, rest.len() as u64);
+                    } else if !rest.is_empty() {
+                        fc_clone.unacked.fetch_add(rest.len() as u64, Ordering::Relaxed);
+                        if agg_tx.send((tid.clone(), rest)).await.is_err() {
+                            break;
+                        }
+                    }
+                    wait_agg_drained(&agg_tx).await;
+                    let _ = cmd_output_tx
+                        .send(ServerMessage::Exit {
+                            code,
+                            term_id: Some(tid.clone()),
+                        })
+                        .await;
+                    break;
+                }
+                // 空闲后的首个输出立即发出，之后按间隔合并
+                _ = flush.tick(), if !pacer.is_empty() => {
+                    wait_flow_control_window(&tid, &fc_clone, &fg_clone, &mut is_paused).await;
+                    if fc_clone.client_paused.load(Ordering::Relaxed) {
+                        record_dropped(&fc_clone, pacer.clear());
+                        continue;
+                    }
+                    if let Some(chunk) = pacer.take(std::time::Instant::now()) {
+                        let data_len = chunk.len() as u64;
+                        if agg_tx.send((tid.clone(), chunk)).await.is_err() {
                             break;
                         }
                         // 记录未确认字节数
                         fc_clone.unacked.fetch_add(data_len, Ordering::Relaxed);
                     }
                 }
-                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
-                    warn!("Terminal {} output lagged by {} messages", tid, n);
-                }
-                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
-                    break;
-                }
             }
         }
 
@@ -148,10 +230,7 @@ mod tests {
 
         // 构造一个 no-op task 作为 handle
         let handle = tokio::spawn(async {});
-        let fc = Arc::new(crate::server::context::FlowControl {
-            unacked: std::sync::atomic::AtomicU64::new(0),
-            notify: tokio::sync::Notify::new(),
-        });
+        let fc = Arc::new(crate::server::context::FlowControl::new());
         {
             let mut subs = subscribed_terms.lock().await;
             subs.insert("term-1".to_string(), (handle, fc, gate.clone()));
@@ -166,6 +245,42 @@ mod tests {
         assert!(!subs.contains_key("term-1"));
     }
 
+    #[tokio::test]
+    async fn flow_control_pause_reports_and_resets_dropped_bytes() {
+        use std::sync::atomic::Ordering;
+
+        use crate::server::ws::terminal::set_terminal_output_paused;
+
+        let subscribed_terms: Arc<tokio::sync::Mutex<HashMap<String, _>>> =
+            Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+        let fc = Arc::new(crate::server::context::FlowControl::new());
+        subscribed_terms.lock().await.insert(
+            "term-1".to_string(),
+            (
+                tokio::spawn(async {}),
+                fc.clone(),
+                Arc::new(PtyFlowGate::new()),
+            ),
+        );
+
+        assert_eq!(
+            set_terminal_output_paused("term-1", true, &subscribed_terms).await,
+            Some(0)
+        );
+        assert!(fc.client_paused.load(Ordering::Relaxed));
+        fc.dropped.fetch_add(4096, Ordering::Relaxed);
+        assert_eq!(
+            set_terminal_output_paused("term-1", false, &subscribed_terms).await,
+            Some(4096)
+        );
+        assert!(!fc.client_paused.load(Ordering::Relaxed));
+        assert_eq!(fc.dropped.load(Ordering::Relaxed), 0);
+        assert_eq!(
+            set_terminal_output_paused("missing", true, &subscribed_terms).await,
+            None
+        );
+    }
+
     // CHK-003: 对不存在的 term_id 调用 unsubscribe_terminal 不会 panic
     #[tokio::test]
     async fn cleanup_nonexistent_terminal_is_noop() {
@@ -13,9 +13,12 @@ use crate::server::remote_connection_registry::{
 };
 use crate::server::remote_sub_registry::{RemoteSubRegistry, SharedRemoteSubRegistry};
 use crate::server::terminal_registry::{
-    spawn_idle_reaper, spawn_scrollback_writer, SharedTerminalRegistry, TerminalRegistry,
+    spawn_idle_reaper, spawn_scrollback_writer, spawn_terminal_state_tracker,
+    SharedTerminalRegistry, TerminalRegistry,
 };
 use crate::workspace::state::AppState;
+use crate::workspace::state_backend::StateBackendKind;
+use crate::workspace::state_hydrator::spawn_project_hydrator;
 use crate::workspace::state_saver::spawn_state_saver;
 use crate::workspace::state_store::StateStore;
 
@@ -26,7 +29,8 @@ pub(in crate::server::ws) struct AppContext {
     pub(in crate::server::ws) save_tx: tokio::sync::mpsc::Sender<()>,
     pub(in crate::server::ws) terminal_registry: SharedTerminalRegistry,
     pub(in crate::server::ws) scrollback_tx: tokio::sync::mpsc::Sender<(String, Vec<u8>)>,
-    pub(in crate::server::ws) expected_ws_token: Option<String>,
+    /// 启动 token：`/ws` 与 `/api/v1/*` 均需携带（或改用远程 API key）
+    pub(in crate::server::ws) expected_ws_token: String,
     pub(in crate::server::ws) api_key_registry:
         crate::server::ws::auth_keys::SharedRemoteAPIKeyRegistry,
     pub(in crate::server::ws) remote_sub_registry: SharedRemoteSubRegistry,
@@ -40,10 +44,66 @@ pub(in crate::server::ws) struct AppContext {
     pub(in crate::server::ws) state_store: Arc<StateStore>,
 }
 
-fn resolve_expected_ws_token() -> Option<String> {
-    std::env::var("TIDYFLOW_WS_TOKEN")
-        .ok()
-        .filter(|token| !token.trim().is_empty())
+/// 生成的启动 token 写入的文件名（位于 TidyFlow 全局数据目录）
+const AUTH_TOKEN_FILE_NAME: &str = "auth-token";
+
+fn token_from_env() -> Option<String> {
+    ["TIDYFLOW_WS_TOKEN", "TIDYFLOW_TOKEN"]
+        .iter()
+        .find_map(|name| {
+            std::env::var(name)
+                .ok()
+                .map(|token| token.trim().to_string())
+                .filter(|token| !token.is_empty())
+        })
+}
+
+fn generate_token() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// 将生成的 token 写入仅当前用户可读的文件，供本机脚本 / 调试工具读取
+fn persist_generated_token(path: &std::path::Path, token: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    {
+        // 文件已存在时 mode 不生效，显式收紧权限
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    std::io::Write::write_all(&mut file, token.as_bytes())
+}
+
+/// 启动 token：优先 `TIDYFLOW_WS_TOKEN`，其次 `TIDYFLOW_TOKEN`，都未设置时随机生成
+fn resolve_expected_ws_token() -> String {
+    if let Some(token) = token_from_env() {
+        return token;
+    }
+    let token = generate_token();
+    let path = crate::util::paths::tidyflow_home_dir().join(AUTH_TOKEN_FILE_NAME);
+    match persist_generated_token(&path, &token) {
+        Ok(()) => info!(path = %path.display(), "Generated WebSocket auth token"),
+        Err(e) => warn!(
+            path = %path.display(),
+            error = %e,
+            "Generated WebSocket auth token but failed to persist it"
+        ),
+    }
+    token
 }
 
 fn resolve_bind_addr() -> String {
@@ -62,12 +122,7 @@ fn resolve_task_broadcast_capacity() -> usize {
         .unwrap_or(1024)
 }
 
-fn log_bootstrap_config(expected_ws_token: Option<&str>, bind_addr: &str) {
-    if expected_ws_token.is_some() {
-        info!("WebSocket token auth enabled");
-    } else {
-        warn!("WebSocket token auth disabled (TIDYFLOW_WS_TOKEN not set)");
-    }
+fn log_bootstrap_config(bind_addr: &str) {
     info!("Binding on {}", bind_addr);
 }
 
@@ -81,13 +136,38 @@ pub(in crate::server::ws) async fn build_app_context() -> (AppContext, String) {
             .await
             .unwrap_or_else(|_| panic!("failed to initialize state store")),
     );
-    let app_state = state_store
-        .load()
-        .await
-        .unwrap_or_else(|_| AppState::default());
+    // 只加载项目 stub，workspace 在后台水合，避免项目较多时阻塞监听
+    let load_started = std::time::Instant::now();
+    let app_state = match state_store.load_project_stubs().await {
+        Ok(state) => state,
+        Err(e) => {
+            warn!(error = %e, "Failed to load state on startup, starting empty");
+            // 空状态随后会被保存并覆盖原数据，先留一份备份（JSON 后端在加载失败时已自行另存）
+            if state_store.backend_kind() == StateBackendKind::Sqlite {
+                let label = format!("unreadable-{}", chrono::Utc::now().format("%Y%m%d%H%M%S"));
+                match state_store.backup_database(&label).await {
+                    Ok(path) => {
+                        warn!(path = %path.display(), "Preserved unreadable state database")
+                    }
+                    Err(err) => warn!(error = %err, "Failed to back up unreadable state database"),
+                }
+            }
+            AppState::default()
+        }
+    };
+    info!(
+        projects = app_state.projects.len(),
+        elapsed_ms = load_started.elapsed().as_millis() as u64,
+        "State loaded on startup"
+    );
+    crate::server::git::configure_git_network(crate::server::git::GitNetworkSettings {
+        proxy: app_state.client_settings.git_proxy.clone(),
+        offline_mode: app_state.client_settings.git_offline_mode,
+    });
     let shared_state: SharedAppState = Arc::new(tokio::sync::RwLock::new(app_state));
 
     let save_tx = spawn_state_saver(shared_state.clone(), state_store.clone());
+    spawn_project_hydrator(shared_state.clone(), state_store.clone());
     let _ = crate::server::node::init_global(
         shared_state.clone(),
         save_tx.clone(),
@@ -98,10 +178,12 @@ pub(in crate::server::ws) async fn build_app_context() -> (AppContext, String) {
     let scrollback_tx = spawn_scrollback_writer(terminal_registry.clone());
     // 启动空闲终端回收后台任务（每 30 秒检查，自动回收无订阅的退出/长期空闲终端）
     spawn_idle_reaper(terminal_registry.clone());
+    // 主机休眠/唤醒兜底检测（客户端未转发系统通知时）
+    crate::server::power::spawn_sleep_detector();
 
     let expected_ws_token = resolve_expected_ws_token();
     let bind_addr = resolve_bind_addr();
-    log_bootstrap_config(expected_ws_token.as_deref(), &bind_addr);
+    log_bootstrap_config(&bind_addr);
 
     let task_broadcast_capacity = resolve_task_broadcast_capacity();
     info!(
@@ -109,6 +191,15 @@ pub(in crate::server::ws) async fn build_app_context() -> (AppContext, String) {
         task_broadcast_capacity
     );
     let (task_broadcast_tx, _) = tokio::sync::broadcast::channel(task_broadcast_capacity);
+    // 跟踪终端工作目录、shell 集成命令与编码 Agent，推送 term_cwd_changed / term_command_finished / term_agent_state_changed
+    spawn_terminal_state_tracker(terminal_registry.clone(), task_broadcast_tx.clone());
+    // 重要事件转发到推送中继（未配置中继地址时不发送）
+    crate::server::push_relay::spawn_push_relay(shared_state.clone());
+    // 按设置的间隔后台 fetch 全部项目，推送 git_remote_updated（默认关闭）
+    crate::server::git_fetch_scheduler::spawn_git_fetch_scheduler(
+        shared_state.clone(),
+        task_broadcast_tx.clone(),
+    );
     let running_commands: SharedRunningCommands = Arc::new(Mutex::new(HashMap::new()));
     let running_ai_tasks: SharedRunningAITasks = Arc::new(Mutex::new(HashMap::new()));
     let task_history: SharedTaskHistory = Arc::new(Mutex::new(Vec::new()));
@@ -177,7 +268,26 @@ pub(in crate::server::ws) async fn build_app_context() -> (AppContext, String) {
 
 #[cfg(test)]
 mod tests {
-    use super::build_shared_ai_state;
+    use super::{build_shared_ai_state, generate_token, persist_generated_token};
+
+    #[test]
+    fn generated_token_should_be_random_and_persisted_privately() {
+        let token = generate_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, generate_token());
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("nested").join("auth-token");
+        persist_generated_token(&path, "stale-token").expect("persist");
+        persist_generated_token(&path, &token).expect("overwrite");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), token);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
 
     #[tokio::test]
     async fn bootstrap_ai_state_should_start_without_preloaded_agents() {
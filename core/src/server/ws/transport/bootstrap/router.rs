@@ -48,6 +48,22 @@ pub(in crate::server::ws) fn build_router(ctx: AppContext) -> Router {
             "/api/v1/projects/:project/workspaces",
             get(crate::server::ws::http_api::workspaces_handler),
         )
+        .route(
+            "/api/v1/projects/:project/workspaces/:workspace/tasks",
+            get(crate::server::ws::http_api::workspace_tasks_handler),
+        )
+        .route(
+            "/api/v1/projects/:project/workspaces/:workspace/ports",
+            get(crate::server::ws::http_api::ports_handler),
+        )
+        .route(
+            "/api/v1/projects/:project/workspaces/:workspace/procs",
+            get(crate::server::ws::http_api::procs_handler),
+        )
+        .route(
+            "/api/v1/projects/:project/workspaces/:workspace/procs/:name/logs",
+            get(crate::server::ws::http_api::proc_logs_handler),
+        )
         .route("/api/v1/tasks", get(crate::server::ws::http_api::tasks_handler))
         .route(
             "/api/v1/client-settings",
@@ -73,22 +89,54 @@ pub(in crate::server::ws) fn build_router(ctx: AppContext) -> Router {
             "/api/v1/projects/:project/workspaces/:workspace/files/index",
             get(crate::server::ws::http_api::file_index_handler),
         )
+        .route(
+            "/api/v1/projects/:project/workspaces/:workspace/files/query",
+            get(crate::server::ws::http_api::file_query_handler),
+        )
+        .route(
+            "/asset/:project/:workspace/*path",
+            get(crate::server::ws::http_api::file_asset_handler),
+        )
         .route(
             "/api/v1/projects/:project/workspaces/:workspace/files/content",
             get(crate::server::ws::http_api::file_content_handler),
         )
+        .route(
+            "/api/v1/projects/:project/workspaces/:workspace/files/revision",
+            get(crate::server::ws::http_api::file_revision_handler),
+        )
         .route(
             "/api/v1/projects/:project/workspaces/:workspace/files/search",
             get(crate::server::ws::http_api::file_search_handler),
         )
+        .route(
+            "/api/v1/projects/:project/workspaces/:workspace/files/definition",
+            get(crate::server::ws::http_api::file_definition_handler),
+        )
+        .route(
+            "/api/v1/projects/:project/workspaces/:workspace/files/editorconfig",
+            get(crate::server::ws::http_api::file_editorconfig_handler),
+        )
+        .route(
+            "/api/v1/projects/:project/workspaces/:workspace/files/highlight",
+            get(crate::server::ws::http_api::file_highlight_handler),
+        )
         .route(
             "/api/v1/projects/:project/workspaces/:workspace/git/status",
             get(crate::server::ws::http_api::git_status_handler),
         )
+        .route(
+            "/api/v1/projects/:project/workspaces/:workspace/git/status-tree",
+            get(crate::server::ws::http_api::git_status_tree_handler),
+        )
         .route(
             "/api/v1/projects/:project/workspaces/:workspace/git/diff",
             get(crate::server::ws::http_api::git_diff_handler),
         )
+        .route(
+            "/api/v1/projects/:project/workspaces/:workspace/git/diff-page",
+            get(crate::server::ws::http_api::git_diff_page_handler),
+        )
         .route(
             "/api/v1/projects/:project/workspaces/:workspace/git/branches",
             get(crate::server::ws::http_api::git_branches_handler),
@@ -101,6 +149,26 @@ pub(in crate::server::ws) fn build_router(ctx: AppContext) -> Router {
             "/api/v1/projects/:project/workspaces/:workspace/git/commits/:sha",
             get(crate::server::ws::http_api::git_commit_show_handler),
         )
+        .route(
+            "/api/v1/projects/:project/workspaces/:workspace/git/commits/:sha/diff",
+            get(crate::server::ws::http_api::git_commit_file_diff_handler),
+        )
+        .route(
+            "/api/v1/projects/:project/workspaces/:workspace/git/suggested-commit-message",
+            get(crate::server::ws::http_api::git_suggested_commit_message_handler),
+        )
+        .route(
+            "/api/v1/projects/:project/workspaces/:workspace/git/blame",
+            get(crate::server::ws::http_api::git_blame_handler),
+        )
+        .route(
+            "/api/v1/projects/:project/workspaces/:workspace/git/diff-range",
+            get(crate::server::ws::http_api::git_diff_range_handler),
+        )
+        .route(
+            "/api/v1/projects/:project/workspaces/:workspace/git/rebase-plan",
+            get(crate::server::ws::http_api::git_rebase_plan_handler),
+        )
         .route(
             "/api/v1/projects/:project/workspaces/:workspace/git/op-status",
             get(crate::server::ws::http_api::git_op_status_handler),
@@ -109,6 +177,26 @@ pub(in crate::server::ws) fn build_router(ctx: AppContext) -> Router {
             "/api/v1/projects/:project/git/integration-status",
             get(crate::server::ws::http_api::git_integration_status_handler),
         )
+        .route(
+            "/api/v1/projects/:project/git/status-all",
+            get(crate::server::ws::http_api::git_status_all_handler),
+        )
+        .route(
+            "/api/v1/projects/:project/git/repo-stats",
+            get(crate::server::ws::http_api::git_repo_stats_handler),
+        )
+        .route(
+            "/api/v1/projects/:project/git/large-blobs",
+            get(crate::server::ws::http_api::git_large_blobs_handler),
+        )
+        .route(
+            "/api/v1/projects/:project/git/pull-requests",
+            get(crate::server::ws::http_api::git_pull_requests_handler),
+        )
+        .route(
+            "/api/v1/projects/:project/git/pull-requests/:number",
+            get(crate::server::ws::http_api::git_pull_request_status_handler),
+        )
         .route(
             "/api/v1/projects/:project/workspaces/:workspace/git/up-to-date",
             get(crate::server::ws::http_api::git_check_branch_up_to_date_handler),
@@ -125,6 +213,10 @@ pub(in crate::server::ws) fn build_router(ctx: AppContext) -> Router {
             "/api/v1/projects/:project/workspaces/:workspace/git/stashes/:stash_id",
             get(crate::server::ws::http_api::git_stash_show_handler),
         )
+        .route(
+            "/api/v1/change-reports/:report_id",
+            get(crate::server::ws::http_api::git_change_report_handler),
+        )
         .route(
             "/api/v1/projects/:project/workspaces/:workspace/ai/sessions",
             get(crate::server::ws::http_api::ai_sessions_handler),
@@ -185,5 +277,31 @@ pub(in crate::server::ws) fn build_router(ctx: AppContext) -> Router {
             "/api/v1/system/repair",
             axum::routing::post(crate::server::ws::http_api::system_repair_handler),
         )
+        .route(
+            "/api/v1/system/state-backups",
+            get(crate::server::ws::http_api::system_state_backups_handler),
+        )
+        .route(
+            "/metrics",
+            get(crate::server::ws::http_api::metrics_handler),
+        )
+        .route(
+            "/healthz",
+            get(crate::server::ws::http_api::healthz_handler),
+        )
+        .route(
+            "/readyz",
+            get(crate::server::ws::http_api::readyz_handler),
+        )
+        .route_layer(axum::middleware::from_fn_with_state(
+            ctx.clone(),
+            crate::server::ws::http_api::hydrate_project_middleware,
+        ))
+        .route_layer(axum::middleware::from_fn(
+            crate::server::ws::http_api::cancel_request_middleware,
+        ))
+        .route_layer(axum::middleware::from_fn(
+            crate::server::ws::http_api::trace_request_middleware,
+        ))
         .with_state(ctx)
 }
@@ -1,6 +1,7 @@
 use axum::{
     extract::ws::WebSocketUpgrade,
     extract::{ConnectInfo, Query, State},
+    http::HeaderMap,
     response::IntoResponse,
 };
 use std::net::SocketAddr;
@@ -9,7 +10,8 @@ pub(in crate::server::ws) async fn ws_handler(
     ws: WebSocketUpgrade,
     State(ctx): State<crate::server::ws::transport::bootstrap::AppContext>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     query: Option<Query<crate::server::ws::auth_keys::WsAuthQuery>>,
 ) -> impl IntoResponse {
-    crate::server::ws::transport::upgrade::handle_ws_upgrade(ws, ctx, addr, query).await
+    crate::server::ws::transport::upgrade::handle_ws_upgrade(ws, ctx, addr, &headers, query).await
 }
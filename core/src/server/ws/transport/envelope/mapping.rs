@@ -33,7 +33,7 @@ pub(in crate::server::ws) fn domain_from_action(action: &str) -> String {
     if action.starts_with("evo_") {
         return "evolution".to_string();
     }
-    if action == "pong" || action == "hello" {
+    if action == "pong" || action == "hello" || action == "cancel_result" {
         return "system".to_string();
     }
     // v1.41: 系统健康诊断域
@@ -57,7 +57,11 @@ pub(in crate::server::ws) fn is_event_action(action: &str) -> bool {
     action == "output_batch"
         || action == "exit"
         || action == "file_changed"
+        || action == "file_watch_changed"
+        || action == "file_index_delta"
         || action == "git_status_changed"
+        || action == "git_remote_updated"
+        || action == "git_op_progress"
         || action == "remote_term_changed"
         // 项目 / 工作区 / 任务事件
         || action == "projects"
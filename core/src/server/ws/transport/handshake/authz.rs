@@ -2,7 +2,7 @@ use axum::http::StatusCode;
 use tracing::warn;
 
 pub(in crate::server::ws) async fn authorize_ws_upgrade(
-    expected_ws_token: Option<&str>,
+    expected_ws_token: &str,
     query: &crate::server::ws::auth_keys::WsAuthQuery,
     api_key_registry: &crate::server::ws::auth_keys::SharedRemoteAPIKeyRegistry,
 ) -> Result<(), StatusCode> {
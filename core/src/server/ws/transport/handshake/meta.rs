@@ -30,13 +30,10 @@ pub(in crate::server::ws) async fn build_connection_meta(
         .map(ToString::to_string);
 
     let (is_remote, api_key_id, subscriber_id, device_name) = if let Some(token) = provided_token {
-        if let Some(info) = crate::server::ws::auth_keys::authorize_token(
-            None,
-            Some(token),
-            query,
-            api_key_registry,
-        )
-        .await
+        // 升级前已完成鉴权，这里只按远程 API key 解析身份（空的启动 token 不会匹配）
+        if let Some(info) =
+            crate::server::ws::auth_keys::authorize_token("", Some(token), query, api_key_registry)
+                .await
         {
             if !info.key_id.is_empty() {
                 let client_id = client_id.clone().unwrap_or_else(|| conn_id.clone());
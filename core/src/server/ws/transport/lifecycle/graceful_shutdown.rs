@@ -0,0 +1,71 @@
+//! 优雅关闭编排
+//!
+//! 收到 SIGTERM / SIGINT 或父进程退出后：
+//! 1. 标记关闭中：`/readyz` 返回 503，新的 `/ws` 升级请求被拒绝；
+//! 2. 向所有连接广播 `server_shutdown`，留出短暂时间让消息发出；
+//! 3. 停止监听（由 axum 优雅关闭完成）；
+//! 4. 立即落盘状态并关闭全部 PTY，再停止 AI 代理与托管进程。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use tracing::{info, warn};
+
+use crate::server::context::{
+    send_task_broadcast_event, SharedAppState, TaskBroadcastEvent, TaskBroadcastTx,
+};
+use crate::server::protocol::ServerMessage;
+use crate::server::terminal_registry::SharedTerminalRegistry;
+use crate::workspace::state_store::StateStore;
+
+/// 广播 `server_shutdown` 后等待出站队列发送的时间
+const SHUTDOWN_NOTIFY_GRACE: Duration = Duration::from_millis(300);
+
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+static SHUTDOWN_REASON: OnceLock<&'static str> = OnceLock::new();
+
+/// 请求关闭：记录原因（首次生效）并置位关闭标志
+pub(in crate::server::ws) fn request_shutdown(shutdown_flag: &AtomicBool, reason: &'static str) {
+    let _ = SHUTDOWN_REASON.set(reason);
+    shutdown_flag.store(true, Ordering::SeqCst);
+}
+
+/// 是否已进入关闭流程
+pub(in crate::server::ws) fn is_shutting_down() -> bool {
+    SHUTTING_DOWN.load(Ordering::SeqCst)
+}
+
+/// 进入关闭流程并通知所有连接
+pub(in crate::server::ws) async fn begin_shutdown(task_broadcast_tx: &TaskBroadcastTx) {
+    SHUTTING_DOWN.store(true, Ordering::SeqCst);
+    let reason = SHUTDOWN_REASON.get().copied().unwrap_or("shutdown");
+    info!(reason, "Notifying clients of server shutdown");
+    send_task_broadcast_event(
+        task_broadcast_tx,
+        TaskBroadcastEvent {
+            origin_conn_id: String::new(),
+            message: ServerMessage::ServerShutdown {
+                reason: reason.to_string(),
+            },
+            target_conn_ids: None,
+            skip_when_single_receiver: false,
+        },
+    );
+    tokio::time::sleep(SHUTDOWN_NOTIFY_GRACE).await;
+}
+
+/// 停止监听后释放资源：立即保存状态（不经防抖）并关闭全部终端
+pub(in crate::server::ws) async fn release_resources(
+    app_state: &SharedAppState,
+    state_store: &Arc<StateStore>,
+    terminal_registry: &SharedTerminalRegistry,
+) {
+    if let Err(e) = crate::workspace::state_saver::flush_state(app_state, state_store).await {
+        warn!(error = %e, "Failed to flush state before shutdown");
+    }
+    let mut registry = terminal_registry.lock().await;
+    let count = registry.term_ids().len();
+    registry.close_all();
+    info!(terminals = count, "Closed terminals before shutdown");
+}
@@ -1,5 +1,9 @@
+mod graceful_shutdown;
 mod parent_monitor;
 mod shutdown_signal;
 
+pub(in crate::server::ws) use graceful_shutdown::{
+    begin_shutdown, is_shutting_down, release_resources, request_shutdown,
+};
 pub(in crate::server::ws) use parent_monitor::spawn_parent_monitor;
 pub(in crate::server::ws) use shutdown_signal::spawn_shutdown_signal_listener;
@@ -1,6 +1,6 @@
 #[cfg(unix)]
 use std::os::unix::process::parent_id;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
 use tracing::{info, warn};
@@ -27,7 +27,7 @@ pub(in crate::server::ws) fn spawn_parent_monitor(shutdown_flag: Arc<AtomicBool>
                         "Parent process died (PPID changed from {} to {}), requesting graceful shutdown",
                         initial_ppid, current_ppid
                     );
-                    shutdown_flag.store(true, Ordering::SeqCst);
+                    super::request_shutdown(&shutdown_flag, "parent_exited");
                     break;
                 }
             }
@@ -1,4 +1,4 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
 use tracing::info;
@@ -10,15 +10,17 @@ pub(in crate::server::ws) fn spawn_shutdown_signal_listener(shutdown_flag: Arc<A
             use tokio::signal::unix::{signal, SignalKind};
             let mut sigterm = signal(SignalKind::terminate()).unwrap();
             let mut sigint = signal(SignalKind::interrupt()).unwrap();
-            tokio::select! {
+            let reason = tokio::select! {
                 _ = sigterm.recv() => {
                     info!("Received SIGTERM, shutting down gracefully");
+                    "sigterm"
                 }
                 _ = sigint.recv() => {
                     info!("Received SIGINT, shutting down gracefully");
+                    "sigint"
                 }
-            }
-            shutdown_flag.store(true, Ordering::SeqCst);
+            };
+            super::request_shutdown(&shutdown_flag, reason);
         });
     }
     #[cfg(not(unix))]
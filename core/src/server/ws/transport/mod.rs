@@ -3,4 +3,5 @@ pub(super) mod endpoint;
 pub(super) mod envelope;
 pub(super) mod handshake;
 pub(super) mod lifecycle;
+pub(super) mod tls;
 pub(super) mod upgrade;
@@ -0,0 +1,82 @@
+//! 远程访问的 TLS 监听（HTTPS / WSS）
+//!
+//! `serve --tls-cert <PEM> --tls-key <PEM>` 时，HTTP API 与 `/ws` 统一经 TLS 提供。
+//! 证书链与私钥均为 PEM；私钥支持 PKCS#8、PKCS#1（RSA）与 SEC1（EC）。
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum_server::tls_rustls::RustlsConfig;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+/// TLS 证书配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsConfig {
+    /// 证书链（PEM，服务端证书在前）
+    pub cert_path: PathBuf,
+    /// 私钥（PEM）
+    pub key_path: PathBuf,
+}
+
+/// 读取证书与私钥并构造 rustls 配置；文件缺失或内容无效时返回可读的错误
+pub(in crate::server::ws) fn load_rustls_config(tls: &TlsConfig) -> Result<RustlsConfig, String> {
+    let certs = CertificateDer::pem_file_iter(&tls.cert_path)
+        .and_then(|iter| iter.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| {
+            format!(
+                "Failed to read TLS certificate {}: {}",
+                tls.cert_path.display(),
+                e
+            )
+        })?;
+    if certs.is_empty() {
+        return Err(format!(
+            "No certificate found in {}",
+            tls.cert_path.display()
+        ));
+    }
+    let key = PrivateKeyDer::from_pem_file(&tls.key_path).map_err(|e| {
+        format!(
+            "Failed to read TLS private key {}: {}",
+            tls.key_path.display(),
+            e
+        )
+    })?;
+
+    let mut config = rustls::ServerConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .map_err(|e| format!("Invalid TLS protocol configuration: {}", e))?
+    .with_no_client_auth()
+    .with_single_cert(certs, key)
+    .map_err(|e| format!("Invalid TLS certificate or key: {}", e))?;
+    // axum 的 WebSocket 升级只支持 HTTP/1.1，不协商 h2
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+    Ok(RustlsConfig::from_config(Arc::new(config)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_rejects_missing_or_empty_pem() {
+        let dir = tempfile::tempdir().unwrap();
+        let cert_path = dir.path().join("cert.pem");
+        let key_path = dir.path().join("key.pem");
+
+        let missing = TlsConfig {
+            cert_path: cert_path.clone(),
+            key_path: key_path.clone(),
+        };
+        let err = load_rustls_config(&missing).unwrap_err();
+        assert!(err.contains("cert.pem"), "{}", err);
+
+        std::fs::write(&cert_path, "not a pem\n").unwrap();
+        let err = load_rustls_config(&missing).unwrap_err();
+        assert!(err.contains("No certificate found"), "{}", err);
+    }
+}
@@ -1,29 +1,73 @@
-use axum::{
-    extract::Query,
-    http::StatusCode,
-    response::{IntoResponse, Response},
-};
+use axum::{extract::Query, http::HeaderMap};
 
+use crate::server::ws::http_api::{parse_bearer_token, parse_optional_header};
+
+/// 合并查询参数与请求头中的认证信息
+///
+/// 查询参数 `token` 优先；缺省时回退到 `Authorization: Bearer <token>`，
+/// `client_id` / `device_name` 同理回退到 `X-TidyFlow-Client-ID` / `X-TidyFlow-Device-Name`。
 pub(in crate::server::ws) fn extract_auth_query(
+    headers: &HeaderMap,
     query: Option<Query<crate::server::ws::auth_keys::WsAuthQuery>>,
 ) -> crate::server::ws::auth_keys::WsAuthQuery {
-    query.map(|value| value.0).unwrap_or_default()
+    let mut auth_query = query.map(|value| value.0).unwrap_or_default();
+    if is_blank(auth_query.token.as_deref()) {
+        auth_query.token = parse_bearer_token(headers);
+    }
+    if is_blank(auth_query.client_id.as_deref()) {
+        auth_query.client_id = parse_optional_header(headers, "X-TidyFlow-Client-ID");
+    }
+    if is_blank(auth_query.device_name.as_deref()) {
+        auth_query.device_name = parse_optional_header(headers, "X-TidyFlow-Device-Name");
+    }
+    auth_query
+}
+
+fn is_blank(value: Option<&str>) -> bool {
+    value.is_none_or(|value| value.trim().is_empty())
 }
 
-pub(in crate::server::ws) async fn ensure_authorized_or_response(
-    expected_ws_token: Option<&str>,
+pub(in crate::server::ws) async fn is_authorized(
+    expected_ws_token: &str,
     query: &crate::server::ws::auth_keys::WsAuthQuery,
     api_key_registry: &crate::server::ws::auth_keys::SharedRemoteAPIKeyRegistry,
-) -> Result<(), Response> {
-    if crate::server::ws::transport::handshake::authorize_ws_upgrade(
+) -> bool {
+    crate::server::ws::transport::handshake::authorize_ws_upgrade(
         expected_ws_token,
         query,
         api_key_registry,
     )
     .await
-    .is_err()
-    {
-        return Err((StatusCode::UNAUTHORIZED, "unauthorized").into_response());
+    .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::extract_auth_query;
+    use crate::server::ws::auth_keys::WsAuthQuery;
+    use axum::{extract::Query, http::HeaderMap};
+
+    #[test]
+    fn auth_query_falls_back_to_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Authorization", "Bearer header-token".parse().unwrap());
+        headers.insert("X-TidyFlow-Client-ID", "client-1".parse().unwrap());
+
+        let query = extract_auth_query(&headers, None);
+        assert_eq!(query.token.as_deref(), Some("header-token"));
+        assert_eq!(query.client_id.as_deref(), Some("client-1"));
+        assert_eq!(query.device_name, None);
+
+        let query = extract_auth_query(
+            &headers,
+            Some(Query(WsAuthQuery {
+                token: Some("query-token".to_string()),
+                client_id: None,
+                device_name: Some("iPad".to_string()),
+            })),
+        );
+        assert_eq!(query.token.as_deref(), Some("query-token"));
+        assert_eq!(query.client_id.as_deref(), Some("client-1"));
+        assert_eq!(query.device_name.as_deref(), Some("iPad"));
     }
-    Ok(())
 }
@@ -1,8 +1,26 @@
 use axum::{
-    extract::ws::WebSocketUpgrade,
+    extract::ws::{CloseFrame, Message, WebSocketUpgrade},
     response::{IntoResponse, Response},
 };
 
+/// 认证失败的关闭码（4000-4999 为应用自定义区间，取 HTTP 401 的含义）
+pub(in crate::server::ws) const WS_CLOSE_UNAUTHORIZED: u16 = 4401;
+
+/// 完成升级后立即以 [`WS_CLOSE_UNAUTHORIZED`] 关闭连接
+///
+/// 浏览器等客户端无法读取升级阶段的 HTTP 状态码，只能通过关闭码区分认证失败与网络错误。
+pub(in crate::server::ws) fn reject_unauthorized(ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(|mut socket| async move {
+        let _ = socket
+            .send(Message::Close(Some(CloseFrame {
+                code: WS_CLOSE_UNAUTHORIZED,
+                reason: "unauthorized".into(),
+            })))
+            .await;
+    })
+    .into_response()
+}
+
 pub(in crate::server::ws) fn bind_upgrade(
     ws: WebSocketUpgrade,
     ctx: crate::server::ws::transport::bootstrap::AppContext,
@@ -1,4 +1,9 @@
-use axum::{extract::ws::WebSocketUpgrade, extract::Query, response::Response};
+use axum::{
+    extract::ws::WebSocketUpgrade,
+    extract::Query,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
 use std::net::SocketAddr;
 
 mod auth;
@@ -9,18 +14,16 @@ pub(in crate::server::ws) async fn handle_ws_upgrade(
     ws: WebSocketUpgrade,
     ctx: crate::server::ws::transport::bootstrap::AppContext,
     addr: SocketAddr,
+    headers: &HeaderMap,
     query: Option<Query<crate::server::ws::auth_keys::WsAuthQuery>>,
 ) -> Response {
-    let auth_query = auth::extract_auth_query(query);
+    if crate::server::ws::transport::lifecycle::is_shutting_down() {
+        return (StatusCode::SERVICE_UNAVAILABLE, "server is shutting down").into_response();
+    }
+    let auth_query = auth::extract_auth_query(headers, query);
 
-    if let Err(resp) = auth::ensure_authorized_or_response(
-        ctx.expected_ws_token.as_deref(),
-        &auth_query,
-        &ctx.api_key_registry,
-    )
-    .await
-    {
-        return resp;
+    if !auth::is_authorized(&ctx.expected_ws_token, &auth_query, &ctx.api_key_registry).await {
+        return bind::reject_unauthorized(ws);
     }
 
     let conn_meta = session::build_conn_meta(addr, &auth_query, &ctx.api_key_registry).await;
@@ -0,0 +1,232 @@
+//! 请求级取消令牌
+//!
+//! 长耗时请求（内容搜索、文件索引、大文件 diff、模板导出等）在进入时以客户端的
+//! request id 登记一个 [`CancelToken`]，客户端发送 `cancel { request_id }` 即可放弃已过时的请求
+//! （例如用户输入了新的搜索词），服务端尽快停止工作并释放资源：
+//! - WS 请求以包络 `request_id` 登记，HTTP 请求以 [`REQUEST_ID_HEADER`] 请求头登记；
+//! - 处理链路在循环中调用 [`is_cancelled`] 提前退出，外部进程经 [`output`] 执行，取消时被 kill；
+//! - 令牌经 tokio task-local 传递，进入阻塞线程须使用 [`crate::util::trace::spawn_blocking`]。
+//!
+//! 请求结束（或其 future 被丢弃）时令牌自动注销；对已结束或未知的 request id 取消是无操作。
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::Read;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+/// HTTP 请求携带客户端 request id 的请求头
+pub const REQUEST_ID_HEADER: &str = "x-tidyflow-request-id";
+
+/// 请求被取消时的错误码
+pub const CANCELLED_CODE: &str = "request_cancelled";
+
+/// 轮询子进程退出与取消标记的间隔
+const PROCESS_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// 单个请求的取消标记
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// 进行中的请求：request id → 令牌
+static REGISTRY: LazyLock<Mutex<HashMap<String, CancelToken>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+tokio::task_local! {
+    static TASK_TOKEN: CancelToken;
+}
+
+thread_local! {
+    static THREAD_TOKEN: RefCell<Option<CancelToken>> = const { RefCell::new(None) };
+}
+
+/// 作用域结束时注销令牌；future 中途被丢弃（连接断开等）时一并标记取消，
+/// 让已派发到阻塞线程的工作尽快停止
+struct Registration {
+    request_id: String,
+    token: CancelToken,
+    finished: bool,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.token.cancel();
+        }
+        let mut registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+        if registry
+            .get(&self.request_id)
+            .is_some_and(|token| Arc::ptr_eq(&token.0, &self.token.0))
+        {
+            registry.remove(&self.request_id);
+        }
+    }
+}
+
+/// 以 `request_id` 登记取消令牌并在其作用域内执行 `fut`
+///
+/// request id 重复时后登记者覆盖前者（前者仍可正常完成，但不再能被取消）。
+pub async fn scope<F>(request_id: String, fut: F) -> F::Output
+where
+    F: Future,
+{
+    let token = CancelToken::default();
+    REGISTRY
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(request_id.clone(), token.clone());
+    let mut registration = Registration {
+        request_id,
+        token: token.clone(),
+        finished: false,
+    };
+    let output = TASK_TOKEN.scope(token, fut).await;
+    registration.finished = true;
+    output
+}
+
+/// 取消进行中的请求，返回是否找到该请求
+pub fn cancel(request_id: &str) -> bool {
+    let registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    match registry.get(request_id) {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+/// 当前执行上下文的取消令牌：阻塞线程上由 `trace::spawn_blocking` 设置，异步任务中取 task-local
+pub fn current() -> Option<CancelToken> {
+    THREAD_TOKEN
+        .with(|token| token.borrow().clone())
+        .or_else(|| TASK_TOKEN.try_with(|token| token.clone()).ok())
+}
+
+/// 当前请求是否已被取消（不在任何请求作用域内时恒为 false）
+pub fn is_cancelled() -> bool {
+    current().is_some_and(|token| token.is_cancelled())
+}
+
+/// 在阻塞线程上安装令牌并执行 `f`，结束后恢复原值
+pub(crate) fn with_thread_token<R>(token: Option<CancelToken>, f: impl FnOnce() -> R) -> R {
+    let previous = THREAD_TOKEN.with(|slot| slot.replace(token));
+    let result = f();
+    THREAD_TOKEN.with(|slot| *slot.borrow_mut() = previous);
+    result
+}
+
+/// 请求被取消时的 I/O 错误
+pub fn cancelled_error() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Interrupted, CANCELLED_CODE)
+}
+
+/// 错误是否由 [`cancelled_error`] 产生
+pub fn is_cancelled_error(err: &std::io::Error) -> bool {
+    err.kind() == std::io::ErrorKind::Interrupted && err.to_string() == CANCELLED_CODE
+}
+
+/// 可取消的 `Command::output`：当前请求被取消时 kill 子进程并返回 [`cancelled_error`]
+///
+/// 不在请求作用域内时等同于 `cmd.output()`。
+pub fn output(cmd: &mut Command) -> std::io::Result<Output> {
+    let Some(token) = current() else {
+        return cmd.output();
+    };
+    if token.is_cancelled() {
+        return Err(cancelled_error());
+    }
+
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if token.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(cancelled_error());
+        }
+        std::thread::sleep(PROCESS_POLL_INTERVAL);
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// 在独立线程读尽管道，避免子进程因输出填满管道而阻塞
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cancel_reaches_blocking_work_and_unregisters() {
+        assert!(!cancel("req-cancel-1"));
+        let result = scope("req-cancel-1".to_string(), async {
+            assert!(!is_cancelled());
+            assert!(cancel("req-cancel-1"));
+            crate::util::trace::spawn_blocking(is_cancelled)
+                .await
+                .unwrap()
+        })
+        .await;
+        assert!(result);
+        assert!(!cancel("req-cancel-1"));
+        assert!(!is_cancelled());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn cancelled_request_kills_child_process() {
+        let finished = scope("req-cancel-2".to_string(), async {
+            let started = std::time::Instant::now();
+            let work = crate::util::trace::spawn_blocking(|| {
+                output(Command::new("sleep").arg("5")).map(|out| out.status.success())
+            });
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert!(cancel("req-cancel-2"));
+            let result = work.await.unwrap();
+            assert!(started.elapsed() < Duration::from_secs(5));
+            result
+        })
+        .await;
+        assert!(finished.is_err_and(|e| is_cancelled_error(&e)));
+
+        let out = output(Command::new("sh").args(["-c", "echo hi"])).unwrap();
+        assert_eq!(out.stdout, b"hi\n");
+    }
+}
@@ -0,0 +1,201 @@
+//! 开发用故障注入（chaos 模式）
+//!
+//! 为 git 调用与工作区文件操作注入人为延迟与失败，无需准备超大仓库即可验证客户端的
+//! 超时、重试与临时态 UI。仅在设置了 `TIDYFLOW_DEV` 时生效，配置在首次使用时读取：
+//!
+//! - `TIDYFLOW_CHAOS_GIT_LATENCY_MS` / `TIDYFLOW_CHAOS_FS_LATENCY_MS`：每次调用前的延迟，
+//!   固定值 `500` 或区间 `200-1500`（区间内均匀随机）
+//! - `TIDYFLOW_CHAOS_GIT_FAILURE_RATE` / `TIDYFLOW_CHAOS_FS_FAILURE_RATE`：失败概率 `0`–`1`
+//!
+//! git 经 [`crate::util::exec_env::git_command`] 注入：失败时替换为以退出码 128 结束、
+//! stderr 为 `fatal: ...` 的进程。文件操作经 `file_api::resolve_safe_path` 注入：失败时返回 I/O 错误。
+//! 延迟在调用线程上同步等待，与真实的慢文件系统 / 慢 git 表现一致。
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::process::Command;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// 注入失败的错误信息
+pub const INJECTED_FAILURE: &str = "tidyflow chaos: injected failure";
+
+/// 单类操作的故障配置
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Fault {
+    /// 延迟区间（毫秒，含两端）
+    latency_ms: (u64, u64),
+    failure_rate: f64,
+}
+
+impl Fault {
+    fn is_active(&self) -> bool {
+        self.latency_ms.1 > 0 || self.failure_rate > 0.0
+    }
+
+    /// 按配置等待，返回本次是否应注入失败
+    fn inject(&self) -> bool {
+        let (min, max) = self.latency_ms;
+        if max > 0 {
+            let ms = min + random_u64() % (max - min + 1);
+            std::thread::sleep(Duration::from_millis(ms));
+        }
+        self.failure_rate > 0.0 && random_unit() < self.failure_rate
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct ChaosConfig {
+    git: Fault,
+    fs: Fault,
+}
+
+impl ChaosConfig {
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        if lookup("TIDYFLOW_DEV").is_none() {
+            return Self::default();
+        }
+        let fault = |kind: &str| Fault {
+            latency_ms: lookup(&format!("TIDYFLOW_CHAOS_{}_LATENCY_MS", kind))
+                .as_deref()
+                .and_then(parse_latency)
+                .unwrap_or_default(),
+            failure_rate: lookup(&format!("TIDYFLOW_CHAOS_{}_FAILURE_RATE", kind))
+                .and_then(|raw| raw.trim().parse::<f64>().ok())
+                .filter(|rate| rate.is_finite())
+                .map(|rate| rate.clamp(0.0, 1.0))
+                .unwrap_or_default(),
+        };
+        Self {
+            git: fault("GIT"),
+            fs: fault("FS"),
+        }
+    }
+}
+
+/// `500` → (500, 500)；`200-1500` → (200, 1500)
+fn parse_latency(raw: &str) -> Option<(u64, u64)> {
+    let raw = raw.trim();
+    let (min, max) = match raw.split_once('-') {
+        Some((min, max)) => (min.trim().parse().ok()?, max.trim().parse().ok()?),
+        None => {
+            let ms = raw.parse().ok()?;
+            (ms, ms)
+        }
+    };
+    (min <= max).then_some((min, max))
+}
+
+fn config() -> &'static ChaosConfig {
+    static CONFIG: OnceLock<ChaosConfig> = OnceLock::new();
+    CONFIG.get_or_init(|| {
+        let config = ChaosConfig::from_lookup(|key| std::env::var(key).ok());
+        if config.git.is_active() || config.fs.is_active() {
+            tracing::warn!(?config, "Chaos mode enabled, injecting faults");
+        }
+        config
+    })
+}
+
+fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+fn random_unit() -> f64 {
+    (random_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// 构造 git 命令前调用：注入延迟，命中失败时返回替代的失败命令
+pub fn git_fault() -> Option<Command> {
+    let fault = config().git;
+    if !fault.is_active() || !fault.inject() {
+        return None;
+    }
+    Some(failing_command())
+}
+
+/// 文件操作前调用：注入延迟，命中失败时返回 I/O 错误
+pub fn fs_fault() -> std::io::Result<()> {
+    let fault = config().fs;
+    if fault.is_active() && fault.inject() {
+        return Err(std::io::Error::other(INJECTED_FAILURE));
+    }
+    Ok(())
+}
+
+/// 以 git 的方式失败的命令；调用方追加的参数被忽略
+fn failing_command() -> Command {
+    #[cfg(windows)]
+    {
+        let mut cmd = Command::new("cmd");
+        cmd.args([
+            "/C",
+            &format!("(echo fatal: {})1>&2 & exit /b 128 & rem", INJECTED_FAILURE),
+        ]);
+        cmd
+    }
+    #[cfg(not(windows))]
+    {
+        let mut cmd = Command::new("sh");
+        cmd.args([
+            "-c",
+            &format!("echo 'fatal: {}' >&2; exit 128", INJECTED_FAILURE),
+            "git",
+        ]);
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config_from(vars: &[(&str, &str)]) -> ChaosConfig {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        ChaosConfig::from_lookup(|key| vars.get(key).cloned())
+    }
+
+    #[test]
+    fn parses_config_only_in_dev_mode() {
+        let vars = [
+            ("TIDYFLOW_CHAOS_GIT_LATENCY_MS", "200-1500"),
+            ("TIDYFLOW_CHAOS_GIT_FAILURE_RATE", "0.25"),
+            ("TIDYFLOW_CHAOS_FS_LATENCY_MS", "50"),
+            ("TIDYFLOW_CHAOS_FS_FAILURE_RATE", "3"),
+        ];
+        assert_eq!(config_from(&vars), ChaosConfig::default());
+
+        let mut dev = vars.to_vec();
+        dev.push(("TIDYFLOW_DEV", "1"));
+        let config = config_from(&dev);
+        assert_eq!(config.git.latency_ms, (200, 1500));
+        assert_eq!(config.git.failure_rate, 0.25);
+        assert_eq!(config.fs.latency_ms, (50, 50));
+        assert_eq!(config.fs.failure_rate, 1.0);
+
+        assert_eq!(parse_latency("1500-200"), None);
+        assert_eq!(parse_latency("abc"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failing_command_looks_like_git_failure() {
+        let output = failing_command()
+            .args(["status", "--porcelain"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(128));
+        assert!(String::from_utf8_lossy(&output.stderr).starts_with("fatal: "));
+
+        let always = Fault {
+            latency_ms: (0, 0),
+            failure_rate: 1.0,
+        };
+        assert!(always.inject());
+        assert!(!Fault::default().inject());
+    }
+}
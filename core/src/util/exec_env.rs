@@ -0,0 +1,311 @@
+//! 项目执行环境（Windows 下的 WSL / 网络共享路径转换）
+//!
+//! 项目根目录可能位于 WSL 发行版内（`\\wsl$\<distro>\...`、`\\wsl.localhost\<distro>\...`），
+//! 或位于网络共享（UNC 路径，以及 canonicalize 后解析为 UNC 的映射盘符）。
+//! 文件读写直接走 UNC 路径即可，外部命令（git）则需要在对应环境中执行：
+//! - `wsl`：经 `wsl.exe -d <distro> --cd <linux 路径> --exec <程序>` 在发行版内运行，
+//!   避免 Windows git 跨 9P 共享访问时的性能、换行与 dubious ownership 问题
+//! - `network`：仍用本机 git，对该仓库放行 `safe.directory`
+//! - `native`：本机直接执行；非 Windows 平台始终为此环境
+//!
+//! 传给命令的绝对路径参数需经 [`ExecEnv::to_exec_path`] 转换为环境内路径。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{LazyLock, Mutex};
+
+/// 项目执行环境
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecEnv {
+    Native,
+    Wsl {
+        distro: String,
+        /// 发行版在 Windows 侧的 UNC 根（如 `\\wsl$\Ubuntu`）
+        unc_root: String,
+    },
+    NetworkShare {
+        /// 检测时的路径（UNC 形式）
+        unc_path: String,
+    },
+}
+
+/// 已检测的路径 -> 执行环境（检测可能触发 canonicalize，按路径缓存）
+static EXEC_ENV_CACHE: LazyLock<Mutex<HashMap<PathBuf, ExecEnv>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+impl ExecEnv {
+    /// 协议中上报的环境名：`native` | `wsl` | `network`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ExecEnv::Native => "native",
+            ExecEnv::Wsl { .. } => "wsl",
+            ExecEnv::NetworkShare { .. } => "network",
+        }
+    }
+
+    pub fn wsl_distro(&self) -> Option<&str> {
+        match self {
+            ExecEnv::Wsl { distro, .. } => Some(distro),
+            _ => None,
+        }
+    }
+
+    /// 从 Windows 路径文本识别执行环境（纯字符串解析，与当前平台无关）
+    ///
+    /// 接受 `\` 或 `/` 分隔，以及 canonicalize 产生的 `\\?\UNC\` 前缀。
+    pub fn parse(path: &str) -> Self {
+        let normalized = path.replace('/', "\\");
+        let unc = if let Some(rest) = normalized.strip_prefix("\\\\?\\UNC\\") {
+            rest
+        } else if let Some(rest) = normalized.strip_prefix("\\\\") {
+            if rest.starts_with("?\\") || rest.starts_with(".\\") {
+                return ExecEnv::Native;
+            }
+            rest
+        } else {
+            return ExecEnv::Native;
+        };
+
+        let mut parts = unc.splitn(3, '\\');
+        let host = parts.next().unwrap_or_default();
+        let share = parts.next().unwrap_or_default();
+        if host.is_empty() || share.is_empty() {
+            return ExecEnv::Native;
+        }
+        if host.eq_ignore_ascii_case("wsl$") || host.eq_ignore_ascii_case("wsl.localhost") {
+            return ExecEnv::Wsl {
+                distro: share.to_string(),
+                unc_root: format!("\\\\{}\\{}", host, share),
+            };
+        }
+        ExecEnv::NetworkShare {
+            unc_path: format!("\\\\{}", unc.trim_end_matches('\\')),
+        }
+    }
+
+    /// 检测路径所在的执行环境；非 Windows 平台直接返回 `Native`
+    pub fn detect(path: &Path) -> Self {
+        if !cfg!(windows) {
+            return ExecEnv::Native;
+        }
+        // 映射的网络盘符只有 canonicalize 后才能看出是 UNC
+        let resolved = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        ExecEnv::parse(&resolved.to_string_lossy())
+    }
+
+    /// 将 Windows 侧路径转换为该环境内可用的路径
+    ///
+    /// WSL：发行版 UNC 路径转为 Linux 绝对路径，盘符路径转为 `/mnt/<盘符>/...`；
+    /// 其余环境原样返回。
+    pub fn to_exec_path(&self, path: &Path) -> String {
+        let raw = path.to_string_lossy();
+        let ExecEnv::Wsl { distro, .. } = self else {
+            return raw.into_owned();
+        };
+        let normalized = raw.replace('/', "\\");
+        let normalized = normalized
+            .strip_prefix("\\\\?\\UNC\\")
+            .map(|rest| format!("\\\\{}", rest))
+            .unwrap_or(normalized);
+
+        if let ExecEnv::Wsl {
+            distro: path_distro,
+            unc_root,
+        } = ExecEnv::parse(&normalized)
+        {
+            if path_distro.eq_ignore_ascii_case(distro) {
+                let rest = &normalized[unc_root.len()..];
+                let linux = rest.replace('\\', "/");
+                return if linux.is_empty() {
+                    "/".to_string()
+                } else {
+                    linux
+                };
+            }
+        }
+
+        let bytes = normalized.as_bytes();
+        if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+            let drive = (bytes[0] as char).to_ascii_lowercase();
+            let rest = normalized[2..].replace('\\', "/");
+            return format!("/mnt/{}{}", drive, rest);
+        }
+        raw.into_owned()
+    }
+
+    /// 构造在该环境中、以 `cwd` 为工作目录运行 `program` 的命令
+    ///
+    /// 当前请求的 trace id 经 `TIDYFLOW_TRACE_ID` 传给子进程（WSL 下经 `WSLENV` 转发）。
+    pub fn command(&self, program: &str, cwd: &Path) -> Command {
+        let mut cmd = self.base_command(program, cwd);
+        crate::util::trace::apply_to_command(&mut cmd);
+        if matches!(self, ExecEnv::Wsl { .. }) && crate::util::trace::current_trace_id().is_some() {
+            let wslenv = match std::env::var("WSLENV") {
+                Ok(existing) if !existing.is_empty() => {
+                    format!("{}:{}", existing, crate::util::trace::TRACE_ID_ENV)
+                }
+                _ => crate::util::trace::TRACE_ID_ENV.to_string(),
+            };
+            cmd.env("WSLENV", wslenv);
+        }
+        cmd
+    }
+
+    fn base_command(&self, program: &str, cwd: &Path) -> Command {
+        match self {
+            ExecEnv::Native => {
+                let mut cmd = Command::new(program);
+                cmd.current_dir(cwd);
+                cmd
+            }
+            ExecEnv::Wsl { distro, .. } => {
+                let mut cmd = Command::new("wsl.exe");
+                cmd.args(["-d", distro, "--cd"])
+                    .arg(self.to_exec_path(cwd))
+                    .args(["--exec", program]);
+                cmd
+            }
+            ExecEnv::NetworkShare { .. } => {
+                let mut cmd = Command::new(program);
+                cmd.current_dir(cwd);
+                if program == "git" {
+                    cmd.arg("-c").arg(format!(
+                        "safe.directory={}",
+                        safe_directory_value(&cwd.to_string_lossy())
+                    ));
+                }
+                cmd
+            }
+        }
+    }
+}
+
+/// Git for Windows 的 `safe.directory` 写法：正斜杠，UNC 路径需加 `%(prefix)/` 前缀
+fn safe_directory_value(path: &str) -> String {
+    let path = path
+        .strip_prefix("\\\\?\\UNC\\")
+        .map(|rest| format!("\\\\{}", rest))
+        .unwrap_or_else(|| path.to_string())
+        .replace('\\', "/");
+    if path.starts_with("//") {
+        format!("%(prefix)/{}", path)
+    } else {
+        path
+    }
+}
+
+/// 路径所在的执行环境（带缓存）
+pub fn exec_env_for(path: &Path) -> ExecEnv {
+    if !cfg!(windows) {
+        return ExecEnv::Native;
+    }
+    if let Ok(cache) = EXEC_ENV_CACHE.lock() {
+        if let Some(env) = cache.get(path) {
+            return env.clone();
+        }
+    }
+    let env = ExecEnv::detect(path);
+    if let Ok(mut cache) = EXEC_ENV_CACHE.lock() {
+        cache.insert(path.to_path_buf(), env.clone());
+    }
+    env
+}
+
+/// 在 `cwd` 所在执行环境中运行 git 的命令（已设置工作目录）
+///
+/// 开发模式下可经 chaos 配置注入延迟与失败，见 [`crate::util::chaos`]。
+pub fn git_command(cwd: impl AsRef<Path>) -> Command {
+    if let Some(failing) = crate::util::chaos::git_fault() {
+        return failing;
+    }
+    let cwd = cwd.as_ref();
+    exec_env_for(cwd).command("git", cwd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_wsl_and_network_roots() {
+        for path in [
+            r"\\wsl$\Ubuntu\home\me\proj",
+            r"\\wsl.localhost\Ubuntu\home\me\proj",
+            r"\\?\UNC\wsl$\Ubuntu\home\me\proj",
+            "//wsl$/Ubuntu/home/me/proj",
+        ] {
+            let env = ExecEnv::parse(path);
+            assert_eq!(env.kind(), "wsl", "{}", path);
+            assert_eq!(env.wsl_distro(), Some("Ubuntu"));
+            assert_eq!(env.to_exec_path(Path::new(path)), "/home/me/proj");
+        }
+
+        let env = ExecEnv::parse(r"\\?\UNC\nas\share\repo\");
+        assert_eq!(
+            env,
+            ExecEnv::NetworkShare {
+                unc_path: r"\\nas\share\repo".to_string()
+            }
+        );
+
+        for path in [
+            r"C:\Users\me\proj",
+            "/home/me/proj",
+            r"\\?\C:\proj",
+            r"\\nas",
+        ] {
+            assert_eq!(ExecEnv::parse(path), ExecEnv::Native, "{}", path);
+        }
+    }
+
+    #[test]
+    fn wsl_translates_drive_paths_and_other_distros_pass_through() {
+        let env = ExecEnv::parse(r"\\wsl$\Ubuntu\home\me\proj");
+        assert_eq!(
+            env.to_exec_path(Path::new(r"C:\Users\me\.tidyflow")),
+            "/mnt/c/Users/me/.tidyflow"
+        );
+        assert_eq!(env.to_exec_path(Path::new(r"\\wsl$\Ubuntu")), "/");
+        assert_eq!(
+            env.to_exec_path(Path::new(r"\\wsl$\Debian\srv")),
+            r"\\wsl$\Debian\srv"
+        );
+        assert_eq!(
+            ExecEnv::Native.to_exec_path(Path::new("/tmp/x")),
+            "/tmp/x".to_string()
+        );
+    }
+
+    #[test]
+    fn wsl_command_runs_inside_distro() {
+        let env = ExecEnv::parse(r"\\wsl$\Ubuntu\home\me\proj");
+        let cmd = env.command("git", Path::new(r"\\wsl$\Ubuntu\home\me\proj"));
+        assert_eq!(cmd.get_program(), "wsl.exe");
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_string_lossy()).collect();
+        assert_eq!(
+            args,
+            ["-d", "Ubuntu", "--cd", "/home/me/proj", "--exec", "git"]
+        );
+    }
+
+    #[test]
+    fn network_share_allows_safe_directory() {
+        assert_eq!(
+            safe_directory_value(r"\\nas\share\repo"),
+            "%(prefix)///nas/share/repo"
+        );
+        assert_eq!(safe_directory_value(r"Z:\repo"), "Z:/repo");
+        let cwd = Path::new(r"\\nas\share\repo");
+        let cmd = ExecEnv::parse(r"\\nas\share\repo").command("git", cwd);
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_string_lossy()).collect();
+        assert_eq!(args, ["-c", "safe.directory=%(prefix)///nas/share/repo"]);
+    }
+
+    #[test]
+    fn native_command_sets_cwd() {
+        let cmd = git_command(Path::new("/tmp"));
+        assert_eq!(cmd.get_program(), "git");
+        assert_eq!(cmd.get_current_dir(), Some(Path::new("/tmp")));
+    }
+}
@@ -36,6 +36,9 @@ struct LogRecord {
     session_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     cycle_id: Option<String>,
+    /// 请求追踪 ID（请求处理期间产生的 core 日志）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace_id: Option<String>,
 }
 
 /// 内部状态，由 Mutex 保护
@@ -89,6 +92,7 @@ impl FileLogger {
             workspace: None,
             session_id: None,
             cycle_id: None,
+            trace_id: crate::util::trace::current_trace_id(),
         };
         self.write_record(&record);
 
@@ -133,6 +137,7 @@ impl FileLogger {
             workspace: workspace.map(|s| s.to_string()),
             session_id: session_id.map(|s| s.to_string()),
             cycle_id: cycle_id.map(|s| s.to_string()),
+            trace_id: None,
         };
         self.write_record(&record);
     }
@@ -1,6 +1,11 @@
+pub mod cancel;
+pub mod chaos;
+pub mod exec_env;
 pub mod file_logger;
 pub mod log;
 pub mod paths;
 pub mod shell_launch;
+pub mod template;
+pub mod trace;
 
 pub use log::{flush_logs, init_logging};
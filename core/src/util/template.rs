@@ -0,0 +1,84 @@
+//! `{name}` 占位符模板的词法切分
+//!
+//! 分支命名模板（[`crate::workspace::branch_name`]）与提交信息模板
+//! （[`crate::server::git::commit_message`]）共用同一语法：`{` 与 `}` 成对出现，
+//! 占位符名须在调用方给定的集合内，其余文本原样保留。
+
+/// 模板片段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token<'a> {
+    Literal(&'a str),
+    Placeholder(&'a str),
+}
+
+/// 切分模板并校验占位符；`kind` 用于错误信息（如 `branch`、`commit`）
+pub fn tokenize<'a>(
+    template: &'a str,
+    kind: &str,
+    placeholders: &[&str],
+) -> Result<Vec<Token<'a>>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        if start > 0 {
+            tokens.push(Token::Literal(&rest[..start]));
+        }
+        let Some(len) = rest[start..].find('}') else {
+            return Err(format!(
+                "Unclosed placeholder in {} template: {}",
+                kind, template
+            ));
+        };
+        let name = &rest[start + 1..start + len];
+        if !placeholders.contains(&name) {
+            return Err(format!(
+                "Unknown placeholder {{{}}} in {} template (expected {})",
+                name,
+                kind,
+                expected_list(placeholders)
+            ));
+        }
+        tokens.push(Token::Placeholder(name));
+        rest = &rest[start + len + 1..];
+    }
+    if rest.contains('}') {
+        return Err(format!("Unmatched '}}' in {} template: {}", kind, template));
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Literal(rest));
+    }
+    Ok(tokens)
+}
+
+/// `a, b or c`
+fn expected_list(placeholders: &[&str]) -> String {
+    match placeholders.split_last() {
+        Some((last, [])) => last.to_string(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_splits_literals_and_known_placeholders() {
+        assert_eq!(
+            tokenize("feat/{user}-{date}", "branch", &["user", "date"]).unwrap(),
+            vec![
+                Token::Literal("feat/"),
+                Token::Placeholder("user"),
+                Token::Literal("-"),
+                Token::Placeholder("date"),
+            ]
+        );
+        assert_eq!(
+            tokenize("{x}", "commit", &["a", "b", "c"]).unwrap_err(),
+            "Unknown placeholder {x} in commit template (expected a, b or c)"
+        );
+        assert!(tokenize("{a", "commit", &["a"]).is_err());
+        assert!(tokenize("a}", "commit", &["a"]).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+//! 请求级追踪 ID（trace id）
+//!
+//! 每个 WS 请求与 HTTP 请求在进入时分配一个 trace id，并在其处理范围内：
+//! - 写入文件日志每一行的 `trace_id` 字段，stdout 日志经 `request` span 带出；
+//! - 经 [`TRACE_ID_ENV`] 传给 git 子进程与项目任务（见 [`apply_to_command`]）；
+//! - 回写到响应包络 / HTTP 错误体，客户端报错时附上即可在服务端日志中定位。
+//!
+//! 异步处理链路经 tokio task-local 传递；进入阻塞线程或后台任务时须使用本模块的
+//! [`spawn_blocking`] / [`spawn`]，否则 trace id 会丢失。
+
+use std::cell::RefCell;
+use std::future::Future;
+
+use tracing::Instrument;
+
+/// 传给子进程的环境变量名
+pub const TRACE_ID_ENV: &str = "TIDYFLOW_TRACE_ID";
+
+tokio::task_local! {
+    static TASK_TRACE_ID: String;
+}
+
+thread_local! {
+    static THREAD_TRACE_ID: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// 生成新的 trace id（16 位十六进制）
+pub fn new_trace_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..16].to_string()
+}
+
+/// 当前执行上下文的 trace id：阻塞线程上由 [`spawn_blocking`] 设置，异步任务中取 task-local
+pub fn current_trace_id() -> Option<String> {
+    THREAD_TRACE_ID
+        .with(|id| id.borrow().clone())
+        .or_else(|| TASK_TRACE_ID.try_with(|id| id.clone()).ok())
+}
+
+/// 在 trace id 作用域内执行 `fut`，并附加 `request` span
+pub async fn scope<F>(trace_id: String, action: &str, fut: F) -> F::Output
+where
+    F: Future,
+{
+    let span = tracing::info_span!("request", trace_id = %trace_id, action = %action);
+    TASK_TRACE_ID.scope(trace_id, fut.instrument(span)).await
+}
+
+/// 为子进程注入 [`TRACE_ID_ENV`]（当前无 trace id 时不做改动）
+pub fn apply_to_command(cmd: &mut std::process::Command) {
+    if let Some(id) = current_trace_id() {
+        cmd.env(TRACE_ID_ENV, id);
+    }
+}
+
+/// 带 trace id、取消令牌（见 [`crate::util::cancel`]）与当前 span 的 `tokio::task::spawn_blocking`
+pub fn spawn_blocking<F, R>(f: F) -> tokio::task::JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let trace_id = current_trace_id();
+    let cancel_token = crate::util::cancel::current();
+    let span = tracing::Span::current();
+    tokio::task::spawn_blocking(move || {
+        let _entered = span.enter();
+        let previous = THREAD_TRACE_ID.with(|id| id.replace(trace_id));
+        let result = crate::util::cancel::with_thread_token(cancel_token, f);
+        THREAD_TRACE_ID.with(|id| *id.borrow_mut() = previous);
+        result
+    })
+}
+
+/// 带 trace id 与当前 span 的 `tokio::spawn`
+pub fn spawn<F>(fut: F) -> tokio::task::JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let span = tracing::Span::current();
+    match current_trace_id() {
+        Some(trace_id) => tokio::spawn(TASK_TRACE_ID.scope(trace_id, fut.instrument(span))),
+        None => tokio::spawn(fut.instrument(span)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn trace_id_follows_blocking_and_spawned_tasks() {
+        assert_eq!(current_trace_id(), None);
+        scope("abc123".to_string(), "git_commit", async {
+            assert_eq!(current_trace_id().as_deref(), Some("abc123"));
+
+            let blocking = spawn_blocking(|| {
+                let mut cmd = std::process::Command::new("git");
+                apply_to_command(&mut cmd);
+                cmd.get_envs()
+                    .find(|(k, _)| *k == TRACE_ID_ENV)
+                    .and_then(|(_, v)| v.map(|v| v.to_string_lossy().to_string()))
+            })
+            .await
+            .unwrap();
+            assert_eq!(blocking.as_deref(), Some("abc123"));
+
+            let spawned = spawn(async { current_trace_id() }).await.unwrap();
+            assert_eq!(spawned.as_deref(), Some("abc123"));
+        })
+        .await;
+        assert_eq!(current_trace_id(), None);
+        assert_eq!(new_trace_id().len(), 16);
+    }
+}
@@ -0,0 +1,227 @@
+//! Workspace 分支命名模板
+//!
+//! `.tidyflow.toml` 的 `[git] branch_template` 决定新建 workspace 的分支名，
+//! 例如 `feat/{user}-{petname}`。支持的占位符：
+//! - `{petname}`：随机生成的两段 petname（如 `brave-otter`）
+//! - `{user}`：仓库 `git config user.name`（缺省时取系统用户名）
+//! - `{date}`：创建日期 `YYYYMMDD`
+//! - `{template}`：创建时选用的工作流模板 ID，未选用时为空
+//!
+//! 占位符取值会被规范化为小写 `a-z0-9-_`；渲染后为空的路径段会被去掉。
+//! workspace 名取分支名的最后一段。
+
+use crate::util::template::{self, Token};
+
+/// 未配置模板时的默认值，与历史行为一致
+pub const DEFAULT_BRANCH_TEMPLATE: &str = "tidy/{petname}";
+
+const PLACEHOLDERS: &[&str] = &["petname", "user", "date", "template"];
+
+/// 渲染模板所需的取值
+#[derive(Debug, Clone, Copy)]
+pub struct BranchNameContext<'a> {
+    pub petname: &'a str,
+    pub user: &'a str,
+    pub date: &'a str,
+    pub template: Option<&'a str>,
+}
+
+fn tokenize(template: &str) -> Result<Vec<Token<'_>>, String> {
+    template::tokenize(template, "branch", PLACEHOLDERS)
+}
+
+/// 校验模板语法（占位符合法、括号成对）
+pub fn validate_branch_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("Branch template is empty".to_string());
+    }
+    tokenize(template).map(|_| ())
+}
+
+/// 模板是否包含 `{petname}`；不包含时重名需靠数字后缀区分
+pub fn template_uses_petname(template: &str) -> bool {
+    template.contains("{petname}")
+}
+
+/// 将任意文本规范化为分支名片段：小写 ASCII 字母数字、`-`、`_`，其余字符折叠为 `-`
+pub fn slugify_branch_component(value: &str) -> String {
+    let mut out = String::new();
+    for c in value.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_matches('-').to_string()
+}
+
+/// 按模板渲染分支名并校验是否为合法的分支引用名
+pub fn render_branch_name(template: &str, ctx: &BranchNameContext<'_>) -> Result<String, String> {
+    let mut rendered = String::new();
+    for token in tokenize(template)? {
+        match token {
+            Token::Literal(text) => rendered.push_str(text),
+            Token::Placeholder(name) => {
+                let value = match name {
+                    "petname" => ctx.petname,
+                    "user" => ctx.user,
+                    "date" => ctx.date,
+                    _ => ctx.template.unwrap_or_default(),
+                };
+                rendered.push_str(&slugify_branch_component(value));
+            }
+        }
+    }
+
+    // 空占位符会留下 `//`、`--` 或首尾分隔符，按段清理
+    let branch = rendered
+        .split('/')
+        .map(|segment| {
+            let mut cleaned = String::new();
+            for c in segment.chars() {
+                if c == '-' && cleaned.ends_with('-') {
+                    continue;
+                }
+                cleaned.push(c);
+            }
+            cleaned.trim_matches('-').to_string()
+        })
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    if !is_valid_branch_name(&branch) {
+        return Err(format!(
+            "Branch template {} renders an invalid branch name: {:?}",
+            template, branch
+        ));
+    }
+    Ok(branch)
+}
+
+/// `git check-ref-format --branch` 的主要规则
+pub fn is_valid_branch_name(name: &str) -> bool {
+    if name.is_empty() || name == "@" || name.starts_with('-') {
+        return false;
+    }
+    if name.contains("..") || name.contains("@{") || name.ends_with('.') {
+        return false;
+    }
+    if name.chars().any(|c| {
+        c.is_ascii_control() || matches!(c, ' ' | '~' | '^' | ':' | '?' | '*' | '[' | '\\')
+    }) {
+        return false;
+    }
+    name.split('/').all(|segment| {
+        !segment.is_empty() && !segment.starts_with('.') && !segment.ends_with(".lock")
+    })
+}
+
+/// workspace 名（也是 worktree 目录名）取分支名的最后一段
+pub fn workspace_name_for_branch(branch: &str) -> &str {
+    branch.rsplit('/').next().unwrap_or(branch)
+}
+
+/// 从分支名推断关联的 issue 编号，用于提示用户关联 issue。
+///
+/// 识别 `feat/123-login`、`fix/issue-45`、`bugfix/gh_12-crash`、`issues/7`、`#9-typo`；
+/// 只接受 1–6 位且不以 0 开头的数字，避免把 `{date}` 等日期段当成 issue 编号。
+pub fn issue_number_from_branch(branch: &str) -> Option<u64> {
+    let parse = |token: &str| {
+        let digits = token.strip_prefix('#').unwrap_or(token);
+        (!digits.is_empty()
+            && digits.len() <= 6
+            && !digits.starts_with('0')
+            && digits.bytes().all(|b| b.is_ascii_digit()))
+        .then(|| digits.parse().ok())
+        .flatten()
+    };
+    for segment in branch.split('/') {
+        let tokens: Vec<&str> = segment.split(['-', '_']).collect();
+        if let Some(number) = tokens.first().and_then(|token| parse(token)) {
+            return Some(number);
+        }
+        for pair in tokens.windows(2) {
+            let keyword = pair[0].to_ascii_lowercase();
+            if matches!(keyword.as_str(), "issue" | "issues" | "gh" | "gl") {
+                if let Some(number) = parse(pair[1]) {
+                    return Some(number);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// 未关联 issue 时，按分支名给出的建议引用（如 `#123`）
+pub fn suggested_issue_reference(branch: &str, linked_issue: Option<&str>) -> Option<String> {
+    if linked_issue.is_some() {
+        return None;
+    }
+    issue_number_from_branch(branch).map(|number| format!("#{}", number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(template: Option<&'static str>) -> BranchNameContext<'static> {
+        BranchNameContext {
+            petname: "brave-otter",
+            user: "Alice Smith",
+            date: "20260102",
+            template,
+        }
+    }
+
+    #[test]
+    fn renders_placeholders() {
+        assert_eq!(
+            render_branch_name(DEFAULT_BRANCH_TEMPLATE, &ctx(None)).unwrap(),
+            "tidy/brave-otter"
+        );
+        assert_eq!(
+            render_branch_name("feat/{user}-{petname}", &ctx(None)).unwrap(),
+            "feat/alice-smith-brave-otter"
+        );
+        assert_eq!(
+            render_branch_name("{template}/{date}-{petname}", &ctx(Some("Bug Fix"))).unwrap(),
+            "bug-fix/20260102-brave-otter"
+        );
+        // 未选用模板时空段被去掉
+        assert_eq!(
+            render_branch_name("{template}/{user}--{petname}", &ctx(None)).unwrap(),
+            "alice-smith-brave-otter"
+        );
+        assert_eq!(
+            workspace_name_for_branch("feat/alice-smith-brave-otter"),
+            "alice-smith-brave-otter"
+        );
+    }
+
+    #[test]
+    fn infers_issue_number_from_branch() {
+        assert_eq!(issue_number_from_branch("feat/123-add-login"), Some(123));
+        assert_eq!(issue_number_from_branch("fix/issue-45"), Some(45));
+        assert_eq!(issue_number_from_branch("bugfix/GH_12-crash"), Some(12));
+        assert_eq!(issue_number_from_branch("issues/7"), Some(7));
+        assert_eq!(issue_number_from_branch("#9-typo"), Some(9));
+        assert_eq!(issue_number_from_branch("tidy/brave-otter"), None);
+        assert_eq!(issue_number_from_branch("feat/20260102-brave-otter"), None);
+        assert_eq!(issue_number_from_branch("release/v2-0"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_templates() {
+        assert!(validate_branch_template("feat/{owner}").is_err());
+        assert!(validate_branch_template("feat/{user").is_err());
+        assert!(validate_branch_template("feat/user}").is_err());
+        assert!(validate_branch_template(" ").is_err());
+        assert!(render_branch_name("feat:{petname}", &ctx(None)).is_err());
+        assert!(render_branch_name("{template}", &ctx(None)).is_err());
+        assert!(render_branch_name(".hidden/{petname}", &ctx(None)).is_err());
+        assert!(is_valid_branch_name("feat/alice"));
+        assert!(!is_valid_branch_name("feat/x.lock"));
+    }
+}
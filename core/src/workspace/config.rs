@@ -16,6 +16,9 @@ pub enum ConfigError {
     ParseError(String),
 }
 
+/// setup 步骤命令与环境变量中的端口占位符，替换为为工作区预留的端口
+pub const PORT_PLACEHOLDER: &str = "{{port}}";
+
 /// Project configuration from .tidyflow.toml
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ProjectConfig {
@@ -25,6 +28,12 @@ pub struct ProjectConfig {
     pub setup: SetupSection,
     #[serde(default)]
     pub env: EnvSection,
+    #[serde(default)]
+    pub git: GitSection,
+    #[serde(default)]
+    pub editor: EditorSection,
+    #[serde(default)]
+    pub warmup: WarmupSection,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -114,6 +123,144 @@ fn default_true() -> bool {
     true
 }
 
+/// `[git]` 段：项目级 Git 默认值
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GitSection {
+    /// 默认 diff 算法（myers / minimal / patience / histogram），请求未指定时使用
+    pub diff_algorithm: Option<String>,
+    /// blame 使用的 ignore-revs 文件（相对项目根）；未设置时探测 `.git-blame-ignore-revs`
+    pub ignore_revs_files: Option<Vec<String>>,
+    /// 新建 workspace 的分支命名模板（如 `feat/{user}-{petname}`），未设置时为 `tidy/{petname}`
+    pub branch_template: Option<String>,
+    /// 建议提交信息的模板（如 `{type}({scope}): {summary}`），未设置时为 `{issue} {type}: {summary}`
+    pub commit_template: Option<String>,
+    /// 生成提交信息的外部命令（argv，不经 shell）：stdin 为暂存区 diff，stdout 为提交信息
+    pub commit_message_command: Option<Vec<String>>,
+    /// 外部命令超时（秒），默认 30，上限 120
+    pub commit_message_timeout: Option<u64>,
+    /// 额外透传给外部命令的环境变量名（如 `OPENAI_API_KEY`）
+    pub commit_message_env: Option<Vec<String>>,
+    /// 生成提交信息的 HTTP 端点（如本地 LLM 服务），POST JSON 并读取返回的提交信息；
+    /// 与 `commit_message_command` 同时配置时以命令为准
+    pub commit_message_endpoint: Option<String>,
+    /// 端点鉴权令牌所在的环境变量名，取值作为 `Authorization: Bearer` 发送
+    pub commit_message_endpoint_token_env: Option<String>,
+    /// fetch 等网络操作使用的代理，覆盖全局设置；`direct` 表示直连
+    pub proxy: Option<String>,
+    /// git status 缓存 TTL（秒），默认 30，上限 600；0 关闭缓存
+    pub status_cache_ttl_secs: Option<u64>,
+    /// status / log / diff / 分支列表的实现：`gix`（默认，不支持时回退命令行）或 `cli`
+    pub backend: Option<String>,
+    /// 合并到默认分支后在集成 worktree 中运行的校验命令（经登录 shell 执行，如 `cargo test`）
+    pub merge_verify_command: Option<String>,
+    /// 校验命令超时（秒），默认 1800，上限 7200
+    pub merge_verify_timeout: Option<u64>,
+    /// 代码托管平台（`github` / `gitlab`），未设置时按 `origin` 主机名推断
+    pub hosting: Option<String>,
+}
+
+/// `[editor]` 段：编辑器相关的项目级行为
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EditorSection {
+    /// 通过 `file_write` 写入文本时按 `.editorconfig` 规范化内容（默认关闭）
+    #[serde(default)]
+    pub apply_editorconfig: bool,
+    /// `file_write` 写入文本时的换行符策略（默认 `preserve`）
+    #[serde(default)]
+    pub line_endings: LineEndingPolicy,
+    /// 文本文件整体读写的大小上限（字节），未设置时为 1MB；超出时可用预览模式只读打开
+    pub max_file_size: Option<u64>,
+}
+
+/// 写入时的换行符策略
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEndingPolicy {
+    /// 沿用磁盘上已有文件的换行符（新文件或混合换行时不改动）
+    #[default]
+    Preserve,
+    Lf,
+    Crlf,
+    /// 按 `.gitattributes` 的 `eol` 属性，未声明时退回 `preserve`
+    #[serde(alias = "auto-from-gitattributes")]
+    Auto,
+}
+
+/// `[warmup]` 段：Core 启动时预热的工作区
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmupSection {
+    /// 预热的工作区：`"all"`（default 与全部工作区）或名称列表；为空时不预热
+    #[serde(default)]
+    pub workspaces: WarmupWorkspaces,
+    /// 预先创建 integration worktree
+    #[serde(default)]
+    pub integration_worktree: bool,
+    /// 预先构建文件索引与 git 状态
+    #[serde(default = "default_true")]
+    pub indexes: bool,
+    /// 在每个预热工作区中启动的项目命令（id 或名称），如 dev server
+    #[serde(default)]
+    pub tasks: Vec<String>,
+}
+
+impl Default for WarmupSection {
+    fn default() -> Self {
+        Self {
+            workspaces: WarmupWorkspaces::default(),
+            integration_worktree: false,
+            indexes: default_true(),
+            tasks: Vec::new(),
+        }
+    }
+}
+
+/// 预热目标：`workspaces = "all"` 或 `workspaces = ["default", "feature-x"]`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum WarmupWorkspaces {
+    One(String),
+    Names(Vec<String>),
+}
+
+impl Default for WarmupWorkspaces {
+    fn default() -> Self {
+        Self::Names(Vec::new())
+    }
+}
+
+impl WarmupSection {
+    /// 是否配置了预热
+    pub fn is_enabled(&self) -> bool {
+        match &self.workspaces {
+            WarmupWorkspaces::One(name) => !name.trim().is_empty(),
+            WarmupWorkspaces::Names(names) => !names.is_empty(),
+        }
+    }
+
+    /// 按配置从 `available`（含 default）中选出预热的工作区，保持 `available` 的顺序；
+    /// 返回 `(选中的工作区, 不存在的名称)`
+    pub fn resolve_workspaces(&self, available: &[String]) -> (Vec<String>, Vec<String>) {
+        let names = match &self.workspaces {
+            WarmupWorkspaces::One(name) => std::slice::from_ref(name),
+            WarmupWorkspaces::Names(names) => names.as_slice(),
+        };
+        if names.iter().any(|name| name == "all") {
+            return (available.to_vec(), Vec::new());
+        }
+        let selected = available
+            .iter()
+            .filter(|name| names.contains(name))
+            .cloned()
+            .collect();
+        let missing = names
+            .iter()
+            .filter(|name| !available.contains(name))
+            .cloned()
+            .collect();
+        (selected, missing)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PathConfig {
     #[serde(default)]
@@ -142,6 +289,16 @@ impl ProjectConfig {
             .clone()
             .unwrap_or_else(|| fallback.to_string())
     }
+
+    /// `[env.vars]` 或任一 setup 步骤的命令 / 环境变量是否引用了 `{{port}}`
+    pub fn uses_port_placeholder(&self) -> bool {
+        let env_uses = self.env.vars.values().any(|v| v.contains(PORT_PLACEHOLDER));
+        env_uses
+            || self.setup.steps.iter().any(|step| {
+                step.run.contains(PORT_PLACEHOLDER)
+                    || step.env.values().any(|v| v.contains(PORT_PLACEHOLDER))
+            })
+    }
 }
 
 /// Check if a condition is satisfied
@@ -186,6 +343,116 @@ mod tests {
         assert_eq!(config.project.default_branch, "main");
         assert_eq!(config.setup.timeout, 600);
         assert!(config.env.inherit);
+        assert!(config.git.diff_algorithm.is_none());
+        assert!(config.git.branch_template.is_none());
+        assert!(!config.editor.apply_editorconfig);
+        assert_eq!(config.editor.line_endings, LineEndingPolicy::Preserve);
+        assert!(config.editor.max_file_size.is_none());
+        assert!(!config.warmup.is_enabled());
+        assert!(config.warmup.indexes);
+    }
+
+    #[test]
+    fn test_parse_warmup_section() {
+        let available = vec![
+            "default".to_string(),
+            "feature-a".to_string(),
+            "feature-b".to_string(),
+        ];
+
+        let config: ProjectConfig = toml::from_str(
+            "[warmup]\nworkspaces = \"all\"\nintegration_worktree = true\ntasks = [\"dev\"]\n",
+        )
+        .unwrap();
+        assert!(config.warmup.is_enabled());
+        assert!(config.warmup.integration_worktree);
+        assert!(config.warmup.indexes);
+        assert_eq!(config.warmup.tasks, vec!["dev".to_string()]);
+        assert_eq!(config.warmup.resolve_workspaces(&available).0, available);
+
+        let config: ProjectConfig = toml::from_str(
+            "[warmup]\nworkspaces = [\"feature-b\", \"gone\", \"default\"]\nindexes = false\n",
+        )
+        .unwrap();
+        assert!(!config.warmup.indexes);
+        let (selected, missing) = config.warmup.resolve_workspaces(&available);
+        assert_eq!(
+            selected,
+            vec!["default".to_string(), "feature-b".to_string()]
+        );
+        assert_eq!(missing, vec!["gone".to_string()]);
+
+        // 单个名称字符串也可作为目标
+        let config: ProjectConfig =
+            toml::from_str("[warmup]\nworkspaces = \"feature-a\"\n").unwrap();
+        assert!(config.warmup.is_enabled());
+        assert_eq!(
+            config.warmup.resolve_workspaces(&available).0,
+            vec!["feature-a".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parse_git_section() {
+        let toml_str = r#"
+[git]
+diff_algorithm = "histogram"
+ignore_revs_files = [".git-blame-ignore-revs", "tools/fmt-revs"]
+branch_template = "feat/{user}-{petname}"
+commit_template = "{type}({scope}): {summary}"
+commit_message_command = ["llm", "-s", "Write a commit message"]
+commit_message_env = ["OPENAI_API_KEY"]
+commit_message_endpoint = "http://127.0.0.1:8080/commit-message"
+"#;
+        let config: ProjectConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.git.diff_algorithm.as_deref(), Some("histogram"));
+        assert_eq!(
+            config.git.ignore_revs_files,
+            Some(vec![
+                ".git-blame-ignore-revs".to_string(),
+                "tools/fmt-revs".to_string()
+            ])
+        );
+        assert_eq!(
+            config.git.branch_template.as_deref(),
+            Some("feat/{user}-{petname}")
+        );
+        assert_eq!(
+            config.git.commit_template.as_deref(),
+            Some("{type}({scope}): {summary}")
+        );
+        assert_eq!(
+            config.git.commit_message_command.as_deref(),
+            Some(
+                &[
+                    "llm".to_string(),
+                    "-s".to_string(),
+                    "Write a commit message".to_string()
+                ][..]
+            )
+        );
+        assert_eq!(
+            config.git.commit_message_env,
+            Some(vec!["OPENAI_API_KEY".to_string()])
+        );
+        assert!(config.git.commit_message_timeout.is_none());
+        assert_eq!(
+            config.git.commit_message_endpoint.as_deref(),
+            Some("http://127.0.0.1:8080/commit-message")
+        );
+        assert_eq!(config.project.default_branch, "main");
+
+        let config: ProjectConfig =
+            toml::from_str("[editor]\napply_editorconfig = true\n").unwrap();
+        assert!(config.editor.apply_editorconfig);
+
+        let config: ProjectConfig =
+            toml::from_str("[editor]\nline_endings = \"auto-from-gitattributes\"\n").unwrap();
+        assert_eq!(config.editor.line_endings, LineEndingPolicy::Auto);
+        assert!(toml::from_str::<ProjectConfig>("[editor]\nline_endings = \"cr\"\n").is_err());
+
+        let config: ProjectConfig = toml::from_str("[editor]\nmax_file_size = 4194304\n").unwrap();
+        assert_eq!(config.editor.max_file_size, Some(4_194_304));
     }
 
     #[test]
@@ -210,6 +477,22 @@ condition = "file_exists:package.json"
         assert_eq!(config.setup.steps[0].name, "Install deps");
     }
 
+    #[test]
+    fn test_uses_port_placeholder() {
+        let config: ProjectConfig =
+            toml::from_str("[[setup.steps]]\nname = \"deps\"\nrun = \"npm install\"\n").unwrap();
+        assert!(!config.uses_port_placeholder());
+
+        let config: ProjectConfig = toml::from_str(
+            "[[setup.steps]]\nname = \"env\"\nrun = \"echo PORT={{port}} > .env\"\n",
+        )
+        .unwrap();
+        assert!(config.uses_port_placeholder());
+
+        let config: ProjectConfig = toml::from_str("[env.vars]\nPORT = \"{{port}}\"\n").unwrap();
+        assert!(config.uses_port_placeholder());
+    }
+
     #[test]
     fn test_load_missing_config_returns_default() {
         let temp_dir = TempDir::new().unwrap();
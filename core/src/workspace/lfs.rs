@@ -0,0 +1,140 @@
+//! Git LFS：检测仓库是否使用 LFS，并在新建的 worktree 中拉取 LFS 对象
+//!
+//! 创建 worktree 时若 smudge 过滤器未生效（未安装 git-lfs 或被跳过），大文件只会检出为
+//! 指针文件。使用 LFS 的仓库在检出时跳过 smudge，随后单独执行 `git lfs pull`，
+//! 以便按 `Downloading LFS objects: 45% (9/20)` 推送下载进度。
+
+use std::path::Path;
+use std::process::Stdio;
+
+use crate::util::exec_env::git_command;
+use crate::workspace::project::{stream_git_progress, CloneProgress};
+
+/// `.gitattributes` 中启用 LFS 的标记
+const LFS_FILTER_MARKER: &str = "filter=lfs";
+
+/// `rev` 中任一 `.gitattributes`（含子目录）是否声明了 `filter=lfs`
+pub fn uses_lfs(repo_root: &Path, rev: &str) -> bool {
+    git_command(repo_root)
+        .args(["grep", "-q", "-F", LFS_FILTER_MARKER, rev, "--"])
+        .args([".gitattributes", "*/.gitattributes"])
+        .stdin(Stdio::null())
+        .output()
+        .is_ok_and(|out| out.status.success())
+}
+
+/// 是否安装了 git-lfs
+pub fn lfs_available(repo_root: &Path) -> bool {
+    git_command(repo_root)
+        .args(["lfs", "version"])
+        .stdin(Stdio::null())
+        .output()
+        .is_ok_and(|out| out.status.success())
+}
+
+/// 在 worktree 中安装 LFS 钩子并拉取当前检出所需的 LFS 对象，逐行回调下载进度
+///
+/// 不会交互式询问凭据（`GIT_TERMINAL_PROMPT=0`）；离线模式下直接返回错误。
+pub fn pull_lfs_objects(
+    worktree: &Path,
+    mut on_progress: impl FnMut(CloneProgress),
+) -> Result<(), String> {
+    if crate::server::git::git_network_settings().offline_mode {
+        return Err("Offline mode is enabled, git lfs pull skipped".to_string());
+    }
+
+    let install = git_command(worktree)
+        .args(["lfs", "install", "--local"])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !install.status.success() {
+        return Err(format!(
+            "git lfs install failed: {}",
+            String::from_utf8_lossy(&install.stderr).trim()
+        ));
+    }
+
+    let mut cmd = git_command(worktree);
+    cmd.args(["lfs", "pull"])
+        // stderr 不是终端时 git-lfs 默认不输出进度
+        .env("GIT_LFS_FORCE_PROGRESS", "1")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    crate::server::git::apply_git_proxy(&mut cmd, Some(worktree));
+    let mut child = cmd.spawn().map_err(|e| e.to_string())?;
+    let stderr_text = child
+        .stderr
+        .take()
+        .map(|stderr| stream_git_progress(stderr, &mut on_progress))
+        .unwrap_or_default();
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if !status.success() {
+        let errors: Vec<&str> = stderr_text
+            .lines()
+            .filter(|line| !line.contains('%'))
+            .collect();
+        return Err(if errors.is_empty() {
+            format!("git lfs pull exited with {}", status)
+        } else {
+            errors.join("\n")
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::project::parse_clone_progress;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let out = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+
+    #[test]
+    fn detects_lfs_attributes_in_revision() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git(root, &["init", "-q", "-b", "main"]);
+        git(root, &["config", "user.name", "T"]);
+        git(root, &["config", "user.email", "t@example.com"]);
+        git(root, &["config", "commit.gpgsign", "false"]);
+        std::fs::write(root.join(".gitattributes"), "*.txt text\n").unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-q", "-m", "init"]);
+        assert!(!uses_lfs(root, "main"));
+
+        std::fs::create_dir_all(root.join("assets")).unwrap();
+        std::fs::write(
+            root.join("assets/.gitattributes"),
+            "*.psd filter=lfs diff=lfs merge=lfs -text\n",
+        )
+        .unwrap();
+        // 仅在工作区中、未提交时不算
+        assert!(!uses_lfs(root, "main"));
+        git(root, &["add", "."]);
+        git(root, &["commit", "-q", "-m", "lfs"]);
+        assert!(uses_lfs(root, "main"));
+        assert!(!uses_lfs(root, "main~1"));
+    }
+
+    #[test]
+    fn parses_lfs_download_progress() {
+        let p = parse_clone_progress("Downloading LFS objects:  45% (9/20), 1.2 MB | 3.4 MB/s")
+            .unwrap();
+        assert_eq!(p.phase, "downloading_lfs_objects");
+        assert_eq!(p.percent, Some(45));
+    }
+}
@@ -0,0 +1,241 @@
+//! 声明式工作区清单（workspace manifest）
+//!
+//! 清单以 TOML 或 JSON 描述项目期望存在的工作区：
+//!
+//! ```toml
+//! [[workspaces]]
+//! name = "api"
+//! base = "main"            # 源分支，缺省为项目默认分支
+//! branch = "feat/api"      # 可选；缺省按分支模板渲染
+//! tasks = ["dev"]          # 工作区就绪后以托管进程启动的任务
+//!
+//! [workspaces.env]
+//! PORT = "3001"
+//! ```
+//!
+//! 本模块只负责解析与对比，实际创建与任务启动由 `application::workspace_manifest` 完成。
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::workspace::branch_name::is_valid_branch_name;
+use crate::workspace::state::DEFAULT_WORKSPACE_NAME;
+
+/// 单个清单可声明的工作区上限
+pub const MAX_MANIFEST_WORKSPACES: usize = 64;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ManifestError {
+    #[error("Failed to parse manifest: {0}")]
+    Parse(String),
+    #[error("Manifest declares no workspaces")]
+    Empty,
+    #[error("Manifest declares too many workspaces (max {MAX_MANIFEST_WORKSPACES})")]
+    TooMany,
+    #[error("Invalid workspace name in manifest: {0}")]
+    InvalidName(String),
+    #[error("Duplicate workspace in manifest: {0}")]
+    DuplicateName(String),
+    #[error("Invalid branch name in manifest: {0}")]
+    InvalidBranch(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkspaceManifest {
+    #[serde(default)]
+    pub workspaces: Vec<ManifestWorkspace>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ManifestWorkspace {
+    pub name: String,
+    /// 新建时的源分支；缺省为项目默认分支
+    #[serde(default)]
+    pub base: Option<String>,
+    /// 工作区分支；缺省按 `[git] branch_template` 以工作区名渲染
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// 启动任务时追加的环境变量
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// 项目命令（id 或名称）或识别出的工作区任务 id（如 `npm:dev`）
+    #[serde(default)]
+    pub tasks: Vec<String>,
+}
+
+/// 清单与现有工作区的对比结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum ManifestAction<'a> {
+    /// 工作区不存在，需要创建
+    Create(&'a ManifestWorkspace),
+    /// 工作区已存在；`branch` 为其实际分支
+    Keep {
+        workspace: &'a ManifestWorkspace,
+        branch: String,
+    },
+}
+
+impl ManifestWorkspace {
+    /// 声明了分支且与实际分支不一致时返回声明的分支
+    pub fn branch_drift(&self, actual: &str) -> Option<&str> {
+        self.branch
+            .as_deref()
+            .filter(|declared| *declared != actual)
+    }
+}
+
+impl WorkspaceManifest {
+    /// 解析清单文本：以 `{` 开头按 JSON，否则按 TOML
+    pub fn parse(text: &str) -> Result<Self, ManifestError> {
+        let manifest: Self = if text.trim_start().starts_with('{') {
+            serde_json::from_str(text).map_err(|e| ManifestError::Parse(e.to_string()))?
+        } else {
+            toml::from_str(text).map_err(|e| ManifestError::Parse(e.to_string()))?
+        };
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    fn validate(&self) -> Result<(), ManifestError> {
+        if self.workspaces.is_empty() {
+            return Err(ManifestError::Empty);
+        }
+        if self.workspaces.len() > MAX_MANIFEST_WORKSPACES {
+            return Err(ManifestError::TooMany);
+        }
+        let mut seen = HashSet::new();
+        for ws in &self.workspaces {
+            let name = ws.name.as_str();
+            if name == DEFAULT_WORKSPACE_NAME || name.contains('/') || !is_valid_branch_name(name) {
+                return Err(ManifestError::InvalidName(ws.name.clone()));
+            }
+            if !seen.insert(name) {
+                return Err(ManifestError::DuplicateName(ws.name.clone()));
+            }
+            for branch in [&ws.branch, &ws.base].into_iter().flatten() {
+                if !is_valid_branch_name(branch) {
+                    return Err(ManifestError::InvalidBranch(branch.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 按清单顺序给出每个声明工作区的动作；`existing` 为现有工作区的 `(名称, 分支)`
+    pub fn plan<'a>(&'a self, existing: &[(String, String)]) -> Vec<ManifestAction<'a>> {
+        self.workspaces
+            .iter()
+            .map(
+                |ws| match existing.iter().find(|(name, _)| *name == ws.name) {
+                    Some((_, branch)) => ManifestAction::Keep {
+                        workspace: ws,
+                        branch: branch.clone(),
+                    },
+                    None => ManifestAction::Create(ws),
+                },
+            )
+            .collect()
+    }
+
+    /// 现有但未在清单中声明的工作区（只标记，不删除）
+    pub fn extras(&self, existing: &[(String, String)]) -> Vec<String> {
+        existing
+            .iter()
+            .filter(|(name, _)| !self.workspaces.iter().any(|ws| ws.name == *name))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_toml_and_json_manifests() {
+        let toml_manifest = WorkspaceManifest::parse(
+            r#"
+[[workspaces]]
+name = "api"
+base = "main"
+tasks = ["dev"]
+
+[workspaces.env]
+PORT = "3001"
+
+[[workspaces]]
+name = "web"
+branch = "feat/web"
+"#,
+        )
+        .unwrap();
+        assert_eq!(toml_manifest.workspaces.len(), 2);
+        assert_eq!(toml_manifest.workspaces[0].base.as_deref(), Some("main"));
+        assert_eq!(
+            toml_manifest.workspaces[0]
+                .env
+                .get("PORT")
+                .map(String::as_str),
+            Some("3001")
+        );
+
+        let json_manifest = WorkspaceManifest::parse(
+            r#"{"workspaces":[{"name":"api","base":"main","tasks":["dev"],"env":{"PORT":"3001"}},{"name":"web","branch":"feat/web"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(json_manifest, toml_manifest);
+    }
+
+    #[test]
+    fn invalid_manifests_are_rejected() {
+        assert_eq!(
+            WorkspaceManifest::parse("workspaces = []"),
+            Err(ManifestError::Empty)
+        );
+        assert_eq!(
+            WorkspaceManifest::parse(r#"{"workspaces":[{"name":"default"}]}"#),
+            Err(ManifestError::InvalidName("default".to_string()))
+        );
+        assert_eq!(
+            WorkspaceManifest::parse(r#"{"workspaces":[{"name":"a"},{"name":"a"}]}"#),
+            Err(ManifestError::DuplicateName("a".to_string()))
+        );
+        assert_eq!(
+            WorkspaceManifest::parse(r#"{"workspaces":[{"name":"a","branch":"bad name"}]}"#),
+            Err(ManifestError::InvalidBranch("bad name".to_string()))
+        );
+        assert!(matches!(
+            WorkspaceManifest::parse("[[workspaces]]\nname = 1"),
+            Err(ManifestError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn plan_reports_missing_existing_and_extras() {
+        let manifest = WorkspaceManifest::parse(
+            r#"{"workspaces":[{"name":"api","branch":"feat/api"},{"name":"web"}]}"#,
+        )
+        .unwrap();
+        let existing = vec![
+            ("api".to_string(), "tidy/api".to_string()),
+            ("old".to_string(), "tidy/old".to_string()),
+        ];
+        let plan = manifest.plan(&existing);
+        assert_eq!(
+            plan[0],
+            ManifestAction::Keep {
+                workspace: &manifest.workspaces[0],
+                branch: "tidy/api".to_string(),
+            }
+        );
+        assert_eq!(
+            manifest.workspaces[0].branch_drift("tidy/api"),
+            Some("feat/api")
+        );
+        assert_eq!(manifest.workspaces[0].branch_drift("feat/api"), None);
+        assert_eq!(plan[1], ManifestAction::Create(&manifest.workspaces[1]));
+        assert_eq!(manifest.extras(&existing), vec!["old".to_string()]);
+    }
+}
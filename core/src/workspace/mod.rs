@@ -2,16 +2,25 @@
 //!
 //! This module provides:
 //! - Project import (local path or git clone)
-//! - Workspace creation using git worktree
+//! - Workspace creation using git worktree (with Git LFS objects pulled when needed)
+//! - Sparse-checkout (cone mode) workspaces for large monorepos
 //! - Setup step execution from project config
 //! - State persistence
 
+pub mod branch_name;
 pub mod cache_metrics;
 pub mod config;
+pub mod lfs;
+pub mod manifest;
 pub mod project;
+pub mod quarantine;
 pub mod setup;
+pub mod sparse;
 pub(crate) mod sqlite_store;
 pub mod state;
+pub mod state_backend;
+pub mod state_backups;
+pub mod state_hydrator;
 pub mod state_saver;
 pub mod state_store;
 pub mod workspace;
@@ -21,8 +30,11 @@ pub use project::ProjectManager;
 pub use setup::{SetupExecutor, SetupResult, StepResult};
 pub use state::{
     normalize_repo_coordination_key, AppState, NodeAuthTokenEntry, NodeDiscoverySettings,
-    NodeIdentity, PairedNodeEntry, Project, RemoteAPIKeyEntry, Workspace, WorkspaceStatus,
+    NodeIdentity, PairedNodeEntry, Project, ProjectLoadStatus, RemoteAPIKeyEntry, Workspace,
+    WorkspaceStatus,
 };
+pub use state_backend::{JsonStateStore, StateBackend, StateBackendKind};
+pub use state_hydrator::{ensure_project_hydrated, spawn_project_hydrator};
 pub use state_saver::spawn_state_saver;
 pub use state_store::StateStore;
-pub use workspace::WorkspaceManager;
+pub use workspace::{WorkspaceManager, WorkspaceRepair};
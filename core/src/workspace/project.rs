@@ -1,11 +1,12 @@
 //! Project management - import from local path or git clone
 
+use crate::util::exec_env::{exec_env_for, git_command};
 use crate::workspace::config::ProjectConfig;
 use crate::workspace::state::{AppState, Project, StateError};
 use chrono::Utc;
 use std::collections::HashMap;
-use std::path::Path;
-use std::process::Command;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 use tracing::{info, warn};
 
@@ -23,6 +24,102 @@ pub enum ProjectError {
     StateError(#[from] StateError),
     #[error("IO error: {0}")]
     IoError(String),
+    #[error("Offline mode is enabled, git clone skipped")]
+    OfflineMode,
+    #[error("Invalid branch name: {0}")]
+    InvalidBranch(String),
+    #[error("Branch not found: {0}")]
+    BranchNotFound(String),
+    #[error("Clone cancelled")]
+    Cancelled,
+}
+
+/// `git clone --progress` 等 git 命令的一条进度
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloneProgress {
+    /// 阶段（如 `receiving_objects`、`resolving_deltas`、`rebasing`），无法识别时为 `other`
+    pub phase: String,
+    /// 当前阶段百分比
+    pub percent: Option<u8>,
+    /// 原始进度行（已去掉 `remote: ` 前缀）
+    pub message: String,
+}
+
+/// 解析 git 的 stderr 进度行（`Receiving objects:  45% (45/100), ...`、`Rebasing (2/5)`）
+pub fn parse_clone_progress(line: &str) -> Option<CloneProgress> {
+    let line = line.trim();
+    // 行首可能带终端清行序列（`ESC[K`）
+    let line = line.strip_prefix("\u{1b}[K").unwrap_or(line);
+    let line = line.strip_prefix("remote:").unwrap_or(line).trim();
+    if line.is_empty() {
+        return None;
+    }
+    if let Some(steps) = line
+        .strip_prefix("Rebasing (")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        let percent = steps.split_once('/').and_then(|(done, total)| {
+            let (done, total) = (done.parse::<u32>().ok()?, total.parse::<u32>().ok()?);
+            (total > 0).then(|| (done.min(total) * 100 / total) as u8)
+        });
+        return Some(CloneProgress {
+            phase: "rebasing".to_string(),
+            percent,
+            message: line.to_string(),
+        });
+    }
+    let (phase, rest) = match line.split_once(':') {
+        Some((phase, rest)) if phase.chars().all(|c| c.is_ascii_alphabetic() || c == ' ') => {
+            (phase.trim().to_ascii_lowercase().replace(' ', "_"), rest)
+        }
+        _ => ("other".to_string(), ""),
+    };
+    let percent = rest
+        .split_once('%')
+        .and_then(|(number, _)| number.trim().parse::<u8>().ok())
+        .filter(|p| *p <= 100);
+    Some(CloneProgress {
+        phase,
+        percent,
+        message: line.to_string(),
+    })
+}
+
+/// 逐行解析 git 的 stderr 进度并回调，返回以 `\n` 结束的完整行（用于错误信息）
+///
+/// 进度行以 `\r` 刷新、阶段结束以 `\n` 换行，两者都视为行尾。
+pub(crate) fn stream_git_progress(
+    mut stderr: impl Read,
+    on_progress: &mut impl FnMut(CloneProgress),
+) -> String {
+    let mut stderr_text = String::new();
+    let mut pending = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = match stderr.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        for &byte in &buf[..n] {
+            if byte == b'\r' || byte == b'\n' {
+                let line = String::from_utf8_lossy(&pending).into_owned();
+                pending.clear();
+                if let Some(progress) = parse_clone_progress(&line) {
+                    on_progress(progress);
+                }
+                if byte == b'\n' && !line.trim().is_empty() {
+                    stderr_text.push_str(line.trim());
+                    stderr_text.push('\n');
+                }
+            } else {
+                pending.push(byte);
+            }
+        }
+    }
+    if !pending.is_empty() {
+        stderr_text.push_str(String::from_utf8_lossy(&pending).trim());
+    }
+    stderr_text
 }
 
 pub struct ProjectManager;
@@ -39,6 +136,10 @@ impl ProjectManager {
             return Err(ProjectError::AlreadyExists(name.to_string()));
         }
 
+        if crate::server::file_api::provider::is_remote_root(path) {
+            return Self::import_remote(state, name, path);
+        }
+
         // Validate path exists
         let abs_path = path
             .canonicalize()
@@ -70,12 +171,58 @@ impl ProjectManager {
         Ok(project)
     }
 
+    /// 导入远程（SSH/SFTP）项目：根目录保存 `ssh://` URL 原文，git 信息经 ssh 在远端读取
+    fn import_remote(
+        state: &mut AppState,
+        name: &str,
+        root: &Path,
+    ) -> Result<Project, ProjectError> {
+        let provider = crate::server::file_api::provider::provider_for_root(root)
+            .map_err(|e| ProjectError::IoError(format!("{}: {}", root.display(), e)))?;
+        let git_line = |args: &[&str]| {
+            provider
+                .git_output(args)
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+                .filter(|line| !line.is_empty())
+        };
+
+        if git_line(&["rev-parse", "--is-inside-work-tree"]).as_deref() != Some("true") {
+            return Err(ProjectError::NotGitRepo(root.display().to_string()));
+        }
+        let default_branch = git_line(&["symbolic-ref", "refs/remotes/origin/HEAD", "--short"])
+            .map(|branch| branch.strip_prefix("origin/").unwrap_or("main").to_string())
+            .or_else(|| git_line(&["branch", "--show-current"]))
+            .unwrap_or_else(|| ProjectConfig::default().project.default_branch);
+
+        let project = Project {
+            name: name.to_string(),
+            root_path: root.to_path_buf(),
+            remote_url: git_line(&["remote", "get-url", "origin"]),
+            default_branch,
+            created_at: Utc::now(),
+            workspaces: HashMap::new(),
+            commands: Vec::new(),
+        };
+
+        state.add_project(project.clone());
+
+        info!(
+            project = name,
+            kind = provider.kind(),
+            "Remote project imported"
+        );
+        Ok(project)
+    }
+
     /// Import a project by cloning from a git URL
     pub fn import_git(
         state: &mut AppState,
         name: &str,
         url: &str,
         branch: Option<&str>,
+        depth: Option<u32>,
         target_dir: Option<&Path>,
     ) -> Result<Project, ProjectError> {
         // Check if project already exists
@@ -91,39 +238,130 @@ impl ProjectManager {
         });
 
         let clone_path = base_dir.join(name);
+        Self::clone_repository(url, branch, depth, &clone_path, |_| {})?;
+        info!(project = name, url = url, "Repository cloned successfully");
 
-        // Create parent directory if needed
-        std::fs::create_dir_all(&base_dir).map_err(|e| ProjectError::IoError(e.to_string()))?;
+        // Now import as local
+        Self::import_local(state, name, &clone_path)
+    }
 
-        // Clone the repository
-        let mut cmd = Command::new("git");
-        cmd.arg("clone");
+    /// 托管克隆目录：`<TIDYFLOW_HOME>/repos/<项目名 slug>`，已存在时追加 `-2`、`-3`…
+    pub fn managed_clone_dir(name: &str) -> PathBuf {
+        let base = crate::util::paths::tidyflow_home_dir().join("repos");
+        let slug = crate::workspace::branch_name::slugify_branch_component(name);
+        let slug = if slug.is_empty() {
+            "project".to_string()
+        } else {
+            slug
+        };
+        let mut candidate = base.join(&slug);
+        let mut suffix = 2;
+        while candidate.exists() {
+            candidate = base.join(format!("{}-{}", slug, suffix));
+            suffix += 1;
+        }
+        candidate
+    }
+
+    /// 将 `url` 克隆到 `clone_path`（须不存在），逐行回调进度；失败时清理已创建的目录
+    ///
+    /// 不会交互式询问凭据（`GIT_TERMINAL_PROMPT=0`），需预先配置 credential helper 或 SSH key。
+    pub fn clone_repository(
+        url: &str,
+        branch: Option<&str>,
+        depth: Option<u32>,
+        clone_path: &Path,
+        mut on_progress: impl FnMut(CloneProgress),
+    ) -> Result<(), ProjectError> {
+        if crate::server::git::git_network_settings().offline_mode {
+            return Err(ProjectError::OfflineMode);
+        }
+        let url = url.trim();
+        if url.is_empty() || url.starts_with('-') {
+            return Err(ProjectError::GitError(format!(
+                "Invalid repository URL: {}",
+                url
+            )));
+        }
+        if branch.is_some_and(|b| b.starts_with('-')) {
+            return Err(ProjectError::GitError(format!(
+                "Invalid branch: {}",
+                branch.unwrap_or_default()
+            )));
+        }
+        if clone_path.exists() {
+            return Err(ProjectError::IoError(format!(
+                "Target directory already exists: {}",
+                clone_path.display()
+            )));
+        }
+        let parent = clone_path
+            .parent()
+            .ok_or_else(|| ProjectError::IoError(clone_path.display().to_string()))?;
+        std::fs::create_dir_all(parent).map_err(|e| ProjectError::IoError(e.to_string()))?;
+
+        let env = exec_env_for(parent);
+        let mut cmd = env.command("git", parent);
+        cmd.args(["-c", "protocol.ext.allow=never", "clone", "--progress"]);
         if let Some(b) = branch {
             cmd.arg("--branch").arg(b);
         }
-        cmd.arg(url).arg(&clone_path);
-
-        let output = cmd
-            .output()
-            .map_err(|e| ProjectError::GitError(e.to_string()))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(ProjectError::GitError(stderr.to_string()));
+        if let Some(depth) = depth.filter(|d| *d > 0) {
+            cmd.arg("--depth").arg(depth.to_string());
         }
-
-        info!(project = name, url = url, "Repository cloned successfully");
-
-        // Now import as local
-        Self::import_local(state, name, &clone_path)
+        cmd.arg("--")
+            .arg(url)
+            .arg(env.to_exec_path(clone_path))
+            .env("GIT_TERMINAL_PROMPT", "0");
+        crate::server::git::apply_git_proxy(&mut cmd, None);
+
+        // 取消（`git_op_cancel`）或失败时不保留半成品目录
+        let remove_partial_clone = || {
+            if clone_path.exists() {
+                if let Err(e) = std::fs::remove_dir_all(clone_path) {
+                    warn!(path = %clone_path.display(), error = %e, "Failed to clean up partial clone");
+                }
+            }
+        };
+        let (status, stderr_text) =
+            match crate::server::git::run_git_streaming(&mut cmd, &mut on_progress) {
+                Ok(result) => result,
+                Err(crate::server::git::GitError::IoError(e))
+                    if crate::util::cancel::is_cancelled_error(&e) =>
+                {
+                    remove_partial_clone();
+                    return Err(ProjectError::Cancelled);
+                }
+                Err(crate::server::git::GitError::IoError(e)) => {
+                    return Err(ProjectError::GitError(e.to_string()));
+                }
+                Err(e) => return Err(ProjectError::GitError(e.to_string())),
+            };
+        if !status.success() {
+            remove_partial_clone();
+            // 只保留真正的错误行，去掉进度噪音
+            let errors: Vec<&str> = stderr_text
+                .lines()
+                .filter(|line| {
+                    !line.starts_with("Cloning into")
+                        && !line.contains('%')
+                        && !line.ends_with(", done.")
+                })
+                .collect();
+            return Err(ProjectError::GitError(if errors.is_empty() {
+                format!("git clone exited with {}", status)
+            } else {
+                errors.join("\n")
+            }));
+        }
+        Ok(())
     }
 
     /// Get the default branch from git
     fn get_default_branch(repo_path: &Path) -> Option<String> {
         // Try to get from remote HEAD
-        let output = Command::new("git")
+        let output = git_command(repo_path)
             .args(["symbolic-ref", "refs/remotes/origin/HEAD", "--short"])
-            .current_dir(repo_path)
             .output()
             .ok()?;
 
@@ -137,9 +375,8 @@ impl ProjectManager {
         }
 
         // Fallback: try to get current branch
-        let output = Command::new("git")
+        let output = git_command(repo_path)
             .args(["branch", "--show-current"])
-            .current_dir(repo_path)
             .output()
             .ok()?;
 
@@ -153,11 +390,52 @@ impl ProjectManager {
         None
     }
 
+    /// 修改项目默认分支；分支须在本地或 `origin` 上存在
+    pub fn set_default_branch(
+        state: &mut AppState,
+        name: &str,
+        branch: &str,
+    ) -> Result<String, ProjectError> {
+        let branch = branch.trim();
+        let branch = branch.strip_prefix("refs/heads/").unwrap_or(branch);
+        if !crate::workspace::branch_name::is_valid_branch_name(branch) {
+            return Err(ProjectError::InvalidBranch(branch.to_string()));
+        }
+        let project = state.get_project_mut(name).ok_or_else(|| {
+            ProjectError::StateError(StateError::ProjectNotFound(name.to_string()))
+        })?;
+
+        let provider = crate::server::file_api::provider::provider_for_root(&project.root_path)
+            .map_err(|e| {
+                ProjectError::IoError(format!("{}: {}", project.root_path.display(), e))
+            })?;
+        let local = format!("refs/heads/{}", branch);
+        let remote = format!("refs/remotes/origin/{}", branch);
+        let exists = [local, remote].iter().any(|reference| {
+            provider
+                .git_output(&["rev-parse", "--verify", "--quiet", reference])
+                .is_ok_and(|output| output.status.success())
+        });
+        if !exists {
+            return Err(ProjectError::BranchNotFound(branch.to_string()));
+        }
+
+        if project.default_branch != branch {
+            info!(
+                project = name,
+                from = %project.default_branch,
+                to = branch,
+                "Project default branch changed"
+            );
+            project.default_branch = branch.to_string();
+        }
+        Ok(project.default_branch.clone())
+    }
+
     /// Get the remote URL from git
     fn get_remote_url(repo_path: &Path) -> Option<String> {
-        let output = Command::new("git")
+        let output = git_command(repo_path)
             .args(["remote", "get-url", "origin"])
-            .current_dir(repo_path)
             .output()
             .ok()?;
 
@@ -218,3 +496,33 @@ impl ProjectManager {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_clone_progress_lines() {
+        let p = parse_clone_progress("Receiving objects:  45% (45/100), 1.20 MiB | 2.00 MiB/s")
+            .unwrap();
+        assert_eq!(p.phase, "receiving_objects");
+        assert_eq!(p.percent, Some(45));
+
+        let p = parse_clone_progress("remote: Counting objects: 100% (10/10), done.").unwrap();
+        assert_eq!(p.phase, "counting_objects");
+        assert_eq!(p.percent, Some(100));
+        assert_eq!(p.message, "Counting objects: 100% (10/10), done.");
+
+        let p = parse_clone_progress("Cloning into '/tmp/x'...").unwrap();
+        assert_eq!(p.phase, "other");
+        assert_eq!(p.percent, None);
+        assert!(parse_clone_progress("  ").is_none());
+
+        let p = parse_clone_progress("Rebasing (2/8)").unwrap();
+        assert_eq!(p.phase, "rebasing");
+        assert_eq!(p.percent, Some(25));
+        let p =
+            parse_clone_progress("\u{1b}[KSuccessfully rebased and updated refs/heads/f.").unwrap();
+        assert_eq!(p.message, "Successfully rebased and updated refs/heads/f.");
+    }
+}
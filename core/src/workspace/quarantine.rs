@@ -0,0 +1,110 @@
+//! 损坏项目条目隔离（quarantine）
+//!
+//! 状态加载时无法解析的项目条目不再让整份状态加载失败：原始数据写入
+//! `<tidyflow_home>/recovery/` 下的恢复文件并输出结构化告警，其余项目照常加载。
+//! 仍在状态中的失败项目会在健康诊断中以 `project_load_failed` incident 呈现，
+//! 可通过 `repair_project` 修复动作恢复。
+
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use tracing::warn;
+
+use super::sqlite_store;
+
+/// 默认恢复文件目录
+pub fn default_recovery_dir() -> PathBuf {
+    sqlite_store::tidyflow_home_dir().join("recovery")
+}
+
+fn file_stem_for(project: &str) -> String {
+    let stem: String = project
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if stem.is_empty() {
+        "unnamed".to_string()
+    } else {
+        stem
+    }
+}
+
+/// 将损坏的项目条目写入恢复文件，返回文件路径。
+///
+/// 同一项目、同一来源重复隔离时覆盖旧文件，避免每次启动堆积副本。
+/// `source` 标识数据来源（`sqlite_project` / `sqlite_workspaces` / `legacy_json`）。
+pub fn quarantine_project_entry(
+    dir: &Path,
+    project: &str,
+    source: &str,
+    reason: &str,
+    payload: serde_json::Value,
+) -> Option<PathBuf> {
+    let path = dir.join(format!("{}-{}.json", source, file_stem_for(project)));
+    let record = serde_json::json!({
+        "project": project,
+        "source": source,
+        "reason": reason,
+        "quarantined_at": Utc::now().to_rfc3339(),
+        "payload": payload,
+    });
+    let written = std::fs::create_dir_all(dir).and_then(|_| {
+        let content = serde_json::to_vec_pretty(&record).map_err(std::io::Error::other)?;
+        std::fs::write(&path, content)
+    });
+    match written {
+        Ok(()) => {
+            warn!(
+                event = "project_quarantined",
+                project,
+                source,
+                reason,
+                recovery_file = %path.display(),
+                "Quarantined corrupt project entry"
+            );
+            Some(path)
+        }
+        Err(e) => {
+            warn!(
+                event = "project_quarantine_failed",
+                project,
+                source,
+                reason,
+                error = %e,
+                "Failed to write recovery file for corrupt project entry"
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_recovery_file_with_sanitized_name() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = quarantine_project_entry(
+            tmp.path(),
+            "my/proj",
+            "legacy_json",
+            "missing field",
+            serde_json::json!({ "name": "my/proj" }),
+        )
+        .expect("recovery file");
+        assert_eq!(path, tmp.path().join("legacy_json-my_proj.json"));
+
+        let record: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(record["project"], "my/proj");
+        assert_eq!(record["reason"], "missing field");
+        assert_eq!(record["payload"]["name"], "my/proj");
+    }
+}
@@ -1,15 +1,18 @@
 //! Setup step execution
 
-use crate::workspace::config::{check_condition, ProjectConfig, SetupStep};
+use crate::workspace::config::{check_condition, ProjectConfig, SetupStep, PORT_PLACEHOLDER};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::net::TcpListener;
 use std::path::Path;
 use std::process::{Command, Stdio};
 use std::time::Duration;
 use tracing::{info, warn};
 
 const MAX_OUTPUT_LEN: usize = 10000;
+/// 预留端口时向系统申请的最大次数（跳过已分配给其他工作区的端口）
+const RESERVE_PORT_ATTEMPTS: usize = 16;
 
 /// Result of executing all setup steps
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,7 +42,9 @@ pub struct SetupExecutor;
 
 impl SetupExecutor {
     /// Execute all setup steps from config
-    pub fn execute(config: &ProjectConfig, working_dir: &Path) -> SetupResult {
+    ///
+    /// `port` 为工作区预留的端口，替换步骤命令与环境变量中的 `{{port}}`
+    pub fn execute(config: &ProjectConfig, working_dir: &Path, port: Option<u16>) -> SetupResult {
         let started_at = Utc::now();
         let mut steps = Vec::new();
         let mut all_success = true;
@@ -55,7 +60,7 @@ impl SetupExecutor {
         }
 
         // Prepare environment
-        let env = Self::prepare_env(config, working_dir);
+        let env = Self::prepare_env(config, working_dir, port);
 
         // Get shell
         let shell = config
@@ -65,7 +70,8 @@ impl SetupExecutor {
             .unwrap_or_else(|| "/bin/sh".to_string());
 
         for step in &config.setup.steps {
-            let result = Self::execute_step(step, working_dir, &shell, &env, config.setup.timeout);
+            let result =
+                Self::execute_step(step, working_dir, &shell, &env, config.setup.timeout, port);
 
             if !result.success && !result.skipped && !step.continue_on_error {
                 all_success = false;
@@ -88,7 +94,11 @@ impl SetupExecutor {
         }
     }
 
-    fn prepare_env(config: &ProjectConfig, working_dir: &Path) -> HashMap<String, String> {
+    fn prepare_env(
+        config: &ProjectConfig,
+        working_dir: &Path,
+        port: Option<u16>,
+    ) -> HashMap<String, String> {
         let mut env: HashMap<String, String> = if config.env.inherit {
             std::env::vars().collect()
         } else {
@@ -97,7 +107,7 @@ impl SetupExecutor {
 
         // Add custom vars
         for (k, v) in &config.env.vars {
-            env.insert(k.clone(), v.clone());
+            env.insert(k.clone(), substitute_port(v, port));
         }
 
         // Modify PATH
@@ -139,8 +149,10 @@ impl SetupExecutor {
         shell: &str,
         base_env: &HashMap<String, String>,
         default_timeout: u32,
+        port: Option<u16>,
     ) -> StepResult {
         let started_at = Utc::now();
+        let command = substitute_port(&step.run, port);
 
         // Check condition
         if let Some(condition) = &step.condition {
@@ -152,7 +164,7 @@ impl SetupExecutor {
                 );
                 return StepResult {
                     name: step.name.clone(),
-                    command: step.run.clone(),
+                    command: command.clone(),
                     success: true,
                     exit_code: None,
                     stdout: None,
@@ -175,17 +187,17 @@ impl SetupExecutor {
         // Merge step-specific env
         let mut env = base_env.clone();
         for (k, v) in &step.env {
-            env.insert(k.clone(), v.clone());
+            env.insert(k.clone(), substitute_port(v, port));
         }
 
-        info!(step = step.name, command = step.run, "Executing setup step");
+        info!(step = step.name, command = command, "Executing setup step");
 
         // Execute command
         let _timeout = Duration::from_secs(step.timeout.unwrap_or(default_timeout) as u64);
 
         let result = Command::new(shell)
             .arg("-c")
-            .arg(&step.run)
+            .arg(&command)
             .current_dir(&step_working_dir)
             .envs(&env)
             .stdout(Stdio::piped())
@@ -208,7 +220,7 @@ impl SetupExecutor {
 
                 StepResult {
                     name: step.name.clone(),
-                    command: step.run.clone(),
+                    command: command.clone(),
                     success,
                     exit_code,
                     stdout: if stdout.is_empty() {
@@ -231,7 +243,7 @@ impl SetupExecutor {
                 warn!(step = step.name, error = %e, "Failed to execute step");
                 StepResult {
                     name: step.name.clone(),
-                    command: step.run.clone(),
+                    command: command.clone(),
                     success: false,
                     exit_code: None,
                     stdout: None,
@@ -246,6 +258,26 @@ impl SetupExecutor {
     }
 }
 
+/// 预留一个本机空闲 TCP 端口：由系统分配后立即释放，跳过 `taken` 中已分配给其他工作区的端口
+pub fn reserve_free_port(taken: &HashSet<u16>) -> Option<u16> {
+    (0..RESERVE_PORT_ATTEMPTS).find_map(|_| {
+        let port = TcpListener::bind(("127.0.0.1", 0))
+            .ok()?
+            .local_addr()
+            .ok()?
+            .port();
+        (!taken.contains(&port)).then_some(port)
+    })
+}
+
+/// 将 `{{port}}` 替换为预留端口；未预留时保持原样
+fn substitute_port(value: &str, port: Option<u16>) -> String {
+    match port {
+        Some(port) => value.replace(PORT_PLACEHOLDER, &port.to_string()),
+        None => value.to_string(),
+    }
+}
+
 fn truncate_output(s: &str) -> String {
     let s = s.trim();
     if s.len() > MAX_OUTPUT_LEN {
@@ -0,0 +1,125 @@
+//! 稀疏检出：超大 monorepo 的 worktree 只检出需要的目录（cone 模式）
+//!
+//! 新建 worktree 时先 `--no-checkout`，设置 `git sparse-checkout set --cone` 后再
+//! `git read-tree -mu HEAD` 检出，避免先完整检出再删除。稀疏配置写入 worktree 自身的
+//! `config.worktree`，不影响主仓库与其他 workspace。
+
+use std::path::Path;
+use std::process::Stdio;
+
+use crate::util::exec_env::git_command;
+
+/// 单个 workspace 允许的稀疏目录数上限
+pub const MAX_SPARSE_PATHS: usize = 256;
+
+/// 校验并规范化稀疏目录：相对路径、以 `/` 分隔、不含 `..` 与通配符；去除首尾空白与
+/// 末尾 `/`，并按原顺序去重。cone 模式只接受目录，这里无法区分文件，交给 git 处理。
+pub fn normalize_sparse_paths(paths: &[String]) -> Result<Vec<String>, String> {
+    if paths.len() > MAX_SPARSE_PATHS {
+        return Err(format!(
+            "Too many sparse paths ({}, max {})",
+            paths.len(),
+            MAX_SPARSE_PATHS
+        ));
+    }
+    let mut normalized: Vec<String> = Vec::with_capacity(paths.len());
+    for raw in paths {
+        let path = raw.trim().trim_end_matches('/');
+        if path.is_empty() || path == "." {
+            return Err(format!("Invalid sparse path '{}': empty path", raw));
+        }
+        if path.starts_with('/') || path.contains('\\') {
+            return Err(format!(
+                "Invalid sparse path '{}': must be a relative path separated by '/'",
+                raw
+            ));
+        }
+        if path.contains(['*', '?', '[', ']', '!']) || path.starts_with('#') {
+            return Err(format!(
+                "Invalid sparse path '{}': cone mode does not accept patterns",
+                raw
+            ));
+        }
+        if path
+            .split('/')
+            .any(|segment| segment.is_empty() || segment == "." || segment == "..")
+        {
+            return Err(format!(
+                "Invalid sparse path '{}': must not contain empty, '.' or '..' segments",
+                raw
+            ));
+        }
+        if !normalized.iter().any(|p| p == path) {
+            normalized.push(path.to_string());
+        }
+    }
+    Ok(normalized)
+}
+
+/// 在 worktree 中设置 cone 模式稀疏目录；`paths` 为空时关闭稀疏检出、恢复完整检出
+pub fn apply_sparse_checkout(worktree: &Path, paths: &[String]) -> Result<(), String> {
+    let mut cmd = git_command(worktree);
+    if paths.is_empty() {
+        cmd.args(["sparse-checkout", "disable"]);
+    } else {
+        cmd.args(["sparse-checkout", "set", "--cone", "--"])
+            .args(paths);
+    }
+    run(cmd, "git sparse-checkout")
+}
+
+/// 按当前稀疏规则检出 `--no-checkout` 创建的 worktree
+pub fn checkout_sparse_worktree(worktree: &Path, skip_lfs_smudge: bool) -> Result<(), String> {
+    let mut cmd = git_command(worktree);
+    if skip_lfs_smudge {
+        cmd.env("GIT_LFS_SKIP_SMUDGE", "1");
+    }
+    cmd.args(["read-tree", "-mu", "HEAD"]);
+    run(cmd, "git read-tree")
+}
+
+fn run(mut cmd: std::process::Command, what: &str) -> Result<(), String> {
+    let output = cmd
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            what,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn normalizes_and_dedupes_paths() {
+        assert_eq!(
+            normalize_sparse_paths(&paths(&[" services/api/ ", "libs", "services/api"])).unwrap(),
+            vec!["services/api", "libs"]
+        );
+        assert!(normalize_sparse_paths(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_paths() {
+        for bad in [
+            "", ".", "/abs", "a/../b", "../up", "a//b", "src/*", "a\\b", "#comment", "!neg",
+        ] {
+            assert!(
+                normalize_sparse_paths(&paths(&[bad])).is_err(),
+                "should reject {:?}",
+                bad
+            );
+        }
+    }
+}
@@ -29,15 +29,20 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use thiserror::Error;
 
+use crate::util::exec_env::{exec_env_for, ExecEnv};
+
 /// 虚拟默认工作区名称。
 /// 每个项目都有一个不持久化的 `default` 工作区，指向项目根目录，状态始终为 `Ready`。
 /// Core 在 `list_workspaces` 与 `system_snapshot` 输出时动态注入，客户端不得本地重建该工作区。
 pub const DEFAULT_WORKSPACE_NAME: &str = "default";
 
+/// AppState JSON 结构版本；结构变化时递增，并在 [`STATE_MIGRATIONS`] 末尾追加对应的迁移步骤
+pub const STATE_SCHEMA_VERSION: u32 = 1;
+
 #[derive(Error, Debug)]
 pub enum StateError {
     #[error("Failed to read state: {0}")]
@@ -160,6 +165,34 @@ pub struct EditorFormattingLanguageConfig {
     pub extra_args: Vec<String>,
 }
 
+/// 单台设备的设置覆盖（key 为客户端提供的 `device_id`）
+///
+/// 字段为 None 时沿用共享值。
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct DeviceSettingsProfile {
+    /// 设备显示名（如 "iPad"）
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub workspace_shortcuts: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub keybindings: Option<Vec<KeybindingConfig>>,
+}
+
+impl DeviceSettingsProfile {
+    /// 已覆盖的字段名
+    pub fn overridden_fields(&self) -> Vec<String> {
+        let mut fields = Vec::new();
+        if self.workspace_shortcuts.is_some() {
+            fields.push("workspace_shortcuts".to_string());
+        }
+        if self.keybindings.is_some() {
+            fields.push("keybindings".to_string());
+        }
+        fields
+    }
+}
+
 /// 客户端设置
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ClientSettings {
@@ -181,6 +214,36 @@ pub struct ClientSettings {
     /// 是否开启节点发现广播
     #[serde(default)]
     pub node_discovery_enabled: bool,
+    /// 新建终端的默认 shell（名称或绝对路径）；为空时使用系统默认
+    #[serde(default)]
+    pub default_shell: Option<String>,
+    /// git 网络操作的全局代理；项目可在 `.tidyflow.toml` 中覆盖
+    #[serde(default)]
+    pub git_proxy: Option<String>,
+    /// 离线模式：fetch / clone 等网络操作直接返回 `offline_mode` 错误
+    #[serde(default)]
+    pub git_offline_mode: bool,
+    /// 代码托管平台访问令牌（key: 主机名，如 "github.com"），用于创建 PR；不回传客户端
+    #[serde(default)]
+    pub git_hosting_tokens: HashMap<String, String>,
+    /// 新建终端默认开启保活（不参与空闲回收，定期发送保活信号）
+    #[serde(default)]
+    pub terminal_keep_alive: bool,
+    /// 保活终端运行期间阻止系统休眠（macOS `caffeinate`）
+    #[serde(default)]
+    pub terminal_prevent_sleep: bool,
+    /// 新建 zsh / bash 终端时注入 shell 集成脚本（OSC 133 命令标记）
+    #[serde(default)]
+    pub terminal_shell_integration: bool,
+    /// 推送通知中继地址（用户自建，负责转发为 APNs 推送）；为空时不推送
+    #[serde(default)]
+    pub push_relay_url: Option<String>,
+    /// 设备 APNs 推送令牌（key: device_id）；不回传客户端
+    #[serde(default)]
+    pub push_tokens: HashMap<String, String>,
+    /// 后台定时 fetch 全部项目的间隔（秒）；0 表示关闭
+    #[serde(default)]
+    pub git_auto_fetch_interval_secs: u64,
     /// Evolution 全局默认配置
     #[serde(default)]
     pub evolution_default_profiles: Vec<EvolutionStageProfile>,
@@ -199,6 +262,9 @@ pub struct ClientSettings {
     /// 语言级格式化配置
     #[serde(default)]
     pub editor_formatting_configs: Vec<EditorFormattingLanguageConfig>,
+    /// 按设备的设置覆盖（key: device_id）；上面的字段为所有设备共享的部分
+    #[serde(default)]
+    pub device_profiles: HashMap<String, DeviceSettingsProfile>,
 }
 
 fn default_evolution_ai_tool() -> String {
@@ -208,6 +274,27 @@ fn default_evolution_ai_tool() -> String {
 impl ClientSettings {
     /// 预留迁移入口（当前无需迁移逻辑）
     pub fn migrate(&mut self) {}
+
+    /// 去掉不应落入快照或导出文件的凭据（代码托管令牌）
+    pub fn strip_secrets(&mut self) {
+        self.git_hosting_tokens.clear();
+    }
+
+    /// 设备实际生效的工作空间快捷键（无覆盖时为共享值）
+    pub fn workspace_shortcuts_for(&self, device_id: Option<&str>) -> &HashMap<String, String> {
+        device_id
+            .and_then(|id| self.device_profiles.get(id))
+            .and_then(|profile| profile.workspace_shortcuts.as_ref())
+            .unwrap_or(&self.workspace_shortcuts)
+    }
+
+    /// 设备实际生效的快捷键绑定（无覆盖时为共享值）
+    pub fn keybindings_for(&self, device_id: Option<&str>) -> &[KeybindingConfig] {
+        device_id
+            .and_then(|id| self.device_profiles.get(id))
+            .and_then(|profile| profile.keybindings.as_deref())
+            .unwrap_or(&self.keybindings)
+    }
 }
 
 /// 远程访问 API key 持久化条目
@@ -272,6 +359,9 @@ pub struct NodeAuthTokenEntry {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppState {
     pub version: u32,
+    /// JSON 结构版本（见 [`migrate_state_json`]）；缺失表示引入版本号之前的文件
+    #[serde(default)]
+    pub schema_version: u32,
     pub projects: HashMap<String, Project>,
     #[serde(default)]
     pub last_updated: Option<DateTime<Utc>>,
@@ -287,12 +377,42 @@ pub struct AppState {
     pub paired_nodes: Vec<PairedNodeEntry>,
     #[serde(default)]
     pub node_auth_tokens: Vec<NodeAuthTokenEntry>,
+    /// 启动懒加载期间各项目的加载状态（不持久化）；缺失表示已完整加载
+    #[serde(skip)]
+    pub project_load_status: HashMap<String, ProjectLoadStatus>,
+}
+
+/// 项目元数据的加载状态
+///
+/// 启动时只读取项目行（stub），workspace 行在后台或首次访问时按项目水合；
+/// 单个项目的数据损坏只会把该项目标记为 `Failed`，不影响其他项目。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectLoadStatus {
+    /// 仅有项目 stub，workspace 尚未加载
+    Pending,
+    Loaded,
+    Failed {
+        error: String,
+        /// 损坏数据的恢复文件（见 `workspace::quarantine`）
+        recovery_file: Option<String>,
+    },
+}
+
+impl ProjectLoadStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProjectLoadStatus::Pending => "pending",
+            ProjectLoadStatus::Loaded => "loaded",
+            ProjectLoadStatus::Failed { .. } => "failed",
+        }
+    }
 }
 
 impl Default for AppState {
     fn default() -> Self {
         Self {
             version: 1,
+            schema_version: STATE_SCHEMA_VERSION,
             projects: HashMap::new(),
             last_updated: Some(Utc::now()),
             client_settings: ClientSettings::default(),
@@ -301,6 +421,7 @@ impl Default for AppState {
             node_discovery: NodeDiscoverySettings::default(),
             paired_nodes: Vec::new(),
             node_auth_tokens: Vec::new(),
+            project_load_status: HashMap::new(),
         }
     }
 }
@@ -422,6 +543,15 @@ pub struct Workspace {
     /// 工作区恢复元数据（崩溃/中断后的状态记录，按 (project, workspace) 隔离）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub recovery_meta: Option<WorkspaceRecoveryMeta>,
+    /// setup 中 `{{port}}` 使用的预留端口，首次引用时分配并在工作区生命周期内保持不变
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// 关联的 issue 地址
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linked_issue: Option<String>,
+    /// 稀疏检出（cone 模式）的目录列表；为空表示完整检出
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sparse_paths: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -446,6 +576,7 @@ pub struct SetupResultSummary {
 impl AppState {
     /// Add a project
     pub fn add_project(&mut self, project: Project) {
+        self.project_load_status.remove(&project.name);
         self.projects.insert(project.name.clone(), project);
     }
 
@@ -461,9 +592,41 @@ impl AppState {
 
     /// Remove a project
     pub fn remove_project(&mut self, name: &str) -> Option<Project> {
+        self.project_load_status.remove(name);
         self.projects.remove(name)
     }
 
+    /// 项目的加载状态；不存在状态记录的项目视为已加载
+    pub fn project_load_status(&self, name: &str) -> ProjectLoadStatus {
+        self.project_load_status
+            .get(name)
+            .cloned()
+            .unwrap_or(ProjectLoadStatus::Loaded)
+    }
+
+    /// 尚未水合 workspace 的项目（按名称排序）
+    pub fn pending_projects(&self) -> Vec<String> {
+        let mut names = self
+            .project_load_status
+            .iter()
+            .filter(|(name, status)| {
+                **status == ProjectLoadStatus::Pending && self.projects.contains_key(*name)
+            })
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    /// 已为各工作区预留的端口
+    pub fn allocated_ports(&self) -> HashSet<u16> {
+        self.projects
+            .values()
+            .flat_map(|p| p.workspaces.values())
+            .filter_map(|w| w.port)
+            .collect()
+    }
+
     /// List all project names
     pub fn list_projects(&self) -> Vec<&str> {
         self.projects.keys().map(|s| s.as_str()).collect()
@@ -551,11 +714,107 @@ impl Project {
     }
 
     /// Get the worktrees directory for this project
+    ///
+    /// WSL 项目的 worktree 需与仓库位于同一发行版内（发行版内的 git 无法使用
+    /// Windows 侧路径），放在项目根目录旁的 `.tidyflow-workspaces` 下。
     pub fn worktrees_dir(&self) -> PathBuf {
+        if let ExecEnv::Wsl { .. } = exec_env_for(&self.root_path) {
+            if let Some(parent) = self.root_path.parent() {
+                return parent.join(".tidyflow-workspaces");
+            }
+        }
         crate::util::paths::tidyflow_home_dir().join("workspaces")
     }
 }
 
+/// 一次 JSON 状态迁移的版本区间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateMigration {
+    pub from: u32,
+    pub to: u32,
+}
+
+impl StateMigration {
+    /// 是否实际执行了迁移步骤（调用方据此在写回前备份原文件）
+    pub fn is_upgrade(&self) -> bool {
+        self.from < self.to
+    }
+}
+
+type StateMigrationStep = fn(&mut serde_json::Map<String, serde_json::Value>);
+
+/// 第 i 步把 `schema_version` i 升级到 i + 1；长度与 [`STATE_SCHEMA_VERSION`] 绑定
+const STATE_MIGRATIONS: [StateMigrationStep; STATE_SCHEMA_VERSION as usize] = [migrate_v0_to_v1];
+
+/// 把 JSON 状态逐步升级到 [`STATE_SCHEMA_VERSION`]，返回迁移区间
+///
+/// 比当前版本更新的文件（由更新版本的 Core 写入）直接报错，调用方不得以默认状态覆盖它。
+pub fn migrate_state_json(value: &mut serde_json::Value) -> Result<StateMigration, StateError> {
+    let root = value
+        .as_object_mut()
+        .ok_or_else(|| StateError::ParseError("State root is not a JSON object".to_string()))?;
+    let from = root
+        .get("schema_version")
+        .and_then(serde_json::Value::as_u64)
+        .map_or(0, |v| u32::try_from(v).unwrap_or(u32::MAX));
+    if from > STATE_SCHEMA_VERSION {
+        return Err(StateError::ParseError(format!(
+            "State schema version {} is newer than supported version {}",
+            from, STATE_SCHEMA_VERSION
+        )));
+    }
+    for step in &STATE_MIGRATIONS[from as usize..] {
+        step(root);
+    }
+    root.insert(
+        "schema_version".to_string(),
+        serde_json::Value::from(STATE_SCHEMA_VERSION),
+    );
+    Ok(StateMigration {
+        from,
+        to: STATE_SCHEMA_VERSION,
+    })
+}
+
+/// v0 → v1：引入 `schema_version` 之前的文件。
+/// 补齐缺失的 `version` / `projects` / `workspaces`，并把数组形式的项目与工作区列表转为按名称索引的对象
+fn migrate_v0_to_v1(root: &mut serde_json::Map<String, serde_json::Value>) {
+    root.entry("version")
+        .or_insert_with(|| serde_json::Value::from(1));
+    let projects = root.entry("projects").or_insert(serde_json::Value::Null);
+    *projects = keyed_by_name(projects.take());
+    if let Some(projects) = projects.as_object_mut() {
+        for project in projects.values_mut().filter_map(|p| p.as_object_mut()) {
+            let workspaces = project
+                .entry("workspaces")
+                .or_insert(serde_json::Value::Null);
+            *workspaces = keyed_by_name(workspaces.take());
+        }
+    }
+}
+
+/// `null` → 空对象；`[{ name, .. }]` → `{ name: {..} }`（缺少名称的条目以下标为键，
+/// 后续解析失败时会被隔离到恢复文件而不是丢弃）；其余原样保留
+fn keyed_by_name(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Null => serde_json::Value::Object(serde_json::Map::new()),
+        serde_json::Value::Array(items) => serde_json::Value::Object(
+            items
+                .into_iter()
+                .enumerate()
+                .map(|(index, item)| {
+                    let key = item
+                        .get("name")
+                        .and_then(serde_json::Value::as_str)
+                        .map_or_else(|| format!("#{}", index), str::to_string);
+                    (key, item)
+                })
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -601,6 +860,9 @@ mod tests {
             last_accessed: Utc::now(),
             setup_result: None,
             recovery_meta: None,
+            port: None,
+            linked_issue: None,
+            sparse_paths: Vec::new(),
         }
     }
 
@@ -762,6 +1024,44 @@ mod tests {
         assert_eq!(summary.steps_total, summary.steps_completed);
     }
 
+    #[test]
+    fn migrate_state_json_upgrades_unversioned_shapes() {
+        let mut value = serde_json::json!({
+            "projects": [{
+                "name": "demo",
+                "root_path": "/tmp/demo",
+                "remote_url": null,
+                "default_branch": "main",
+                "created_at": "2024-01-01T00:00:00Z",
+                "workspaces": [{
+                    "name": "feature",
+                    "worktree_path": "/tmp/demo-feature",
+                    "branch": "tidy/feature",
+                    "status": "ready",
+                    "created_at": "2024-01-01T00:00:00Z",
+                    "last_accessed": "2024-01-01T00:00:00Z",
+                    "setup_result": null
+                }]
+            }]
+        });
+        let migration = migrate_state_json(&mut value).unwrap();
+        assert_eq!(migration.from, 0);
+        assert!(migration.is_upgrade());
+
+        let state: AppState = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(state.schema_version, STATE_SCHEMA_VERSION);
+        assert_eq!(state.version, 1);
+        let project = state.get_project("demo").unwrap();
+        assert!(project.get_workspace("feature").is_some());
+
+        // 已是当前版本时不再迁移
+        let again = migrate_state_json(&mut value).unwrap();
+        assert!(!again.is_upgrade());
+
+        let mut newer = serde_json::json!({ "schema_version": STATE_SCHEMA_VERSION + 1 });
+        assert!(migrate_state_json(&mut newer).is_err());
+    }
+
     #[test]
     fn state_models_json_roundtrip() {
         let state = AppState::default();
@@ -800,6 +1100,9 @@ mod tests {
                     last_accessed: Utc::now(),
                     setup_result: None,
                     recovery_meta: None,
+                    port: None,
+                    linked_issue: None,
+                    sparse_paths: Vec::new(),
                 },
             );
         }
@@ -868,6 +1171,9 @@ mod tests {
                     last_accessed: Utc::now(),
                     setup_result: None,
                     recovery_meta: None,
+                    port: None,
+                    linked_issue: None,
+                    sparse_paths: Vec::new(),
                 };
                 (ws_name.to_string(), ws)
             })
@@ -0,0 +1,390 @@
+//! AppState 存储后端
+//!
+//! [`StateBackend`] 抽象 AppState 快照的读写，目前有两种实现：
+//! - SQLite（[`StateStore`]，默认）：事务写入、按表局部更新、schema 迁移；
+//! - JSON（[`JsonStateStore`]）：单文件 `state.json`，原子替换写入并保留上一版 `.bak`。
+//!
+//! `TIDYFLOW_STATE_BACKEND=json` 时 AppState 改存 JSON 文件（终端恢复等附属数据仍在 SQLite）；
+//! SQLite 数据库无法打开时也会自动回退到 JSON，避免服务无法启动。
+//! 两种后端之间经 [`import_state_file`] / [`export_state_file`] 迁移（CLI `tidyflow-core state`）。
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tracing::{info, warn};
+
+use super::sqlite_store;
+use super::state::{AppState, StateError, StateMigration};
+use super::state_store::parse_state_json;
+
+/// 选择后端的环境变量
+pub const STATE_BACKEND_ENV: &str = "TIDYFLOW_STATE_BACKEND";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateBackendKind {
+    Sqlite,
+    Json,
+}
+
+impl StateBackendKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StateBackendKind::Sqlite => "sqlite",
+            StateBackendKind::Json => "json",
+        }
+    }
+
+    /// 读取 [`STATE_BACKEND_ENV`]；未设置或无法识别时为 SQLite
+    pub fn from_env() -> Self {
+        match std::env::var(STATE_BACKEND_ENV) {
+            Ok(raw) if raw.trim().eq_ignore_ascii_case("json") => StateBackendKind::Json,
+            Ok(raw) if !raw.trim().is_empty() && !raw.trim().eq_ignore_ascii_case("sqlite") => {
+                warn!(value = %raw, "Unknown state backend, using sqlite");
+                StateBackendKind::Sqlite
+            }
+            _ => StateBackendKind::Sqlite,
+        }
+    }
+}
+
+/// AppState 快照的持久化后端
+#[async_trait]
+pub trait StateBackend: Send + Sync {
+    fn kind(&self) -> StateBackendKind;
+
+    /// 完整加载（含所有项目的 workspace）；尚无数据时返回默认状态
+    async fn load(&self) -> Result<AppState, StateError>;
+
+    /// 全量写入
+    async fn save(&self, state: &AppState) -> Result<(), StateError>;
+}
+
+/// JSON 文件后端
+///
+/// 写入先落到临时文件再原子替换，替换前把现有文件保留为 `.bak`；
+/// 主文件无法解析时回退读取 `.bak`。无法解析的项目条目与 SQLite 后端一样隔离到恢复文件。
+/// 旧结构版本的文件在迁移写回前备份为 `state.json.schema-v<N>.bak`；
+/// 主文件与 `.bak` 都无法读取时另存为 `state.json.unreadable-<时间>.bak`，避免被随后的保存覆盖。
+pub struct JsonStateStore {
+    path: PathBuf,
+    recovery_dir: PathBuf,
+    /// 串行化写入，避免并发保存交错替换文件
+    write_lock: tokio::sync::Mutex<()>,
+}
+
+impl JsonStateStore {
+    pub fn new(path: PathBuf, recovery_dir: PathBuf) -> Self {
+        Self {
+            path,
+            recovery_dir,
+            write_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// 数据目录下的 `state.json`
+    pub fn default_path() -> PathBuf {
+        sqlite_store::tidyflow_home_dir().join("state.json")
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn backup_path(&self) -> PathBuf {
+        self.path.with_extension("json.bak")
+    }
+
+    /// 迁移写回前保留原文件；备份失败时中止迁移，原文件保持不变
+    async fn backup_before_migrate(&self, from: u32) -> Result<(), StateError> {
+        if !tokio::fs::try_exists(&self.path).await.unwrap_or(false) {
+            return Ok(());
+        }
+        let backup = self
+            .path
+            .with_extension(format!("json.schema-v{}.bak", from));
+        tokio::fs::copy(&self.path, &backup)
+            .await
+            .map(|_| ())
+            .map_err(|e| StateError::WriteError(e.to_string()))
+    }
+
+    /// 无法读取的状态文件另存一份，之后以默认状态启动时的保存不会覆盖原数据
+    async fn preserve_unreadable(&self) {
+        let stamp = chrono::Utc::now().format("%Y%m%d%H%M%S");
+        let target = self
+            .path
+            .with_extension(format!("json.unreadable-{}.bak", stamp));
+        match tokio::fs::copy(&self.path, &target).await {
+            Ok(_) => warn!(path = %target.display(), "Preserved unreadable state file"),
+            Err(e) => warn!(error = %e, "Failed to preserve unreadable state file"),
+        }
+    }
+
+    async fn read_state(
+        &self,
+        path: &Path,
+    ) -> Result<Option<(AppState, StateMigration)>, StateError> {
+        match tokio::fs::read_to_string(path).await {
+            Ok(content) => parse_state_json(&content, &self.recovery_dir).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(StateError::ReadError(e.to_string())),
+        }
+    }
+}
+
+#[async_trait]
+impl StateBackend for JsonStateStore {
+    fn kind(&self) -> StateBackendKind {
+        StateBackendKind::Json
+    }
+
+    async fn load(&self) -> Result<AppState, StateError> {
+        let primary = self.read_state(&self.path).await;
+        let loaded = match primary {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                warn!(
+                    path = %self.path.display(),
+                    error = %e,
+                    "State file unreadable, trying backup"
+                );
+                match self.read_state(&self.backup_path()).await {
+                    Ok(Some(loaded)) => Some(loaded),
+                    _ => {
+                        self.preserve_unreadable().await;
+                        return Err(e);
+                    }
+                }
+            }
+        };
+        let Some((mut state, migration)) = loaded else {
+            return Ok(AppState::default());
+        };
+        state.client_settings.migrate();
+        if migration.is_upgrade() {
+            self.backup_before_migrate(migration.from).await?;
+            self.save(&state).await?;
+            info!(
+                from = migration.from,
+                to = migration.to,
+                path = %self.path.display(),
+                "Migrated state file schema"
+            );
+        }
+        Ok(state)
+    }
+
+    async fn save(&self, state: &AppState) -> Result<(), StateError> {
+        let content =
+            serde_json::to_vec_pretty(state).map_err(|e| StateError::WriteError(e.to_string()))?;
+        let _guard = self.write_lock.lock().await;
+        if tokio::fs::try_exists(&self.path).await.unwrap_or(false) {
+            let _ = tokio::fs::copy(&self.path, self.backup_path()).await;
+        }
+        write_file_atomic(&self.path, &content).await
+    }
+}
+
+/// 崩溃安全的整文件写入：写临时文件并 fsync，再 rename 覆盖目标并 fsync 所在目录。
+/// 任意时刻崩溃，目标文件要么是旧内容、要么是完整的新内容。
+pub(super) async fn write_file_atomic(path: &Path, content: &[u8]) -> Result<(), StateError> {
+    sqlite_store::ensure_parent_dir_async(path)
+        .await
+        .map_err(StateError::WriteError)?;
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let write = async {
+        let mut file = tokio::fs::File::create(&tmp_path).await?;
+        tokio::io::AsyncWriteExt::write_all(&mut file, content).await?;
+        file.sync_all().await?;
+        drop(file);
+        tokio::fs::rename(&tmp_path, path).await?;
+        #[cfg(unix)]
+        if let Some(parent) = path.parent() {
+            tokio::fs::File::open(parent).await?.sync_all().await?;
+        }
+        Ok::<_, std::io::Error>(())
+    };
+    write.await.map_err(|e| {
+        let _ = std::fs::remove_file(&tmp_path);
+        StateError::WriteError(e.to_string())
+    })
+}
+
+/// 从 JSON 文件（`state.json`、`state export` 的导出或 legacy `tidyflow.json`）导入，
+/// 整体替换 `target` 中的状态；文件不含代码托管令牌时保留 `target` 现有的令牌
+pub async fn import_state_file(
+    target: &dyn StateBackend,
+    path: &Path,
+    recovery_dir: &Path,
+) -> Result<AppState, StateError> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| StateError::ReadError(e.to_string()))?;
+    let (mut state, _) = parse_state_json(&content, recovery_dir)?;
+    state.client_settings.migrate();
+    if state.client_settings.git_hosting_tokens.is_empty() {
+        state.client_settings.git_hosting_tokens =
+            target.load().await?.client_settings.git_hosting_tokens;
+    }
+    state.last_updated = Some(chrono::Utc::now());
+    target.save(&state).await?;
+    Ok(state)
+}
+
+/// 把 `source` 的完整状态导出为 JSON 文件（格式与 JSON 后端一致，可再次导入）；
+/// 导出文件不含代码托管令牌
+pub async fn export_state_file(
+    source: &dyn StateBackend,
+    path: &Path,
+) -> Result<AppState, StateError> {
+    let mut state = source.load().await?;
+    state.client_settings.strip_secrets();
+    JsonStateStore::new(path.to_path_buf(), PathBuf::new())
+        .save(&state)
+        .await?;
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::state::Project;
+    use crate::workspace::state_store::StateStore;
+    use std::collections::HashMap;
+
+    fn sample_state() -> AppState {
+        let mut state = AppState::default();
+        state.client_settings.fixed_port = 4321;
+        state.add_project(Project {
+            name: "demo".to_string(),
+            root_path: PathBuf::from("/tmp/demo"),
+            remote_url: None,
+            default_branch: "main".to_string(),
+            created_at: chrono::Utc::now(),
+            workspaces: HashMap::new(),
+            commands: Vec::new(),
+        });
+        state
+    }
+
+    #[tokio::test]
+    async fn json_store_roundtrips_and_falls_back_to_backup() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = JsonStateStore::new(temp.path().join("state.json"), temp.path().join("rec"));
+        assert!(store.load().await.unwrap().projects.is_empty());
+
+        let state = sample_state();
+        store.save(&state).await.unwrap();
+        store.save(&state).await.unwrap();
+        let loaded = store.load().await.unwrap();
+        assert_eq!(loaded.client_settings.fixed_port, 4321);
+        assert!(loaded.get_project("demo").is_some());
+
+        // 主文件损坏时读取上一版
+        std::fs::write(store.path(), "{ truncated").unwrap();
+        let loaded = store.load().await.unwrap();
+        assert!(loaded.get_project("demo").is_some());
+    }
+
+    #[tokio::test]
+    async fn json_store_backs_up_before_migrating_and_keeps_unreadable_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("state.json");
+        // 引入 schema_version 之前的文件：缺少 version，项目以数组保存
+        let legacy = r#"{"projects":[{"name":"demo","root_path":"/tmp/demo","remote_url":null,
+            "default_branch":"main","created_at":"2024-01-01T00:00:00Z","workspaces":null}]}"#;
+        std::fs::write(&path, legacy).unwrap();
+        let store = JsonStateStore::new(path.clone(), temp.path().join("rec"));
+
+        let loaded = store.load().await.unwrap();
+        assert!(loaded.get_project("demo").is_some());
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join("state.json.schema-v0.bak")).unwrap(),
+            legacy
+        );
+        let rewritten: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            rewritten["schema_version"],
+            crate::workspace::state::STATE_SCHEMA_VERSION
+        );
+
+        // 更新版本写入的文件不可读：返回错误并另存，不静默回到空状态
+        std::fs::write(&path, r#"{"schema_version":999,"projects":{}}"#).unwrap();
+        std::fs::remove_file(temp.path().join("state.json.bak")).ok();
+        assert!(store.load().await.is_err());
+        let preserved = std::fs::read_dir(temp.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .any(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("state.json.unreadable-")
+            });
+        assert!(preserved);
+    }
+
+    #[tokio::test]
+    async fn imports_json_file_into_sqlite_and_exports_back() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("export.json");
+        JsonStateStore::new(source.clone(), temp.path().join("rec"))
+            .save(&sample_state())
+            .await
+            .unwrap();
+
+        let sqlite = StateStore::open_in_memory_for_test().await.unwrap();
+        assert_eq!(sqlite.kind(), StateBackendKind::Sqlite);
+        let imported = import_state_file(&sqlite, &source, &temp.path().join("rec"))
+            .await
+            .unwrap();
+        assert_eq!(imported.projects.len(), 1);
+        let loaded = StateBackend::load(&sqlite).await.unwrap();
+        assert_eq!(loaded.client_settings.fixed_port, 4321);
+        assert!(loaded.get_project("demo").is_some());
+
+        let target = temp.path().join("roundtrip.json");
+        export_state_file(&sqlite, &target).await.unwrap();
+        let exported = JsonStateStore::new(target, temp.path().join("rec"))
+            .load()
+            .await
+            .unwrap();
+        assert!(exported.get_project("demo").is_some());
+    }
+
+    #[tokio::test]
+    async fn export_omits_hosting_tokens_and_import_keeps_existing_ones() {
+        let temp = tempfile::tempdir().unwrap();
+        let sqlite = StateStore::open_in_memory_for_test().await.unwrap();
+        let mut state = sample_state();
+        state
+            .client_settings
+            .git_hosting_tokens
+            .insert("github.com".to_string(), "ghp_secret".to_string());
+        StateBackend::save(&sqlite, &state).await.unwrap();
+
+        let target = temp.path().join("export.json");
+        export_state_file(&sqlite, &target).await.unwrap();
+        assert!(!std::fs::read_to_string(&target)
+            .unwrap()
+            .contains("ghp_secret"));
+
+        let imported = import_state_file(&sqlite, &target, &temp.path().join("rec"))
+            .await
+            .unwrap();
+        assert!(imported.get_project("demo").is_some());
+        let loaded = StateBackend::load(&sqlite).await.unwrap();
+        assert_eq!(
+            loaded
+                .client_settings
+                .git_hosting_tokens
+                .get("github.com")
+                .map(String::as_str),
+            Some("ghp_secret")
+        );
+    }
+}
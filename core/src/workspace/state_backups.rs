@@ -0,0 +1,199 @@
+//! AppState 轮转备份
+//!
+//! StateSaver 在保存成功后按 [`STATE_BACKUP_INTERVAL`] 把完整状态写成 `state-backups/` 下的 JSON 快照，
+//! 只保留最近 [`STATE_BACKUP_KEEP`] 份。快照与存储后端无关（SQLite / JSON 均适用），
+//! 客户端经 `GET /api/v1/system/state-backups` 查看、`restore_state_backup` 恢复。
+//! 快照不含代码托管令牌，恢复时沿用当前令牌。
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::sqlite_store;
+use super::state::{AppState, StateError};
+use super::state_backend::write_file_atomic;
+use super::state_store::parse_state_json;
+
+/// 保留的快照份数
+pub const STATE_BACKUP_KEEP: usize = 10;
+
+/// 两次自动快照的最小间隔
+pub const STATE_BACKUP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+const BACKUP_PREFIX: &str = "state-";
+const BACKUP_SUFFIX: &str = ".json";
+const BACKUP_ID_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StateBackupInfo {
+    /// 快照 ID（UTC 时间戳，按字典序即时间顺序）
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub size: u64,
+}
+
+/// 数据目录下的 `state-backups/`
+pub fn default_backups_dir() -> PathBuf {
+    sqlite_store::tidyflow_home_dir().join("state-backups")
+}
+
+fn backup_path(dir: &Path, id: &str) -> Result<PathBuf, StateError> {
+    // ID 只含时间戳字符，避免拼出目录外的路径
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, 'T' | 'Z' | '.'))
+        && DateTime::parse_from_str(&id.replace('Z', "+0000"), "%Y%m%dT%H%M%S%.3f%z").is_ok();
+    if !valid {
+        return Err(StateError::ReadError(format!("Invalid backup id: {}", id)));
+    }
+    Ok(dir.join(format!("{}{}{}", BACKUP_PREFIX, id, BACKUP_SUFFIX)))
+}
+
+/// 写入一份快照并删除超出 `keep` 的旧快照
+pub async fn write_backup(
+    dir: &Path,
+    state: &AppState,
+    keep: usize,
+) -> Result<StateBackupInfo, StateError> {
+    let created_at = Utc::now();
+    let id = created_at.format(BACKUP_ID_FORMAT).to_string();
+    let mut snapshot = state.clone();
+    snapshot.client_settings.strip_secrets();
+    let content =
+        serde_json::to_vec_pretty(&snapshot).map_err(|e| StateError::WriteError(e.to_string()))?;
+    write_file_atomic(&backup_path(dir, &id)?, &content).await?;
+
+    let backups = list_backups(dir).await?;
+    for stale in backups.iter().skip(keep.max(1)) {
+        if let Ok(path) = backup_path(dir, &stale.id) {
+            let _ = tokio::fs::remove_file(path).await;
+        }
+    }
+    Ok(StateBackupInfo {
+        id,
+        created_at,
+        size: content.len() as u64,
+    })
+}
+
+/// 列出快照，最新的在前；目录不存在时为空
+pub async fn list_backups(dir: &Path) -> Result<Vec<StateBackupInfo>, StateError> {
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(StateError::ReadError(e.to_string())),
+    };
+    let mut backups = Vec::new();
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| StateError::ReadError(e.to_string()))?
+    {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(id) = name
+            .strip_prefix(BACKUP_PREFIX)
+            .and_then(|rest| rest.strip_suffix(BACKUP_SUFFIX))
+        else {
+            continue;
+        };
+        let Ok(created_at) =
+            DateTime::parse_from_str(&id.replace('Z', "+0000"), "%Y%m%dT%H%M%S%.3f%z")
+        else {
+            continue;
+        };
+        let size = entry.metadata().await.map(|m| m.len()).unwrap_or(0);
+        backups.push(StateBackupInfo {
+            id: id.to_string(),
+            created_at: created_at.with_timezone(&Utc),
+            size,
+        });
+    }
+    backups.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(backups)
+}
+
+/// 读取快照（按 `schema_version` 迁移到当前结构）
+pub async fn read_backup(
+    dir: &Path,
+    id: &str,
+    recovery_dir: &Path,
+) -> Result<AppState, StateError> {
+    let path = backup_path(dir, id)?;
+    let content = match tokio::fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(StateError::ReadError(format!("Backup not found: {}", id)))
+        }
+        Err(e) => return Err(StateError::ReadError(e.to_string())),
+    };
+    let (mut state, _) = parse_state_json(&content, recovery_dir)?;
+    state.client_settings.migrate();
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::state::Project;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn rotates_lists_and_reads_backups() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("state-backups");
+        let mut state = AppState::default();
+
+        let mut ids = Vec::new();
+        for i in 0..4 {
+            state.client_settings.fixed_port = 4000 + i;
+            ids.push(write_backup(&dir, &state, 3).await.unwrap().id);
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let listed = list_backups(&dir).await.unwrap();
+        assert_eq!(
+            listed.iter().map(|b| b.id.clone()).collect::<Vec<_>>(),
+            ids.iter().rev().take(3).cloned().collect::<Vec<_>>()
+        );
+
+        state.add_project(Project {
+            name: "demo".to_string(),
+            root_path: PathBuf::from("/tmp/demo"),
+            remote_url: None,
+            default_branch: "main".to_string(),
+            created_at: Utc::now(),
+            workspaces: HashMap::new(),
+            commands: Vec::new(),
+        });
+        let latest = write_backup(&dir, &state, 3).await.unwrap();
+        let restored = read_backup(&dir, &latest.id, temp.path()).await.unwrap();
+        assert!(restored.get_project("demo").is_some());
+
+        // 新快照挤掉了最旧的一份
+        assert!(read_backup(&dir, &listed[2].id, temp.path()).await.is_err());
+        let older = read_backup(&dir, &listed[1].id, temp.path()).await.unwrap();
+        assert_eq!(older.client_settings.fixed_port, 4002);
+
+        assert!(read_backup(&dir, &ids[0], temp.path()).await.is_err());
+        assert!(read_backup(&dir, "../state", temp.path()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn backups_omit_hosting_tokens() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("state-backups");
+        let mut state = AppState::default();
+        state
+            .client_settings
+            .git_hosting_tokens
+            .insert("github.com".to_string(), "ghp_secret".to_string());
+
+        let backup = write_backup(&dir, &state, 3).await.unwrap();
+        let content = std::fs::read_to_string(backup_path(&dir, &backup.id).unwrap()).unwrap();
+        assert!(!content.contains("ghp_secret"));
+        assert_eq!(state.client_settings.git_hosting_tokens.len(), 1);
+    }
+}
@@ -0,0 +1,60 @@
+//! 项目懒加载（水合）
+//!
+//! 启动时 `StateStore::load_project_stubs` 只读取项目行，服务即可开始接受连接；
+//! workspace 行由后台任务逐个项目补全，请求命中尚未水合的项目时按需优先加载。
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use tokio::sync::RwLock;
+use tracing::info;
+
+use super::state::{AppState, ProjectLoadStatus};
+use super::state_store::{apply_project_hydration, StateStore};
+
+/// 确保项目已水合；已加载、加载失败或不存在的项目直接返回
+pub async fn ensure_project_hydrated(
+    app_state: &Arc<RwLock<AppState>>,
+    state_store: &StateStore,
+    project: &str,
+) {
+    if app_state.read().await.project_load_status(project) != ProjectLoadStatus::Pending {
+        return;
+    }
+    // 数据库读取在锁外进行；并发水合时先写回者生效
+    let result = state_store.load_project_workspaces(project).await;
+    let mut state = app_state.write().await;
+    apply_project_hydration(&mut state, project, result);
+}
+
+/// 启动后台任务，按名称顺序水合所有 stub 项目
+pub fn spawn_project_hydrator(
+    app_state: Arc<RwLock<AppState>>,
+    state_store: Arc<StateStore>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let started = Instant::now();
+        let pending = app_state.read().await.pending_projects();
+        for project in &pending {
+            ensure_project_hydrated(&app_state, &state_store, project).await;
+        }
+        let failed = {
+            let state = app_state.read().await;
+            pending
+                .iter()
+                .filter(|name| {
+                    matches!(
+                        state.project_load_status(name),
+                        ProjectLoadStatus::Failed { .. }
+                    )
+                })
+                .count()
+        };
+        info!(
+            projects = pending.len(),
+            failed,
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "Project hydration finished"
+        );
+    })
+}
@@ -1,12 +1,17 @@
 //! StateSaver — 后台防抖持久化 actor
 //!
-//! 通过 channel 接收保存信号，500ms 防抖窗口内合并多次请求为一次 SQLite 写入。
+//! 通过 channel 接收保存信号，500ms 防抖窗口内合并多次请求为一次写入。
+//! 写入本身是崩溃安全的：SQLite 后端整体在一个事务内提交，JSON 后端写临时文件 + fsync + rename。
+//! 保存成功后按 [`STATE_BACKUP_INTERVAL`] 额外写一份轮转快照（见 `state_backups`）。
 
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::{mpsc, RwLock};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-use super::state::AppState;
+use super::state::{AppState, StateError};
+use super::state_backups::{self, STATE_BACKUP_INTERVAL, STATE_BACKUP_KEEP};
 use super::state_store::StateStore;
 
 /// 启动 StateSaver 后台 actor，返回用于触发保存的 Sender。
@@ -20,13 +25,14 @@ pub fn spawn_state_saver(
     state_store: Arc<StateStore>,
 ) -> mpsc::Sender<()> {
     let (tx, mut rx) = mpsc::channel::<()>(32);
+    let mut rotation = BackupRotation::new(state_backups::default_backups_dir());
 
     tokio::spawn(async move {
         loop {
             // 等待第一个保存信号
             if rx.recv().await.is_none() {
                 // channel 已关闭，执行最终保存后退出
-                do_save(&app_state, &state_store).await;
+                do_save(&app_state, &state_store, &mut rotation).await;
                 info!("StateSaver: channel closed, final save done");
                 return;
             }
@@ -46,7 +52,7 @@ pub fn spawn_state_saver(
                             }
                             None => {
                                 // channel 关闭，执行最终保存后退出
-                                do_save(&app_state, &state_store).await;
+                                do_save(&app_state, &state_store, &mut rotation).await;
                                 info!("StateSaver: channel closed during debounce, final save done");
                                 return;
                             }
@@ -55,15 +61,41 @@ pub fn spawn_state_saver(
                 }
             }
 
-            do_save(&app_state, &state_store).await;
+            do_save(&app_state, &state_store, &mut rotation).await;
         }
     });
 
     tx
 }
 
-/// 短暂持锁 clone 状态，然后写入 SQLite
-async fn do_save(app_state: &Arc<RwLock<AppState>>, state_store: &Arc<StateStore>) {
+/// 轮转快照的节流状态：启动后的首次保存即写一份，之后至少间隔 [`STATE_BACKUP_INTERVAL`]
+struct BackupRotation {
+    dir: PathBuf,
+    last: Option<Instant>,
+}
+
+impl BackupRotation {
+    fn new(dir: PathBuf) -> Self {
+        Self { dir, last: None }
+    }
+
+    async fn maybe_backup(&mut self, snapshot: &AppState) {
+        if self
+            .last
+            .is_some_and(|last| last.elapsed() < STATE_BACKUP_INTERVAL)
+        {
+            return;
+        }
+        self.last = Some(Instant::now());
+        match state_backups::write_backup(&self.dir, snapshot, STATE_BACKUP_KEEP).await {
+            Ok(info) => info!(backup_id = %info.id, "State backup written"),
+            Err(e) => warn!("StateSaver: failed to write state backup: {}", e),
+        }
+    }
+}
+
+/// 短暂持锁 clone 状态，并同步更新 `last_updated`
+async fn take_snapshot(app_state: &Arc<RwLock<AppState>>) -> AppState {
     let mut state = app_state.write().await;
     // clone 后立即释放锁，最小化持锁时间
     let mut snapshot = state.clone();
@@ -71,11 +103,32 @@ async fn do_save(app_state: &Arc<RwLock<AppState>>, state_store: &Arc<StateStore
     let now = chrono::Utc::now();
     state.last_updated = Some(now);
     snapshot.last_updated = Some(now);
-    drop(state);
+    snapshot
+}
+
+/// 立即保存一次，不经防抖也不写轮转快照（Core 退出前调用）
+pub async fn flush_state(
+    app_state: &Arc<RwLock<AppState>>,
+    state_store: &Arc<StateStore>,
+) -> Result<(), StateError> {
+    let snapshot = take_snapshot(app_state).await;
+    state_store.save(&snapshot).await?;
+    info!("State flushed to disk");
+    Ok(())
+}
+
+/// 短暂持锁 clone 状态，然后写入存储
+async fn do_save(
+    app_state: &Arc<RwLock<AppState>>,
+    state_store: &Arc<StateStore>,
+    rotation: &mut BackupRotation,
+) {
+    let snapshot = take_snapshot(app_state).await;
 
     match state_store.save(&snapshot).await {
         Ok(()) => {
             info!("State saved to disk (debounced)");
+            rotation.maybe_backup(&snapshot).await;
         }
         Err(e) => {
             error!("StateSaver: failed to write state: {}", e);
@@ -5,27 +5,37 @@
 //! - 从 SQLite 读取并组装 AppState
 //! - 将 AppState 全量事务写回 SQLite
 //! - 首次从 legacy JSON (`~/.tidyflow/tidyflow.json`) 一次性迁移
+//! - 选用 JSON 后端（或 SQLite 无法打开）时把 AppState 快照委托给 [`JsonStateStore`]
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
-use sqlx::{Pool, Row, Sqlite};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Column, Pool, Row, Sqlite};
+use tracing::{info, warn};
 
+use super::quarantine;
 use super::sqlite_store;
 use super::state::{
-    AppState, ClientSettings, EvolutionModelSelection, EvolutionStageProfile, KeybindingConfig,
-    NodeAuthTokenEntry, NodeDiscoverySettings, NodeIdentity, PairedNodeEntry, Project,
-    ProjectCommand, RemoteAPIKeyEntry, SetupResultSummary, StateError, TemplateCommand,
-    WorkflowTemplate, Workspace, WorkspaceRecoveryMeta, WorkspaceStatus,
-    WorkspaceTerminalRecoveryEntry, WorkspaceTodoItem,
+    migrate_state_json, AppState, ClientSettings, EvolutionModelSelection, EvolutionStageProfile,
+    KeybindingConfig, NodeAuthTokenEntry, NodeDiscoverySettings, NodeIdentity, PairedNodeEntry,
+    Project, ProjectCommand, ProjectLoadStatus, RemoteAPIKeyEntry, SetupResultSummary, StateError,
+    StateMigration, TemplateCommand, WorkflowTemplate, Workspace, WorkspaceRecoveryMeta,
+    WorkspaceStatus, WorkspaceTerminalRecoveryEntry, WorkspaceTodoItem, STATE_SCHEMA_VERSION,
 };
+use super::state_backend::{JsonStateStore, StateBackend, StateBackendKind};
 
 const DB_SCHEMA_VERSION: &str = "2";
 
 #[derive(Clone)]
 pub struct StateStore {
     pool: Pool<Sqlite>,
+    /// 损坏项目条目的恢复文件目录
+    recovery_dir: PathBuf,
+    /// AppState 快照改由其他后端保存时设置；终端恢复等附属数据仍在 SQLite
+    snapshot: Option<Arc<dyn StateBackend>>,
 }
 
 impl StateStore {
@@ -37,7 +47,35 @@ impl StateStore {
         sqlite_store::legacy_json_path()
     }
 
+    /// 按 [`StateBackendKind::from_env`] 打开默认存储
+    ///
+    /// SQLite 数据库无法打开时回退到内存库 + JSON 快照，保证服务仍可启动。
     pub async fn open_default() -> Result<Self, StateError> {
+        let kind = StateBackendKind::from_env();
+        let mut store = match Self::open_default_sqlite().await {
+            Ok(store) => store,
+            Err(e) => {
+                warn!(error = %e, "Failed to open SQLite state, falling back to JSON state file");
+                let mut store = Self::open_in_memory().await?;
+                store.snapshot = Some(Arc::new(store.default_json_store()));
+                return Ok(store);
+            }
+        };
+        if kind == StateBackendKind::Json {
+            let json = store.default_json_store();
+            // 首次切换到 JSON 时从 SQLite 带入现有状态
+            if !tokio::fs::try_exists(json.path()).await.unwrap_or(false)
+                && store.has_any_state().await?
+            {
+                json.save(&store.load().await?).await?;
+                info!(path = %json.path().display(), "Seeded JSON state file from SQLite");
+            }
+            store.snapshot = Some(Arc::new(json));
+        }
+        Ok(store)
+    }
+
+    async fn open_default_sqlite() -> Result<Self, StateError> {
         let db_path = Self::db_path();
         let target_exists = tokio::fs::try_exists(&db_path)
             .await
@@ -51,7 +89,22 @@ impl StateStore {
             .await
             .map_err(StateError::WriteError)?;
 
-        let store = Self { pool };
+        let store = Self {
+            pool,
+            recovery_dir: quarantine::default_recovery_dir(),
+            snapshot: None,
+        };
+        if target_exists {
+            // 旧 schema 的数据库在补列迁移前先备份
+            if let Some(stored) = store.stored_schema_version().await {
+                if stored.parse::<u32>().unwrap_or(0) < DB_SCHEMA_VERSION.parse().unwrap_or(0) {
+                    let backup = store
+                        .backup_database(&format!("schema-v{}", stored))
+                        .await?;
+                    info!(path = %backup.display(), "Backed up state database before schema migration");
+                }
+            }
+        }
         store.init_schema().await?;
         if !target_exists {
             store.migrate_from_production_db_if_needed().await?;
@@ -60,18 +113,72 @@ impl StateStore {
         Ok(store)
     }
 
-    #[cfg(test)]
-    pub async fn open_in_memory_for_test() -> Result<Self, StateError> {
+    /// 不落盘的 SQLite 存储
+    async fn open_in_memory() -> Result<Self, StateError> {
         let pool = sqlx::sqlite::SqlitePoolOptions::new()
             .max_connections(1)
             .connect("sqlite::memory:")
             .await
             .map_err(|e| StateError::WriteError(e.to_string()))?;
-        let store = Self { pool };
+        let store = Self {
+            pool,
+            recovery_dir: quarantine::default_recovery_dir(),
+            snapshot: None,
+        };
         store.init_schema().await?;
         Ok(store)
     }
 
+    async fn stored_schema_version(&self) -> Option<String> {
+        sqlx::query("SELECT value FROM meta WHERE key = 'schema_version'")
+            .fetch_optional(&self.pool)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|row| row.try_get::<String, _>("value").ok())
+    }
+
+    /// 把当前数据库完整复制到 `tidyflow.db.<label>.bak`（`VACUUM INTO`，与正在进行的写入一致）。
+    ///
+    /// 用于 schema 迁移前与启动时加载失败（随后以空状态启动的保存会覆盖原数据）。
+    pub async fn backup_database(&self, label: &str) -> Result<PathBuf, StateError> {
+        let target = Self::db_path().with_extension(format!("db.{}.bak", label));
+        if tokio::fs::try_exists(&target).await.unwrap_or(false) {
+            tokio::fs::remove_file(&target)
+                .await
+                .map_err(|e| StateError::WriteError(e.to_string()))?;
+        }
+        sqlx::query("VACUUM INTO ?1")
+            .bind(target.to_string_lossy().to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StateError::WriteError(e.to_string()))?;
+        Ok(target)
+    }
+
+    fn default_json_store(&self) -> JsonStateStore {
+        JsonStateStore::new(JsonStateStore::default_path(), self.recovery_dir.clone())
+    }
+
+    /// 当前 AppState 快照所在的后端
+    pub fn backend_kind(&self) -> StateBackendKind {
+        self.snapshot
+            .as_ref()
+            .map_or(StateBackendKind::Sqlite, |backend| backend.kind())
+    }
+
+    pub fn recovery_dir(&self) -> &Path {
+        &self.recovery_dir
+    }
+
+    #[cfg(test)]
+    pub async fn open_in_memory_for_test() -> Result<Self, StateError> {
+        let mut store = Self::open_in_memory().await?;
+        store.recovery_dir =
+            std::env::temp_dir().join(format!("tidyflow-test-recovery-{}", uuid::Uuid::new_v4()));
+        Ok(store)
+    }
+
     pub async fn save_node_profile_settings(
         &self,
         merge_ai_agent: Option<String>,
@@ -84,6 +191,25 @@ impl StateStore {
         last_updated: &DateTime<Utc>,
         state_version: u32,
     ) -> Result<(), StateError> {
+        if let Some(backend) = &self.snapshot {
+            // JSON 文件没有局部更新，读出后整体写回
+            let mut state = backend.load().await?;
+            let settings = &mut state.client_settings;
+            settings.merge_ai_agent = merge_ai_agent;
+            settings.fixed_port = fixed_port;
+            settings.remote_access_enabled = remote_access_enabled;
+            settings.evolution_default_profiles = evolution_default_profiles.to_vec();
+            settings.node_name = node_name;
+            settings.node_discovery_enabled = node_discovery_enabled;
+            state.node_discovery.discovery_enabled = node_discovery_enabled;
+            if let Some(identity) = node_identity {
+                state.node_identity = Some(identity.clone());
+            }
+            state.last_updated = Some(*last_updated);
+            state.version = state_version;
+            return backend.save(&state).await;
+        }
+
         let mut tx = self
             .pool
             .begin()
@@ -164,7 +290,25 @@ impl StateStore {
             .map_err(|e| StateError::WriteError(e.to_string()))
     }
 
+    /// 完整加载：读取项目 stub 后逐个水合 workspace
     pub async fn load(&self) -> Result<AppState, StateError> {
+        let mut state = self.load_project_stubs().await?;
+        for project in state.pending_projects() {
+            self.hydrate_project(&mut state, &project).await;
+        }
+        Ok(state)
+    }
+
+    /// 启动用的轻量加载：读取全局设置与项目行，不读取 workspace 行。
+    ///
+    /// 返回的项目 `workspaces` 为空、状态为 `Pending`，需通过
+    /// [`StateStore::hydrate_project`] 按需补全。
+    pub async fn load_project_stubs(&self) -> Result<AppState, StateError> {
+        if let Some(backend) = &self.snapshot {
+            // JSON 快照总是完整读取，项目直接处于已加载状态
+            return backend.load().await;
+        }
+        let started = std::time::Instant::now();
         self.init_schema().await?;
 
         let has_state = self.has_any_state().await?;
@@ -191,7 +335,10 @@ impl StateStore {
         if let Some(row) = sqlx::query(
             r#"
             SELECT merge_ai_agent, fixed_port, remote_access_enabled, evolution_default_profiles_json
-                 , node_name, node_discovery_enabled
+                 , node_name, node_discovery_enabled, default_shell
+                 , git_proxy, git_offline_mode, terminal_keep_alive, terminal_prevent_sleep
+                 , device_profiles_json, git_hosting_tokens_json, terminal_shell_integration
+                 , push_relay_url, push_tokens_json, git_auto_fetch_interval_secs
             FROM client_settings
             WHERE id = 1
             "#,
@@ -217,6 +364,28 @@ impl StateStore {
                 .ok()
                 .unwrap_or(0)
                 != 0;
+            client_settings.default_shell = row.try_get("default_shell").ok().flatten();
+            client_settings.git_proxy = row.try_get("git_proxy").ok().flatten();
+            client_settings.git_offline_mode = row
+                .try_get::<i64, _>("git_offline_mode")
+                .ok()
+                .unwrap_or(0)
+                != 0;
+            client_settings.terminal_keep_alive = row
+                .try_get::<i64, _>("terminal_keep_alive")
+                .ok()
+                .unwrap_or(0)
+                != 0;
+            client_settings.terminal_prevent_sleep = row
+                .try_get::<i64, _>("terminal_prevent_sleep")
+                .ok()
+                .unwrap_or(0)
+                != 0;
+            client_settings.terminal_shell_integration = row
+                .try_get::<i64, _>("terminal_shell_integration")
+                .ok()
+                .unwrap_or(0)
+                != 0;
             let evolution_default_profiles_json: String = row
                 .try_get("evolution_default_profiles_json")
                 .unwrap_or_else(|_| "[]".to_string());
@@ -224,6 +393,27 @@ impl StateStore {
                 &evolution_default_profiles_json,
             )
             .unwrap_or_default();
+            let device_profiles_json: String = row
+                .try_get("device_profiles_json")
+                .unwrap_or_else(|_| "{}".to_string());
+            client_settings.device_profiles =
+                serde_json::from_str(&device_profiles_json).unwrap_or_default();
+            let git_hosting_tokens_json: String = row
+                .try_get("git_hosting_tokens_json")
+                .unwrap_or_else(|_| "{}".to_string());
+            client_settings.git_hosting_tokens =
+                serde_json::from_str(&git_hosting_tokens_json).unwrap_or_default();
+            client_settings.push_relay_url = row.try_get("push_relay_url").ok().flatten();
+            let push_tokens_json: String = row
+                .try_get("push_tokens_json")
+                .unwrap_or_else(|_| "{}".to_string());
+            client_settings.push_tokens =
+                serde_json::from_str(&push_tokens_json).unwrap_or_default();
+            client_settings.git_auto_fetch_interval_secs = row
+                .try_get::<i64, _>("git_auto_fetch_interval_secs")
+                .ok()
+                .and_then(|v| u64::try_from(v).ok())
+                .unwrap_or(0);
         }
 
         client_settings.workspace_shortcuts = sqlx::query(
@@ -550,145 +740,60 @@ impl StateStore {
                 });
         }
 
-        let workspace_rows = sqlx::query(
-            r#"
-            SELECT
-                project_name, name, worktree_path, branch, status, created_at, last_accessed,
-                setup_success, setup_steps_total, setup_steps_completed, setup_last_error, setup_completed_at,
-                recovery_state, recovery_cursor, recovery_failed_context, recovery_interrupted_at
-            FROM workspaces
-            ORDER BY project_name, name
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| StateError::ReadError(e.to_string()))?;
-        let mut project_workspaces: HashMap<String, HashMap<String, Workspace>> = HashMap::new();
-        for row in workspace_rows {
-            let project_name: String = row.try_get("project_name").unwrap_or_default();
-            let name: String = row.try_get("name").unwrap_or_default();
-            let status_raw: String = row
-                .try_get("status")
-                .unwrap_or_else(|_| "ready".to_string());
-            let created_at = row
-                .try_get::<String, _>("created_at")
-                .ok()
-                .and_then(|s| parse_rfc3339_utc(&s))
-                .unwrap_or_else(Utc::now);
-            let last_accessed = row
-                .try_get::<String, _>("last_accessed")
-                .ok()
-                .and_then(|s| parse_rfc3339_utc(&s))
-                .unwrap_or_else(Utc::now);
-
-            let setup_success = row
-                .try_get::<Option<i64>, _>("setup_success")
-                .ok()
-                .flatten();
-            let setup_result = if let Some(success) = setup_success {
-                let steps_total = row
-                    .try_get::<Option<i64>, _>("setup_steps_total")
-                    .ok()
-                    .flatten()
-                    .and_then(|v| usize::try_from(v).ok())
-                    .unwrap_or(0);
-                let steps_completed = row
-                    .try_get::<Option<i64>, _>("setup_steps_completed")
-                    .ok()
-                    .flatten()
-                    .and_then(|v| usize::try_from(v).ok())
-                    .unwrap_or(0);
-                let last_error = row
-                    .try_get::<Option<String>, _>("setup_last_error")
-                    .ok()
-                    .flatten();
-                let completed_at = row
-                    .try_get::<Option<String>, _>("setup_completed_at")
-                    .ok()
-                    .flatten()
-                    .and_then(|s| parse_rfc3339_utc(&s))
-                    .unwrap_or_else(Utc::now);
-                Some(SetupResultSummary {
-                    success: success != 0,
-                    steps_total,
-                    steps_completed,
-                    last_error,
-                    completed_at,
-                })
-            } else {
-                None
-            };
-
-            // 恢复元数据：旧快照中缺失时回退为 None（可恢复）
-            let recovery_meta = row
-                .try_get::<Option<String>, _>("recovery_state")
-                .ok()
-                .flatten()
-                .map(|state| WorkspaceRecoveryMeta {
-                    recovery_state: state,
-                    recovery_cursor: row
-                        .try_get::<Option<String>, _>("recovery_cursor")
-                        .ok()
-                        .flatten(),
-                    failed_context: row
-                        .try_get::<Option<String>, _>("recovery_failed_context")
-                        .ok()
-                        .flatten(),
-                    interrupted_at: row
-                        .try_get::<Option<String>, _>("recovery_interrupted_at")
-                        .ok()
-                        .flatten()
-                        .and_then(|s| parse_rfc3339_utc(&s)),
-                });
-
-            let workspace = Workspace {
-                name: name.clone(),
-                worktree_path: PathBuf::from(
-                    row.try_get::<String, _>("worktree_path")
-                        .unwrap_or_default(),
-                ),
-                branch: row.try_get("branch").unwrap_or_default(),
-                status: parse_workspace_status(&status_raw),
-                created_at,
-                last_accessed,
-                setup_result,
-                recovery_meta,
-            };
-
-            project_workspaces
-                .entry(project_name)
-                .or_default()
-                .insert(name, workspace);
-        }
-
         let mut projects: HashMap<String, Project> = HashMap::new();
+        let mut project_load_status: HashMap<String, ProjectLoadStatus> = HashMap::new();
         for row in project_rows {
-            let name: String = row.try_get("name").unwrap_or_default();
+            let Ok(name) = row.try_get::<String, _>("name") else {
+                warn!("Skipping project row with unreadable name");
+                continue;
+            };
             let created_at = row
                 .try_get::<String, _>("created_at")
                 .ok()
                 .and_then(|s| parse_rfc3339_utc(&s))
                 .unwrap_or_else(Utc::now);
+            let root_path = row.try_get::<String, _>("root_path").unwrap_or_default();
+            let status = if root_path.trim().is_empty() {
+                let error = "project root_path is missing or unreadable".to_string();
+                let recovery_file = quarantine::quarantine_project_entry(
+                    &self.recovery_dir,
+                    &name,
+                    "sqlite_project",
+                    &error,
+                    row_to_json(&row),
+                );
+                ProjectLoadStatus::Failed {
+                    error,
+                    recovery_file: recovery_file.map(|p| p.to_string_lossy().to_string()),
+                }
+            } else {
+                ProjectLoadStatus::Pending
+            };
+            project_load_status.insert(name.clone(), status);
             projects.insert(
                 name.clone(),
                 Project {
                     name: name.clone(),
-                    root_path: PathBuf::from(
-                        row.try_get::<String, _>("root_path").unwrap_or_default(),
-                    ),
+                    root_path: PathBuf::from(root_path),
                     remote_url: row.try_get("remote_url").ok(),
                     default_branch: row
                         .try_get("default_branch")
                         .unwrap_or_else(|_| "main".to_string()),
                     created_at,
-                    workspaces: project_workspaces.remove(&name).unwrap_or_default(),
+                    workspaces: HashMap::new(),
                     commands: project_commands.remove(&name).unwrap_or_default(),
                 },
             );
         }
+        info!(
+            projects = projects.len(),
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "Loaded project stubs"
+        );
 
         Ok(AppState {
             version,
+            schema_version: STATE_SCHEMA_VERSION,
             projects,
             last_updated,
             client_settings,
@@ -697,9 +802,111 @@ impl StateStore {
             node_discovery,
             paired_nodes,
             node_auth_tokens,
+            project_load_status,
         })
     }
 
+    async fn fetch_project_workspace_rows(
+        &self,
+        project: &str,
+    ) -> Result<Vec<SqliteRow>, StateError> {
+        sqlx::query(
+            r#"
+            SELECT
+                project_name, name, worktree_path, branch, status, created_at, last_accessed,
+                setup_success, setup_steps_total, setup_steps_completed, setup_last_error, setup_completed_at,
+                recovery_state, recovery_cursor, recovery_failed_context, recovery_interrupted_at,
+                port, linked_issue, sparse_paths
+            FROM workspaces
+            WHERE project_name = ?1
+            ORDER BY name
+            "#,
+        )
+        .bind(project)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::ReadError(e.to_string()))
+    }
+
+    /// 读取单个项目的 workspace 行；任何一行损坏都会使整个项目水合失败，
+    /// 并把该项目的 workspace 原始数据隔离到恢复文件
+    pub async fn load_project_workspaces(
+        &self,
+        project: &str,
+    ) -> Result<HashMap<String, Workspace>, ProjectLoadFailure> {
+        if let Some(backend) = &self.snapshot {
+            let state = backend.load().await.map_err(|e| ProjectLoadFailure {
+                error: e.to_string(),
+                recovery_file: None,
+            })?;
+            return Ok(state
+                .projects
+                .get(project)
+                .map(|p| p.workspaces.clone())
+                .unwrap_or_default());
+        }
+        let workspace_rows = self
+            .fetch_project_workspace_rows(project)
+            .await
+            .map_err(|e| ProjectLoadFailure {
+                error: e.to_string(),
+                recovery_file: None,
+            })?;
+
+        let mut workspaces = HashMap::with_capacity(workspace_rows.len());
+        for row in &workspace_rows {
+            match workspace_from_row(row) {
+                Ok(workspace) => {
+                    workspaces.insert(workspace.name.clone(), workspace);
+                }
+                Err(error) => {
+                    let payload =
+                        serde_json::Value::Array(workspace_rows.iter().map(row_to_json).collect());
+                    let recovery_file = quarantine::quarantine_project_entry(
+                        &self.recovery_dir,
+                        project,
+                        "sqlite_workspaces",
+                        &error,
+                        payload,
+                    );
+                    return Err(ProjectLoadFailure {
+                        error,
+                        recovery_file,
+                    });
+                }
+            }
+        }
+        Ok(workspaces)
+    }
+
+    /// 修复加载失败的项目：保留可解析的 workspace 行，跳过损坏行。
+    ///
+    /// 返回可用的 workspace 与被丢弃的行数；被丢弃的数据在加载时已写入恢复文件，
+    /// 调用方将项目标记为已加载后，下一次保存会把损坏行从数据库中清除。
+    pub async fn salvage_project_workspaces(
+        &self,
+        project: &str,
+    ) -> Result<(HashMap<String, Workspace>, usize), StateError> {
+        let workspace_rows = self.fetch_project_workspace_rows(project).await?;
+        let mut workspaces = HashMap::with_capacity(workspace_rows.len());
+        let mut dropped = 0;
+        for row in &workspace_rows {
+            match workspace_from_row(row) {
+                Ok(workspace) => {
+                    workspaces.insert(workspace.name.clone(), workspace);
+                }
+                Err(_) => dropped += 1,
+            }
+        }
+        Ok((workspaces, dropped))
+    }
+
+    /// 水合 `state` 中的一个 stub 项目并更新其加载状态
+    pub async fn hydrate_project(&self, state: &mut AppState, project: &str) {
+        let result = self.load_project_workspaces(project).await;
+        apply_project_hydration(state, project, result);
+    }
+
     async fn migrate_from_production_db_if_needed(&self) -> Result<(), StateError> {
         let active_home = sqlite_store::tidyflow_home_dir();
         let production_home = sqlite_store::production_tidyflow_home_dir();
@@ -719,7 +926,11 @@ impl StateStore {
         let source_pool = sqlite_store::open_single_connection_pool(&source_db_url)
             .await
             .map_err(StateError::WriteError)?;
-        let source_store = Self { pool: source_pool };
+        let source_store = Self {
+            pool: source_pool,
+            recovery_dir: self.recovery_dir.clone(),
+            snapshot: None,
+        };
         source_store.init_schema().await?;
 
         if !source_store.has_any_state().await? {
@@ -732,6 +943,9 @@ impl StateStore {
     }
 
     pub async fn save(&self, state: &AppState) -> Result<(), StateError> {
+        if let Some(backend) = &self.snapshot {
+            return backend.save(state).await;
+        }
         self.init_schema().await?;
 
         let mut tx = self
@@ -743,7 +957,6 @@ impl StateStore {
         for table in [
             "projects",
             "project_commands",
-            "workspaces",
             "workspace_shortcuts",
             "workspace_todos",
             "evolution_stage_profiles",
@@ -761,6 +974,33 @@ impl StateStore {
                 .map_err(|e| StateError::WriteError(e.to_string()))?;
         }
 
+        // 尚未水合（或水合失败）的项目在内存中只有 stub，保留其 workspace 行
+        let unhydrated = state
+            .projects
+            .keys()
+            .filter(|name| state.project_load_status(name) != ProjectLoadStatus::Loaded)
+            .collect::<Vec<_>>();
+        let delete_workspaces_sql = if unhydrated.is_empty() {
+            "DELETE FROM workspaces".to_string()
+        } else {
+            let placeholders = (1..=unhydrated.len())
+                .map(|idx| format!("?{}", idx))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "DELETE FROM workspaces WHERE project_name NOT IN ({})",
+                placeholders
+            )
+        };
+        let mut delete_workspaces = sqlx::query(&delete_workspaces_sql);
+        for name in unhydrated {
+            delete_workspaces = delete_workspaces.bind(name);
+        }
+        delete_workspaces
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| StateError::WriteError(e.to_string()))?;
+
         sqlx::query("DELETE FROM client_settings")
             .execute(&mut *tx)
             .await
@@ -775,9 +1015,20 @@ impl StateStore {
                 remote_access_enabled,
                 evolution_default_profiles_json,
                 node_name,
-                node_discovery_enabled
+                node_discovery_enabled,
+                default_shell,
+                git_proxy,
+                git_offline_mode,
+                terminal_keep_alive,
+                terminal_prevent_sleep,
+                device_profiles_json,
+                git_hosting_tokens_json,
+                terminal_shell_integration,
+                push_relay_url,
+                push_tokens_json,
+                git_auto_fetch_interval_secs
             )
-            VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6)
+            VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
             "#,
         )
         .bind(state.client_settings.merge_ai_agent.clone())
@@ -797,6 +1048,42 @@ impl StateStore {
         } else {
             0_i64
         })
+        .bind(state.client_settings.default_shell.clone())
+        .bind(state.client_settings.git_proxy.clone())
+        .bind(if state.client_settings.git_offline_mode {
+            1_i64
+        } else {
+            0_i64
+        })
+        .bind(if state.client_settings.terminal_keep_alive {
+            1_i64
+        } else {
+            0_i64
+        })
+        .bind(if state.client_settings.terminal_prevent_sleep {
+            1_i64
+        } else {
+            0_i64
+        })
+        .bind(
+            serde_json::to_string(&state.client_settings.device_profiles)
+                .map_err(|e| StateError::WriteError(e.to_string()))?,
+        )
+        .bind(
+            serde_json::to_string(&state.client_settings.git_hosting_tokens)
+                .map_err(|e| StateError::WriteError(e.to_string()))?,
+        )
+        .bind(if state.client_settings.terminal_shell_integration {
+            1_i64
+        } else {
+            0_i64
+        })
+        .bind(state.client_settings.push_relay_url.clone())
+        .bind(
+            serde_json::to_string(&state.client_settings.push_tokens)
+                .map_err(|e| StateError::WriteError(e.to_string()))?,
+        )
+        .bind(i64::try_from(state.client_settings.git_auto_fetch_interval_secs).unwrap_or(i64::MAX))
         .execute(&mut *tx)
         .await
         .map_err(|e| StateError::WriteError(e.to_string()))?;
@@ -1082,12 +1369,13 @@ impl StateStore {
 
                 sqlx::query(
                     r#"
-                    INSERT INTO workspaces (
+                    INSERT OR REPLACE INTO workspaces (
                         project_name, name, worktree_path, branch, status, created_at, last_accessed,
                         setup_success, setup_steps_total, setup_steps_completed, setup_last_error, setup_completed_at,
-                        recovery_state, recovery_cursor, recovery_failed_context, recovery_interrupted_at
+                        recovery_state, recovery_cursor, recovery_failed_context, recovery_interrupted_at,
+                        port, linked_issue, sparse_paths
                     )
-                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)
                     "#,
                 )
                 .bind(&project.name)
@@ -1106,6 +1394,13 @@ impl StateStore {
                 .bind(recovery_cursor)
                 .bind(recovery_failed_context)
                 .bind(recovery_interrupted_at)
+                .bind(workspace.port.map(i64::from))
+                .bind(&workspace.linked_issue)
+                .bind(
+                    (!workspace.sparse_paths.is_empty())
+                        .then(|| serde_json::to_string(&workspace.sparse_paths).ok())
+                        .flatten(),
+                )
                 .execute(&mut *tx)
                 .await
                 .map_err(|e| StateError::WriteError(e.to_string()))?;
@@ -1185,8 +1480,7 @@ impl StateStore {
         let content = tokio::fs::read_to_string(&legacy_path)
             .await
             .map_err(|e| StateError::ReadError(e.to_string()))?;
-        let mut state: AppState =
-            serde_json::from_str(&content).map_err(|e| StateError::ParseError(e.to_string()))?;
+        let (mut state, _) = parse_state_json(&content, &self.recovery_dir)?;
         state.client_settings.migrate();
         self.save(&state).await?;
 
@@ -1247,6 +1541,9 @@ impl StateStore {
                 recovery_cursor TEXT,
                 recovery_failed_context TEXT,
                 recovery_interrupted_at TEXT,
+                port INTEGER,
+                linked_issue TEXT,
+                sparse_paths TEXT,
                 PRIMARY KEY (project_name, name)
             )
             "#,
@@ -1258,7 +1555,18 @@ impl StateStore {
                 remote_access_enabled INTEGER NOT NULL DEFAULT 0,
                 evolution_default_profiles_json TEXT NOT NULL DEFAULT '[]',
                 node_name TEXT,
-                node_discovery_enabled INTEGER NOT NULL DEFAULT 0
+                node_discovery_enabled INTEGER NOT NULL DEFAULT 0,
+                default_shell TEXT,
+                git_proxy TEXT,
+                git_offline_mode INTEGER NOT NULL DEFAULT 0,
+                terminal_keep_alive INTEGER NOT NULL DEFAULT 0,
+                terminal_prevent_sleep INTEGER NOT NULL DEFAULT 0,
+                device_profiles_json TEXT NOT NULL DEFAULT '{}',
+                git_hosting_tokens_json TEXT NOT NULL DEFAULT '{}',
+                terminal_shell_integration INTEGER NOT NULL DEFAULT 0,
+                push_relay_url TEXT,
+                push_tokens_json TEXT NOT NULL DEFAULT '{}',
+                git_auto_fetch_interval_secs INTEGER NOT NULL DEFAULT 0
             )
             "#,
             r#"
@@ -1400,13 +1708,16 @@ impl StateStore {
         Ok(())
     }
 
-    /// 为旧版数据库的 workspaces 表追加恢复元数据列（幂等，列已存在时跳过）
+    /// 为旧版数据库的 workspaces 表追加恢复元数据、预留端口、关联 issue 与稀疏检出列（幂等，列已存在时跳过）
     async fn ensure_workspace_recovery_columns(&self) -> Result<(), StateError> {
         let migrations: &[&str] = &[
             "ALTER TABLE workspaces ADD COLUMN recovery_state TEXT",
             "ALTER TABLE workspaces ADD COLUMN recovery_cursor TEXT",
             "ALTER TABLE workspaces ADD COLUMN recovery_failed_context TEXT",
             "ALTER TABLE workspaces ADD COLUMN recovery_interrupted_at TEXT",
+            "ALTER TABLE workspaces ADD COLUMN port INTEGER",
+            "ALTER TABLE workspaces ADD COLUMN linked_issue TEXT",
+            "ALTER TABLE workspaces ADD COLUMN sparse_paths TEXT",
         ];
         for sql in migrations {
             match sqlx::query(sql).execute(&self.pool).await {
@@ -1427,6 +1738,17 @@ impl StateStore {
             "ALTER TABLE client_settings ADD COLUMN evolution_default_profiles_json TEXT NOT NULL DEFAULT '[]'",
             "ALTER TABLE client_settings ADD COLUMN node_name TEXT",
             "ALTER TABLE client_settings ADD COLUMN node_discovery_enabled INTEGER NOT NULL DEFAULT 0",
+            "ALTER TABLE client_settings ADD COLUMN default_shell TEXT",
+            "ALTER TABLE client_settings ADD COLUMN git_proxy TEXT",
+            "ALTER TABLE client_settings ADD COLUMN git_offline_mode INTEGER NOT NULL DEFAULT 0",
+            "ALTER TABLE client_settings ADD COLUMN terminal_keep_alive INTEGER NOT NULL DEFAULT 0",
+            "ALTER TABLE client_settings ADD COLUMN terminal_prevent_sleep INTEGER NOT NULL DEFAULT 0",
+            "ALTER TABLE client_settings ADD COLUMN device_profiles_json TEXT NOT NULL DEFAULT '{}'",
+            "ALTER TABLE client_settings ADD COLUMN git_hosting_tokens_json TEXT NOT NULL DEFAULT '{}'",
+            "ALTER TABLE client_settings ADD COLUMN terminal_shell_integration INTEGER NOT NULL DEFAULT 0",
+            "ALTER TABLE client_settings ADD COLUMN push_relay_url TEXT",
+            "ALTER TABLE client_settings ADD COLUMN push_tokens_json TEXT NOT NULL DEFAULT '{}'",
+            "ALTER TABLE client_settings ADD COLUMN git_auto_fetch_interval_secs INTEGER NOT NULL DEFAULT 0",
         ];
         for sql in migrations {
             match sqlx::query(sql).execute(&self.pool).await {
@@ -1583,6 +1905,244 @@ impl StateStore {
     }
 }
 
+#[async_trait::async_trait]
+impl StateBackend for StateStore {
+    fn kind(&self) -> StateBackendKind {
+        self.backend_kind()
+    }
+
+    async fn load(&self) -> Result<AppState, StateError> {
+        StateStore::load(self).await
+    }
+
+    async fn save(&self, state: &AppState) -> Result<(), StateError> {
+        StateStore::save(self, state).await
+    }
+}
+
+/// 将水合结果写回 `state`：成功时合并 workspace（内存中已有的同名项优先），
+/// 失败时仅把该项目标记为 `Failed`，不影响其他项目
+/// 解析 JSON 状态（legacy `tidyflow.json` 或 JSON 后端文件）：先按 `schema_version` 迁移到当前结构，
+/// 无法解析的项目条目隔离到恢复文件，其余照常加载；同时返回所做的迁移
+pub(super) fn parse_state_json(
+    content: &str,
+    recovery_dir: &Path,
+) -> Result<(AppState, StateMigration), StateError> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| StateError::ParseError(e.to_string()))?;
+    let migration = migrate_state_json(&mut value)?;
+    if let Some(projects) = value
+        .get_mut("projects")
+        .and_then(|projects| projects.as_object_mut())
+    {
+        let corrupt = projects
+            .iter()
+            .filter_map(|(name, entry)| {
+                serde_json::from_value::<Project>(entry.clone())
+                    .err()
+                    .map(|e| (name.clone(), e.to_string()))
+            })
+            .collect::<Vec<_>>();
+        for (name, error) in corrupt {
+            if let Some(entry) = projects.remove(&name) {
+                quarantine::quarantine_project_entry(
+                    recovery_dir,
+                    &name,
+                    "legacy_json",
+                    &error,
+                    entry,
+                );
+            }
+        }
+    }
+    serde_json::from_value(value)
+        .map(|state| (state, migration))
+        .map_err(|e| StateError::ParseError(e.to_string()))
+}
+
+/// 单个项目水合失败的原因
+#[derive(Debug, Clone)]
+pub struct ProjectLoadFailure {
+    pub error: String,
+    /// 数据损坏时写入的恢复文件；数据库读取失败等暂时性错误时为 None
+    pub recovery_file: Option<PathBuf>,
+}
+
+pub fn apply_project_hydration(
+    state: &mut AppState,
+    project: &str,
+    result: Result<HashMap<String, Workspace>, ProjectLoadFailure>,
+) {
+    if state.project_load_status(project) != ProjectLoadStatus::Pending {
+        return;
+    }
+    let Some(entry) = state.projects.get_mut(project) else {
+        state.project_load_status.remove(project);
+        return;
+    };
+    match result {
+        Ok(workspaces) => {
+            for (name, workspace) in workspaces {
+                entry.workspaces.entry(name).or_insert(workspace);
+            }
+            state.project_load_status.remove(project);
+        }
+        Err(failure) => {
+            warn!(
+                project,
+                error = %failure.error,
+                recovery_file = ?failure.recovery_file,
+                "Failed to hydrate project workspaces"
+            );
+            state.project_load_status.insert(
+                project.to_string(),
+                ProjectLoadStatus::Failed {
+                    error: failure.error,
+                    recovery_file: failure
+                        .recovery_file
+                        .map(|p| p.to_string_lossy().to_string()),
+                },
+            );
+        }
+    }
+}
+
+/// 将一行记录尽量无损地转为 JSON（写入恢复文件用）；BLOB 以十六进制保存
+fn row_to_json(row: &SqliteRow) -> serde_json::Value {
+    let mut object = serde_json::Map::new();
+    for (idx, column) in row.columns().iter().enumerate() {
+        let value = if let Ok(Some(text)) = row.try_get::<Option<String>, _>(idx) {
+            serde_json::Value::String(text)
+        } else if let Ok(Some(number)) = row.try_get::<Option<i64>, _>(idx) {
+            serde_json::Value::from(number)
+        } else if let Ok(Some(number)) = row.try_get::<Option<f64>, _>(idx) {
+            serde_json::Value::from(number)
+        } else if let Ok(Some(bytes)) = row.try_get::<Option<Vec<u8>>, _>(idx) {
+            serde_json::Value::String(format!("hex:{}", hex_encode(&bytes)))
+        } else {
+            serde_json::Value::Null
+        };
+        object.insert(column.name().to_string(), value);
+    }
+    serde_json::Value::Object(object)
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 解析一行 workspace 记录；必需列无法读取时返回错误
+fn workspace_from_row(row: &SqliteRow) -> Result<Workspace, String> {
+    let name: String = row
+        .try_get("name")
+        .map_err(|e| format!("workspace name unreadable: {}", e))?;
+    if name.trim().is_empty() {
+        return Err("workspace row has an empty name".to_string());
+    }
+    let worktree_path: String = row
+        .try_get("worktree_path")
+        .map_err(|e| format!("workspace '{}' worktree_path unreadable: {}", name, e))?;
+    let status_raw: String = row
+        .try_get("status")
+        .unwrap_or_else(|_| "ready".to_string());
+    let created_at = row
+        .try_get::<String, _>("created_at")
+        .ok()
+        .and_then(|s| parse_rfc3339_utc(&s))
+        .unwrap_or_else(Utc::now);
+    let last_accessed = row
+        .try_get::<String, _>("last_accessed")
+        .ok()
+        .and_then(|s| parse_rfc3339_utc(&s))
+        .unwrap_or_else(Utc::now);
+
+    let setup_success = row
+        .try_get::<Option<i64>, _>("setup_success")
+        .ok()
+        .flatten();
+    let setup_result = if let Some(success) = setup_success {
+        let steps_total = row
+            .try_get::<Option<i64>, _>("setup_steps_total")
+            .ok()
+            .flatten()
+            .and_then(|v| usize::try_from(v).ok())
+            .unwrap_or(0);
+        let steps_completed = row
+            .try_get::<Option<i64>, _>("setup_steps_completed")
+            .ok()
+            .flatten()
+            .and_then(|v| usize::try_from(v).ok())
+            .unwrap_or(0);
+        let last_error = row
+            .try_get::<Option<String>, _>("setup_last_error")
+            .ok()
+            .flatten();
+        let completed_at = row
+            .try_get::<Option<String>, _>("setup_completed_at")
+            .ok()
+            .flatten()
+            .and_then(|s| parse_rfc3339_utc(&s))
+            .unwrap_or_else(Utc::now);
+        Some(SetupResultSummary {
+            success: success != 0,
+            steps_total,
+            steps_completed,
+            last_error,
+            completed_at,
+        })
+    } else {
+        None
+    };
+
+    // 恢复元数据：旧快照中缺失时回退为 None（可恢复）
+    let recovery_meta = row
+        .try_get::<Option<String>, _>("recovery_state")
+        .ok()
+        .flatten()
+        .map(|state| WorkspaceRecoveryMeta {
+            recovery_state: state,
+            recovery_cursor: row
+                .try_get::<Option<String>, _>("recovery_cursor")
+                .ok()
+                .flatten(),
+            failed_context: row
+                .try_get::<Option<String>, _>("recovery_failed_context")
+                .ok()
+                .flatten(),
+            interrupted_at: row
+                .try_get::<Option<String>, _>("recovery_interrupted_at")
+                .ok()
+                .flatten()
+                .and_then(|s| parse_rfc3339_utc(&s)),
+        });
+
+    Ok(Workspace {
+        name,
+        worktree_path: PathBuf::from(worktree_path),
+        branch: row.try_get("branch").unwrap_or_default(),
+        status: parse_workspace_status(&status_raw),
+        created_at,
+        last_accessed,
+        setup_result,
+        recovery_meta,
+        port: row
+            .try_get::<Option<i64>, _>("port")
+            .ok()
+            .flatten()
+            .and_then(|v| u16::try_from(v).ok()),
+        linked_issue: row
+            .try_get::<Option<String>, _>("linked_issue")
+            .ok()
+            .flatten(),
+        sparse_paths: row
+            .try_get::<Option<String>, _>("sparse_paths")
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+    })
+}
+
 fn parse_rfc3339_utc(raw: &str) -> Option<DateTime<Utc>> {
     DateTime::parse_from_rfc3339(raw)
         .ok()
@@ -1612,6 +2172,7 @@ fn workspace_status_to_str(status: &WorkspaceStatus) -> &'static str {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::workspace::state::DeviceSettingsProfile;
     use chrono::Utc;
     use std::collections::HashMap;
 
@@ -1628,6 +2189,33 @@ mod tests {
         state.client_settings.merge_ai_agent = Some("codex".to_string());
         state.client_settings.fixed_port = 18439;
         state.client_settings.remote_access_enabled = true;
+        state.client_settings.default_shell = Some("fish".to_string());
+        state.client_settings.git_proxy = Some("http://127.0.0.1:7890".to_string());
+        state.client_settings.git_offline_mode = true;
+        state
+            .client_settings
+            .git_hosting_tokens
+            .insert("github.com".to_string(), "ghp_test".to_string());
+        state.client_settings.terminal_keep_alive = true;
+        state.client_settings.terminal_prevent_sleep = true;
+        state.client_settings.terminal_shell_integration = true;
+        state.client_settings.push_relay_url = Some("https://push.example.com/notify".to_string());
+        state.client_settings.git_auto_fetch_interval_secs = 900;
+        state
+            .client_settings
+            .push_tokens
+            .insert("iphone-1".to_string(), "apns-token".to_string());
+        state.client_settings.device_profiles.insert(
+            "ipad-1".to_string(),
+            DeviceSettingsProfile {
+                name: Some("iPad".to_string()),
+                workspace_shortcuts: Some(HashMap::from([(
+                    "1".to_string(),
+                    "demo/ipad".to_string(),
+                )])),
+                keybindings: None,
+            },
+        );
         state.client_settings.evolution_default_profiles = vec![EvolutionStageProfile {
             stage: "auto_commit".to_string(),
             ai_tool: "opencode".to_string(),
@@ -1693,6 +2281,9 @@ mod tests {
                         completed_at: now,
                     }),
                     recovery_meta: None,
+                    port: None,
+                    linked_issue: None,
+                    sparse_paths: Vec::new(),
                 },
             )]),
             commands: vec![ProjectCommand {
@@ -1712,6 +2303,39 @@ mod tests {
         assert_eq!(loaded.version, 42);
         assert_eq!(loaded.client_settings.fixed_port, 18439);
         assert!(loaded.client_settings.remote_access_enabled);
+        assert_eq!(
+            loaded.client_settings.default_shell.as_deref(),
+            Some("fish")
+        );
+        assert_eq!(
+            loaded.client_settings.git_proxy.as_deref(),
+            Some("http://127.0.0.1:7890")
+        );
+        assert!(loaded.client_settings.git_offline_mode);
+        assert_eq!(
+            loaded
+                .client_settings
+                .git_hosting_tokens
+                .get("github.com")
+                .map(String::as_str),
+            Some("ghp_test")
+        );
+        assert!(loaded.client_settings.terminal_keep_alive);
+        assert!(loaded.client_settings.terminal_prevent_sleep);
+        assert!(loaded.client_settings.terminal_shell_integration);
+        assert_eq!(loaded.client_settings.git_auto_fetch_interval_secs, 900);
+        assert_eq!(
+            loaded.client_settings.push_relay_url.as_deref(),
+            Some("https://push.example.com/notify")
+        );
+        assert_eq!(
+            loaded.client_settings.push_tokens,
+            state.client_settings.push_tokens
+        );
+        assert_eq!(
+            loaded.client_settings.device_profiles["ipad-1"],
+            state.client_settings.device_profiles["ipad-1"]
+        );
         assert_eq!(
             loaded.client_settings.merge_ai_agent.as_deref(),
             Some("codex")
@@ -1822,6 +2446,9 @@ mod tests {
                 failed_context: Some(r#"{"cycle_id":"c1","stage":"initializing"}"#.to_string()),
                 interrupted_at: Some(interrupted_at),
             }),
+            port: None,
+            linked_issue: None,
+            sparse_paths: Vec::new(),
         };
 
         // project-a: feature-interrupted（中断态）
@@ -1848,6 +2475,9 @@ mod tests {
             last_accessed: now,
             setup_result: None,
             recovery_meta: None,
+            port: Some(4173),
+            linked_issue: Some("https://github.com/o/r/issues/12".to_string()),
+            sparse_paths: vec!["services/api".to_string(), "libs/shared".to_string()],
         };
         let mut proj_b = Project {
             name: "project-b".to_string(),
@@ -1917,6 +2547,18 @@ mod tests {
             loaded_ws_b.recovery_meta.is_none(),
             "project-b workspace should have no recovery_meta (no cross-workspace leakage)"
         );
+        assert_eq!(loaded_ws_b.port, Some(4173), "port should roundtrip");
+        assert_eq!(loaded_ws_a.port, None);
+        assert_eq!(
+            loaded_ws_b.linked_issue.as_deref(),
+            Some("https://github.com/o/r/issues/12")
+        );
+        assert_eq!(loaded_ws_a.linked_issue, None);
+        assert_eq!(
+            loaded_ws_b.sparse_paths,
+            vec!["services/api", "libs/shared"]
+        );
+        assert!(loaded_ws_a.sparse_paths.is_empty());
     }
 
     /// CHK-003: 旧快照（无 recovery 列）加载时不应崩溃，应回退为 None
@@ -1940,6 +2582,9 @@ mod tests {
             last_accessed: now,
             setup_result: None,
             recovery_meta: None, // 无恢复元数据
+            port: None,
+            linked_issue: None,
+            sparse_paths: Vec::new(),
         };
         let mut proj = Project {
             name: "proj".to_string(),
@@ -1979,4 +2624,108 @@ mod tests {
         };
         assert!(interrupted_meta.needs_attention());
     }
+
+    #[tokio::test]
+    async fn corrupt_project_is_isolated_during_lazy_load() {
+        let store = StateStore::open_in_memory_for_test()
+            .await
+            .expect("in-memory store should initialize");
+
+        let now = Utc::now();
+        let mut state = AppState::default();
+        for name in ["alpha", "broken"] {
+            let mut project = Project {
+                name: name.to_string(),
+                root_path: PathBuf::from(format!("/tmp/{}", name)),
+                remote_url: None,
+                default_branch: "main".to_string(),
+                created_at: now,
+                workspaces: HashMap::new(),
+                commands: vec![],
+            };
+            project.workspaces.insert(
+                "ws".to_string(),
+                Workspace {
+                    name: "ws".to_string(),
+                    worktree_path: PathBuf::from(format!("/tmp/{}/.worktrees/ws", name)),
+                    branch: "tidy/ws".to_string(),
+                    status: WorkspaceStatus::Ready,
+                    created_at: now,
+                    last_accessed: now,
+                    setup_result: None,
+                    recovery_meta: None,
+                    port: None,
+                    linked_issue: None,
+                    sparse_paths: Vec::new(),
+                },
+            );
+            state.add_project(project);
+        }
+        store.save(&state).await.expect("save should succeed");
+        sqlx::query("UPDATE workspaces SET worktree_path = X'00FF' WHERE project_name = 'broken'")
+            .execute(&store.pool)
+            .await
+            .expect("corrupt row");
+
+        let stubs = store.load_project_stubs().await.expect("stubs should load");
+        assert_eq!(stubs.pending_projects(), vec!["alpha", "broken"]);
+        assert!(stubs.projects.values().all(|p| p.workspaces.is_empty()));
+
+        let loaded = store.load().await.expect("load should succeed");
+        assert_eq!(
+            loaded.project_load_status("alpha"),
+            ProjectLoadStatus::Loaded
+        );
+        assert!(loaded.projects["alpha"].workspaces.contains_key("ws"));
+        let ProjectLoadStatus::Failed {
+            recovery_file: Some(recovery_file),
+            ..
+        } = loaded.project_load_status("broken")
+        else {
+            panic!("broken project should fail with a recovery file");
+        };
+        let record: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&recovery_file).unwrap()).unwrap();
+        assert_eq!(record["payload"][0]["worktree_path"], "hex:00ff");
+        assert!(loaded.projects["broken"].workspaces.is_empty());
+
+        let (salvaged, dropped) = store
+            .salvage_project_workspaces("broken")
+            .await
+            .expect("salvage should succeed");
+        assert!(salvaged.is_empty());
+        assert_eq!(dropped, 1);
+
+        // 失败项目的 workspace 行不会被 stub 覆盖
+        store.save(&loaded).await.expect("save should succeed");
+        let rows: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM workspaces WHERE project_name = 'broken'")
+                .fetch_one(&store.pool)
+                .await
+                .expect("count rows");
+        assert_eq!(rows, 1);
+    }
+
+    #[test]
+    fn legacy_json_quarantines_corrupt_projects() {
+        let tmp = tempfile::tempdir().unwrap();
+        let content = r#"{
+            "version": 1,
+            "projects": {
+                "good": {
+                    "name": "good",
+                    "root_path": "/tmp/good",
+                    "default_branch": "main",
+                    "created_at": "2024-01-01T00:00:00Z",
+                    "workspaces": {}
+                },
+                "bad": { "name": "bad", "root_path": 42 }
+            }
+        }"#;
+
+        let (state, _) = parse_state_json(content, tmp.path()).expect("state should parse");
+        assert!(state.projects.contains_key("good"));
+        assert!(!state.projects.contains_key("bad"));
+        assert!(tmp.path().join("legacy_json-bad.json").exists());
+    }
 }
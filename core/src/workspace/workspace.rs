@@ -1,14 +1,21 @@
 //! Workspace management using git worktree
 
+use crate::util::exec_env::{exec_env_for, git_command};
+use crate::workspace::branch_name::{
+    is_valid_branch_name, render_branch_name, template_uses_petname, validate_branch_template,
+    workspace_name_for_branch, BranchNameContext, DEFAULT_BRANCH_TEMPLATE,
+};
 use crate::workspace::config::ProjectConfig;
-use crate::workspace::setup::SetupExecutor;
+use crate::workspace::lfs;
+use crate::workspace::project::CloneProgress;
+use crate::workspace::setup::{reserve_free_port, SetupExecutor};
+use crate::workspace::sparse;
 use crate::workspace::state::{
-    AppState, SetupResultSummary, StateError, Workspace, WorkspaceStatus,
+    AppState, SetupResultSummary, StateError, Workspace, WorkspaceStatus, DEFAULT_WORKSPACE_NAME,
 };
 use chrono::Utc;
 use petname::{Generator, Petnames};
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use thiserror::Error;
 use tracing::{error, info, warn};
 
@@ -30,19 +37,199 @@ pub enum WorkspaceError {
     IoError(String),
     #[error("Setup failed: {0}")]
     SetupFailed(String),
+    #[error("Invalid branch name: {0}")]
+    InvalidBranchName(String),
+    #[error("Invalid workspace name: {0}")]
+    InvalidName(String),
+    #[error("{0}")]
+    InvalidSparsePath(String),
 }
 
 pub struct WorkspaceManager;
 
+/// [`WorkspaceManager::repair`] 的结果
+#[derive(Debug, Clone)]
+pub struct WorkspaceRepair {
+    pub workspace: Workspace,
+    /// 实际执行的修复动作，全部正常时为空
+    pub actions: Vec<String>,
+}
+
 impl WorkspaceManager {
     /// Create a new workspace using git worktree.
-    /// 名称由 Core 用 petname 生成唯一名。
+    /// 分支名按项目 `[git] branch_template` 渲染（默认 `tidy/{petname}`），
+    /// workspace 名取分支名最后一段；`template` 为选用的工作流模板 ID。
     pub fn create(
         state: &mut AppState,
         project_name: &str,
         from_branch: Option<&str>,
+        template: Option<&str>,
+        run_setup: bool,
+    ) -> Result<Workspace, WorkspaceError> {
+        Self::create_with_progress(
+            state,
+            project_name,
+            from_branch,
+            template,
+            run_setup,
+            &[],
+            |_, _| {},
+        )
+    }
+
+    /// 同 [`Self::create`]，并以 `(workspace 名, 进度)` 回调 worktree 准备进度
+    /// （目前为 Git LFS 对象下载）；`sparse_paths` 非空时以 cone 模式稀疏检出这些目录
+    pub fn create_with_progress(
+        state: &mut AppState,
+        project_name: &str,
+        from_branch: Option<&str>,
+        template: Option<&str>,
+        run_setup: bool,
+        sparse_paths: &[String],
+        mut on_progress: impl FnMut(&str, CloneProgress),
+    ) -> Result<Workspace, WorkspaceError> {
+        let sparse_paths = sparse::normalize_sparse_paths(sparse_paths)
+            .map_err(WorkspaceError::InvalidSparsePath)?;
+        let (project_root, source_branch) =
+            Self::checked_source_branch(state, project_name, from_branch)?;
+
+        let config = ProjectConfig::load(&project_root).unwrap_or_default();
+        let branch_template = config
+            .git
+            .branch_template
+            .as_deref()
+            .unwrap_or(DEFAULT_BRANCH_TEMPLATE);
+        validate_branch_template(branch_template).map_err(WorkspaceError::InvalidBranchName)?;
+        let user = Self::branch_user_name(&project_root);
+        let date = Utc::now().format("%Y%m%d").to_string();
+
+        // Generate branch name with retry on conflict (branch or workspace name).
+        // 模板不含 {petname} 时无法靠重新生成去重，改为追加数字后缀
+        let uses_petname = template_uses_petname(branch_template);
+        let max_attempts: u32 = if uses_petname { 5 } else { 100 };
+        let mut attempt = 0;
+
+        let (workspace_branch, workspace_display_name) = loop {
+            let petname = Self::generate_random_branch_name();
+            let ctx = BranchNameContext {
+                petname: &petname,
+                user: &user,
+                date: &date,
+                template,
+            };
+            let mut branch = render_branch_name(branch_template, &ctx)
+                .map_err(WorkspaceError::InvalidBranchName)?;
+            if !uses_petname && attempt > 0 {
+                branch = format!("{}-{}", branch, attempt + 1);
+            }
+            let display = workspace_name_for_branch(&branch).to_string();
+
+            let name_exists = state
+                .get_project(project_name)
+                .map(|p| p.get_workspace(&display).is_some())
+                .unwrap_or(false);
+
+            if !name_exists && !Self::branch_ref_conflicts(&project_root, &branch) {
+                break (branch, display);
+            }
+            attempt += 1;
+            if attempt >= max_attempts {
+                return Err(WorkspaceError::GitError(format!(
+                    "Failed to generate unique workspace name after {} attempts",
+                    max_attempts
+                )));
+            }
+        };
+
+        Self::add_worktree(
+            state,
+            project_name,
+            &workspace_display_name,
+            &workspace_branch,
+            &source_branch,
+            run_setup,
+            &sparse_paths,
+            &mut on_progress,
+        )
+    }
+
+    /// 按指定名称创建 workspace（声明式 manifest 使用）。
+    /// `branch` 未指定时按分支模板渲染，`{petname}` 取 workspace 名；
+    /// 模板不含 `{petname}` 时在渲染结果后追加 `-<name>`。分支已存在时直接检出。
+    pub fn create_named(
+        state: &mut AppState,
+        project_name: &str,
+        name: &str,
+        branch: Option<&str>,
+        from_branch: Option<&str>,
         run_setup: bool,
     ) -> Result<Workspace, WorkspaceError> {
+        if name == DEFAULT_WORKSPACE_NAME || name.contains('/') || !is_valid_branch_name(name) {
+            return Err(WorkspaceError::InvalidName(name.to_string()));
+        }
+        let exists = state
+            .get_project(project_name)
+            .ok_or_else(|| WorkspaceError::ProjectNotFound(project_name.to_string()))?
+            .get_workspace(name)
+            .is_some();
+        if exists {
+            return Err(WorkspaceError::AlreadyExists(name.to_string()));
+        }
+        let (project_root, source_branch) =
+            Self::checked_source_branch(state, project_name, from_branch)?;
+
+        let branch = match branch {
+            Some(branch) => {
+                if !is_valid_branch_name(branch) {
+                    return Err(WorkspaceError::InvalidBranchName(branch.to_string()));
+                }
+                branch.to_string()
+            }
+            None => {
+                let config = ProjectConfig::load(&project_root).unwrap_or_default();
+                let branch_template = config
+                    .git
+                    .branch_template
+                    .as_deref()
+                    .unwrap_or(DEFAULT_BRANCH_TEMPLATE);
+                validate_branch_template(branch_template)
+                    .map_err(WorkspaceError::InvalidBranchName)?;
+                let user = Self::branch_user_name(&project_root);
+                let date = Utc::now().format("%Y%m%d").to_string();
+                let ctx = BranchNameContext {
+                    petname: name,
+                    user: &user,
+                    date: &date,
+                    template: None,
+                };
+                let rendered = render_branch_name(branch_template, &ctx)
+                    .map_err(WorkspaceError::InvalidBranchName)?;
+                if template_uses_petname(branch_template) {
+                    rendered
+                } else {
+                    format!("{}-{}", rendered, name)
+                }
+            }
+        };
+
+        Self::add_worktree(
+            state,
+            project_name,
+            name,
+            &branch,
+            &source_branch,
+            run_setup,
+            &[],
+            &mut |_, _| {},
+        )
+    }
+
+    /// 校验项目可创建 workspace，返回项目根目录与源分支
+    fn checked_source_branch(
+        state: &AppState,
+        project_name: &str,
+        from_branch: Option<&str>,
+    ) -> Result<(PathBuf, String), WorkspaceError> {
         // Get project
         let project = state
             .get_project(project_name)
@@ -67,16 +254,15 @@ impl WorkspaceManager {
         let default_branch = project.default_branch.clone();
 
         // Determine source branch
-        let source_branch = from_branch.unwrap_or(&default_branch);
+        let source_branch = from_branch.unwrap_or(&default_branch).to_string();
 
         // Check if source branch exists locally
-        let branch_check = Command::new("git")
+        let branch_check = git_command(&project_root)
             .args([
                 "rev-parse",
                 "--verify",
                 &format!("refs/heads/{}", source_branch),
             ])
-            .current_dir(&project_root)
             .output();
 
         let source_branch_exists = matches!(branch_check, Ok(ref out) if out.status.success());
@@ -88,63 +274,54 @@ impl WorkspaceManager {
             )));
         }
 
-        // Generate random branch name with retry on conflict (branch or workspace name)
-        let mut workspace_branch = format!("tidy/{}", Self::generate_random_branch_name());
-        let mut attempts = 0;
-        const MAX_ATTEMPTS: u32 = 5;
-
-        let workspace_display_name = loop {
-            let display = workspace_branch
-                .strip_prefix("tidy/")
-                .unwrap_or(&workspace_branch)
-                .to_string();
+        Ok((project_root, source_branch))
+    }
 
-            let branch_exists = Command::new("git")
-                .args([
-                    "show-ref",
-                    "--quiet",
-                    "--verify",
-                    &format!("refs/heads/{}", workspace_branch),
-                ])
-                .current_dir(&project_root)
-                .output();
-            let branch_exists = matches!(branch_exists, Ok(ref out) if out.status.success());
+    /// 在 `worktrees_dir/<name>` 创建 worktree 并登记 workspace；分支已存在时直接检出。
+    /// `sparse_paths`（已规范化）非空时只检出这些目录；仓库使用 Git LFS 时检出后单独拉取 LFS 对象。
+    #[allow(clippy::too_many_arguments)]
+    fn add_worktree(
+        state: &mut AppState,
+        project_name: &str,
+        workspace_display_name: &str,
+        workspace_branch: &str,
+        source_branch: &str,
+        run_setup: bool,
+        sparse_paths: &[String],
+        on_progress: &mut dyn FnMut(&str, CloneProgress),
+    ) -> Result<Workspace, WorkspaceError> {
+        let project = state
+            .get_project(project_name)
+            .ok_or_else(|| WorkspaceError::ProjectNotFound(project_name.to_string()))?;
+        let project_root = project.root_path.clone();
+        let worktrees_dir = project.worktrees_dir();
+        std::fs::create_dir_all(&worktrees_dir)
+            .map_err(|e| WorkspaceError::IoError(e.to_string()))?;
 
-            let name_exists = state
-                .get_project(project_name)
-                .map(|p| p.get_workspace(&display).is_some())
-                .unwrap_or(false);
+        let worktree_path = worktrees_dir.join(workspace_display_name);
+        // worktree 路径作为参数传给 git，需转换为项目执行环境内的路径
+        let worktree_arg = exec_env_for(&project_root).to_exec_path(&worktree_path);
 
-            if !branch_exists && !name_exists {
-                break display;
+        // LFS 对象改由检出后的 `git lfs pull` 下载，以便推送进度
+        let lfs_status =
+            lfs::uses_lfs(&project_root, source_branch).then(|| lfs::lfs_available(&project_root));
+        let worktree_add = || {
+            let mut cmd = git_command(&project_root);
+            if lfs_status == Some(true) {
+                cmd.env("GIT_LFS_SKIP_SMUDGE", "1");
             }
-            workspace_branch = format!("tidy/{}", Self::generate_random_branch_name());
-            attempts += 1;
-            if attempts >= MAX_ATTEMPTS {
-                return Err(WorkspaceError::GitError(format!(
-                    "Failed to generate unique workspace name after {} attempts",
-                    MAX_ATTEMPTS
-                )));
+            cmd.args(["worktree", "add"]);
+            // 稀疏检出时先不检出，设置好规则后再按规则检出
+            if !sparse_paths.is_empty() {
+                cmd.arg("--no-checkout");
             }
+            cmd
         };
 
-        let worktrees_dir = project.worktrees_dir();
-        std::fs::create_dir_all(&worktrees_dir)
-            .map_err(|e| WorkspaceError::IoError(e.to_string()))?;
-
-        let worktree_path = worktrees_dir.join(&workspace_display_name);
-
         // Create the worktree with a new branch
-        let output = Command::new("git")
-            .args([
-                "worktree",
-                "add",
-                "-b",
-                &workspace_branch,
-                worktree_path.to_str().unwrap(),
-                source_branch,
-            ])
-            .current_dir(&project_root)
+        let mut created_branch = true;
+        let output = worktree_add()
+            .args(["-b", workspace_branch, &worktree_arg, source_branch])
             .output()
             .map_err(|e| WorkspaceError::GitError(e.to_string()))?;
 
@@ -152,14 +329,9 @@ impl WorkspaceManager {
             let stderr = String::from_utf8_lossy(&output.stderr);
             // If branch already exists, try without -b
             if stderr.contains("already exists") {
-                let output = Command::new("git")
-                    .args([
-                        "worktree",
-                        "add",
-                        worktree_path.to_str().unwrap(),
-                        &workspace_branch,
-                    ])
-                    .current_dir(&project_root)
+                created_branch = false;
+                let output = worktree_add()
+                    .args([&worktree_arg, workspace_branch])
                     .output()
                     .map_err(|e| WorkspaceError::GitError(e.to_string()))?;
 
@@ -178,16 +350,52 @@ impl WorkspaceManager {
             branch = workspace_branch,
             "Worktree created"
         );
+        if !sparse_paths.is_empty() {
+            let checked_out =
+                sparse::apply_sparse_checkout(&worktree_path, sparse_paths).and_then(|_| {
+                    sparse::checkout_sparse_worktree(&worktree_path, lfs_status == Some(true))
+                });
+            if let Err(e) = checked_out {
+                // 未检出的 worktree 不可用，回滚本次创建
+                let _ = git_command(&project_root)
+                    .args(["worktree", "remove", "--force", &worktree_arg])
+                    .output();
+                if created_branch {
+                    let _ = git_command(&project_root)
+                        .args(["branch", "-D", workspace_branch])
+                        .output();
+                }
+                return Err(WorkspaceError::GitError(e));
+            }
+            info!(
+                project = project_name,
+                workspace = workspace_display_name,
+                paths = sparse_paths.len(),
+                "Sparse checkout applied"
+            );
+        }
+        if let Some(available) = lfs_status {
+            Self::pull_lfs_objects(
+                project_name,
+                workspace_display_name,
+                &worktree_path,
+                available,
+                on_progress,
+            );
+        }
 
         let mut workspace = Workspace {
-            name: workspace_display_name.clone(),
+            name: workspace_display_name.to_string(),
             worktree_path: worktree_path.clone(),
-            branch: workspace_branch,
+            branch: workspace_branch.to_string(),
             status: WorkspaceStatus::Creating,
             created_at: Utc::now(),
             last_accessed: Utc::now(),
             setup_result: None,
             recovery_meta: None,
+            port: None,
+            linked_issue: None,
+            sparse_paths: sparse_paths.to_vec(),
         };
 
         // Update state
@@ -201,13 +409,13 @@ impl WorkspaceManager {
             workspace = Self::run_setup_internal(
                 state,
                 project_name,
-                &workspace_display_name,
+                workspace_display_name,
                 &worktree_path,
             )?;
         } else {
             // Mark as ready if no setup
             let project = state.get_project_mut(project_name).unwrap();
-            if let Some(ws) = project.get_workspace_mut(&workspace_display_name) {
+            if let Some(ws) = project.get_workspace_mut(workspace_display_name) {
                 ws.status = WorkspaceStatus::Ready;
                 workspace.status = WorkspaceStatus::Ready;
             }
@@ -216,6 +424,75 @@ impl WorkspaceManager {
         Ok(workspace)
     }
 
+    /// 拉取 LFS 对象；失败只记录并通过进度回调提示，不影响 workspace 创建
+    fn pull_lfs_objects(
+        project_name: &str,
+        workspace_name: &str,
+        worktree_path: &Path,
+        available: bool,
+        on_progress: &mut dyn FnMut(&str, CloneProgress),
+    ) {
+        let report =
+            |on_progress: &mut dyn FnMut(&str, CloneProgress), phase: &str, message: String| {
+                on_progress(
+                    workspace_name,
+                    CloneProgress {
+                        phase: phase.to_string(),
+                        percent: None,
+                        message,
+                    },
+                )
+            };
+        if !available {
+            warn!(
+                project = project_name,
+                workspace = workspace_name,
+                "Repository uses Git LFS but git-lfs is not installed"
+            );
+            report(
+                on_progress,
+                "lfs_unavailable",
+                "Repository uses Git LFS but git-lfs is not installed; large files are left as pointer files".to_string(),
+            );
+            return;
+        }
+
+        report(
+            on_progress,
+            "lfs_pull",
+            "Pulling Git LFS objects".to_string(),
+        );
+        match lfs::pull_lfs_objects(worktree_path, |progress| {
+            on_progress(workspace_name, progress)
+        }) {
+            Ok(()) => {
+                info!(
+                    project = project_name,
+                    workspace = workspace_name,
+                    "Git LFS objects pulled"
+                );
+                report(
+                    on_progress,
+                    "lfs_done",
+                    "Git LFS objects pulled".to_string(),
+                );
+            }
+            Err(e) => {
+                warn!(
+                    project = project_name,
+                    workspace = workspace_name,
+                    error = %e,
+                    "Git LFS pull failed"
+                );
+                report(
+                    on_progress,
+                    "lfs_failed",
+                    format!("Git LFS pull failed: {}", e),
+                );
+            }
+        }
+    }
+
     /// Run setup for an existing workspace
     pub fn run_setup(
         state: &mut AppState,
@@ -251,7 +528,8 @@ impl WorkspaceManager {
 
         // Load config and run setup
         let config = ProjectConfig::load(worktree_path).unwrap_or_default();
-        let result = SetupExecutor::execute(&config, worktree_path);
+        let port = Self::ensure_port(state, project_name, workspace_name, &config);
+        let result = SetupExecutor::execute(&config, worktree_path, port);
 
         // Update workspace with result
         let project = state.get_project_mut(project_name).unwrap();
@@ -296,6 +574,92 @@ impl WorkspaceManager {
         Ok(ws_clone)
     }
 
+    /// 工作区的预留端口；配置引用了 `{{port}}` 而尚未分配时预留一个并记录到工作区
+    fn ensure_port(
+        state: &mut AppState,
+        project_name: &str,
+        workspace_name: &str,
+        config: &ProjectConfig,
+    ) -> Option<u16> {
+        let existing = state
+            .get_project(project_name)
+            .and_then(|p| p.get_workspace(workspace_name))
+            .and_then(|ws| ws.port);
+        if existing.is_some() || !config.uses_port_placeholder() {
+            return existing;
+        }
+
+        let port = reserve_free_port(&state.allocated_ports());
+        match port {
+            Some(port) => info!(
+                project = project_name,
+                workspace = workspace_name,
+                port,
+                "Port reserved for workspace"
+            ),
+            None => warn!(
+                project = project_name,
+                workspace = workspace_name,
+                "No free port available for workspace"
+            ),
+        }
+        if let Some(ws) = state
+            .get_project_mut(project_name)
+            .and_then(|p| p.get_workspace_mut(workspace_name))
+        {
+            ws.port = port;
+        }
+        port
+    }
+
+    /// 分支名中的 `{user}`：优先 git `user.name`，其次系统用户名
+    fn branch_user_name(project_root: &Path) -> String {
+        let git_user = git_command(project_root)
+            .args(["config", "user.name"])
+            .output()
+            .ok()
+            .filter(|out| out.status.success())
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+            .filter(|name| !name.is_empty());
+        git_user
+            .or_else(|| std::env::var("USER").ok())
+            .or_else(|| std::env::var("USERNAME").ok())
+            .unwrap_or_else(|| "user".to_string())
+    }
+
+    /// 分支是否与已有引用冲突：同名分支、以其祖先路径命名的分支（`feat` 阻止 `feat/x`），
+    /// 或以其为前缀的分支（`feat/x/y` 阻止 `feat/x`）
+    fn branch_ref_conflicts(project_root: &Path, branch: &str) -> bool {
+        let ref_exists = |name: &str| {
+            git_command(project_root)
+                .args([
+                    "show-ref",
+                    "--quiet",
+                    "--verify",
+                    &format!("refs/heads/{}", name),
+                ])
+                .output()
+                .map(|out| out.status.success())
+                .unwrap_or(false)
+        };
+        let mut prefix = String::new();
+        for segment in branch.split('/') {
+            if !prefix.is_empty() {
+                prefix.push('/');
+            }
+            prefix.push_str(segment);
+            if ref_exists(&prefix) {
+                return true;
+            }
+        }
+        git_command(project_root)
+            .args(["for-each-ref", "--count=1", "--format=%(refname)"])
+            .arg(format!("refs/heads/{}/", branch))
+            .output()
+            .map(|out| !out.stdout.is_empty())
+            .unwrap_or(false)
+    }
+
     fn generate_random_branch_name() -> String {
         Petnames::default()
             .generate_one(2, "-")
@@ -320,14 +684,13 @@ impl WorkspaceManager {
         let project_root = project.root_path.clone();
 
         // Remove git worktree
-        let output = Command::new("git")
+        let output = git_command(&project_root)
             .args([
                 "worktree",
                 "remove",
                 "--force",
-                worktree_path.to_str().unwrap(),
+                &exec_env_for(&project_root).to_exec_path(&worktree_path),
             ])
-            .current_dir(&project_root)
             .output()
             .map_err(|e| WorkspaceError::GitError(e.to_string()))?;
 
@@ -350,6 +713,323 @@ impl WorkspaceManager {
         Ok(())
     }
 
+    /// 重命名 workspace。
+    /// `rename_branch` 时把分支名最后一段替换为新名称（`tidy/brave-otter` → `tidy/<new_name>`），
+    /// `move_worktree` 时把 worktree 目录移动到 `worktrees_dir/<new_name>`；
+    /// 客户端设置中以 workspace 名为键的快捷键、待办与 Evolution 配置一并迁移。
+    pub fn rename(
+        state: &mut AppState,
+        project_name: &str,
+        workspace_name: &str,
+        new_name: &str,
+        rename_branch: bool,
+        move_worktree: bool,
+    ) -> Result<Workspace, WorkspaceError> {
+        let new_name = new_name.trim();
+        let project = state
+            .get_project(project_name)
+            .ok_or_else(|| WorkspaceError::ProjectNotFound(project_name.to_string()))?;
+        let workspace = project
+            .get_workspace(workspace_name)
+            .ok_or_else(|| WorkspaceError::NotFound(workspace_name.to_string()))?
+            .clone();
+
+        // 新名称同时用作目录名与分支最后一段，按单段分支名校验
+        if new_name == DEFAULT_WORKSPACE_NAME
+            || new_name.contains('/')
+            || !is_valid_branch_name(new_name)
+        {
+            return Err(WorkspaceError::InvalidName(new_name.to_string()));
+        }
+        if new_name == workspace_name {
+            return Ok(workspace);
+        }
+        if project.get_workspace(new_name).is_some() {
+            return Err(WorkspaceError::AlreadyExists(new_name.to_string()));
+        }
+
+        let project_root = project.root_path.clone();
+        let new_branch = match workspace.branch.rsplit_once('/') {
+            Some((prefix, _)) => format!("{}/{}", prefix, new_name),
+            None => new_name.to_string(),
+        };
+        let new_worktree_path = project.worktrees_dir().join(new_name);
+
+        if rename_branch
+            && new_branch != workspace.branch
+            && Self::branch_ref_conflicts(&project_root, &new_branch)
+        {
+            return Err(WorkspaceError::InvalidBranchName(format!(
+                "分支 '{}' 已存在或与现有分支冲突",
+                new_branch
+            )));
+        }
+        if move_worktree && new_worktree_path.exists() {
+            return Err(WorkspaceError::AlreadyExists(
+                new_worktree_path.to_string_lossy().to_string(),
+            ));
+        }
+
+        let mut renamed = workspace.clone();
+        renamed.name = new_name.to_string();
+
+        if rename_branch && new_branch != workspace.branch {
+            let output = git_command(&project_root)
+                .args(["branch", "-m", &workspace.branch, &new_branch])
+                .output()
+                .map_err(|e| WorkspaceError::GitError(e.to_string()))?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(WorkspaceError::GitError(stderr.trim().to_string()));
+            }
+            renamed.branch = new_branch;
+        }
+
+        if move_worktree {
+            let exec_env = exec_env_for(&project_root);
+            let output = git_command(&project_root)
+                .args([
+                    "worktree",
+                    "move",
+                    &exec_env.to_exec_path(&workspace.worktree_path),
+                    &exec_env.to_exec_path(&new_worktree_path),
+                ])
+                .output()
+                .map_err(|e| WorkspaceError::GitError(e.to_string()))?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                // 目录移动失败时回滚分支改名，避免状态与仓库不一致
+                if renamed.branch != workspace.branch {
+                    let _ = git_command(&project_root)
+                        .args(["branch", "-m", &renamed.branch, &workspace.branch])
+                        .output();
+                }
+                return Err(WorkspaceError::GitError(stderr.trim().to_string()));
+            }
+            renamed.worktree_path = new_worktree_path;
+        }
+
+        let project = state.get_project_mut(project_name).unwrap();
+        project.remove_workspace(workspace_name);
+        project.add_workspace(renamed.clone());
+        Self::migrate_workspace_settings(state, project_name, workspace_name, new_name);
+
+        info!(
+            project = project_name,
+            workspace = workspace_name,
+            new_name = new_name,
+            branch = renamed.branch,
+            "Workspace renamed"
+        );
+
+        Ok(renamed)
+    }
+
+    /// 设置或清除 workspace 关联的 issue 地址（调用方已校验地址）
+    pub fn link_issue(
+        state: &mut AppState,
+        project_name: &str,
+        workspace_name: &str,
+        url: Option<String>,
+    ) -> Result<Workspace, WorkspaceError> {
+        let project = state
+            .get_project_mut(project_name)
+            .ok_or_else(|| WorkspaceError::ProjectNotFound(project_name.to_string()))?;
+        let workspace = project
+            .workspaces
+            .get_mut(workspace_name)
+            .ok_or_else(|| WorkspaceError::NotFound(workspace_name.to_string()))?;
+        workspace.linked_issue = url;
+        Ok(workspace.clone())
+    }
+
+    /// 调整 workspace 的稀疏检出目录（cone 模式），`paths` 为空时恢复完整检出。
+    /// 默认工作空间即项目根目录，不允许修改。
+    pub fn set_sparse_paths(
+        state: &mut AppState,
+        project_name: &str,
+        workspace_name: &str,
+        paths: &[String],
+    ) -> Result<Workspace, WorkspaceError> {
+        if workspace_name == DEFAULT_WORKSPACE_NAME {
+            return Err(WorkspaceError::InvalidSparsePath(
+                "Sparse checkout is not supported for the default workspace".to_string(),
+            ));
+        }
+        let paths =
+            sparse::normalize_sparse_paths(paths).map_err(WorkspaceError::InvalidSparsePath)?;
+        let project = state
+            .get_project_mut(project_name)
+            .ok_or_else(|| WorkspaceError::ProjectNotFound(project_name.to_string()))?;
+        let workspace = project
+            .workspaces
+            .get_mut(workspace_name)
+            .ok_or_else(|| WorkspaceError::NotFound(workspace_name.to_string()))?;
+        if !workspace.worktree_path.exists() {
+            return Err(WorkspaceError::IoError(format!(
+                "Worktree directory is missing: {}",
+                workspace.worktree_path.display()
+            )));
+        }
+        sparse::apply_sparse_checkout(&workspace.worktree_path, &paths)
+            .map_err(WorkspaceError::GitError)?;
+        info!(
+            project = project_name,
+            workspace = workspace_name,
+            paths = paths.len(),
+            "Sparse paths updated"
+        );
+        workspace.sparse_paths = paths;
+        Ok(workspace.clone())
+    }
+
+    /// 修复 worktree 目录被手动删除或 git worktree 元数据过期的 workspace：
+    /// 先 `git worktree prune` 清理指向不存在目录的元数据；目录缺失（或为空）时从原分支重新检出，
+    /// 目录仍在时 `git worktree repair` 修复仓库与 worktree 之间的双向链接。
+    /// 成功后状态置为 `Ready`；重建的目录不会重新执行 setup。
+    pub fn repair(
+        state: &mut AppState,
+        project_name: &str,
+        workspace_name: &str,
+    ) -> Result<WorkspaceRepair, WorkspaceError> {
+        let project = state
+            .get_project(project_name)
+            .ok_or_else(|| WorkspaceError::ProjectNotFound(project_name.to_string()))?;
+        let workspace = project
+            .get_workspace(workspace_name)
+            .ok_or_else(|| WorkspaceError::NotFound(workspace_name.to_string()))?
+            .clone();
+        let project_root = project.root_path.clone();
+        let worktree_path = workspace.worktree_path.clone();
+        let worktree_arg = exec_env_for(&project_root).to_exec_path(&worktree_path);
+        let mut actions = Vec::new();
+
+        let pruned = Self::git_output(&project_root, &["worktree", "prune", "--verbose"])?;
+        if !pruned.is_empty() {
+            actions.push("已清理过期的 worktree 元数据".to_string());
+        }
+
+        let is_empty_dir = std::fs::read_dir(&worktree_path)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false);
+        if !worktree_path.exists() || is_empty_dir {
+            let branch_ref = format!("refs/heads/{}", workspace.branch);
+            Self::git_output(
+                &project_root,
+                &["rev-parse", "--verify", "--quiet", &branch_ref],
+            )
+            .map_err(|_| {
+                WorkspaceError::GitError(format!(
+                    "分支 '{}' 不存在，无法重建 worktree",
+                    workspace.branch
+                ))
+            })?;
+            if let Some(parent) = worktree_path.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| WorkspaceError::IoError(e.to_string()))?;
+            }
+            if workspace.sparse_paths.is_empty() {
+                Self::git_output(
+                    &project_root,
+                    &["worktree", "add", &worktree_arg, &workspace.branch],
+                )?;
+            } else {
+                Self::git_output(
+                    &project_root,
+                    &[
+                        "worktree",
+                        "add",
+                        "--no-checkout",
+                        &worktree_arg,
+                        &workspace.branch,
+                    ],
+                )?;
+                sparse::apply_sparse_checkout(&worktree_path, &workspace.sparse_paths)
+                    .and_then(|_| sparse::checkout_sparse_worktree(&worktree_path, false))
+                    .map_err(WorkspaceError::GitError)?;
+            }
+            actions.push(format!("已从分支 {} 重建 worktree 目录", workspace.branch));
+        } else if worktree_path.join(".git").exists() {
+            let repaired = Self::git_output(&project_root, &["worktree", "repair", &worktree_arg])?;
+            if !repaired.is_empty() {
+                actions.push("已修复 worktree 链接".to_string());
+            }
+        } else {
+            return Err(WorkspaceError::IoError(format!(
+                "目录已存在但不是 git worktree: {}",
+                worktree_path.display()
+            )));
+        }
+
+        // 修复后 worktree 须能被 git 识别，否则视为修复失败
+        Self::git_output(&worktree_path, &["rev-parse", "--git-dir"])?;
+
+        let project = state.get_project_mut(project_name).unwrap();
+        let ws = project.get_workspace_mut(workspace_name).unwrap();
+        if ws.status != WorkspaceStatus::Ready {
+            actions.push(format!("状态由 {:?} 更新为 Ready", ws.status));
+            ws.status = WorkspaceStatus::Ready;
+        }
+        let repaired = ws.clone();
+
+        info!(
+            project = project_name,
+            workspace = workspace_name,
+            actions = actions.len(),
+            "Workspace repaired"
+        );
+
+        Ok(WorkspaceRepair {
+            workspace: repaired,
+            actions,
+        })
+    }
+
+    /// 执行 git 命令，返回合并后的 stdout/stderr；失败时以 stderr 作为错误
+    fn git_output(cwd: &Path, args: &[&str]) -> Result<String, WorkspaceError> {
+        let output = git_command(cwd)
+            .args(args)
+            .output()
+            .map_err(|e| WorkspaceError::GitError(e.to_string()))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(WorkspaceError::GitError(stderr.trim().to_string()));
+        }
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        Ok(text.trim().to_string())
+    }
+
+    /// 迁移客户端设置中引用旧 workspace 名的条目
+    fn migrate_workspace_settings(
+        state: &mut AppState,
+        project_name: &str,
+        old_name: &str,
+        new_name: &str,
+    ) {
+        let settings = &mut state.client_settings;
+
+        let old_ref = format!("{}/{}", project_name, old_name);
+        let new_ref = format!("{}/{}", project_name, new_name);
+        for value in settings.workspace_shortcuts.values_mut() {
+            if *value == old_ref {
+                *value = new_ref.clone();
+            }
+        }
+        if let Some(profiles) = settings.evolution_agent_profiles.remove(&old_ref) {
+            settings
+                .evolution_agent_profiles
+                .insert(new_ref.clone(), profiles);
+        }
+
+        let old_key = format!("{}:{}", project_name, old_name);
+        if let Some(todos) = settings.workspace_todos.remove(&old_key) {
+            settings
+                .workspace_todos
+                .insert(format!("{}:{}", project_name, new_name), todos);
+        }
+    }
+
     /// Get workspace root path
     pub fn get_root_path(
         state: &AppState,
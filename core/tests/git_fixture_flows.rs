@@ -0,0 +1,1101 @@
+//! Git / workspace 端到端流程测试
+//!
+//! 基于 `support::git_fixture` 构造的临时仓库，覆盖：
+//!   - 重命名与未跟踪文件的状态识别
+//!   - 跨分支历史的 log 分页与路径过滤
+//!   - 提交内单文件 diff（根提交、重命名、合并提交）
+//!   - 读取文件的历史版本
+//!   - 按工作区上下文与项目模板生成提交信息建议，以及外部命令生成
+//!   - rebase 冲突 → 自定义解决 → continue
+//!   - stash 保存与恢复
+//!   - 仓库统计（对象数、历史大文件、gc 后的 pack 与维护建议）
+//!   - 历史大 blob 检测（路径、写入提交）与 filter-repo 计划
+//!   - 子模块变更的状态呈现
+//!   - 项目导入 → 创建 workspace（worktree）→ 删除
+//!   - workspace 重命名（分支、worktree 目录与客户端设置迁移）
+//!   - workspace 修复（worktree 目录被删除后从分支重建）
+//!   - 导入时从 origin/HEAD 识别默认分支，以及修改项目默认分支
+//!   - 按目标分支隔离的集成 worktree（合并冲突互不影响）
+//!   - 同仓库各 worktree 的 fetch 共用去重键
+//!   - 从 URL 浅克隆导入（进度回调、失败清理）
+
+mod support;
+
+use support::git_fixture::{git_in, isolated_tidyflow_home, FixtureRepo};
+use tidyflow_core::application::file::file_read_at_revision_message;
+use tidyflow_core::server::git::{self, GitLogFilter, GitOpState, StashOpState};
+use tidyflow_core::server::protocol::ServerMessage;
+use tidyflow_core::workspace::lfs;
+use tidyflow_core::workspace::project::ProjectError;
+use tidyflow_core::workspace::workspace::WorkspaceError;
+use tidyflow_core::workspace::{AppState, ProjectManager, WorkspaceManager, WorkspaceStatus};
+
+#[test]
+fn status_reports_staged_rename_and_untracked_files() {
+    let repo = FixtureRepo::with_initial_commit();
+    repo.commit_file("src/old.rs", "fn main() {}\n", "add old.rs");
+    repo.git(&["mv", "src/old.rs", "src/new.rs"]);
+    repo.write("notes.txt", "todo\n");
+    repo.write("README.md", "# fixture\n\nchanged\n");
+
+    let status = git::git_status(repo.path(), "main").unwrap();
+    assert_eq!(status.current_branch.as_deref(), Some("main"));
+
+    let renamed = status
+        .items
+        .iter()
+        .find(|item| item.path == "src/new.rs")
+        .expect("renamed entry");
+    assert!(renamed.code.starts_with('R'), "code = {}", renamed.code);
+    assert_eq!(renamed.orig_path.as_deref(), Some("src/old.rs"));
+    assert!(renamed.staged);
+
+    let untracked = status
+        .items
+        .iter()
+        .find(|item| item.path == "notes.txt")
+        .expect("untracked entry");
+    assert_eq!(untracked.code, "??");
+    assert!(!untracked.staged);
+
+    let modified = status
+        .items
+        .iter()
+        .find(|item| item.path == "README.md")
+        .expect("modified entry");
+    assert!(!modified.staged);
+    assert_eq!(status.staged_count, 1);
+}
+
+#[test]
+fn log_paginates_and_filters_across_branches() {
+    let repo = FixtureRepo::with_initial_commit();
+    for i in 1..=4 {
+        repo.commit_file("main.txt", &format!("{}\n", i), &format!("main {}", i));
+    }
+    repo.git(&["checkout", "-q", "-b", "feature"]);
+    let feature_sha = repo.commit_file("feature.txt", "f\n", "feature work");
+    repo.checkout("main");
+
+    let first = git::git_log(repo.path(), 2, &GitLogFilter::default()).unwrap();
+    assert_eq!(first.entries.len(), 2);
+    assert!(first.has_more);
+    assert_eq!(first.entries[0].message, "main 4");
+
+    // 游标翻页：从上一页最后一条之后继续
+    let cursor = first.entries.last().unwrap().sha.clone();
+    let second = git::git_log(
+        repo.path(),
+        10,
+        &GitLogFilter {
+            before_sha: Some(cursor),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let messages: Vec<_> = second.entries.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages, ["main 2", "main 1", "initial commit"]);
+    assert!(!second.has_more);
+
+    // 指定分支与路径：只看到 feature 分支上触及 feature.txt 的提交
+    let feature = git::git_log(
+        repo.path(),
+        10,
+        &GitLogFilter {
+            branch: Some("feature".to_string()),
+            path: Some("feature.txt".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(feature.entries.len(), 1);
+    assert!(feature_sha.starts_with(&feature.entries[0].sha));
+}
+
+#[test]
+fn log_keeps_messages_containing_record_separator() {
+    let repo = FixtureRepo::with_initial_commit();
+    repo.commit_file("a.txt", "a\n", "subject\u{1e}body");
+    repo.commit_file("b.txt", "b\n", "latest");
+
+    // 带过滤条件时走 `git log` 文本解析，消息里的 0x1e 不能拆出伪提交
+    let log = git::git_log(
+        repo.path(),
+        10,
+        &GitLogFilter {
+            branch: Some("main".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let messages: Vec<_> = log.entries.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages, ["latest", "subject\u{1e}body", "initial commit"]);
+    assert!(!log.has_more);
+}
+
+#[test]
+fn show_file_diff_covers_root_rename_and_merge_commits() {
+    let repo = FixtureRepo::with_initial_commit();
+    let root_sha = repo.head();
+    let root = git::git_show_file_diff(repo.path(), &root_sha, "README.md", None).unwrap();
+    assert_eq!(root.full_sha, root_sha);
+    assert_eq!(root.diff.code, "A");
+    assert!(root.diff.text.contains("+# fixture"));
+
+    repo.commit_file(
+        "src/old.rs",
+        "fn one() {}\nfn two() {}\nfn three() {}\n",
+        "add old.rs",
+    );
+    let renamed_sha = repo.rename("src/old.rs", "src/new.rs", "rename old.rs");
+    let renamed = git::git_show_file_diff(repo.path(), &renamed_sha, "src/new.rs", None).unwrap();
+    assert_eq!(renamed.diff.code, "R");
+    assert_eq!(renamed.old_path.as_deref(), Some("src/old.rs"));
+    assert!(renamed.diff.text.contains("rename from src/old.rs"));
+
+    // 合并提交相对第一父提交：只看到被合入分支带来的文件
+    repo.git(&["checkout", "-q", "-b", "feature"]);
+    repo.commit_file("feature.txt", "feature\n", "feature work");
+    repo.checkout("main");
+    repo.commit_file("main.txt", "main\n", "main work");
+    repo.git(&["merge", "-q", "--no-ff", "-m", "merge feature", "feature"]);
+    let merge_sha = repo.head();
+    let merged = git::git_show_file_diff(repo.path(), &merge_sha, "feature.txt", None).unwrap();
+    assert_eq!(merged.diff.code, "A");
+    assert!(merged.diff.text.contains("+feature"));
+    assert!(!merged.diff.text.contains("main.txt"));
+
+    repo.write("logo.bin", "\0\x01binary");
+    let binary_sha = repo.commit("add binary");
+    let binary = git::git_show_file_diff(repo.path(), &binary_sha, "logo.bin", None).unwrap();
+    assert!(binary.diff.is_binary);
+    assert!(binary.diff.text.is_empty());
+
+    // 未在该提交中变更的文件直接报错
+    assert!(git::git_show_file_diff(repo.path(), &merge_sha, "main.txt", None).is_err());
+}
+
+#[test]
+fn read_file_at_revision_returns_historical_content() {
+    let repo = FixtureRepo::with_initial_commit();
+    let first = repo.commit_file("src/lib.rs", "v1\r\n", "v1");
+    repo.commit_file("src/lib.rs", "v2\n", "v2");
+    repo.write("src/lib.rs", "working copy\n");
+
+    let read =
+        |path: &str, rev: &str| file_read_at_revision_message(repo.path(), "p", "w", path, rev);
+    let ServerMessage::FileReadAtRevisionResult {
+        sha,
+        content,
+        encoding,
+        line_ending,
+        ..
+    } = read("src/lib.rs", "HEAD~1")
+    else {
+        panic!("expected file read at revision result");
+    };
+    assert_eq!(sha, first);
+    assert_eq!(content, b"v1\r\n");
+    assert_eq!(encoding, "utf8");
+    assert_eq!(line_ending.as_deref(), Some("crlf"));
+
+    let ServerMessage::FileReadAtRevisionResult { content, .. } = read("./src/lib.rs", "main")
+    else {
+        panic!("expected file read at revision result");
+    };
+    assert_eq!(content, b"v2\n");
+
+    repo.write("logo.bin", "\0\x01\x02");
+    repo.commit("add binary");
+    let ServerMessage::FileReadAtRevisionResult { encoding, .. } = read("logo.bin", "HEAD") else {
+        panic!("expected file read at revision result");
+    };
+    assert_eq!(encoding, "binary");
+
+    let error_code = |msg: ServerMessage| match msg {
+        ServerMessage::Error { code, .. } => code,
+        other => panic!("expected error, got {:?}", other),
+    };
+    assert_eq!(error_code(read("logo.bin", &first)), "file_not_found");
+    assert_eq!(error_code(read("src", "HEAD")), "git_error");
+    assert_eq!(
+        error_code(read("src/lib.rs", "no-such-branch")),
+        "git_error"
+    );
+    assert_eq!(error_code(read("src/lib.rs", "--output=x")), "git_error");
+    assert_eq!(error_code(read("../outside.txt", "HEAD")), "path_escape");
+}
+
+#[test]
+fn suggested_commit_message_uses_branch_link_and_template() {
+    let repo = FixtureRepo::with_initial_commit();
+    repo.git(&["checkout", "-q", "-b", "feature/proj-7-login"]);
+    repo.write("src/auth/login.rs", "pub fn login() {}\n");
+    repo.write("src/auth/token.rs", "pub fn token() {}\n");
+    repo.write("notes.txt", "unstaged\n");
+    repo.git(&["add", "src/auth"]);
+
+    let suggested = git::git_suggested_commit_message(repo.path(), "login", None, false).unwrap();
+    assert_eq!(suggested.branch, "feature/proj-7-login");
+    assert_eq!(suggested.issue_id.as_deref(), Some("PROJ-7"));
+    assert_eq!(suggested.commit_type, "feat");
+    assert_eq!(suggested.scope.as_deref(), Some("auth"));
+    assert_eq!(suggested.staged_files, 2);
+    assert_eq!(suggested.template, git::DEFAULT_COMMIT_TEMPLATE);
+    assert_eq!(suggested.message, "PROJ-7 feat: add login.rs and token.rs");
+
+    // 关联链接优先于分支名；项目模板覆盖默认格式
+    repo.write(
+        ".tidyflow.toml",
+        "[git]\ncommit_template = \"{type}({scope}): {summary} [{issue}]\"\n",
+    );
+    let suggested = git::git_suggested_commit_message(
+        repo.path(),
+        "login",
+        Some("https://github.com/acme/app/issues/42"),
+        false,
+    )
+    .unwrap();
+    assert_eq!(
+        suggested.message,
+        "feat(auth): add login.rs and token.rs [#42]"
+    );
+
+    repo.write(".tidyflow.toml", "[git]\ncommit_template = \"{ticket}\"\n");
+    assert!(git::git_suggested_commit_message(repo.path(), "login", None, false).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn suggested_commit_message_can_be_generated_by_command() {
+    let repo = FixtureRepo::with_initial_commit();
+    repo.write(
+        ".tidyflow.toml",
+        concat!(
+            "[git]\n",
+            "commit_message_command = [\"sh\", \"-c\", ",
+            "\"printf '%s (%s files)' \\\"$TIDYFLOW_SUGGESTED_MESSAGE\\\" ",
+            "$(grep -c '^diff --git')\"]\n",
+        ),
+    );
+    repo.git(&["add", ".tidyflow.toml"]);
+    repo.commit_staged("add config");
+
+    let empty = git::git_suggested_commit_message(repo.path(), "ws", None, true).unwrap();
+    assert!(empty.generator_available);
+    assert_eq!(empty.source, "template");
+
+    repo.write("a.txt", "a\n");
+    repo.write("b.txt", "b\n");
+    repo.git(&["add", "a.txt", "b.txt"]);
+    let template = git::git_suggested_commit_message(repo.path(), "ws", None, false).unwrap();
+    assert_eq!(template.source, "template");
+    let generated = git::git_suggested_commit_message(repo.path(), "ws", None, true).unwrap();
+    assert_eq!(generated.source, "command");
+    assert_eq!(generated.message, format!("{} (2 files)", template.message));
+
+    repo.write(".tidyflow.toml", "[git]\n");
+    let err = git::git_suggested_commit_message(repo.path(), "ws", None, true).unwrap_err();
+    assert!(
+        err.to_string().contains("commit_message_command"),
+        "{}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn commit_message_can_be_generated_by_http_endpoint() {
+    use axum::routing::post;
+    use axum::Json;
+    use serde_json::{json, Value};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = axum::Router::new().route(
+        "/commit",
+        post(|Json(body): Json<Value>| async move {
+            let files = body["diff"]
+                .as_str()
+                .unwrap_or_default()
+                .matches("diff --git")
+                .count();
+            let suggested = body["suggested_message"].as_str().unwrap_or_default();
+            Json(json!({ "message": format!("{} ({} files)", suggested, files) }))
+        }),
+    );
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, router).await;
+    });
+
+    let repo = FixtureRepo::with_initial_commit();
+    repo.write(
+        ".tidyflow.toml",
+        &format!(
+            "[git]\ncommit_message_endpoint = \"http://{}/commit\"\n",
+            addr
+        ),
+    );
+    repo.git(&["add", ".tidyflow.toml"]);
+    repo.commit_staged("add config");
+
+    let root = repo.path().to_path_buf();
+    let empty = git::git_generate_commit_message(root.clone(), "ws".into(), None)
+        .await
+        .unwrap();
+    assert!(empty.generator_available);
+    assert_eq!(empty.source, "template");
+
+    repo.write("a.txt", "a\n");
+    repo.write("b.txt", "b\n");
+    repo.git(&["add", "a.txt", "b.txt"]);
+    let template = git::git_suggested_commit_message(repo.path(), "ws", None, false).unwrap();
+    assert!(template.generator_available);
+    let generated = git::git_generate_commit_message(root.clone(), "ws".into(), None)
+        .await
+        .unwrap();
+    assert_eq!(generated.source, "endpoint");
+    assert_eq!(generated.message, format!("{} (2 files)", template.message));
+
+    repo.write(".tidyflow.toml", "[git]\n");
+    let err = git::git_generate_commit_message(root, "ws".into(), None)
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("commit_message_endpoint"),
+        "{}",
+        err
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn commit_signing_follows_config_override_and_reports_failures() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo = FixtureRepo::with_initial_commit();
+    let script = |name: &str, body: &str| {
+        let path = repo.sibling(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{}", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path.to_string_lossy().to_string()
+    };
+    // 模拟 pinentry 无法弹出时 gpg 的失败输出
+    let failing = script(
+        "gpg-fail",
+        "cat >/dev/null\necho 'gpg: signing failed: Inappropriate ioctl for device' >&2\nexit 2\n",
+    );
+    let signing = script(
+        "gpg-ok",
+        concat!(
+            "cat >/dev/null\n",
+            "printf '[GNUPG:] BEGIN_SIGNING\\n[GNUPG:] SIG_CREATED D 1 8 00 0 X\\n' >&2\n",
+            "printf -- '-----BEGIN PGP SIGNATURE-----\\nfake\\n-----END PGP SIGNATURE-----\\n'\n",
+        ),
+    );
+    repo.git(&["config", "commit.gpgsign", "true"]);
+    repo.git(&["config", "user.signingkey", "ABCD1234"]);
+    repo.git(&["config", "gpg.program", &failing]);
+
+    let config = git::commit_signing_config(repo.path());
+    assert!(config.enabled);
+    assert_eq!(config.format, "openpgp");
+    assert_eq!(config.signing_key.as_deref(), Some("ABCD1234"));
+    assert_eq!(config.program.as_deref(), Some(failing.as_str()));
+
+    repo.write("a.txt", "a\n");
+    repo.git(&["add", "a.txt"]);
+    let failed = git::git_commit(repo.path(), "add a", None).unwrap();
+    assert!(!failed.ok);
+    assert_eq!(
+        failed.error_code.as_deref(),
+        Some(git::COMMIT_ERROR_SIGNING)
+    );
+    assert!(
+        failed.message.as_deref().unwrap().contains("gpg-agent"),
+        "{:?}",
+        failed.message
+    );
+
+    // 显式关闭签名可绕过失败的密钥
+    let unsigned = git::git_commit(repo.path(), "add a", Some(false)).unwrap();
+    assert!(unsigned.ok, "{:?}", unsigned.message);
+    assert!(!unsigned.signed);
+
+    repo.git(&["config", "commit.gpgsign", "false"]);
+    repo.git(&["config", "gpg.program", &signing]);
+    repo.write("b.txt", "b\n");
+    repo.git(&["add", "b.txt"]);
+    let signed = git::git_commit(repo.path(), "add b", Some(true)).unwrap();
+    assert!(signed.ok, "{:?}", signed.message);
+    assert!(signed.signed);
+    assert!(repo.git(&["cat-file", "commit", "HEAD"]).contains("gpgsig"));
+
+    let empty = git::git_commit(repo.path(), "nothing", None).unwrap();
+    assert_eq!(
+        empty.error_code.as_deref(),
+        Some(git::COMMIT_ERROR_NOTHING_STAGED)
+    );
+}
+
+#[test]
+fn rebase_conflict_can_be_resolved_and_continued() {
+    let repo = FixtureRepo::with_initial_commit();
+    repo.commit_file("shared.txt", "base\n", "add shared");
+    repo.diverge_file("shared.txt", "feature", "main side\n", "feature side\n");
+    repo.checkout("feature");
+
+    let result = git::git_rebase(repo.path(), "main").unwrap();
+    assert!(!result.ok);
+    assert_eq!(result.state, "conflict");
+    assert!(result
+        .conflict_files
+        .iter()
+        .any(|entry| entry.path == "shared.txt" && entry.conflict_type == "content"));
+    assert_eq!(
+        git::git_op_status(repo.path()).unwrap().state,
+        GitOpState::Rebasing
+    );
+
+    let resolved =
+        git::git_conflict_resolve_custom(repo.path(), "shared.txt", "workspace", "merged\n")
+            .unwrap();
+    assert!(resolved.ok);
+
+    let result = git::git_rebase_continue(repo.path()).unwrap();
+    assert!(result.ok, "continue failed: {:?}", result.message);
+    assert_eq!(result.state, "completed");
+    assert_eq!(
+        git::git_op_status(repo.path()).unwrap().state,
+        GitOpState::Normal
+    );
+    assert_eq!(repo.read("shared.txt"), "merged\n");
+    assert_eq!(repo.current_branch(), "feature");
+    // feature 已线性接在 main 之后
+    assert_eq!(
+        repo.git(&["merge-base", "main", "feature"]),
+        repo.git(&["rev-parse", "main"])
+    );
+}
+
+#[test]
+fn stash_save_and_pop_restore_changes() {
+    let repo = FixtureRepo::with_initial_commit();
+    repo.write("README.md", "# stashed\n");
+    repo.write("scratch.txt", "wip\n");
+
+    let saved = git::git_stash_save(repo.path(), Some("wip"), true, false, &[]).unwrap();
+    assert!(saved.ok, "save failed: {:?}", saved.message);
+    assert!(git::git_status(repo.path(), "main")
+        .unwrap()
+        .items
+        .is_empty());
+    assert_eq!(git::git_stash_list(repo.path()).unwrap().entries.len(), 1);
+
+    let popped = git::git_stash_pop(repo.path(), "stash@{0}").unwrap();
+    assert_eq!(popped.state, StashOpState::Completed);
+    assert_eq!(repo.read("README.md"), "# stashed\n");
+    assert_eq!(repo.read("scratch.txt"), "wip\n");
+    assert!(git::git_stash_list(repo.path()).unwrap().entries.is_empty());
+}
+
+#[test]
+fn submodule_changes_surface_in_parent_status() {
+    let lib = FixtureRepo::with_initial_commit();
+    let repo = FixtureRepo::with_initial_commit();
+    repo.add_submodule("vendor/lib", &lib);
+    assert!(git::git_status(repo.path(), "main")
+        .unwrap()
+        .items
+        .is_empty());
+
+    // 子模块内产生新提交后，父仓库看到子模块路径被修改
+    let sub_root = repo.path().join("vendor/lib");
+    git_in(&sub_root, &["config", "user.name", "Fixture"]);
+    git_in(&sub_root, &["config", "user.email", "fixture@example.com"]);
+    git_in(&sub_root, &["config", "commit.gpgsign", "false"]);
+    std::fs::write(sub_root.join("lib.txt"), "bump\n").unwrap();
+    git_in(&sub_root, &["add", "-A"]);
+    git_in(&sub_root, &["commit", "-q", "-m", "bump"]);
+
+    // 父仓库的 index / HEAD 未变，状态缓存指纹命中；运行时由文件监听负责失效
+    git::invalidate_git_status_cache(repo.path());
+    let status = git::git_status(repo.path(), "main").unwrap();
+    let entry = status
+        .items
+        .iter()
+        .find(|item| item.path == "vendor/lib")
+        .unwrap_or_else(|| panic!("submodule entry missing: {:?}", status.items));
+    assert!(!entry.staged);
+    assert!(entry.code.contains('M'), "code = {}", entry.code);
+}
+
+#[test]
+fn workspace_lifecycle_creates_and_removes_worktree() {
+    let home = isolated_tidyflow_home();
+    let repo = FixtureRepo::with_initial_commit();
+    repo.with_origin();
+
+    let mut state = AppState::default();
+    let project = ProjectManager::import_local(&mut state, "fixture", repo.path()).unwrap();
+    assert_eq!(project.default_branch, "main");
+    assert!(project.remote_url.is_some());
+
+    let workspace = WorkspaceManager::create(&mut state, "fixture", None, None, false).unwrap();
+    assert_eq!(workspace.status, WorkspaceStatus::Ready);
+    assert!(workspace.worktree_path.starts_with(home));
+    assert!(workspace.worktree_path.join("README.md").exists());
+
+    let status = git::git_status(&workspace.worktree_path, "main").unwrap();
+    assert_eq!(
+        status.current_branch.as_deref(),
+        Some(workspace.branch.as_str())
+    );
+    assert!(status.items.is_empty());
+
+    // 在 worktree 中提交，项目根目录能看到该分支的新提交
+    std::fs::write(workspace.worktree_path.join("ws.txt"), "ws\n").unwrap();
+    git_in(&workspace.worktree_path, &["add", "-A"]);
+    git_in(
+        &workspace.worktree_path,
+        &["commit", "-q", "-m", "workspace commit"],
+    );
+    let log = git::git_log(
+        repo.path(),
+        1,
+        &GitLogFilter {
+            branch: Some(workspace.branch.clone()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(log.entries[0].message, "workspace commit");
+
+    WorkspaceManager::remove(&mut state, "fixture", &workspace.name).unwrap();
+    assert!(!workspace.worktree_path.exists());
+    assert!(state
+        .get_project("fixture")
+        .unwrap()
+        .get_workspace(&workspace.name)
+        .is_none());
+}
+
+#[test]
+fn workspace_create_pulls_lfs_objects_when_attributes_use_lfs() {
+    let _home = isolated_tidyflow_home();
+    let repo = FixtureRepo::with_initial_commit();
+    repo.with_origin();
+    let mut state = AppState::default();
+    ProjectManager::import_local(&mut state, "fixture", repo.path()).unwrap();
+
+    let mut phases: Vec<(String, String)> = Vec::new();
+    let plain = WorkspaceManager::create_with_progress(
+        &mut state,
+        "fixture",
+        None,
+        None,
+        false,
+        &[],
+        |name, progress| phases.push((name.to_string(), progress.phase)),
+    )
+    .unwrap();
+    assert_eq!(plain.status, WorkspaceStatus::Ready);
+    assert!(phases.is_empty(), "{:?}", phases);
+
+    repo.commit_file(
+        "assets/.gitattributes",
+        "*.psd filter=lfs diff=lfs merge=lfs -text\n",
+        "track psd with lfs",
+    );
+    let workspace = WorkspaceManager::create_with_progress(
+        &mut state,
+        "fixture",
+        None,
+        None,
+        false,
+        &[],
+        |name, progress| phases.push((name.to_string(), progress.phase)),
+    )
+    .unwrap();
+    assert_eq!(workspace.status, WorkspaceStatus::Ready);
+    assert!(phases.iter().all(|(name, _)| name == &workspace.name));
+    let phases: Vec<&str> = phases.iter().map(|(_, phase)| phase.as_str()).collect();
+    if lfs::lfs_available(repo.path()) {
+        assert_eq!(phases.first(), Some(&"lfs_pull"));
+        assert_eq!(phases.last(), Some(&"lfs_done"));
+    } else {
+        assert_eq!(phases, ["lfs_unavailable"]);
+    }
+}
+
+#[test]
+fn sparse_workspace_checks_out_selected_directories() {
+    let _home = isolated_tidyflow_home();
+    let repo = FixtureRepo::with_initial_commit();
+    repo.commit_file("services/api/main.rs", "fn main() {}\n", "api");
+    repo.commit_file("services/web/index.ts", "export {};\n", "web");
+    repo.commit_file("libs/shared/lib.rs", "pub fn f() {}\n", "shared");
+    repo.with_origin();
+    let mut state = AppState::default();
+    ProjectManager::import_local(&mut state, "fixture", repo.path()).unwrap();
+
+    let paths = vec!["services/api/".to_string()];
+    let workspace = WorkspaceManager::create_with_progress(
+        &mut state,
+        "fixture",
+        None,
+        None,
+        false,
+        &paths,
+        |_, _| {},
+    )
+    .unwrap();
+    let root = &workspace.worktree_path;
+    assert_eq!(workspace.sparse_paths, ["services/api"]);
+    // cone 模式始终保留根目录下的文件
+    assert!(root.join("README.md").exists());
+    assert!(root.join("services/api/main.rs").exists());
+    assert!(!root.join("services/web").exists());
+    assert!(!root.join("libs").exists());
+    // 稀疏配置只作用于该 worktree
+    assert!(repo.path().join("services/web/index.ts").exists());
+
+    let updated = WorkspaceManager::set_sparse_paths(
+        &mut state,
+        "fixture",
+        &workspace.name,
+        &["libs/shared".to_string()],
+    )
+    .unwrap();
+    assert_eq!(updated.sparse_paths, ["libs/shared"]);
+    assert!(root.join("libs/shared/lib.rs").exists());
+    assert!(!root.join("services/api").exists());
+
+    let full =
+        WorkspaceManager::set_sparse_paths(&mut state, "fixture", &workspace.name, &[]).unwrap();
+    assert!(full.sparse_paths.is_empty());
+    assert!(root.join("services/web/index.ts").exists());
+    let status = git::git_status(root, "main").unwrap();
+    assert!(status.items.is_empty(), "{:?}", status.items);
+
+    for bad in [vec!["../outside".to_string()], vec!["src/*".to_string()]] {
+        assert!(matches!(
+            WorkspaceManager::create_with_progress(
+                &mut state,
+                "fixture",
+                None,
+                None,
+                false,
+                &bad,
+                |_, _| {},
+            ),
+            Err(WorkspaceError::InvalidSparsePath(_))
+        ));
+    }
+    assert!(WorkspaceManager::set_sparse_paths(&mut state, "fixture", "default", &paths).is_err());
+}
+
+#[test]
+fn named_workspace_uses_declared_or_rendered_branch() {
+    let _home = isolated_tidyflow_home();
+    let repo = FixtureRepo::with_initial_commit();
+    repo.with_origin();
+    git_in(repo.path(), &["branch", "feat/shared"]);
+
+    let mut state = AppState::default();
+    ProjectManager::import_local(&mut state, "fixture", repo.path()).unwrap();
+
+    // 未声明分支：按默认模板以工作区名渲染
+    let api =
+        WorkspaceManager::create_named(&mut state, "fixture", "api", None, None, false).unwrap();
+    assert_eq!(api.name, "api");
+    assert_eq!(api.branch, "tidy/api");
+    assert!(api.worktree_path.ends_with("api"));
+
+    // 声明的分支已存在时直接检出
+    let web = WorkspaceManager::create_named(
+        &mut state,
+        "fixture",
+        "web",
+        Some("feat/shared"),
+        Some("main"),
+        false,
+    )
+    .unwrap();
+    let status = git::git_status(&web.worktree_path, "main").unwrap();
+    assert_eq!(status.current_branch.as_deref(), Some("feat/shared"));
+
+    for bad in ["default", "a/b", "bad name", "api"] {
+        assert!(
+            WorkspaceManager::create_named(&mut state, "fixture", bad, None, None, false).is_err(),
+            "{:?} should be rejected",
+            bad
+        );
+    }
+}
+
+#[test]
+fn workspace_rename_moves_branch_and_worktree() {
+    let _home = isolated_tidyflow_home();
+    let repo = FixtureRepo::with_initial_commit();
+    repo.with_origin();
+
+    let mut state = AppState::default();
+    ProjectManager::import_local(&mut state, "fixture", repo.path()).unwrap();
+    let workspace = WorkspaceManager::create(&mut state, "fixture", None, None, false).unwrap();
+    let other = WorkspaceManager::create(&mut state, "fixture", None, None, false).unwrap();
+    state
+        .client_settings
+        .workspace_shortcuts
+        .insert("1".to_string(), format!("fixture/{}", workspace.name));
+
+    // 非法名称与已占用名称均被拒绝
+    for bad in ["default", "a/b", "bad name", ""] {
+        assert!(
+            WorkspaceManager::rename(&mut state, "fixture", &workspace.name, bad, true, true)
+                .is_err(),
+            "{:?} should be rejected",
+            bad
+        );
+    }
+    assert!(WorkspaceManager::rename(
+        &mut state,
+        "fixture",
+        &workspace.name,
+        &other.name,
+        false,
+        false
+    )
+    .is_err());
+
+    let renamed = WorkspaceManager::rename(
+        &mut state,
+        "fixture",
+        &workspace.name,
+        "login-page",
+        true,
+        true,
+    )
+    .unwrap();
+    assert_eq!(renamed.name, "login-page");
+    assert_eq!(renamed.branch, "tidy/login-page");
+    assert_eq!(
+        renamed.worktree_path,
+        workspace.worktree_path.with_file_name("login-page")
+    );
+    assert!(!workspace.worktree_path.exists());
+    assert!(renamed.worktree_path.join("README.md").exists());
+
+    let status = git::git_status(&renamed.worktree_path, "main").unwrap();
+    assert_eq!(status.current_branch.as_deref(), Some("tidy/login-page"));
+
+    let project = state.get_project("fixture").unwrap();
+    assert!(project.get_workspace(&workspace.name).is_none());
+    assert_eq!(
+        project.get_workspace("login-page").unwrap().branch,
+        "tidy/login-page"
+    );
+    assert_eq!(
+        state
+            .client_settings
+            .workspace_shortcuts
+            .get("1")
+            .map(String::as_str),
+        Some("fixture/login-page")
+    );
+
+    // 仅改名：分支与目录保持不变
+    let display_only =
+        WorkspaceManager::rename(&mut state, "fixture", "login-page", "login", false, false)
+            .unwrap();
+    assert_eq!(display_only.branch, "tidy/login-page");
+    assert_eq!(display_only.worktree_path, renamed.worktree_path);
+}
+
+#[test]
+fn workspace_repair_recreates_deleted_worktree() {
+    let _home = isolated_tidyflow_home();
+    let repo = FixtureRepo::with_initial_commit();
+    repo.with_origin();
+
+    let mut state = AppState::default();
+    ProjectManager::import_local(&mut state, "fixture", repo.path()).unwrap();
+    let workspace = WorkspaceManager::create(&mut state, "fixture", None, None, false).unwrap();
+    std::fs::write(workspace.worktree_path.join("ws.txt"), "ws\n").unwrap();
+    git_in(&workspace.worktree_path, &["add", "-A"]);
+    git_in(
+        &workspace.worktree_path,
+        &["commit", "-q", "-m", "workspace commit"],
+    );
+
+    // 健康的工作空间无需修复
+    let healthy = WorkspaceManager::repair(&mut state, "fixture", &workspace.name).unwrap();
+    assert!(healthy.actions.is_empty());
+
+    std::fs::remove_dir_all(&workspace.worktree_path).unwrap();
+    state
+        .get_project_mut("fixture")
+        .unwrap()
+        .get_workspace_mut(&workspace.name)
+        .unwrap()
+        .status = WorkspaceStatus::SetupFailed;
+
+    let repaired = WorkspaceManager::repair(&mut state, "fixture", &workspace.name).unwrap();
+    assert_eq!(repaired.actions.len(), 3, "{:?}", repaired.actions);
+    assert_eq!(repaired.workspace.status, WorkspaceStatus::Ready);
+    // 分支上的提交随重建的目录一并恢复
+    assert!(workspace.worktree_path.join("ws.txt").exists());
+    let status = git::git_status(&workspace.worktree_path, "main").unwrap();
+    assert_eq!(
+        status.current_branch.as_deref(),
+        Some(workspace.branch.as_str())
+    );
+
+    // 分支被删除后无法重建
+    std::fs::remove_dir_all(&workspace.worktree_path).unwrap();
+    git_in(repo.path(), &["worktree", "prune"]);
+    git_in(repo.path(), &["branch", "-D", &workspace.branch]);
+    assert!(WorkspaceManager::repair(&mut state, "fixture", &workspace.name).is_err());
+}
+
+#[test]
+fn project_default_branch_detected_from_origin_and_settable() {
+    let _home = isolated_tidyflow_home();
+    let repo = FixtureRepo::with_initial_commit();
+    repo.git(&["branch", "-m", "trunk"]);
+    repo.with_origin();
+    repo.git(&["remote", "set-head", "origin", "trunk"]);
+    repo.git(&["push", "-q", "origin", "trunk:release"]);
+
+    let mut state = AppState::default();
+    let project = ProjectManager::import_local(&mut state, "fixture", repo.path()).unwrap();
+    assert_eq!(project.default_branch, "trunk");
+
+    // 仅存在于 origin 的分支也可作为默认分支
+    let branch = ProjectManager::set_default_branch(&mut state, "fixture", " release ").unwrap();
+    assert_eq!(branch, "release");
+    assert_eq!(
+        state.get_project("fixture").unwrap().default_branch,
+        "release"
+    );
+
+    assert!(matches!(
+        ProjectManager::set_default_branch(&mut state, "fixture", "missing"),
+        Err(ProjectError::BranchNotFound(_))
+    ));
+    assert!(matches!(
+        ProjectManager::set_default_branch(&mut state, "fixture", "bad..name"),
+        Err(ProjectError::InvalidBranch(_))
+    ));
+    assert!(ProjectManager::set_default_branch(&mut state, "nope", "trunk").is_err());
+    assert_eq!(
+        state.get_project("fixture").unwrap().default_branch,
+        "release"
+    );
+}
+
+#[test]
+fn integration_worktree_is_separate_per_target_branch() {
+    let _home = isolated_tidyflow_home();
+    let repo = FixtureRepo::with_initial_commit();
+    let default_branch = repo.current_branch();
+    repo.branch("release/1.x");
+    repo.checkout("release/1.x");
+    repo.diverge_file("notes.txt", "feature", "release fix\n", "feature change\n");
+    repo.checkout("feature");
+
+    let default_target = git::IntegrationTarget::resolve("fixture", &default_branch, None);
+    let release_target =
+        git::IntegrationTarget::resolve("fixture", &default_branch, Some("release/1.x"));
+    assert_eq!(
+        git::IntegrationTarget::resolve("fixture", &default_branch, Some(&default_branch)),
+        default_target
+    );
+    assert!(default_target.worktree_path().ends_with("__integration"));
+    assert!(release_target
+        .worktree_path()
+        .ends_with("__integration-release-1.x"));
+
+    let default_path = git::ensure_integration_worktree(repo.path(), &default_target).unwrap();
+    let release_path = git::ensure_integration_worktree(repo.path(), &release_target).unwrap();
+    assert_ne!(default_path, release_path);
+
+    // 合并到 release 分支产生冲突，默认分支的集成 worktree 不受影响
+    let merged = git::merge_to_default(repo.path(), &release_target, "feature").unwrap();
+    assert!(!merged.ok);
+    assert_eq!(merged.conflicts, vec!["notes.txt".to_string()]);
+    let release_status = git::integration_status(&release_target).unwrap();
+    assert_eq!(release_status.state.as_str(), "conflict");
+    assert_eq!(release_status.default_branch, "release/1.x");
+    let default_status = git::integration_status(&default_target).unwrap();
+    assert_eq!(default_status.state.as_str(), "idle");
+
+    let merged = git::merge_to_default(repo.path(), &default_target, "feature").unwrap();
+    assert!(merged.ok, "{:?}", merged.message);
+
+    let aborted = git::merge_abort(&release_target).unwrap();
+    assert!(aborted.ok, "{:?}", aborted.message);
+    assert_eq!(
+        git::integration_status(&release_target)
+            .unwrap()
+            .state
+            .as_str(),
+        "idle"
+    );
+
+    assert_eq!(git::integration_context_branch("integration"), Some(None));
+    assert_eq!(
+        git::integration_context_branch("integration:release/1.x"),
+        Some(Some("release/1.x"))
+    );
+    assert_eq!(git::integration_context_branch("workspace"), None);
+}
+
+#[test]
+fn fetch_coordination_key_is_shared_across_worktrees() {
+    let _home = isolated_tidyflow_home();
+    let repo = FixtureRepo::with_initial_commit();
+    repo.with_origin();
+
+    let mut state = AppState::default();
+    ProjectManager::import_local(&mut state, "fixture", repo.path()).unwrap();
+    let workspace = WorkspaceManager::create(&mut state, "fixture", None, None, false).unwrap();
+
+    assert_eq!(
+        git::fetch_coordination_key(repo.path()),
+        git::fetch_coordination_key(&workspace.worktree_path)
+    );
+    let other = FixtureRepo::with_initial_commit();
+    assert_ne!(
+        git::fetch_coordination_key(repo.path()),
+        git::fetch_coordination_key(other.path())
+    );
+
+    let fetched = git::git_fetch(&workspace.worktree_path).unwrap();
+    assert!(fetched.ok, "{:?}", fetched.message);
+}
+
+#[test]
+fn clone_import_reports_progress_and_honours_depth() {
+    let home = isolated_tidyflow_home();
+    let upstream = FixtureRepo::with_initial_commit();
+    upstream.commit_file("a.txt", "a\n", "second");
+    upstream.commit_file("b.txt", "b\n", "third");
+    // 本地路径克隆会忽略 --depth，需走 file:// 传输
+    let url = format!("file://{}", upstream.path().display());
+
+    let target = ProjectManager::managed_clone_dir("Cloned Repo");
+    assert_eq!(target, home.join("repos").join("cloned-repo"));
+
+    let mut progress = Vec::new();
+    ProjectManager::clone_repository(&url, Some("main"), Some(1), &target, |p| progress.push(p))
+        .unwrap();
+    assert!(
+        progress
+            .iter()
+            .any(|p| p.phase == "receiving_objects" && p.percent == Some(100)),
+        "{:?}",
+        progress
+    );
+    assert_eq!(
+        git_in(&target, &["rev-list", "--count", "HEAD"]).trim(),
+        "1"
+    );
+
+    let mut state = AppState::default();
+    let project = ProjectManager::import_local(&mut state, "Cloned Repo", &target).unwrap();
+    assert_eq!(project.default_branch, "main");
+    assert_eq!(project.remote_url.as_deref(), Some(url.as_str()));
+    // 同名目录已被占用时换用后缀
+    assert_eq!(
+        ProjectManager::managed_clone_dir("Cloned Repo"),
+        home.join("repos").join("cloned-repo-2")
+    );
+
+    let missing = home.join("repos").join("missing");
+    let err = ProjectManager::clone_repository(
+        &format!("file://{}", upstream.sibling("does-not-exist").display()),
+        None,
+        None,
+        &missing,
+        |_| {},
+    )
+    .unwrap_err();
+    assert!(!err.to_string().contains('%'), "{}", err);
+    assert!(!missing.exists());
+    assert!(ProjectManager::clone_repository(
+        "--upload-pack=touch /tmp/x",
+        None,
+        None,
+        &missing,
+        |_| {}
+    )
+    .is_err());
+}
+
+#[test]
+fn repo_stats_report_history_objects_and_gc() {
+    let repo = FixtureRepo::with_initial_commit();
+    repo.commit_file("assets/big.bin", &"x".repeat(64 * 1024), "add big asset");
+    repo.git(&["rm", "-q", "assets/big.bin"]);
+    repo.git(&["commit", "-q", "-m", "drop big asset"]);
+
+    let before = git::git_repo_stats(repo.path()).unwrap();
+    assert_eq!(before.commit_count, 3);
+    assert!(before.loose_objects > 0);
+    assert_eq!(before.pack_count, 0);
+    assert_eq!(before.last_gc_at, None);
+    // 已删除的文件仍按历史 blob 统计
+    let biggest = &before.largest_files[0];
+    assert_eq!(biggest.path, "assets/big.bin");
+    assert_eq!(biggest.size, 64 * 1024);
+
+    repo.git(&["config", "gc.auto", "1"]);
+    let crowded = git::git_repo_stats(repo.path()).unwrap();
+    assert!(crowded.suggestions.contains(&"gc".to_string()));
+
+    repo.git(&["gc", "-q"]);
+    let after = git::git_repo_stats(repo.path()).unwrap();
+    assert_eq!(after.loose_objects, 0);
+    assert_eq!(after.pack_count, 1);
+    assert_eq!(after.packed_objects, before.loose_objects);
+    assert!(after.last_gc_at.is_some());
+    assert!(after.suggestions.is_empty(), "{:?}", after.suggestions);
+    assert_eq!(after.largest_files[0].path, "assets/big.bin");
+}
+
+#[test]
+fn detect_large_blobs_lists_paths_commits_and_filter_repo_plan() {
+    let repo = FixtureRepo::with_initial_commit();
+    let big = "y".repeat(8 * 1024);
+    repo.commit_file("media/clip one.mov", &big, "add clip");
+    repo.git(&["mv", "media/clip one.mov", "media/clip.mov"]);
+    repo.git(&["commit", "-q", "-m", "rename clip"]);
+    repo.commit_file("data.csv", &"z".repeat(4 * 1024), "add data");
+
+    let report = git::git_detect_large_blobs(repo.path(), Some(6 * 1024), true).unwrap();
+    assert_eq!(report.threshold, 6 * 1024);
+    assert_eq!(report.total_count, 1);
+    assert!(!report.truncated);
+    let blob = &report.blobs[0];
+    assert_eq!(blob.size, 8 * 1024);
+    assert_eq!(blob.paths, vec!["media/clip one.mov", "media/clip.mov"]);
+    // --no-renames 下重命名视为新增，两次写入都算
+    let subjects: Vec<&str> = blob.commits.iter().map(|c| c.subject.as_str()).collect();
+    assert_eq!(subjects, vec!["rename clip", "add clip"]);
+
+    let plan = report.filter_repo.expect("plan requested");
+    assert_eq!(
+        plan.strip_by_size_command,
+        "git filter-repo --strip-blobs-bigger-than 6144"
+    );
+    assert_eq!(
+        plan.remove_paths_command,
+        "git filter-repo --invert-paths --path 'media/clip one.mov' --path media/clip.mov"
+    );
+    assert_eq!(plan.estimated_savings_bytes, blob.disk_size);
+
+    let lower = git::git_detect_large_blobs(repo.path(), Some(1024), false).unwrap();
+    assert_eq!(lower.total_count, 2);
+    assert_eq!(lower.blobs[1].paths, vec!["data.csv"]);
+    assert!(lower.filter_repo.is_none());
+}
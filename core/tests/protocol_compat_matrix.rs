@@ -17,6 +17,9 @@ use tokio::sync::{Mutex, OwnedMutexGuard};
 use tokio::time::timeout;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
+/// 测试服务器的启动 token（经 `TIDYFLOW_WS_TOKEN` 注入）
+const TEST_WS_TOKEN: &str = "compat-matrix-token";
+
 /// 动态端口分配起始端口
 static NEXT_PORT: AtomicU16 = AtomicU16::new(49100);
 
@@ -65,7 +68,7 @@ impl ServerGuard {
         let mut child = Command::new(&bin_path)
             .args(["serve", "--port", &port.to_string()])
             .env("TIDYFLOW_DEV", "1")
-            .env_remove("TIDYFLOW_WS_TOKEN")
+            .env("TIDYFLOW_WS_TOKEN", TEST_WS_TOKEN)
             .stdout(Stdio::piped())
             .stderr(Stdio::null()) // stderr 不消费会导致管道缓冲区满，阻塞服务器进程
             .spawn()
@@ -186,7 +189,7 @@ async fn connect(
     ),
     String,
 > {
-    let url = format!("ws://127.0.0.1:{}/ws", port);
+    let url = format!("ws://127.0.0.1:{}/ws?token={}", port, TEST_WS_TOKEN);
     let (ws, _) = connect_async(&url).await.map_err(|e| e.to_string())?;
     Ok(ws.split())
 }
@@ -287,6 +290,45 @@ async fn test_system_domain_matrix() {
     assert_eq!(pong.kind, "result");
 }
 
+/// 未携带或携带错误 token 的连接应在升级后以 4401 关闭；Bearer 头与查询参数等效
+#[tokio::test]
+async fn test_ws_auth_handshake() {
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+    let _lock = acquire_test_lock().await;
+    let server = ServerGuard::start().expect("启动服务器失败");
+    let port = server.port();
+
+    for url in [
+        format!("ws://127.0.0.1:{}/ws", port),
+        format!("ws://127.0.0.1:{}/ws?token=wrong", port),
+    ] {
+        let (mut ws, _) = connect_async(&url).await.expect("升级应成功");
+        let frame = timeout(Duration::from_secs(5), ws.next())
+            .await
+            .expect("等待关闭帧超时");
+        match frame {
+            Some(Ok(Message::Close(Some(close)))) => {
+                assert_eq!(u16::from(close.code), 4401, "{}", url);
+                assert_eq!(close.reason, "unauthorized");
+            }
+            other => panic!("{} 期望 4401 关闭帧，实际: {:?}", url, other),
+        }
+    }
+
+    let mut request = format!("ws://127.0.0.1:{}/ws", port)
+        .into_client_request()
+        .unwrap();
+    request.headers_mut().insert(
+        "Authorization",
+        format!("Bearer {}", TEST_WS_TOKEN).parse().unwrap(),
+    );
+    let (ws, _) = connect_async(request).await.expect("Bearer 头连接失败");
+    let (_write, mut read) = ws.split();
+    let hello = wait_for_action(&mut read, "hello").await.expect("无 hello");
+    assert_eq!(hello.domain, "system");
+}
+
 /// 测试 terminal 域的消息路由
 #[tokio::test]
 async fn test_terminal_domain_matrix() {
@@ -601,6 +643,34 @@ async fn test_v9_ws_query_surface_requires_http() {
             Some("testproject"),
             None,
         ),
+        (
+            "project",
+            "list_workspace_tasks",
+            json!({ "project": "testproject", "workspace": "default" }),
+            Some("testproject"),
+            Some("default"),
+        ),
+        (
+            "project",
+            "list_ports",
+            json!({ "project": "testproject", "workspace": "default" }),
+            Some("testproject"),
+            Some("default"),
+        ),
+        (
+            "project",
+            "proc_list",
+            json!({ "project": "testproject", "workspace": "default" }),
+            Some("testproject"),
+            Some("default"),
+        ),
+        (
+            "project",
+            "proc_logs",
+            json!({ "project": "testproject", "workspace": "default", "name": "dev" }),
+            Some("testproject"),
+            Some("default"),
+        ),
         ("project", "list_tasks", json!({}), None, None),
         ("settings", "get_client_settings", json!({}), None, None),
         ("project", "list_templates", json!({}), None, None),
@@ -633,6 +703,27 @@ async fn test_v9_ws_query_surface_requires_http() {
             Some("testproject"),
             Some("default"),
         ),
+        (
+            "file",
+            "file_read_at_revision",
+            json!({ "project": "testproject", "workspace": "default", "path": "README.md", "rev": "HEAD" }),
+            Some("testproject"),
+            Some("default"),
+        ),
+        (
+            "file",
+            "file_definition_guess",
+            json!({ "project": "testproject", "workspace": "default", "symbol": "main", "path": "src/main.rs" }),
+            Some("testproject"),
+            Some("default"),
+        ),
+        (
+            "file",
+            "file_editorconfig",
+            json!({ "project": "testproject", "workspace": "default", "path": "src/main.rs" }),
+            Some("testproject"),
+            Some("default"),
+        ),
         (
             "git",
             "git_status",
@@ -668,6 +759,48 @@ async fn test_v9_ws_query_surface_requires_http() {
             Some("testproject"),
             Some("default"),
         ),
+        (
+            "git",
+            "git_show_file_diff",
+            json!({ "project": "testproject", "workspace": "default", "sha": "abc123", "path": "README.md" }),
+            Some("testproject"),
+            Some("default"),
+        ),
+        (
+            "git",
+            "git_suggested_commit_message",
+            json!({ "project": "testproject", "workspace": "default", "link_url": "https://github.com/acme/app/issues/42" }),
+            Some("testproject"),
+            Some("default"),
+        ),
+        (
+            "git",
+            "git_blame",
+            json!({ "project": "testproject", "workspace": "default", "path": "README.md" }),
+            Some("testproject"),
+            Some("default"),
+        ),
+        (
+            "git",
+            "git_diff_page",
+            json!({ "project": "testproject", "workspace": "default", "path": "README.md", "offset": 1048576 }),
+            Some("testproject"),
+            Some("default"),
+        ),
+        (
+            "git",
+            "git_diff_range",
+            json!({ "project": "testproject", "workspace": "default", "range": "main...HEAD" }),
+            Some("testproject"),
+            Some("default"),
+        ),
+        (
+            "git",
+            "git_rebase_interactive_plan",
+            json!({ "project": "testproject", "workspace": "default", "base": "main" }),
+            Some("testproject"),
+            Some("default"),
+        ),
         (
             "git",
             "git_op_status",
@@ -682,6 +815,48 @@ async fn test_v9_ws_query_surface_requires_http() {
             Some("testproject"),
             None,
         ),
+        (
+            "git",
+            "git_status_tree",
+            json!({ "project": "testproject", "workspace": "default" }),
+            Some("testproject"),
+            Some("default"),
+        ),
+        (
+            "git",
+            "git_status_all",
+            json!({ "project": "testproject" }),
+            Some("testproject"),
+            None,
+        ),
+        (
+            "git",
+            "git_repo_stats",
+            json!({ "project": "testproject" }),
+            Some("testproject"),
+            None,
+        ),
+        (
+            "git",
+            "git_detect_large_blobs",
+            json!({ "project": "testproject", "threshold": 1048576, "filter_repo_plan": true }),
+            Some("testproject"),
+            None,
+        ),
+        (
+            "git",
+            "git_list_pull_requests",
+            json!({ "project": "testproject" }),
+            Some("testproject"),
+            None,
+        ),
+        (
+            "git",
+            "git_pull_request_status",
+            json!({ "project": "testproject", "number": 1 }),
+            Some("testproject"),
+            None,
+        ),
         (
             "git",
             "git_check_branch_up_to_date",
@@ -19,6 +19,9 @@ use tempfile::TempDir;
 use tokio::time::timeout;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
+/// 测试服务器的启动 token（经 `TIDYFLOW_WS_TOKEN` 注入）
+const TEST_WS_TOKEN: &str = "protocol-v1-token";
+
 /// 动态端口分配起始端口
 static NEXT_PORT: AtomicU16 = AtomicU16::new(49000);
 
@@ -63,7 +66,7 @@ impl ServerGuard {
         let mut child = Command::new(&bin_path)
             .args(["serve", "--port", &port.to_string()])
             .env("TIDYFLOW_DEV", "1")
-            .env_remove("TIDYFLOW_WS_TOKEN") // 禁用 token 认证以便测试
+            .env("TIDYFLOW_WS_TOKEN", TEST_WS_TOKEN)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
@@ -266,7 +269,7 @@ async fn connect_to_server(
     ),
     String,
 > {
-    let url = format!("ws://127.0.0.1:{}/ws", port);
+    let url = format!("ws://127.0.0.1:{}/ws?token={}", port, TEST_WS_TOKEN);
     let (ws_stream, _) = connect_async(&url).await.map_err(|e| {
         format!(
             "Connection failed (is server running on port {}?): {}",
@@ -408,6 +411,27 @@ async fn test_ping_pong() {
     println!("  ✓ Pong received");
 }
 
+/// Test 2b: 取消未知请求返回 cancelled = false
+#[tokio::test]
+async fn test_cancel_unknown_request() {
+    let server = ServerGuard::start().expect("启动服务器失败");
+    let port = server.port();
+
+    let (mut write, mut read) = connect_to_server(port).await.expect("Failed to connect");
+    let _ = wait_for_action(&mut read, "hello").await;
+
+    let msg = encode_client_message("system", "cancel", json!({"request_id": "no-such-request"}));
+    write.send(Message::Binary(msg)).await.unwrap();
+
+    let env = wait_for_action(&mut read, "cancel_result")
+        .await
+        .expect("No cancel_result");
+    assert_eq!(env.domain, "system");
+    assert_eq!(env.payload["request_id"], "no-such-request");
+    assert_eq!(env.payload["cancelled"], false);
+    println!("  ✓ Cancel result received");
+}
+
 /// Test 3: List Projects（v9 起要求经 HTTP 读取）
 #[tokio::test]
 async fn test_list_projects() {
@@ -696,3 +720,45 @@ async fn test_watch_subscribe_unsubscribe() {
     assert_eq!(env.domain, "file");
     println!("  ✓ Watch unsubscribed");
 }
+
+/// Test: 探针端点与 SIGTERM 优雅关闭
+#[cfg(unix)]
+#[tokio::test]
+async fn test_probes_and_sigterm_graceful_shutdown() {
+    let mut server = ServerGuard::start().expect("启动服务器失败");
+    let port = server.port();
+
+    for path in ["healthz", "readyz"] {
+        let resp = reqwest::get(format!("http://127.0.0.1:{}/{}", port, path))
+            .await
+            .expect("探针请求失败");
+        assert_eq!(resp.status(), 200, "/{} 应返回 200", path);
+    }
+
+    let (_, mut read) = connect_to_server(port).await.expect("Failed to connect");
+    let _ = wait_for_action(&mut read, "hello").await;
+
+    let mut child = server.child.take().expect("服务器进程");
+    unsafe {
+        libc::kill(child.id() as i32, libc::SIGTERM);
+    }
+
+    let env = wait_for_action(&mut read, "server_shutdown")
+        .await
+        .expect("未收到 server_shutdown");
+    assert_eq!(env.payload["reason"], "sigterm");
+
+    let start = std::time::Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().expect("等待进程退出失败") {
+            break status;
+        }
+        if start.elapsed() > Duration::from_secs(15) {
+            let _ = child.kill();
+            panic!("SIGTERM 后服务器未在 15 秒内退出");
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    };
+    assert!(status.success(), "服务器应正常退出: {}", status);
+    println!("  ✓ Server shut down gracefully");
+}
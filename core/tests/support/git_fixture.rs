@@ -0,0 +1,223 @@
+//! 临时 Git 夹具仓库
+//!
+//! 以代码方式构造测试用仓库（提交、分支、冲突、重命名、子模块、远程），
+//! 让 git / workspace 相关功能可以用行为测试覆盖，而不是依赖手工验证。
+//!
+//! 约定：
+//! - 仓库位于 `TempDir/repo`，同一 TempDir 下还可放置 `origin.git` 等辅助仓库，随夹具一起清理
+//! - 默认分支为 `main`，关闭提交签名，提交时间由内部时钟递增，保证 log 顺序稳定
+//! - 所有 git 调用失败即 panic，并带上 stderr，方便定位夹具本身的问题
+
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::OnceLock;
+
+use tempfile::TempDir;
+
+/// 夹具提交时间起点（2024-01-01T00:00:00Z）
+const FIXTURE_EPOCH: i64 = 1_704_067_200;
+
+/// 将 `TIDYFLOW_HOME` 指向进程级临时目录，避免 workspace 流程写入真实的 `~/.tidyflow`。
+///
+/// 需在调用任何读取全局数据目录的 API 之前调用；同一测试进程内只初始化一次。
+pub fn isolated_tidyflow_home() -> &'static Path {
+    static HOME: OnceLock<TempDir> = OnceLock::new();
+    HOME.get_or_init(|| {
+        let dir = TempDir::new().expect("create tidyflow home");
+        std::env::set_var("TIDYFLOW_HOME", dir.path());
+        dir
+    })
+    .path()
+}
+
+/// 在 `dir` 中运行 git，失败时 panic
+pub fn git_in(dir: &Path, args: &[&str]) -> String {
+    let output = try_git_in(dir, args);
+    assert!(
+        output.status.success(),
+        "git {:?} failed in {}: {}",
+        args,
+        dir.display(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string()
+}
+
+/// 在 `dir` 中运行 git，返回原始输出（用于断言预期的失败）
+pub fn try_git_in(dir: &Path, args: &[&str]) -> Output {
+    Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_EDITOR", "true")
+        .output()
+        .expect("run git")
+}
+
+/// 临时夹具仓库
+pub struct FixtureRepo {
+    _dir: TempDir,
+    root: PathBuf,
+    clock: Cell<i64>,
+}
+
+impl FixtureRepo {
+    /// 初始化空仓库（尚无提交，HEAD 指向 `main`）
+    pub fn new() -> Self {
+        let dir = TempDir::new().expect("create fixture dir");
+        // macOS 的临时目录是符号链接，规范化后与被测代码 canonicalize 的结果一致
+        let base = dir.path().canonicalize().expect("canonicalize fixture dir");
+        let root = base.join("repo");
+        std::fs::create_dir_all(&root).expect("create fixture repo dir");
+
+        let repo = Self {
+            _dir: dir,
+            root,
+            clock: Cell::new(FIXTURE_EPOCH),
+        };
+        repo.git(&["init", "-q", "-b", "main"]);
+        repo.git(&["config", "user.name", "Fixture"]);
+        repo.git(&["config", "user.email", "fixture@example.com"]);
+        repo.git(&["config", "commit.gpgsign", "false"]);
+        repo.git(&["config", "core.autocrlf", "false"]);
+        repo
+    }
+
+    /// 初始化仓库并在 `main` 上创建一个含 README 的初始提交
+    pub fn with_initial_commit() -> Self {
+        let repo = Self::new();
+        repo.commit_file("README.md", "# fixture\n", "initial commit");
+        repo
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    /// 夹具 TempDir 下的兄弟路径（如 `origin.git`），随夹具一起清理
+    pub fn sibling(&self, name: &str) -> PathBuf {
+        self.root.parent().expect("fixture parent").join(name)
+    }
+
+    pub fn git(&self, args: &[&str]) -> String {
+        git_in(&self.root, args)
+    }
+
+    pub fn try_git(&self, args: &[&str]) -> Output {
+        try_git_in(&self.root, args)
+    }
+
+    /// 写入工作区文件（自动创建父目录），不暂存
+    pub fn write(&self, rel: &str, content: &str) {
+        let path = self.root.join(rel);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("create parent dir");
+        }
+        std::fs::write(path, content).expect("write fixture file");
+    }
+
+    pub fn read(&self, rel: &str) -> String {
+        std::fs::read_to_string(self.root.join(rel)).expect("read fixture file")
+    }
+
+    /// 暂存全部变更并提交，返回完整 SHA
+    pub fn commit(&self, message: &str) -> String {
+        self.git(&["add", "-A"]);
+        self.commit_staged(message)
+    }
+
+    /// 只提交已暂存内容，返回完整 SHA
+    pub fn commit_staged(&self, message: &str) -> String {
+        let at = self.clock.get() + 60;
+        self.clock.set(at);
+        let date = format!("@{} +0000", at);
+        let output = Command::new("git")
+            .args(["commit", "-q", "--allow-empty", "-m", message])
+            .current_dir(&self.root)
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .output()
+            .expect("run git commit");
+        assert!(
+            output.status.success(),
+            "git commit failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        self.head()
+    }
+
+    /// 写入单个文件并提交
+    pub fn commit_file(&self, rel: &str, content: &str, message: &str) -> String {
+        self.write(rel, content);
+        self.commit(message)
+    }
+
+    /// `git mv` 后提交
+    pub fn rename(&self, from: &str, to: &str, message: &str) -> String {
+        if let Some(parent) = self.root.join(to).parent() {
+            std::fs::create_dir_all(parent).expect("create rename target dir");
+        }
+        self.git(&["mv", from, to]);
+        self.commit_staged(message)
+    }
+
+    pub fn head(&self) -> String {
+        self.git(&["rev-parse", "HEAD"])
+    }
+
+    pub fn current_branch(&self) -> String {
+        self.git(&["rev-parse", "--abbrev-ref", "HEAD"])
+    }
+
+    /// 在当前 HEAD 创建分支（不切换）
+    pub fn branch(&self, name: &str) {
+        self.git(&["branch", name]);
+    }
+
+    pub fn checkout(&self, name: &str) {
+        self.git(&["checkout", "-q", name]);
+    }
+
+    /// 构造同一文件的分叉修改：`branch` 上把 `rel` 改为 `theirs`，
+    /// 当前分支上改为 `ours`。之后 merge / rebase 会在 `rel` 上产生内容冲突。
+    pub fn diverge_file(&self, rel: &str, branch: &str, ours: &str, theirs: &str) {
+        let current = self.current_branch();
+        self.git(&["checkout", "-q", "-b", branch]);
+        self.commit_file(rel, theirs, &format!("{}: change {}", branch, rel));
+        self.checkout(&current);
+        self.commit_file(rel, ours, &format!("{}: change {}", current, rel));
+    }
+
+    /// 在夹具目录下创建 bare 远程 `origin.git` 并推送当前分支（设置 upstream）
+    pub fn with_origin(&self) -> PathBuf {
+        let origin = self.sibling("origin.git");
+        git_in(
+            self.root.parent().expect("fixture parent"),
+            &["init", "-q", "--bare", "-b", "main", "origin.git"],
+        );
+        let url = origin.to_string_lossy().to_string();
+        self.git(&["remote", "add", "origin", &url]);
+        let branch = self.current_branch();
+        self.git(&["push", "-q", "-u", "origin", &branch]);
+        origin
+    }
+
+    /// 将另一个夹具仓库作为子模块挂载到 `rel` 并提交
+    pub fn add_submodule(&self, rel: &str, sub: &FixtureRepo) -> String {
+        let url = sub.path().to_string_lossy().to_string();
+        // 新版 git 默认禁止 file 协议的子模块
+        self.git(&[
+            "-c",
+            "protocol.file.allow=always",
+            "submodule",
+            "add",
+            "-q",
+            &url,
+            rel,
+        ]);
+        self.commit_staged(&format!("add submodule {}", rel))
+    }
+}
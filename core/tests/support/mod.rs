@@ -0,0 +1,7 @@
+//! 集成测试共享支持代码
+//!
+//! 各测试二进制通过 `mod support;` 引入；并非每个测试都用到全部辅助函数。
+
+#![allow(dead_code)]
+
+pub mod git_fixture;